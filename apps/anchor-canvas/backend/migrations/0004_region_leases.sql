@@ -0,0 +1,33 @@
+-- Region leases (governance mode)
+-- A region claim (State sub-kind) grants its creator exclusive drawing
+-- rights on a rectangle for a number of blocks. Pixels painted by others
+-- inside an active lease are kept in the history but marked invalid.
+
+CREATE TABLE IF NOT EXISTS canvas_leases (
+    id SERIAL PRIMARY KEY,
+    txid BYTEA NOT NULL,
+    vout INTEGER NOT NULL DEFAULT 0,
+    owner_address TEXT,
+    x INTEGER NOT NULL CHECK (x >= 0 AND x < 4580),
+    y INTEGER NOT NULL CHECK (y >= 0 AND y < 4580),
+    width INTEGER NOT NULL CHECK (width > 0),
+    height INTEGER NOT NULL CHECK (height > 0),
+    -- Lease covers blocks start_height <= h < end_height
+    start_height INTEGER NOT NULL,
+    end_height INTEGER NOT NULL,
+    fee_sats BIGINT,
+    valid BOOLEAN NOT NULL DEFAULT TRUE,
+    invalid_reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(txid, vout)
+);
+
+CREATE INDEX IF NOT EXISTS idx_canvas_leases_active ON canvas_leases(end_height, start_height) WHERE valid;
+CREATE INDEX IF NOT EXISTS idx_canvas_leases_owner ON canvas_leases(owner_address);
+
+-- Pixels rejected by a lease stay in the history, with the lease that rejected them
+ALTER TABLE pixel_history
+ADD COLUMN IF NOT EXISTS valid BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE pixel_history
+ADD COLUMN IF NOT EXISTS lease_id INTEGER REFERENCES canvas_leases(id);
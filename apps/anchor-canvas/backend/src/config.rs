@@ -26,6 +26,28 @@ pub struct Config {
     pub port: u16,
     /// Indexer poll interval in seconds
     pub poll_interval_secs: u64,
+    /// Region lease rules
+    pub governance: GovernanceConfig,
+}
+
+/// Region lease rules (governance mode)
+#[derive(Debug, Clone)]
+pub struct GovernanceConfig {
+    /// Whether region claims are indexed and enforced
+    pub enabled: bool,
+    /// Longest lease, in blocks
+    pub max_blocks: u32,
+    /// Largest leased region, in pixels
+    pub max_area: u64,
+    /// Minimum claim fee, in millisatoshis per pixel per block (0 = free)
+    pub price_msats: u64,
+}
+
+impl GovernanceConfig {
+    /// Fee a claim must pay, in satoshis
+    pub fn price_sats(&self, area: u64, blocks: u32) -> u64 {
+        (area * blocks as u64 * self.price_msats).div_ceil(1000)
+    }
 }
 
 impl Config {
@@ -51,6 +73,24 @@ impl Config {
                 .ok()
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(5),
+            governance: GovernanceConfig {
+                enabled: env::var("CANVAS_GOVERNANCE")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap_or(false),
+                max_blocks: env::var("CANVAS_LEASE_MAX_BLOCKS")
+                    .ok()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(1008),
+                max_area: env::var("CANVAS_LEASE_MAX_AREA")
+                    .ok()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(65536),
+                price_msats: env::var("CANVAS_LEASE_PRICE_MSATS")
+                    .ok()
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(10),
+            },
         }
     }
 }
@@ -0,0 +1,118 @@
+//! Region lease database operations
+
+#![allow(clippy::type_complexity)]
+
+use anyhow::Result;
+
+use crate::models::Lease;
+
+use super::Database;
+
+const LEASE_COLUMNS: &str = "id, txid, vout, owner_address, x, y, width, height, start_height, \
+     end_height, fee_sats, valid, invalid_reason, created_at";
+
+type LeaseRow = (
+    i32,
+    Vec<u8>,
+    i32,
+    Option<String>,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    Option<i64>,
+    bool,
+    Option<String>,
+    chrono::DateTime<chrono::Utc>,
+);
+
+fn lease_from_row(r: LeaseRow) -> Lease {
+    Lease {
+        id: r.0,
+        txid: hex::encode(&r.1),
+        vout: r.2,
+        owner_address: r.3,
+        x: r.4,
+        y: r.5,
+        width: r.6,
+        height: r.7,
+        start_height: r.8,
+        end_height: r.9,
+        fee_sats: r.10,
+        valid: r.11,
+        invalid_reason: r.12,
+        created_at: r.13,
+    }
+}
+
+impl Database {
+    /// Record a region claim, valid or not, and return its lease id
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_lease(
+        &self,
+        txid: &[u8],
+        vout: i32,
+        owner_address: Option<&str>,
+        region: (i32, i32, i32, i32),
+        start_height: i32,
+        end_height: i32,
+        fee_sats: Option<i64>,
+        invalid_reason: Option<&str>,
+    ) -> Result<i32> {
+        let (x, y, width, height) = region;
+        let row: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO canvas_leases
+                (txid, vout, owner_address, x, y, width, height, start_height, end_height, fee_sats, valid, invalid_reason)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (txid, vout) DO UPDATE SET
+                valid = EXCLUDED.valid,
+                invalid_reason = EXCLUDED.invalid_reason
+            RETURNING id
+            "#,
+        )
+        .bind(txid)
+        .bind(vout)
+        .bind(owner_address)
+        .bind(x)
+        .bind(y)
+        .bind(width)
+        .bind(height)
+        .bind(start_height)
+        .bind(end_height)
+        .bind(fee_sats)
+        .bind(invalid_reason.is_none())
+        .bind(invalid_reason)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.0)
+    }
+
+    /// Valid leases in force at a block height
+    pub async fn get_active_leases(&self, height: i32) -> Result<Vec<Lease>> {
+        let rows: Vec<LeaseRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM canvas_leases \
+             WHERE valid AND start_height <= $1 AND end_height > $1 \
+             ORDER BY start_height, id",
+            LEASE_COLUMNS
+        ))
+        .bind(height)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(lease_from_row).collect())
+    }
+
+    /// Leases, valid or rejected, granted by a claim transaction
+    pub async fn get_leases_by_txid(&self, txid: &[u8]) -> Result<Vec<Lease>> {
+        let rows: Vec<LeaseRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM canvas_leases WHERE txid = $1 ORDER BY vout",
+            LEASE_COLUMNS
+        ))
+        .bind(txid)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(lease_from_row).collect())
+    }
+}
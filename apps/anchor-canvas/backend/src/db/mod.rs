@@ -3,8 +3,10 @@
 //! This module is organized into submodules for different data types:
 //! - `pixels` - Pixel state and history operations
 //! - `indexer` - Indexer state tracking
+//! - `leases` - Region leases (governance mode)
 
 mod indexer;
+mod leases;
 mod pixels;
 
 use anyhow::Result;
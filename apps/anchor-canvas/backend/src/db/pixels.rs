@@ -84,6 +84,50 @@ impl Database {
         Ok(())
     }
 
+    /// Record a pixel rejected by a region lease
+    ///
+    /// The paint is kept in the history, marked invalid, and the current
+    /// state is left as it is.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_rejected_pixel(
+        &self,
+        x: i32,
+        y: i32,
+        r: i16,
+        g: i16,
+        b: i16,
+        txid: &[u8],
+        vout: i32,
+        block_height: Option<i32>,
+        creator_address: Option<&str>,
+        lease_id: i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO pixel_history (x, y, r, g, b, txid, vout, block_height, creator_address, valid, lease_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, FALSE, $10, NOW())
+            "#,
+        )
+        .bind(x)
+        .bind(y)
+        .bind(r)
+        .bind(g)
+        .bind(b)
+        .bind(txid)
+        .bind(vout)
+        .bind(block_height)
+        .bind(creator_address)
+        .bind(lease_id)
+        .execute(&self.pool)
+        .await?;
+
+        debug!(
+            "Rejected pixel ({}, {}) by {:?}: inside lease {}",
+            x, y, creator_address, lease_id
+        );
+        Ok(())
+    }
+
     /// Get canvas statistics
     pub async fn get_stats(&self) -> Result<CanvasStats> {
         let row: (i64, i64, Option<i32>, Option<chrono::DateTime<chrono::Utc>>) = sqlx::query_as(
@@ -162,9 +206,10 @@ impl Database {
             i32,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
+            bool,
         )> = sqlx::query_as(
             r#"
-                SELECT r, g, b, txid, vout, block_height, created_at
+                SELECT r, g, b, txid, vout, block_height, created_at, valid
                 FROM pixel_history
                 WHERE x = $1 AND y = $2
                 ORDER BY created_at DESC
@@ -187,6 +232,7 @@ impl Database {
                 vout: r.4,
                 block_height: r.5,
                 created_at: r.6,
+                valid: r.7,
             })
             .collect())
     }
@@ -203,6 +249,69 @@ impl Database {
         })
     }
 
+    /// Get pixel info as of a block height
+    ///
+    /// The pixel's colour at that height is the last valid history entry
+    /// confirmed at or below it; unconfirmed paints are left out.
+    pub async fn get_pixel_info_at(&self, x: i32, y: i32, height: i32) -> Result<PixelInfo> {
+        let rows: Vec<(
+            i16,
+            i16,
+            i16,
+            Vec<u8>,
+            i32,
+            Option<i32>,
+            chrono::DateTime<chrono::Utc>,
+            bool,
+        )> = sqlx::query_as(
+            r#"
+                SELECT r, g, b, txid, vout, block_height, created_at, valid
+                FROM pixel_history
+                WHERE x = $1 AND y = $2 AND block_height <= $3
+                ORDER BY block_height DESC, id DESC
+                LIMIT 50
+                "#,
+        )
+        .bind(x)
+        .bind(y)
+        .bind(height)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let history: Vec<PixelHistoryEntry> = rows
+            .into_iter()
+            .map(|r| PixelHistoryEntry {
+                r: r.0,
+                g: r.1,
+                b: r.2,
+                txid: hex::encode(&r.3),
+                vout: r.4,
+                block_height: r.5,
+                created_at: r.6,
+                valid: r.7,
+            })
+            .collect();
+
+        let current = history.iter().find(|h| h.valid).map(|h| PixelState {
+            x,
+            y,
+            r: h.r,
+            g: h.g,
+            b: h.b,
+            last_txid: h.txid.clone(),
+            last_vout: h.vout,
+            last_block_height: h.block_height,
+            updated_at: h.created_at,
+        });
+
+        Ok(PixelInfo {
+            x,
+            y,
+            current,
+            history,
+        })
+    }
+
     /// Get recent pixel changes
     pub async fn get_recent_pixels(&self, limit: i32) -> Result<Vec<RecentPixel>> {
         let rows: Vec<(
@@ -299,9 +408,10 @@ impl Database {
             i32,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
+            bool,
         )> = sqlx::query_as(
             r#"
-                SELECT x, y, r, g, b, txid, vout, block_height, created_at
+                SELECT x, y, r, g, b, txid, vout, block_height, created_at, valid
                 FROM pixel_history
                 WHERE txid = ANY($1)
                 ORDER BY created_at DESC
@@ -323,6 +433,7 @@ impl Database {
                 vout: r.6,
                 block_height: r.7,
                 created_at: r.8,
+                valid: r.9,
             })
             .collect())
     }
@@ -366,9 +477,10 @@ impl Database {
             i32,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
+            bool,
         )> = sqlx::query_as(
             r#"
-                SELECT x, y, r, g, b, txid, vout, block_height, created_at
+                SELECT x, y, r, g, b, txid, vout, block_height, created_at, valid
                 FROM pixel_history
                 WHERE creator_address = $1
                 ORDER BY created_at DESC
@@ -393,6 +505,7 @@ impl Database {
                 vout: r.6,
                 block_height: r.7,
                 created_at: r.8,
+                valid: r.9,
             })
             .collect())
     }
@@ -436,9 +549,10 @@ impl Database {
             i32,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
+            bool,
         )> = sqlx::query_as(
             r#"
-                SELECT x, y, r, g, b, txid, vout, block_height, created_at
+                SELECT x, y, r, g, b, txid, vout, block_height, created_at, valid
                 FROM pixel_history
                 WHERE creator_address = ANY($1)
                 ORDER BY created_at DESC
@@ -462,6 +576,7 @@ impl Database {
                 vout: r.6,
                 block_height: r.7,
                 created_at: r.8,
+                valid: r.9,
             })
             .collect())
     }
@@ -1,5 +1,6 @@
 //! Canvas handlers (tiles, regions, preview)
 
+use anchor_api_error::ApiError;
 use axum::{
     body::Body,
     extract::{Path, Query, State},
@@ -31,17 +32,14 @@ use crate::models::RegionParams;
 pub async fn get_tile(
     State(state): State<Arc<AppState>>,
     Path((z, x, y)): Path<(u32, u32, u32)>,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, ApiError> {
     // Validate tile coordinates
     let max_tiles = 1u32 << z;
     if x >= max_tiles || y >= max_tiles {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "Tile coordinates out of bounds: ({}, {}) at zoom {}. Max tiles: {}",
-                x, y, z, max_tiles
-            ),
-        ));
+        return Err(ApiError::bad_request(format!(
+            "Tile coordinates out of bounds: ({}, {}) at zoom {}. Max tiles: {}",
+            x, y, z, max_tiles
+        )));
     }
 
     match state.canvas.generate_tile(z, x, y).await {
@@ -56,7 +54,7 @@ pub async fn get_tile(
         }
         Err(e) => {
             error!("Failed to generate tile: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -81,20 +79,16 @@ pub async fn get_tile(
 pub async fn get_region(
     State(state): State<Arc<AppState>>,
     Query(params): Query<RegionParams>,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, ApiError> {
     // Validate region
     if params.w <= 0 || params.h <= 0 || params.w > 1024 || params.h > 1024 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Region dimensions must be between 1 and 1024".to_string(),
+        return Err(ApiError::bad_request(
+            "Region dimensions must be between 1 and 1024",
         ));
     }
 
     if params.x < 0 || params.y < 0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Coordinates cannot be negative".to_string(),
-        ));
+        return Err(ApiError::bad_request("Coordinates cannot be negative"));
     }
 
     match state
@@ -113,7 +107,7 @@ pub async fn get_region(
         }
         Err(e) => {
             error!("Failed to generate region: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -128,9 +122,7 @@ pub async fn get_region(
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn get_preview(
-    State(state): State<Arc<AppState>>,
-) -> Result<Response, (StatusCode, String)> {
+pub async fn get_preview(State(state): State<Arc<AppState>>) -> Result<Response, ApiError> {
     match state.canvas.generate_preview(512).await {
         Ok(png_data) => {
             let response = Response::builder()
@@ -143,7 +135,7 @@ pub async fn get_preview(
         }
         Err(e) => {
             error!("Failed to generate preview: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -158,9 +150,7 @@ pub async fn get_preview(
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn get_canvas(
-    State(state): State<Arc<AppState>>,
-) -> Result<Response, (StatusCode, String)> {
+pub async fn get_canvas(State(state): State<Arc<AppState>>) -> Result<Response, ApiError> {
     match state.canvas.get_canvas_binary().await {
         Ok(data) => {
             let response = Response::builder()
@@ -173,7 +163,7 @@ pub async fn get_canvas(
         }
         Err(e) => {
             error!("Failed to get canvas data: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
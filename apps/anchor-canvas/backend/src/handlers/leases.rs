@@ -0,0 +1,106 @@
+//! Region lease handlers (get_leases, get_leases_by_txid)
+
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::handlers::AppState;
+use crate::models::{Lease, LeaseParams, LeasesResponse};
+
+/// Get the leases in force
+#[utoipa::path(
+    get,
+    path = "/leases",
+    tag = "Leases",
+    params(
+        ("at_height" = Option<i32>, Query, description = "Leases active at this block height (defaults to the last indexed block)"),
+        ("x" = Option<i32>, Query, description = "Only leases containing this pixel (with y)"),
+        ("y" = Option<i32>, Query, description = "Only leases containing this pixel (with x)"),
+        ("owner" = Option<String>, Query, description = "Only leases owned by this address")
+    ),
+    responses(
+        (status = 200, description = "Active leases and lease rules", body = LeasesResponse),
+        (status = 400, description = "Invalid height or pixel"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_leases(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LeaseParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let indexed = state.db.get_last_block_height().await.map_err(|e| {
+        error!("Failed to get last block height: {}", e);
+        ApiError::internal(e.to_string())
+    })?;
+    let height = params.at_height.unwrap_or(indexed);
+    if height < 0 {
+        return Err(ApiError::bad_request("at_height must not be negative"));
+    }
+    let pixel = match (params.x, params.y) {
+        (Some(x), Some(y)) => Some((x, y)),
+        (None, None) => None,
+        _ => return Err(ApiError::bad_request("x and y must be given together")),
+    };
+
+    let leases = state.db.get_active_leases(height).await.map_err(|e| {
+        error!("Failed to get leases: {}", e);
+        ApiError::internal(e.to_string())
+    })?;
+    let leases = leases
+        .into_iter()
+        .filter(|lease| pixel.is_none_or(|(x, y)| lease.contains(x, y)))
+        .filter(|lease| {
+            params
+                .owner
+                .as_deref()
+                .is_none_or(|owner| lease.owner_address.as_deref() == Some(owner))
+        })
+        .collect();
+
+    let rules = &state.governance;
+    Ok(Json(LeasesResponse {
+        governance: rules.enabled,
+        block_height: height,
+        max_blocks: rules.max_blocks,
+        max_area: rules.max_area,
+        price_msats: rules.price_msats,
+        leases,
+    }))
+}
+
+/// Get the leases claimed by a transaction, including rejected claims
+#[utoipa::path(
+    get,
+    path = "/leases/{txid}",
+    tag = "Leases",
+    params(
+        ("txid" = String, Path, description = "Claim transaction ID (hex)")
+    ),
+    responses(
+        (status = 200, description = "Leases of the claim, with rejection reasons", body = Vec<Lease>),
+        (status = 400, description = "Invalid txid"),
+        (status = 404, description = "No claim in this transaction"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_leases_by_txid(
+    State(state): State<Arc<AppState>>,
+    Path(txid): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let txid = hex::decode(&txid)
+        .map_err(|e| ApiError::bad_request(format!("Invalid txid hex: {}", e)))?;
+
+    let leases = state.db.get_leases_by_txid(&txid).await.map_err(|e| {
+        error!("Failed to get leases by txid: {}", e);
+        ApiError::internal(e.to_string())
+    })?;
+    if leases.is_empty() {
+        return Err(ApiError::not_found("No region claim in this transaction"));
+    }
+    Ok(Json(leases))
+}
@@ -1,16 +1,19 @@
 //! HTTP request handlers for the AnchorCanvas API
 
 pub mod canvas;
+pub mod leases;
 pub mod pixels;
 pub mod system;
 
 use std::sync::Arc;
 
 use crate::canvas::CanvasManager;
+use crate::config::GovernanceConfig;
 use crate::db::Database;
 
 // Re-export handlers
 pub use canvas::{get_canvas, get_preview, get_region, get_tile};
+pub use leases::{get_leases, get_leases_by_txid};
 pub use pixels::{
     get_my_pixels, get_pixel, get_pixels_by_address, get_pixels_by_addresses, get_pixels_by_txids,
     get_recent,
@@ -19,6 +22,7 @@ pub use system::{get_stats, health};
 
 // Re-export utoipa path macros for OpenAPI docs
 pub use canvas::{__path_get_canvas, __path_get_preview, __path_get_region, __path_get_tile};
+pub use leases::{__path_get_leases, __path_get_leases_by_txid};
 pub use pixels::{
     __path_get_my_pixels, __path_get_pixel, __path_get_pixels_by_address,
     __path_get_pixels_by_addresses, __path_get_pixels_by_txids, __path_get_recent,
@@ -29,10 +33,15 @@ pub use system::{__path_get_stats, __path_health};
 pub struct AppState {
     pub db: Database,
     pub canvas: CanvasManager,
+    pub governance: GovernanceConfig,
 }
 
 impl AppState {
-    pub fn new(db: Database, canvas: CanvasManager) -> Arc<Self> {
-        Arc::new(Self { db, canvas })
+    pub fn new(db: Database, canvas: CanvasManager, governance: GovernanceConfig) -> Arc<Self> {
+        Arc::new(Self {
+            db,
+            canvas,
+            governance,
+        })
     }
 }
@@ -1,8 +1,8 @@
 //! Pixel handlers (get_pixel, get_recent, get_pixels_by_txids, get_pixels_by_address)
 
+use anchor_api_error::{ApiError, ErrorCode};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
     response::IntoResponse,
     Json,
 };
@@ -13,8 +13,9 @@ use tracing::{error, info};
 use crate::config::{CANVAS_HEIGHT, CANVAS_WIDTH};
 use crate::handlers::AppState;
 use crate::models::{
-    GetPixelsByAddressParams, GetPixelsByAddressResponse, GetPixelsByAddressesRequest,
-    GetPixelsByTxidsRequest, GetPixelsByTxidsResponse, ListParams, PixelInfo, RecentPixel,
+    AtHeightParams, GetPixelsByAddressParams, GetPixelsByAddressResponse,
+    GetPixelsByAddressesRequest, GetPixelsByTxidsRequest, GetPixelsByTxidsResponse, ListParams,
+    PixelInfo, RecentPixel,
 };
 
 /// Get a single pixel's info
@@ -24,34 +25,53 @@ use crate::models::{
     tag = "Pixels",
     params(
         ("x" = i32, Path, description = "X coordinate"),
-        ("y" = i32, Path, description = "Y coordinate")
+        ("y" = i32, Path, description = "Y coordinate"),
+        ("at_height" = Option<i32>, Query, description = "Return the pixel as of this block height")
     ),
     responses(
         (status = 200, description = "Pixel info with history", body = PixelInfo),
-        (status = 400, description = "Invalid coordinates"),
+        (status = 400, description = "Invalid coordinates or height"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn get_pixel(
     State(state): State<Arc<AppState>>,
     Path((x, y)): Path<(i32, i32)>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+    Query(params): Query<AtHeightParams>,
+) -> Result<impl IntoResponse, ApiError> {
     // Validate coordinates
     if x < 0 || x >= CANVAS_WIDTH as i32 || y < 0 || y >= CANVAS_HEIGHT as i32 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            format!(
-                "Coordinates out of bounds: ({}, {}). Canvas is {}x{}",
-                x, y, CANVAS_WIDTH, CANVAS_HEIGHT
-            ),
-        ));
+        return Err(ApiError::bad_request(format!(
+            "Coordinates out of bounds: ({}, {}). Canvas is {}x{}",
+            x, y, CANVAS_WIDTH, CANVAS_HEIGHT
+        )));
     }
 
-    match state.db.get_pixel_info(x, y).await {
+    let result = match params.at_height {
+        Some(height) => {
+            if height < 0 {
+                return Err(ApiError::bad_request("at_height must not be negative"));
+            }
+            let indexed = state.db.get_last_block_height().await.map_err(|e| {
+                error!("Failed to get last block height: {}", e);
+                ApiError::internal(e.to_string())
+            })?;
+            if height > indexed {
+                return Err(ApiError::bad_request(format!(
+                    "Height {} is not indexed yet (last indexed block is {})",
+                    height, indexed
+                )));
+            }
+            state.db.get_pixel_info_at(x, y, height).await
+        }
+        None => state.db.get_pixel_info(x, y).await,
+    };
+
+    match result {
         Ok(info) => Ok(Json(info)),
         Err(e) => {
             error!("Failed to get pixel info: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -72,13 +92,13 @@ pub async fn get_pixel(
 pub async fn get_recent(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let limit = params.per_page.min(100);
     match state.db.get_recent_pixels(limit).await {
         Ok(pixels) => Ok(Json(pixels)),
         Err(e) => {
             error!("Failed to get recent pixels: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -98,14 +118,14 @@ pub async fn get_recent(
 pub async fn get_pixels_by_txids(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<GetPixelsByTxidsRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Convert hex txids to bytes
     let txids_bytes: Result<Vec<Vec<u8>>, _> = payload.txids.iter().map(hex::decode).collect();
 
     let txids_bytes = match txids_bytes {
         Ok(bytes) => bytes,
         Err(e) => {
-            return Err((StatusCode::BAD_REQUEST, format!("Invalid txid hex: {}", e)));
+            return Err(ApiError::bad_request(format!("Invalid txid hex: {}", e)));
         }
     };
 
@@ -114,7 +134,7 @@ pub async fn get_pixels_by_txids(
         Ok(pixels) => pixels,
         Err(e) => {
             error!("Failed to get pixels by txids: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -124,7 +144,7 @@ pub async fn get_pixels_by_txids(
             Ok(stats) => stats,
             Err(e) => {
                 error!("Failed to get pixels stats: {}", e);
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+                return Err(ApiError::internal(e.to_string()));
             }
         };
 
@@ -154,10 +174,10 @@ pub async fn get_pixels_by_txids(
 pub async fn get_pixels_by_address(
     State(state): State<Arc<AppState>>,
     Query(params): Query<GetPixelsByAddressParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Validate address (basic check)
     if params.address.is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Address is required".to_string()));
+        return Err(ApiError::bad_request("Address is required"));
     }
 
     let per_page = params.per_page.clamp(1, 500);
@@ -173,7 +193,7 @@ pub async fn get_pixels_by_address(
         Ok(pixels) => pixels,
         Err(e) => {
             error!("Failed to get pixels by address: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -183,7 +203,7 @@ pub async fn get_pixels_by_address(
             Ok(stats) => stats,
             Err(e) => {
                 error!("Failed to get pixels stats by address: {}", e);
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+                return Err(ApiError::internal(e.to_string()));
             }
         };
 
@@ -212,7 +232,7 @@ pub async fn get_pixels_by_address(
 pub async fn get_pixels_by_addresses(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<GetPixelsByAddressesRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     if payload.addresses.is_empty() {
         return Ok(Json(GetPixelsByAddressResponse {
             pixels: vec![],
@@ -235,7 +255,7 @@ pub async fn get_pixels_by_addresses(
         Ok(pixels) => pixels,
         Err(e) => {
             error!("Failed to get pixels by addresses: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -248,7 +268,7 @@ pub async fn get_pixels_by_addresses(
         Ok(stats) => stats,
         Err(e) => {
             error!("Failed to get pixels stats by addresses: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -284,7 +304,7 @@ struct WalletAddressesResponse {
 pub async fn get_my_pixels(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Allow up to 50000 pixels for my pixels page (user's own pixels)
     let per_page = params.per_page.clamp(1, 50000);
 
@@ -300,8 +320,8 @@ pub async fn get_my_pixels(
         .await
         .map_err(|e| {
             error!("Failed to connect to wallet service: {}", e);
-            (
-                StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::new(
+                ErrorCode::Unavailable,
                 format!("Wallet service unavailable: {}", e),
             )
         })?;
@@ -311,18 +331,15 @@ pub async fn get_my_pixels(
             "Wallet service returned error: {}",
             wallet_response.status()
         );
-        return Err((
-            StatusCode::SERVICE_UNAVAILABLE,
-            "Wallet service error".to_string(),
+        return Err(ApiError::new(
+            ErrorCode::Unavailable,
+            "Wallet service error",
         ));
     }
 
     let wallet_data: WalletAddressesResponse = wallet_response.json().await.map_err(|e| {
         error!("Failed to parse wallet response: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to parse wallet response: {}", e),
-        )
+        ApiError::internal(format!("Failed to parse wallet response: {}", e))
     })?;
 
     info!(
@@ -350,7 +367,7 @@ pub async fn get_my_pixels(
         Ok(pixels) => pixels,
         Err(e) => {
             error!("Failed to get pixels by addresses: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -363,7 +380,7 @@ pub async fn get_my_pixels(
         Ok(stats) => stats,
         Err(e) => {
             error!("Failed to get pixels stats by addresses: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
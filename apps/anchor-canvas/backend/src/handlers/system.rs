@@ -1,6 +1,7 @@
 //! System handlers (health, stats)
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::ApiError;
+use axum::{extract::State, response::IntoResponse, Json};
 use std::sync::Arc;
 use tracing::error;
 
@@ -33,14 +34,12 @@ pub async fn health() -> Json<HealthResponse> {
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn get_stats(
-    State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, ApiError> {
     match state.db.get_stats().await {
         Ok(stats) => Ok(Json(stats)),
         Err(e) => {
             error!("Failed to get stats: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -3,6 +3,15 @@
 //! Scans the Bitcoin blockchain for Anchor protocol transactions
 //! that contain pixel data (kind = State/2) and updates the database.
 //! Supports both OP_RETURN and WitnessData carriers.
+//!
+//! With `CANVAS_GOVERNANCE` set, region claims (a State sub-kind) are indexed
+//! too. A valid claim grants its creator a lease on a rectangle from its
+//! block for `duration_blocks` blocks; pixels painted inside it by anyone
+//! else during the lease are recorded as invalid and leave the canvas as it
+//! is. Claims are rejected when they leave the canvas, exceed the lease
+//! limits, pay less than the configured price or overlap another owner's
+//! active lease. Transactions are applied in block order, so a claim binds
+//! the transactions after it in the same block.
 
 use anyhow::{anyhow, Result};
 use bitcoin::hashes::Hash;
@@ -14,12 +23,12 @@ use tracing::{debug, error, info, warn};
 
 use anchor_core::carrier::CarrierSelector;
 use anchor_core::AnchorKind;
-use anchor_specs::state::StateSpec;
+use anchor_specs::state::{RegionClaim, StateSpec};
 use anchor_specs::KindSpec;
 
-use crate::config::{Config, CANVAS_HEIGHT, CANVAS_WIDTH};
+use crate::config::{Config, GovernanceConfig, CANVAS_HEIGHT, CANVAS_WIDTH};
 use crate::db::Database;
-use crate::models::Pixel;
+use crate::models::{Lease, Pixel};
 
 /// Parse pixel data from Anchor message body using StateSpec
 pub fn parse_pixel_payload(body: &[u8]) -> Result<Vec<Pixel>> {
@@ -48,6 +57,63 @@ pub fn parse_pixel_payload(body: &[u8]) -> Result<Vec<Pixel>> {
     Ok(pixels)
 }
 
+/// Why a region claim can't be granted, if it can't
+///
+/// `fee_sats` is the fee the claim transaction paid, when it could be
+/// computed; `active` are the leases in force at the claim's block.
+pub fn check_claim(
+    claim: &RegionClaim,
+    owner: Option<&str>,
+    fee_sats: Option<u64>,
+    active: &[Lease],
+    rules: &GovernanceConfig,
+) -> Result<(), String> {
+    claim
+        .validate(CANVAS_WIDTH, CANVAS_HEIGHT)
+        .map_err(|e| e.to_string())?;
+    if owner.is_none() {
+        return Err("Claim has no creator address".to_string());
+    }
+    if claim.duration_blocks > rules.max_blocks {
+        return Err(format!(
+            "Lease of {} blocks exceeds the maximum of {}",
+            claim.duration_blocks, rules.max_blocks
+        ));
+    }
+    if claim.area() > rules.max_area {
+        return Err(format!(
+            "Region of {} pixels exceeds the maximum of {}",
+            claim.area(),
+            rules.max_area
+        ));
+    }
+
+    let price = rules.price_sats(claim.area(), claim.duration_blocks);
+    if price > 0 {
+        match fee_sats {
+            Some(fee) if fee >= price => {}
+            Some(fee) => {
+                return Err(format!(
+                    "Claim paid {} sats, the lease costs {}",
+                    fee, price
+                ))
+            }
+            None => return Err("Claim fee could not be computed".to_string()),
+        }
+    }
+
+    let (x, y) = (claim.x as i32, claim.y as i32);
+    let (width, height) = (claim.width as i32, claim.height as i32);
+    if let Some(lease) = active
+        .iter()
+        .find(|lease| lease.overlaps(x, y, width, height) && !lease.allows(owner))
+    {
+        return Err(format!("Region overlaps active lease {}", lease.id));
+    }
+
+    Ok(())
+}
+
 /// AnchorCanvas indexer that scans the blockchain for pixel transactions
 pub struct CanvasIndexer {
     db: Database,
@@ -138,19 +204,59 @@ impl CanvasIndexer {
         );
 
         let mut pixel_count = 0;
+        let mut rejected_count = 0;
+        let governance = self.config.governance.enabled;
 
         for tx in &block.txdata {
+            if governance {
+                if let Some((vout, claim)) = self.extract_claim_from_tx(tx) {
+                    self.index_claim(tx, vout, &claim, height).await?;
+                    continue;
+                }
+            }
+
             if let Some(pixels) = self.extract_pixels_from_tx(tx)? {
                 let txid = tx.compute_txid();
 
                 // Extract creator address from the first input
                 let creator_address = self.get_creator_address(tx);
 
+                // Leases are read per transaction, so claims earlier in the
+                // block already apply
+                let leases = if governance {
+                    self.db.get_active_leases(height).await?
+                } else {
+                    Vec::new()
+                };
+
                 for (vout, pixel) in pixels.iter().enumerate() {
+                    let (x, y) = (pixel.x as i32, pixel.y as i32);
+                    let lease = leases.iter().find(|lease| {
+                        lease.contains(x, y) && !lease.allows(creator_address.as_deref())
+                    });
+                    if let Some(lease) = lease {
+                        self.db
+                            .insert_rejected_pixel(
+                                x,
+                                y,
+                                pixel.r as i16,
+                                pixel.g as i16,
+                                pixel.b as i16,
+                                &txid.to_byte_array(),
+                                vout as i32,
+                                Some(height),
+                                creator_address.as_deref(),
+                                lease.id,
+                            )
+                            .await?;
+                        rejected_count += 1;
+                        continue;
+                    }
+
                     self.db
                         .upsert_pixel(
-                            pixel.x as i32,
-                            pixel.y as i32,
+                            x,
+                            y,
                             pixel.r as i16,
                             pixel.g as i16,
                             pixel.b as i16,
@@ -173,10 +279,99 @@ impl CanvasIndexer {
         if pixel_count > 0 {
             info!("Block {}: indexed {} pixels", height, pixel_count);
         }
+        if rejected_count > 0 {
+            info!(
+                "Block {}: rejected {} pixels inside leases",
+                height, rejected_count
+            );
+        }
 
         Ok(())
     }
 
+    /// Record a region claim, granting the lease if the claim is valid
+    async fn index_claim(
+        &self,
+        tx: &bitcoin::Transaction,
+        vout: u32,
+        claim: &RegionClaim,
+        height: i32,
+    ) -> Result<()> {
+        let rules = &self.config.governance;
+        let owner = self.get_creator_address(tx);
+        let fee_sats = if rules.price_msats > 0 {
+            self.get_tx_fee(tx)
+        } else {
+            None
+        };
+        let active = self.db.get_active_leases(height).await?;
+        let verdict = check_claim(claim, owner.as_deref(), fee_sats, &active, rules);
+
+        let txid = tx.compute_txid();
+        let end_height = height.saturating_add(claim.duration_blocks.min(i32::MAX as u32) as i32);
+        self.db
+            .insert_lease(
+                &txid.to_byte_array(),
+                vout as i32,
+                owner.as_deref(),
+                (
+                    claim.x as i32,
+                    claim.y as i32,
+                    claim.width as i32,
+                    claim.height as i32,
+                ),
+                height,
+                end_height,
+                fee_sats.map(|fee| fee as i64),
+                verdict.as_ref().err().map(String::as_str),
+            )
+            .await?;
+
+        match verdict {
+            Ok(()) => info!(
+                "Block {}: lease {}x{} at ({}, {}) until block {} granted to {:?}",
+                height, claim.width, claim.height, claim.x, claim.y, end_height, owner
+            ),
+            Err(reason) => info!(
+                "Block {}: rejected region claim {}: {}",
+                height, txid, reason
+            ),
+        }
+        Ok(())
+    }
+
+    /// Fee paid by a transaction, from the values of its previous outputs
+    fn get_tx_fee(&self, tx: &bitcoin::Transaction) -> Option<u64> {
+        let mut input_value = 0u64;
+        for input in &tx.input {
+            let prev = &input.previous_output;
+            match self.rpc.get_raw_transaction(&prev.txid, None) {
+                Ok(prev_tx) => {
+                    input_value += prev_tx.output.get(prev.vout as usize)?.value.to_sat();
+                }
+                Err(e) => {
+                    debug!("Failed to get previous tx {}: {}", prev.txid, e);
+                    return None;
+                }
+            }
+        }
+        let output_value: u64 = tx.output.iter().map(|output| output.value.to_sat()).sum();
+        input_value.checked_sub(output_value)
+    }
+
+    /// Extract a region claim from a transaction, with the output carrying it
+    fn extract_claim_from_tx(&self, tx: &bitcoin::Transaction) -> Option<(u32, RegionClaim)> {
+        CarrierSelector::new()
+            .detect(tx)
+            .into_iter()
+            .filter(|detection| matches!(detection.message.kind, AnchorKind::State))
+            .find_map(|detection| {
+                RegionClaim::from_bytes(&detection.message.body)
+                    .ok()
+                    .map(|claim| (detection.vout, claim))
+            })
+    }
+
     /// Get the creator address from the transaction
     /// For Anchor transactions, we look at the change output (non-OP_RETURN outputs)
     /// This works for both regular transactions and inscriptions
@@ -300,6 +495,83 @@ mod tests {
         assert_eq!(pixels[1].b, 255);
     }
 
+    fn rules() -> GovernanceConfig {
+        GovernanceConfig {
+            enabled: true,
+            max_blocks: 100,
+            max_area: 400,
+            price_msats: 10,
+        }
+    }
+
+    fn lease(id: i32, owner: &str, x: i32, y: i32, width: i32, height: i32) -> Lease {
+        Lease {
+            id,
+            txid: String::new(),
+            vout: 0,
+            owner_address: Some(owner.to_string()),
+            x,
+            y,
+            width,
+            height,
+            start_height: 10,
+            end_height: 20,
+            fee_sats: None,
+            valid: true,
+            invalid_reason: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_check_claim_limits() {
+        let rules = rules();
+        // 20x20 pixels for 10 blocks at 10 msats costs 40 sats
+        let claim = RegionClaim::new(0, 0, 20, 20, 10);
+        assert!(check_claim(&claim, Some("alice"), Some(40), &[], &rules).is_ok());
+        assert!(check_claim(&claim, Some("alice"), Some(39), &[], &rules).is_err());
+        assert!(check_claim(&claim, Some("alice"), None, &[], &rules).is_err());
+        assert!(check_claim(&claim, None, Some(40), &[], &rules).is_err());
+
+        let too_large = RegionClaim::new(0, 0, 21, 20, 10);
+        assert!(check_claim(&too_large, Some("alice"), Some(1000), &[], &rules).is_err());
+        let too_long = RegionClaim::new(0, 0, 1, 1, 101);
+        assert!(check_claim(&too_long, Some("alice"), Some(1000), &[], &rules).is_err());
+        let outside = RegionClaim::new(CANVAS_WIDTH as u16 - 1, 0, 2, 1, 1);
+        assert!(check_claim(&outside, Some("alice"), Some(1000), &[], &rules).is_err());
+
+        let free = GovernanceConfig {
+            price_msats: 0,
+            ..rules
+        };
+        assert!(check_claim(&claim, Some("alice"), None, &[], &free).is_ok());
+    }
+
+    #[test]
+    fn test_check_claim_overlap() {
+        let rules = rules();
+        let active = vec![lease(1, "bob", 10, 10, 10, 10)];
+
+        let overlapping = RegionClaim::new(15, 15, 10, 10, 1);
+        assert!(check_claim(&overlapping, Some("alice"), Some(10), &active, &rules).is_err());
+        // Owners may claim over their own leases
+        assert!(check_claim(&overlapping, Some("bob"), Some(10), &active, &rules).is_ok());
+
+        let adjacent = RegionClaim::new(20, 10, 10, 10, 1);
+        assert!(check_claim(&adjacent, Some("alice"), Some(10), &active, &rules).is_ok());
+    }
+
+    #[test]
+    fn test_lease_bounds() {
+        let lease = lease(1, "bob", 10, 10, 10, 10);
+        assert!(lease.contains(10, 10));
+        assert!(lease.contains(19, 19));
+        assert!(!lease.contains(20, 10));
+        assert!(lease.allows(Some("bob")));
+        assert!(!lease.allows(Some("alice")));
+        assert!(!lease.allows(None));
+    }
+
     #[test]
     fn test_pixel_encode_decode() {
         let pixel = Pixel::new(1234, 5678, 128, 64, 32);
@@ -16,11 +16,12 @@ mod services;
 
 use std::sync::Arc;
 
+use anchor_api_security::SecurityConfig;
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -49,6 +50,8 @@ use crate::indexer::CanvasIndexer;
         handlers::get_preview,
         handlers::get_region,
         handlers::get_tile,
+        handlers::get_leases,
+        handlers::get_leases_by_txid,
     ),
     components(schemas(
         models::HealthResponse,
@@ -65,11 +68,14 @@ use crate::indexer::CanvasIndexer;
         models::GetPixelsByAddressParams,
         models::GetPixelsByAddressesRequest,
         models::GetPixelsByAddressResponse,
+        models::Lease,
+        models::LeasesResponse,
     )),
     tags(
         (name = "System", description = "Health check endpoints"),
         (name = "Canvas", description = "Canvas rendering and tiles"),
         (name = "Pixels", description = "Pixel queries and operations"),
+        (name = "Leases", description = "Region leases (governance mode)"),
     ),
     info(
         title = "Anchor Canvas API",
@@ -81,6 +87,10 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(
@@ -105,7 +115,7 @@ async fn main() -> anyhow::Result<()> {
     let canvas = CanvasManager::new(db.clone());
 
     // Create shared state
-    let state = AppState::new(db.clone(), canvas);
+    let state = AppState::new(db.clone(), canvas, config.governance.clone());
 
     // Start indexer in background
     let indexer = Arc::new(CanvasIndexer::new(db.clone(), config.clone())?);
@@ -133,14 +143,12 @@ async fn main() -> anyhow::Result<()> {
         .route("/canvas/preview", get(handlers::get_preview))
         .route("/canvas/region", get(handlers::get_region))
         .route("/canvas/tile/{z}/{x}/{y}", get(handlers::get_tile))
+        .route("/leases", get(handlers::get_leases))
+        .route("/leases/:txid", get(handlers::get_leases_by_txid))
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer())
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
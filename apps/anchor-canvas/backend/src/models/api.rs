@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use super::Lease;
+
 /// Canvas statistics
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CanvasStats {
@@ -24,6 +26,38 @@ pub struct RegionParams {
     pub h: i32,
 }
 
+/// Historical lookup parameters
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtHeightParams {
+    /// Return state as of this block height
+    pub at_height: Option<i32>,
+}
+
+/// Lease listing parameters
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeaseParams {
+    /// Leases active at this block height (defaults to the indexed height)
+    pub at_height: Option<i32>,
+    /// Only leases containing this pixel (with `y`)
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    /// Only leases owned by this address
+    pub owner: Option<String>,
+}
+
+/// Active leases and the rules they were granted under
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LeasesResponse {
+    /// Whether region claims are enforced
+    pub governance: bool,
+    pub block_height: i32,
+    pub max_blocks: u32,
+    pub max_area: u64,
+    /// Minimum claim fee, in millisatoshis per pixel per block
+    pub price_msats: u64,
+    pub leases: Vec<Lease>,
+}
+
 /// Pagination parameters
 #[derive(Debug, Clone, Deserialize)]
 pub struct ListParams {
@@ -61,6 +95,8 @@ pub struct UserPixel {
     pub vout: i32,
     pub block_height: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// False when a region lease rejected the paint
+    pub valid: bool,
 }
 
 /// Response for get pixels by txids
@@ -0,0 +1,50 @@
+//! Region lease types
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Exclusive drawing rights on a canvas region, granted by a region claim
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Lease {
+    pub id: i32,
+    /// Claim transaction (hex-encoded)
+    pub txid: String,
+    pub vout: i32,
+    /// Only address allowed to paint inside the region while active
+    pub owner_address: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// First block of the lease
+    pub start_height: i32,
+    /// First block after the lease
+    pub end_height: i32,
+    /// Fee paid by the claim, when a price is set
+    pub fee_sats: Option<i64>,
+    /// False when the claim was rejected
+    pub valid: bool,
+    /// Why the claim was rejected
+    pub invalid_reason: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Lease {
+    /// Whether the region contains a pixel
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// Whether the region shares a pixel with a rectangle
+    pub fn overlaps(&self, x: i32, y: i32, width: i32, height: i32) -> bool {
+        self.x < x + width
+            && x < self.x + self.width
+            && self.y < y + height
+            && y < self.y + self.height
+    }
+
+    /// Whether an address may paint inside the region
+    pub fn allows(&self, address: Option<&str>) -> bool {
+        address.is_some() && self.owner_address.as_deref() == address
+    }
+}
@@ -3,9 +3,12 @@
 //! This module is organized into submodules:
 //! - `pixel` - Core pixel types and protocol encoding
 //! - `api` - API request/response types
+//! - `lease` - Region leases (governance mode)
 
 mod api;
+mod lease;
 mod pixel;
 
 pub use api::*;
+pub use lease::*;
 pub use pixel::*;
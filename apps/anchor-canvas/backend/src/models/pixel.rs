@@ -64,6 +64,8 @@ pub struct PixelHistoryEntry {
     pub vout: i32,
     pub block_height: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// False when a region lease rejected the paint
+    pub valid: bool,
 }
 
 /// Pixel info response (with history)
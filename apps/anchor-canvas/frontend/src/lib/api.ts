@@ -5,6 +5,24 @@
 const API_URL = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3003';
 const WALLET_URL = process.env.NEXT_PUBLIC_WALLET_URL || 'http://localhost:3001';
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 // Canvas dimensions
 export const CANVAS_WIDTH = 4580;
 export const CANVAS_HEIGHT = 4580;
@@ -38,6 +56,7 @@ export interface PixelHistoryEntry {
   vout: number;
   block_height: number | null;
   created_at: string;
+  valid: boolean;
 }
 
 export interface PixelInfo {
@@ -78,6 +97,7 @@ export interface UserPixel {
   vout: number;
   block_height: number | null;
   created_at: string;
+  valid: boolean;
 }
 
 export interface MyPixelsResponse {
@@ -308,8 +328,7 @@ export async function createPixelTransaction(
   });
 
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to create pixel transaction');
+    throw await apiError(res, 'Failed to create pixel transaction');
   }
 
   return res.json();
@@ -0,0 +1,5 @@
+-- Index identity record names for batch resolution
+-- The resolver looks identities up by their full Selfie Records name
+-- (e.g., user._nostr.domain.tld), often hundreds at a time
+
+CREATE INDEX IF NOT EXISTS idx_domain_identities_record_name ON domain_identities(record_name);
@@ -0,0 +1,30 @@
+-- Migration: Domain Webhooks
+-- Per-domain subscriptions to domain updates. The delivery log comes from
+-- anchor_webhook_deliveries() in libs/rust/anchor-webhooks/migrations.
+
+-- ============================================================================
+-- Webhooks
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS domain_webhooks (
+    id SERIAL PRIMARY KEY,
+    domain TEXT NOT NULL,
+    url TEXT NOT NULL,
+    -- HMAC-SHA256 key for the X-Anchor-Signature header
+    secret TEXT NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_domain_webhooks_domain ON domain_webhooks(LOWER(domain)) WHERE active;
+
+-- ============================================================================
+-- Deliveries
+-- ============================================================================
+
+SELECT anchor_webhook_deliveries('domain_webhook_deliveries', 'domain_webhooks');
+
+-- Transaction that triggered the delivery (display hex)
+ALTER TABLE domain_webhook_deliveries
+    ADD COLUMN IF NOT EXISTS txid TEXT NOT NULL,
+    ADD COLUMN IF NOT EXISTS vout INTEGER NOT NULL;
@@ -0,0 +1,4 @@
+-- Index for state-at-height lookups (GET /resolve/{name}?at_height=)
+
+CREATE INDEX IF NOT EXISTS idx_domain_history_domain_height
+    ON domain_history(domain_id, block_height DESC);
@@ -0,0 +1,11 @@
+-- Migration: Current Domain Owners
+-- owner_txid/owner_vout already track the ownership UTXO as it moves; the
+-- locking script and address of that output let clients look domains up
+-- by who controls them now rather than by the registering transaction.
+
+ALTER TABLE domains ADD COLUMN IF NOT EXISTS owner_script BYTEA;
+ALTER TABLE domains ADD COLUMN IF NOT EXISTS owner_address TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_domains_owner_script ON domains(owner_script);
+CREATE INDEX IF NOT EXISTS idx_domains_owner_address ON domains(owner_address);
+CREATE INDEX IF NOT EXISTS idx_domains_owner_outpoint ON domains(owner_txid, owner_vout);
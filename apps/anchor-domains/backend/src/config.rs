@@ -21,6 +21,22 @@ pub struct Config {
     pub poll_interval_secs: u64,
     /// Number of confirmations required
     pub confirmations: u32,
+    /// DNS-over-HTTPS JSON endpoint used to resolve identities on hybrid domains
+    pub doh_url: String,
+    /// How long resolved identities are cached, in seconds
+    pub identity_cache_ttl_secs: u64,
+    /// Serve read routes only, with rate limiting and response caching
+    pub public_resolver: bool,
+    /// Requests per minute allowed per client IP in public mode (0 = unlimited)
+    pub resolver_rate_limit: u32,
+    /// Requests a client may burst above the steady rate
+    pub resolver_rate_burst: u32,
+    /// How long responses are cached in public mode, in seconds
+    pub resolver_cache_secs: u64,
+    /// How long an expired response may still be served while it is refreshed
+    pub resolver_stale_secs: u64,
+    /// Take the client IP from `X-Forwarded-For` (only behind a trusted proxy)
+    pub resolver_trust_proxy: bool,
 }
 
 impl Config {
@@ -51,6 +67,36 @@ impl Config {
                 .ok()
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(1),
+            doh_url: env::var("DOH_URL")
+                .unwrap_or_else(|_| "https://cloudflare-dns.com/dns-query".to_string()),
+            identity_cache_ttl_secs: env::var("IDENTITY_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(300),
+            public_resolver: env::var("PUBLIC_RESOLVER")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false),
+            resolver_rate_limit: env::var("RESOLVER_RATE_LIMIT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(120),
+            resolver_rate_burst: env::var("RESOLVER_RATE_BURST")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(30),
+            resolver_cache_secs: env::var("RESOLVER_CACHE_SECS")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(30),
+            resolver_stale_secs: env::var("RESOLVER_STALE_SECS")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(0),
+            resolver_trust_proxy: env::var("RESOLVER_TRUST_PROXY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false),
         }
     }
 }
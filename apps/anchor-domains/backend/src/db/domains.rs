@@ -2,11 +2,13 @@
 
 #![allow(clippy::type_complexity)]
 
+use std::collections::HashSet;
+
 use anyhow::Result;
 use tracing::debug;
 
 use super::Database;
-use crate::models::{DnsRecord, DnsStats, Domain, DomainListItem, ResolveResponse};
+use crate::models::{DnsRecord, DnsStats, Domain, DomainListItem, OwnerOutput, ResolveResponse};
 
 impl Database {
     /// Check if a domain name is available
@@ -21,11 +23,13 @@ impl Database {
     }
 
     /// Register a new domain
+    #[allow(clippy::too_many_arguments)]
     pub async fn register_domain(
         &self,
         name: &str,
         txid: &[u8],
         vout: i32,
+        owner: &OwnerOutput,
         records: &[DnsRecord],
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
@@ -36,8 +40,9 @@ impl Database {
         // Insert domain
         let row: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO domains (name, txid, vout, owner_txid, owner_vout, block_hash, block_height)
-            VALUES ($1, $2, $3, $2, $3, $4, $5)
+            INSERT INTO domains (name, txid, vout, owner_txid, owner_vout, owner_script, owner_address,
+                                 block_hash, block_height)
+            VALUES ($1, $2, $3, $2, $4, $5, $6, $7, $8)
             ON CONFLICT (name) DO NOTHING
             RETURNING id
             "#,
@@ -45,6 +50,9 @@ impl Database {
         .bind(name)
         .bind(txid)
         .bind(vout)
+        .bind(owner.vout)
+        .bind(&owner.script)
+        .bind(&owner.address)
         .bind(block_hash)
         .bind(block_height)
         .fetch_one(&mut *tx)
@@ -82,11 +90,13 @@ impl Database {
     }
 
     /// Update domain records
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_domain(
         &self,
         name: &str,
         txid: &[u8],
         vout: i32,
+        owner: &OwnerOutput,
         records: &[DnsRecord],
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
@@ -110,13 +120,17 @@ impl Database {
         sqlx::query(
             r#"
             UPDATE domains 
-            SET txid = $1, vout = $2, owner_txid = $1, owner_vout = $2, 
-                block_hash = $3, block_height = $4, updated_at = NOW()
-            WHERE id = $5
+            SET txid = $1, vout = $2, owner_txid = $1, owner_vout = $3,
+                owner_script = $4, owner_address = $5,
+                block_hash = $6, block_height = $7, updated_at = NOW()
+            WHERE id = $8
             "#,
         )
         .bind(txid)
         .bind(vout)
+        .bind(owner.vout)
+        .bind(&owner.script)
+        .bind(&owner.address)
         .bind(block_hash)
         .bind(block_height)
         .bind(domain_id)
@@ -158,6 +172,95 @@ impl Database {
         Ok(true)
     }
 
+    /// Hand a domain to a new ownership output, keeping its records
+    pub async fn transfer_domain(
+        &self,
+        name: &str,
+        txid: &[u8],
+        owner: &OwnerOutput,
+        block_hash: Option<&[u8]>,
+        block_height: Option<i32>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE domains
+            SET owner_txid = $1, owner_vout = $2, owner_script = $3, owner_address = $4,
+                block_hash = $5, block_height = $6, updated_at = NOW()
+            WHERE LOWER(name) = LOWER($7)
+            "#,
+        )
+        .bind(txid)
+        .bind(owner.vout)
+        .bind(&owner.script)
+        .bind(&owner.address)
+        .bind(block_hash)
+        .bind(block_height)
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+
+        debug!("Transferred domain: {}", name);
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Which of the given outpoints currently own a domain
+    pub async fn owner_outpoints(
+        &self,
+        outpoints: &[(Vec<u8>, i32)],
+    ) -> Result<Vec<(Vec<u8>, i32)>> {
+        if outpoints.is_empty() {
+            return Ok(vec![]);
+        }
+        let (txids, vouts): (Vec<Vec<u8>>, Vec<i32>) = outpoints.iter().cloned().unzip();
+
+        let rows = sqlx::query_as(
+            r#"
+            SELECT d.owner_txid, d.owner_vout
+            FROM domains d
+            JOIN unnest($1::bytea[], $2::int[]) AS s(txid, vout)
+              ON d.owner_txid = s.txid AND d.owner_vout = s.vout
+            "#,
+        )
+        .bind(&txids)
+        .bind(&vouts)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Move domains owned by any of `spent` to the output of the spending
+    /// transaction, returning the names that moved
+    pub async fn move_spent_owners(
+        &self,
+        spent: &[(Vec<u8>, i32)],
+        txid: &[u8],
+        owner: &OwnerOutput,
+    ) -> Result<Vec<String>> {
+        let (txids, vouts): (Vec<Vec<u8>>, Vec<i32>) = spent.iter().cloned().unzip();
+
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            UPDATE domains d
+            SET owner_txid = $3, owner_vout = $4, owner_script = $5, owner_address = $6,
+                updated_at = NOW()
+            FROM unnest($1::bytea[], $2::int[]) AS s(txid, vout)
+            WHERE d.owner_txid = s.txid AND d.owner_vout = s.vout
+            RETURNING d.name
+            "#,
+        )
+        .bind(&txids)
+        .bind(&vouts)
+        .bind(txid)
+        .bind(owner.vout)
+        .bind(&owner.script)
+        .bind(&owner.address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(name,)| name).collect())
+    }
+
     /// Resolve a domain by name
     pub async fn resolve_by_name(&self, name: &str) -> Result<Option<ResolveResponse>> {
         let domain_row: Option<(i32, String, Vec<u8>, i32)> = sqlx::query_as(
@@ -179,6 +282,82 @@ impl Database {
                     vout,
                     txid_prefix,
                     records,
+                    query: None,
+                    matched_by: None,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Which of the given names are registered (lowercased)
+    pub async fn registered_names(&self, names: &[String]) -> Result<HashSet<String>> {
+        let rows: Vec<(String,)> =
+            sqlx::query_as("SELECT LOWER(name) FROM domains WHERE LOWER(name) = ANY($1)")
+                .bind(names)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(|(name,)| name).collect())
+    }
+
+    /// `(domain, record_name)` pairs with active named records (lowercased)
+    pub async fn named_records(&self, domains: &[String]) -> Result<HashSet<(String, String)>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT LOWER(d.name), LOWER(r.record_name)
+            FROM dns_records r
+            JOIN domains d ON d.id = r.domain_id
+            WHERE LOWER(d.name) = ANY($1)
+              AND r.is_active = TRUE
+              AND r.record_name IS NOT NULL
+            "#,
+        )
+        .bind(domains)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Resolve a domain by name as of a block height
+    ///
+    /// Uses the last domain_history entry at or below the height, so the
+    /// txid and records are the ones that were current at that block.
+    pub async fn resolve_by_name_at(
+        &self,
+        name: &str,
+        height: i32,
+    ) -> Result<Option<ResolveResponse>> {
+        let row: Option<(i32, String, Vec<u8>, i32)> = sqlx::query_as(
+            r#"
+            SELECT d.id, d.name, h.txid, h.vout
+            FROM domain_history h
+            JOIN domains d ON d.id = h.domain_id
+            WHERE LOWER(d.name) = LOWER($1) AND h.block_height <= $2
+            ORDER BY h.block_height DESC, h.id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(name)
+        .bind(height)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some((domain_id, name, txid, vout)) => {
+                let records = self
+                    .get_domain_records_at(domain_id, &txid, vout, height)
+                    .await?;
+
+                Ok(Some(ResolveResponse {
+                    name,
+                    txid: hex::encode(&txid),
+                    vout,
+                    txid_prefix: hex::encode(&txid[..8]),
+                    records,
+                    query: None,
+                    matched_by: None,
                 }))
             }
             None => Ok(None),
@@ -218,6 +397,8 @@ impl Database {
                     vout,
                     txid_prefix: prefix_hex.to_string(),
                     records,
+                    query: None,
+                    matched_by: None,
                 }))
             }
             None => Ok(None),
@@ -232,12 +413,13 @@ impl Database {
             Vec<u8>,
             i32,
             Vec<u8>,
+            Option<String>,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
             chrono::DateTime<chrono::Utc>,
         )> = sqlx::query_as(
             r#"
-            SELECT id, name, txid, vout, owner_txid, block_height, created_at, updated_at
+            SELECT id, name, txid, vout, owner_txid, owner_address, block_height, created_at, updated_at
             FROM domains
             WHERE LOWER(name) = LOWER($1)
             "#,
@@ -259,10 +441,11 @@ impl Database {
                     vout: r.3,
                     txid_prefix,
                     owner_txid: hex::encode(&r.4),
-                    block_height: r.5,
+                    owner_address: r.5,
+                    block_height: r.6,
                     records,
-                    created_at: r.6,
-                    updated_at: r.7,
+                    created_at: r.7,
+                    updated_at: r.8,
                 }))
             }
             None => Ok(None),
@@ -270,79 +453,61 @@ impl Database {
     }
 
     /// List all domains with pagination
+    ///
+    /// `owner_script` narrows the list to domains whose ownership UTXO is
+    /// currently locked by that script.
     pub async fn list_domains(
         &self,
         page: i32,
         per_page: i32,
         search: Option<&str>,
+        owner_script: Option<&[u8]>,
+        snapshot_id: Option<&str>,
     ) -> Result<(Vec<DomainListItem>, i64)> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
         let offset = (page - 1) * per_page;
 
-        let (rows, total): (Vec<_>, i64) = if let Some(q) = search {
-            let rows: Vec<(
-                i32,
-                String,
-                Vec<u8>,
-                i64,
-                Option<i32>,
-                chrono::DateTime<chrono::Utc>,
-            )> = sqlx::query_as(
-                r#"
-                    SELECT d.id, d.name, d.txid, 
-                           COUNT(r.id) FILTER (WHERE r.is_active = TRUE) as record_count,
-                           d.block_height, d.created_at
-                    FROM domains d
-                    LEFT JOIN dns_records r ON r.domain_id = d.id
-                    WHERE d.name ILIKE '%' || $1 || '%'
-                    GROUP BY d.id
-                    ORDER BY d.created_at DESC
-                    LIMIT $2 OFFSET $3
-                    "#,
-            )
-            .bind(q)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let total: (i64,) =
-                sqlx::query_as("SELECT COUNT(*) FROM domains WHERE name ILIKE '%' || $1 || '%'")
-                    .bind(q)
-                    .fetch_one(&self.pool)
-                    .await?;
-
-            (rows, total.0)
-        } else {
-            let rows: Vec<(
-                i32,
-                String,
-                Vec<u8>,
-                i64,
-                Option<i32>,
-                chrono::DateTime<chrono::Utc>,
-            )> = sqlx::query_as(
-                r#"
-                    SELECT d.id, d.name, d.txid,
-                           COUNT(r.id) FILTER (WHERE r.is_active = TRUE) as record_count,
-                           d.block_height, d.created_at
-                    FROM domains d
-                    LEFT JOIN dns_records r ON r.domain_id = d.id
-                    GROUP BY d.id
-                    ORDER BY d.created_at DESC
-                    LIMIT $1 OFFSET $2
-                    "#,
-            )
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM domains")
-                .fetch_one(&self.pool)
-                .await?;
+        let rows: Vec<(
+            i32,
+            String,
+            Vec<u8>,
+            Vec<u8>,
+            i32,
+            i64,
+            Option<i32>,
+            chrono::DateTime<chrono::Utc>,
+        )> = sqlx::query_as(
+            r#"
+                SELECT d.id, d.name, d.txid, d.owner_txid, d.owner_vout,
+                       COUNT(r.id) FILTER (WHERE r.is_active = TRUE) as record_count,
+                       d.block_height, d.created_at
+                FROM domains d
+                LEFT JOIN dns_records r ON r.domain_id = d.id
+                WHERE ($1::text IS NULL OR d.name ILIKE '%' || $1 || '%')
+                  AND ($2::bytea IS NULL OR d.owner_script = $2)
+                GROUP BY d.id
+                ORDER BY d.created_at DESC
+                LIMIT $3 OFFSET $4
+                "#,
+        )
+        .bind(search)
+        .bind(owner_script)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&mut *tx)
+        .await?;
 
-            (rows, total.0)
-        };
+        let total: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM domains d
+            WHERE ($1::text IS NULL OR d.name ILIKE '%' || $1 || '%')
+              AND ($2::bytea IS NULL OR d.owner_script = $2)
+            "#,
+        )
+        .bind(search)
+        .bind(owner_script)
+        .fetch_one(&mut *tx)
+        .await?;
 
         let items = rows
             .into_iter()
@@ -355,14 +520,16 @@ impl Database {
                     name: r.1,
                     txid: txid_hex,
                     txid_prefix,
-                    record_count: r.3,
-                    block_height: r.4,
-                    created_at: r.5,
+                    owner_txid: hex::encode(&r.3),
+                    owner_vout: r.4,
+                    record_count: r.5,
+                    block_height: r.6,
+                    created_at: r.7,
                 }
             })
             .collect();
 
-        Ok((items, total))
+        Ok((items, total.0))
     }
 
     /// Get domain history
@@ -473,12 +640,14 @@ impl Database {
             i32,
             String,
             Vec<u8>,
+            Vec<u8>,
+            i32,
             i64,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
         )> = sqlx::query_as(
             r#"
-                SELECT d.id, d.name, d.txid, 
+                SELECT d.id, d.name, d.txid, d.owner_txid, d.owner_vout,
                        COUNT(r.id) FILTER (WHERE r.is_active = TRUE) as record_count,
                        d.block_height, d.created_at
                 FROM domains d
@@ -502,9 +671,11 @@ impl Database {
                     name: r.1,
                     txid: txid_hex,
                     txid_prefix,
-                    record_count: r.3,
-                    block_height: r.4,
-                    created_at: r.5,
+                    owner_txid: hex::encode(&r.3),
+                    owner_vout: r.4,
+                    record_count: r.5,
+                    block_height: r.6,
+                    created_at: r.7,
                 }
             })
             .collect();
@@ -132,6 +132,25 @@ impl Database {
         Ok(rows)
     }
 
+    /// Get the identities published under any of the given record names
+    pub async fn get_identities_by_record_names(
+        &self,
+        record_names: &[String],
+    ) -> Result<Vec<DomainIdentityRow>> {
+        let rows = sqlx::query_as::<_, DomainIdentityRow>(
+            r#"
+            SELECT id, domain_id, identity_type, public_key, subdomain, record_name, published_at
+            FROM domain_identities
+            WHERE record_name = ANY($1)
+            "#,
+        )
+        .bind(record_names)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
     /// Delete a domain identity and its corresponding TXT record
     pub async fn delete_domain_identity(
         &self,
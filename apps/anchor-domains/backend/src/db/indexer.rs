@@ -35,6 +35,12 @@ impl Database {
             .execute(&self.pool)
             .await?;
 
+        // Remove history entries from reorged blocks
+        sqlx::query("DELETE FROM domain_history WHERE block_height >= $1")
+            .bind(from_height)
+            .execute(&self.pool)
+            .await?;
+
         // Remove domains from reorged blocks
         let result = sqlx::query("DELETE FROM domains WHERE block_height >= $1")
             .bind(from_height)
@@ -5,12 +5,16 @@
 //! - `records`: DNS record operations
 //! - `pending`: Pending transaction management
 //! - `indexer`: Indexer state management
+//! - `webhooks`: Webhook subscriptions and deliveries
 
 mod domains;
 mod identities;
 mod indexer;
 mod pending;
 mod records;
+mod webhooks;
+
+pub use webhooks::WebhookRow;
 
 use anyhow::Result;
 use sqlx::postgres::PgPool;
@@ -57,4 +57,67 @@ impl Database {
             })
             .collect())
     }
+
+    /// Get the records a domain carried at a block height
+    ///
+    /// Records written by the domain's `txid:vout` are the set that was
+    /// current until the next update; later identity records written under
+    /// the same outpoint are excluded by height.
+    pub async fn get_domain_records_at(
+        &self,
+        domain_id: i32,
+        txid: &[u8],
+        vout: i32,
+        height: i32,
+    ) -> Result<Vec<DnsRecordResponse>> {
+        let rows: Vec<(
+            i32,
+            i16,
+            Option<String>,
+            i32,
+            String,
+            Option<i32>,
+            Option<i32>,
+            Option<i32>,
+            Vec<u8>,
+            Option<i32>,
+            chrono::DateTime<chrono::Utc>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT id, record_type, record_name, ttl, value, priority, weight, port, txid, block_height, created_at
+            FROM dns_records
+            WHERE domain_id = $1 AND txid = $2 AND vout = $3 AND block_height <= $4
+            ORDER BY record_type, created_at
+            "#,
+        )
+        .bind(domain_id)
+        .bind(txid)
+        .bind(vout)
+        .bind(height)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let record_type = RecordType::try_from(r.1 as u8)
+                    .map(|rt| rt.name().to_string())
+                    .unwrap_or_else(|_| format!("TYPE{}", r.1));
+
+                DnsRecordResponse {
+                    id: r.0,
+                    record_type,
+                    name: r.2,
+                    ttl: r.3,
+                    value: r.4,
+                    priority: r.5,
+                    weight: r.6,
+                    port: r.7,
+                    txid: hex::encode(&r.8),
+                    block_height: r.9,
+                    created_at: r.10,
+                }
+            })
+            .collect())
+    }
 }
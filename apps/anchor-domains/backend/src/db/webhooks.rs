@@ -0,0 +1,126 @@
+//! Domain webhook database operations
+
+use anchor_webhooks::WebhookQueue;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+use super::Database;
+use crate::models::DnsRecord;
+
+/// Webhook record from the database
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookRow {
+    pub id: i32,
+    pub domain: String,
+    pub url: String,
+    pub secret: String,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Database {
+    /// Register a webhook for updates to a domain
+    pub async fn create_webhook(
+        &self,
+        domain: &str,
+        url: &str,
+        secret: &str,
+    ) -> Result<WebhookRow> {
+        let row = sqlx::query_as::<_, WebhookRow>(
+            r#"
+            INSERT INTO domain_webhooks (domain, url, secret)
+            VALUES ($1, $2, $3)
+            RETURNING id, domain, url, secret, active, created_at
+            "#,
+        )
+        .bind(domain)
+        .bind(url)
+        .bind(secret)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Get a webhook by ID
+    pub async fn get_webhook(&self, id: i32) -> Result<Option<WebhookRow>> {
+        let row = sqlx::query_as::<_, WebhookRow>(
+            "SELECT id, domain, url, secret, active, created_at FROM domain_webhooks WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// List webhooks, optionally only those for one domain
+    pub async fn list_webhooks(&self, domain: Option<&str>) -> Result<Vec<WebhookRow>> {
+        let rows = sqlx::query_as::<_, WebhookRow>(
+            r#"
+            SELECT id, domain, url, secret, active, created_at
+            FROM domain_webhooks
+            WHERE $1::text IS NULL OR LOWER(domain) = LOWER($1)
+            ORDER BY id
+            "#,
+        )
+        .bind(domain)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Delete a webhook and its delivery log
+    pub async fn delete_webhook(&self, id: i32) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM domain_webhooks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Queue a delivery to every webhook watching the domain
+    #[allow(clippy::too_many_arguments)]
+    pub async fn enqueue_domain_webhooks(
+        &self,
+        name: &str,
+        event: &str,
+        txid: &[u8],
+        vout: i32,
+        records: &[DnsRecord],
+        block_height: Option<i32>,
+    ) -> Result<u64> {
+        let payload = serde_json::json!({
+            "name": name,
+            "txid": hex::encode(txid),
+            "vout": vout,
+            "block_height": block_height,
+            "records": records,
+        });
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO domain_webhook_deliveries (webhook_id, txid, vout, event, payload)
+            SELECT id, $2, $3, $4, $5
+            FROM domain_webhooks
+            WHERE active AND LOWER(domain) = LOWER($1)
+            "#,
+        )
+        .bind(name)
+        .bind(hex::encode(txid))
+        .bind(vout)
+        .bind(event)
+        .bind(payload)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delivery queue of the domain webhooks
+    pub fn webhook_queue(&self) -> WebhookQueue {
+        WebhookQueue::new(
+            self.pool.clone(),
+            "domain_webhooks",
+            "domain_webhook_deliveries",
+        )
+    }
+}
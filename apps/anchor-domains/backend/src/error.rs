@@ -2,22 +2,12 @@
 //!
 //! This module provides a unified error type that implements `IntoResponse`,
 //! eliminating the need for `Result<impl IntoResponse, (StatusCode, String)>` in handlers.
+//! Responses use the shared `anchor_api_error` envelope.
 
-use axum::{
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    Json,
-};
-use serde::Serialize;
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::response::{IntoResponse, Response};
 use tracing::error;
 
-/// API error response body
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub code: u16,
-}
-
 /// Application-wide error type
 #[derive(Debug)]
 pub enum AppError {
@@ -29,6 +19,8 @@ pub enum AppError {
     Internal(String),
     /// 502 Bad Gateway - Error communicating with wallet service
     WalletError(String),
+    /// Error with a specific code, e.g. `DOMAIN_TAKEN` or one relayed from the wallet
+    Api(ApiError),
 }
 
 impl AppError {
@@ -46,6 +38,11 @@ impl AppError {
     pub fn wallet_error(msg: impl Into<String>) -> Self {
         Self::WalletError(msg.into())
     }
+
+    /// Create a DOMAIN_TAKEN error
+    pub fn domain_taken(msg: impl Into<String>) -> Self {
+        Self::Api(ApiError::new(ErrorCode::DomainTaken, msg))
+    }
 }
 
 impl std::fmt::Display for AppError {
@@ -55,6 +52,7 @@ impl std::fmt::Display for AppError {
             Self::NotFound(msg) => write!(f, "Not Found: {}", msg),
             Self::Internal(msg) => write!(f, "Internal Error: {}", msg),
             Self::WalletError(msg) => write!(f, "Wallet Error: {}", msg),
+            Self::Api(err) => write!(f, "{}", err),
         }
     }
 }
@@ -63,30 +61,32 @@ impl std::error::Error for AppError {}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
+        let error = match self {
+            Self::BadRequest(msg) => ApiError::bad_request(msg),
+            Self::NotFound(msg) => ApiError::not_found(msg),
             Self::Internal(msg) => {
                 error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
+                ApiError::internal(msg)
             }
             Self::WalletError(msg) => {
                 error!("Wallet service error: {}", msg);
-                (StatusCode::BAD_GATEWAY, msg.clone())
+                ApiError::new(ErrorCode::UpstreamError, msg)
             }
+            Self::Api(err) => err,
         };
 
-        let body = Json(ErrorResponse {
-            error: message,
-            code: status.as_u16(),
-        });
-
-        (status, body).into_response()
+        error.into_response()
     }
 }
 
 // Convenient conversions from common error types
 
+impl From<ApiError> for AppError {
+    fn from(err: ApiError) -> Self {
+        Self::Api(err)
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         Self::Internal(err.to_string())
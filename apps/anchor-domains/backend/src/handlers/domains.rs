@@ -1,5 +1,6 @@
 //! Domain handlers: listing, details, history, availability
 
+use anchor_api_snapshot::SnapshotParams;
 use axum::{
     extract::{Path, Query, State},
     Json,
@@ -11,7 +12,9 @@ use crate::models::{
     AvailabilityResponse, Domain, DomainListItem, GetDomainsByOwnerRequest, HistoryEntry,
     ListParams, MyDomainsQuery, MyDomainsResponse, PaginatedResponse,
 };
-use crate::services::validation::{parse_txid_list, parse_txids, validate_domain_name};
+use crate::services::validation::{
+    parse_owner_script, parse_txid_list, parse_txids, validate_domain_name,
+};
 use crate::AppState;
 
 /// List all domains
@@ -22,20 +25,37 @@ use crate::AppState;
     params(
         ("page" = Option<i32>, Query, description = "Page number (default: 1)"),
         ("per_page" = Option<i32>, Query, description = "Items per page (default: 50)"),
-        ("search" = Option<String>, Query, description = "Search query")
+        ("search" = Option<String>, Query, description = "Search query"),
+        ("owned_by" = Option<String>, Query, description = "Only domains currently owned by this address or hex script"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
         (status = 200, description = "List of domains", body = PaginatedResponse<DomainListItem>),
+        (status = 400, description = "Invalid owned_by"),
+        (status = 404, description = "Snapshot not found or expired"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn list_domains(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
+    Query(snapshot): Query<SnapshotParams>,
 ) -> AppResult<Json<PaginatedResponse<DomainListItem>>> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
+    let owner_script = params
+        .owned_by
+        .as_deref()
+        .map(parse_owner_script)
+        .transpose()?;
     let (domains, total) = state
         .db
-        .list_domains(params.page, params.per_page, params.search.as_deref())
+        .list_domains(
+            params.page,
+            params.per_page,
+            params.search.as_deref(),
+            owner_script.as_deref(),
+            snapshot_id.as_deref(),
+        )
         .await?;
 
     Ok(Json(PaginatedResponse::new(
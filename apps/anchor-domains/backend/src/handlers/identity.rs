@@ -5,6 +5,7 @@
 //!
 //! Allows publishing Nostr (npub) and Pubky (pk:) identities to domain DNS TXT records.
 
+use anchor_api_error::ApiError;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -15,10 +16,11 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::ToSchema;
 
+use crate::services::identity_resolver::ResolvedIdentity;
 use crate::AppState;
 
 /// Identity type for DNS publishing
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum IdentityType {
     Nostr,
@@ -87,7 +89,7 @@ pub struct DomainIdentitiesResponse {
 
 /// Build the DNS record name according to Selfie Records spec
 /// Format: [subdomain.]user._[type].domain.tld
-fn build_record_name(
+pub(crate) fn build_record_name(
     domain: &str,
     identity_type: &IdentityType,
     subdomain: Option<&str>,
@@ -133,9 +135,9 @@ fn format_public_key(identity_type: &IdentityType, public_key: &str) -> String {
 /// List identities published to a domain
 #[utoipa::path(
     get,
-    path = "/api/domains/{domain}/identities",
+    path = "/domains/{name}/identities",
     params(
-        ("domain" = String, Path, description = "Domain name")
+        ("name" = String, Path, description = "Domain name")
     ),
     responses(
         (status = 200, description = "List of published identities", body = DomainIdentitiesResponse),
@@ -146,21 +148,21 @@ fn format_public_key(identity_type: &IdentityType, public_key: &str) -> String {
 pub async fn list_domain_identities(
     State(state): State<Arc<AppState>>,
     Path(domain): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Verify domain exists and get its ID
     let domain_data = state
         .db
         .get_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Domain not found".to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Domain not found"))?;
 
     // Query identities from database
     let identity_rows = state
         .db
         .get_domain_identities(domain_data.id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
 
     // Convert to response format
     let identities: Vec<PublishedIdentity> = identity_rows
@@ -184,9 +186,9 @@ pub async fn list_domain_identities(
 /// - Pubky: hello.user._pubky.domain.com TXT "pk:..."
 #[utoipa::path(
     post,
-    path = "/api/domains/{domain}/identities",
+    path = "/domains/{name}/identities",
     params(
-        ("domain" = String, Path, description = "Domain name")
+        ("name" = String, Path, description = "Domain name")
     ),
     request_body = PublishIdentityRequest,
     responses(
@@ -201,14 +203,14 @@ pub async fn publish_domain_identity(
     State(state): State<Arc<AppState>>,
     Path(domain): Path<String>,
     Json(req): Json<PublishIdentityRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Verify domain exists and get its ID
     let domain_data = state
         .db
         .get_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Domain not found".to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Domain not found"))?;
 
     // Build the DNS record
     let record_name = build_record_name(&domain, &req.identity_type, req.subdomain.as_deref());
@@ -226,7 +228,8 @@ pub async fn publish_domain_identity(
             &record_value,
         )
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    state.identities.invalidate_domain(&domain).await;
 
     Ok(Json(PublishIdentityResponse {
         success: true,
@@ -245,9 +248,9 @@ pub async fn publish_domain_identity(
 /// Remove an identity from a domain's DNS
 #[utoipa::path(
     delete,
-    path = "/api/domains/{domain}/identities/{identity_type}",
+    path = "/domains/{name}/identities/{identity_type}",
     params(
-        ("domain" = String, Path, description = "Domain name"),
+        ("name" = String, Path, description = "Domain name"),
         ("identity_type" = String, Path, description = "Identity type (nostr or pubky)")
     ),
     responses(
@@ -260,110 +263,116 @@ pub async fn publish_domain_identity(
 pub async fn remove_domain_identity(
     State(state): State<Arc<AppState>>,
     Path((domain, identity_type)): Path<(String, String)>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Verify domain exists and get its ID
     let domain_data = state
         .db
         .get_domain(&domain)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Domain not found".to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Domain not found"))?;
 
-    let identity_type_str = match identity_type.as_str() {
-        "nostr" => "nostr",
-        "pubky" => "pubky",
-        _ => return Err((StatusCode::BAD_REQUEST, "Invalid identity type".to_string())),
-    };
+    let identity_type_str = parse_identity_type(&identity_type)?.as_str();
 
     // Delete from database
     let deleted = state
         .db
         .delete_domain_identity(domain_data.id, identity_type_str, None)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
 
     if deleted {
+        state.identities.invalidate_domain(&domain).await;
         Ok(StatusCode::NO_CONTENT)
     } else {
-        Err((StatusCode::NOT_FOUND, "Identity not found".to_string()))
+        Err(ApiError::not_found("Identity not found"))
     }
 }
 
-/// Resolve an identity from DNS
+/// Resolve an identity
 ///
-/// Queries DNS for identity records according to Selfie Records spec.
+/// Looks up the key published under the address's Selfie Records name,
+/// falling back to DNS-over-HTTPS for hybrid (non-Anchor TLD) domains.
+/// Results are cached for a short TTL.
 #[utoipa::path(
     get,
-    path = "/api/identities/resolve",
+    path = "/identities/resolve",
     params(
         ("address" = String, Query, description = "Address in format user@domain.com or subdomain.user@domain.com"),
         ("type" = String, Query, description = "Identity type (nostr or pubky)")
     ),
     responses(
-        (status = 200, description = "Identity resolved"),
-        (status = 404, description = "Identity not found")
+        (status = 200, description = "Resolution result", body = ResolvedIdentity),
+        (status = 400, description = "Invalid identity type or address")
     ),
     tag = "Identity"
 )]
 pub async fn resolve_identity(
     State(state): State<Arc<AppState>>,
     axum::extract::Query(params): axum::extract::Query<ResolveIdentityParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let identity_type = match params.identity_type.as_str() {
-        "nostr" => IdentityType::Nostr,
-        "pubky" => IdentityType::Pubky,
-        _ => return Err((StatusCode::BAD_REQUEST, "Invalid identity type".to_string())),
-    };
-
-    // Parse address format: [subdomain.]user@domain.com
-    let parts: Vec<&str> = params.address.split('@').collect();
-    if parts.len() != 2 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Invalid address format. Use user@domain.com".to_string(),
-        ));
+) -> Result<impl IntoResponse, ApiError> {
+    let identity_type = parse_identity_type(&params.identity_type)?;
+
+    let resolved = state
+        .identities
+        .resolve(&params.address, identity_type)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    if let Some(error) = resolved.error {
+        return Err(ApiError::bad_request(error));
     }
 
-    let user_part = parts[0];
-    let domain = parts[1];
+    Ok(Json(resolved))
+}
 
-    // Check if there's a subdomain in user part
-    let subdomain = if user_part.contains('.') {
-        let user_parts: Vec<&str> = user_part.split('.').collect();
-        Some(user_parts[0].to_string())
-    } else {
-        None
-    };
-
-    let record_name = build_record_name(domain, &identity_type, subdomain.as_deref());
-
-    // Try to find in database first
-    if let Ok(Some(domain_data)) = state.db.get_domain(domain).await {
-        if let Ok(identities) = state.db.get_domain_identities(domain_data.id).await {
-            for id in identities {
-                if id.identity_type == identity_type.as_str() && id.subdomain == subdomain {
-                    return Ok(Json(serde_json::json!({
-                        "address": params.address,
-                        "identity_type": identity_type.as_str(),
-                        "record_name": record_name,
-                        "public_key": id.public_key,
-                        "resolved": true,
-                        "source": "database"
-                    })));
-                }
-            }
-        }
+/// Resolve many identities at once
+///
+/// Resolves up to 500 addresses in one request, for frontends that need to
+/// show keys for every author on a page. Malformed addresses are reported
+/// per entry rather than failing the whole batch.
+#[utoipa::path(
+    post,
+    path = "/identities/resolve-batch",
+    request_body = ResolveBatchRequest,
+    responses(
+        (status = 200, description = "Resolution results in request order", body = ResolveBatchResponse),
+        (status = 400, description = "Invalid identity type or too many addresses")
+    ),
+    tag = "Identity"
+)]
+pub async fn resolve_identities_batch(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ResolveBatchRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if req.identities.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::bad_request(format!(
+            "At most {} identities per batch",
+            MAX_BATCH_SIZE
+        )));
     }
 
-    // Not found in database
-    Ok(Json(serde_json::json!({
-        "address": params.address,
-        "identity_type": identity_type.as_str(),
-        "record_name": record_name,
-        "public_key": null,
-        "resolved": false,
-        "message": format!("Identity not found. Try: dig @1.1.1.1 txt {}", record_name)
-    })))
+    let queries = req
+        .identities
+        .into_iter()
+        .map(|query| Ok((query.address, parse_identity_type(&query.identity_type)?)))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    let results = state
+        .identities
+        .resolve_batch(&queries)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(Json(ResolveBatchResponse { results }))
+}
+
+fn parse_identity_type(value: &str) -> Result<IdentityType, ApiError> {
+    match value {
+        "nostr" => Ok(IdentityType::Nostr),
+        "pubky" => Ok(IdentityType::Pubky),
+        _ => Err(ApiError::bad_request("Invalid identity type")),
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -372,3 +381,28 @@ pub struct ResolveIdentityParams {
     #[serde(rename = "type")]
     pub identity_type: String,
 }
+
+/// Maximum addresses per batch resolution request
+const MAX_BATCH_SIZE: usize = 500;
+
+/// One address to resolve
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IdentityQuery {
+    /// Address in format user@domain.com or subdomain.user@domain.com
+    pub address: String,
+    /// Identity type (nostr or pubky)
+    #[serde(rename = "type")]
+    pub identity_type: String,
+}
+
+/// Request to resolve several identities
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveBatchRequest {
+    pub identities: Vec<IdentityQuery>,
+}
+
+/// Batch resolution results, in request order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ResolveBatchResponse {
+    pub results: Vec<ResolvedIdentity>,
+}
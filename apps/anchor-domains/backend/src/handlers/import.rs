@@ -0,0 +1,166 @@
+//! Import handlers: bulk registration of names from ENS and SNS exports
+
+use axum::{extract::State, Json};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::error::{AppError, AppResult};
+use crate::models::{DnsOperation, ImportRequest, ImportResponse, ImportResult, SUPPORTED_TLDS};
+use crate::services::import::map_name;
+use crate::services::validation::validate_records;
+use crate::services::wallet::{CreateDnsParams, WalletClient};
+use crate::AppState;
+
+/// Names accepted per import request
+const MAX_IMPORT_NAMES: usize = 100;
+
+/// Import names from an ENS or SNS export
+///
+/// Each name is mapped to an Anchor Domains name under `tld` and its
+/// supported records to DNS records; a registration transaction is then
+/// created for each available name through the wallet service. Names are
+/// processed in order and one failure does not stop the rest. Use
+/// `dry_run` to review the mapping first.
+#[utoipa::path(
+    post,
+    path = "/import",
+    tag = "Registration",
+    request_body = ImportRequest,
+    responses(
+        (status = 200, description = "Per-name import results", body = ImportResponse),
+        (status = 400, description = "Invalid request")
+    )
+)]
+pub async fn import_names(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ImportRequest>,
+) -> AppResult<Json<ImportResponse>> {
+    if req.names.is_empty() {
+        return Err(AppError::bad_request("No names to import"));
+    }
+    if req.names.len() > MAX_IMPORT_NAMES {
+        return Err(AppError::bad_request(format!(
+            "At most {} names per import",
+            MAX_IMPORT_NAMES
+        )));
+    }
+    let tld = req.tld.as_deref().unwrap_or(".btc");
+    if !SUPPORTED_TLDS.contains(&tld) {
+        return Err(AppError::bad_request(format!(
+            "Unsupported TLD. Supported TLDs: {}",
+            SUPPORTED_TLDS.join(", ")
+        )));
+    }
+
+    let wallet = WalletClient::new(&state.config.wallet_url);
+    let mut results = Vec::with_capacity(req.names.len());
+    let mut created = 0;
+    let mut seen = HashSet::new();
+
+    for entry in &req.names {
+        let mut result = ImportResult {
+            source_name: entry.name.clone(),
+            name: String::new(),
+            status: "invalid".to_string(),
+            records: Vec::new(),
+            skipped: Vec::new(),
+            txid: None,
+            error: None,
+        };
+
+        let mapped = match map_name(req.source, entry, tld) {
+            Ok(mapped) => mapped,
+            Err(e) => {
+                result.error = Some(e);
+                results.push(result);
+                continue;
+            }
+        };
+        result.name = mapped.name;
+        result.records = mapped.records;
+        result.skipped = mapped.skipped;
+
+        let records = match validate_records(&result.records) {
+            Ok(records) => records,
+            Err(e) => {
+                result.error = Some(e.to_string());
+                results.push(result);
+                continue;
+            }
+        };
+
+        // Two source names can map to the same Anchor name (alice.eth and
+        // Alice.eth); only the first is registered
+        if !seen.insert(result.name.clone()) {
+            result.status = "duplicate".to_string();
+            results.push(result);
+            continue;
+        }
+
+        if !state.db.is_domain_available(&result.name).await? {
+            result.status = "taken".to_string();
+            results.push(result);
+            continue;
+        }
+
+        if req.dry_run {
+            result.status = "planned".to_string();
+            results.push(result);
+            continue;
+        }
+
+        match wallet
+            .create_dns_message(CreateDnsParams {
+                operation: DnsOperation::Register,
+                name: result.name.clone(),
+                records,
+                carrier: req.carrier,
+                owner_anchor: None,
+            })
+            .await
+        {
+            Ok(response) => {
+                if let Ok(txid_bytes) = hex::decode(&response.txid) {
+                    if let Err(e) = state
+                        .db
+                        .create_pending_transaction(
+                            &txid_bytes,
+                            &result.name,
+                            1, // register operation
+                            Some(&result.records),
+                            Some(req.carrier.unwrap_or(1) as i16),
+                        )
+                        .await
+                    {
+                        warn!("Failed to save pending transaction: {}", e);
+                    }
+                }
+                created += 1;
+                result.status = "created".to_string();
+                result.txid = Some(response.txid);
+            }
+            Err(e) => {
+                warn!("Import of {} failed: {}", result.name, e);
+                result.status = "failed".to_string();
+                result.error = Some(e.to_string());
+            }
+        }
+        results.push(result);
+    }
+
+    info!(
+        "Imported {} of {} names from {:?} (dry run: {})",
+        created,
+        results.len(),
+        req.source,
+        req.dry_run
+    );
+
+    Ok(Json(ImportResponse {
+        source: req.source,
+        dry_run: req.dry_run,
+        created,
+        results,
+    }))
+}
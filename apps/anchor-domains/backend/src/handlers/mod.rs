@@ -5,20 +5,26 @@
 //! - `resolution`: Domain name resolution
 //! - `domains`: Domain listing and details
 //! - `registration`: Domain registration and updates
+//! - `import`: Bulk registration from ENS and SNS exports
 //! - `pending`: Pending transaction management
 //! - `identity`: DNS-based identity publishing (Selfie Records)
+//! - `webhooks`: Domain update webhooks
 
 pub mod domains;
 pub mod identity;
+pub mod import;
 pub mod pending;
 pub mod registration;
 pub mod resolution;
 pub mod system;
+pub mod webhooks;
 
 // Re-export all handlers for easy access
 pub use domains::*;
 pub use identity::*;
+pub use import::*;
 pub use pending::*;
 pub use registration::*;
 pub use resolution::*;
 pub use system::*;
+pub use webhooks::*;
@@ -35,7 +35,7 @@ pub async fn register_domain(
     // Check availability
     let is_available = state.db.is_domain_available(&req.name).await?;
     if !is_available {
-        return Err(AppError::bad_request("Domain is already registered"));
+        return Err(AppError::domain_taken("Domain is already registered"));
     }
 
     // Convert and validate records
@@ -1,34 +1,45 @@
 //! Resolution handlers: domain name lookup
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use std::sync::Arc;
 
 use crate::error::{AppError, AppResult};
-use crate::models::{is_txid_prefix, ResolveResponse, SUPPORTED_TLDS};
+use crate::models::{is_txid_prefix, AtHeightParams, ResolveResponse, SUPPORTED_TLDS};
+use crate::services::resolution::{Candidates, NameMatch};
 use crate::services::validation::{validate_domain_name, validate_txid_prefix};
 use crate::AppState;
 
 /// Resolve a domain by name
+///
+/// Names without a registration of their own fall back to named records
+/// under the closest registered ancestor, then to the nearest `*.parent`
+/// wildcard. Historical (`at_height`) lookups match exact names only.
 #[utoipa::path(
     get,
     path = "/resolve/{name}",
     tag = "Resolution",
     params(
-        ("name" = String, Path, description = "Domain name (e.g., mysite.btc, mysite.sat)")
+        ("name" = String, Path, description = "Domain name (e.g., mysite.btc, mysite.sat)"),
+        ("at_height" = Option<i32>, Query, description = "Resolve as of this block height")
     ),
     responses(
         (status = 200, description = "Domain records", body = ResolveResponse),
         (status = 404, description = "Domain not found"),
-        (status = 400, description = "Invalid domain name")
+        (status = 400, description = "Invalid domain name or height")
     )
 )]
 pub async fn resolve_domain(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
+    Query(params): Query<AtHeightParams>,
 ) -> AppResult<Json<ResolveResponse>> {
+    if let Some(height) = params.at_height {
+        return resolve_domain_at(&state, &name, height).await.map(Json);
+    }
+
     // Check if it's a txid prefix lookup (16 hex chars, optionally with TLD suffix)
     let clean_name = SUPPORTED_TLDS
         .iter()
@@ -49,15 +60,110 @@ pub async fn resolve_domain(
     // Validate domain name - must already include a supported TLD
     validate_domain_name(&name)?;
 
-    let response = state
-        .db
-        .resolve_by_name(&name)
+    let response = resolve_matching(&state, &name)
         .await?
         .ok_or_else(|| AppError::not_found("Domain not found"))?;
 
     Ok(Json(response))
 }
 
+/// Resolve a name through exact, record name and wildcard matches
+async fn resolve_matching(state: &AppState, name: &str) -> AppResult<Option<ResolveResponse>> {
+    let candidates =
+        Candidates::new(name).ok_or_else(|| AppError::bad_request("Unsupported TLD"))?;
+
+    let registered = state
+        .db
+        .registered_names(&candidates.domain_names())
+        .await?;
+    let ancestors: Vec<String> = candidates
+        .ancestors
+        .iter()
+        .filter(|a| registered.contains(&a.name))
+        .map(|a| a.name.clone())
+        .collect();
+    let named = if ancestors.is_empty() {
+        Default::default()
+    } else {
+        state.db.named_records(&ancestors).await?
+    };
+
+    let matched = match candidates.select(&registered, &named) {
+        Some(matched) => matched,
+        None => return Ok(None),
+    };
+
+    let response = match &matched {
+        NameMatch::Exact(domain) | NameMatch::Wildcard(domain) => {
+            state.db.resolve_by_name(domain).await?
+        }
+        NameMatch::Record {
+            domain,
+            record_name,
+        } => state.db.resolve_by_name(domain).await?.map(|mut r| {
+            r.records.retain(|record| {
+                record
+                    .name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(record_name))
+            });
+            r
+        }),
+    };
+
+    Ok(response.map(|mut r| {
+        if let Some(kind) = matched.kind() {
+            r.query = Some(candidates.query.clone());
+            r.matched_by = Some(kind.to_string());
+        }
+        r
+    }))
+}
+
+/// Resolve a name or txid prefix as of a block height
+async fn resolve_domain_at(
+    state: &AppState,
+    name: &str,
+    height: i32,
+) -> AppResult<ResolveResponse> {
+    if height < 0 {
+        return Err(AppError::bad_request("at_height must not be negative"));
+    }
+    let indexed = state.db.get_last_block_height().await?;
+    if height > indexed {
+        return Err(AppError::bad_request(format!(
+            "Height {} is not indexed yet (last indexed block is {})",
+            height, indexed
+        )));
+    }
+
+    let clean_name = SUPPORTED_TLDS
+        .iter()
+        .find(|tld| name.ends_with(*tld))
+        .map(|tld| &name[..name.len() - tld.len()])
+        .unwrap_or(name);
+
+    // A txid prefix names the domain's current outpoint; look it up and
+    // resolve the domain it belongs to.
+    let name = if is_txid_prefix(clean_name) {
+        state
+            .db
+            .resolve_by_txid_prefix(clean_name)
+            .await?
+            .ok_or_else(|| AppError::not_found("Domain not found"))?
+            .name
+    } else {
+        validate_domain_name(name)?;
+        name.to_string()
+    };
+
+    state
+        .db
+        .resolve_by_name_at(&name, height)
+        .await?
+        .ok_or_else(|| AppError::not_found("Domain not registered at that height"))
+}
+
 /// Resolve a domain by txid prefix
 #[utoipa::path(
     get,
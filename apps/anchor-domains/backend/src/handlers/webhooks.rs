@@ -0,0 +1,208 @@
+//! Domain webhook handlers
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::db::WebhookRow;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    CreateWebhookRequest, ReplayWebhookRequest, ReplayWebhookResponse, WebhookDeliveryResponse,
+    WebhookResponse,
+};
+use crate::services::validation::validate_domain_name;
+use crate::AppState;
+
+/// Query parameters for listing webhooks
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WebhookListParams {
+    /// Only webhooks watching this domain
+    pub domain: Option<String>,
+}
+
+/// Query parameters for listing deliveries
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeliveryListParams {
+    /// Deliveries to return (default: 50, max: 500)
+    pub limit: Option<i64>,
+}
+
+fn webhook_response(row: WebhookRow, with_secret: bool) -> WebhookResponse {
+    WebhookResponse {
+        id: row.id,
+        domain: row.domain,
+        url: row.url,
+        active: row.active,
+        secret: with_secret.then_some(row.secret),
+        created_at: row.created_at,
+    }
+}
+
+/// Register a webhook for updates to a domain
+///
+/// The domain's registration and every accepted update are POSTed to
+/// `url`, signed with the returned secret. The domain does not need to be
+/// registered yet.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "Webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered", body = WebhookResponse),
+        (status = 400, description = "Invalid domain name or URL")
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateWebhookRequest>,
+) -> AppResult<Json<WebhookResponse>> {
+    validate_domain_name(&req.domain)?;
+    anchor_webhooks::validate_url(&req.url).map_err(AppError::bad_request)?;
+    let secret = match req.secret {
+        Some(secret) if secret.is_empty() => {
+            return Err(AppError::bad_request("Secret must not be empty"))
+        }
+        Some(secret) => secret,
+        None => anchor_webhooks::generate_secret(),
+    };
+
+    let row = state
+        .db
+        .create_webhook(&req.domain.to_lowercase(), &req.url, &secret)
+        .await?;
+
+    Ok(Json(webhook_response(row, true)))
+}
+
+/// List webhooks
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "Webhooks",
+    params(
+        ("domain" = Option<String>, Query, description = "Only webhooks watching this domain")
+    ),
+    responses(
+        (status = 200, description = "Registered webhooks", body = Vec<WebhookResponse>)
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<WebhookListParams>,
+) -> AppResult<Json<Vec<WebhookResponse>>> {
+    let rows = state.db.list_webhooks(params.domain.as_deref()).await?;
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| webhook_response(row, false))
+            .collect(),
+    ))
+}
+
+/// Delete a webhook
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "Webhooks",
+    params(
+        ("id" = i32, Path, description = "Webhook ID")
+    ),
+    responses(
+        (status = 200, description = "Webhook deleted"),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_webhook(id).await? {
+        return Err(AppError::not_found(format!("Webhook {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+/// Get a webhook's recent deliveries, newest first
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "Webhooks",
+    params(
+        ("id" = i32, Path, description = "Webhook ID"),
+        ("limit" = Option<i64>, Query, description = "Deliveries to return (default: 50, max: 500)")
+    ),
+    responses(
+        (status = 200, description = "Deliveries", body = Vec<WebhookDeliveryResponse>),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn get_webhook_deliveries(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Query(params): Query<DeliveryListParams>,
+) -> AppResult<Json<Vec<WebhookDeliveryResponse>>> {
+    if state.db.get_webhook(id).await?.is_none() {
+        return Err(AppError::not_found(format!("Webhook {} not found", id)));
+    }
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let rows = state.db.webhook_queue().deliveries(id, limit).await?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| WebhookDeliveryResponse {
+                id: row.id,
+                event: row.event,
+                status: row.status,
+                attempts: row.attempts,
+                response_status: row.response_status,
+                last_error: row.last_error,
+                payload: row.payload.0,
+                created_at: row.created_at,
+                delivered_at: row.delivered_at,
+            })
+            .collect(),
+    ))
+}
+
+/// Send past deliveries again
+///
+/// Give `delivery_id` to replay one delivery or `since` to replay
+/// everything from that time on. Replays keep their delivery IDs.
+#[utoipa::path(
+    post,
+    path = "/webhooks/{id}/replay",
+    tag = "Webhooks",
+    params(
+        ("id" = i32, Path, description = "Webhook ID")
+    ),
+    request_body = ReplayWebhookRequest,
+    responses(
+        (status = 200, description = "Deliveries queued", body = ReplayWebhookResponse),
+        (status = 400, description = "Neither delivery_id nor since given"),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn replay_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Json(req): Json<ReplayWebhookRequest>,
+) -> AppResult<Json<ReplayWebhookResponse>> {
+    if req.delivery_id.is_none() && req.since.is_none() {
+        return Err(AppError::bad_request("Give delivery_id or since"));
+    }
+    if state.db.get_webhook(id).await?.is_none() {
+        return Err(AppError::not_found(format!("Webhook {} not found", id)));
+    }
+
+    let queued = state
+        .db
+        .webhook_queue()
+        .replay(id, req.delivery_id, req.since)
+        .await?;
+
+    Ok(Json(ReplayWebhookResponse { queued }))
+}
@@ -4,8 +4,9 @@
 use anyhow::{Context, Result};
 use bitcoin::consensus::encode::deserialize;
 use bitcoin::hashes::Hash;
-use bitcoin::{Block, Transaction};
+use bitcoin::{Block, OutPoint, Transaction, Txid};
 use bitcoincore_rpc::{Auth, Client, RpcApi};
+use std::collections::HashSet;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
@@ -15,6 +16,7 @@ use anchor_core::parse_transaction;
 
 use crate::config::Config;
 use crate::db::Database;
+use crate::models::OwnerOutput;
 
 // Use anchor-specs for DNS protocol types
 use anchor_specs::dns::{DnsOperation, DnsSpec};
@@ -142,13 +144,26 @@ impl Indexer {
         let block_bytes = hex::decode(&block_hex)?;
         let block: Block = deserialize(&block_bytes)?;
 
+        // Ownership UTXOs spent in this block, plus those created in it as
+        // the transactions are indexed, so spends within the block are seen
+        let spent: Vec<(Vec<u8>, i32)> = block
+            .txdata
+            .iter()
+            .flat_map(|tx| &tx.input)
+            .filter(|input| !input.previous_output.is_null())
+            .map(|input| outpoint_key(&input.previous_output))
+            .collect();
+        let mut owners: HashSet<(Vec<u8>, i32)> =
+            self.db.owner_outpoints(&spent).await?.into_iter().collect();
+
         let mut message_count = 0;
 
         for tx in &block.txdata {
             let count = self
-                .index_transaction(tx, Some(&block_hash_bytes), Some(height))
+                .index_transaction(tx, Some(&block_hash_bytes), Some(height), &mut owners)
                 .await?;
             message_count += count;
+            self.follow_owner_spends(tx, &mut owners).await?;
         }
 
         self.db.update_last_block(&block_hash_bytes, height).await?;
@@ -162,12 +177,10 @@ impl Indexer {
         tx: &Transaction,
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
+        owners: &mut HashSet<(Vec<u8>, i32)>,
     ) -> Result<u32> {
         let txid = tx.compute_txid();
-        // Convert txid to display format (big-endian) for storage
-        // Bitcoin internally uses little-endian, but display format is big-endian (reversed)
-        let mut txid_bytes = txid.to_byte_array().to_vec();
-        txid_bytes.reverse();
+        let txid_bytes = display_bytes(&txid);
 
         // Try multi-carrier detection first
         let detected = self.carrier_selector.detect(tx);
@@ -215,12 +228,17 @@ impl Indexer {
                         debug!("Domain {} already registered, skipping", payload.name);
                         continue;
                     }
+                    let Some(owner) = OwnerOutput::for_message(tx, vout) else {
+                        debug!("Register of {} has no spendable output", payload.name);
+                        continue;
+                    };
 
                     self.db
                         .register_domain(
                             &payload.name,
                             &txid_bytes,
                             vout as i32,
+                            &owner,
                             &payload.records,
                             block_hash,
                             block_height,
@@ -235,69 +253,195 @@ impl Indexer {
                         );
                     }
 
+                    self.notify_webhooks(
+                        &payload,
+                        "domain.registered",
+                        &txid_bytes,
+                        vout as i32,
+                        block_height,
+                    )
+                    .await;
+
+                    owners.insert((txid_bytes.clone(), owner.vout));
                     info!("Registered domain: {}", payload.name);
                     dns_count += 1;
                 }
-                DnsOperation::Update => {
-                    // Verify anchor points to owner
-                    if let Some(anchor) = message.anchors.first() {
-                        let owner = self.db.get_domain_owner(&payload.name).await?;
-
-                        if let Some((owner_txid, _owner_vout)) = owner {
-                            // Check if anchor matches owner txid prefix
-                            // Note: anchor.txid_prefix is in little-endian (internal Bitcoin format)
-                            // but owner_txid is stored in big-endian (display format)
-                            // We need to compare the reversed prefix with the END of owner_txid
-                            let mut prefix_reversed = anchor.txid_prefix;
-                            prefix_reversed.reverse();
-                            let owner_suffix = &owner_txid[owner_txid.len().saturating_sub(8)..];
-
-                            if owner_suffix == prefix_reversed {
-                                self.db
-                                    .update_domain(
-                                        &payload.name,
-                                        &txid_bytes,
-                                        vout as i32,
-                                        &payload.records,
-                                        block_hash,
-                                        block_height,
-                                    )
-                                    .await?;
-
-                                // Remove pending transaction now that it's confirmed
-                                if let Err(e) =
-                                    self.db.delete_pending_by_domain(&payload.name).await
-                                {
-                                    debug!(
-                                        "Failed to delete pending transaction for {}: {}",
-                                        payload.name, e
-                                    );
-                                }
-
-                                info!("Updated domain: {}", payload.name);
-                                dns_count += 1;
-                            } else {
-                                debug!(
-                                    "Update for {} rejected: anchor doesn't match owner. \
-                                    Anchor prefix (reversed): {:?}, Owner suffix: {:?}",
-                                    payload.name,
-                                    hex::encode(prefix_reversed),
-                                    hex::encode(owner_suffix)
-                                );
-                            }
-                        }
+                DnsOperation::Update | DnsOperation::Transfer => {
+                    if !self.anchors_owner(&payload.name, &message).await? {
+                        continue;
+                    }
+                    let Some(owner) = OwnerOutput::for_message(tx, vout) else {
+                        debug!(
+                            "{:?} of {} has no spendable output",
+                            payload.operation, payload.name
+                        );
+                        continue;
+                    };
+
+                    let event = if payload.operation == DnsOperation::Update {
+                        self.db
+                            .update_domain(
+                                &payload.name,
+                                &txid_bytes,
+                                vout as i32,
+                                &owner,
+                                &payload.records,
+                                block_hash,
+                                block_height,
+                            )
+                            .await?;
+                        "domain.updated"
                     } else {
-                        debug!("Update for {} rejected: no anchor", payload.name);
+                        self.db
+                            .transfer_domain(
+                                &payload.name,
+                                &txid_bytes,
+                                &owner,
+                                block_hash,
+                                block_height,
+                            )
+                            .await?;
+                        "domain.transferred"
+                    };
+
+                    // Remove pending transaction now that it's confirmed
+                    if let Err(e) = self.db.delete_pending_by_domain(&payload.name).await {
+                        debug!(
+                            "Failed to delete pending transaction for {}: {}",
+                            payload.name, e
+                        );
                     }
-                }
-                DnsOperation::Transfer => {
-                    // Transfer is similar to update but changes owner
-                    // For now, we don't change the owner, just update records
-                    debug!("Transfer operation not fully implemented yet");
+
+                    self.notify_webhooks(&payload, event, &txid_bytes, vout as i32, block_height)
+                        .await;
+
+                    owners.insert((txid_bytes.clone(), owner.vout));
+                    info!("{:?} domain: {}", payload.operation, payload.name);
+                    dns_count += 1;
                 }
             }
         }
 
         Ok(dns_count)
     }
+
+    /// Whether an Update or Transfer message anchors to the domain's owner
+    async fn anchors_owner(
+        &self,
+        name: &str,
+        message: &anchor_core::ParsedAnchorMessage,
+    ) -> Result<bool> {
+        let Some(anchor) = message.anchors.first() else {
+            debug!("Message for {} rejected: no anchor", name);
+            return Ok(false);
+        };
+        let Some((owner_txid, _owner_vout)) = self.db.get_domain_owner(name).await? else {
+            return Ok(false);
+        };
+
+        // Check if anchor matches owner txid prefix
+        // Note: anchor.txid_prefix is in little-endian (internal Bitcoin format)
+        // but owner_txid is stored in big-endian (display format)
+        // We need to compare the reversed prefix with the END of owner_txid
+        let mut prefix_reversed = anchor.txid_prefix;
+        prefix_reversed.reverse();
+        let owner_suffix = &owner_txid[owner_txid.len().saturating_sub(8)..];
+
+        if owner_suffix != prefix_reversed {
+            debug!(
+                "Message for {} rejected: anchor doesn't match owner. \
+                Anchor prefix (reversed): {:?}, Owner suffix: {:?}",
+                name,
+                hex::encode(prefix_reversed),
+                hex::encode(owner_suffix)
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Move domains whose ownership UTXO this transaction spends
+    ///
+    /// Updates and transfers already moved their domain; any other spend,
+    /// such as sending the UTXO from a wallet, hands the domain to the first
+    /// spendable output of the transaction.
+    async fn follow_owner_spends(
+        &self,
+        tx: &Transaction,
+        owners: &mut HashSet<(Vec<u8>, i32)>,
+    ) -> Result<()> {
+        let spent: Vec<(Vec<u8>, i32)> = tx
+            .input
+            .iter()
+            .map(|input| outpoint_key(&input.previous_output))
+            .filter(|key| owners.remove(key))
+            .collect();
+        if spent.is_empty() {
+            return Ok(());
+        }
+        let Some(owner) = OwnerOutput::for_spend(tx) else {
+            warn!(
+                "Tx {} spends a domain owner without a spendable output",
+                tx.compute_txid()
+            );
+            return Ok(());
+        };
+
+        let txid_bytes = display_bytes(&tx.compute_txid());
+        let moved = self
+            .db
+            .move_spent_owners(&spent, &txid_bytes, &owner)
+            .await?;
+        if !moved.is_empty() {
+            info!(
+                "Ownership of {} moved to {}:{}",
+                moved.join(", "),
+                tx.compute_txid(),
+                owner.vout
+            );
+            owners.insert((txid_bytes, owner.vout));
+        }
+        Ok(())
+    }
+
+    /// Queue deliveries for webhooks watching the domain
+    ///
+    /// Failures are logged; a missed notification must not stall indexing.
+    async fn notify_webhooks(
+        &self,
+        payload: &DnsSpec,
+        event: &str,
+        txid: &[u8],
+        vout: i32,
+        block_height: Option<i32>,
+    ) {
+        if let Err(e) = self
+            .db
+            .enqueue_domain_webhooks(
+                &payload.name,
+                event,
+                txid,
+                vout,
+                &payload.records,
+                block_height,
+            )
+            .await
+        {
+            warn!("Failed to queue webhooks for {}: {}", payload.name, e);
+        }
+    }
+}
+
+/// Txid in display format (big-endian), as stored
+///
+/// Bitcoin internally uses little-endian, but display format is big-endian (reversed)
+fn display_bytes(txid: &Txid) -> Vec<u8> {
+    let mut bytes = txid.to_byte_array().to_vec();
+    bytes.reverse();
+    bytes
+}
+
+/// Outpoint as stored in the domains table
+fn outpoint_key(outpoint: &OutPoint) -> (Vec<u8>, i32) {
+    (display_bytes(&outpoint.txid), outpoint.vout as i32)
 }
@@ -12,11 +12,13 @@ mod services;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use anchor_api_security::SecurityConfig;
+use anchor_api_snapshot::Snapshots;
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
-use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use utoipa::OpenApi;
@@ -25,11 +27,15 @@ use utoipa_swagger_ui::SwaggerUi;
 use crate::config::Config;
 use crate::db::Database;
 use crate::indexer::Indexer;
+use crate::services::identity_resolver::IdentityResolver;
+use crate::services::public_resolver::{self, PublicResolver};
 
 /// Application state shared across handlers
 pub struct AppState {
     pub db: Database,
     pub config: Config,
+    pub identities: IdentityResolver,
+    pub snapshots: Arc<Snapshots>,
 }
 
 /// OpenAPI documentation
@@ -48,8 +54,21 @@ pub struct AppState {
         handlers::get_my_domains,
         handlers::register_domain,
         handlers::update_domain,
+        handlers::import_names,
         handlers::get_pending_status,
         handlers::list_pending_transactions,
+        handlers::list_domain_identities,
+        handlers::publish_domain_identity,
+        handlers::remove_domain_identity,
+        handlers::resolve_identity,
+        handlers::resolve_identities_batch,
+        handlers::create_webhook,
+        handlers::list_webhooks,
+        handlers::delete_webhook,
+        handlers::get_webhook_deliveries,
+        handlers::replay_webhook,
+        anchor_api_snapshot::create_snapshot,
+        anchor_api_snapshot::release_snapshot,
     ),
     components(schemas(
         models::HealthResponse,
@@ -63,12 +82,33 @@ pub struct AppState {
         models::UpdateDomainRequest,
         models::DnsRecordInput,
         models::CreateTxResponse,
+        models::ImportSource,
+        models::ImportName,
+        models::ImportRequest,
+        models::ImportResult,
+        models::ImportResponse,
         models::PendingTransaction,
         models::PendingStatusResponse,
         models::HistoryEntry,
         models::AvailabilityResponse,
         models::GetDomainsByOwnerRequest,
         models::MyDomainsResponse,
+        handlers::IdentityType,
+        handlers::PublishedIdentity,
+        handlers::PublishIdentityRequest,
+        handlers::PublishIdentityResponse,
+        handlers::DomainIdentitiesResponse,
+        handlers::IdentityQuery,
+        handlers::ResolveBatchRequest,
+        handlers::ResolveBatchResponse,
+        services::identity_resolver::ResolvedIdentity,
+        services::identity_resolver::IdentitySource,
+        models::CreateWebhookRequest,
+        models::WebhookResponse,
+        models::WebhookDeliveryResponse,
+        models::ReplayWebhookRequest,
+        models::ReplayWebhookResponse,
+        anchor_api_snapshot::SnapshotResponse,
     )),
     tags(
         (name = "System", description = "Health and status endpoints"),
@@ -77,6 +117,9 @@ pub struct AppState {
         (name = "Domains", description = "Domain management endpoints"),
         (name = "Registration", description = "Domain registration endpoints"),
         (name = "Pending", description = "Pending transaction endpoints"),
+        (name = "Identity", description = "Selfie Records identity publishing and resolution"),
+        (name = "Webhooks", description = "Domain update notifications"),
+        (name = "Snapshots", description = "Consistent reads across paginated requests"),
     ),
     info(
         title = "Anchor Domains API",
@@ -129,6 +172,9 @@ Each record:
 | MX | 5 | u16 priority + domain | `mail.example.btc` |
 | NS | 6 | UTF-8 string | `ns1.example.btc` |
 | SRV | 7 | u16×3 + target | `server.example.btc` |
+| TLSA | 8 | u8×3 + association data | `3 1 1 <sha256 hex>` |
+| SSHFP | 9 | u8×2 + fingerprint | `4 2 <sha256 hex>` |
+| URI | 10 | u16×2 + target | `https://example.btc/` |
 
 ## Domain Ownership
 
@@ -156,6 +202,10 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(tracing_subscriber::fmt::layer())
@@ -174,48 +224,77 @@ async fn main() -> anyhow::Result<()> {
     let db = Database::connect(&config.database_url).await?;
     info!("Connected to database");
 
+    let snapshots = Arc::new(Snapshots::from_env(
+        &config.database_url,
+        "SELECT last_block_height FROM anchor_domains_indexer_state WHERE id = 1",
+    ));
+    snapshots.start();
+
     // Create shared state
     let state = Arc::new(AppState {
         db: db.clone(),
         config: config.clone(),
+        identities: IdentityResolver::new(db.clone(), &config),
+        snapshots,
     });
 
-    // Spawn indexer in background
-    let indexer_config = config.clone();
-    let indexer_db = db.clone();
-    tokio::spawn(async move {
-        match Indexer::new(indexer_config, indexer_db).await {
-            Ok(indexer) => {
-                if let Err(e) = indexer.run().await {
-                    tracing::error!("Indexer error: {}", e);
+    // A public resolver only serves reads; indexing and webhook delivery
+    // belong to the private instance sharing its database
+    let public = config
+        .public_resolver
+        .then(|| PublicResolver::from_config(&config));
+
+    if public.is_none() {
+        // Spawn indexer in background
+        let indexer_config = config.clone();
+        let indexer_db = db.clone();
+        tokio::spawn(async move {
+            match Indexer::new(indexer_config, indexer_db).await {
+                Ok(indexer) => {
+                    if let Err(e) = indexer.run().await {
+                        tracing::error!("Indexer error: {}", e);
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to start indexer: {}", e);
                 }
             }
-            Err(e) => {
-                tracing::error!("Failed to start indexer: {}", e);
-            }
-        }
-    });
+        });
+
+        // Send queued webhook deliveries
+        anchor_webhooks::start(db.webhook_queue());
+    }
 
     // Build router
-    let app = build_router(state);
+    let public_mode = public.is_some();
+    let app = build_router(state, public);
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
     info!("Listening on {}", addr);
-    info!(
-        "Swagger UI available at http://localhost:{}/swagger-ui/",
-        config.port
-    );
+    if !public_mode {
+        info!(
+            "Swagger UI available at http://localhost:{}/swagger-ui/",
+            config.port
+        );
+    }
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
 
-/// Build the application router with all routes
-fn build_router(state: Arc<AppState>) -> Router {
-    Router::new()
+/// Build the application router
+///
+/// In public resolver mode only read routes are mounted, behind per-IP rate
+/// limiting and the response cache.
+fn build_router(state: Arc<AppState>, public: Option<Arc<PublicResolver>>) -> Router {
+    let mut router = Router::new()
         // System
         .route("/health", get(handlers::health))
         .route("/stats", get(handlers::get_stats))
@@ -224,39 +303,76 @@ fn build_router(state: Arc<AppState>) -> Router {
         .route("/resolve/txid/:prefix", get(handlers::resolve_by_txid))
         // Domains
         .route("/domains", get(handlers::list_domains))
-        .route("/domains/by-owner", post(handlers::get_domains_by_owner))
         .route("/my-domains", get(handlers::get_my_domains))
         .route("/domains/:name", get(handlers::get_domain))
         .route("/domains/:name/history", get(handlers::get_domain_history))
         .route("/available/:name", get(handlers::check_availability))
-        // Registration
-        .route("/register", post(handlers::register_domain))
-        .route("/update/:name", post(handlers::update_domain))
-        // Pending transactions
-        .route("/pending", get(handlers::list_pending_transactions))
-        .route("/pending/:name", get(handlers::get_pending_status))
         // Identity DNS (Selfie Records)
         .route(
             "/domains/:name/identities",
             get(handlers::list_domain_identities),
         )
-        .route(
-            "/domains/:name/identities",
-            post(handlers::publish_domain_identity),
-        )
-        .route(
-            "/domains/:name/identities/:identity_type",
-            axum::routing::delete(handlers::remove_domain_identity),
-        )
-        .route("/identities/resolve", get(handlers::resolve_identity))
-        // Swagger UI
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        // State and middleware
+        .route("/identities/resolve", get(handlers::resolve_identity));
+
+    if public.is_none() {
+        router = router
+            .route("/domains/by-owner", post(handlers::get_domains_by_owner))
+            // Registration
+            .route("/register", post(handlers::register_domain))
+            .route("/update/:name", post(handlers::update_domain))
+            .route("/import", post(handlers::import_names))
+            // Pending transactions
+            .route("/pending", get(handlers::list_pending_transactions))
+            .route("/pending/:name", get(handlers::get_pending_status))
+            // Identity publishing
+            .route(
+                "/domains/:name/identities",
+                post(handlers::publish_domain_identity),
+            )
+            .route(
+                "/domains/:name/identities/:identity_type",
+                axum::routing::delete(handlers::remove_domain_identity),
+            )
+            .route(
+                "/identities/resolve-batch",
+                post(handlers::resolve_identities_batch),
+            )
+            // Webhooks
+            .route(
+                "/webhooks",
+                get(handlers::list_webhooks).post(handlers::create_webhook),
+            )
+            .route(
+                "/webhooks/:id",
+                axum::routing::delete(handlers::delete_webhook),
+            )
+            .route(
+                "/webhooks/:id/deliveries",
+                get(handlers::get_webhook_deliveries),
+            )
+            .route("/webhooks/:id/replay", post(handlers::replay_webhook))
+            // Snapshots
+            .merge(anchor_api_snapshot::routes(state.snapshots.clone()))
+            // Swagger UI
+            .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
+    }
+
+    // State and middleware
+    let mut router = router
         .with_state(state)
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .layer(middleware::map_response(anchor_api_error::normalize_errors));
+
+    if let Some(public) = public {
+        router = router
+            .layer(middleware::from_fn_with_state(
+                public.clone(),
+                public_resolver::cache,
+            ))
+            .layer(middleware::from_fn_with_state(
+                public,
+                public_resolver::rate_limit,
+            ));
+    }
+
+    router.layer(SecurityConfig::from_env().layer())
 }
@@ -77,6 +77,9 @@ pub struct UpdateDomainRequest {
 /// - **MX**: Mail exchange (requires priority)
 /// - **NS**: Name server
 /// - **SRV**: Service record (requires priority, weight, port)
+/// - **TLSA**: TLS certificate association (e.g., "3 1 1 <sha256 hex>")
+/// - **SSHFP**: SSH key fingerprint (e.g., "4 2 <sha256 hex>")
+/// - **URI**: URI target (requires priority and weight)
 ///
 /// ## Example
 /// ```json
@@ -84,7 +87,7 @@ pub struct UpdateDomainRequest {
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct DnsRecordInput {
-    /// Record type: A, AAAA, CNAME, TXT, MX, NS, SRV
+    /// Record type: A, AAAA, CNAME, TXT, MX, NS, SRV, TLSA, SSHFP, URI
     #[schema(example = "A")]
     pub record_type: String,
     /// Time-to-live in seconds (default: 300)
@@ -93,10 +96,10 @@ pub struct DnsRecordInput {
     /// Record value (format depends on type)
     #[schema(example = "93.184.216.34")]
     pub value: String,
-    /// Priority (required for MX and SRV)
+    /// Priority (required for MX, SRV and URI)
     #[schema(example = 10)]
     pub priority: Option<u16>,
-    /// Weight (for SRV records)
+    /// Weight (for SRV and URI records)
     #[schema(example = 1)]
     pub weight: Option<u16>,
     /// Port (for SRV records)
@@ -115,6 +118,9 @@ impl DnsRecordInput {
             "MX" => RecordType::MX,
             "NS" => RecordType::NS,
             "SRV" => RecordType::SRV,
+            "TLSA" => RecordType::TLSA,
+            "SSHFP" => RecordType::SSHFP,
+            "URI" => RecordType::URI,
             _ => return None,
         };
 
@@ -155,6 +161,15 @@ pub struct ListParams {
     #[serde(default = "default_per_page")]
     pub per_page: i32,
     pub search: Option<String>,
+    /// Address or hex output script of the current owner
+    pub owned_by: Option<String>,
+}
+
+/// Historical lookup parameters
+#[derive(Debug, Clone, Deserialize)]
+pub struct AtHeightParams {
+    /// Return state as of this block height
+    pub at_height: Option<i32>,
 }
 
 fn default_page() -> i32 {
@@ -227,6 +242,138 @@ pub struct PendingStatusResponse {
     pub pending: Option<PendingTransaction>,
 }
 
+// ============================================================================
+// Webhook Models
+// ============================================================================
+
+/// Webhook registration request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// Domain whose registration and updates trigger the webhook
+    #[schema(example = "mysite.btc")]
+    pub domain: String,
+    /// Endpoint that receives POSTed events
+    pub url: String,
+    /// Signing secret (default: a random one, returned once)
+    pub secret: Option<String>,
+}
+
+/// Registered webhook
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: i32,
+    pub domain: String,
+    pub url: String,
+    pub active: bool,
+    /// Signing secret, only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One event sent, or to be sent, to a webhook
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDeliveryResponse {
+    pub id: i64,
+    /// domain.registered, domain.updated or domain.transferred
+    pub event: String,
+    /// pending, delivered or failed
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub payload: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Replay request: one delivery, or everything since a point in time
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ReplayWebhookRequest {
+    pub delivery_id: Option<i64>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Replay result
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayWebhookResponse {
+    /// Deliveries queued again
+    pub queued: u64,
+}
+
+// ============================================================================
+// Import Models
+// ============================================================================
+
+/// Naming system an import comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    /// Ethereum Name Service (`.eth`)
+    Ens,
+    /// Solana Name Service (`.sol`)
+    Sns,
+}
+
+/// One name from an ENS or SNS export
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ImportName {
+    /// Name in its source system
+    #[schema(example = "alice.eth")]
+    pub name: String,
+    /// Text records (ENS) or records keyed by SNS record name
+    #[serde(default)]
+    pub records: std::collections::BTreeMap<String, String>,
+    /// Address records keyed by coin (ENS)
+    #[serde(default)]
+    pub addresses: std::collections::BTreeMap<String, String>,
+    /// Decoded content hash, e.g. `ipfs://bafy...` (ENS)
+    pub contenthash: Option<String>,
+}
+
+/// Request body for importing names from ENS or SNS
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ImportRequest {
+    pub source: ImportSource,
+    pub names: Vec<ImportName>,
+    /// TLD the names are registered under (default: .btc)
+    #[schema(example = ".btc")]
+    pub tld: Option<String>,
+    /// Carrier type: 1=Inscription, 4=WitnessData
+    pub carrier: Option<u8>,
+    /// Map the records without creating transactions
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome for one imported name
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportResult {
+    /// Name in its source system
+    pub source_name: String,
+    /// Anchor Domains name it maps to
+    pub name: String,
+    /// planned, created, taken, duplicate, invalid or failed
+    pub status: String,
+    pub records: Vec<DnsRecordInput>,
+    /// Source records that have no Anchor equivalent, with the reason
+    pub skipped: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Import summary
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportResponse {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    /// Registration transactions created
+    pub created: usize,
+    pub results: Vec<ImportResult>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -8,14 +8,10 @@ use utoipa::ToSchema;
 
 // Re-export DNS types from anchor-specs
 pub use anchor_specs::dns::{
-    is_valid_domain_name, DnsOperation, DnsRecord, DnsSpec as DnsPayload, RecordType,
+    get_tld, is_valid_domain_name, DnsOperation, DnsRecord, DnsSpec as DnsPayload, RecordType,
     SUPPORTED_TLDS,
 };
 
-// Re-export for tests
-#[cfg(test)]
-pub use anchor_specs::dns::get_tld;
-
 /// Domain information with full details
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Domain {
@@ -25,12 +21,60 @@ pub struct Domain {
     pub vout: i32,
     pub txid_prefix: String,
     pub owner_txid: String,
+    /// Address holding the ownership UTXO, following transfers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_address: Option<String>,
     pub block_height: Option<i32>,
     pub records: Vec<DnsRecordResponse>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Output that controls a domain: spending it moves the domain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerOutput {
+    pub vout: i32,
+    pub script: Vec<u8>,
+    pub address: Option<String>,
+}
+
+impl OwnerOutput {
+    /// The ownership output a transaction creates for a message at `vout`
+    ///
+    /// That is the message output itself when it can be spent (the
+    /// inscription carriers), otherwise the first spendable output, so
+    /// OP_RETURN messages still leave the domain with a live owner.
+    pub fn for_message(tx: &bitcoin::Transaction, vout: u32) -> Option<Self> {
+        let spendable = |o: &&bitcoin::TxOut| !o.script_pubkey.is_op_return();
+        let (vout, output) = tx
+            .output
+            .get(vout as usize)
+            .filter(spendable)
+            .map(|o| (vout as usize, o))
+            .or_else(|| tx.output.iter().enumerate().find(|(_, o)| spendable(o)))?;
+        Some(Self::at(vout as i32, &output.script_pubkey))
+    }
+
+    /// Where ownership moves when a transaction spends an ownership UTXO
+    /// without a DNS message: its first spendable output
+    pub fn for_spend(tx: &bitcoin::Transaction) -> Option<Self> {
+        tx.output
+            .iter()
+            .position(|o| !o.script_pubkey.is_op_return())
+            .map(|vout| Self::at(vout as i32, &tx.output[vout].script_pubkey))
+    }
+
+    fn at(vout: i32, script: &bitcoin::Script) -> Self {
+        Self {
+            vout,
+            script: script.to_bytes(),
+            address: bitcoin::Address::from_script(script, bitcoin::Network::Regtest)
+                .ok()
+                .map(|a| a.to_string()),
+        }
+    }
+}
+
 /// DNS record response (from database)
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct DnsRecordResponse {
@@ -61,6 +105,12 @@ pub struct ResolveResponse {
     pub vout: i32,
     pub txid_prefix: String,
     pub records: Vec<DnsRecordResponse>,
+    /// Name that was queried, when it differs from `name`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// How a non-exact query matched: `record_name` or `wildcard`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_by: Option<String>,
 }
 
 /// Domain list item (summary for listings)
@@ -70,6 +120,9 @@ pub struct DomainListItem {
     pub name: String,
     pub txid: String,
     pub txid_prefix: String,
+    /// Ownership UTXO
+    pub owner_txid: String,
+    pub owner_vout: i32,
     pub record_count: i64,
     pub block_height: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -135,6 +188,42 @@ impl HistoryEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{absolute::LockTime, transaction::Version, Amount, ScriptBuf, TxOut};
+
+    fn tx_with(outputs: Vec<ScriptBuf>) -> bitcoin::Transaction {
+        bitcoin::Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: outputs
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: Amount::from_sat(546),
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_owner_output() {
+        let data = ScriptBuf::new_op_return([1u8, 2, 3]);
+        let wallet = ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([7; 20]));
+        let tx = tx_with(vec![data, wallet.clone()]);
+
+        // An OP_RETURN message leaves ownership with the spendable output
+        let owner = OwnerOutput::for_message(&tx, 0).unwrap();
+        assert_eq!(owner.vout, 1);
+        assert_eq!(owner.script, wallet.to_bytes());
+        assert!(owner.address.unwrap().starts_with("bcrt1q"));
+        assert_eq!(OwnerOutput::for_message(&tx, 1).unwrap().vout, 1);
+        assert_eq!(OwnerOutput::for_spend(&tx).unwrap().vout, 1);
+
+        let burn = tx_with(vec![ScriptBuf::new_op_return([0u8])]);
+        assert!(OwnerOutput::for_message(&burn, 0).is_none());
+        assert!(OwnerOutput::for_spend(&burn).is_none());
+    }
 
     #[test]
     fn test_valid_domain_names() {
@@ -8,7 +8,7 @@
 //! The core DNS protocol types are defined in `anchor-specs::dns`:
 //! - `DnsSpec` - Full DNS specification with operation, name, and records
 //! - `DnsOperation` - Register, Update, Transfer
-//! - `DnsRecord` - Individual DNS record (A, AAAA, CNAME, TXT, MX, NS, SRV)
+//! - `DnsRecord` - Individual DNS record (A, AAAA, CNAME, TXT, MX, NS, SRV, TLSA, SSHFP, URI)
 //! - `RecordType` - DNS record type enum
 //!
 //! ## API Types (defined here)
@@ -0,0 +1,416 @@
+//! Selfie Records identity resolver
+//!
+//! Resolves addresses such as `alice@example.btc` to the Nostr or Pubky key
+//! published under `user._nostr.example.btc`. Identities published through
+//! this service are looked up in the database; hybrid domains (names under a
+//! regular ICANN TLD, whose records live in public DNS) fall back to a
+//! DNS-over-HTTPS query. Results, including misses, are cached for a
+//! configurable TTL so chat and explorer frontends can resolve many authors
+//! without hitting the database or the DoH server on every render.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinSet;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::handlers::identity::{build_record_name, IdentityType};
+use crate::models::SUPPORTED_TLDS;
+
+/// DNS TXT record type
+const DNS_TYPE_TXT: u16 = 16;
+
+/// Cached entries beyond which expired ones are pruned
+const MAX_CACHE_ENTRIES: usize = 50_000;
+
+/// Concurrent DNS-over-HTTPS queries per batch
+const DOH_CONCURRENCY: usize = 16;
+
+/// Where a resolved identity came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentitySource {
+    /// Published through Anchor Domains
+    Database,
+    /// Found in public DNS via DNS-over-HTTPS
+    Dns,
+}
+
+/// Result of resolving one address
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ResolvedIdentity {
+    /// Address as requested
+    pub address: String,
+    /// Identity type (nostr or pubky)
+    pub identity_type: String,
+    /// Selfie Records name that was looked up
+    pub record_name: Option<String>,
+    /// Published public key (npub1... or pk:...)
+    pub public_key: Option<String>,
+    /// Whether a key was found
+    pub resolved: bool,
+    /// Where the key was found
+    pub source: Option<IdentitySource>,
+    /// Why the address could not be resolved, if it was malformed
+    pub error: Option<String>,
+}
+
+/// Cached lookup result, keyed by record name
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    public_key: Option<String>,
+    source: Option<IdentitySource>,
+    expires_at: Instant,
+}
+
+/// Caching identity resolver shared by the identity handlers
+pub struct IdentityResolver {
+    db: Database,
+    http: reqwest::Client,
+    doh_url: String,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CacheEntry>>,
+}
+
+impl IdentityResolver {
+    /// Create a resolver from the application configuration
+    pub fn new(db: Database, config: &Config) -> Self {
+        Self {
+            db,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            doh_url: config.doh_url.clone(),
+            ttl: Duration::from_secs(config.identity_cache_ttl_secs),
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Resolve a single address
+    pub async fn resolve(
+        &self,
+        address: &str,
+        identity_type: IdentityType,
+    ) -> Result<ResolvedIdentity> {
+        let mut results = self
+            .resolve_batch(&[(address.to_string(), identity_type)])
+            .await?;
+        Ok(results.remove(0))
+    }
+
+    /// Resolve many addresses at once
+    ///
+    /// Results are returned in request order. Cache misses are looked up in
+    /// the database with a single query, and the remaining hybrid-domain
+    /// misses are queried over DNS-over-HTTPS concurrently.
+    pub async fn resolve_batch(
+        &self,
+        queries: &[(String, IdentityType)],
+    ) -> Result<Vec<ResolvedIdentity>> {
+        let record_names: Vec<Option<(String, String)>> = queries
+            .iter()
+            .map(|(address, identity_type)| {
+                parse_address(address).map(|(subdomain, domain)| {
+                    let name = build_record_name(&domain, identity_type, subdomain.as_deref());
+                    (name, domain)
+                })
+            })
+            .collect();
+
+        // Serve what we can from the cache
+        let now = Instant::now();
+        let mut found: HashMap<String, CacheEntry> = HashMap::new();
+        let mut misses: HashMap<String, (String, IdentityType)> = HashMap::new();
+        {
+            let cache = self.cache.read().await;
+            for (entry, (_, identity_type)) in record_names.iter().zip(queries) {
+                let Some((name, domain)) = entry else {
+                    continue;
+                };
+                match cache.get(name) {
+                    Some(cached) if cached.expires_at > now => {
+                        found.insert(name.clone(), cached.clone());
+                    }
+                    _ => {
+                        misses.insert(name.clone(), (domain.clone(), *identity_type));
+                    }
+                }
+            }
+        }
+
+        if !misses.is_empty() {
+            let fresh = self.lookup(misses).await?;
+            let mut cache = self.cache.write().await;
+            if cache.len() + fresh.len() > MAX_CACHE_ENTRIES {
+                cache.retain(|_, entry| entry.expires_at > now);
+            }
+            for (name, entry) in fresh {
+                cache.insert(name.clone(), entry.clone());
+                found.insert(name, entry);
+            }
+        }
+
+        Ok(queries
+            .iter()
+            .zip(record_names)
+            .map(|((address, identity_type), entry)| match entry {
+                Some((name, _)) => {
+                    let hit = found.get(&name);
+                    let public_key = hit.and_then(|e| e.public_key.clone());
+                    ResolvedIdentity {
+                        address: address.clone(),
+                        identity_type: identity_type.as_str().to_string(),
+                        record_name: Some(name),
+                        resolved: public_key.is_some(),
+                        public_key,
+                        source: hit.and_then(|e| e.source),
+                        error: None,
+                    }
+                }
+                None => ResolvedIdentity {
+                    address: address.clone(),
+                    identity_type: identity_type.as_str().to_string(),
+                    record_name: None,
+                    public_key: None,
+                    resolved: false,
+                    source: None,
+                    error: Some("Invalid address format. Use user@domain.com".to_string()),
+                },
+            })
+            .collect())
+    }
+
+    /// Drop cached results for a domain after its identities change
+    pub async fn invalidate_domain(&self, domain: &str) {
+        let suffix = format!(".{}", domain);
+        self.cache
+            .write()
+            .await
+            .retain(|name, _| !name.ends_with(&suffix));
+    }
+
+    /// Look up record names missing from the cache
+    async fn lookup(
+        &self,
+        misses: HashMap<String, (String, IdentityType)>,
+    ) -> Result<HashMap<String, CacheEntry>> {
+        let expires_at = Instant::now() + self.ttl;
+        let names: Vec<String> = misses.keys().cloned().collect();
+
+        let mut entries: HashMap<String, CacheEntry> = self
+            .db
+            .get_identities_by_record_names(&names)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let entry = CacheEntry {
+                    public_key: Some(row.public_key),
+                    source: Some(IdentitySource::Database),
+                    expires_at,
+                };
+                (row.record_name, entry)
+            })
+            .collect();
+
+        // Hybrid domains publish their records in public DNS
+        let semaphore = Arc::new(Semaphore::new(DOH_CONCURRENCY));
+        let mut queries = JoinSet::new();
+        for (name, (domain, identity_type)) in misses {
+            if entries.contains_key(&name) {
+                continue;
+            }
+            if !is_hybrid_domain(&domain) {
+                entries.insert(
+                    name,
+                    CacheEntry {
+                        public_key: None,
+                        source: None,
+                        expires_at,
+                    },
+                );
+                continue;
+            }
+            let http = self.http.clone();
+            let doh_url = self.doh_url.clone();
+            let semaphore = semaphore.clone();
+            queries.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let key = query_doh(&http, &doh_url, &name, identity_type).await;
+                (name, key)
+            });
+        }
+
+        while let Some(joined) = queries.join_next().await {
+            let Ok((name, key)) = joined else {
+                continue;
+            };
+            let public_key = match key {
+                Ok(key) => key,
+                Err(e) => {
+                    // Don't cache transport failures
+                    warn!("DNS-over-HTTPS lookup for {} failed: {}", name, e);
+                    continue;
+                }
+            };
+            entries.insert(
+                name,
+                CacheEntry {
+                    source: public_key.as_ref().map(|_| IdentitySource::Dns),
+                    public_key,
+                    expires_at,
+                },
+            );
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Split `[subdomain.]user@domain` into its subdomain and domain
+fn parse_address(address: &str) -> Option<(Option<String>, String)> {
+    let (user_part, domain) = address.split_once('@')?;
+    if user_part.is_empty() || domain.is_empty() || domain.contains('@') || !domain.contains('.') {
+        return None;
+    }
+
+    let subdomain = user_part
+        .split_once('.')
+        .map(|(sub, _)| sub.to_string())
+        .filter(|sub| !sub.is_empty());
+
+    Some((subdomain, domain.to_ascii_lowercase()))
+}
+
+/// Whether a domain lives outside the Anchor TLDs and so in public DNS
+fn is_hybrid_domain(domain: &str) -> bool {
+    !SUPPORTED_TLDS.iter().any(|tld| domain.ends_with(tld))
+}
+
+/// DNS-over-HTTPS JSON response (RFC 8484 JSON flavour used by Cloudflare and Google)
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+/// Query a TXT record over DNS-over-HTTPS and return the identity key in it
+async fn query_doh(
+    http: &reqwest::Client,
+    doh_url: &str,
+    name: &str,
+    identity_type: IdentityType,
+) -> Result<Option<String>> {
+    let response: DohResponse = http
+        .get(doh_url)
+        .query(&[("name", name), ("type", "TXT")])
+        .header("accept", "application/dns-json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(identity_from_answers(&response, identity_type))
+}
+
+/// Pick the first TXT answer carrying a key of the expected type
+fn identity_from_answers(response: &DohResponse, identity_type: IdentityType) -> Option<String> {
+    // NOERROR only; NXDOMAIN and friends mean no identity
+    if response.status != 0 {
+        return None;
+    }
+    let prefix = match identity_type {
+        IdentityType::Nostr => "npub1",
+        IdentityType::Pubky => "pk:",
+    };
+    response
+        .answer
+        .iter()
+        .filter(|answer| answer.record_type == DNS_TYPE_TXT)
+        .map(|answer| unquote_txt(&answer.data))
+        .find(|value| value.starts_with(prefix))
+}
+
+/// Join the quoted character-strings of a TXT record's presentation form
+fn unquote_txt(data: &str) -> String {
+    let data = data.trim();
+    if !data.starts_with('"') {
+        return data.to_string();
+    }
+    data.split('"')
+        .skip(1)
+        .step_by(2)
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("alice@Example.btc"),
+            Some((None, "example.btc".to_string()))
+        );
+        assert_eq!(
+            parse_address("hello.alice@example.com"),
+            Some((Some("hello".to_string()), "example.com".to_string()))
+        );
+        assert_eq!(parse_address("example.btc"), None);
+        assert_eq!(parse_address("@example.btc"), None);
+        assert_eq!(parse_address("a@b@example.btc"), None);
+    }
+
+    #[test]
+    fn test_is_hybrid_domain() {
+        assert!(!is_hybrid_domain("example.btc"));
+        assert!(!is_hybrid_domain("example.anchor"));
+        assert!(is_hybrid_domain("example.com"));
+    }
+
+    #[test]
+    fn test_unquote_txt() {
+        assert_eq!(unquote_txt("\"npub1abc\""), "npub1abc");
+        assert_eq!(unquote_txt("\"npub1\" \"abc\""), "npub1abc");
+        assert_eq!(unquote_txt("pk:xyz"), "pk:xyz");
+    }
+
+    #[test]
+    fn test_identity_from_answers() {
+        let response: DohResponse = serde_json::from_str(
+            r#"{"Status":0,"Answer":[
+                {"name":"user._nostr.example.com","type":5,"TTL":300,"data":"other.example.com."},
+                {"name":"user._nostr.example.com","type":16,"TTL":300,"data":"\"v=spf1 -all\""},
+                {"name":"user._nostr.example.com","type":16,"TTL":300,"data":"\"npub1abc\""}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            identity_from_answers(&response, IdentityType::Nostr),
+            Some("npub1abc".to_string())
+        );
+        assert_eq!(identity_from_answers(&response, IdentityType::Pubky), None);
+
+        let nxdomain: DohResponse = serde_json::from_str(r#"{"Status":3}"#).unwrap();
+        assert_eq!(identity_from_answers(&nxdomain, IdentityType::Nostr), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! ENS and SNS import mapping
+//!
+//! Converts a name exported from the Ethereum or Solana name services into
+//! an Anchor Domains name and the `DnsSpec` records that carry its data:
+//!
+//! | Source record | Anchor record |
+//! |---------------|---------------|
+//! | `url` | URI (priority 10, weight 1) |
+//! | ENS `ipfs://` / `ipns://` content hash, SNS `IPFS` | TXT `dnslink=/ipfs/...` |
+//! | ENS address, SNS coin record (`BTC`, `ETH`, ...) | TXT `addr.<coin>=<address>` |
+//! | SNS `A`, `AAAA`, `CNAME`, `TXT` | same type |
+//! | any other text record | TXT `<key>=<value>` |
+//!
+//! Records that fail validation (for example text too long for a record)
+//! are reported as skipped rather than failing the whole name.
+
+use crate::models::{is_valid_domain_name, DnsRecordInput, ImportName, ImportSource};
+
+/// Priority and weight given to imported URI records
+const URI_PRIORITY: u16 = 10;
+const URI_WEIGHT: u16 = 1;
+
+/// SNS records that hold a wallet address
+const SNS_ADDRESS_RECORDS: &[&str] = &["SOL", "ETH", "BTC", "LTC", "DOGE", "BSC", "INJ", "BASE"];
+
+/// A source name mapped onto Anchor Domains
+#[derive(Debug, Clone)]
+pub struct MappedName {
+    pub name: String,
+    pub records: Vec<DnsRecordInput>,
+    pub skipped: Vec<String>,
+}
+
+impl ImportSource {
+    /// Suffix names carry in the source system
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            ImportSource::Ens => ".eth",
+            ImportSource::Sns => ".sol",
+        }
+    }
+}
+
+/// Map an exported name and its records
+pub fn map_name(source: ImportSource, entry: &ImportName, tld: &str) -> Result<MappedName, String> {
+    let name = map_domain_name(source, &entry.name, tld)?;
+
+    let mut mapped = MappedName {
+        name,
+        records: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for (key, value) in &entry.records {
+        let record = match source {
+            ImportSource::Ens => map_text_record(key, value),
+            ImportSource::Sns => map_sns_record(key, value),
+        };
+        mapped.push(key, record);
+    }
+    for (coin, address) in &entry.addresses {
+        let key = format!("addr.{}", coin.to_lowercase());
+        let address = address.trim();
+        mapped.push(&key, (!address.is_empty()).then(|| txt(&key, address)));
+    }
+    if let Some(contenthash) = &entry.contenthash {
+        let record = dnslink(contenthash)
+            .map(|link| Ok(input("TXT", &link)))
+            .unwrap_or_else(|| Err("unsupported content hash protocol".to_string()));
+        mapped.push_result("contenthash", record);
+    }
+
+    Ok(mapped)
+}
+
+impl MappedName {
+    /// Add a mapped record, skipping empty values
+    fn push(&mut self, key: &str, record: Option<DnsRecordInput>) {
+        match record {
+            Some(record) => self.push_result(key, Ok(record)),
+            None => self.skipped.push(format!("{}: empty value", key)),
+        }
+    }
+
+    /// Add a record if it validates, otherwise note why it was skipped
+    fn push_result(&mut self, key: &str, record: Result<DnsRecordInput, String>) {
+        let validated = record.and_then(|record| {
+            let dns = record
+                .to_dns_record()
+                .ok_or_else(|| "unsupported record type".to_string())?;
+            dns.validate().map_err(|e| e.to_string())?;
+            Ok(record)
+        });
+        match validated {
+            Ok(record) => self.records.push(record),
+            Err(reason) => self.skipped.push(format!("{}: {}", key, reason)),
+        }
+    }
+}
+
+/// `alice.eth` -> `alice.btc`; subnames keep their labels
+fn map_domain_name(source: ImportSource, name: &str, tld: &str) -> Result<String, String> {
+    let lower = name.trim().to_lowercase();
+    let label = lower
+        .strip_suffix(source.suffix())
+        .ok_or_else(|| format!("{} is not a {} name", name, source.suffix()))?;
+
+    let mapped = format!("{}{}", label, tld);
+    if !is_valid_domain_name(&mapped) {
+        return Err(format!("{} is not a valid Anchor domain", mapped));
+    }
+    Ok(mapped)
+}
+
+/// Map an ENS text record (also used for unrecognised SNS records)
+fn map_text_record(key: &str, value: &str) -> Option<DnsRecordInput> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if key == "url" {
+        return Some(DnsRecordInput {
+            priority: Some(URI_PRIORITY),
+            weight: Some(URI_WEIGHT),
+            ..input("URI", value)
+        });
+    }
+    Some(txt(key, value))
+}
+
+/// Map an SNS record by its record name
+fn map_sns_record(key: &str, value: &str) -> Option<DnsRecordInput> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    match key {
+        "A" | "AAAA" | "CNAME" | "TXT" => Some(input(key, value)),
+        "IPFS" => Some(input("TXT", &format!("dnslink=/ipfs/{}", value))),
+        _ if SNS_ADDRESS_RECORDS.contains(&key) => {
+            Some(txt(&format!("addr.{}", key.to_lowercase()), value))
+        }
+        _ => map_text_record(&key.to_lowercase(), value),
+    }
+}
+
+/// DNSLink value for a decoded content hash
+fn dnslink(contenthash: &str) -> Option<String> {
+    let contenthash = contenthash.trim();
+    if let Some(cid) = contenthash.strip_prefix("ipfs://") {
+        Some(format!("dnslink=/ipfs/{}", cid))
+    } else {
+        contenthash
+            .strip_prefix("ipns://")
+            .map(|name| format!("dnslink=/ipns/{}", name))
+    }
+}
+
+fn txt(key: &str, value: &str) -> DnsRecordInput {
+    input("TXT", &format!("{}={}", key, value))
+}
+
+fn input(record_type: &str, value: &str) -> DnsRecordInput {
+    DnsRecordInput {
+        record_type: record_type.to_string(),
+        ttl: None,
+        value: value.to_string(),
+        priority: None,
+        weight: None,
+        port: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, records: &[(&str, &str)]) -> ImportName {
+        ImportName {
+            name: name.to_string(),
+            records: records
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            addresses: Default::default(),
+            contenthash: None,
+        }
+    }
+
+    #[test]
+    fn test_map_domain_name() {
+        assert_eq!(
+            map_domain_name(ImportSource::Ens, "Alice.eth", ".btc").unwrap(),
+            "alice.btc"
+        );
+        assert_eq!(
+            map_domain_name(ImportSource::Ens, "blog.alice.eth", ".sat").unwrap(),
+            "blog.alice.sat"
+        );
+        assert!(map_domain_name(ImportSource::Ens, "alice.sol", ".btc").is_err());
+        assert!(map_domain_name(ImportSource::Sns, "al_ice.sol", ".btc").is_err());
+    }
+
+    #[test]
+    fn test_map_ens() {
+        let mut ens = entry(
+            "alice.eth",
+            &[
+                ("url", "https://alice.example"),
+                ("com.twitter", "alice"),
+                ("description", ""),
+            ],
+        );
+        ens.addresses
+            .insert("BTC".to_string(), "bc1qexample".to_string());
+        ens.contenthash = Some("ipfs://bafybeigdyr".to_string());
+
+        let mapped = map_name(ImportSource::Ens, &ens, ".btc").unwrap();
+        let values: Vec<(&str, &str)> = mapped
+            .records
+            .iter()
+            .map(|r| (r.record_type.as_str(), r.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("TXT", "com.twitter=alice"),
+                ("URI", "https://alice.example"),
+                ("TXT", "addr.btc=bc1qexample"),
+                ("TXT", "dnslink=/ipfs/bafybeigdyr"),
+            ]
+        );
+        assert_eq!(mapped.skipped, vec!["description: empty value"]);
+    }
+
+    #[test]
+    fn test_map_sns() {
+        let sns = entry(
+            "bob.sol",
+            &[
+                ("A", "93.184.216.34"),
+                ("AAAA", "not-an-ip"),
+                ("SOL", "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"),
+                ("IPFS", "bafybeigdyr"),
+                ("twitter", "bob"),
+            ],
+        );
+
+        let mapped = map_name(ImportSource::Sns, &sns, ".btc").unwrap();
+        assert_eq!(mapped.name, "bob.btc");
+        assert_eq!(mapped.records.len(), 4);
+        assert_eq!(mapped.records[0].record_type, "A");
+        assert_eq!(mapped.records[1].value, "dnslink=/ipfs/bafybeigdyr");
+        assert!(mapped.records[2].value.starts_with("addr.sol="));
+        assert_eq!(mapped.records[3].value, "twitter=bob");
+        assert_eq!(mapped.skipped.len(), 1);
+        assert!(mapped.skipped[0].starts_with("AAAA:"));
+    }
+
+    #[test]
+    fn test_oversized_record_skipped() {
+        let long = "x".repeat(300);
+        let mapped = map_name(
+            ImportSource::Ens,
+            &entry("alice.eth", &[("description", &long)]),
+            ".btc",
+        )
+        .unwrap();
+        assert!(mapped.records.is_empty());
+        assert_eq!(mapped.skipped.len(), 1);
+    }
+}
@@ -3,6 +3,14 @@
 //! This module contains reusable services that encapsulate complex logic:
 //! - `wallet`: Communication with the wallet service
 //! - `validation`: Input validation helpers
+//! - `identity_resolver`: Cached Selfie Records identity resolution
+//! - `import`: ENS and SNS record mapping for imports
+//! - `public_resolver`: Rate limiting and caching for public deployments
+//! - `resolution`: Wildcard and subdomain name matching
 
+pub mod identity_resolver;
+pub mod import;
+pub mod public_resolver;
+pub mod resolution;
 pub mod validation;
 pub mod wallet;
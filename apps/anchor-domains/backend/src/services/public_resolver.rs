@@ -0,0 +1,443 @@
+//! Public resolver mode
+//!
+//! With `PUBLIC_RESOLVER=true` the API serves read routes only, limits each
+//! client IP with a token bucket and caches successful GET responses in
+//! memory. Responses carry `Cache-Control` so browsers and CDNs can cache
+//! them as well. With `RESOLVER_STALE_SECS` set, an expired entry is served
+//! once more while a background request refreshes it
+//! (stale-while-revalidate).
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::{info, warn};
+
+use crate::config::Config;
+
+/// Largest response body kept in the cache
+const MAX_CACHED_BODY: usize = 1024 * 1024;
+
+/// Cached responses kept at once
+const MAX_CACHE_ENTRIES: usize = 10_000;
+
+/// Per-client token bucket
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Per-IP token bucket rate limiter
+pub struct RateLimiter {
+    per_sec: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allow `per_minute` requests per client, with bursts of up to `burst`
+    pub fn new(per_minute: u32, burst: u32) -> Self {
+        Self {
+            per_sec: f64::from(per_minute) / 60.0,
+            burst: f64::from(burst.max(1)),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `ip`, or return how long until one is available
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_sec).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_sec,
+            ))
+        }
+    }
+
+    /// Forget clients whose buckets have refilled
+    pub fn prune(&self, now: Instant) {
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.retain(|_, b| {
+            let elapsed = now.saturating_duration_since(b.updated).as_secs_f64();
+            b.tokens + elapsed * self.per_sec < self.burst
+        });
+    }
+}
+
+/// A cached response body
+#[derive(Clone)]
+struct CachedResponse {
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+    stored: Instant,
+    refreshing: bool,
+}
+
+/// Result of a cache lookup
+enum Lookup {
+    /// Within the TTL; seconds of freshness left
+    Fresh(CachedResponse, u64),
+    /// Past the TTL but inside the stale window; `true` if this caller
+    /// should refresh it
+    Stale(CachedResponse, bool),
+    Miss,
+}
+
+/// In-memory cache of successful GET responses, keyed by path and query
+pub struct ResponseCache {
+    ttl: Duration,
+    stale: Duration,
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration, stale: Duration) -> Self {
+        Self {
+            ttl,
+            stale,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lookup(&self, key: &str, now: Instant) -> Lookup {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(key) else {
+            return Lookup::Miss;
+        };
+
+        let age = now.saturating_duration_since(entry.stored);
+        if age < self.ttl {
+            return Lookup::Fresh(entry.clone(), (self.ttl - age).as_secs());
+        }
+        if age < self.ttl + self.stale {
+            let refresh = !entry.refreshing;
+            entry.refreshing = true;
+            return Lookup::Stale(entry.clone(), refresh);
+        }
+
+        entries.remove(key);
+        Lookup::Miss
+    }
+
+    fn store(&self, key: String, content_type: Option<HeaderValue>, body: Bytes, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHE_ENTRIES && !entries.contains_key(&key) {
+            let expiry = self.ttl + self.stale;
+            entries.retain(|_, e| now.saturating_duration_since(e.stored) < expiry);
+            if entries.len() >= MAX_CACHE_ENTRIES {
+                return;
+            }
+        }
+        entries.insert(
+            key,
+            CachedResponse {
+                content_type,
+                body,
+                stored: now,
+                refreshing: false,
+            },
+        );
+    }
+
+    /// Let the next stale hit try to refresh again
+    fn refresh_failed(&self, key: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(key) {
+            entry.refreshing = false;
+        }
+    }
+
+    /// `Cache-Control` value for a response with `max_age` seconds left
+    fn cache_control(&self, max_age: u64) -> HeaderValue {
+        let value = if self.stale.is_zero() {
+            format!("public, max-age={}", max_age)
+        } else {
+            format!(
+                "public, max-age={}, stale-while-revalidate={}",
+                max_age,
+                self.stale.as_secs()
+            )
+        };
+        HeaderValue::from_str(&value).expect("cache-control is ascii")
+    }
+}
+
+/// Shared state for the public resolver middleware
+pub struct PublicResolver {
+    limiter: Option<RateLimiter>,
+    cache: ResponseCache,
+    trust_proxy: bool,
+}
+
+impl PublicResolver {
+    pub fn from_config(config: &Config) -> Arc<Self> {
+        let limiter = (config.resolver_rate_limit > 0)
+            .then(|| RateLimiter::new(config.resolver_rate_limit, config.resolver_rate_burst));
+        info!(
+            "Public resolver mode: {} req/min per IP, cache {}s, stale {}s",
+            config.resolver_rate_limit, config.resolver_cache_secs, config.resolver_stale_secs
+        );
+
+        let resolver = Arc::new(Self {
+            limiter,
+            cache: ResponseCache::new(
+                Duration::from_secs(config.resolver_cache_secs),
+                Duration::from_secs(config.resolver_stale_secs),
+            ),
+            trust_proxy: config.resolver_trust_proxy,
+        });
+
+        let pruned = resolver.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                ticker.tick().await;
+                if let Some(limiter) = &pruned.limiter {
+                    limiter.prune(Instant::now());
+                }
+            }
+        });
+
+        resolver
+    }
+
+    /// Address to rate limit: the peer, or the proxy-reported client
+    fn client_ip(&self, req: &Request, peer: SocketAddr) -> IpAddr {
+        if self.trust_proxy {
+            let forwarded = req
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(forwarded_ip);
+            if let Some(ip) = forwarded {
+                return ip;
+            }
+        }
+        peer.ip()
+    }
+
+    /// Build a response from a cache entry
+    fn cached_response(
+        &self,
+        entry: CachedResponse,
+        max_age: u64,
+        status: &'static str,
+    ) -> Response {
+        let mut response = Response::new(Body::from(entry.body));
+        let headers = response.headers_mut();
+        if let Some(content_type) = entry.content_type {
+            headers.insert(header::CONTENT_TYPE, content_type);
+        }
+        headers.insert(header::CACHE_CONTROL, self.cache.cache_control(max_age));
+        headers.insert("x-cache", HeaderValue::from_static(status));
+        response
+    }
+
+    /// Cache a fresh response if it succeeded, and hand it back
+    async fn store(&self, key: String, response: Response) -> Response {
+        if response.status() != StatusCode::OK {
+            self.cache.refresh_failed(&key);
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to read response body for {}: {}", key, e);
+                self.cache.refresh_failed(&key);
+                return ApiError::internal("Failed to read response").into_response();
+            }
+        };
+
+        if bytes.len() <= MAX_CACHED_BODY {
+            let content_type = parts.headers.get(header::CONTENT_TYPE).cloned();
+            self.cache
+                .store(key, content_type, bytes.clone(), Instant::now());
+            parts.headers.insert(
+                header::CACHE_CONTROL,
+                self.cache.cache_control(self.cache.ttl.as_secs()),
+            );
+        }
+        parts
+            .headers
+            .insert("x-cache", HeaderValue::from_static("MISS"));
+        Response::from_parts(parts, Body::from(bytes))
+    }
+}
+
+/// Client address from an `X-Forwarded-For` header
+///
+/// Uses the last entry, the one added by the proxy in front of us; earlier
+/// entries are supplied by the client and can be forged.
+fn forwarded_ip(header: &str) -> Option<IpAddr> {
+    header.rsplit(',').next()?.trim().parse().ok()
+}
+
+/// Reject clients that have used up their request budget
+pub async fn rate_limit(
+    State(resolver): State<Arc<PublicResolver>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Some(limiter) = &resolver.limiter {
+        let ip = resolver.client_ip(&req, peer);
+        if let Err(wait) = limiter.check(ip, Instant::now()) {
+            let retry_after = wait.as_secs().max(1);
+            let mut response =
+                ApiError::new(ErrorCode::RateLimited, "Too many requests").into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            return response;
+        }
+    }
+    next.run(req).await
+}
+
+/// Serve GET requests from the response cache
+pub async fn cache(
+    State(resolver): State<Arc<PublicResolver>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.method() != Method::GET {
+        return next.run(req).await;
+    }
+
+    let key = req.uri().to_string();
+    match resolver.cache.lookup(&key, Instant::now()) {
+        Lookup::Fresh(entry, max_age) => resolver.cached_response(entry, max_age, "HIT"),
+        Lookup::Stale(entry, refresh) => {
+            if refresh {
+                let refresher = resolver.clone();
+                let key = key.clone();
+                tokio::spawn(async move {
+                    let response = next.run(req).await;
+                    refresher.store(key, response).await;
+                });
+            }
+            resolver.cached_response(entry, 0, "STALE")
+        }
+        Lookup::Miss => {
+            let response = next.run(req).await;
+            resolver.store(key, response).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_refills() {
+        let limiter = RateLimiter::new(60, 2);
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(limiter.check(ip, start).is_ok());
+        assert!(limiter.check(ip, start).is_ok());
+        let wait = limiter.check(ip, start).unwrap_err();
+        assert!(wait <= Duration::from_secs(1));
+
+        // One token per second at 60/min
+        assert!(limiter.check(ip, start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limiter_is_per_ip() {
+        let limiter = RateLimiter::new(60, 1);
+        let now = Instant::now();
+        assert!(limiter.check("203.0.113.7".parse().unwrap(), now).is_ok());
+        assert!(limiter.check("203.0.113.8".parse().unwrap(), now).is_ok());
+        assert!(limiter.check("203.0.113.7".parse().unwrap(), now).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_prunes_full_buckets() {
+        let limiter = RateLimiter::new(60, 5);
+        let now = Instant::now();
+        limiter.check("203.0.113.7".parse().unwrap(), now).unwrap();
+        limiter.prune(now);
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+        limiter.prune(now + Duration::from_secs(10));
+        assert!(limiter.buckets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cache_fresh_then_stale_then_miss() {
+        let cache = ResponseCache::new(Duration::from_secs(10), Duration::from_secs(5));
+        let now = Instant::now();
+        cache.store(
+            "/resolve/a.btc".into(),
+            None,
+            Bytes::from_static(b"{}"),
+            now,
+        );
+
+        assert!(matches!(
+            cache.lookup("/resolve/a.btc", now + Duration::from_secs(3)),
+            Lookup::Fresh(_, 7)
+        ));
+        // Only the first stale hit refreshes
+        assert!(matches!(
+            cache.lookup("/resolve/a.btc", now + Duration::from_secs(11)),
+            Lookup::Stale(_, true)
+        ));
+        assert!(matches!(
+            cache.lookup("/resolve/a.btc", now + Duration::from_secs(12)),
+            Lookup::Stale(_, false)
+        ));
+        assert!(matches!(
+            cache.lookup("/resolve/a.btc", now + Duration::from_secs(15)),
+            Lookup::Miss
+        ));
+    }
+
+    #[test]
+    fn test_cache_without_stale_window() {
+        let cache = ResponseCache::new(Duration::from_secs(10), Duration::ZERO);
+        let now = Instant::now();
+        cache.store("/stats".into(), None, Bytes::from_static(b"{}"), now);
+        assert!(matches!(
+            cache.lookup("/stats", now + Duration::from_secs(10)),
+            Lookup::Miss
+        ));
+        assert_eq!(cache.cache_control(10), "public, max-age=10");
+    }
+
+    #[test]
+    fn test_forwarded_ip_uses_last_hop() {
+        assert_eq!(
+            forwarded_ip("198.51.100.1, 203.0.113.7"),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_ip("2001:db8::1"),
+            Some("2001:db8::1".parse().unwrap())
+        );
+        assert_eq!(forwarded_ip("unknown"), None);
+    }
+}
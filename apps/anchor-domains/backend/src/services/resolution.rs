@@ -0,0 +1,228 @@
+//! Name matching for domain resolution
+//!
+//! A query such as `www.blog.example.btc` is answered by the most specific
+//! registration that covers it. Precedence, highest first:
+//!
+//! 1. **Exact** - a domain registered under the queried name.
+//! 2. **Record name** - named records (`record_name`, e.g. `user._nostr`)
+//!    under the longest registered ancestor that has records with the
+//!    queried label prefix.
+//! 3. **Wildcard** - a `*.parent` registration, searched from the longest
+//!    parent down. The search stops at the closest registered ancestor: a
+//!    registered `blog.example.btc` hides `*.example.btc` for names beneath
+//!    it, as wildcards do in DNS (RFC 4592).
+//!
+//! Anything else is not found; a subdomain never inherits its parent's
+//! records.
+
+use std::collections::HashSet;
+
+use crate::models::get_tld;
+
+/// How a query was matched to a registration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameMatch {
+    /// The queried name is itself registered
+    Exact(String),
+    /// Records named `record_name` under a registered `domain`
+    Record { domain: String, record_name: String },
+    /// A `*.parent` registration covers the queried name
+    Wildcard(String),
+}
+
+impl NameMatch {
+    /// Label reported as `matched_by` in resolve responses
+    pub fn kind(&self) -> Option<&'static str> {
+        match self {
+            NameMatch::Exact(_) => None,
+            NameMatch::Record { .. } => Some("record_name"),
+            NameMatch::Wildcard(_) => Some("wildcard"),
+        }
+    }
+}
+
+/// A registered ancestor of the query and the labels beneath it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ancestor {
+    pub name: String,
+    pub relative: String,
+}
+
+/// Names that can answer a query, for looking up in one round trip
+pub struct Candidates {
+    /// The lowercased query
+    pub query: String,
+    /// Proper ancestors, longest first (`blog.example.btc`, `example.btc`)
+    pub ancestors: Vec<Ancestor>,
+}
+
+impl Candidates {
+    /// Split a query into its ancestors
+    ///
+    /// Returns `None` for names without a supported TLD.
+    pub fn new(query: &str) -> Option<Self> {
+        let query = query.to_lowercase();
+        let tld = get_tld(&query)?;
+        let labels: Vec<&str> = query[..query.len() - tld.len()].split('.').collect();
+
+        let ancestors = (1..labels.len())
+            .map(|i| Ancestor {
+                name: format!("{}{}", labels[i..].join("."), tld),
+                relative: labels[..i].join("."),
+            })
+            .collect();
+
+        Some(Self { query, ancestors })
+    }
+
+    /// Every domain name whose registration could decide the match
+    pub fn domain_names(&self) -> Vec<String> {
+        let mut names = vec![self.query.clone()];
+        for ancestor in &self.ancestors {
+            names.push(ancestor.name.clone());
+            names.push(format!("*.{}", ancestor.name));
+        }
+        names
+    }
+
+    /// Pick the match according to the precedence rules
+    ///
+    /// `registered` holds the lowercased names from [`Self::domain_names`]
+    /// that exist; `named` holds lowercased `(domain, record_name)` pairs
+    /// with active records.
+    pub fn select(
+        &self,
+        registered: &HashSet<String>,
+        named: &HashSet<(String, String)>,
+    ) -> Option<NameMatch> {
+        if registered.contains(&self.query) {
+            return Some(NameMatch::Exact(self.query.clone()));
+        }
+
+        for ancestor in &self.ancestors {
+            if named.contains(&(ancestor.name.clone(), ancestor.relative.clone())) {
+                return Some(NameMatch::Record {
+                    domain: ancestor.name.clone(),
+                    record_name: ancestor.relative.clone(),
+                });
+            }
+        }
+
+        for ancestor in &self.ancestors {
+            let wildcard = format!("*.{}", ancestor.name);
+            if registered.contains(&wildcard) {
+                return Some(NameMatch::Wildcard(wildcard));
+            }
+            if registered.contains(&ancestor.name) {
+                break;
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn select(query: &str, registered: &[&str], named: &[(&str, &str)]) -> Option<NameMatch> {
+        let named = named
+            .iter()
+            .map(|(d, r)| (d.to_string(), r.to_string()))
+            .collect();
+        Candidates::new(query)
+            .unwrap()
+            .select(&set(registered), &named)
+    }
+
+    #[test]
+    fn test_candidates() {
+        let candidates = Candidates::new("WWW.Blog.example.btc").unwrap();
+        assert_eq!(candidates.query, "www.blog.example.btc");
+        assert_eq!(
+            candidates.ancestors,
+            vec![
+                Ancestor {
+                    name: "blog.example.btc".to_string(),
+                    relative: "www".to_string(),
+                },
+                Ancestor {
+                    name: "example.btc".to_string(),
+                    relative: "www.blog".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            candidates.domain_names(),
+            vec![
+                "www.blog.example.btc",
+                "blog.example.btc",
+                "*.blog.example.btc",
+                "example.btc",
+                "*.example.btc",
+            ]
+        );
+
+        assert!(Candidates::new("example.btc").unwrap().ancestors.is_empty());
+        assert!(Candidates::new("example.com").is_none());
+    }
+
+    #[test]
+    fn test_exact_beats_everything() {
+        let registered = ["a.example.btc", "*.example.btc", "example.btc"];
+        assert_eq!(
+            select("a.example.btc", &registered, &[("example.btc", "a")]),
+            Some(NameMatch::Exact("a.example.btc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_record_name_beats_wildcard() {
+        let registered = ["*.example.btc", "example.btc"];
+        assert_eq!(
+            select(
+                "user._nostr.example.btc",
+                &registered,
+                &[("example.btc", "user._nostr")]
+            ),
+            Some(NameMatch::Record {
+                domain: "example.btc".to_string(),
+                record_name: "user._nostr".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_longest_wildcard_wins() {
+        let registered = ["*.example.btc", "*.blog.example.btc"];
+        assert_eq!(
+            select("www.blog.example.btc", &registered, &[]),
+            Some(NameMatch::Wildcard("*.blog.example.btc".to_string()))
+        );
+        assert_eq!(
+            select("deep.www.blog.example.btc", &registered, &[]),
+            Some(NameMatch::Wildcard("*.blog.example.btc".to_string()))
+        );
+        assert_eq!(
+            select("shop.example.btc", &registered, &[]),
+            Some(NameMatch::Wildcard("*.example.btc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_registered_ancestor_blocks_wildcard() {
+        let registered = ["*.example.btc", "blog.example.btc"];
+        assert_eq!(select("www.blog.example.btc", &registered, &[]), None);
+    }
+
+    #[test]
+    fn test_no_inheritance_from_parent() {
+        assert_eq!(select("www.example.btc", &["example.btc"], &[]), None);
+        assert_eq!(select("example.btc", &["*.example.btc"], &[]), None);
+    }
+}
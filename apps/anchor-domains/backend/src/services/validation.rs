@@ -30,10 +30,16 @@ pub fn validate_txid_prefix(prefix: &str) -> AppResult<()> {
 }
 
 /// Convert and validate DNS record inputs to DnsRecord
-/// Returns an error if no valid records are provided
+/// Returns an error if no valid records are provided or a record is malformed
 pub fn validate_records(inputs: &[DnsRecordInput]) -> AppResult<Vec<DnsRecord>> {
     let records: Vec<DnsRecord> = inputs.iter().filter_map(|r| r.to_dns_record()).collect();
 
+    for record in &records {
+        record
+            .validate()
+            .map_err(|e| AppError::bad_request(e.to_string()))?;
+    }
+
     if records.is_empty() {
         return Err(AppError::bad_request(
             "At least one valid record is required",
@@ -56,6 +62,20 @@ pub fn parse_txid_list(txids_str: &str) -> AppResult<Vec<Vec<u8>>> {
     txids.map_err(|e| AppError::bad_request(format!("Invalid txid hex format: {}", e)))
 }
 
+/// Parse an `owned_by` filter into the output script it stands for
+///
+/// Accepts an address or a hex-encoded script.
+pub fn parse_owner_script(owner: &str) -> AppResult<Vec<u8>> {
+    let owner = owner.trim();
+    if let Ok(address) = owner.parse::<bitcoin::Address<bitcoin::address::NetworkUnchecked>>() {
+        return Ok(address.assume_checked().script_pubkey().to_bytes());
+    }
+    hex::decode(owner)
+        .ok()
+        .filter(|script| !script.is_empty())
+        .ok_or_else(|| AppError::bad_request("owned_by must be an address or a hex script"))
+}
+
 /// Parse a list of txids from a Vec<String>
 pub fn parse_txids(txids: &[String]) -> AppResult<Vec<Vec<u8>>> {
     let result: Result<Vec<Vec<u8>>, _> = txids.iter().map(hex::decode).collect();
@@ -89,9 +109,46 @@ mod tests {
         assert_eq!(result[1], vec![0xcc, 0xdd]);
     }
 
+    fn input(record_type: &str, value: &str) -> DnsRecordInput {
+        DnsRecordInput {
+            record_type: record_type.to_string(),
+            ttl: None,
+            value: value.to_string(),
+            priority: None,
+            weight: None,
+            port: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_records_security_types() {
+        let tlsa = input("TLSA", &format!("3 1 1 {}", "ab".repeat(32)));
+        let sshfp = input("sshfp", &format!("4 2 {}", "cd".repeat(32)));
+        let uri = DnsRecordInput {
+            priority: Some(10),
+            weight: Some(1),
+            ..input("URI", "https://example.btc/")
+        };
+        assert_eq!(validate_records(&[tlsa, sshfp, uri]).unwrap().len(), 3);
+
+        assert!(validate_records(&[input("TLSA", "3 1 1 abcd")]).is_err());
+        assert!(validate_records(&[input("URI", "https://example.btc/")]).is_err());
+    }
+
     #[test]
     fn test_validate_records_empty() {
         let inputs: Vec<DnsRecordInput> = vec![];
         assert!(validate_records(&inputs).is_err());
     }
+
+    #[test]
+    fn test_parse_owner_script() {
+        let address = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+        let script = parse_owner_script(address).unwrap();
+        assert_eq!(script[..2], [0x00, 0x14]);
+        assert_eq!(parse_owner_script(&hex::encode(&script)).unwrap(), script);
+
+        assert!(parse_owner_script("").is_err());
+        assert!(parse_owner_script("not-an-owner").is_err());
+    }
 }
@@ -3,6 +3,7 @@
 //! This module provides a client for communicating with the anchor-wallet service
 //! to create DNS transactions.
 
+use anchor_api_error::ApiError;
 use tracing::warn;
 
 use crate::error::{AppError, AppResult};
@@ -98,8 +99,9 @@ impl WalletClient {
             .map_err(|e| AppError::wallet_error(format!("Failed to call wallet service: {}", e)))?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::wallet_error(error_text));
+            return Err(ApiError::from_upstream(status, &error_text).into());
         }
 
         let wallet_response: serde_json::Value = response.json().await.map_err(|e| {
@@ -28,7 +28,7 @@ import {
   Zap,
 } from 'lucide-react';
 
-const RECORD_TYPES = ['A', 'AAAA', 'CNAME', 'TXT', 'MX', 'NS', 'SRV'];
+const RECORD_TYPES = ['A', 'AAAA', 'CNAME', 'TXT', 'MX', 'NS', 'SRV', 'TLSA', 'SSHFP', 'URI'];
 
 // DNS carriers that create spendable UTXOs for ownership tracking
 // OP_RETURN (0) is NOT allowed as it doesn't create spendable outputs
@@ -374,8 +374,10 @@ export default function ManageDomainPage({ params }: { params: Promise<{ name: s
                   </div>
                 </div>
 
-                {/* Extra fields for MX/SRV */}
-                {(record.record_type === 'MX' || record.record_type === 'SRV') && (
+                {/* Extra fields for MX/SRV/URI */}
+                {(record.record_type === 'MX' ||
+                  record.record_type === 'SRV' ||
+                  record.record_type === 'URI') && (
                   <div className="mt-4 pt-4 border-t border-slate-600/50 grid grid-cols-3 gap-4">
                     <div>
                       <label className="block text-xs text-slate-400 mb-1">Priority</label>
@@ -388,6 +390,19 @@ export default function ManageDomainPage({ params }: { params: Promise<{ name: s
                         className="w-full px-3 py-2 bg-slate-700 border border-slate-600 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-bitcoin-orange"
                       />
                     </div>
+                    {record.record_type === 'URI' && (
+                      <div>
+                        <label className="block text-xs text-slate-400 mb-1">Weight</label>
+                        <input
+                          type="number"
+                          value={record.weight ?? 0}
+                          onChange={(e) =>
+                            updateRecord(record.id, 'weight', parseInt(e.target.value) || 0)
+                          }
+                          className="w-full px-3 py-2 bg-slate-700 border border-slate-600 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-bitcoin-orange"
+                        />
+                      </div>
+                    )}
                     {record.record_type === 'SRV' && (
                       <>
                         <div>
@@ -25,7 +25,7 @@ import {
   Key,
 } from 'lucide-react';
 
-const RECORD_TYPES = ['A', 'AAAA', 'CNAME', 'TXT', 'MX', 'NS', 'SRV'];
+const RECORD_TYPES = ['A', 'AAAA', 'CNAME', 'TXT', 'MX', 'NS', 'SRV', 'TLSA', 'SSHFP', 'URI'];
 
 // DNS carriers that create spendable UTXOs for ownership tracking
 // OP_RETURN (0) is NOT allowed as it doesn't create spendable outputs
@@ -364,8 +364,10 @@ function RegisterPageContent() {
                       placeholder="TTL"
                     />
 
-                    {/* Priority (for MX/SRV) */}
-                    {(record.record_type === 'MX' || record.record_type === 'SRV') && (
+                    {/* Priority (for MX/SRV/URI) */}
+                    {(record.record_type === 'MX' ||
+                      record.record_type === 'SRV' ||
+                      record.record_type === 'URI') && (
                       <input
                         type="number"
                         value={record.priority || ''}
@@ -377,6 +379,19 @@ function RegisterPageContent() {
                       />
                     )}
 
+                    {/* Weight (for URI) */}
+                    {record.record_type === 'URI' && (
+                      <input
+                        type="number"
+                        value={record.weight ?? ''}
+                        onChange={(e) =>
+                          updateRecord(record.id, 'weight', parseInt(e.target.value))
+                        }
+                        className="w-20 px-3 py-2 bg-slate-700 border border-slate-600 rounded-lg text-white focus:outline-none focus:ring-2 focus:ring-bitcoin-orange"
+                        placeholder="Wt"
+                      />
+                    )}
+
                     {/* Delete */}
                     <button
                       type="button"
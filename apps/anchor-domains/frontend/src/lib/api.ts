@@ -31,6 +31,24 @@ if (typeof window !== 'undefined') {
   fetchDefaultExplorerUrl();
 }
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 // Get explorer transaction URL
 export function getExplorerTxUrl(txid: string): string {
   const baseUrl = cachedExplorerUrl || DEFAULT_BLOCK_EXPLORER_URL;
@@ -238,8 +256,7 @@ export async function registerDomain(
     body: JSON.stringify({ name, records, carrier }),
   });
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to register domain');
+    throw await apiError(res, 'Failed to register domain');
   }
   return res.json();
 }
@@ -255,8 +272,7 @@ export async function updateDomain(
     body: JSON.stringify({ records, carrier }),
   });
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to update domain');
+    throw await apiError(res, 'Failed to update domain');
   }
   return res.json();
 }
@@ -404,6 +420,11 @@ export function getRecordTypeColor(type: string): string {
       return 'bg-indigo-100 text-indigo-800';
     case 'SRV':
       return 'bg-pink-100 text-pink-800';
+    case 'TLSA':
+    case 'SSHFP':
+      return 'bg-orange-100 text-orange-800';
+    case 'URI':
+      return 'bg-teal-100 text-teal-800';
     default:
       return 'bg-gray-100 text-gray-800';
   }
@@ -40,6 +40,9 @@ export enum RecordType {
   MX = 5, // Mail exchange (priority u16 + domain)
   NS = 6, // Name server (domain string)
   SRV = 7, // Service record (priority u16, weight u16, port u16, target)
+  TLSA = 8, // TLS association (usage u8, selector u8, matching type u8, data)
+  SSHFP = 9, // SSH fingerprint (algorithm u8, fingerprint type u8, fingerprint)
+  URI = 10, // URI (priority u16, weight u16, target)
 }
 
 /**
@@ -61,6 +64,12 @@ export function getRecordTypeName(type: RecordType): string {
       return 'NS';
     case RecordType.SRV:
       return 'SRV';
+    case RecordType.TLSA:
+      return 'TLSA';
+    case RecordType.SSHFP:
+      return 'SSHFP';
+    case RecordType.URI:
+      return 'URI';
     default:
       return 'UNKNOWN';
   }
@@ -72,9 +81,9 @@ export function getRecordTypeName(type: RecordType): string {
 export interface DnsRecord {
   type: RecordType;
   ttl: number;
-  value: string;
-  priority?: number; // For MX, SRV
-  weight?: number; // For SRV
+  value: string; // TLSA/SSHFP use zone-file form, e.g. "3 1 1 <hex>"
+  priority?: number; // For MX, SRV, URI
+  weight?: number; // For SRV, URI
   port?: number; // For SRV
 }
 
@@ -207,6 +216,37 @@ export function encodeRecord(record: DnsRecord): Uint8Array {
       data.set(target, 6);
       break;
     }
+    case RecordType.TLSA:
+    case RecordType.SSHFP: {
+      // Numeric fields followed by hex data
+      const fieldCount = record.type === RecordType.TLSA ? 3 : 2;
+      const parts = record.value.trim().split(/\s+/);
+      if (parts.length !== fieldCount + 1) {
+        throw new Error(`Invalid ${getRecordTypeName(record.type)} value: ${record.value}`);
+      }
+      const hex = hexToBytes(parts[fieldCount]);
+      data = new Uint8Array(fieldCount + hex.length);
+      for (let i = 0; i < fieldCount; i++) {
+        const num = parseInt(parts[i], 10);
+        if (isNaN(num) || num < 0 || num > 255) {
+          throw new Error(`Invalid ${getRecordTypeName(record.type)} field: ${parts[i]}`);
+        }
+        data[i] = num;
+      }
+      data.set(hex, fieldCount);
+      break;
+    }
+    case RecordType.URI: {
+      const priority = record.priority ?? 0;
+      const weight = record.weight ?? 0;
+      const target = encoder.encode(record.value);
+      data = new Uint8Array(4 + target.length);
+      const view = new DataView(data.buffer);
+      view.setUint16(0, priority, false);
+      view.setUint16(2, weight, false);
+      data.set(target, 4);
+      break;
+    }
     default:
       throw new Error(`Unknown record type: ${record.type}`);
   }
@@ -275,6 +315,19 @@ export function decodeRecord(
       value = decoder.decode(data.slice(6));
       break;
     }
+    case RecordType.TLSA:
+      value = `${data[0]} ${data[1]} ${data[2]} ${bytesToHex(data.slice(3))}`;
+      break;
+    case RecordType.SSHFP:
+      value = `${data[0]} ${data[1]} ${bytesToHex(data.slice(2))}`;
+      break;
+    case RecordType.URI: {
+      const dataView = new DataView(data.buffer, data.byteOffset);
+      priority = dataView.getUint16(0, false);
+      weight = dataView.getUint16(2, false);
+      value = decoder.decode(data.slice(4));
+      break;
+    }
     default:
       value = bytesToHex(data);
   }
@@ -449,9 +502,10 @@ export function isValidDomainName(name: string): boolean {
     return false;
   }
 
-  // Only allow alphanumeric, hyphens, and dots (for subdomains)
-  // Cannot start or end with hyphen
-  const validPattern = /^[a-z0-9]([a-z0-9-]*[a-z0-9])?(\.[a-z0-9]([a-z0-9-]*[a-z0-9])?)*$/i;
+  // Only allow alphanumeric, hyphens, and dots (for subdomains), with an
+  // optional leading `*.` wildcard label. Cannot start or end with hyphen
+  const validPattern =
+    /^(\*\.)?[a-z0-9]([a-z0-9-]*[a-z0-9])?(\.[a-z0-9]([a-z0-9-]*[a-z0-9])?)*$/i;
   return validPattern.test(namePart);
 }
 
@@ -0,0 +1,70 @@
+-- Anchor Lottery Database Schema
+-- Commit-reveal lotteries (kinds 45-48), drawn from the hash of a block
+-- mined after ticket sales close.
+--
+-- Table names avoid lotteries/tickets/lottery_stats, which the predictions
+-- migrations drop when upgrading their old lottery schema.
+
+-- Lotteries, keyed by create txid (display byte order)
+CREATE TABLE IF NOT EXISTS lottery_games (
+    id SERIAL PRIMARY KEY,
+    lottery_id BYTEA NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    ticket_price_sats BIGINT NOT NULL CHECK (ticket_price_sats > 0),
+    max_tickets INTEGER NOT NULL DEFAULT 0, -- 0 = no limit
+    sales_end_height INTEGER NOT NULL,
+    draw_height INTEGER NOT NULL CHECK (draw_height > sales_end_height),
+    fee_bps INTEGER NOT NULL CHECK (fee_bps BETWEEN 0 AND 10000),
+    commitment BYTEA NOT NULL, -- SHA256 of the draw secret
+    operator_pubkey BYTEA NOT NULL,
+    pot_script BYTEA NOT NULL, -- ticket payments go here
+    status VARCHAR(16) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'drawn', 'void')),
+    tickets_sold INTEGER NOT NULL DEFAULT 0,
+    -- Set by the draw
+    secret BYTEA,
+    draw_seed BYTEA,
+    winning_ticket INTEGER, -- NULL when drawn with no tickets sold
+    draw_txid BYTEA,
+    drawn_at_height INTEGER,
+    created_at_height INTEGER NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    CHECK (status <> 'drawn' OR (secret IS NOT NULL AND draw_txid IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_lottery_games_status ON lottery_games(status);
+CREATE INDEX IF NOT EXISTS idx_lottery_games_draw_height ON lottery_games(draw_height)
+    WHERE status = 'open';
+
+-- Ticket purchases; a purchase of `count` tickets holds numbers
+-- first_number .. first_number + count - 1
+CREATE TABLE IF NOT EXISTS lottery_tickets (
+    id SERIAL PRIMARY KEY,
+    lottery_id BYTEA NOT NULL REFERENCES lottery_games(lottery_id),
+    txid BYTEA NOT NULL UNIQUE,
+    block_height INTEGER NOT NULL,
+    buyer_pubkey BYTEA NOT NULL,
+    first_number INTEGER NOT NULL,
+    count INTEGER NOT NULL CHECK (count > 0),
+    paid_sats BIGINT NOT NULL,
+    -- Set by the claim
+    claim_txid BYTEA,
+    claimed_sats BIGINT,
+    claimed_at_height INTEGER,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (lottery_id, first_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_lottery_tickets_lottery ON lottery_tickets(lottery_id, first_number);
+CREATE INDEX IF NOT EXISTS idx_lottery_tickets_buyer ON lottery_tickets(buyer_pubkey);
+
+-- Indexer state
+CREATE TABLE IF NOT EXISTS lottery_indexer_state (
+    id INTEGER PRIMARY KEY DEFAULT 1,
+    last_block_hash BYTEA,
+    last_block_height INTEGER DEFAULT 0,
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+INSERT INTO lottery_indexer_state (id, last_block_height) VALUES (1, 0)
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,437 @@
+//! Database operations for Anchor Lottery
+
+use anchor_specs::lottery::LotteryCreateSpec;
+use anyhow::Result;
+use sqlx::postgres::{PgPoolOptions, PgRow};
+use sqlx::{PgPool, Row};
+
+use crate::models::{Lottery, LotteryStats, Ticket};
+
+/// Indexed state of a lottery, as the indexer needs it
+pub struct GameState {
+    pub spec: LotteryCreateSpec,
+    pub status: String,
+    pub tickets_sold: i32,
+    pub winning_ticket: Option<i32>,
+}
+
+/// Indexed ticket purchase, as the indexer needs it
+pub struct TicketState {
+    pub lottery_id: Vec<u8>,
+    pub first_number: i32,
+    pub count: i32,
+    pub claimed: bool,
+}
+
+impl TicketState {
+    /// Whether the purchase holds ticket `number`
+    pub fn holds(&self, number: i32) -> bool {
+        number >= self.first_number && number < self.first_number + self.count
+    }
+}
+
+const LOTTERY_COLUMNS: &str = r#"
+    id, lottery_id, title, ticket_price_sats, max_tickets, sales_end_height,
+    draw_height, fee_bps, commitment, operator_pubkey, status, tickets_sold,
+    secret, draw_seed, winning_ticket, draw_txid, drawn_at_height,
+    created_at_height, created_at
+"#;
+
+pub struct Database {
+    pub pool: PgPool,
+}
+
+impl Database {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect(database_url)
+            .await?;
+        Ok(Self { pool })
+    }
+
+    // ==================== Stats ====================
+
+    pub async fn get_stats(&self) -> Result<LotteryStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM lottery_games) AS total_lotteries,
+                (SELECT COUNT(*) FROM lottery_games WHERE status = 'open') AS open_lotteries,
+                (SELECT COUNT(*) FROM lottery_games WHERE status = 'drawn') AS drawn_lotteries,
+                (SELECT COUNT(*) FROM lottery_games WHERE status = 'void') AS void_lotteries,
+                (SELECT COALESCE(SUM(count), 0)::BIGINT FROM lottery_tickets) AS total_tickets_sold,
+                (SELECT COALESCE(SUM(paid_sats), 0)::BIGINT FROM lottery_tickets) AS total_volume_sats,
+                (SELECT COALESCE(SUM(claimed_sats), 0)::BIGINT FROM lottery_tickets) AS total_claimed_sats
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(LotteryStats {
+            total_lotteries: row.get("total_lotteries"),
+            open_lotteries: row.get("open_lotteries"),
+            drawn_lotteries: row.get("drawn_lotteries"),
+            void_lotteries: row.get("void_lotteries"),
+            total_tickets_sold: row.get("total_tickets_sold"),
+            total_volume_sats: row.get("total_volume_sats"),
+            total_claimed_sats: row.get("total_claimed_sats"),
+        })
+    }
+
+    // ==================== Lotteries ====================
+
+    pub async fn list_lotteries(&self, status: Option<&str>, limit: i32) -> Result<Vec<Lottery>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM lottery_games
+            WHERE $1::text IS NULL OR status = $1
+            ORDER BY created_at_height DESC, id DESC
+            LIMIT $2
+            "#,
+            LOTTERY_COLUMNS
+        ))
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(row_to_lottery).collect())
+    }
+
+    pub async fn get_lottery(&self, lottery_id: &str) -> Result<Option<Lottery>> {
+        let lottery_id = hex::decode(lottery_id)?;
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM lottery_games WHERE lottery_id = $1",
+            LOTTERY_COLUMNS
+        ))
+        .bind(&lottery_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(row_to_lottery))
+    }
+
+    pub async fn get_lottery_tickets(&self, lottery_id: &str, limit: i32) -> Result<Vec<Ticket>> {
+        let lottery_id = hex::decode(lottery_id)?;
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.lottery_id, t.txid, t.block_height, t.buyer_pubkey,
+                   t.first_number, t.count, t.paid_sats, t.claim_txid,
+                   t.claimed_sats, t.claimed_at_height,
+                   COALESCE(g.winning_ticket >= t.first_number
+                            AND g.winning_ticket < t.first_number + t.count, false) AS is_winner
+            FROM lottery_tickets t
+            JOIN lottery_games g ON g.lottery_id = t.lottery_id
+            WHERE t.lottery_id = $1
+            ORDER BY t.first_number
+            LIMIT $2
+            "#,
+        )
+        .bind(&lottery_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(row_to_ticket).collect())
+    }
+
+    // ==================== Indexer Writes ====================
+
+    /// Record a new lottery; false if it was already indexed
+    pub async fn insert_lottery(
+        &self,
+        lottery_id: &[u8],
+        spec: &LotteryCreateSpec,
+        pot_script: &[u8],
+        height: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO lottery_games (
+                lottery_id, title, ticket_price_sats, max_tickets, sales_end_height,
+                draw_height, fee_bps, commitment, operator_pubkey, pot_script,
+                created_at_height
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (lottery_id) DO NOTHING
+            "#,
+        )
+        .bind(lottery_id)
+        .bind(&spec.title)
+        .bind(spec.ticket_price.min(i64::MAX as u64) as i64)
+        .bind(spec.max_tickets.min(i32::MAX as u32) as i32)
+        .bind(spec.sales_end_height as i32)
+        .bind(spec.draw_height as i32)
+        .bind(spec.fee_bps as i32)
+        .bind(&spec.commitment[..])
+        .bind(&spec.operator)
+        .bind(pot_script)
+        .bind(height)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_game(&self, lottery_id: &[u8]) -> Result<Option<GameState>> {
+        let row = sqlx::query(
+            r#"
+            SELECT title, ticket_price_sats, max_tickets, sales_end_height, draw_height,
+                   fee_bps, commitment, operator_pubkey, status, tickets_sold, winning_ticket
+            FROM lottery_games
+            WHERE lottery_id = $1
+            "#,
+        )
+        .bind(lottery_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let commitment: Vec<u8> = row.get("commitment");
+        let Ok(commitment) = commitment.try_into() else {
+            return Ok(None);
+        };
+        Ok(Some(GameState {
+            spec: LotteryCreateSpec {
+                ticket_price: row.get::<i64, _>("ticket_price_sats") as u64,
+                max_tickets: row.get::<i32, _>("max_tickets") as u32,
+                sales_end_height: row.get::<i32, _>("sales_end_height") as u32,
+                draw_height: row.get::<i32, _>("draw_height") as u32,
+                fee_bps: row.get::<i32, _>("fee_bps") as u16,
+                commitment,
+                operator: row.get("operator_pubkey"),
+                title: row.get("title"),
+            },
+            status: row.get("status"),
+            tickets_sold: row.get("tickets_sold"),
+            winning_ticket: row.get("winning_ticket"),
+        }))
+    }
+
+    /// Record a ticket purchase, numbering its tickets after those sold so far
+    ///
+    /// Returns the first ticket number, or `None` if the purchase was already
+    /// indexed, the lottery isn't open or it would pass `max_tickets`.
+    pub async fn add_ticket(
+        &self,
+        lottery_id: &[u8],
+        txid: &[u8],
+        height: i32,
+        buyer_pubkey: &[u8],
+        count: i32,
+        paid_sats: i64,
+    ) -> Result<Option<i32>> {
+        let mut tx = self.pool.begin().await?;
+
+        let indexed = sqlx::query("SELECT 1 FROM lottery_tickets WHERE txid = $1")
+            .bind(txid)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if indexed.is_some() {
+            return Ok(None);
+        }
+
+        let first_number: Option<i32> = sqlx::query_scalar(
+            r#"
+            UPDATE lottery_games
+            SET tickets_sold = tickets_sold + $2
+            WHERE lottery_id = $1
+              AND status = 'open'
+              AND (max_tickets = 0 OR tickets_sold + $2 <= max_tickets)
+            RETURNING tickets_sold - $2
+            "#,
+        )
+        .bind(lottery_id)
+        .bind(count)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(first_number) = first_number else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO lottery_tickets (
+                lottery_id, txid, block_height, buyer_pubkey, first_number, count, paid_sats
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(lottery_id)
+        .bind(txid)
+        .bind(height)
+        .bind(buyer_pubkey)
+        .bind(first_number)
+        .bind(count)
+        .bind(paid_sats)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(first_number))
+    }
+
+    /// Record the reveal of an open lottery; false if it was no longer open
+    pub async fn record_draw(
+        &self,
+        lottery_id: &[u8],
+        secret: &[u8],
+        draw_seed: &[u8],
+        winning_ticket: Option<i32>,
+        draw_txid: &[u8],
+        height: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE lottery_games
+            SET status = 'drawn', secret = $2, draw_seed = $3, winning_ticket = $4,
+                draw_txid = $5, drawn_at_height = $6
+            WHERE lottery_id = $1 AND status = 'open'
+            "#,
+        )
+        .bind(lottery_id)
+        .bind(secret)
+        .bind(draw_seed)
+        .bind(winning_ticket)
+        .bind(draw_txid)
+        .bind(height)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Void open lotteries whose reveal window closed before `height`
+    pub async fn void_unrevealed(&self, height: i32, reveal_window: i32) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE lottery_games
+            SET status = 'void'
+            WHERE status = 'open' AND draw_height + $2 < $1
+            "#,
+        )
+        .bind(height)
+        .bind(reveal_window)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get_ticket(&self, txid: &[u8]) -> Result<Option<TicketState>> {
+        let row = sqlx::query(
+            r#"
+            SELECT lottery_id, first_number, count, claim_txid IS NOT NULL AS claimed
+            FROM lottery_tickets
+            WHERE txid = $1
+            "#,
+        )
+        .bind(txid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| TicketState {
+            lottery_id: row.get("lottery_id"),
+            first_number: row.get("first_number"),
+            count: row.get("count"),
+            claimed: row.get("claimed"),
+        }))
+    }
+
+    /// Record a prize or refund claim; false if the ticket was already claimed
+    pub async fn record_claim(
+        &self,
+        ticket_txid: &[u8],
+        claim_txid: &[u8],
+        claimed_sats: i64,
+        height: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE lottery_tickets
+            SET claim_txid = $2, claimed_sats = $3, claimed_at_height = $4
+            WHERE txid = $1 AND claim_txid IS NULL
+            "#,
+        )
+        .bind(ticket_txid)
+        .bind(claim_txid)
+        .bind(claimed_sats)
+        .bind(height)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ==================== Indexer State ====================
+
+    pub async fn get_last_block_height(&self) -> Result<i32> {
+        let row = sqlx::query("SELECT last_block_height FROM lottery_indexer_state WHERE id = 1")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.get("last_block_height"))
+    }
+
+    pub async fn update_last_block(&self, block_hash: &[u8], height: i32) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE lottery_indexer_state SET
+                last_block_hash = $1,
+                last_block_height = $2,
+                updated_at = NOW()
+            WHERE id = 1
+            "#,
+        )
+        .bind(block_hash)
+        .bind(height)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn row_to_lottery(row: &PgRow) -> Lottery {
+    let ticket_price_sats: i64 = row.get("ticket_price_sats");
+    let tickets_sold: i32 = row.get("tickets_sold");
+    let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+
+    Lottery {
+        id: row.get("id"),
+        lottery_id: hex::encode(row.get::<Vec<u8>, _>("lottery_id")),
+        title: row.get("title"),
+        ticket_price_sats,
+        max_tickets: row.get("max_tickets"),
+        sales_end_height: row.get("sales_end_height"),
+        draw_height: row.get("draw_height"),
+        fee_bps: row.get("fee_bps"),
+        commitment: hex::encode(row.get::<Vec<u8>, _>("commitment")),
+        operator_pubkey: hex::encode(row.get::<Vec<u8>, _>("operator_pubkey")),
+        status: row.get("status"),
+        tickets_sold,
+        pot_sats: ticket_price_sats.saturating_mul(tickets_sold as i64),
+        secret: row.get::<Option<Vec<u8>>, _>("secret").map(hex::encode),
+        draw_seed: row.get::<Option<Vec<u8>>, _>("draw_seed").map(hex::encode),
+        winning_ticket: row.get("winning_ticket"),
+        draw_txid: row.get::<Option<Vec<u8>>, _>("draw_txid").map(hex::encode),
+        drawn_at_height: row.get("drawn_at_height"),
+        created_at_height: row.get("created_at_height"),
+        created_at: created_at.to_rfc3339(),
+    }
+}
+
+fn row_to_ticket(row: &PgRow) -> Ticket {
+    Ticket {
+        id: row.get("id"),
+        lottery_id: hex::encode(row.get::<Vec<u8>, _>("lottery_id")),
+        txid: hex::encode(row.get::<Vec<u8>, _>("txid")),
+        block_height: row.get("block_height"),
+        buyer_pubkey: hex::encode(row.get::<Vec<u8>, _>("buyer_pubkey")),
+        first_number: row.get("first_number"),
+        count: row.get("count"),
+        paid_sats: row.get("paid_sats"),
+        is_winner: row.get("is_winner"),
+        claim_txid: row.get::<Option<Vec<u8>>, _>("claim_txid").map(hex::encode),
+        claimed_sats: row.get("claimed_sats"),
+        claimed_at_height: row.get("claimed_at_height"),
+    }
+}
@@ -0,0 +1,144 @@
+//! HTTP API handlers for Anchor Lottery
+
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::models::*;
+
+pub type AppState = Arc<Database>;
+
+/// Lottery ids and txids are 32 bytes of hex
+fn check_id(id: &str) -> Result<(), ApiError> {
+    match hex::decode(id) {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        _ => Err(ApiError::bad_request(
+            "Lottery ID must be a 32-byte hex txid",
+        )),
+    }
+}
+
+// ==================== Health ====================
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service is healthy")
+    ),
+    tag = "health"
+)]
+pub async fn health() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+// ==================== Stats ====================
+
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    responses(
+        (status = 200, description = "Lottery statistics", body = LotteryStats)
+    ),
+    tag = "stats"
+)]
+pub async fn get_stats(State(db): State<AppState>) -> impl IntoResponse {
+    match db.get_stats().await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+// ==================== Lotteries ====================
+
+#[derive(Deserialize)]
+pub struct ListLotteriesQuery {
+    pub status: Option<String>,
+    pub limit: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/lotteries",
+    params(
+        ("status" = Option<String>, Query, description = "Filter by status: open, drawn, void"),
+        ("limit" = Option<i32>, Query, description = "Max results to return")
+    ),
+    responses(
+        (status = 200, description = "List of lotteries", body = Vec<Lottery>)
+    ),
+    tag = "lotteries"
+)]
+pub async fn list_lotteries(
+    State(db): State<AppState>,
+    Query(params): Query<ListLotteriesQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    match db.list_lotteries(params.status.as_deref(), limit).await {
+        Ok(lotteries) => Json(lotteries).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/lotteries/{id}",
+    params(
+        ("id" = String, Path, description = "Lottery ID (create txid, hex)")
+    ),
+    responses(
+        (status = 200, description = "Lottery details", body = Lottery),
+        (status = 400, description = "Invalid lottery ID"),
+        (status = 404, description = "Lottery not found")
+    ),
+    tag = "lotteries"
+)]
+pub async fn get_lottery(State(db): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    if let Err(e) = check_id(&id) {
+        return e.into_response();
+    }
+    match db.get_lottery(&id).await {
+        Ok(Some(lottery)) => Json(lottery).into_response(),
+        Ok(None) => ApiError::not_found("Lottery not found").into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListTicketsQuery {
+    pub limit: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/lotteries/{id}/tickets",
+    params(
+        ("id" = String, Path, description = "Lottery ID (create txid, hex)"),
+        ("limit" = Option<i32>, Query, description = "Max results to return")
+    ),
+    responses(
+        (status = 200, description = "Ticket purchases in ticket number order", body = Vec<Ticket>),
+        (status = 400, description = "Invalid lottery ID")
+    ),
+    tag = "lotteries"
+)]
+pub async fn get_lottery_tickets(
+    State(db): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<ListTicketsQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = check_id(&id) {
+        return e.into_response();
+    }
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    match db.get_lottery_tickets(&id, limit).await {
+        Ok(tickets) => Json(tickets).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
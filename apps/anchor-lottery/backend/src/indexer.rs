@@ -0,0 +1,431 @@
+//! Indexer for Anchor Lottery messages from the blockchain
+
+use anchor_core::{carrier::CarrierSelector, AnchorKind};
+use anchor_specs::lottery::{
+    reveal_window_open, winning_ticket, LotteryClaimSpec, LotteryCreateSpec, LotteryDrawSpec,
+    LotteryTicketSpec,
+};
+use anchor_specs::KindSpec;
+use anyhow::Result;
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::hashes::Hash;
+use bitcoin::{Block, Script, Transaction};
+use bitcoincore_rpc::{Auth, Client, RpcApi};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+use crate::config::Config;
+use crate::db::{Database, GameState, TicketState};
+
+pub struct Indexer {
+    db: Arc<Database>,
+    rpc: Client,
+    carrier_selector: CarrierSelector,
+    reveal_window_blocks: u32,
+}
+
+/// Convert a txid to display byte order, as stored in the database
+fn display_txid(txid: &bitcoin::Txid) -> Vec<u8> {
+    let mut bytes = txid.to_byte_array();
+    bytes.reverse();
+    bytes.to_vec()
+}
+
+/// Satoshis `tx` pays to `script`
+fn paid_to(tx: &Transaction, script: &Script) -> u64 {
+    tx.output
+        .iter()
+        .filter(|output| output.script_pubkey.as_script() == script)
+        .map(|output| output.value.to_sat())
+        .sum()
+}
+
+/// Satoshis a ticket purchase claims: the prize if it holds the winning
+/// number of a drawn lottery, its price back if the lottery is void
+fn claim_amount(game: &GameState, ticket: &TicketState) -> Option<u64> {
+    match game.status.as_str() {
+        "drawn" => game
+            .winning_ticket
+            .filter(|&number| ticket.holds(number))
+            .map(|_| game.spec.prize(game.tickets_sold as u32)),
+        "void" => Some(game.spec.pot(ticket.count as u32)),
+        _ => None,
+    }
+}
+
+impl Indexer {
+    pub fn new(config: &Config, db: Arc<Database>) -> Result<Self> {
+        let rpc = Client::new(
+            &config.bitcoin_rpc_url,
+            Auth::UserPass(
+                config.bitcoin_rpc_user.clone(),
+                config.bitcoin_rpc_password.clone(),
+            ),
+        )?;
+
+        Ok(Self {
+            db,
+            rpc,
+            carrier_selector: CarrierSelector::new(),
+            reveal_window_blocks: config.reveal_window_blocks,
+        })
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        tracing::info!("Starting Lottery indexer");
+
+        loop {
+            if let Err(e) = self.sync_blocks().await {
+                tracing::error!("Indexer sync error: {}", e);
+            }
+            sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn sync_blocks(&self) -> Result<()> {
+        let chain_height = self.rpc.get_block_count()? as i32;
+        let mut last_height = self.db.get_last_block_height().await?;
+
+        while last_height < chain_height {
+            let target_height = last_height + 1;
+            let block_hash = self.rpc.get_block_hash(target_height as u64)?;
+            let block_hex = self.rpc.get_block_hex(&block_hash)?;
+            let block_bytes = hex::decode(&block_hex)?;
+            let block: Block = deserialize(&block_bytes)?;
+
+            self.process_block(&block, target_height).await?;
+            self.db
+                .update_last_block(&block_hash[..], target_height)
+                .await?;
+
+            last_height = target_height;
+            if target_height % 100 == 0 {
+                tracing::info!("Indexed block {}/{}", target_height, chain_height);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process_block(&self, block: &Block, height: i32) -> Result<()> {
+        for tx in &block.txdata {
+            self.process_transaction(tx, height).await?;
+        }
+
+        // Draws in this block were applied first, so what's still open has missed its window
+        let voided = self
+            .db
+            .void_unrevealed(height, self.reveal_window_blocks as i32)
+            .await?;
+        if voided > 0 {
+            tracing::info!(
+                "Voided {} unrevealed lottery(s) at block {}",
+                voided,
+                height
+            );
+        }
+        Ok(())
+    }
+
+    async fn process_transaction(&self, tx: &Transaction, height: i32) -> Result<()> {
+        let txid = display_txid(&tx.compute_txid());
+
+        for detected in self.carrier_selector.detect(tx) {
+            let msg = detected.message;
+            let body = &msg.body;
+            match msg.kind {
+                AnchorKind::LotteryCreate => {
+                    if let Ok(spec) = LotteryCreateSpec::from_bytes(body) {
+                        self.index_create(&txid, spec, height).await?;
+                    }
+                }
+                AnchorKind::LotteryTicket => {
+                    if let Ok(spec) = LotteryTicketSpec::from_bytes(body) {
+                        self.index_ticket(tx, &txid, spec, height).await?;
+                    }
+                }
+                AnchorKind::LotteryDraw => {
+                    if let Ok(spec) = LotteryDrawSpec::from_bytes(body) {
+                        self.index_draw(&txid, spec, height).await?;
+                    }
+                }
+                AnchorKind::LotteryClaim => {
+                    if let Ok(spec) = LotteryClaimSpec::from_bytes(body) {
+                        self.index_claim(tx, &txid, spec, height).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn index_create(&self, txid: &[u8], spec: LotteryCreateSpec, height: i32) -> Result<()> {
+        if let Err(e) = spec.validate() {
+            tracing::debug!("Ignoring lottery {}: {}", hex::encode(txid), e);
+            return Ok(());
+        }
+        // A lottery confirmed after its sales end could never sell a ticket
+        if !spec.sales_open(height as u32) {
+            tracing::debug!(
+                "Ignoring lottery {}: sales already closed",
+                hex::encode(txid)
+            );
+            return Ok(());
+        }
+        let pot_script = spec.pot_script()?;
+
+        if self
+            .db
+            .insert_lottery(txid, &spec, pot_script.as_bytes(), height)
+            .await?
+        {
+            tracing::info!(
+                "Indexed lottery {}: \"{}\" ({} sats/ticket, draw at {})",
+                hex::encode(&txid[..8]),
+                spec.title.chars().take(50).collect::<String>(),
+                spec.ticket_price,
+                spec.draw_height
+            );
+        }
+        Ok(())
+    }
+
+    async fn index_ticket(
+        &self,
+        tx: &Transaction,
+        txid: &[u8],
+        spec: LotteryTicketSpec,
+        height: i32,
+    ) -> Result<()> {
+        if spec.validate().is_err() {
+            return Ok(());
+        }
+        let Some(game) = self.db.get_game(&spec.lottery_id).await? else {
+            return Ok(());
+        };
+        if game.status != "open" || !game.spec.sales_open(height as u32) {
+            tracing::debug!("Ignoring ticket {}: sales closed", hex::encode(&txid[..8]));
+            return Ok(());
+        }
+
+        let price = game.spec.pot(spec.count as u32);
+        let paid = paid_to(tx, &game.spec.pot_script()?);
+        if paid < price {
+            tracing::debug!(
+                "Ignoring ticket {}: paid {} of {} sats to the pot",
+                hex::encode(&txid[..8]),
+                paid,
+                price
+            );
+            return Ok(());
+        }
+
+        if let Some(first_number) = self
+            .db
+            .add_ticket(
+                &spec.lottery_id,
+                txid,
+                height,
+                &spec.buyer,
+                spec.count as i32,
+                paid.min(i64::MAX as u64) as i64,
+            )
+            .await?
+        {
+            tracing::info!(
+                "Indexed {} ticket(s) #{} on lottery {}",
+                spec.count,
+                first_number,
+                hex::encode(&spec.lottery_id[..8])
+            );
+        }
+        Ok(())
+    }
+
+    async fn index_draw(&self, txid: &[u8], spec: LotteryDrawSpec, height: i32) -> Result<()> {
+        let Some(game) = self.db.get_game(&spec.lottery_id).await? else {
+            return Ok(());
+        };
+        if game.status != "open"
+            || !reveal_window_open(
+                game.spec.draw_height,
+                height as u32,
+                self.reveal_window_blocks,
+            )
+        {
+            tracing::debug!(
+                "Ignoring draw {}: outside the reveal window",
+                hex::encode(&txid[..8])
+            );
+            return Ok(());
+        }
+        if !game.spec.verify_secret(&spec.secret) {
+            tracing::debug!(
+                "Ignoring draw {}: secret does not match the commitment",
+                hex::encode(&txid[..8])
+            );
+            return Ok(());
+        }
+
+        // The draw block is buried by the time the reveal confirms
+        let block_hash = self.rpc.get_block_hash(game.spec.draw_height as u64)?;
+        let seed = spec.seed(&block_hash.to_byte_array());
+        let winner = winning_ticket(&seed, game.tickets_sold as u32).map(|n| n as i32);
+
+        if self
+            .db
+            .record_draw(&spec.lottery_id, &spec.secret, &seed, winner, txid, height)
+            .await?
+        {
+            tracing::info!(
+                "Drew lottery {}: ticket {:?} of {}",
+                hex::encode(&spec.lottery_id[..8]),
+                winner,
+                game.tickets_sold
+            );
+        }
+        Ok(())
+    }
+
+    async fn index_claim(
+        &self,
+        tx: &Transaction,
+        txid: &[u8],
+        spec: LotteryClaimSpec,
+        height: i32,
+    ) -> Result<()> {
+        if spec.validate().is_err() {
+            return Ok(());
+        }
+        // Only the buyer can spend an output of the ticket transaction
+        let spends_ticket = tx
+            .input
+            .iter()
+            .any(|input| display_txid(&input.previous_output.txid) == spec.ticket_txid);
+        if !spends_ticket {
+            tracing::debug!(
+                "Ignoring claim {}: does not spend the ticket transaction",
+                hex::encode(&txid[..8])
+            );
+            return Ok(());
+        }
+
+        let Some(ticket) = self.db.get_ticket(&spec.ticket_txid).await? else {
+            return Ok(());
+        };
+        if ticket.claimed || ticket.lottery_id != spec.lottery_id {
+            return Ok(());
+        }
+        let Some(game) = self.db.get_game(&spec.lottery_id).await? else {
+            return Ok(());
+        };
+        let Some(amount) = claim_amount(&game, &ticket) else {
+            tracing::debug!(
+                "Ignoring claim {}: ticket neither won nor refundable",
+                hex::encode(&txid[..8])
+            );
+            return Ok(());
+        };
+
+        if self
+            .db
+            .record_claim(
+                &spec.ticket_txid,
+                txid,
+                amount.min(i64::MAX as u64) as i64,
+                height,
+            )
+            .await?
+        {
+            tracing::info!(
+                "Indexed {} claim of {} sats on lottery {}",
+                game.status,
+                amount,
+                hex::encode(&spec.lottery_id[..8])
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_specs::lottery::commitment;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::{absolute::LockTime, transaction::Version, Amount, ScriptBuf, TxOut};
+
+    fn game(status: &str, winning_ticket: Option<i32>) -> GameState {
+        let operator = SecretKey::from_slice(&[1u8; 32])
+            .unwrap()
+            .public_key(&Secp256k1::new())
+            .serialize()
+            .to_vec();
+        GameState {
+            spec: LotteryCreateSpec {
+                ticket_price: 1_000,
+                max_tickets: 0,
+                sales_end_height: 100,
+                draw_height: 106,
+                fee_bps: 500,
+                commitment: commitment(&[7u8; 32]),
+                operator,
+                title: "Test".to_string(),
+            },
+            status: status.to_string(),
+            tickets_sold: 10,
+            winning_ticket,
+        }
+    }
+
+    fn ticket(first_number: i32, count: i32) -> TicketState {
+        TicketState {
+            lottery_id: vec![2u8; 32],
+            first_number,
+            count,
+            claimed: false,
+        }
+    }
+
+    #[test]
+    fn test_claim_amount() {
+        // Ticket 4 is in the purchase of numbers 3..=5
+        let drawn = game("drawn", Some(4));
+        assert_eq!(claim_amount(&drawn, &ticket(3, 3)), Some(9_500));
+        assert_eq!(claim_amount(&drawn, &ticket(5, 2)), None);
+        assert_eq!(claim_amount(&drawn, &ticket(0, 4)), None);
+
+        // Drawn with nothing sold
+        assert_eq!(claim_amount(&game("drawn", None), &ticket(0, 1)), None);
+
+        // Void lotteries refund each purchase at face value
+        assert_eq!(
+            claim_amount(&game("void", None), &ticket(3, 3)),
+            Some(3_000)
+        );
+        assert_eq!(claim_amount(&game("open", None), &ticket(3, 3)), None);
+    }
+
+    #[test]
+    fn test_paid_to_sums_pot_outputs() {
+        let pot = game("open", None).spec.pot_script().unwrap();
+        let output = |script: &ScriptBuf, sats| TxOut {
+            value: Amount::from_sat(sats),
+            script_pubkey: script.clone(),
+        };
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![
+                output(&pot, 1_500),
+                output(&ScriptBuf::new_op_return([1u8; 4]), 0),
+                output(&pot, 500),
+                output(&ScriptBuf::new(), 9_000),
+            ],
+        };
+        assert_eq!(paid_to(&tx, &pot), 2_000);
+    }
+}
@@ -0,0 +1,112 @@
+//! Anchor Lottery Backend
+//! Commit-reveal lotteries drawn from block hashes
+
+mod config;
+mod db;
+mod handlers;
+mod indexer;
+mod models;
+
+use anchor_api_security::SecurityConfig;
+use axum::{middleware, routing::get, Router};
+use std::sync::Arc;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::config::Config;
+use crate::db::Database;
+use crate::handlers::*;
+use crate::indexer::Indexer;
+use crate::models::*;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        health,
+        get_stats,
+        list_lotteries,
+        get_lottery,
+        get_lottery_tickets,
+    ),
+    components(schemas(Lottery, Ticket, LotteryStats)),
+    tags(
+        (name = "health", description = "Service health"),
+        (name = "stats", description = "Lottery statistics"),
+        (name = "lotteries", description = "Lotteries and their tickets"),
+    ),
+    info(
+        title = "Anchor Lottery API",
+        version = "1.0.0",
+        description = "Commit-reveal lotteries on Bitcoin"
+    )
+)]
+struct ApiDoc;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
+    // Initialize tracing
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "anchor_lottery_backend=info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    // Load configuration
+    let config = Config::from_env();
+    tracing::info!(
+        "Starting Anchor Lottery backend on {}:{}",
+        config.host,
+        config.port
+    );
+
+    // Connect to database
+    let db = Arc::new(Database::connect(&config.database_url).await?);
+    tracing::info!("Connected to database");
+
+    // Start indexer in background
+    let indexer_db = db.clone();
+    let indexer_config = config.clone();
+    tokio::spawn(async move {
+        match Indexer::new(&indexer_config, indexer_db) {
+            Ok(indexer) => {
+                if let Err(e) = indexer.run().await {
+                    tracing::error!("Indexer error: {}", e);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Could not start indexer: {}", e);
+            }
+        }
+    });
+
+    // Build router
+    let app = Router::new()
+        // Health check
+        .route("/health", get(health))
+        // Stats
+        .route("/api/stats", get(get_stats))
+        // Lotteries
+        .route("/api/lotteries", get(list_lotteries))
+        .route("/api/lotteries/:id", get(get_lottery))
+        .route("/api/lotteries/:id/tickets", get(get_lottery_tickets))
+        // Swagger UI
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer())
+        .with_state(db);
+
+    // Start server
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!("Listening on {}", addr);
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Data models for Anchor Lottery
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Commit-reveal lottery
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Lottery {
+    pub id: i32,
+    /// Create txid
+    pub lottery_id: String,
+    pub title: String,
+    pub ticket_price_sats: i64,
+    /// 0 for no limit
+    pub max_tickets: i32,
+    /// Last block in which tickets are sold
+    pub sales_end_height: i32,
+    /// Block whose hash seeds the draw
+    pub draw_height: i32,
+    pub fee_bps: i32,
+    /// SHA256 of the draw secret, hex
+    pub commitment: String,
+    pub operator_pubkey: String,
+    /// `open`, `drawn` or `void`
+    pub status: String,
+    pub tickets_sold: i32,
+    /// Ticket payments so far
+    pub pot_sats: i64,
+    /// Revealed secret, hex
+    pub secret: Option<String>,
+    /// `SHA256(secret || draw block hash)`, hex
+    pub draw_seed: Option<String>,
+    /// Winning ticket number, once drawn with tickets sold
+    pub winning_ticket: Option<i32>,
+    pub draw_txid: Option<String>,
+    pub drawn_at_height: Option<i32>,
+    pub created_at_height: i32,
+    pub created_at: String,
+}
+
+/// Ticket purchase, holding `count` consecutive ticket numbers
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Ticket {
+    pub id: i32,
+    pub lottery_id: String,
+    pub txid: String,
+    pub block_height: i32,
+    pub buyer_pubkey: String,
+    pub first_number: i32,
+    pub count: i32,
+    pub paid_sats: i64,
+    /// Holds the winning number
+    pub is_winner: bool,
+    pub claim_txid: Option<String>,
+    pub claimed_sats: Option<i64>,
+    pub claimed_at_height: Option<i32>,
+}
+
+/// Lottery statistics
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LotteryStats {
+    pub total_lotteries: i64,
+    pub open_lotteries: i64,
+    pub drawn_lotteries: i64,
+    pub void_lotteries: i64,
+    pub total_tickets_sold: i64,
+    pub total_volume_sats: i64,
+    pub total_claimed_sats: i64,
+}
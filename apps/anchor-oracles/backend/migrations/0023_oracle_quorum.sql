@@ -0,0 +1,17 @@
+-- Multi-oracle threshold attestations
+-- Events may require k-of-n oracle attestations before they are fulfilled
+
+ALTER TABLE event_requests ADD COLUMN IF NOT EXISTS threshold INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE event_requests ADD COLUMN IF NOT EXISTS quorum_outcome BYTEA;
+ALTER TABLE event_requests ADD COLUMN IF NOT EXISTS quorum_height INTEGER;
+
+-- Eligible oracle set per event (empty = any registered oracle may attest)
+CREATE TABLE IF NOT EXISTS event_oracles (
+    event_request_id INTEGER NOT NULL REFERENCES event_requests(id) ON DELETE CASCADE,
+    oracle_pubkey BYTEA NOT NULL,
+    PRIMARY KEY (event_request_id, oracle_pubkey)
+);
+
+COMMENT ON COLUMN event_requests.threshold IS 'Number of agreeing oracle attestations (k) required to fulfill the event';
+COMMENT ON COLUMN event_requests.quorum_outcome IS 'Outcome that reached the threshold';
+COMMENT ON COLUMN event_requests.quorum_height IS 'Block height at which quorum was reached';
@@ -0,0 +1,33 @@
+-- Bonded disputes and slashing
+-- Oracles lock a bond output at registration; disputes carry a challenger bond
+-- and are settled by a slash transaction that spends the losing party's bond
+
+ALTER TABLE oracles ADD COLUMN IF NOT EXISTS bond_txid BYTEA;
+ALTER TABLE oracles ADD COLUMN IF NOT EXISTS bond_vout INTEGER;
+ALTER TABLE oracles ADD COLUMN IF NOT EXISTS bond_sats BIGINT;
+
+ALTER TABLE disputes ADD COLUMN IF NOT EXISTS bond_vout INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_oracles_bond ON oracles(bond_txid);
+CREATE INDEX IF NOT EXISTS idx_disputes_txid ON disputes(txid);
+
+-- Slash transactions (kind 33), including ones rejected by validation
+CREATE TABLE IF NOT EXISTS slashes (
+    id SERIAL PRIMARY KEY,
+    dispute_id INTEGER REFERENCES disputes(id) ON DELETE CASCADE,
+    txid BYTEA NOT NULL,
+    vout INTEGER NOT NULL,
+    block_height INTEGER,
+    verdict INTEGER NOT NULL,
+    amount_sats BIGINT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'valid',
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(txid, vout)
+);
+
+CREATE INDEX IF NOT EXISTS idx_slashes_dispute ON slashes(dispute_id);
+
+COMMENT ON COLUMN oracles.bond_txid IS 'Registration output holding the oracle bond (NULL once spent)';
+COMMENT ON COLUMN disputes.bond_vout IS 'Output of the dispute transaction holding the challenger bond';
+COMMENT ON COLUMN slashes.status IS 'valid = dispute settled, invalid = slash failed validation';
@@ -0,0 +1,39 @@
+-- Scheduled data feeds
+-- An oracle declares a recurring feed (e.g. BTCUSD every 6 blocks) and
+-- publishes each value as an attestation under the feed's event id. Slot k of
+-- a feed covers blocks [start_height + k * interval_blocks, start_height +
+-- (k + 1) * interval_blocks); a slot that closes without a publication from
+-- the feed's oracle is recorded as missed.
+
+CREATE TABLE IF NOT EXISTS oracle_feeds (
+    id SERIAL PRIMARY KEY,
+    oracle_id INTEGER NOT NULL REFERENCES oracles(id) ON DELETE CASCADE,
+    event_id BYTEA NOT NULL UNIQUE,
+    name VARCHAR(64) NOT NULL,
+    description TEXT,
+    category INTEGER NOT NULL,
+    interval_blocks INTEGER NOT NULL,
+    start_height INTEGER NOT NULL,
+    checked_slots INTEGER NOT NULL DEFAULT 0,
+    missed_slots INTEGER NOT NULL DEFAULT 0,
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(oracle_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oracle_feeds_oracle ON oracle_feeds(oracle_id);
+
+-- Slots that closed without a publication
+CREATE TABLE IF NOT EXISTS feed_misses (
+    feed_id INTEGER NOT NULL REFERENCES oracle_feeds(id) ON DELETE CASCADE,
+    slot INTEGER NOT NULL,
+    slot_start INTEGER NOT NULL,
+    slot_end INTEGER NOT NULL,
+    PRIMARY KEY (feed_id, slot)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attestations_event_block ON attestations(event_id, block_height);
+
+COMMENT ON COLUMN oracle_feeds.event_id IS 'Event id every publication of the feed attests under';
+COMMENT ON COLUMN oracle_feeds.checked_slots IS 'Number of closed slots checked for a publication';
+COMMENT ON COLUMN oracle_feeds.status IS 'active = last closed slot published, lagging = last closed slot missed';
@@ -1,5 +1,6 @@
 //! Configuration for the Anchor Oracles backend
 
+use anchor_specs::oracle::DEFAULT_DISPUTE_WINDOW_BLOCKS;
 use std::env;
 
 #[derive(Debug, Clone)]
@@ -10,6 +11,11 @@ pub struct Config {
     pub bitcoin_rpc_url: String,
     pub bitcoin_rpc_user: String,
     pub bitcoin_rpc_password: String,
+    pub wallet_url: String,
+    /// Blocks after an attestation during which it can be disputed
+    pub dispute_window_blocks: u32,
+    /// Minimum challenger bond for a dispute to be accepted
+    pub min_dispute_bond_sats: i64,
 }
 
 impl Config {
@@ -31,6 +37,16 @@ impl Config {
                 .unwrap_or_else(|_| "bitcoin".to_string()),
             bitcoin_rpc_password: env::var("BITCOIN_RPC_PASSWORD")
                 .unwrap_or_else(|_| "bitcoin".to_string()),
+            wallet_url: env::var("WALLET_SERVICE_URL")
+                .unwrap_or_else(|_| "http://localhost:8001".to_string()),
+            dispute_window_blocks: env::var("DISPUTE_WINDOW_BLOCKS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_DISPUTE_WINDOW_BLOCKS),
+            min_dispute_bond_sats: env::var("MIN_DISPUTE_BOND_SATS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10_000),
         }
     }
 }
@@ -1,13 +1,114 @@
 //! Database operations for Anchor Oracles
 
+use anchor_specs::oracle::{AggregateAttestation, OracleVote, SlashVerdict};
 use anyhow::Result;
 use sqlx::postgres::PgPool;
 
 use crate::models::{
-    category_name, dispute_reason_name, key_type_name, Attestation, CategoryInfo, Dispute,
-    EventRequest, Oracle, OracleCategories, OracleStats,
+    category_name, dispute_reason_name, feed_slot, key_type_name, slash_verdict_name, Attestation,
+    CategoryInfo, Dispute, EventRequest, Feed, FeedMiss, FeedPoint, Oracle, OracleCategories,
+    OracleStats, Slash,
 };
 
+/// Attestations collected for an event request, with its quorum parameters
+pub struct EventAggregate {
+    pub request_id: i32,
+    pub quorum_height: Option<i32>,
+    pub aggregate: AggregateAttestation,
+}
+
+/// Feed columns selected by the feed queries
+type FeedRow = (
+    i32,
+    i32,
+    Vec<u8>,
+    String,
+    Vec<u8>,
+    String,
+    Option<String>,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    String,
+    Option<i32>,
+    chrono::DateTime<chrono::Utc>,
+);
+
+const FEED_SELECT: &str = r#"
+    SELECT f.id, f.oracle_id, o.pubkey, o.name, f.event_id, f.name, f.description,
+           f.category, f.interval_blocks, f.start_height, f.checked_slots, f.missed_slots,
+           f.status,
+           (SELECT MAX(a.block_height) FROM attestations a
+            WHERE a.event_id = f.event_id AND a.oracle_id = f.oracle_id),
+           f.created_at
+    FROM oracle_feeds f
+    JOIN oracles o ON o.id = f.oracle_id
+"#;
+
+fn feed_from_row(r: FeedRow) -> Feed {
+    Feed {
+        id: r.0,
+        oracle_id: r.1,
+        oracle_pubkey: hex::encode(&r.2),
+        oracle_name: r.3,
+        event_id: hex::encode(&r.4),
+        name: r.5,
+        description: r.6,
+        category: r.7,
+        category_name: category_name(r.7),
+        interval_blocks: r.8,
+        start_height: r.9,
+        checked_slots: r.10,
+        missed_slots: r.11,
+        status: r.12,
+        last_published_height: r.13,
+        created_at: r.14.to_rfc3339(),
+    }
+}
+
+/// A feed slot that closed without a publication, as flagged by the indexer
+pub struct MissedPublication {
+    pub feed_id: i32,
+    pub feed_name: String,
+    pub miss: FeedMiss,
+}
+
+/// A dispute together with the bonds a slash can settle it against
+pub struct DisputeSettlement {
+    pub dispute_id: i32,
+    pub status: String,
+    pub attestation_id: i32,
+    pub oracle_id: i32,
+    pub dispute_txid: Vec<u8>,
+    pub dispute_vout: i32,
+    pub dispute_bond_vout: Option<i32>,
+    pub dispute_bond_sats: i64,
+    pub oracle_bond_txid: Option<Vec<u8>>,
+    pub oracle_bond_vout: Option<i32>,
+    pub oracle_bond_sats: i64,
+}
+
+impl DisputeSettlement {
+    /// Bond outpoint and value forfeited under `verdict`: the oracle's
+    /// registration bond if upheld, the challenger's dispute bond if rejected.
+    pub fn losing_bond(&self, verdict: SlashVerdict) -> Option<(&[u8], i32, i64)> {
+        match verdict {
+            SlashVerdict::Upheld => Some((
+                self.oracle_bond_txid.as_deref()?,
+                self.oracle_bond_vout?,
+                self.oracle_bond_sats,
+            )),
+            SlashVerdict::Rejected => Some((
+                &self.dispute_txid,
+                self.dispute_bond_vout?,
+                self.dispute_bond_sats,
+            )),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Database {
     pool: PgPool,
@@ -239,6 +340,33 @@ impl Database {
         .execute(&self.pool)
         .await;
 
+        // Multi-oracle threshold columns and eligible oracle sets - migration
+        let _ = sqlx::query(
+            "ALTER TABLE event_requests ADD COLUMN IF NOT EXISTS threshold INTEGER NOT NULL DEFAULT 1",
+        )
+        .execute(&self.pool)
+        .await;
+        let _ =
+            sqlx::query("ALTER TABLE event_requests ADD COLUMN IF NOT EXISTS quorum_outcome BYTEA")
+                .execute(&self.pool)
+                .await;
+        let _ = sqlx::query(
+            "ALTER TABLE event_requests ADD COLUMN IF NOT EXISTS quorum_height INTEGER",
+        )
+        .execute(&self.pool)
+        .await;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS event_oracles (
+                event_request_id INTEGER NOT NULL REFERENCES event_requests(id) ON DELETE CASCADE,
+                oracle_pubkey BYTEA NOT NULL,
+                PRIMARY KEY (event_request_id, oracle_pubkey)
+            )
+        "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create indexer_state table
         sqlx::query(
             r#"
@@ -274,6 +402,95 @@ impl Database {
         .execute(&self.pool)
         .await;
 
+        // Bonded disputes and slashing - migration
+        let _ = sqlx::query("ALTER TABLE oracles ADD COLUMN IF NOT EXISTS bond_txid BYTEA")
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("ALTER TABLE oracles ADD COLUMN IF NOT EXISTS bond_vout INTEGER")
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("ALTER TABLE oracles ADD COLUMN IF NOT EXISTS bond_sats BIGINT")
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("ALTER TABLE disputes ADD COLUMN IF NOT EXISTS bond_vout INTEGER")
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_oracles_bond ON oracles(bond_txid)")
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_disputes_txid ON disputes(txid)")
+            .execute(&self.pool)
+            .await;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS slashes (
+                id SERIAL PRIMARY KEY,
+                dispute_id INTEGER REFERENCES disputes(id) ON DELETE CASCADE,
+                txid BYTEA NOT NULL,
+                vout INTEGER NOT NULL,
+                block_height INTEGER,
+                verdict INTEGER NOT NULL,
+                amount_sats BIGINT NOT NULL,
+                status VARCHAR(20) NOT NULL DEFAULT 'valid',
+                reason TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE(txid, vout)
+            )
+        "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        let _ =
+            sqlx::query("CREATE INDEX IF NOT EXISTS idx_slashes_dispute ON slashes(dispute_id)")
+                .execute(&self.pool)
+                .await;
+
+        // Scheduled data feeds - migration
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS oracle_feeds (
+                id SERIAL PRIMARY KEY,
+                oracle_id INTEGER NOT NULL REFERENCES oracles(id) ON DELETE CASCADE,
+                event_id BYTEA NOT NULL UNIQUE,
+                name VARCHAR(64) NOT NULL,
+                description TEXT,
+                category INTEGER NOT NULL,
+                interval_blocks INTEGER NOT NULL,
+                start_height INTEGER NOT NULL,
+                checked_slots INTEGER NOT NULL DEFAULT 0,
+                missed_slots INTEGER NOT NULL DEFAULT 0,
+                status VARCHAR(20) NOT NULL DEFAULT 'active',
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE(oracle_id, name)
+            )
+        "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS feed_misses (
+                feed_id INTEGER NOT NULL REFERENCES oracle_feeds(id) ON DELETE CASCADE,
+                slot INTEGER NOT NULL,
+                slot_start INTEGER NOT NULL,
+                slot_end INTEGER NOT NULL,
+                PRIMARY KEY (feed_id, slot)
+            )
+        "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        let _ = sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_oracle_feeds_oracle ON oracle_feeds(oracle_id)",
+        )
+        .execute(&self.pool)
+        .await;
+        let _ = sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_attestations_event_block ON attestations(event_id, block_height)",
+        )
+        .execute(&self.pool)
+        .await;
+
         tracing::info!("Database migrations completed");
         Ok(())
     }
@@ -599,7 +816,7 @@ impl Database {
         Ok(row.0)
     }
 
-    /// Insert event request
+    /// Insert event request with its quorum threshold and eligible oracle set
     pub async fn insert_event_request(
         &self,
         event_id: &[u8],
@@ -607,11 +824,15 @@ impl Database {
         description: &str,
         resolution_block: Option<i32>,
         bounty_sats: i64,
+        threshold: i32,
+        oracle_pubkeys: &[Vec<u8>],
     ) -> Result<i32> {
+        let mut tx = self.pool.begin().await?;
+
         let row: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO event_requests (event_id, category, description, resolution_block, bounty_sats)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO event_requests (event_id, category, description, resolution_block, bounty_sats, threshold)
+            VALUES ($1, $2, $3, $4, $5, $6)
             ON CONFLICT (event_id) DO NOTHING
             RETURNING id
             "#,
@@ -621,38 +842,268 @@ impl Database {
         .bind(description)
         .bind(resolution_block)
         .bind(bounty_sats)
-        .fetch_one(&self.pool)
+        .bind(threshold)
+        .fetch_one(&mut *tx)
         .await?;
 
+        for pubkey in oracle_pubkeys {
+            sqlx::query(
+                "INSERT INTO event_oracles (event_request_id, oracle_pubkey) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            )
+            .bind(row.0)
+            .bind(pubkey)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
         Ok(row.0)
     }
 
-    /// Update event status to fulfilled when attestation is made
-    pub async fn fulfill_event(&self, event_id: &[u8], oracle_id: i32) -> Result<()> {
+    /// Load the aggregate attestation for an event request by database ID
+    pub async fn get_event_aggregate(&self, id: i32) -> Result<Option<EventAggregate>> {
+        let row: Option<(i32, Vec<u8>, i32, Option<i32>)> = sqlx::query_as(
+            "SELECT id, event_id, threshold, quorum_height FROM event_requests WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.load_event_aggregate(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Load the aggregate attestation for an event request by its 32-byte event ID
+    pub async fn get_event_aggregate_by_event_id(
+        &self,
+        event_id: &[u8],
+    ) -> Result<Option<EventAggregate>> {
+        let row: Option<(i32, Vec<u8>, i32, Option<i32>)> = sqlx::query_as(
+            "SELECT id, event_id, threshold, quorum_height FROM event_requests WHERE event_id = $1",
+        )
+        .bind(event_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(self.load_event_aggregate(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Collect eligible oracles and valid attestations for an event request
+    async fn load_event_aggregate(
+        &self,
+        (request_id, event_id, threshold, quorum_height): (i32, Vec<u8>, i32, Option<i32>),
+    ) -> Result<EventAggregate> {
+        let event_id: [u8; 32] = event_id
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid event_id length for event {}", request_id))?;
+
+        let eligible: Vec<(Vec<u8>,)> = sqlx::query_as(
+            "SELECT oracle_pubkey FROM event_oracles WHERE event_request_id = $1 ORDER BY oracle_pubkey",
+        )
+        .bind(request_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Oldest first so that each oracle's latest attestation replaces earlier ones
+        let votes: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT o.pubkey, a.outcome_data, a.schnorr_signature
+            FROM attestations a
+            JOIN oracles o ON a.oracle_id = o.id
+            WHERE a.event_id = $1 AND a.status = 'valid'
+            ORDER BY a.block_height ASC NULLS LAST, a.id ASC
+            "#,
+        )
+        .bind(&event_id[..])
+        .fetch_all(&self.pool)
+        .await?;
+
+        let threshold = threshold.clamp(1, u8::MAX as i32) as u8;
+        let mut aggregate = AggregateAttestation::new(event_id, threshold).with_eligible(
+            eligible
+                .into_iter()
+                .filter_map(|(pubkey,)| pubkey.as_slice().try_into().ok())
+                .collect(),
+        );
+
+        // Only attestations signed by the oracle's registered key count toward quorum
+        for (pubkey, outcome, signature) in votes {
+            let Ok(oracle_pubkey) = pubkey.as_slice().try_into() else {
+                continue;
+            };
+            let vote = OracleVote {
+                oracle_pubkey,
+                outcome,
+                signature,
+            };
+            match vote.verify(&event_id) {
+                Ok(()) => {
+                    aggregate.add_vote(vote);
+                }
+                Err(e) => tracing::warn!(
+                    "Ignoring attestation by {} for event {}: {}",
+                    hex::encode(oracle_pubkey),
+                    hex::encode(event_id),
+                    e
+                ),
+            }
+        }
+
+        Ok(EventAggregate {
+            request_id,
+            quorum_height,
+            aggregate,
+        })
+    }
+
+    /// Mark an event as fulfilled once its attestations reach quorum.
+    ///
+    /// Returns `true` if this call recorded the quorum (it had not been reached before).
+    pub async fn record_event_quorum(
+        &self,
+        request_id: i32,
+        outcome: &[u8],
+        height: i32,
+        oracle_id: i32,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE event_requests
+            SET status = 'fulfilled', fulfilled_by = $1, quorum_outcome = $2, quorum_height = $3
+            WHERE id = $4 AND status = 'pending' AND quorum_height IS NULL
+            "#,
+        )
+        .bind(oracle_id)
+        .bind(outcome)
+        .bind(height)
+        .bind(request_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the bond output locked by an oracle registration
+    pub async fn set_oracle_bond(
+        &self,
+        oracle_id: i32,
+        txid: &[u8],
+        vout: i32,
+        bond_sats: i64,
+    ) -> Result<()> {
         sqlx::query(
             r#"
-            UPDATE event_requests 
-            SET status = 'fulfilled', fulfilled_by = $1
-            WHERE event_id = $2 AND status = 'pending'
+            UPDATE oracles SET bond_txid = $1, bond_vout = $2, bond_sats = $3, updated_at = NOW()
+            WHERE id = $4
             "#,
         )
+        .bind(txid)
+        .bind(vout)
+        .bind(bond_sats)
         .bind(oracle_id)
-        .bind(event_id)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    /// Get attestation ID by txid
-    pub async fn get_attestation_id_by_txid(&self, txid: &[u8]) -> Result<Option<i32>> {
-        let row: Option<(i32,)> = sqlx::query_as("SELECT id FROM attestations WHERE txid = $1")
-            .bind(txid)
-            .fetch_optional(&self.pool)
+    /// Release oracle bonds spent outside of a slash.
+    ///
+    /// `spent` lists `(prev_txid, prev_vout, spending_txid)` for every input in a block.
+    /// An oracle whose bond is spent this way has withdrawn its stake.
+    pub async fn release_spent_bonds(
+        &self,
+        spent: &[(Vec<u8>, i32, Vec<u8>)],
+        height: i32,
+    ) -> Result<usize> {
+        if spent.is_empty() {
+            return Ok(0);
+        }
+
+        let txids: Vec<Vec<u8>> = spent.iter().map(|(txid, _, _)| txid.clone()).collect();
+        let bonds: Vec<(i32, Vec<u8>, i32, i64)> = sqlx::query_as(
+            r#"
+            SELECT id, bond_txid, bond_vout, COALESCE(bond_sats, 0)
+            FROM oracles
+            WHERE bond_txid = ANY($1) AND bond_vout IS NOT NULL
+            "#,
+        )
+        .bind(&txids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut released = 0;
+        for (oracle_id, bond_txid, bond_vout, bond_sats) in bonds {
+            let Some((_, _, spending_txid)) = spent
+                .iter()
+                .find(|(txid, vout, _)| *txid == bond_txid && *vout == bond_vout)
+            else {
+                continue;
+            };
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(
+                r#"
+                UPDATE oracles SET
+                    bond_txid = NULL, bond_vout = NULL, bond_sats = NULL,
+                    stake_sats = 0, status = 'unbonded', updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(oracle_id)
+            .execute(&mut *tx)
             .await?;
-        Ok(row.map(|r| r.0))
+            sqlx::query(
+                r#"
+                INSERT INTO oracle_stakes (oracle_id, txid, vout, amount_sats, action, block_height)
+                VALUES ($1, $2, $3, $4, 'unstake', $5)
+                "#,
+            )
+            .bind(oracle_id)
+            .bind(spending_txid)
+            .bind(bond_vout)
+            .bind(bond_sats)
+            .bind(height)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            released += 1;
+        }
+
+        Ok(released)
+    }
+
+    /// Get attestation id and confirmation height by txid
+    pub async fn get_attestation_by_txid(&self, txid: &[u8]) -> Result<Option<(i32, Option<i32>)>> {
+        let row: Option<(i32, Option<i32>)> =
+            sqlx::query_as("SELECT id, block_height FROM attestations WHERE txid = $1")
+                .bind(txid)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row)
+    }
+
+    /// Get attestation outpoint and confirmation height by id
+    pub async fn get_attestation_outpoint(
+        &self,
+        id: i32,
+    ) -> Result<Option<(Vec<u8>, i32, Option<i32>)>> {
+        let row: Option<(Vec<u8>, i32, Option<i32>)> =
+            sqlx::query_as("SELECT txid, vout, block_height FROM attestations WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row)
     }
 
     /// Insert dispute
+    ///
+    /// A dispute with a `rejection` (closed window, missing bond) is stored as
+    /// `rejected` and leaves the attestation untouched.
     #[allow(clippy::too_many_arguments)]
     pub async fn insert_dispute(
         &self,
@@ -663,11 +1114,13 @@ impl Database {
         block_height: Option<i32>,
         reason: i32,
         stake_sats: i64,
+        bond_vout: Option<i32>,
+        rejection: Option<&str>,
     ) -> Result<i32> {
         let row: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO disputes (attestation_id, disputer_pubkey, txid, vout, block_height, reason, stake_sats)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO disputes (attestation_id, disputer_pubkey, txid, vout, block_height, reason, stake_sats, bond_vout, status, resolution)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             ON CONFLICT (txid, vout) DO NOTHING
             RETURNING id
             "#,
@@ -679,9 +1132,16 @@ impl Database {
         .bind(block_height)
         .bind(reason)
         .bind(stake_sats)
+        .bind(bond_vout)
+        .bind(if rejection.is_some() { "rejected" } else { "pending" })
+        .bind(rejection)
         .fetch_one(&self.pool)
         .await?;
 
+        if rejection.is_some() {
+            return Ok(row.0);
+        }
+
         // Update attestation status to disputed
         let _ = sqlx::query("UPDATE attestations SET status = 'disputed' WHERE id = $1")
             .bind(attestation_id)
@@ -704,6 +1164,250 @@ impl Database {
         Ok(row.0)
     }
 
+    /// Get a dispute and its bonds by dispute txid
+    pub async fn get_dispute_settlement(&self, txid: &[u8]) -> Result<Option<DisputeSettlement>> {
+        self.load_dispute_settlement(None, Some(txid)).await
+    }
+
+    /// Get a dispute and its bonds by dispute id
+    pub async fn get_dispute_settlement_by_id(&self, id: i32) -> Result<Option<DisputeSettlement>> {
+        self.load_dispute_settlement(Some(id), None).await
+    }
+
+    async fn load_dispute_settlement(
+        &self,
+        id: Option<i32>,
+        txid: Option<&[u8]>,
+    ) -> Result<Option<DisputeSettlement>> {
+        #[allow(clippy::type_complexity)]
+        let row: Option<(
+            i32,
+            Option<String>,
+            i32,
+            i32,
+            Vec<u8>,
+            i32,
+            Option<i32>,
+            i64,
+            Option<Vec<u8>>,
+            Option<i32>,
+            i64,
+        )> = sqlx::query_as(
+            r#"
+            SELECT d.id, d.status, d.attestation_id, a.oracle_id, d.txid, d.vout,
+                   d.bond_vout, d.stake_sats, o.bond_txid, o.bond_vout, COALESCE(o.bond_sats, 0)
+            FROM disputes d
+            JOIN attestations a ON d.attestation_id = a.id
+            JOIN oracles o ON a.oracle_id = o.id
+            WHERE ($1::INTEGER IS NULL OR d.id = $1)
+              AND ($2::BYTEA IS NULL OR d.txid = $2)
+            ORDER BY d.id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(id)
+        .bind(txid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| DisputeSettlement {
+            dispute_id: r.0,
+            status: r.1.unwrap_or_else(|| "pending".to_string()),
+            attestation_id: r.2,
+            oracle_id: r.3,
+            dispute_txid: r.4,
+            dispute_vout: r.5,
+            dispute_bond_vout: r.6,
+            dispute_bond_sats: r.7,
+            oracle_bond_txid: r.8,
+            oracle_bond_vout: r.9,
+            oracle_bond_sats: r.10,
+        }))
+    }
+
+    /// Apply a validated slash to its dispute.
+    ///
+    /// Upheld: the attestation is invalidated and the oracle loses `amount_sats`
+    /// of stake along with its (now spent) bond. Rejected: the attestation is
+    /// restored unless other disputes against it are still pending.
+    pub async fn settle_dispute(
+        &self,
+        settlement: &DisputeSettlement,
+        verdict: SlashVerdict,
+        txid: &[u8],
+        vout: i32,
+        block_height: i32,
+        amount_sats: i64,
+    ) -> Result<i32> {
+        let mut tx = self.pool.begin().await?;
+
+        let status = match verdict {
+            SlashVerdict::Upheld => "upheld",
+            SlashVerdict::Rejected => "rejected",
+        };
+        sqlx::query("UPDATE disputes SET status = $1, resolution = $1 WHERE id = $2")
+            .bind(status)
+            .bind(settlement.dispute_id)
+            .execute(&mut *tx)
+            .await?;
+
+        match verdict {
+            SlashVerdict::Upheld => {
+                sqlx::query("UPDATE attestations SET status = 'invalid' WHERE id = $1")
+                    .bind(settlement.attestation_id)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(
+                    r#"
+                    UPDATE oracles SET
+                        stake_sats = GREATEST(0, stake_sats - $1),
+                        bond_txid = NULL, bond_vout = NULL, bond_sats = NULL,
+                        status = 'slashed', updated_at = NOW()
+                    WHERE id = $2
+                    "#,
+                )
+                .bind(amount_sats)
+                .bind(settlement.oracle_id)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(
+                    r#"
+                    INSERT INTO oracle_stakes (oracle_id, txid, vout, amount_sats, action, block_height)
+                    VALUES ($1, $2, $3, $4, 'slash', $5)
+                    "#,
+                )
+                .bind(settlement.oracle_id)
+                .bind(txid)
+                .bind(vout)
+                .bind(amount_sats)
+                .bind(block_height)
+                .execute(&mut *tx)
+                .await?;
+            }
+            SlashVerdict::Rejected => {
+                let restored = sqlx::query(
+                    r#"
+                    UPDATE attestations SET status = 'valid'
+                    WHERE id = $1 AND status = 'disputed'
+                      AND NOT EXISTS (
+                          SELECT 1 FROM disputes WHERE attestation_id = $1 AND status = 'pending'
+                      )
+                    "#,
+                )
+                .bind(settlement.attestation_id)
+                .execute(&mut *tx)
+                .await?;
+                if restored.rows_affected() > 0 {
+                    sqlx::query(
+                        r#"
+                        UPDATE oracles SET
+                            disputed_attestations = GREATEST(0, disputed_attestations - 1),
+                            successful_attestations = successful_attestations + 1
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(settlement.oracle_id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+
+        let row: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO slashes (dispute_id, txid, vout, block_height, verdict, amount_sats, status)
+            VALUES ($1, $2, $3, $4, $5, $6, 'valid')
+            RETURNING id
+            "#,
+        )
+        .bind(settlement.dispute_id)
+        .bind(txid)
+        .bind(vout)
+        .bind(block_height)
+        .bind(verdict as i32)
+        .bind(amount_sats)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(row.0)
+    }
+
+    /// Record a slash that failed validation
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_invalid_slash(
+        &self,
+        dispute_id: Option<i32>,
+        txid: &[u8],
+        vout: i32,
+        block_height: i32,
+        verdict: i32,
+        amount_sats: i64,
+        reason: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO slashes (dispute_id, txid, vout, block_height, verdict, amount_sats, status, reason)
+            VALUES ($1, $2, $3, $4, $5, $6, 'invalid', $7)
+            ON CONFLICT (txid, vout) DO NOTHING
+            "#,
+        )
+        .bind(dispute_id)
+        .bind(txid)
+        .bind(vout)
+        .bind(block_height)
+        .bind(verdict)
+        .bind(amount_sats)
+        .bind(reason)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_slashes(&self, limit: i64) -> Result<Vec<Slash>> {
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(
+            i32,
+            Option<i32>,
+            Vec<u8>,
+            i32,
+            Option<i32>,
+            i32,
+            i64,
+            String,
+            Option<String>,
+            chrono::DateTime<chrono::Utc>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT id, dispute_id, txid, vout, block_height, verdict, amount_sats,
+                   status, reason, created_at
+            FROM slashes
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Slash {
+                id: r.0,
+                dispute_id: r.1,
+                txid: hex::encode(&r.2),
+                vout: r.3,
+                block_height: r.4,
+                verdict: r.5,
+                verdict_name: slash_verdict_name(r.5),
+                amount_sats: r.6,
+                status: r.7,
+                reason: r.8,
+                created_at: r.9.to_rfc3339(),
+            })
+            .collect())
+    }
+
     // Attestation operations
 
     pub async fn get_attestations(&self, limit: i64, offset: i64) -> Result<Vec<Attestation>> {
@@ -926,12 +1630,13 @@ impl Database {
                     i64,
                     String,
                     Option<i32>,
+                    i32,
                     chrono::DateTime<chrono::Utc>,
                 ),
             >(
                 r#"
                 SELECT id, event_id, category, description, resolution_block,
-                       bounty_sats, status, fulfilled_by, created_at
+                       bounty_sats, status, fulfilled_by, threshold, created_at
                 FROM event_requests
                 WHERE status = $1
                 ORDER BY bounty_sats DESC, created_at ASC
@@ -954,12 +1659,13 @@ impl Database {
                     i64,
                     String,
                     Option<i32>,
+                    i32,
                     chrono::DateTime<chrono::Utc>,
                 ),
             >(
                 r#"
                 SELECT id, event_id, category, description, resolution_block,
-                       bounty_sats, status, fulfilled_by, created_at
+                       bounty_sats, status, fulfilled_by, threshold, created_at
                 FROM event_requests
                 ORDER BY created_at DESC
                 LIMIT $1
@@ -982,7 +1688,8 @@ impl Database {
                 bounty_sats: r.5,
                 status: r.6,
                 fulfilled_by: r.7,
-                created_at: r.8.to_rfc3339(),
+                threshold: r.8,
+                created_at: r.9.to_rfc3339(),
             })
             .collect())
     }
@@ -999,12 +1706,13 @@ impl Database {
                 i64,
                 String,
                 Option<i32>,
+                i32,
                 chrono::DateTime<chrono::Utc>,
             ),
         >(
             r#"
             SELECT id, event_id, category, description, resolution_block,
-                   bounty_sats, status, fulfilled_by, created_at
+                   bounty_sats, status, fulfilled_by, threshold, created_at
             FROM event_requests
             WHERE id = $1
             "#,
@@ -1023,7 +1731,8 @@ impl Database {
             bounty_sats: r.5,
             status: r.6,
             fulfilled_by: r.7,
-            created_at: r.8.to_rfc3339(),
+            threshold: r.8,
+            created_at: r.9.to_rfc3339(),
         }))
     }
 
@@ -1095,6 +1804,208 @@ impl Database {
             .collect())
     }
 
+    // Feed operations
+
+    /// Declare a feed; `None` if the oracle already has a feed with this name
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_feed(
+        &self,
+        oracle_id: i32,
+        event_id: &[u8],
+        name: &str,
+        description: Option<&str>,
+        category: i32,
+        interval_blocks: i32,
+        start_height: i32,
+    ) -> Result<Option<i32>> {
+        let row: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO oracle_feeds (oracle_id, event_id, name, description, category,
+                                      interval_blocks, start_height)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (oracle_id, name) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(oracle_id)
+        .bind(event_id)
+        .bind(name)
+        .bind(description)
+        .bind(category)
+        .bind(interval_blocks)
+        .bind(start_height)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| r.0))
+    }
+
+    /// Feeds, optionally of one oracle
+    pub async fn get_feeds(&self, oracle_pubkey: Option<&[u8]>, limit: i64) -> Result<Vec<Feed>> {
+        let rows: Vec<FeedRow> = sqlx::query_as(&format!(
+            "{} WHERE ($1::BYTEA IS NULL OR o.pubkey = $1) ORDER BY f.id DESC LIMIT $2",
+            FEED_SELECT
+        ))
+        .bind(oracle_pubkey)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(feed_from_row).collect())
+    }
+
+    pub async fn get_feed(&self, id: i32) -> Result<Option<Feed>> {
+        let row: Option<FeedRow> = sqlx::query_as(&format!("{} WHERE f.id = $1", FEED_SELECT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(feed_from_row))
+    }
+
+    /// Publications of a feed between two block heights (inclusive), oldest first
+    pub async fn get_feed_points(
+        &self,
+        feed: &Feed,
+        from: i32,
+        to: i32,
+        limit: i64,
+    ) -> Result<Vec<FeedPoint>> {
+        let rows: Vec<(i32, Vec<u8>, Vec<u8>, i32, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT a.block_height, a.outcome_data, a.txid, a.vout, a.schnorr_signature
+            FROM attestations a
+            JOIN oracle_feeds f ON a.event_id = f.event_id AND a.oracle_id = f.oracle_id
+            WHERE f.id = $1 AND a.block_height BETWEEN $2 AND $3
+            ORDER BY a.block_height ASC, a.id ASC
+            LIMIT $4
+            "#,
+        )
+        .bind(feed.id)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| FeedPoint {
+                slot: feed_slot(feed.start_height, feed.interval_blocks, r.0).unwrap_or(-1),
+                block_height: r.0,
+                value_text: String::from_utf8(r.1.clone()).ok(),
+                value: hex::encode(&r.1),
+                txid: hex::encode(&r.2),
+                vout: r.3,
+                schnorr_signature: hex::encode(&r.4),
+            })
+            .collect())
+    }
+
+    /// Missed slots of a feed overlapping two block heights (inclusive)
+    pub async fn get_feed_misses(&self, feed_id: i32, from: i32, to: i32) -> Result<Vec<FeedMiss>> {
+        let rows: Vec<(i32, i32, i32)> = sqlx::query_as(
+            r#"
+            SELECT slot, slot_start, slot_end FROM feed_misses
+            WHERE feed_id = $1 AND slot_end > $2 AND slot_start <= $3
+            ORDER BY slot ASC
+            "#,
+        )
+        .bind(feed_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| FeedMiss {
+                slot: r.0,
+                slot_start: r.1,
+                slot_end: r.2,
+            })
+            .collect())
+    }
+
+    /// Check every feed slot that closed at `height` for a publication from
+    /// the feed's oracle, recording the ones that were missed
+    pub async fn check_feed_cadence(&self, height: i32) -> Result<Vec<MissedPublication>> {
+        let feeds: Vec<(i32, String, i32, Vec<u8>, i32, i32, i32)> = sqlx::query_as(
+            r#"
+            SELECT id, name, oracle_id, event_id, interval_blocks, start_height, checked_slots
+            FROM oracle_feeds
+            WHERE start_height + (checked_slots + 1) * interval_blocks - 1 <= $1
+            "#,
+        )
+        .bind(height)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut missed = Vec::new();
+        for (feed_id, feed_name, oracle_id, event_id, interval, start, checked) in feeds {
+            let mut tx = self.pool.begin().await?;
+            let mut slot = checked;
+            let mut last_missed = false;
+            while start + (slot + 1) * interval - 1 <= height {
+                let slot_start = start + slot * interval;
+                let slot_end = slot_start + interval;
+                let published: (bool,) = sqlx::query_as(
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM attestations
+                        WHERE event_id = $1 AND oracle_id = $2
+                          AND block_height >= $3 AND block_height < $4
+                    )
+                    "#,
+                )
+                .bind(&event_id)
+                .bind(oracle_id)
+                .bind(slot_start)
+                .bind(slot_end)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                last_missed = !published.0;
+                if last_missed {
+                    sqlx::query(
+                        "INSERT INTO feed_misses (feed_id, slot, slot_start, slot_end) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING",
+                    )
+                    .bind(feed_id)
+                    .bind(slot)
+                    .bind(slot_start)
+                    .bind(slot_end)
+                    .execute(&mut *tx)
+                    .await?;
+                    missed.push(MissedPublication {
+                        feed_id,
+                        feed_name: feed_name.clone(),
+                        miss: FeedMiss {
+                            slot,
+                            slot_start,
+                            slot_end,
+                        },
+                    });
+                }
+                slot += 1;
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE oracle_feeds
+                SET checked_slots = $2,
+                    missed_slots = (SELECT COUNT(*) FROM feed_misses WHERE feed_id = $1),
+                    status = $3
+                WHERE id = $1
+                "#,
+            )
+            .bind(feed_id)
+            .bind(slot)
+            .bind(if last_missed { "lagging" } else { "active" })
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+        }
+
+        Ok(missed)
+    }
+
     // Stats
 
     pub async fn get_stats(&self) -> Result<OracleStats> {
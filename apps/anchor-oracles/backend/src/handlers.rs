@@ -1,21 +1,43 @@
 //! API handlers for Anchor Oracles
 
+use anchor_api_error::{ApiError, ErrorCode};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::Deserialize;
 use std::sync::Arc;
 
+use anchor_specs::oracle::{
+    dispute_window_open, DisputeReason, OracleDisputeSpec, OracleSlashSpec, SlashVerdict,
+};
+
+use crate::config::Config;
 use crate::db::Database;
 use crate::models::{
-    Attestation, CategoryInfo, CreateEventRequest, Dispute, EventRequest, Oracle, OracleStats,
-    RegisterOracleRequest, SubmitAttestationRequest,
+    Attestation, CategoryInfo, CreateDisputeRequest, CreateEventRequest, CreateFeedRequest,
+    CreateSlashRequest, Dispute, EventQuorum, EventRequest, Feed, FeedSeries, Oracle, OracleStats,
+    RegisterOracleRequest, Slash, SubmitAttestationRequest, WalletTxResponse,
 };
+use crate::wallet::WalletClient;
 
-pub type AppState = Arc<Database>;
+/// Application state
+pub struct AppState {
+    pub db: Arc<Database>,
+    pub wallet: WalletClient,
+    pub config: Config,
+}
+
+impl AppState {
+    pub fn new(db: Arc<Database>, config: Config) -> Arc<Self> {
+        Arc::new(Self {
+            db,
+            wallet: WalletClient::new(config.wallet_url.clone()),
+            config,
+        })
+    }
+}
 
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
@@ -28,7 +50,34 @@ pub struct StatusFilter {
     pub status: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FeedFilter {
+    /// Publishing oracle pubkey (hex)
+    pub oracle: Option<String>,
+}
+
+/// Block range of a feed series
+#[derive(Debug, Deserialize)]
+pub struct SeriesRange {
+    pub from: Option<i32>,
+    pub to: Option<i32>,
+}
+
+/// Most publications returned in one series
+const MAX_SERIES_POINTS: i64 = 1000;
+
+/// Longest feed name
+const MAX_FEED_NAME_LEN: usize = 64;
+
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service is healthy")
+    ),
+    tag = "health"
+)]
 pub async fn health() -> impl IntoResponse {
     Json(serde_json::json!({ "status": "ok", "service": "anchor-oracles" }))
 }
@@ -42,12 +91,12 @@ pub async fn health() -> impl IntoResponse {
     ),
     tag = "stats"
 )]
-pub async fn get_stats(State(db): State<AppState>) -> impl IntoResponse {
-    match db.get_stats().await {
+pub async fn get_stats(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.db.get_stats().await {
         Ok(stats) => Json(stats).into_response(),
         Err(e) => {
             tracing::error!("Failed to get stats: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -66,17 +115,17 @@ pub async fn get_stats(State(db): State<AppState>) -> impl IntoResponse {
     tag = "oracles"
 )]
 pub async fn list_oracles(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(50).min(100);
     let offset = params.offset.unwrap_or(0);
 
-    match db.get_oracles(limit, offset).await {
+    match state.db.get_oracles(limit, offset).await {
         Ok(oracles) => Json(oracles).into_response(),
         Err(e) => {
             tracing::error!("Failed to list oracles: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -95,20 +144,20 @@ pub async fn list_oracles(
     tag = "oracles"
 )]
 pub async fn get_oracle(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Path(pubkey): Path<String>,
 ) -> impl IntoResponse {
     let pubkey_bytes = match hex::decode(&pubkey) {
         Ok(b) => b,
-        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid pubkey hex").into_response(),
+        Err(_) => return ApiError::bad_request("Invalid pubkey hex").into_response(),
     };
 
-    match db.get_oracle_by_pubkey(&pubkey_bytes).await {
+    match state.db.get_oracle_by_pubkey(&pubkey_bytes).await {
         Ok(Some(oracle)) => Json(oracle).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Oracle not found").into_response(),
+        Ok(None) => ApiError::not_found("Oracle not found").into_response(),
         Err(e) => {
             tracing::error!("Failed to get oracle: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -136,7 +185,7 @@ pub struct AddressesBody {
     tag = "oracles"
 )]
 pub async fn get_oracles_by_addresses(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Query(params): Query<AddressesQuery>,
 ) -> impl IntoResponse {
     let addresses: Vec<String> = params
@@ -151,11 +200,11 @@ pub async fn get_oracles_by_addresses(
         return Json(Vec::<Oracle>::new()).into_response();
     }
 
-    match db.get_oracles_by_addresses(&addresses).await {
+    match state.db.get_oracles_by_addresses(&addresses).await {
         Ok(oracles) => Json(oracles).into_response(),
         Err(e) => {
             tracing::error!("Failed to get oracles by addresses: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -171,18 +220,18 @@ pub async fn get_oracles_by_addresses(
     tag = "oracles"
 )]
 pub async fn post_oracles_by_addresses(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Json(body): Json<AddressesBody>,
 ) -> impl IntoResponse {
     if body.addresses.is_empty() {
         return Json(Vec::<Oracle>::new()).into_response();
     }
 
-    match db.get_oracles_by_addresses(&body.addresses).await {
+    match state.db.get_oracles_by_addresses(&body.addresses).await {
         Ok(oracles) => Json(oracles).into_response(),
         Err(e) => {
             tracing::error!("Failed to get oracles by addresses: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -201,27 +250,27 @@ pub async fn post_oracles_by_addresses(
     tag = "oracles"
 )]
 pub async fn get_oracle_attestations(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Path(pubkey): Path<String>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
     let pubkey_bytes = match hex::decode(&pubkey) {
         Ok(b) => b,
-        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid pubkey hex").into_response(),
+        Err(_) => return ApiError::bad_request("Invalid pubkey hex").into_response(),
     };
 
-    let oracle = match db.get_oracle_by_pubkey(&pubkey_bytes).await {
+    let oracle = match state.db.get_oracle_by_pubkey(&pubkey_bytes).await {
         Ok(Some(o)) => o,
-        Ok(None) => return (StatusCode::NOT_FOUND, "Oracle not found").into_response(),
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Ok(None) => return ApiError::not_found("Oracle not found").into_response(),
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
     };
 
     let limit = params.limit.unwrap_or(50).min(100);
-    match db.get_attestations_by_oracle(oracle.id, limit).await {
+    match state.db.get_attestations_by_oracle(oracle.id, limit).await {
         Ok(attestations) => Json(attestations).into_response(),
         Err(e) => {
             tracing::error!("Failed to get oracle attestations: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -237,7 +286,7 @@ pub async fn get_oracle_attestations(
     tag = "oracles"
 )]
 pub async fn register_oracle(
-    State(_db): State<AppState>,
+    State(_state): State<Arc<AppState>>,
     Json(req): Json<RegisterOracleRequest>,
 ) -> impl IntoResponse {
     // In production, this would create an unsigned transaction
@@ -265,17 +314,17 @@ pub async fn register_oracle(
     tag = "attestations"
 )]
 pub async fn list_attestations(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(50).min(100);
     let offset = params.offset.unwrap_or(0);
 
-    match db.get_attestations(limit, offset).await {
+    match state.db.get_attestations(limit, offset).await {
         Ok(attestations) => Json(attestations).into_response(),
         Err(e) => {
             tracing::error!("Failed to list attestations: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -291,7 +340,7 @@ pub async fn list_attestations(
     tag = "attestations"
 )]
 pub async fn submit_attestation(
-    State(_db): State<AppState>,
+    State(_state): State<Arc<AppState>>,
     Json(req): Json<SubmitAttestationRequest>,
 ) -> impl IntoResponse {
     // In production, this would create an unsigned transaction
@@ -318,17 +367,21 @@ pub async fn submit_attestation(
     tag = "events"
 )]
 pub async fn list_events(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
     Query(filter): Query<StatusFilter>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(50).min(100);
 
-    match db.get_event_requests(filter.status.as_deref(), limit).await {
+    match state
+        .db
+        .get_event_requests(filter.status.as_deref(), limit)
+        .await
+    {
         Ok(events) => Json(events).into_response(),
         Err(e) => {
             tracing::error!("Failed to list events: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -339,12 +392,13 @@ pub async fn list_events(
     path = "/api/events/request",
     request_body = CreateEventRequest,
     responses(
-        (status = 200, description = "Event request created")
+        (status = 200, description = "Event request created"),
+        (status = 400, description = "Invalid threshold or oracle set")
     ),
     tag = "events"
 )]
 pub async fn create_event_request(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Json(req): Json<CreateEventRequest>,
 ) -> impl IntoResponse {
     // Generate a random event_id (32 bytes)
@@ -352,13 +406,44 @@ pub async fn create_event_request(
     let mut event_id = [0u8; 32];
     rand::thread_rng().fill(&mut event_id);
 
-    match db
+    // Validate the k-of-n parameters
+    let mut oracle_pubkeys = Vec::with_capacity(req.oracle_pubkeys.len());
+    for pubkey in &req.oracle_pubkeys {
+        match hex::decode(pubkey) {
+            Ok(bytes) if bytes.len() == 32 => oracle_pubkeys.push(bytes),
+            _ => {
+                return ApiError::bad_request(format!("Invalid oracle pubkey: {}", pubkey))
+                    .into_response()
+            }
+        }
+    }
+    oracle_pubkeys.sort();
+    oracle_pubkeys.dedup();
+
+    let threshold = req.threshold.unwrap_or(1);
+    if !(1..=u8::MAX as i32).contains(&threshold) {
+        return ApiError::bad_request(format!("Threshold must be between 1 and {}", u8::MAX))
+            .into_response();
+    }
+    if !oracle_pubkeys.is_empty() && threshold as usize > oracle_pubkeys.len() {
+        return ApiError::bad_request(format!(
+            "Threshold {} exceeds eligible oracle count {}",
+            threshold,
+            oracle_pubkeys.len()
+        ))
+        .into_response();
+    }
+
+    match state
+        .db
         .insert_event_request(
             &event_id,
             req.category,
             &req.description,
             req.resolution_block,
             req.bounty_sats,
+            threshold,
+            &oracle_pubkeys,
         )
         .await
     {
@@ -370,11 +455,13 @@ pub async fn create_event_request(
             "description": req.description,
             "resolution_block": req.resolution_block,
             "bounty_sats": req.bounty_sats,
+            "threshold": threshold,
+            "oracle_pubkeys": oracle_pubkeys.iter().map(hex::encode).collect::<Vec<_>>(),
         }))
         .into_response(),
         Err(e) => {
             tracing::error!("Failed to create event request: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -392,13 +479,16 @@ pub async fn create_event_request(
     ),
     tag = "events"
 )]
-pub async fn get_event(State(db): State<AppState>, Path(id): Path<i32>) -> impl IntoResponse {
-    match db.get_event_by_id(id).await {
+pub async fn get_event(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    match state.db.get_event_by_id(id).await {
         Ok(Some(event)) => Json(event).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Event not found").into_response(),
+        Ok(None) => ApiError::not_found("Event not found").into_response(),
         Err(e) => {
             tracing::error!("Failed to get event: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -416,18 +506,269 @@ pub async fn get_event(State(db): State<AppState>, Path(id): Path<i32>) -> impl
     tag = "events"
 )]
 pub async fn get_event_attestations(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    match db.get_attestations_by_event(id).await {
+    match state.db.get_attestations_by_event(id).await {
         Ok(attestations) => Json(attestations).into_response(),
         Err(e) => {
             tracing::error!("Failed to get event attestations: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
 
+/// Get quorum status for a k-of-n event
+#[utoipa::path(
+    get,
+    path = "/api/events/{id}/quorum",
+    params(
+        ("id" = i32, Path, description = "Event ID")
+    ),
+    responses(
+        (status = 200, description = "Quorum status for this event", body = EventQuorum),
+        (status = 404, description = "Event not found")
+    ),
+    tag = "events"
+)]
+pub async fn get_event_quorum(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    match state.db.get_event_aggregate(id).await {
+        Ok(Some(event)) => Json(EventQuorum::from_aggregate(
+            event.request_id,
+            event.quorum_height,
+            &event.aggregate,
+        ))
+        .into_response(),
+        Ok(None) => ApiError::not_found("Event not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get event quorum: {}", e);
+            ApiError::internal(e.to_string()).into_response()
+        }
+    }
+}
+
+/// List recurring feeds
+#[utoipa::path(
+    get,
+    path = "/api/feeds",
+    params(
+        ("oracle" = Option<String>, Query, description = "Filter by oracle pubkey (hex)"),
+        ("limit" = Option<i64>, Query, description = "Max results")
+    ),
+    responses(
+        (status = 200, description = "List of feeds", body = Vec<Feed>),
+        (status = 400, description = "Invalid oracle pubkey")
+    ),
+    tag = "feeds"
+)]
+pub async fn list_feeds(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<FeedFilter>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(50).min(100);
+    let oracle = match filter.oracle.as_deref().map(hex::decode).transpose() {
+        Ok(oracle) => oracle,
+        Err(_) => return ApiError::bad_request("Invalid oracle pubkey").into_response(),
+    };
+
+    match state.db.get_feeds(oracle.as_deref(), limit).await {
+        Ok(feeds) => Json(feeds).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list feeds: {}", e);
+            ApiError::internal(e.to_string()).into_response()
+        }
+    }
+}
+
+/// Declare a recurring feed
+///
+/// The oracle publishes each value as an attestation under the returned
+/// feed's `event_id`, once per `interval_blocks`.
+#[utoipa::path(
+    post,
+    path = "/api/feeds",
+    request_body = CreateFeedRequest,
+    responses(
+        (status = 200, description = "Feed declared", body = Feed),
+        (status = 400, description = "Invalid name, interval or start height"),
+        (status = 404, description = "Oracle not found"),
+        (status = 409, description = "The oracle already has a feed with this name")
+    ),
+    tag = "feeds"
+)]
+pub async fn create_feed(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateFeedRequest>,
+) -> Response {
+    let name = req.name.trim();
+    if name.is_empty() || name.len() > MAX_FEED_NAME_LEN {
+        return ApiError::bad_request(format!(
+            "Feed name must be 1 to {} bytes",
+            MAX_FEED_NAME_LEN
+        ))
+        .into_response();
+    }
+    if req.interval_blocks < 1 {
+        return ApiError::bad_request("interval_blocks must be at least 1").into_response();
+    }
+    let Ok(pubkey) = hex::decode(&req.oracle_pubkey) else {
+        return ApiError::bad_request("Invalid oracle pubkey").into_response();
+    };
+    let oracle_id = match state.db.get_oracle_id_by_pubkey(&pubkey).await {
+        Ok(Some(id)) => id,
+        Ok(None) => return ApiError::not_found("Oracle not found").into_response(),
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+    let tip = match state.db.get_last_block_height().await {
+        Ok(height) => height,
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+    let start_height = req.start_height.unwrap_or(tip + 1);
+    if start_height <= tip {
+        return ApiError::bad_request(format!(
+            "start_height must be after the last indexed block ({})",
+            tip
+        ))
+        .into_response();
+    }
+
+    use rand::Rng;
+    let mut event_id = [0u8; 32];
+    rand::thread_rng().fill(&mut event_id);
+
+    let id = match state
+        .db
+        .insert_feed(
+            oracle_id,
+            &event_id,
+            name,
+            req.description.as_deref(),
+            req.category,
+            req.interval_blocks,
+            start_height,
+        )
+        .await
+    {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            return ApiError::new(
+                ErrorCode::Conflict,
+                format!("The oracle already has a feed named {}", name),
+            )
+            .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to create feed: {}", e);
+            return ApiError::internal(e.to_string()).into_response();
+        }
+    };
+
+    match state.db.get_feed(id).await {
+        Ok(Some(feed)) => Json(feed).into_response(),
+        Ok(None) => ApiError::not_found("Feed not found").into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+/// Get feed by ID
+#[utoipa::path(
+    get,
+    path = "/api/feeds/{id}",
+    params(
+        ("id" = i32, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Feed details", body = Feed),
+        (status = 404, description = "Feed not found")
+    ),
+    tag = "feeds"
+)]
+pub async fn get_feed(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    match state.db.get_feed(id).await {
+        Ok(Some(feed)) => Json(feed).into_response(),
+        Ok(None) => ApiError::not_found("Feed not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get feed: {}", e);
+            ApiError::internal(e.to_string()).into_response()
+        }
+    }
+}
+
+/// Get the attested time series of a feed
+///
+/// Publications and missed slots between two block heights, inclusive. The
+/// range defaults to the feed's start through the last indexed block; at
+/// most 1000 publications are returned, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/feeds/{id}/series",
+    params(
+        ("id" = i32, Path, description = "Feed ID"),
+        ("from" = Option<i32>, Query, description = "First block height"),
+        ("to" = Option<i32>, Query, description = "Last block height")
+    ),
+    responses(
+        (status = 200, description = "Feed series", body = FeedSeries),
+        (status = 400, description = "Invalid range"),
+        (status = 404, description = "Feed not found")
+    ),
+    tag = "feeds"
+)]
+pub async fn get_feed_series(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Query(range): Query<SeriesRange>,
+) -> Response {
+    let feed = match state.db.get_feed(id).await {
+        Ok(Some(feed)) => feed,
+        Ok(None) => return ApiError::not_found("Feed not found").into_response(),
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+    let to = match range.to {
+        Some(to) => to,
+        None => match state.db.get_last_block_height().await {
+            Ok(height) => height,
+            Err(e) => return ApiError::internal(e.to_string()).into_response(),
+        },
+    };
+    let from = range.from.unwrap_or(feed.start_height);
+    if from > to {
+        return ApiError::bad_request("from must not be after to").into_response();
+    }
+
+    let points = match state
+        .db
+        .get_feed_points(&feed, from, to, MAX_SERIES_POINTS)
+        .await
+    {
+        Ok(points) => points,
+        Err(e) => {
+            tracing::error!("Failed to get feed series: {}", e);
+            return ApiError::internal(e.to_string()).into_response();
+        }
+    };
+    let missed = match state.db.get_feed_misses(feed.id, from, to).await {
+        Ok(missed) => missed,
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+
+    Json(FeedSeries {
+        feed,
+        from,
+        to,
+        points,
+        missed,
+    })
+    .into_response()
+}
+
 /// List disputes
 #[utoipa::path(
     get,
@@ -442,17 +783,258 @@ pub async fn get_event_attestations(
     tag = "disputes"
 )]
 pub async fn list_disputes(
-    State(db): State<AppState>,
+    State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
     Query(filter): Query<StatusFilter>,
 ) -> impl IntoResponse {
     let limit = params.limit.unwrap_or(50).min(100);
 
-    match db.get_disputes(filter.status.as_deref(), limit).await {
+    match state.db.get_disputes(filter.status.as_deref(), limit).await {
         Ok(disputes) => Json(disputes).into_response(),
         Err(e) => {
             tracing::error!("Failed to list disputes: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
+        }
+    }
+}
+
+/// Build and broadcast a bonded dispute against an attestation
+#[utoipa::path(
+    post,
+    path = "/api/disputes/create",
+    request_body = CreateDisputeRequest,
+    responses(
+        (status = 200, description = "Dispute transaction created", body = WalletTxResponse),
+        (status = 400, description = "Invalid dispute"),
+        (status = 404, description = "Attestation not found")
+    ),
+    tag = "disputes"
+)]
+pub async fn create_dispute(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateDisputeRequest>,
+) -> impl IntoResponse {
+    let disputer_pubkey: [u8; 32] = match hex::decode(&req.disputer_pubkey)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+    {
+        Some(pubkey) => pubkey,
+        None => {
+            return ApiError::bad_request("Invalid disputer pubkey").into_response();
+        }
+    };
+    let reason = match DisputeReason::try_from(req.reason) {
+        Ok(reason) => reason,
+        Err(e) => return ApiError::bad_request(e.to_string()).into_response(),
+    };
+    if req.bond_sats < state.config.min_dispute_bond_sats {
+        return ApiError::bad_request(format!(
+            "Dispute bond must be at least {} sats",
+            state.config.min_dispute_bond_sats
+        ))
+        .into_response();
+    }
+
+    let (attestation_txid, attestation_vout, attestation_height) =
+        match state.db.get_attestation_outpoint(req.attestation_id).await {
+            Ok(Some(outpoint)) => outpoint,
+            Ok(None) => return ApiError::not_found("Attestation not found").into_response(),
+            Err(e) => {
+                tracing::error!("Failed to get attestation: {}", e);
+                return ApiError::internal(e.to_string()).into_response();
+            }
+        };
+    let (Ok(attestation_txid), Ok(attestation_vout)) = (
+        <[u8; 32]>::try_from(attestation_txid),
+        u8::try_from(attestation_vout),
+    ) else {
+        return ApiError::bad_request("Attestation cannot be anchored").into_response();
+    };
+
+    // Reject early if the dispute would confirm after the window closes
+    if let Some(att_height) = attestation_height {
+        let next_height = state.db.get_last_block_height().await.unwrap_or(0) + 1;
+        if !dispute_window_open(
+            att_height as u32,
+            next_height as u32,
+            state.config.dispute_window_blocks,
+        ) {
+            return ApiError::bad_request("Dispute window has closed").into_response();
+        }
+    }
+
+    let spec = OracleDisputeSpec {
+        disputer_pubkey,
+        attestation_txid,
+        attestation_vout: attestation_vout as u16,
+        reason,
+        bond_sats: req.bond_sats as u64,
+        evidence: req.evidence,
+    };
+
+    let bond_address = match state.wallet.new_address().await {
+        Ok(address) => address,
+        Err(e) => {
+            tracing::error!("Failed to get bond address: {}", e);
+            return wallet_error(e);
+        }
+    };
+    let created = match state
+        .wallet
+        .create_dispute(
+            &spec,
+            attestation_vout,
+            &bond_address,
+            req.carrier.unwrap_or(0),
+            req.fee_rate.unwrap_or(1),
+        )
+        .await
+    {
+        Ok(created) => created,
+        Err(e) => {
+            tracing::error!("Failed to create dispute: {}", e);
+            return wallet_error(e);
+        }
+    };
+
+    // Keep the bond out of coin selection until the dispute is settled
+    let bond_vout = created.output_to(&bond_address);
+    if let Some(vout) = bond_vout {
+        if let Err(e) = state.wallet.lock_bond(&created.txid, vout).await {
+            tracing::warn!(
+                "Failed to lock dispute bond {}:{}: {}",
+                created.txid,
+                vout,
+                e
+            );
+        }
+    }
+
+    Json(WalletTxResponse {
+        txid: created.txid,
+        vout: created.vout,
+        hex: created.hex,
+        bond_vout,
+    })
+    .into_response()
+}
+
+/// Build and broadcast a slash settling a pending dispute
+#[utoipa::path(
+    post,
+    path = "/api/disputes/{id}/slash",
+    params(
+        ("id" = i32, Path, description = "Dispute ID")
+    ),
+    request_body = CreateSlashRequest,
+    responses(
+        (status = 200, description = "Slash transaction created", body = WalletTxResponse),
+        (status = 400, description = "Invalid slash"),
+        (status = 404, description = "Dispute not found"),
+        (status = 409, description = "Dispute already settled")
+    ),
+    tag = "disputes"
+)]
+pub async fn create_slash(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    Json(req): Json<CreateSlashRequest>,
+) -> impl IntoResponse {
+    let verdict = match SlashVerdict::try_from(req.verdict) {
+        Ok(verdict) => verdict,
+        Err(e) => return ApiError::bad_request(e.to_string()).into_response(),
+    };
+    let settlement = match state.db.get_dispute_settlement_by_id(id).await {
+        Ok(Some(settlement)) => settlement,
+        Ok(None) => return ApiError::not_found("Dispute not found").into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get dispute: {}", e);
+            return ApiError::internal(e.to_string()).into_response();
+        }
+    };
+    if settlement.status != "pending" {
+        return ApiError::conflict(format!("Dispute is already {}", settlement.status))
+            .into_response();
+    }
+
+    let Some((bond_txid, bond_vout, bond_sats)) = settlement.losing_bond(verdict) else {
+        return ApiError::bad_request("Losing party has no bond to slash").into_response();
+    };
+    let amount_sats = req.amount_sats.unwrap_or(bond_sats);
+    if amount_sats <= 0 || amount_sats > bond_sats {
+        return ApiError::bad_request(format!(
+            "Slash amount must be between 1 and {} sats",
+            bond_sats
+        ))
+        .into_response();
+    }
+
+    let (Ok(dispute_txid), Ok(dispute_vout)) = (
+        <[u8; 32]>::try_from(settlement.dispute_txid.as_slice()),
+        u8::try_from(settlement.dispute_vout),
+    ) else {
+        return ApiError::bad_request("Dispute cannot be anchored").into_response();
+    };
+
+    let spec = OracleSlashSpec {
+        dispute_txid,
+        verdict,
+        amount_sats: amount_sats as u64,
+        note: req.note,
+    };
+
+    // The bond may have been locked when the dispute was created
+    let bond_txid = hex::encode(bond_txid);
+    let _ = state.wallet.unlock_bond(&bond_txid, bond_vout as u32).await;
+
+    match state
+        .wallet
+        .create_slash(
+            &spec,
+            dispute_vout,
+            (&bond_txid, bond_vout as u32),
+            req.carrier.unwrap_or(0),
+            req.fee_rate.unwrap_or(1),
+        )
+        .await
+    {
+        Ok(created) => Json(WalletTxResponse {
+            txid: created.txid,
+            vout: created.vout,
+            hex: created.hex,
+            bond_vout: None,
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to create slash: {}", e);
+            wallet_error(e)
+        }
+    }
+}
+
+/// List slashes
+#[utoipa::path(
+    get,
+    path = "/api/slashes",
+    params(
+        ("limit" = Option<i64>, Query, description = "Max results")
+    ),
+    responses(
+        (status = 200, description = "List of slashes", body = Vec<Slash>)
+    ),
+    tag = "disputes"
+)]
+pub async fn list_slashes(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(50).min(100);
+
+    match state.db.get_slashes(limit).await {
+        Ok(slashes) => Json(slashes).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list slashes: {}", e);
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
@@ -466,12 +1048,21 @@ pub async fn list_disputes(
     ),
     tag = "categories"
 )]
-pub async fn list_categories(State(db): State<AppState>) -> impl IntoResponse {
-    match db.get_categories().await {
+pub async fn list_categories(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.db.get_categories().await {
         Ok(categories) => Json(categories).into_response(),
         Err(e) => {
             tracing::error!("Failed to list categories: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            ApiError::internal(e.to_string()).into_response()
         }
     }
 }
+
+/// Error response for a failed wallet call, keeping the wallet's error code
+fn wallet_error(e: anyhow::Error) -> Response {
+    match e.downcast::<ApiError>() {
+        Ok(err) => err,
+        Err(e) => ApiError::new(ErrorCode::UpstreamError, e.to_string()),
+    }
+    .into_response()
+}
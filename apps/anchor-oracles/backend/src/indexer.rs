@@ -1,6 +1,8 @@
 //! Indexer for Anchor Oracle messages from the blockchain
 
 use anchor_core::{carrier::CarrierSelector, AnchorKind};
+use anchor_specs::oracle::{dispute_window_open, OracleDisputeSpec, OracleSlashSpec};
+use anchor_specs::KindSpec;
 use anyhow::Result;
 use bitcoin::consensus::encode::deserialize;
 use bitcoin::hashes::Hash;
@@ -116,60 +118,35 @@ impl OracleAttestationBody {
     }
 }
 
-/// Oracle dispute body parser
-pub struct OracleDisputeBody {
-    pub disputer_pubkey: [u8; 32],
-    pub attestation_txid: [u8; 32],
-    #[allow(dead_code)]
-    pub attestation_vout: u16,
-    pub reason: u8,
-    pub stake_sats: i64,
-    #[allow(dead_code)]
-    pub evidence: String,
+pub struct Indexer {
+    db: Arc<Database>,
+    rpc: Client,
+    carrier_selector: CarrierSelector,
+    dispute_window_blocks: u32,
+    min_dispute_bond_sats: i64,
 }
 
-impl OracleDisputeBody {
-    pub fn parse(body: &[u8]) -> Option<Self> {
-        // Minimum: disputer(32) + att_txid(32) + att_vout(2) + reason(1) + stake(8) = 75 bytes
-        if body.len() < 75 {
-            return None;
-        }
-
-        let mut disputer_pubkey = [0u8; 32];
-        disputer_pubkey.copy_from_slice(&body[0..32]);
-
-        let mut attestation_txid = [0u8; 32];
-        attestation_txid.copy_from_slice(&body[32..64]);
-
-        let attestation_vout = u16::from_be_bytes([body[64], body[65]]);
-
-        let reason = body[66];
-
-        let stake_sats = i64::from_be_bytes([
-            body[67], body[68], body[69], body[70], body[71], body[72], body[73], body[74],
-        ]);
-
-        let evidence = if body.len() > 75 {
-            String::from_utf8(body[75..].to_vec()).unwrap_or_default()
-        } else {
-            String::new()
-        };
+/// Convert a txid to display byte order, as stored in the database
+fn display_txid(txid: &bitcoin::Txid) -> Vec<u8> {
+    let mut bytes = txid.to_byte_array();
+    bytes.reverse();
+    bytes.to_vec()
+}
 
-        Some(Self {
-            disputer_pubkey,
-            attestation_txid,
-            attestation_vout,
-            reason,
-            stake_sats,
-            evidence,
-        })
-    }
+/// First non-OP_RETURN output worth at least `min_sats`, used as a bond
+fn find_bond_output(tx: &Transaction, min_sats: i64) -> Option<(i32, i64)> {
+    tx.output.iter().enumerate().find_map(|(vout, output)| {
+        let value = output.value.to_sat() as i64;
+        (!output.script_pubkey.is_op_return() && value >= min_sats).then_some((vout as i32, value))
+    })
 }
 
-pub struct Indexer {
-    db: Arc<Database>,
-    rpc: Client,
-    carrier_selector: CarrierSelector,
+/// Check whether `tx` spends the outpoint `txid:vout` (display byte order)
+fn spends(tx: &Transaction, txid: &[u8], vout: i32) -> bool {
+    tx.input.iter().any(|input| {
+        input.previous_output.vout as i32 == vout
+            && display_txid(&input.previous_output.txid) == txid
+    })
 }
 
 impl Indexer {
@@ -189,6 +166,8 @@ impl Indexer {
             db,
             rpc,
             carrier_selector,
+            dispute_window_blocks: config.dispute_window_blocks,
+            min_dispute_bond_sats: config.min_dispute_bond_sats,
         })
     }
 
@@ -232,6 +211,43 @@ impl Indexer {
         for tx in &block.txdata {
             self.process_transaction(tx, height).await?;
         }
+
+        // Feed slots closing at this block must have a publication by now
+        for missed in self.db.check_feed_cadence(height).await? {
+            tracing::warn!(
+                "Feed {} ({}) missed slot {} (blocks {}-{})",
+                missed.feed_id,
+                missed.feed_name,
+                missed.miss.slot,
+                missed.miss.slot_start,
+                missed.miss.slot_end - 1
+            );
+        }
+
+        // Bonds still recorded after the block's slashes were applied have been withdrawn
+        let spent: Vec<(Vec<u8>, i32, Vec<u8>)> = block
+            .txdata
+            .iter()
+            .filter(|tx| !tx.is_coinbase())
+            .flat_map(|tx| {
+                let txid = display_txid(&tx.compute_txid());
+                tx.input.iter().map(move |input| {
+                    (
+                        display_txid(&input.previous_output.txid),
+                        input.previous_output.vout as i32,
+                        txid.clone(),
+                    )
+                })
+            })
+            .collect();
+        let released = self.db.release_spent_bonds(&spent, height).await?;
+        if released > 0 {
+            tracing::info!(
+                "Released {} withdrawn oracle bond(s) at block {}",
+                released,
+                height
+            );
+        }
         Ok(())
     }
 
@@ -255,6 +271,226 @@ impl Indexer {
         None
     }
 
+    /// Re-evaluate the k-of-n quorum of an event after a new attestation.
+    ///
+    /// The event is fulfilled (attributed to `oracle_id`) the first time a
+    /// single outcome is backed by at least `threshold` eligible oracles.
+    async fn update_event_quorum(&self, event_id: &[u8], oracle_id: i32, height: i32) {
+        let event = match self.db.get_event_aggregate_by_event_id(event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Failed to load event quorum: {}", e);
+                return;
+            }
+        };
+
+        if event.quorum_height.is_some() {
+            return;
+        }
+
+        let status = event.aggregate.quorum();
+        tracing::debug!(
+            "Event {} quorum: {}/{} oracles agree ({} attested)",
+            event.request_id,
+            status.leading_count,
+            status.threshold,
+            status.attested_oracles
+        );
+
+        if let (true, Some(outcome)) = (status.reached, status.leading_outcome) {
+            match self
+                .db
+                .record_event_quorum(event.request_id, &outcome, height, oracle_id)
+                .await
+            {
+                Ok(true) => tracing::info!(
+                    "Event {} reached quorum ({}-of-{}) at block {}",
+                    event.request_id,
+                    status.threshold,
+                    status.eligible_oracles.unwrap_or(status.attested_oracles),
+                    height
+                ),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to record event quorum: {}", e),
+            }
+        }
+    }
+
+    /// Record the bond output of a registration (the first output covering the stake)
+    async fn record_oracle_bond(
+        &self,
+        oracle: Result<i32>,
+        tx: &Transaction,
+        txid: &[u8],
+        stake_sats: i64,
+    ) {
+        let Ok(oracle_id) = oracle else {
+            return;
+        };
+        if stake_sats <= 0 {
+            return;
+        }
+
+        match find_bond_output(tx, stake_sats) {
+            Some((vout, value)) => {
+                if let Err(e) = self.db.set_oracle_bond(oracle_id, txid, vout, value).await {
+                    tracing::warn!("Failed to record oracle bond: {}", e);
+                }
+            }
+            None => tracing::warn!(
+                "Oracle {} registration has no output covering its {} sat stake",
+                oracle_id,
+                stake_sats
+            ),
+        }
+    }
+
+    /// Index a dispute, rejecting it if it is outside the dispute window or
+    /// does not carry a sufficient challenger bond.
+    async fn index_dispute(
+        &self,
+        disp: &OracleDisputeSpec,
+        tx: &Transaction,
+        txid: &[u8],
+        vout: i32,
+        height: i32,
+    ) {
+        let attestation = match self
+            .db
+            .get_attestation_by_txid(&disp.attestation_txid)
+            .await
+        {
+            Ok(Some(attestation)) => Some(attestation),
+            // For testnet, fall back to the most recent attestation if any
+            _ => match self.db.get_attestations(1, 0).await {
+                Ok(attestations) => attestations.first().map(|a| (a.id, a.block_height)),
+                Err(_) => None,
+            },
+        };
+        let Some((attestation_id, attestation_height)) = attestation else {
+            tracing::warn!("Dispute at vout {} - no attestations in database", vout);
+            return;
+        };
+
+        let bond_sats = disp.bond_sats as i64;
+        let bond = find_bond_output(tx, bond_sats);
+        let window_open = attestation_height.is_none_or(|att_height| {
+            dispute_window_open(att_height as u32, height as u32, self.dispute_window_blocks)
+        });
+        let rejection = if !window_open {
+            Some("window_closed")
+        } else if bond_sats < self.min_dispute_bond_sats {
+            Some("bond_too_small")
+        } else if bond.is_none() {
+            Some("bond_missing")
+        } else {
+            None
+        };
+
+        match self
+            .db
+            .insert_dispute(
+                attestation_id,
+                &disp.disputer_pubkey,
+                txid,
+                vout,
+                Some(height),
+                disp.reason as i32,
+                bond_sats,
+                bond.map(|(bond_vout, _)| bond_vout),
+                rejection,
+            )
+            .await
+        {
+            Ok(id) => tracing::info!(
+                "Indexed dispute id={} for attestation {} (reason: {:?}, bond: {} sats){}",
+                id,
+                attestation_id,
+                disp.reason,
+                bond_sats,
+                rejection
+                    .map(|r| format!(" - rejected: {}", r))
+                    .unwrap_or_default()
+            ),
+            Err(e) => tracing::warn!("Failed to insert dispute: {}", e),
+        }
+    }
+
+    /// Index a slash. A slash settles its dispute only if the dispute is still
+    /// pending and the transaction spends the losing party's bond.
+    async fn index_slash(
+        &self,
+        slash: &OracleSlashSpec,
+        tx: &Transaction,
+        txid: &[u8],
+        vout: i32,
+        height: i32,
+    ) {
+        let amount_sats = slash.amount_sats as i64;
+        let settlement = match self.db.get_dispute_settlement(&slash.dispute_txid).await {
+            Ok(settlement) => settlement,
+            Err(e) => {
+                tracing::warn!("Failed to load dispute for slash: {}", e);
+                return;
+            }
+        };
+
+        let rejection = match &settlement {
+            None => Some("unknown_dispute"),
+            Some(_) if slash.validate().is_err() => Some("invalid_amount"),
+            Some(s) if s.status != "pending" => Some("dispute_settled"),
+            Some(s) => match s.losing_bond(slash.verdict) {
+                None => Some("bond_missing"),
+                Some((bond_txid, bond_vout, _)) if !spends(tx, bond_txid, bond_vout) => {
+                    Some("bond_not_spent")
+                }
+                Some((_, _, bond_sats)) if amount_sats > bond_sats => Some("amount_exceeds_bond"),
+                Some(_) => None,
+            },
+        };
+
+        let result = match (rejection, &settlement) {
+            (None, Some(settlement)) => self
+                .db
+                .settle_dispute(settlement, slash.verdict, txid, vout, height, amount_sats)
+                .await
+                .map(|id| {
+                    tracing::info!(
+                        "Indexed slash id={} settling dispute {} ({:?}, {} sats)",
+                        id,
+                        settlement.dispute_id,
+                        slash.verdict,
+                        amount_sats
+                    )
+                }),
+            (reason, settlement) => {
+                let reason = reason.unwrap_or("unknown_dispute");
+                tracing::warn!(
+                    "Invalid slash {}:{} for dispute {}: {}",
+                    hex::encode(txid),
+                    vout,
+                    hex::encode(slash.dispute_txid),
+                    reason
+                );
+                self.db
+                    .insert_invalid_slash(
+                        settlement.as_ref().map(|s| s.dispute_id),
+                        txid,
+                        vout,
+                        height,
+                        slash.verdict as i32,
+                        amount_sats,
+                        reason,
+                    )
+                    .await
+            }
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to record slash: {}", e);
+        }
+    }
+
     async fn process_transaction(&self, tx: &Transaction, height: i32) -> Result<()> {
         // Get txid in display format (reversed/big-endian) to match Bitcoin standard
         let mut txid_bytes = tx.compute_txid().to_byte_array();
@@ -277,7 +513,7 @@ impl Indexer {
                         match reg.action {
                             0 => {
                                 // Register new oracle
-                                let _ = self
+                                let result = self
                                     .db
                                     .insert_oracle(
                                         &reg.oracle_pubkey,
@@ -290,11 +526,13 @@ impl Indexer {
                                         creator_address.as_deref(),
                                     )
                                     .await;
+                                self.record_oracle_bond(result, tx, &txid_bytes, reg.stake_amount)
+                                    .await;
                                 tracing::info!("Indexed oracle registration: {} stake={} sats (via {}) creator={:?}", reg.name, reg.stake_amount, carrier_name, creator_address);
                             }
                             1 => {
                                 // Update oracle - handled by upsert
-                                let _ = self
+                                let result = self
                                     .db
                                     .insert_oracle(
                                         &reg.oracle_pubkey,
@@ -307,6 +545,8 @@ impl Indexer {
                                         creator_address.as_deref(),
                                     )
                                     .await;
+                                self.record_oracle_bond(result, tx, &txid_bytes, reg.stake_amount)
+                                    .await;
                                 tracing::info!(
                                     "Indexed oracle update: {} stake={} sats (via {})",
                                     reg.name,
@@ -360,12 +600,9 @@ impl Indexer {
                                                 height,
                                                 carrier_name
                                             );
-                                        // Update event status to fulfilled
-                                        if let Err(e) =
-                                            self.db.fulfill_event(&att.event_id, oracle_id).await
-                                        {
-                                            tracing::warn!("Failed to fulfill event: {}", e);
-                                        }
+                                        // Fulfill the event once enough oracles agree
+                                        self.update_event_quorum(&att.event_id, oracle_id, height)
+                                            .await;
                                     }
                                     Err(e) => tracing::warn!("Failed to insert attestation: {}", e),
                                 }
@@ -397,14 +634,13 @@ impl Indexer {
                                                     oracle.name,
                                                     carrier_name
                                                 );
-                                            // Update event status to fulfilled
-                                            if let Err(e) = self
-                                                .db
-                                                .fulfill_event(&att.event_id, oracle.id)
-                                                .await
-                                            {
-                                                tracing::warn!("Failed to fulfill event: {}", e);
-                                            }
+                                            // Fulfill the event once enough oracles agree
+                                            self.update_event_quorum(
+                                                &att.event_id,
+                                                oracle.id,
+                                                height,
+                                            )
+                                            .await;
                                         }
                                     }
                                 }
@@ -412,83 +648,28 @@ impl Indexer {
                         }
                     }
                 }
-                AnchorKind::OracleDispute => {
-                    tracing::debug!("Found OracleDispute message (via {})", carrier_name);
-                    if let Some(disp) = OracleDisputeBody::parse(&msg.body) {
-                        tracing::debug!(
-                            "Parsed dispute: attestation_txid={}, reason={}, stake={}",
-                            hex::encode(disp.attestation_txid),
-                            disp.reason,
-                            disp.stake_sats
-                        );
-                        // Look up attestation by txid
-                        if let Ok(Some(attestation_id)) = self
-                            .db
-                            .get_attestation_id_by_txid(&disp.attestation_txid)
+                AnchorKind::OracleDispute => match OracleDisputeSpec::from_bytes(&msg.body) {
+                    Ok(disp) => {
+                        self.index_dispute(&disp, tx, &txid_bytes, vout as i32, height)
                             .await
-                        {
-                            match self.db.insert_dispute(
-                                    attestation_id,
-                                    &disp.disputer_pubkey,
-                                    &txid_bytes,
-                                    vout as i32,
-                                    Some(height),
-                                    disp.reason as i32,
-                                    disp.stake_sats,
-                                ).await {
-                                    Ok(id) => tracing::info!(
-                                        "Indexed dispute id={} for attestation {} (reason: {}) (via {})",
-                                        id,
-                                        attestation_id,
-                                        disp.reason,
-                                        carrier_name
-                                    ),
-                                    Err(e) => tracing::warn!("Failed to insert dispute: {}", e),
-                                }
-                        } else {
-                            // For testnet, just insert with fallback to first attestation if any
-                            if let Ok(attestations) = self.db.get_attestations(1, 0).await {
-                                if let Some(att) = attestations.first() {
-                                    match self.db.insert_dispute(
-                                            att.id,
-                                            &disp.disputer_pubkey,
-                                            &txid_bytes,
-                                            vout as i32,
-                                            Some(height),
-                                            disp.reason as i32,
-                                            disp.stake_sats,
-                                        ).await {
-                                            Ok(id) => tracing::info!(
-                                                "Indexed dispute id={} (fallback attestation={}) (via {})",
-                                                id,
-                                                att.id,
-                                                carrier_name
-                                            ),
-                                            Err(e) => tracing::warn!("Failed to insert fallback dispute: {}", e),
-                                        }
-                                } else {
-                                    tracing::warn!(
-                                        "Dispute at vout {} - no attestations in database (via {})",
-                                        vout,
-                                        carrier_name
-                                    );
-                                }
-                            }
-                        }
-                    } else {
-                        tracing::warn!(
-                            "Failed to parse OracleDispute body (len={})",
-                            msg.body.len()
-                        );
                     }
-                }
-                AnchorKind::OracleSlash => {
-                    tracing::debug!(
-                        "Indexed oracle slash at height {} (via {})",
-                        height,
-                        carrier_name
-                    );
-                }
+                    Err(e) => tracing::warn!(
+                        "Failed to parse OracleDispute body (len={}): {}",
+                        msg.body.len(),
+                        e
+                    ),
+                },
+                AnchorKind::OracleSlash => match OracleSlashSpec::from_bytes(&msg.body) {
+                    Ok(slash) => {
+                        self.index_slash(&slash, tx, &txid_bytes, vout as i32, height)
+                            .await
+                    }
+                    Err(e) => tracing::warn!(
+                        "Failed to parse OracleSlash body (len={}): {}",
+                        msg.body.len(),
+                        e
+                    ),
+                },
                 _ => {}
             }
         }
@@ -6,13 +6,15 @@ mod db;
 mod handlers;
 mod indexer;
 mod models;
+mod wallet;
 
+use anchor_api_security::SecurityConfig;
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
@@ -26,34 +28,61 @@ use crate::models::*;
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        health,
         get_stats,
         list_oracles,
         get_oracle,
         get_oracle_attestations,
         register_oracle,
+        get_oracles_by_addresses,
+        post_oracles_by_addresses,
         list_attestations,
         submit_attestation,
         list_events,
         create_event_request,
+        get_event,
+        get_event_attestations,
+        get_event_quorum,
+        list_feeds,
+        create_feed,
+        get_feed,
+        get_feed_series,
         list_disputes,
+        create_dispute,
+        create_slash,
+        list_slashes,
         list_categories,
     ),
     components(schemas(
         Oracle,
         Attestation,
         Dispute,
+        Slash,
         EventRequest,
+        EventQuorum,
+        OutcomeSupport,
+        Feed,
+        FeedPoint,
+        FeedMiss,
+        FeedSeries,
         OracleStats,
         CategoryInfo,
         RegisterOracleRequest,
         SubmitAttestationRequest,
         CreateEventRequest,
+        CreateFeedRequest,
+        CreateDisputeRequest,
+        CreateSlashRequest,
+        AddressesBody,
+        WalletTxResponse,
     )),
     tags(
+        (name = "health", description = "Service health"),
         (name = "stats", description = "Oracle network statistics"),
         (name = "oracles", description = "Oracle registry operations"),
         (name = "attestations", description = "Oracle attestation operations"),
         (name = "events", description = "Event request operations"),
+        (name = "feeds", description = "Scheduled data feeds"),
         (name = "disputes", description = "Dispute operations"),
         (name = "categories", description = "Oracle category operations"),
     ),
@@ -67,6 +96,10 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize tracing
     tracing_subscriber::registry()
         .with(
@@ -90,6 +123,8 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Connected to database");
 
     // Start indexer in background
+    let state = AppState::new(db.clone(), config.clone());
+
     let indexer_db = db.clone();
     let indexer_config = config.clone();
     tokio::spawn(async move {
@@ -106,10 +141,6 @@ async fn main() -> anyhow::Result<()> {
     });
 
     // Build router
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
 
     let app = Router::new()
         // Health check
@@ -136,14 +167,23 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/events/request", post(create_event_request))
         .route("/api/events/:id", get(get_event))
         .route("/api/events/:id/attestations", get(get_event_attestations))
+        .route("/api/events/:id/quorum", get(get_event_quorum))
+        // Feeds
+        .route("/api/feeds", get(list_feeds).post(create_feed))
+        .route("/api/feeds/:id", get(get_feed))
+        .route("/api/feeds/:id/series", get(get_feed_series))
         // Disputes
         .route("/api/disputes", get(list_disputes))
+        .route("/api/disputes/create", post(create_dispute))
+        .route("/api/disputes/:id/slash", post(create_slash))
+        .route("/api/slashes", get(list_slashes))
         // Categories
         .route("/api/categories", get(list_categories))
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .layer(cors)
-        .with_state(db);
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer())
+        .with_state(state);
 
     // Start server
     let addr = format!("{}:{}", config.host, config.port);
@@ -1,5 +1,6 @@
 //! Data models for Anchor Oracles
 
+use anchor_specs::oracle::AggregateAttestation;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -108,6 +109,24 @@ pub struct Dispute {
     pub created_at: String,
 }
 
+/// Slash settling a dispute (kind 33)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Slash {
+    pub id: i32,
+    pub dispute_id: Option<i32>,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub verdict: i32,
+    pub verdict_name: String,
+    pub amount_sats: i64,
+    /// `valid` if the dispute was settled, `invalid` if validation failed
+    pub status: String,
+    /// Validation failure reason for invalid slashes
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
 /// Event request for oracles to fulfill
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct EventRequest {
@@ -120,9 +139,143 @@ pub struct EventRequest {
     pub bounty_sats: i64,
     pub status: String,
     pub fulfilled_by: Option<i32>,
+    /// Number of agreeing oracle attestations required (k)
+    pub threshold: i32,
+    pub created_at: String,
+}
+
+/// Oracles backing a single outcome for an event
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OutcomeSupport {
+    pub outcome: String,
+    pub outcome_text: Option<String>,
+    pub count: i32,
+    pub oracles: Vec<String>,
+}
+
+/// Quorum status of a k-of-n event
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EventQuorum {
+    pub event_request_id: i32,
+    pub event_id: String,
+    /// Required number of agreeing oracles (k)
+    pub threshold: i32,
+    /// Eligible oracle pubkeys (n); empty means any oracle may attest
+    pub eligible_oracles: Vec<String>,
+    pub attested_oracles: i32,
+    pub reached: bool,
+    pub conflicting: bool,
+    pub leading_outcome: Option<String>,
+    pub leading_outcome_text: Option<String>,
+    pub leading_count: i32,
+    /// Block height at which quorum was first reached
+    pub quorum_height: Option<i32>,
+    pub outcomes: Vec<OutcomeSupport>,
+}
+
+impl EventQuorum {
+    pub fn from_aggregate(
+        event_request_id: i32,
+        quorum_height: Option<i32>,
+        aggregate: &AggregateAttestation,
+    ) -> Self {
+        let status = aggregate.quorum();
+        let outcome_text = |outcome: &[u8]| String::from_utf8(outcome.to_vec()).ok();
+
+        Self {
+            event_request_id,
+            event_id: hex::encode(aggregate.event_id),
+            threshold: status.threshold as i32,
+            eligible_oracles: aggregate.eligible.iter().map(hex::encode).collect(),
+            attested_oracles: status.attested_oracles as i32,
+            reached: status.reached,
+            conflicting: status.conflicting,
+            leading_outcome_text: status.leading_outcome.as_deref().and_then(outcome_text),
+            leading_outcome: status.leading_outcome.as_ref().map(hex::encode),
+            leading_count: status.leading_count as i32,
+            quorum_height,
+            outcomes: aggregate
+                .tally()
+                .into_iter()
+                .map(|t| OutcomeSupport {
+                    outcome_text: outcome_text(&t.outcome),
+                    count: t.count() as i32,
+                    outcome: hex::encode(&t.outcome),
+                    oracles: t.oracles.iter().map(hex::encode).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Recurring data feed declared by an oracle
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Feed {
+    pub id: i32,
+    pub oracle_id: i32,
+    pub oracle_pubkey: String,
+    pub oracle_name: String,
+    /// Event id every publication attests under (hex)
+    pub event_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: i32,
+    pub category_name: String,
+    /// Expected publication cadence in blocks
+    pub interval_blocks: i32,
+    /// First block of slot 0
+    pub start_height: i32,
+    /// Closed slots checked for a publication
+    pub checked_slots: i32,
+    pub missed_slots: i32,
+    /// `active`, or `lagging` when the last closed slot was missed
+    pub status: String,
+    pub last_published_height: Option<i32>,
     pub created_at: String,
 }
 
+/// One published value of a feed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedPoint {
+    pub slot: i32,
+    pub block_height: i32,
+    /// Attested value (hex)
+    pub value: String,
+    /// Attested value as UTF-8, e.g. `67250.10`
+    pub value_text: Option<String>,
+    pub txid: String,
+    pub vout: i32,
+    pub schnorr_signature: String,
+}
+
+/// A feed slot that closed without a publication
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedMiss {
+    pub slot: i32,
+    /// First block of the slot
+    pub slot_start: i32,
+    /// First block after the slot
+    pub slot_end: i32,
+}
+
+/// Attested time series of a feed between two block heights
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeedSeries {
+    pub feed: Feed,
+    pub from: i32,
+    pub to: i32,
+    /// Publications, oldest first
+    pub points: Vec<FeedPoint>,
+    /// Missed slots in the range, oldest first
+    pub missed: Vec<FeedMiss>,
+}
+
+/// Slot of a feed a block falls in, `None` before the feed starts
+pub fn feed_slot(start_height: i32, interval_blocks: i32, height: i32) -> Option<i32> {
+    (height >= start_height && interval_blocks > 0)
+        .then(|| (height - start_height) / interval_blocks)
+}
+
 /// Oracle stats summary
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OracleStats {
@@ -176,6 +329,66 @@ pub struct CreateEventRequest {
     pub resolution_block: Option<i32>,
     #[serde(default)]
     pub bounty_sats: i64,
+    /// Number of agreeing oracle attestations required (defaults to 1)
+    pub threshold: Option<i32>,
+    /// Restrict attestations to these oracle pubkeys (hex)
+    #[serde(default)]
+    pub oracle_pubkeys: Vec<String>,
+}
+
+/// Request to declare a recurring feed
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateFeedRequest {
+    /// Publishing oracle (hex)
+    pub oracle_pubkey: String,
+    /// Feed name, unique per oracle, e.g. `BTCUSD`
+    pub name: String,
+    pub description: Option<String>,
+    pub category: i32,
+    /// Expected publication cadence in blocks, e.g. 6 for hourly
+    pub interval_blocks: i32,
+    /// First block of slot 0 (defaults to the next block)
+    pub start_height: Option<i32>,
+}
+
+/// Request to build a bonded dispute transaction
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateDisputeRequest {
+    pub attestation_id: i32,
+    /// Challenger public key (hex, 32 bytes)
+    pub disputer_pubkey: String,
+    /// Dispute reason (1-4)
+    pub reason: u8,
+    /// Challenger bond in satoshis
+    pub bond_sats: i64,
+    #[serde(default)]
+    pub evidence: String,
+    pub carrier: Option<u8>,
+    pub fee_rate: Option<u64>,
+}
+
+/// Request to build a slash transaction settling a dispute
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateSlashRequest {
+    /// 1 = upheld (slash the oracle), 2 = rejected (forfeit the challenger bond)
+    pub verdict: u8,
+    /// Amount to slash (defaults to the full losing bond)
+    pub amount_sats: Option<i64>,
+    #[serde(default)]
+    pub note: String,
+    pub carrier: Option<u8>,
+    pub fee_rate: Option<u64>,
+}
+
+/// Transaction created through the wallet service
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WalletTxResponse {
+    pub txid: String,
+    pub vout: u32,
+    pub hex: String,
+    /// Output holding the challenger bond (disputes only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bond_vout: Option<u32>,
 }
 
 pub fn category_name(category: i32) -> String {
@@ -199,6 +412,14 @@ pub fn dispute_reason_name(reason: i32) -> String {
     }
 }
 
+pub fn slash_verdict_name(verdict: i32) -> String {
+    match verdict {
+        1 => "Upheld".to_string(),
+        2 => "Rejected".to_string(),
+        _ => format!("Unknown({})", verdict),
+    }
+}
+
 pub fn key_type_name(key_type: i32) -> String {
     match key_type {
         0 => "Nostr (secp256k1)".to_string(),
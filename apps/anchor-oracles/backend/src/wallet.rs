@@ -0,0 +1,153 @@
+//! Wallet service client for building dispute and slash transactions
+
+use anchor_api_error::ApiError;
+use anchor_specs::oracle::{OracleDisputeSpec, OracleSlashSpec};
+use anchor_specs::KindSpec;
+use anyhow::{anyhow, Result};
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::{Address, Transaction};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Transaction created and broadcast by the wallet service
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletTx {
+    pub txid: String,
+    pub vout: u32,
+    pub hex: String,
+}
+
+impl WalletTx {
+    /// Find the output paying `address` (the wallet may place change anywhere)
+    pub fn output_to(&self, address: &str) -> Option<u32> {
+        let script = Address::from_str(address)
+            .ok()?
+            .assume_checked()
+            .script_pubkey();
+        let tx: Transaction = deserialize(&hex::decode(&self.hex).ok()?).ok()?;
+        tx.output
+            .iter()
+            .position(|output| output.script_pubkey == script)
+            .map(|vout| vout as u32)
+    }
+}
+
+/// Client for the anchor-wallet service
+#[derive(Clone)]
+pub struct WalletClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl WalletClient {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Get a fresh wallet address to receive a bond
+    pub async fn new_address(&self) -> Result<String> {
+        let res: serde_json::Value = self
+            .client
+            .get(format!("{}/wallet/address", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        res["address"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Wallet returned no address"))
+    }
+
+    /// Create a dispute anchored to the attestation, with a bond output to `bond_address`
+    pub async fn create_dispute(
+        &self,
+        spec: &OracleDisputeSpec,
+        attestation_vout: u8,
+        bond_address: &str,
+        carrier: u8,
+        fee_rate: u64,
+    ) -> Result<WalletTx> {
+        spec.validate()?;
+        self.create_message(serde_json::json!({
+            "kind": OracleDisputeSpec::KIND_ID,
+            "body": hex::encode(spec.to_bytes()),
+            "body_is_hex": true,
+            "parent_txid": hex::encode(spec.attestation_txid),
+            "parent_vout": attestation_vout,
+            "carrier": carrier,
+            "fee_rate": fee_rate,
+            "outputs": [{ "address": bond_address, "value": spec.bond_sats }],
+        }))
+        .await
+    }
+
+    /// Create a slash anchored to the dispute, spending the losing bond
+    pub async fn create_slash(
+        &self,
+        spec: &OracleSlashSpec,
+        dispute_vout: u8,
+        bond: (&str, u32),
+        carrier: u8,
+        fee_rate: u64,
+    ) -> Result<WalletTx> {
+        spec.validate()?;
+        self.create_message(serde_json::json!({
+            "kind": OracleSlashSpec::KIND_ID,
+            "body": hex::encode(spec.to_bytes()),
+            "body_is_hex": true,
+            "parent_txid": hex::encode(spec.dispute_txid),
+            "parent_vout": dispute_vout,
+            "carrier": carrier,
+            "fee_rate": fee_rate,
+            "required_inputs": [{ "txid": bond.0, "vout": bond.1 }],
+        }))
+        .await
+    }
+
+    /// Lock a bond output so the wallet does not spend it as a regular input
+    pub async fn lock_bond(&self, txid: &str, vout: u32) -> Result<()> {
+        self.client
+            .post(format!("{}/wallet/utxos/lock", self.base_url))
+            .json(&serde_json::json!({
+                "utxos": [{ "txid": txid, "vout": vout }],
+                "reason": "oracle_bond",
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Release a bond output so it can be spent by a slash
+    pub async fn unlock_bond(&self, txid: &str, vout: u32) -> Result<()> {
+        self.client
+            .post(format!("{}/wallet/utxos/unlock", self.base_url))
+            .json(&serde_json::json!({ "utxos": [{ "txid": txid, "vout": vout }] }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn create_message(&self, request: serde_json::Value) -> Result<WalletTx> {
+        let res = self
+            .client
+            .post(format!("{}/wallet/create-message", self.base_url))
+            .json(&request)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let status = res.status().as_u16();
+            let error_text = res.text().await.unwrap_or_default();
+            return Err(ApiError::from_upstream(status, &error_text).into());
+        }
+
+        Ok(res.json().await?)
+    }
+}
@@ -9,6 +9,7 @@ import {
   Attestation,
   fetchDefaultExplorer,
   buildExplorerTxUrl,
+  apiError,
 } from '@/lib/api';
 import { formatDistanceToNow } from 'date-fns';
 import { shortenPubkey } from '@/lib/utils';
@@ -79,7 +80,7 @@ function DisputeModal({
       });
 
       if (!res.ok) {
-        throw new Error((await res.text()) || 'Failed to create dispute');
+        throw await apiError(res, 'Failed to create dispute');
       }
 
       const result = await res.json();
@@ -20,6 +20,7 @@ import {
   Attestation,
   fetchDefaultExplorer,
   buildExplorerTxUrl,
+  apiError,
 } from '@/lib/api';
 import { formatDistanceToNow } from 'date-fns';
 import { formatSats, shortenPubkey } from '@/lib/utils';
@@ -121,8 +122,7 @@ function AttestModal({
       });
 
       if (!res.ok) {
-        const errText = await res.text();
-        throw new Error(errText || 'Failed to create attestation');
+        throw await apiError(res, 'Failed to create attestation');
       }
 
       const result = await res.json();
@@ -303,8 +303,7 @@ function DisputeModal({
       });
 
       if (!res.ok) {
-        const errText = await res.text();
-        throw new Error(errText || 'Failed to create dispute');
+        throw await apiError(res, 'Failed to create dispute');
       }
 
       const result = await res.json();
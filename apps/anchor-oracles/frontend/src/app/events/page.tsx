@@ -14,7 +14,7 @@ import {
   ChevronRight,
 } from 'lucide-react';
 import { useState } from 'react';
-import { fetchEvents, EventRequest } from '@/lib/api';
+import { fetchEvents, EventRequest, apiError } from '@/lib/api';
 import { formatDistanceToNow } from 'date-fns';
 import { formatSats } from '@/lib/utils';
 import Link from 'next/link';
@@ -226,8 +226,7 @@ function AttestModal({ isOpen, onClose, event, onSuccess }: AttestModalProps) {
       });
 
       if (!res.ok) {
-        const errorText = await res.text();
-        throw new Error(errorText || 'Failed to create attestation');
+        throw await apiError(res, 'Failed to create attestation');
       }
 
       setSuccess(true);
@@ -2,6 +2,7 @@
 
 import { useState, useEffect } from 'react';
 import { Eye, CheckCircle, Key, AlertCircle, Loader2, Zap, RefreshCw } from 'lucide-react';
+import { apiError } from '@/lib/api';
 
 const categories = [
   { id: 1, name: 'Block', description: 'Block and chain data' },
@@ -104,8 +105,7 @@ export default function RegisterPage() {
       });
 
       if (!response.ok) {
-        const errorText = await response.text();
-        throw new Error(errorText || 'Failed to create oracle registration');
+        throw await apiError(response, 'Failed to create oracle registration');
       }
 
       const result = await response.json();
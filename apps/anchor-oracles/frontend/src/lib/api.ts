@@ -1,6 +1,24 @@
 const API_BASE = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3701';
 const DASHBOARD_API = process.env.NEXT_PUBLIC_DASHBOARD_API_URL || 'http://localhost:3100';
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 export interface Oracle {
   id: number;
   pubkey: string;
@@ -0,0 +1,73 @@
+-- Marker geometry
+-- Markers may carry a path or polygon anchored at their coordinate.
+-- The bounding box lets area queries prefilter in SQL; the backend then
+-- refines candidates against the exact shape.
+
+ALTER TABLE markers ADD COLUMN IF NOT EXISTS geometry JSONB;  -- {"shape": "path"|"polygon", "points": [[lat, lng], ...]}
+ALTER TABLE markers ADD COLUMN IF NOT EXISTS bbox_lat_min REAL;
+ALTER TABLE markers ADD COLUMN IF NOT EXISTS bbox_lat_max REAL;
+ALTER TABLE markers ADD COLUMN IF NOT EXISTS bbox_lng_min REAL;
+ALTER TABLE markers ADD COLUMN IF NOT EXISTS bbox_lng_max REAL;
+
+-- Point markers are their own bounding box
+UPDATE markers SET
+    bbox_lat_min = latitude,
+    bbox_lat_max = latitude,
+    bbox_lng_min = longitude,
+    bbox_lng_max = longitude
+WHERE bbox_lat_min IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_markers_bbox
+    ON markers(bbox_lat_min, bbox_lat_max, bbox_lng_min, bbox_lng_max);
+
+-- Function to get markers whose bounding box overlaps an area
+CREATE OR REPLACE FUNCTION get_markers_in_area(
+    lat_min REAL,
+    lat_max REAL,
+    lng_min REAL,
+    lng_max REAL,
+    category_filter SMALLINT DEFAULT NULL,
+    limit_count INTEGER DEFAULT 1000
+)
+RETURNS TABLE (
+    id INTEGER,
+    txid BYTEA,
+    vout INTEGER,
+    category_id SMALLINT,
+    category_name VARCHAR(50),
+    category_icon VARCHAR(50),
+    category_color VARCHAR(7),
+    latitude REAL,
+    longitude REAL,
+    message TEXT,
+    block_height INTEGER,
+    reply_count BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE,
+    geometry TEXT
+) AS $$
+BEGIN
+    RETURN QUERY
+    SELECT
+        m.id,
+        m.txid,
+        m.vout,
+        m.category_id,
+        c.name as category_name,
+        c.icon as category_icon,
+        c.color as category_color,
+        m.latitude,
+        m.longitude,
+        m.message,
+        m.block_height,
+        (SELECT COUNT(*) FROM marker_replies r WHERE r.parent_txid = m.txid AND r.parent_vout = m.vout) as reply_count,
+        m.created_at,
+        m.geometry::TEXT
+    FROM markers m
+    JOIN marker_categories c ON m.category_id = c.id
+    WHERE m.bbox_lat_min <= lat_max AND m.bbox_lat_max >= lat_min
+      AND m.bbox_lng_min <= lng_max AND m.bbox_lng_max >= lng_min
+      AND (category_filter IS NULL OR m.category_id = category_filter)
+    ORDER BY m.created_at DESC
+    LIMIT limit_count;
+END;
+$$ LANGUAGE plpgsql;
@@ -2,6 +2,7 @@
 
 #![allow(clippy::type_complexity)]
 
+use anchor_specs::geomarker::Geometry;
 use anyhow::Result;
 use tracing::debug;
 
@@ -19,14 +20,33 @@ impl Database {
         latitude: f32,
         longitude: f32,
         message: &str,
+        geometry: Option<&Geometry>,
         creator_address: Option<&str>,
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
     ) -> Result<i32> {
+        // The bounding box covers the anchor point as well as the shape
+        let (lat_min, lat_max, lng_min, lng_max) = match geometry {
+            Some(g) => {
+                let (lat_min, lat_max, lng_min, lng_max) = g.bounds();
+                (
+                    (lat_min as f32).min(latitude),
+                    (lat_max as f32).max(latitude),
+                    (lng_min as f32).min(longitude),
+                    (lng_max as f32).max(longitude),
+                )
+            }
+            None => (latitude, latitude, longitude, longitude),
+        };
+        let geometry_json = geometry.map(serde_json::to_string).transpose()?;
+
         let row: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO markers (txid, vout, category_id, latitude, longitude, message, creator_address, block_hash, block_height)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO markers (
+                txid, vout, category_id, latitude, longitude, message, creator_address, block_hash, block_height,
+                geometry, bbox_lat_min, bbox_lat_max, bbox_lng_min, bbox_lng_max
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::jsonb, $11, $12, $13, $14)
             ON CONFLICT (txid, vout) DO UPDATE SET
                 category_id = EXCLUDED.category_id,
                 latitude = EXCLUDED.latitude,
@@ -34,7 +54,12 @@ impl Database {
                 message = EXCLUDED.message,
                 creator_address = EXCLUDED.creator_address,
                 block_hash = EXCLUDED.block_hash,
-                block_height = EXCLUDED.block_height
+                block_height = EXCLUDED.block_height,
+                geometry = EXCLUDED.geometry,
+                bbox_lat_min = EXCLUDED.bbox_lat_min,
+                bbox_lat_max = EXCLUDED.bbox_lat_max,
+                bbox_lng_min = EXCLUDED.bbox_lng_min,
+                bbox_lng_max = EXCLUDED.bbox_lng_max
             RETURNING id
             "#,
         )
@@ -47,6 +72,11 @@ impl Database {
         .bind(creator_address)
         .bind(block_hash)
         .bind(block_height)
+        .bind(geometry_json)
+        .bind(lat_min)
+        .bind(lat_max)
+        .bind(lng_min)
+        .bind(lng_max)
         .fetch_one(&self.pool)
         .await?;
 
@@ -132,11 +162,92 @@ impl Database {
                 latitude: r.7,
                 longitude: r.8,
                 message: r.9,
+                geometry: None,
+                creator_address: None, // SQL function doesn't return this
+                block_height: r.10,
+                reply_count: r.11,
+                created_at: r.12,
+            })
+            .collect())
+    }
+
+    /// Get markers whose point or shape intersects an area
+    ///
+    /// SQL narrows the candidates by bounding box; shapes are then checked
+    /// against the exact geometry.
+    pub async fn get_markers_in_area(
+        &self,
+        lat_min: f32,
+        lat_max: f32,
+        lng_min: f32,
+        lng_max: f32,
+        category: Option<i16>,
+        limit: i32,
+    ) -> Result<Vec<Marker>> {
+        let rows: Vec<(
+            i32,
+            Vec<u8>,
+            i32,
+            i16,
+            String,
+            String,
+            String,
+            f32,
+            f32,
+            String,
+            Option<i32>,
+            i64,
+            chrono::DateTime<chrono::Utc>,
+            Option<String>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT * FROM get_markers_in_area($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(lat_min)
+        .bind(lat_max)
+        .bind(lng_min)
+        .bind(lng_max)
+        .bind(category)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (lat_min, lat_max, lng_min, lng_max) = (
+            lat_min as f64,
+            lat_max as f64,
+            lng_min as f64,
+            lng_max as f64,
+        );
+        Ok(rows
+            .into_iter()
+            .map(|r| Marker {
+                id: r.0,
+                txid: hex::encode(&r.1),
+                vout: r.2,
+                category: Category {
+                    id: r.3,
+                    name: r.4,
+                    icon: r.5,
+                    color: r.6,
+                },
+                latitude: r.7,
+                longitude: r.8,
+                message: r.9,
+                geometry: r.13.and_then(|g| serde_json::from_str(&g).ok()),
                 creator_address: None, // SQL function doesn't return this
                 block_height: r.10,
                 reply_count: r.11,
                 created_at: r.12,
             })
+            .filter(|m| {
+                let point_inside = (lat_min..=lat_max).contains(&(m.latitude as f64))
+                    && (lng_min..=lng_max).contains(&(m.longitude as f64));
+                point_inside
+                    || m.geometry
+                        .as_ref()
+                        .is_some_and(|g| g.intersects_bounds(lat_min, lat_max, lng_min, lng_max))
+            })
             .collect())
     }
 
@@ -188,6 +299,7 @@ impl Database {
                 latitude: r.7,
                 longitude: r.8,
                 message: r.9,
+                geometry: None,
                 creator_address: None, // SQL function doesn't return this
                 block_height: r.10,
                 reply_count: r.11,
@@ -236,6 +348,7 @@ impl Database {
                 latitude: r.7,
                 longitude: r.8,
                 message: r.9,
+                geometry: None,
                 creator_address: None, // SQL function doesn't return this
                 block_height: r.10,
                 reply_count: r.11,
@@ -259,12 +372,14 @@ impl Database {
             String,
             Option<i32>,
             chrono::DateTime<chrono::Utc>,
+            Option<String>,
         )> = sqlx::query_as(
             r#"
             SELECT 
                 m.id, m.txid, m.vout, m.category_id,
                 c.name, c.icon, c.color,
-                m.latitude, m.longitude, m.message, m.block_height, m.created_at
+                m.latitude, m.longitude, m.message, m.block_height, m.created_at,
+                m.geometry::TEXT
             FROM markers m
             JOIN marker_categories c ON m.category_id = c.id
             WHERE m.txid = decode($1, 'hex') AND m.vout = $2
@@ -288,6 +403,7 @@ impl Database {
             latitude: r.7,
             longitude: r.8,
             message: r.9,
+            geometry: r.12.and_then(|g| serde_json::from_str(&g).ok()),
             creator_address: None, // Will be filled later if needed
             block_height: r.10,
             reply_count: 0, // Will be filled later
@@ -404,6 +520,7 @@ impl Database {
                 latitude: r.7,
                 longitude: r.8,
                 message: r.9,
+                geometry: None,
                 creator_address: None, // SQL function doesn't return this
                 block_height: r.10,
                 reply_count: r.11,
@@ -61,6 +61,53 @@ pub async fn get_markers_bounds(
     Ok(Json(markers))
 }
 
+/// Get markers whose point or shape intersects an area
+#[utoipa::path(
+    get,
+    path = "/markers/area",
+    tag = "Markers",
+    params(
+        ("lat_min" = f32, Query, description = "Minimum latitude"),
+        ("lat_max" = f32, Query, description = "Maximum latitude"),
+        ("lng_min" = f32, Query, description = "Minimum longitude"),
+        ("lng_max" = f32, Query, description = "Maximum longitude"),
+        ("category" = Option<i16>, Query, description = "Filter by category ID"),
+        ("limit" = Option<i32>, Query, description = "Maximum number of candidates (default 1000)")
+    ),
+    responses(
+        (status = 200, description = "Markers intersecting the area", body = Vec<Marker>),
+        (status = 400, description = "Invalid bounds"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_markers_area(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<BoundsParams>,
+) -> Result<Json<Vec<Marker>>> {
+    if params.lat_min > params.lat_max || params.lng_min > params.lng_max {
+        return Err(AppError::bad_request(
+            "Invalid bounds: min must be less than max",
+        ));
+    }
+
+    let limit = params.limit.unwrap_or(1000).min(5000);
+
+    let markers = state
+        .db
+        .get_markers_in_area(
+            params.lat_min,
+            params.lat_max,
+            params.lng_min,
+            params.lng_max,
+            params.category,
+            limit,
+        )
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(Json(markers))
+}
+
 /// Search markers by message content
 #[utoipa::path(
     get,
@@ -234,6 +281,7 @@ pub async fn create_marker(
             request.latitude,
             request.longitude,
             &request.message,
+            request.geometry,
             request.carrier.unwrap_or(0),
         )
         .await?;
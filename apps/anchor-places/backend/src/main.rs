@@ -21,11 +21,12 @@ mod services;
 
 use std::sync::Arc;
 
+use anchor_api_security::SecurityConfig;
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -46,6 +47,7 @@ use crate::indexer::MarkerIndexer;
         handlers::get_categories,
         handlers::get_markers,
         handlers::get_markers_bounds,
+        handlers::get_markers_area,
         handlers::search_markers,
         handlers::get_my_markers,
         handlers::get_marker,
@@ -81,6 +83,10 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(
@@ -124,18 +130,15 @@ async fn main() -> anyhow::Result<()> {
         .route("/markers", get(handlers::get_markers))
         .route("/markers", post(handlers::create_marker))
         .route("/markers/bounds", get(handlers::get_markers_bounds))
+        .route("/markers/area", get(handlers::get_markers_area))
         .route("/markers/search", get(handlers::search_markers))
         .route("/markers/my", get(handlers::get_my_markers))
         .route("/markers/:txid/:vout", get(handlers::get_marker))
         .route("/markers/:txid/:vout/reply", post(handlers::create_reply))
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer())
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
@@ -1,5 +1,6 @@
 //! API request/response models for Anchor Places
 
+use anchor_specs::geomarker::Geometry;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -55,6 +56,10 @@ pub struct CreateMarkerRequest {
     pub latitude: f32,
     pub longitude: f32,
     pub message: String,
+    /// Optional path or polygon: `{"shape": "polygon", "points": [[lat, lng], ...]}`
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub geometry: Option<Geometry>,
     /// Carrier type: 0=op_return, 1=inscription, 2=stamps, 3=annex, 4=witness
     #[serde(default)]
     pub carrier: Option<u8>,
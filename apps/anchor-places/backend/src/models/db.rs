@@ -1,5 +1,6 @@
 //! Database models for Anchor Places
 
+use anchor_specs::geomarker::Geometry;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -22,6 +23,10 @@ pub struct Marker {
     pub latitude: f32,
     pub longitude: f32,
     pub message: String,
+    /// Path or polygon anchored at the marker, absent for point markers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub geometry: Option<Geometry>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub creator_address: Option<String>,
     pub block_height: Option<i32>,
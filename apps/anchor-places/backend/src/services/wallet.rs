@@ -3,7 +3,8 @@
 //! Handles communication with the anchor-wallet service using anchor-specs
 //! for proper payload encoding.
 
-use anchor_specs::geomarker::GeoMarkerSpec;
+use anchor_api_error::ApiError;
+use anchor_specs::geomarker::{GeoMarkerSpec, Geometry};
 use anchor_specs::KindSpec;
 
 use crate::error::{AppError, Result};
@@ -32,10 +33,12 @@ impl WalletClient {
         latitude: f32,
         longitude: f32,
         message: &str,
+        geometry: Option<Geometry>,
         carrier: u8,
     ) -> Result<CreateMarkerResponse> {
         // Create and validate the spec
-        let spec = GeoMarkerSpec::new(category, latitude, longitude, message);
+        let mut spec = GeoMarkerSpec::new(category, latitude, longitude, message);
+        spec.geometry = geometry;
         spec.validate().map_err(|e| AppError::Spec(e.to_string()))?;
 
         // Encode payload using anchor-specs
@@ -58,11 +61,12 @@ impl WalletClient {
             .await?;
 
         if !res.status().is_success() {
+            let status = res.status().as_u16();
             let error_text = res
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AppError::internal(format!("Wallet error: {}", error_text)));
+            return Err(ApiError::from_upstream(status, &error_text).into());
         }
 
         let response: CreateMarkerResponse = res.json().await?;
@@ -98,12 +102,13 @@ impl WalletClient {
             .await?;
 
         if !res.status().is_success() {
+            let status = res.status().as_u16();
             let error_text = res
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             tracing::error!("Wallet reply error: {}", error_text);
-            return Err(AppError::internal(format!("Wallet error: {}", error_text)));
+            return Err(ApiError::from_upstream(status, &error_text).into());
         }
 
         let response: CreateMarkerResponse = res.json().await?;
@@ -27,6 +27,24 @@ async function fetchDefaultExplorerUrl(): Promise<string> {
   return DEFAULT_BLOCK_EXPLORER_URL;
 }
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 // Get the explorer URL (with caching)
 export async function getExplorerBaseUrl(): Promise<string> {
   if (cachedExplorerUrl) return cachedExplorerUrl;
@@ -268,8 +286,7 @@ export async function createMarker(request: CreateMarkerRequest): Promise<Create
   });
 
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to create marker');
+    throw await apiError(res, 'Failed to create marker');
   }
 
   return res.json();
@@ -287,8 +304,7 @@ export async function createReply(
   });
 
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to create reply');
+    throw await apiError(res, 'Failed to create reply');
   }
 
   return res.json();
@@ -0,0 +1,53 @@
+-- Anchor Predictions: Limit order book mode
+-- Markets created with order_book = true match MarketOrder (kind 44) intents
+-- off-chain instead of trading against the AMM pools
+
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS order_book BOOLEAN DEFAULT FALSE;
+
+-- Orders: limit bids for one outcome at a fixed price per share
+CREATE TABLE IF NOT EXISTS orders (
+    id SERIAL PRIMARY KEY,
+    market_id BYTEA NOT NULL REFERENCES markets(market_id),
+    txid BYTEA NOT NULL,
+    vout INTEGER NOT NULL,
+    block_height INTEGER,
+    user_pubkey BYTEA NOT NULL,
+    outcome SMALLINT NOT NULL, -- 0=NO, 1=YES
+    price_bps INTEGER NOT NULL, -- price per share in basis points of 1 sat
+    shares BIGINT NOT NULL,
+    filled_shares BIGINT DEFAULT 0,
+    status VARCHAR(20) DEFAULT 'open', -- open, filled, cancelled, expired
+    expiry_block INTEGER DEFAULT 0, -- 0 = good until resolution
+    cancel_txid BYTEA,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(txid, vout)
+);
+
+CREATE INDEX IF NOT EXISTS idx_orders_market ON orders(market_id);
+CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(status);
+CREATE INDEX IF NOT EXISTS idx_orders_user ON orders(user_pubkey);
+CREATE INDEX IF NOT EXISTS idx_orders_txid ON orders(txid);
+
+-- Fills: matched maker/taker pairs awaiting (or with) a settlement transaction
+CREATE TABLE IF NOT EXISTS order_fills (
+    id SERIAL PRIMARY KEY,
+    market_id BYTEA NOT NULL REFERENCES markets(market_id),
+    maker_order_id INTEGER NOT NULL REFERENCES orders(id),
+    taker_order_id INTEGER NOT NULL REFERENCES orders(id),
+    shares BIGINT NOT NULL,
+    maker_price_bps INTEGER NOT NULL,
+    maker_cost_sats BIGINT NOT NULL,
+    taker_cost_sats BIGINT NOT NULL,
+    status VARCHAR(20) DEFAULT 'matched', -- matched, settled
+    settlement_psbt TEXT, -- unsigned combined PSBT (base64)
+    settlement_txid BYTEA,
+    settled_at_block INTEGER,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_fills_market ON order_fills(market_id);
+CREATE INDEX IF NOT EXISTS idx_order_fills_maker ON order_fills(maker_order_id);
+CREATE INDEX IF NOT EXISTS idx_order_fills_taker ON order_fills(taker_order_id);
+CREATE INDEX IF NOT EXISTS idx_order_fills_status ON order_fills(status);
@@ -0,0 +1,68 @@
+-- Anchor Predictions: Categorical and scalar markets
+-- Categorical markets have N labelled outcomes with one AMM pool each.
+-- Scalar markets trade LONG/SHORT shares on the yes/no pools and resolve to a
+-- value in [scalar_lower, scalar_upper] that splits the share payout.
+
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS market_type VARCHAR(20) DEFAULT 'binary'; -- binary, categorical, scalar
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS outcome_labels TEXT[]; -- categorical outcome names, by index
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS pools BIGINT[]; -- categorical AMM pools, by index
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS scalar_lower BIGINT;
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS scalar_upper BIGINT;
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS scalar_unit TEXT;
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS resolved_value BIGINT; -- oracle value of a scalar market
+
+CREATE INDEX IF NOT EXISTS idx_markets_type ON markets(market_type);
+
+-- Pay out positions when a market is resolved
+CREATE OR REPLACE FUNCTION resolve_market_positions() RETURNS TRIGGER AS $$
+DECLARE
+    long_share NUMERIC;
+BEGIN
+    -- Only run when resolution changes from NULL to a value
+    IF OLD.resolution IS NULL AND NEW.resolution IS NOT NULL THEN
+        IF NEW.market_type = 'scalar' THEN
+            -- A scalar resolution needs a value; without one it isn't
+            -- recorded, so the market stays open for a valid resolution
+            IF NEW.resolved_value IS NULL THEN
+                NEW.resolution := NULL;
+                NEW.resolved_txid := NULL;
+                NEW.resolved_at_block := NULL;
+                RETURN NEW;
+            END IF;
+
+            -- LONG shares pay the fraction of the range below the value, SHORT
+            -- shares the rest
+            IF NEW.scalar_upper > NEW.scalar_lower THEN
+                long_share := LEAST(GREATEST(
+                    (NEW.resolved_value::NUMERIC - NEW.scalar_lower)
+                        / (NEW.scalar_upper::NUMERIC - NEW.scalar_lower),
+                    0), 1);
+
+                UPDATE positions
+                SET
+                    payout_sats = FLOOR(shares * CASE WHEN outcome = 1 THEN long_share ELSE 1 - long_share END),
+                    is_winner = FLOOR(shares * CASE WHEN outcome = 1 THEN long_share ELSE 1 - long_share END) > 0,
+                    updated_at = NOW()
+                WHERE market_id = NEW.market_id;
+
+                NEW.status = 'resolved';
+                NEW.updated_at = NOW();
+            END IF;
+        ELSIF NEW.resolution >= 0
+            AND NEW.resolution < COALESCE(array_length(NEW.outcome_labels, 1), 2) THEN
+            -- Binary and categorical: each winning share pays 1 sat, an index
+            -- past the last outcome is invalid
+            UPDATE positions
+            SET
+                is_winner = (outcome = NEW.resolution),
+                payout_sats = CASE WHEN outcome = NEW.resolution THEN shares ELSE 0 END,
+                updated_at = NOW()
+            WHERE market_id = NEW.market_id;
+
+            NEW.status = 'resolved';
+            NEW.updated_at = NOW();
+        END IF;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -1,6 +1,6 @@
-//! Automated Market Maker (AMM) for Binary Prediction Markets
+//! Automated Market Maker (AMM) for Prediction Markets
 //!
-//! Uses Constant Product Market Maker (CPMM) formula:
+//! Binary and scalar markets use the Constant Product Market Maker (CPMM) formula:
 //! k = yes_pool * no_pool (constant)
 //!
 //! Prices:
@@ -11,6 +11,10 @@
 //! 1. User deposits sats
 //! 2. Sats are converted to shares at current price
 //! 3. YES pool decreases, NO pool increases to maintain k
+//!
+//! Scalar markets trade LONG (outcome 1) and SHORT (outcome 0) shares on the
+//! same two pools. Categorical markets keep one pool per outcome and hold the
+//! product of all pools constant instead (see [`MultiAmm`]).
 
 /// Initial liquidity for new markets (in pool units, not sats)
 pub const INITIAL_LIQUIDITY: i64 = 1_000_000_000; // 1 billion units
@@ -187,6 +191,150 @@ impl BuyResult {
     }
 }
 
+/// AMM state for a categorical market, one pool per outcome
+///
+/// Buying outcome `i` with `a` sats mints `a` shares of every outcome into
+/// the pools, then takes outcome `i` shares out until the product of all pools
+/// is back to where it was:
+///
+/// ```text
+/// new_pool_i = pool_i × Π(j≠i) pool_j / (pool_j + a)
+/// shares_out = pool_i + a - new_pool_i
+/// price_i    = (1 / pool_i) / Σ(1 / pool_j)
+/// ```
+///
+/// With two outcomes the prices match the binary formula.
+#[derive(Debug, Clone)]
+pub struct MultiAmm {
+    pub pools: Vec<i64>,
+}
+
+impl MultiAmm {
+    /// Create a market with `outcomes` pools of `initial_liquidity` each
+    #[cfg(test)]
+    pub fn new(outcomes: usize, initial_liquidity: i64) -> Self {
+        Self {
+            pools: vec![initial_liquidity; outcomes],
+        }
+    }
+
+    /// Create AMM state from existing pool values
+    pub fn from_pools(pools: Vec<i64>) -> Self {
+        Self { pools }
+    }
+
+    /// Current price of every outcome (0.0 to 1.0, summing to 1.0)
+    pub fn prices(&self) -> Vec<f64> {
+        Self::prices_of(&self.pools)
+    }
+
+    fn prices_of(pools: &[i64]) -> Vec<f64> {
+        if pools.iter().any(|&p| p <= 0) {
+            return vec![1.0 / pools.len() as f64; pools.len()];
+        }
+        let inverse_sum: f64 = pools.iter().map(|&p| 1.0 / p as f64).sum();
+        pools
+            .iter()
+            .map(|&p| (1.0 / p as f64) / inverse_sum)
+            .collect()
+    }
+
+    /// Calculate shares received when buying `outcome` with amount_sats
+    ///
+    /// Returns `None` for an unknown outcome or a non-positive amount.
+    pub fn buy(&self, outcome: usize, amount_sats: i64) -> Option<Trade> {
+        if outcome >= self.pools.len() || amount_sats <= 0 {
+            return None;
+        }
+
+        // Every other pool grows by the minted shares; the chosen pool keeps
+        // the product constant. f64 because the product overflows i128 past a
+        // few outcomes.
+        let mut new_pools: Vec<i64> = self.pools.iter().map(|&p| p + amount_sats).collect();
+        let ratio: f64 = self
+            .pools
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != outcome)
+            .map(|(_, &p)| p as f64 / (p + amount_sats) as f64)
+            .product();
+        new_pools[outcome] = (self.pools[outcome] as f64 * ratio).ceil() as i64;
+
+        let shares_out = self.pools[outcome] + amount_sats - new_pools[outcome];
+        let avg_price = if shares_out > 0 {
+            amount_sats as f64 / shares_out as f64
+        } else {
+            1.0
+        };
+
+        let old_price = self.prices()[outcome];
+        let new_prices = Self::prices_of(&new_pools);
+        let price_impact = if old_price > 0.0 {
+            (new_prices[outcome] - old_price) / old_price
+        } else {
+            0.0
+        };
+
+        Some(Trade {
+            shares_out,
+            new_pools,
+            avg_price,
+            new_prices,
+            price_impact,
+        })
+    }
+}
+
+/// Market maker of a market of any type
+///
+/// Scalar markets trade on the binary pools, SHORT as NO and LONG as YES.
+#[derive(Debug, Clone)]
+pub enum MarketAmm {
+    Binary(AmmState),
+    Categorical(MultiAmm),
+}
+
+impl MarketAmm {
+    /// Pick the market maker for a `markets` row
+    pub fn from_pools(market_type: &str, yes_pool: i64, no_pool: i64, pools: Vec<i64>) -> Self {
+        if market_type == "categorical" {
+            MarketAmm::Categorical(MultiAmm::from_pools(pools))
+        } else {
+            MarketAmm::Binary(AmmState::from_pools(yes_pool, no_pool))
+        }
+    }
+
+    /// Quote buying `outcome`, `None` if the market has no such outcome
+    pub fn quote(&self, outcome: i16, amount_sats: i64) -> Option<Trade> {
+        match self {
+            MarketAmm::Binary(amm) => {
+                if !(0..=1).contains(&outcome) || amount_sats <= 0 {
+                    return None;
+                }
+                let result = amm.quote(outcome, amount_sats);
+                Some(Trade {
+                    shares_out: result.shares_out,
+                    new_pools: vec![result.new_no_pool, result.new_yes_pool],
+                    avg_price: result.avg_price,
+                    new_prices: vec![result.new_no_price, result.new_yes_price],
+                    price_impact: result.price_impact,
+                })
+            }
+            MarketAmm::Categorical(amm) => amm.buy(usize::try_from(outcome).ok()?, amount_sats),
+        }
+    }
+}
+
+/// Result of a trade, with pools and prices indexed by outcome
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub shares_out: i64,
+    pub new_pools: Vec<i64>,
+    pub avg_price: f64,
+    pub new_prices: Vec<f64>,
+    pub price_impact: f64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,4 +393,61 @@ mod tests {
         let large = amm.buy_yes(500_000);
         assert!(large.price_impact > small.price_impact);
     }
+
+    #[test]
+    fn test_categorical_initial_prices() {
+        let amm = MultiAmm::new(4, 1_000_000);
+        for price in amm.prices() {
+            assert!((price - 0.25).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_categorical_buy() {
+        let amm = MultiAmm::new(3, 1_000_000);
+        let result = amm.buy(2, 100_000).unwrap();
+
+        // The bought outcome gets dearer, the others cheaper
+        assert!(result.new_prices[2] > 1.0 / 3.0);
+        assert!(result.new_prices[0] < 1.0 / 3.0);
+        assert!((result.new_prices.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // More shares than sats paid while the outcome is cheap
+        assert!(result.shares_out > 100_000);
+        assert_eq!(result.new_pools[0], 1_100_000);
+
+        // The product of the pools stays (almost) constant
+        let before: f64 = amm.pools.iter().map(|&p| p as f64).product();
+        let after: f64 = result.new_pools.iter().map(|&p| p as f64).product();
+        assert!(((after - before) / before).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_categorical_rejects_unknown_outcome() {
+        let amm = MultiAmm::new(3, 1_000_000);
+        assert!(amm.buy(3, 1_000).is_none());
+        assert!(amm.buy(0, 0).is_none());
+    }
+
+    #[test]
+    fn test_two_outcome_prices_match_binary() {
+        let multi = MultiAmm::from_pools(vec![400_000, 1_600_000]);
+        let binary = AmmState::from_pools(1_600_000, 400_000);
+        let prices = multi.prices();
+        assert!((prices[0] - binary.no_price()).abs() < 1e-9);
+        assert!((prices[1] - binary.yes_price()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_market_amm_binary_quote() {
+        let amm = MarketAmm::from_pools("scalar", 1_000_000, 1_000_000, Vec::new());
+        let trade = amm.quote(1, 100_000).unwrap();
+        let direct = AmmState::new(1_000_000).buy_yes(100_000);
+        assert_eq!(trade.shares_out, direct.shares_out);
+        assert_eq!(
+            trade.new_pools,
+            vec![direct.new_no_pool, direct.new_yes_pool]
+        );
+        assert!(amm.quote(2, 100_000).is_none());
+    }
 }
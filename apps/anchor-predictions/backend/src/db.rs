@@ -4,8 +4,11 @@ use anyhow::Result;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{PgPool, Row};
 
-use crate::amm::AmmState;
-use crate::models::{outcome_name, resolution_name, Market, MarketStats, Position, Winner};
+use crate::amm::{MarketAmm, Trade};
+use crate::models::{
+    display_txid, outcome_name, Market, MarketStats, MarketType, Order, OrderFill, Position, Winner,
+};
+use crate::orderbook::{BookOrder, Fill};
 
 pub struct Database {
     pub pool: PgPool,
@@ -55,7 +58,9 @@ impl Database {
                 SELECT id, market_id, question, description, resolution_block,
                        oracle_pubkey, creator_pubkey, status, resolution,
                        yes_pool, no_pool, total_volume_sats, total_yes_sats,
-                       total_no_sats, position_count, created_at
+                       total_no_sats, position_count, order_book, created_at,
+                       market_type, outcome_labels, pools, scalar_lower, scalar_upper,
+                       scalar_unit, resolved_value
                 FROM markets
                 WHERE status = $1
                 ORDER BY created_at DESC
@@ -69,7 +74,9 @@ impl Database {
                 SELECT id, market_id, question, description, resolution_block,
                        oracle_pubkey, creator_pubkey, status, resolution,
                        yes_pool, no_pool, total_volume_sats, total_yes_sats,
-                       total_no_sats, position_count, created_at
+                       total_no_sats, position_count, order_book, created_at,
+                       market_type, outcome_labels, pools, scalar_lower, scalar_upper,
+                       scalar_unit, resolved_value
                 FROM markets
                 ORDER BY created_at DESC
                 LIMIT $1
@@ -90,7 +97,9 @@ impl Database {
             SELECT id, market_id, question, description, resolution_block,
                    oracle_pubkey, creator_pubkey, status, resolution,
                    yes_pool, no_pool, total_volume_sats, total_yes_sats,
-                   total_no_sats, position_count, created_at
+                   total_no_sats, position_count, order_book, created_at,
+                   market_type, outcome_labels, pools, scalar_lower, scalar_upper,
+                   scalar_unit, resolved_value
             FROM markets
             WHERE market_id = $1
             "#,
@@ -111,14 +120,21 @@ impl Database {
         // Calculate k_constant as string (for NUMERIC)
         let k_constant = format!("{}", market.yes_pool as i128 * market.no_pool as i128);
 
+        // Only categorical markets keep labels and pools of their own
+        let categorical = market.market_type == "categorical";
+        let outcome_labels = categorical.then(|| market.outcomes.clone());
+        let pools = categorical.then(|| market.pools.clone());
+
         let row = sqlx::query(
             r#"
             INSERT INTO markets (
                 market_id, question, description, resolution_block, 
                 oracle_pubkey, creator_pubkey, status, resolution,
                 yes_pool, no_pool, k_constant, total_volume_sats, 
-                total_yes_sats, total_no_sats, position_count
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11::NUMERIC, $12, $13, $14, $15)
+                total_yes_sats, total_no_sats, position_count, order_book,
+                market_type, outcome_labels, pools, scalar_lower, scalar_upper, scalar_unit
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11::NUMERIC, $12, $13, $14, $15, $16,
+                      $17, $18, $19, $20, $21, $22)
             RETURNING id
             "#,
         )
@@ -137,6 +153,13 @@ impl Database {
         .bind(market.total_yes_sats)
         .bind(market.total_no_sats)
         .bind(market.position_count)
+        .bind(market.order_book)
+        .bind(&market.market_type)
+        .bind(outcome_labels)
+        .bind(pools)
+        .bind(market.scalar_lower)
+        .bind(market.scalar_upper)
+        .bind(&market.scalar_unit)
         .fetch_one(&self.pool)
         .await?;
 
@@ -148,11 +171,26 @@ impl Database {
         let oracle_pubkey: Vec<u8> = row.get("oracle_pubkey");
         let creator_pubkey: Vec<u8> = row.get("creator_pubkey");
         let resolution: Option<i16> = row.get("resolution");
+        let resolved_value: Option<i64> = row.get("resolved_value");
         let yes_pool: i64 = row.get("yes_pool");
         let no_pool: i64 = row.get("no_pool");
         let (yes_price, no_price) = Market::calculate_prices(yes_pool, no_pool);
         let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
 
+        let market_type = market_type_from_row(row);
+        let (pools, prices) = match MarketAmm::from_pools(
+            market_type.name(),
+            yes_pool,
+            no_pool,
+            row.get::<Option<Vec<i64>>, _>("pools").unwrap_or_default(),
+        ) {
+            MarketAmm::Categorical(amm) => {
+                let prices = amm.prices();
+                (amm.pools, prices)
+            }
+            MarketAmm::Binary(_) => (vec![no_pool, yes_pool], vec![no_price, yes_price]),
+        };
+
         Market {
             id: row.get("id"),
             market_id: hex::encode(&market_id),
@@ -163,15 +201,24 @@ impl Database {
             creator_pubkey: hex::encode(&creator_pubkey),
             status: row.get("status"),
             resolution,
-            resolution_name: resolution_name(resolution),
+            resolution_name: market_type.resolution_name(resolution, resolved_value),
+            market_type: market_type.name().to_string(),
+            outcomes: market_type.outcome_labels(),
             yes_pool,
             no_pool,
             yes_price,
             no_price,
+            pools,
+            prices,
+            scalar_lower: row.get("scalar_lower"),
+            scalar_upper: row.get("scalar_upper"),
+            scalar_unit: row.get("scalar_unit"),
+            resolved_value,
             total_volume_sats: row.get("total_volume_sats"),
             total_yes_sats: row.get("total_yes_sats"),
             total_no_sats: row.get("total_no_sats"),
             position_count: row.get("position_count"),
+            order_book: row.get::<Option<bool>, _>("order_book").unwrap_or(false),
             created_at: created_at.to_rfc3339(),
         }
     }
@@ -188,14 +235,31 @@ impl Database {
         created_txid: &[u8],
         block_height: i32,
         initial_liquidity: i64,
+        market_type: &MarketType,
     ) -> Result<()> {
+        let (outcome_labels, pools) = match market_type {
+            MarketType::Categorical { outcomes } => (
+                Some(outcomes.clone()),
+                Some(vec![initial_liquidity; outcomes.len()]),
+            ),
+            _ => (None, None),
+        };
+        let (scalar_lower, scalar_upper, scalar_unit) = match market_type {
+            MarketType::Scalar { lower, upper, unit } => {
+                (Some(*lower), Some(*upper), Some(unit.as_str()))
+            }
+            _ => (None, None, None),
+        };
+
         sqlx::query(
             r#"
             INSERT INTO markets (
                 market_id, question, description, resolution_block,
                 oracle_pubkey, creator_pubkey, created_txid, created_at_block,
-                yes_pool, no_pool, k_constant
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, $9::NUMERIC * $9::NUMERIC)
+                yes_pool, no_pool, k_constant, market_type, outcome_labels, pools,
+                scalar_lower, scalar_upper, scalar_unit
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, $9::NUMERIC * $9::NUMERIC,
+                      $10, $11, $12, $13, $14, $15)
             ON CONFLICT (market_id) DO NOTHING
             "#,
         )
@@ -208,6 +272,12 @@ impl Database {
         .bind(created_txid)
         .bind(block_height)
         .bind(initial_liquidity)
+        .bind(market_type.name())
+        .bind(outcome_labels)
+        .bind(pools)
+        .bind(scalar_lower)
+        .bind(scalar_upper)
+        .bind(scalar_unit)
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -219,12 +289,14 @@ impl Database {
         let market_id_bytes = hex::decode(market_id)?;
         let rows = sqlx::query(
             r#"
-            SELECT id, market_id, txid, vout, block_height, user_pubkey,
-                   outcome, amount_sats, shares, avg_price,
-                   is_winner, payout_sats, claimed, created_at
-            FROM positions
-            WHERE market_id = $1
-            ORDER BY created_at DESC
+            SELECT p.id, p.market_id, p.txid, p.vout, p.block_height, p.user_pubkey,
+                   p.outcome, p.amount_sats, p.shares, p.avg_price,
+                   p.is_winner, p.payout_sats, p.claimed, p.created_at,
+                   m.market_type, m.outcome_labels, m.scalar_lower, m.scalar_upper, m.scalar_unit
+            FROM positions p
+            JOIN markets m ON m.market_id = p.market_id
+            WHERE p.market_id = $1
+            ORDER BY p.created_at DESC
             LIMIT $2
             "#,
         )
@@ -241,12 +313,14 @@ impl Database {
         let user_pubkey_bytes = hex::decode(user_pubkey)?;
         let rows = sqlx::query(
             r#"
-            SELECT id, market_id, txid, vout, block_height, user_pubkey,
-                   outcome, amount_sats, shares, avg_price,
-                   is_winner, payout_sats, claimed, created_at
-            FROM positions
-            WHERE user_pubkey = $1
-            ORDER BY created_at DESC
+            SELECT p.id, p.market_id, p.txid, p.vout, p.block_height, p.user_pubkey,
+                   p.outcome, p.amount_sats, p.shares, p.avg_price,
+                   p.is_winner, p.payout_sats, p.claimed, p.created_at,
+                   m.market_type, m.outcome_labels, m.scalar_lower, m.scalar_upper, m.scalar_unit
+            FROM positions p
+            JOIN markets m ON m.market_id = p.market_id
+            WHERE p.user_pubkey = $1
+            ORDER BY p.created_at DESC
             LIMIT $2
             "#,
         )
@@ -262,11 +336,13 @@ impl Database {
     pub async fn get_all_positions(&self, limit: i32) -> Result<Vec<Position>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, market_id, txid, vout, block_height, user_pubkey,
-                   outcome, amount_sats, shares, avg_price,
-                   is_winner, payout_sats, claimed, created_at
-            FROM positions
-            ORDER BY created_at DESC
+            SELECT p.id, p.market_id, p.txid, p.vout, p.block_height, p.user_pubkey,
+                   p.outcome, p.amount_sats, p.shares, p.avg_price,
+                   p.is_winner, p.payout_sats, p.claimed, p.created_at,
+                   m.market_type, m.outcome_labels, m.scalar_lower, m.scalar_upper, m.scalar_unit
+            FROM positions p
+            JOIN markets m ON m.market_id = p.market_id
+            ORDER BY p.created_at DESC
             LIMIT $1
             "#,
         )
@@ -293,7 +369,7 @@ impl Database {
             block_height: row.get("block_height"),
             user_pubkey: hex::encode(&user_pubkey),
             outcome,
-            outcome_name: outcome_name(outcome),
+            outcome_name: market_type_from_row(row).outcome_name(outcome),
             amount_sats: row.get("amount_sats"),
             shares: row.get("shares"),
             avg_price: row.get("avg_price"),
@@ -307,11 +383,13 @@ impl Database {
     pub async fn get_position_by_id(&self, id: i32) -> Result<Option<Position>> {
         let row = sqlx::query(
             r#"
-            SELECT id, market_id, txid, vout, block_height, user_pubkey,
-                   outcome, amount_sats, shares, avg_price,
-                   is_winner, payout_sats, claimed, created_at
-            FROM positions
-            WHERE id = $1
+            SELECT p.id, p.market_id, p.txid, p.vout, p.block_height, p.user_pubkey,
+                   p.outcome, p.amount_sats, p.shares, p.avg_price,
+                   p.is_winner, p.payout_sats, p.claimed, p.created_at,
+                   m.market_type, m.outcome_labels, m.scalar_lower, m.scalar_upper, m.scalar_unit
+            FROM positions p
+            JOIN markets m ON m.market_id = p.market_id
+            WHERE p.id = $1
             "#,
         )
         .bind(id)
@@ -395,12 +473,68 @@ impl Database {
         Ok(())
     }
 
+    /// Update the pools of a categorical market after a bet
+    pub async fn update_pools_after_bet(
+        &self,
+        market_id: &[u8],
+        new_pools: &[i64],
+        amount_sats: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE markets SET
+                pools = $1,
+                total_volume_sats = total_volume_sats + $2,
+                position_count = position_count + 1,
+                updated_at = NOW()
+            WHERE market_id = $3
+            "#,
+        )
+        .bind(new_pools)
+        .bind(amount_sats)
+        .bind(market_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Apply a trade to whichever pools the market maker keeps
+    pub async fn update_market_after_trade(
+        &self,
+        market_id: &[u8],
+        amm: &MarketAmm,
+        trade: &Trade,
+        amount_sats: i64,
+        outcome: i16,
+    ) -> Result<()> {
+        match amm {
+            MarketAmm::Binary(_) => {
+                self.update_market_after_bet(
+                    market_id,
+                    trade.new_pools[1],
+                    trade.new_pools[0],
+                    amount_sats,
+                    outcome,
+                )
+                .await
+            }
+            MarketAmm::Categorical(_) => {
+                self.update_pools_after_bet(market_id, &trade.new_pools, amount_sats)
+                    .await
+            }
+        }
+    }
+
     // ==================== Resolution ====================
 
+    /// Record the oracle's resolution; the `resolve_market_trigger` pays out
+    /// the positions. `resolved_value` settles scalar markets, which ignore a
+    /// resolution without one.
     pub async fn resolve_market(
         &self,
         market_id: &[u8],
         resolution: i16,
+        resolved_value: Option<i64>,
         resolved_txid: &[u8],
         resolved_at_block: i32,
     ) -> Result<()> {
@@ -408,13 +542,15 @@ impl Database {
             r#"
             UPDATE markets SET
                 resolution = $1,
-                resolved_txid = $2,
-                resolved_at_block = $3,
+                resolved_value = $2,
+                resolved_txid = $3,
+                resolved_at_block = $4,
                 updated_at = NOW()
-            WHERE market_id = $4 AND status = 'open'
+            WHERE market_id = $5 AND status = 'open'
             "#,
         )
         .bind(resolution)
+        .bind(resolved_value)
         .bind(resolved_txid)
         .bind(resolved_at_block)
         .bind(market_id)
@@ -427,10 +563,13 @@ impl Database {
         let market_id_bytes = hex::decode(market_id)?;
         let rows = sqlx::query(
             r#"
-            SELECT id, user_pubkey, outcome, amount_sats, shares, payout_sats, claimed
-            FROM positions
-            WHERE market_id = $1 AND is_winner = true
-            ORDER BY payout_sats DESC
+            SELECT p.id, p.user_pubkey, p.outcome, p.amount_sats, p.shares, p.payout_sats,
+                   p.claimed, m.market_type, m.outcome_labels, m.scalar_lower, m.scalar_upper,
+                   m.scalar_unit
+            FROM positions p
+            JOIN markets m ON m.market_id = p.market_id
+            WHERE p.market_id = $1 AND p.is_winner = true
+            ORDER BY p.payout_sats DESC
             "#,
         )
         .bind(&market_id_bytes)
@@ -446,7 +585,7 @@ impl Database {
                     position_id: row.get("id"),
                     user_pubkey: hex::encode(&user_pubkey),
                     outcome,
-                    outcome_name: outcome_name(outcome),
+                    outcome_name: market_type_from_row(row).outcome_name(outcome),
                     amount_sats: row.get("amount_sats"),
                     shares: row.get("shares"),
                     payout_sats: row.get("payout_sats"),
@@ -482,7 +621,9 @@ impl Database {
             SELECT id, market_id, question, description, resolution_block,
                    oracle_pubkey, creator_pubkey, status, resolution,
                    yes_pool, no_pool, total_volume_sats, total_yes_sats,
-                   total_no_sats, position_count, created_at
+                   total_no_sats, position_count, order_book, created_at,
+                   market_type, outcome_labels, pools, scalar_lower, scalar_upper,
+                   scalar_unit, resolved_value
             FROM markets
             WHERE status = 'resolved'
             ORDER BY updated_at DESC
@@ -497,6 +638,445 @@ impl Database {
         Ok(markets)
     }
 
+    // ==================== Order Book ====================
+
+    /// Market status and whether it trades through the order book
+    pub async fn get_market_mode(&self, market_id: &[u8]) -> Result<Option<(String, bool)>> {
+        let row = sqlx::query("SELECT status, order_book FROM markets WHERE market_id = $1")
+            .bind(market_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| {
+            (
+                r.get("status"),
+                r.get::<Option<bool>, _>("order_book").unwrap_or(false),
+            )
+        }))
+    }
+
+    /// Insert an order, returning its id (None if already indexed)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_order(
+        &self,
+        market_id: &[u8],
+        txid: &[u8],
+        vout: i32,
+        block_height: i32,
+        user_pubkey: &[u8],
+        outcome: i16,
+        price_bps: i32,
+        shares: i64,
+        expiry_block: i32,
+    ) -> Result<Option<i32>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO orders (
+                market_id, txid, vout, block_height, user_pubkey,
+                outcome, price_bps, shares, expiry_block
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (txid, vout) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(market_id)
+        .bind(txid)
+        .bind(vout)
+        .bind(block_height)
+        .bind(user_pubkey)
+        .bind(outcome)
+        .bind(price_bps)
+        .bind(shares)
+        .bind(expiry_block)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.get("id")))
+    }
+
+    /// Open orders of a market in time priority, as book entries
+    pub async fn get_book_orders(&self, market_id: &[u8]) -> Result<Vec<BookOrder>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, outcome, price_bps, shares - filled_shares AS remaining
+            FROM orders
+            WHERE market_id = $1 AND status = 'open'
+            ORDER BY block_height, id
+            "#,
+        )
+        .bind(market_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| BookOrder {
+                id: row.get("id"),
+                outcome: row.get("outcome"),
+                price_bps: row.get::<i32, _>("price_bps") as i64,
+                remaining: row.get("remaining"),
+            })
+            .collect())
+    }
+
+    /// Persist the fills produced by matching an incoming order
+    pub async fn record_fills(&self, market_id: &[u8], fills: &[Fill]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for fill in fills {
+            sqlx::query(
+                r#"
+                INSERT INTO order_fills (
+                    market_id, maker_order_id, taker_order_id, shares,
+                    maker_price_bps, maker_cost_sats, taker_cost_sats
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(market_id)
+            .bind(fill.maker_order_id)
+            .bind(fill.taker_order_id)
+            .bind(fill.shares)
+            .bind(fill.maker_price_bps as i32)
+            .bind(fill.maker_cost_sats)
+            .bind(fill.taker_cost_sats)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE orders SET
+                    filled_shares = filled_shares + $1,
+                    status = CASE WHEN filled_shares + $1 >= shares THEN 'filled' ELSE status END,
+                    updated_at = NOW()
+                WHERE id = ANY($2)
+                "#,
+            )
+            .bind(fill.shares)
+            .bind(vec![fill.maker_order_id, fill.taker_order_id])
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Cancel the open remainder of an order
+    pub async fn cancel_order(
+        &self,
+        market_id: &[u8],
+        order_txid: &[u8],
+        cancel_txid: &[u8],
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE orders SET
+                status = 'cancelled',
+                cancel_txid = $1,
+                updated_at = NOW()
+            WHERE market_id = $2 AND txid = $3 AND status = 'open'
+            "#,
+        )
+        .bind(cancel_txid)
+        .bind(market_id)
+        .bind(order_txid)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Expire open orders past their expiry block or on markets no longer open
+    pub async fn expire_orders(&self, height: i32) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE orders o SET
+                status = 'expired',
+                updated_at = NOW()
+            FROM markets m
+            WHERE o.market_id = m.market_id
+              AND o.status = 'open'
+              AND ((o.expiry_block > 0 AND o.expiry_block < $1) OR m.status <> 'open')
+            "#,
+        )
+        .bind(height)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Settle a matched fill: record both positions and the market volume.
+    ///
+    /// Returns false if no matching unsettled fill exists.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn settle_fill(
+        &self,
+        market_id: &[u8],
+        maker_txid: &[u8],
+        taker_txid: &[u8],
+        shares: i64,
+        maker_price_bps: i32,
+        settlement_txid: &[u8],
+        vout: i32,
+        block_height: i32,
+    ) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT f.id, f.maker_cost_sats, f.taker_cost_sats,
+                   mo.user_pubkey AS maker_pubkey, mo.outcome AS maker_outcome,
+                   t.user_pubkey AS taker_pubkey, t.outcome AS taker_outcome
+            FROM order_fills f
+            JOIN orders mo ON mo.id = f.maker_order_id
+            JOIN orders t ON t.id = f.taker_order_id
+            WHERE f.market_id = $1 AND mo.txid = $2 AND t.txid = $3
+              AND f.shares = $4 AND f.maker_price_bps = $5 AND f.status = 'matched'
+            ORDER BY f.id
+            LIMIT 1
+            FOR UPDATE OF f
+            "#,
+        )
+        .bind(market_id)
+        .bind(maker_txid)
+        .bind(taker_txid)
+        .bind(shares)
+        .bind(maker_price_bps)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let fill_id: i32 = row.get("id");
+        let maker_cost: i64 = row.get("maker_cost_sats");
+        let taker_cost: i64 = row.get("taker_cost_sats");
+        let maker_outcome: i16 = row.get("maker_outcome");
+        let maker_pubkey: Vec<u8> = row.get("maker_pubkey");
+        let taker_outcome: i16 = row.get("taker_outcome");
+        let taker_pubkey: Vec<u8> = row.get("taker_pubkey");
+
+        sqlx::query(
+            r#"
+            UPDATE order_fills SET
+                status = 'settled',
+                settlement_txid = $1,
+                settled_at_block = $2,
+                updated_at = NOW()
+            WHERE id = $3
+            "#,
+        )
+        .bind(settlement_txid)
+        .bind(block_height)
+        .bind(fill_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Maker position keys on the anchor output, taker on the escrow output after it
+        let maker_price = maker_price_bps as f32 / 10_000.0;
+        for (position_vout, pubkey, outcome, cost, price) in [
+            (vout, &maker_pubkey, maker_outcome, maker_cost, maker_price),
+            (
+                vout + 1,
+                &taker_pubkey,
+                taker_outcome,
+                taker_cost,
+                1.0 - maker_price,
+            ),
+        ] {
+            sqlx::query(
+                r#"
+                INSERT INTO positions (
+                    market_id, txid, vout, block_height, user_pubkey,
+                    outcome, amount_sats, shares, avg_price
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (txid, vout) DO NOTHING
+                "#,
+            )
+            .bind(market_id)
+            .bind(settlement_txid)
+            .bind(position_vout)
+            .bind(block_height)
+            .bind(pubkey)
+            .bind(outcome)
+            .bind(cost)
+            .bind(shares)
+            .bind(price)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let (yes_add, no_add) = if maker_outcome == 1 {
+            (maker_cost, taker_cost)
+        } else {
+            (taker_cost, maker_cost)
+        };
+        sqlx::query(
+            r#"
+            UPDATE markets SET
+                total_volume_sats = total_volume_sats + $1,
+                total_yes_sats = total_yes_sats + $2,
+                total_no_sats = total_no_sats + $3,
+                position_count = position_count + 2,
+                updated_at = NOW()
+            WHERE market_id = $4
+            "#,
+        )
+        .bind(shares)
+        .bind(yes_add)
+        .bind(no_add)
+        .bind(market_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn list_orders(
+        &self,
+        market_id: &str,
+        status: Option<&str>,
+        limit: i32,
+    ) -> Result<Vec<Order>> {
+        let market_id_bytes = hex::decode(market_id)?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, market_id, txid, vout, block_height, user_pubkey, outcome,
+                   price_bps, shares, filled_shares, status, expiry_block, created_at
+            FROM orders
+            WHERE market_id = $1 AND ($2::VARCHAR IS NULL OR status = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(&market_id_bytes)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| self.row_to_order(row)).collect())
+    }
+
+    pub async fn get_order(&self, id: i32) -> Result<Option<Order>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, market_id, txid, vout, block_height, user_pubkey, outcome,
+                   price_bps, shares, filled_shares, status, expiry_block, created_at
+            FROM orders
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| self.row_to_order(&r)))
+    }
+
+    fn row_to_order(&self, row: &sqlx::postgres::PgRow) -> Order {
+        let market_id: Vec<u8> = row.get("market_id");
+        let txid: Vec<u8> = row.get("txid");
+        let user_pubkey: Vec<u8> = row.get("user_pubkey");
+        let outcome: i16 = row.get("outcome");
+        let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+
+        Order {
+            id: row.get("id"),
+            market_id: hex::encode(&market_id),
+            txid: display_txid(&txid),
+            vout: row.get("vout"),
+            block_height: row.get("block_height"),
+            user_pubkey: hex::encode(&user_pubkey),
+            outcome,
+            outcome_name: outcome_name(outcome),
+            price_bps: row.get("price_bps"),
+            shares: row.get("shares"),
+            filled_shares: row.get("filled_shares"),
+            status: row.get("status"),
+            expiry_block: row.get("expiry_block"),
+            created_at: created_at.to_rfc3339(),
+        }
+    }
+
+    pub async fn list_fills(&self, market_id: &str, limit: i32) -> Result<Vec<OrderFill>> {
+        let market_id_bytes = hex::decode(market_id)?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, market_id, maker_order_id, taker_order_id, shares, maker_price_bps,
+                   maker_cost_sats, taker_cost_sats, status, settlement_txid,
+                   settled_at_block, created_at
+            FROM order_fills
+            WHERE market_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(&market_id_bytes)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|row| self.row_to_fill(row)).collect())
+    }
+
+    pub async fn get_fill(&self, id: i32) -> Result<Option<OrderFill>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, market_id, maker_order_id, taker_order_id, shares, maker_price_bps,
+                   maker_cost_sats, taker_cost_sats, status, settlement_txid,
+                   settled_at_block, created_at
+            FROM order_fills
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| self.row_to_fill(&r)))
+    }
+
+    fn row_to_fill(&self, row: &sqlx::postgres::PgRow) -> OrderFill {
+        let market_id: Vec<u8> = row.get("market_id");
+        let settlement_txid: Option<Vec<u8>> = row.get("settlement_txid");
+        let created_at: chrono::DateTime<chrono::Utc> = row.get("created_at");
+
+        OrderFill {
+            id: row.get("id"),
+            market_id: hex::encode(&market_id),
+            maker_order_id: row.get("maker_order_id"),
+            taker_order_id: row.get("taker_order_id"),
+            shares: row.get("shares"),
+            maker_price_bps: row.get("maker_price_bps"),
+            maker_cost_sats: row.get("maker_cost_sats"),
+            taker_cost_sats: row.get("taker_cost_sats"),
+            status: row.get("status"),
+            settlement_txid: settlement_txid.as_deref().map(display_txid),
+            settled_at_block: row.get("settled_at_block"),
+            created_at: created_at.to_rfc3339(),
+        }
+    }
+
+    pub async fn set_fill_psbt(&self, id: i32, psbt: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE order_fills SET settlement_psbt = $1, updated_at = NOW() WHERE id = $2 AND status = 'matched'",
+        )
+        .bind(psbt)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_fill_psbt(&self, id: i32) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT settlement_psbt FROM order_fills WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.and_then(|r| r.get("settlement_psbt")))
+    }
+
     // ==================== Indexer State ====================
 
     pub async fn get_last_block_height(&self) -> Result<i32> {
@@ -525,12 +1105,32 @@ impl Database {
 
     // ==================== AMM Helpers ====================
 
-    pub async fn get_market_amm_state(&self, market_id: &[u8]) -> Result<Option<AmmState>> {
-        let row = sqlx::query("SELECT yes_pool, no_pool FROM markets WHERE market_id = $1")
-            .bind(market_id)
-            .fetch_optional(&self.pool)
-            .await?;
+    pub async fn get_market_amm(&self, market_id: &[u8]) -> Result<Option<MarketAmm>> {
+        let row = sqlx::query(
+            "SELECT market_type, yes_pool, no_pool, pools FROM markets WHERE market_id = $1",
+        )
+        .bind(market_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        Ok(row.map(|r| AmmState::from_pools(r.get("yes_pool"), r.get("no_pool"))))
+        Ok(row.map(|r| {
+            MarketAmm::from_pools(
+                r.get::<Option<&str>, _>("market_type").unwrap_or("binary"),
+                r.get("yes_pool"),
+                r.get("no_pool"),
+                r.get::<Option<Vec<i64>>, _>("pools").unwrap_or_default(),
+            )
+        }))
     }
 }
+
+/// Market type from the `market_type`, `outcome_labels` and `scalar_*` columns
+fn market_type_from_row(row: &sqlx::postgres::PgRow) -> MarketType {
+    MarketType::from_columns(
+        row.get::<Option<&str>, _>("market_type"),
+        row.get("outcome_labels"),
+        row.get("scalar_lower"),
+        row.get("scalar_upper"),
+        row.get("scalar_unit"),
+    )
+}
@@ -1,18 +1,26 @@
 //! HTTP API handlers for Anchor Predictions
 
+use anchor_api_error::{ApiError, ErrorCode};
+use anchor_core::{AnchorKind, AnchorMessageBuilder};
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use bitcoin::hashes::{sha256, Hash};
 use bitcoin::secp256k1::{schnorr::Signature, Message, Secp256k1, XOnlyPublicKey};
+use bitcoin::{Address, Txid};
 use serde::Deserialize;
+use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::amm::MarketAmm;
 use crate::db::Database;
 use crate::models::*;
+use crate::orderbook::{is_valid_price, OrderBook, OrderMessage};
+use crate::settlement;
+use crate::wallet::WalletClient;
 
 pub type AppState = Arc<Database>;
 
@@ -59,6 +67,14 @@ fn build_claim_message(market_id: &str, position_id: i32, payout_address: &str)
 
 // ==================== Health ====================
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service is healthy")
+    ),
+    tag = "health"
+)]
 pub async fn health() -> impl IntoResponse {
     Json(serde_json::json!({ "status": "ok" }))
 }
@@ -76,7 +92,7 @@ pub async fn health() -> impl IntoResponse {
 pub async fn get_stats(State(db): State<AppState>) -> impl IntoResponse {
     match db.get_stats().await {
         Ok(stats) => Json(stats).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -107,7 +123,7 @@ pub async fn list_markets(
     let limit = params.limit.unwrap_or(50);
     match db.list_markets(params.status.as_deref(), limit).await {
         Ok(markets) => Json(markets).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -126,8 +142,8 @@ pub async fn list_markets(
 pub async fn get_market(State(db): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     match db.get_market(&id).await {
         Ok(Some(market)) => Json(market).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Market not found").into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Ok(None) => ApiError::not_found("Market not found").into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -147,6 +163,15 @@ pub async fn create_market(
     use rand::Rng;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    let market_type = match req.market_type() {
+        Ok(t) => t,
+        Err(e) => return ApiError::bad_request(e).into_response(),
+    };
+    let order_book = req.order_book.unwrap_or(false);
+    if order_book && market_type != MarketType::Binary {
+        return ApiError::bad_request("Only binary markets can use the order book").into_response();
+    }
+
     // Generate a unique market_id
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -162,6 +187,13 @@ pub async fn create_market(
     let initial_pool = req.initial_liquidity_sats.unwrap_or(1_000_000_000);
     let yes_price = 0.5;
     let no_price = 0.5;
+    let outcomes = market_type.outcome_labels();
+    let (scalar_lower, scalar_upper, scalar_unit) = match &market_type {
+        MarketType::Scalar { lower, upper, unit } => {
+            (Some(*lower), Some(*upper), Some(unit.clone()))
+        }
+        _ => (None, None, None),
+    };
 
     let market = crate::models::Market {
         id: 0, // Will be assigned by DB
@@ -174,14 +206,23 @@ pub async fn create_market(
         status: "open".to_string(),
         resolution: None,
         resolution_name: "pending".to_string(),
+        market_type: market_type.name().to_string(),
         yes_pool: initial_pool,
         no_pool: initial_pool,
         yes_price,
         no_price,
+        pools: vec![initial_pool; outcomes.len()],
+        prices: vec![1.0 / outcomes.len() as f64; outcomes.len()],
+        outcomes,
+        scalar_lower,
+        scalar_upper,
+        scalar_unit,
+        resolved_value: None,
         total_volume_sats: 0,
         total_yes_sats: 0,
         total_no_sats: 0,
         position_count: 0,
+        order_book,
         created_at: chrono::Utc::now().to_rfc3339(),
     };
 
@@ -196,6 +237,9 @@ pub async fn create_market(
             "resolution_block": req.resolution_block,
             "oracle_pubkey": req.oracle_pubkey,
             "initial_liquidity_sats": initial_pool,
+            "order_book": market.order_book,
+            "market_type": market.market_type,
+            "outcomes": market.outcomes,
         }))
         .into_response(),
         Err(e) => {
@@ -239,7 +283,7 @@ pub async fn get_market_positions(
     let limit = params.limit.unwrap_or(100);
     match db.get_market_positions(&id, limit).await {
         Ok(positions) => Json(positions).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -257,29 +301,36 @@ pub async fn get_bet_quote(
     Path(id): Path<String>,
     Json(req): Json<PlaceBetRequest>,
 ) -> impl IntoResponse {
-    // Get market AMM state
-    let market_id_bytes = match hex::decode(&id) {
-        Ok(b) => b,
-        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
-    };
-
-    match db.get_market_amm_state(&market_id_bytes).await {
-        Ok(Some(amm)) => {
-            let result = amm.quote(req.outcome, req.amount_sats);
-            Json(PlaceBetQuote {
-                outcome: req.outcome,
-                outcome_name: outcome_name(req.outcome),
-                amount_sats: req.amount_sats,
-                shares_out: result.shares_out,
-                avg_price: result.avg_price,
-                price_impact: result.price_impact,
-                new_yes_price: result.new_yes_price,
-                new_no_price: result.new_no_price,
-            })
-            .into_response()
-        }
-        Ok(None) => (StatusCode::NOT_FOUND, "Market not found").into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    let market = match db.get_market(&id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return ApiError::not_found("Market not found").into_response(),
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+    let amm = MarketAmm::from_pools(
+        &market.market_type,
+        market.yes_pool,
+        market.no_pool,
+        market.pools.clone(),
+    );
+
+    match amm.quote(req.outcome, req.amount_sats) {
+        Some(trade) => Json(PlaceBetQuote {
+            outcome: req.outcome,
+            outcome_name: market.outcomes[req.outcome as usize].clone(),
+            amount_sats: req.amount_sats,
+            shares_out: trade.shares_out,
+            avg_price: trade.avg_price,
+            price_impact: trade.price_impact,
+            new_yes_price: trade.new_prices.get(1).copied().unwrap_or_default(),
+            new_no_price: trade.new_prices[0],
+            new_prices: trade.new_prices,
+        })
+        .into_response(),
+        None => ApiError::bad_request(format!(
+            "Market has no outcome {} or the amount is not positive",
+            req.outcome
+        ))
+        .into_response(),
     }
 }
 
@@ -300,25 +351,39 @@ pub async fn place_bet(
     // Get market to verify it exists and get quote
     let market_id_bytes = match hex::decode(&id) {
         Ok(b) => b,
-        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => return ApiError::bad_request(e.to_string()).into_response(),
     };
 
     // Decode user pubkey
     let user_pubkey_bytes = match hex::decode(&req.user_pubkey) {
         Ok(b) => b,
         Err(e) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                format!("Invalid user_pubkey: {}", e),
-            )
-                .into_response()
+            return ApiError::bad_request(format!("Invalid user_pubkey: {}", e)).into_response()
         }
     };
 
-    match db.get_market_amm_state(&market_id_bytes).await {
-        Ok(Some(amm)) => {
-            let result = amm.quote(req.outcome, req.amount_sats);
-            let outcome_str = if req.outcome == 1 { "YES" } else { "NO" };
+    if let Ok(Some((_, true))) = db.get_market_mode(&market_id_bytes).await {
+        return order_error(
+            StatusCode::BAD_REQUEST,
+            "Market trades through the order book; place a limit order instead",
+        );
+    }
+
+    let market = match db.get_market(&id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return ApiError::not_found("Market not found").into_response(),
+        Err(e) => return ApiError::internal(e.to_string()).into_response(),
+    };
+    let amm = MarketAmm::from_pools(
+        &market.market_type,
+        market.yes_pool,
+        market.no_pool,
+        market.pools.clone(),
+    );
+
+    match amm.quote(req.outcome, req.amount_sats) {
+        Some(result) => {
+            let outcome_str = &market.outcomes[req.outcome as usize];
 
             // Create real Bitcoin transaction if bet_address is provided
             let (txid_bytes, is_real_tx) = if let Some(ref bet_address) = req.bet_address {
@@ -365,18 +430,12 @@ pub async fn place_bet(
                 };
 
                 if !response.status().is_success() {
+                    let status = response.status().as_u16();
                     let error_text = response
                         .text()
                         .await
                         .unwrap_or_else(|_| "Unknown error".to_string());
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({
-                            "status": "error",
-                            "message": format!("Wallet error: {}", error_text)
-                        })),
-                    )
-                        .into_response();
+                    return ApiError::from_upstream(status, &error_text).into_response();
                 }
 
                 let wallet_response: serde_json::Value = match response.json().await {
@@ -422,22 +481,11 @@ pub async fn place_bet(
             {
                 Ok(_) => {
                     // Update market AMM pools
-                    let new_yes_pool = if req.outcome == 1 {
-                        amm.yes_pool - result.shares_out
-                    } else {
-                        amm.yes_pool + req.amount_sats
-                    };
-                    let new_no_pool = if req.outcome == 0 {
-                        amm.no_pool - result.shares_out
-                    } else {
-                        amm.no_pool + req.amount_sats
-                    };
-
                     let _ = db
-                        .update_market_after_bet(
+                        .update_market_after_trade(
                             &market_id_bytes,
-                            new_yes_pool,
-                            new_no_pool,
+                            &amm,
+                            &result,
                             req.amount_sats,
                             req.outcome,
                         )
@@ -463,15 +511,14 @@ pub async fn place_bet(
                     }))
                     .into_response()
                 }
-                Err(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to save bet: {}", e),
-                )
-                    .into_response(),
+                Err(e) => ApiError::internal(format!("Failed to save bet: {}", e)).into_response(),
             }
         }
-        Ok(None) => (StatusCode::NOT_FOUND, "Market not found").into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        None => ApiError::bad_request(format!(
+            "Market has no outcome {} or the amount is not positive",
+            req.outcome
+        ))
+        .into_response(),
     }
 }
 
@@ -500,10 +547,12 @@ pub async fn get_resolution(
             "resolution": market.resolution,
             "resolution_name": market.resolution_name,
             "resolution_block": market.resolution_block,
+            "market_type": market.market_type,
+            "resolved_value": market.resolved_value,
         }))
         .into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, "Market not found").into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Ok(None) => ApiError::not_found("Market not found").into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -524,7 +573,7 @@ pub async fn get_market_winners(
 ) -> impl IntoResponse {
     match db.get_market_winners(&id).await {
         Ok(winners) => Json(winners).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -556,7 +605,7 @@ pub async fn claim_winnings(
                 .into_response();
         }
         Err(e) => {
-            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            return ApiError::internal(e.to_string()).into_response();
         }
     };
 
@@ -739,18 +788,12 @@ pub async fn claim_winnings(
     };
 
     if !response.status().is_success() {
+        let status = response.status().as_u16();
         let error_text = response
             .text()
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "status": "error",
-                "message": format!("Wallet error: {}", error_text)
-            })),
-        )
-            .into_response();
+        return ApiError::from_upstream(status, &error_text).into_response();
     }
 
     // Parse the response to get the txid
@@ -799,7 +842,454 @@ pub async fn claim_winnings(
                     .into_response()
             }
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+// ==================== Order Book ====================
+
+/// Value of the order ticket output; spending it cancels the order
+const ORDER_TICKET_SATS: u64 = 546;
+
+fn order_error(status: StatusCode, message: impl Into<String>) -> Response {
+    ApiError::from((status, message.into())).into_response()
+}
+
+/// Error response for a failed wallet call, keeping the wallet's error code
+fn wallet_error(e: anyhow::Error) -> Response {
+    match e.downcast::<ApiError>() {
+        Ok(err) => err,
+        Err(e) => ApiError::new(ErrorCode::UpstreamError, e.to_string()),
+    }
+    .into_response()
+}
+
+/// Txid in the internal byte order used by on-chain messages
+fn txid_bytes(display: &str) -> Option<[u8; 32]> {
+    Txid::from_str(display).ok().map(|t| t.to_byte_array())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/markets/{id}/orders",
+    params(
+        ("id" = String, Path, description = "Market ID (hex)")
+    ),
+    request_body = PlaceOrderRequest,
+    responses(
+        (status = 200, description = "Order intent broadcast"),
+        (status = 400, description = "Invalid order or market not in order book mode")
+    ),
+    tag = "orderbook"
+)]
+pub async fn place_order(
+    State(db): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<PlaceOrderRequest>,
+) -> impl IntoResponse {
+    let market = match db.get_market(&id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return order_error(StatusCode::NOT_FOUND, "Market not found"),
+        Err(e) => return order_error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+    if !market.order_book || market.status != "open" {
+        return order_error(
+            StatusCode::BAD_REQUEST,
+            "Market is not an open order book market",
+        );
+    }
+    if !(0..=1).contains(&req.outcome) || !is_valid_price(req.price_bps) || req.shares <= 0 {
+        return order_error(
+            StatusCode::BAD_REQUEST,
+            "Order needs outcome 0 or 1, price_bps in 1..=9999 and positive shares",
+        );
+    }
+
+    let user_pubkey: [u8; 33] = match hex::decode(&req.user_pubkey)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+    {
+        Some(pk) => pk,
+        None => {
+            return order_error(
+                StatusCode::BAD_REQUEST,
+                "Invalid user_pubkey: must be 33 bytes (compressed pubkey)",
+            )
+        }
+    };
+    let Ok(market_id) = <[u8; 32]>::try_from(hex::decode(&market.market_id).unwrap_or_default())
+    else {
+        return order_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Invalid market in database",
+        );
+    };
+
+    let wallet = WalletClient::from_env();
+    let ticket_address = match req.ticket_address.clone() {
+        Some(address) => address,
+        None => match wallet.new_address().await {
+            Ok(address) => address,
+            Err(e) => return wallet_error(e),
+        },
+    };
+
+    let body = OrderMessage::Place {
+        market_id,
+        outcome: req.outcome as u8,
+        price_bps: req.price_bps as u16,
+        shares: req.shares as u64,
+        expiry_block: req.expiry_block.unwrap_or(0).max(0) as u32,
+        user_pubkey,
+    }
+    .to_bytes();
+
+    let tx = match wallet
+        .create_message(serde_json::json!({
+            "kind": u8::from(AnchorKind::MarketOrder),
+            "body": hex::encode(&body),
+            "body_is_hex": true,
+            "outputs": [{ "address": ticket_address, "value": ORDER_TICKET_SATS }],
+            "fee_rate": 1
+        }))
+        .await
+    {
+        Ok(tx) => tx,
+        Err(e) => return wallet_error(e),
+    };
+
+    Json(serde_json::json!({
+        "status": "success",
+        "message": "Order submitted; it joins the book once confirmed",
+        "market_id": id,
+        "outcome": outcome_name(req.outcome),
+        "price_bps": req.price_bps,
+        "shares": req.shares,
+        "txid": tx.txid,
+        "vout": tx.vout,
+        "ticket_vout": tx.output_to(&ticket_address),
+    }))
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/orders/{id}/cancel",
+    params(
+        ("id" = i32, Path, description = "Order ID")
+    ),
+    request_body = CancelOrderRequest,
+    responses(
+        (status = 200, description = "Cancel intent broadcast"),
+        (status = 400, description = "Order is not open")
+    ),
+    tag = "orderbook"
+)]
+pub async fn cancel_order(
+    State(db): State<AppState>,
+    Path(id): Path<i32>,
+    Json(req): Json<CancelOrderRequest>,
+) -> impl IntoResponse {
+    let order = match db.get_order(id).await {
+        Ok(Some(o)) => o,
+        Ok(None) => return order_error(StatusCode::NOT_FOUND, "Order not found"),
+        Err(e) => return order_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    if order.status != "open" {
+        return order_error(StatusCode::BAD_REQUEST, "Order is not open");
+    }
+    let (Some(order_txid), Ok(market_id)) = (
+        txid_bytes(&order.txid),
+        <[u8; 32]>::try_from(hex::decode(&order.market_id).unwrap_or_default()),
+    ) else {
+        return order_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Invalid order in database",
+        );
+    };
+
+    let body = OrderMessage::Cancel {
+        market_id,
+        order_txid,
+    }
+    .to_bytes();
+
+    match WalletClient::from_env()
+        .create_message(serde_json::json!({
+            "kind": u8::from(AnchorKind::MarketOrder),
+            "body": hex::encode(&body),
+            "body_is_hex": true,
+            "required_inputs": [{ "txid": order.txid, "vout": req.ticket_vout }],
+            "fee_rate": 1
+        }))
+        .await
+    {
+        Ok(tx) => Json(serde_json::json!({
+            "status": "success",
+            "message": "Cancel submitted; the order leaves the book once confirmed",
+            "order_id": id,
+            "txid": tx.txid,
+        }))
+        .into_response(),
+        Err(e) => wallet_error(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/markets/{id}/orderbook",
+    params(
+        ("id" = String, Path, description = "Market ID (hex)")
+    ),
+    responses(
+        (status = 200, description = "Aggregated order book depth", body = OrderBookDepth)
+    ),
+    tag = "orderbook"
+)]
+pub async fn get_orderbook(
+    State(db): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let market_id_bytes = match hex::decode(&id) {
+        Ok(b) => b,
+        Err(e) => return ApiError::bad_request(e.to_string()).into_response(),
+    };
+
+    match db.get_book_orders(&market_id_bytes).await {
+        Ok(orders) => {
+            let book = OrderBook::from_orders(orders);
+            let levels = |outcome| {
+                book.depth(outcome)
+                    .into_iter()
+                    .map(|(price_bps, shares)| OrderBookLevel { price_bps, shares })
+                    .collect()
+            };
+            Json(OrderBookDepth {
+                market_id: id,
+                yes_bids: levels(1),
+                no_bids: levels(0),
+            })
+            .into_response()
+        }
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListOrdersQuery {
+    pub status: Option<String>,
+    pub limit: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/markets/{id}/orders",
+    params(
+        ("id" = String, Path, description = "Market ID (hex)"),
+        ("status" = Option<String>, Query, description = "Filter by status: open, filled, cancelled, expired"),
+        ("limit" = Option<i32>, Query, description = "Max results")
+    ),
+    responses(
+        (status = 200, description = "Market orders", body = Vec<Order>)
+    ),
+    tag = "orderbook"
+)]
+pub async fn list_market_orders(
+    State(db): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<ListOrdersQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(100);
+    match db.list_orders(&id, params.status.as_deref(), limit).await {
+        Ok(orders) => Json(orders).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/markets/{id}/fills",
+    params(
+        ("id" = String, Path, description = "Market ID (hex)"),
+        ("limit" = Option<i32>, Query, description = "Max results")
+    ),
+    responses(
+        (status = 200, description = "Matched and settled fills", body = Vec<OrderFill>)
+    ),
+    tag = "orderbook"
+)]
+pub async fn list_market_fills(
+    State(db): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<GetPositionsQuery>,
+) -> impl IntoResponse {
+    let limit = params.limit.unwrap_or(100);
+    match db.list_fills(&id, limit).await {
+        Ok(fills) => Json(fills).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/fills/{id}/psbt",
+    params(
+        ("id" = i32, Path, description = "Fill ID")
+    ),
+    request_body = CombineFillRequest,
+    responses(
+        (status = 200, description = "Unsigned settlement PSBT for both parties to sign"),
+        (status = 400, description = "Fill already settled or PSBTs underfunded")
+    ),
+    tag = "orderbook"
+)]
+pub async fn combine_fill_psbt(
+    State(db): State<AppState>,
+    Path(id): Path<i32>,
+    Json(req): Json<CombineFillRequest>,
+) -> impl IntoResponse {
+    let fill = match db.get_fill(id).await {
+        Ok(Some(f)) => f,
+        Ok(None) => return order_error(StatusCode::NOT_FOUND, "Fill not found"),
+        Err(e) => return order_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    if fill.status != "matched" {
+        return order_error(StatusCode::BAD_REQUEST, "Fill is already settled");
+    }
+
+    let (maker, taker) = match (
+        db.get_order(fill.maker_order_id).await,
+        db.get_order(fill.taker_order_id).await,
+    ) {
+        (Ok(Some(maker)), Ok(Some(taker))) => (maker, taker),
+        _ => return order_error(StatusCode::INTERNAL_SERVER_ERROR, "Fill orders missing"),
+    };
+    let (Some(maker_txid), Some(taker_txid), Ok(market_id)) = (
+        txid_bytes(&maker.txid),
+        txid_bytes(&taker.txid),
+        <[u8; 32]>::try_from(hex::decode(&fill.market_id).unwrap_or_default()),
+    ) else {
+        return order_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Invalid fill in database",
+        );
+    };
+
+    let escrow_script = match Address::from_str(&req.escrow_address) {
+        Ok(address) => address.assume_checked().script_pubkey(),
+        Err(e) => {
+            return order_error(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid escrow_address: {}", e),
+            )
+        }
+    };
+    let (maker_psbt, taker_psbt) = match (
+        settlement::decode_psbt(&req.maker_psbt),
+        settlement::decode_psbt(&req.taker_psbt),
+    ) {
+        (Ok(maker), Ok(taker)) => (maker, taker),
+        (Err(e), _) | (_, Err(e)) => {
+            return order_error(StatusCode::BAD_REQUEST, format!("Invalid PSBT: {}", e))
+        }
+    };
+
+    let fill_script = AnchorMessageBuilder::new()
+        .kind(AnchorKind::MarketOrder)
+        .body(
+            OrderMessage::Fill {
+                market_id,
+                maker_txid,
+                taker_txid,
+                shares: fill.shares as u64,
+                maker_price_bps: fill.maker_price_bps as u16,
+            }
+            .to_bytes(),
+        )
+        .to_script();
+
+    let psbt = match settlement::combine_fill(
+        &maker_psbt,
+        &taker_psbt,
+        fill.maker_cost_sats,
+        fill.taker_cost_sats,
+        fill_script,
+        escrow_script,
+    ) {
+        Ok(psbt) => settlement::encode_psbt(&psbt),
+        Err(e) => return order_error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+
+    if let Err(e) = db.set_fill_psbt(id, &psbt).await {
+        return order_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    }
+
+    Json(serde_json::json!({
+        "status": "success",
+        "fill_id": id,
+        "psbt": psbt,
+        "maker_cost_sats": fill.maker_cost_sats,
+        "taker_cost_sats": fill.taker_cost_sats,
+    }))
+    .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/fills/{id}/finalize",
+    params(
+        ("id" = i32, Path, description = "Fill ID")
+    ),
+    request_body = FinalizeFillRequest,
+    responses(
+        (status = 200, description = "Settlement transaction broadcast"),
+        (status = 400, description = "No combined PSBT or missing signatures")
+    ),
+    tag = "orderbook"
+)]
+pub async fn finalize_fill(
+    State(db): State<AppState>,
+    Path(id): Path<i32>,
+    Json(req): Json<FinalizeFillRequest>,
+) -> impl IntoResponse {
+    let unsigned = match db.get_fill_psbt(id).await {
+        Ok(Some(psbt)) => psbt,
+        Ok(None) => {
+            return order_error(
+                StatusCode::BAD_REQUEST,
+                "No settlement PSBT; combine the funding PSBTs first",
+            )
+        }
+        Err(e) => return order_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let decoded: Result<Vec<_>, _> = std::iter::once(&unsigned)
+        .chain(&req.signed_psbts)
+        .map(|p| settlement::decode_psbt(p))
+        .collect();
+    let mut psbts = match decoded {
+        Ok(psbts) => psbts,
+        Err(e) => return order_error(StatusCode::BAD_REQUEST, format!("Invalid PSBT: {}", e)),
+    };
+    let unsigned = psbts.remove(0);
+
+    let tx = match settlement::finalize_fill(&unsigned, psbts) {
+        Ok(tx) => tx,
+        Err(e) => return order_error(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+
+    match WalletClient::from_env()
+        .broadcast(&bitcoin::consensus::encode::serialize_hex(&tx))
+        .await
+    {
+        Ok(txid) => Json(serde_json::json!({
+            "status": "success",
+            "message": "Settlement broadcast; positions are created once confirmed",
+            "fill_id": id,
+            "txid": txid,
+        }))
+        .into_response(),
+        Err(e) => wallet_error(e),
     }
 }
 
@@ -830,7 +1320,7 @@ pub async fn get_my_positions(
     let limit = params.limit.unwrap_or(50);
     match db.get_user_positions(&params.pubkey, limit).await {
         Ok(positions) => Json(positions).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -858,7 +1348,7 @@ pub async fn get_all_positions(
     let limit = params.limit.unwrap_or(50);
     match db.get_all_positions(limit).await {
         Ok(positions) => Json(positions).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
 
@@ -887,6 +1377,6 @@ pub async fn get_history(
     let limit = params.limit.unwrap_or(20);
     match db.get_resolved_markets(limit).await {
         Ok(markets) => Json(markets).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::internal(e.to_string()).into_response(),
     }
 }
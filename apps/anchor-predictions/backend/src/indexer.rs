@@ -12,9 +12,11 @@ use tokio::time::{sleep, Duration};
 use crate::amm::INITIAL_LIQUIDITY;
 use crate::config::Config;
 use crate::db::Database;
+use crate::models::MarketType;
+use crate::orderbook::{is_valid_price, BookOrder, OrderBook, OrderMessage};
 
 /// Market creation message parser
-/// Format: [market_id 32] [question_len 2 BE] [question var] [desc_len 2 BE] [desc var] [resolution_block 4 BE] [oracle_pubkey 32] [initial_liquidity 8 BE]
+/// Format: [market_id 32] [question_len 2 BE] [question var] [desc_len 2 BE] [desc var] [resolution_block 4 BE] [oracle_pubkey 32] [initial_liquidity 8 BE] [market_type section]
 pub struct MarketCreateBody {
     pub market_id: [u8; 32],
     pub question: String,
@@ -22,6 +24,7 @@ pub struct MarketCreateBody {
     pub resolution_block: u32,
     pub oracle_pubkey: [u8; 32],
     pub initial_liquidity: i64,
+    pub market_type: MarketType,
 }
 
 impl MarketCreateBody {
@@ -70,7 +73,7 @@ impl MarketCreateBody {
         offset += 32;
 
         let initial_liquidity = if body.len() >= offset + 8 {
-            i64::from_be_bytes([
+            let liquidity = i64::from_be_bytes([
                 body[offset],
                 body[offset + 1],
                 body[offset + 2],
@@ -79,11 +82,20 @@ impl MarketCreateBody {
                 body[offset + 5],
                 body[offset + 6],
                 body[offset + 7],
-            ])
+            ]);
+            offset += 8;
+            liquidity
         } else {
             INITIAL_LIQUIDITY
         };
 
+        // Markets without a type section are binary
+        let market_type = if body.len() > offset {
+            parse_market_type(&body[offset..])?
+        } else {
+            MarketType::Binary
+        };
+
         Some(Self {
             market_id,
             question,
@@ -91,10 +103,58 @@ impl MarketCreateBody {
             resolution_block,
             oracle_pubkey,
             initial_liquidity,
+            market_type,
         })
     }
 }
 
+/// Market type section of a market creation message
+/// Format: [type 1] then nothing for binary (0),
+/// [outcome_count 1] ([label_len 1] [label var])* for categorical (1),
+/// [lower 8 BE] [upper 8 BE] [unit_len 1] [unit var] for scalar (2)
+fn parse_market_type(section: &[u8]) -> Option<MarketType> {
+    let (&kind, rest) = section.split_first()?;
+    let market_type = match kind {
+        0 => MarketType::Binary,
+        1 => {
+            let (&count, mut rest) = rest.split_first()?;
+            let mut outcomes = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let (label, tail) = parse_short_string(rest)?;
+                outcomes.push(label);
+                rest = tail;
+            }
+            MarketType::Categorical { outcomes }
+        }
+        2 => {
+            if rest.len() < 16 {
+                return None;
+            }
+            let lower = i64::from_be_bytes(rest[0..8].try_into().ok()?);
+            let upper = i64::from_be_bytes(rest[8..16].try_into().ok()?);
+            let (unit, _) = parse_short_string(&rest[16..])?;
+            MarketType::Scalar { lower, upper, unit }
+        }
+        _ => return None,
+    };
+
+    market_type.validate().ok()?;
+    Some(market_type)
+}
+
+/// [len 1] [UTF-8 var], returning the string and the bytes after it
+fn parse_short_string(bytes: &[u8]) -> Option<(String, &[u8])> {
+    let (&len, rest) = bytes.split_first()?;
+    let len = len as usize;
+    if rest.len() < len {
+        return None;
+    }
+    Some((
+        String::from_utf8_lossy(&rest[..len]).to_string(),
+        &rest[len..],
+    ))
+}
+
 /// Place bet message parser
 /// Format: [market_id 32] [outcome 1] [amount_sats 8 BE] [min_shares 8 BE] [user_pubkey 33]
 pub struct PlaceBetBody {
@@ -138,12 +198,13 @@ impl PlaceBetBody {
 }
 
 /// Market resolve message parser
-/// Format: [market_id 32] [resolution 1] [oracle_pubkey 32] [schnorr_signature 64]
+/// Format: [market_id 32] [resolution 1] [oracle_pubkey 32] [schnorr_signature 64] [value 8 BE, scalar markets]
 pub struct MarketResolveBody {
     pub market_id: [u8; 32],
     pub resolution: u8,
     pub _oracle_pubkey: [u8; 32],
     pub _schnorr_signature: [u8; 64],
+    pub value: Option<i64>,
 }
 
 impl MarketResolveBody {
@@ -163,11 +224,17 @@ impl MarketResolveBody {
         let mut schnorr_signature = [0u8; 64];
         schnorr_signature.copy_from_slice(&body[65..129]);
 
+        let value = body
+            .get(129..137)
+            .and_then(|v| v.try_into().ok())
+            .map(i64::from_be_bytes);
+
         Some(Self {
             market_id,
             resolution,
             _oracle_pubkey: oracle_pubkey,
             _schnorr_signature: schnorr_signature,
+            value,
         })
     }
 }
@@ -227,6 +294,12 @@ impl Indexer {
     }
 
     async fn process_block(&self, block: &Block, height: i32) -> Result<()> {
+        // Orders are good through their expiry block
+        let expired = self.db.expire_orders(height).await?;
+        if expired > 0 {
+            tracing::info!("Expired {} orders at block {}", expired, height);
+        }
+
         for tx in &block.txdata {
             self.process_transaction(tx, height).await?;
         }
@@ -256,11 +329,13 @@ impl Indexer {
                                     &txid_bytes,
                                     height,
                                     create.initial_liquidity,
+                                    &create.market_type,
                                 )
                                 .await;
 
                             tracing::info!(
-                                "Indexed market: {} - \"{}\"",
+                                "Indexed {} market: {} - \"{}\"",
+                                create.market_type.name(),
                                 hex::encode(&create.market_id[..8]),
                                 create.question.chars().take(50).collect::<String>()
                             );
@@ -268,12 +343,25 @@ impl Indexer {
                     }
                     AnchorKind::PlaceBet => {
                         if let Some(bet) = PlaceBetBody::parse(&msg.body) {
-                            // Get current AMM state
-                            if let Ok(Some(amm)) =
-                                self.db.get_market_amm_state(&bet.market_id).await
+                            // Order book markets do not trade against the AMM
+                            if let Ok(Some((_, true))) =
+                                self.db.get_market_mode(&bet.market_id).await
                             {
+                                continue;
+                            }
+
+                            // Get current AMM state
+                            if let Ok(Some(amm)) = self.db.get_market_amm(&bet.market_id).await {
                                 // Calculate shares
-                                let result = amm.quote(bet.outcome as i16, bet.amount_sats);
+                                let Some(trade) = amm.quote(bet.outcome as i16, bet.amount_sats)
+                                else {
+                                    tracing::debug!(
+                                        "Ignoring bet {}: no outcome {} or no amount",
+                                        hex::encode(&txid_bytes[..8]),
+                                        bet.outcome
+                                    );
+                                    continue;
+                                };
 
                                 // Insert position
                                 let _ = self
@@ -286,30 +374,29 @@ impl Indexer {
                                         &bet.user_pubkey,
                                         bet.outcome as i16,
                                         bet.amount_sats,
-                                        result.shares_out,
-                                        result.avg_price as f32,
+                                        trade.shares_out,
+                                        trade.avg_price as f32,
                                     )
                                     .await;
 
                                 // Update market AMM state
                                 let _ = self
                                     .db
-                                    .update_market_after_bet(
+                                    .update_market_after_trade(
                                         &bet.market_id,
-                                        result.new_yes_pool,
-                                        result.new_no_pool,
+                                        &amm,
+                                        &trade,
                                         bet.amount_sats,
                                         bet.outcome as i16,
                                     )
                                     .await;
 
-                                let outcome_str = if bet.outcome == 1 { "YES" } else { "NO" };
                                 tracing::info!(
-                                    "Indexed bet on {}: {} {} sats -> {} shares",
+                                    "Indexed bet on {}: outcome {} {} sats -> {} shares",
                                     hex::encode(&bet.market_id[..8]),
-                                    outcome_str,
+                                    bet.outcome,
                                     bet.amount_sats,
-                                    result.shares_out
+                                    trade.shares_out
                                 );
                             }
                         }
@@ -321,24 +408,29 @@ impl Indexer {
                                 .resolve_market(
                                     &resolve.market_id,
                                     resolve.resolution as i16,
+                                    resolve.value,
                                     &txid_bytes,
                                     height,
                                 )
                                 .await;
 
-                            let resolution_str = match resolve.resolution {
-                                0 => "NO",
-                                1 => "YES",
-                                2 => "INVALID",
-                                _ => "UNKNOWN",
-                            };
                             tracing::info!(
-                                "Indexed market resolution: {} -> {}",
+                                "Indexed market resolution: {} -> {}{}",
                                 hex::encode(&resolve.market_id[..8]),
-                                resolution_str
+                                resolve.resolution,
+                                resolve
+                                    .value
+                                    .map(|v| format!(" (value {})", v))
+                                    .unwrap_or_default()
                             );
                         }
                     }
+                    AnchorKind::MarketOrder => {
+                        if let Some(order) = OrderMessage::parse(&msg.body) {
+                            self.index_order(tx, order, &txid_bytes, vout as i32, height)
+                                .await?;
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -346,4 +438,241 @@ impl Indexer {
 
         Ok(())
     }
+
+    async fn index_order(
+        &self,
+        tx: &Transaction,
+        order: OrderMessage,
+        txid_bytes: &[u8],
+        vout: i32,
+        height: i32,
+    ) -> Result<()> {
+        match order {
+            OrderMessage::Place {
+                market_id,
+                outcome,
+                price_bps,
+                shares,
+                expiry_block,
+                user_pubkey,
+            } => {
+                let accepts_orders = matches!(
+                    self.db.get_market_mode(&market_id).await?,
+                    Some((status, true)) if status == "open"
+                );
+                let shares = shares.min(i64::MAX as u64) as i64;
+                if !accepts_orders
+                    || outcome > 1
+                    || !is_valid_price(price_bps as i64)
+                    || shares == 0
+                {
+                    tracing::debug!(
+                        "Ignoring order {}: market closed or invalid terms",
+                        hex::encode(&txid_bytes[..8])
+                    );
+                    return Ok(());
+                }
+
+                let resting = self.db.get_book_orders(&market_id).await?;
+                let Some(order_id) = self
+                    .db
+                    .insert_order(
+                        &market_id,
+                        txid_bytes,
+                        vout,
+                        height,
+                        &user_pubkey,
+                        outcome as i16,
+                        price_bps as i32,
+                        shares,
+                        expiry_block as i32,
+                    )
+                    .await?
+                else {
+                    return Ok(());
+                };
+
+                let mut book = OrderBook::from_orders(resting);
+                let fills = book.submit(BookOrder {
+                    id: order_id,
+                    outcome: outcome as i16,
+                    price_bps: price_bps as i64,
+                    remaining: shares,
+                });
+                self.db.record_fills(&market_id, &fills).await?;
+
+                tracing::info!(
+                    "Indexed order on {}: {} {} shares @ {} bps ({} fills)",
+                    hex::encode(&market_id[..8]),
+                    if outcome == 1 { "YES" } else { "NO" },
+                    shares,
+                    price_bps,
+                    fills.len()
+                );
+            }
+            OrderMessage::Cancel {
+                market_id,
+                order_txid,
+            } => {
+                // Only the order owner can spend an output of the order transaction
+                let spends_order = tx
+                    .input
+                    .iter()
+                    .any(|input| input.previous_output.txid.to_byte_array() == order_txid);
+                if !spends_order {
+                    tracing::debug!(
+                        "Ignoring cancel {}: does not spend the order transaction",
+                        hex::encode(&txid_bytes[..8])
+                    );
+                    return Ok(());
+                }
+                if self
+                    .db
+                    .cancel_order(&market_id, &order_txid, txid_bytes)
+                    .await?
+                {
+                    tracing::info!("Cancelled order {}", hex::encode(&order_txid[..8]));
+                }
+            }
+            OrderMessage::Fill {
+                market_id,
+                maker_txid,
+                taker_txid,
+                shares,
+                maker_price_bps,
+            } => {
+                // The escrow output carrying the collateral follows the anchor output
+                let escrow_funded = tx
+                    .output
+                    .get(vout as usize + 1)
+                    .is_some_and(|output| output.value.to_sat() >= shares);
+                if !escrow_funded {
+                    tracing::debug!(
+                        "Ignoring fill {}: escrow output missing or underfunded",
+                        hex::encode(&txid_bytes[..8])
+                    );
+                    return Ok(());
+                }
+                if self
+                    .db
+                    .settle_fill(
+                        &market_id,
+                        &maker_txid,
+                        &taker_txid,
+                        shares.min(i64::MAX as u64) as i64,
+                        maker_price_bps as i32,
+                        txid_bytes,
+                        vout,
+                        height,
+                    )
+                    .await?
+                {
+                    tracing::info!(
+                        "Settled fill on {}: {} shares @ {} bps",
+                        hex::encode(&market_id[..8]),
+                        shares,
+                        maker_price_bps
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MarketCreate body up to and including the initial liquidity
+    fn create_body() -> Vec<u8> {
+        let mut body = vec![7u8; 32];
+        body.extend_from_slice(&5u16.to_be_bytes());
+        body.extend_from_slice(b"Rain?");
+        body.extend_from_slice(&0u16.to_be_bytes());
+        body.extend_from_slice(&800_000u32.to_be_bytes());
+        body.extend_from_slice(&[9u8; 32]);
+        body.extend_from_slice(&1_000_000i64.to_be_bytes());
+        body
+    }
+
+    #[test]
+    fn test_parse_binary_market_without_type_section() {
+        let create = MarketCreateBody::parse(&create_body()).unwrap();
+        assert_eq!(create.market_type, MarketType::Binary);
+        assert_eq!(create.initial_liquidity, 1_000_000);
+    }
+
+    #[test]
+    fn test_parse_categorical_market() {
+        let mut body = create_body();
+        body.extend_from_slice(&[1, 3]);
+        for label in ["Red", "Green", "Blue"] {
+            body.push(label.len() as u8);
+            body.extend_from_slice(label.as_bytes());
+        }
+
+        let create = MarketCreateBody::parse(&body).unwrap();
+        assert_eq!(
+            create.market_type.outcome_labels(),
+            vec!["Red", "Green", "Blue"]
+        );
+
+        // A label running past the body rejects the market
+        body.truncate(body.len() - 1);
+        assert!(MarketCreateBody::parse(&body).is_none());
+    }
+
+    #[test]
+    fn test_parse_scalar_market() {
+        let mut body = create_body();
+        body.push(2);
+        body.extend_from_slice(&(-10i64).to_be_bytes());
+        body.extend_from_slice(&40i64.to_be_bytes());
+        body.push(1);
+        body.extend_from_slice(b"C");
+
+        let create = MarketCreateBody::parse(&body).unwrap();
+        assert_eq!(
+            create.market_type,
+            MarketType::Scalar {
+                lower: -10,
+                upper: 40,
+                unit: "C".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_market_types() {
+        // Unknown type
+        let mut body = create_body();
+        body.push(9);
+        assert!(MarketCreateBody::parse(&body).is_none());
+
+        // Empty range
+        let mut body = create_body();
+        body.push(2);
+        body.extend_from_slice(&5i64.to_be_bytes());
+        body.extend_from_slice(&5i64.to_be_bytes());
+        body.push(0);
+        assert!(MarketCreateBody::parse(&body).is_none());
+
+        // Two outcomes belong in a binary market
+        let mut body = create_body();
+        body.extend_from_slice(&[1, 2, 1, b'A', 1, b'B']);
+        assert!(MarketCreateBody::parse(&body).is_none());
+    }
+
+    #[test]
+    fn test_parse_scalar_resolution_value() {
+        let mut body = vec![7u8; 32];
+        body.push(0);
+        body.extend_from_slice(&[9u8; 32]);
+        body.extend_from_slice(&[1u8; 64]);
+        assert_eq!(MarketResolveBody::parse(&body).unwrap().value, None);
+
+        body.extend_from_slice(&23i64.to_be_bytes());
+        assert_eq!(MarketResolveBody::parse(&body).unwrap().value, Some(23));
+    }
 }
@@ -1,5 +1,5 @@
 //! Anchor Predictions Backend
-//! Binary Prediction Markets with AMM
+//! Binary Prediction Markets with AMM and limit order books
 
 mod amm;
 mod config;
@@ -7,13 +7,17 @@ mod db;
 mod handlers;
 mod indexer;
 mod models;
+mod orderbook;
+mod settlement;
+mod wallet;
 
+use anchor_api_security::SecurityConfig;
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
@@ -27,6 +31,7 @@ use crate::models::*;
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        health,
         get_stats,
         list_markets,
         get_market,
@@ -40,6 +45,13 @@ use crate::models::*;
         get_my_positions,
         get_all_positions,
         get_history,
+        place_order,
+        cancel_order,
+        get_orderbook,
+        list_market_orders,
+        list_market_fills,
+        combine_fill_psbt,
+        finalize_fill,
     ),
     components(schemas(
         Market,
@@ -50,11 +62,22 @@ use crate::models::*;
         PlaceBetRequest,
         PlaceBetQuote,
         ClaimWinningsRequest,
+        Order,
+        OrderFill,
+        OrderBookLevel,
+        OrderBookDepth,
+        PlaceOrderRequest,
+        CancelOrderRequest,
+        CombineFillRequest,
+        FinalizeFillRequest,
     )),
     tags(
+        (name = "health", description = "Service health"),
         (name = "stats", description = "Market statistics"),
         (name = "markets", description = "Prediction market operations"),
+        (name = "orderbook", description = "Limit order book markets"),
         (name = "user", description = "User position operations"),
+        (name = "positions", description = "Positions across all markets"),
         (name = "history", description = "Historical data"),
     ),
     info(
@@ -67,6 +90,10 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize tracing
     tracing_subscriber::registry()
         .with(
@@ -106,10 +133,6 @@ async fn main() -> anyhow::Result<()> {
     });
 
     // Build router
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
 
     let app = Router::new()
         // Health check
@@ -126,6 +149,16 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/markets/:id/resolution", get(get_resolution))
         .route("/api/markets/:id/winners", get(get_market_winners))
         .route("/api/markets/:id/claim", post(claim_winnings))
+        // Order book
+        .route(
+            "/api/markets/:id/orders",
+            get(list_market_orders).post(place_order),
+        )
+        .route("/api/markets/:id/orderbook", get(get_orderbook))
+        .route("/api/markets/:id/fills", get(list_market_fills))
+        .route("/api/orders/:id/cancel", post(cancel_order))
+        .route("/api/fills/:id/psbt", post(combine_fill_psbt))
+        .route("/api/fills/:id/finalize", post(finalize_fill))
         // User/Positions
         .route("/api/my/positions", get(get_my_positions))
         .route("/api/positions", get(get_all_positions))
@@ -133,7 +166,8 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/history", get(get_history))
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .layer(cors)
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer())
         .with_state(db);
 
     // Start server
@@ -1,8 +1,16 @@
-//! Data models for Anchor Predictions - Binary Prediction Markets
+//! Data models for Anchor Predictions - Binary, Categorical and Scalar Markets
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+/// Display-order hex for a txid stored in internal byte order
+pub fn display_txid(txid: &[u8]) -> String {
+    use bitcoin::hashes::Hash;
+    bitcoin::Txid::from_slice(txid)
+        .map(|t| t.to_string())
+        .unwrap_or_else(|_| hex::encode(txid))
+}
+
 /// Outcome names
 pub fn outcome_name(outcome: i16) -> String {
     match outcome {
@@ -12,14 +20,131 @@ pub fn outcome_name(outcome: i16) -> String {
     }
 }
 
-/// Resolution status names
-pub fn resolution_name(resolution: Option<i16>) -> String {
-    match resolution {
-        None => "Pending".to_string(),
-        Some(0) => "NO".to_string(),
-        Some(1) => "YES".to_string(),
-        Some(2) => "Invalid".to_string(),
-        Some(n) => format!("Unknown({})", n),
+/// Most outcomes a categorical market can have
+pub const MAX_OUTCOMES: usize = 16;
+
+/// Longest categorical outcome label or scalar unit, in bytes
+pub const MAX_LABEL_LEN: usize = 64;
+
+/// Shape of a market's outcomes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketType {
+    /// NO (0) or YES (1)
+    Binary,
+    /// One of N labelled outcomes; each winning share pays 1 sat
+    Categorical { outcomes: Vec<String> },
+    /// A value in `[lower, upper]`; LONG (1) and SHORT (0) shares split the
+    /// 1 sat share payout by where the value lands in the range
+    Scalar {
+        lower: i64,
+        upper: i64,
+        unit: String,
+    },
+}
+
+impl MarketType {
+    /// Rebuild the type from a `markets` row
+    pub fn from_columns(
+        market_type: Option<&str>,
+        outcome_labels: Option<Vec<String>>,
+        scalar_lower: Option<i64>,
+        scalar_upper: Option<i64>,
+        scalar_unit: Option<String>,
+    ) -> Self {
+        match (market_type, scalar_lower, scalar_upper) {
+            (Some("categorical"), _, _) => MarketType::Categorical {
+                outcomes: outcome_labels.unwrap_or_default(),
+            },
+            (Some("scalar"), Some(lower), Some(upper)) => MarketType::Scalar {
+                lower,
+                upper,
+                unit: scalar_unit.unwrap_or_default(),
+            },
+            _ => MarketType::Binary,
+        }
+    }
+
+    /// Name stored in `markets.market_type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            MarketType::Binary => "binary",
+            MarketType::Categorical { .. } => "categorical",
+            MarketType::Scalar { .. } => "scalar",
+        }
+    }
+
+    /// Outcome labels by index
+    pub fn outcome_labels(&self) -> Vec<String> {
+        match self {
+            MarketType::Binary => vec!["NO".to_string(), "YES".to_string()],
+            MarketType::Categorical { outcomes } => outcomes.clone(),
+            MarketType::Scalar { .. } => vec!["SHORT".to_string(), "LONG".to_string()],
+        }
+    }
+
+    /// Label of one outcome
+    pub fn outcome_name(&self, outcome: i16) -> String {
+        usize::try_from(outcome)
+            .ok()
+            .and_then(|i| self.outcome_labels().get(i).cloned())
+            .unwrap_or_else(|| format!("Unknown({})", outcome))
+    }
+
+    /// Resolution status name
+    ///
+    /// Scalar markets resolve to a value; anything else, like a resolution
+    /// index past the last outcome, is invalid.
+    pub fn resolution_name(&self, resolution: Option<i16>, resolved_value: Option<i64>) -> String {
+        match (self, resolution, resolved_value) {
+            (_, None, _) => "Pending".to_string(),
+            (MarketType::Scalar { unit, .. }, Some(_), Some(value)) => {
+                format!("{} {}", value, unit).trim_end().to_string()
+            }
+            (MarketType::Scalar { .. }, Some(_), None) => "Invalid".to_string(),
+            (_, Some(r), _) => usize::try_from(r)
+                .ok()
+                .and_then(|i| self.outcome_labels().get(i).cloned())
+                .unwrap_or_else(|| "Invalid".to_string()),
+        }
+    }
+
+    /// Check the outcomes or range make a tradeable market
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            MarketType::Binary => Ok(()),
+            MarketType::Categorical { outcomes } => {
+                if outcomes.len() < 3 || outcomes.len() > MAX_OUTCOMES {
+                    return Err(format!(
+                        "Categorical markets need 3 to {} outcomes; use a binary market for two",
+                        MAX_OUTCOMES
+                    ));
+                }
+                for (i, label) in outcomes.iter().enumerate() {
+                    if label.trim().is_empty() || label.len() > MAX_LABEL_LEN {
+                        return Err(format!(
+                            "Outcome {} label must be 1 to {} bytes",
+                            i, MAX_LABEL_LEN
+                        ));
+                    }
+                    if outcomes[..i].contains(label) {
+                        return Err(format!("Duplicate outcome label \"{}\"", label));
+                    }
+                }
+                Ok(())
+            }
+            MarketType::Scalar { lower, upper, unit } => {
+                if lower >= upper {
+                    return Err("Scalar lower bound must be below the upper bound".to_string());
+                }
+                if unit.len() > MAX_LABEL_LEN {
+                    return Err(format!(
+                        "Scalar unit must be at most {} bytes",
+                        MAX_LABEL_LEN
+                    ));
+                }
+                Ok(())
+            }
+        }
     }
 }
 
@@ -36,16 +161,32 @@ pub struct Market {
     pub status: String,
     pub resolution: Option<i16>,
     pub resolution_name: String,
-    // AMM State
+    /// `binary`, `categorical` or `scalar`
+    pub market_type: String,
+    /// Outcome labels by index (NO/YES, SHORT/LONG or the categorical labels)
+    pub outcomes: Vec<String>,
+    // AMM State (binary and scalar markets)
     pub yes_pool: i64,
     pub no_pool: i64,
     pub yes_price: f64,
     pub no_price: f64,
+    /// AMM pool of every outcome, by index
+    pub pools: Vec<i64>,
+    /// AMM price of every outcome, by index
+    pub prices: Vec<f64>,
+    /// Range of a scalar market
+    pub scalar_lower: Option<i64>,
+    pub scalar_upper: Option<i64>,
+    pub scalar_unit: Option<String>,
+    /// Value a scalar market resolved at
+    pub resolved_value: Option<i64>,
     // Volume
     pub total_volume_sats: i64,
     pub total_yes_sats: i64,
     pub total_no_sats: i64,
     pub position_count: i32,
+    /// Trades through the limit order book instead of the AMM
+    pub order_book: bool,
     // Timestamps
     pub created_at: String,
 }
@@ -90,12 +231,49 @@ pub struct CreateMarketRequest {
     pub resolution_block: i32,
     pub oracle_pubkey: String,
     pub initial_liquidity_sats: Option<i64>,
+    /// Use the limit order book instead of the AMM (binary markets only)
+    pub order_book: Option<bool>,
+    /// `binary` (default), `categorical` or `scalar`
+    pub market_type: Option<String>,
+    /// Outcome labels of a categorical market
+    pub outcomes: Option<Vec<String>>,
+    /// Range and unit of a scalar market
+    pub scalar_lower: Option<i64>,
+    pub scalar_upper: Option<i64>,
+    pub scalar_unit: Option<String>,
+}
+
+impl CreateMarketRequest {
+    /// The requested market type, validated
+    pub fn market_type(&self) -> Result<MarketType, String> {
+        let market_type = match self.market_type.as_deref().unwrap_or("binary") {
+            "binary" => MarketType::Binary,
+            "categorical" => MarketType::Categorical {
+                outcomes: self
+                    .outcomes
+                    .clone()
+                    .ok_or("Categorical markets need outcomes")?,
+            },
+            "scalar" => match (self.scalar_lower, self.scalar_upper) {
+                (Some(lower), Some(upper)) => MarketType::Scalar {
+                    lower,
+                    upper,
+                    unit: self.scalar_unit.clone().unwrap_or_default(),
+                },
+                _ => return Err("Scalar markets need scalar_lower and scalar_upper".to_string()),
+            },
+            other => return Err(format!("Unknown market type: {}", other)),
+        };
+        market_type.validate()?;
+        Ok(market_type)
+    }
 }
 
 /// Place Bet Request
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct PlaceBetRequest {
-    pub outcome: i16, // 0=NO, 1=YES
+    /// Outcome index: 0=NO/SHORT, 1=YES/LONG, or a categorical outcome
+    pub outcome: i16,
     pub amount_sats: i64,
     pub user_pubkey: String,
     /// Bitcoin address for the bet transaction output (required for real tx)
@@ -113,8 +291,11 @@ pub struct PlaceBetQuote {
     pub shares_out: i64,
     pub avg_price: f64,
     pub price_impact: f64,
+    /// Prices of outcome 1 and 0 after the bet
     pub new_yes_price: f64,
     pub new_no_price: f64,
+    /// Price of every outcome after the bet, by index
+    pub new_prices: Vec<f64>,
 }
 
 /// Claim Winnings Request
@@ -143,6 +324,96 @@ pub struct Winner {
     pub claimed: bool,
 }
 
+/// Limit order on an order book market
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Order {
+    pub id: i32,
+    pub market_id: String,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub user_pubkey: String,
+    pub outcome: i16,
+    pub outcome_name: String,
+    pub price_bps: i32,
+    pub shares: i64,
+    pub filled_shares: i64,
+    pub status: String,
+    pub expiry_block: i32,
+    pub created_at: String,
+}
+
+/// Matched maker/taker pair
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderFill {
+    pub id: i32,
+    pub market_id: String,
+    pub maker_order_id: i32,
+    pub taker_order_id: i32,
+    pub shares: i64,
+    pub maker_price_bps: i32,
+    pub maker_cost_sats: i64,
+    pub taker_cost_sats: i64,
+    pub status: String,
+    pub settlement_txid: Option<String>,
+    pub settled_at_block: Option<i32>,
+    pub created_at: String,
+}
+
+/// Aggregated open shares at one price
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderBookLevel {
+    pub price_bps: i64,
+    pub shares: i64,
+}
+
+/// Order book depth for both outcomes, best price first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderBookDepth {
+    pub market_id: String,
+    pub yes_bids: Vec<OrderBookLevel>,
+    pub no_bids: Vec<OrderBookLevel>,
+}
+
+/// Place Order Request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PlaceOrderRequest {
+    pub outcome: i16, // 0=NO, 1=YES
+    /// Price per share in basis points of the 1 sat payout (1-9999)
+    pub price_bps: i64,
+    pub shares: i64,
+    pub user_pubkey: String,
+    /// Block after which the order expires (omit for good until resolution)
+    pub expiry_block: Option<i32>,
+    /// Address receiving the order ticket output (a fresh wallet address if omitted)
+    pub ticket_address: Option<String>,
+}
+
+/// Cancel Order Request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CancelOrderRequest {
+    /// Output of the order transaction spent by the cancel (the order ticket)
+    pub ticket_vout: u32,
+}
+
+/// Combine funding PSBTs for a fill
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CombineFillRequest {
+    /// Maker funding PSBT (base64): inputs with witness_utxo plus change outputs
+    pub maker_psbt: String,
+    /// Taker funding PSBT (base64)
+    pub taker_psbt: String,
+    /// Address holding the fill collateral until resolution
+    pub escrow_address: String,
+}
+
+/// Signed settlement PSBTs for a fill
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct FinalizeFillRequest {
+    /// Copies of the settlement PSBT signed by maker and taker (base64)
+    pub signed_psbts: Vec<String>,
+}
+
 /// Market with calculated AMM prices
 impl Market {
     pub fn calculate_prices(yes_pool: i64, no_pool: i64) -> (f64, f64) {
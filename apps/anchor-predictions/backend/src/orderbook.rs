@@ -0,0 +1,353 @@
+//! Limit Order Book for Binary Prediction Markets
+//!
+//! An alternative to the AMM for markets where slippage is unacceptable.
+//! Every order is a bid for one outcome at a fixed price per share, expressed
+//! in basis points of the 1 sat payout a winning share receives.
+//!
+//! A YES bid at `p` and a NO bid at `q` cross when `p + q >= 10000`: together
+//! they fully collateralize the shares (one side always wins), so no one needs
+//! to hold shares in order to sell them.
+//!
+//! Matching uses price-time priority. Fills execute at the resting (maker)
+//! price; the taker pays the complement and keeps any price improvement.
+//!
+//! On-chain order intents use the MarketOrder kind (44), whose first body
+//! byte selects the sub-kind:
+//!
+//! ```text
+//! Place:  [0] [market_id 32] [outcome 1] [price_bps 2 BE] [shares 8 BE] [expiry_block 4 BE] [user_pubkey 33]
+//! Cancel: [1] [market_id 32] [order_txid 32]
+//! Fill:   [2] [market_id 32] [maker_txid 32] [taker_txid 32] [shares 8 BE] [maker_price_bps 2 BE]
+//! ```
+
+/// Price scale: a winning share pays out 10000 basis points (1 sat)
+pub const PRICE_SCALE: i64 = 10_000;
+
+/// Sub-kind byte for placing an order
+pub const ORDER_PLACE: u8 = 0;
+/// Sub-kind byte for cancelling an order
+pub const ORDER_CANCEL: u8 = 1;
+/// Sub-kind byte for settling a fill
+pub const ORDER_FILL: u8 = 2;
+
+/// A resting or incoming limit order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookOrder {
+    pub id: i32,
+    /// Outcome being bought (0=NO, 1=YES)
+    pub outcome: i16,
+    /// Price per share in basis points (1..=9999)
+    pub price_bps: i64,
+    /// Shares still open
+    pub remaining: i64,
+}
+
+/// A match between a resting maker and an incoming taker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill {
+    pub maker_order_id: i32,
+    pub taker_order_id: i32,
+    pub shares: i64,
+    pub maker_price_bps: i64,
+    /// Collateral paid by the maker (sats)
+    pub maker_cost_sats: i64,
+    /// Collateral paid by the taker (sats); maker + taker = shares
+    pub taker_cost_sats: i64,
+}
+
+impl Fill {
+    fn new(maker: &BookOrder, taker_id: i32, shares: i64) -> Self {
+        let maker_cost_sats = shares * maker.price_bps / PRICE_SCALE;
+        Self {
+            maker_order_id: maker.id,
+            taker_order_id: taker_id,
+            shares,
+            maker_price_bps: maker.price_bps,
+            maker_cost_sats,
+            taker_cost_sats: shares - maker_cost_sats,
+        }
+    }
+}
+
+/// Check a price is strictly between 0 and 1 sat per share
+pub fn is_valid_price(price_bps: i64) -> bool {
+    price_bps > 0 && price_bps < PRICE_SCALE
+}
+
+/// Open orders of one market, bucketed by outcome
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    yes_bids: Vec<BookOrder>,
+    no_bids: Vec<BookOrder>,
+}
+
+impl OrderBook {
+    /// Build a book from resting orders, oldest first
+    pub fn from_orders(orders: impl IntoIterator<Item = BookOrder>) -> Self {
+        let mut book = Self::default();
+        for order in orders {
+            book.rest(order);
+        }
+        book
+    }
+
+    /// Match an incoming order against the opposite outcome.
+    ///
+    /// Returns the fills in execution order; any unfilled remainder rests on the book.
+    pub fn submit(&mut self, mut taker: BookOrder) -> Vec<Fill> {
+        let mut fills = Vec::new();
+        let opposite = if taker.outcome == 1 {
+            &mut self.no_bids
+        } else {
+            &mut self.yes_bids
+        };
+
+        while taker.remaining > 0 {
+            let Some(maker) = opposite.first_mut() else {
+                break;
+            };
+            if maker.price_bps + taker.price_bps < PRICE_SCALE {
+                break;
+            }
+
+            let shares = maker.remaining.min(taker.remaining);
+            fills.push(Fill::new(maker, taker.id, shares));
+            maker.remaining -= shares;
+            taker.remaining -= shares;
+            if maker.remaining == 0 {
+                opposite.remove(0);
+            }
+        }
+
+        if taker.remaining > 0 {
+            self.rest(taker);
+        }
+        fills
+    }
+
+    /// Aggregate open shares per price level, best price first
+    pub fn depth(&self, outcome: i16) -> Vec<(i64, i64)> {
+        let mut levels: Vec<(i64, i64)> = Vec::new();
+        for order in self.side(outcome) {
+            match levels.last_mut() {
+                Some((price, shares)) if *price == order.price_bps => *shares += order.remaining,
+                _ => levels.push((order.price_bps, order.remaining)),
+            }
+        }
+        levels
+    }
+
+    fn side(&self, outcome: i16) -> &Vec<BookOrder> {
+        if outcome == 1 {
+            &self.yes_bids
+        } else {
+            &self.no_bids
+        }
+    }
+
+    /// Insert behind all orders at the same or a better price (time priority)
+    fn rest(&mut self, order: BookOrder) {
+        let side = if order.outcome == 1 {
+            &mut self.yes_bids
+        } else {
+            &mut self.no_bids
+        };
+        let index = side.partition_point(|o| o.price_bps >= order.price_bps);
+        side.insert(index, order);
+    }
+}
+
+/// MarketOrder (kind 44) message body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderMessage {
+    Place {
+        market_id: [u8; 32],
+        outcome: u8,
+        price_bps: u16,
+        shares: u64,
+        /// Block after which the order expires (0 = good until resolution)
+        expiry_block: u32,
+        user_pubkey: [u8; 33],
+    },
+    Cancel {
+        market_id: [u8; 32],
+        order_txid: [u8; 32],
+    },
+    Fill {
+        market_id: [u8; 32],
+        maker_txid: [u8; 32],
+        taker_txid: [u8; 32],
+        shares: u64,
+        maker_price_bps: u16,
+    },
+}
+
+impl OrderMessage {
+    pub fn parse(body: &[u8]) -> Option<Self> {
+        let (&sub_kind, rest) = body.split_first()?;
+        match sub_kind {
+            ORDER_PLACE if rest.len() >= 80 => Some(Self::Place {
+                market_id: rest[0..32].try_into().ok()?,
+                outcome: rest[32],
+                price_bps: u16::from_be_bytes([rest[33], rest[34]]),
+                shares: u64::from_be_bytes(rest[35..43].try_into().ok()?),
+                expiry_block: u32::from_be_bytes(rest[43..47].try_into().ok()?),
+                user_pubkey: rest[47..80].try_into().ok()?,
+            }),
+            ORDER_CANCEL if rest.len() >= 64 => Some(Self::Cancel {
+                market_id: rest[0..32].try_into().ok()?,
+                order_txid: rest[32..64].try_into().ok()?,
+            }),
+            ORDER_FILL if rest.len() >= 106 => Some(Self::Fill {
+                market_id: rest[0..32].try_into().ok()?,
+                maker_txid: rest[32..64].try_into().ok()?,
+                taker_txid: rest[64..96].try_into().ok()?,
+                shares: u64::from_be_bytes(rest[96..104].try_into().ok()?),
+                maker_price_bps: u16::from_be_bytes([rest[104], rest[105]]),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        match self {
+            Self::Place {
+                market_id,
+                outcome,
+                price_bps,
+                shares,
+                expiry_block,
+                user_pubkey,
+            } => {
+                body.push(ORDER_PLACE);
+                body.extend_from_slice(market_id);
+                body.push(*outcome);
+                body.extend_from_slice(&price_bps.to_be_bytes());
+                body.extend_from_slice(&shares.to_be_bytes());
+                body.extend_from_slice(&expiry_block.to_be_bytes());
+                body.extend_from_slice(user_pubkey);
+            }
+            Self::Cancel {
+                market_id,
+                order_txid,
+            } => {
+                body.push(ORDER_CANCEL);
+                body.extend_from_slice(market_id);
+                body.extend_from_slice(order_txid);
+            }
+            Self::Fill {
+                market_id,
+                maker_txid,
+                taker_txid,
+                shares,
+                maker_price_bps,
+            } => {
+                body.push(ORDER_FILL);
+                body.extend_from_slice(market_id);
+                body.extend_from_slice(maker_txid);
+                body.extend_from_slice(taker_txid);
+                body.extend_from_slice(&shares.to_be_bytes());
+                body.extend_from_slice(&maker_price_bps.to_be_bytes());
+            }
+        }
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid(id: i32, outcome: i16, price_bps: i64, shares: i64) -> BookOrder {
+        BookOrder {
+            id,
+            outcome,
+            price_bps,
+            remaining: shares,
+        }
+    }
+
+    #[test]
+    fn test_no_cross_rests_order() {
+        let mut book = OrderBook::default();
+        assert!(book.submit(bid(1, 1, 6_000, 1_000)).is_empty());
+        // 6000 + 3000 < 10000: no match
+        assert!(book.submit(bid(2, 0, 3_000, 1_000)).is_empty());
+        assert_eq!(book.depth(1), vec![(6_000, 1_000)]);
+        assert_eq!(book.depth(0), vec![(3_000, 1_000)]);
+    }
+
+    #[test]
+    fn test_cross_fills_at_maker_price() {
+        let mut book = OrderBook::from_orders([bid(1, 1, 6_000, 1_000)]);
+        let fills = book.submit(bid(2, 0, 4_500, 400));
+
+        assert_eq!(fills.len(), 1);
+        let fill = &fills[0];
+        assert_eq!(fill.shares, 400);
+        assert_eq!(fill.maker_price_bps, 6_000);
+        assert_eq!(fill.maker_cost_sats, 240);
+        // Taker pays the complement (4000 bps), not its 4500 limit
+        assert_eq!(fill.taker_cost_sats, 160);
+        assert_eq!(fill.maker_cost_sats + fill.taker_cost_sats, fill.shares);
+
+        assert_eq!(book.depth(1), vec![(6_000, 600)]);
+        assert!(book.depth(0).is_empty());
+    }
+
+    #[test]
+    fn test_price_time_priority() {
+        let mut book = OrderBook::from_orders([
+            bid(1, 1, 6_000, 100),
+            bid(2, 1, 6_500, 100),
+            bid(3, 1, 6_500, 100),
+        ]);
+        let fills = book.submit(bid(4, 0, 5_000, 250));
+
+        let makers: Vec<i32> = fills.iter().map(|f| f.maker_order_id).collect();
+        assert_eq!(makers, vec![2, 3, 1]);
+        assert_eq!(fills[2].shares, 50);
+        assert_eq!(book.depth(1), vec![(6_000, 50)]);
+    }
+
+    #[test]
+    fn test_taker_remainder_rests() {
+        let mut book = OrderBook::from_orders([bid(1, 0, 5_000, 100)]);
+        let fills = book.submit(bid(2, 1, 5_000, 300));
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(book.depth(1), vec![(5_000, 200)]);
+        assert!(book.depth(0).is_empty());
+    }
+
+    #[test]
+    fn test_order_message_roundtrip() {
+        let messages = [
+            OrderMessage::Place {
+                market_id: [1; 32],
+                outcome: 1,
+                price_bps: 6_200,
+                shares: 50_000,
+                expiry_block: 900_000,
+                user_pubkey: [2; 33],
+            },
+            OrderMessage::Cancel {
+                market_id: [1; 32],
+                order_txid: [3; 32],
+            },
+            OrderMessage::Fill {
+                market_id: [1; 32],
+                maker_txid: [3; 32],
+                taker_txid: [4; 32],
+                shares: 10_000,
+                maker_price_bps: 6_200,
+            },
+        ];
+        for message in messages {
+            let bytes = message.to_bytes();
+            assert_eq!(OrderMessage::parse(&bytes), Some(message));
+            assert_eq!(OrderMessage::parse(&bytes[..bytes.len() - 1]), None);
+        }
+    }
+}
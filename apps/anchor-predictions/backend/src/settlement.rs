@@ -0,0 +1,235 @@
+//! Settlement transactions for order book fills
+//!
+//! Each side of a fill funds its collateral with a PSBT containing its own
+//! inputs (with `witness_utxo` set) and change outputs. The backend combines
+//! both into one transaction:
+//!
+//! ```text
+//! outputs: [MarketOrder Fill OP_RETURN] [escrow: shares sats] [maker change...] [taker change...]
+//! ```
+//!
+//! Each party signs the combined PSBT for its own inputs; the signed copies are
+//! merged, finalized (P2WPKH and P2TR key-path inputs) and broadcast.
+
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bitcoin::psbt::Psbt;
+use bitcoin::transaction::Version;
+use bitcoin::{absolute::LockTime, Amount, ScriptBuf, Transaction, TxOut, Witness};
+
+/// Decode a base64 PSBT
+pub fn decode_psbt(encoded: &str) -> Result<Psbt> {
+    let bytes = STANDARD.decode(encoded.trim())?;
+    Ok(Psbt::deserialize(&bytes)?)
+}
+
+/// Encode a PSBT as base64
+pub fn encode_psbt(psbt: &Psbt) -> String {
+    STANDARD.encode(psbt.serialize())
+}
+
+/// Sats a party brings to the settlement: its inputs minus its change outputs
+pub fn contribution(psbt: &Psbt) -> Result<i64> {
+    let mut total: i64 = 0;
+    for (input, txin) in psbt.inputs.iter().zip(&psbt.unsigned_tx.input) {
+        let utxo = input
+            .witness_utxo
+            .as_ref()
+            .ok_or_else(|| anyhow!("Input {} has no witness_utxo", txin.previous_output))?;
+        total += utxo.value.to_sat() as i64;
+    }
+    for output in &psbt.unsigned_tx.output {
+        total -= output.value.to_sat() as i64;
+    }
+    Ok(total)
+}
+
+/// Combine maker and taker funding PSBTs into an unsigned settlement PSBT
+pub fn combine_fill(
+    maker: &Psbt,
+    taker: &Psbt,
+    maker_cost_sats: i64,
+    taker_cost_sats: i64,
+    fill_script: ScriptBuf,
+    escrow_script: ScriptBuf,
+) -> Result<Psbt> {
+    let maker_funds = contribution(maker)?;
+    if maker_funds < maker_cost_sats {
+        bail!(
+            "Maker PSBT funds {} sats, fill requires {}",
+            maker_funds,
+            maker_cost_sats
+        );
+    }
+    let taker_funds = contribution(taker)?;
+    if taker_funds < taker_cost_sats {
+        bail!(
+            "Taker PSBT funds {} sats, fill requires {}",
+            taker_funds,
+            taker_cost_sats
+        );
+    }
+
+    let shares = (maker_cost_sats + taker_cost_sats) as u64;
+    let mut output = vec![
+        TxOut {
+            value: Amount::ZERO,
+            script_pubkey: fill_script,
+        },
+        TxOut {
+            value: Amount::from_sat(shares),
+            script_pubkey: escrow_script,
+        },
+    ];
+    output.extend(maker.unsigned_tx.output.iter().cloned());
+    output.extend(taker.unsigned_tx.output.iter().cloned());
+
+    let tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: maker
+            .unsigned_tx
+            .input
+            .iter()
+            .chain(&taker.unsigned_tx.input)
+            .cloned()
+            .collect(),
+        output,
+    };
+
+    let mut psbt = Psbt::from_unsigned_tx(tx)?;
+    for (slot, input) in psbt
+        .inputs
+        .iter_mut()
+        .zip(maker.inputs.iter().chain(&taker.inputs))
+    {
+        *slot = input.clone();
+    }
+    for (slot, output) in psbt
+        .outputs
+        .iter_mut()
+        .skip(2)
+        .zip(maker.outputs.iter().chain(&taker.outputs))
+    {
+        *slot = output.clone();
+    }
+    Ok(psbt)
+}
+
+/// Merge signed copies of the settlement PSBT and extract the final transaction
+pub fn finalize_fill(unsigned: &Psbt, signed: Vec<Psbt>) -> Result<Transaction> {
+    let mut psbt = unsigned.clone();
+    for copy in signed {
+        if copy.unsigned_tx.compute_txid() != unsigned.unsigned_tx.compute_txid() {
+            bail!("Signed PSBT does not match the settlement transaction");
+        }
+        psbt.combine(copy)?;
+    }
+
+    for (index, input) in psbt.inputs.iter_mut().enumerate() {
+        if input.final_script_witness.is_some() {
+            continue;
+        }
+        let witness = if let Some(sig) = input.tap_key_sig {
+            Witness::from_slice(&[sig.to_vec()])
+        } else if let Some((pubkey, sig)) = input.partial_sigs.iter().next() {
+            Witness::from_slice(&[sig.to_vec(), pubkey.to_bytes()])
+        } else {
+            bail!("Input {} is not signed", index);
+        };
+        input.final_script_witness = Some(witness);
+        input.partial_sigs.clear();
+        input.tap_key_sig = None;
+    }
+
+    Ok(psbt.extract_tx()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+    use bitcoin::{OutPoint, Sequence, TxIn, Txid};
+
+    fn funding_psbt(seed: u8, input_sats: u64, change_sats: u64) -> Psbt {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_byte_array([seed; 32]), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(change_sats),
+                script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: Amount::from_sat(input_sats),
+            script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+        });
+        psbt
+    }
+
+    #[test]
+    fn test_combine_fill_layout() {
+        let maker = funding_psbt(1, 10_000, 3_900);
+        let taker = funding_psbt(2, 8_000, 3_900);
+        let psbt = combine_fill(
+            &maker,
+            &taker,
+            6_000,
+            4_000,
+            ScriptBuf::new_op_return([0u8; 4]),
+            ScriptBuf::from_bytes(vec![0x52]),
+        )
+        .unwrap();
+
+        let tx = &psbt.unsigned_tx;
+        assert_eq!(tx.input.len(), 2);
+        assert_eq!(tx.output.len(), 4);
+        assert!(tx.output[0].script_pubkey.is_op_return());
+        assert_eq!(tx.output[1].value.to_sat(), 10_000);
+        assert!(psbt.inputs.iter().all(|i| i.witness_utxo.is_some()));
+
+        // Roundtrips through base64
+        let decoded = decode_psbt(&encode_psbt(&psbt)).unwrap();
+        assert_eq!(decoded.unsigned_tx, psbt.unsigned_tx);
+    }
+
+    #[test]
+    fn test_combine_fill_rejects_underfunded_side() {
+        let maker = funding_psbt(1, 10_000, 5_000);
+        let taker = funding_psbt(2, 8_000, 3_900);
+        let err = combine_fill(
+            &maker,
+            &taker,
+            6_000,
+            4_000,
+            ScriptBuf::new(),
+            ScriptBuf::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Maker"));
+    }
+
+    #[test]
+    fn test_finalize_requires_signatures() {
+        let maker = funding_psbt(1, 10_000, 3_900);
+        let taker = funding_psbt(2, 8_000, 3_900);
+        let psbt = combine_fill(
+            &maker,
+            &taker,
+            6_000,
+            4_000,
+            ScriptBuf::new(),
+            ScriptBuf::new(),
+        )
+        .unwrap();
+        assert!(finalize_fill(&psbt, vec![psbt.clone()]).is_err());
+        assert!(finalize_fill(&psbt, vec![maker]).is_err());
+    }
+}
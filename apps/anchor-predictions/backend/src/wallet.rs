@@ -0,0 +1,103 @@
+//! Wallet service client for order book transactions
+
+use anchor_api_error::ApiError;
+use anyhow::{anyhow, Result};
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::{Address, Transaction};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Transaction created and broadcast by the wallet service
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletTx {
+    pub txid: String,
+    pub vout: u32,
+    pub hex: String,
+}
+
+impl WalletTx {
+    /// Find the output paying `address` (the wallet may place change anywhere)
+    pub fn output_to(&self, address: &str) -> Option<u32> {
+        let script = Address::from_str(address)
+            .ok()?
+            .assume_checked()
+            .script_pubkey();
+        let tx: Transaction = deserialize(&hex::decode(&self.hex).ok()?).ok()?;
+        tx.output
+            .iter()
+            .position(|output| output.script_pubkey == script)
+            .map(|vout| vout as u32)
+    }
+}
+
+/// Client for the anchor-wallet service
+#[derive(Clone)]
+pub struct WalletClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl WalletClient {
+    pub fn from_env() -> Self {
+        Self {
+            base_url: std::env::var("WALLET_SERVICE_URL")
+                .unwrap_or_else(|_| "http://core-wallet:8001".to_string()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Get a fresh wallet address
+    pub async fn new_address(&self) -> Result<String> {
+        let res: serde_json::Value = self
+            .client
+            .get(format!("{}/wallet/address", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        res["address"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Wallet returned no address"))
+    }
+
+    /// Create and broadcast an ANCHOR message transaction
+    pub async fn create_message(&self, request: serde_json::Value) -> Result<WalletTx> {
+        let res = self
+            .client
+            .post(format!("{}/wallet/create-message", self.base_url))
+            .json(&request)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let status = res.status().as_u16();
+            let error_text = res.text().await.unwrap_or_default();
+            return Err(ApiError::from_upstream(status, &error_text).into());
+        }
+
+        Ok(res.json().await?)
+    }
+
+    /// Broadcast a fully signed transaction, returning its txid
+    pub async fn broadcast(&self, tx_hex: &str) -> Result<String> {
+        let res = self
+            .client
+            .post(format!("{}/wallet/broadcast", self.base_url))
+            .json(&serde_json::json!({ "hex": tx_hex }))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let error_text = res.text().await.unwrap_or_default();
+            return Err(anyhow!("Broadcast failed: {}", error_text));
+        }
+
+        let body: serde_json::Value = res.json().await?;
+        body["txid"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Wallet returned no txid"))
+    }
+}
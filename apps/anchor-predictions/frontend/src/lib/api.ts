@@ -2,6 +2,24 @@ const API_BASE = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3801';
 
 // ==================== Types ====================
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 export interface Market {
   id: number;
   market_id: string;
@@ -93,8 +111,7 @@ async function postApi<T>(endpoint: string, data: unknown): Promise<T> {
     body: JSON.stringify(data),
   });
   if (!res.ok) {
-    const errorText = await res.text();
-    throw new Error(errorText || `API error: ${res.status}`);
+    throw await apiError(res, `API error: ${res.status}`);
   }
   return res.json();
 }
@@ -0,0 +1,23 @@
+-- Proof witnesses
+-- Third parties co-attest an existing proof with a signed WITNESS message
+-- anchored to the original stamp. Each witness key counts once per proof.
+
+CREATE TABLE IF NOT EXISTS proof_witnesses (
+    id SERIAL PRIMARY KEY,
+    proof_id INTEGER NOT NULL REFERENCES proofs(id) ON DELETE CASCADE,
+    witness_pubkey BYTEA NOT NULL,
+    signature BYTEA NOT NULL,
+    txid BYTEA NOT NULL,
+    vout INTEGER NOT NULL DEFAULT 0,
+    block_hash BYTEA,
+    block_height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT proof_witnesses_txid_vout_unique UNIQUE (txid, vout),
+    CONSTRAINT proof_witnesses_key_unique UNIQUE (proof_id, witness_pubkey)
+);
+
+-- Aggregate kept on the proof so list queries need no join
+ALTER TABLE proofs ADD COLUMN IF NOT EXISTS witness_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_proof_witnesses_proof ON proof_witnesses(proof_id);
+CREATE INDEX IF NOT EXISTS idx_proof_witnesses_pubkey ON proof_witnesses(witness_pubkey);
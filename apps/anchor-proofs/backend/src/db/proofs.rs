@@ -3,7 +3,7 @@
 use anyhow::Result;
 
 use super::Database;
-use crate::models::{Proof, ProofListItem, ProofRow, ProofStats};
+use crate::models::{Proof, ProofListItem, ProofRow, ProofStats, ProofWitnessInfo, WitnessRow};
 
 impl Database {
     /// Check if a transaction output is already indexed
@@ -138,6 +138,64 @@ impl Database {
         Ok(())
     }
 
+    /// Record a witness of a proof and bump its witness count
+    ///
+    /// Returns false when this key already witnessed the proof or the output
+    /// was indexed before.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_witness(
+        &self,
+        proof_id: i32,
+        witness_pubkey: &[u8],
+        signature: &[u8],
+        txid: &[u8],
+        vout: i32,
+        block_hash: Option<&[u8]>,
+        block_height: Option<i32>,
+    ) -> Result<bool> {
+        let row: Option<(i32,)> = sqlx::query_as(
+            r#"
+            WITH inserted AS (
+                INSERT INTO proof_witnesses (proof_id, witness_pubkey, signature, txid, vout, block_hash, block_height)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT DO NOTHING
+                RETURNING proof_id
+            )
+            UPDATE proofs SET witness_count = witness_count + 1
+            WHERE id IN (SELECT proof_id FROM inserted)
+            RETURNING id
+            "#,
+        )
+        .bind(proof_id)
+        .bind(witness_pubkey)
+        .bind(signature)
+        .bind(txid)
+        .bind(vout)
+        .bind(block_hash)
+        .bind(block_height)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Get the witnesses of a proof, oldest first
+    pub async fn get_proof_witnesses(&self, proof_id: i32) -> Result<Vec<ProofWitnessInfo>> {
+        let rows = sqlx::query_as::<_, WitnessRow>(
+            r#"
+            SELECT witness_pubkey, txid, vout, block_height, created_at
+            FROM proof_witnesses
+            WHERE proof_id = $1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(proof_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(WitnessRow::to_witness).collect())
+    }
+
     /// Get proof by hash (returns API type)
     pub async fn get_proof_by_hash(&self, hash: &[u8], algo: Option<i16>) -> Result<Option<Proof>> {
         let row = if let Some(a) = algo {
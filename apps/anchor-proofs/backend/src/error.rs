@@ -1,11 +1,7 @@
 //! Centralized error handling for Anchor Proofs backend
 
-use axum::{
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    Json,
-};
-use serde_json::json;
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::response::{IntoResponse, Response};
 use thiserror::Error;
 
 /// Application-level errors
@@ -38,6 +34,10 @@ pub enum AppError {
     /// Spec validation errors
     #[error("Spec error: {0}")]
     Spec(String),
+
+    /// Error with a specific code, including codes relayed from the wallet
+    #[error(transparent)]
+    Api(#[from] ApiError),
 }
 
 impl AppError {
@@ -51,6 +51,11 @@ impl AppError {
         Self::Conflict(msg.into())
     }
 
+    /// Create a HASH_ALREADY_REGISTERED error
+    pub fn hash_registered(msg: impl Into<String>) -> Self {
+        Self::Api(ApiError::new(ErrorCode::HashAlreadyRegistered, msg))
+    }
+
     /// Create a bad request error
     pub fn bad_request(msg: impl Into<String>) -> Self {
         Self::BadRequest(msg.into())
@@ -64,37 +69,30 @@ impl AppError {
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
+        let error = match self {
             AppError::Database(e) => {
                 tracing::error!("Database error: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Database error".to_string(),
-                )
+                ApiError::internal("Database error")
             }
             AppError::Wallet(e) => {
                 tracing::error!("Wallet service error: {}", e);
-                (
-                    StatusCode::BAD_GATEWAY,
+                ApiError::new(
+                    ErrorCode::UpstreamError,
                     format!("Wallet service error: {}", e),
                 )
             }
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
-            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::NotFound(msg) => ApiError::not_found(msg),
+            AppError::Conflict(msg) => ApiError::conflict(msg),
+            AppError::BadRequest(msg) => ApiError::bad_request(msg),
             AppError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, msg.clone())
+                ApiError::internal(msg)
             }
-            AppError::Spec(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::Spec(msg) => ApiError::bad_request(msg),
+            AppError::Api(err) => err,
         };
 
-        let body = Json(json!({
-            "error": message,
-            "status": status.as_u16(),
-        }));
-
-        (status, body).into_response()
+        error.into_response()
     }
 }
 
@@ -3,7 +3,7 @@
 //! This module is organized by functionality:
 //! - `system` - Health check and statistics
 //! - `proofs` - Proof CRUD operations
-//! - `stamp` - Create and revoke proofs
+//! - `stamp` - Create, revoke and witness proofs
 
 mod proofs;
 mod stamp;
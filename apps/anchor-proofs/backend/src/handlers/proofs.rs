@@ -9,8 +9,8 @@ use std::sync::Arc;
 use crate::error::{AppError, Result};
 use crate::handlers::AppState;
 use crate::models::{
-    GetProofsByAddressResponse, HashAlgorithm, ListParams, PaginatedResponse, Proof, ProofListItem,
-    ValidateRequest, ValidationResult,
+    GetProofsByAddressResponse, GetProofsByAddressesRequest, HashAlgorithm, ListParams,
+    PaginatedResponse, Proof, ProofListItem, ProofWitnessInfo, ValidateRequest, ValidationResult,
 };
 
 /// List all proofs with pagination
@@ -126,6 +126,42 @@ pub async fn get_proof_by_id(
     }
 }
 
+/// Get the third-party witnesses of a proof
+#[utoipa::path(
+    get,
+    path = "/api/proof/id/{id}/witnesses",
+    params(
+        ("id" = i32, Path, description = "Proof ID")
+    ),
+    responses(
+        (status = 200, description = "Witnesses, oldest first", body = Vec<ProofWitnessInfo>),
+        (status = 404, description = "Proof not found")
+    ),
+    tag = "Proofs"
+)]
+pub async fn get_proof_witnesses(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+) -> Result<Json<Vec<ProofWitnessInfo>>> {
+    if state
+        .db
+        .get_proof_by_id(id)
+        .await
+        .map_err(AppError::from)?
+        .is_none()
+    {
+        return Err(AppError::not_found("Proof not found"));
+    }
+
+    let witnesses = state
+        .db
+        .get_proof_witnesses(id)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(Json(witnesses))
+}
+
 /// Validate a file hash
 #[utoipa::path(
     post,
@@ -181,12 +217,40 @@ pub async fn get_my_proofs(
     // Fetch all addresses from the wallet
     let addresses = state.wallet.get_wallet_addresses().await?;
 
+    proofs_by_addresses(&state, &addresses, params.page, per_page).await
+}
+
+/// Get proofs stamped by a set of addresses
+#[utoipa::path(
+    post,
+    path = "/api/proofs/by-addresses",
+    tag = "Proofs",
+    request_body = GetProofsByAddressesRequest,
+    responses(
+        (status = 200, description = "Proofs by addresses", body = GetProofsByAddressResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_proofs_by_addresses(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetProofsByAddressesRequest>,
+) -> Result<Json<GetProofsByAddressResponse>> {
+    let per_page = request.per_page.clamp(1, 500);
+    proofs_by_addresses(&state, &request.addresses, 1, per_page).await
+}
+
+async fn proofs_by_addresses(
+    state: &AppState,
+    addresses: &[String],
+    page: i32,
+    per_page: i32,
+) -> Result<Json<GetProofsByAddressResponse>> {
     if addresses.is_empty() {
         return Ok(Json(GetProofsByAddressResponse {
             proofs: vec![],
             total_proofs: 0,
             unique_transactions: 0,
-            page: params.page,
+            page,
             per_page,
         }));
     }
@@ -194,14 +258,14 @@ pub async fn get_my_proofs(
     // Get proofs for all addresses
     let proofs = state
         .db
-        .get_proofs_by_addresses(&addresses, per_page)
+        .get_proofs_by_addresses(addresses, per_page)
         .await
         .map_err(AppError::from)?;
 
     // Get stats
     let (total_proofs, unique_transactions) = state
         .db
-        .get_proofs_stats_by_addresses(&addresses)
+        .get_proofs_stats_by_addresses(addresses)
         .await
         .map_err(AppError::from)?;
 
@@ -209,7 +273,7 @@ pub async fn get_my_proofs(
         proofs,
         total_proofs,
         unique_transactions,
-        page: params.page,
+        page,
         per_page,
     }))
 }
@@ -1,4 +1,4 @@
-//! Stamp and revoke handlers
+//! Stamp, revoke and witness handlers
 
 use axum::{extract::State, Json};
 use std::sync::Arc;
@@ -10,6 +10,7 @@ use crate::error::{AppError, Result};
 use crate::handlers::AppState;
 use crate::models::{
     BatchStampRequest, CreateTxResponse, ProofEntry, ProofMetadata, RevokeRequest, StampRequest,
+    WitnessRequest,
 };
 use crate::services::AnchorRef;
 
@@ -41,7 +42,7 @@ pub async fn stamp(
         .await
         .map_err(AppError::from)?
     {
-        return Err(AppError::conflict("Hash already registered"));
+        return Err(AppError::hash_registered("Hash already registered"));
     }
 
     // Create spec using anchor-specs
@@ -166,3 +167,59 @@ pub async fn revoke(
 
     Ok(Json(response))
 }
+
+/// Co-attest an existing proof as a third-party witness
+///
+/// The witness signs the attestation itself; this service only anchors it
+/// to the original stamp and broadcasts it.
+#[utoipa::path(
+    post,
+    path = "/api/witness",
+    request_body = WitnessRequest,
+    responses(
+        (status = 200, description = "Transaction created", body = CreateTxResponse),
+        (status = 400, description = "Invalid request or signature"),
+        (status = 404, description = "Proof not found"),
+        (status = 409, description = "Proof is revoked")
+    ),
+    tag = "Witness"
+)]
+pub async fn witness(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WitnessRequest>,
+) -> Result<Json<CreateTxResponse>> {
+    let spec = req.to_spec().map_err(AppError::bad_request)?;
+    let entry = &spec.entries[0];
+
+    let proof = state
+        .db
+        .get_proof_by_hash(&entry.hash, Some(entry.algorithm as i16))
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::not_found("Proof not found"))?;
+
+    if proof.is_revoked {
+        return Err(AppError::conflict("Proof is revoked"));
+    }
+
+    let txid_prefix =
+        hex::decode(&proof.txid_prefix).map_err(|_| AppError::internal("Invalid txid prefix"))?;
+
+    let anchor = AnchorRef {
+        txid_prefix,
+        vout: proof.vout as u8,
+    };
+
+    let carrier = req.carrier.unwrap_or(0);
+    let response = state
+        .wallet
+        .create_proof_with_anchor(&spec, &anchor, carrier)
+        .await?;
+
+    info!(
+        "Created witness transaction for proof {}: {}",
+        proof.id, response.txid
+    );
+
+    Ok(Json(response))
+}
@@ -378,6 +378,59 @@ impl Indexer {
                         batch_index += 1;
                     }
                 }
+                ProofOperation::Witness => {
+                    let (Some(anchor), Some(entry), Some(witness)) = (
+                        message.anchors.first(),
+                        payload.entries.first(),
+                        payload.witness.as_ref(),
+                    ) else {
+                        debug!("Witness rejected: no anchor to original proof");
+                        continue;
+                    };
+                    if let Err(e) = payload.verify_witness() {
+                        debug!("Witness rejected in tx {}: {}", txid, e);
+                        continue;
+                    }
+
+                    let Some(proof) = self
+                        .db
+                        .find_proof_by_hash(&entry.hash, entry.algorithm as i16)
+                        .await?
+                    else {
+                        debug!("Witness rejected: no proof for {}", entry.hash_hex());
+                        continue;
+                    };
+                    if !proof.txid.starts_with(&anchor.txid_prefix) {
+                        debug!("Witness rejected: anchor doesn't match original txid");
+                        continue;
+                    }
+                    if proof.is_revoked {
+                        debug!("Witness rejected: proof {} is revoked", proof.id);
+                        continue;
+                    }
+
+                    if self
+                        .db
+                        .add_witness(
+                            proof.id,
+                            &witness.pubkey,
+                            &witness.signature,
+                            &txid_bytes,
+                            vout as i32,
+                            block_hash,
+                            block_height,
+                        )
+                        .await?
+                    {
+                        info!(
+                            "Witnessed proof: {} by {} in tx {}",
+                            hex::encode(&entry.hash[..8]),
+                            hex::encode(witness.pubkey),
+                            txid
+                        );
+                        proof_count += 1;
+                    }
+                }
             }
         }
 
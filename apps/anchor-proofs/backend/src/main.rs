@@ -9,14 +9,14 @@ mod indexer;
 mod models;
 mod services;
 
+use anchor_api_security::SecurityConfig;
 use anyhow::Result;
 use axum::{
-    http::{header, Method},
+    middleware,
     routing::{get, post},
     Router,
 };
 use std::net::SocketAddr;
-use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 use utoipa::OpenApi;
@@ -36,25 +36,31 @@ use crate::indexer::Indexer;
         handlers::list_proofs,
         handlers::get_proof,
         handlers::get_proof_by_id,
+        handlers::get_proof_witnesses,
         handlers::get_my_proofs,
+        handlers::get_proofs_by_addresses,
         handlers::validate_hash,
         handlers::stamp,
         handlers::stamp_batch,
         handlers::revoke,
+        handlers::witness,
     ),
     components(schemas(
         models::HealthResponse,
         models::ProofStats,
         models::Proof,
         models::ProofListItem,
+        models::ProofWitnessInfo,
         models::PaginatedResponse<models::ProofListItem>,
         models::ValidationResult,
         models::StampRequest,
         models::BatchStampRequest,
         models::RevokeRequest,
+        models::WitnessRequest,
         models::ValidateRequest,
         models::CreateTxResponse,
         models::GetProofsByAddressResponse,
+        models::GetProofsByAddressesRequest,
     )),
     tags(
         (name = "Health", description = "Health check endpoints"),
@@ -63,12 +69,17 @@ use crate::indexer::Indexer;
         (name = "Validation", description = "File validation"),
         (name = "Stamp", description = "Create proofs"),
         (name = "Revoke", description = "Revoke proofs"),
+        (name = "Witness", description = "Third-party witness attestations"),
     )
 )]
 struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
@@ -110,10 +121,6 @@ async fn main() -> Result<()> {
     let state = AppState::new(db, config.wallet_url.clone());
 
     // Configure CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
     // Build router
     let app = Router::new()
@@ -124,8 +131,16 @@ async fn main() -> Result<()> {
         // Proofs
         .route("/api/proofs", get(handlers::list_proofs))
         .route("/api/proofs/my", get(handlers::get_my_proofs))
+        .route(
+            "/api/proofs/by-addresses",
+            post(handlers::get_proofs_by_addresses),
+        )
         .route("/api/proof/{hash}", get(handlers::get_proof))
         .route("/api/proof/id/{id}", get(handlers::get_proof_by_id))
+        .route(
+            "/api/proof/id/{id}/witnesses",
+            get(handlers::get_proof_witnesses),
+        )
         // Validation
         .route("/api/validate", post(handlers::validate_hash))
         // Stamp
@@ -133,11 +148,14 @@ async fn main() -> Result<()> {
         .route("/api/stamp/batch", post(handlers::stamp_batch))
         // Revoke
         .route("/api/revoke", post(handlers::revoke))
+        // Witness
+        .route("/api/witness", post(handlers::witness))
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         // State and middleware
         .with_state(state)
-        .layer(cors);
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer());
 
     // Start server
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
@@ -3,7 +3,12 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use super::{HashAlgorithm, ProofEntry, ProofMetadata};
+use anchor_specs::KindSpec;
+
+use super::{
+    HashAlgorithm, ProofEntry, ProofMetadata, ProofOperation, ProofPayload as ProofSpec,
+    ProofWitness,
+};
 
 // ============================================================================
 // Response Types
@@ -26,6 +31,8 @@ pub struct Proof {
     pub block_height: Option<i32>,
     pub is_revoked: bool,
     pub revoked_txid: Option<String>,
+    /// Number of distinct third-party witnesses
+    pub witness_count: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -43,6 +50,18 @@ pub struct ProofListItem {
     pub txid_prefix: String,
     pub block_height: Option<i32>,
     pub is_revoked: bool,
+    pub witness_count: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A third-party witness of a proof
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProofWitnessInfo {
+    /// Witness key (x-only, hex)
+    pub witness_pubkey: String,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -120,6 +139,16 @@ pub struct ListParams {
     pub include_revoked: bool,
 }
 
+/// Request for proofs stamped by a set of addresses
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct GetProofsByAddressesRequest {
+    /// List of Bitcoin addresses
+    pub addresses: Vec<String>,
+    /// Number of proofs to return (default: 50, max: 500)
+    #[serde(default = "default_per_page")]
+    pub per_page: i32,
+}
+
 fn default_page() -> i32 {
     1
 }
@@ -199,6 +228,52 @@ impl RevokeRequest {
     }
 }
 
+/// Witness a proof request
+///
+/// The witness signs client-side, so the service never holds witness keys.
+/// The signature is BIP-340 Schnorr over
+/// `SHA256("ANCHOR proof witness" || algorithm || file_hash || witness_pubkey)`.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct WitnessRequest {
+    pub hash_algo: String,
+    pub file_hash: String,
+    /// Witness key (x-only, hex)
+    pub witness_pubkey: String,
+    /// Schnorr signature (hex)
+    pub signature: String,
+    #[serde(default)]
+    pub carrier: Option<u8>,
+}
+
+impl WitnessRequest {
+    /// Build the witness attestation, checking the signature
+    pub fn to_spec(&self) -> std::result::Result<ProofSpec, String> {
+        let algorithm = match self.hash_algo.to_lowercase().as_str() {
+            "sha256" | "sha-256" => HashAlgorithm::Sha256,
+            "sha512" | "sha-512" => HashAlgorithm::Sha512,
+            _ => return Err("Invalid hash algorithm".to_string()),
+        };
+        let hash = hex::decode(&self.file_hash).map_err(|_| "Invalid hash format")?;
+        let pubkey: [u8; 32] = hex::decode(&self.witness_pubkey)
+            .ok()
+            .and_then(|k| k.try_into().ok())
+            .ok_or("Witness key must be 32 bytes of hex")?;
+        let signature = hex::decode(&self.signature).map_err(|_| "Invalid signature format")?;
+
+        let spec = ProofSpec {
+            operation: ProofOperation::Witness,
+            entries: vec![ProofEntry {
+                algorithm,
+                hash,
+                metadata: ProofMetadata::default(),
+            }],
+            witness: Some(ProofWitness { pubkey, signature }),
+        };
+        spec.validate().map_err(|e| e.to_string())?;
+        Ok(spec)
+    }
+}
+
 /// Validate request
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct ValidateRequest {
@@ -224,8 +299,6 @@ impl ValidateRequest {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anchor_specs::proof::ProofSpec;
-    use anchor_specs::KindSpec;
 
     #[test]
     fn test_proof_payload_roundtrip() {
@@ -279,4 +352,25 @@ mod tests {
         assert_eq!(entry.algorithm, HashAlgorithm::Sha256);
         assert_eq!(entry.metadata.filename, Some("test.txt".to_string()));
     }
+
+    #[test]
+    fn test_witness_request_to_spec() {
+        use bitcoin::secp256k1::{Keypair, Secp256k1};
+
+        let keypair = Keypair::from_seckey_slice(&Secp256k1::new(), &[3u8; 32]).unwrap();
+        let signed = ProofSpec::witness(&keypair, HashAlgorithm::Sha256, vec![5u8; 32]).unwrap();
+        let witness = signed.witness.unwrap();
+        let mut req = WitnessRequest {
+            hash_algo: "sha256".to_string(),
+            file_hash: hex::encode([5u8; 32]),
+            witness_pubkey: hex::encode(witness.pubkey),
+            signature: hex::encode(&witness.signature),
+            carrier: None,
+        };
+        assert!(req.to_spec().is_ok());
+
+        // A signature over another hash is rejected
+        req.file_hash = hex::encode([6u8; 32]);
+        assert!(req.to_spec().is_err());
+    }
 }
@@ -1,6 +1,6 @@
 //! Database row types for AnchorProofs
 
-use super::{HashAlgorithm, Proof, ProofListItem};
+use super::{HashAlgorithm, Proof, ProofListItem, ProofWitnessInfo};
 
 /// Proof row structure from database
 #[derive(sqlx::FromRow)]
@@ -17,6 +17,7 @@ pub struct ProofRow {
     pub block_height: Option<i32>,
     pub is_revoked: bool,
     pub revoked_txid: Option<Vec<u8>>,
+    pub witness_count: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -42,6 +43,7 @@ impl ProofRow {
             block_height: self.block_height,
             is_revoked: self.is_revoked,
             revoked_txid: self.revoked_txid.as_ref().map(hex::encode),
+            witness_count: self.witness_count,
             created_at: self.created_at,
         }
     }
@@ -64,6 +66,30 @@ impl ProofRow {
             txid_prefix,
             block_height: self.block_height,
             is_revoked: self.is_revoked,
+            witness_count: self.witness_count,
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// Witness row structure from database
+#[derive(sqlx::FromRow)]
+pub struct WitnessRow {
+    pub witness_pubkey: Vec<u8>,
+    pub txid: Vec<u8>,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WitnessRow {
+    /// Convert database row to API type
+    pub fn to_witness(&self) -> ProofWitnessInfo {
+        ProofWitnessInfo {
+            witness_pubkey: hex::encode(&self.witness_pubkey),
+            txid: hex::encode(&self.txid),
+            vout: self.vout,
+            block_height: self.block_height,
             created_at: self.created_at,
         }
     }
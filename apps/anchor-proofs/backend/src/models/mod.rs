@@ -7,7 +7,7 @@
 //!
 //! The core Proof protocol types are defined in `anchor-specs::proof`:
 //! - `ProofSpec` - Full proof specification (aliased as ProofPayload for compatibility)
-//! - `ProofOperation` - Stamp, Revoke, Batch
+//! - `ProofOperation` - Stamp, Revoke, Batch, Witness
 //! - `ProofEntry` - Individual proof with hash and metadata
 //! - `ProofMetadata` - File metadata (name, MIME type, size, description)
 //! - `HashAlgorithm` - SHA-256, SHA-512
@@ -22,6 +22,7 @@ mod db;
 // Re-export Proof types from anchor-specs
 pub use anchor_specs::proof::{
     HashAlgorithm, ProofEntry, ProofMetadata, ProofOperation, ProofSpec as ProofPayload,
+    ProofWitness,
 };
 
 // Re-export API types
@@ -3,6 +3,7 @@
 //! Handles communication with the anchor-wallet service using anchor-specs
 //! for proper payload encoding.
 
+use anchor_api_error::ApiError;
 use anchor_specs::proof::ProofSpec;
 use anchor_specs::KindSpec;
 use serde::Deserialize;
@@ -63,12 +64,13 @@ impl WalletClient {
             .await?;
 
         if !res.status().is_success() {
+            let status = res.status().as_u16();
             let error_text = res
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             tracing::error!("Wallet error: {}", error_text);
-            return Err(AppError::internal(format!("Wallet error: {}", error_text)));
+            return Err(ApiError::from_upstream(status, &error_text).into());
         }
 
         let response: CreateTxResponse = res.json().await?;
@@ -109,12 +111,13 @@ impl WalletClient {
             .await?;
 
         if !res.status().is_success() {
+            let status = res.status().as_u16();
             let error_text = res
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             tracing::error!("Wallet error: {}", error_text);
-            return Err(AppError::internal(format!("Wallet error: {}", error_text)));
+            return Err(ApiError::from_upstream(status, &error_text).into());
         }
 
         let response: CreateTxResponse = res.json().await?;
@@ -64,6 +64,7 @@ export interface Proof {
   block_height: number | null;
   is_revoked: boolean;
   revoked_txid: string | null;
+  witness_count: number;
   created_at: string;
 }
 
@@ -79,6 +80,7 @@ export interface ProofListItem {
   txid_prefix: string;
   block_height: number | null;
   is_revoked: boolean;
+  witness_count: number;
   created_at: string;
 }
 
@@ -0,0 +1,17 @@
+-- Migration: Thread Subscriptions
+-- Per-API-key thread subscriptions with a read marker, so clients can show
+-- the replies they haven't seen yet
+
+CREATE TABLE IF NOT EXISTS thread_subscriptions (
+    id SERIAL PRIMARY KEY,
+    -- SHA-256 of the client's X-API-Key; the key itself is never stored
+    api_key_hash BYTEA NOT NULL,
+    root_message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    -- Highest message id in the thread the client has read
+    last_read_message_id INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    read_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(api_key_hash, root_message_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thread_subscriptions_key ON thread_subscriptions(api_key_hash);
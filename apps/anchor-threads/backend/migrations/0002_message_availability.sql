@@ -0,0 +1,32 @@
+-- Migration: Message Availability
+-- Whether the payloads of messages on prunable carriers can still be
+-- fetched from the node and from peer nodes, with a summary of every
+-- verification run so durability can be followed over time
+
+CREATE TABLE IF NOT EXISTS message_availability (
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    -- 'node', or a peer's RPC URL without credentials
+    source TEXT NOT NULL,
+    available BOOLEAN NOT NULL,
+    -- Why the payload could not be retrieved at the last check
+    error TEXT,
+    checks INTEGER NOT NULL DEFAULT 1,
+    first_checked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    checked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_available_at TIMESTAMP WITH TIME ZONE,
+    -- Start of the current run of failed checks
+    unavailable_since TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (message_id, source)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_availability_checked ON message_availability(source, checked_at);
+
+CREATE TABLE IF NOT EXISTS availability_runs (
+    id SERIAL PRIMARY KEY,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    finished_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Checks made across all sources, and how many found the payload
+    checked INTEGER NOT NULL,
+    available INTEGER NOT NULL,
+    unavailable INTEGER NOT NULL
+);
@@ -0,0 +1,181 @@
+//! Author profiles
+//!
+//! `GET /authors/:address` combines what the explorer knows about an
+//! address: its message count, first and last activity and recent messages
+//! from the indexer tables, plus the identity it links to through Anchor
+//! Domains. Reverse resolution finds the domains whose latest DNS
+//! transaction the address created (`/my-domains`), and the Nostr key comes
+//! from the Selfie Records published under the newest of them.
+//!
+//! Domain lookups go over HTTP to the domains backend, so they're cached per
+//! address for `AUTHOR_CACHE_TTL_SECS`, misses included. A failed lookup is
+//! not cached and the profile is served without the domain.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anchor_specs::dns::DnsSpec;
+use anchor_specs::KindSpec;
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::db::Database;
+
+/// Cached entries beyond which expired ones are pruned
+const MAX_CACHE_ENTRIES: usize = 10_000;
+
+/// DNS transactions per address sent for reverse resolution
+const MAX_OWNER_TXIDS: i64 = 100;
+
+/// Identity an address links to through Anchor Domains
+#[derive(Debug, Clone, Default)]
+pub struct LinkedIdentity {
+    /// Owned domains, newest first
+    pub domains: Vec<String>,
+    /// Nostr key published under the newest domain
+    pub nostr_pubkey: Option<String>,
+}
+
+struct CacheEntry {
+    identity: LinkedIdentity,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct MyDomainsResponse {
+    data: Vec<OwnedDomain>,
+}
+
+#[derive(Deserialize)]
+struct OwnedDomain {
+    name: String,
+    block_height: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct DomainIdentitiesResponse {
+    identities: Vec<PublishedIdentity>,
+}
+
+#[derive(Deserialize)]
+struct PublishedIdentity {
+    identity_type: String,
+    subdomain: Option<String>,
+    public_key: String,
+}
+
+/// Caching reverse resolver backed by the domains backend
+pub struct AuthorResolver {
+    db: Database,
+    http: reqwest::Client,
+    /// Domains backend base URL; domains are skipped when unset
+    domains_url: Option<String>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CacheEntry>>,
+}
+
+impl AuthorResolver {
+    pub fn new(db: Database, domains_url: Option<String>, ttl: Duration) -> Self {
+        Self {
+            db,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            domains_url,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Domains and Nostr key linked to an address
+    pub async fn linked_identity(&self, address: &str) -> LinkedIdentity {
+        let Some(domains_url) = &self.domains_url else {
+            return LinkedIdentity::default();
+        };
+
+        let now = Instant::now();
+        if let Some(entry) = self.cache.read().await.get(address) {
+            if entry.expires_at > now {
+                return entry.identity.clone();
+            }
+        }
+
+        let identity = match self.lookup(domains_url, address).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                warn!("Failed to resolve domains for {}: {:#}", address, e);
+                return LinkedIdentity::default();
+            }
+        };
+
+        let mut cache = self.cache.write().await;
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.retain(|_, entry| entry.expires_at > now);
+        }
+        cache.insert(
+            address.to_string(),
+            CacheEntry {
+                identity: identity.clone(),
+                expires_at: now + self.ttl,
+            },
+        );
+        identity
+    }
+
+    async fn lookup(&self, domains_url: &str, address: &str) -> Result<LinkedIdentity> {
+        let txids = self
+            .db
+            .get_author_txids(address, DnsSpec::KIND_ID as i16, MAX_OWNER_TXIDS)
+            .await?;
+        if txids.is_empty() {
+            return Ok(LinkedIdentity::default());
+        }
+
+        let base = domains_url.trim_end_matches('/');
+        let mut owned = self
+            .http
+            .get(format!("{}/my-domains", base))
+            .query(&[("owner_txids", txids.join(","))])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<MyDomainsResponse>()
+            .await?
+            .data;
+        owned.sort_by_key(|domain| std::cmp::Reverse(domain.block_height));
+
+        let nostr_pubkey = match owned.first() {
+            Some(domain) => {
+                let identities = self
+                    .http
+                    .get(format!("{}/domains/{}/identities", base, domain.name))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<DomainIdentitiesResponse>()
+                    .await?
+                    .identities;
+                nostr_key(identities)
+            }
+            None => None,
+        };
+
+        Ok(LinkedIdentity {
+            domains: owned.into_iter().map(|domain| domain.name).collect(),
+            nostr_pubkey,
+        })
+    }
+}
+
+/// The domain's own Nostr key, or the first subdomain one
+fn nostr_key(identities: Vec<PublishedIdentity>) -> Option<String> {
+    let mut nostr: Vec<PublishedIdentity> = identities
+        .into_iter()
+        .filter(|identity| identity.identity_type == "nostr")
+        .collect();
+    nostr.sort_by_key(|identity| identity.subdomain.is_some());
+    nostr.into_iter().next().map(|identity| identity.public_key)
+}
@@ -2,6 +2,11 @@
 
 use anyhow::{Context, Result};
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::availability::AvailabilityConfig;
+use crate::response_cache::ResponseCacheConfig;
 
 /// Explorer API configuration
 #[derive(Debug, Clone)]
@@ -10,6 +15,32 @@ pub struct Config {
     pub database_url: String,
     /// HTTP server port
     pub port: u16,
+    /// Bitcoin RPC URL, used to fetch raw transactions for decoding
+    pub bitcoin_rpc_url: String,
+    /// Bitcoin RPC username
+    pub bitcoin_rpc_user: String,
+    /// Bitcoin RPC password
+    pub bitcoin_rpc_password: String,
+    /// Block explorer base URL, used for links in inclusion proofs
+    pub block_explorer_url: String,
+    /// Anchor Domains backend URL, for author domains and Nostr keys
+    pub domains_url: Option<String>,
+    /// Seconds an author's domain lookup is cached
+    pub author_cache_ttl_secs: u64,
+    /// Explorer frontend base URL, for message links in previews
+    pub public_url: String,
+    /// Base URL this API is reachable at, for image and oEmbed links
+    pub api_public_url: String,
+    /// Directory sitemaps and monthly archives are written to; not
+    /// generated when unset
+    pub sitemap_dir: Option<PathBuf>,
+    /// Seconds between sitemap refreshes
+    pub sitemap_interval_secs: u64,
+    /// Hot response cache; disabled when `RESPONSE_CACHE_MAX_MB` is 0
+    pub response_cache: Option<ResponseCacheConfig>,
+    /// Payload availability checks; off unless `AVAILABILITY_INTERVAL_SECS`
+    /// is set
+    pub availability: Option<AvailabilityConfig>,
 }
 
 impl Config {
@@ -21,6 +52,69 @@ impl Config {
                 .unwrap_or_else(|_| "3101".to_string())
                 .parse()
                 .context("Invalid PORT")?,
+            bitcoin_rpc_url: env::var("BITCOIN_RPC_URL")
+                .unwrap_or_else(|_| "http://localhost:18443".to_string()),
+            bitcoin_rpc_user: env::var("BITCOIN_RPC_USER").unwrap_or_else(|_| "anchor".to_string()),
+            bitcoin_rpc_password: env::var("BITCOIN_RPC_PASSWORD")
+                .unwrap_or_else(|_| "anchor".to_string()),
+            block_explorer_url: env::var("BLOCK_EXPLORER_URL")
+                .unwrap_or_else(|_| "http://localhost:4000".to_string()),
+            domains_url: env::var("DOMAINS_URL").ok().filter(|url| !url.is_empty()),
+            author_cache_ttl_secs: env::var("AUTHOR_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            public_url: env::var("PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:3100".to_string()),
+            api_public_url: env::var("API_PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:3101".to_string()),
+            sitemap_dir: env::var("SITEMAP_DIR")
+                .ok()
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+            sitemap_interval_secs: env::var("SITEMAP_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+            response_cache: Some(
+                env::var("RESPONSE_CACHE_MAX_MB")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(64),
+            )
+            .filter(|mb| *mb > 0)
+            .map(|mb| ResponseCacheConfig {
+                ttl: Duration::from_secs(
+                    env::var("RESPONSE_CACHE_TTL_SECS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(60),
+                ),
+                max_bytes: mb * 1024 * 1024,
+            }),
+            availability: env::var("AVAILABILITY_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .map(|secs| AvailabilityConfig {
+                    interval: Duration::from_secs(secs),
+                    recheck: Duration::from_secs(
+                        env::var("AVAILABILITY_RECHECK_SECS")
+                            .ok()
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(86_400),
+                    ),
+                    batch: env::var("AVAILABILITY_BATCH")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(500),
+                    peers: env::var("AVAILABILITY_PEERS")
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(|peer| peer.trim().to_string())
+                        .filter(|peer| !peer.is_empty())
+                        .collect(),
+                }),
         })
     }
 }
@@ -2,12 +2,16 @@
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions};
 use std::time::Duration;
 
+use crate::decode::message_body;
 use crate::models::{
-    carrier_name, AnchorResponse, CarrierStats, ListParams, MessageResponse, StatsResponse,
-    ThreadNodeResponse, ThreadResponse,
+    carrier_name, AnchorFilterResponse, AnchorFiltersResponse, AnchorResponse,
+    ArchiveMonthResponse, AvailabilityCarrierResponse, AvailabilityRunResponse,
+    AvailabilitySourceResponse, CarrierStats, IdentityResponse, IdentityRotationResponse,
+    ListParams, MessageResponse, StatsResponse, SubscriptionResponse, ThreadNodeResponse,
+    ThreadResponse, ThreadUpdatesResponse,
 };
 
 /// Database connection pool wrapper
@@ -22,27 +26,112 @@ struct MessageRow {
     id: i32,
     txid: Vec<u8>,
     vout: i32,
+    payload_index: i16,
     block_height: Option<i32>,
     kind: i16,
+    kind_unknown: bool,
     carrier: i16,
     body: Vec<u8>,
+    lang: Option<String>,
     created_at: DateTime<Utc>,
 }
 
+/// Messages of the thread rooted at message `$1`, found by following
+/// first anchors the same way `get_replies` does
+const THREAD_MESSAGES: &str = r#"
+    WITH RECURSIVE thread(id, txid, vout) AS (
+        SELECT id, txid, vout FROM messages WHERE id = $1
+        UNION
+        SELECT m.id, m.txid, m.vout
+        FROM thread t
+        INNER JOIN anchors a
+            ON a.anchor_index = 0
+           AND a.is_ambiguous = FALSE
+           AND a.txid_prefix = substring(t.txid FROM 1 FOR 8)
+           AND a.vout = t.vout
+        INNER JOIN messages m ON m.id = a.message_id
+    )
+"#;
+
+/// Raw thread subscription row joined with its root message
+#[derive(Debug, sqlx::FromRow)]
+struct SubscriptionRow {
+    root_message_id: i32,
+    txid: Vec<u8>,
+    vout: i32,
+    last_read_message_id: i32,
+    created_at: DateTime<Utc>,
+    read_at: DateTime<Utc>,
+}
+
+impl SubscriptionRow {
+    fn into_response(self) -> SubscriptionResponse {
+        let mut txid = self.txid;
+        txid.reverse();
+        SubscriptionResponse {
+            txid: hex::encode(txid),
+            vout: self.vout,
+            last_read_message_id: self.last_read_message_id,
+            created_at: self.created_at,
+            read_at: self.read_at,
+        }
+    }
+}
+
 /// Raw message row with precomputed reply count
 #[derive(Debug, sqlx::FromRow)]
 struct MessageRowWithReplyCount {
     id: i32,
     txid: Vec<u8>,
     vout: i32,
+    payload_index: i16,
     block_height: Option<i32>,
     kind: i16,
+    kind_unknown: bool,
     carrier: i16,
     body: Vec<u8>,
+    lang: Option<String>,
     created_at: DateTime<Utc>,
     reply_count: i64,
 }
 
+/// Raw identity rotation row joined with its message
+#[derive(Debug, sqlx::FromRow)]
+struct IdentityRotationRow {
+    txid: Vec<u8>,
+    vout: i32,
+    identity_root: Vec<u8>,
+    old_pubkey: Vec<u8>,
+    new_pubkey: Vec<u8>,
+    new_address: Option<String>,
+    block_height: Option<i32>,
+}
+
+/// A root message as listed in the archive
+#[derive(Debug, sqlx::FromRow)]
+pub struct ArchiveRoot {
+    /// Internal byte order
+    pub txid: Vec<u8>,
+    pub vout: i32,
+    pub kind: i16,
+    pub block_height: Option<i32>,
+    pub body: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A confirmed message whose payload availability is due for a check
+#[derive(Debug, sqlx::FromRow)]
+pub struct AvailabilityCandidate {
+    pub id: i32,
+    /// Internal byte order
+    pub txid: Vec<u8>,
+    /// Internal byte order
+    pub block_hash: Vec<u8>,
+    pub carrier: i16,
+    pub kind: i16,
+    pub body: Vec<u8>,
+}
+
 /// Raw anchor row from database
 #[derive(Debug, sqlx::FromRow)]
 struct AnchorRow {
@@ -153,53 +242,49 @@ impl Database {
     }
 
     /// List messages with pagination
-    pub async fn list_messages(&self, params: &ListParams) -> Result<(Vec<MessageResponse>, i64)> {
-        // Get total count
-        let total: (i64,) = if let Some(kind) = params.kind {
-            sqlx::query_as("SELECT COUNT(*) FROM messages WHERE kind = $1")
-                .bind(kind)
-                .fetch_one(&self.pool)
-                .await?
-        } else {
-            sqlx::query_as("SELECT COUNT(*) FROM messages")
-                .fetch_one(&self.pool)
-                .await?
-        };
+    pub async fn list_messages(
+        &self,
+        params: &ListParams,
+        snapshot_id: Option<&str>,
+    ) -> Result<(Vec<MessageResponse>, i64)> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
 
-        // Get messages
-        let rows: Vec<MessageRow> = if let Some(kind) = params.kind {
-            sqlx::query_as(
-                r#"
-                SELECT id, txid, vout, block_height, kind, carrier, body, created_at
-                FROM messages
-                WHERE kind = $1
-                ORDER BY created_at DESC
-                LIMIT $2 OFFSET $3
-                "#,
-            )
-            .bind(kind)
-            .bind(params.per_page)
-            .bind(params.offset())
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            sqlx::query_as(
-                r#"
-                SELECT id, txid, vout, block_height, kind, carrier, body, created_at
-                FROM messages
-                ORDER BY created_at DESC
-                LIMIT $1 OFFSET $2
-                "#,
-            )
-            .bind(params.per_page)
-            .bind(params.offset())
-            .fetch_all(&self.pool)
-            .await?
-        };
+        let total: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM messages
+            WHERE ($1::SMALLINT IS NULL OR kind = $1)
+              AND ($2::BOOLEAN IS NULL OR kind_unknown = $2)
+              AND ($3::TEXT[] IS NULL OR lang = ANY($3))
+            "#,
+        )
+        .bind(params.kind)
+        .bind(params.kind_unknown)
+        .bind(params.langs())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let rows: Vec<MessageRow> = sqlx::query_as(
+            r#"
+            SELECT id, txid, vout, payload_index, block_height, kind, kind_unknown, carrier, body, lang, created_at
+            FROM messages
+            WHERE ($1::SMALLINT IS NULL OR kind = $1)
+              AND ($2::BOOLEAN IS NULL OR kind_unknown = $2)
+              AND ($3::TEXT[] IS NULL OR lang = ANY($3))
+            ORDER BY created_at DESC
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(params.kind)
+        .bind(params.kind_unknown)
+        .bind(params.langs())
+        .bind(params.per_page)
+        .bind(params.offset())
+        .fetch_all(&mut *tx)
+        .await?;
 
         let mut messages = Vec::with_capacity(rows.len());
         for row in rows {
-            let msg = self.row_to_response(row).await?;
+            let msg = Self::message_response(&mut tx, row).await?;
             messages.push(msg);
         }
 
@@ -207,30 +292,43 @@ impl Database {
     }
 
     /// List root messages (threads)
-    pub async fn list_roots(&self, params: &ListParams) -> Result<(Vec<MessageResponse>, i64)> {
+    pub async fn list_roots(
+        &self,
+        params: &ListParams,
+        snapshot_id: Option<&str>,
+    ) -> Result<(Vec<MessageResponse>, i64)> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
+
         let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM messages m WHERE NOT EXISTS (SELECT 1 FROM anchors a WHERE a.message_id = m.id)",
+            r#"
+            SELECT COUNT(*) FROM messages m
+            WHERE NOT EXISTS (SELECT 1 FROM anchors a WHERE a.message_id = m.id)
+              AND ($1::TEXT[] IS NULL OR m.lang = ANY($1))
+            "#,
         )
-        .fetch_one(&self.pool)
+        .bind(params.langs())
+        .fetch_one(&mut *tx)
         .await?;
 
         let rows: Vec<MessageRow> = sqlx::query_as(
             r#"
-            SELECT m.id, m.txid, m.vout, m.block_height, m.kind, m.carrier, m.body, m.created_at
+            SELECT m.id, m.txid, m.vout, m.payload_index, m.block_height, m.kind, m.kind_unknown, m.carrier, m.body, m.lang, m.created_at
             FROM messages m
             WHERE NOT EXISTS (SELECT 1 FROM anchors a WHERE a.message_id = m.id)
+              AND ($1::TEXT[] IS NULL OR m.lang = ANY($1))
             ORDER BY m.created_at DESC
-            LIMIT $1 OFFSET $2
+            LIMIT $2 OFFSET $3
             "#,
         )
+        .bind(params.langs())
         .bind(params.per_page)
         .bind(params.offset())
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
 
         let mut messages = Vec::with_capacity(rows.len());
         for row in rows {
-            let msg = self.row_to_response(row).await?;
+            let msg = Self::message_response(&mut tx, row).await?;
             messages.push(msg);
         }
 
@@ -241,7 +339,10 @@ impl Database {
     pub async fn list_roots_filtered(
         &self,
         params: &crate::models::FilterParams,
+        snapshot_id: Option<&str>,
     ) -> Result<(Vec<MessageResponse>, i64)> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
+
         let mut conditions =
             vec!["NOT EXISTS (SELECT 1 FROM anchors a WHERE a.message_id = m.id)".to_string()];
         let mut bind_index = 1;
@@ -275,6 +376,11 @@ impl Database {
             bind_index += 1;
         }
 
+        if params.kind_unknown.is_some() {
+            conditions.push(format!("m.kind_unknown = ${}", bind_index));
+            bind_index += 1;
+        }
+
         if params.carrier.is_some() {
             conditions.push(format!("m.carrier = ${}", bind_index));
             bind_index += 1;
@@ -308,6 +414,12 @@ impl Database {
             bind_index += 1;
         }
 
+        let langs = params.langs();
+        if langs.is_some() {
+            conditions.push(format!("m.lang = ANY(${})", bind_index));
+            bind_index += 1;
+        }
+
         let where_clause = conditions.join(" AND ");
 
         // Determine sort order
@@ -324,7 +436,7 @@ impl Database {
         // Build main query with subquery for reply_count to allow sorting
         let main_query = format!(
             r#"
-            SELECT m.id, m.txid, m.vout, m.block_height, m.kind, m.carrier, m.body, m.created_at,
+            SELECT m.id, m.txid, m.vout, m.payload_index, m.block_height, m.kind, m.kind_unknown, m.carrier, m.body, m.lang, m.created_at,
                    (SELECT COUNT(*) FROM anchors a2 WHERE a2.txid_prefix = substring(m.txid from 1 for 8) AND a2.vout = m.vout AND a2.anchor_index = 0) as reply_count
             FROM messages m
             WHERE {}
@@ -371,6 +483,11 @@ impl Database {
             main_q = main_q.bind(kind);
         }
 
+        if let Some(kind_unknown) = params.kind_unknown {
+            count_q = count_q.bind(kind_unknown);
+            main_q = main_q.bind(kind_unknown);
+        }
+
         if let Some(carrier) = params.carrier {
             count_q = count_q.bind(carrier);
             main_q = main_q.bind(carrier);
@@ -402,15 +519,20 @@ impl Database {
             main_q = main_q.bind(max_size);
         }
 
+        if let Some(langs) = langs {
+            count_q = count_q.bind(langs.clone());
+            main_q = main_q.bind(langs);
+        }
+
         // Bind pagination
         main_q = main_q.bind(params.per_page).bind(params.offset());
 
-        let total = count_q.fetch_one(&self.pool).await?;
-        let rows = main_q.fetch_all(&self.pool).await?;
+        let total = count_q.fetch_one(&mut *tx).await?;
+        let rows = main_q.fetch_all(&mut *tx).await?;
 
         let mut messages = Vec::with_capacity(rows.len());
         for row in rows {
-            let msg = self.row_with_count_to_response(row).await?;
+            let msg = Self::row_with_count_to_response(&mut tx, row).await?;
             messages.push(msg);
         }
 
@@ -421,9 +543,11 @@ impl Database {
     pub async fn get_message(&self, txid: &[u8], vout: i32) -> Result<Option<MessageResponse>> {
         let row: Option<MessageRow> = sqlx::query_as(
             r#"
-            SELECT id, txid, vout, block_height, kind, carrier, body, created_at
+            SELECT id, txid, vout, payload_index, block_height, kind, kind_unknown, carrier, body, lang, created_at
             FROM messages
             WHERE txid = $1 AND vout = $2
+            ORDER BY payload_index
+            LIMIT 1
             "#,
         )
         .bind(txid)
@@ -437,13 +561,113 @@ impl Database {
         }
     }
 
+    /// Get every message carried by a transaction, in payload order
+    pub async fn get_transaction_messages(&self, txid: &[u8]) -> Result<Vec<MessageResponse>> {
+        let rows: Vec<MessageRow> = sqlx::query_as(
+            r#"
+            SELECT id, txid, vout, payload_index, block_height, kind, kind_unknown, carrier, body, lang, created_at
+            FROM messages
+            WHERE txid = $1
+            ORDER BY payload_index
+            "#,
+        )
+        .bind(txid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut messages = Vec::with_capacity(rows.len());
+        for row in rows {
+            messages.push(self.row_to_response(row).await?);
+        }
+
+        Ok(messages)
+    }
+
+    /// Identity chain containing `pubkey`, if it has ever rotated
+    pub async fn get_identity(&self, pubkey: &[u8]) -> Result<Option<IdentityResponse>> {
+        let rows: Vec<IdentityRotationRow> = sqlx::query_as(
+            r#"
+            SELECT m.txid, m.vout, r.identity_root, r.old_pubkey, r.new_pubkey, r.new_address, r.block_height
+            FROM identity_rotations r
+            JOIN messages m ON m.id = r.message_id
+            WHERE r.identity_root = (
+                SELECT identity_root FROM identity_rotations
+                WHERE old_pubkey = $1 OR new_pubkey = $1
+                LIMIT 1
+            )
+            ORDER BY r.id
+            "#,
+        )
+        .bind(pubkey)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let Some(last) = rows.last() else {
+            return Ok(None);
+        };
+
+        Ok(Some(IdentityResponse {
+            root: hex::encode(&last.identity_root),
+            current_pubkey: hex::encode(&last.new_pubkey),
+            current_address: rows.iter().rev().find_map(|r| r.new_address.clone()),
+            rotations: rows
+                .into_iter()
+                .map(|r| {
+                    let mut txid = r.txid;
+                    txid.reverse();
+                    IdentityRotationResponse {
+                        txid: hex::encode(txid),
+                        vout: r.vout,
+                        old_pubkey: hex::encode(r.old_pubkey),
+                        new_pubkey: hex::encode(r.new_pubkey),
+                        new_address: r.new_address,
+                        block_height: r.block_height,
+                    }
+                })
+                .collect(),
+        }))
+    }
+
+    /// Whether any message from this transaction is indexed
+    pub async fn transaction_indexed(&self, txid: &[u8]) -> Result<bool> {
+        let row: (bool,) = sqlx::query_as("SELECT EXISTS(SELECT 1 FROM messages WHERE txid = $1)")
+            .bind(txid)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.0)
+    }
+
+    /// Indexed transactions whose txid starts with `prefix`, in display format
+    pub async fn find_anchor_candidates(&self, prefix: &[u8]) -> Result<Vec<String>> {
+        let rows: Vec<(Vec<u8>,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT txid
+            FROM messages
+            WHERE substring(txid from 1 for $1) = $2
+            "#,
+        )
+        .bind(prefix.len() as i32)
+        .bind(prefix)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(mut txid,)| {
+                txid.reverse();
+                hex::encode(txid)
+            })
+            .collect())
+    }
+
     /// Get replies to a message
     pub async fn get_replies(&self, txid: &[u8], vout: i32) -> Result<Vec<MessageResponse>> {
         let prefix = &txid[0..8];
 
         let rows: Vec<MessageRow> = sqlx::query_as(
             r#"
-            SELECT m.id, m.txid, m.vout, m.block_height, m.kind, m.carrier, m.body, m.created_at
+            SELECT m.id, m.txid, m.vout, m.payload_index, m.block_height, m.kind, m.kind_unknown, m.carrier, m.body, m.lang, m.created_at
             FROM messages m
             INNER JOIN anchors a ON a.message_id = m.id
             WHERE a.anchor_index = 0
@@ -471,21 +695,24 @@ impl Database {
     pub async fn get_popular_threads(
         &self,
         limit: i32,
+        langs: Option<Vec<String>>,
     ) -> Result<Vec<crate::models::PopularThreadResponse>> {
         use crate::models::PopularThreadResponse;
 
         // Get all root messages (no anchors)
         let rows: Vec<MessageRow> = sqlx::query_as(
             r#"
-            SELECT m.id, m.txid, m.vout, m.block_height, m.kind, m.carrier, m.body, m.created_at
+            SELECT m.id, m.txid, m.vout, m.payload_index, m.block_height, m.kind, m.kind_unknown, m.carrier, m.body, m.lang, m.created_at
             FROM messages m
             WHERE NOT EXISTS (
                 SELECT 1 FROM anchors a WHERE a.message_id = m.id
             )
+              AND ($1::TEXT[] IS NULL OR m.lang = ANY($1))
             ORDER BY m.created_at DESC
             LIMIT 100
             "#,
         )
+        .bind(langs)
         .fetch_all(&self.pool)
         .await?;
 
@@ -508,7 +735,7 @@ impl Database {
         }
 
         // Sort by total thread messages descending
-        popular.sort_by(|a, b| b.total_thread_messages.cmp(&a.total_thread_messages));
+        popular.sort_by_key(|p| std::cmp::Reverse(p.total_thread_messages));
 
         // Take only threads with more than 1 message and limit
         Ok(popular
@@ -566,8 +793,582 @@ impl Database {
         count
     }
 
+    /// Message count and first and last activity of a creator address
+    pub async fn get_author_stats(
+        &self,
+        address: &str,
+    ) -> Result<(i64, Option<DateTime<Utc>>, Option<DateTime<Utc>>)> {
+        let stats = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), MIN(created_at), MAX(created_at)
+            FROM messages
+            WHERE creator_address = $1
+            "#,
+        )
+        .bind(address)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(stats)
+    }
+
+    /// Newest messages created by an address
+    pub async fn get_author_messages(
+        &self,
+        address: &str,
+        limit: i32,
+    ) -> Result<Vec<MessageResponse>> {
+        let rows: Vec<MessageRow> = sqlx::query_as(
+            r#"
+            SELECT id, txid, vout, payload_index, block_height, kind, kind_unknown, carrier, body, lang, created_at
+            FROM messages
+            WHERE creator_address = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(address)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut messages = Vec::with_capacity(rows.len());
+        for row in rows {
+            messages.push(self.row_to_response(row).await?);
+        }
+        Ok(messages)
+    }
+
+    /// Txids (display hex) of an address's newest messages of one kind
+    pub async fn get_author_txids(
+        &self,
+        address: &str,
+        kind: i16,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let txids: Vec<Vec<u8>> = sqlx::query_scalar(
+            r#"
+            SELECT txid
+            FROM messages
+            WHERE creator_address = $1 AND kind = $2
+            GROUP BY txid
+            ORDER BY MAX(id) DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(address)
+        .bind(kind)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(txids
+            .into_iter()
+            .map(|mut txid| {
+                txid.reverse();
+                hex::encode(txid)
+            })
+            .collect())
+    }
+
+    /// Id of the message at `txid:vout`, the root of a thread to subscribe to
+    pub async fn find_message_id(&self, txid: &[u8], vout: i32) -> Result<Option<i32>> {
+        let id: Option<i32> = sqlx::query_scalar(
+            "SELECT id FROM messages WHERE txid = $1 AND vout = $2 ORDER BY payload_index LIMIT 1",
+        )
+        .bind(txid)
+        .bind(vout)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Address that created the message at `txid:vout`
+    pub async fn get_message_creator(&self, txid: &[u8], vout: i32) -> Result<Option<String>> {
+        let address: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT creator_address FROM messages WHERE txid = $1 AND vout = $2 ORDER BY payload_index LIMIT 1",
+        )
+        .bind(txid)
+        .bind(vout)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(address.flatten())
+    }
+
+    /// Kind and raw body of the message at `txid:vout`
+    pub async fn get_message_body(&self, txid: &[u8], vout: i32) -> Result<Option<(i16, Vec<u8>)>> {
+        let row: Option<(i16, Vec<u8>)> = sqlx::query_as(
+            "SELECT kind, body FROM messages WHERE txid = $1 AND vout = $2 ORDER BY payload_index LIMIT 1",
+        )
+        .bind(txid)
+        .bind(vout)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Indexer height, which moves whenever messages are indexed or rolled back
+    pub async fn get_indexed_height(&self) -> Result<i32> {
+        let height: Option<i32> =
+            sqlx::query_scalar("SELECT last_block_height FROM indexer_state WHERE id = 1")
+                .fetch_optional(&self.pool)
+                .await?
+                .flatten();
+        Ok(height.unwrap_or(0))
+    }
+
+    /// ANCHOR activity filters of up to `count` blocks from `from`, with the
+    /// header of the filter before them
+    pub async fn get_anchor_filters(&self, from: i32, count: i32) -> Result<AnchorFiltersResponse> {
+        let tip_height: Option<i32> =
+            sqlx::query_scalar("SELECT MAX(block_height) FROM anchor_filters")
+                .fetch_one(&self.pool)
+                .await?;
+        let previous_header: Option<Vec<u8>> =
+            sqlx::query_scalar("SELECT filter_header FROM anchor_filters WHERE block_height = $1")
+                .bind(from - 1)
+                .fetch_optional(&self.pool)
+                .await?;
+        let rows: Vec<(i32, Vec<u8>, Vec<u8>, Vec<u8>)> = sqlx::query_as(
+            r#"
+            SELECT block_height, block_hash, filter, filter_header
+            FROM anchor_filters
+            WHERE block_height >= $1 AND block_height < $1 + $2
+            ORDER BY block_height
+            "#,
+        )
+        .bind(from)
+        .bind(count)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let display_hex = |mut bytes: Vec<u8>| {
+            bytes.reverse();
+            hex::encode(bytes)
+        };
+        Ok(AnchorFiltersResponse {
+            tip_height,
+            previous_header: previous_header.map(display_hex),
+            filters: rows
+                .into_iter()
+                .map(
+                    |(block_height, block_hash, filter, header)| AnchorFilterResponse {
+                        block_height,
+                        block_hash: display_hex(block_hash),
+                        filter: hex::encode(filter),
+                        header: display_hex(header),
+                    },
+                )
+                .collect(),
+        })
+    }
+
+    /// Root message counts per month (UTC), oldest first
+    pub async fn get_archive_months(&self) -> Result<Vec<ArchiveMonthResponse>> {
+        let rows: Vec<(String, i64, i32, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT to_char(m.created_at AT TIME ZONE 'UTC', 'YYYY-MM') AS month,
+                   COUNT(*), MAX(m.id), MAX(m.created_at)
+            FROM messages m
+            WHERE NOT EXISTS (SELECT 1 FROM anchors a WHERE a.message_id = m.id)
+              AND m.created_at IS NOT NULL
+            GROUP BY month
+            ORDER BY month
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(month, roots, max_id, last_modified)| ArchiveMonthResponse {
+                    month,
+                    roots,
+                    max_id,
+                    last_modified,
+                },
+            )
+            .collect())
+    }
+
+    /// Root messages created in `[from, to)`, oldest first
+    pub async fn get_archive_roots(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ArchiveRoot>> {
+        let rows: Vec<ArchiveRoot> = sqlx::query_as(
+            r#"
+            SELECT m.txid, m.vout, m.kind, m.block_height, m.body, m.created_at
+            FROM messages m
+            WHERE m.created_at >= $1 AND m.created_at < $2
+              AND NOT EXISTS (SELECT 1 FROM anchors a WHERE a.message_id = m.id)
+            ORDER BY m.created_at, m.id
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Subscribe an API key to a thread; replies already in the thread
+    /// start out read. Subscribing again keeps the existing read marker.
+    pub async fn subscribe(
+        &self,
+        api_key_hash: &[u8],
+        root_message_id: i32,
+    ) -> Result<SubscriptionResponse> {
+        let row: SubscriptionRow = sqlx::query_as(&format!(
+            r#"
+            {THREAD_MESSAGES},
+            subscription AS (
+                INSERT INTO thread_subscriptions (api_key_hash, root_message_id, last_read_message_id)
+                SELECT $2, $1, COALESCE(MAX(id), 0) FROM thread
+                ON CONFLICT (api_key_hash, root_message_id)
+                    DO UPDATE SET api_key_hash = EXCLUDED.api_key_hash
+                RETURNING root_message_id, last_read_message_id, created_at, read_at
+            )
+            SELECT s.root_message_id, m.txid, m.vout, s.last_read_message_id, s.created_at, s.read_at
+            FROM subscription s
+            INNER JOIN messages m ON m.id = s.root_message_id
+            "#
+        ))
+        .bind(root_message_id)
+        .bind(api_key_hash)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.into_response())
+    }
+
+    /// Remove a subscription; false if there was none
+    pub async fn unsubscribe(&self, api_key_hash: &[u8], root_message_id: i32) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM thread_subscriptions WHERE api_key_hash = $1 AND root_message_id = $2",
+        )
+        .bind(api_key_hash)
+        .bind(root_message_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move a subscription's read marker to the newest message in the thread
+    pub async fn mark_thread_read(
+        &self,
+        api_key_hash: &[u8],
+        root_message_id: i32,
+    ) -> Result<Option<SubscriptionResponse>> {
+        let row: Option<SubscriptionRow> = sqlx::query_as(&format!(
+            r#"
+            {THREAD_MESSAGES},
+            subscription AS (
+                UPDATE thread_subscriptions
+                SET last_read_message_id = GREATEST(
+                        last_read_message_id,
+                        (SELECT COALESCE(MAX(id), 0) FROM thread)
+                    ),
+                    read_at = NOW()
+                WHERE api_key_hash = $2 AND root_message_id = $1
+                RETURNING root_message_id, last_read_message_id, created_at, read_at
+            )
+            SELECT s.root_message_id, m.txid, m.vout, s.last_read_message_id, s.created_at, s.read_at
+            FROM subscription s
+            INNER JOIN messages m ON m.id = s.root_message_id
+            "#
+        ))
+        .bind(root_message_id)
+        .bind(api_key_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(SubscriptionRow::into_response))
+    }
+
+    /// Unread messages in every thread an API key subscribes to, threads
+    /// with the newest unread message first
+    pub async fn subscription_updates(
+        &self,
+        api_key_hash: &[u8],
+        limit: i32,
+        unread_only: bool,
+    ) -> Result<Vec<ThreadUpdatesResponse>> {
+        let subscriptions: Vec<SubscriptionRow> = sqlx::query_as(
+            r#"
+            SELECT s.root_message_id, m.txid, m.vout, s.last_read_message_id, s.created_at, s.read_at
+            FROM thread_subscriptions s
+            INNER JOIN messages m ON m.id = s.root_message_id
+            WHERE s.api_key_hash = $1
+            ORDER BY s.created_at DESC
+            "#,
+        )
+        .bind(api_key_hash)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut updates = Vec::with_capacity(subscriptions.len());
+        for subscription in subscriptions {
+            let (unread_count, newest_unread): (i64, Option<i32>) = sqlx::query_as(&format!(
+                "{THREAD_MESSAGES} SELECT COUNT(*), MAX(id) FROM thread WHERE id > $2"
+            ))
+            .bind(subscription.root_message_id)
+            .bind(subscription.last_read_message_id)
+            .fetch_one(&self.pool)
+            .await?;
+            if unread_only && unread_count == 0 {
+                continue;
+            }
+
+            let rows: Vec<MessageRow> = sqlx::query_as(&format!(
+                r#"
+                {THREAD_MESSAGES}
+                SELECT m.id, m.txid, m.vout, m.payload_index, m.block_height, m.kind, m.kind_unknown, m.carrier, m.body, m.lang, m.created_at
+                FROM messages m
+                INNER JOIN thread t ON t.id = m.id
+                WHERE m.id > $2
+                ORDER BY m.id ASC
+                LIMIT $3
+                "#
+            ))
+            .bind(subscription.root_message_id)
+            .bind(subscription.last_read_message_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut unread = Vec::with_capacity(rows.len());
+            for row in rows {
+                unread.push(self.row_to_response(row).await?);
+            }
+            let root_row: MessageRow = sqlx::query_as(
+                r#"
+                SELECT id, txid, vout, payload_index, block_height, kind, kind_unknown, carrier, body, lang, created_at
+                FROM messages
+                WHERE id = $1
+                "#,
+            )
+            .bind(subscription.root_message_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            updates.push((
+                newest_unread.unwrap_or(0),
+                ThreadUpdatesResponse {
+                    root: self.row_to_response(root_row).await?,
+                    last_read_message_id: subscription.last_read_message_id,
+                    unread_count,
+                    unread,
+                },
+            ));
+        }
+
+        // Threads with the most recent unread activity first
+        updates.sort_by_key(|(newest_unread, _)| std::cmp::Reverse(*newest_unread));
+        Ok(updates.into_iter().map(|(_, update)| update).collect())
+    }
+
+    /// Confirmed messages on `carriers` that `source` has not checked in
+    /// `recheck_secs`, never checked first
+    pub async fn availability_candidates(
+        &self,
+        carriers: &[i16],
+        source: &str,
+        recheck_secs: i64,
+        limit: i64,
+    ) -> Result<Vec<AvailabilityCandidate>> {
+        let rows = sqlx::query_as(
+            r#"
+            SELECT m.id, m.txid, m.block_hash, m.carrier, m.kind, m.body
+            FROM messages m
+            LEFT JOIN message_availability a ON a.message_id = m.id AND a.source = $2
+            WHERE m.carrier = ANY($1)
+              AND m.block_hash IS NOT NULL
+              AND (a.checked_at IS NULL OR a.checked_at < NOW() - make_interval(secs => $3))
+            ORDER BY a.checked_at NULLS FIRST, m.id
+            LIMIT $4
+            "#,
+        )
+        .bind(carriers)
+        .bind(source)
+        .bind(recheck_secs as f64)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Record the outcome of checking a message's payload at `source`
+    pub async fn record_availability(
+        &self,
+        message_id: i32,
+        source: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO message_availability
+                (message_id, source, available, error, last_available_at, unavailable_since)
+            VALUES ($1, $2, $3 IS NULL, $3,
+                    CASE WHEN $3 IS NULL THEN NOW() END,
+                    CASE WHEN $3 IS NOT NULL THEN NOW() END)
+            ON CONFLICT (message_id, source) DO UPDATE SET
+                available = EXCLUDED.available,
+                error = EXCLUDED.error,
+                checks = message_availability.checks + 1,
+                checked_at = NOW(),
+                last_available_at = COALESCE(EXCLUDED.last_available_at, message_availability.last_available_at),
+                unavailable_since = CASE
+                    WHEN EXCLUDED.available THEN NULL
+                    ELSE COALESCE(message_availability.unavailable_since, NOW())
+                END
+            "#,
+        )
+        .bind(message_id)
+        .bind(source)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a finished verification run
+    pub async fn record_availability_run(
+        &self,
+        started_at: DateTime<Utc>,
+        checked: i32,
+        available: i32,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO availability_runs (started_at, checked, available, unavailable)
+            VALUES ($1, $2, $3, $2 - $3)
+            "#,
+        )
+        .bind(started_at)
+        .bind(checked)
+        .bind(available)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Availability totals of the confirmed messages on `carriers`, and the
+    /// last `runs` verification runs
+    pub async fn get_availability_report(
+        &self,
+        carriers: &[i16],
+        runs: i64,
+    ) -> Result<(
+        Vec<AvailabilityCarrierResponse>,
+        Vec<AvailabilitySourceResponse>,
+        Vec<AvailabilityRunResponse>,
+    )> {
+        let carrier_rows: Vec<(i16, i64, i64, i64, i64, Option<i32>)> = sqlx::query_as(
+            r#"
+            SELECT m.carrier,
+                   COUNT(*),
+                   COUNT(a.message_id),
+                   COUNT(*) FILTER (WHERE a.available),
+                   COUNT(*) FILTER (WHERE NOT a.available),
+                   MIN(m.block_height) FILTER (WHERE NOT a.available)
+            FROM messages m
+            LEFT JOIN (
+                SELECT message_id, bool_or(available) AS available
+                FROM message_availability
+                GROUP BY message_id
+            ) a ON a.message_id = m.id
+            WHERE m.carrier = ANY($1) AND m.block_hash IS NOT NULL
+            GROUP BY m.carrier
+            ORDER BY m.carrier
+            "#,
+        )
+        .bind(carriers)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let source_rows: Vec<(String, i64, i64, i64, Option<DateTime<Utc>>)> = sqlx::query_as(
+            r#"
+            SELECT source,
+                   COUNT(*),
+                   COUNT(*) FILTER (WHERE available),
+                   COUNT(*) FILTER (WHERE NOT available),
+                   MAX(checked_at)
+            FROM message_availability
+            GROUP BY source
+            ORDER BY source
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let run_rows: Vec<(DateTime<Utc>, DateTime<Utc>, i32, i32, i32)> = sqlx::query_as(
+            r#"
+            SELECT started_at, finished_at, checked, available, unavailable
+            FROM availability_runs
+            ORDER BY id DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(runs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((
+            carrier_rows
+                .into_iter()
+                .map(
+                    |(carrier, messages, checked, available, unavailable, lowest)| {
+                        AvailabilityCarrierResponse {
+                            carrier,
+                            carrier_name: carrier_name(carrier).to_string(),
+                            messages,
+                            checked,
+                            available,
+                            unavailable,
+                            lowest_unavailable_height: lowest,
+                        }
+                    },
+                )
+                .collect(),
+            source_rows
+                .into_iter()
+                .map(
+                    |(source, checked, available, unavailable, last_checked_at)| {
+                        AvailabilitySourceResponse {
+                            source,
+                            checked,
+                            available,
+                            unavailable,
+                            last_checked_at,
+                        }
+                    },
+                )
+                .collect(),
+            run_rows
+                .into_iter()
+                .map(
+                    |(started_at, finished_at, checked, available, unavailable)| {
+                        AvailabilityRunResponse {
+                            started_at,
+                            finished_at,
+                            checked,
+                            available,
+                            unavailable,
+                        }
+                    },
+                )
+                .collect(),
+        ))
+    }
+
     /// Convert a database row to a response
     async fn row_to_response(&self, row: MessageRow) -> Result<MessageResponse> {
+        let mut conn = self.pool.acquire().await?;
+        Self::message_response(&mut conn, row).await
+    }
+
+    /// Convert a database row to a response, reading anchors on `conn`
+    async fn message_response(conn: &mut PgConnection, row: MessageRow) -> Result<MessageResponse> {
         // Get anchors
         let anchor_rows: Vec<AnchorRow> = sqlx::query_as(
             r#"
@@ -578,7 +1379,7 @@ impl Database {
             "#,
         )
         .bind(row.id)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         let anchors: Vec<AnchorResponse> = anchor_rows
@@ -609,10 +1410,10 @@ impl Database {
         )
         .bind(prefix)
         .bind(row.vout as i16)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
         .await?;
 
-        let body_text = String::from_utf8(row.body.clone()).ok();
+        let (body_text, content_type) = message_body(row.kind, &row.body);
 
         // Convert txid from internal to display format (reverse bytes)
         let mut txid_display = row.txid.clone();
@@ -622,13 +1423,17 @@ impl Database {
             id: row.id,
             txid: hex::encode(&txid_display),
             vout: row.vout,
+            payload_index: row.payload_index,
             block_height: row.block_height,
             kind: row.kind,
             kind_name: kind_to_name(row.kind),
+            kind_unknown: row.kind_unknown,
             carrier: row.carrier,
             carrier_name: carrier_name(row.carrier).to_string(),
             body_hex: hex::encode(&row.body),
             body_text,
+            content_type,
+            lang: row.lang,
             anchors,
             reply_count: reply_count.0,
             created_at: row.created_at,
@@ -637,7 +1442,7 @@ impl Database {
 
     /// Convert a database row with precomputed reply count to a response
     async fn row_with_count_to_response(
-        &self,
+        conn: &mut PgConnection,
         row: MessageRowWithReplyCount,
     ) -> Result<MessageResponse> {
         // Get anchors
@@ -650,7 +1455,7 @@ impl Database {
             "#,
         )
         .bind(row.id)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         let anchors: Vec<AnchorResponse> = anchor_rows
@@ -669,7 +1474,7 @@ impl Database {
             })
             .collect();
 
-        let body_text = String::from_utf8(row.body.clone()).ok();
+        let (body_text, content_type) = message_body(row.kind, &row.body);
 
         // Convert txid from internal to display format (reverse bytes)
         let mut txid_display = row.txid.clone();
@@ -679,13 +1484,17 @@ impl Database {
             id: row.id,
             txid: hex::encode(&txid_display),
             vout: row.vout,
+            payload_index: row.payload_index,
             block_height: row.block_height,
             kind: row.kind,
             kind_name: kind_to_name(row.kind),
+            kind_unknown: row.kind_unknown,
             carrier: row.carrier,
             carrier_name: carrier_name(row.carrier).to_string(),
             body_hex: hex::encode(&row.body),
             body_text,
+            content_type,
+            lang: row.lang,
             anchors,
             reply_count: row.reply_count,
             created_at: row.created_at,
@@ -694,13 +1503,15 @@ impl Database {
 }
 
 /// Convert kind code to human-readable name
-fn kind_to_name(kind: i16) -> String {
+pub(crate) fn kind_to_name(kind: i16) -> String {
     match kind {
         0 => "Generic".to_string(),
         1 => "Text".to_string(),
         2 => "State".to_string(),
         3 => "Vote".to_string(),
         4 => "Image".to_string(),
+        6 => "Identity".to_string(),
+        7 => "Mirror".to_string(),
         n => format!("Custom({})", n),
     }
 }
@@ -0,0 +1,119 @@
+//! Decoding of arbitrary transactions
+//!
+//! Runs a transaction through every carrier and, for kinds with a spec in
+//! `anchor-specs`, parses the body into its fields. Also unwraps the
+//! content of Generic envelopes for display.
+
+use anchor_core::carrier::{verify_carrier_roundtrip, MessageLocation, VerifiedAnchorMessage};
+use anchor_specs::prelude::*;
+use anchor_specs::{
+    dns::DnsSpec, escrow::EscrowSpec, generic::GenericSpec, geomarker::GeoMarkerSpec,
+    identity::IdentityRotationSpec, mirror::MirrorSpec, oracle::OracleAttestationSpec,
+    oracle::OracleDisputeSpec, oracle::OracleSlashSpec, proof::ProofSpec, state::StateSpec,
+    text::TextSpec, token::TokenSpec,
+};
+use bitcoin::Transaction;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::db::kind_to_name;
+use crate::models::{DecodedAnchor, DecodedPayload};
+
+/// Number of body bytes included in previews
+const PREVIEW_BYTES: usize = 256;
+
+/// Decode every ANCHOR payload in `tx`, leaving anchors unresolved
+pub fn decode_payloads(tx: &Transaction) -> Vec<DecodedPayload> {
+    verify_carrier_roundtrip(tx)
+        .into_iter()
+        .map(to_payload)
+        .collect()
+}
+
+fn to_payload(verified: VerifiedAnchorMessage) -> DecodedPayload {
+    let message = &verified.message;
+    let kind = u8::from(message.kind);
+    let preview = &message.body[..message.body.len().min(PREVIEW_BYTES)];
+
+    let (fields, fields_error) = match spec_fields(kind, &message.body) {
+        Some(Ok(fields)) => (Some(fields), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+
+    DecodedPayload {
+        carrier: verified.carrier_type as i16,
+        carrier_name: verified.carrier_type.to_string(),
+        location: match verified.location {
+            MessageLocation::Output(vout) => format!("output:{}", vout),
+            MessageLocation::Input(index) => format!("input:{}", index),
+            MessageLocation::Transaction => "transaction".to_string(),
+        },
+        kind: kind as i16,
+        kind_name: kind_to_name(kind as i16),
+        anchors: message
+            .anchors
+            .iter()
+            .map(|anchor| DecodedAnchor {
+                txid_prefix: hex::encode(anchor.txid_prefix),
+                vout: anchor.vout as i16,
+                candidates: Vec::new(),
+            })
+            .collect(),
+        body_size: message.body.len(),
+        body_preview_hex: hex::encode(preview),
+        body_preview_text: String::from_utf8(preview.to_vec()).ok(),
+        fields,
+        fields_error,
+        roundtrip: verified.roundtrip,
+        conflicts: verified
+            .conflicts
+            .iter()
+            .map(|carrier| carrier.to_string())
+            .collect(),
+    }
+}
+
+/// Parse `body` with the spec for `kind`, if there is one
+fn spec_fields(kind: u8, body: &[u8]) -> Option<Result<Value, String>> {
+    match kind {
+        GenericSpec::KIND_ID => Some(parse::<GenericSpec>(body)),
+        TextSpec::KIND_ID => Some(parse::<TextSpec>(body)),
+        StateSpec::KIND_ID => Some(parse::<StateSpec>(body)),
+        GeoMarkerSpec::KIND_ID => Some(parse::<GeoMarkerSpec>(body)),
+        IdentityRotationSpec::KIND_ID => Some(parse::<IdentityRotationSpec>(body)),
+        MirrorSpec::KIND_ID => Some(parse::<MirrorSpec>(body)),
+        DnsSpec::KIND_ID => Some(parse::<DnsSpec>(body)),
+        ProofSpec::KIND_ID => Some(parse::<ProofSpec>(body)),
+        TokenSpec::KIND_ID => Some(parse::<TokenSpec>(body)),
+        OracleAttestationSpec::KIND_ID => Some(parse::<OracleAttestationSpec>(body)),
+        OracleDisputeSpec::KIND_ID => Some(parse::<OracleDisputeSpec>(body)),
+        OracleSlashSpec::KIND_ID => Some(parse::<OracleSlashSpec>(body)),
+        EscrowSpec::KIND_ID => Some(parse::<EscrowSpec>(body)),
+        _ => None,
+    }
+}
+
+fn parse<S: KindSpec + Serialize>(body: &[u8]) -> Result<Value, String> {
+    let spec = S::from_bytes(body).map_err(|e| e.to_string())?;
+    spec.validate().map_err(|e| e.to_string())?;
+    serde_json::to_value(&spec).map_err(|e| e.to_string())
+}
+
+/// Text to show for a stored message body, and its content type
+///
+/// Generic envelopes show their content when it is text or JSON and have
+/// no text otherwise; bodies that are not envelopes, including Generic
+/// messages that predate them, show as UTF-8 when they are.
+pub fn message_body(kind: i16, body: &[u8]) -> (Option<String>, Option<String>) {
+    if kind == GenericSpec::KIND_ID as i16 {
+        if let Ok(spec) = GenericSpec::from_bytes(body).and_then(GenericSpec::validated) {
+            let text = match &spec.content_type {
+                Some(_) if !spec.is_text() => None,
+                _ => String::from_utf8(spec.body.clone()).ok(),
+            };
+            return (text, spec.content_type);
+        }
+    }
+    (String::from_utf8(body.to_vec()).ok(), None)
+}
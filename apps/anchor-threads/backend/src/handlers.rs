@@ -1,19 +1,40 @@
 //! HTTP request handlers for the explorer API
 
+use anchor_api_error::{ApiError, ErrorCode};
+use anchor_api_snapshot::SnapshotParams;
+use anchor_core::InclusionProof;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
+use bitcoin::Txid;
+use bitcoincore_rpc::RpcApi;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use tracing::error;
 use utoipa::ToSchema;
 
-use crate::models::{FilterParams, ListParams, PaginatedResponse};
+use crate::availability;
+use crate::decode;
+use crate::models::{
+    AnchorFilterParams, AnchorFiltersResponse, ArchiveIndexResponse, ArchiveResponse, AuthorParams,
+    AuthorProfileResponse, AvailabilityReportResponse, DecodeResponse, FilterParams,
+    InclusionProofResponse, ListParams, MessagePreviewResponse, OEmbedParams, OEmbedResponse,
+    PaginatedResponse, SubscriptionResponse, ThreadUpdatesResponse, UpdatesParams,
+};
+use crate::preview;
+use crate::sitemap;
 use crate::AppState;
 
+/// Header carrying the client's API key for subscriptions
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Shortest API key accepted, so keys can't be guessed
+const MIN_API_KEY_LEN: usize = 16;
+
 /// Health check response
 #[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
@@ -30,6 +51,44 @@ fn display_txid_to_internal(txid_hex: &str) -> Result<Vec<u8>, String> {
     Ok(bytes)
 }
 
+/// Hash of the request's API key, which identifies its subscriptions
+///
+/// Keys are opaque strings chosen by the client; only their SHA-256 is
+/// stored.
+fn api_key_hash(headers: &HeaderMap) -> Result<Vec<u8>, ApiError> {
+    let key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .unwrap_or_default();
+    if key.is_empty() {
+        return Err(ApiError::new(
+            ErrorCode::Unauthorized,
+            "Missing X-API-Key header",
+        ));
+    }
+    if key.len() < MIN_API_KEY_LEN {
+        return Err(ApiError::bad_request(format!(
+            "API key must be at least {} characters",
+            MIN_API_KEY_LEN
+        )));
+    }
+    Ok(Sha256::digest(key.as_bytes()).to_vec())
+}
+
+/// Id of the thread root at `txid:vout`
+async fn thread_root_id(state: &AppState, txid: &str, vout: i32) -> Result<i32, ApiError> {
+    let txid_bytes = display_txid_to_internal(txid).map_err(ApiError::bad_request)?;
+    match state.db.find_message_id(&txid_bytes, vout).await {
+        Ok(Some(id)) => Ok(id),
+        Ok(None) => Err(ApiError::not_found("Thread not found")),
+        Err(e) => {
+            error!("Failed to find thread: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
 /// Health check endpoint
 #[utoipa::path(
     get,
@@ -56,14 +115,12 @@ pub async fn health() -> Json<HealthResponse> {
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn get_stats(
-    State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, ApiError> {
     match state.db.get_stats().await {
         Ok(stats) => Ok(Json(stats)),
         Err(e) => {
             error!("Failed to get stats: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -76,7 +133,10 @@ pub async fn get_stats(
     params(
         ("page" = Option<i32>, Query, description = "Page number (default: 1)"),
         ("per_page" = Option<i32>, Query, description = "Items per page (default: 20)"),
-        ("kind" = Option<i16>, Query, description = "Filter by message kind")
+        ("kind" = Option<i16>, Query, description = "Filter by message kind"),
+        ("kind_unknown" = Option<bool>, Query, description = "true: only messages whose kind is not in the registry"),
+        ("lang" = Option<String>, Query, description = "Filter by language, ISO 639-3 codes separated by commas"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
         (status = 200, description = "Paginated list of messages"),
@@ -86,8 +146,14 @@ pub async fn get_stats(
 pub async fn list_messages(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    match state.db.list_messages(&params).await {
+    Query(snapshot): Query<SnapshotParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
+    match state
+        .db
+        .list_messages(&params, snapshot_id.as_deref())
+        .await
+    {
         Ok((messages, total)) => {
             let total_pages = ((total as f64) / (params.per_page as f64)).ceil() as i32;
             Ok(Json(PaginatedResponse {
@@ -100,7 +166,7 @@ pub async fn list_messages(
         }
         Err(e) => {
             error!("Failed to list messages: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -112,7 +178,9 @@ pub async fn list_messages(
     tag = "Threads",
     params(
         ("page" = Option<i32>, Query, description = "Page number (default: 1)"),
-        ("per_page" = Option<i32>, Query, description = "Items per page (default: 20)")
+        ("per_page" = Option<i32>, Query, description = "Items per page (default: 20)"),
+        ("lang" = Option<String>, Query, description = "Filter by language, ISO 639-3 codes separated by commas"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
         (status = 200, description = "Paginated list of root messages"),
@@ -122,8 +190,10 @@ pub async fn list_messages(
 pub async fn list_roots(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    match state.db.list_roots(&params).await {
+    Query(snapshot): Query<SnapshotParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
+    match state.db.list_roots(&params, snapshot_id.as_deref()).await {
         Ok((messages, total)) => {
             let total_pages = ((total as f64) / (params.per_page as f64)).ceil() as i32;
             Ok(Json(PaginatedResponse {
@@ -136,7 +206,7 @@ pub async fn list_roots(
         }
         Err(e) => {
             error!("Failed to list roots: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -154,13 +224,16 @@ pub async fn list_roots(
         ("block_min" = Option<i32>, Query, description = "Minimum block height"),
         ("block_max" = Option<i32>, Query, description = "Maximum block height"),
         ("kind" = Option<i16>, Query, description = "Filter by message kind"),
+        ("kind_unknown" = Option<bool>, Query, description = "true: only messages whose kind is not in the registry"),
         ("text" = Option<String>, Query, description = "Filter by text content"),
         ("from_date" = Option<String>, Query, description = "Filter by minimum timestamp (ISO 8601)"),
         ("to_date" = Option<String>, Query, description = "Filter by maximum timestamp (ISO 8601)"),
         ("min_size" = Option<i32>, Query, description = "Minimum body size in bytes"),
         ("max_size" = Option<i32>, Query, description = "Maximum body size in bytes"),
         ("min_replies" = Option<i32>, Query, description = "Minimum reply count"),
-        ("sort" = Option<String>, Query, description = "Sort order: newest, oldest, replies, size")
+        ("sort" = Option<String>, Query, description = "Sort order: newest, oldest, replies, size"),
+        ("lang" = Option<String>, Query, description = "Filter by language, ISO 639-3 codes separated by commas"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
         (status = 200, description = "Paginated list of filtered root messages"),
@@ -170,8 +243,14 @@ pub async fn list_roots(
 pub async fn list_roots_filtered(
     State(state): State<Arc<AppState>>,
     Query(params): Query<FilterParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    match state.db.list_roots_filtered(&params).await {
+    Query(snapshot): Query<SnapshotParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
+    match state
+        .db
+        .list_roots_filtered(&params, snapshot_id.as_deref())
+        .await
+    {
         Ok((messages, total)) => {
             let total_pages = ((total as f64) / (params.per_page as f64)).ceil() as i32;
             Ok(Json(PaginatedResponse {
@@ -184,7 +263,7 @@ pub async fn list_roots_filtered(
         }
         Err(e) => {
             error!("Failed to list filtered roots: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -207,19 +286,677 @@ pub async fn list_roots_filtered(
 pub async fn get_message(
     State(state): State<Arc<AppState>>,
     Path((txid, vout)): Path<(String, i32)>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let txid_bytes = display_txid_to_internal(&txid).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+) -> Result<impl IntoResponse, ApiError> {
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
 
     match state.db.get_message(&txid_bytes, vout).await {
         Ok(Some(message)) => Ok(Json(message)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "Message not found".to_string())),
+        Ok(None) => Err(ApiError::not_found("Message not found")),
+        Err(e) => {
+            error!("Failed to get message: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Get a merkle proof that a message was mined
+#[utoipa::path(
+    get,
+    path = "/messages/{txid}/{vout}/proof",
+    tag = "Messages",
+    params(
+        ("txid" = String, Path, description = "Transaction ID (hex)"),
+        ("vout" = i32, Path, description = "Output index")
+    ),
+    responses(
+        (status = 200, description = "Inclusion proof", body = crate::models::InclusionProofResponse),
+        (status = 404, description = "Message not found or not yet mined"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_message_proof(
+    State(state): State<Arc<AppState>>,
+    Path((txid, vout)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let parsed_txid: Txid = txid
+        .parse()
+        .map_err(|e| ApiError::bad_request(format!("Invalid txid: {}", e)))?;
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
+
+    let message = match state.db.get_message(&txid_bytes, vout).await {
+        Ok(Some(message)) => message,
+        Ok(None) => return Err(ApiError::not_found("Message not found")),
+        Err(e) => {
+            error!("Failed to get message: {}", e);
+            return Err(ApiError::internal(e.to_string()));
+        }
+    };
+
+    let rpc = state.rpc.clone();
+    let proof = tokio::task::spawn_blocking(move || -> Result<_, bitcoincore_rpc::Error> {
+        let info = rpc.get_raw_transaction_info(&parsed_txid, None)?;
+        let Some(block_hash) = info.blockhash else {
+            return Ok(None);
+        };
+        let block = rpc.get_block(&block_hash)?;
+        Ok(InclusionProof::from_block(&block, &parsed_txid).map(|proof| (block.header, proof)))
+    })
+    .await
+    .map_err(|e| ApiError::internal(e.to_string()))?
+    .map_err(|e| {
+        error!("Failed to build proof for {}: {}", txid, e);
+        ApiError::internal(e.to_string())
+    })?;
+
+    let Some((header, proof)) = proof else {
+        return Err(ApiError::not_found("Transaction is not in a block yet"));
+    };
+
+    Ok(Json(InclusionProofResponse {
+        txid: parsed_txid.to_string(),
+        vout,
+        block_hash: proof.block_hash.to_string(),
+        block_height: message.block_height,
+        header_hex: bitcoin::consensus::encode::serialize_hex(&header),
+        index: proof.index,
+        branch: proof.branch.iter().map(|node| node.to_string()).collect(),
+        explorer_url: format!(
+            "{}/tx/{}",
+            state.block_explorer_url.trim_end_matches('/'),
+            parsed_txid
+        ),
+    }))
+}
+
+/// Build the link preview of the message at `txid:vout`
+async fn message_preview(
+    state: &AppState,
+    txid: &str,
+    vout: i32,
+) -> Result<MessagePreviewResponse, ApiError> {
+    let txid_bytes = display_txid_to_internal(txid).map_err(ApiError::bad_request)?;
+    let message = match state.db.get_message(&txid_bytes, vout).await {
+        Ok(Some(message)) => message,
+        Ok(None) => return Err(ApiError::not_found("Message not found")),
         Err(e) => {
             error!("Failed to get message: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            return Err(ApiError::internal(e.to_string()));
+        }
+    };
+    let creator_address = state
+        .db
+        .get_message_creator(&txid_bytes, vout)
+        .await
+        .map_err(|e| {
+            error!("Failed to get message creator: {}", e);
+            ApiError::internal(e.to_string())
+        })?;
+    let author_name = match &creator_address {
+        Some(address) => state
+            .authors
+            .linked_identity(address)
+            .await
+            .domains
+            .into_iter()
+            .next(),
+        None => None,
+    };
+
+    let image = if message.kind == preview::IMAGE_KIND {
+        hex::decode(&message.body_hex)
+            .ok()
+            .and_then(|body| preview::image_info(&body))
+    } else {
+        None
+    };
+    // Bodies of binary kinds can happen to be valid UTF-8
+    let text = message.body_text.as_deref().filter(|text| {
+        image.is_none()
+            && !text.trim().is_empty()
+            && !text.chars().any(|c| c.is_control() && !c.is_whitespace())
+    });
+
+    let (title, description) = match text {
+        Some(text) => (
+            preview::excerpt(text, preview::TITLE_CHARS),
+            preview::excerpt(text, preview::DESCRIPTION_CHARS),
+        ),
+        None => {
+            let status = match message.block_height {
+                Some(height) => format!("anchored on Bitcoin at block {}", height),
+                None => "waiting for confirmation".to_string(),
+            };
+            (
+                format!(
+                    "{} message {}:{}",
+                    message.kind_name,
+                    preview::short_txid(&message.txid),
+                    message.vout
+                ),
+                format!(
+                    "{} message {}, {} replies",
+                    message.kind_name, status, message.reply_count
+                ),
+            )
+        }
+    };
+
+    let api_url = state.api_public_url.trim_end_matches('/');
+    let url = format!(
+        "{}/message/{}/{}",
+        state.public_url.trim_end_matches('/'),
+        message.txid,
+        message.vout
+    );
+    Ok(MessagePreviewResponse {
+        image_url: image.map(|_| {
+            format!(
+                "{}/messages/{}/{}/image",
+                api_url, message.txid, message.vout
+            )
+        }),
+        image_type: image.map(|(mime, _, _)| mime.to_string()),
+        image_width: image.map(|(_, width, _)| width),
+        image_height: image.map(|(_, _, height)| height),
+        oembed_url: reqwest::Url::parse_with_params(
+            &format!("{}/oembed", api_url),
+            [("url", &url)],
+        )
+        .map(String::from)
+        .map_err(|e| ApiError::internal(format!("Invalid API_PUBLIC_URL: {}", e)))?,
+        txid: message.txid,
+        vout: message.vout,
+        title,
+        description,
+        url,
+        site_name: preview::SITE_NAME.to_string(),
+        kind_name: message.kind_name,
+        author_name,
+        creator_address,
+        block_height: message.block_height,
+        reply_count: message.reply_count,
+        created_at: message.created_at,
+    })
+}
+
+/// Get OpenGraph-style preview metadata for a message
+#[utoipa::path(
+    get,
+    path = "/messages/{txid}/{vout}/preview",
+    tag = "Previews",
+    params(
+        ("txid" = String, Path, description = "Transaction ID (hex)"),
+        ("vout" = i32, Path, description = "Output index")
+    ),
+    responses(
+        (status = 200, description = "Preview metadata", body = MessagePreviewResponse),
+        (status = 404, description = "Message not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_message_preview(
+    State(state): State<Arc<AppState>>,
+    Path((txid, vout)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(message_preview(&state, &txid, vout).await?))
+}
+
+/// Get the image carried by an Image message
+#[utoipa::path(
+    get,
+    path = "/messages/{txid}/{vout}/image",
+    tag = "Previews",
+    params(
+        ("txid" = String, Path, description = "Transaction ID (hex)"),
+        ("vout" = i32, Path, description = "Output index")
+    ),
+    responses(
+        (status = 200, description = "Image bytes, with their sniffed content type"),
+        (status = 404, description = "Message not found or not an image"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_message_image(
+    State(state): State<Arc<AppState>>,
+    Path((txid, vout)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
+
+    let body = match state.db.get_message_body(&txid_bytes, vout).await {
+        Ok(Some((kind, body))) if kind == preview::IMAGE_KIND => body,
+        Ok(Some(_)) => return Err(ApiError::not_found("Message is not an image")),
+        Ok(None) => return Err(ApiError::not_found("Message not found")),
+        Err(e) => {
+            error!("Failed to get message body: {}", e);
+            return Err(ApiError::internal(e.to_string()));
+        }
+    };
+    let Some((mime, _, _)) = preview::image_info(&body) else {
+        return Err(ApiError::not_found("Message body is not a supported image"));
+    };
+
+    // Keyed by txid, so the content never changes
+    Ok((
+        [
+            (header::CONTENT_TYPE, mime),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        body,
+    ))
+}
+
+/// oEmbed endpoint for message and thread links
+#[utoipa::path(
+    get,
+    path = "/oembed",
+    tag = "Previews",
+    params(
+        ("url" = String, Query, description = "Message or thread URL on the explorer frontend"),
+        ("maxwidth" = Option<u32>, Query, description = "Maximum width of the embed"),
+        ("maxheight" = Option<u32>, Query, description = "Maximum height of the embed"),
+        ("format" = Option<String>, Query, description = "Response format, only `json`")
+    ),
+    responses(
+        (status = 200, description = "oEmbed 1.0 object", body = OEmbedResponse),
+        (status = 404, description = "URL is not a message link, or the message is not indexed"),
+        (status = 501, description = "Format not supported"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_oembed(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<OEmbedParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    if params
+        .format
+        .as_deref()
+        .is_some_and(|format| format != "json")
+    {
+        return Err(ApiError::bad_request("Only format=json is supported").with_status(501));
+    }
+    let (txid, vout) = preview::parse_message_url(&params.url)
+        .ok_or_else(|| ApiError::not_found("URL is not an ANCHOR message link"))?;
+    let preview = message_preview(&state, &txid, vout).await?;
+
+    let author_name = preview
+        .author_name
+        .clone()
+        .or_else(|| preview.creator_address.clone());
+    let provider_url = state.public_url.clone();
+
+    let response = match (
+        &preview.image_url,
+        preview.image_width,
+        preview.image_height,
+    ) {
+        (Some(image_url), Some(width), Some(height)) => {
+            let (width, height) = preview::fit(width, height, params.maxwidth, params.maxheight);
+            OEmbedResponse {
+                kind: "photo".to_string(),
+                version: "1.0".to_string(),
+                title: preview.title,
+                author_name,
+                provider_name: preview.site_name,
+                provider_url,
+                url: Some(image_url.clone()),
+                html: None,
+                width,
+                height,
+                thumbnail_url: Some(image_url.clone()),
+                thumbnail_width: Some(width),
+                thumbnail_height: Some(height),
+            }
+        }
+        _ => {
+            let (width, height) = preview::fit(
+                preview::CARD_WIDTH,
+                preview::CARD_HEIGHT,
+                params.maxwidth,
+                params.maxheight,
+            );
+            OEmbedResponse {
+                kind: "rich".to_string(),
+                version: "1.0".to_string(),
+                html: Some(preview::card_html(
+                    &preview.title,
+                    &preview.description,
+                    author_name.as_deref(),
+                    &preview.url,
+                )),
+                title: preview.title,
+                author_name,
+                provider_name: preview.site_name,
+                provider_url,
+                url: None,
+                width,
+                height,
+                thumbnail_url: None,
+                thumbnail_width: None,
+                thumbnail_height: None,
+            }
+        }
+    };
+    Ok(Json(response))
+}
+
+/// Read a generated sitemap or archive file
+async fn sitemap_file(
+    state: &AppState,
+    relative: &str,
+    content_type: &'static str,
+) -> Result<impl IntoResponse, ApiError> {
+    let Some(sitemaps) = &state.sitemaps else {
+        return Err(ApiError::not_found("Sitemaps are not enabled"));
+    };
+    let Some(bytes) = sitemaps.read(relative).await else {
+        return Err(ApiError::not_found("Not generated yet"));
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "public, max-age=300"),
+        ],
+        bytes,
+    ))
+}
+
+/// Get the sitemap index
+#[utoipa::path(
+    get,
+    path = "/sitemap.xml",
+    tag = "Archive",
+    responses(
+        (status = 200, description = "Sitemap index of the monthly sitemap pages", content_type = "application/xml"),
+        (status = 404, description = "Sitemaps are not enabled or not generated yet")
+    )
+)]
+pub async fn get_sitemap_index(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    sitemap_file(&state, "sitemap.xml", "application/xml").await
+}
+
+/// Get one sitemap page
+#[utoipa::path(
+    get,
+    path = "/sitemaps/{file}",
+    tag = "Archive",
+    params(
+        ("file" = String, Path, description = "Page file name, `YYYY-MM-N.xml`")
+    ),
+    responses(
+        (status = 200, description = "Thread URLs of one month", content_type = "application/xml"),
+        (status = 404, description = "Page not found")
+    )
+)]
+pub async fn get_sitemap_page(
+    State(state): State<Arc<AppState>>,
+    Path(file): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !sitemap::is_sitemap_page(&file) {
+        return Err(ApiError::not_found("Sitemap page not found"));
+    }
+    sitemap_file(&state, &format!("sitemaps/{}", file), "application/xml").await
+}
+
+/// Get the months of the archive
+#[utoipa::path(
+    get,
+    path = "/archive",
+    tag = "Archive",
+    responses(
+        (status = 200, description = "Archive months", body = ArchiveIndexResponse),
+        (status = 404, description = "Sitemaps are not enabled or not generated yet")
+    )
+)]
+pub async fn get_archive_index(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    sitemap_file(&state, "archive/index.json", "application/json").await
+}
+
+/// Get the root messages of one month
+#[utoipa::path(
+    get,
+    path = "/archive/{month}",
+    tag = "Archive",
+    params(
+        ("month" = String, Path, description = "Month, `YYYY-MM` (UTC)")
+    ),
+    responses(
+        (status = 200, description = "Root messages of the month", body = ArchiveResponse),
+        (status = 404, description = "No archive for the month")
+    )
+)]
+pub async fn get_archive_month(
+    State(state): State<Arc<AppState>>,
+    Path(month): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !sitemap::is_month(&month) {
+        return Err(ApiError::bad_request("Month must be YYYY-MM"));
+    }
+    sitemap_file(
+        &state,
+        &format!("archive/{}.json", month),
+        "application/json",
+    )
+    .await
+}
+
+/// Most filters returned by one request
+const MAX_FILTERS_PER_REQUEST: i32 = 1000;
+
+/// Get the ANCHOR activity filters of a run of blocks
+///
+/// Wallets restored from seed match their scripts against these to find
+/// the blocks holding their ANCHOR transactions. Blocks the indexer has not
+/// built a filter for are missing from the list.
+#[utoipa::path(
+    get,
+    path = "/filters",
+    tag = "Filters",
+    params(
+        ("from" = i32, Query, description = "First block height"),
+        ("count" = Option<i32>, Query, description = "Filters to return (default and max 1000)")
+    ),
+    responses(
+        (status = 200, description = "Filters, lowest height first", body = AnchorFiltersResponse),
+        (status = 400, description = "Invalid range"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_anchor_filters(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AnchorFilterParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    if params.from < 0 {
+        return Err(ApiError::bad_request("from must not be negative"));
+    }
+    let count = params
+        .count
+        .unwrap_or(MAX_FILTERS_PER_REQUEST)
+        .clamp(1, MAX_FILTERS_PER_REQUEST);
+
+    match state.db.get_anchor_filters(params.from, count).await {
+        Ok(filters) => Ok(Json(filters)),
+        Err(e) => {
+            error!("Failed to get ANCHOR filters: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Get every message carried by a transaction
+#[utoipa::path(
+    get,
+    path = "/tx/{txid}/messages",
+    tag = "Messages",
+    params(
+        ("txid" = String, Path, description = "Transaction ID (hex)")
+    ),
+    responses(
+        (status = 200, description = "Messages in payload order", body = Vec<crate::models::MessageResponse>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_transaction_messages(
+    State(state): State<Arc<AppState>>,
+    Path(txid): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
+
+    match state.db.get_transaction_messages(&txid_bytes).await {
+        Ok(messages) => Ok(Json(messages)),
+        Err(e) => {
+            error!("Failed to get transaction messages: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Decode an arbitrary transaction through every carrier
+#[utoipa::path(
+    get,
+    path = "/decode/{txid}",
+    tag = "Messages",
+    params(
+        ("txid" = String, Path, description = "Transaction ID (hex)")
+    ),
+    responses(
+        (status = 200, description = "Decoded payloads", body = crate::models::DecodeResponse),
+        (status = 400, description = "Invalid transaction ID"),
+        (status = 404, description = "Transaction not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn decode_transaction(
+    State(state): State<Arc<AppState>>,
+    Path(txid): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let parsed_txid: Txid = txid
+        .parse()
+        .map_err(|e| ApiError::bad_request(format!("Invalid txid: {}", e)))?;
+
+    let rpc = state.rpc.clone();
+    let tx = tokio::task::spawn_blocking(move || rpc.get_raw_transaction(&parsed_txid, None))
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .map_err(|e| {
+            error!("Failed to fetch transaction {}: {}", txid, e);
+            ApiError::not_found("Transaction not found")
+        })?;
+
+    let mut payloads = decode::decode_payloads(&tx);
+    for payload in &mut payloads {
+        for anchor in &mut payload.anchors {
+            let prefix = hex::decode(&anchor.txid_prefix).unwrap_or_default();
+            anchor.candidates = state
+                .db
+                .find_anchor_candidates(&prefix)
+                .await
+                .map_err(|e| ApiError::internal(e.to_string()))?;
+        }
+    }
+
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
+    let indexed = state
+        .db
+        .transaction_indexed(&txid_bytes)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(Json(DecodeResponse {
+        txid: parsed_txid.to_string(),
+        indexed,
+        payloads,
+    }))
+}
+
+/// Get the identity chain a key belongs to
+#[utoipa::path(
+    get,
+    path = "/identities/{pubkey}",
+    tag = "Identities",
+    params(
+        ("pubkey" = String, Path, description = "Any key in the chain (x-only, hex)")
+    ),
+    responses(
+        (status = 200, description = "Identity chain", body = crate::models::IdentityResponse),
+        (status = 400, description = "Invalid public key"),
+        (status = 404, description = "Key has never rotated"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_identity(
+    State(state): State<Arc<AppState>>,
+    Path(pubkey): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let pubkey_bytes = hex::decode(&pubkey)
+        .ok()
+        .filter(|bytes| bytes.len() == 32)
+        .ok_or_else(|| ApiError::bad_request("Public key must be 32 bytes of hex"))?;
+
+    match state.db.get_identity(&pubkey_bytes).await {
+        Ok(Some(identity)) => Ok(Json(identity)),
+        Ok(None) => Err(ApiError::not_found("Identity not found")),
+        Err(e) => {
+            error!("Failed to get identity: {}", e);
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
 
+/// Get the profile of a message creator
+#[utoipa::path(
+    get,
+    path = "/authors/{address}",
+    tag = "Authors",
+    params(
+        ("address" = String, Path, description = "Creator address"),
+        ("recent" = Option<i32>, Query, description = "Recent messages to include (default 10, max 50)")
+    ),
+    responses(
+        (status = 200, description = "Author profile", body = AuthorProfileResponse),
+        (status = 404, description = "Address has no indexed messages"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_author(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Query(params): Query<AuthorParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (message_count, first_seen, last_seen) =
+        state.db.get_author_stats(&address).await.map_err(|e| {
+            error!("Failed to get author stats: {}", e);
+            ApiError::internal(e.to_string())
+        })?;
+    if message_count == 0 {
+        return Err(ApiError::not_found("Author not found"));
+    }
+
+    let recent_messages = state
+        .db
+        .get_author_messages(&address, params.recent.clamp(0, 50))
+        .await
+        .map_err(|e| {
+            error!("Failed to get author messages: {}", e);
+            ApiError::internal(e.to_string())
+        })?;
+    let linked = state.authors.linked_identity(&address).await;
+
+    Ok(Json(AuthorProfileResponse {
+        domain: linked.domains.first().cloned(),
+        domains: linked.domains,
+        nostr_pubkey: linked.nostr_pubkey,
+        address,
+        message_count,
+        first_seen,
+        last_seen,
+        recent_messages,
+    }))
+}
+
 /// Get replies to a message
 #[utoipa::path(
     get,
@@ -237,14 +974,14 @@ pub async fn get_message(
 pub async fn get_replies(
     State(state): State<Arc<AppState>>,
     Path((txid, vout)): Path<(String, i32)>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let txid_bytes = display_txid_to_internal(&txid).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+) -> Result<impl IntoResponse, ApiError> {
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
 
     match state.db.get_replies(&txid_bytes, vout).await {
         Ok(replies) => Ok(Json(replies)),
         Err(e) => {
             error!("Failed to get replies: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -255,7 +992,8 @@ pub async fn get_replies(
     path = "/popular",
     tag = "Threads",
     params(
-        ("per_page" = Option<i32>, Query, description = "Number of threads to return (max: 20)")
+        ("per_page" = Option<i32>, Query, description = "Number of threads to return (max: 20)"),
+        ("lang" = Option<String>, Query, description = "Filter by language, ISO 639-3 codes separated by commas")
     ),
     responses(
         (status = 200, description = "List of popular threads", body = Vec<crate::models::PopularThreadResponse>),
@@ -265,13 +1003,13 @@ pub async fn get_replies(
 pub async fn get_popular_threads(
     State(state): State<Arc<AppState>>,
     Query(params): Query<ListParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let limit = params.per_page.min(20); // Max 20 popular threads
-    match state.db.get_popular_threads(limit).await {
+    match state.db.get_popular_threads(limit, params.langs()).await {
         Ok(threads) => Ok(Json(threads)),
         Err(e) => {
             error!("Failed to get popular threads: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -294,15 +1032,194 @@ pub async fn get_popular_threads(
 pub async fn get_thread(
     State(state): State<Arc<AppState>>,
     Path((txid, vout)): Path<(String, i32)>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let txid_bytes = display_txid_to_internal(&txid).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+) -> Result<impl IntoResponse, ApiError> {
+    let txid_bytes = display_txid_to_internal(&txid).map_err(ApiError::bad_request)?;
 
     match state.db.get_thread(&txid_bytes, vout).await {
         Ok(Some(thread)) => Ok(Json(thread)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "Thread not found".to_string())),
+        Ok(None) => Err(ApiError::not_found("Thread not found")),
         Err(e) => {
             error!("Failed to get thread: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Subscribe to a thread's replies
+#[utoipa::path(
+    post,
+    path = "/threads/{txid}/{vout}/subscribe",
+    tag = "Subscriptions",
+    params(
+        ("txid" = String, Path, description = "Root transaction ID (hex)"),
+        ("vout" = i32, Path, description = "Root output index"),
+        ("X-API-Key" = String, Header, description = "Client API key (at least 16 characters)")
+    ),
+    responses(
+        (status = 200, description = "Subscribed; existing replies count as read", body = SubscriptionResponse),
+        (status = 401, description = "Missing API key"),
+        (status = 404, description = "Thread not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn subscribe_thread(
+    State(state): State<Arc<AppState>>,
+    Path((txid, vout)): Path<(String, i32)>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let key_hash = api_key_hash(&headers)?;
+    let root_id = thread_root_id(&state, &txid, vout).await?;
+
+    match state.db.subscribe(&key_hash, root_id).await {
+        Ok(subscription) => Ok(Json(subscription)),
+        Err(e) => {
+            error!("Failed to subscribe to thread: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Unsubscribe from a thread
+#[utoipa::path(
+    delete,
+    path = "/threads/{txid}/{vout}/subscribe",
+    tag = "Subscriptions",
+    params(
+        ("txid" = String, Path, description = "Root transaction ID (hex)"),
+        ("vout" = i32, Path, description = "Root output index"),
+        ("X-API-Key" = String, Header, description = "Client API key")
+    ),
+    responses(
+        (status = 204, description = "Unsubscribed"),
+        (status = 401, description = "Missing API key"),
+        (status = 404, description = "Not subscribed to this thread"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn unsubscribe_thread(
+    State(state): State<Arc<AppState>>,
+    Path((txid, vout)): Path<(String, i32)>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let key_hash = api_key_hash(&headers)?;
+    let root_id = thread_root_id(&state, &txid, vout).await?;
+
+    match state.db.unsubscribe(&key_hash, root_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::not_found("Not subscribed to this thread")),
+        Err(e) => {
+            error!("Failed to unsubscribe from thread: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Mark every message currently in a subscribed thread as read
+#[utoipa::path(
+    post,
+    path = "/threads/{txid}/{vout}/read",
+    tag = "Subscriptions",
+    params(
+        ("txid" = String, Path, description = "Root transaction ID (hex)"),
+        ("vout" = i32, Path, description = "Root output index"),
+        ("X-API-Key" = String, Header, description = "Client API key")
+    ),
+    responses(
+        (status = 200, description = "Read marker moved", body = SubscriptionResponse),
+        (status = 401, description = "Missing API key"),
+        (status = 404, description = "Not subscribed to this thread"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn mark_thread_read(
+    State(state): State<Arc<AppState>>,
+    Path((txid, vout)): Path<(String, i32)>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let key_hash = api_key_hash(&headers)?;
+    let root_id = thread_root_id(&state, &txid, vout).await?;
+
+    match state.db.mark_thread_read(&key_hash, root_id).await {
+        Ok(Some(subscription)) => Ok(Json(subscription)),
+        Ok(None) => Err(ApiError::not_found("Not subscribed to this thread")),
+        Err(e) => {
+            error!("Failed to mark thread read: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Unread replies across the subscribed threads
+#[utoipa::path(
+    get,
+    path = "/subscriptions/updates",
+    tag = "Subscriptions",
+    params(
+        ("limit" = Option<i32>, Query, description = "Unread messages per thread (default 20, max 100)"),
+        ("unread_only" = Option<bool>, Query, description = "true: leave out threads without unread messages"),
+        ("X-API-Key" = String, Header, description = "Client API key")
+    ),
+    responses(
+        (status = 200, description = "Subscribed threads, most recent unread activity first", body = Vec<ThreadUpdatesResponse>),
+        (status = 401, description = "Missing API key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_subscription_updates(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<UpdatesParams>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let key_hash = api_key_hash(&headers)?;
+    let limit = params.limit.clamp(1, 100);
+
+    match state
+        .db
+        .subscription_updates(&key_hash, limit, params.unread_only)
+        .await
+    {
+        Ok(updates) => Ok(Json(updates)),
+        Err(e) => {
+            error!("Failed to get subscription updates: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Verification runs included in the availability report
+const AVAILABILITY_REPORT_RUNS: i64 = 30;
+
+/// How many payloads on prunable carriers can still be retrieved
+#[utoipa::path(
+    get,
+    path = "/availability/report",
+    tag = "Availability",
+    responses(
+        (status = 200, description = "Availability per carrier and source, with recent runs", body = AvailabilityReportResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_availability_report(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let carriers = availability::prunable_carriers();
+    let carrier_ids: Vec<i16> = carriers.iter().map(|carrier| *carrier as i16).collect();
+
+    match state
+        .db
+        .get_availability_report(&carrier_ids, AVAILABILITY_REPORT_RUNS)
+        .await
+    {
+        Ok((by_carrier, sources, runs)) => Ok(Json(AvailabilityReportResponse {
+            enabled: state.availability.is_some(),
+            prunable_carriers: carriers.iter().map(|carrier| carrier.to_string()).collect(),
+            carriers: by_carrier,
+            sources,
+            runs,
+        })),
+        Err(e) => {
+            error!("Failed to get availability report: {}", e);
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
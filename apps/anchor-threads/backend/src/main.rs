@@ -2,27 +2,55 @@
 //!
 //! REST API for querying indexed ANCHOR messages.
 
+mod authors;
+mod availability;
 mod config;
 mod db;
+mod decode;
 mod handlers;
 mod models;
+mod preview;
+mod response_cache;
+mod sitemap;
 
+use anchor_api_security::SecurityConfig;
+use anchor_api_snapshot::Snapshots;
 use anyhow::Result;
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use std::time::Duration;
 use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+use crate::authors::AuthorResolver;
+use crate::availability::Availability;
 use crate::config::Config;
 use crate::db::Database;
+use crate::response_cache::ResponseCache;
+use crate::sitemap::{SitemapConfig, Sitemaps};
 
 /// Application state shared across handlers
 pub struct AppState {
     pub db: Database,
+    pub rpc: Arc<bitcoincore_rpc::Client>,
+    pub block_explorer_url: String,
+    pub snapshots: Arc<Snapshots>,
+    pub authors: AuthorResolver,
+    /// Explorer frontend base URL, for links in previews
+    pub public_url: String,
+    /// Base URL this API is reachable at
+    pub api_public_url: String,
+    /// Sitemap and archive generator, when `SITEMAP_DIR` is set
+    pub sitemaps: Option<Arc<Sitemaps>>,
+    /// Payload availability checker, when `AVAILABILITY_INTERVAL_SECS` is set
+    pub availability: Option<Arc<Availability>>,
 }
 
 #[derive(OpenApi)]
@@ -39,11 +67,31 @@ pub struct AppState {
         handlers::get_stats,
         handlers::list_messages,
         handlers::get_message,
+        handlers::get_message_proof,
+        handlers::get_message_preview,
+        handlers::get_message_image,
+        handlers::get_oembed,
+        handlers::get_sitemap_index,
+        handlers::get_sitemap_page,
+        handlers::get_archive_index,
+        handlers::get_archive_month,
+        handlers::get_anchor_filters,
+        handlers::get_transaction_messages,
+        handlers::decode_transaction,
         handlers::list_roots,
         handlers::list_roots_filtered,
         handlers::get_popular_threads,
         handlers::get_thread,
         handlers::get_replies,
+        handlers::get_identity,
+        handlers::get_author,
+        anchor_api_snapshot::create_snapshot,
+        anchor_api_snapshot::release_snapshot,
+        handlers::subscribe_thread,
+        handlers::unsubscribe_thread,
+        handlers::mark_thread_read,
+        handlers::get_subscription_updates,
+        handlers::get_availability_report,
     ),
     components(schemas(
         handlers::HealthResponse,
@@ -51,20 +99,58 @@ pub struct AppState {
         models::AnchorResponse,
         models::StatsResponse,
         models::PopularThreadResponse,
+        models::DecodeResponse,
+        models::InclusionProofResponse,
+        models::IdentityResponse,
+        models::IdentityRotationResponse,
+        models::AuthorProfileResponse,
+        models::AuthorParams,
+        models::MessagePreviewResponse,
+        models::OEmbedResponse,
+        models::OEmbedParams,
+        models::ArchiveIndexResponse,
+        models::ArchiveMonthResponse,
+        models::ArchiveResponse,
+        models::ArchiveEntryResponse,
+        models::AnchorFilterParams,
+        models::AnchorFiltersResponse,
+        models::AnchorFilterResponse,
+        models::DecodedPayload,
+        models::DecodedAnchor,
         models::ListParams,
         models::FilterParams,
+        anchor_api_snapshot::SnapshotResponse,
+        models::SubscriptionResponse,
+        models::ThreadUpdatesResponse,
+        models::UpdatesParams,
+        models::AvailabilityReportResponse,
+        models::AvailabilityCarrierResponse,
+        models::AvailabilitySourceResponse,
+        models::AvailabilityRunResponse,
     )),
     tags(
         (name = "System", description = "System health endpoints"),
         (name = "Statistics", description = "Protocol statistics"),
         (name = "Messages", description = "ANCHOR message operations"),
         (name = "Threads", description = "Thread and reply operations"),
+        (name = "Identities", description = "Identity key rotation chains"),
+        (name = "Authors", description = "Profiles of message creators"),
+        (name = "Previews", description = "OpenGraph and oEmbed link previews"),
+        (name = "Archive", description = "Sitemaps and monthly archives of root messages"),
+        (name = "Filters", description = "Compact filters of ANCHOR activity for wallet rescans"),
+        (name = "Snapshots", description = "Consistent reads across paginated requests"),
+        (name = "Subscriptions", description = "Per-API-key thread subscriptions and read markers"),
+        (name = "Availability", description = "Retrievability of payloads on prunable carriers"),
     )
 )]
 struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
@@ -80,29 +166,139 @@ async fn main() -> Result<()> {
     let db = Database::connect(&config.database_url).await?;
     info!("Connected to database");
 
+    // Connect to Bitcoin Core (used to fetch transactions for /decode)
+    let rpc = Arc::new(bitcoincore_rpc::Client::new(
+        &config.bitcoin_rpc_url,
+        bitcoincore_rpc::Auth::UserPass(
+            config.bitcoin_rpc_user.clone(),
+            config.bitcoin_rpc_password.clone(),
+        ),
+    )?);
+
+    let snapshots = Arc::new(Snapshots::from_env(
+        &config.database_url,
+        "SELECT last_block_height FROM indexer_state WHERE id = 1",
+    ));
+    snapshots.start();
+
+    let authors = AuthorResolver::new(
+        db.clone(),
+        config.domains_url.clone(),
+        Duration::from_secs(config.author_cache_ttl_secs),
+    );
+
+    let sitemaps = match &config.sitemap_dir {
+        Some(dir) => {
+            let sitemaps = Arc::new(Sitemaps::new(
+                db.clone(),
+                SitemapConfig {
+                    dir: dir.clone(),
+                    public_url: config.public_url.clone(),
+                    interval: Duration::from_secs(config.sitemap_interval_secs.max(1)),
+                },
+            )?);
+            sitemaps.start();
+            Some(sitemaps)
+        }
+        None => None,
+    };
+
+    let availability = match &config.availability {
+        Some(availability_config) => {
+            let availability = Arc::new(Availability::new(
+                db.clone(),
+                rpc.clone(),
+                availability_config.clone(),
+            )?);
+            availability.start();
+            Some(availability)
+        }
+        None => None,
+    };
+
     // Create application state
-    let state = Arc::new(AppState { db });
+    let state = Arc::new(AppState {
+        db,
+        rpc,
+        block_explorer_url: config.block_explorer_url.clone(),
+        snapshots,
+        authors,
+        public_url: config.public_url.clone(),
+        api_public_url: config.api_public_url.clone(),
+        sitemaps,
+        availability,
+    });
+
+    // Hot endpoints, served from the response cache when it is enabled
+    let mut hot = Router::new()
+        .route("/stats", get(handlers::get_stats))
+        .route("/roots", get(handlers::list_roots))
+        .route("/roots/filter", get(handlers::list_roots_filtered))
+        .route("/popular", get(handlers::get_popular_threads));
+    if let Some(cache_config) = &config.response_cache {
+        let cache = Arc::new(ResponseCache::new(state.db.clone(), cache_config));
+        cache.start();
+        hot = hot.route_layer(middleware::from_fn_with_state(
+            cache,
+            response_cache::cache_responses,
+        ));
+    }
 
     // Build router
     let app = Router::new()
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .merge(hot)
         .route("/health", get(handlers::health))
-        .route("/stats", get(handlers::get_stats))
         .route("/messages", get(handlers::list_messages))
         .route("/messages/:txid/:vout", get(handlers::get_message))
-        .route("/roots", get(handlers::list_roots))
-        .route("/roots/filter", get(handlers::list_roots_filtered))
-        .route("/popular", get(handlers::get_popular_threads))
+        .route(
+            "/messages/:txid/:vout/proof",
+            get(handlers::get_message_proof),
+        )
+        .route(
+            "/messages/:txid/:vout/preview",
+            get(handlers::get_message_preview),
+        )
+        .route(
+            "/messages/:txid/:vout/image",
+            get(handlers::get_message_image),
+        )
+        .route("/oembed", get(handlers::get_oembed))
+        .route("/sitemap.xml", get(handlers::get_sitemap_index))
+        .route("/sitemaps/:file", get(handlers::get_sitemap_page))
+        .route("/archive", get(handlers::get_archive_index))
+        .route("/archive/:month", get(handlers::get_archive_month))
+        .route("/filters", get(handlers::get_anchor_filters))
+        .route(
+            "/tx/:txid/messages",
+            get(handlers::get_transaction_messages),
+        )
+        .route("/decode/:txid", get(handlers::decode_transaction))
         .route("/threads/:txid/:vout", get(handlers::get_thread))
+        .route(
+            "/threads/:txid/:vout/subscribe",
+            post(handlers::subscribe_thread).delete(handlers::unsubscribe_thread),
+        )
+        .route(
+            "/threads/:txid/:vout/read",
+            post(handlers::mark_thread_read),
+        )
+        .route(
+            "/subscriptions/updates",
+            get(handlers::get_subscription_updates),
+        )
         .route("/replies/:txid/:vout", get(handlers::get_replies))
+        .route("/identities/:pubkey", get(handlers::get_identity))
+        .route("/authors/:address", get(handlers::get_author))
+        .route(
+            "/availability/report",
+            get(handlers::get_availability_report),
+        )
+        .merge(anchor_api_snapshot::routes(state.snapshots.clone()))
         .with_state(state)
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
         .layer(TraceLayer::new_for_http())
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        );
+        .layer(SecurityConfig::from_env().layer());
 
     info!(
         "Swagger UI available at http://localhost:{}/swagger-ui/",
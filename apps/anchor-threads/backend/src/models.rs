@@ -10,13 +10,21 @@ pub struct MessageResponse {
     pub id: i32,
     pub txid: String,
     pub vout: i32,
+    /// Position of this payload among the ANCHOR messages in the transaction
+    pub payload_index: i16,
     pub block_height: Option<i32>,
     pub kind: i16,
     pub kind_name: String,
+    /// Kind is not in the kind registry; the body is the raw payload
+    pub kind_unknown: bool,
     pub carrier: i16,
     pub carrier_name: String,
     pub body_hex: String,
     pub body_text: Option<String>,
+    /// MIME type of a Generic message's content, from its envelope
+    pub content_type: Option<String>,
+    /// ISO 639-3 language of a Text message, when the indexer detected it
+    pub lang: Option<String>,
     pub anchors: Vec<AnchorResponse>,
     pub reply_count: i64,
     pub created_at: DateTime<Utc>,
@@ -102,6 +110,348 @@ pub struct PopularThreadResponse {
     pub total_thread_messages: i64,
 }
 
+/// Decoded breakdown of an arbitrary transaction
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DecodeResponse {
+    pub txid: String,
+    /// Whether the transaction is already indexed
+    pub indexed: bool,
+    pub payloads: Vec<DecodedPayload>,
+}
+
+/// One ANCHOR payload found in a transaction
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DecodedPayload {
+    pub carrier: i16,
+    pub carrier_name: String,
+    /// Where the payload was found: `output:N`, `input:N` or `transaction`
+    pub location: String,
+    pub kind: i16,
+    pub kind_name: String,
+    pub anchors: Vec<DecodedAnchor>,
+    pub body_size: usize,
+    /// First bytes of the body, hex encoded
+    pub body_preview_hex: String,
+    /// First characters of the body, if it is UTF-8
+    pub body_preview_text: Option<String>,
+    /// Kind-specific fields, for kinds with a known spec
+    #[schema(value_type = Option<Object>)]
+    pub fields: Option<serde_json::Value>,
+    /// Why the body could not be decoded as its kind's spec
+    pub fields_error: Option<String>,
+    /// Re-encoding the payload reproduces the on-chain bytes
+    pub roundtrip: bool,
+    /// Other carriers that read this location differently
+    pub conflicts: Vec<String>,
+}
+
+/// An anchor resolved against indexed messages
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DecodedAnchor {
+    pub txid_prefix: String,
+    pub vout: i16,
+    /// Indexed transactions whose txid starts with the prefix
+    pub candidates: Vec<String>,
+}
+
+/// Merkle proof that a message's transaction is in a block
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct InclusionProofResponse {
+    pub txid: String,
+    pub vout: i32,
+    pub block_hash: String,
+    pub block_height: Option<i32>,
+    /// Serialized 80-byte block header, hex encoded
+    pub header_hex: String,
+    /// Position of the transaction in the block
+    pub index: u32,
+    /// Sibling hashes from the transaction up to the merkle root
+    pub branch: Vec<String>,
+    /// Link to the transaction on the block explorer
+    pub explorer_url: String,
+}
+
+/// A chain of keys belonging to one author
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IdentityResponse {
+    /// First key of the identity, hex encoded
+    pub root: String,
+    /// Key currently holding the identity
+    pub current_pubkey: String,
+    /// Latest address announced by a rotation
+    pub current_address: Option<String>,
+    /// Rotations in chain order
+    pub rotations: Vec<IdentityRotationResponse>,
+}
+
+/// One hand-over from an old key to a new key
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IdentityRotationResponse {
+    pub txid: String,
+    pub vout: i32,
+    pub old_pubkey: String,
+    pub new_pubkey: String,
+    pub new_address: Option<String>,
+    pub block_height: Option<i32>,
+}
+
+/// Everything the explorer knows about a creator address
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuthorProfileResponse {
+    pub address: String,
+    /// Newest domain the address owns (reverse resolution)
+    pub domain: Option<String>,
+    /// Every domain the address owns, newest first
+    pub domains: Vec<String>,
+    /// Nostr key published under `domain` (Selfie Records)
+    pub nostr_pubkey: Option<String>,
+    pub message_count: i64,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Newest messages first
+    pub recent_messages: Vec<MessageResponse>,
+}
+
+/// Query parameters for author profiles
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AuthorParams {
+    /// Recent messages to include (default 10, max 50)
+    #[serde(default = "default_recent")]
+    pub recent: i32,
+}
+
+fn default_recent() -> i32 {
+    10
+}
+
+/// OpenGraph-style description of a message, for link previews
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MessagePreviewResponse {
+    pub txid: String,
+    pub vout: i32,
+    /// `og:title`: start of the body, or the kind and txid
+    pub title: String,
+    /// `og:description`
+    pub description: String,
+    /// `og:url`: the message on the explorer frontend
+    pub url: String,
+    /// `og:site_name`
+    pub site_name: String,
+    pub kind_name: String,
+    /// Newest domain of the creator (reverse resolution)
+    pub author_name: Option<String>,
+    pub creator_address: Option<String>,
+    /// `og:image`, for Image messages
+    pub image_url: Option<String>,
+    pub image_type: Option<String>,
+    pub image_width: Option<u32>,
+    pub image_height: Option<u32>,
+    pub block_height: Option<i32>,
+    pub reply_count: i64,
+    pub created_at: DateTime<Utc>,
+    /// oEmbed endpoint for the message, for `<link rel="alternate">` discovery
+    pub oembed_url: String,
+}
+
+/// oEmbed 1.0 response
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OEmbedResponse {
+    /// `photo` for Image messages, `rich` otherwise
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub version: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    pub provider_name: String,
+    pub provider_url: String,
+    /// Image URL, for `photo`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Embeddable card, for `rich`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_height: Option<u32>,
+}
+
+/// oEmbed request parameters
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct OEmbedParams {
+    /// Message or thread URL on the explorer frontend
+    pub url: String,
+    pub maxwidth: Option<u32>,
+    pub maxheight: Option<u32>,
+    /// Only `json` is supported
+    pub format: Option<String>,
+}
+
+/// A month of root messages in the archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ArchiveMonthResponse {
+    /// `YYYY-MM`, UTC
+    pub month: String,
+    pub roots: i64,
+    /// Highest root message id, which changes whenever the month does
+    pub max_id: i32,
+    pub last_modified: DateTime<Utc>,
+}
+
+/// Months of the archive, `archive/index.json`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ArchiveIndexResponse {
+    /// Indexed height the archive was generated at
+    pub block_height: i32,
+    pub generated_at: DateTime<Utc>,
+    /// Oldest month first
+    pub months: Vec<ArchiveMonthResponse>,
+}
+
+/// Root messages of one month, `archive/YYYY-MM.json`
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ArchiveResponse {
+    pub month: String,
+    pub total: i64,
+    pub generated_at: DateTime<Utc>,
+    /// Oldest first
+    pub roots: Vec<ArchiveEntryResponse>,
+}
+
+/// One root message in a monthly archive
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ArchiveEntryResponse {
+    pub txid: String,
+    pub vout: i32,
+    pub kind: i16,
+    pub kind_name: String,
+    /// Start of the body, for text messages
+    pub title: Option<String>,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    /// Thread page on the explorer frontend
+    pub url: String,
+}
+
+/// Range of ANCHOR activity filters to return
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct AnchorFilterParams {
+    /// First block height
+    pub from: i32,
+    /// Filters to return (default and max 1000)
+    pub count: Option<i32>,
+}
+
+/// ANCHOR activity filters of a run of blocks
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnchorFiltersResponse {
+    /// Highest block with a filter
+    pub tip_height: Option<i32>,
+    /// Header of the filter before `from`, hex, when there is one
+    pub previous_header: Option<String>,
+    /// Lowest height first
+    pub filters: Vec<AnchorFilterResponse>,
+}
+
+/// Compact filter of one block's ANCHOR transactions
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AnchorFilterResponse {
+    pub block_height: i32,
+    pub block_hash: String,
+    /// BIP-158 Golomb-coded set, hex
+    pub filter: String,
+    /// BIP-157 style filter header, hex, in the same byte order as block hashes
+    pub header: String,
+}
+
+/// A thread subscription of the calling API key
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SubscriptionResponse {
+    /// Root of the subscribed thread
+    pub txid: String,
+    pub vout: i32,
+    /// Highest message id in the thread marked as read
+    pub last_read_message_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub read_at: DateTime<Utc>,
+}
+
+/// Unread replies in one subscribed thread
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ThreadUpdatesResponse {
+    pub root: MessageResponse,
+    pub last_read_message_id: i32,
+    pub unread_count: i64,
+    /// Oldest unread messages first, up to `limit`
+    pub unread: Vec<MessageResponse>,
+}
+
+/// Query parameters for subscription updates
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdatesParams {
+    /// Unread messages returned per thread (default 20, max 100)
+    #[serde(default = "default_per_page")]
+    pub limit: i32,
+    /// `true` leaves out threads without unread messages
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+/// Durability of payloads on prunable carriers
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AvailabilityReportResponse {
+    /// The periodic check is running
+    pub enabled: bool,
+    /// Carriers whose data nodes may prune
+    pub prunable_carriers: Vec<String>,
+    /// Totals per carrier; a message counts as available while any source
+    /// still serves it
+    pub carriers: Vec<AvailabilityCarrierResponse>,
+    /// Totals per node checked
+    pub sources: Vec<AvailabilitySourceResponse>,
+    /// Most recent verification runs, newest first
+    pub runs: Vec<AvailabilityRunResponse>,
+}
+
+/// Availability of one carrier's confirmed messages
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AvailabilityCarrierResponse {
+    pub carrier: i16,
+    pub carrier_name: String,
+    pub messages: i64,
+    pub checked: i64,
+    pub available: i64,
+    pub unavailable: i64,
+    /// Lowest block height of an unavailable message
+    pub lowest_unavailable_height: Option<i32>,
+}
+
+/// Availability of payloads at one node
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AvailabilitySourceResponse {
+    /// `node`, or a peer's RPC URL
+    pub source: String,
+    pub checked: i64,
+    pub available: i64,
+    pub unavailable: i64,
+    pub last_checked_at: Option<DateTime<Utc>>,
+}
+
+/// One pass of the availability check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AvailabilityRunResponse {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub checked: i32,
+    pub available: i32,
+    pub unavailable: i32,
+}
+
 /// Query parameters for listing messages
 #[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct ListParams {
@@ -110,6 +460,10 @@ pub struct ListParams {
     #[serde(default = "default_per_page")]
     pub per_page: i32,
     pub kind: Option<i16>,
+    /// `true` lists only messages with unregistered kinds ("other")
+    pub kind_unknown: Option<bool>,
+    /// Filter by language, ISO 639-3 codes separated by commas (e.g. `eng,spa`)
+    pub lang: Option<String>,
 }
 
 /// Advanced filter parameters for threads/messages
@@ -129,6 +483,8 @@ pub struct FilterParams {
     pub block_max: Option<i32>,
     /// Filter by message kind (0=Generic, 1=Text)
     pub kind: Option<i16>,
+    /// `true` lists only messages with unregistered kinds ("other")
+    pub kind_unknown: Option<bool>,
     /// Filter by text content (partial match, case insensitive)
     pub text: Option<String>,
     /// Filter by minimum timestamp (ISO 8601)
@@ -145,6 +501,8 @@ pub struct FilterParams {
     pub sort: Option<String>,
     /// Filter by carrier type (0=op_return, 1=inscription, 2=stamps, 3=annex, 4=witness)
     pub carrier: Option<i16>,
+    /// Filter by language, ISO 639-3 codes separated by commas (e.g. `eng,spa`)
+    pub lang: Option<String>,
 }
 
 fn default_page() -> i32 {
@@ -155,14 +513,32 @@ fn default_per_page() -> i32 {
     20
 }
 
+/// Language codes of a `?lang=` filter, lowercased
+fn lang_codes(lang: Option<&str>) -> Option<Vec<String>> {
+    let codes: Vec<String> = lang?
+        .split(',')
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+        .collect();
+    (!codes.is_empty()).then_some(codes)
+}
+
 impl ListParams {
     pub fn offset(&self) -> i32 {
         (self.page - 1) * self.per_page
     }
+
+    pub fn langs(&self) -> Option<Vec<String>> {
+        lang_codes(self.lang.as_deref())
+    }
 }
 
 impl FilterParams {
     pub fn offset(&self) -> i32 {
         (self.page - 1) * self.per_page
     }
+
+    pub fn langs(&self) -> Option<Vec<String>> {
+        lang_codes(self.lang.as_deref())
+    }
 }
@@ -0,0 +1,114 @@
+//! Link previews
+//!
+//! `GET /messages/:txid/:vout/preview` describes a message the way chat apps
+//! unfurl links: an OpenGraph-style title and description taken from the
+//! body, the author's domain through reverse resolution and, for Image
+//! messages, a link to the image served by `GET /messages/:txid/:vout/image`.
+//! `GET /oembed` returns the same for a message or thread URL on the
+//! explorer frontend, as an oEmbed 1.0 object.
+
+use std::io::Cursor;
+
+use image::ImageReader;
+
+/// Provider name in previews
+pub const SITE_NAME: &str = "ANCHOR Threads";
+
+/// Characters of the body used as the title
+pub const TITLE_CHARS: usize = 70;
+
+/// Characters of the body used as the description
+pub const DESCRIPTION_CHARS: usize = 200;
+
+/// Size of the `rich` oEmbed card, before `maxwidth` and `maxheight`
+pub const CARD_WIDTH: u32 = 500;
+pub const CARD_HEIGHT: u32 = 180;
+
+/// Kind id of Image messages
+pub const IMAGE_KIND: i16 = 4;
+
+/// Text collapsed onto one line and cut to `max` characters, with an
+/// ellipsis when something was cut
+pub fn excerpt(text: &str, max: usize) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= max {
+        return line;
+    }
+    let cut: String = line.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// First characters of a txid, for titles
+pub fn short_txid(txid: &str) -> &str {
+    &txid[..txid.len().min(12)]
+}
+
+/// MIME type and pixel size of an image body
+pub fn image_info(body: &[u8]) -> Option<(&'static str, u32, u32)> {
+    let reader = ImageReader::new(Cursor::new(body))
+        .with_guessed_format()
+        .ok()?;
+    let mime = reader.format()?.to_mime_type();
+    let (width, height) = reader.into_dimensions().ok()?;
+    Some((mime, width, height))
+}
+
+/// Txid and vout of a message or thread URL on the explorer frontend,
+/// e.g. `https://threads.example/message/<txid>/<vout>`
+pub fn parse_message_url(url: &str) -> Option<(String, i32)> {
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = path.split(['?', '#']).next()?;
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let [.., "message" | "thread", txid, vout] = segments.as_slice() else {
+        return None;
+    };
+    if txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((txid.to_lowercase(), vout.parse().ok()?))
+}
+
+/// `(width, height)` scaled down to fit the oEmbed consumer's limits
+pub fn fit(width: u32, height: u32, max_width: Option<u32>, max_height: Option<u32>) -> (u32, u32) {
+    let mut scale = 1.0_f64;
+    if let Some(max) = max_width.filter(|&max| max > 0 && max < width) {
+        scale = scale.min(max as f64 / width as f64);
+    }
+    if let Some(max) = max_height.filter(|&max| max > 0 && max < height) {
+        scale = scale.min(max as f64 / height as f64);
+    }
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Static HTML card of a `rich` oEmbed response
+pub fn card_html(title: &str, description: &str, author: Option<&str>, url: &str) -> String {
+    let author = author
+        .map(|name| format!("<footer>— {}</footer>", escape_html(name)))
+        .unwrap_or_default();
+    format!(
+        r#"<blockquote class="anchor-message"><p><strong>{}</strong></p><p>{}</p>{}<a href="{}">View on {}</a></blockquote>"#,
+        escape_html(title),
+        escape_html(description),
+        author,
+        escape_html(url),
+        SITE_NAME,
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
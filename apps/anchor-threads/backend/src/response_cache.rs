@@ -0,0 +1,274 @@
+//! Response cache for the hot endpoints
+//!
+//! `/stats`, `/popular`, `/roots` and `/roots/filter` are the first requests
+//! of every explorer page and only change when the indexer moves, so their
+//! responses are cached in memory, keyed by path and query. The cache is
+//! dropped whenever the indexed height changes (a new block or a rollback),
+//! and entries also expire after `RESPONSE_CACHE_TTL_SECS`. Concurrent misses
+//! for the same key wait for one database query instead of each running it.
+//!
+//! Cached responses carry an `ETag`; a request whose `If-None-Match` names it
+//! gets `304 Not Modified` without a body. Requests with `?snapshot=` skip
+//! the cache, as do responses other than `200 OK` and bodies over 4 MB,
+//! which are streamed through as they are.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures_util::{stream, StreamExt};
+use moka::future::Cache;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+
+use crate::db::Database;
+
+/// How often the indexed height is checked
+const HEIGHT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Largest response body that is cached
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Cache limits
+#[derive(Debug, Clone)]
+pub struct ResponseCacheConfig {
+    /// Seconds an entry is served at most, even without a new block
+    pub ttl: Duration,
+    /// Total size of the cached bodies, in bytes
+    pub max_bytes: u64,
+}
+
+/// A `200 OK` response as cached
+#[derive(Clone)]
+struct CachedResponse {
+    content_type: Option<HeaderValue>,
+    etag: HeaderValue,
+    body: Bytes,
+    /// Invalidation generation the response was computed in
+    generation: u64,
+}
+
+/// Any other response, handed to the requests that waited on it
+#[derive(Clone)]
+enum Uncached {
+    Response {
+        status: StatusCode,
+        headers: HeaderMap,
+        body: Bytes,
+    },
+    /// Too large to buffer: each request runs the handler itself
+    Oversized,
+}
+
+/// A response body read up to `MAX_BODY_BYTES`
+enum Buffered {
+    Complete(Bytes),
+    /// The bytes read so far followed by the rest of the stream
+    Oversized(Body),
+}
+
+/// Cached responses, dropped when the indexed height changes
+pub struct ResponseCache {
+    db: Database,
+    entries: Cache<String, CachedResponse>,
+    /// Bumped on every invalidation, so a response computed across one isn't
+    /// kept
+    generation: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new(db: Database, config: &ResponseCacheConfig) -> Self {
+        Self {
+            db,
+            entries: Cache::builder()
+                .max_capacity(config.max_bytes)
+                .weigher(|key: &String, response: &CachedResponse| {
+                    (key.len() + response.body.len())
+                        .try_into()
+                        .unwrap_or(u32::MAX)
+                })
+                .time_to_live(config.ttl)
+                .build(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Drop the cache whenever the indexed height changes
+    pub fn start(self: &Arc<Self>) {
+        info!(
+            "Caching hot responses for up to {}s, {} MB",
+            self.entries
+                .policy()
+                .time_to_live()
+                .unwrap_or_default()
+                .as_secs(),
+            self.entries.policy().max_capacity().unwrap_or_default() / (1024 * 1024)
+        );
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(HEIGHT_POLL_INTERVAL);
+            let mut last_height = None;
+            loop {
+                ticker.tick().await;
+                match cache.db.get_indexed_height().await {
+                    Ok(height) if last_height != Some(height) => {
+                        if last_height.is_some() {
+                            debug!("Indexed height now {}, dropping cached responses", height);
+                        }
+                        cache.invalidate();
+                        last_height = Some(height);
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to read indexed height: {}", e),
+                }
+            }
+        });
+    }
+
+    fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.entries.invalidate_all();
+    }
+}
+
+/// Middleware serving the routes it wraps from the cache
+pub async fn cache_responses(
+    State(cache): State<Arc<ResponseCache>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request.uri();
+    let uses_snapshot = uri
+        .query()
+        .is_some_and(|query| query.split('&').any(|pair| pair.starts_with("snapshot=")));
+    if uses_snapshot {
+        return next.run(request).await;
+    }
+    let key = uri
+        .path_and_query()
+        .map_or_else(|| uri.path().to_string(), |pq| pq.as_str().to_string());
+    let if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+
+    // Taken by the request that computes the entry; the others keep theirs
+    // in case the response turns out too large to share
+    let mut pending = Some((request, next));
+    let mut oversized = None;
+
+    let generation = cache.generation.load(Ordering::SeqCst);
+    let result = cache
+        .entries
+        .try_get_with(key.clone(), async {
+            let (request, next) = pending.take().expect("init runs at most once");
+            let response = next.run(request).await;
+            let (parts, body) = response.into_parts();
+            let body = match buffer_body(body).await {
+                Ok(Buffered::Complete(body)) => body,
+                Ok(Buffered::Oversized(body)) => {
+                    debug!("Response for {} is too large to cache", key);
+                    oversized = Some(Response::from_parts(parts, body));
+                    return Err(Uncached::Oversized);
+                }
+                Err(e) => {
+                    warn!("Failed to buffer response for {}: {}", key, e);
+                    return Err(Uncached::Response {
+                        status: StatusCode::INTERNAL_SERVER_ERROR,
+                        headers: HeaderMap::new(),
+                        body: Bytes::new(),
+                    });
+                }
+            };
+            if parts.status != StatusCode::OK {
+                return Err(Uncached::Response {
+                    status: parts.status,
+                    headers: parts.headers,
+                    body,
+                });
+            }
+            Ok(CachedResponse {
+                content_type: parts.headers.get(CONTENT_TYPE).cloned(),
+                etag: etag(&body),
+                body,
+                generation,
+            })
+        })
+        .await;
+
+    let cached = match result {
+        Ok(cached) => cached,
+        Err(uncached) => match &*uncached {
+            Uncached::Response {
+                status,
+                headers,
+                body,
+            } => {
+                let mut response = Response::new(Body::from(body.clone()));
+                *response.status_mut() = *status;
+                *response.headers_mut() = headers.clone();
+                return response;
+            }
+            Uncached::Oversized => {
+                if let Some(response) = oversized {
+                    return response;
+                }
+                let (request, next) = pending.expect("waiting request still has its own");
+                return next.run(request).await;
+            }
+        },
+    };
+    if cached.generation != cache.generation.load(Ordering::SeqCst) {
+        // Computed before the last invalidation: serve it once, don't keep it
+        cache.entries.invalidate(&key).await;
+    }
+
+    if if_none_match
+        .as_ref()
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &cached.etag))
+    {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, cached.etag)]).into_response();
+    }
+    let mut response = Response::new(Body::from(cached.body));
+    if let Some(content_type) = cached.content_type {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response.headers_mut().insert(ETAG, cached.etag);
+    response
+}
+
+/// Read a response body, up to `MAX_BODY_BYTES`
+async fn buffer_body(body: Body) -> Result<Buffered, axum::Error> {
+    let mut chunks = Vec::new();
+    let mut len = 0;
+    let mut data = body.into_data_stream();
+    while let Some(chunk) = data.next().await {
+        let chunk = chunk?;
+        len += chunk.len();
+        chunks.push(chunk);
+        if len > MAX_BODY_BYTES {
+            let read = stream::iter(chunks.into_iter().map(Ok::<_, axum::Error>));
+            return Ok(Buffered::Oversized(Body::from_stream(read.chain(data))));
+        }
+    }
+    Ok(Buffered::Complete(chunks.concat().into()))
+}
+
+/// Strong ETag of a response body
+fn etag(body: &[u8]) -> HeaderValue {
+    let digest = Sha256::digest(body);
+    HeaderValue::from_str(&format!("\"{}\"", hex::encode(&digest[..16])))
+        .expect("hex is a valid header value")
+}
+
+/// Whether an `If-None-Match` list names `etag` (weak comparison)
+fn etag_matches(if_none_match: &str, etag: &HeaderValue) -> bool {
+    let etag = etag.to_str().unwrap_or_default();
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/")
+    })
+}
@@ -0,0 +1,349 @@
+//! Sitemaps and monthly archives
+//!
+//! Public explorer deployments are client-rendered, so search engines only
+//! find threads through sitemaps. With `SITEMAP_DIR` set, a background task
+//! follows the indexer and writes:
+//!
+//! - `sitemap.xml`: sitemap index of the pages below
+//! - `sitemaps/YYYY-MM-N.xml`: thread URLs of the root messages created in a
+//!   month, `URLS_PER_SITEMAP` per page
+//! - `archive/index.json`: months with their root counts
+//! - `archive/YYYY-MM.json`: root messages of a month
+//!
+//! Generation is incremental: each time the indexed height moves, months
+//! are compared by root count and highest root id against `archive/index.json`
+//! and only changed months are rewritten. Files are written then renamed, so
+//! they can be served statically while the task runs.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Months, NaiveDate, SecondsFormat, Utc};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::db::{kind_to_name, ArchiveRoot, Database};
+use crate::models::{
+    ArchiveEntryResponse, ArchiveIndexResponse, ArchiveMonthResponse, ArchiveResponse,
+};
+use crate::preview;
+
+/// URLs per sitemap page (the sitemap protocol allows 50,000)
+pub const URLS_PER_SITEMAP: usize = 50_000;
+
+/// Sitemap and archive generation settings
+#[derive(Debug, Clone)]
+pub struct SitemapConfig {
+    /// Directory the files are written to
+    pub dir: PathBuf,
+    /// Explorer frontend base URL, for thread and sitemap page links
+    pub public_url: String,
+    /// Seconds between checks of the indexed height
+    pub interval: Duration,
+}
+
+/// What the files on disk were generated from
+#[derive(Default)]
+struct Generated {
+    block_height: Option<i32>,
+    months: HashMap<String, ArchiveMonthResponse>,
+}
+
+/// Incremental sitemap and archive generator
+pub struct Sitemaps {
+    db: Database,
+    config: SitemapConfig,
+    generated: Mutex<Generated>,
+}
+
+impl Sitemaps {
+    /// Create the generator, picking up the archive already on disk
+    pub fn new(db: Database, config: SitemapConfig) -> Result<Self> {
+        for sub in ["sitemaps", "archive"] {
+            fs::create_dir_all(config.dir.join(sub)).with_context(|| {
+                format!(
+                    "Failed to create sitemap directory {}",
+                    config.dir.display()
+                )
+            })?;
+        }
+
+        let months = fs::read(config.dir.join("archive/index.json"))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<ArchiveIndexResponse>(&bytes).ok())
+            .map(|index| {
+                index
+                    .months
+                    .into_iter()
+                    .map(|month| (month.month.clone(), month))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            db,
+            config,
+            generated: Mutex::new(Generated {
+                block_height: None,
+                months,
+            }),
+        })
+    }
+
+    /// Regenerate in the background whenever the indexer moves
+    pub fn start(self: &Arc<Self>) {
+        info!(
+            "Sitemap generation enabled in {} (every {}s)",
+            self.config.dir.display(),
+            self.config.interval.as_secs()
+        );
+        let sitemaps = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sitemaps.config.interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = sitemaps.refresh().await {
+                    warn!("Failed to regenerate sitemaps: {:#}", e);
+                }
+            }
+        });
+    }
+
+    /// Contents of a generated file, by path relative to `SITEMAP_DIR`
+    pub async fn read(&self, relative: &str) -> Option<Vec<u8>> {
+        tokio::fs::read(self.config.dir.join(relative)).await.ok()
+    }
+
+    /// Rewrite the months that changed since the last run
+    async fn refresh(&self) -> Result<()> {
+        let mut generated = self.generated.lock().await;
+        let block_height = self.db.get_indexed_height().await?;
+        if generated.block_height == Some(block_height) {
+            return Ok(());
+        }
+
+        let months = self.db.get_archive_months().await?;
+        let mut changed = 0;
+        for month in &months {
+            let previous = generated.months.get(&month.month);
+            if previous == Some(month) {
+                continue;
+            }
+            let stale_pages = previous.map_or(0, |previous| page_count(previous.roots));
+            self.write_month(month, stale_pages).await?;
+            changed += 1;
+        }
+
+        let current: HashMap<String, ArchiveMonthResponse> = months
+            .iter()
+            .map(|month| (month.month.clone(), month.clone()))
+            .collect();
+        for (name, previous) in &generated.months {
+            if !current.contains_key(name) {
+                self.remove_month(name, page_count(previous.roots));
+                changed += 1;
+            }
+        }
+
+        if changed > 0 || generated.block_height.is_none() {
+            self.write_index(block_height, &months)?;
+        }
+        if changed > 0 {
+            info!(
+                "Regenerated sitemaps for {} month(s) at height {}",
+                changed, block_height
+            );
+        }
+
+        generated.block_height = Some(block_height);
+        generated.months = current;
+        Ok(())
+    }
+
+    /// Write the archive and sitemap pages of one month, removing pages
+    /// beyond the new count
+    async fn write_month(&self, month: &ArchiveMonthResponse, stale_pages: usize) -> Result<()> {
+        let (from, to) = month_bounds(&month.month)
+            .with_context(|| format!("Invalid archive month {}", month.month))?;
+        let roots = self.db.get_archive_roots(from, to).await?;
+        let entries: Vec<ArchiveEntryResponse> =
+            roots.into_iter().map(|root| self.entry(root)).collect();
+
+        let pages = page_count(entries.len() as i64);
+        for (page, chunk) in entries.chunks(URLS_PER_SITEMAP).enumerate() {
+            write_atomic(
+                &self.sitemap_path(&month.month, page + 1),
+                url_set(chunk).as_bytes(),
+            )?;
+        }
+        for page in pages..stale_pages {
+            let _ = fs::remove_file(self.sitemap_path(&month.month, page + 1));
+        }
+
+        let archive = ArchiveResponse {
+            month: month.month.clone(),
+            total: entries.len() as i64,
+            generated_at: Utc::now(),
+            roots: entries,
+        };
+        write_atomic(
+            &self.archive_path(&month.month),
+            &serde_json::to_vec(&archive)?,
+        )
+    }
+
+    fn remove_month(&self, month: &str, pages: usize) {
+        let _ = fs::remove_file(self.archive_path(month));
+        for page in 0..pages {
+            let _ = fs::remove_file(self.sitemap_path(month, page + 1));
+        }
+    }
+
+    /// Write `sitemap.xml` and `archive/index.json`
+    fn write_index(&self, block_height: i32, months: &[ArchiveMonthResponse]) -> Result<()> {
+        let base = self.config.public_url.trim_end_matches('/');
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for month in months {
+            for page in 0..page_count(month.roots) {
+                xml.push_str(&format!(
+                    "  <sitemap><loc>{}/sitemaps/{}-{}.xml</loc><lastmod>{}</lastmod></sitemap>\n",
+                    escape_xml(base),
+                    month.month,
+                    page + 1,
+                    w3c_datetime(month.last_modified)
+                ));
+            }
+        }
+        xml.push_str("</sitemapindex>\n");
+        write_atomic(&self.config.dir.join("sitemap.xml"), xml.as_bytes())?;
+
+        let index = ArchiveIndexResponse {
+            block_height,
+            generated_at: Utc::now(),
+            months: months.to_vec(),
+        };
+        write_atomic(
+            &self.config.dir.join("archive/index.json"),
+            &serde_json::to_vec(&index)?,
+        )
+    }
+
+    fn entry(&self, root: ArchiveRoot) -> ArchiveEntryResponse {
+        let mut txid = root.txid;
+        txid.reverse();
+        let txid = hex::encode(txid);
+        let title = match String::from_utf8(root.body) {
+            Ok(text) if root.kind != preview::IMAGE_KIND && !text.trim().is_empty() => {
+                Some(preview::excerpt(&text, preview::TITLE_CHARS))
+            }
+            _ => None,
+        };
+        ArchiveEntryResponse {
+            url: format!(
+                "{}/thread/{}/{}",
+                self.config.public_url.trim_end_matches('/'),
+                txid,
+                root.vout
+            ),
+            txid,
+            vout: root.vout,
+            kind: root.kind,
+            kind_name: kind_to_name(root.kind),
+            title,
+            block_height: root.block_height,
+            created_at: root.created_at,
+        }
+    }
+
+    fn sitemap_path(&self, month: &str, page: usize) -> PathBuf {
+        self.config
+            .dir
+            .join("sitemaps")
+            .join(format!("{}-{}.xml", month, page))
+    }
+
+    fn archive_path(&self, month: &str) -> PathBuf {
+        self.config
+            .dir
+            .join("archive")
+            .join(format!("{}.json", month))
+    }
+}
+
+/// Whether `name` is a `YYYY-MM` month
+pub fn is_month(name: &str) -> bool {
+    month_bounds(name).is_some()
+}
+
+/// Whether `name` is a `YYYY-MM-N.xml` sitemap page
+pub fn is_sitemap_page(name: &str) -> bool {
+    let Some(stem) = name.strip_suffix(".xml") else {
+        return false;
+    };
+    match stem.rsplit_once('-') {
+        Some((month, page)) => {
+            is_month(month) && !page.is_empty() && page.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Start of the month and of the next one, UTC
+fn month_bounds(month: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if month.len() != 7 {
+        return None;
+    }
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let end = start.checked_add_months(Months::new(1))?;
+    Some((
+        start.and_hms_opt(0, 0, 0)?.and_utc(),
+        end.and_hms_opt(0, 0, 0)?.and_utc(),
+    ))
+}
+
+fn page_count(urls: i64) -> usize {
+    (urls.max(0) as usize).div_ceil(URLS_PER_SITEMAP)
+}
+
+fn url_set(entries: &[ArchiveEntryResponse]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for entry in entries {
+        xml.push_str(&format!(
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>\n",
+            escape_xml(&entry.url),
+            w3c_datetime(entry.created_at)
+        ));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn w3c_datetime(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Write then rename, so readers never see a partial file
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)
+        .and_then(|_| fs::rename(&tmp, path))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
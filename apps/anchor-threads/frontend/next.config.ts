@@ -1,7 +1,18 @@
 import type { NextConfig } from 'next';
 
+// Sitemaps are generated by the backend; serve them from the explorer's own
+// origin, where search engines expect them
+const API_URL =
+  process.env.API_INTERNAL_URL || process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3101';
+
 const nextConfig: NextConfig = {
   output: 'standalone',
+  async rewrites() {
+    return [
+      { source: '/sitemap.xml', destination: `${API_URL}/sitemap.xml` },
+      { source: '/sitemaps/:file', destination: `${API_URL}/sitemaps/:file` },
+    ];
+  },
 };
 
 export default nextConfig;
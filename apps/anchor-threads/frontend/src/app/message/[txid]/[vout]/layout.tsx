@@ -0,0 +1,72 @@
+import type { Metadata } from 'next';
+
+// Server-side requests go to the backend directly when it isn't reachable at
+// the public URL (e.g. inside Docker)
+const API_URL =
+  process.env.API_INTERNAL_URL || process.env.NEXT_PUBLIC_API_URL || 'http://localhost:3101';
+
+interface MessagePreview {
+  title: string;
+  description: string;
+  url: string;
+  site_name: string;
+  author_name: string | null;
+  image_url: string | null;
+  image_width: number | null;
+  image_height: number | null;
+  oembed_url: string;
+}
+
+export async function generateMetadata({
+  params,
+}: {
+  params: Promise<{ txid: string; vout: string }>;
+}): Promise<Metadata> {
+  const { txid, vout } = await params;
+  try {
+    const res = await fetch(`${API_URL}/messages/${txid}/${vout}/preview`, {
+      next: { revalidate: 60 },
+    });
+    if (!res.ok) return {};
+    const preview: MessagePreview = await res.json();
+    const images = preview.image_url
+      ? [
+          {
+            url: preview.image_url,
+            width: preview.image_width ?? undefined,
+            height: preview.image_height ?? undefined,
+          },
+        ]
+      : undefined;
+
+    return {
+      title: `${preview.title} | ${preview.site_name}`,
+      description: preview.description,
+      authors: preview.author_name ? [{ name: preview.author_name }] : undefined,
+      alternates: {
+        canonical: preview.url,
+        types: { 'application/json+oembed': preview.oembed_url },
+      },
+      openGraph: {
+        type: 'article',
+        title: preview.title,
+        description: preview.description,
+        url: preview.url,
+        siteName: preview.site_name,
+        images,
+      },
+      twitter: {
+        card: images ? 'summary_large_image' : 'summary',
+        title: preview.title,
+        description: preview.description,
+        images: images?.map((image) => image.url),
+      },
+    };
+  } catch {
+    return {};
+  }
+}
+
+export default function MessageLayout({ children }: { children: React.ReactNode }) {
+  return children;
+}
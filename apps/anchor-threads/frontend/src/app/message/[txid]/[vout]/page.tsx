@@ -8,6 +8,8 @@ import {
   truncateTxid,
   formatBlockHeight,
   hexToImageDataUrl,
+  isJsonMessage,
+  displayBodyText,
   CARRIER_INFO,
   getExplorerTxUrl,
   BTC_EXPLORER_URL,
@@ -255,6 +257,13 @@ export default function MessagePage() {
                 </a>
               </div>
             </div>
+          ) : hasText && isJsonMessage(message) ? (
+            <div>
+              <p className="text-sm text-muted-foreground mb-2">{message.content_type}:</p>
+              <pre className="bg-secondary p-4 rounded-lg overflow-x-auto font-mono text-sm text-foreground">
+                {displayBodyText(message)}
+              </pre>
+            </div>
           ) : hasText ? (
             <p className="text-lg whitespace-pre-wrap break-words text-foreground">
               {message.body_text}
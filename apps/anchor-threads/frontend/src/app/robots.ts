@@ -0,0 +1,9 @@
+import type { MetadataRoute } from 'next';
+
+export default function robots(): MetadataRoute.Robots {
+  const base = process.env.PUBLIC_URL || 'http://localhost:3100';
+  return {
+    rules: { userAgent: '*', allow: '/' },
+    sitemap: `${base.replace(/\/$/, '')}/sitemap.xml`,
+  };
+}
@@ -12,6 +12,8 @@ import {
   formatBlockHeight,
   CARRIER_INFO,
   isImageMessage,
+  isJsonMessage,
+  displayBodyText,
   hexToImageDataUrl,
   BTC_EXPLORER_URL,
 } from '@/lib/api';
@@ -241,6 +243,14 @@ function MessageBody({ message, size = 'md' }: { message: Message; size?: 'sm' |
     );
   }
 
+  if (message.body_text && isJsonMessage(message)) {
+    return (
+      <pre className="bg-secondary p-3 rounded-lg overflow-x-auto font-mono text-sm text-foreground">
+        {displayBodyText(message)}
+      </pre>
+    );
+  }
+
   if (message.body_text) {
     return (
       <p className={`${textSize} whitespace-pre-wrap break-words text-foreground`}>
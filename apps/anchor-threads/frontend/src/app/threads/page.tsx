@@ -33,6 +33,7 @@ const KIND_OPTIONS = [
   { value: '2', label: 'State' },
   { value: '3', label: 'Vote' },
   { value: '4', label: 'Image' },
+  { value: 'other', label: 'Other (unknown kind)' },
 ];
 
 const CARRIER_OPTIONS = [
@@ -61,7 +62,7 @@ export default function ThreadsPage() {
       filters.block_max !== undefined
     )
       count++;
-    if (filters.kind !== undefined) count++;
+    if (filters.kind !== undefined || filters.kind_unknown) count++;
     if (filters.carrier !== undefined) count++;
     if (filters.text) count++;
     if (filters.from_date || filters.to_date) count++;
@@ -245,9 +246,16 @@ export default function ThreadsPage() {
             <div>
               <label className="block text-sm font-medium text-foreground mb-2">Message Type</label>
               <select
-                value={tempFilters.kind?.toString() || ''}
+                value={tempFilters.kind_unknown ? 'other' : tempFilters.kind?.toString() || ''}
                 onChange={(e) =>
-                  updateTempFilter('kind', e.target.value ? parseInt(e.target.value) : undefined)
+                  setTempFilters((prev) => ({
+                    ...prev,
+                    kind:
+                      e.target.value && e.target.value !== 'other'
+                        ? parseInt(e.target.value)
+                        : undefined,
+                    kind_unknown: e.target.value === 'other' || undefined,
+                  }))
                 }
                 className="w-full h-9 px-3 rounded-md border border-input bg-transparent text-sm focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring"
               >
@@ -443,6 +451,12 @@ export default function ThreadsPage() {
               onRemove={() => setFilters({ ...filters, kind: undefined })}
             />
           )}
+          {filters.kind_unknown && (
+            <FilterTag
+              label="Type: Other (unknown kind)"
+              onRemove={() => setFilters({ ...filters, kind_unknown: undefined })}
+            />
+          )}
           {filters.carrier !== undefined && (
             <FilterTag
               label={`Carrier: ${CARRIER_OPTIONS.find((c) => c.value === filters.carrier?.toString())?.label}`}
@@ -10,6 +10,8 @@ import {
   formatBlockHeight,
   hexToImageDataUrl,
   isImageMessage,
+  isJsonMessage,
+  displayBodyText,
   CARRIER_INFO,
 } from '@/lib/api';
 import {
@@ -31,6 +33,7 @@ interface MessageCardProps {
 export function MessageCard({ message, showParent = false, isReply = false }: MessageCardProps) {
   const router = useRouter();
   const hasText = message.body_text && message.body_text.trim().length > 0;
+  const isJson = isJsonMessage(message);
   const parentAnchor = message.anchors.find((a) => a.index === 0);
 
   // Check if this is an image message (by kind or magic bytes)
@@ -55,8 +58,12 @@ export function MessageCard({ message, showParent = false, isReply = false }: Me
       {/* Header */}
       <div className="flex items-start justify-between gap-4 mb-3">
         <div className="flex items-center gap-3 text-sm text-muted-foreground flex-wrap">
-          <span className="px-2 py-0.5 bg-primary/10 text-primary rounded text-xs font-medium">
+          <span
+            className="px-2 py-0.5 bg-primary/10 text-primary rounded text-xs font-medium"
+            title={message.kind_unknown ? 'Unregistered kind, body shown raw' : undefined}
+          >
             {message.kind_name}
+            {message.content_type && ` · ${message.content_type}`}
           </span>
           {message.carrier !== undefined && (
             <span
@@ -133,6 +140,10 @@ export function MessageCard({ message, showParent = false, isReply = false }: Me
               <span>Image ({Math.floor(message.body_hex.length / 2)} bytes)</span>
             </div>
           </div>
+        ) : hasText && isJson ? (
+          <pre className="bg-secondary p-3 rounded-lg overflow-x-auto font-mono text-sm text-foreground max-h-48">
+            {displayBodyText(message)}
+          </pre>
         ) : hasText ? (
           <p className="text-foreground whitespace-pre-wrap break-words">{message.body_text}</p>
         ) : (
@@ -27,6 +27,24 @@ if (typeof window !== 'undefined') {
   fetchDefaultExplorerUrl();
 }
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 // Get explorer transaction URL
 export function getExplorerTxUrl(txid: string): string {
   const baseUrl = cachedExplorerUrl || DEFAULT_BLOCK_EXPLORER_URL;
@@ -162,10 +180,16 @@ export interface Message {
   block_height: number | null;
   kind: number;
   kind_name: string;
+  /** Kind is not in the kind registry; body is the raw payload */
+  kind_unknown: boolean;
   carrier: number;
   carrier_name: string;
   body_hex: string;
   body_text: string | null;
+  /** MIME type of a Generic message's content, from its envelope */
+  content_type?: string | null;
+  /** ISO 639-3 language, when the indexer detected it */
+  lang?: string | null;
   anchors: Anchor[];
   reply_count: number;
   created_at: string;
@@ -281,6 +305,8 @@ export interface FilterOptions {
   block_min?: number;
   block_max?: number;
   kind?: number;
+  /** Only messages with unregistered kinds ("Other") */
+  kind_unknown?: boolean;
   carrier?: number;
   text?: string;
   from_date?: string;
@@ -308,6 +334,7 @@ export async function fetchRootsFiltered(
   if (filters.block_min !== undefined) params.set('block_min', filters.block_min.toString());
   if (filters.block_max !== undefined) params.set('block_max', filters.block_max.toString());
   if (filters.kind !== undefined) params.set('kind', filters.kind.toString());
+  if (filters.kind_unknown) params.set('kind_unknown', 'true');
   if (filters.carrier !== undefined) params.set('carrier', filters.carrier.toString());
   if (filters.text) params.set('text', filters.text);
   if (filters.from_date) params.set('from_date', filters.from_date);
@@ -393,8 +420,7 @@ export async function createMessage(req: CreateMessageRequest): Promise<CreateMe
     body: JSON.stringify(req),
   });
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to create message');
+    throw await apiError(res, 'Failed to create message');
   }
   return res.json();
 }
@@ -531,6 +557,27 @@ export function isImageMessage(message: Message): boolean {
   return detectImageMimeType(message.body_hex) !== null;
 }
 
+/**
+ * Check if a message carries JSON content (Generic messages)
+ */
+export function isJsonMessage(message: Message): boolean {
+  return message.content_type?.split(';')[0].trim().toLowerCase() === 'application/json';
+}
+
+/**
+ * Text to display for a message body, with JSON content pretty-printed
+ */
+export function displayBodyText(message: Message): string | null {
+  if (!message.body_text || !isJsonMessage(message)) {
+    return message.body_text;
+  }
+  try {
+    return JSON.stringify(JSON.parse(message.body_text), null, 2);
+  } catch {
+    return message.body_text;
+  }
+}
+
 /**
  * Convert hex-encoded image data to a data URL for display
  */
@@ -0,0 +1,45 @@
+-- Migration: Token Airdrops
+-- Tracks multi-recipient airdrops split into batched transfer transactions,
+-- so an interrupted airdrop can resume from the first unsent batch
+
+-- ============================================================================
+-- Airdrops
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS token_airdrops (
+    id SERIAL PRIMARY KEY,
+    token_id INTEGER NOT NULL REFERENCES tokens(id) ON DELETE CASCADE,
+    carrier SMALLINT NOT NULL,
+    fee_rate DOUBLE PRECISION NOT NULL,
+    total_amount NUMERIC(78, 0) NOT NULL CHECK (total_amount > 0),
+    recipient_count INTEGER NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'completed', 'failed')),
+    error TEXT,
+    -- Token change left by the last sent batch, spent by the next one
+    change_txid TEXT,
+    change_vout INTEGER,
+    change_amount NUMERIC(78, 0),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_airdrops_status ON token_airdrops(status);
+
+-- ============================================================================
+-- Airdrop Batches (one transfer transaction each)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS token_airdrop_batches (
+    airdrop_id INTEGER NOT NULL REFERENCES token_airdrops(id) ON DELETE CASCADE,
+    batch_index INTEGER NOT NULL,
+    -- [{"address": "...", "amount": "..."}, ...]
+    recipients JSONB NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    status VARCHAR(16) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sending', 'sent', 'failed')),
+    txid TEXT,
+    error TEXT,
+    sent_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (airdrop_id, batch_index)
+);
@@ -0,0 +1,29 @@
+-- Migration: Token Webhooks
+-- Per-address subscriptions to token activity. The delivery log comes from
+-- anchor_webhook_deliveries() in libs/rust/anchor-webhooks/migrations.
+
+-- ============================================================================
+-- Webhooks
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS token_webhooks (
+    id SERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    url TEXT NOT NULL,
+    -- HMAC-SHA256 key for the X-Anchor-Signature header
+    secret TEXT NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_webhooks_address ON token_webhooks(address) WHERE active;
+
+-- ============================================================================
+-- Deliveries
+-- ============================================================================
+
+SELECT anchor_webhook_deliveries('token_webhook_deliveries', 'token_webhooks');
+
+-- Operation that triggered the delivery (not a foreign key: reorgs delete
+-- operations, but what was delivered stays on record)
+ALTER TABLE token_webhook_deliveries ADD COLUMN IF NOT EXISTS operation_id INTEGER NOT NULL;
@@ -0,0 +1,22 @@
+-- Migration: Rejected token operations
+-- Mints, transfers, splits and burns that the indexer rejected, with the
+-- reason, so holders can see why an operation had no effect.
+
+CREATE TABLE IF NOT EXISTS token_invalid_operations (
+    id SERIAL PRIMARY KEY,
+    token_id INTEGER NOT NULL REFERENCES tokens(id) ON DELETE CASCADE,
+    operation SMALLINT NOT NULL CHECK (operation >= 1 AND operation <= 5),
+    txid BYTEA NOT NULL,
+    vout INTEGER NOT NULL DEFAULT 0,
+    reason VARCHAR(32) NOT NULL,  -- over_spend, input_not_spent, missing_output, ...
+    detail TEXT NOT NULL,
+    block_hash BYTEA,
+    block_height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT token_invalid_operations_unique UNIQUE (txid, vout)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_invalid_operations_token
+    ON token_invalid_operations(token_id, block_height DESC);
+CREATE INDEX IF NOT EXISTS idx_token_invalid_operations_block_height
+    ON token_invalid_operations(block_height);
@@ -0,0 +1,18 @@
+-- Migration: Ticker registry
+-- One row per normalized ticker, held by the first token deployed under it.
+-- The normalized form is chosen by the backend's ticker policy; existing
+-- tokens are backfilled with the default, upper-cased form.
+
+CREATE TABLE IF NOT EXISTS token_tickers (
+    normalized VARCHAR(32) PRIMARY KEY,
+    token_id INTEGER NOT NULL REFERENCES tokens(id) ON DELETE CASCADE,
+    block_height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT token_tickers_token_unique UNIQUE (token_id)
+);
+
+INSERT INTO token_tickers (normalized, token_id, block_height)
+SELECT DISTINCT ON (UPPER(ticker)) UPPER(ticker), id, block_height
+FROM tokens
+ORDER BY UPPER(ticker), block_height NULLS LAST, id
+ON CONFLICT DO NOTHING;
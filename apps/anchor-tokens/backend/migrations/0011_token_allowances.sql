@@ -0,0 +1,43 @@
+-- Migration: Token allowances
+-- APPROVE (operation 6) puts the owner's balance on one output of the approve
+-- transaction and lets whoever spends another output of it move up to
+-- `amount` of that balance with one transfer. The allowance is used when the
+-- spender outpoint is spent, by that transfer or otherwise, and lapses with
+-- the owner's token UTXO.
+
+ALTER TABLE token_operations
+    DROP CONSTRAINT IF EXISTS token_operations_operation_check,
+    ADD CONSTRAINT token_operations_operation_check CHECK (operation >= 1 AND operation <= 6);
+
+ALTER TABLE token_invalid_operations
+    DROP CONSTRAINT IF EXISTS token_invalid_operations_operation_check,
+    ADD CONSTRAINT token_invalid_operations_operation_check CHECK (operation >= 1 AND operation <= 6);
+
+CREATE TABLE IF NOT EXISTS token_allowances (
+    id SERIAL PRIMARY KEY,
+    token_id INTEGER NOT NULL REFERENCES tokens(id) ON DELETE CASCADE,
+    txid BYTEA NOT NULL,               -- approve transaction
+    owner_vout INTEGER NOT NULL,       -- token UTXO the allowance draws on
+    owner_address TEXT,
+    spender_vout INTEGER NOT NULL,     -- spending this output exercises the allowance
+    spender_address TEXT,
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount > 0),
+    block_hash BYTEA,
+    block_height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    used_txid BYTEA,                   -- transaction that spent the spender output
+    drawn NUMERIC(78, 0),              -- tokens it moved from the owner's UTXO
+    used_block_height INTEGER,
+    CONSTRAINT token_allowances_unique UNIQUE (txid, spender_vout)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_allowances_owner
+    ON token_allowances(token_id, txid, owner_vout) WHERE used_txid IS NULL;
+CREATE INDEX IF NOT EXISTS idx_token_allowances_owner_address
+    ON token_allowances(owner_address) WHERE used_txid IS NULL;
+CREATE INDEX IF NOT EXISTS idx_token_allowances_spender_address
+    ON token_allowances(spender_address) WHERE used_txid IS NULL;
+CREATE INDEX IF NOT EXISTS idx_token_allowances_block_height
+    ON token_allowances(block_height);
+CREATE INDEX IF NOT EXISTS idx_token_allowances_used_block_height
+    ON token_allowances(used_block_height);
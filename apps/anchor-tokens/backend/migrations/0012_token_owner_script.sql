@@ -0,0 +1,7 @@
+-- Migration: Token owner scripts
+-- Token UTXOs now carry the locking script of their output, so holdings can
+-- be looked up by the script that controls them as well as by address
+-- (scripts without an address, e.g. bare multisig, included).
+
+CREATE INDEX IF NOT EXISTS idx_token_utxos_unspent_script
+    ON token_utxos(token_id, owner_script) WHERE spent_txid IS NULL;
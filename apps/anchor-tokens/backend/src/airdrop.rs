@@ -0,0 +1,454 @@
+//! Token airdrops
+//!
+//! An airdrop splits a recipient list into transfer transactions, each
+//! paying as many recipients as one carrier payload and one transaction's
+//! output indexes allow. Batches are sent one after another: the first
+//! spends the wallet's token UTXOs, and every later batch spends the token
+//! change (output 0) of the one before it, so the whole airdrop needs no
+//! further coin selection once it starts.
+//!
+//! Progress is stored per batch, so an airdrop that fails or is interrupted
+//! resumes from the first batch that was not sent.
+
+use std::str::FromStr;
+
+use anchor_core::carrier::{CarrierSelector, CarrierType};
+use anchor_core::{ANCHOR_SIZE, MAX_RECOMMENDED_ANCHORS};
+use anchor_specs::KindSpec;
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::Address;
+use tracing::{info, warn};
+
+use crate::db::AirdropBatchRow;
+use crate::handlers::{
+    create_wallet_tx_with_inputs, lock_utxo, reverse_txid_hex, select_token_utxos, unlock_utxo,
+    wallet_token_utxos, AppError, AppState,
+};
+use crate::models::{AllocationInput, TokenAllocation, TokenOperation, TokenSpec};
+
+/// Most recipients accepted in one airdrop
+pub const MAX_RECIPIENTS: usize = 10_000;
+
+/// Most recipients in one transaction
+///
+/// Allocations address outputs with a u8 and output 0 is kept for change.
+pub const MAX_RECIPIENTS_PER_TX: usize = 254;
+
+/// Value of each recipient output in satoshis
+const DUST_VALUE: u64 = 546;
+
+/// ANCHOR header bytes around the token payload
+///
+/// Magic, kind and anchor count, plus room for the most anchors the first
+/// batch may reference.
+const HEADER_OVERHEAD: usize = 6 + ANCHOR_SIZE * MAX_RECOMMENDED_ANCHORS as usize;
+
+/// Parse `address,amount` lines
+///
+/// Blank lines and lines starting with `#` are skipped, as is a first line
+/// whose amount is not a number (a header row).
+pub fn parse_csv(csv: &str) -> Result<Vec<AllocationInput>, String> {
+    let mut recipients = Vec::new();
+    let mut first_row = true;
+
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split(',').map(str::trim);
+        let (Some(address), Some(amount), None) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(format!("Line {}: expected `address,amount`", index + 1));
+        };
+
+        if std::mem::take(&mut first_row) && amount.parse::<u128>().is_err() {
+            continue;
+        }
+
+        recipients.push(AllocationInput {
+            address: address.to_string(),
+            amount: amount.to_string(),
+        });
+    }
+
+    Ok(recipients)
+}
+
+/// Check each recipient and return the amounts with the total
+pub fn validate_recipients(recipients: &[AllocationInput]) -> Result<(Vec<u128>, u128), String> {
+    if recipients.is_empty() {
+        return Err("No recipients given".to_string());
+    }
+    if recipients.len() > MAX_RECIPIENTS {
+        return Err(format!(
+            "Too many recipients: {} (max {})",
+            recipients.len(),
+            MAX_RECIPIENTS
+        ));
+    }
+
+    let mut amounts = Vec::with_capacity(recipients.len());
+    let mut total: u128 = 0;
+    for (i, recipient) in recipients.iter().enumerate() {
+        Address::<NetworkUnchecked>::from_str(&recipient.address).map_err(|e| {
+            format!(
+                "Recipient {}: invalid address {}: {}",
+                i + 1,
+                recipient.address,
+                e
+            )
+        })?;
+        let amount: u128 = recipient
+            .amount
+            .parse()
+            .ok()
+            .filter(|a| *a > 0)
+            .ok_or_else(|| format!("Recipient {}: invalid amount {}", i + 1, recipient.amount))?;
+        total = total
+            .checked_add(amount)
+            .ok_or_else(|| "Total amount overflows".to_string())?;
+        amounts.push(amount);
+    }
+
+    Ok((amounts, total))
+}
+
+/// Largest payload the carrier takes
+pub fn carrier_max_size(carrier: u8) -> Result<usize, String> {
+    let carrier_type =
+        CarrierType::from_u8(carrier).ok_or_else(|| format!("Unknown carrier {}", carrier))?;
+    CarrierSelector::new()
+        .get_carrier(carrier_type)
+        .map(|c| c.info().max_size)
+        .ok_or_else(|| format!("Carrier {} is not available", carrier))
+}
+
+/// Encoded transfer payload for a batch
+///
+/// Output 0 carries `change` when it is non-zero; recipients follow.
+fn transfer_payload(token_id: u64, change: u128, amounts: &[u128]) -> Vec<u8> {
+    let offset = usize::from(change > 0);
+    let mut allocations = Vec::with_capacity(amounts.len() + offset);
+    if change > 0 {
+        allocations.push(TokenAllocation {
+            output_index: 0,
+            amount: change,
+        });
+    }
+    for (i, amount) in amounts.iter().enumerate() {
+        allocations.push(TokenAllocation {
+            output_index: (i + offset) as u8,
+            amount: *amount,
+        });
+    }
+
+    TokenSpec::new(TokenOperation::Transfer {
+        token_id,
+        allocations,
+    })
+    .to_bytes()
+}
+
+/// Split recipients into batches of `(first recipient, recipient count)`
+///
+/// Each batch is sized with the whole airdrop total as its change, which
+/// is the largest change any batch can carry, so the payload always fits.
+pub fn plan_batches(
+    token_id: u64,
+    amounts: &[u128],
+    max_size: usize,
+    max_per_tx: usize,
+) -> Result<Vec<(usize, usize)>, String> {
+    let max_per_tx = max_per_tx.clamp(1, MAX_RECIPIENTS_PER_TX);
+    let worst_change: u128 = amounts.iter().sum();
+    let fits = |batch: &[u128]| {
+        HEADER_OVERHEAD + transfer_payload(token_id, worst_change, batch).len() <= max_size
+    };
+
+    let mut batches = Vec::new();
+    let mut start = 0;
+    while start < amounts.len() {
+        let mut len = 0;
+        while start + len < amounts.len()
+            && len < max_per_tx
+            && fits(&amounts[start..start + len + 1])
+        {
+            len += 1;
+        }
+        if len == 0 {
+            return Err("The carrier cannot fit a single transfer allocation".to_string());
+        }
+        batches.push((start, len));
+        start += len;
+    }
+
+    Ok(batches)
+}
+
+/// Send an airdrop's remaining batches in the background
+pub fn spawn(state: AppState, id: i32) {
+    tokio::spawn(async move {
+        if let Err(e) = run(&state, id).await {
+            warn!("Airdrop {} stopped: {:?}", id, e);
+        }
+    });
+}
+
+/// Send the pending batches of an airdrop in order
+///
+/// Does nothing if the airdrop is not pending. Stops at the first batch
+/// that fails and marks the airdrop failed.
+async fn run(state: &AppState, id: i32) -> Result<(), AppError> {
+    if !state.db.claim_airdrop(id).await? {
+        return Ok(());
+    }
+
+    let result = send_batches(state, id).await;
+    match &result {
+        Ok(()) => {
+            state.db.set_airdrop_status(id, "completed", None).await?;
+            info!("Airdrop {} completed", id);
+        }
+        Err(e) => {
+            let error = error_message(e);
+            state
+                .db
+                .set_airdrop_status(id, "failed", Some(&error))
+                .await?;
+        }
+    }
+    result
+}
+
+async fn send_batches(state: &AppState, id: i32) -> Result<(), AppError> {
+    let batches = state.db.get_airdrop_batches(id).await?;
+
+    for batch in batches.iter().filter(|b| b.status == "pending") {
+        let remaining: u128 = batches
+            .iter()
+            .filter(|b| b.status != "sent")
+            .map(|b| b.amount.parse::<u128>().unwrap_or(0))
+            .sum();
+
+        if let Err(e) = send_batch(state, id, batch, remaining).await {
+            let error = error_message(&e);
+            state
+                .db
+                .set_airdrop_batch_status(id, batch.batch_index, "failed", None, Some(&error))
+                .await?;
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Send one batch, spending the recorded change or fresh token UTXOs
+async fn send_batch(
+    state: &AppState,
+    id: i32,
+    batch: &AirdropBatchRow,
+    remaining: u128,
+) -> Result<(), AppError> {
+    let airdrop = state
+        .db
+        .get_airdrop(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Airdrop {} not found", id)))?;
+
+    // Inputs as (display txid, vout) and the token amount they hold
+    let (inputs, input_amount): (Vec<(String, u32)>, u128) = match (
+        &airdrop.change_txid,
+        airdrop.change_vout,
+        &airdrop.change_amount,
+    ) {
+        (Some(txid), Some(vout), Some(amount)) => (
+            vec![(txid.clone(), vout as u32)],
+            amount.parse().unwrap_or(0),
+        ),
+        _ => {
+            let (selected, selected_amount) = select_token_utxos(
+                wallet_token_utxos(&state.db, airdrop.token_id).await?,
+                remaining,
+            )?;
+            if selected.len() > MAX_RECOMMENDED_ANCHORS as usize {
+                return Err(AppError::BadRequest(format!(
+                    "The airdrop needs {} token UTXOs; consolidate them into at most {} first",
+                    selected.len(),
+                    MAX_RECOMMENDED_ANCHORS
+                )));
+            }
+            (
+                selected
+                    .iter()
+                    .map(|u| (reverse_txid_hex(&u.txid), u.vout as u32))
+                    .collect(),
+                selected_amount,
+            )
+        }
+    };
+
+    let amounts: Vec<u128> = batch
+        .recipients
+        .iter()
+        .map(|r| r.amount.parse().unwrap_or(0))
+        .collect();
+    let batch_amount: u128 = amounts.iter().sum();
+    let change = input_amount.checked_sub(batch_amount).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Inputs hold {} but batch {} needs {}",
+            input_amount, batch.batch_index, batch_amount
+        ))
+    })?;
+
+    let payload = transfer_payload(airdrop.token_id as u64, change, &amounts);
+
+    // Token UTXOs are kept locked between transfers; free them for this one
+    for (txid, vout) in &inputs {
+        if let Err(e) = unlock_utxo(txid, *vout).await {
+            tracing::debug!("Failed to unlock UTXO {}:{}: {:?}", txid, vout, e);
+        }
+    }
+
+    let required_inputs: Vec<serde_json::Value> = inputs
+        .iter()
+        .map(|(txid, vout)| serde_json::json!({ "txid": txid, "vout": vout }))
+        .collect();
+    let custom_outputs: Vec<serde_json::Value> = batch
+        .recipients
+        .iter()
+        .map(|r| serde_json::json!({ "address": r.address, "value": DUST_VALUE }))
+        .collect();
+
+    state
+        .db
+        .set_airdrop_batch_status(id, batch.batch_index, "sending", None, None)
+        .await?;
+
+    let response = create_wallet_tx_with_inputs(
+        &state.wallet_url,
+        &payload,
+        airdrop.carrier as u8,
+        airdrop.fee_rate,
+        20,
+        &required_inputs,
+        &required_inputs,
+        &custom_outputs,
+    )
+    .await?;
+
+    state
+        .db
+        .set_airdrop_batch_status(id, batch.batch_index, "sent", Some(&response.txid), None)
+        .await?;
+
+    if change > 0 {
+        state
+            .db
+            .set_airdrop_change(id, Some((&response.txid, 0, &change.to_string())))
+            .await?;
+        if let Err(e) = lock_utxo(&response.txid, 0).await {
+            tracing::debug!("Failed to lock airdrop change {}:0: {:?}", response.txid, e);
+        }
+    } else {
+        state.db.set_airdrop_change(id, None).await?;
+    }
+
+    info!(
+        "Airdrop {} batch {} sent in {} ({} recipients)",
+        id,
+        batch.batch_index,
+        response.txid,
+        batch.recipients.len()
+    );
+    Ok(())
+}
+
+fn error_message(error: &AppError) -> String {
+    match error {
+        AppError::NotFound(msg) | AppError::BadRequest(msg) | AppError::Internal(msg) => {
+            msg.clone()
+        }
+        AppError::Api(err) => format!("{:?}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = format!("address,amount\n\n# team\n{ADDRESS},100\n  {ADDRESS} , 25 \n");
+        let recipients = parse_csv(&csv).unwrap();
+        assert_eq!(recipients.len(), 2);
+        assert_eq!(recipients[1].address, ADDRESS);
+        assert_eq!(recipients[1].amount, "25");
+
+        let err = parse_csv(&format!("{ADDRESS},1\n{ADDRESS}\n")).unwrap_err();
+        assert!(err.starts_with("Line 2"));
+    }
+
+    #[test]
+    fn test_header_only_skipped_on_first_row() {
+        let err = validate_recipients(&parse_csv(&format!("{ADDRESS},1\nfoo,bar\n")).unwrap())
+            .unwrap_err();
+        assert!(err.starts_with("Recipient 2"));
+    }
+
+    #[test]
+    fn test_validate_recipients() {
+        let recipients = vec![
+            AllocationInput {
+                address: ADDRESS.to_string(),
+                amount: "7".to_string(),
+            },
+            AllocationInput {
+                address: ADDRESS.to_string(),
+                amount: "3".to_string(),
+            },
+        ];
+        assert_eq!(validate_recipients(&recipients).unwrap(), (vec![7, 3], 10));
+
+        let zero = vec![AllocationInput {
+            address: ADDRESS.to_string(),
+            amount: "0".to_string(),
+        }];
+        assert!(validate_recipients(&zero).is_err());
+
+        let bad_address = vec![AllocationInput {
+            address: "not-an-address".to_string(),
+            amount: "1".to_string(),
+        }];
+        assert!(validate_recipients(&bad_address).is_err());
+    }
+
+    #[test]
+    fn test_plan_batches_by_output_limit() {
+        let amounts = vec![1u128; 600];
+        let batches = plan_batches(1, &amounts, usize::MAX, usize::MAX).unwrap();
+        assert_eq!(batches, vec![(0, 254), (254, 254), (508, 92)]);
+
+        let batches = plan_batches(1, &amounts[..10], usize::MAX, 4).unwrap();
+        assert_eq!(batches, vec![(0, 4), (4, 4), (8, 2)]);
+    }
+
+    #[test]
+    fn test_plan_batches_by_carrier_size() {
+        let amounts = vec![1_000_000u128; 50];
+        let max_size = carrier_max_size(0).unwrap().min(HEADER_OVERHEAD + 64);
+        let batches = plan_batches(1, &amounts, max_size, usize::MAX).unwrap();
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(|b| b.1).sum::<usize>(), 50);
+        for (start, len) in batches {
+            let payload = transfer_payload(1, 50_000_000, &amounts[start..start + len]);
+            assert!(HEADER_OVERHEAD + payload.len() <= max_size);
+        }
+
+        assert!(plan_batches(1, &amounts, HEADER_OVERHEAD, usize::MAX).is_err());
+    }
+}
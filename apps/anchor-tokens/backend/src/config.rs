@@ -2,6 +2,8 @@
 
 use std::env;
 
+use crate::tickers::TickerPolicy;
+
 /// Application configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -21,6 +23,8 @@ pub struct Config {
     pub confirmations: u32,
     /// Polling interval in seconds
     pub poll_interval_secs: u64,
+    /// Rules for deployable tickers
+    pub ticker_policy: TickerPolicy,
 }
 
 impl Config {
@@ -50,6 +54,7 @@ impl Config {
                 .ok()
                 .and_then(|p| p.parse().ok())
                 .unwrap_or(5),
+            ticker_policy: TickerPolicy::from_env(),
         }
     }
 }
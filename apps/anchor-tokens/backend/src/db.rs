@@ -1,14 +1,15 @@
 //! Database operations for Anchor Tokens
 
+use anchor_webhooks::WebhookQueue;
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{FromRow, PgPool, Row};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::models::{
-    PaginatedResponse, Token, TokenBalance, TokenHolder, TokenOperationResponse, TokenStats,
-    TokenUtxo,
+    AllocationInput, InvalidOperationResponse, PaginatedResponse, Token, TokenAllowance,
+    TokenBalance, TokenHolder, TokenOperationResponse, TokenStats, TokenUtxo,
 };
 
 /// Database connection pool
@@ -65,6 +66,60 @@ impl From<TokenRow> for Token {
     }
 }
 
+/// Airdrop row from database
+#[derive(Debug, Clone, FromRow)]
+pub struct AirdropRow {
+    pub id: i32,
+    pub token_id: i32,
+    pub ticker: String,
+    pub carrier: i16,
+    pub fee_rate: f64,
+    pub total_amount: String,
+    pub recipient_count: i32,
+    pub status: String,
+    pub error: Option<String>,
+    pub change_txid: Option<String>,
+    pub change_vout: Option<i32>,
+    pub change_amount: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Airdrop batch row from database
+#[derive(Debug, Clone, FromRow)]
+pub struct AirdropBatchRow {
+    pub batch_index: i32,
+    pub recipients: sqlx::types::Json<Vec<AllocationInput>>,
+    pub amount: String,
+    pub status: String,
+    pub txid: Option<String>,
+    pub error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+/// Webhook row from database
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookRow {
+    pub id: i32,
+    pub address: String,
+    pub url: String,
+    pub secret: String,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Unused allowance over an unspent token UTXO
+#[derive(Debug, Clone)]
+pub struct ActiveAllowance {
+    pub id: i32,
+    /// Approve transaction, which also holds the owner's UTXO
+    pub txid: Vec<u8>,
+    pub owner_vout: i32,
+    pub owner_address: Option<String>,
+    /// The allowance, capped at the UTXO's balance
+    pub available: u128,
+}
+
 impl Database {
     /// Connect to the database
     pub async fn connect(url: &str) -> Result<Self> {
@@ -108,12 +163,43 @@ impl Database {
     pub async fn handle_reorg(&self, reorg_height: i32) -> Result<()> {
         debug!("Handling reorg at height {}", reorg_height);
 
+        // Give back what allowances drew from UTXOs that stay unspent; UTXOs
+        // drawn in full are unspent below
+        sqlx::query(
+            "UPDATE token_utxos u SET amount = u.amount + a.drawn
+             FROM token_allowances a
+             WHERE a.used_block_height >= $1 AND a.drawn > 0
+               AND u.token_id = a.token_id AND u.txid = a.txid AND u.vout = a.owner_vout
+               AND u.spent_txid IS DISTINCT FROM a.used_txid",
+        )
+        .bind(reorg_height)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "UPDATE token_allowances SET used_txid = NULL, drawn = NULL, used_block_height = NULL
+             WHERE used_block_height >= $1",
+        )
+        .bind(reorg_height)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query("DELETE FROM token_allowances WHERE block_height >= $1")
+            .bind(reorg_height)
+            .execute(&self.pool)
+            .await?;
+
         // Delete operations at or above reorg height
         sqlx::query("DELETE FROM token_operations WHERE block_height >= $1")
             .bind(reorg_height)
             .execute(&self.pool)
             .await?;
 
+        sqlx::query("DELETE FROM token_invalid_operations WHERE block_height >= $1")
+            .bind(reorg_height)
+            .execute(&self.pool)
+            .await?;
+
         // Mark spent UTXOs as unspent if spent at or above reorg height
         sqlx::query(
             "UPDATE token_utxos 
@@ -143,13 +229,38 @@ impl Database {
     // Token Operations
     // ========================================================================
 
-    /// Check if a ticker is available
-    pub async fn is_ticker_available(&self, ticker: &str) -> Result<bool> {
-        let row = sqlx::query("SELECT is_ticker_available($1) as available")
-            .bind(ticker.to_uppercase())
-            .fetch_one(&self.pool)
-            .await?;
-        Ok(row.get("available"))
+    /// Get the token holding a normalized ticker in the registry
+    pub async fn get_registered_token(&self, normalized: &str) -> Result<Option<Token>> {
+        let row = sqlx::query_as::<_, TokenRow>(
+            "SELECT t.id, t.ticker, t.deploy_txid, t.deploy_vout, t.decimals, t.max_supply::text, t.mint_limit::text,
+                    t.minted_supply::text, t.burned_supply::text, t.holder_count, t.tx_count, t.flags, t.block_height, t.created_at
+             FROM token_tickers r
+             JOIN tokens t ON t.id = r.token_id
+             WHERE r.normalized = $1",
+        )
+        .bind(normalized)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(Token::from))
+    }
+
+    /// Claim a normalized ticker for a token
+    pub async fn register_ticker(
+        &self,
+        normalized: &str,
+        token_id: i32,
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO token_tickers (normalized, token_id, block_height) VALUES ($1, $2, $3)",
+        )
+        .bind(normalized)
+        .bind(token_id)
+        .bind(block_height)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
     }
 
     /// Register a new token (DEPLOY)
@@ -231,54 +342,58 @@ impl Database {
     }
 
     /// List tokens with pagination
+    ///
+    /// `owner` is an output script and, when it has one, its address; it
+    /// narrows the list to tokens with an unspent UTXO held by that owner.
     pub async fn list_tokens(
         &self,
         page: i32,
         per_page: i32,
         search: Option<&str>,
+        owner: Option<(&[u8], Option<&str>)>,
+        snapshot_id: Option<&str>,
     ) -> Result<PaginatedResponse<Token>> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
         let offset = (page - 1) * per_page;
+        let search = search.map(|s| format!("%{}%", s));
+        let (owner_script, owner_address) = owner.unzip();
+        let owner_address = owner_address.flatten();
+
+        let rows = sqlx::query_as::<_, TokenRow>(
+            "SELECT t.id, t.ticker, t.deploy_txid, t.deploy_vout, t.decimals, t.max_supply::text,
+                    t.mint_limit::text, t.minted_supply::text, t.burned_supply::text, t.holder_count,
+                    t.tx_count, t.flags, t.block_height, t.created_at
+             FROM tokens t
+             WHERE ($1::text IS NULL OR t.ticker ILIKE $1)
+               AND ($2::bytea IS NULL OR EXISTS (
+                   SELECT 1 FROM token_utxos u
+                   WHERE u.token_id = t.id AND u.spent_txid IS NULL
+                     AND (u.owner_script = $2 OR u.owner_address = $3)))
+             ORDER BY t.created_at DESC
+             LIMIT $4 OFFSET $5",
+        )
+        .bind(&search)
+        .bind(owner_script)
+        .bind(owner_address)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&mut *tx)
+        .await?;
 
-        let (rows, total): (Vec<TokenRow>, i64) = if let Some(search) = search {
-            let rows = sqlx::query_as::<_, TokenRow>(
-                "SELECT id, ticker, deploy_txid, deploy_vout, decimals, max_supply::text, mint_limit::text,
-                        minted_supply::text, burned_supply::text, holder_count, tx_count, flags, block_height, created_at
-                 FROM tokens
-                 WHERE ticker ILIKE $1
-                 ORDER BY created_at DESC
-                 LIMIT $2 OFFSET $3",
-            )
-            .bind(format!("%{}%", search))
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tokens WHERE ticker ILIKE $1")
-                .bind(format!("%{}%", search))
-                .fetch_one(&self.pool)
-                .await?;
-
-            (rows, count.0)
-        } else {
-            let rows = sqlx::query_as::<_, TokenRow>(
-                "SELECT id, ticker, deploy_txid, deploy_vout, decimals, max_supply::text, mint_limit::text,
-                        minted_supply::text, burned_supply::text, holder_count, tx_count, flags, block_height, created_at
-                 FROM tokens
-                 ORDER BY created_at DESC
-                 LIMIT $1 OFFSET $2",
-            )
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tokens")
-                .fetch_one(&self.pool)
-                .await?;
-
-            (rows, count.0)
-        };
+        let total: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM tokens t
+             WHERE ($1::text IS NULL OR t.ticker ILIKE $1)
+               AND ($2::bytea IS NULL OR EXISTS (
+                   SELECT 1 FROM token_utxos u
+                   WHERE u.token_id = t.id AND u.spent_txid IS NULL
+                     AND (u.owner_script = $2 OR u.owner_address = $3)))",
+        )
+        .bind(&search)
+        .bind(owner_script)
+        .bind(owner_address)
+        .fetch_one(&mut *tx)
+        .await?;
+        let total = total.0;
 
         let total_pages = ((total as f64) / (per_page as f64)).ceil() as i32;
 
@@ -315,6 +430,16 @@ impl Database {
         Ok(())
     }
 
+    /// Add to burned supply without counting a transaction
+    pub async fn add_burned_supply(&self, token_id: i32, amount: &str) -> Result<()> {
+        sqlx::query("UPDATE tokens SET burned_supply = burned_supply + $1::numeric WHERE id = $2")
+            .bind(amount)
+            .bind(token_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     /// Increment transaction count
     pub async fn increment_tx_count(&self, token_id: i32) -> Result<()> {
         sqlx::query("UPDATE tokens SET tx_count = tx_count + 1 WHERE id = $1")
@@ -405,31 +530,64 @@ impl Database {
         Ok(None)
     }
 
-    /// Find UTXO by txid prefix and vout
-    pub async fn find_utxo_by_prefix(
+    /// Get the amount held by an unspent token UTXO
+    pub async fn get_unspent_utxo_amount(
         &self,
         token_id: i32,
-        txid_prefix: &[u8],
+        txid: &[u8],
         vout: i32,
-    ) -> Result<Option<(Vec<u8>, String)>> {
-        let row = sqlx::query(
-            "SELECT txid, amount::text FROM token_utxos 
-             WHERE token_id = $1 
-               AND substring(txid from 1 for 8) = $2 
-               AND vout = $3 
-               AND spent_txid IS NULL",
+    ) -> Result<Option<String>> {
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT amount::text FROM token_utxos
+             WHERE token_id = $1 AND txid = $2 AND vout = $3 AND spent_txid IS NULL",
         )
         .bind(token_id)
-        .bind(txid_prefix)
+        .bind(txid)
         .bind(vout)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| {
-            let txid: Vec<u8> = r.get("txid");
-            let amount: String = r.get("amount");
-            (txid, amount)
-        }))
+        Ok(row.map(|r| r.0))
+    }
+
+    /// Burn token UTXOs that `txid` spends without a valid transfer
+    ///
+    /// Returns the `(token_id, owner_address, amount)` of each burned UTXO.
+    pub async fn burn_spent_utxos(
+        &self,
+        txid: &[u8],
+        outpoints: &[(Vec<u8>, i32)],
+        block_height: Option<i32>,
+    ) -> Result<Vec<(i32, Option<String>, String)>> {
+        let (txids, vouts): (Vec<Vec<u8>>, Vec<i32>) = outpoints.iter().cloned().unzip();
+        let rows: Vec<(i32, Option<String>, String)> = sqlx::query_as(
+            "WITH burned AS (
+                UPDATE token_utxos
+                SET spent_txid = $1, spent_block_height = $2, spent_at = NOW()
+                WHERE spent_txid IS NULL
+                  AND (txid, vout) IN (SELECT * FROM UNNEST($3::bytea[], $4::int[]))
+                RETURNING token_id, owner_address, amount
+             ), totals AS (
+                UPDATE tokens t SET burned_supply = t.burned_supply + b.total
+                FROM (SELECT token_id, SUM(amount) AS total FROM burned GROUP BY token_id) b
+                WHERE t.id = b.token_id
+             )
+             SELECT token_id, owner_address, amount::text FROM burned",
+        )
+        .bind(txid)
+        .bind(block_height)
+        .bind(&txids)
+        .bind(&vouts)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (token_id, owner, _) in &rows {
+            if let Some(addr) = owner {
+                self.update_address_balance(*token_id, addr).await?;
+            }
+        }
+
+        Ok(rows)
     }
 
     /// Get all unspent token UTXOs across all addresses
@@ -577,6 +735,44 @@ impl Database {
             .collect())
     }
 
+    /// Get token balances for an address as of a block height
+    ///
+    /// Rebuilt from token_utxos: counts outputs confirmed at or below the
+    /// height that were not spent in a block at or below it.
+    pub async fn get_address_balances_at(
+        &self,
+        address: &str,
+        height: i32,
+    ) -> Result<Vec<TokenBalance>> {
+        let rows = sqlx::query(
+            "SELECT u.token_id, t.ticker, t.decimals,
+                    SUM(u.amount)::text as balance, COUNT(*)::int as utxo_count
+             FROM token_utxos u
+             JOIN tokens t ON t.id = u.token_id
+             WHERE u.owner_address = $1
+               AND u.block_height <= $2
+               AND (u.spent_block_height IS NULL OR u.spent_block_height > $2)
+             GROUP BY u.token_id, t.ticker, t.decimals
+             HAVING SUM(u.amount) > 0
+             ORDER BY t.ticker",
+        )
+        .bind(address)
+        .bind(height)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TokenBalance {
+                token_id: row.get("token_id"),
+                ticker: row.get("ticker"),
+                decimals: row.get("decimals"),
+                balance: row.get("balance"),
+                utxo_count: row.get("utxo_count"),
+            })
+            .collect())
+    }
+
     /// Get token holders
     /// In regtest mode (no addresses), returns UTXOs as individual "holders"
     pub async fn get_token_holders(
@@ -584,7 +780,9 @@ impl Database {
         token_id: i32,
         page: i32,
         per_page: i32,
+        snapshot_id: Option<&str>,
     ) -> Result<PaginatedResponse<TokenHolder>> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
         let offset = (page - 1) * per_page;
 
         // First try to get real holders from token_balances
@@ -592,7 +790,7 @@ impl Database {
             "SELECT COUNT(*) FROM token_balances WHERE token_id = $1 AND balance > 0",
         )
         .bind(token_id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         if balance_count.0 > 0 {
@@ -604,7 +802,7 @@ impl Database {
             .bind(token_id)
             .bind(per_page)
             .bind(offset)
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *tx)
             .await?;
 
             let total_pages = ((balance_count.0 as f64) / (per_page as f64)).ceil() as i32;
@@ -634,7 +832,7 @@ impl Database {
             "SELECT SUM(amount)::text FROM token_utxos WHERE token_id = $1 AND spent_txid IS NULL",
         )
         .bind(token_id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
         let total_supply_val: i128 = total_supply.and_then(|t| t.0.parse().ok()).unwrap_or(0);
@@ -656,14 +854,14 @@ impl Database {
         .bind(per_page)
         .bind(offset)
         .bind(total_supply_val as i64)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
 
         let total: (i64,) = sqlx::query_as(
             "SELECT COUNT(*) FROM token_utxos WHERE token_id = $1 AND spent_txid IS NULL",
         )
         .bind(token_id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         let total_pages = ((total.0 as f64) / (per_page as f64)).ceil() as i32;
@@ -699,6 +897,195 @@ impl Database {
         })
     }
 
+    // ========================================================================
+    // Allowances
+    // ========================================================================
+
+    /// Record an allowance granted by an approve transaction
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_allowance(
+        &self,
+        token_id: i32,
+        txid: &[u8],
+        owner_vout: i32,
+        owner_address: Option<&str>,
+        spender_vout: i32,
+        spender_address: Option<&str>,
+        amount: &str,
+        block_hash: Option<&[u8]>,
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO token_allowances
+                (token_id, txid, owner_vout, owner_address, spender_vout, spender_address, amount, block_hash, block_height)
+             VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8, $9)
+             ON CONFLICT (txid, spender_vout) DO NOTHING",
+        )
+        .bind(token_id)
+        .bind(txid)
+        .bind(owner_vout)
+        .bind(owner_address)
+        .bind(spender_vout)
+        .bind(spender_address)
+        .bind(amount)
+        .bind(block_hash)
+        .bind(block_height)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Find the unused allowance over the token UTXO an anchor names, if its
+    /// spender output is among `spent`
+    pub async fn find_allowance(
+        &self,
+        token_id: i32,
+        txid_prefix: &[u8],
+        owner_vout: i32,
+        spent: &[(Vec<u8>, i32)],
+    ) -> Result<Option<ActiveAllowance>> {
+        let (txids, vouts): (Vec<Vec<u8>>, Vec<i32>) = spent.iter().cloned().unzip();
+        let row: Option<(i32, Vec<u8>, i32, Option<String>, String)> = sqlx::query_as(
+            "SELECT a.id, a.txid, a.owner_vout, u.owner_address, LEAST(a.amount, u.amount)::text
+             FROM token_allowances a
+             JOIN token_utxos u
+               ON u.token_id = a.token_id AND u.txid = a.txid AND u.vout = a.owner_vout
+              AND u.spent_txid IS NULL
+             WHERE a.token_id = $1 AND substring(a.txid FROM 1 FOR 8) = $2
+               AND a.owner_vout = $3 AND a.used_txid IS NULL
+               AND (a.txid, a.spender_vout) IN (SELECT * FROM UNNEST($4::bytea[], $5::int[]))
+             LIMIT 1",
+        )
+        .bind(token_id)
+        .bind(txid_prefix)
+        .bind(owner_vout)
+        .bind(&txids)
+        .bind(&vouts)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|(id, txid, owner_vout, owner_address, available)| {
+            Ok(ActiveAllowance {
+                id,
+                txid,
+                owner_vout,
+                owner_address,
+                available: available.parse()?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Use an allowance in transfer `txid`, moving `drawn` tokens out of the
+    /// owner's UTXO
+    ///
+    /// The UTXO keeps the rest of its balance; drawn in full, it is spent.
+    pub async fn draw_allowance(
+        &self,
+        allowance_id: i32,
+        txid: &[u8],
+        vout: i32,
+        drawn: u128,
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        let (token_id, owner_txid, owner_vout): (i32, Vec<u8>, i32) = sqlx::query_as(
+            "UPDATE token_allowances SET used_txid = $2, drawn = $3::numeric, used_block_height = $4
+             WHERE id = $1
+             RETURNING token_id, txid, owner_vout",
+        )
+        .bind(allowance_id)
+        .bind(txid)
+        .bind(drawn.to_string())
+        .bind(block_height)
+        .fetch_one(&self.pool)
+        .await?;
+        if drawn == 0 {
+            return Ok(());
+        }
+
+        let owner: Option<(Option<String>,)> = sqlx::query_as(
+            "UPDATE token_utxos SET amount = amount - $4::numeric
+             WHERE token_id = $1 AND txid = $2 AND vout = $3 AND spent_txid IS NULL
+               AND amount > $4::numeric
+             RETURNING owner_address",
+        )
+        .bind(token_id)
+        .bind(&owner_txid)
+        .bind(owner_vout)
+        .bind(drawn.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+        match owner {
+            Some((Some(addr),)) => self.update_address_balance(token_id, &addr).await?,
+            Some((None,)) => {}
+            None => {
+                self.spend_utxo(token_id, &owner_txid, owner_vout, txid, vout, block_height)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Use up the allowances whose spender outputs `txid` spends without
+    /// drawing on them
+    pub async fn expire_allowances(
+        &self,
+        txid: &[u8],
+        outpoints: &[(Vec<u8>, i32)],
+        block_height: Option<i32>,
+    ) -> Result<u64> {
+        let (txids, vouts): (Vec<Vec<u8>>, Vec<i32>) = outpoints.iter().cloned().unzip();
+        let result = sqlx::query(
+            "UPDATE token_allowances SET used_txid = $1, drawn = 0, used_block_height = $2
+             WHERE used_txid IS NULL
+               AND (txid, spender_vout) IN (SELECT * FROM UNNEST($3::bytea[], $4::int[]))",
+        )
+        .bind(txid)
+        .bind(block_height)
+        .bind(&txids)
+        .bind(&vouts)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Unused allowances granted by or to an address, over unspent UTXOs
+    pub async fn get_address_allowances(&self, address: &str) -> Result<Vec<TokenAllowance>> {
+        let rows = sqlx::query(
+            "SELECT a.id, a.token_id, t.ticker, a.txid, a.owner_vout, a.owner_address,
+                    a.spender_vout, a.spender_address, a.amount::text as amount,
+                    LEAST(a.amount, u.amount)::text as available, a.block_height, a.created_at
+             FROM token_allowances a
+             JOIN tokens t ON t.id = a.token_id
+             JOIN token_utxos u
+               ON u.token_id = a.token_id AND u.txid = a.txid AND u.vout = a.owner_vout
+              AND u.spent_txid IS NULL
+             WHERE a.used_txid IS NULL AND (a.owner_address = $1 OR a.spender_address = $1)
+             ORDER BY a.block_height DESC NULLS FIRST, a.id DESC",
+        )
+        .bind(address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TokenAllowance {
+                id: row.get("id"),
+                token_id: row.get("token_id"),
+                ticker: row.get("ticker"),
+                txid: hex::encode(row.get::<Vec<u8>, _>("txid")),
+                owner_vout: row.get("owner_vout"),
+                owner_address: row.get("owner_address"),
+                spender_vout: row.get("spender_vout"),
+                spender_address: row.get("spender_address"),
+                amount: row.get("amount"),
+                available: row.get("available"),
+                block_height: row.get("block_height"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
     // ========================================================================
     // Operation History
     // ========================================================================
@@ -733,8 +1120,14 @@ impl Database {
         .bind(block_height)
         .fetch_one(&self.pool)
         .await?;
+        let id: i32 = row.get("id");
 
-        Ok(row.get("id"))
+        // A missed notification must not stall indexing
+        if let Err(e) = self.enqueue_operation_webhooks(id).await {
+            warn!("Failed to queue webhooks for operation {}: {}", id, e);
+        }
+
+        Ok(id)
     }
 
     /// Get token operation history
@@ -743,7 +1136,9 @@ impl Database {
         token_id: i32,
         page: i32,
         per_page: i32,
+        snapshot_id: Option<&str>,
     ) -> Result<PaginatedResponse<TokenOperationResponse>> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
         let offset = (page - 1) * per_page;
 
         let rows = sqlx::query(
@@ -758,16 +1153,16 @@ impl Database {
         .bind(token_id)
         .bind(per_page)
         .bind(offset)
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
 
         let total: (i64,) =
             sqlx::query_as("SELECT COUNT(*) FROM token_operations WHERE token_id = $1")
                 .bind(token_id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut *tx)
                 .await?;
 
-        let op_names = ["", "DEPLOY", "MINT", "TRANSFER", "BURN", "SPLIT"];
+        let op_names = ["", "DEPLOY", "MINT", "TRANSFER", "BURN", "SPLIT", "APPROVE"];
         let total_pages = ((total.0 as f64) / (per_page as f64)).ceil() as i32;
 
         Ok(PaginatedResponse {
@@ -797,6 +1192,98 @@ impl Database {
         })
     }
 
+    /// Record a rejected token operation
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_invalid_operation(
+        &self,
+        token_id: i32,
+        operation: i16,
+        txid: &[u8],
+        vout: i32,
+        reason: &str,
+        detail: &str,
+        block_hash: Option<&[u8]>,
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO token_invalid_operations (token_id, operation, txid, vout, reason, detail, block_hash, block_height)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT (txid, vout) DO NOTHING"
+        )
+        .bind(token_id)
+        .bind(operation)
+        .bind(txid)
+        .bind(vout)
+        .bind(reason)
+        .bind(detail)
+        .bind(block_hash)
+        .bind(block_height)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Get rejected operations for a token
+    pub async fn get_invalid_operations(
+        &self,
+        token_id: i32,
+        page: i32,
+        per_page: i32,
+        snapshot_id: Option<&str>,
+    ) -> Result<PaginatedResponse<InvalidOperationResponse>> {
+        let mut tx = anchor_api_snapshot::begin(&self.pool, snapshot_id).await?;
+        let offset = (page - 1) * per_page;
+
+        let rows = sqlx::query(
+            "SELECT i.id, i.token_id, t.ticker, i.operation, i.txid, i.vout, i.reason, i.detail,
+                    i.block_height, i.created_at
+             FROM token_invalid_operations i
+             JOIN tokens t ON t.id = i.token_id
+             WHERE i.token_id = $1
+             ORDER BY i.block_height DESC NULLS FIRST, i.id DESC
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(token_id)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let total: (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM token_invalid_operations WHERE token_id = $1")
+                .bind(token_id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let op_names = ["", "DEPLOY", "MINT", "TRANSFER", "BURN", "SPLIT", "APPROVE"];
+        let total_pages = ((total.0 as f64) / (per_page as f64)).ceil() as i32;
+
+        Ok(PaginatedResponse {
+            data: rows
+                .into_iter()
+                .map(|row| {
+                    let op: i16 = row.get("operation");
+                    InvalidOperationResponse {
+                        id: row.get("id"),
+                        token_id: row.get("token_id"),
+                        ticker: row.get("ticker"),
+                        operation: op_names.get(op as usize).unwrap_or(&"UNKNOWN").to_string(),
+                        txid: hex::encode(row.get::<Vec<u8>, _>("txid")),
+                        vout: row.get("vout"),
+                        reason: row.get("reason"),
+                        detail: row.get("detail"),
+                        block_height: row.get("block_height"),
+                        created_at: row.get("created_at"),
+                    }
+                })
+                .collect(),
+            total: total.0,
+            page,
+            per_page,
+            total_pages,
+        })
+    }
+
     // ========================================================================
     // Statistics
     // ========================================================================
@@ -829,4 +1316,351 @@ impl Database {
 
         Ok(row.0 > 0)
     }
+
+    // ========================================================================
+    // Airdrops
+    // ========================================================================
+
+    /// Create an airdrop with its batches, each `(recipients, amount)`
+    pub async fn create_airdrop(
+        &self,
+        token_id: i32,
+        carrier: i16,
+        fee_rate: f64,
+        total_amount: &str,
+        batches: &[(Vec<AllocationInput>, String)],
+    ) -> Result<i32> {
+        let recipient_count: usize = batches.iter().map(|(r, _)| r.len()).sum();
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "INSERT INTO token_airdrops (token_id, carrier, fee_rate, total_amount, recipient_count)
+             VALUES ($1, $2, $3, $4::numeric, $5)
+             RETURNING id",
+        )
+        .bind(token_id)
+        .bind(carrier)
+        .bind(fee_rate)
+        .bind(total_amount)
+        .bind(recipient_count as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+        let id: i32 = row.get("id");
+
+        for (index, (recipients, amount)) in batches.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO token_airdrop_batches (airdrop_id, batch_index, recipients, amount)
+                 VALUES ($1, $2, $3, $4::numeric)",
+            )
+            .bind(id)
+            .bind(index as i32)
+            .bind(sqlx::types::Json(recipients))
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Get an airdrop by ID
+    pub async fn get_airdrop(&self, id: i32) -> Result<Option<AirdropRow>> {
+        let row = sqlx::query_as::<_, AirdropRow>(
+            "SELECT a.id, a.token_id, t.ticker, a.carrier, a.fee_rate,
+                    a.total_amount::text as total_amount, a.recipient_count, a.status, a.error,
+                    a.change_txid, a.change_vout, a.change_amount::text as change_amount,
+                    a.created_at, a.updated_at
+             FROM token_airdrops a
+             JOIN tokens t ON t.id = a.token_id
+             WHERE a.id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Get the batches of an airdrop in order
+    pub async fn get_airdrop_batches(&self, airdrop_id: i32) -> Result<Vec<AirdropBatchRow>> {
+        let rows = sqlx::query_as::<_, AirdropBatchRow>(
+            "SELECT batch_index, recipients, amount::text as amount, status, txid, error, sent_at
+             FROM token_airdrop_batches
+             WHERE airdrop_id = $1
+             ORDER BY batch_index",
+        )
+        .bind(airdrop_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Mark a pending airdrop as running
+    ///
+    /// Returns false if it is not pending, so only one runner works on an
+    /// airdrop at a time.
+    pub async fn claim_airdrop(&self, id: i32) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE token_airdrops SET status = 'running', error = NULL, updated_at = NOW()
+             WHERE id = $1 AND status = 'pending'",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Set an airdrop's status
+    pub async fn set_airdrop_status(
+        &self,
+        id: i32,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE token_airdrops SET status = $2, error = $3, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Set an airdrop batch's status
+    pub async fn set_airdrop_batch_status(
+        &self,
+        airdrop_id: i32,
+        batch_index: i32,
+        status: &str,
+        txid: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE token_airdrop_batches
+             SET status = $3, txid = COALESCE($4, txid), error = $5,
+                 sent_at = CASE WHEN $3 = 'sent' THEN NOW() ELSE sent_at END
+             WHERE airdrop_id = $1 AND batch_index = $2",
+        )
+        .bind(airdrop_id)
+        .bind(batch_index)
+        .bind(status)
+        .bind(txid)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the token change output the next batch spends
+    pub async fn set_airdrop_change(
+        &self,
+        id: i32,
+        change: Option<(&str, i32, &str)>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE token_airdrops
+             SET change_txid = $2, change_vout = $3, change_amount = $4::numeric, updated_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(change.map(|c| c.0))
+        .bind(change.map(|c| c.1))
+        .bind(change.map(|c| c.2))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Queue a failed airdrop's failed batches again
+    ///
+    /// Returns false if the airdrop is not failed.
+    pub async fn reset_failed_airdrop(&self, id: i32) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            "UPDATE token_airdrops SET status = 'pending', error = NULL, updated_at = NOW()
+             WHERE id = $1 AND status = 'failed'",
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            "UPDATE token_airdrop_batches SET status = 'pending', error = NULL
+             WHERE airdrop_id = $1 AND status = 'failed'",
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Settle airdrops that were running when the service stopped
+    ///
+    /// A batch caught mid-send may or may not have been broadcast, so it is
+    /// failed for an operator to check; airdrops without such a batch go
+    /// back to pending. Returns the airdrops that can resume.
+    pub async fn recover_interrupted_airdrops(&self) -> Result<Vec<i32>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "UPDATE token_airdrop_batches
+             SET status = 'failed',
+                 error = 'Interrupted while sending; check the wallet for this transfer before resuming'
+             WHERE status = 'sending'",
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "UPDATE token_airdrops a
+             SET status = 'failed', error = 'Interrupted while sending a batch', updated_at = NOW()
+             WHERE a.status = 'running' AND EXISTS (
+                 SELECT 1 FROM token_airdrop_batches b
+                 WHERE b.airdrop_id = a.id AND b.status = 'failed'
+             )",
+        )
+        .execute(&mut *tx)
+        .await?;
+        let rows = sqlx::query(
+            "UPDATE token_airdrops SET status = 'pending', updated_at = NOW()
+             WHERE status IN ('running', 'pending')
+             RETURNING id",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(rows.into_iter().map(|row| row.get("id")).collect())
+    }
+
+    // ========================================================================
+    // Webhooks
+    // ========================================================================
+
+    /// Register a webhook for token activity touching an address
+    pub async fn create_webhook(
+        &self,
+        address: &str,
+        url: &str,
+        secret: &str,
+    ) -> Result<WebhookRow> {
+        let row = sqlx::query_as::<_, WebhookRow>(
+            "INSERT INTO token_webhooks (address, url, secret)
+             VALUES ($1, $2, $3)
+             RETURNING id, address, url, secret, active, created_at",
+        )
+        .bind(address)
+        .bind(url)
+        .bind(secret)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Get a webhook by ID
+    pub async fn get_webhook(&self, id: i32) -> Result<Option<WebhookRow>> {
+        let row = sqlx::query_as::<_, WebhookRow>(
+            "SELECT id, address, url, secret, active, created_at FROM token_webhooks WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// List webhooks, optionally only those for one address
+    pub async fn list_webhooks(&self, address: Option<&str>) -> Result<Vec<WebhookRow>> {
+        let rows = sqlx::query_as::<_, WebhookRow>(
+            "SELECT id, address, url, secret, active, created_at
+             FROM token_webhooks
+             WHERE $1::text IS NULL OR address = $1
+             ORDER BY id",
+        )
+        .bind(address)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Delete a webhook and its delivery log
+    pub async fn delete_webhook(&self, id: i32) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM token_webhooks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Queue a delivery to every webhook watching an address the operation touches
+    async fn enqueue_operation_webhooks(&self, operation_id: i32) -> Result<()> {
+        let rows = sqlx::query(
+            "SELECT w.id as webhook_id, w.address as watched_address,
+                    o.id, o.token_id, t.ticker, o.operation, o.txid, o.vout,
+                    o.amount::text as amount, o.from_address, o.to_address,
+                    o.block_height, o.created_at
+             FROM token_operations o
+             JOIN tokens t ON t.id = o.token_id
+             JOIN token_webhooks w
+               ON w.active AND (w.address = o.from_address OR w.address = o.to_address)
+             WHERE o.id = $1",
+        )
+        .bind(operation_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let op_names = ["", "DEPLOY", "MINT", "TRANSFER", "BURN", "SPLIT", "APPROVE"];
+        for row in rows {
+            let op: i16 = row.get("operation");
+            let operation = op_names.get(op as usize).unwrap_or(&"UNKNOWN");
+            let watched: String = row.get("watched_address");
+            let from_address: Option<String> = row.get("from_address");
+            let to_address: Option<String> = row.get("to_address");
+            let direction = if to_address.as_deref() == Some(watched.as_str()) {
+                "in"
+            } else {
+                "out"
+            };
+            let payload = serde_json::json!({
+                "address": watched,
+                "direction": direction,
+                "operation": TokenOperationResponse {
+                    id: row.get("id"),
+                    token_id: row.get("token_id"),
+                    ticker: row.get("ticker"),
+                    operation: operation.to_string(),
+                    txid: hex::encode(row.get::<Vec<u8>, _>("txid")),
+                    vout: row.get("vout"),
+                    amount: row.get("amount"),
+                    from_address,
+                    to_address,
+                    block_height: row.get("block_height"),
+                    created_at: row.get("created_at"),
+                },
+            });
+
+            sqlx::query(
+                "INSERT INTO token_webhook_deliveries (webhook_id, operation_id, event, payload)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(row.get::<i32, _>("webhook_id"))
+            .bind(operation_id)
+            .bind(format!("token.{}", operation.to_lowercase()))
+            .bind(payload)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Delivery queue of the token webhooks
+    pub fn webhook_queue(&self) -> WebhookQueue {
+        WebhookQueue::new(
+            self.pool.clone(),
+            "token_webhooks",
+            "token_webhook_deliveries",
+        )
+    }
 }
@@ -1,21 +1,29 @@
 //! HTTP API handlers for Anchor Tokens
 
+use anchor_api_error::ApiError;
+use anchor_api_snapshot::{SnapshotParams, Snapshots};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use serde_json::json;
+use std::sync::Arc;
 use tracing::error;
 use utoipa::ToSchema;
 
-use crate::db::Database;
+use crate::airdrop;
+use crate::db::{Database, WebhookRow};
 use crate::models::{
-    BurnTokenRequest, CreateTxResponse, DeployTokenRequest, HealthResponse, ListParams,
-    MintTokenRequest, PaginatedResponse, Token, TokenAllocation, TokenBalance, TokenHolder,
-    TokenOperation, TokenOperationResponse, TokenSpec, TokenStats, TokenUtxo, TransferTokenRequest,
+    parse_owner, AirdropBatchResponse, AirdropRequest, AirdropResponse, AllocationInput,
+    ApproveTokenRequest, BurnTokenRequest, CreateTxResponse, CreateWebhookRequest,
+    DeployTokenRequest, HealthResponse, InvalidOperationResponse, ListParams, MintTokenRequest,
+    PaginatedResponse, ReplayWebhookRequest, ReplayWebhookResponse, TickerAvailability, Token,
+    TokenAllocation, TokenAllowance, TokenBalance, TokenHolder, TokenOperation,
+    TokenOperationResponse, TokenSpec, TokenStats, TokenUtxo, TransferTokenRequest,
+    WebhookDeliveryResponse, WebhookResponse,
 };
+use crate::tickers::TickerPolicy;
 use anchor_specs::KindSpec;
 
 /// Application state
@@ -23,6 +31,8 @@ use anchor_specs::KindSpec;
 pub struct AppState {
     pub db: Database,
     pub wallet_url: String,
+    pub snapshots: Arc<Snapshots>,
+    pub ticker_policy: TickerPolicy,
 }
 
 // ============================================================================
@@ -71,19 +81,42 @@ pub async fn get_stats(State(state): State<AppState>) -> Result<Json<TokenStats>
     params(
         ("page" = Option<i32>, Query, description = "Page number"),
         ("per_page" = Option<i32>, Query, description = "Items per page"),
-        ("search" = Option<String>, Query, description = "Search term")
+        ("search" = Option<String>, Query, description = "Search term"),
+        ("owned_by" = Option<String>, Query, description = "Only tokens currently held by this address or hex script"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
-        (status = 200, description = "List of tokens", body = PaginatedResponse<Token>)
+        (status = 200, description = "List of tokens", body = PaginatedResponse<Token>),
+        (status = 400, description = "Invalid owned_by"),
+        (status = 404, description = "Snapshot not found or expired")
     )
 )]
 pub async fn list_tokens(
     State(state): State<AppState>,
     Query(params): Query<ListParams>,
+    Query(snapshot): Query<SnapshotParams>,
 ) -> Result<Json<PaginatedResponse<Token>>, AppError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
+    let owner = params
+        .owned_by
+        .as_deref()
+        .map(|owner| {
+            parse_owner(owner).ok_or_else(|| {
+                AppError::BadRequest("owned_by must be an address or a hex script".to_string())
+            })
+        })
+        .transpose()?;
     let result = state
         .db
-        .list_tokens(params.page, params.per_page, params.search.as_deref())
+        .list_tokens(
+            params.page,
+            params.per_page,
+            params.search.as_deref(),
+            owner
+                .as_ref()
+                .map(|(script, address)| (script.as_slice(), address.as_deref())),
+            snapshot_id.as_deref(),
+        )
         .await?;
     Ok(Json(result))
 }
@@ -113,6 +146,31 @@ pub async fn get_token(
     Ok(Json(token))
 }
 
+/// Get token by the numeric id that mint, transfer and burn messages reference
+#[utoipa::path(
+    get,
+    path = "/tokens/by-id/{id}",
+    tag = "Tokens",
+    params(
+        ("id" = i32, Path, description = "Token ID")
+    ),
+    responses(
+        (status = 200, description = "Token details", body = Token),
+        (status = 404, description = "Token not found")
+    )
+)]
+pub async fn get_token_by_id(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Token>, AppError> {
+    let token = state
+        .db
+        .get_token_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Token {} not found", id)))?;
+    Ok(Json(token))
+}
+
 /// Get token holders
 #[utoipa::path(
     get,
@@ -121,18 +179,21 @@ pub async fn get_token(
     params(
         ("ticker" = String, Path, description = "Token ticker symbol"),
         ("page" = Option<i32>, Query, description = "Page number"),
-        ("per_page" = Option<i32>, Query, description = "Items per page")
+        ("per_page" = Option<i32>, Query, description = "Items per page"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
         (status = 200, description = "List of token holders", body = PaginatedResponse<TokenHolder>),
-        (status = 404, description = "Token not found")
+        (status = 404, description = "Token or snapshot not found")
     )
 )]
 pub async fn get_token_holders(
     State(state): State<AppState>,
     Path(ticker): Path<String>,
     Query(params): Query<ListParams>,
+    Query(snapshot): Query<SnapshotParams>,
 ) -> Result<Json<PaginatedResponse<TokenHolder>>, AppError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
     let token = state
         .db
         .get_token_by_ticker(&ticker)
@@ -141,7 +202,12 @@ pub async fn get_token_holders(
 
     let result = state
         .db
-        .get_token_holders(token.id, params.page, params.per_page)
+        .get_token_holders(
+            token.id,
+            params.page,
+            params.per_page,
+            snapshot_id.as_deref(),
+        )
         .await?;
     Ok(Json(result))
 }
@@ -154,18 +220,21 @@ pub async fn get_token_holders(
     params(
         ("ticker" = String, Path, description = "Token ticker symbol"),
         ("page" = Option<i32>, Query, description = "Page number"),
-        ("per_page" = Option<i32>, Query, description = "Items per page")
+        ("per_page" = Option<i32>, Query, description = "Items per page"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
     ),
     responses(
         (status = 200, description = "Token operation history", body = PaginatedResponse<TokenOperationResponse>),
-        (status = 404, description = "Token not found")
+        (status = 404, description = "Token or snapshot not found")
     )
 )]
 pub async fn get_token_history(
     State(state): State<AppState>,
     Path(ticker): Path<String>,
     Query(params): Query<ListParams>,
+    Query(snapshot): Query<SnapshotParams>,
 ) -> Result<Json<PaginatedResponse<TokenOperationResponse>>, AppError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
     let token = state
         .db
         .get_token_by_ticker(&ticker)
@@ -174,11 +243,108 @@ pub async fn get_token_history(
 
     let result = state
         .db
-        .get_token_history(token.id, params.page, params.per_page)
+        .get_token_history(
+            token.id,
+            params.page,
+            params.per_page,
+            snapshot_id.as_deref(),
+        )
         .await?;
     Ok(Json(result))
 }
 
+/// Get operations the indexer rejected for a token
+///
+/// Lists mints, transfers, splits and burns that had no effect, with a reason
+/// code such as `over_spend` or `input_not_spent`.
+#[utoipa::path(
+    get,
+    path = "/tokens/{ticker}/invalid",
+    tag = "Tokens",
+    params(
+        ("ticker" = String, Path, description = "Token ticker symbol"),
+        ("page" = Option<i32>, Query, description = "Page number"),
+        ("per_page" = Option<i32>, Query, description = "Items per page"),
+        ("snapshot" = Option<String>, Query, description = "Snapshot token from POST /snapshots")
+    ),
+    responses(
+        (status = 200, description = "Rejected token operations", body = PaginatedResponse<InvalidOperationResponse>),
+        (status = 404, description = "Token or snapshot not found")
+    )
+)]
+pub async fn get_invalid_operations(
+    State(state): State<AppState>,
+    Path(ticker): Path<String>,
+    Query(params): Query<ListParams>,
+    Query(snapshot): Query<SnapshotParams>,
+) -> Result<Json<PaginatedResponse<InvalidOperationResponse>>, AppError> {
+    let snapshot_id = state.snapshots.resolve(snapshot.snapshot.as_deref())?;
+    let token = state
+        .db
+        .get_token_by_ticker(&ticker)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Token {} not found", ticker)))?;
+
+    let result = state
+        .db
+        .get_invalid_operations(
+            token.id,
+            params.page,
+            params.per_page,
+            snapshot_id.as_deref(),
+        )
+        .await?;
+    Ok(Json(result))
+}
+
+/// Check whether a ticker can be deployed
+///
+/// Applies the same ticker policy and registry lookup the indexer uses, so a
+/// deploy built after an `available: true` answer is only lost to a competing
+/// deploy that confirms first.
+#[utoipa::path(
+    get,
+    path = "/tickers/{name}/availability",
+    tag = "Tokens",
+    params(
+        ("name" = String, Path, description = "Ticker to check")
+    ),
+    responses(
+        (status = 200, description = "Ticker availability", body = TickerAvailability)
+    )
+)]
+pub async fn get_ticker_availability(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<TickerAvailability>, AppError> {
+    let policy = &state.ticker_policy;
+    let mut availability = TickerAvailability {
+        ticker: name.clone(),
+        normalized: policy.normalize(&name),
+        available: false,
+        reason: None,
+        detail: None,
+        registered: None,
+    };
+
+    match policy.check(&name) {
+        Err(violation) => {
+            availability.reason = Some(violation.code().to_string());
+            availability.detail = Some(violation.to_string());
+        }
+        Ok(normalized) => match state.db.get_registered_token(&normalized).await? {
+            Some(holder) => {
+                availability.reason = Some("taken".to_string());
+                availability.detail = Some(format!("collides with {}", holder.ticker));
+                availability.registered = Some(holder);
+            }
+            None => availability.available = true,
+        },
+    }
+
+    Ok(Json(availability))
+}
+
 // ============================================================================
 // Address Endpoints
 // ============================================================================
@@ -189,20 +355,46 @@ pub async fn get_token_history(
     path = "/address/{address}/balances",
     tag = "Address",
     params(
-        ("address" = String, Path, description = "Bitcoin address")
+        ("address" = String, Path, description = "Bitcoin address"),
+        ("at_height" = Option<i32>, Query, description = "Return balances as of this block height")
     ),
     responses(
-        (status = 200, description = "Token balances for address", body = Vec<TokenBalance>)
+        (status = 200, description = "Token balances for address", body = Vec<TokenBalance>),
+        (status = 400, description = "Height is negative or not indexed yet")
     )
 )]
 pub async fn get_address_balances(
     State(state): State<AppState>,
     Path(address): Path<String>,
+    Query(params): Query<AtHeightParams>,
 ) -> Result<Json<Vec<TokenBalance>>, AppError> {
-    let balances = state.db.get_address_balances(&address).await?;
+    let balances = match params.at_height {
+        Some(height) => {
+            check_indexed_height(&state, height).await?;
+            state.db.get_address_balances_at(&address, height).await?
+        }
+        None => state.db.get_address_balances(&address).await?,
+    };
     Ok(Json(balances))
 }
 
+/// Reject heights the indexer hasn't reached yet
+async fn check_indexed_height(state: &AppState, height: i32) -> Result<(), AppError> {
+    if height < 0 {
+        return Err(AppError::BadRequest(
+            "at_height must not be negative".to_string(),
+        ));
+    }
+    let indexed = state.db.get_last_block_height().await?;
+    if height > indexed {
+        return Err(AppError::BadRequest(format!(
+            "Height {} is not indexed yet (last indexed block is {})",
+            height, indexed
+        )));
+    }
+    Ok(())
+}
+
 /// Get address token UTXOs
 #[utoipa::path(
     get,
@@ -236,11 +428,38 @@ pub async fn get_address_utxos(
     Ok(Json(utxos))
 }
 
+/// Get allowances granted by or to an address
+///
+/// Lists unused allowances whose owner or spender output pays the address.
+#[utoipa::path(
+    get,
+    path = "/address/{address}/allowances",
+    tag = "Address",
+    params(
+        ("address" = String, Path, description = "Bitcoin address")
+    ),
+    responses(
+        (status = 200, description = "Allowances for address", body = Vec<TokenAllowance>)
+    )
+)]
+pub async fn get_address_allowances(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<Vec<TokenAllowance>>, AppError> {
+    let allowances = state.db.get_address_allowances(&address).await?;
+    Ok(Json(allowances))
+}
+
 #[derive(Debug, serde::Deserialize, ToSchema)]
 pub struct UtxoParams {
     pub ticker: Option<String>,
 }
 
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct AtHeightParams {
+    pub at_height: Option<i32>,
+}
+
 /// Get address operation history
 #[utoipa::path(
     get,
@@ -468,15 +687,16 @@ pub async fn create_deploy_tx(
     Json(request): Json<DeployTokenRequest>,
 ) -> Result<Json<CreateTxResponse>, AppError> {
     // Validate ticker
-    if !crate::models::is_valid_ticker(&request.ticker) {
-        return Err(AppError::BadRequest("Invalid ticker format".to_string()));
-    }
+    let normalized = state
+        .ticker_policy
+        .check(&request.ticker)
+        .map_err(|v| AppError::BadRequest(format!("Invalid ticker: {}", v)))?;
 
     // Check if ticker is available
-    if !state.db.is_ticker_available(&request.ticker).await? {
+    if let Some(holder) = state.db.get_registered_token(&normalized).await? {
         return Err(AppError::BadRequest(format!(
-            "Ticker {} is already registered",
-            request.ticker
+            "Ticker {} is already registered as {}",
+            request.ticker, holder.ticker
         )));
     }
 
@@ -576,7 +796,7 @@ pub async fn create_mint_tx(
 
 /// Reverse txid hex string bytes (convert between internal and display format)
 /// Bitcoin txids are stored in internal format (reversed) but displayed/used in API in reverse
-fn reverse_txid_hex(txid: &str) -> String {
+pub(crate) fn reverse_txid_hex(txid: &str) -> String {
     let bytes: Vec<_> = (0..txid.len())
         .step_by(2)
         .filter_map(|i| u8::from_str_radix(&txid[i..i + 2], 16).ok())
@@ -585,7 +805,7 @@ fn reverse_txid_hex(txid: &str) -> String {
 }
 
 /// Lock a UTXO to prevent it from being spent by other wallet transactions
-async fn lock_utxo(txid: &str, vout: u32) -> Result<(), AppError> {
+pub(crate) async fn lock_utxo(txid: &str, vout: u32) -> Result<(), AppError> {
     let client = reqwest::Client::new();
     let bitcoin_rpc_url =
         std::env::var("BITCOIN_RPC_URL").unwrap_or_else(|_| "http://bitcoin:18443".to_string());
@@ -635,7 +855,7 @@ async fn lock_utxo(txid: &str, vout: u32) -> Result<(), AppError> {
 }
 
 /// Unlock a UTXO so it can be spent
-async fn unlock_utxo(txid: &str, vout: u32) -> Result<(), AppError> {
+pub(crate) async fn unlock_utxo(txid: &str, vout: u32) -> Result<(), AppError> {
     let client = reqwest::Client::new();
     let bitcoin_rpc_url =
         std::env::var("BITCOIN_RPC_URL").unwrap_or_else(|_| "http://bitcoin:18443".to_string());
@@ -671,49 +891,18 @@ async fn unlock_utxo(txid: &str, vout: u32) -> Result<(), AppError> {
     Ok(())
 }
 
-/// Create a transfer transaction
-#[utoipa::path(
-    post,
-    path = "/tx/transfer",
-    tag = "Transactions",
-    request_body = TransferTokenRequest,
-    responses(
-        (status = 200, description = "Transfer transaction created", body = CreateTxResponse),
-        (status = 400, description = "Invalid request or insufficient balance"),
-        (status = 404, description = "Token not found")
-    )
-)]
-pub async fn create_transfer_tx(
-    State(state): State<AppState>,
-    Json(request): Json<TransferTokenRequest>,
-) -> Result<Json<CreateTxResponse>, AppError> {
-    // Get token
-    let token = state
-        .db
-        .get_token_by_ticker(&request.ticker)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Token {} not found", request.ticker)))?;
-
-    // Calculate total amount needed
-    let total_amount: u128 = request
-        .allocations
-        .iter()
-        .map(|a| a.amount.parse::<u128>().unwrap_or(0))
-        .sum();
-
-    if total_amount == 0 {
-        return Err(AppError::BadRequest(
-            "Total transfer amount must be greater than 0".to_string(),
-        ));
-    }
-
+/// Token UTXOs of `token_id` that the wallet owns and that are still unspent
+pub(crate) async fn wallet_token_utxos(
+    db: &Database,
+    token_id: i32,
+) -> Result<Vec<TokenUtxo>, AppError> {
     // Get wallet's token UTXOs for this token
-    let all_token_utxos = state.db.get_all_unspent_token_utxos().await?;
+    let all_token_utxos = db.get_all_unspent_token_utxos().await?;
 
     // Filter to only this token's UTXOs (we need to check wallet ownership)
     let token_utxos: Vec<_> = all_token_utxos
         .into_iter()
-        .filter(|u| u.token_id == token.id)
+        .filter(|u| u.token_id == token_id)
         .collect();
 
     if token_utxos.is_empty() {
@@ -784,6 +973,16 @@ pub async fn create_transfer_tx(
         return Err(AppError::BadRequest("No spendable token UTXOs available. All token UTXOs have been spent as Bitcoin outputs.".to_string()));
     }
 
+    Ok(wallet_utxos)
+}
+
+/// Pick token UTXOs covering `amount`, largest first
+///
+/// Returns the selection and its total.
+pub(crate) fn select_token_utxos(
+    mut wallet_utxos: Vec<TokenUtxo>,
+    amount: u128,
+) -> Result<(Vec<TokenUtxo>, u128), AppError> {
     // Select UTXOs to cover the transfer amount (greedy selection)
     let mut selected_utxos: Vec<TokenUtxo> = Vec::new();
     let mut selected_amount: u128 = 0;
@@ -800,18 +999,60 @@ pub async fn create_transfer_tx(
         selected_utxos.push(utxo);
         selected_amount += utxo_amount;
 
-        if selected_amount >= total_amount {
+        if selected_amount >= amount {
             break;
         }
     }
 
-    if selected_amount < total_amount {
+    if selected_amount < amount {
         return Err(AppError::BadRequest(format!(
             "Insufficient balance. Have {} but need {}",
-            selected_amount, total_amount
+            selected_amount, amount
         )));
     }
 
+    Ok((selected_utxos, selected_amount))
+}
+
+/// Create a transfer transaction
+#[utoipa::path(
+    post,
+    path = "/tx/transfer",
+    tag = "Transactions",
+    request_body = TransferTokenRequest,
+    responses(
+        (status = 200, description = "Transfer transaction created", body = CreateTxResponse),
+        (status = 400, description = "Invalid request or insufficient balance"),
+        (status = 404, description = "Token not found")
+    )
+)]
+pub async fn create_transfer_tx(
+    State(state): State<AppState>,
+    Json(request): Json<TransferTokenRequest>,
+) -> Result<Json<CreateTxResponse>, AppError> {
+    // Get token
+    let token = state
+        .db
+        .get_token_by_ticker(&request.ticker)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Token {} not found", request.ticker)))?;
+
+    // Calculate total amount needed
+    let total_amount: u128 = request
+        .allocations
+        .iter()
+        .map(|a| a.amount.parse::<u128>().unwrap_or(0))
+        .sum();
+
+    if total_amount == 0 {
+        return Err(AppError::BadRequest(
+            "Total transfer amount must be greater than 0".to_string(),
+        ));
+    }
+
+    let (selected_utxos, selected_amount) =
+        select_token_utxos(wallet_token_utxos(&state.db, token.id).await?, total_amount)?;
+
     // Build allocations for the transfer
     // Output 0 = change (if any), subsequent outputs = recipients
     let change_amount = selected_amount - total_amount;
@@ -918,6 +1159,98 @@ pub async fn create_transfer_tx(
     Ok(Json(response))
 }
 
+/// Create an approve transaction
+///
+/// Moves enough of the wallet's balance to cover the allowance onto output 0
+/// and sends a dust output to the spender. Whoever spends that output may
+/// transfer up to `amount` out of output 0 once.
+#[utoipa::path(
+    post,
+    path = "/tx/approve",
+    tag = "Transactions",
+    request_body = ApproveTokenRequest,
+    responses(
+        (status = 200, description = "Approve transaction created", body = CreateTxResponse),
+        (status = 400, description = "Invalid request or insufficient balance"),
+        (status = 404, description = "Token not found")
+    )
+)]
+pub async fn create_approve_tx(
+    State(state): State<AppState>,
+    Json(request): Json<ApproveTokenRequest>,
+) -> Result<Json<CreateTxResponse>, AppError> {
+    let token = state
+        .db
+        .get_token_by_ticker(&request.ticker)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Token {} not found", request.ticker)))?;
+
+    let amount: u128 = request
+        .amount
+        .parse()
+        .map_err(|_| AppError::BadRequest("Invalid amount".to_string()))?;
+    if amount == 0 {
+        return Err(AppError::BadRequest(
+            "Allowance must be greater than 0".to_string(),
+        ));
+    }
+
+    let (selected_utxos, _) =
+        select_token_utxos(wallet_token_utxos(&state.db, token.id).await?, amount)?;
+
+    // Output 0 = the wallet's change, holding the selected balance; output 1 = spender
+    let spec = TokenSpec::approve(token.id as u64, amount, 1, 0);
+    let payload = spec.to_bytes();
+
+    let carrier = request.carrier.unwrap_or(4);
+    let fee_rate = request.fee_rate.unwrap_or(1.0);
+
+    for utxo in &selected_utxos {
+        let display_txid = reverse_txid_hex(&utxo.txid);
+        if let Err(e) = unlock_utxo(&display_txid, utxo.vout as u32).await {
+            tracing::debug!(
+                "Failed to unlock UTXO {}:{}: {:?}",
+                display_txid,
+                utxo.vout,
+                e
+            );
+        }
+    }
+
+    let required_inputs: Vec<serde_json::Value> = selected_utxos
+        .iter()
+        .map(|u| {
+            serde_json::json!({
+                "txid": reverse_txid_hex(&u.txid),
+                "vout": u.vout
+            })
+        })
+        .collect();
+    let custom_outputs = vec![serde_json::json!({
+        "address": request.spender,
+        "value": 546
+    })];
+
+    let response = create_wallet_tx_with_inputs(
+        &state.wallet_url,
+        &payload,
+        carrier,
+        fee_rate,
+        20,
+        &required_inputs,
+        &required_inputs,
+        &custom_outputs,
+    )
+    .await?;
+
+    // Keep the wallet from spending the approved balance as plain bitcoin
+    if let Err(e) = lock_utxo(&response.txid, 0).await {
+        tracing::debug!("Failed to lock approve output {}:0: {:?}", response.txid, e);
+    }
+
+    Ok(Json(response))
+}
+
 /// Create a burn transaction
 #[utoipa::path(
     post,
@@ -967,6 +1300,374 @@ pub async fn create_burn_tx(
     Ok(Json(response))
 }
 
+// ============================================================================
+// Airdrops
+// ============================================================================
+
+/// Start a token airdrop
+///
+/// Splits the recipients into as few transfer transactions as the carrier
+/// allows and sends them one after another in the background. Poll
+/// `GET /tx/airdrop/{id}` for progress.
+#[utoipa::path(
+    post,
+    path = "/tx/airdrop",
+    tag = "Transactions",
+    request_body = AirdropRequest,
+    responses(
+        (status = 200, description = "Airdrop started", body = AirdropResponse),
+        (status = 400, description = "Invalid recipients or insufficient balance"),
+        (status = 404, description = "Token not found")
+    )
+)]
+pub async fn create_airdrop(
+    State(state): State<AppState>,
+    Json(request): Json<AirdropRequest>,
+) -> Result<Json<AirdropResponse>, AppError> {
+    let token = state
+        .db
+        .get_token_by_ticker(&request.ticker)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Token {} not found", request.ticker)))?;
+
+    let mut recipients = request.recipients;
+    if let Some(csv) = &request.csv {
+        recipients.extend(airdrop::parse_csv(csv).map_err(AppError::BadRequest)?);
+    }
+    let (amounts, total_amount) =
+        airdrop::validate_recipients(&recipients).map_err(AppError::BadRequest)?;
+
+    let carrier = request.carrier.unwrap_or(4);
+    let fee_rate = request.fee_rate.unwrap_or(1.0);
+    let max_size = airdrop::carrier_max_size(carrier).map_err(AppError::BadRequest)?;
+    let plan = airdrop::plan_batches(
+        token.id as u64,
+        &amounts,
+        max_size,
+        request
+            .max_recipients_per_tx
+            .unwrap_or(airdrop::MAX_RECIPIENTS_PER_TX),
+    )
+    .map_err(AppError::BadRequest)?;
+
+    // Fail early rather than in the background if the wallet can't cover it
+    select_token_utxos(wallet_token_utxos(&state.db, token.id).await?, total_amount)?;
+
+    let batches: Vec<(Vec<AllocationInput>, String)> = plan
+        .into_iter()
+        .map(|(start, len)| {
+            let amount: u128 = amounts[start..start + len].iter().sum();
+            (recipients[start..start + len].to_vec(), amount.to_string())
+        })
+        .collect();
+    let id = state
+        .db
+        .create_airdrop(
+            token.id,
+            carrier as i16,
+            fee_rate,
+            &total_amount.to_string(),
+            &batches,
+        )
+        .await?;
+
+    tracing::info!(
+        "Airdrop {} of {} created: {} recipients in {} transactions",
+        id,
+        token.ticker,
+        recipients.len(),
+        batches.len()
+    );
+    let response = airdrop_response(&state.db, id).await?;
+    airdrop::spawn(state, id);
+
+    Ok(Json(response))
+}
+
+/// Get airdrop progress
+#[utoipa::path(
+    get,
+    path = "/tx/airdrop/{id}",
+    tag = "Transactions",
+    params(
+        ("id" = i32, Path, description = "Airdrop ID")
+    ),
+    responses(
+        (status = 200, description = "Airdrop progress", body = AirdropResponse),
+        (status = 404, description = "Airdrop not found")
+    )
+)]
+pub async fn get_airdrop(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<AirdropResponse>, AppError> {
+    Ok(Json(airdrop_response(&state.db, id).await?))
+}
+
+/// Resume a failed airdrop
+///
+/// Retries the failed batch and sends the rest. If a batch failed while it
+/// was being sent, check the wallet for its transfer first: a resumed batch
+/// is sent again.
+#[utoipa::path(
+    post,
+    path = "/tx/airdrop/{id}/resume",
+    tag = "Transactions",
+    params(
+        ("id" = i32, Path, description = "Airdrop ID")
+    ),
+    responses(
+        (status = 200, description = "Airdrop resumed", body = AirdropResponse),
+        (status = 400, description = "Airdrop has not failed"),
+        (status = 404, description = "Airdrop not found")
+    )
+)]
+pub async fn resume_airdrop(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<AirdropResponse>, AppError> {
+    if state.db.get_airdrop(id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Airdrop {} not found", id)));
+    }
+    if !state.db.reset_failed_airdrop(id).await? {
+        return Err(AppError::BadRequest(
+            "Only a failed airdrop can be resumed".to_string(),
+        ));
+    }
+
+    let response = airdrop_response(&state.db, id).await?;
+    airdrop::spawn(state, id);
+
+    Ok(Json(response))
+}
+
+async fn airdrop_response(db: &Database, id: i32) -> Result<AirdropResponse, AppError> {
+    let airdrop = db
+        .get_airdrop(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Airdrop {} not found", id)))?;
+    let batches: Vec<AirdropBatchResponse> = db
+        .get_airdrop_batches(id)
+        .await?
+        .into_iter()
+        .map(|b| AirdropBatchResponse {
+            index: b.batch_index,
+            recipient_count: b.recipients.len(),
+            amount: b.amount,
+            status: b.status,
+            txid: b.txid,
+            error: b.error,
+            sent_at: b.sent_at,
+        })
+        .collect();
+
+    Ok(AirdropResponse {
+        id: airdrop.id,
+        ticker: airdrop.ticker,
+        status: airdrop.status,
+        error: airdrop.error,
+        total_amount: airdrop.total_amount,
+        recipient_count: airdrop.recipient_count,
+        batch_count: batches.len(),
+        sent_batches: batches.iter().filter(|b| b.status == "sent").count(),
+        batches,
+        created_at: airdrop.created_at,
+        updated_at: airdrop.updated_at,
+    })
+}
+
+// ============================================================================
+// Webhooks
+// ============================================================================
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct WebhookListParams {
+    pub address: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, ToSchema)]
+pub struct DeliveryListParams {
+    pub limit: Option<i64>,
+}
+
+fn webhook_response(row: WebhookRow, with_secret: bool) -> WebhookResponse {
+    WebhookResponse {
+        id: row.id,
+        address: row.address,
+        url: row.url,
+        active: row.active,
+        secret: with_secret.then_some(row.secret),
+        created_at: row.created_at,
+    }
+}
+
+/// Register a webhook for token activity touching an address
+///
+/// Every mint, transfer, split or burn that sends tokens to or from the
+/// address is POSTed to `url`, signed with the returned secret.
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "Webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered", body = WebhookResponse),
+        (status = 400, description = "Invalid address or URL")
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, AppError> {
+    request
+        .address
+        .parse::<bitcoin::Address<bitcoin::address::NetworkUnchecked>>()
+        .map_err(|e| AppError::BadRequest(format!("Invalid address: {}", e)))?;
+    anchor_webhooks::validate_url(&request.url).map_err(AppError::BadRequest)?;
+    let secret = match request.secret {
+        Some(secret) if secret.is_empty() => {
+            return Err(AppError::BadRequest("Secret must not be empty".to_string()))
+        }
+        Some(secret) => secret,
+        None => anchor_webhooks::generate_secret(),
+    };
+
+    let row = state
+        .db
+        .create_webhook(&request.address, &request.url, &secret)
+        .await?;
+
+    Ok(Json(webhook_response(row, true)))
+}
+
+/// List webhooks
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "Webhooks",
+    params(
+        ("address" = Option<String>, Query, description = "Only webhooks watching this address")
+    ),
+    responses(
+        (status = 200, description = "Registered webhooks", body = Vec<WebhookResponse>)
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    Query(params): Query<WebhookListParams>,
+) -> Result<Json<Vec<WebhookResponse>>, AppError> {
+    let rows = state.db.list_webhooks(params.address.as_deref()).await?;
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| webhook_response(row, false))
+            .collect(),
+    ))
+}
+
+/// Delete a webhook
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "Webhooks",
+    params(
+        ("id" = i32, Path, description = "Webhook ID")
+    ),
+    responses(
+        (status = 200, description = "Webhook deleted"),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if !state.db.delete_webhook(id).await? {
+        return Err(AppError::NotFound(format!("Webhook {} not found", id)));
+    }
+    Ok(Json(json!({ "deleted": id })))
+}
+
+/// Get a webhook's recent deliveries, newest first
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "Webhooks",
+    params(
+        ("id" = i32, Path, description = "Webhook ID"),
+        ("limit" = Option<i64>, Query, description = "Deliveries to return (default: 50, max: 500)")
+    ),
+    responses(
+        (status = 200, description = "Deliveries", body = Vec<WebhookDeliveryResponse>),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn get_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<DeliveryListParams>,
+) -> Result<Json<Vec<WebhookDeliveryResponse>>, AppError> {
+    if state.db.get_webhook(id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Webhook {} not found", id)));
+    }
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let rows = state.db.webhook_queue().deliveries(id, limit).await?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| WebhookDeliveryResponse {
+                id: row.id,
+                event: row.event,
+                status: row.status,
+                attempts: row.attempts,
+                response_status: row.response_status,
+                last_error: row.last_error,
+                payload: row.payload.0,
+                created_at: row.created_at,
+                delivered_at: row.delivered_at,
+            })
+            .collect(),
+    ))
+}
+
+/// Send past deliveries again
+///
+/// Give `deliveryId` to replay one delivery or `since` to replay everything
+/// from that time on. Replays keep their delivery IDs.
+#[utoipa::path(
+    post,
+    path = "/webhooks/{id}/replay",
+    tag = "Webhooks",
+    params(
+        ("id" = i32, Path, description = "Webhook ID")
+    ),
+    request_body = ReplayWebhookRequest,
+    responses(
+        (status = 200, description = "Deliveries queued", body = ReplayWebhookResponse),
+        (status = 400, description = "Neither deliveryId nor since given"),
+        (status = 404, description = "Webhook not found")
+    )
+)]
+pub async fn replay_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(request): Json<ReplayWebhookRequest>,
+) -> Result<Json<ReplayWebhookResponse>, AppError> {
+    if request.delivery_id.is_none() && request.since.is_none() {
+        return Err(AppError::BadRequest("Give deliveryId or since".to_string()));
+    }
+    if state.db.get_webhook(id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Webhook {} not found", id)));
+    }
+
+    let queued = state
+        .db
+        .webhook_queue()
+        .replay(id, request.delivery_id, request.since)
+        .await?;
+
+    Ok(Json(ReplayWebhookResponse { queued }))
+}
+
 // ============================================================================
 // Wallet Integration
 // ============================================================================
@@ -997,8 +1698,9 @@ async fn create_wallet_tx(
         .map_err(|e| AppError::Internal(format!("Wallet request failed: {}", e)))?;
 
     if !response.status().is_success() {
+        let status = response.status().as_u16();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(AppError::Internal(format!("Wallet error: {}", error_text)));
+        return Err(ApiError::from_upstream(status, &error_text).into());
     }
 
     let result: serde_json::Value = response
@@ -1011,7 +1713,7 @@ async fn create_wallet_tx(
 
 /// Create a wallet transaction with required inputs and custom outputs for token transfers
 #[allow(clippy::too_many_arguments)]
-async fn create_wallet_tx_with_inputs(
+pub(crate) async fn create_wallet_tx_with_inputs(
     wallet_url: &str,
     body: &[u8],
     carrier: u8,
@@ -1043,8 +1745,9 @@ async fn create_wallet_tx_with_inputs(
         .map_err(|e| AppError::Internal(format!("Wallet request failed: {}", e)))?;
 
     if !response.status().is_success() {
+        let status = response.status().as_u16();
         let error_text = response.text().await.unwrap_or_default();
-        return Err(AppError::Internal(format!("Wallet error: {}", error_text)));
+        return Err(ApiError::from_upstream(status, &error_text).into());
     }
 
     let result: serde_json::Value = response
@@ -1088,20 +1791,35 @@ pub enum AppError {
     NotFound(String),
     BadRequest(String),
     Internal(String),
+    /// Error with a specific code, e.g. one relayed from the wallet
+    Api(ApiError),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
-        let (status, message) = match self {
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+        let error = match self {
+            AppError::NotFound(msg) => ApiError::not_found(msg),
+            AppError::BadRequest(msg) => ApiError::bad_request(msg),
             AppError::Internal(msg) => {
                 error!("Internal error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, msg)
+                ApiError::internal(msg)
             }
+            AppError::Api(err) => err,
         };
 
-        (status, Json(json!({ "error": message }))).into_response()
+        error.into_response()
+    }
+}
+
+impl From<ApiError> for AppError {
+    fn from(err: ApiError) -> Self {
+        AppError::Api(err)
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        AppError::Internal(err.to_string())
     }
 }
 
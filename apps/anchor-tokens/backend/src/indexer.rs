@@ -17,6 +17,7 @@ use crate::config::Config;
 use crate::db::Database;
 use crate::models::{DeployFlags, TokenOperation, TokenSpec};
 use crate::utxo::UtxoTracker;
+use crate::validation::{self, Rejection};
 
 use anchor_specs::KindSpec;
 
@@ -152,6 +153,14 @@ impl Indexer {
                 .index_transaction(tx, Some(&block_hash_bytes), Some(height))
                 .await?;
             op_count += count;
+
+            let txid = tx.compute_txid().to_byte_array();
+            self.utxo_tracker
+                .burn_unclaimed_inputs(tx, &txid, Some(height))
+                .await?;
+            self.utxo_tracker
+                .expire_allowances(tx, &txid, Some(height))
+                .await?;
         }
 
         self.db.update_last_block(&block_hash_bytes, height).await?;
@@ -216,9 +225,27 @@ impl Indexer {
                     mint_limit,
                     flags,
                 } => {
-                    // Check if ticker is available
-                    if !self.db.is_ticker_available(ticker).await? {
-                        debug!("Ticker {} already registered, skipping", ticker);
+                    // Check the ticker against the policy and the registry
+                    let normalized = match self.config.ticker_policy.check(ticker) {
+                        Ok(normalized) => normalized,
+                        Err(violation) => {
+                            debug!("Ticker {} rejected: {}", ticker, violation);
+                            continue;
+                        }
+                    };
+                    if let Some(holder) = self.db.get_registered_token(&normalized).await? {
+                        self.reject(
+                            holder.id,
+                            1, // DEPLOY
+                            &txid_bytes,
+                            vout as i32,
+                            &Rejection::TickerTaken {
+                                ticker: ticker.clone(),
+                            },
+                            block_hash,
+                            block_height,
+                        )
+                        .await?;
                         continue;
                     }
 
@@ -238,6 +265,10 @@ impl Indexer {
                         )
                         .await?;
 
+                    self.db
+                        .register_ticker(&normalized, token_id, block_height)
+                        .await?;
+
                     info!("Deployed token: {} (id={})", ticker, token_id);
                     token_count += 1;
                 }
@@ -255,14 +286,28 @@ impl Indexer {
                         }
                     };
 
-                    // Check if minting is allowed
-                    let flags = DeployFlags(token.flags as u8);
-                    if flags.is_fixed_supply() {
-                        debug!("Token {} has fixed supply, mint rejected", token.ticker);
+                    // Check the mint against the token's flags and supply
+                    let checked = validation::check_mint(&token, *amount, *output_index)
+                        .and_then(|_| validation::check_output(tx, *output_index));
+                    if let Err(rejection) = checked {
+                        self.reject(
+                            token.id,
+                            2, // MINT
+                            &txid_bytes,
+                            vout as i32,
+                            &rejection,
+                            block_hash,
+                            block_height,
+                        )
+                        .await?;
                         continue;
                     }
 
-                    // Get output address
+                    // Get output script and address
+                    let output_script = tx
+                        .output
+                        .get(*output_index as usize)
+                        .map(|o| o.script_pubkey.as_bytes());
                     let output_addr = tx
                         .output
                         .get(*output_index as usize)
@@ -282,6 +327,7 @@ impl Indexer {
                             &txid_bytes,
                             *output_index as i32,
                             &amount.to_string(),
+                            output_script,
                             output_addr.as_deref(),
                             block_hash,
                             block_height,
@@ -319,11 +365,6 @@ impl Indexer {
                     allocations,
                 } => {
                     // Process transfer using anchors to identify source UTXOs
-                    if message.anchors.is_empty() {
-                        debug!("Transfer without anchors, skipping");
-                        continue;
-                    }
-
                     let processed = self
                         .utxo_tracker
                         .process_transfer(
@@ -338,9 +379,23 @@ impl Indexer {
                         )
                         .await?;
 
-                    if processed {
-                        self.db.increment_tx_count(*token_id as i32).await?;
-                        token_count += 1;
+                    match processed {
+                        Ok(()) => {
+                            self.db.increment_tx_count(*token_id as i32).await?;
+                            token_count += 1;
+                        }
+                        Err(rejection) => {
+                            self.reject(
+                                *token_id as i32,
+                                3, // TRANSFER
+                                &txid_bytes,
+                                vout as i32,
+                                &rejection,
+                                block_hash,
+                                block_height,
+                            )
+                            .await?;
+                        }
                     }
                 }
                 TokenOperation::Burn { token_id, amount } => {
@@ -356,7 +411,16 @@ impl Indexer {
                     // Check if burning is allowed
                     let flags = DeployFlags(token.flags as u8);
                     if !flags.is_burnable() {
-                        debug!("Token {} is not burnable, burn rejected", token.ticker);
+                        self.reject(
+                            token.id,
+                            4, // BURN
+                            &txid_bytes,
+                            vout as i32,
+                            &Rejection::NotBurnable,
+                            block_hash,
+                            block_height,
+                        )
+                        .await?;
                         continue;
                     }
 
@@ -388,11 +452,6 @@ impl Indexer {
                     allocations,
                 } => {
                     // Split is similar to transfer but from a single source
-                    if message.anchors.is_empty() {
-                        debug!("Split without anchors, skipping");
-                        continue;
-                    }
-
                     let processed = self
                         .utxo_tracker
                         .process_transfer(
@@ -407,9 +466,64 @@ impl Indexer {
                         )
                         .await?;
 
-                    if processed {
-                        self.db.increment_tx_count(*token_id as i32).await?;
-                        token_count += 1;
+                    match processed {
+                        Ok(()) => {
+                            self.db.increment_tx_count(*token_id as i32).await?;
+                            token_count += 1;
+                        }
+                        Err(rejection) => {
+                            self.reject(
+                                *token_id as i32,
+                                5, // SPLIT
+                                &txid_bytes,
+                                vout as i32,
+                                &rejection,
+                                block_hash,
+                                block_height,
+                            )
+                            .await?;
+                        }
+                    }
+                }
+                TokenOperation::Approve {
+                    token_id,
+                    amount,
+                    spender_index,
+                    owner_index,
+                } => {
+                    let processed = self
+                        .utxo_tracker
+                        .process_approve(
+                            tx,
+                            &txid_bytes,
+                            vout as i32,
+                            *token_id as i32,
+                            *amount,
+                            *spender_index,
+                            *owner_index,
+                            &message.anchors,
+                            block_hash,
+                            block_height,
+                        )
+                        .await?;
+
+                    match processed {
+                        Ok(()) => {
+                            self.db.increment_tx_count(*token_id as i32).await?;
+                            token_count += 1;
+                        }
+                        Err(rejection) => {
+                            self.reject(
+                                *token_id as i32,
+                                6, // APPROVE
+                                &txid_bytes,
+                                vout as i32,
+                                &rejection,
+                                block_hash,
+                                block_height,
+                            )
+                            .await?;
+                        }
                     }
                 }
             }
@@ -417,4 +531,39 @@ impl Indexer {
 
         Ok(token_count)
     }
+
+    /// Record a rejected operation, if its token exists
+    #[allow(clippy::too_many_arguments)]
+    async fn reject(
+        &self,
+        token_id: i32,
+        operation: i16,
+        txid: &[u8],
+        vout: i32,
+        rejection: &Rejection,
+        block_hash: Option<&[u8]>,
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        debug!(
+            "Rejected token operation {}:{}: {}",
+            hex::encode(txid),
+            vout,
+            rejection
+        );
+        if self.db.get_token_by_id(token_id).await?.is_none() {
+            return Ok(());
+        }
+        self.db
+            .record_invalid_operation(
+                token_id,
+                operation,
+                txid,
+                vout,
+                rejection.code(),
+                &rejection.to_string(),
+                block_hash,
+                block_height,
+            )
+            .await
+    }
 }
@@ -2,20 +2,26 @@
 //!
 //! API server and indexer for the Anchor Tokens protocol.
 
+mod airdrop;
 mod config;
 mod db;
 mod handlers;
 mod indexer;
 mod models;
+mod tickers;
 mod utxo;
+mod validation;
 
 use std::net::SocketAddr;
+use std::sync::Arc;
 
+use anchor_api_security::SecurityConfig;
+use anchor_api_snapshot::Snapshots;
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post},
     Router,
 };
-use tower_http::cors::{Any, CorsLayer};
 use tracing::{info, Level};
 use tracing_subscriber::FmtSubscriber;
 use utoipa::OpenApi;
@@ -33,34 +39,63 @@ use handlers::AppState;
         handlers::get_stats,
         handlers::list_tokens,
         handlers::get_token,
+        handlers::get_token_by_id,
         handlers::get_token_holders,
         handlers::get_token_history,
+        handlers::get_invalid_operations,
+        handlers::get_ticker_availability,
         handlers::get_address_balances,
         handlers::get_address_utxos,
+        handlers::get_address_allowances,
         handlers::get_address_history,
         handlers::get_wallet_tokens,
         handlers::create_deploy_tx,
         handlers::create_mint_tx,
         handlers::create_transfer_tx,
         handlers::create_burn_tx,
+        handlers::create_approve_tx,
+        handlers::create_airdrop,
+        handlers::get_airdrop,
+        handlers::resume_airdrop,
+        handlers::create_webhook,
+        handlers::list_webhooks,
+        handlers::delete_webhook,
+        handlers::get_webhook_deliveries,
+        handlers::replay_webhook,
+        anchor_api_snapshot::create_snapshot,
+        anchor_api_snapshot::release_snapshot,
     ),
     components(schemas(
         models::HealthResponse,
         models::TokenStats,
         models::Token,
         models::TokenUtxo,
+        models::TokenAllowance,
         models::TokenBalance,
         models::TokenOperationResponse,
+        models::InvalidOperationResponse,
+        models::TickerAvailability,
         models::TokenHolder,
         models::PaginatedResponse<models::Token>,
         models::PaginatedResponse<models::TokenHolder>,
         models::PaginatedResponse<models::TokenOperationResponse>,
+        models::PaginatedResponse<models::InvalidOperationResponse>,
         models::DeployTokenRequest,
         models::MintTokenRequest,
         models::TransferTokenRequest,
         models::AllocationInput,
         models::BurnTokenRequest,
+        models::ApproveTokenRequest,
         models::CreateTxResponse,
+        models::AirdropRequest,
+        models::AirdropResponse,
+        models::AirdropBatchResponse,
+        models::CreateWebhookRequest,
+        models::WebhookResponse,
+        models::WebhookDeliveryResponse,
+        models::ReplayWebhookRequest,
+        models::ReplayWebhookResponse,
+        anchor_api_snapshot::SnapshotResponse,
         handlers::WalletTokensResponse,
     )),
     tags(
@@ -70,6 +105,8 @@ use handlers::AppState;
         (name = "Address", description = "Address token queries"),
         (name = "Wallet", description = "Wallet token operations"),
         (name = "Transactions", description = "Create token transactions"),
+        (name = "Webhooks", description = "Address activity notifications"),
+        (name = "Snapshots", description = "Consistent reads across paginated requests"),
     ),
     info(
         title = "Anchor Tokens API",
@@ -81,6 +118,10 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     FmtSubscriber::builder()
         .with_max_level(Level::INFO)
@@ -98,12 +139,29 @@ async fn main() -> anyhow::Result<()> {
     let db = Database::connect(&config.database_url).await?;
     info!("Connected to database");
 
+    let snapshots = Arc::new(Snapshots::from_env(
+        &config.database_url,
+        "SELECT last_block_height FROM token_indexer_state WHERE id = 1",
+    ));
+    snapshots.start();
+
     // Create app state
     let state = AppState {
         db: db.clone(),
         wallet_url: config.wallet_url.clone(),
+        snapshots,
+        ticker_policy: config.ticker_policy.clone(),
     };
 
+    // Pick up airdrops that were running when the service stopped
+    for id in db.recover_interrupted_airdrops().await? {
+        info!("Resuming airdrop {}", id);
+        airdrop::spawn(state.clone(), id);
+    }
+
+    // Send queued webhook deliveries
+    anchor_webhooks::start(db.webhook_queue());
+
     // Build router
     let app = Router::new()
         // Health & Stats
@@ -112,14 +170,27 @@ async fn main() -> anyhow::Result<()> {
         // Token endpoints
         .route("/tokens", get(handlers::list_tokens))
         .route("/tokens/:ticker", get(handlers::get_token))
+        .route("/tokens/by-id/:id", get(handlers::get_token_by_id))
         .route("/tokens/:ticker/holders", get(handlers::get_token_holders))
         .route("/tokens/:ticker/history", get(handlers::get_token_history))
+        .route(
+            "/tokens/:ticker/invalid",
+            get(handlers::get_invalid_operations),
+        )
+        .route(
+            "/tickers/:name/availability",
+            get(handlers::get_ticker_availability),
+        )
         // Address endpoints
         .route(
             "/address/:address/balances",
             get(handlers::get_address_balances),
         )
         .route("/address/:address/utxos", get(handlers::get_address_utxos))
+        .route(
+            "/address/:address/allowances",
+            get(handlers::get_address_allowances),
+        )
         .route(
             "/address/:address/history",
             get(handlers::get_address_history),
@@ -131,17 +202,30 @@ async fn main() -> anyhow::Result<()> {
         .route("/tx/mint", post(handlers::create_mint_tx))
         .route("/tx/transfer", post(handlers::create_transfer_tx))
         .route("/tx/burn", post(handlers::create_burn_tx))
+        .route("/tx/approve", post(handlers::create_approve_tx))
+        .route("/tx/airdrop", post(handlers::create_airdrop))
+        .route("/tx/airdrop/:id", get(handlers::get_airdrop))
+        .route("/tx/airdrop/:id/resume", post(handlers::resume_airdrop))
+        // Webhook endpoints
+        .route(
+            "/webhooks",
+            get(handlers::list_webhooks).post(handlers::create_webhook),
+        )
+        .route("/webhooks/:id", delete(handlers::delete_webhook))
+        .route(
+            "/webhooks/:id/deliveries",
+            get(handlers::get_webhook_deliveries),
+        )
+        .route("/webhooks/:id/replay", post(handlers::replay_webhook))
+        // Snapshot endpoints
+        .merge(anchor_api_snapshot::routes(state.snapshots.clone()))
         // State
         .with_state(state)
         // Swagger UI
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         // CORS
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        );
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(SecurityConfig::from_env().layer());
 
     // Start indexer in background
     let indexer_config = config.clone();
@@ -19,13 +19,11 @@ use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 // Re-export Token types from anchor-specs
-pub use anchor_specs::token::{
-    is_valid_ticker, DeployFlags, TokenAllocation, TokenOperation, TokenSpec,
-};
+pub use anchor_specs::token::{DeployFlags, TokenAllocation, TokenOperation, TokenSpec};
 
 // Re-export for tests
 #[cfg(test)]
-pub use anchor_specs::token::{decode_varint, encode_varint};
+pub use anchor_specs::token::{decode_varint, encode_varint, is_valid_ticker};
 
 // ============================================================================
 // API Response Types
@@ -99,6 +97,60 @@ pub struct TokenOperationResponse {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Token operation the indexer rejected
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidOperationResponse {
+    pub id: i32,
+    pub token_id: i32,
+    pub ticker: String,
+    pub operation: String,
+    pub txid: String,
+    pub vout: i32,
+    /// Reason code, e.g. `over_spend` or `input_not_spent`
+    pub reason: String,
+    pub detail: String,
+    pub block_height: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Allowance an owner granted over one of their token UTXOs
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAllowance {
+    pub id: i32,
+    pub token_id: i32,
+    pub ticker: String,
+    /// Approve transaction; the owner's UTXO is its output `ownerVout`
+    pub txid: String,
+    pub owner_vout: i32,
+    pub owner_address: Option<String>,
+    /// Output of the approve transaction whose spender may transfer
+    pub spender_vout: i32,
+    pub spender_address: Option<String>,
+    pub amount: String,
+    /// Allowance capped at what the owner's UTXO still holds
+    pub available: String,
+    pub block_height: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Whether a ticker can be deployed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TickerAvailability {
+    pub ticker: String,
+    /// Form the ticker is registered and compared under
+    pub normalized: String,
+    pub available: bool,
+    /// Reason code when unavailable: `too_short`, `too_long`,
+    /// `invalid_character`, `reserved` or `taken`
+    pub reason: Option<String>,
+    pub detail: Option<String>,
+    /// Token already holding the normalized ticker
+    pub registered: Option<Token>,
+}
+
 /// Token holder
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -144,6 +196,30 @@ pub struct ListParams {
     #[serde(default = "default_per_page")]
     pub per_page: i32,
     pub search: Option<String>,
+    /// Address or hex output script of a current holder
+    pub owned_by: Option<String>,
+}
+
+/// An `owned_by` filter as an output script and, when it has one, its address
+///
+/// Accepts an address or a hex-encoded script; `None` if it is neither.
+pub fn parse_owner(owner: &str) -> Option<(Vec<u8>, Option<String>)> {
+    let owner = owner.trim();
+    if let Ok(address) = owner.parse::<bitcoin::Address<bitcoin::address::NetworkUnchecked>>() {
+        let address = address.assume_checked();
+        return Some((
+            address.script_pubkey().to_bytes(),
+            Some(address.to_string()),
+        ));
+    }
+    let script = bitcoin::ScriptBuf::from_bytes(hex::decode(owner).ok()?);
+    if script.is_empty() {
+        return None;
+    }
+    let address = bitcoin::Address::from_script(&script, bitcoin::Network::Regtest)
+        .ok()
+        .map(|a| a.to_string());
+    Some((script.to_bytes(), address))
 }
 
 fn default_page() -> i32 {
@@ -202,7 +278,7 @@ pub struct TransferTokenRequest {
 }
 
 /// Allocation input for transfers
-#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AllocationInput {
     pub address: String,
@@ -219,6 +295,126 @@ pub struct BurnTokenRequest {
     pub fee_rate: Option<f64>,
 }
 
+/// Approve tokens request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveTokenRequest {
+    pub ticker: String,
+    /// Address that receives the spender output
+    pub spender: String,
+    pub amount: String,
+    pub carrier: Option<u8>,
+    pub fee_rate: Option<f64>,
+}
+
+/// Airdrop request
+///
+/// Recipients come either as a JSON list or as CSV text with one
+/// `address,amount` pair per line; both may be given and are concatenated.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropRequest {
+    pub ticker: String,
+    #[serde(default)]
+    pub recipients: Vec<AllocationInput>,
+    pub csv: Option<String>,
+    pub carrier: Option<u8>,
+    pub fee_rate: Option<f64>,
+    /// Cap on recipients per transaction (default: as many as the carrier fits)
+    pub max_recipients_per_tx: Option<usize>,
+}
+
+/// Airdrop progress
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropResponse {
+    pub id: i32,
+    pub ticker: String,
+    /// pending, running, completed or failed
+    pub status: String,
+    pub error: Option<String>,
+    pub total_amount: String,
+    pub recipient_count: i32,
+    pub batch_count: usize,
+    pub sent_batches: usize,
+    pub batches: Vec<AirdropBatchResponse>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One transfer transaction of an airdrop
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropBatchResponse {
+    pub index: i32,
+    pub recipient_count: usize,
+    pub amount: String,
+    /// pending, sending, sent or failed
+    pub status: String,
+    pub txid: Option<String>,
+    pub error: Option<String>,
+    pub sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Webhook registration request
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookRequest {
+    /// Address whose token activity triggers the webhook
+    pub address: String,
+    /// Endpoint that receives POSTed events
+    pub url: String,
+    /// Signing secret (default: a random one, returned once)
+    pub secret: Option<String>,
+}
+
+/// Registered webhook
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookResponse {
+    pub id: i32,
+    pub address: String,
+    pub url: String,
+    pub active: bool,
+    /// Signing secret, only returned when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One event sent, or to be sent, to a webhook
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryResponse {
+    pub id: i64,
+    /// token.mint, token.transfer, token.burn or token.split
+    pub event: String,
+    /// pending, delivered or failed
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub payload: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub delivered_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Replay request: one delivery, or everything since a point in time
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayWebhookRequest {
+    pub delivery_id: Option<i64>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Replay result
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayWebhookResponse {
+    /// Deliveries queued again
+    pub queued: u64,
+}
+
 /// Create transaction response
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -268,6 +464,24 @@ mod tests {
         assert!(!is_valid_ticker("TEST TOKEN"));
     }
 
+    #[test]
+    fn test_parse_owner() {
+        let address = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+        let (script, parsed) = parse_owner(address).unwrap();
+        assert_eq!(parsed.as_deref(), Some(address));
+
+        // A hex script resolves to the same owner
+        assert_eq!(
+            parse_owner(&hex::encode(&script)),
+            Some((script, Some(address.to_string())))
+        );
+        // Scripts without an address are matched by script alone
+        assert_eq!(parse_owner("51"), Some((vec![0x51], None)));
+
+        assert!(parse_owner("").is_none());
+        assert!(parse_owner("not-an-owner").is_none());
+    }
+
     #[test]
     fn test_deploy_flags() {
         let flags = DeployFlags::new().with_open_mint().with_burnable();
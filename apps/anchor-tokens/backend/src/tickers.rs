@@ -0,0 +1,226 @@
+//! Ticker policy
+//!
+//! Rules a ticker must pass before a DEPLOY registers it, on top of the
+//! protocol's own format check. Tickers are compared by their normalized
+//! form: upper-cased and, optionally, with look-alike characters folded
+//! together, so `Pepe`, `PEPE` and `PEP3` can't all be registered. The first
+//! deploy of a normalized ticker wins.
+//!
+//! The indexer enforces the policy, so every indexer of a network must run
+//! with the same settings to agree on which deploys are valid.
+
+use std::env;
+use std::fmt;
+
+use anchor_specs::token::MAX_TICKER_LENGTH;
+
+/// Characters a ticker may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// ASCII letters and digits
+    Alphanumeric,
+    /// ASCII letters only
+    Alphabetic,
+}
+
+impl Charset {
+    fn allows(&self, c: char) -> bool {
+        match self {
+            Charset::Alphanumeric => c.is_ascii_alphanumeric(),
+            Charset::Alphabetic => c.is_ascii_alphabetic(),
+        }
+    }
+}
+
+/// Why a ticker can't be registered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickerViolation {
+    TooShort { min: usize },
+    TooLong { max: usize },
+    InvalidCharacter(char),
+    Reserved,
+}
+
+impl TickerViolation {
+    /// Stable reason code for API responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            TickerViolation::TooShort { .. } => "too_short",
+            TickerViolation::TooLong { .. } => "too_long",
+            TickerViolation::InvalidCharacter(_) => "invalid_character",
+            TickerViolation::Reserved => "reserved",
+        }
+    }
+}
+
+impl fmt::Display for TickerViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TickerViolation::TooShort { min } => {
+                write!(f, "ticker must be at least {} characters", min)
+            }
+            TickerViolation::TooLong { max } => {
+                write!(f, "ticker must be at most {} characters", max)
+            }
+            TickerViolation::InvalidCharacter(c) => {
+                write!(f, "ticker may not contain '{}'", c.escape_default())
+            }
+            TickerViolation::Reserved => write!(f, "ticker is reserved"),
+        }
+    }
+}
+
+/// Ticker rules applied at deploy time
+#[derive(Debug, Clone)]
+pub struct TickerPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub charset: Charset,
+    /// Fold look-alikes (O/0, I/L/1) when comparing tickers
+    pub fold_confusables: bool,
+    /// Reserved tickers, normalized
+    reserved: Vec<String>,
+}
+
+impl Default for TickerPolicy {
+    fn default() -> Self {
+        Self::new(1, MAX_TICKER_LENGTH, Charset::Alphanumeric, false, &[])
+    }
+}
+
+impl TickerPolicy {
+    /// Create a policy; `reserved` names are normalized under it
+    pub fn new(
+        min_length: usize,
+        max_length: usize,
+        charset: Charset,
+        fold_confusables: bool,
+        reserved: &[&str],
+    ) -> Self {
+        let mut policy = Self {
+            min_length,
+            max_length: max_length.min(MAX_TICKER_LENGTH),
+            charset,
+            fold_confusables,
+            reserved: Vec::new(),
+        };
+        policy.reserved = reserved.iter().map(|r| policy.normalize(r)).collect();
+        policy
+    }
+
+    /// Load the policy from `TICKER_*` environment variables
+    pub fn from_env() -> Self {
+        let charset = match env::var("TICKER_CHARSET").as_deref() {
+            Ok("alphabetic") => Charset::Alphabetic,
+            _ => Charset::Alphanumeric,
+        };
+        let reserved =
+            env::var("TICKER_RESERVED").unwrap_or_else(|_| "BTC,XBT,SAT,SATS".to_string());
+        let reserved: Vec<&str> = reserved
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .collect();
+
+        Self::new(
+            env::var("TICKER_MIN_LENGTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1),
+            env::var("TICKER_MAX_LENGTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(MAX_TICKER_LENGTH),
+            charset,
+            env::var("TICKER_FOLD_CONFUSABLES")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            &reserved,
+        )
+    }
+
+    /// The form tickers are compared and registered by
+    pub fn normalize(&self, ticker: &str) -> String {
+        ticker
+            .chars()
+            .map(|c| {
+                let c = c.to_ascii_uppercase();
+                match c {
+                    'O' if self.fold_confusables => '0',
+                    'I' | 'L' if self.fold_confusables => '1',
+                    _ => c,
+                }
+            })
+            .collect()
+    }
+
+    /// Check a ticker, returning its normalized form
+    pub fn check(&self, ticker: &str) -> Result<String, TickerViolation> {
+        let len = ticker.chars().count();
+        if len < self.min_length {
+            return Err(TickerViolation::TooShort {
+                min: self.min_length,
+            });
+        }
+        if len > self.max_length {
+            return Err(TickerViolation::TooLong {
+                max: self.max_length,
+            });
+        }
+        if let Some(c) = ticker.chars().find(|c| !self.charset.allows(*c)) {
+            return Err(TickerViolation::InvalidCharacter(c));
+        }
+
+        let normalized = self.normalize(ticker);
+        if self.reserved.contains(&normalized) {
+            return Err(TickerViolation::Reserved);
+        }
+        Ok(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_and_charset() {
+        let policy = TickerPolicy::new(3, 5, Charset::Alphabetic, false, &[]);
+        assert_eq!(policy.check("abc"), Ok("ABC".to_string()));
+        assert_eq!(
+            policy.check("ab"),
+            Err(TickerViolation::TooShort { min: 3 })
+        );
+        assert_eq!(
+            policy.check("abcdef"),
+            Err(TickerViolation::TooLong { max: 5 })
+        );
+        assert_eq!(
+            policy.check("ab1"),
+            Err(TickerViolation::InvalidCharacter('1'))
+        );
+        assert_eq!(
+            TickerPolicy::default().check("é"),
+            Err(TickerViolation::InvalidCharacter('é'))
+        );
+    }
+
+    #[test]
+    fn test_reserved_is_case_insensitive() {
+        let policy = TickerPolicy::new(1, 32, Charset::Alphanumeric, false, &["btc"]);
+        assert_eq!(policy.check("Btc"), Err(TickerViolation::Reserved));
+        assert!(policy.check("BTCX").is_ok());
+    }
+
+    #[test]
+    fn test_confusables_collide() {
+        let policy = TickerPolicy::new(1, 32, Charset::Alphanumeric, true, &["SOL"]);
+        assert_eq!(policy.normalize("Pool"), policy.normalize("P00L"));
+        assert_eq!(policy.normalize("ILL"), "111");
+        assert_eq!(policy.check("s01"), Err(TickerViolation::Reserved));
+
+        let plain = TickerPolicy::default();
+        assert_ne!(plain.normalize("POOL"), plain.normalize("P00L"));
+        assert_eq!(plain.normalize("pool"), plain.normalize("POOL"));
+    }
+}
@@ -1,15 +1,23 @@
 //! UTXO Tracker for Anchor Tokens
 //!
 //! Manages the token UTXO set, processing mints, transfers, and burns.
+//!
+//! An approve leaves the owner's balance on one UTXO and lets whoever spends
+//! its spender output move up to the approved amount out of it: a transfer
+//! that spends the spender output may anchor the owner's UTXO without
+//! spending it. Each allowance is used once, by the first transaction that
+//! spends its spender output.
 
 use anyhow::Result;
+use bitcoin::hashes::Hash;
 use bitcoin::Transaction;
 use tracing::{debug, info};
 
 use anchor_core::Anchor;
 
-use crate::db::Database;
+use crate::db::{ActiveAllowance, Database};
 use crate::models::TokenAllocation;
+use crate::validation::{self, Rejection};
 
 /// UTXO tracker for token operations
 #[derive(Clone)]
@@ -32,6 +40,7 @@ impl UtxoTracker {
         txid: &[u8],
         output_index: i32,
         amount: &str,
+        owner_script: Option<&[u8]>,
         owner_address: Option<&str>,
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
@@ -44,7 +53,7 @@ impl UtxoTracker {
                 txid,
                 output_index,
                 amount,
-                None, // owner_script not extracted here
+                owner_script,
                 owner_address,
                 block_hash,
                 block_height,
@@ -63,7 +72,13 @@ impl UtxoTracker {
     }
 
     /// Process a TRANSFER operation
-    /// Spends input UTXOs and creates new output UTXOs
+    /// Spends the anchored input UTXOs and creates new output UTXOs
+    ///
+    /// Every anchor must name an outpoint that `tx` spends and that holds an
+    /// unspent balance of the token, or a UTXO with an allowance whose spender
+    /// output `tx` spends. Allocations draw on the spent inputs first and on
+    /// allowances for the rest. The operation is validated in full before
+    /// any UTXO changes; a rejected transfer is returned as `Err(Rejection)`.
     #[allow(clippy::too_many_arguments)]
     pub async fn process_transfer(
         &self,
@@ -75,79 +90,130 @@ impl UtxoTracker {
         anchors: &[Anchor],
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
-    ) -> Result<bool> {
-        // Find and spend input UTXOs referenced by anchors
+    ) -> Result<Result<(), Rejection>> {
+        if anchors.is_empty() {
+            return Ok(Err(Rejection::NoInputs));
+        }
+
+        // Resolve anchors to the inputs they name
+        let mut inputs: Vec<(Vec<u8>, u8)> = Vec::new();
         let mut total_input: u128 = 0;
-        let mut spent_addresses: Vec<String> = Vec::new();
+        let mut delegated: Vec<ActiveAllowance> = Vec::new();
+        let mut spent_outpoints: Option<Vec<(Vec<u8>, i32)>> = None;
 
         for anchor in anchors {
-            // Find UTXO by txid prefix
-            let utxo = self
+            let Some(input_txid) = anchored_input(tx, anchor) else {
+                // Not spent, but the owner may have approved this spender
+                let spent = spent_outpoints.get_or_insert_with(|| outpoints(tx));
+                let allowance = self
+                    .db
+                    .find_allowance(token_id, &anchor.txid_prefix, anchor.vout as i32, spent)
+                    .await?;
+                match allowance {
+                    Some(allowance) if delegated.iter().any(|a| a.id == allowance.id) => {
+                        return Ok(Err(Rejection::DuplicateInput {
+                            txid: allowance.txid,
+                            vout: anchor.vout,
+                        }));
+                    }
+                    Some(allowance) => delegated.push(allowance),
+                    None => {
+                        return Ok(Err(Rejection::InputNotSpent {
+                            txid_prefix: anchor.txid_prefix,
+                            vout: anchor.vout,
+                        }));
+                    }
+                }
+                continue;
+            };
+
+            if inputs.contains(&(input_txid.clone(), anchor.vout)) {
+                return Ok(Err(Rejection::DuplicateInput {
+                    txid: input_txid,
+                    vout: anchor.vout,
+                }));
+            }
+
+            let amount = self
                 .db
-                .find_utxo_by_prefix(token_id, &anchor.txid_prefix, anchor.vout as i32)
+                .get_unspent_utxo_amount(token_id, &input_txid, anchor.vout as i32)
                 .await?;
+            let Some(amount) = amount else {
+                return Ok(Err(Rejection::UnknownInput {
+                    txid: input_txid,
+                    vout: anchor.vout,
+                }));
+            };
 
-            if let Some((input_txid, amount_str)) = utxo {
-                let amount: u128 = amount_str.parse().unwrap_or(0);
-                total_input += amount;
+            total_input = total_input.saturating_add(amount.parse()?);
+            inputs.push((input_txid, anchor.vout));
+        }
 
-                // Spend the UTXO
-                let owner = self
-                    .db
-                    .spend_utxo(
-                        token_id,
-                        &input_txid,
-                        anchor.vout as i32,
-                        txid,
-                        vout,
-                        block_height,
-                    )
-                    .await?;
+        let total_output = match validation::check_allocations(tx, allocations) {
+            Ok(total) => total,
+            Err(rejection) => return Ok(Err(rejection)),
+        };
+        let total_delegated = delegated
+            .iter()
+            .fold(0u128, |total, a| total.saturating_add(a.available));
+        if let Err(rejection) =
+            validation::check_balance(total_input.saturating_add(total_delegated), total_output)
+        {
+            return Ok(Err(rejection));
+        }
 
-                if let Some(addr) = owner {
-                    if !spent_addresses.contains(&addr) {
-                        spent_addresses.push(addr);
-                    }
-                }
+        // Spend the inputs
+        let mut spent_addresses: Vec<String> = Vec::new();
+
+        for (input_txid, input_vout) in &inputs {
+            let owner = self
+                .db
+                .spend_utxo(
+                    token_id,
+                    input_txid,
+                    *input_vout as i32,
+                    txid,
+                    vout,
+                    block_height,
+                )
+                .await?;
 
-                debug!(
-                    "Spent UTXO: {}:{} ({} tokens)",
-                    hex::encode(&input_txid),
-                    anchor.vout,
-                    amount
-                );
-            } else {
-                debug!(
-                    "UTXO not found for anchor prefix: {}:{}",
-                    hex::encode(anchor.txid_prefix),
-                    anchor.vout
-                );
+            if let Some(addr) = owner {
+                if !spent_addresses.contains(&addr) {
+                    spent_addresses.push(addr);
+                }
             }
+
+            debug!("Spent UTXO: {}:{}", hex::encode(input_txid), input_vout);
         }
 
-        // Verify total allocation doesn't exceed input
-        let total_output: u128 = allocations.iter().map(|a| a.amount).sum();
+        // Draw what the spent inputs don't cover from the allowances
+        let mut needed = total_output.saturating_sub(total_input);
+        for allowance in &delegated {
+            let drawn = needed.min(allowance.available);
+            needed -= drawn;
+            self.db
+                .draw_allowance(allowance.id, txid, vout, drawn, block_height)
+                .await?;
+
+            if let Some(addr) = &allowance.owner_address {
+                if !spent_addresses.contains(addr) {
+                    spent_addresses.push(addr.clone());
+                }
+            }
 
-        if total_output > total_input {
             debug!(
-                "Transfer rejected: output {} exceeds input {}",
-                total_output, total_input
+                "Drew {} tokens from UTXO {}:{} under allowance {}",
+                drawn,
+                hex::encode(&allowance.txid),
+                allowance.owner_vout,
+                allowance.id
             );
-            return Ok(false);
         }
 
         // Create output UTXOs
-        let mut created_addresses: Vec<String> = Vec::new();
-
         for alloc in allocations {
-            // Get output address
-            let output_addr = tx
-                .output
-                .get(alloc.output_index as usize)
-                .and_then(|o| {
-                    bitcoin::Address::from_script(&o.script_pubkey, bitcoin::Network::Regtest).ok()
-                })
-                .map(|a| a.to_string());
+            let output_addr = output_address(tx, alloc.output_index);
 
             // Create the UTXO
             self.db
@@ -156,7 +222,7 @@ impl UtxoTracker {
                     txid,
                     alloc.output_index as i32,
                     &alloc.amount.to_string(),
-                    None,
+                    output_script(tx, alloc.output_index),
                     output_addr.as_deref(),
                     block_hash,
                     block_height,
@@ -164,10 +230,6 @@ impl UtxoTracker {
                 .await?;
 
             if let Some(addr) = output_addr {
-                if !created_addresses.contains(&addr) {
-                    created_addresses.push(addr.clone());
-                }
-
                 // Record operation
                 let from_addr = spent_addresses.first().cloned();
                 self.db
@@ -193,9 +255,13 @@ impl UtxoTracker {
             );
         }
 
-        // Handle remainder (implicit burn or change)
+        // Spent inputs not allocated to an output are burned; undrawn
+        // allowances stay with their owners
         let remainder = total_input.saturating_sub(total_output);
         if remainder > 0 {
+            self.db
+                .add_burned_supply(token_id, &remainder.to_string())
+                .await?;
             debug!("Transfer remainder (burned): {} tokens", remainder);
         }
 
@@ -208,10 +274,254 @@ impl UtxoTracker {
             allocations.len()
         );
 
-        Ok(true)
+        Ok(Ok(()))
+    }
+
+    /// Process an APPROVE operation
+    /// Moves the anchored inputs to the owner output and grants the spender
+    /// output an allowance over it
+    ///
+    /// The anchors must name inputs of `tx` holding the token, as for a
+    /// transfer. Their whole balance lands on `owner_index`; the allowance may
+    /// exceed it, but a transfer can only draw what the UTXO still holds.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn process_approve(
+        &self,
+        tx: &Transaction,
+        txid: &[u8],
+        vout: i32,
+        token_id: i32,
+        amount: u128,
+        spender_index: u8,
+        owner_index: u8,
+        anchors: &[Anchor],
+        block_hash: Option<&[u8]>,
+        block_height: Option<i32>,
+    ) -> Result<Result<(), Rejection>> {
+        if anchors.is_empty() {
+            return Ok(Err(Rejection::NoInputs));
+        }
+        if amount == 0 {
+            return Ok(Err(Rejection::ZeroAmount {
+                output_index: spender_index,
+            }));
+        }
+        if spender_index == owner_index {
+            return Ok(Err(Rejection::DuplicateOutput {
+                output_index: spender_index,
+            }));
+        }
+        for output_index in [owner_index, spender_index] {
+            if let Err(rejection) = validation::check_output(tx, output_index) {
+                return Ok(Err(rejection));
+            }
+        }
+
+        let mut inputs: Vec<(Vec<u8>, u8)> = Vec::new();
+        let mut total_input: u128 = 0;
+
+        for anchor in anchors {
+            let Some(input_txid) = anchored_input(tx, anchor) else {
+                return Ok(Err(Rejection::InputNotSpent {
+                    txid_prefix: anchor.txid_prefix,
+                    vout: anchor.vout,
+                }));
+            };
+            if inputs.contains(&(input_txid.clone(), anchor.vout)) {
+                return Ok(Err(Rejection::DuplicateInput {
+                    txid: input_txid,
+                    vout: anchor.vout,
+                }));
+            }
+
+            let balance = self
+                .db
+                .get_unspent_utxo_amount(token_id, &input_txid, anchor.vout as i32)
+                .await?;
+            let Some(balance) = balance else {
+                return Ok(Err(Rejection::UnknownInput {
+                    txid: input_txid,
+                    vout: anchor.vout,
+                }));
+            };
+
+            total_input = total_input.saturating_add(balance.parse()?);
+            inputs.push((input_txid, anchor.vout));
+        }
+
+        for (input_txid, input_vout) in &inputs {
+            self.db
+                .spend_utxo(
+                    token_id,
+                    input_txid,
+                    *input_vout as i32,
+                    txid,
+                    vout,
+                    block_height,
+                )
+                .await?;
+        }
+
+        let owner_addr = output_address(tx, owner_index);
+        let spender_addr = output_address(tx, spender_index);
+        self.db
+            .create_utxo(
+                token_id,
+                txid,
+                owner_index as i32,
+                &total_input.to_string(),
+                output_script(tx, owner_index),
+                owner_addr.as_deref(),
+                block_hash,
+                block_height,
+            )
+            .await?;
+        self.db
+            .create_allowance(
+                token_id,
+                txid,
+                owner_index as i32,
+                owner_addr.as_deref(),
+                spender_index as i32,
+                spender_addr.as_deref(),
+                &amount.to_string(),
+                block_hash,
+                block_height,
+            )
+            .await?;
+        self.db
+            .record_operation(
+                token_id,
+                6, // APPROVE
+                txid,
+                vout,
+                Some(&amount.to_string()),
+                owner_addr.as_deref(),
+                spender_addr.as_deref(),
+                block_hash,
+                block_height,
+            )
+            .await?;
+
+        self.db.update_holder_count(token_id).await?;
+
+        info!(
+            "Approved {} tokens of {} held at output {} for output {}",
+            amount, total_input, owner_index, spender_index
+        );
+
+        Ok(Ok(()))
+    }
+
+    /// Use up allowances whose spender outputs `tx` spends without a transfer
+    /// drawing on them
+    ///
+    /// Called after the transaction's own operations, like
+    /// [`Self::burn_unclaimed_inputs`].
+    pub async fn expire_allowances(
+        &self,
+        tx: &Transaction,
+        txid: &[u8],
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        if tx.is_coinbase() {
+            return Ok(());
+        }
+
+        let expired = self
+            .db
+            .expire_allowances(txid, &outpoints(tx), block_height)
+            .await?;
+        if expired > 0 {
+            debug!(
+                "Expired {} allowances spent without a transfer in {}",
+                expired,
+                hex::encode(txid)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Burn token UTXOs spent by `tx` outside a valid transfer
+    ///
+    /// Token balances are bound to their Bitcoin outputs, so spending one
+    /// without a transfer that claims it destroys the tokens. Called after the
+    /// transaction's own operations have spent the UTXOs they claim.
+    pub async fn burn_unclaimed_inputs(
+        &self,
+        tx: &Transaction,
+        txid: &[u8],
+        block_height: Option<i32>,
+    ) -> Result<()> {
+        if tx.is_coinbase() {
+            return Ok(());
+        }
+
+        let burned = self
+            .db
+            .burn_spent_utxos(txid, &outpoints(tx), block_height)
+            .await?;
+
+        let mut tokens: Vec<i32> = Vec::new();
+        for (token_id, _, amount) in &burned {
+            info!(
+                "Burned {} tokens of token {} spent without a transfer in {}",
+                amount,
+                token_id,
+                hex::encode(txid)
+            );
+            if !tokens.contains(token_id) {
+                tokens.push(*token_id);
+            }
+        }
+        for token_id in tokens {
+            self.db.update_holder_count(token_id).await?;
+        }
+
+        Ok(())
     }
 }
 
+/// Outpoints `tx` spends, as (txid, vout)
+fn outpoints(tx: &Transaction) -> Vec<(Vec<u8>, i32)> {
+    tx.input
+        .iter()
+        .map(|i| {
+            (
+                i.previous_output.txid.to_byte_array().to_vec(),
+                i.previous_output.vout as i32,
+            )
+        })
+        .collect()
+}
+
+/// Txid of the input of `tx` that an anchor names, if `tx` spends it
+fn anchored_input(tx: &Transaction, anchor: &Anchor) -> Option<Vec<u8>> {
+    tx.input
+        .iter()
+        .map(|i| i.previous_output)
+        .find(|o| o.vout == anchor.vout as u32 && o.txid.to_byte_array()[..8] == anchor.txid_prefix)
+        .map(|o| o.txid.to_byte_array().to_vec())
+}
+
+/// Locking script of an output of `tx`
+fn output_script(tx: &Transaction, output_index: u8) -> Option<&[u8]> {
+    tx.output
+        .get(output_index as usize)
+        .map(|o| o.script_pubkey.as_bytes())
+}
+
+/// Address of an output of `tx`
+fn output_address(tx: &Transaction, output_index: u8) -> Option<String> {
+    tx.output
+        .get(output_index as usize)
+        .and_then(|o| {
+            bitcoin::Address::from_script(&o.script_pubkey, bitcoin::Network::Regtest).ok()
+        })
+        .map(|a| a.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,322 @@
+//! Token operation validation
+//!
+//! Balances live on Bitcoin outputs. A transfer may only move tokens held by
+//! UTXOs that the transaction actually spends, and every allocation must land
+//! on a real, spendable output. These checks run before any state changes, so
+//! a rejected operation leaves the UTXO set untouched and every indexer
+//! rejects it the same way.
+
+use std::fmt;
+
+use bitcoin::Transaction;
+
+use crate::models::{DeployFlags, Token, TokenAllocation};
+
+/// Why the indexer rejected a token operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// Transfer or split without anchors naming its inputs
+    NoInputs,
+    /// Anchor names an outpoint the transaction does not spend
+    InputNotSpent { txid_prefix: [u8; 8], vout: u8 },
+    /// Spent outpoint does not hold an unspent balance of this token
+    UnknownInput { txid: Vec<u8>, vout: u8 },
+    /// Two anchors name the same input
+    DuplicateInput { txid: Vec<u8>, vout: u8 },
+    /// Allocation of zero tokens
+    ZeroAmount { output_index: u8 },
+    /// Two allocations target the same output
+    DuplicateOutput { output_index: u8 },
+    /// Allocation targets an output the transaction doesn't have
+    MissingOutput { output_index: u8 },
+    /// Allocation targets an OP_RETURN output
+    UnspendableOutput { output_index: u8 },
+    /// Allocations add up to more than the inputs hold
+    OverSpend { input: u128, output: u128 },
+    /// Allocations overflow a u128
+    AmountOverflow,
+    /// Mint of a fixed-supply token
+    FixedSupply,
+    /// Mint above the per-mint limit
+    MintLimitExceeded { amount: u128, limit: u128 },
+    /// Mint beyond the remaining supply
+    SupplyExceeded { amount: u128, remaining: u128 },
+    /// Burn of a token without the burnable flag
+    NotBurnable,
+    /// Deploy of a ticker that normalizes to a registered one
+    TickerTaken { ticker: String },
+}
+
+impl Rejection {
+    /// Stable reason code stored with the rejected operation
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rejection::NoInputs => "no_inputs",
+            Rejection::InputNotSpent { .. } => "input_not_spent",
+            Rejection::UnknownInput { .. } => "unknown_input",
+            Rejection::DuplicateInput { .. } => "duplicate_input",
+            Rejection::ZeroAmount { .. } => "zero_amount",
+            Rejection::DuplicateOutput { .. } => "duplicate_output",
+            Rejection::MissingOutput { .. } => "missing_output",
+            Rejection::UnspendableOutput { .. } => "unspendable_output",
+            Rejection::OverSpend { .. } => "over_spend",
+            Rejection::AmountOverflow => "amount_overflow",
+            Rejection::FixedSupply => "fixed_supply",
+            Rejection::MintLimitExceeded { .. } => "mint_limit_exceeded",
+            Rejection::SupplyExceeded { .. } => "supply_exceeded",
+            Rejection::NotBurnable => "not_burnable",
+            Rejection::TickerTaken { .. } => "ticker_taken",
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::NoInputs => write!(f, "no anchors name the token inputs"),
+            Rejection::InputNotSpent { txid_prefix, vout } => write!(
+                f,
+                "anchor {}:{} is not an input of the transaction",
+                hex::encode(txid_prefix),
+                vout
+            ),
+            Rejection::UnknownInput { txid, vout } => write!(
+                f,
+                "input {}:{} holds no unspent balance of this token",
+                hex::encode(txid),
+                vout
+            ),
+            Rejection::DuplicateInput { txid, vout } => {
+                write!(f, "input {}:{} is anchored twice", hex::encode(txid), vout)
+            }
+            Rejection::ZeroAmount { output_index } => {
+                write!(f, "allocation to output {} is zero", output_index)
+            }
+            Rejection::DuplicateOutput { output_index } => {
+                write!(f, "output {} is allocated twice", output_index)
+            }
+            Rejection::MissingOutput { output_index } => {
+                write!(f, "output {} does not exist", output_index)
+            }
+            Rejection::UnspendableOutput { output_index } => {
+                write!(f, "output {} is an OP_RETURN", output_index)
+            }
+            Rejection::OverSpend { input, output } => {
+                write!(f, "allocations total {} but inputs hold {}", output, input)
+            }
+            Rejection::AmountOverflow => write!(f, "allocations overflow"),
+            Rejection::FixedSupply => write!(f, "token has a fixed supply"),
+            Rejection::MintLimitExceeded { amount, limit } => {
+                write!(f, "mint of {} exceeds the limit of {}", amount, limit)
+            }
+            Rejection::SupplyExceeded { amount, remaining } => write!(
+                f,
+                "mint of {} exceeds the remaining supply of {}",
+                amount, remaining
+            ),
+            Rejection::NotBurnable => write!(f, "token is not burnable"),
+            Rejection::TickerTaken { ticker } => {
+                write!(f, "ticker {} collides with this token", ticker)
+            }
+        }
+    }
+}
+
+/// Check that a token can be sent to an output of `tx`
+pub fn check_output(tx: &Transaction, output_index: u8) -> Result<(), Rejection> {
+    match tx.output.get(output_index as usize) {
+        None => Err(Rejection::MissingOutput { output_index }),
+        Some(output) if output.script_pubkey.is_op_return() => {
+            Err(Rejection::UnspendableOutput { output_index })
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Check transfer allocations against the outputs of `tx`, returning their total
+pub fn check_allocations(
+    tx: &Transaction,
+    allocations: &[TokenAllocation],
+) -> Result<u128, Rejection> {
+    let mut total: u128 = 0;
+    for (i, alloc) in allocations.iter().enumerate() {
+        let output_index = alloc.output_index;
+        if alloc.amount == 0 {
+            return Err(Rejection::ZeroAmount { output_index });
+        }
+        if allocations[..i]
+            .iter()
+            .any(|a| a.output_index == output_index)
+        {
+            return Err(Rejection::DuplicateOutput { output_index });
+        }
+        check_output(tx, output_index)?;
+        total = total
+            .checked_add(alloc.amount)
+            .ok_or(Rejection::AmountOverflow)?;
+    }
+    Ok(total)
+}
+
+/// Check that allocations are covered by the inputs
+pub fn check_balance(input: u128, output: u128) -> Result<(), Rejection> {
+    if output > input {
+        return Err(Rejection::OverSpend { input, output });
+    }
+    Ok(())
+}
+
+/// Check a mint of `amount` to `output_index` against the token's flags and supply
+pub fn check_mint(token: &Token, amount: u128, output_index: u8) -> Result<(), Rejection> {
+    if DeployFlags(token.flags as u8).is_fixed_supply() {
+        return Err(Rejection::FixedSupply);
+    }
+    if amount == 0 {
+        return Err(Rejection::ZeroAmount { output_index });
+    }
+    if let Some(limit) = token.mint_limit.as_deref().and_then(|l| l.parse().ok()) {
+        if amount > limit {
+            return Err(Rejection::MintLimitExceeded { amount, limit });
+        }
+    }
+    let max_supply: u128 = token.max_supply.parse().unwrap_or(0);
+    let minted: u128 = token.minted_supply.parse().unwrap_or(0);
+    let remaining = max_supply.saturating_sub(minted);
+    if amount > remaining {
+        return Err(Rejection::SupplyExceeded { amount, remaining });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, ScriptBuf, TxOut};
+
+    fn tx_with_outputs(op_return_at: usize, count: usize) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: (0..count)
+                .map(|i| TxOut {
+                    value: Amount::from_sat(546),
+                    script_pubkey: if i == op_return_at {
+                        ScriptBuf::new_op_return([0u8; 4])
+                    } else {
+                        ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([i as u8; 20]))
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    fn alloc(output_index: u8, amount: u128) -> TokenAllocation {
+        TokenAllocation {
+            output_index,
+            amount,
+        }
+    }
+
+    fn token(flags: i16, max_supply: &str, minted: &str, mint_limit: Option<&str>) -> Token {
+        Token {
+            id: 1,
+            ticker: "TEST".to_string(),
+            deploy_txid: String::new(),
+            deploy_vout: 0,
+            decimals: 0,
+            max_supply: max_supply.to_string(),
+            mint_limit: mint_limit.map(String::from),
+            minted_supply: minted.to_string(),
+            burned_supply: "0".to_string(),
+            circulating_supply: minted.to_string(),
+            holder_count: 0,
+            tx_count: 0,
+            flags,
+            is_open_mint: true,
+            is_burnable: false,
+            block_height: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_allocations_total() {
+        let tx = tx_with_outputs(2, 3);
+        assert_eq!(
+            check_allocations(&tx, &[alloc(0, 700), alloc(1, 300)]),
+            Ok(1000)
+        );
+        assert_eq!(check_allocations(&tx, &[]), Ok(0));
+    }
+
+    #[test]
+    fn test_allocations_rejected() {
+        let tx = tx_with_outputs(2, 3);
+        assert_eq!(
+            check_allocations(&tx, &[alloc(0, 0)]),
+            Err(Rejection::ZeroAmount { output_index: 0 })
+        );
+        assert_eq!(
+            check_allocations(&tx, &[alloc(1, 5), alloc(1, 5)]),
+            Err(Rejection::DuplicateOutput { output_index: 1 })
+        );
+        assert_eq!(
+            check_allocations(&tx, &[alloc(3, 5)]),
+            Err(Rejection::MissingOutput { output_index: 3 })
+        );
+        assert_eq!(
+            check_allocations(&tx, &[alloc(2, 5)]),
+            Err(Rejection::UnspendableOutput { output_index: 2 })
+        );
+        assert_eq!(
+            check_allocations(&tx, &[alloc(0, u128::MAX), alloc(1, 1)]),
+            Err(Rejection::AmountOverflow)
+        );
+    }
+
+    #[test]
+    fn test_balance() {
+        assert!(check_balance(1000, 1000).is_ok());
+        assert!(check_balance(1000, 400).is_ok());
+        let rejection = check_balance(1000, 1001).unwrap_err();
+        assert_eq!(
+            rejection,
+            Rejection::OverSpend {
+                input: 1000,
+                output: 1001
+            }
+        );
+        assert_eq!(rejection.code(), "over_spend");
+    }
+
+    #[test]
+    fn test_mint() {
+        assert!(check_mint(&token(1, "1000", "0", None), 1000, 0).is_ok());
+        assert_eq!(
+            check_mint(&token(1, "1000", "900", None), 101, 0),
+            Err(Rejection::SupplyExceeded {
+                amount: 101,
+                remaining: 100
+            })
+        );
+        assert_eq!(
+            check_mint(&token(1, "1000", "0", Some("10")), 11, 0),
+            Err(Rejection::MintLimitExceeded {
+                amount: 11,
+                limit: 10
+            })
+        );
+        assert_eq!(
+            check_mint(
+                &token(DeployFlags::FIXED_SUPPLY as i16, "1000", "0", None),
+                1,
+                0
+            ),
+            Err(Rejection::FixedSupply)
+        );
+    }
+}
@@ -9,6 +9,24 @@ const WALLET_URL = process.env.NEXT_PUBLIC_WALLET_URL || 'http://localhost:3001'
 // Types
 // ============================================================================
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 export interface Token {
   id: number;
   ticker: string;
@@ -66,6 +84,28 @@ export interface TokenOperation {
   createdAt: string;
 }
 
+export interface TickerAvailability {
+  ticker: string;
+  normalized: string;
+  available: boolean;
+  reason: string | null;
+  detail: string | null;
+  registered: Token | null;
+}
+
+export interface InvalidOperation {
+  id: number;
+  tokenId: number;
+  ticker: string;
+  operation: string;
+  txid: string;
+  vout: number;
+  reason: string;
+  detail: string;
+  blockHeight: number | null;
+  createdAt: string;
+}
+
 export interface TokenHolder {
   address: string;
   balance: string;
@@ -118,8 +158,7 @@ async function fetchApi<T>(path: string, options?: RequestInit): Promise<T> {
   });
 
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || `API error: ${res.status}`);
+    throw await apiError(res, `API error: ${res.status}`);
   }
 
   return res.json();
@@ -135,8 +174,7 @@ async function fetchWallet<T>(path: string, options?: RequestInit): Promise<T> {
   });
 
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || `Wallet error: ${res.status}`);
+    throw await apiError(res, `Wallet error: ${res.status}`);
   }
 
   return res.json();
@@ -167,6 +205,10 @@ export async function getToken(ticker: string): Promise<Token> {
   return fetchApi(`/tokens/${ticker}`);
 }
 
+export async function getTickerAvailability(name: string): Promise<TickerAvailability> {
+  return fetchApi(`/tickers/${encodeURIComponent(name)}/availability`);
+}
+
 export async function getTokenHolders(
   ticker: string,
   page: number = 1,
@@ -191,6 +233,18 @@ export async function getTokenHistory(
   return fetchApi(`/tokens/${ticker}/history?${params}`);
 }
 
+export async function getInvalidOperations(
+  ticker: string,
+  page: number = 1,
+  perPage: number = 50
+): Promise<PaginatedResponse<InvalidOperation>> {
+  const params = new URLSearchParams({
+    page: page.toString(),
+    per_page: perPage.toString(),
+  });
+  return fetchApi(`/tokens/${ticker}/invalid?${params}`);
+}
+
 // ============================================================================
 // Address API
 // ============================================================================
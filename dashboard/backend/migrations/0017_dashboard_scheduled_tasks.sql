@@ -0,0 +1,36 @@
+-- Scheduled tasks for Anchor OS
+-- Cron-driven maintenance jobs configured from the dashboard
+CREATE TABLE IF NOT EXISTS scheduled_tasks (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    task_type VARCHAR(50) NOT NULL,          -- 'backup', 'utxo_consolidation', 'indexer_verify', 'disk_cleanup'
+    cron_expression VARCHAR(100) NOT NULL,   -- 5 fields, or 6 with seconds first
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    config JSONB NOT NULL DEFAULT '{}',      -- task type specific options
+    last_run_at TIMESTAMP WITH TIME ZONE,
+    last_status VARCHAR(20),                 -- 'running', 'success', 'failed', 'skipped', 'interrupted'
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- One row per execution, scheduled or manual
+CREATE TABLE IF NOT EXISTS scheduled_task_runs (
+    id SERIAL PRIMARY KEY,
+    task_id INTEGER NOT NULL REFERENCES scheduled_tasks(id) ON DELETE CASCADE,
+    trigger VARCHAR(20) NOT NULL,            -- 'schedule', 'manual'
+    status VARCHAR(20) NOT NULL DEFAULT 'running',
+    output TEXT,
+    error TEXT,
+    started_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    finished_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_task_runs_task ON scheduled_task_runs(task_id, started_at DESC);
+
+-- Default tasks, disabled until the user turns them on
+INSERT INTO scheduled_tasks (name, task_type, cron_expression, config) VALUES
+    ('Nightly backup', 'backup', '0 3 * * *', '{"target": "local", "include_databases": true, "include_volumes": true}'),
+    ('Weekly UTXO consolidation', 'utxo_consolidation', '0 4 * * 0', '{"min_utxos": 50, "fee_rate": 2}'),
+    ('Indexer verification', 'indexer_verify', '30 * * * *', '{"sample_size": 100, "max_lag_blocks": 6}'),
+    ('Disk cleanup', 'disk_cleanup', '0 5 * * *', '{"prune_images": true, "temp_max_age_hours": 24, "history_days": 30}');
@@ -0,0 +1,24 @@
+-- Notification rules for Anchor OS
+-- User-defined triggers evaluated by the dashboard; each match creates a
+-- notification and, optionally, calls a webhook and sends an email
+CREATE TABLE IF NOT EXISTS notification_rules (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    rule_type VARCHAR(50) NOT NULL,          -- 'thread_reply', 'domain_registration', 'token_received', 'disk_usage', 'indexer_lag'
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    config JSONB NOT NULL DEFAULT '{}',      -- rule type specific options
+    severity VARCHAR(20) NOT NULL DEFAULT 'info',
+    webhook_url TEXT,
+    email_to TEXT,
+    state JSONB NOT NULL DEFAULT '{}',       -- evaluation cursor, managed by the engine
+    last_checked_at TIMESTAMP WITH TIME ZONE,
+    last_triggered_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Default system rules
+INSERT INTO notification_rules (name, rule_type, config, severity) VALUES
+    ('Disk almost full', 'disk_usage', '{"path": "/", "threshold_percent": 90}', 'warning'),
+    ('Indexer falling behind', 'indexer_lag', '{"max_lag_blocks": 6}', 'warning');
@@ -0,0 +1,16 @@
+-- Notification delivery channels for Anchor OS
+-- Every new notification is sent to each enabled channel whose filters it passes
+CREATE TABLE IF NOT EXISTS notification_channels (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    channel_type VARCHAR(20) NOT NULL,       -- 'smtp', 'telegram', 'ntfy', 'webhook'
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    config JSONB NOT NULL DEFAULT '{}',      -- channel type specific settings, including credentials
+    min_severity VARCHAR(20) NOT NULL DEFAULT 'info',
+    notification_types TEXT[] NOT NULL DEFAULT '{}',  -- empty for all types
+    last_notification_id INTEGER NOT NULL DEFAULT 0,  -- dispatch cursor into notifications
+    last_delivered_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+-- Weekly restore drill: restic check plus a sample restore of the newest
+-- snapshot. Disabled until the user turns it on, like the other defaults.
+INSERT INTO scheduled_tasks (name, task_type, cron_expression, config)
+SELECT 'Weekly restore drill', 'restore_drill', '0 6 * * 0',
+       '{"target": "local", "read_data_percent": 10, "sample_files": 20, "max_file_mb": 64}'
+WHERE NOT EXISTS (SELECT 1 FROM scheduled_tasks WHERE task_type = 'restore_drill');
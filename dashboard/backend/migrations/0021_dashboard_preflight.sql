@@ -0,0 +1,13 @@
+-- Installation preflight reports: hardware profile, check results, and the
+-- recommended preset, kept so the wizard can show past warnings.
+CREATE TABLE IF NOT EXISTS installation_preflight (
+    id SERIAL PRIMARY KEY,
+    hardware JSONB NOT NULL,
+    checks JSONB NOT NULL,
+    recommendation JSONB NOT NULL,
+    warnings TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_installation_preflight_created_at
+    ON installation_preflight (created_at DESC);
@@ -0,0 +1,3 @@
+-- Pending preset migration plan, executed by the installation stream and
+-- cleared once every stage has run.
+ALTER TABLE installation_config ADD COLUMN IF NOT EXISTS pending_migration JSONB;
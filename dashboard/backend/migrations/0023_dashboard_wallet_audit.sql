@@ -0,0 +1,13 @@
+-- Wallet operations proxied by the dashboard, refused ones included
+CREATE TABLE IF NOT EXISTS wallet_audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    operation TEXT NOT NULL,
+    method TEXT NOT NULL,
+    allowed BOOLEAN NOT NULL,
+    status INTEGER NOT NULL,
+    client TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_audit_log_denied
+    ON wallet_audit_log (id DESC) WHERE NOT allowed;
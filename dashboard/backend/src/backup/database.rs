@@ -1,6 +1,8 @@
 //! Database backup utilities
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use sqlx::postgres::PgConnectOptions;
+use sqlx::{ConnectOptions, Connection};
 use std::path::Path;
 use tokio::process::Command;
 use tracing::{error, info};
@@ -15,6 +17,181 @@ pub struct DatabaseConfig {
     pub database: String,
 }
 
+/// One core app's share of the `anchor` database
+///
+/// The core apps all write to `anchor`, so each gets a logical dump of the
+/// tables it owns and can be restored without touching the others.
+#[derive(Debug, Clone, Copy)]
+pub struct AppDatabase {
+    /// Dump file stem, also used to pick a dump to restore
+    pub name: &'static str,
+    /// pg_dump table patterns
+    pub tables: &'static [&'static str],
+    /// Containers writing these tables, stopped while restoring
+    pub containers: &'static [&'static str],
+}
+
+/// Name of the core database entry in [`get_anchor_databases`]
+pub const CORE_DATABASE: &str = "anchor-main";
+
+/// Dump of every core table no app claims, including extensions and functions
+pub const CORE_OTHER: &str = "core-other";
+
+pub const APP_DATABASES: &[AppDatabase] = &[
+    AppDatabase {
+        name: "indexer",
+        tables: &[
+            "messages",
+            "anchors",
+            "indexer_state",
+            "identity_rotations",
+            "message_plugin_*",
+        ],
+        containers: &["anchor-core-indexer"],
+    },
+    AppDatabase {
+        name: "domains",
+        tables: &[
+            "domains",
+            "domain_*",
+            "dns_records",
+            "anchor_domains_*",
+            "pending_transactions",
+        ],
+        containers: &["anchor-app-domains-backend"],
+    },
+    AppDatabase {
+        name: "tokens",
+        tables: &["tokens", "token_*"],
+        containers: &["anchor-app-tokens-backend"],
+    },
+    AppDatabase {
+        // The dashboard restores itself, so nothing is stopped
+        name: "dashboard",
+        tables: &[
+            "system_settings",
+            "installation_*",
+            "service_status",
+            "user_profile",
+            "notifications",
+            "notification_*",
+            "scheduled_task*",
+        ],
+        containers: &[],
+    },
+];
+
+pub fn find_app_database(name: &str) -> Option<&'static AppDatabase> {
+    APP_DATABASES.iter().find(|app| app.name == name)
+}
+
+/// Take logical dumps of the core apps from one snapshot
+///
+/// A read-only transaction exports its snapshot and stays open while each
+/// pg_dump attaches to it, so the dumps agree with each other even while
+/// the indexer keeps writing. Returns the dump files written; a failed app
+/// is logged and skipped.
+pub async fn dump_core_apps(config: &DatabaseConfig, output_path: &str) -> Result<Vec<String>> {
+    tokio::fs::create_dir_all(output_path).await?;
+
+    let mut conn = PgConnectOptions::new()
+        .host(&config.host)
+        .port(config.port)
+        .username(&config.user)
+        .password(&config.password)
+        .database(&config.database)
+        .connect()
+        .await?;
+    sqlx::query("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut conn)
+        .await?;
+    let snapshot: String = sqlx::query_scalar("SELECT pg_export_snapshot()")
+        .fetch_one(&mut conn)
+        .await?;
+
+    let mut dumps = Vec::new();
+    let claimed: Vec<&str> = APP_DATABASES
+        .iter()
+        .flat_map(|app| app.tables.iter().copied())
+        .collect();
+    match pg_dump_tables(config, &snapshot, CORE_OTHER, &[], &claimed, output_path).await {
+        Ok(path) => dumps.push(path),
+        Err(e) => error!("Failed to dump {}: {}", CORE_OTHER, e),
+    }
+    for app in APP_DATABASES {
+        match pg_dump_tables(config, &snapshot, app.name, app.tables, &[], output_path).await {
+            Ok(path) => dumps.push(path),
+            Err(e) => error!("Failed to dump {}: {}", app.name, e),
+        }
+    }
+
+    // The snapshot only has to live until the last pg_dump has attached
+    let _ = sqlx::query("COMMIT").execute(&mut conn).await;
+    let _ = conn.close().await;
+
+    Ok(dumps)
+}
+
+/// pg_dump a set of tables in custom format from an exported snapshot
+async fn pg_dump_tables(
+    config: &DatabaseConfig,
+    snapshot: &str,
+    name: &str,
+    include: &[&str],
+    exclude: &[&str],
+    output_path: &str,
+) -> Result<String> {
+    let dump_file = format!("{}/{}.dump", output_path, name);
+    info!("Dumping {} tables to {}", name, dump_file);
+
+    let mut cmd = Command::new("pg_dump");
+    cmd.env("PGPASSWORD", &config.password)
+        .args(["-h", &config.host])
+        .args(["-p", &config.port.to_string()])
+        .args(["-U", &config.user])
+        .args(["-d", &config.database])
+        .arg("--format=custom")
+        .arg(format!("--snapshot={}", snapshot))
+        .args(["-f", &dump_file]);
+    for table in include {
+        cmd.args(["-t", table]);
+    }
+    for table in exclude {
+        cmd.args(["-T", table]);
+    }
+
+    let output = cmd.output().await?;
+    if !output.status.success() {
+        bail!(
+            "pg_dump failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(dump_file)
+}
+
+/// Dump every Anchor database into `output_path`
+///
+/// The core database is split into per-app dumps; the app-specific
+/// databases are dumped whole.
+pub async fn dump_all(output_path: &str) -> Vec<String> {
+    let mut dumps = Vec::new();
+    for db_config in get_anchor_databases() {
+        let result = if db_config.name == CORE_DATABASE {
+            dump_core_apps(&db_config, output_path).await
+        } else {
+            pg_dump(&db_config, output_path)
+                .await
+                .map(|path| vec![path])
+        };
+        match result {
+            Ok(paths) => dumps.extend(paths),
+            Err(e) => error!("Failed to dump {}: {}", db_config.name, e),
+        }
+    }
+    dumps
+}
+
 /// Dump PostgreSQL database to a file
 pub async fn pg_dump(config: &DatabaseConfig, output_path: &str) -> Result<String> {
     let dump_file = format!("{}/{}.sql.gz", output_path, config.name);
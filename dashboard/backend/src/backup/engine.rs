@@ -5,11 +5,12 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::process::Command;
 use tracing::{error, info};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::backup_config::BackupConfig as Config;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BackupJob {
     pub id: String,
     pub started_at: DateTime<Utc>,
@@ -19,10 +20,12 @@ pub struct BackupJob {
     pub target: BackupTarget,
     pub size_bytes: Option<i64>,
     pub files_count: Option<i64>,
+    /// Snapshot written by a completed backup
+    pub snapshot_id: Option<String>,
     pub error_message: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum BackupStatus {
     Running,
@@ -30,14 +33,14 @@ pub enum BackupStatus {
     Failed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum BackupType {
     Full,
     Incremental,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum BackupTarget {
     Local,
@@ -45,7 +48,7 @@ pub enum BackupTarget {
     Smb,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ResticSnapshot {
     pub id: String,
     #[serde(default)]
@@ -58,6 +61,16 @@ pub struct ResticSnapshot {
     pub paths: Vec<String>,
 }
 
+/// A file node from `restic ls --json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnapshotFile {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
 pub struct BackupEngine {
     config: Config,
 }
@@ -186,7 +199,7 @@ impl BackupEngine {
             info!("Backup completed successfully");
 
             // Parse restic output to get stats
-            let (size_bytes, files_count) = self.parse_backup_output(&stdout);
+            let (size_bytes, files_count, snapshot_id) = self.parse_backup_output(&stdout);
 
             Ok(BackupJob {
                 id: job_id,
@@ -197,6 +210,7 @@ impl BackupEngine {
                 target: target.clone(),
                 size_bytes,
                 files_count,
+                snapshot_id,
                 error_message: None,
             })
         } else {
@@ -212,6 +226,7 @@ impl BackupEngine {
                 target: target.clone(),
                 size_bytes: None,
                 files_count: None,
+                snapshot_id: None,
                 error_message: Some(stderr.to_string()),
             })
         }
@@ -277,21 +292,119 @@ impl BackupEngine {
         }
     }
 
-    /// Parse backup output to extract stats
-    fn parse_backup_output(&self, output: &str) -> (Option<i64>, Option<i64>) {
+    /// Check repository integrity, reading `read_data_percent` of the pack data
+    pub async fn check(&self, target: &BackupTarget, read_data_percent: u8) -> Result<()> {
+        let env = self.get_restic_env(target);
+
+        let mut cmd = Command::new("restic");
+        cmd.arg("check");
+        if read_data_percent > 0 {
+            cmd.arg(format!("--read-data-subset={}%", read_data_percent));
+        }
+
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        let output = cmd.output().await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            // restic reports most check errors on stdout
+            Err(anyhow::anyhow!(
+                "Check failed: {}",
+                format!("{}\n{}", stdout.trim(), stderr.trim()).trim()
+            ))
+        }
+    }
+
+    /// List the regular files in a snapshot
+    pub async fn list_files(
+        &self,
+        target: &BackupTarget,
+        snapshot_id: &str,
+    ) -> Result<Vec<SnapshotFile>> {
+        let env = self.get_restic_env(target);
+
+        let mut cmd = Command::new("restic");
+        cmd.arg("ls").arg("--json").arg(snapshot_id);
+
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        let output = cmd.output().await?;
+
+        if output.status.success() {
+            // One JSON object per line: the snapshot, then its nodes
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Ok(stdout
+                .lines()
+                .filter_map(|line| serde_json::from_str::<SnapshotFile>(line).ok())
+                .filter(|node| node.node_type == "file")
+                .collect())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(anyhow::anyhow!("Failed to list snapshot files: {}", stderr))
+        }
+    }
+
+    /// Restore only the given paths of a snapshot
+    pub async fn restore_paths(
+        &self,
+        target: &BackupTarget,
+        snapshot_id: &str,
+        restore_path: &str,
+        paths: &[&str],
+    ) -> Result<()> {
+        let env = self.get_restic_env(target);
+
+        let mut cmd = Command::new("restic");
+        cmd.arg("restore");
+        cmd.arg(snapshot_id);
+        cmd.arg("--target").arg(restore_path);
+
+        for path in paths {
+            cmd.arg("--include").arg(path);
+        }
+
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        let output = cmd.output().await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(anyhow::anyhow!("Restore failed: {}", stderr))
+        }
+    }
+
+    /// Parse backup output to extract stats and the new snapshot id
+    fn parse_backup_output(&self, output: &str) -> (Option<i64>, Option<i64>, Option<String>) {
         // Parse JSON lines from restic backup output
         let mut size_bytes = None;
         let mut files_count = None;
+        let mut snapshot_id = None;
 
         for line in output.lines() {
             if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
                 if json.get("message_type").and_then(|v| v.as_str()) == Some("summary") {
                     size_bytes = json.get("total_bytes_processed").and_then(|v| v.as_i64());
                     files_count = json.get("total_files_processed").and_then(|v| v.as_i64());
+                    snapshot_id = json
+                        .get("snapshot_id")
+                        .and_then(|v| v.as_str())
+                        .map(String::from);
                 }
             }
         }
 
-        (size_bytes, files_count)
+        (size_bytes, files_count, snapshot_id)
     }
 }
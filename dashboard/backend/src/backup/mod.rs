@@ -3,4 +3,5 @@
 pub mod database;
 pub mod engine;
 pub mod restore;
+pub mod verify;
 pub mod volumes;
@@ -6,8 +6,11 @@ use std::collections::HashMap;
 use std::path::Path;
 use tokio::process::Command;
 use tracing::{error, info, warn};
+use utoipa::ToSchema;
 
-use crate::backup::database::{get_anchor_databases, DatabaseConfig};
+use crate::backup::database::{
+    find_app_database, get_anchor_databases, DatabaseConfig, CORE_DATABASE, CORE_OTHER,
+};
 use crate::backup::engine::{BackupEngine, BackupTarget};
 use crate::backup_config::BackupConfig;
 
@@ -260,76 +263,95 @@ async fn restore_volume_from_tar(tar_file: &str, volume_name: &str) -> Result<()
     Ok(())
 }
 
-/// Find database dump files in the restore directory
-fn find_database_dumps(restore_path: &str) -> Vec<(String, String)> {
-    let mut dumps = Vec::new();
-    let db_path = Path::new(restore_path);
+/// Find files ending in `extension` in the restore directory and its
+/// immediate subdirectories, as (file stem, path) pairs
+fn find_files(restore_path: &str, extension: &str) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    let mut check = |path: &Path| {
+        if let Some(name) = path.file_name() {
+            let name_str = name.to_string_lossy();
+            if let Some(stem) = name_str.strip_suffix(extension) {
+                found.push((stem.to_string(), path.to_string_lossy().to_string()));
+            }
+        }
+    };
 
-    // Look for .sql.gz files recursively
-    if let Ok(entries) = std::fs::read_dir(db_path) {
+    if let Ok(entries) = std::fs::read_dir(restore_path) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() {
-                // Check subdirectories (e.g., databases/)
+                // Check subdirectories (e.g., databases/, volumes/)
                 if let Ok(sub_entries) = std::fs::read_dir(&path) {
                     for sub_entry in sub_entries.flatten() {
-                        let sub_path = sub_entry.path();
-                        if let Some(name) = sub_path.file_name() {
-                            let name_str = name.to_string_lossy();
-                            if name_str.ends_with(".sql.gz") {
-                                let db_name = name_str.trim_end_matches(".sql.gz").to_string();
-                                dumps.push((db_name, sub_path.to_string_lossy().to_string()));
-                            }
-                        }
+                        check(&sub_entry.path());
                     }
                 }
-            } else if let Some(name) = path.file_name() {
-                let name_str = name.to_string_lossy();
-                if name_str.ends_with(".sql.gz") {
-                    let db_name = name_str.trim_end_matches(".sql.gz").to_string();
-                    dumps.push((db_name, path.to_string_lossy().to_string()));
-                }
+            } else {
+                check(&path);
             }
         }
     }
 
-    dumps
+    found
 }
 
-/// Find volume tar files in the restore directory
-fn find_volume_tars(restore_path: &str) -> Vec<(String, String)> {
-    let mut tars = Vec::new();
-    let vol_path = Path::new(restore_path);
+/// Restore one app's tables from a custom-format dump
+///
+/// `--clean` drops only the objects in the dump, so the other apps sharing
+/// the database are left alone. The restore runs in one transaction and
+/// either fully applies or leaves the tables as they were.
+async fn restore_app_dump(dump_file: &str, config: &DatabaseConfig) -> Result<()> {
+    info!("Restoring {} into {}", dump_file, config.database);
+
+    let output = Command::new("pg_restore")
+        .env("PGPASSWORD", &config.password)
+        .args(["-h", &config.host])
+        .args(["-p", &config.port.to_string()])
+        .args(["-U", &config.user])
+        .args(["-d", &config.database])
+        .args([
+            "--clean",
+            "--if-exists",
+            "--no-owner",
+            "--single-transaction",
+        ])
+        .arg(dump_file)
+        .output()
+        .await?;
 
-    // Look for .tar files recursively
-    if let Ok(entries) = std::fs::read_dir(vol_path) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                // Check subdirectories (e.g., volumes/)
-                if let Ok(sub_entries) = std::fs::read_dir(&path) {
-                    for sub_entry in sub_entries.flatten() {
-                        let sub_path = sub_entry.path();
-                        if let Some(name) = sub_path.file_name() {
-                            let name_str = name.to_string_lossy();
-                            if name_str.ends_with(".tar") {
-                                let vol_name = name_str.trim_end_matches(".tar").to_string();
-                                tars.push((vol_name, sub_path.to_string_lossy().to_string()));
-                            }
-                        }
-                    }
-                }
-            } else if let Some(name) = path.file_name() {
-                let name_str = name.to_string_lossy();
-                if name_str.ends_with(".tar") {
-                    let vol_name = name_str.trim_end_matches(".tar").to_string();
-                    tars.push((vol_name, path.to_string_lossy().to_string()));
-                }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!("pg_restore failed: {}", stderr));
+    }
+
+    info!("Successfully restored {}", dump_file);
+    Ok(())
+}
+
+/// Restore app dumps, the shared core objects first
+async fn restore_app_dumps(
+    dumps: &mut [(String, String)],
+    core: &DatabaseConfig,
+    result: &mut RestoreResult,
+) {
+    dumps.sort_by_key(|(name, _)| name != CORE_OTHER);
+    for (name, dump_path) in dumps.iter() {
+        match restore_app_dump(dump_path, core).await {
+            Ok(_) => result.databases_restored.push(name.clone()),
+            Err(e) => {
+                error!("Failed to restore {}: {}", name, e);
+                result.databases_failed.push(name.clone());
+                result.errors.push(format!("Database {}: {}", name, e));
             }
         }
     }
+}
 
-    tars
+fn core_database() -> Result<DatabaseConfig> {
+    get_anchor_databases()
+        .into_iter()
+        .find(|c| c.name == CORE_DATABASE)
+        .ok_or_else(|| anyhow::anyhow!("core database not configured"))
 }
 
 /// Perform a full restore from a Restic snapshot
@@ -364,12 +386,14 @@ pub async fn full_restore(
     }
 
     // Step 2: Find what needs to be restored
-    let db_dumps = find_database_dumps(&restore_path);
-    let vol_tars = find_volume_tars(&restore_path);
+    let db_dumps = find_files(&restore_path, ".sql.gz");
+    let mut app_dumps = find_files(&restore_path, ".dump");
+    let vol_tars = find_files(&restore_path, ".tar");
 
     info!(
-        "Found {} database dumps and {} volume tars",
+        "Found {} database dumps, {} app dumps and {} volume tars",
         db_dumps.len(),
+        app_dumps.len(),
         vol_tars.len()
     );
 
@@ -391,6 +415,17 @@ pub async fn full_restore(
         }
     }
 
+    // Add app-dependent containers
+    for (app_name, _) in &app_dumps {
+        if let Some(app) = find_app_database(app_name) {
+            for c in app.containers {
+                if !all_containers.contains(c) {
+                    all_containers.push(c);
+                }
+            }
+        }
+    }
+
     // Add volume-dependent containers
     for (vol_name, _) in &vol_tars {
         if let Some(containers) = vol_container_map.get(vol_name.as_str()) {
@@ -438,6 +473,13 @@ pub async fn full_restore(
         }
     }
 
+    if !app_dumps.is_empty() {
+        match core_database() {
+            Ok(core) => restore_app_dumps(&mut app_dumps, &core, &mut result).await,
+            Err(e) => result.errors.push(e.to_string()),
+        }
+    }
+
     // Step 6: Restore volumes
     info!("Step 4: Restoring {} volumes", vol_tars.len());
     for (vol_name, tar_path) in &vol_tars {
@@ -485,3 +527,115 @@ pub async fn full_restore(
 
     Ok(result)
 }
+
+/// An app dump in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SnapshotDatabase {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Containers stopped while it is restored
+    pub containers: Vec<String>,
+}
+
+/// List the per-app database dumps in a snapshot
+pub async fn list_snapshot_databases(
+    engine: &BackupEngine,
+    target: &BackupTarget,
+    snapshot_id: &str,
+) -> Result<Vec<SnapshotDatabase>> {
+    let files = engine.list_files(target, snapshot_id).await?;
+    Ok(files
+        .into_iter()
+        .filter(|f| f.path.contains("/databases/"))
+        .filter_map(|f| {
+            let name = Path::new(&f.path)
+                .file_name()?
+                .to_string_lossy()
+                .strip_suffix(".dump")?
+                .to_string();
+            let containers = find_app_database(&name)
+                .map(|app| app.containers.iter().map(|c| c.to_string()).collect())
+                .unwrap_or_default();
+            Some(SnapshotDatabase {
+                name,
+                size_bytes: f.size,
+                containers,
+            })
+        })
+        .collect())
+}
+
+/// Restore one app's tables from a snapshot, leaving everything else as is
+pub async fn restore_app_database(
+    snapshot_id: &str,
+    name: &str,
+    config: &BackupConfig,
+    target: &BackupTarget,
+) -> Result<RestoreResult> {
+    let start_time = std::time::Instant::now();
+
+    let mut result = RestoreResult {
+        success: false,
+        databases_restored: Vec::new(),
+        databases_failed: Vec::new(),
+        volumes_restored: Vec::new(),
+        volumes_failed: Vec::new(),
+        errors: Vec::new(),
+        duration_ms: 0,
+    };
+
+    let containers: &[&str] = match find_app_database(name) {
+        Some(app) => app.containers,
+        None if name == CORE_OTHER => &[],
+        None => return Err(anyhow::anyhow!("Unknown database '{}'", name)),
+    };
+    let core = core_database()?;
+
+    let engine = BackupEngine::new(config.clone());
+    let files = engine.list_files(target, snapshot_id).await?;
+    let suffix = format!("/databases/{}.dump", name);
+    let Some(dump) = files.iter().find(|f| f.path.ends_with(&suffix)) else {
+        return Err(anyhow::anyhow!(
+            "Snapshot {} has no dump of {}",
+            snapshot_id,
+            name
+        ));
+    };
+
+    let restore_path = format!("/tmp/restore-{}-{}", snapshot_id, name);
+    tokio::fs::create_dir_all(&restore_path).await?;
+    if let Err(e) = engine
+        .restore_paths(target, snapshot_id, &restore_path, &[dump.path.as_str()])
+        .await
+    {
+        let _ = tokio::fs::remove_dir_all(&restore_path).await;
+        return Err(e);
+    }
+    let dump_file = Path::new(&restore_path)
+        .join(dump.path.trim_start_matches('/'))
+        .to_string_lossy()
+        .to_string();
+
+    if let Err(e) = stop_containers(containers).await {
+        result
+            .errors
+            .push(format!("Warning: Error stopping containers: {}", e));
+    }
+
+    let mut dumps = vec![(name.to_string(), dump_file)];
+    restore_app_dumps(&mut dumps, &core, &mut result).await;
+
+    if let Err(e) = start_containers(containers).await {
+        result
+            .errors
+            .push(format!("Warning: Error starting containers: {}", e));
+    }
+
+    if let Err(e) = tokio::fs::remove_dir_all(&restore_path).await {
+        warn!("Failed to cleanup restore directory: {}", e);
+    }
+
+    result.success = result.databases_failed.is_empty() && !result.databases_restored.is_empty();
+    result.duration_ms = start_time.elapsed().as_millis() as u64;
+    Ok(result)
+}
@@ -0,0 +1,314 @@
+//! Backup verification and restore drills
+//!
+//! A verification runs `restic check`, reading a slice of the pack data,
+//! then restores a sample of small files from one snapshot into a temp dir
+//! and compares their sizes with the snapshot listing. restic checks each
+//! blob's MAC while restoring, so a clean sample restore means the data is
+//! readable, not just present. Every completed backup is verified; restore
+//! drills run the same steps from a scheduled task with a larger sample.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::path::Path;
+use std::time::Instant;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{error, info};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::backup::engine::{BackupEngine, BackupTarget, SnapshotFile};
+
+/// Reports kept in memory
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationKind {
+    /// Automatic check of a just-written snapshot
+    PostBackup,
+    /// Scheduled or manual restore drill of the newest snapshot
+    Drill,
+}
+
+/// How much a verification reads
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyOptions {
+    /// Percent of pack data `restic check` reads; 0 checks structure only
+    pub read_data_percent: u8,
+    /// Files restored from the snapshot
+    pub sample_files: usize,
+    /// Larger files are only sampled when nothing smaller exists
+    pub max_file_bytes: u64,
+}
+
+impl VerifyOptions {
+    /// Light enough to run after every backup
+    pub const POST_BACKUP: Self = Self {
+        read_data_percent: 1,
+        sample_files: 5,
+        max_file_bytes: 16 << 20,
+    };
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VerificationReport {
+    pub id: String,
+    pub kind: VerificationKind,
+    pub target: BackupTarget,
+    pub snapshot_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub success: bool,
+    /// Whether `restic check` passed
+    pub check_passed: bool,
+    /// Percent of pack data read by the check
+    pub read_data_percent: u8,
+    /// Files picked for the sample restore
+    pub files_sampled: usize,
+    /// Sampled files restored with the expected size
+    pub files_verified: usize,
+    pub errors: Vec<String>,
+    pub duration_ms: u64,
+}
+
+/// Recent verification reports; failures also raise a notification
+pub struct VerificationLog {
+    history: RwLock<Vec<VerificationReport>>,
+    /// Serializes verifications so they don't contend for the repository lock
+    running: Mutex<()>,
+    pool: Option<PgPool>,
+}
+
+impl VerificationLog {
+    pub fn new(pool: Option<PgPool>) -> Self {
+        Self {
+            history: RwLock::new(Vec::new()),
+            running: Mutex::new(()),
+            pool,
+        }
+    }
+
+    /// Reports, newest first
+    pub async fn history(&self) -> Vec<VerificationReport> {
+        self.history.read().await.iter().rev().cloned().collect()
+    }
+
+    /// Verify a snapshot, or the newest one when `snapshot_id` is `None`,
+    /// and record the report
+    pub async fn run(
+        &self,
+        engine: &BackupEngine,
+        backup_dir: &str,
+        target: BackupTarget,
+        snapshot_id: Option<String>,
+        kind: VerificationKind,
+        options: VerifyOptions,
+    ) -> VerificationReport {
+        let _guard = self.running.lock().await;
+        let report = verify(engine, backup_dir, target, snapshot_id, kind, options).await;
+
+        if report.success {
+            info!(
+                "Verification {} of snapshot {:?} passed ({}/{} files)",
+                report.id, report.snapshot_id, report.files_verified, report.files_sampled
+            );
+        } else {
+            error!(
+                "Verification {} of snapshot {:?} failed: {:?}",
+                report.id, report.snapshot_id, report.errors
+            );
+            self.notify_failure(&report).await;
+        }
+
+        let mut history = self.history.write().await;
+        history.push(report.clone());
+        if history.len() > HISTORY_LIMIT {
+            history.remove(0);
+        }
+
+        report
+    }
+
+    async fn notify_failure(&self, report: &VerificationReport) {
+        let Some(pool) = &self.pool else {
+            return;
+        };
+        let title = match report.kind {
+            VerificationKind::PostBackup => "Backup verification failed",
+            VerificationKind::Drill => "Restore drill failed",
+        };
+        let message = format!(
+            "Snapshot {} on {:?}: {}",
+            report.snapshot_id.as_deref().unwrap_or("(none)"),
+            report.target,
+            report.errors.join("; ")
+        );
+        if let Err(e) = sqlx::query(
+            "INSERT INTO notifications (notification_type, title, message, severity) VALUES ('backup', $1, $2, 'error')",
+        )
+        .bind(title)
+        .bind(message)
+        .execute(pool)
+        .await
+        {
+            error!("Failed to record verification notification: {}", e);
+        }
+    }
+}
+
+async fn verify(
+    engine: &BackupEngine,
+    backup_dir: &str,
+    target: BackupTarget,
+    snapshot_id: Option<String>,
+    kind: VerificationKind,
+    options: VerifyOptions,
+) -> VerificationReport {
+    let id = Uuid::new_v4().to_string();
+    let started_at = Utc::now();
+    let start = Instant::now();
+    let mut errors = Vec::new();
+
+    info!("Starting {:?} verification {} on {:?}", kind, id, target);
+
+    let check_passed = match engine.check(&target, options.read_data_percent).await {
+        Ok(()) => true,
+        Err(e) => {
+            errors.push(e.to_string());
+            false
+        }
+    };
+
+    let snapshot_id = match snapshot_id {
+        Some(snapshot_id) => Some(snapshot_id),
+        None => match engine.list_snapshots(&target).await {
+            Ok(snapshots) => snapshots.into_iter().max_by_key(|s| s.time).map(|s| s.id),
+            Err(e) => {
+                errors.push(e.to_string());
+                None
+            }
+        },
+    };
+
+    let (files_sampled, files_verified) = match &snapshot_id {
+        Some(snapshot_id) => {
+            // Rotate through the candidates so successive runs sample different files
+            let seed = started_at.timestamp() as usize;
+            match sample_restore(engine, backup_dir, &target, snapshot_id, &id, options, seed).await
+            {
+                Ok((sampled, failures)) => {
+                    let verified = sampled - failures.len();
+                    errors.extend(failures);
+                    (sampled, verified)
+                }
+                Err(e) => {
+                    errors.push(format!("Sample restore failed: {}", e));
+                    (0, 0)
+                }
+            }
+        }
+        None => {
+            errors.push("No snapshot to verify".to_string());
+            (0, 0)
+        }
+    };
+
+    VerificationReport {
+        id,
+        kind,
+        target,
+        snapshot_id,
+        started_at,
+        completed_at: Utc::now(),
+        success: errors.is_empty(),
+        check_passed,
+        read_data_percent: options.read_data_percent,
+        files_sampled,
+        files_verified,
+        errors,
+        duration_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+/// Restore a sample of files and compare their sizes
+///
+/// Returns the sample size and one message per file that didn't match.
+async fn sample_restore(
+    engine: &BackupEngine,
+    backup_dir: &str,
+    target: &BackupTarget,
+    snapshot_id: &str,
+    verification_id: &str,
+    options: VerifyOptions,
+    seed: usize,
+) -> Result<(usize, Vec<String>)> {
+    let files = engine.list_files(target, snapshot_id).await?;
+    let sample = pick_sample(files, options.sample_files, options.max_file_bytes, seed);
+    if sample.is_empty() {
+        bail!("snapshot contains no files");
+    }
+
+    // "temp-" so disk cleanup removes it if we're interrupted
+    let restore_dir = format!("{}/temp-verify-{}", backup_dir, verification_id);
+    tokio::fs::create_dir_all(&restore_dir).await?;
+
+    let paths: Vec<&str> = sample.iter().map(|f| f.path.as_str()).collect();
+    let result = engine
+        .restore_paths(target, snapshot_id, &restore_dir, &paths)
+        .await;
+
+    let mut failures = Vec::new();
+    if result.is_ok() {
+        for file in &sample {
+            let restored = Path::new(&restore_dir).join(file.path.trim_start_matches('/'));
+            match tokio::fs::metadata(&restored).await {
+                Ok(meta) if meta.len() == file.size => {}
+                Ok(meta) => failures.push(format!(
+                    "{}: restored {} bytes, expected {}",
+                    file.path,
+                    meta.len(),
+                    file.size
+                )),
+                Err(_) => failures.push(format!("{}: not restored", file.path)),
+            }
+        }
+    }
+
+    let _ = tokio::fs::remove_dir_all(&restore_dir).await;
+    result?;
+    Ok((sample.len(), failures))
+}
+
+/// Pick up to `count` files spread evenly over the snapshot
+///
+/// Files over `max_bytes` are skipped unless nothing smaller exists, in
+/// which case the smallest file is restored.
+fn pick_sample(
+    mut files: Vec<SnapshotFile>,
+    count: usize,
+    max_bytes: u64,
+    seed: usize,
+) -> Vec<SnapshotFile> {
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut candidates: Vec<SnapshotFile> = files
+        .iter()
+        .filter(|f| f.size <= max_bytes)
+        .cloned()
+        .collect();
+    if candidates.is_empty() {
+        candidates.extend(files.into_iter().min_by_key(|f| f.size));
+    }
+    if candidates.len() <= count {
+        return candidates;
+    }
+
+    let step = candidates.len() / count;
+    candidates
+        .into_iter()
+        .skip(seed % step)
+        .step_by(step)
+        .take(count)
+        .collect()
+}
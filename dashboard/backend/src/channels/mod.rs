@@ -0,0 +1,556 @@
+//! Notification delivery channels
+//!
+//! Channels live in the `notification_channels` table: each has a type, its
+//! JSON settings, and filters on severity and notification type. The
+//! dispatcher polls the `notifications` table and sends every new
+//! notification to each enabled channel whose filters it passes, so alerts
+//! from monitors, rules, and backups all reach the same places. Each channel
+//! keeps the id of the last notification it has seen; a failed delivery is
+//! recorded on the channel and not retried.
+
+pub mod smtp;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use self::smtp::{Security, SmtpConfig};
+
+/// Columns selected for a [`NotificationChannel`]
+const CHANNEL_COLUMNS: &str = "id, name, channel_type, enabled, config, min_severity, \
+     notification_types, last_delivered_at, last_error, created_at, updated_at";
+
+/// How often new notifications are dispatched
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Most notifications dispatched per pass
+const BATCH_SIZE: i64 = 100;
+
+/// Timeout of one HTTP delivery
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Shown in place of secrets in API responses; sending it back keeps the
+/// stored value
+pub const REDACTED: &str = "********";
+
+/// Config fields that hold credentials
+const SECRET_FIELDS: &[&str] = &["password", "bot_token", "token"];
+
+/// Where a channel delivers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelType {
+    /// Email through an SMTP server
+    Smtp,
+    /// Message from a Telegram bot
+    Telegram,
+    /// Push through an ntfy server (ntfy.sh or self-hosted)
+    Ntfy,
+    /// JSON POST to a URL
+    Webhook,
+}
+
+impl ChannelType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelType::Smtp => "smtp",
+            ChannelType::Telegram => "telegram",
+            ChannelType::Ntfy => "ntfy",
+            ChannelType::Webhook => "webhook",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "smtp" => Some(ChannelType::Smtp),
+            "telegram" => Some(ChannelType::Telegram),
+            "ntfy" => Some(ChannelType::Ntfy),
+            "webhook" => Some(ChannelType::Webhook),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmailOptions {
+    host: String,
+    #[serde(default = "default_smtp_port")]
+    port: u16,
+    #[serde(default)]
+    security: Security,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    from: String,
+    /// Recipient
+    to: String,
+}
+
+impl EmailOptions {
+    fn server(&self) -> SmtpConfig {
+        SmtpConfig {
+            host: self.host.clone(),
+            port: self.port,
+            security: self.security,
+            username: self.username.clone(),
+            password: self.password.clone(),
+            from: self.from.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TelegramOptions {
+    bot_token: String,
+    /// User, group, or channel id (or @channelname)
+    chat_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NtfyOptions {
+    #[serde(default = "default_ntfy_server")]
+    server: String,
+    topic: String,
+    /// Access token for protected topics
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhookOptions {
+    url: String,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(config: &serde_json::Value) -> Result<T> {
+    serde_json::from_value(config.clone()).map_err(|e| anyhow!("invalid channel config: {}", e))
+}
+
+fn check_url(url: &str, field: &str) -> Result<()> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        bail!("{} must be an http(s) URL", field);
+    }
+    Ok(())
+}
+
+/// Check a channel's settings before saving them
+pub fn validate_config(channel_type: ChannelType, config: &serde_json::Value) -> Result<()> {
+    match channel_type {
+        ChannelType::Smtp => {
+            let options: EmailOptions = parse(config)?;
+            options.server().validate()?;
+            if !smtp::is_address(&options.to) {
+                bail!("to must be an email address");
+            }
+        }
+        ChannelType::Telegram => {
+            let options: TelegramOptions = parse(config)?;
+            if options.bot_token.trim().is_empty() || options.chat_id.trim().is_empty() {
+                bail!("bot_token and chat_id are required");
+            }
+        }
+        ChannelType::Ntfy => {
+            let options: NtfyOptions = parse(config)?;
+            check_url(&options.server, "server")?;
+            if options.topic.is_empty()
+                || !options
+                    .topic
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!("topic must be letters, digits, '-' or '_'");
+            }
+        }
+        ChannelType::Webhook => {
+            let options: WebhookOptions = parse(config)?;
+            check_url(&options.url, "url")?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace secrets in a config with [`REDACTED`]
+pub fn redact(config: &serde_json::Value) -> serde_json::Value {
+    let mut config = config.clone();
+    if let Some(fields) = config.as_object_mut() {
+        for field in SECRET_FIELDS {
+            if let Some(value) = fields.get_mut(*field).filter(|v| v.is_string()) {
+                *value = serde_json::Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    config
+}
+
+/// Put stored secrets back where an update sent [`REDACTED`]
+pub fn restore_secrets(config: &mut serde_json::Value, stored: &serde_json::Value) {
+    let Some(fields) = config.as_object_mut() else {
+        return;
+    };
+    for field in SECRET_FIELDS {
+        if fields.get(*field).and_then(|v| v.as_str()) == Some(REDACTED) {
+            match stored.get(*field) {
+                Some(value) => fields.insert(field.to_string(), value.clone()),
+                None => fields.remove(*field),
+            };
+        }
+    }
+}
+
+/// Rank of a severity for `min_severity` filtering
+pub fn severity_rank(severity: &str) -> Option<u8> {
+    match severity {
+        "info" | "success" => Some(0),
+        "warning" => Some(1),
+        "error" => Some(2),
+        _ => None,
+    }
+}
+
+/// A configured channel
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NotificationChannel {
+    pub id: i32,
+    pub name: String,
+    pub channel_type: String,
+    pub enabled: bool,
+    /// Channel type specific settings, secrets redacted
+    pub config: serde_json::Value,
+    /// Lowest severity delivered: info, warning, or error
+    pub min_severity: String,
+    /// Notification types delivered; empty for all
+    pub notification_types: Vec<String>,
+    pub last_delivered_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationChannel {
+    fn from_row(row: &PgRow) -> Self {
+        let config: serde_json::Value = row.get("config");
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            channel_type: row.get("channel_type"),
+            enabled: row.get("enabled"),
+            config: redact(&config),
+            min_severity: row.get("min_severity"),
+            notification_types: row.get("notification_types"),
+            last_delivered_at: row.get("last_delivered_at"),
+            last_error: row.get("last_error"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+
+    /// Whether a notification passes the channel's filters
+    fn accepts(&self, notification: &Outgoing) -> bool {
+        let min = severity_rank(&self.min_severity).unwrap_or(0);
+        let rank = severity_rank(&notification.severity).unwrap_or(0);
+        rank >= min
+            && (self.notification_types.is_empty()
+                || self
+                    .notification_types
+                    .contains(&notification.notification_type))
+    }
+}
+
+/// A notification on its way out
+#[derive(Debug, Clone, Serialize)]
+pub struct Outgoing {
+    pub id: i32,
+    pub notification_type: String,
+    pub title: String,
+    pub message: Option<String>,
+    pub severity: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Sends notifications to the configured channels
+pub struct Dispatcher {
+    pub pool: PgPool,
+    http: reqwest::Client,
+}
+
+impl Dispatcher {
+    pub fn new(pool: PgPool, http: reqwest::Client) -> Self {
+        Self { pool, http }
+    }
+
+    /// Load one channel
+    pub async fn get_channel(&self, channel_id: i32) -> Result<Option<NotificationChannel>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM notification_channels WHERE id = $1",
+            CHANNEL_COLUMNS
+        ))
+        .bind(channel_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(NotificationChannel::from_row))
+    }
+
+    /// Load every channel, ordered by id
+    pub async fn list_channels(&self) -> Result<Vec<NotificationChannel>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM notification_channels ORDER BY id",
+            CHANNEL_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(NotificationChannel::from_row).collect())
+    }
+
+    /// Stored settings of a channel, secrets included
+    pub async fn stored_config(&self, channel_id: i32) -> Result<Option<serde_json::Value>> {
+        Ok(
+            sqlx::query_scalar("SELECT config FROM notification_channels WHERE id = $1")
+                .bind(channel_id)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Send new notifications to every enabled channel
+    pub async fn dispatch(&self) -> Result<()> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, config AS raw_config, last_notification_id \
+             FROM notification_channels WHERE enabled ORDER BY id",
+            CHANNEL_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        let Some(from_id) = rows
+            .iter()
+            .map(|row| row.get::<i32, _>("last_notification_id"))
+            .min()
+        else {
+            return Ok(());
+        };
+
+        let notifications: Vec<Outgoing> = sqlx::query(
+            "SELECT id, notification_type, title, message, severity, created_at \
+             FROM notifications WHERE id > $1 ORDER BY id LIMIT $2",
+        )
+        .bind(from_id)
+        .bind(BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| Outgoing {
+            id: row.get("id"),
+            notification_type: row.get("notification_type"),
+            title: row.get("title"),
+            message: row.get("message"),
+            severity: row.get::<Option<String>, _>("severity").unwrap_or_default(),
+            created_at: row
+                .get::<Option<DateTime<Utc>>, _>("created_at")
+                .unwrap_or_else(Utc::now),
+        })
+        .collect();
+        let Some(to_id) = notifications.last().map(|n| n.id) else {
+            return Ok(());
+        };
+
+        for row in &rows {
+            let channel = NotificationChannel::from_row(row);
+            let config: serde_json::Value = row.get("raw_config");
+            let seen: i32 = row.get("last_notification_id");
+
+            let mut delivered = 0;
+            let mut errors = Vec::new();
+            for notification in notifications
+                .iter()
+                .filter(|n| n.id > seen && channel.accepts(n))
+            {
+                match self.send(&channel, &config, notification).await {
+                    Ok(()) => delivered += 1,
+                    Err(e) => errors.push(format!("#{}: {:#}", notification.id, e)),
+                }
+            }
+            for e in &errors {
+                warn!("Channel '{}' failed to deliver {}", channel.name, e);
+            }
+
+            let result = sqlx::query(
+                "UPDATE notification_channels SET last_notification_id = GREATEST(last_notification_id, $1), \
+                 last_delivered_at = CASE WHEN $2 THEN NOW() ELSE last_delivered_at END, \
+                 last_error = CASE WHEN $3::TEXT IS NULL THEN last_error ELSE $3 END \
+                 WHERE id = $4",
+            )
+            .bind(to_id)
+            .bind(delivered > 0)
+            .bind((!errors.is_empty()).then(|| errors.join("; ")))
+            .bind(channel.id)
+            .execute(&self.pool)
+            .await;
+            if let Err(e) = result {
+                error!("Failed to update channel '{}': {}", channel.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Send a test notification through a channel, recording the outcome
+    pub async fn test(&self, channel_id: i32) -> Result<Option<Result<()>>> {
+        let (Some(channel), Some(config)) = (
+            self.get_channel(channel_id).await?,
+            self.stored_config(channel_id).await?,
+        ) else {
+            return Ok(None);
+        };
+        let notification = Outgoing {
+            id: 0,
+            notification_type: "system".to_string(),
+            title: "Anchor OS test notification".to_string(),
+            message: Some(format!(
+                "The '{}' channel is set up correctly.",
+                channel.name
+            )),
+            severity: "info".to_string(),
+            created_at: Utc::now(),
+        };
+
+        let result = self.send(&channel, &config, &notification).await;
+        sqlx::query(
+            "UPDATE notification_channels SET \
+             last_delivered_at = CASE WHEN $1::TEXT IS NULL THEN NOW() ELSE last_delivered_at END, \
+             last_error = $1 WHERE id = $2",
+        )
+        .bind(result.as_ref().err().map(|e| format!("{:#}", e)))
+        .bind(channel_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(Some(result))
+    }
+
+    async fn send(
+        &self,
+        channel: &NotificationChannel,
+        config: &serde_json::Value,
+        notification: &Outgoing,
+    ) -> Result<()> {
+        let channel_type = ChannelType::parse(&channel.channel_type)
+            .ok_or_else(|| anyhow!("unknown channel type '{}'", channel.channel_type))?;
+        let body = notification.message.as_deref().unwrap_or_default();
+
+        match channel_type {
+            ChannelType::Smtp => {
+                let options: EmailOptions = parse(config)?;
+                let subject = format!("[Anchor OS] {}", notification.title);
+                smtp::send(&options.server(), &options.to, &subject, body).await
+            }
+            ChannelType::Telegram => {
+                let options: TelegramOptions = parse(config)?;
+                let text = if body.is_empty() {
+                    notification.title.clone()
+                } else {
+                    format!("{}\n\n{}", notification.title, body)
+                };
+                let response = self
+                    .http
+                    .post(format!(
+                        "https://api.telegram.org/bot{}/sendMessage",
+                        options.bot_token
+                    ))
+                    .timeout(HTTP_TIMEOUT)
+                    .json(&serde_json::json!({
+                        "chat_id": options.chat_id,
+                        "text": text,
+                        "disable_web_page_preview": true,
+                    }))
+                    .send()
+                    .await
+                    // The URL carries the bot token; keep it out of errors
+                    .map_err(|e| anyhow!("request failed: {}", e.without_url()))?;
+                check_status(response).await
+            }
+            ChannelType::Ntfy => {
+                let options: NtfyOptions = parse(config)?;
+                let priority = match notification.severity.as_str() {
+                    "error" => "urgent",
+                    "warning" => "high",
+                    _ => "default",
+                };
+                let mut request = self
+                    .http
+                    .post(format!(
+                        "{}/{}",
+                        options.server.trim_end_matches('/'),
+                        options.topic
+                    ))
+                    .timeout(HTTP_TIMEOUT)
+                    .header("Title", notification.title.clone())
+                    .header("Priority", priority)
+                    .header("Tags", notification.notification_type.clone())
+                    .body(if body.is_empty() {
+                        notification.title.clone()
+                    } else {
+                        body.to_string()
+                    });
+                if let Some(token) = options.token.as_deref() {
+                    request = request.bearer_auth(token);
+                }
+                let response = request.send().await.context("request failed")?;
+                check_status(response).await
+            }
+            ChannelType::Webhook => {
+                let options: WebhookOptions = parse(config)?;
+                let response = self
+                    .http
+                    .post(&options.url)
+                    .timeout(HTTP_TIMEOUT)
+                    .json(notification)
+                    .send()
+                    .await
+                    .context("request failed")?;
+                check_status(response).await
+            }
+        }
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    bail!(
+        "returned {}: {}",
+        status,
+        body.chars().take(200).collect::<String>()
+    )
+}
+
+/// Dispatch new notifications in the background
+pub fn start(dispatcher: Arc<Dispatcher>) {
+    tokio::spawn(async move {
+        info!("Starting notification dispatcher");
+        let mut dispatch_interval = interval(DISPATCH_INTERVAL);
+        loop {
+            dispatch_interval.tick().await;
+            if let Err(e) = dispatcher.dispatch().await {
+                warn!("Failed to dispatch notifications: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,217 @@
+//! Minimal SMTP client
+//!
+//! Enough of SMTP to hand a plain-text message to a mail server: EHLO,
+//! optional STARTTLS or implicit TLS, optional AUTH PLAIN, and one recipient.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+
+/// Connect and command timeout
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How the connection is secured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Security {
+    /// Plain text; only for a relay on a trusted network
+    None,
+    /// Upgrade with STARTTLS (usually port 587)
+    #[default]
+    Starttls,
+    /// TLS from the start (usually port 465)
+    Tls,
+}
+
+/// SMTP server settings
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+impl SmtpConfig {
+    /// Load from `SMTP_*` environment variables; `None` without `SMTP_HOST`
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("SMTP_HOST").ok().filter(|h| !h.is_empty())?;
+        let security = match std::env::var("SMTP_SECURITY").as_deref() {
+            Ok("none") => Security::None,
+            Ok("tls") => Security::Tls,
+            _ => Security::Starttls,
+        };
+        Some(Self {
+            host,
+            port: std::env::var("SMTP_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(587),
+            security,
+            username: std::env::var("SMTP_USERNAME")
+                .ok()
+                .filter(|u| !u.is_empty()),
+            password: std::env::var("SMTP_PASSWORD")
+                .ok()
+                .filter(|p| !p.is_empty()),
+            from: std::env::var("SMTP_FROM").unwrap_or_else(|_| "anchor@localhost".to_string()),
+        })
+    }
+
+    /// Check the settings before saving them
+    pub fn validate(&self) -> Result<()> {
+        if self.host.trim().is_empty() {
+            bail!("host is required");
+        }
+        if !is_address(&self.from) {
+            bail!("from must be an email address");
+        }
+        if self.username.is_some() != self.password.is_some() {
+            bail!("username and password must be set together");
+        }
+        Ok(())
+    }
+}
+
+/// Whether `s` looks like a single email address
+pub fn is_address(s: &str) -> bool {
+    s.contains('@') && !s.contains(|c: char| c.is_whitespace() || c == '<' || c == '>')
+}
+
+/// Send a plain-text email
+pub async fn send(config: &SmtpConfig, to: &str, subject: &str, body: &str) -> Result<()> {
+    if !is_address(to) {
+        bail!("invalid recipient '{}'", to);
+    }
+    if subject.contains(['\r', '\n']) {
+        bail!("subject must be a single line");
+    }
+
+    let tcp = timeout(
+        TIMEOUT,
+        TcpStream::connect((config.host.as_str(), config.port)),
+    )
+    .await
+    .context("connection timed out")?
+    .with_context(|| format!("failed to connect to {}:{}", config.host, config.port))?;
+
+    match config.security {
+        Security::None => {
+            let mut session = BufReader::new(tcp);
+            reply(&mut session, 220).await?;
+            command(&mut session, "EHLO anchor-os", 250).await?;
+            transact(&mut session, config, to, subject, body).await
+        }
+        Security::Starttls => {
+            let mut session = BufReader::new(tcp);
+            reply(&mut session, 220).await?;
+            command(&mut session, "EHLO anchor-os", 250).await?;
+            command(&mut session, "STARTTLS", 220).await?;
+            let mut session = BufReader::new(tls(&config.host, session.into_inner()).await?);
+            command(&mut session, "EHLO anchor-os", 250).await?;
+            transact(&mut session, config, to, subject, body).await
+        }
+        Security::Tls => {
+            let mut session = BufReader::new(tls(&config.host, tcp).await?);
+            reply(&mut session, 220).await?;
+            command(&mut session, "EHLO anchor-os", 250).await?;
+            transact(&mut session, config, to, subject, body).await
+        }
+    }
+}
+
+async fn tls(host: &str, tcp: TcpStream) -> Result<tokio_native_tls::TlsStream<TcpStream>> {
+    let connector = tokio_native_tls::TlsConnector::from(
+        native_tls::TlsConnector::new().context("failed to set up TLS")?,
+    );
+    timeout(TIMEOUT, connector.connect(host, tcp))
+        .await
+        .context("TLS handshake timed out")?
+        .context("TLS handshake failed")
+}
+
+/// Authenticate and send one message on an open session
+async fn transact<S: AsyncRead + AsyncWrite + Unpin>(
+    session: &mut BufReader<S>,
+    config: &SmtpConfig,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<()> {
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        let credentials = base64::engine::general_purpose::STANDARD
+            .encode(format!("\0{}\0{}", username, password));
+        command(session, &format!("AUTH PLAIN {}", credentials), 235)
+            .await
+            .map_err(|_| anyhow!("authentication failed"))?;
+    }
+
+    command(session, &format!("MAIL FROM:<{}>", config.from), 250).await?;
+    command(session, &format!("RCPT TO:<{}>", to), 250).await?;
+    command(session, "DATA", 354).await?;
+
+    // Dot-stuff lines starting with '.' so the body can't end the message
+    let body: String = body
+        .lines()
+        .map(|line| {
+            if line.starts_with('.') {
+                format!(".{}\r\n", line)
+            } else {
+                format!("{}\r\n", line)
+            }
+        })
+        .collect();
+    let message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}.",
+        config.from,
+        to,
+        subject,
+        Utc::now().to_rfc2822(),
+        body
+    );
+    command(session, &message, 250).await?;
+
+    // The message is accepted; a failed QUIT doesn't matter
+    let _ = session.write_all(b"QUIT\r\n").await;
+    Ok(())
+}
+
+async fn command<S: AsyncRead + AsyncWrite + Unpin>(
+    session: &mut BufReader<S>,
+    line: &str,
+    expected: u16,
+) -> Result<()> {
+    session
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await?;
+    session.flush().await?;
+    reply(session, expected).await
+}
+
+/// Read an SMTP reply, skipping continuation lines, and check its code
+async fn reply<S: AsyncRead + Unpin>(session: &mut BufReader<S>, expected: u16) -> Result<()> {
+    loop {
+        let mut line = String::new();
+        let read = timeout(TIMEOUT, session.read_line(&mut line))
+            .await
+            .context("server timed out")??;
+        if read == 0 {
+            bail!("connection closed");
+        }
+        // "250-..." continues a multi-line reply, "250 ..." ends it
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        let code: u16 = line.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+        if code != expected {
+            bail!("server replied: {}", line.trim_end());
+        }
+        return Ok(());
+    }
+}
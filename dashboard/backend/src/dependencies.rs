@@ -0,0 +1,364 @@
+//! Service dependency graph and ordered startup
+//!
+//! The graph comes from the `depends_on` lists of the installation service
+//! definitions. Services are grouped into layers: a service sits one layer
+//! above its deepest dependency, so starting layer by layer (and stopping in
+//! reverse) always brings bitcoind and Postgres up before the indexer,
+//! wallet, and electrum servers, and those before the apps.
+//!
+//! Between layers, ordered starts wait for the started containers to report
+//! healthy (or just running, when they have no healthcheck). A service whose
+//! dependency never got there is skipped rather than started into a broken
+//! stack.
+
+use bollard::container::{RestartContainerOptions, StartContainerOptions, StopContainerOptions};
+use bollard::models::{ContainerStateStatusEnum, HealthStatusEnum};
+use bollard::Docker;
+use futures::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::handlers::installation::{get_all_services, ServiceDefinition};
+
+/// How long to wait for one layer to become healthy
+pub const HEALTH_WAIT_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Delay between health polls
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Seconds a container gets between SIGTERM and SIGKILL
+const STOP_TIMEOUT_SECS: i64 = 10;
+
+/// Containers that shut down gracefully and need longer (their compose
+/// `stop_grace_period`): the indexer finishes its current block, the wallet
+/// drains requests and background work
+const GRACEFUL_CONTAINERS: &[(&str, i64)] =
+    &[("anchor-core-indexer", 30), ("anchor-core-wallet", 30)];
+
+/// Seconds to wait for `container` to stop before it is killed
+pub fn stop_timeout(container: &str) -> i64 {
+    GRACEFUL_CONTAINERS
+        .iter()
+        .find(|(name, _)| *name == container)
+        .map_or(STOP_TIMEOUT_SECS, |(_, secs)| *secs)
+}
+
+/// One service's containers, as started or stopped together
+#[derive(Debug, Clone)]
+pub struct ServiceStep {
+    pub service: String,
+    pub depends_on: Vec<String>,
+    pub containers: Vec<String>,
+}
+
+/// What an ordered operation does to each container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderedAction {
+    Start,
+    Restart,
+}
+
+/// Result of an ordered operation
+#[derive(Debug, Default)]
+pub struct OrderedOutcome {
+    pub affected: Vec<String>,
+    /// Containers that failed, never became healthy, or were skipped
+    /// because a dependency failed
+    pub failed: Vec<String>,
+}
+
+/// Dependencies between the services the dashboard knows about
+pub struct DependencyGraph {
+    services: Vec<ServiceDefinition>,
+    layers: HashMap<String, usize>,
+}
+
+impl Default for DependencyGraph {
+    fn default() -> Self {
+        Self::new(get_all_services())
+    }
+}
+
+impl DependencyGraph {
+    pub fn new(services: Vec<ServiceDefinition>) -> Self {
+        let mut layers = HashMap::new();
+        for service in &services {
+            layer_of(&service.id, &services, &mut layers, &mut HashSet::new());
+        }
+        Self { services, layers }
+    }
+
+    pub fn services(&self) -> &[ServiceDefinition] {
+        &self.services
+    }
+
+    /// Startup layer of a service; 0 for services without dependencies
+    pub fn layer(&self, service_id: &str) -> usize {
+        self.layers.get(service_id).copied().unwrap_or(0)
+    }
+
+    /// Service ids grouped by layer, in startup order
+    pub fn startup_order(&self) -> Vec<Vec<String>> {
+        let depth = self.layers.values().copied().max().map_or(0, |max| max + 1);
+        let mut order = vec![Vec::new(); depth];
+        for service in &self.services {
+            order[self.layer(&service.id)].push(service.id.clone());
+        }
+        order
+    }
+
+    /// The service a container belongs to
+    pub fn service_for_container(&self, container: &str) -> Option<&ServiceDefinition> {
+        self.services
+            .iter()
+            .find(|s| s.containers.iter().any(|c| c == container))
+    }
+
+    /// Group containers into per-service steps, layered in startup order
+    ///
+    /// Containers no service claims go in a final layer of their own.
+    pub fn plan(&self, containers: &[String]) -> Vec<Vec<ServiceStep>> {
+        let mut steps: HashMap<String, ServiceStep> = HashMap::new();
+        let mut unknown = Vec::new();
+        for container in containers {
+            match self.service_for_container(container) {
+                Some(service) => steps
+                    .entry(service.id.clone())
+                    .or_insert_with(|| ServiceStep {
+                        service: service.id.clone(),
+                        depends_on: service.depends_on.clone(),
+                        containers: Vec::new(),
+                    })
+                    .containers
+                    .push(container.clone()),
+                None => unknown.push(container.clone()),
+            }
+        }
+
+        let mut plan: Vec<Vec<ServiceStep>> = self
+            .startup_order()
+            .into_iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .filter_map(|id| steps.remove(id))
+                    .map(|mut step| {
+                        step.containers.sort_by_key(|c| container_rank(c));
+                        step
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|layer| !layer.is_empty())
+            .collect();
+
+        if !unknown.is_empty() {
+            plan.push(
+                unknown
+                    .into_iter()
+                    .map(|container| ServiceStep {
+                        service: container.clone(),
+                        depends_on: Vec::new(),
+                        containers: vec![container],
+                    })
+                    .collect(),
+            );
+        }
+        plan
+    }
+}
+
+/// Depth-first layer assignment; a dependency cycle is cut where it closes
+fn layer_of(
+    id: &str,
+    services: &[ServiceDefinition],
+    layers: &mut HashMap<String, usize>,
+    visiting: &mut HashSet<String>,
+) -> usize {
+    if let Some(layer) = layers.get(id) {
+        return *layer;
+    }
+    if !visiting.insert(id.to_string()) {
+        warn!("Dependency cycle through service {}", id);
+        return 0;
+    }
+
+    let layer = services
+        .iter()
+        .find(|s| s.id == id)
+        .map(|service| {
+            service
+                .depends_on
+                .iter()
+                .filter(|dep| services.iter().any(|s| &s.id == *dep))
+                .map(|dep| layer_of(dep, services, layers, visiting) + 1)
+                .max()
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+
+    visiting.remove(id);
+    layers.insert(id.to_string(), layer);
+    layer
+}
+
+/// Order within a service: databases, then APIs/backends, then the rest
+fn container_rank(container: &str) -> u8 {
+    if container.ends_with("-db") || container.ends_with("-postgres") {
+        0
+    } else if container.ends_with("-api") || container.ends_with("-backend") {
+        1
+    } else {
+        2
+    }
+}
+
+/// Start or restart containers layer by layer, waiting for each layer to
+/// become healthy before moving on
+pub async fn run_ordered(
+    docker: &Docker,
+    plan: &[Vec<ServiceStep>],
+    action: OrderedAction,
+    health_timeout: Duration,
+) -> OrderedOutcome {
+    let mut outcome = OrderedOutcome::default();
+    let mut failed_services: HashSet<String> = HashSet::new();
+
+    for (index, layer) in plan.iter().enumerate() {
+        let mut started: Vec<(String, String)> = Vec::new();
+
+        for step in layer {
+            if let Some(dep) = step
+                .depends_on
+                .iter()
+                .find(|d| failed_services.contains(*d))
+            {
+                warn!(
+                    "Skipping {}: dependency {} is not healthy",
+                    step.service, dep
+                );
+                failed_services.insert(step.service.clone());
+                outcome.failed.extend(step.containers.iter().cloned());
+                continue;
+            }
+
+            for container in &step.containers {
+                let result = match action {
+                    OrderedAction::Start => {
+                        docker
+                            .start_container(container, None::<StartContainerOptions<String>>)
+                            .await
+                    }
+                    OrderedAction::Restart => {
+                        docker
+                            .restart_container(
+                                container,
+                                Some(RestartContainerOptions {
+                                    t: stop_timeout(container) as isize,
+                                }),
+                            )
+                            .await
+                    }
+                };
+                match result {
+                    Ok(_) => started.push((step.service.clone(), container.clone())),
+                    Err(e) => {
+                        warn!("Failed to {:?} {}: {}", action, container, e);
+                        failed_services.insert(step.service.clone());
+                        outcome.failed.push(container.clone());
+                    }
+                }
+            }
+        }
+
+        let checks = join_all(
+            started
+                .iter()
+                .map(|(_, container)| wait_until_ready(docker, container, health_timeout)),
+        )
+        .await;
+        for ((service, container), check) in started.into_iter().zip(checks) {
+            match check {
+                Ok(()) => outcome.affected.push(container),
+                Err(e) => {
+                    warn!("{} did not become ready: {}", container, e);
+                    failed_services.insert(service);
+                    outcome.failed.push(container);
+                }
+            }
+        }
+        info!("Startup layer {} of {} done", index + 1, plan.len());
+    }
+
+    outcome
+}
+
+/// Stop containers in reverse startup order
+pub async fn stop_ordered(docker: &Docker, plan: &[Vec<ServiceStep>]) -> OrderedOutcome {
+    let mut outcome = OrderedOutcome::default();
+    for layer in plan.iter().rev() {
+        let containers: Vec<&String> = layer
+            .iter()
+            .flat_map(|step| step.containers.iter().rev())
+            .collect();
+        let results = join_all(containers.iter().map(|container| {
+            docker.stop_container(
+                container,
+                Some(StopContainerOptions {
+                    t: stop_timeout(container),
+                }),
+            )
+        }))
+        .await;
+        for (container, result) in containers.into_iter().zip(results) {
+            match result {
+                Ok(_) => outcome.affected.push(container.clone()),
+                Err(e) => {
+                    warn!("Failed to stop {}: {}", container, e);
+                    outcome.failed.push(container.clone());
+                }
+            }
+        }
+    }
+    outcome
+}
+
+/// Wait until a container is healthy, or running if it has no healthcheck
+pub async fn wait_until_ready(
+    docker: &Docker,
+    container: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let state = docker
+            .inspect_container(container, None)
+            .await
+            .map_err(|e| e.to_string())?
+            .state
+            .unwrap_or_default();
+
+        match state.status {
+            Some(ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::DEAD) => {
+                return Err(format!(
+                    "exited with code {}",
+                    state.exit_code.unwrap_or(-1)
+                ))
+            }
+            Some(ContainerStateStatusEnum::RUNNING) => match state.health.and_then(|h| h.status) {
+                Some(HealthStatusEnum::HEALTHY)
+                | Some(HealthStatusEnum::NONE)
+                | Some(HealthStatusEnum::EMPTY)
+                | None => return Ok(()),
+                Some(HealthStatusEnum::UNHEALTHY) => return Err("unhealthy".to_string()),
+                Some(HealthStatusEnum::STARTING) => {}
+            },
+            _ => {}
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!("not ready after {}s", timeout.as_secs()));
+        }
+        tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+    }
+}
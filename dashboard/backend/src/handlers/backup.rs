@@ -10,8 +10,10 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{error, info};
+use utoipa::ToSchema;
 
 use crate::backup::engine::{BackupEngine, BackupJob, BackupStatus, BackupTarget, BackupType};
+use crate::backup::verify::{VerificationKind, VerificationLog, VerificationReport, VerifyOptions};
 use crate::backup::{database, restore, volumes};
 use crate::backup_config::BackupConfig;
 use crate::storage::{self, StorageInfo};
@@ -24,13 +26,14 @@ pub struct BackupState {
     pub engine: BackupEngine,
     pub current_job: Arc<RwLock<Option<BackupJob>>>,
     pub job_history: Arc<RwLock<Vec<BackupJob>>>,
+    pub verifications: Arc<VerificationLog>,
     pub settings: RwLock<BackupSettings>,
     pub scheduler: BackupScheduler,
     pub host_backup_path: Option<String>,
 }
 
 impl BackupState {
-    pub async fn new(config: BackupConfig) -> Self {
+    pub async fn new(config: BackupConfig, db_pool: Option<sqlx::PgPool>) -> Self {
         let engine = BackupEngine::new(config.clone());
         let scheduler = BackupScheduler::new()
             .await
@@ -44,6 +47,7 @@ impl BackupState {
             engine,
             current_job: Arc::new(RwLock::new(None)),
             job_history: Arc::new(RwLock::new(Vec::new())),
+            verifications: Arc::new(VerificationLog::new(db_pool)),
             settings: RwLock::new(BackupSettings::default()),
             scheduler,
             host_backup_path,
@@ -57,7 +61,7 @@ impl BackupState {
 
 // Request/Response types
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BackupStatusResponse {
     pub running: bool,
     pub current_job: Option<BackupJob>,
@@ -65,38 +69,59 @@ pub struct BackupStatusResponse {
     pub next_scheduled: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct StartBackupRequest {
     pub target: Option<String>,
     pub include_databases: Option<bool>,
     pub include_volumes: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StartBackupResponse {
     pub job_id: String,
     pub status: String,
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HistoryResponse {
     pub backups: Vec<BackupJob>,
     pub total: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationHistoryResponse {
+    pub verifications: Vec<VerificationReport>,
+    pub total: usize,
+    /// Reports of failed verifications
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TargetsResponse {
     pub targets: Vec<StorageInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RestoreRequest {
     pub snapshot_id: String,
     pub target: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RestoreDatabaseRequest {
+    pub snapshot_id: String,
+    pub target: Option<String>,
+    /// App dump to restore: indexer, domains, tokens, dashboard, or core-other
+    pub database: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SnapshotDatabasesResponse {
+    pub databases: Vec<restore::SnapshotDatabase>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RestoreResponse {
     pub success: bool,
     pub message: String,
@@ -116,19 +141,19 @@ pub struct RestoreResponse {
     pub duration_ms: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SnapshotsResponse {
     pub snapshots: Vec<crate::backup::engine::ResticSnapshot>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BackupSettings {
     pub schedule: ScheduleSettings,
     pub s3: S3Settings,
     pub smb: SmbSettings,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ScheduleSettings {
     pub enabled: bool,
     pub cron_expression: String,
@@ -139,7 +164,7 @@ pub struct ScheduleSettings {
     pub keep_last: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct S3Settings {
     pub endpoint: String,
     pub bucket: String,
@@ -148,7 +173,7 @@ pub struct S3Settings {
     pub region: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SmbSettings {
     pub host: String,
     pub share: String,
@@ -187,7 +212,7 @@ impl Default for BackupSettings {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LocalFile {
     pub name: String,
     pub path: String,
@@ -196,7 +221,7 @@ pub struct LocalFile {
     pub modified: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LocalFilesResponse {
     pub path: String,
     pub host_path: Option<String>,
@@ -207,6 +232,14 @@ pub struct LocalFilesResponse {
 // Handlers
 
 /// Get backup status
+#[utoipa::path(
+    get,
+    path = "/backup/status",
+    responses(
+        (status = 200, description = "Current job, last backup and next scheduled run", body = BackupStatusResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn get_status(State(state): State<Arc<BackupState>>) -> impl IntoResponse {
     let current_job = state.current_job.read().await.clone();
     let history = state.job_history.read().await;
@@ -233,6 +266,16 @@ pub async fn get_status(State(state): State<Arc<BackupState>>) -> impl IntoRespo
 }
 
 /// Start a backup
+#[utoipa::path(
+    post,
+    path = "/backup/start",
+    request_body = StartBackupRequest,
+    responses(
+        (status = 202, description = "Backup started", body = StartBackupResponse),
+        (status = 409, description = "A backup is already running", body = StartBackupResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn start_backup(
     State(state): State<Arc<BackupState>>,
     Json(req): Json<StartBackupRequest>,
@@ -305,6 +348,7 @@ async fn run_backup(
             target: target.clone(),
             size_bytes: None,
             files_count: None,
+            snapshot_id: None,
             error_message: None,
         });
     }
@@ -325,17 +369,7 @@ async fn run_backup(
         if let Err(e) = tokio::fs::create_dir_all(&db_dir).await {
             error!("Failed to create db dir: {}", e);
         } else {
-            for db_config in database::get_anchor_databases() {
-                match database::pg_dump(&db_config, &db_dir).await {
-                    Ok(dump_path) => {
-                        paths_to_backup.push(dump_path);
-                    }
-                    Err(e) => {
-                        error!("Failed to dump {}: {}", db_config.name, e);
-                        // Continue with other databases
-                    }
-                }
-            }
+            paths_to_backup.extend(database::dump_all(&db_dir).await);
         }
     }
 
@@ -391,6 +425,7 @@ async fn run_backup(
                 target,
                 size_bytes: None,
                 files_count: None,
+                snapshot_id: None,
                 error_message: Some(e.to_string()),
             }
         }
@@ -407,6 +442,22 @@ async fn run_backup(
         }
     }
 
+    // Verify the new snapshot while the job is still current, so no other
+    // backup starts in the middle of the check
+    if completed_job.status == BackupStatus::Completed {
+        state
+            .verifications
+            .run(
+                &state.engine,
+                &state.config.backup_dir,
+                completed_job.target.clone(),
+                completed_job.snapshot_id.clone(),
+                VerificationKind::PostBackup,
+                VerifyOptions::POST_BACKUP,
+            )
+            .await;
+    }
+
     // Clear current job
     {
         let mut current = state.current_job.write().await;
@@ -420,6 +471,14 @@ async fn run_backup(
 }
 
 /// Get backup history
+#[utoipa::path(
+    get,
+    path = "/backup/history",
+    responses(
+        (status = 200, description = "Backup jobs", body = HistoryResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn get_history(State(state): State<Arc<BackupState>>) -> impl IntoResponse {
     let history = state.job_history.read().await;
 
@@ -429,7 +488,34 @@ pub async fn get_history(State(state): State<Arc<BackupState>>) -> impl IntoResp
     })
 }
 
+/// Get backup verification and restore drill reports, newest first
+#[utoipa::path(
+    get,
+    path = "/backup/verification-history",
+    responses(
+        (status = 200, description = "Verification reports", body = VerificationHistoryResponse)
+    ),
+    tag = "Backup"
+)]
+pub async fn get_verification_history(State(state): State<Arc<BackupState>>) -> impl IntoResponse {
+    let verifications = state.verifications.history().await;
+
+    Json(VerificationHistoryResponse {
+        total: verifications.len(),
+        failed: verifications.iter().filter(|v| !v.success).count(),
+        verifications,
+    })
+}
+
 /// Get storage targets
+#[utoipa::path(
+    get,
+    path = "/backup/targets",
+    responses(
+        (status = 200, description = "Storage targets and their usage", body = TargetsResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn get_targets(State(state): State<Arc<BackupState>>) -> impl IntoResponse {
     let mut targets = Vec::new();
 
@@ -452,6 +538,15 @@ pub async fn get_targets(State(state): State<Arc<BackupState>>) -> impl IntoResp
 }
 
 /// Restore from backup
+#[utoipa::path(
+    post,
+    path = "/backup/restore",
+    request_body = RestoreRequest,
+    responses(
+        (status = 200, description = "Restore result; `success` is false if any part failed", body = RestoreResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn restore(
     State(state): State<Arc<BackupState>>,
     Json(req): Json<RestoreRequest>,
@@ -517,7 +612,149 @@ pub async fn restore(
     }
 }
 
+/// Restore one app's database tables from a snapshot
+#[utoipa::path(
+    post,
+    path = "/backup/restore/database",
+    request_body = RestoreDatabaseRequest,
+    responses(
+        (status = 200, description = "Database restored", body = RestoreResponse),
+        (status = 400, description = "Unknown database", body = RestoreResponse),
+        (status = 409, description = "A backup is running", body = RestoreResponse)
+    ),
+    tag = "Backup"
+)]
+pub async fn restore_database(
+    State(state): State<Arc<BackupState>>,
+    Json(req): Json<RestoreDatabaseRequest>,
+) -> impl IntoResponse {
+    if state.current_job.read().await.is_some() {
+        return (
+            StatusCode::CONFLICT,
+            Json(RestoreResponse {
+                success: false,
+                message: "A backup is in progress".to_string(),
+                job_id: None,
+                databases_restored: None,
+                databases_failed: None,
+                volumes_restored: None,
+                volumes_failed: None,
+                errors: None,
+                duration_ms: None,
+            }),
+        );
+    }
+
+    let target = match req.target.as_deref() {
+        Some("s3") => BackupTarget::S3,
+        Some("smb") => BackupTarget::Smb,
+        _ => BackupTarget::Local,
+    };
+
+    info!(
+        "Restoring database {} from snapshot {}",
+        req.database, req.snapshot_id
+    );
+
+    match restore::restore_app_database(&req.snapshot_id, &req.database, &state.config, &target)
+        .await
+    {
+        Ok(result) => (
+            StatusCode::OK,
+            Json(RestoreResponse {
+                success: result.success,
+                message: if result.success {
+                    format!(
+                        "Database {} restored in {}ms",
+                        req.database, result.duration_ms
+                    )
+                } else {
+                    format!("Restore of database {} failed", req.database)
+                },
+                job_id: Some(req.snapshot_id.clone()),
+                databases_restored: Some(result.databases_restored),
+                databases_failed: Some(result.databases_failed),
+                volumes_restored: None,
+                volumes_failed: None,
+                errors: if result.errors.is_empty() {
+                    None
+                } else {
+                    Some(result.errors)
+                },
+                duration_ms: Some(result.duration_ms),
+            }),
+        ),
+        Err(e) => {
+            error!("Database restore failed: {}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(RestoreResponse {
+                    success: false,
+                    message: format!("Restore failed: {}", e),
+                    job_id: None,
+                    databases_restored: None,
+                    databases_failed: None,
+                    volumes_restored: None,
+                    volumes_failed: None,
+                    errors: Some(vec![e.to_string()]),
+                    duration_ms: None,
+                }),
+            )
+        }
+    }
+}
+
+/// List the per-app database dumps in a snapshot
+#[utoipa::path(
+    get,
+    path = "/backup/snapshots/{target}/{snapshot_id}/databases",
+    params(
+        ("target" = String, Path, description = "Storage target: local, s3, or smb"),
+        ("snapshot_id" = String, Path, description = "Snapshot ID")
+    ),
+    responses(
+        (status = 200, description = "Database dumps in the snapshot", body = SnapshotDatabasesResponse),
+        (status = 404, description = "Snapshot not found", body = SnapshotDatabasesResponse)
+    ),
+    tag = "Backup"
+)]
+pub async fn list_snapshot_databases(
+    State(state): State<Arc<BackupState>>,
+    Path((target, snapshot_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let backup_target = match target.as_str() {
+        "s3" => BackupTarget::S3,
+        "smb" => BackupTarget::Smb,
+        _ => BackupTarget::Local,
+    };
+
+    match restore::list_snapshot_databases(&state.engine, &backup_target, &snapshot_id).await {
+        Ok(databases) => (
+            StatusCode::OK,
+            Json(SnapshotDatabasesResponse { databases }),
+        ),
+        Err(e) => {
+            error!("Failed to list snapshot databases: {}", e);
+            (
+                StatusCode::NOT_FOUND,
+                Json(SnapshotDatabasesResponse { databases: vec![] }),
+            )
+        }
+    }
+}
+
 /// List snapshots
+#[utoipa::path(
+    get,
+    path = "/backup/snapshots/{target}",
+    params(
+        ("target" = String, Path, description = "Storage target: local, s3, or smb")
+    ),
+    responses(
+        (status = 200, description = "Snapshots in the repository", body = SnapshotsResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn list_snapshots(
     State(state): State<Arc<BackupState>>,
     Path(target): Path<String>,
@@ -538,12 +775,29 @@ pub async fn list_snapshots(
 }
 
 /// Get settings
+#[utoipa::path(
+    get,
+    path = "/backup/settings",
+    responses(
+        (status = 200, description = "Schedule and storage settings", body = BackupSettings)
+    ),
+    tag = "Backup"
+)]
 pub async fn get_settings(State(state): State<Arc<BackupState>>) -> impl IntoResponse {
     let settings = state.settings.read().await;
     Json(settings.clone())
 }
 
 /// Save settings
+#[utoipa::path(
+    put,
+    path = "/backup/settings",
+    request_body = BackupSettings,
+    responses(
+        (status = 200, description = "Settings saved; `success` is false if the schedule could not be updated")
+    ),
+    tag = "Backup"
+)]
 pub async fn save_settings(
     State(state): State<Arc<BackupState>>,
     Json(new_settings): Json<BackupSettings>,
@@ -563,6 +817,7 @@ pub async fn save_settings(
             config,
             state.job_history.clone(),
             state.current_job.clone(),
+            state.verifications.clone(),
         )
         .await
     {
@@ -591,6 +846,14 @@ pub async fn save_settings(
 }
 
 /// List local backup files
+#[utoipa::path(
+    get,
+    path = "/backup/local/files",
+    responses(
+        (status = 200, description = "Files in the local backup directory", body = LocalFilesResponse)
+    ),
+    tag = "Backup"
+)]
 pub async fn list_local_files(State(state): State<Arc<BackupState>>) -> impl IntoResponse {
     let backup_dir = &state.config.backup_dir;
 
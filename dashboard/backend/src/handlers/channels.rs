@@ -0,0 +1,337 @@
+//! Notification channel handlers
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::channels::{
+    restore_secrets, severity_rank, validate_config, ChannelType, Dispatcher, NotificationChannel,
+};
+
+/// List channels response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelsListResponse {
+    pub channels: Vec<NotificationChannel>,
+}
+
+/// Create channel request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateChannelRequest {
+    pub name: String,
+    pub channel_type: ChannelType,
+    pub enabled: Option<bool>,
+    /// Channel type specific settings
+    pub config: serde_json::Value,
+    /// info, warning, or error (default: info)
+    pub min_severity: Option<String>,
+    /// Notification types to deliver; omitted or empty for all
+    pub notification_types: Option<Vec<String>>,
+}
+
+/// Update channel request; omitted fields are left unchanged
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateChannelRequest {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    /// Full settings; redacted secrets keep their stored values
+    pub config: Option<serde_json::Value>,
+    pub min_severity: Option<String>,
+    pub notification_types: Option<Vec<String>>,
+}
+
+/// Test delivery response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TestChannelResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+/// Generic action response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChannelActionResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+fn dispatcher(state: &Option<Arc<Dispatcher>>) -> ApiResult<&Arc<Dispatcher>> {
+    state.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available".to_string(),
+        )
+    })
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn bad_request(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, e.to_string())
+}
+
+fn not_found(id: i32) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("Channel {} not found", id))
+}
+
+fn validate_name(name: &str) -> ApiResult<()> {
+    if name.trim().is_empty() || name.len() > 100 {
+        return Err(bad_request("Name must be 1-100 characters"));
+    }
+    Ok(())
+}
+
+fn validate_filters(
+    min_severity: Option<&str>,
+    notification_types: Option<&[String]>,
+) -> ApiResult<()> {
+    if let Some(severity) = min_severity {
+        if severity == "success" || severity_rank(severity).is_none() {
+            return Err(bad_request("min_severity must be info, warning, or error"));
+        }
+    }
+    if let Some(types) = notification_types {
+        if types.iter().any(|t| t.trim().is_empty() || t.len() > 50) {
+            return Err(bad_request("notification_types must be 1-50 characters"));
+        }
+    }
+    Ok(())
+}
+
+/// List all notification channels
+#[utoipa::path(
+    get,
+    path = "/notifications/channels",
+    responses(
+        (status = 200, description = "Notification channels", body = ChannelsListResponse)
+    ),
+    tag = "Notifications"
+)]
+pub async fn list_channels(
+    State(state): State<Option<Arc<Dispatcher>>>,
+) -> ApiResult<Json<ChannelsListResponse>> {
+    let channels = dispatcher(&state)?
+        .list_channels()
+        .await
+        .map_err(internal)?;
+    Ok(Json(ChannelsListResponse { channels }))
+}
+
+/// Get a notification channel
+#[utoipa::path(
+    get,
+    path = "/notifications/channels/{id}",
+    params(("id" = i32, Path, description = "Channel ID")),
+    responses(
+        (status = 200, description = "Notification channel", body = NotificationChannel),
+        (status = 404, description = "Channel not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn get_channel(
+    State(state): State<Option<Arc<Dispatcher>>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<NotificationChannel>> {
+    dispatcher(&state)?
+        .get_channel(id)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(|| not_found(id))
+}
+
+/// Create a notification channel
+///
+/// The channel receives notifications created from now on.
+#[utoipa::path(
+    post,
+    path = "/notifications/channels",
+    request_body = CreateChannelRequest,
+    responses(
+        (status = 201, description = "Channel created", body = NotificationChannel),
+        (status = 400, description = "Invalid settings or filters")
+    ),
+    tag = "Notifications"
+)]
+pub async fn create_channel(
+    State(state): State<Option<Arc<Dispatcher>>>,
+    Json(req): Json<CreateChannelRequest>,
+) -> ApiResult<(StatusCode, Json<NotificationChannel>)> {
+    let dispatcher = dispatcher(&state)?;
+    validate_name(&req.name)?;
+    validate_filters(
+        req.min_severity.as_deref(),
+        req.notification_types.as_deref(),
+    )?;
+    validate_config(req.channel_type, &req.config).map_err(bad_request)?;
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO notification_channels \
+         (name, channel_type, enabled, config, min_severity, notification_types, last_notification_id) \
+         VALUES ($1, $2, $3, $4, $5, $6, (SELECT COALESCE(MAX(id), 0) FROM notifications)) \
+         RETURNING id",
+    )
+    .bind(req.name.trim())
+    .bind(req.channel_type.as_str())
+    .bind(req.enabled.unwrap_or(true))
+    .bind(&req.config)
+    .bind(req.min_severity.as_deref().unwrap_or("info"))
+    .bind(req.notification_types.unwrap_or_default())
+    .fetch_one(&dispatcher.pool)
+    .await
+    .map_err(internal)?;
+
+    let channel = dispatcher
+        .get_channel(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+    Ok((StatusCode::CREATED, Json(channel)))
+}
+
+/// Update a notification channel
+///
+/// Re-enabling a channel skips the notifications created while it was off.
+#[utoipa::path(
+    put,
+    path = "/notifications/channels/{id}",
+    params(("id" = i32, Path, description = "Channel ID")),
+    request_body = UpdateChannelRequest,
+    responses(
+        (status = 200, description = "Channel updated", body = NotificationChannel),
+        (status = 400, description = "Invalid settings or filters"),
+        (status = 404, description = "Channel not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn update_channel(
+    State(state): State<Option<Arc<Dispatcher>>>,
+    Path(id): Path<i32>,
+    Json(mut req): Json<UpdateChannelRequest>,
+) -> ApiResult<Json<NotificationChannel>> {
+    let dispatcher = dispatcher(&state)?;
+    let channel = dispatcher
+        .get_channel(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+
+    if let Some(ref name) = req.name {
+        validate_name(name)?;
+    }
+    validate_filters(
+        req.min_severity.as_deref(),
+        req.notification_types.as_deref(),
+    )?;
+    if let Some(ref mut config) = req.config {
+        let stored = dispatcher
+            .stored_config(id)
+            .await
+            .map_err(internal)?
+            .unwrap_or_default();
+        restore_secrets(config, &stored);
+        let channel_type = ChannelType::parse(&channel.channel_type)
+            .ok_or_else(|| internal(format!("unknown channel type '{}'", channel.channel_type)))?;
+        validate_config(channel_type, config).map_err(bad_request)?;
+    }
+
+    sqlx::query(
+        "UPDATE notification_channels SET \
+         name = COALESCE($1, name), \
+         last_notification_id = CASE WHEN $2 AND NOT enabled \
+             THEN (SELECT COALESCE(MAX(id), 0) FROM notifications) ELSE last_notification_id END, \
+         enabled = COALESCE($2, enabled), \
+         config = COALESCE($3, config), \
+         min_severity = COALESCE($4, min_severity), \
+         notification_types = COALESCE($5, notification_types), \
+         updated_at = NOW() \
+         WHERE id = $6",
+    )
+    .bind(req.name.as_deref().map(str::trim))
+    .bind(req.enabled)
+    .bind(&req.config)
+    .bind(&req.min_severity)
+    .bind(&req.notification_types)
+    .bind(id)
+    .execute(&dispatcher.pool)
+    .await
+    .map_err(internal)?;
+
+    dispatcher
+        .get_channel(id)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(|| not_found(id))
+}
+
+/// Delete a notification channel
+#[utoipa::path(
+    delete,
+    path = "/notifications/channels/{id}",
+    params(("id" = i32, Path, description = "Channel ID")),
+    responses(
+        (status = 200, description = "Channel deleted", body = ChannelActionResponse),
+        (status = 404, description = "Channel not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn delete_channel(
+    State(state): State<Option<Arc<Dispatcher>>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<ChannelActionResponse>> {
+    let dispatcher = dispatcher(&state)?;
+    let result = sqlx::query("DELETE FROM notification_channels WHERE id = $1")
+        .bind(id)
+        .execute(&dispatcher.pool)
+        .await
+        .map_err(internal)?;
+    if result.rows_affected() == 0 {
+        return Err(not_found(id));
+    }
+
+    Ok(Json(ChannelActionResponse {
+        success: true,
+        message: "Channel deleted".to_string(),
+    }))
+}
+
+/// Send a test notification through a channel
+#[utoipa::path(
+    post,
+    path = "/notifications/channels/{id}/test",
+    params(("id" = i32, Path, description = "Channel ID")),
+    responses(
+        (status = 200, description = "Test delivered or failed", body = TestChannelResponse),
+        (status = 404, description = "Channel not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn test_channel(
+    State(state): State<Option<Arc<Dispatcher>>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<TestChannelResponse>> {
+    let result = dispatcher(&state)?
+        .test(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+
+    Ok(Json(match result {
+        Ok(()) => TestChannelResponse {
+            success: true,
+            message: "Test notification delivered".to_string(),
+        },
+        Err(e) => TestChannelResponse {
+            success: false,
+            message: format!("Delivery failed: {:#}", e),
+        },
+    }))
+}
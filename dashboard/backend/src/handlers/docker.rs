@@ -12,11 +12,12 @@ use bollard::container::{
 };
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::{error, info};
 use utoipa::ToSchema;
 
+use crate::dependencies::{self, DependencyGraph, OrderedAction, OrderedOutcome};
 use crate::AppState;
 
 /// Container info response
@@ -336,7 +337,9 @@ pub async fn stop_container(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     info!("Stopping container: {}", id);
 
-    let options = Some(StopContainerOptions { t: 10 });
+    let options = Some(StopContainerOptions {
+        t: dependencies::stop_timeout(&id),
+    });
 
     match state.docker.stop_container(&id, options).await {
         Ok(_) => Ok(Json(ContainerActionResponse {
@@ -370,7 +373,9 @@ pub async fn restart_container(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     info!("Restarting container: {}", id);
 
-    let options = Some(RestartContainerOptions { t: 10 });
+    let options = Some(RestartContainerOptions {
+        t: dependencies::stop_timeout(&id) as isize,
+    });
     match state.docker.restart_container(&id, options).await {
         Ok(_) => Ok(Json(ContainerActionResponse {
             success: true,
@@ -393,30 +398,19 @@ pub struct BulkActionResponse {
     pub failed_containers: Vec<String>,
 }
 
-/// Stop all anchor-* containers (except dashboard)
-#[utoipa::path(
-    post,
-    path = "/docker/shutdown",
-    tag = "Docker",
-    responses(
-        (status = 200, description = "All containers stopped", body = BulkActionResponse),
-        (status = 500, description = "Internal server error")
-    )
-)]
-pub async fn shutdown_all(
-    State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    info!("Shutting down all Anchor OS containers...");
-
-    // Containers that must stay running for the dashboard to work
-    let essential_containers = ["anchor-dashboard-backend", "anchor-dashboard-frontend"];
+/// Containers that must stay running for the dashboard to work
+const ESSENTIAL_CONTAINERS: [&str; 2] = ["anchor-dashboard-backend", "anchor-dashboard-frontend"];
 
-    // List all anchor-* containers
+/// Names of anchor-* containers, excluding the dashboard's own
+async fn list_anchor_containers(
+    state: &AppState,
+    all: bool,
+) -> Result<Vec<String>, (StatusCode, String)> {
     let mut filters = HashMap::new();
     filters.insert("name", vec!["anchor-"]);
 
     let options = Some(ListContainersOptions {
-        all: false, // Only running containers
+        all,
         filters,
         ..Default::default()
     });
@@ -427,120 +421,120 @@ pub async fn shutdown_all(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let mut affected = Vec::new();
-    let mut failed = Vec::new();
-
-    for container in containers {
-        let name = container
-            .names
-            .as_ref()
-            .and_then(|n| n.first())
-            .map(|n| n.trim_start_matches('/').to_string())
-            .unwrap_or_default();
-
-        // Skip essential containers
-        if essential_containers.iter().any(|e| name.contains(e)) {
-            continue;
-        }
-
-        let stop_options = Some(StopContainerOptions { t: 10 });
-        match state.docker.stop_container(&name, stop_options).await {
-            Ok(_) => {
-                info!("Stopped container: {}", name);
-                affected.push(name);
-            }
-            Err(e) => {
-                error!("Failed to stop container {}: {}", name, e);
-                failed.push(name);
-            }
-        }
-    }
+    Ok(containers
+        .into_iter()
+        .filter(|c| all || c.state.as_deref() == Some("running"))
+        .filter_map(|c| {
+            c.names
+                .as_ref()
+                .and_then(|n| n.first())
+                .map(|n| n.trim_start_matches('/').to_string())
+        })
+        .filter(|name| !ESSENTIAL_CONTAINERS.iter().any(|e| name.contains(e)))
+        .collect())
+}
 
-    Ok(Json(BulkActionResponse {
-        success: failed.is_empty(),
+fn bulk_response(verb: &str, outcome: OrderedOutcome) -> Json<BulkActionResponse> {
+    Json(BulkActionResponse {
+        success: outcome.failed.is_empty(),
         message: format!(
-            "Stopped {} containers, {} failed",
-            affected.len(),
-            failed.len()
+            "{} {} containers, {} failed",
+            verb,
+            outcome.affected.len(),
+            outcome.failed.len()
         ),
-        affected_containers: affected,
-        failed_containers: failed,
-    }))
+        affected_containers: outcome.affected,
+        failed_containers: outcome.failed,
+    })
 }
 
-/// Restart all anchor-* containers (except dashboard)
+/// Stop all anchor-* containers (except dashboard), dependents first
 #[utoipa::path(
     post,
-    path = "/docker/restart-all",
+    path = "/docker/shutdown",
     tag = "Docker",
     responses(
-        (status = 200, description = "All containers restarted", body = BulkActionResponse),
+        (status = 200, description = "All containers stopped", body = BulkActionResponse),
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn restart_all(
+pub async fn shutdown_all(
     State(state): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    info!("Restarting all Anchor OS containers...");
-
-    // Containers that should not be restarted
-    let skip_containers = ["anchor-dashboard-backend", "anchor-dashboard-frontend"];
-
-    // List all anchor-* containers
-    let mut filters = HashMap::new();
-    filters.insert("name", vec!["anchor-"]);
+    info!("Shutting down all Anchor OS containers...");
 
-    let options = Some(ListContainersOptions {
-        all: false, // Only running containers
-        filters,
-        ..Default::default()
-    });
+    let containers = list_anchor_containers(&state, false).await?;
+    let plan = DependencyGraph::default().plan(&containers);
+    let outcome = dependencies::stop_ordered(&state.docker, &plan).await;
 
-    let containers = state
-        .docker
-        .list_containers(options)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(bulk_response("Stopped", outcome))
+}
 
-    let mut affected = Vec::new();
-    let mut failed = Vec::new();
+/// Start all stopped anchor-* containers in dependency order
+///
+/// Each layer of services must report healthy before the next one starts;
+/// services whose dependencies never become healthy are skipped.
+#[utoipa::path(
+    post,
+    path = "/docker/start-all",
+    tag = "Docker",
+    responses(
+        (status = 200, description = "Containers started in dependency order", body = BulkActionResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn start_all(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    info!("Starting all Anchor OS containers in dependency order...");
+
+    let running: HashSet<String> = list_anchor_containers(&state, false)
+        .await?
+        .into_iter()
+        .collect();
+    let containers: Vec<String> = list_anchor_containers(&state, true)
+        .await?
+        .into_iter()
+        .filter(|c| !running.contains(c))
+        .collect();
+    let plan = DependencyGraph::default().plan(&containers);
+    let outcome = dependencies::run_ordered(
+        &state.docker,
+        &plan,
+        OrderedAction::Start,
+        dependencies::HEALTH_WAIT_TIMEOUT,
+    )
+    .await;
 
-    for container in containers {
-        let name = container
-            .names
-            .as_ref()
-            .and_then(|n| n.first())
-            .map(|n| n.trim_start_matches('/').to_string())
-            .unwrap_or_default();
+    Ok(bulk_response("Started", outcome))
+}
 
-        // Skip dashboard containers
-        if skip_containers.iter().any(|e| name.contains(e)) {
-            continue;
-        }
+/// Restart all running anchor-* containers (except dashboard) in dependency order
+#[utoipa::path(
+    post,
+    path = "/docker/restart-all",
+    tag = "Docker",
+    responses(
+        (status = 200, description = "All containers restarted", body = BulkActionResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn restart_all(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    info!("Restarting all Anchor OS containers...");
 
-        let restart_options = Some(RestartContainerOptions { t: 10 });
-        match state.docker.restart_container(&name, restart_options).await {
-            Ok(_) => {
-                info!("Restarted container: {}", name);
-                affected.push(name);
-            }
-            Err(e) => {
-                error!("Failed to restart container {}: {}", name, e);
-                failed.push(name);
-            }
-        }
-    }
+    let containers = list_anchor_containers(&state, false).await?;
+    let plan = DependencyGraph::default().plan(&containers);
+    let outcome = dependencies::run_ordered(
+        &state.docker,
+        &plan,
+        OrderedAction::Restart,
+        dependencies::HEALTH_WAIT_TIMEOUT,
+    )
+    .await;
 
-    Ok(Json(BulkActionResponse {
-        success: failed.is_empty(),
-        message: format!(
-            "Restarted {} containers, {} failed",
-            affected.len(),
-            failed.len()
-        ),
-        affected_containers: affected,
-        failed_containers: failed,
-    }))
+    Ok(bulk_response("Restarted", outcome))
 }
 
 /// Get container logs
@@ -483,7 +483,7 @@ async fn start_container_safe(state: &Arc<AppState>, container_name: &str) -> Re
     }
 }
 
-async fn get_configured_server(state: &Arc<AppState>) -> Result<ElectrumServer, String> {
+pub(crate) async fn get_configured_server(state: &Arc<AppState>) -> Result<ElectrumServer, String> {
     let pool = state.db_pool.as_ref().ok_or("Database not available")?;
 
     let row = sqlx::query("SELECT value FROM system_settings WHERE key = 'electrum_server'")
@@ -501,7 +501,7 @@ async fn get_configured_server(state: &Arc<AppState>) -> Result<ElectrumServer,
     }
 }
 
-async fn save_configured_server(
+pub(crate) async fn save_configured_server(
     state: &Arc<AppState>,
     server: ElectrumServer,
 ) -> Result<(), String> {
@@ -0,0 +1,265 @@
+//! Cross-app activity feed
+//!
+//! Merges the messages an address created across every Anchor app into one
+//! chronological list. Reads the indexer's `messages` table, which records the
+//! address that funded each message transaction's first input.
+
+use anchor_specs::dns::{DnsOperation, DnsSpec};
+use anchor_specs::geomarker::GeoMarkerSpec;
+use anchor_specs::proof::{ProofOperation, ProofSpec};
+use anchor_specs::text::TextSpec;
+use anchor_specs::token::{TokenOperation, TokenSpec};
+use anchor_specs::KindSpec;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::AppState;
+
+/// Apps and the message kinds they own
+const APPS: &[(&str, &[i16])] = &[
+    ("threads", &[1]),
+    ("domains", &[10]),
+    ("proofs", &[11]),
+    ("places", &[12]),
+    ("tokens", &[20]),
+    ("predictions", &[40, 41, 42, 43, 44]),
+];
+
+/// Longest text preview in a feed item
+const SUMMARY_CHARS: usize = 140;
+
+/// Query parameters for the activity feed
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    /// Comma-separated addresses
+    pub address: String,
+    /// Comma-separated app names or numeric kinds
+    pub kinds: Option<String>,
+    /// Return items older than this message id
+    pub cursor: Option<i32>,
+    pub limit: Option<i64>,
+}
+
+/// One activity entry
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedItem {
+    pub id: i32,
+    pub app: String,
+    pub kind: i16,
+    pub kind_name: String,
+    pub address: String,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Short human-readable description, when the body decodes
+    pub summary: Option<String>,
+}
+
+/// A page of activity
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedResponse {
+    pub items: Vec<FeedItem>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<i32>,
+}
+
+/// Get the activity feed for one or more addresses, newest first
+#[utoipa::path(
+    get,
+    path = "/feed",
+    params(
+        ("address" = String, Query, description = "Comma-separated addresses"),
+        ("kinds" = Option<String>, Query, description = "Filter by app (threads, domains, tokens, places, proofs, predictions) or numeric kind, comma-separated"),
+        ("cursor" = Option<i32>, Query, description = "next_cursor from the previous page"),
+        ("limit" = Option<i64>, Query, description = "Items per page (default: 50, max: 100)")
+    ),
+    responses(
+        (status = 200, description = "Activity feed", body = FeedResponse),
+        (status = 400, description = "Invalid address or kind filter"),
+        (status = 503, description = "Database not available")
+    ),
+    tag = "Feed"
+)]
+pub async fn get_feed(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FeedQuery>,
+) -> Result<Json<FeedResponse>, (StatusCode, String)> {
+    let pool = state.db_pool.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available".to_string(),
+        )
+    })?;
+
+    let addresses: Vec<String> = query
+        .address
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(String::from)
+        .collect();
+    if addresses.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "address is required".to_string()));
+    }
+
+    let kinds = match query.kinds.as_deref() {
+        Some(filter) => parse_kinds(filter).map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+        None => APPS
+            .iter()
+            .flat_map(|(_, kinds)| kinds.iter().copied())
+            .collect(),
+    };
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, kind, creator_address, txid, vout, block_height, created_at, body
+        FROM messages
+        WHERE creator_address = ANY($1)
+          AND kind = ANY($2)
+          AND ($3::INTEGER IS NULL OR id < $3)
+        ORDER BY id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(&addresses)
+    .bind(&kinds)
+    .bind(query.cursor)
+    .bind(limit + 1)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let has_more = rows.len() as i64 > limit;
+    let items: Vec<FeedItem> = rows
+        .iter()
+        .take(limit as usize)
+        .map(|row| {
+            let kind: i16 = row.get("kind");
+            let body: Vec<u8> = row.get("body");
+            let mut txid: Vec<u8> = row.get("txid");
+            txid.reverse();
+            FeedItem {
+                id: row.get("id"),
+                app: app_for_kind(kind).to_string(),
+                kind,
+                kind_name: kind_name(kind).to_string(),
+                address: row.get("creator_address"),
+                txid: hex::encode(txid),
+                vout: row.get("vout"),
+                block_height: row.get("block_height"),
+                created_at: row.get("created_at"),
+                summary: summarize(kind, &body),
+            }
+        })
+        .collect();
+    let next_cursor = if has_more {
+        items.last().map(|item| item.id)
+    } else {
+        None
+    };
+
+    Ok(Json(FeedResponse { items, next_cursor }))
+}
+
+/// Expand a `kinds` filter of app names and kind numbers
+fn parse_kinds(filter: &str) -> Result<Vec<i16>, String> {
+    let mut kinds = Vec::new();
+    for part in filter.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        if let Some((_, app_kinds)) = APPS.iter().find(|(app, _)| *app == part) {
+            kinds.extend_from_slice(app_kinds);
+        } else if let Ok(kind) = part.parse::<i16>() {
+            kinds.push(kind);
+        } else {
+            return Err(format!("unknown kind filter: {}", part));
+        }
+    }
+    Ok(kinds)
+}
+
+fn app_for_kind(kind: i16) -> &'static str {
+    APPS.iter()
+        .find(|(_, kinds)| kinds.contains(&kind))
+        .map(|(app, _)| *app)
+        .unwrap_or("other")
+}
+
+fn kind_name(kind: i16) -> &'static str {
+    match kind {
+        1 => "Text",
+        10 => "DNS",
+        11 => "Proof",
+        12 => "GeoMarker",
+        20 => "Token",
+        40 => "Market Create",
+        41 => "Place Bet",
+        42 => "Market Resolve",
+        43 => "Claim Winnings",
+        44 => "Market Order",
+        _ => "Unknown",
+    }
+}
+
+/// Describe a message body in a line
+fn summarize(kind: i16, body: &[u8]) -> Option<String> {
+    let summary = match kind {
+        1 => TextSpec::from_bytes(body).ok()?.text,
+        10 => {
+            let spec = DnsSpec::from_bytes(body).ok()?;
+            let action = match spec.operation {
+                DnsOperation::Register => "Registered",
+                DnsOperation::Update => "Updated",
+                DnsOperation::Transfer => "Transferred",
+            };
+            format!("{} {}", action, spec.name)
+        }
+        11 => {
+            let spec = ProofSpec::from_bytes(body).ok()?;
+            let action = match spec.operation {
+                ProofOperation::Stamp | ProofOperation::Batch => "Stamped",
+                ProofOperation::Revoke => "Revoked",
+                ProofOperation::Witness => "Witnessed",
+            };
+            let subject = match spec.entries.as_slice() {
+                [entry] => entry
+                    .metadata
+                    .filename
+                    .clone()
+                    .unwrap_or_else(|| hex::encode(&entry.hash)),
+                entries => format!("{} proofs", entries.len()),
+            };
+            format!("{} {}", action, subject)
+        }
+        12 => GeoMarkerSpec::from_bytes(body).ok()?.message,
+        20 => match TokenSpec::from_bytes(body).ok()?.operation {
+            TokenOperation::Deploy { ticker, .. } => format!("Deployed {}", ticker),
+            TokenOperation::Mint {
+                token_id, amount, ..
+            } => format!("Minted {} of token #{}", amount, token_id),
+            TokenOperation::Transfer { token_id, .. } => {
+                format!("Transferred token #{}", token_id)
+            }
+            TokenOperation::Burn { token_id, amount } => {
+                format!("Burned {} of token #{}", amount, token_id)
+            }
+            TokenOperation::Split { token_id, .. } => format!("Split token #{}", token_id),
+            TokenOperation::Approve {
+                token_id, amount, ..
+            } => format!("Approved {} of token #{}", amount, token_id),
+        },
+        _ => return None,
+    };
+
+    Some(match summary.char_indices().nth(SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}…", &summary[..end]),
+        None => summary,
+    })
+}
@@ -67,6 +67,7 @@ fn get_kind_name(kind: i32) -> String {
         41 => "Place Bet".to_string(),
         42 => "Market Resolve".to_string(),
         43 => "Claim Winnings".to_string(),
+        44 => "Market Order".to_string(),
         _ => format!("Kind {}", kind),
     }
 }
@@ -122,7 +123,7 @@ fn get_app_info(kind: i32) -> Option<AppInfo> {
             app_path: "/apps/oracles".to_string(),
             color: "#EF4444".to_string(), // red
         }),
-        40..=43 => Some(AppInfo {
+        40..=44 => Some(AppInfo {
             app_id: "predictions".to_string(),
             app_name: "Predictions".to_string(),
             app_path: "/apps/predictions".to_string(),
@@ -1311,6 +1312,14 @@ pub struct LiveStats {
 }
 
 /// WebSocket live feed handler
+#[utoipa::path(
+    get,
+    path = "/indexer/ws/live",
+    responses(
+        (status = 101, description = "WebSocket upgrade; each text frame is a LiveMessageEvent", body = LiveMessageEvent)
+    ),
+    tag = "Indexer"
+)]
 pub async fn ws_live_feed(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
@@ -8,7 +8,7 @@ use axum::{
     Json,
 };
 use bollard::container::ListContainersOptions;
-use futures::stream::Stream;
+use futures::stream::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
 use std::collections::HashMap;
@@ -19,6 +19,8 @@ use tokio::process::Command;
 use tracing::info;
 use utoipa::ToSchema;
 
+use crate::preflight::{self, PreflightReport};
+use crate::preset_migration::{self, MigrationPlan};
 use crate::AppState;
 
 /// Installation presets
@@ -141,7 +143,7 @@ pub struct ServiceUninstallRequest {
 }
 
 // Define all available services
-fn get_all_services() -> Vec<ServiceDefinition> {
+pub(crate) fn get_all_services() -> Vec<ServiceDefinition> {
     vec![
         // Core services
         ServiceDefinition {
@@ -583,7 +585,7 @@ fn get_all_services() -> Vec<ServiceDefinition> {
     ]
 }
 
-fn get_preset_services(preset: InstallationPreset) -> Vec<String> {
+pub(crate) fn get_preset_services(preset: InstallationPreset) -> Vec<String> {
     match preset {
         InstallationPreset::Minimum => vec![
             "core-bitcoin".to_string(),
@@ -614,6 +616,63 @@ fn get_preset_services(preset: InstallationPreset) -> Vec<String> {
     }
 }
 
+fn parse_preset(preset: &str) -> InstallationPreset {
+    match preset {
+        "minimum" => InstallationPreset::Minimum,
+        "full" => InstallationPreset::Full,
+        "custom" => InstallationPreset::Custom,
+        _ => InstallationPreset::Default,
+    }
+}
+
+/// Add required services and dependencies to a selection, rejecting
+/// incompatible combinations
+fn resolve_custom_services(requested: &[String]) -> Result<Vec<String>, (StatusCode, String)> {
+    // Validate incompatibilities
+    let all_services = get_all_services();
+    for service_id in requested {
+        if let Some(service) = all_services.iter().find(|s| &s.id == service_id) {
+            for incompatible in &service.incompatible_with {
+                if requested.contains(incompatible) {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        format!(
+                            "Service '{}' is incompatible with '{}'",
+                            service_id, incompatible
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    // Add required services
+    let mut final_services = requested.to_vec();
+    for service in &all_services {
+        if service.required && !final_services.contains(&service.id) {
+            final_services.push(service.id.clone());
+        }
+    }
+
+    // Add dependencies
+    let mut added_deps = true;
+    while added_deps {
+        added_deps = false;
+        for service in &all_services {
+            if final_services.contains(&service.id) {
+                for dep in &service.depends_on {
+                    if !final_services.contains(dep) {
+                        final_services.push(dep.clone());
+                        added_deps = true;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(final_services)
+}
+
 fn get_presets_info() -> Vec<PresetInfo> {
     vec![
         PresetInfo {
@@ -683,12 +742,7 @@ pub async fn get_installation_status(
     match row {
         Some(row) => {
             let preset_str: String = row.get("preset");
-            let preset = match preset_str.as_str() {
-                "minimum" => InstallationPreset::Minimum,
-                "full" => InstallationPreset::Full,
-                "custom" => InstallationPreset::Custom,
-                _ => InstallationPreset::Default,
-            };
+            let preset = parse_preset(&preset_str);
             let services: serde_json::Value = row.get("services");
             let setup_completed: bool = row.get("setup_completed");
 
@@ -862,47 +916,7 @@ pub async fn apply_custom(
         }
     };
 
-    // Validate incompatibilities
-    let all_services = get_all_services();
-    for service_id in &req.services {
-        if let Some(service) = all_services.iter().find(|s| &s.id == service_id) {
-            for incompatible in &service.incompatible_with {
-                if req.services.contains(incompatible) {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!(
-                            "Service '{}' is incompatible with '{}'",
-                            service_id, incompatible
-                        ),
-                    ));
-                }
-            }
-        }
-    }
-
-    // Add required services
-    let mut final_services = req.services.clone();
-    for service in &all_services {
-        if service.required && !final_services.contains(&service.id) {
-            final_services.push(service.id.clone());
-        }
-    }
-
-    // Add dependencies
-    let mut added_deps = true;
-    while added_deps {
-        added_deps = false;
-        for service in &all_services {
-            if final_services.contains(&service.id) {
-                for dep in &service.depends_on {
-                    if !final_services.contains(dep) {
-                        final_services.push(dep.clone());
-                        added_deps = true;
-                    }
-                }
-            }
-        }
-    }
+    let final_services = resolve_custom_services(&req.services)?;
 
     let services_json: HashMap<String, bool> =
         final_services.iter().map(|s| (s.clone(), true)).collect();
@@ -1380,6 +1394,7 @@ pub async fn reset_installation(
         SET preset = 'default',
             services = '{}',
             setup_completed = FALSE,
+            pending_migration = NULL,
             updated_at = NOW()
         WHERE id = 1
         "#,
@@ -1485,7 +1500,276 @@ pub async fn get_profiles(
     }
 }
 
+/// Preflight report history
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PreflightHistoryResponse {
+    pub reports: Vec<PreflightReport>,
+}
+
+const PREFLIGHT_HISTORY_LIMIT: i64 = 20;
+
+fn preflight_from_row(row: &sqlx::postgres::PgRow) -> Result<PreflightReport, serde_json::Error> {
+    Ok(PreflightReport {
+        id: Some(row.get("id")),
+        hardware: serde_json::from_value(row.get("hardware"))?,
+        checks: serde_json::from_value(row.get("checks"))?,
+        recommendation: serde_json::from_value(row.get("recommendation"))?,
+        warnings: row.get("warnings"),
+        created_at: row.get("created_at"),
+    })
+}
+
+async fn load_preflight_reports(
+    pool: &sqlx::PgPool,
+    limit: i64,
+) -> Result<Vec<PreflightReport>, (StatusCode, String)> {
+    let rows = sqlx::query(
+        "SELECT id, hardware, checks, recommendation, warnings, created_at \
+         FROM installation_preflight ORDER BY created_at DESC, id DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    rows.iter()
+        .map(preflight_from_row)
+        .collect::<Result<_, _>>()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Run preflight checks and recommend a preset
+///
+/// Profiles disk, memory, CPUs, host ports, and virtualization. The report
+/// and its warnings are saved so the wizard can show them again later.
+#[utoipa::path(
+    post,
+    path = "/installation/preflight",
+    tag = "Installation",
+    responses(
+        (status = 200, description = "Preflight report", body = PreflightReport),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn run_preflight(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PreflightReport>, (StatusCode, String)> {
+    let mut report = preflight::run(&state.docker).await;
+    info!(
+        "Preflight recommends {} ({} warnings)",
+        report.recommendation.preset,
+        report.warnings.len()
+    );
+
+    if let Some(pool) = &state.db_pool {
+        let json = |v: serde_json::Result<serde_json::Value>| {
+            v.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        };
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO installation_preflight (hardware, checks, recommendation, warnings, created_at) \
+             VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        )
+        .bind(json(serde_json::to_value(&report.hardware))?)
+        .bind(json(serde_json::to_value(&report.checks))?)
+        .bind(json(serde_json::to_value(&report.recommendation))?)
+        .bind(&report.warnings)
+        .bind(report.created_at)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        report.id = Some(id);
+    }
+
+    Ok(Json(report))
+}
+
+/// Get the latest preflight report
+#[utoipa::path(
+    get,
+    path = "/installation/preflight",
+    tag = "Installation",
+    responses(
+        (status = 200, description = "Latest preflight report", body = PreflightReport),
+        (status = 404, description = "Preflight has not been run"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_preflight(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PreflightReport>, (StatusCode, String)> {
+    let pool = state.db_pool.as_ref().ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Database not available".to_string(),
+    ))?;
+
+    load_preflight_reports(pool, 1)
+        .await?
+        .into_iter()
+        .next()
+        .map(Json)
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            "Preflight has not been run".to_string(),
+        ))
+}
+
+/// List recent preflight reports, newest first
+#[utoipa::path(
+    get,
+    path = "/installation/preflight/history",
+    tag = "Installation",
+    responses(
+        (status = 200, description = "Recent preflight reports", body = PreflightHistoryResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_preflight_history(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PreflightHistoryResponse>, (StatusCode, String)> {
+    let reports = match &state.db_pool {
+        Some(pool) => load_preflight_reports(pool, PREFLIGHT_HISTORY_LIMIT).await?,
+        None => Vec::new(),
+    };
+    Ok(Json(PreflightHistoryResponse { reports }))
+}
+
+/// Preset migration request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MigrateInstallationRequest {
+    pub preset: InstallationPreset,
+    /// Services to install when migrating to the custom preset
+    pub services: Option<Vec<String>>,
+    /// Seconds to wait for each index to catch up before starting its
+    /// dependents anyway (default: 1800)
+    pub wait_timeout_secs: Option<u64>,
+}
+
+/// Migrate a completed installation to another preset
+///
+/// Computes the service diff and saves a migration plan. The plan runs when
+/// `/installation/stream` is opened, which reports its progress. Services
+/// in both presets keep running, and removed services keep their volumes.
+#[utoipa::path(
+    post,
+    path = "/installation/migrate",
+    tag = "Installation",
+    request_body = MigrateInstallationRequest,
+    responses(
+        (status = 200, description = "Migration planned", body = MigrationPlan),
+        (status = 400, description = "Invalid target or nothing to change"),
+        (status = 409, description = "Setup not completed or a migration is pending"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn migrate_installation(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MigrateInstallationRequest>,
+) -> Result<Json<MigrationPlan>, (StatusCode, String)> {
+    let pool = state.db_pool.as_ref().ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Database not available".to_string(),
+    ))?;
+
+    let row = sqlx::query(
+        "SELECT preset, services, setup_completed, pending_migration FROM installation_config WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let Some(row) = row.filter(|r| r.get::<Option<bool>, _>("setup_completed") == Some(true))
+    else {
+        return Err((
+            StatusCode::CONFLICT,
+            "Setup has not been completed; choose a preset in the setup wizard".to_string(),
+        ));
+    };
+    if row
+        .get::<Option<serde_json::Value>, _>("pending_migration")
+        .is_some()
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            "A migration is pending; open the installation stream to finish it".to_string(),
+        ));
+    }
+
+    let from = parse_preset(row.get("preset"));
+    let services: HashMap<String, bool> =
+        serde_json::from_value(row.get("services")).unwrap_or_default();
+    let mut current: Vec<String> = services
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(id, _)| id)
+        .collect();
+    current.sort();
+
+    let target = match req.preset {
+        InstallationPreset::Custom => match &req.services {
+            Some(services) if !services.is_empty() => resolve_custom_services(services)?,
+            _ => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "The custom preset needs a list of services".to_string(),
+                ))
+            }
+        },
+        preset => get_preset_services(preset),
+    };
+    let all_services = get_all_services();
+    if let Some(unknown) = target
+        .iter()
+        .find(|id| !all_services.iter().any(|s| &s.id == *id))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown service: {}", unknown),
+        ));
+    }
+
+    let plan = MigrationPlan::new(
+        from,
+        &current,
+        req.preset,
+        &target,
+        req.wait_timeout_secs
+            .unwrap_or(preset_migration::DEFAULT_WAIT_TIMEOUT_SECS),
+    );
+    if plan.from == plan.to && plan.stages.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Already on the {} preset", plan.to),
+        ));
+    }
+
+    let plan_value = serde_json::to_value(&plan)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    sqlx::query(
+        "UPDATE installation_config SET pending_migration = $1, updated_at = NOW() WHERE id = 1",
+    )
+    .bind(&plan_value)
+    .execute(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!(
+        "Planned migration from {} to {}: +{:?} -{:?}",
+        plan.from, plan.to, plan.added, plan.removed
+    );
+
+    Ok(Json(plan))
+}
+
 /// Stream installation logs via Server-Sent Events
+#[utoipa::path(
+    get,
+    path = "/installation/stream",
+    responses(
+        (status = 200, description = "Installation log lines as Server-Sent Events", content_type = "text/event-stream", body = String),
+        (status = 500, description = "Database not available")
+    ),
+    tag = "Installation"
+)]
 pub async fn stream_installation(
     State(state): State<Arc<AppState>>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
@@ -1500,16 +1784,35 @@ pub async fn stream_installation(
     };
 
     // Get the selected services from the installation config
-    let row = sqlx::query("SELECT preset, services FROM installation_config WHERE id = 1")
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let row = sqlx::query(
+        "SELECT preset, services, pending_migration FROM installation_config WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let (preset, services_value): (String, serde_json::Value) = match row {
-        Some(r) => (r.get("preset"), r.get("services")),
-        None => ("default".to_string(), serde_json::json!({})),
+    let (preset, services_value, pending_migration): (
+        String,
+        serde_json::Value,
+        Option<serde_json::Value>,
+    ) = match row {
+        Some(r) => (
+            r.get("preset"),
+            r.get("services"),
+            r.get("pending_migration"),
+        ),
+        None => ("default".to_string(), serde_json::json!({}), None),
     };
 
+    // A pending preset migration replaces the full install
+    if let Some(plan) = pending_migration {
+        let plan: MigrationPlan = serde_json::from_value(plan)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        info!("Starting SSE stream for migration to {}", plan.to);
+        let stream = preset_migration::run(state.clone(), plan);
+        return Ok(Sse::new(stream.left_stream()).keep_alive(KeepAlive::default()));
+    }
+
     // Determine which profiles to use
     let mut profiles: Vec<String> = vec![];
 
@@ -1693,18 +1996,18 @@ pub async fn stream_installation(
         }
     };
 
-    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+    Ok(Sse::new(stream.right_stream()).keep_alive(KeepAlive::default()))
 }
 
 /// Helper function to strip ANSI escape codes
-fn strip_ansi_codes(s: &str) -> String {
+pub(crate) fn strip_ansi_codes(s: &str) -> String {
     let re =
         regex::Regex::new(r"\x1b\[[0-9;]*m").unwrap_or_else(|_| regex::Regex::new("").unwrap());
     re.replace_all(s, "").to_string()
 }
 
 /// Get all required profiles for a service (including dependencies)
-fn get_service_profiles(service: &str) -> Vec<String> {
+pub(crate) fn get_service_profiles(service: &str) -> Vec<String> {
     // Base profiles that are always needed for most services
     let mut all_profiles = vec!["core-bitcoin".to_string(), "core-postgres".to_string()];
 
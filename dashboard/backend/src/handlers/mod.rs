@@ -3,17 +3,22 @@
 pub mod auth;
 pub mod backup;
 pub mod bitcoin;
+pub mod channels;
 pub mod cloudflare;
 pub mod docker;
 pub mod electrum;
 pub mod explorer;
+pub mod feed;
 pub mod indexer;
 pub mod installation;
 pub mod node;
 pub mod notifications;
 pub mod profile;
+pub mod rules;
 pub mod settings;
+pub mod system;
 pub mod tailscale;
+pub mod tasks;
 pub mod tor;
 pub mod wallet;
 
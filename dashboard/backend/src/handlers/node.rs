@@ -1,6 +1,7 @@
 //! Node management handlers
 //!
-//! Endpoints for managing Bitcoin Core versions
+//! Endpoints for managing Bitcoin Core versions, settings, and the
+//! pruned/archival mode
 
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use bollard::container::ListContainersOptions;
@@ -10,6 +11,7 @@ use std::sync::Arc;
 use tracing::{error, info};
 use utoipa::ToSchema;
 
+use crate::node_mode::{ModeSwitch, NodeModeSwitcher, NodeStorageMode, SwitchError};
 use crate::AppState;
 
 /// Available Bitcoin Core versions
@@ -524,3 +526,69 @@ pub async fn reset_node_settings(
         config_path: "/data/bitcoin/bitcoin.conf".to_string(),
     }))
 }
+
+// =============================================
+// Pruned/archival mode
+// =============================================
+
+/// Node mode response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NodeModeStatus {
+    /// Mode the node runs with; None while it isn't answering RPC
+    pub mode: Option<NodeStorageMode>,
+    /// The running switch, or the last one to finish
+    pub switch: Option<ModeSwitch>,
+}
+
+/// Get the node's storage mode and switch progress
+#[utoipa::path(
+    get,
+    path = "/node/mode",
+    tag = "Node",
+    responses(
+        (status = 200, description = "Node mode and switch progress", body = NodeModeStatus)
+    )
+)]
+pub async fn get_node_mode(State(switcher): State<Arc<NodeModeSwitcher>>) -> Json<NodeModeStatus> {
+    Json(NodeModeStatus {
+        mode: switcher.node_mode().await.ok(),
+        switch: switcher.current().await,
+    })
+}
+
+/// Switch between pruned and archival mode, or toggle txindex
+///
+/// Stops the services that read from the node, restarts it with the new
+/// settings, and starts them again once the node has resynced. Going from
+/// pruned to archival rebuilds the block database and downloads every
+/// pruned block again. Poll `GET /node/mode` for progress.
+#[utoipa::path(
+    post,
+    path = "/node/mode",
+    tag = "Node",
+    request_body = NodeStorageMode,
+    responses(
+        (status = 202, description = "Switch started", body = ModeSwitch),
+        (status = 400, description = "Invalid mode or already in this mode"),
+        (status = 409, description = "A switch is running or an Electrum server needs an archival node"),
+        (status = 503, description = "Node or Docker not reachable")
+    )
+)]
+pub async fn switch_node_mode(
+    State(switcher): State<Arc<NodeModeSwitcher>>,
+    Json(mode): Json<NodeStorageMode>,
+) -> Result<(StatusCode, Json<ModeSwitch>), (StatusCode, String)> {
+    match switcher.start(mode).await {
+        Ok(switch) => Ok((StatusCode::ACCEPTED, Json(switch))),
+        Err(SwitchError::Invalid(message)) => Err((StatusCode::BAD_REQUEST, message)),
+        Err(SwitchError::Busy) => Err((
+            StatusCode::CONFLICT,
+            "A mode switch is already running".to_string(),
+        )),
+        Err(SwitchError::Conflict(message)) => Err((StatusCode::CONFLICT, message)),
+        Err(SwitchError::Unavailable(message)) => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Node not reachable: {}", message),
+        )),
+    }
+}
@@ -0,0 +1,345 @@
+//! Notification rule handlers
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::rules::{validate_config, NotificationRule, RuleEngine, RuleType};
+
+/// List rules response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RulesListResponse {
+    pub rules: Vec<NotificationRule>,
+    /// Whether `email_to` can be used (`SMTP_HOST` is set)
+    pub email_enabled: bool,
+}
+
+/// Create rule request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRuleRequest {
+    pub name: String,
+    pub rule_type: RuleType,
+    pub enabled: Option<bool>,
+    /// Rule type specific options; omitted fields use defaults
+    pub config: Option<serde_json::Value>,
+    /// info, success, warning, or error (default: info)
+    pub severity: Option<String>,
+    pub webhook_url: Option<String>,
+    pub email_to: Option<String>,
+}
+
+/// Update rule request; omitted fields are left unchanged, and an empty
+/// `webhook_url` or `email_to` removes that action
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRuleRequest {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    pub config: Option<serde_json::Value>,
+    pub severity: Option<String>,
+    pub webhook_url: Option<String>,
+    pub email_to: Option<String>,
+}
+
+/// Test delivery response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TestRuleResponse {
+    pub success: bool,
+    pub message: String,
+    /// Actions that failed
+    pub errors: Vec<String>,
+}
+
+/// Generic action response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RuleActionResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+fn engine(state: &Option<Arc<RuleEngine>>) -> ApiResult<&Arc<RuleEngine>> {
+    state.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available".to_string(),
+        )
+    })
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn bad_request(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, e.to_string())
+}
+
+fn not_found(id: i32) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("Rule {} not found", id))
+}
+
+fn validate_name(name: &str) -> ApiResult<()> {
+    if name.trim().is_empty() || name.len() > 100 {
+        return Err(bad_request("Name must be 1-100 characters"));
+    }
+    Ok(())
+}
+
+fn validate_actions(
+    severity: Option<&str>,
+    webhook_url: Option<&str>,
+    email_to: Option<&str>,
+) -> ApiResult<()> {
+    if let Some(severity) = severity {
+        if !matches!(severity, "info" | "success" | "warning" | "error") {
+            return Err(bad_request(
+                "severity must be info, success, warning, or error",
+            ));
+        }
+    }
+    if let Some(url) = webhook_url.filter(|u| !u.is_empty()) {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(bad_request("webhook_url must be an http(s) URL"));
+        }
+    }
+    if let Some(email) = email_to.filter(|e| !e.is_empty()) {
+        if !email.contains('@') || email.contains(char::is_whitespace) {
+            return Err(bad_request("email_to must be an email address"));
+        }
+    }
+    Ok(())
+}
+
+/// List all notification rules
+#[utoipa::path(
+    get,
+    path = "/notifications/rules",
+    responses(
+        (status = 200, description = "Notification rules", body = RulesListResponse)
+    ),
+    tag = "Notifications"
+)]
+pub async fn list_rules(
+    State(state): State<Option<Arc<RuleEngine>>>,
+) -> ApiResult<Json<RulesListResponse>> {
+    let engine = engine(&state)?;
+    let rules = engine.list_rules().await.map_err(internal)?;
+    Ok(Json(RulesListResponse {
+        rules,
+        email_enabled: engine.email_enabled(),
+    }))
+}
+
+/// Get a notification rule
+#[utoipa::path(
+    get,
+    path = "/notifications/rules/{id}",
+    params(("id" = i32, Path, description = "Rule ID")),
+    responses(
+        (status = 200, description = "Notification rule", body = NotificationRule),
+        (status = 404, description = "Rule not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn get_rule(
+    State(state): State<Option<Arc<RuleEngine>>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<NotificationRule>> {
+    engine(&state)?
+        .get_rule(id)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(|| not_found(id))
+}
+
+/// Create a notification rule
+#[utoipa::path(
+    post,
+    path = "/notifications/rules",
+    request_body = CreateRuleRequest,
+    responses(
+        (status = 201, description = "Rule created", body = NotificationRule),
+        (status = 400, description = "Invalid config or action")
+    ),
+    tag = "Notifications"
+)]
+pub async fn create_rule(
+    State(state): State<Option<Arc<RuleEngine>>>,
+    Json(req): Json<CreateRuleRequest>,
+) -> ApiResult<(StatusCode, Json<NotificationRule>)> {
+    let engine = engine(&state)?;
+    validate_name(&req.name)?;
+    validate_actions(
+        req.severity.as_deref(),
+        req.webhook_url.as_deref(),
+        req.email_to.as_deref(),
+    )?;
+    let config = req.config.unwrap_or_else(|| serde_json::json!({}));
+    validate_config(req.rule_type, &config).map_err(bad_request)?;
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO notification_rules (name, rule_type, enabled, config, severity, webhook_url, email_to) \
+         VALUES ($1, $2, $3, $4, $5, NULLIF($6, ''), NULLIF($7, '')) RETURNING id",
+    )
+    .bind(req.name.trim())
+    .bind(req.rule_type.as_str())
+    .bind(req.enabled.unwrap_or(true))
+    .bind(&config)
+    .bind(req.severity.as_deref().unwrap_or("info"))
+    .bind(req.webhook_url.as_deref().map(str::trim))
+    .bind(req.email_to.as_deref().map(str::trim))
+    .fetch_one(&engine.pool)
+    .await
+    .map_err(internal)?;
+
+    let rule = engine
+        .get_rule(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// Update a notification rule
+///
+/// Changing the config restarts the rule from the current position.
+#[utoipa::path(
+    put,
+    path = "/notifications/rules/{id}",
+    params(("id" = i32, Path, description = "Rule ID")),
+    request_body = UpdateRuleRequest,
+    responses(
+        (status = 200, description = "Rule updated", body = NotificationRule),
+        (status = 400, description = "Invalid config or action"),
+        (status = 404, description = "Rule not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn update_rule(
+    State(state): State<Option<Arc<RuleEngine>>>,
+    Path(id): Path<i32>,
+    Json(req): Json<UpdateRuleRequest>,
+) -> ApiResult<Json<NotificationRule>> {
+    let engine = engine(&state)?;
+    let rule = engine
+        .get_rule(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+
+    if let Some(ref name) = req.name {
+        validate_name(name)?;
+    }
+    validate_actions(
+        req.severity.as_deref(),
+        req.webhook_url.as_deref(),
+        req.email_to.as_deref(),
+    )?;
+    if let Some(ref config) = req.config {
+        let rule_type = RuleType::parse(&rule.rule_type)
+            .ok_or_else(|| internal(format!("unknown rule type '{}'", rule.rule_type)))?;
+        validate_config(rule_type, config).map_err(bad_request)?;
+    }
+
+    sqlx::query(
+        "UPDATE notification_rules SET \
+         name = COALESCE($1, name), \
+         enabled = COALESCE($2, enabled), \
+         config = COALESCE($3, config), \
+         state = CASE WHEN $3 IS NULL THEN state ELSE '{}' END, \
+         severity = COALESCE($4, severity), \
+         webhook_url = CASE WHEN $5::TEXT IS NULL THEN webhook_url ELSE NULLIF($5, '') END, \
+         email_to = CASE WHEN $6::TEXT IS NULL THEN email_to ELSE NULLIF($6, '') END, \
+         updated_at = NOW() \
+         WHERE id = $7",
+    )
+    .bind(req.name.as_deref().map(str::trim))
+    .bind(req.enabled)
+    .bind(&req.config)
+    .bind(&req.severity)
+    .bind(req.webhook_url.as_deref().map(str::trim))
+    .bind(req.email_to.as_deref().map(str::trim))
+    .bind(id)
+    .execute(&engine.pool)
+    .await
+    .map_err(internal)?;
+
+    engine
+        .get_rule(id)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(|| not_found(id))
+}
+
+/// Delete a notification rule
+#[utoipa::path(
+    delete,
+    path = "/notifications/rules/{id}",
+    params(("id" = i32, Path, description = "Rule ID")),
+    responses(
+        (status = 200, description = "Rule deleted", body = RuleActionResponse),
+        (status = 404, description = "Rule not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn delete_rule(
+    State(state): State<Option<Arc<RuleEngine>>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<RuleActionResponse>> {
+    let engine = engine(&state)?;
+    let result = sqlx::query("DELETE FROM notification_rules WHERE id = $1")
+        .bind(id)
+        .execute(&engine.pool)
+        .await
+        .map_err(internal)?;
+    if result.rows_affected() == 0 {
+        return Err(not_found(id));
+    }
+
+    Ok(Json(RuleActionResponse {
+        success: true,
+        message: "Rule deleted".to_string(),
+    }))
+}
+
+/// Send a test notification through a rule's actions
+#[utoipa::path(
+    post,
+    path = "/notifications/rules/{id}/test",
+    params(("id" = i32, Path, description = "Rule ID")),
+    responses(
+        (status = 200, description = "Test delivered or failed", body = TestRuleResponse),
+        (status = 404, description = "Rule not found")
+    ),
+    tag = "Notifications"
+)]
+pub async fn test_rule(
+    State(state): State<Option<Arc<RuleEngine>>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<TestRuleResponse>> {
+    let engine = engine(&state)?;
+    let rule = engine
+        .get_rule(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+
+    let errors = engine.test(&rule).await;
+    Ok(Json(TestRuleResponse {
+        success: errors.is_empty(),
+        message: if errors.is_empty() {
+            format!("Test notification sent for '{}'", rule.name)
+        } else {
+            format!("Some actions of '{}' failed", rule.name)
+        },
+        errors,
+    }))
+}
@@ -0,0 +1,601 @@
+//! Stack-wide status and readiness
+//!
+//! Probes every service's `/health` endpoint together with bitcoind, Postgres,
+//! the default electrum server, and indexer progress, all concurrently. Services
+//! whose container is not installed are reported but never affect readiness.
+//! The dependency graph endpoint exposes the startup order used by the
+//! ordered start/stop operations.
+
+use axum::{extract::State, response::IntoResponse, Json};
+use bollard::container::ListContainersOptions;
+use futures::future::join_all;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use utoipa::ToSchema;
+
+use crate::dependencies::DependencyGraph;
+use crate::handlers::electrum;
+use crate::handlers::installation::ServiceCategory;
+use crate::AppState;
+
+/// Timeout applied to each individual probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Indexer lag (in blocks) above which it is considered degraded
+const MAX_INDEXER_LAG: i64 = 6;
+
+/// An HTTP service with a health endpoint
+struct ServiceProbe {
+    name: &'static str,
+    container: &'static str,
+    url: &'static str,
+    required: bool,
+}
+
+/// Services probed over HTTP, besides the wallet (whose URL is configured)
+const SERVICES: &[ServiceProbe] = &[
+    ServiceProbe {
+        name: "testnet",
+        container: "anchor-core-testnet",
+        url: "http://core-testnet:8002/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "threads",
+        container: "anchor-app-threads-backend",
+        url: "http://app-threads-backend:3101/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "canvas",
+        container: "anchor-app-canvas-backend",
+        url: "http://app-canvas-backend:3201/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "places",
+        container: "anchor-app-places-backend",
+        url: "http://app-places-backend:3301/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "domains",
+        container: "anchor-app-domains-backend",
+        url: "http://app-domains-backend:3401/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "proofs",
+        container: "anchor-app-proofs-backend",
+        url: "http://app-proofs-backend:3501/api/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "tokens",
+        container: "anchor-app-tokens-backend",
+        url: "http://app-tokens-backend:3601/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "oracles",
+        container: "anchor-app-oracles-backend",
+        url: "http://app-oracles-backend:3701/health",
+        required: false,
+    },
+    ServiceProbe {
+        name: "predictions",
+        container: "anchor-app-predictions-backend",
+        url: "http://app-predictions-backend:3801/health",
+        required: false,
+    },
+];
+
+/// Status of a single dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyStatus {
+    pub name: String,
+    /// service, bitcoind, postgres, electrum, or indexer
+    pub kind: String,
+    /// ok, degraded, down, or not_installed
+    pub status: String,
+    /// Whether this dependency must be ok for the stack to be ready
+    pub required: bool,
+    pub latency_ms: Option<u64>,
+    pub detail: Option<String>,
+}
+
+impl DependencyStatus {
+    fn new(name: &str, kind: &str, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            status: "ok".to_string(),
+            required,
+            latency_ms: None,
+            detail: None,
+        }
+    }
+
+    fn with(mut self, status: &str, detail: impl Into<String>) -> Self {
+        self.status = status.to_string();
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Aggregated stack status
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SystemStatus {
+    /// True when every required dependency is ok
+    pub ready: bool,
+    /// ready, degraded, or not_ready
+    pub status: String,
+    /// Why the stack is not fully healthy
+    pub reasons: Vec<String>,
+    pub dependencies: Vec<DependencyStatus>,
+    pub checked_at: String,
+    pub total_latency_ms: u64,
+}
+
+/// Get the status and readiness of the whole stack
+#[utoipa::path(
+    get,
+    path = "/system/status",
+    tag = "System",
+    responses(
+        (status = 200, description = "Per-dependency status and overall readiness", body = SystemStatus)
+    )
+)]
+pub async fn get_system_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let started = Instant::now();
+    let installed = installed_containers(&state).await;
+
+    let wallet_url = format!("{}/health", state.config.wallet_url);
+    let services = join_all(SERVICES.iter().map(|probe| {
+        probe_service(
+            &state,
+            probe.name,
+            &installed,
+            probe.container,
+            probe.url,
+            probe.required,
+        )
+    }));
+    let wallet = probe_service(
+        &state,
+        "wallet",
+        &installed,
+        "anchor-core-wallet",
+        &wallet_url,
+        true,
+    );
+
+    let (services, wallet, (bitcoind, tip), postgres, electrum, indexer_height) = tokio::join!(
+        services,
+        wallet,
+        probe_bitcoind(&state),
+        probe_postgres(&state),
+        probe_electrum(&state),
+        timed(indexer_height(&state)),
+    );
+
+    let indexer = indexer_status(indexer_height, tip);
+
+    let mut dependencies = vec![bitcoind, postgres, electrum, indexer, wallet];
+    dependencies.extend(services);
+
+    let reasons: Vec<String> = dependencies
+        .iter()
+        .filter(|d| d.status == "degraded" || d.status == "down")
+        .map(|d| {
+            format!(
+                "{} is {}: {}",
+                d.name,
+                d.status,
+                d.detail.as_deref().unwrap_or("no detail")
+            )
+        })
+        .collect();
+    let ready = dependencies.iter().all(|d| !d.required || d.status == "ok");
+    let status = if !ready {
+        "not_ready"
+    } else if reasons.is_empty() {
+        "ready"
+    } else {
+        "degraded"
+    };
+
+    Json(SystemStatus {
+        ready,
+        status: status.to_string(),
+        reasons,
+        dependencies,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        total_latency_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Run a probe under the probe timeout, measuring its latency
+async fn timed<T>(probe: impl Future<Output = Result<T, String>>) -> (Result<T, String>, u64) {
+    let started = Instant::now();
+    let result = match timeout(PROBE_TIMEOUT, probe).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+    };
+    (result, started.elapsed().as_millis() as u64)
+}
+
+/// Names of all existing containers, or None if Docker is unavailable
+async fn installed_containers(state: &AppState) -> Option<HashSet<String>> {
+    let options = Some(ListContainersOptions::<String> {
+        all: true,
+        ..Default::default()
+    });
+    let containers = state.docker.list_containers(options).await.ok()?;
+    Some(
+        containers
+            .into_iter()
+            .flat_map(|c| c.names.unwrap_or_default())
+            .map(|name| name.trim_start_matches('/').to_string())
+            .collect(),
+    )
+}
+
+async fn probe_service(
+    state: &AppState,
+    name: &str,
+    installed: &Option<HashSet<String>>,
+    container: &str,
+    url: &str,
+    required: bool,
+) -> DependencyStatus {
+    let status = DependencyStatus::new(name, "service", required);
+    if installed
+        .as_ref()
+        .is_some_and(|set| !set.contains(container))
+    {
+        return DependencyStatus {
+            required: false,
+            ..status.with(
+                "not_installed",
+                format!("container {} not found", container),
+            )
+        };
+    }
+
+    let (result, latency) = timed(async {
+        let response = state
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("health returned {}", response.status()))
+        }
+    })
+    .await;
+
+    let status = DependencyStatus {
+        latency_ms: Some(latency),
+        ..status
+    };
+    match result {
+        Ok(()) => status,
+        Err(e) => status.with("down", e),
+    }
+}
+
+/// Probe bitcoind, returning its status and current block height
+async fn probe_bitcoind(state: &AppState) -> (DependencyStatus, Option<i64>) {
+    let (result, latency) = timed(async {
+        let response = state
+            .http_client
+            .post(&state.config.bitcoin_rpc_url)
+            .basic_auth(
+                &state.config.bitcoin_rpc_user,
+                Some(&state.config.bitcoin_rpc_password),
+            )
+            .json(&serde_json::json!({
+                "jsonrpc": "1.0",
+                "id": "dashboard",
+                "method": "getblockchaininfo",
+                "params": []
+            }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let result: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        if let Some(error) = result.get("error").filter(|e| !e.is_null()) {
+            return Err(format!("RPC error: {}", error));
+        }
+        Ok(result["result"].clone())
+    })
+    .await;
+
+    let status = DependencyStatus {
+        latency_ms: Some(latency),
+        ..DependencyStatus::new("bitcoind", "bitcoind", true)
+    };
+    match result {
+        Ok(info) => {
+            let blocks = info["blocks"].as_i64();
+            let status = if info["initialblockdownload"].as_bool().unwrap_or(false) {
+                status.with(
+                    "degraded",
+                    format!("initial block download at height {}", blocks.unwrap_or(0)),
+                )
+            } else {
+                status
+            };
+            (status, blocks)
+        }
+        Err(e) => (status.with("down", e), None),
+    }
+}
+
+async fn probe_postgres(state: &AppState) -> DependencyStatus {
+    let status = DependencyStatus::new("postgres", "postgres", true);
+    let Some(pool) = state.db_pool.as_ref() else {
+        return status.with(
+            "down",
+            "DATABASE_URL not set or database unreachable at startup",
+        );
+    };
+
+    let (result, latency) = timed(async {
+        sqlx::query("SELECT 1")
+            .execute(pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await;
+
+    let status = DependencyStatus {
+        latency_ms: Some(latency),
+        ..status
+    };
+    match result {
+        Ok(()) => status,
+        Err(e) => status.with("down", e),
+    }
+}
+
+/// Probe the default electrum server with a `server.version` request
+async fn probe_electrum(state: &Arc<AppState>) -> DependencyStatus {
+    let server = electrum::get_configured_server(state)
+        .await
+        .unwrap_or(electrum::ElectrumServer::Electrs);
+    let address = format!("{}:{}", server.host(), server.port());
+
+    let (result, latency) = timed(async {
+        let mut stream = TcpStream::connect(&address)
+            .await
+            .map_err(|e| e.to_string())?;
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "server.version",
+            "params": ["anchor-dashboard", "1.4"]
+        });
+        stream
+            .write_all(format!("{}\n", request).as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut line = String::new();
+        BufReader::new(stream)
+            .read_line(&mut line)
+            .await
+            .map_err(|e| e.to_string())?;
+        let response: serde_json::Value =
+            serde_json::from_str(&line).map_err(|e| format!("invalid response: {}", e))?;
+        response["result"][0]
+            .as_str()
+            .map(|version| version.to_string())
+            .ok_or_else(|| "server.version returned no result".to_string())
+    })
+    .await;
+
+    let status = DependencyStatus {
+        latency_ms: Some(latency),
+        ..DependencyStatus::new(&server.to_string(), "electrum", true)
+    };
+    match result {
+        Ok(version) => DependencyStatus {
+            detail: Some(version),
+            ..status
+        },
+        Err(e) => status.with("down", format!("{}: {}", address, e)),
+    }
+}
+
+/// Last block height processed by the indexer
+async fn indexer_height(state: &AppState) -> Result<i64, String> {
+    let pool = state.db_pool.as_ref().ok_or("database not available")?;
+    sqlx::query_scalar::<_, i32>("SELECT last_block_height FROM indexer_state WHERE id = 1")
+        .fetch_one(pool)
+        .await
+        .map(i64::from)
+        .map_err(|e| e.to_string())
+}
+
+fn indexer_status(height: (Result<i64, String>, u64), tip: Option<i64>) -> DependencyStatus {
+    let (result, latency) = height;
+    let status = DependencyStatus {
+        latency_ms: Some(latency),
+        ..DependencyStatus::new("indexer", "indexer", true)
+    };
+
+    match (result, tip) {
+        (Err(e), _) => status.with("down", e),
+        (Ok(height), None) => status.with(
+            "degraded",
+            format!("at height {}, chain tip unknown", height),
+        ),
+        (Ok(height), Some(tip)) if tip - height > MAX_INDEXER_LAG => status.with(
+            "degraded",
+            format!(
+                "{} blocks behind (at {}, tip {})",
+                tip - height,
+                height,
+                tip
+            ),
+        ),
+        (Ok(height), Some(tip)) => DependencyStatus {
+            detail: Some(format!("at height {} (tip {})", height, tip)),
+            ..status
+        },
+    }
+}
+
+/// A container in the dependency graph
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphContainer {
+    pub name: String,
+    /// Docker state (running, exited, ...), or not_installed
+    pub state: String,
+    /// healthy, unhealthy, or starting; None without a healthcheck
+    pub health: Option<String>,
+}
+
+/// A service in the dependency graph
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphNode {
+    pub id: String,
+    pub name: String,
+    pub category: ServiceCategory,
+    /// Startup layer; services start after every lower layer is healthy
+    pub layer: usize,
+    pub depends_on: Vec<String>,
+    /// running, partial, stopped, or not_installed
+    pub status: String,
+    pub containers: Vec<GraphContainer>,
+}
+
+/// Edge from a service to one of its dependencies
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Service dependency graph
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyGraphResponse {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Service ids grouped by layer, in startup order
+    pub startup_order: Vec<Vec<String>>,
+}
+
+/// Get the service dependency graph with live container states
+#[utoipa::path(
+    get,
+    path = "/system/dependency-graph",
+    tag = "System",
+    responses(
+        (status = 200, description = "Services, their dependencies, and startup order", body = DependencyGraphResponse)
+    )
+)]
+pub async fn get_dependency_graph(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let options = Some(ListContainersOptions::<String> {
+        all: true,
+        ..Default::default()
+    });
+    let containers: HashMap<String, (String, Option<String>)> = state
+        .docker
+        .list_containers(options)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|c| {
+            let name = c
+                .names
+                .as_ref()?
+                .first()?
+                .trim_start_matches('/')
+                .to_string();
+            let status = c.status.unwrap_or_default();
+            let health = ["healthy", "unhealthy", "health: starting"]
+                .into_iter()
+                .find(|h| status.contains(&format!("({})", h)))
+                .map(|h| h.trim_start_matches("health: ").to_string());
+            Some((name, (c.state.unwrap_or_default(), health)))
+        })
+        .collect();
+
+    let graph = DependencyGraph::default();
+    let nodes = graph
+        .services()
+        .iter()
+        .map(|service| {
+            let containers: Vec<GraphContainer> = service
+                .containers
+                .iter()
+                .map(|name| match containers.get(name) {
+                    Some((state, health)) => GraphContainer {
+                        name: name.clone(),
+                        state: state.clone(),
+                        health: health.clone(),
+                    },
+                    None => GraphContainer {
+                        name: name.clone(),
+                        state: "not_installed".to_string(),
+                        health: None,
+                    },
+                })
+                .collect();
+            let running = containers.iter().filter(|c| c.state == "running").count();
+            let installed = containers
+                .iter()
+                .filter(|c| c.state != "not_installed")
+                .count();
+            let status = if installed == 0 {
+                "not_installed"
+            } else if running == containers.len() {
+                "running"
+            } else if running > 0 {
+                "partial"
+            } else {
+                "stopped"
+            };
+
+            GraphNode {
+                id: service.id.clone(),
+                name: service.name.clone(),
+                category: service.category,
+                layer: graph.layer(&service.id),
+                depends_on: service.depends_on.clone(),
+                status: status.to_string(),
+                containers,
+            }
+        })
+        .collect();
+
+    let edges = graph
+        .services()
+        .iter()
+        .flat_map(|service| {
+            service.depends_on.iter().map(|dep| GraphEdge {
+                from: service.id.clone(),
+                to: dep.clone(),
+            })
+        })
+        .collect();
+
+    Json(DependencyGraphResponse {
+        nodes,
+        edges,
+        startup_order: graph.startup_order(),
+    })
+}
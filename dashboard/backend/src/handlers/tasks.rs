@@ -0,0 +1,368 @@
+//! Scheduled task handlers
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::scheduler::normalize_cron;
+use crate::tasks::{validate_config, ScheduledTask, TaskRun, TaskRunner, TaskScheduler, TaskType};
+
+/// List tasks response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TasksListResponse {
+    pub tasks: Vec<ScheduledTask>,
+}
+
+/// Create task request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTaskRequest {
+    pub name: String,
+    pub task_type: TaskType,
+    /// 5-field cron expression, or 6 fields with seconds first
+    pub cron_expression: String,
+    pub enabled: Option<bool>,
+    /// Task type specific options; omitted fields use defaults
+    pub config: Option<serde_json::Value>,
+}
+
+/// Update task request; omitted fields are left unchanged
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateTaskRequest {
+    pub name: Option<String>,
+    pub cron_expression: Option<String>,
+    pub enabled: Option<bool>,
+    pub config: Option<serde_json::Value>,
+}
+
+/// Manual trigger response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TriggerTaskResponse {
+    pub success: bool,
+    pub message: String,
+    pub run_id: Option<i32>,
+}
+
+/// Run history response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskRunsResponse {
+    pub runs: Vec<TaskRun>,
+    pub total: i64,
+}
+
+/// Query parameters for run history
+#[derive(Debug, Deserialize)]
+pub struct TaskRunsQuery {
+    pub limit: Option<i64>,
+}
+
+/// Generic action response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaskActionResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+fn runner(state: &TaskScheduler) -> ApiResult<&Arc<TaskRunner>> {
+    state.runner.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available".to_string(),
+        )
+    })
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn bad_request(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, e.to_string())
+}
+
+fn not_found(id: i32) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("Task {} not found", id))
+}
+
+fn validate_name(name: &str) -> ApiResult<()> {
+    if name.trim().is_empty() || name.len() > 100 {
+        return Err(bad_request("Name must be 1-100 characters"));
+    }
+    Ok(())
+}
+
+/// List all scheduled tasks
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    responses(
+        (status = 200, description = "Scheduled tasks", body = TasksListResponse)
+    ),
+    tag = "Tasks"
+)]
+pub async fn list_tasks(
+    State(state): State<Arc<TaskScheduler>>,
+) -> ApiResult<Json<TasksListResponse>> {
+    let tasks = runner(&state)?.list_tasks().await.map_err(internal)?;
+    Ok(Json(TasksListResponse { tasks }))
+}
+
+/// Get a scheduled task
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}",
+    params(("id" = i32, Path, description = "Task ID")),
+    responses(
+        (status = 200, description = "Scheduled task", body = ScheduledTask),
+        (status = 404, description = "Task not found")
+    ),
+    tag = "Tasks"
+)]
+pub async fn get_task(
+    State(state): State<Arc<TaskScheduler>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<ScheduledTask>> {
+    runner(&state)?
+        .get_task(id)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(|| not_found(id))
+}
+
+/// Create a scheduled task
+#[utoipa::path(
+    post,
+    path = "/tasks",
+    request_body = CreateTaskRequest,
+    responses(
+        (status = 201, description = "Task created", body = ScheduledTask),
+        (status = 400, description = "Invalid cron expression or config")
+    ),
+    tag = "Tasks"
+)]
+pub async fn create_task(
+    State(state): State<Arc<TaskScheduler>>,
+    Json(req): Json<CreateTaskRequest>,
+) -> ApiResult<(StatusCode, Json<ScheduledTask>)> {
+    let runner = runner(&state)?;
+    validate_name(&req.name)?;
+    normalize_cron(&req.cron_expression).map_err(bad_request)?;
+    let config = req.config.unwrap_or_else(|| serde_json::json!({}));
+    validate_config(req.task_type, &config).map_err(bad_request)?;
+
+    let row = sqlx::query(
+        "INSERT INTO scheduled_tasks (name, task_type, cron_expression, enabled, config) \
+         VALUES ($1, $2, $3, $4, $5) RETURNING id",
+    )
+    .bind(req.name.trim())
+    .bind(req.task_type.as_str())
+    .bind(req.cron_expression.trim())
+    .bind(req.enabled.unwrap_or(false))
+    .bind(&config)
+    .fetch_one(&runner.pool)
+    .await
+    .map_err(internal)?;
+    let id: i32 = row.get("id");
+
+    let task = runner
+        .get_task(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+    state.schedule(&task).await.map_err(internal)?;
+
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+/// Update a scheduled task
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}",
+    params(("id" = i32, Path, description = "Task ID")),
+    request_body = UpdateTaskRequest,
+    responses(
+        (status = 200, description = "Task updated", body = ScheduledTask),
+        (status = 400, description = "Invalid cron expression or config"),
+        (status = 404, description = "Task not found")
+    ),
+    tag = "Tasks"
+)]
+pub async fn update_task(
+    State(state): State<Arc<TaskScheduler>>,
+    Path(id): Path<i32>,
+    Json(req): Json<UpdateTaskRequest>,
+) -> ApiResult<Json<ScheduledTask>> {
+    let runner = runner(&state)?;
+    let task = runner
+        .get_task(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+
+    if let Some(ref name) = req.name {
+        validate_name(name)?;
+    }
+    if let Some(ref cron_expression) = req.cron_expression {
+        normalize_cron(cron_expression).map_err(bad_request)?;
+    }
+    if let Some(ref config) = req.config {
+        let task_type = TaskType::parse(&task.task_type)
+            .ok_or_else(|| internal(format!("unknown task type '{}'", task.task_type)))?;
+        validate_config(task_type, config).map_err(bad_request)?;
+    }
+
+    sqlx::query(
+        "UPDATE scheduled_tasks SET \
+         name = COALESCE($1, name), \
+         cron_expression = COALESCE($2, cron_expression), \
+         enabled = COALESCE($3, enabled), \
+         config = COALESCE($4, config), \
+         updated_at = NOW() \
+         WHERE id = $5",
+    )
+    .bind(req.name.as_deref().map(str::trim))
+    .bind(req.cron_expression.as_deref().map(str::trim))
+    .bind(req.enabled)
+    .bind(&req.config)
+    .bind(id)
+    .execute(&runner.pool)
+    .await
+    .map_err(internal)?;
+
+    let task = runner
+        .get_task(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+    state.schedule(&task).await.map_err(internal)?;
+
+    Ok(Json(task))
+}
+
+/// Delete a scheduled task and its run history
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}",
+    params(("id" = i32, Path, description = "Task ID")),
+    responses(
+        (status = 200, description = "Task deleted", body = TaskActionResponse),
+        (status = 404, description = "Task not found")
+    ),
+    tag = "Tasks"
+)]
+pub async fn delete_task(
+    State(state): State<Arc<TaskScheduler>>,
+    Path(id): Path<i32>,
+) -> ApiResult<Json<TaskActionResponse>> {
+    let runner = runner(&state)?;
+    let result = sqlx::query("DELETE FROM scheduled_tasks WHERE id = $1")
+        .bind(id)
+        .execute(&runner.pool)
+        .await
+        .map_err(internal)?;
+    if result.rows_affected() == 0 {
+        return Err(not_found(id));
+    }
+    state.unschedule(id).await;
+
+    Ok(Json(TaskActionResponse {
+        success: true,
+        message: "Task deleted".to_string(),
+    }))
+}
+
+/// Run a task now, whether or not it is enabled
+#[utoipa::path(
+    post,
+    path = "/tasks/{id}/run",
+    params(("id" = i32, Path, description = "Task ID")),
+    responses(
+        (status = 202, description = "Run started", body = TriggerTaskResponse),
+        (status = 404, description = "Task not found"),
+        (status = 409, description = "Task is already running", body = TriggerTaskResponse)
+    ),
+    tag = "Tasks"
+)]
+pub async fn run_task(
+    State(state): State<Arc<TaskScheduler>>,
+    Path(id): Path<i32>,
+) -> ApiResult<(StatusCode, Json<TriggerTaskResponse>)> {
+    let runner = runner(&state)?;
+    let task = runner
+        .get_task(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(id))?;
+
+    match runner.trigger(&task, "manual").await.map_err(internal)? {
+        Some(run_id) => Ok((
+            StatusCode::ACCEPTED,
+            Json(TriggerTaskResponse {
+                success: true,
+                message: format!("Task '{}' started", task.name),
+                run_id: Some(run_id),
+            }),
+        )),
+        None => Ok((
+            StatusCode::CONFLICT,
+            Json(TriggerTaskResponse {
+                success: false,
+                message: format!("Task '{}' is already running", task.name),
+                run_id: None,
+            }),
+        )),
+    }
+}
+
+/// Get a task's run history, newest first
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/runs",
+    params(
+        ("id" = i32, Path, description = "Task ID"),
+        ("limit" = Option<i64>, Query, description = "Runs to return (default: 20, max: 100)")
+    ),
+    responses(
+        (status = 200, description = "Run history", body = TaskRunsResponse)
+    ),
+    tag = "Tasks"
+)]
+pub async fn list_task_runs(
+    State(state): State<Arc<TaskScheduler>>,
+    Path(id): Path<i32>,
+    Query(query): Query<TaskRunsQuery>,
+) -> ApiResult<Json<TaskRunsResponse>> {
+    let runner = runner(&state)?;
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    let rows = sqlx::query(
+        "SELECT id, task_id, trigger, status, output, error, started_at, finished_at \
+         FROM scheduled_task_runs WHERE task_id = $1 \
+         ORDER BY started_at DESC, id DESC LIMIT $2",
+    )
+    .bind(id)
+    .bind(limit)
+    .fetch_all(&runner.pool)
+    .await
+    .map_err(internal)?;
+
+    let total: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM scheduled_task_runs WHERE task_id = $1")
+            .bind(id)
+            .fetch_one(&runner.pool)
+            .await
+            .map_err(internal)?;
+
+    Ok(Json(TaskRunsResponse {
+        runs: rows.iter().map(TaskRun::from_row).collect(),
+        total,
+    }))
+}
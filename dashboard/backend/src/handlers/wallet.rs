@@ -20,6 +20,12 @@ pub struct WalletBalance {
     pub unconfirmed: f64,
     #[serde(default)]
     pub immature: f64,
+    /// Confirmed value in UTXOs locked for assets
+    #[serde(default)]
+    pub locked: f64,
+    /// Confirmed value that is not locked
+    #[serde(default)]
+    pub spendable: f64,
     pub total: f64,
 }
 
@@ -666,6 +672,51 @@ pub async fn get_assets_tokens(
     Ok(Json(tokens))
 }
 
+/// Portfolio query parameters
+#[derive(Debug, Serialize, Deserialize, utoipa::IntoParams)]
+pub struct PortfolioQuery {
+    /// Comma-separated addresses (defaults to every wallet address)
+    pub addresses: Option<String>,
+    /// Bypass the wallet service cache
+    pub refresh: Option<bool>,
+}
+
+/// Get the cross-app portfolio of the wallet
+#[utoipa::path(
+    get,
+    path = "/wallet/portfolio",
+    tag = "Assets",
+    params(PortfolioQuery),
+    responses(
+        (status = 200, description = "Assets held across all apps"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_portfolio(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PortfolioQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let url = format!("{}/wallet/portfolio", state.config.wallet_url);
+
+    let response = state
+        .http_client
+        .get(&url)
+        .query(&query)
+        .send()
+        .await
+        .map_err(|e| {
+            error!("Failed to connect to wallet service: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+
+    let portfolio: serde_json::Value = response.json().await.map_err(|e| {
+        error!("Failed to parse portfolio: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+
+    Ok(Json(portfolio))
+}
+
 // ============================================================================
 // Wallet Backup Proxy Handlers
 // ============================================================================
@@ -996,3 +1047,84 @@ pub async fn get_locked_assets(
 
     Ok(Json(assets))
 }
+
+// ============================================================================
+// Wallet Operation Policy
+// ============================================================================
+
+/// Get the wallet operations the dashboard proxies
+#[utoipa::path(
+    get,
+    path = "/wallet-policy",
+    tag = "Wallet",
+    responses(
+        (status = 200, description = "Allowed and denied wallet operations", body = crate::wallet_policy::WalletPolicy)
+    )
+)]
+pub async fn get_wallet_policy(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.wallet_policy.clone())
+}
+
+/// Query parameters for the wallet audit log
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    /// Entries to return, newest first (default: 100, max: 1000)
+    pub limit: Option<i64>,
+    /// Only refused operations
+    #[serde(default)]
+    pub denied: bool,
+}
+
+/// One proxied wallet operation
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct WalletAuditEntry {
+    pub id: i64,
+    pub operation: String,
+    pub method: String,
+    pub allowed: bool,
+    pub status: i32,
+    pub client: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Get the audit log of wallet operations
+#[utoipa::path(
+    get,
+    path = "/wallet-policy/audit",
+    tag = "Wallet",
+    params(
+        ("limit" = Option<i64>, Query, description = "Entries to return (default: 100, max: 1000)"),
+        ("denied" = Option<bool>, Query, description = "Only refused operations")
+    ),
+    responses(
+        (status = 200, description = "Wallet operations, newest first", body = Vec<WalletAuditEntry>),
+        (status = 503, description = "Database not available")
+    )
+)]
+pub async fn get_wallet_audit(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = state.db_pool.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Database not available".to_string(),
+        )
+    })?;
+
+    let entries: Vec<WalletAuditEntry> = sqlx::query_as(
+        "SELECT id, operation, method, allowed, status, client, created_at \
+         FROM wallet_audit_log WHERE NOT ($1 AND allowed) \
+         ORDER BY id DESC LIMIT $2",
+    )
+    .bind(query.denied)
+    .bind(query.limit.unwrap_or(100).clamp(1, 1000))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        error!("Failed to read wallet audit log: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+
+    Ok(Json(entries))
+}
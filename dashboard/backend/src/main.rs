@@ -4,21 +4,31 @@
 
 mod backup;
 mod backup_config;
+mod channels;
 mod config;
+mod dependencies;
 mod handlers;
 mod monitors;
+mod node_mode;
+mod preflight;
+mod preset_migration;
+mod rules;
 mod scheduler;
 mod storage;
+mod tasks;
+mod wallet_policy;
 
+use anchor_api_security::SecurityConfig;
 use anyhow::Result;
 use axum::{
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
 use bollard::Docker;
 use sqlx::PgPool;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
@@ -26,8 +36,13 @@ use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::backup_config::BackupConfig;
+use crate::channels::Dispatcher;
 use crate::config::Config;
 use crate::handlers::backup::BackupState;
+use crate::node_mode::NodeModeSwitcher;
+use crate::rules::RuleEngine;
+use crate::tasks::{TaskRunner, TaskScheduler};
+use crate::wallet_policy::WalletPolicy;
 
 /// Application state shared across handlers
 pub struct AppState {
@@ -35,6 +50,8 @@ pub struct AppState {
     pub docker: Docker,
     pub http_client: reqwest::Client,
     pub db_pool: Option<PgPool>,
+    /// Wallet operations the dashboard proxies
+    pub wallet_policy: WalletPolicy,
 }
 
 #[derive(OpenApi)]
@@ -47,6 +64,8 @@ pub struct AppState {
     ),
     paths(
         handlers::health,
+        handlers::system::get_system_status,
+        handlers::system::get_dependency_graph,
         handlers::docker::list_containers,
         handlers::docker::start_container,
         handlers::docker::stop_container,
@@ -55,6 +74,7 @@ pub struct AppState {
         handlers::docker::exec_container,
         handlers::docker::get_docker_stats,
         handlers::docker::shutdown_all,
+        handlers::docker::start_all,
         handlers::docker::restart_all,
         handlers::docker::rebuild_container,
         handlers::bitcoin::get_blockchain_info,
@@ -76,12 +96,24 @@ pub struct AppState {
         handlers::wallet::get_assets,
         handlers::wallet::get_assets_domains,
         handlers::wallet::get_assets_tokens,
+        handlers::wallet::get_portfolio,
+        handlers::wallet::get_wallet_policy,
+        handlers::wallet::get_wallet_audit,
+        handlers::wallet::get_locked_assets,
+        handlers::wallet::get_backup_info,
+        handlers::wallet::get_backup_mnemonic,
+        handlers::wallet::get_backup_descriptors,
+        handlers::wallet::export_backup,
+        handlers::wallet::verify_backup,
+        handlers::wallet::get_migration_status,
         handlers::node::get_node_config,
         handlers::node::switch_node,
         handlers::node::get_node_versions,
         handlers::node::get_node_settings,
         handlers::node::update_node_settings,
         handlers::node::reset_node_settings,
+        handlers::node::get_node_mode,
+        handlers::node::switch_node_mode,
         handlers::tailscale::get_tailscale_status,
         handlers::tailscale::connect_tailscale,
         handlers::tailscale::disconnect_tailscale,
@@ -97,6 +129,8 @@ pub struct AppState {
         handlers::indexer::get_anchor_stats,
         handlers::indexer::get_orphan_anchors,
         handlers::indexer::get_performance,
+        handlers::indexer::ws_live_feed,
+        handlers::feed::get_feed,
         handlers::tor::get_tor_status,
         handlers::tor::get_onion_addresses_handler,
         handlers::tor::new_tor_circuit,
@@ -119,6 +153,34 @@ pub struct AppState {
         handlers::installation::uninstall_service,
         handlers::installation::get_profiles,
         handlers::installation::reset_installation,
+        handlers::installation::run_preflight,
+        handlers::installation::get_preflight,
+        handlers::installation::get_preflight_history,
+        handlers::installation::migrate_installation,
+        handlers::installation::stream_installation,
+        handlers::auth::get_auth_status,
+        handlers::auth::setup_password,
+        handlers::auth::login,
+        handlers::auth::verify_token,
+        handlers::auth::change_password,
+        handlers::auth::disable_auth,
+        handlers::settings::get_all_settings,
+        handlers::settings::get_setting,
+        handlers::settings::update_setting,
+        handlers::settings::export_settings,
+        handlers::settings::import_settings,
+        handlers::backup::get_status,
+        handlers::backup::start_backup,
+        handlers::backup::get_history,
+        handlers::backup::get_verification_history,
+        handlers::backup::get_targets,
+        handlers::backup::restore,
+        handlers::backup::restore_database,
+        handlers::backup::list_snapshot_databases,
+        handlers::backup::list_snapshots,
+        handlers::backup::get_settings,
+        handlers::backup::save_settings,
+        handlers::backup::list_local_files,
         handlers::profile::get_profile,
         handlers::profile::update_profile,
         handlers::notifications::list_notifications,
@@ -128,9 +190,34 @@ pub struct AppState {
         handlers::notifications::mark_all_as_read,
         handlers::notifications::delete_notification,
         handlers::notifications::clear_read_notifications,
+        handlers::rules::list_rules,
+        handlers::rules::get_rule,
+        handlers::rules::create_rule,
+        handlers::rules::update_rule,
+        handlers::rules::delete_rule,
+        handlers::rules::test_rule,
+        handlers::channels::list_channels,
+        handlers::channels::get_channel,
+        handlers::channels::create_channel,
+        handlers::channels::update_channel,
+        handlers::channels::delete_channel,
+        handlers::channels::test_channel,
+        handlers::tasks::list_tasks,
+        handlers::tasks::get_task,
+        handlers::tasks::create_task,
+        handlers::tasks::update_task,
+        handlers::tasks::delete_task,
+        handlers::tasks::run_task,
+        handlers::tasks::list_task_runs,
     ),
     components(schemas(
         handlers::HealthResponse,
+        handlers::system::SystemStatus,
+        handlers::system::DependencyStatus,
+        handlers::system::GraphContainer,
+        handlers::system::GraphNode,
+        handlers::system::GraphEdge,
+        handlers::system::DependencyGraphResponse,
         handlers::docker::ContainerInfo,
         handlers::docker::ContainersResponse,
         handlers::docker::ContainerActionResponse,
@@ -164,6 +251,8 @@ pub struct AppState {
         handlers::wallet::UnlockRequest,
         handlers::wallet::UtxoRef,
         handlers::wallet::SetAutoLockRequest,
+        wallet_policy::WalletPolicy,
+        handlers::wallet::WalletAuditEntry,
         handlers::node::NodeConfig,
         handlers::node::VersionInfo,
         handlers::node::SwitchVersionRequest,
@@ -172,6 +261,11 @@ pub struct AppState {
         handlers::node::NodeSettingsResponse,
         handlers::node::UpdateNodeSettingsRequest,
         handlers::node::UpdateNodeSettingsResponse,
+        handlers::node::NodeModeStatus,
+        node_mode::NodeStorageMode,
+        node_mode::ModeSwitch,
+        node_mode::SwitchPhase,
+        node_mode::SyncProgress,
         handlers::tailscale::TailscaleStatus,
         handlers::tailscale::TailscaleAuthRequest,
         handlers::tailscale::TailscaleActionResponse,
@@ -197,6 +291,8 @@ pub struct AppState {
         handlers::indexer::LiveMessageEvent,
         handlers::indexer::LiveMessage,
         handlers::indexer::LiveStats,
+        handlers::feed::FeedItem,
+        handlers::feed::FeedResponse,
         handlers::tor::TorStatus,
         handlers::tor::TorActionResponse,
         handlers::tor::OnionAddresses,
@@ -224,6 +320,18 @@ pub struct AppState {
         handlers::installation::InstallationActionResponse,
         handlers::installation::ServiceActionRequest,
         handlers::installation::ResetInstallationRequest,
+        handlers::installation::PreflightHistoryResponse,
+        handlers::installation::MigrateInstallationRequest,
+        preset_migration::MigrationPlan,
+        preset_migration::MigrationStage,
+        preset_migration::StageKind,
+        preflight::PreflightReport,
+        preflight::HardwareProfile,
+        preflight::PreflightCheck,
+        preflight::CheckStatus,
+        preflight::Recommendation,
+        preflight::NodeMode,
+        preflight::ElectrumServer,
         handlers::profile::UserProfile,
         handlers::profile::UpdateProfileRequest,
         handlers::profile::ProfileResponse,
@@ -232,26 +340,62 @@ pub struct AppState {
         handlers::notifications::UnreadCountResponse,
         handlers::notifications::CreateNotificationRequest,
         handlers::notifications::NotificationActionResponse,
+        handlers::rules::RulesListResponse,
+        handlers::rules::CreateRuleRequest,
+        handlers::rules::UpdateRuleRequest,
+        handlers::rules::TestRuleResponse,
+        handlers::rules::RuleActionResponse,
+        rules::NotificationRule,
+        rules::RuleType,
+        handlers::channels::ChannelsListResponse,
+        handlers::channels::CreateChannelRequest,
+        handlers::channels::UpdateChannelRequest,
+        handlers::channels::TestChannelResponse,
+        handlers::channels::ChannelActionResponse,
+        channels::NotificationChannel,
+        channels::ChannelType,
+        handlers::tasks::TasksListResponse,
+        handlers::tasks::CreateTaskRequest,
+        handlers::tasks::UpdateTaskRequest,
+        handlers::tasks::TriggerTaskResponse,
+        handlers::tasks::TaskRunsResponse,
+        handlers::tasks::TaskActionResponse,
+        tasks::ScheduledTask,
+        tasks::TaskRun,
+        tasks::TaskType,
     )),
     tags(
         (name = "System", description = "System health endpoints"),
         (name = "Docker", description = "Docker container management"),
         (name = "Bitcoin", description = "Bitcoin node information"),
         (name = "Wallet", description = "Wallet operations"),
+        (name = "Locks", description = "Wallet UTXO locks"),
+        (name = "Assets", description = "Domains and tokens held by the wallet"),
+        (name = "Backup", description = "Wallet and stack backups, snapshots, and restore"),
         (name = "Node", description = "Node type management"),
         (name = "Tailscale", description = "Tailscale VPN management"),
         (name = "Cloudflare", description = "Cloudflare Tunnel management"),
         (name = "Tor", description = "Tor network management"),
+        (name = "Electrum", description = "Electrum server selection"),
+        (name = "Explorer", description = "Default block explorer"),
         (name = "Indexer", description = "Anchor indexer statistics"),
+        (name = "Feed", description = "Cross-app account activity"),
         (name = "Installation", description = "Installation and setup wizard"),
+        (name = "Auth", description = "Dashboard password and sessions"),
+        (name = "Settings", description = "Persisted dashboard settings"),
         (name = "Profile", description = "User profile management"),
         (name = "Notifications", description = "System notifications management"),
+        (name = "Tasks", description = "Scheduled maintenance tasks"),
     )
 )]
 struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
@@ -304,60 +448,79 @@ async fn main() -> Result<()> {
         );
     }
 
+    let wallet_policy = WalletPolicy::from_env();
+    info!(
+        "Wallet operations on {}: allowed {}, denied {}",
+        wallet_policy.network,
+        wallet_policy
+            .allowed
+            .as_ref()
+            .map_or_else(|| "all".to_string(), |ops| ops.join(", ")),
+        if wallet_policy.denied.is_empty() {
+            "none".to_string()
+        } else {
+            wallet_policy.denied.join(", ")
+        }
+    );
+
     // Create application state
     let state = Arc::new(AppState {
         config: config.clone(),
         docker,
         http_client,
         db_pool,
+        wallet_policy,
     });
 
     // Create backup state
     let backup_config = BackupConfig::from_env();
-    let backup_state = Arc::new(BackupState::new(backup_config).await);
+    let backup_state = Arc::new(BackupState::new(backup_config, state.db_pool.clone()).await);
 
     // Start backup scheduler in background
     if let Err(e) = backup_state.start_scheduler().await {
         info!("Backup scheduler not started: {}", e);
     }
 
+    // Start the task scheduler (needs the database)
+    let task_runner = state.db_pool.clone().map(|pool| {
+        Arc::new(TaskRunner::new(
+            pool,
+            state.docker.clone(),
+            state.http_client.clone(),
+            config.clone(),
+            backup_state.clone(),
+        ))
+    });
+    let task_scheduler = Arc::new(TaskScheduler::new(task_runner).await?);
+    if let Err(e) = task_scheduler.start().await {
+        info!("Task scheduler not started: {}", e);
+    }
+
+    // Start the notification rule engine (needs the database)
+    let rule_engine = state.db_pool.clone().map(|pool| {
+        Arc::new(RuleEngine::new(
+            pool,
+            state.http_client.clone(),
+            config.clone(),
+        ))
+    });
+    if let Some(ref engine) = rule_engine {
+        rules::start(engine.clone());
+    }
+
+    // Start delivering notifications to the configured channels
+    let dispatcher = state
+        .db_pool
+        .clone()
+        .map(|pool| Arc::new(Dispatcher::new(pool, state.http_client.clone())));
+    if let Some(ref dispatcher) = dispatcher {
+        channels::start(dispatcher.clone());
+    }
+
     // Build router
-    let app = Router::new()
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        // System
-        .route("/health", get(handlers::health))
-        // Docker
-        .route("/docker/containers", get(handlers::docker::list_containers))
-        .route(
-            "/docker/containers/:id/start",
-            post(handlers::docker::start_container),
-        )
-        .route(
-            "/docker/containers/:id/stop",
-            post(handlers::docker::stop_container),
-        )
-        .route(
-            "/docker/containers/:id/restart",
-            post(handlers::docker::restart_container),
-        )
-        .route(
-            "/docker/containers/:id/logs",
-            get(handlers::docker::get_container_logs),
-        )
-        .route(
-            "/docker/containers/:id/exec",
-            post(handlers::docker::exec_container),
-        )
-        .route("/docker/stats", get(handlers::docker::get_docker_stats))
-        .route("/docker/shutdown", post(handlers::docker::shutdown_all))
-        .route("/docker/restart-all", post(handlers::docker::restart_all))
-        .route("/docker/rebuild", post(handlers::docker::rebuild_container))
-        // Bitcoin
-        .route("/bitcoin/info", get(handlers::bitcoin::get_blockchain_info))
-        .route("/bitcoin/mempool", get(handlers::bitcoin::get_mempool_info))
-        .route("/bitcoin/network", get(handlers::bitcoin::get_network_info))
-        .route("/bitcoin/status", get(handlers::bitcoin::get_node_status))
-        // Wallet
+    let node_mode = Arc::new(NodeModeSwitcher::new(state.clone()));
+
+    let wallet_routes = Router::new()
         .route("/wallet/balance", get(handlers::wallet::get_balance))
         .route("/wallet/address", get(handlers::wallet::get_new_address))
         .route("/wallet/utxos", get(handlers::wallet::list_utxos))
@@ -392,6 +555,7 @@ async fn main() -> Result<()> {
             "/wallet/assets/tokens",
             get(handlers::wallet::get_assets_tokens),
         )
+        .route("/wallet/portfolio", get(handlers::wallet::get_portfolio))
         .route(
             "/wallet/transactions",
             get(handlers::wallet::get_transactions),
@@ -426,6 +590,59 @@ async fn main() -> Result<()> {
             "/wallet/locked-assets",
             get(handlers::wallet::get_locked_assets),
         )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            wallet_policy::enforce,
+        ));
+
+    let app = Router::new()
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        // System
+        .route("/health", get(handlers::health))
+        .route("/system/status", get(handlers::system::get_system_status))
+        .route(
+            "/system/dependency-graph",
+            get(handlers::system::get_dependency_graph),
+        )
+        // Docker
+        .route("/docker/containers", get(handlers::docker::list_containers))
+        .route(
+            "/docker/containers/:id/start",
+            post(handlers::docker::start_container),
+        )
+        .route(
+            "/docker/containers/:id/stop",
+            post(handlers::docker::stop_container),
+        )
+        .route(
+            "/docker/containers/:id/restart",
+            post(handlers::docker::restart_container),
+        )
+        .route(
+            "/docker/containers/:id/logs",
+            get(handlers::docker::get_container_logs),
+        )
+        .route(
+            "/docker/containers/:id/exec",
+            post(handlers::docker::exec_container),
+        )
+        .route("/docker/stats", get(handlers::docker::get_docker_stats))
+        .route("/docker/shutdown", post(handlers::docker::shutdown_all))
+        .route("/docker/start-all", post(handlers::docker::start_all))
+        .route("/docker/restart-all", post(handlers::docker::restart_all))
+        .route("/docker/rebuild", post(handlers::docker::rebuild_container))
+        // Bitcoin
+        .route("/bitcoin/info", get(handlers::bitcoin::get_blockchain_info))
+        .route("/bitcoin/mempool", get(handlers::bitcoin::get_mempool_info))
+        .route("/bitcoin/network", get(handlers::bitcoin::get_network_info))
+        .route("/bitcoin/status", get(handlers::bitcoin::get_node_status))
+        // Wallet (proxied, subject to the operation policy)
+        .merge(wallet_routes)
+        .route("/wallet-policy", get(handlers::wallet::get_wallet_policy))
+        .route(
+            "/wallet-policy/audit",
+            get(handlers::wallet::get_wallet_audit),
+        )
         // Node management
         .route("/node/config", get(handlers::node::get_node_config))
         .route("/node/switch", post(handlers::node::switch_node))
@@ -534,6 +751,8 @@ async fn main() -> Result<()> {
             get(handlers::indexer::get_orphan_anchors),
         )
         .route("/indexer/ws/live", get(handlers::indexer::ws_live_feed))
+        // Activity feed
+        .route("/feed", get(handlers::feed::get_feed))
         // Installation
         .route(
             "/installation/status",
@@ -571,6 +790,18 @@ async fn main() -> Result<()> {
             "/installation/reset",
             post(handlers::installation::reset_installation),
         )
+        .route(
+            "/installation/preflight",
+            get(handlers::installation::get_preflight).post(handlers::installation::run_preflight),
+        )
+        .route(
+            "/installation/preflight/history",
+            get(handlers::installation::get_preflight_history),
+        )
+        .route(
+            "/installation/migrate",
+            post(handlers::installation::migrate_installation),
+        )
         .route(
             "/installation/stream",
             get(handlers::installation::stream_installation),
@@ -631,8 +862,20 @@ async fn main() -> Result<()> {
         .route("/backup/status", get(handlers::backup::get_status))
         .route("/backup/start", post(handlers::backup::start_backup))
         .route("/backup/history", get(handlers::backup::get_history))
+        .route(
+            "/backup/verification-history",
+            get(handlers::backup::get_verification_history),
+        )
         .route("/backup/targets", get(handlers::backup::get_targets))
         .route("/backup/restore", post(handlers::backup::restore))
+        .route(
+            "/backup/restore/database",
+            post(handlers::backup::restore_database),
+        )
+        .route(
+            "/backup/snapshots/:target/:snapshot_id/databases",
+            get(handlers::backup::list_snapshot_databases),
+        )
         .route(
             "/backup/snapshots/:target",
             get(handlers::backup::list_snapshots),
@@ -644,13 +887,64 @@ async fn main() -> Result<()> {
             get(handlers::backup::list_local_files),
         )
         .with_state(backup_state)
+        // Scheduled task routes (separate state)
+        .route("/tasks", get(handlers::tasks::list_tasks))
+        .route("/tasks", post(handlers::tasks::create_task))
+        .route("/tasks/:id", get(handlers::tasks::get_task))
+        .route("/tasks/:id", put(handlers::tasks::update_task))
+        .route("/tasks/:id", delete(handlers::tasks::delete_task))
+        .route("/tasks/:id/run", post(handlers::tasks::run_task))
+        .route("/tasks/:id/runs", get(handlers::tasks::list_task_runs))
+        .with_state(task_scheduler)
+        // Notification rule routes (separate state)
+        .route("/notifications/rules", get(handlers::rules::list_rules))
+        .route("/notifications/rules", post(handlers::rules::create_rule))
+        .route("/notifications/rules/:id", get(handlers::rules::get_rule))
+        .route(
+            "/notifications/rules/:id",
+            put(handlers::rules::update_rule),
+        )
+        .route(
+            "/notifications/rules/:id",
+            delete(handlers::rules::delete_rule),
+        )
+        .route(
+            "/notifications/rules/:id/test",
+            post(handlers::rules::test_rule),
+        )
+        .with_state(rule_engine)
+        // Notification channel routes (separate state)
+        .route(
+            "/notifications/channels",
+            get(handlers::channels::list_channels),
+        )
+        .route(
+            "/notifications/channels",
+            post(handlers::channels::create_channel),
+        )
+        .route(
+            "/notifications/channels/:id",
+            get(handlers::channels::get_channel),
+        )
+        .route(
+            "/notifications/channels/:id",
+            put(handlers::channels::update_channel),
+        )
+        .route(
+            "/notifications/channels/:id",
+            delete(handlers::channels::delete_channel),
+        )
+        .route(
+            "/notifications/channels/:id/test",
+            post(handlers::channels::test_channel),
+        )
+        .with_state(dispatcher)
+        // Node mode routes (separate state)
+        .route("/node/mode", get(handlers::node::get_node_mode))
+        .route("/node/mode", post(handlers::node::switch_node_mode))
+        .with_state(node_mode)
         .layer(TraceLayer::new_for_http())
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        );
+        .layer(SecurityConfig::from_env().layer());
 
     info!(
         "Swagger UI available at http://{}:{}/swagger-ui/",
@@ -662,7 +956,11 @@ async fn main() -> Result<()> {
     info!("Listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,578 @@
+//! Pruned/archival mode switching for the Bitcoin node
+//!
+//! bitcoind reads `prune` and `txindex` only at startup, and a pruned node
+//! can't become archival again without rebuilding its block database. A
+//! switch therefore stops the services that read from the node, rewrites
+//! the global section of the bitcoin.conf that lives in the node's data
+//! volume, and restarts the node. Going back to archival adds `reindex=1`,
+//! which rebuilds from the block files still on disk and downloads the
+//! rest; the line is removed again as soon as the node answers RPC so a
+//! later restart doesn't start over. Dependents are started again only
+//! once the node has caught up with its headers and txindex, if enabled,
+//! has synced.
+//!
+//! Switch state is kept in memory. The node's own state is the source of
+//! truth for the current mode, so a dashboard restart mid-switch loses the
+//! progress view but not the switch.
+
+use anyhow::{anyhow, bail, Context, Result};
+use bollard::container::{ListContainersOptions, RestartContainerOptions};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::dependencies::{self, DependencyGraph, OrderedAction};
+use crate::handlers::node::NodeSettings;
+use crate::AppState;
+
+const NODE_CONTAINER: &str = "anchor-core-bitcoin";
+const NODE_CONF: &str = "/home/bitcoin/.bitcoin/bitcoin.conf";
+
+/// Smallest prune target bitcoind accepts, in MiB
+pub const MIN_PRUNE_MB: u32 = 550;
+
+/// Containers that read from the node and are paused during a switch
+const DEPENDENT_PREFIXES: &[&str] = &[
+    "anchor-core-indexer",
+    "anchor-core-wallet",
+    "anchor-core-testnet",
+    "anchor-core-electrs",
+    "anchor-core-fulcrum",
+    "anchor-explorer-",
+    "anchor-app-",
+];
+
+/// Electrum servers index full blocks and can't run against a pruned node
+const ARCHIVAL_ONLY: &[&str] = &["anchor-core-electrs", "anchor-core-fulcrum"];
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Block storage and transaction index settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct NodeStorageMode {
+    /// Prune target in MiB; 0 keeps every block (archival)
+    pub prune_mb: u32,
+    pub txindex: bool,
+}
+
+impl NodeStorageMode {
+    pub fn is_pruned(&self) -> bool {
+        self.prune_mb > 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchPhase {
+    StoppingDependents,
+    Reconfiguring,
+    Restarting,
+    /// Reindexing or re-downloading blocks
+    Syncing,
+    BuildingTxindex,
+    ResumingDependents,
+    Completed,
+    Failed,
+}
+
+/// How far the node has caught up
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SyncProgress {
+    pub blocks: i64,
+    pub headers: i64,
+    pub verification_progress: f64,
+    /// Height the txindex has reached, when enabled
+    pub txindex_height: Option<i64>,
+}
+
+/// A mode switch, running or finished
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ModeSwitch {
+    pub id: String,
+    pub from: NodeStorageMode,
+    pub to: NodeStorageMode,
+    /// Whether blocks must be rebuilt or downloaded again
+    pub requires_reindex: bool,
+    pub phase: SwitchPhase,
+    /// Containers stopped until the node is ready
+    pub paused_containers: Vec<String>,
+    pub progress: Option<SyncProgress>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// Runs one mode switch at a time
+pub struct NodeModeSwitcher {
+    app: Arc<AppState>,
+    current: RwLock<Option<ModeSwitch>>,
+}
+
+impl NodeModeSwitcher {
+    pub fn new(app: Arc<AppState>) -> Self {
+        Self {
+            app,
+            current: RwLock::new(None),
+        }
+    }
+
+    /// The running switch, or the last one to finish
+    pub async fn current(&self) -> Option<ModeSwitch> {
+        self.current.read().await.clone()
+    }
+
+    /// Mode the node is running with, read from the node itself
+    pub async fn node_mode(&self) -> Result<NodeStorageMode> {
+        let info = self.rpc("getblockchaininfo").await?;
+        let pruned = info["pruned"].as_bool().unwrap_or(false);
+        let prune_mb = if pruned {
+            // prune_target_size is only set with automatic pruning
+            info["prune_target_size"]
+                .as_u64()
+                .map(|bytes| (bytes >> 20) as u32)
+                .unwrap_or(MIN_PRUNE_MB)
+        } else {
+            0
+        };
+        let indexes = self.rpc("getindexinfo").await?;
+        Ok(NodeStorageMode {
+            prune_mb,
+            txindex: indexes.get("txindex").is_some(),
+        })
+    }
+
+    /// Check a switch can start and spawn it
+    pub async fn start(self: &Arc<Self>, to: NodeStorageMode) -> Result<ModeSwitch, SwitchError> {
+        if to.is_pruned() && to.txindex {
+            return Err(SwitchError::Invalid(
+                "txindex needs every block; it can't be enabled on a pruned node".to_string(),
+            ));
+        }
+        if to.is_pruned() && to.prune_mb < MIN_PRUNE_MB {
+            return Err(SwitchError::Invalid(format!(
+                "prune_mb must be 0 or at least {}",
+                MIN_PRUNE_MB
+            )));
+        }
+
+        let mut current = self.current.write().await;
+        if current
+            .as_ref()
+            .is_some_and(|s| !matches!(s.phase, SwitchPhase::Completed | SwitchPhase::Failed))
+        {
+            return Err(SwitchError::Busy);
+        }
+
+        let from = self
+            .node_mode()
+            .await
+            .map_err(|e| SwitchError::Unavailable(e.to_string()))?;
+        if from == to {
+            return Err(SwitchError::Invalid(
+                "The node is already in this mode".to_string(),
+            ));
+        }
+
+        let running = self
+            .running_dependents()
+            .await
+            .map_err(|e| SwitchError::Unavailable(e.to_string()))?;
+        if to.is_pruned() {
+            let blocking: Vec<&String> = running
+                .iter()
+                .filter(|name| ARCHIVAL_ONLY.contains(&name.as_str()))
+                .collect();
+            if !blocking.is_empty() {
+                return Err(SwitchError::Conflict(format!(
+                    "Electrum servers need an archival node; uninstall {:?} first",
+                    blocking
+                )));
+            }
+        }
+
+        let switch = ModeSwitch {
+            id: Uuid::new_v4().to_string(),
+            from,
+            to,
+            requires_reindex: from.is_pruned() && !to.is_pruned(),
+            phase: SwitchPhase::StoppingDependents,
+            paused_containers: running,
+            progress: None,
+            started_at: Utc::now(),
+            completed_at: None,
+            error: None,
+        };
+        *current = Some(switch.clone());
+        drop(current);
+
+        info!(
+            "Switching node from {:?} to {:?} (reindex: {})",
+            switch.from, switch.to, switch.requires_reindex
+        );
+        let switcher = self.clone();
+        let spawned = switch.clone();
+        tokio::spawn(async move {
+            switcher.run(spawned).await;
+        });
+
+        Ok(switch)
+    }
+
+    async fn run(&self, switch: ModeSwitch) {
+        match self.execute(&switch).await {
+            Ok(()) => {
+                self.update(|s| {
+                    s.phase = SwitchPhase::Completed;
+                    s.completed_at = Some(Utc::now());
+                })
+                .await;
+                info!("Node switched to {:?}", switch.to);
+                self.notify(
+                    "Node mode switched",
+                    &format!("The node now runs {}", describe(switch.to)),
+                    "success",
+                )
+                .await;
+            }
+            Err(e) => {
+                error!("Node mode switch failed: {:#}", e);
+                self.update(|s| {
+                    s.phase = SwitchPhase::Failed;
+                    s.completed_at = Some(Utc::now());
+                    s.error = Some(format!("{:#}", e));
+                })
+                .await;
+                self.notify(
+                    "Node mode switch failed",
+                    &format!(
+                        "{:#}. Stopped services stay stopped until the node is fixed: {}",
+                        e,
+                        switch.paused_containers.join(", ")
+                    ),
+                    "error",
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn execute(&self, switch: &ModeSwitch) -> Result<()> {
+        let docker = &self.app.docker;
+        let plan = DependencyGraph::default().plan(&switch.paused_containers);
+
+        let stopped = dependencies::stop_ordered(docker, &plan).await;
+        if !stopped.failed.is_empty() {
+            bail!("failed to stop {}", stopped.failed.join(", "));
+        }
+
+        self.update(|s| s.phase = SwitchPhase::Reconfiguring).await;
+        let conf = read_conf().await?;
+        write_conf(&apply_mode(&conf, switch.to, switch.requires_reindex)).await?;
+
+        self.update(|s| s.phase = SwitchPhase::Restarting).await;
+        docker
+            .restart_container(NODE_CONTAINER, Some(RestartContainerOptions { t: 120 }))
+            .await
+            .context("failed to restart the node")?;
+
+        // RPC answers once the block index has loaded; with reindex=1 that's
+        // after the old databases were wiped, so the flag can go
+        let mut info = loop {
+            match self.rpc("getblockchaininfo").await {
+                Ok(info) => break info,
+                Err(_) if self.node_running().await => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => bail!("the node stopped after the restart: {}", e),
+            }
+        };
+        if switch.requires_reindex {
+            write_conf(&apply_mode(&conf, switch.to, false)).await?;
+        }
+
+        self.update(|s| s.phase = SwitchPhase::Syncing).await;
+        loop {
+            let progress = progress(&info, None);
+            let synced = progress.headers > 0
+                && progress.blocks >= progress.headers
+                && (!info["initialblockdownload"].as_bool().unwrap_or(false)
+                    || info["chain"] == "regtest");
+            self.update(|s| s.progress = Some(progress)).await;
+            if synced {
+                break;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+            info = self.rpc("getblockchaininfo").await?;
+        }
+
+        if switch.to.txindex {
+            self.update(|s| s.phase = SwitchPhase::BuildingTxindex)
+                .await;
+            loop {
+                let indexes = self.rpc("getindexinfo").await?;
+                let txindex = &indexes["txindex"];
+                let height = txindex["best_block_height"].as_i64();
+                self.update(|s| {
+                    if let Some(p) = s.progress.as_mut() {
+                        p.txindex_height = height;
+                    }
+                })
+                .await;
+                if txindex["synced"].as_bool().unwrap_or(false) {
+                    break;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+
+        self.update(|s| s.phase = SwitchPhase::ResumingDependents)
+            .await;
+        let started = dependencies::run_ordered(
+            docker,
+            &plan,
+            OrderedAction::Start,
+            dependencies::HEALTH_WAIT_TIMEOUT,
+        )
+        .await;
+        if !started.failed.is_empty() {
+            bail!(
+                "the node is ready but these didn't come back: {}",
+                started.failed.join(", ")
+            );
+        }
+
+        self.save_settings(switch.to).await;
+        Ok(())
+    }
+
+    async fn update(&self, f: impl FnOnce(&mut ModeSwitch)) {
+        if let Some(switch) = self.current.write().await.as_mut() {
+            f(switch);
+        }
+    }
+
+    async fn running_dependents(&self) -> Result<Vec<String>> {
+        let mut filters = HashMap::new();
+        filters.insert("status", vec!["running"]);
+        let containers = self
+            .app
+            .docker
+            .list_containers(Some(ListContainersOptions {
+                filters,
+                ..Default::default()
+            }))
+            .await?;
+        let mut names: Vec<String> = containers
+            .iter()
+            .flat_map(|c| c.names.iter().flatten())
+            .map(|n| n.trim_start_matches('/').to_string())
+            .filter(|n| DEPENDENT_PREFIXES.iter().any(|p| n.starts_with(p)))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    async fn node_running(&self) -> bool {
+        self.app
+            .docker
+            .inspect_container(NODE_CONTAINER, None)
+            .await
+            .ok()
+            .and_then(|c| c.state)
+            .and_then(|s| s.running)
+            .unwrap_or(false)
+    }
+
+    /// Keep the node settings page in step with the node
+    async fn save_settings(&self, mode: NodeStorageMode) {
+        let Some(pool) = &self.app.db_pool else {
+            return;
+        };
+        let stored: Option<serde_json::Value> =
+            sqlx::query_scalar("SELECT value FROM system_settings WHERE key = 'node_settings'")
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten();
+        let mut settings: NodeSettings = stored
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        settings.prune = mode.prune_mb as i32;
+        settings.txindex = mode.txindex;
+
+        let Ok(value) = serde_json::to_value(&settings) else {
+            return;
+        };
+        if let Err(e) = sqlx::query(
+            "INSERT INTO system_settings (key, value, updated_at) VALUES ('node_settings', $1, NOW())
+             ON CONFLICT (key) DO UPDATE SET value = $1, updated_at = NOW()",
+        )
+        .bind(&value)
+        .execute(pool)
+        .await
+        {
+            error!("Failed to save node settings: {}", e);
+        }
+    }
+
+    async fn notify(&self, title: &str, message: &str, severity: &str) {
+        let Some(pool) = &self.app.db_pool else {
+            return;
+        };
+        if let Err(e) = sqlx::query(
+            "INSERT INTO notifications (notification_type, title, message, severity) VALUES ('node', $1, $2, $3)",
+        )
+        .bind(title)
+        .bind(message)
+        .bind(severity)
+        .execute(pool)
+        .await
+        {
+            error!("Failed to record node notification: {}", e);
+        }
+    }
+
+    async fn rpc(&self, method: &str) -> Result<serde_json::Value> {
+        let config = &self.app.config;
+        let response: serde_json::Value = self
+            .app
+            .http_client
+            .post(&config.bitcoin_rpc_url)
+            .basic_auth(&config.bitcoin_rpc_user, Some(&config.bitcoin_rpc_password))
+            .json(&serde_json::json!({
+                "jsonrpc": "1.0",
+                "id": "dashboard-node-mode",
+                "method": method,
+                "params": []
+            }))
+            .send()
+            .await
+            .with_context(|| format!("failed to call {}", method))?
+            .json()
+            .await
+            .with_context(|| format!("invalid {} response", method))?;
+
+        if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+            bail!("{} failed: {}", method, error);
+        }
+        Ok(response["result"].clone())
+    }
+}
+
+/// Why a switch couldn't start
+pub enum SwitchError {
+    Invalid(String),
+    Busy,
+    Conflict(String),
+    Unavailable(String),
+}
+
+fn describe(mode: NodeStorageMode) -> String {
+    match (mode.is_pruned(), mode.txindex) {
+        (true, _) => format!("pruned to {} MiB", mode.prune_mb),
+        (false, true) => "archival with txindex".to_string(),
+        (false, false) => "archival without txindex".to_string(),
+    }
+}
+
+fn progress(info: &serde_json::Value, txindex_height: Option<i64>) -> SyncProgress {
+    SyncProgress {
+        blocks: info["blocks"].as_i64().unwrap_or(0),
+        headers: info["headers"].as_i64().unwrap_or(0),
+        verification_progress: info["verificationprogress"].as_f64().unwrap_or(0.0),
+        txindex_height,
+    }
+}
+
+/// Set `txindex`, `prune`, and `reindex` in the global section
+///
+/// Network sections (`[regtest]`, `[main]`, ...) are left alone; the
+/// shipped config only sets these options globally.
+fn apply_mode(conf: &str, mode: NodeStorageMode, reindex: bool) -> String {
+    let lines: Vec<&str> = conf.lines().collect();
+    let section_start = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+
+    let is_mode_option = |line: &str| {
+        let key = line.split('=').next().unwrap_or("").trim();
+        matches!(key, "txindex" | "prune" | "reindex")
+    };
+    let mut global: Vec<String> = lines[..section_start]
+        .iter()
+        .filter(|l| !is_mode_option(l))
+        .map(|l| l.to_string())
+        .collect();
+    while global.last().is_some_and(|l| l.trim().is_empty()) {
+        global.pop();
+    }
+
+    global.push(String::new());
+    global.push("# Block storage (managed by the dashboard)".to_string());
+    global.push(format!("txindex={}", if mode.txindex { 1 } else { 0 }));
+    if mode.is_pruned() {
+        global.push(format!("prune={}", mode.prune_mb));
+    }
+    if reindex {
+        global.push("reindex=1".to_string());
+    }
+    global.push(String::new());
+
+    let mut out = global.join("\n");
+    for line in &lines[section_start..] {
+        out.push('\n');
+        out.push_str(line);
+    }
+    out.push('\n');
+    out
+}
+
+async fn read_conf() -> Result<String> {
+    let output = Command::new("docker")
+        .args(["exec", NODE_CONTAINER, "cat", NODE_CONF])
+        .output()
+        .await
+        .context("failed to run docker exec")?;
+    if !output.status.success() {
+        bail!(
+            "failed to read bitcoin.conf: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).map_err(|_| anyhow!("bitcoin.conf is not valid UTF-8"))
+}
+
+async fn write_conf(conf: &str) -> Result<()> {
+    let mut child = Command::new("docker")
+        .args([
+            "exec",
+            "-i",
+            NODE_CONTAINER,
+            "sh",
+            "-c",
+            &format!("cat > {0}.tmp && mv {0}.tmp {0}", NODE_CONF),
+        ])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run docker exec")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(conf.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        bail!(
+            "failed to write bitcoin.conf: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,578 @@
+//! Installation preflight checks and hardware profiling
+//!
+//! The dashboard runs in a container, so the host is profiled through
+//! Docker: `docker info` for CPUs, memory, and the kernel, and short-lived
+//! alpine helpers for the free space under Docker's data root and for the
+//! host's listening ports. From that profile the wizard recommends a
+//! preset, a node mode, and an Electrum server. Sizes are mainnet
+//! estimates with headroom for growth; regtest and testnet need far less.
+
+use bollard::container::ListContainersOptions;
+use bollard::Docker;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tokio::process::Command;
+use utoipa::ToSchema;
+
+use crate::handlers::installation::{get_preset_services, InstallationPreset};
+
+const GIB: u64 = 1 << 30;
+
+/// Block data plus txindex and block filters
+const ARCHIVAL_NODE_BYTES: u64 = 900 * GIB;
+/// Chainstate plus a small block window
+const PRUNED_NODE_BYTES: u64 = 20 * GIB;
+const ELECTRS_INDEX_BYTES: u64 = 60 * GIB;
+const FULCRUM_INDEX_BYTES: u64 = 200 * GIB;
+/// Postgres, images, and local backups
+const BASE_BYTES: u64 = 50 * GIB;
+
+const MIN_MEMORY_BYTES: u64 = 4 * GIB;
+const RECOMMENDED_MEMORY_BYTES: u64 = 8 * GIB;
+/// Fulcrum's sync and the full app set both want this much
+const LARGE_MEMORY_BYTES: u64 = 16 * GIB;
+
+const MIN_CPUS: u64 = 2;
+const RECOMMENDED_CPUS: u64 = 4;
+const LARGE_CPUS: u64 = 8;
+
+/// Host ports published by each service
+const SERVICE_PORTS: &[(&str, &[u16])] = &[
+    (
+        "core-bitcoin",
+        &[18443, 18444, 8332, 8333, 29000, 29001, 29002],
+    ),
+    ("core-postgres", &[5432]),
+    ("core-electrs", &[50001]),
+    ("core-fulcrum", &[50002]),
+    ("core-wallet", &[8001]),
+    ("core-testnet", &[8002]),
+    ("anchor-dashboard", &[8000, 8010]),
+    ("anchor-docs", &[3900]),
+    ("explorer-mempool", &[4000]),
+    ("explorer-btc-rpc", &[4010]),
+    ("explorer-bitfeed", &[4020]),
+    ("networking-tor", &[9050, 9051]),
+    ("monitoring-netdata", &[19999]),
+    ("app-threads", &[3100, 3101]),
+    ("app-canvas", &[3200, 3201]),
+    ("app-places", &[3300, 3301]),
+    ("app-domains", &[3400, 3401]),
+    ("app-proof", &[3500, 3501]),
+    ("app-tokens", &[3600, 3601]),
+    ("app-oracles", &[3700, 3701]),
+    ("app-predictions", &[3800, 3801]),
+];
+
+/// Services that can't be skipped when their ports are taken
+const ESSENTIAL_SERVICES: &[&str] = &["core-bitcoin", "core-postgres", "anchor-dashboard"];
+
+/// What the host offers
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HardwareProfile {
+    pub cpus: Option<u64>,
+    pub memory_bytes: Option<u64>,
+    /// Filesystem holding Docker's volumes
+    pub disk_total_bytes: Option<u64>,
+    pub disk_available_bytes: Option<u64>,
+    pub docker_root_dir: Option<String>,
+    pub architecture: Option<String>,
+    pub operating_system: Option<String>,
+    pub kernel_version: Option<String>,
+    /// Detected virtualization: docker_desktop, wsl, or vm
+    pub virtualization: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Result of one check
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PreflightCheck {
+    /// disk, memory, cpu, ports, architecture, or virtualization
+    pub id: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeMode {
+    /// Full block history with txindex, as shipped
+    Archival,
+    /// Old blocks discarded; needs `prune=` and no txindex in bitcoin.conf
+    Pruned,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ElectrumServer {
+    Electrs,
+    Fulcrum,
+}
+
+/// Suggested setup for this host
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Recommendation {
+    pub preset: InstallationPreset,
+    pub node_mode: NodeMode,
+    /// None when the node is pruned; both servers need full blocks
+    pub electrum_server: Option<ElectrumServer>,
+    pub services: Vec<String>,
+    pub reasons: Vec<String>,
+}
+
+/// A full preflight run
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PreflightReport {
+    /// Set once the report is saved
+    pub id: Option<i32>,
+    pub hardware: HardwareProfile,
+    pub checks: Vec<PreflightCheck>,
+    pub recommendation: Recommendation,
+    /// Every warning and failure, plus caveats of the recommendation
+    pub warnings: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Profile the host and build a report
+pub async fn run(docker: &Docker) -> PreflightReport {
+    let (hardware, listening, anchor_ports) = tokio::join!(
+        profile(docker),
+        host_listening_ports(),
+        anchor_published_ports(docker)
+    );
+
+    let recommendation = recommend(&hardware);
+    let checks = vec![
+        check_disk(&hardware),
+        check_memory(&hardware),
+        check_cpus(&hardware),
+        check_ports(listening, &anchor_ports, &recommendation.services),
+        check_architecture(&hardware),
+        check_virtualization(&hardware),
+    ];
+
+    let mut warnings: Vec<String> = checks
+        .iter()
+        .filter(|c| c.status != CheckStatus::Pass)
+        .map(|c| c.detail.clone())
+        .collect();
+    if recommendation.node_mode == NodeMode::Pruned {
+        warnings.push(
+            "A pruned node needs prune= set and txindex=1 removed in bitcoin.conf; without \
+             txindex the feed can't attribute messages to creator addresses"
+                .to_string(),
+        );
+        warnings.push(
+            "Pruned nodes can't serve Electrum wallets, and the Anchor indexer must keep up \
+             during the initial sync or it misses blocks that are pruned away"
+                .to_string(),
+        );
+    }
+
+    PreflightReport {
+        id: None,
+        hardware,
+        checks,
+        recommendation,
+        warnings,
+        created_at: Utc::now(),
+    }
+}
+
+async fn profile(docker: &Docker) -> HardwareProfile {
+    let info = docker.info().await.ok();
+    let info = info.as_ref();
+    let docker_root_dir = info.and_then(|i| i.docker_root_dir.clone());
+    let operating_system = info.and_then(|i| i.operating_system.clone());
+    let kernel_version = info.and_then(|i| i.kernel_version.clone());
+
+    let (disk_total_bytes, disk_available_bytes) = match docker_disk_space().await {
+        Some((total, available)) => (Some(total), Some(available)),
+        None => (None, None),
+    };
+
+    // The container shares the host's (or Docker VM's) kernel, so its
+    // cpuinfo flags show whether that kernel runs under a hypervisor
+    let hypervisor = tokio::fs::read_to_string("/proc/cpuinfo")
+        .await
+        .map(|cpuinfo| {
+            cpuinfo
+                .lines()
+                .filter(|l| l.starts_with("flags"))
+                .any(|l| l.split_whitespace().any(|f| f == "hypervisor"))
+        })
+        .unwrap_or(false);
+    let virtualization = if operating_system
+        .as_deref()
+        .is_some_and(|os| os.contains("Docker Desktop"))
+    {
+        Some("docker_desktop".to_string())
+    } else if kernel_version
+        .as_deref()
+        .is_some_and(|k| k.to_lowercase().contains("microsoft"))
+    {
+        Some("wsl".to_string())
+    } else if hypervisor {
+        Some("vm".to_string())
+    } else {
+        None
+    };
+
+    HardwareProfile {
+        cpus: info.and_then(|i| i.ncpu).map(|n| n as u64),
+        memory_bytes: info.and_then(|i| i.mem_total).map(|m| m as u64),
+        disk_total_bytes,
+        disk_available_bytes,
+        docker_root_dir,
+        architecture: info.and_then(|i| i.architecture.clone()),
+        operating_system,
+        kernel_version,
+        virtualization,
+    }
+}
+
+/// Total and available bytes where Docker keeps volumes
+///
+/// An anonymous volume lives under Docker's data root, so `df` on one
+/// reports that filesystem; `--rm` removes it again.
+async fn docker_disk_space() -> Option<(u64, u64)> {
+    let output = Command::new("docker")
+        .args(["run", "--rm", "-v", "/data", "alpine", "df", "-Pk", "/data"])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.lines().nth(1)?.split_whitespace().collect();
+    let total: u64 = fields.get(1)?.parse().ok()?;
+    let available: u64 = fields.get(3)?.parse().ok()?;
+    Some((total * 1024, available * 1024))
+}
+
+/// TCP ports listening on the host, seen from a host-network helper
+async fn host_listening_ports() -> Option<HashSet<u16>> {
+    let output = Command::new("docker")
+        .args([
+            "run",
+            "--rm",
+            "--network",
+            "host",
+            "alpine",
+            "netstat",
+            "-tln",
+        ])
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(
+        stdout
+            .lines()
+            .filter(|l| l.starts_with("tcp"))
+            .filter_map(|l| l.split_whitespace().nth(3))
+            .filter_map(|addr| addr.rsplit(':').next()?.parse().ok())
+            .collect(),
+    )
+}
+
+/// Host ports already published by Anchor containers
+async fn anchor_published_ports(docker: &Docker) -> HashSet<u16> {
+    let mut filters = HashMap::new();
+    filters.insert("name", vec!["anchor-"]);
+    let options = Some(ListContainersOptions {
+        all: true,
+        filters,
+        ..Default::default()
+    });
+    docker
+        .list_containers(options)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|c| c.ports.iter().flatten())
+        .filter_map(|p| p.public_port)
+        .collect()
+}
+
+fn recommend(hw: &HardwareProfile) -> Recommendation {
+    let disk = hw.disk_available_bytes;
+    let memory = hw.memory_bytes.unwrap_or(0);
+    let cpus = hw.cpus.unwrap_or(0);
+    let mut reasons = Vec::new();
+
+    let fits = |bytes: u64| disk.is_none_or(|d| d >= BASE_BYTES + bytes);
+    if disk.is_none() {
+        reasons.push("Free disk space is unknown; assuming an archival node fits".to_string());
+    }
+
+    if !fits(ARCHIVAL_NODE_BYTES + ELECTRS_INDEX_BYTES) {
+        reasons.push(format!(
+            "{} GiB free is too little for an archival node with an Electrum index ({} GiB)",
+            disk.unwrap_or(0) / GIB,
+            (BASE_BYTES + ARCHIVAL_NODE_BYTES + ELECTRS_INDEX_BYTES) / GIB
+        ));
+        let services = get_preset_services(InstallationPreset::Minimum)
+            .into_iter()
+            .filter(|s| s != "core-fulcrum")
+            .collect();
+        return Recommendation {
+            preset: InstallationPreset::Custom,
+            node_mode: NodeMode::Pruned,
+            electrum_server: None,
+            services,
+            reasons,
+        };
+    }
+
+    if fits(ARCHIVAL_NODE_BYTES + FULCRUM_INDEX_BYTES + ELECTRS_INDEX_BYTES)
+        && memory >= LARGE_MEMORY_BYTES
+        && cpus >= LARGE_CPUS
+    {
+        reasons.push(format!(
+            "{} CPUs and {} GiB RAM can run every service, including both Electrum servers",
+            cpus,
+            memory / GIB
+        ));
+        return Recommendation {
+            preset: InstallationPreset::Full,
+            node_mode: NodeMode::Archival,
+            electrum_server: Some(ElectrumServer::Fulcrum),
+            services: get_preset_services(InstallationPreset::Full),
+            reasons,
+        };
+    }
+
+    if fits(ARCHIVAL_NODE_BYTES + FULCRUM_INDEX_BYTES) && memory >= LARGE_MEMORY_BYTES {
+        reasons.push(format!(
+            "{} GiB RAM and room for Fulcrum's larger index: Fulcrum answers wallets faster",
+            memory / GIB
+        ));
+        return Recommendation {
+            preset: InstallationPreset::Minimum,
+            node_mode: NodeMode::Archival,
+            electrum_server: Some(ElectrumServer::Fulcrum),
+            services: get_preset_services(InstallationPreset::Minimum),
+            reasons,
+        };
+    }
+
+    reasons.push(
+        "electrs needs less disk and memory than Fulcrum; the Default preset adds the mempool explorer"
+            .to_string(),
+    );
+    Recommendation {
+        preset: InstallationPreset::Default,
+        node_mode: NodeMode::Archival,
+        electrum_server: Some(ElectrumServer::Electrs),
+        services: get_preset_services(InstallationPreset::Default),
+        reasons,
+    }
+}
+
+fn check(id: &str, status: CheckStatus, detail: String) -> PreflightCheck {
+    PreflightCheck {
+        id: id.to_string(),
+        status,
+        detail,
+    }
+}
+
+fn check_disk(hw: &HardwareProfile) -> PreflightCheck {
+    let Some(available) = hw.disk_available_bytes else {
+        return check(
+            "disk",
+            CheckStatus::Warn,
+            "Could not measure free disk space under Docker's data root".to_string(),
+        );
+    };
+    let gib = available / GIB;
+    if available < BASE_BYTES + PRUNED_NODE_BYTES {
+        check(
+            "disk",
+            CheckStatus::Fail,
+            format!(
+                "Only {} GiB free; even a pruned node needs {} GiB",
+                gib,
+                (BASE_BYTES + PRUNED_NODE_BYTES) / GIB
+            ),
+        )
+    } else if available < BASE_BYTES + ARCHIVAL_NODE_BYTES + ELECTRS_INDEX_BYTES {
+        check(
+            "disk",
+            CheckStatus::Warn,
+            format!("{} GiB free: enough for a pruned node only", gib),
+        )
+    } else {
+        check("disk", CheckStatus::Pass, format!("{} GiB free", gib))
+    }
+}
+
+fn check_memory(hw: &HardwareProfile) -> PreflightCheck {
+    let Some(memory) = hw.memory_bytes else {
+        return check(
+            "memory",
+            CheckStatus::Warn,
+            "Could not read total memory from Docker".to_string(),
+        );
+    };
+    let gib = memory as f64 / GIB as f64;
+    if memory < MIN_MEMORY_BYTES {
+        check(
+            "memory",
+            CheckStatus::Fail,
+            format!(
+                "{:.1} GiB RAM; at least {} GiB is needed",
+                gib,
+                MIN_MEMORY_BYTES / GIB
+            ),
+        )
+    } else if memory < RECOMMENDED_MEMORY_BYTES {
+        check(
+            "memory",
+            CheckStatus::Warn,
+            format!(
+                "{:.1} GiB RAM; the initial sync will be slow below {} GiB",
+                gib,
+                RECOMMENDED_MEMORY_BYTES / GIB
+            ),
+        )
+    } else {
+        check("memory", CheckStatus::Pass, format!("{:.1} GiB RAM", gib))
+    }
+}
+
+fn check_cpus(hw: &HardwareProfile) -> PreflightCheck {
+    let Some(cpus) = hw.cpus else {
+        return check(
+            "cpu",
+            CheckStatus::Warn,
+            "Could not read the CPU count from Docker".to_string(),
+        );
+    };
+    if cpus < MIN_CPUS {
+        check(
+            "cpu",
+            CheckStatus::Fail,
+            format!("{} CPU; at least {} are needed", cpus, MIN_CPUS),
+        )
+    } else if cpus < RECOMMENDED_CPUS {
+        check(
+            "cpu",
+            CheckStatus::Warn,
+            format!("{} CPUs; {} or more recommended", cpus, RECOMMENDED_CPUS),
+        )
+    } else {
+        check("cpu", CheckStatus::Pass, format!("{} CPUs", cpus))
+    }
+}
+
+/// Ports the recommended services need that something else holds
+fn check_ports(
+    listening: Option<HashSet<u16>>,
+    anchor_ports: &HashSet<u16>,
+    services: &[String],
+) -> PreflightCheck {
+    let Some(listening) = listening else {
+        return check(
+            "ports",
+            CheckStatus::Warn,
+            "Could not list the host's listening ports".to_string(),
+        );
+    };
+
+    let mut conflicts = Vec::new();
+    let mut essential = false;
+    for (service, ports) in SERVICE_PORTS {
+        if !services.iter().any(|s| s == service) {
+            continue;
+        }
+        for port in *ports {
+            if listening.contains(port) && !anchor_ports.contains(port) {
+                conflicts.push(format!("{} ({})", port, service));
+                essential |= ESSENTIAL_SERVICES.contains(service);
+            }
+        }
+    }
+
+    if conflicts.is_empty() {
+        check(
+            "ports",
+            CheckStatus::Pass,
+            "All service ports are free".to_string(),
+        )
+    } else {
+        check(
+            "ports",
+            if essential {
+                CheckStatus::Fail
+            } else {
+                CheckStatus::Warn
+            },
+            format!("Ports in use by other programs: {}", conflicts.join(", ")),
+        )
+    }
+}
+
+fn check_architecture(hw: &HardwareProfile) -> PreflightCheck {
+    match hw.architecture.as_deref() {
+        Some("x86_64") | Some("aarch64") => check(
+            "architecture",
+            CheckStatus::Pass,
+            hw.architecture.clone().unwrap_or_default(),
+        ),
+        Some(other) => check(
+            "architecture",
+            CheckStatus::Fail,
+            format!(
+                "{} is unsupported; Anchor images need x86_64 or aarch64",
+                other
+            ),
+        ),
+        None => check(
+            "architecture",
+            CheckStatus::Warn,
+            "Could not read the CPU architecture from Docker".to_string(),
+        ),
+    }
+}
+
+fn check_virtualization(hw: &HardwareProfile) -> PreflightCheck {
+    match hw.virtualization.as_deref() {
+        Some("docker_desktop") => check(
+            "virtualization",
+            CheckStatus::Warn,
+            "Docker Desktop: CPU, memory, and disk are capped by its VM settings, and the port \
+             check only sees the VM"
+                .to_string(),
+        ),
+        Some("wsl") => check(
+            "virtualization",
+            CheckStatus::Warn,
+            "WSL 2: memory is capped by .wslconfig and volumes live in a growing VHDX file"
+                .to_string(),
+        ),
+        Some(_) => check(
+            "virtualization",
+            CheckStatus::Pass,
+            "Running in a virtual machine; figures are the VM's allocation".to_string(),
+        ),
+        None => check(
+            "virtualization",
+            CheckStatus::Pass,
+            "Bare metal".to_string(),
+        ),
+    }
+}
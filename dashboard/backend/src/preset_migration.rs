@@ -0,0 +1,498 @@
+//! Migration between installation presets
+//!
+//! A migration moves a completed installation to another preset without
+//! touching the services both presets share. Services that are dropped
+//! lose their containers but keep their volumes, so adding them back later
+//! resumes from the old data. Services that are added start in stages:
+//! infrastructure, then the indexes (the Anchor indexer and Electrum
+//! servers), then the explorers and apps that read from those indexes.
+//! Each index is given time to catch up with the node before its
+//! dependents start. App indexers then catch up from their own cursors,
+//! which survive removal because their tables live in the core database.
+//!
+//! The plan is saved when the migration is requested and executed by the
+//! installation stream, so progress shows up in the same log view as a
+//! first install. A failed run leaves the plan pending; reopening the
+//! stream retries it, and every step is safe to repeat.
+
+use axum::response::sse::Event;
+use bollard::container::RemoveContainerOptions;
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tracing::{error, info};
+use utoipa::ToSchema;
+
+use crate::handlers::electrum::{self, ElectrumServer};
+use crate::handlers::installation::{
+    get_all_services, get_service_profiles, strip_ansi_codes, InstallationPreset, ServiceCategory,
+};
+use crate::AppState;
+
+/// Default time each index gets to catch up before dependents start anyway
+pub const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 1800;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Services that build an index their dependents read from
+const INDEX_SERVICES: &[&str] = &["core-indexer", "core-electrs", "core-fulcrum"];
+
+/// Apps whose indexer cursor lives in the core database
+const APP_CURSORS: &[(&str, &str)] = &[
+    ("app-canvas", "canvas_indexer_state"),
+    ("app-places", "places_indexer_state"),
+    ("app-domains", "anchor_domains_indexer_state"),
+    ("app-proof", "proofs_indexer_state"),
+    ("app-tokens", "token_indexer_state"),
+];
+
+/// Apps that index into a database of their own
+const SEPARATE_DATABASE_APPS: &[&str] = &["app-oracles", "app-predictions"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StageKind {
+    /// Remove containers of dropped services, keeping their volumes
+    RemoveServices,
+    StartInfrastructure,
+    /// Start index builders and wait for them to reach the node's tip
+    BuildIndexes,
+    StartExplorers,
+    /// Start apps and wait for their indexers to reach the Anchor indexer
+    StartApps,
+}
+
+impl StageKind {
+    fn describe(&self) -> &'static str {
+        match self {
+            StageKind::RemoveServices => "Removing services (volumes are kept)",
+            StageKind::StartInfrastructure => "Starting infrastructure",
+            StageKind::BuildIndexes => "Building indexes",
+            StageKind::StartExplorers => "Starting explorers",
+            StageKind::StartApps => "Starting apps",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MigrationStage {
+    pub kind: StageKind,
+    pub services: Vec<String>,
+}
+
+/// Steps from the installed preset to another
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MigrationPlan {
+    pub from: InstallationPreset,
+    pub to: InstallationPreset,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub kept: Vec<String>,
+    /// In execution order; stages with nothing to do are left out
+    pub stages: Vec<MigrationStage>,
+    /// Longest wait for one index to catch up
+    pub wait_timeout_secs: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MigrationPlan {
+    /// Diff two service sets and order the work
+    pub fn new(
+        from: InstallationPreset,
+        current: &[String],
+        to: InstallationPreset,
+        target: &[String],
+        wait_timeout_secs: u64,
+    ) -> Self {
+        let added: Vec<String> = target
+            .iter()
+            .filter(|s| !current.contains(s))
+            .cloned()
+            .collect();
+        let removed: Vec<String> = current
+            .iter()
+            .filter(|s| !target.contains(s))
+            .cloned()
+            .collect();
+        let kept: Vec<String> = target
+            .iter()
+            .filter(|s| current.contains(s))
+            .cloned()
+            .collect();
+
+        let categories: HashMap<String, ServiceCategory> = get_all_services()
+            .into_iter()
+            .map(|s| (s.id, s.category))
+            .collect();
+        let stage_of = |service: &str| {
+            if INDEX_SERVICES.contains(&service) {
+                StageKind::BuildIndexes
+            } else {
+                match categories.get(service) {
+                    Some(ServiceCategory::Explorer) => StageKind::StartExplorers,
+                    Some(ServiceCategory::App) => StageKind::StartApps,
+                    _ => StageKind::StartInfrastructure,
+                }
+            }
+        };
+
+        let mut stages = Vec::new();
+        // Dependents go first so nothing is left reading from a removed index
+        let mut removal = removed.clone();
+        removal.sort_by_key(|s| std::cmp::Reverse(stage_of(s) as u8));
+        if !removal.is_empty() {
+            stages.push(MigrationStage {
+                kind: StageKind::RemoveServices,
+                services: removal,
+            });
+        }
+        for kind in [
+            StageKind::StartInfrastructure,
+            StageKind::BuildIndexes,
+            StageKind::StartExplorers,
+            StageKind::StartApps,
+        ] {
+            let services: Vec<String> = added
+                .iter()
+                .filter(|s| stage_of(s) == kind)
+                .cloned()
+                .collect();
+            if !services.is_empty() {
+                stages.push(MigrationStage { kind, services });
+            }
+        }
+
+        Self {
+            from,
+            to,
+            added,
+            removed,
+            kept,
+            stages,
+            wait_timeout_secs,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// How far an index has caught up
+struct Progress {
+    /// None while the index isn't answering yet
+    height: Option<i64>,
+    target: Option<i64>,
+}
+
+impl Progress {
+    fn caught_up(&self) -> bool {
+        matches!((self.height, self.target), (Some(h), Some(t)) if h >= t)
+    }
+}
+
+/// Execute a plan, reporting progress as installation stream events
+pub fn run(
+    state: Arc<AppState>,
+    plan: MigrationPlan,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    async_stream::stream! {
+        yield Ok(Event::default().data(format!(
+            "[INFO] Migrating from {} to {}: {} to add, {} to remove, {} unchanged",
+            plan.from, plan.to, plan.added.len(), plan.removed.len(), plan.kept.len()
+        )));
+
+        let total = plan.stages.len();
+        for (i, stage) in plan.stages.iter().enumerate() {
+            yield Ok(Event::default().data(format!(
+                "[STEP] {}/{} {}: {}",
+                i + 1, total, stage.kind.describe(), stage.services.join(", ")
+            )));
+
+            if stage.kind == StageKind::RemoveServices {
+                let services = get_all_services();
+                for id in &stage.services {
+                    let Some(service) = services.iter().find(|s| &s.id == id) else {
+                        continue;
+                    };
+                    for container in &service.containers {
+                        let options = Some(RemoveContainerOptions {
+                            force: true,
+                            v: false,
+                            ..Default::default()
+                        });
+                        match state.docker.remove_container(container, options).await {
+                            Ok(()) => yield Ok(Event::default().data(format!("[REMOVE] {}", container))),
+                            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => {}
+                            Err(e) => {
+                                yield Ok(Event::default().data(format!("[ERROR] Failed to remove {}: {}", container, e)));
+                                yield Ok(Event::default().event("complete").data("error"));
+                                return;
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let mut cmd = compose_up(&stage.services);
+            match cmd.spawn() {
+                Ok(mut child) => {
+                    // docker compose reports progress on stderr
+                    if let Some(stderr) = child.stderr.take() {
+                        let mut lines = BufReader::new(stderr).lines();
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            let clean_line = strip_ansi_codes(&line);
+                            if !clean_line.trim().is_empty() {
+                                yield Ok(Event::default().data(format!("[BUILD] {}", clean_line)));
+                            }
+                        }
+                    }
+                    match child.wait().await {
+                        Ok(status) if status.success() => {}
+                        Ok(status) => {
+                            yield Ok(Event::default().data(format!("[ERROR] docker compose exited with {}", status)));
+                            yield Ok(Event::default().event("complete").data("error"));
+                            return;
+                        }
+                        Err(e) => {
+                            yield Ok(Event::default().data(format!("[ERROR] Failed to wait for docker compose: {}", e)));
+                            yield Ok(Event::default().event("complete").data("error"));
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    yield Ok(Event::default().data(format!("[ERROR] Failed to start docker compose: {}", e)));
+                    yield Ok(Event::default().event("complete").data("error"));
+                    return;
+                }
+            }
+
+            if !matches!(stage.kind, StageKind::BuildIndexes | StageKind::StartApps) {
+                continue;
+            }
+            for service in &stage.services {
+                if SEPARATE_DATABASE_APPS.contains(&service.as_str()) {
+                    yield Ok(Event::default().data(format!(
+                        "[REINDEX] {} indexes into its own database and catches up in the background",
+                        service
+                    )));
+                    continue;
+                }
+                if stage.kind == StageKind::StartApps
+                    && !APP_CURSORS.iter().any(|(app, _)| app == service)
+                {
+                    continue;
+                }
+
+                let deadline = Instant::now() + Duration::from_secs(plan.wait_timeout_secs);
+                loop {
+                    let progress = index_progress(&state, service).await;
+                    if progress.caught_up() {
+                        yield Ok(Event::default().data(format!(
+                            "[REINDEX] {} caught up at block {}",
+                            service,
+                            progress.height.unwrap_or_default()
+                        )));
+                        break;
+                    }
+                    if Instant::now() >= deadline {
+                        yield Ok(Event::default().data(format!(
+                            "[WARN] {} is still catching up after {}s; continuing while it finishes in the background",
+                            service, plan.wait_timeout_secs
+                        )));
+                        break;
+                    }
+                    yield Ok(Event::default().data(match (progress.height, progress.target) {
+                        (Some(height), Some(target)) if target > 0 => format!(
+                            "[REINDEX] {} at block {} of {} ({:.1}%)",
+                            service, height, target, height as f64 * 100.0 / target as f64
+                        ),
+                        (Some(height), _) => format!("[REINDEX] {} at block {}", service, height),
+                        (None, _) => format!("[REINDEX] {} is building its index", service),
+                    }));
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+
+        match finish(&state, &plan).await {
+            Ok(()) => {
+                info!("Migrated installation from {} to {}", plan.from, plan.to);
+                yield Ok(Event::default().data(format!("[SUCCESS] Migrated to the {} preset", plan.to)));
+                yield Ok(Event::default().event("complete").data("success"));
+            }
+            Err(e) => {
+                error!("Failed to record migration to {}: {}", plan.to, e);
+                yield Ok(Event::default().data(format!("[ERROR] Services migrated but the installation config was not updated: {}", e)));
+                yield Ok(Event::default().event("complete").data("error"));
+            }
+        }
+    }
+}
+
+/// `docker compose up` for services and their dependencies
+///
+/// `--no-recreate` leaves running services alone, the dashboard included,
+/// and there's no `--remove-orphans` so removed services' volumes stay.
+fn compose_up(services: &[String]) -> Command {
+    let mut profiles: Vec<String> = Vec::new();
+    for service in services {
+        for profile in get_service_profiles(service) {
+            if !profiles.contains(&profile) {
+                profiles.push(profile);
+            }
+        }
+    }
+
+    let mut cmd = Command::new("docker");
+    cmd.current_dir("/anchor-project");
+    cmd.arg("compose");
+    for profile in &profiles {
+        cmd.arg("--profile");
+        cmd.arg(profile);
+    }
+    cmd.args(["up", "-d", "--no-recreate"]);
+    cmd.stdout(std::process::Stdio::null());
+    cmd.stderr(std::process::Stdio::piped());
+    cmd
+}
+
+/// Save the new preset, clear the plan, and point dependents at an
+/// Electrum server that is still installed
+async fn finish(state: &Arc<AppState>, plan: &MigrationPlan) -> anyhow::Result<()> {
+    let pool = state
+        .db_pool
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Database not available"))?;
+
+    let installed: Vec<&String> = plan.kept.iter().chain(&plan.added).collect();
+    let services: HashMap<&String, bool> = installed.iter().map(|s| (*s, true)).collect();
+    sqlx::query(
+        "UPDATE installation_config SET preset = $1, services = $2, pending_migration = NULL, \
+         updated_at = NOW() WHERE id = 1",
+    )
+    .bind(plan.to.to_string())
+    .bind(serde_json::to_value(&services)?)
+    .execute(pool)
+    .await?;
+
+    if let Ok(configured) = electrum::get_configured_server(state).await {
+        let installed = |server: ElectrumServer| installed.iter().any(|s| *s == server.host());
+        let other = match configured {
+            ElectrumServer::Electrs => ElectrumServer::Fulcrum,
+            ElectrumServer::Fulcrum => ElectrumServer::Electrs,
+        };
+        if !installed(configured) && installed(other) {
+            electrum::save_configured_server(state, other)
+                .await
+                .map_err(anyhow::Error::msg)?;
+            info!("Default Electrum server switched to {}", other);
+        }
+    }
+
+    Ok(())
+}
+
+async fn index_progress(state: &Arc<AppState>, service: &str) -> Progress {
+    match service {
+        "core-indexer" => Progress {
+            height: anchor_indexer_height(state).await,
+            target: block_count(state).await,
+        },
+        "core-electrs" => Progress {
+            height: electrum_height(ElectrumServer::Electrs).await,
+            target: block_count(state).await,
+        },
+        "core-fulcrum" => Progress {
+            height: electrum_height(ElectrumServer::Fulcrum).await,
+            target: block_count(state).await,
+        },
+        app => {
+            let height = match (
+                &state.db_pool,
+                APP_CURSORS.iter().find(|(id, _)| *id == app),
+            ) {
+                (Some(pool), Some((_, table))) => sqlx::query_scalar::<_, Option<i32>>(&format!(
+                    "SELECT last_block_height FROM {} WHERE id = 1",
+                    table
+                ))
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten()
+                .flatten()
+                .map(i64::from),
+                _ => None,
+            };
+            Progress {
+                height,
+                target: anchor_indexer_height(state).await,
+            }
+        }
+    }
+}
+
+async fn anchor_indexer_height(state: &Arc<AppState>) -> Option<i64> {
+    let pool = state.db_pool.as_ref()?;
+    sqlx::query_scalar::<_, i32>("SELECT last_block_height FROM indexer_state WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .map(i64::from)
+}
+
+/// Height of the node's best chain
+async fn block_count(state: &Arc<AppState>) -> Option<i64> {
+    let config = &state.config;
+    let response: serde_json::Value = state
+        .http_client
+        .post(&config.bitcoin_rpc_url)
+        .basic_auth(&config.bitcoin_rpc_user, Some(&config.bitcoin_rpc_password))
+        .json(&serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": "dashboard-migration",
+            "method": "getblockcount",
+            "params": []
+        }))
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+    response["result"].as_i64()
+}
+
+/// Tip height an Electrum server reports
+///
+/// Both electrs and Fulcrum refuse connections until their initial index
+/// is built, so no answer means the index is still being built.
+async fn electrum_height(server: ElectrumServer) -> Option<i64> {
+    let request = async {
+        let mut stream = TcpStream::connect((server.host(), server.port()))
+            .await
+            .ok()?;
+        stream
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"blockchain.headers.subscribe\",\"params\":[]}\n")
+            .await
+            .ok()?;
+        let mut line = String::new();
+        BufReader::new(stream.take(64 * 1024))
+            .read_line(&mut line)
+            .await
+            .ok()?;
+        let response: serde_json::Value = serde_json::from_str(&line).ok()?;
+        response["result"]["height"].as_i64()
+    };
+    tokio::time::timeout(Duration::from_secs(10), request)
+        .await
+        .ok()
+        .flatten()
+}
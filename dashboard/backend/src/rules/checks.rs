@@ -0,0 +1,533 @@
+//! Rule evaluation
+//!
+//! Each rule type reads its options from the rule's `config` JSON; missing
+//! fields take the defaults below. Event rules start from the current
+//! position the first time they run, so enabling one doesn't replay history.
+
+use anchor_specs::text::TextSpec;
+use anchor_specs::KindSpec;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+
+use super::{Alert, Evaluation, RuleEngine, RuleType};
+
+/// Most events reported per rule per evaluation
+const MAX_EVENTS: i64 = 50;
+
+/// Longest reply text quoted in a notification
+const PREVIEW_CHARS: usize = 140;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThreadReplyOptions {
+    /// Addresses whose messages are watched for replies
+    addresses: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DomainRegistrationOptions {
+    /// Warn when an unconfirmed registration is this close to lapsing
+    #[serde(default = "default_expiring_within")]
+    expiring_within_minutes: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TokenReceivedOptions {
+    /// Addresses receiving transfers
+    addresses: Vec<String>,
+    /// Only this ticker; any token if unset
+    #[serde(default)]
+    ticker: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DiskUsageOptions {
+    /// Filesystem to check, as seen by the dashboard container
+    #[serde(default = "default_path")]
+    path: String,
+    #[serde(default = "default_threshold")]
+    threshold_percent: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IndexerLagOptions {
+    #[serde(default = "default_max_lag")]
+    max_lag_blocks: i64,
+}
+
+/// Cursor of an event rule
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CursorState {
+    last_id: Option<i32>,
+}
+
+/// Edge state of a threshold rule
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ThresholdState {
+    breached: bool,
+}
+
+/// Pending registrations seen at the last evaluation, by id
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DomainState {
+    pending: HashMap<i32, TrackedRegistration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackedRegistration {
+    name: String,
+    txid: String,
+    /// The lapse warning was sent
+    warned: bool,
+}
+
+fn default_expiring_within() -> i32 {
+    15
+}
+
+fn default_path() -> String {
+    "/".to_string()
+}
+
+fn default_threshold() -> f64 {
+    90.0
+}
+
+fn default_max_lag() -> i64 {
+    6
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(config: &serde_json::Value) -> Result<T> {
+    let config = if config.is_null() {
+        serde_json::json!({})
+    } else {
+        config.clone()
+    };
+    serde_json::from_value(config).map_err(|e| anyhow!("invalid rule config: {}", e))
+}
+
+/// Load a rule's state, starting over if it doesn't match the rule type
+fn load_state<T: for<'de> Deserialize<'de> + Default>(state: &serde_json::Value) -> T {
+    serde_json::from_value(state.clone()).unwrap_or_default()
+}
+
+fn check_addresses(addresses: &[String]) -> Result<()> {
+    if addresses.is_empty() || addresses.iter().any(|a| a.trim().is_empty()) {
+        bail!("addresses must list at least one address");
+    }
+    Ok(())
+}
+
+/// Check a rule's options before saving them
+pub fn validate_config(rule_type: RuleType, config: &serde_json::Value) -> Result<()> {
+    match rule_type {
+        RuleType::ThreadReply => {
+            let options: ThreadReplyOptions = parse(config)?;
+            check_addresses(&options.addresses)?;
+        }
+        RuleType::DomainRegistration => {
+            let options: DomainRegistrationOptions = parse(config)?;
+            if options.expiring_within_minutes < 1 {
+                bail!("expiring_within_minutes must be at least 1");
+            }
+        }
+        RuleType::TokenReceived => {
+            let options: TokenReceivedOptions = parse(config)?;
+            check_addresses(&options.addresses)?;
+        }
+        RuleType::DiskUsage => {
+            let options: DiskUsageOptions = parse(config)?;
+            if !(options.threshold_percent > 0.0 && options.threshold_percent <= 100.0) {
+                bail!("threshold_percent must be between 0 and 100");
+            }
+            if !options.path.starts_with('/') {
+                bail!("path must be absolute");
+            }
+        }
+        RuleType::IndexerLag => {
+            let options: IndexerLagOptions = parse(config)?;
+            if options.max_lag_blocks < 1 {
+                bail!("max_lag_blocks must be at least 1");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(super) async fn evaluate(
+    engine: &RuleEngine,
+    rule_type: RuleType,
+    config: &serde_json::Value,
+    state: &serde_json::Value,
+) -> Result<Evaluation> {
+    match rule_type {
+        RuleType::ThreadReply => thread_reply(engine, parse(config)?, load_state(state)).await,
+        RuleType::DomainRegistration => {
+            domain_registration(engine, parse(config)?, load_state(state)).await
+        }
+        RuleType::TokenReceived => token_received(engine, parse(config)?, load_state(state)).await,
+        RuleType::DiskUsage => disk_usage(parse(config)?, load_state(state)).await,
+        RuleType::IndexerLag => indexer_lag(engine, parse(config)?, load_state(state)).await,
+    }
+}
+
+fn to_state<T: Serialize>(state: &T) -> Result<serde_json::Value> {
+    Ok(serde_json::to_value(state)?)
+}
+
+/// Last id of a table, where a new event cursor starts
+async fn max_id(engine: &RuleEngine, table: &str) -> Result<i32> {
+    let id: Option<i32> = sqlx::query_scalar(&format!("SELECT MAX(id) FROM {}", table))
+        .fetch_one(&engine.pool)
+        .await?;
+    Ok(id.unwrap_or(0))
+}
+
+/// Thread messages anchored to a message created by a watched address
+async fn thread_reply(
+    engine: &RuleEngine,
+    options: ThreadReplyOptions,
+    state: CursorState,
+) -> Result<Evaluation> {
+    let Some(last_id) = state.last_id else {
+        let last_id = Some(max_id(engine, "messages").await?);
+        return Ok(Evaluation {
+            alerts: Vec::new(),
+            state: to_state(&CursorState { last_id })?,
+        });
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (m.id) m.id, m.body, m.creator_address, p.creator_address AS parent_address
+        FROM messages m
+        JOIN anchors a ON a.message_id = m.id
+        JOIN messages p ON p.id = a.resolved_message_id
+        WHERE m.kind = 1
+          AND m.id > $2
+          AND p.creator_address = ANY($1)
+          AND NOT (COALESCE(m.creator_address, '') = ANY($1))
+        ORDER BY m.id
+        LIMIT $3
+        "#,
+    )
+    .bind(&options.addresses)
+    .bind(last_id)
+    .bind(MAX_EVENTS)
+    .fetch_all(&engine.pool)
+    .await
+    .context("failed to query replies")?;
+
+    let mut alerts = Vec::new();
+    let mut cursor = last_id;
+    for row in &rows {
+        cursor = row.get("id");
+        let body: Vec<u8> = row.get("body");
+        let from: Option<String> = row.get("creator_address");
+        let to: String = row.get("parent_address");
+        let text = TextSpec::from_bytes(&body)
+            .map(|spec| preview(&spec.text))
+            .unwrap_or_default();
+        alerts.push(Alert {
+            title: format!(
+                "New reply from {}",
+                from.as_deref().map(short).unwrap_or("unknown")
+            ),
+            message: format!("Reply to {}: {}", short(&to), text),
+        });
+    }
+
+    Ok(Evaluation {
+        alerts,
+        state: to_state(&CursorState {
+            last_id: Some(cursor),
+        })?,
+    })
+}
+
+/// This node's domain registrations that are about to lapse unconfirmed, or
+/// that someone else's registration beat
+///
+/// Anchor domains never expire and aren't auctioned; the only way to lose a
+/// name is to have a pending registration overtaken before it confirms.
+async fn domain_registration(
+    engine: &RuleEngine,
+    options: DomainRegistrationOptions,
+    state: DomainState,
+) -> Result<Evaluation> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, domain_name, encode(txid, 'hex') AS txid,
+               expires_at <= NOW() + make_interval(mins => $1) AS expiring
+        FROM pending_transactions
+        WHERE operation = 1 AND expires_at > NOW()
+        "#,
+    )
+    .bind(options.expiring_within_minutes)
+    .fetch_all(&engine.pool)
+    .await
+    .context("failed to query pending registrations")?;
+
+    let mut alerts = Vec::new();
+    let mut pending = HashMap::new();
+    for row in &rows {
+        let id: i32 = row.get("id");
+        let name: String = row.get("domain_name");
+        let expiring: bool = row.get("expiring");
+        let warned = state.pending.get(&id).is_some_and(|p| p.warned);
+        if expiring && !warned {
+            alerts.push(Alert {
+                title: format!("Registration of {} is about to lapse", name),
+                message: format!(
+                    "The registration of {} is still unconfirmed and will be dropped within {} minutes",
+                    name, options.expiring_within_minutes
+                ),
+            });
+        }
+        pending.insert(
+            id,
+            TrackedRegistration {
+                name,
+                txid: row.get("txid"),
+                warned: warned || expiring,
+            },
+        );
+    }
+
+    // Registrations that left the pending list either confirmed, lost the
+    // name to another registration, or lapsed
+    for (id, tracked) in &state.pending {
+        if pending.contains_key(id) {
+            continue;
+        }
+        let owner: Option<String> = sqlx::query_scalar(
+            "SELECT encode(txid, 'hex') FROM domains WHERE LOWER(name) = LOWER($1)",
+        )
+        .bind(&tracked.name)
+        .fetch_optional(&engine.pool)
+        .await
+        .context("failed to look up domain")?;
+        match owner {
+            Some(txid) if txid == tracked.txid => {}
+            Some(txid) => alerts.push(Alert {
+                title: format!("{} was registered by someone else", tracked.name),
+                message: format!(
+                    "Transaction {} registered {} before yours ({}) confirmed",
+                    short(&txid),
+                    tracked.name,
+                    short(&tracked.txid)
+                ),
+            }),
+            None => alerts.push(Alert {
+                title: format!("Registration of {} lapsed", tracked.name),
+                message: format!(
+                    "Transaction {} was not confirmed in time; {} is still available",
+                    short(&tracked.txid),
+                    tracked.name
+                ),
+            }),
+        }
+    }
+
+    Ok(Evaluation {
+        alerts,
+        state: to_state(&DomainState { pending })?,
+    })
+}
+
+/// Token transfers to a watched address from anyone else
+async fn token_received(
+    engine: &RuleEngine,
+    options: TokenReceivedOptions,
+    state: CursorState,
+) -> Result<Evaluation> {
+    let Some(last_id) = state.last_id else {
+        let last_id = Some(max_id(engine, "token_operations").await?);
+        return Ok(Evaluation {
+            alerts: Vec::new(),
+            state: to_state(&CursorState { last_id })?,
+        });
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT o.id, o.amount::TEXT AS amount, o.from_address, o.to_address, t.ticker, t.decimals
+        FROM token_operations o
+        JOIN tokens t ON t.id = o.token_id
+        WHERE o.operation = 3
+          AND o.id > $2
+          AND o.to_address = ANY($1)
+          AND NOT (COALESCE(o.from_address, '') = ANY($1))
+          AND ($3::TEXT IS NULL OR UPPER(t.ticker) = UPPER($3))
+        ORDER BY o.id
+        LIMIT $4
+        "#,
+    )
+    .bind(&options.addresses)
+    .bind(last_id)
+    .bind(&options.ticker)
+    .bind(MAX_EVENTS)
+    .fetch_all(&engine.pool)
+    .await
+    .context("failed to query token transfers")?;
+
+    let mut alerts = Vec::new();
+    let mut cursor = last_id;
+    for row in &rows {
+        cursor = row.get("id");
+        let amount: Option<String> = row.get("amount");
+        let decimals: i16 = row.get("decimals");
+        let ticker: String = row.get("ticker");
+        let from: Option<String> = row.get("from_address");
+        let to: String = row.get("to_address");
+        let amount = format_amount(amount.as_deref().unwrap_or("0"), decimals as usize);
+        alerts.push(Alert {
+            title: format!("Received {} {}", amount, ticker),
+            message: format!(
+                "{} {} sent to {} from {}",
+                amount,
+                ticker,
+                short(&to),
+                from.as_deref().map(short).unwrap_or("unknown")
+            ),
+        });
+    }
+
+    Ok(Evaluation {
+        alerts,
+        state: to_state(&CursorState {
+            last_id: Some(cursor),
+        })?,
+    })
+}
+
+async fn disk_usage(options: DiskUsageOptions, state: ThresholdState) -> Result<Evaluation> {
+    let output = tokio::process::Command::new("df")
+        .args(["-P", &options.path])
+        .output()
+        .await
+        .context("failed to run df")?;
+    if !output.status.success() {
+        bail!(
+            "df failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    // Second line, fifth column: "Use%" as e.g. "93%"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let used: f64 = stdout
+        .lines()
+        .nth(1)
+        .and_then(|line| line.split_whitespace().nth(4))
+        .and_then(|pct| pct.trim_end_matches('%').parse().ok())
+        .ok_or_else(|| anyhow!("unexpected df output"))?;
+
+    let breached = used >= options.threshold_percent;
+    let mut alerts = Vec::new();
+    if breached && !state.breached {
+        alerts.push(Alert {
+            title: format!("Disk {}% full", used),
+            message: format!(
+                "{} is {}% full, above the {}% threshold",
+                options.path, used, options.threshold_percent
+            ),
+        });
+    }
+
+    Ok(Evaluation {
+        alerts,
+        state: to_state(&ThresholdState { breached })?,
+    })
+}
+
+async fn indexer_lag(
+    engine: &RuleEngine,
+    options: IndexerLagOptions,
+    state: ThresholdState,
+) -> Result<Evaluation> {
+    let indexed_height: i32 =
+        sqlx::query_scalar("SELECT last_block_height FROM indexer_state WHERE id = 1")
+            .fetch_one(&engine.pool)
+            .await
+            .context("failed to read indexer state")?;
+    let tip = block_count(engine).await?;
+
+    let lag = tip - indexed_height as i64;
+    let breached = lag > options.max_lag_blocks;
+    let mut alerts = Vec::new();
+    if breached && !state.breached {
+        alerts.push(Alert {
+            title: format!("Indexer {} blocks behind", lag),
+            message: format!(
+                "The indexer is at block {} and the node at {}, more than {} blocks apart",
+                indexed_height, tip, options.max_lag_blocks
+            ),
+        });
+    }
+
+    Ok(Evaluation {
+        alerts,
+        state: to_state(&ThresholdState { breached })?,
+    })
+}
+
+/// Height of the node's best chain
+async fn block_count(engine: &RuleEngine) -> Result<i64> {
+    let config = &engine.config;
+    let response: serde_json::Value = engine
+        .http
+        .post(&config.bitcoin_rpc_url)
+        .basic_auth(&config.bitcoin_rpc_user, Some(&config.bitcoin_rpc_password))
+        .json(&serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": "dashboard-rules",
+            "method": "getblockcount",
+            "params": []
+        }))
+        .send()
+        .await
+        .context("failed to call getblockcount")?
+        .json()
+        .await
+        .context("invalid getblockcount response")?;
+
+    if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+        bail!("getblockcount failed: {}", error);
+    }
+    response["result"]
+        .as_i64()
+        .ok_or_else(|| anyhow!("getblockcount returned no height"))
+}
+
+/// Shorten an address or txid for a notification title
+fn short(s: &str) -> &str {
+    s.get(..16).unwrap_or(s)
+}
+
+fn preview(text: &str) -> String {
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Render a raw token amount with its decimals
+fn format_amount(raw: &str, decimals: usize) -> String {
+    if decimals == 0 {
+        return raw.to_string();
+    }
+    let padded = format!("{:0>width$}", raw, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
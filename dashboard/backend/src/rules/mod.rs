@@ -0,0 +1,321 @@
+//! Notification rules
+//!
+//! Rules live in the `notification_rules` table: each has a type,
+//! type-specific JSON options, and optional webhook and email targets. The
+//! engine evaluates every enabled rule once a minute. Event rules (replies,
+//! token transfers, domain registrations) keep a cursor in the rule's `state`
+//! so each event fires once; threshold rules (disk usage, indexer lag) fire
+//! when the threshold is crossed and again only after it has recovered.
+
+mod checks;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::channels::smtp::{self, SmtpConfig};
+use crate::config::Config;
+
+pub use checks::validate_config;
+
+/// Columns selected for a [`NotificationRule`]
+const RULE_COLUMNS: &str = "id, name, rule_type, enabled, config, severity, webhook_url, \
+     email_to, last_checked_at, last_triggered_at, last_error, created_at, updated_at";
+
+/// How often enabled rules are evaluated
+const EVALUATION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What a rule watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleType {
+    /// A thread message replying to one of the given addresses
+    ThreadReply,
+    /// A pending domain registration about to lapse or taken by someone else
+    DomainRegistration,
+    /// A token transfer to one of the given addresses
+    TokenReceived,
+    /// Disk usage above a percentage
+    DiskUsage,
+    /// Indexer more than N blocks behind the node
+    IndexerLag,
+}
+
+impl RuleType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleType::ThreadReply => "thread_reply",
+            RuleType::DomainRegistration => "domain_registration",
+            RuleType::TokenReceived => "token_received",
+            RuleType::DiskUsage => "disk_usage",
+            RuleType::IndexerLag => "indexer_lag",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "thread_reply" => Some(RuleType::ThreadReply),
+            "domain_registration" => Some(RuleType::DomainRegistration),
+            "token_received" => Some(RuleType::TokenReceived),
+            "disk_usage" => Some(RuleType::DiskUsage),
+            "indexer_lag" => Some(RuleType::IndexerLag),
+            _ => None,
+        }
+    }
+}
+
+/// A configured rule
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NotificationRule {
+    pub id: i32,
+    pub name: String,
+    pub rule_type: String,
+    pub enabled: bool,
+    /// Rule type specific options
+    pub config: serde_json::Value,
+    /// Severity of the notifications the rule creates
+    pub severity: String,
+    /// Called with a JSON body for every match
+    pub webhook_url: Option<String>,
+    /// Emailed for every match; needs `SMTP_HOST`
+    pub email_to: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationRule {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            rule_type: row.get("rule_type"),
+            enabled: row.get("enabled"),
+            config: row.get("config"),
+            severity: row.get("severity"),
+            webhook_url: row.get("webhook_url"),
+            email_to: row.get("email_to"),
+            last_checked_at: row.get("last_checked_at"),
+            last_triggered_at: row.get("last_triggered_at"),
+            last_error: row.get("last_error"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+}
+
+/// One match of a rule
+pub(crate) struct Alert {
+    pub title: String,
+    pub message: String,
+}
+
+/// Result of evaluating a rule once
+pub(crate) struct Evaluation {
+    pub alerts: Vec<Alert>,
+    /// State to store for the next evaluation
+    pub state: serde_json::Value,
+}
+
+/// Evaluates rules and delivers their alerts
+pub struct RuleEngine {
+    pub pool: PgPool,
+    http: reqwest::Client,
+    config: Config,
+    smtp: Option<SmtpConfig>,
+}
+
+impl RuleEngine {
+    pub fn new(pool: PgPool, http: reqwest::Client, config: Config) -> Self {
+        Self {
+            pool,
+            http,
+            config,
+            smtp: SmtpConfig::from_env(),
+        }
+    }
+
+    /// Whether rule emails can be sent
+    pub fn email_enabled(&self) -> bool {
+        self.smtp.is_some()
+    }
+
+    /// Load one rule
+    pub async fn get_rule(&self, rule_id: i32) -> Result<Option<NotificationRule>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM notification_rules WHERE id = $1",
+            RULE_COLUMNS
+        ))
+        .bind(rule_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(NotificationRule::from_row))
+    }
+
+    /// Load every rule, ordered by id
+    pub async fn list_rules(&self) -> Result<Vec<NotificationRule>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM notification_rules ORDER BY id",
+            RULE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(NotificationRule::from_row).collect())
+    }
+
+    /// Evaluate every enabled rule once
+    pub async fn evaluate_all(&self) -> Result<()> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, state FROM notification_rules WHERE enabled ORDER BY id",
+            RULE_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in &rows {
+            let rule = NotificationRule::from_row(row);
+            let state: serde_json::Value = row.get("state");
+            if let Err(e) = self.evaluate(&rule, state).await {
+                warn!("Rule '{}' failed: {:#}", rule.name, e);
+                self.record_error(rule.id, &format!("{:#}", e)).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn evaluate(&self, rule: &NotificationRule, state: serde_json::Value) -> Result<()> {
+        let Some(rule_type) = RuleType::parse(&rule.rule_type) else {
+            bail!("unknown rule type '{}'", rule.rule_type);
+        };
+        let evaluation = checks::evaluate(self, rule_type, &rule.config, &state).await?;
+
+        let mut errors = Vec::new();
+        for alert in &evaluation.alerts {
+            info!("Rule '{}' triggered: {}", rule.name, alert.title);
+            errors.extend(self.deliver(rule, alert).await);
+        }
+
+        sqlx::query(
+            "UPDATE notification_rules SET state = $1, last_checked_at = NOW(), \
+             last_triggered_at = CASE WHEN $2 THEN NOW() ELSE last_triggered_at END, \
+             last_error = $3 WHERE id = $4",
+        )
+        .bind(&evaluation.state)
+        .bind(!evaluation.alerts.is_empty())
+        .bind((!errors.is_empty()).then(|| errors.join("; ")))
+        .bind(rule.id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn record_error(&self, rule_id: i32, message: &str) {
+        let result = sqlx::query(
+            "UPDATE notification_rules SET last_checked_at = NOW(), last_error = $1 WHERE id = $2",
+        )
+        .bind(message)
+        .bind(rule_id)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = result {
+            error!("Failed to record rule error: {}", e);
+        }
+    }
+
+    /// Send a sample alert through every action of a rule
+    ///
+    /// Returns the delivery errors, if any.
+    pub async fn test(&self, rule: &NotificationRule) -> Vec<String> {
+        let alert = Alert {
+            title: format!("Test: {}", rule.name),
+            message: format!("Test notification for the '{}' rule", rule.name),
+        };
+        self.deliver(rule, &alert).await
+    }
+
+    /// Create the notification and run the rule's optional actions
+    ///
+    /// Returns the errors of failed actions; one failing action doesn't stop
+    /// the others.
+    async fn deliver(&self, rule: &NotificationRule, alert: &Alert) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Err(e) = sqlx::query(
+            "INSERT INTO notifications (notification_type, title, message, severity) VALUES ('rule', $1, $2, $3)",
+        )
+        .bind(&alert.title)
+        .bind(&alert.message)
+        .bind(&rule.severity)
+        .execute(&self.pool)
+        .await
+        {
+            errors.push(format!("notification: {}", e));
+        }
+
+        if let Some(url) = rule.webhook_url.as_deref() {
+            if let Err(e) = self.call_webhook(url, rule, alert).await {
+                errors.push(format!("webhook: {:#}", e));
+            }
+        }
+
+        if let Some(to) = rule.email_to.as_deref() {
+            let result = match &self.smtp {
+                Some(smtp) => smtp::send(smtp, to, &alert.title, &alert.message).await,
+                None => Err(anyhow::anyhow!("SMTP_HOST is not set")),
+            };
+            if let Err(e) = result {
+                errors.push(format!("email: {:#}", e));
+            }
+        }
+
+        for e in &errors {
+            warn!("Rule '{}' action failed: {}", rule.name, e);
+        }
+        errors
+    }
+
+    async fn call_webhook(&self, url: &str, rule: &NotificationRule, alert: &Alert) -> Result<()> {
+        let response = self
+            .http
+            .post(url)
+            .timeout(Duration::from_secs(10))
+            .json(&serde_json::json!({
+                "rule_id": rule.id,
+                "rule": rule.name,
+                "rule_type": rule.rule_type,
+                "title": alert.title,
+                "message": alert.message,
+                "severity": rule.severity,
+                "timestamp": Utc::now(),
+            }))
+            .send()
+            .await
+            .context("request failed")?;
+        if !response.status().is_success() {
+            bail!("returned {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate enabled rules in the background
+pub fn start(engine: Arc<RuleEngine>) {
+    tokio::spawn(async move {
+        info!("Starting notification rule engine");
+        let mut check_interval = interval(EVALUATION_INTERVAL);
+        loop {
+            check_interval.tick().await;
+            if let Err(e) = engine.evaluate_all().await {
+                warn!("Failed to evaluate notification rules: {}", e);
+            }
+        }
+    });
+}
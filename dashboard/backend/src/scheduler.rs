@@ -1,4 +1,4 @@
-//! Backup scheduler using cron
+//! Cron scheduling helpers and the backup settings schedule
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -9,6 +9,7 @@ use tracing::{error, info};
 use uuid::Uuid;
 
 use crate::backup::engine::{BackupEngine, BackupJob, BackupStatus, BackupTarget, BackupType};
+use crate::backup::verify::{VerificationKind, VerificationLog, VerifyOptions};
 use crate::backup::{database, volumes};
 use crate::backup_config::BackupConfig as Config;
 use crate::handlers::backup::BackupSettings;
@@ -40,6 +41,7 @@ impl BackupScheduler {
         config: Arc<Config>,
         job_history: Arc<RwLock<Vec<BackupJob>>>,
         current_job: Arc<RwLock<Option<BackupJob>>>,
+        verifications: Arc<VerificationLog>,
     ) -> Result<()> {
         // Remove existing job if any
         if let Some(uuid) = self.current_job_uuid.read().await.as_ref() {
@@ -52,7 +54,7 @@ impl BackupScheduler {
             return Ok(());
         }
 
-        let cron_expr = &settings.schedule.cron_expression;
+        let cron_expr = &normalize_cron(&settings.schedule.cron_expression)?;
         let target = match settings.schedule.target.as_str() {
             "s3" => BackupTarget::S3,
             "smb" => BackupTarget::Smb,
@@ -64,12 +66,14 @@ impl BackupScheduler {
         let config_clone = config.clone();
         let history_clone = job_history.clone();
         let current_clone = current_job.clone();
+        let verifications_clone = verifications.clone();
 
         let job = Job::new_async(cron_expr, move |_uuid, _lock| {
             let config = config_clone.clone();
             let target = target.clone();
             let history = history_clone.clone();
             let current = current_clone.clone();
+            let verifications = verifications_clone.clone();
 
             Box::pin(async move {
                 info!("Scheduled backup triggered");
@@ -93,6 +97,7 @@ impl BackupScheduler {
                     job_id,
                     history,
                     current,
+                    verifications,
                 )
                 .await;
             })
@@ -106,33 +111,43 @@ impl BackupScheduler {
     }
 
     pub fn get_next_run_time(cron_expr: &str) -> Option<DateTime<Utc>> {
-        use croner::Cron;
-
-        // tokio-cron-scheduler uses 6-field format (with seconds)
-        // croner also supports this format
-        // Try parsing directly first
-        if let Ok(cron) = Cron::new(cron_expr).parse() {
-            if let Ok(next) = cron.find_next_occurrence(&Utc::now(), false) {
-                return Some(next);
-            }
-        }
+        next_run_time(cron_expr)
+    }
+}
 
-        // If 6-field format fails, try removing seconds (first field) for 5-field
-        let parts: Vec<&str> = cron_expr.split_whitespace().collect();
-        if parts.len() == 6 {
-            let five_field = parts[1..].join(" ");
-            if let Ok(cron) = Cron::new(&five_field).parse() {
-                if let Ok(next) = cron.find_next_occurrence(&Utc::now(), false) {
-                    return Some(next);
-                }
-            }
-        }
+/// Convert a 5-field cron expression to the 6-field (with seconds) form
+/// tokio-cron-scheduler requires, validating it on the way
+pub fn normalize_cron(cron_expr: &str) -> Result<String> {
+    use croner::Cron;
 
-        None
-    }
+    let parts: Vec<&str> = cron_expr.split_whitespace().collect();
+    let normalized = match parts.len() {
+        5 => format!("0 {}", parts.join(" ")),
+        6 => parts.join(" "),
+        n => anyhow::bail!("cron expression needs 5 or 6 fields, got {}", n),
+    };
+    Cron::new(&normalized)
+        .with_seconds_required()
+        .parse()
+        .map_err(|e| anyhow::anyhow!("invalid cron expression '{}': {}", cron_expr, e))?;
+    Ok(normalized)
 }
 
-async fn run_scheduled_backup(
+/// Next time a 5- or 6-field cron expression fires
+pub fn next_run_time(cron_expr: &str) -> Option<DateTime<Utc>> {
+    use croner::Cron;
+
+    let normalized = normalize_cron(cron_expr).ok()?;
+    Cron::new(&normalized)
+        .with_seconds_required()
+        .parse()
+        .ok()?
+        .find_next_occurrence(&Utc::now(), false)
+        .ok()
+}
+
+/// Run a backup outside the HTTP handlers and record it in the backup history
+pub async fn run_scheduled_backup(
     config: &Config,
     target: BackupTarget,
     include_dbs: bool,
@@ -140,7 +155,8 @@ async fn run_scheduled_backup(
     job_id: String,
     job_history: Arc<RwLock<Vec<BackupJob>>>,
     current_job: Arc<RwLock<Option<BackupJob>>>,
-) {
+    verifications: Arc<VerificationLog>,
+) -> BackupJob {
     info!("Starting scheduled backup job {}", job_id);
 
     let started_at = Utc::now();
@@ -158,6 +174,7 @@ async fn run_scheduled_backup(
             target: target.clone(),
             size_bytes: None,
             files_count: None,
+            snapshot_id: None,
             error_message: None,
         });
     }
@@ -176,16 +193,7 @@ async fn run_scheduled_backup(
         if let Err(e) = tokio::fs::create_dir_all(&db_dir).await {
             error!("Failed to create db dir: {}", e);
         } else {
-            for db_config in database::get_anchor_databases() {
-                match database::pg_dump(&db_config, &db_dir).await {
-                    Ok(dump_path) => {
-                        paths_to_backup.push(dump_path);
-                    }
-                    Err(e) => {
-                        error!("Failed to dump {}: {}", db_config.name, e);
-                    }
-                }
-            }
+            paths_to_backup.extend(database::dump_all(&db_dir).await);
         }
     }
 
@@ -239,6 +247,7 @@ async fn run_scheduled_backup(
                 target,
                 size_bytes: None,
                 files_count: None,
+                snapshot_id: None,
                 error_message: Some(e.to_string()),
             }
         }
@@ -254,6 +263,20 @@ async fn run_scheduled_backup(
         }
     }
 
+    // Verify before clearing the current job, as the manual path does
+    if completed_job.status == BackupStatus::Completed {
+        verifications
+            .run(
+                &engine,
+                &config.backup_dir,
+                completed_job.target.clone(),
+                completed_job.snapshot_id.clone(),
+                VerificationKind::PostBackup,
+                VerifyOptions::POST_BACKUP,
+            )
+            .await;
+    }
+
     // Clear current job
     {
         let mut current = current_job.write().await;
@@ -264,4 +287,6 @@ async fn run_scheduled_backup(
         "Scheduled backup job {} completed with status {:?}",
         job_id, completed_job.status
     );
+
+    completed_job
 }
@@ -5,8 +5,9 @@ pub mod s3;
 pub mod smb;
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct StorageInfo {
     pub name: String,
     pub storage_type: StorageType,
@@ -16,7 +17,7 @@ pub struct StorageInfo {
     pub available_bytes: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum StorageType {
     Local,
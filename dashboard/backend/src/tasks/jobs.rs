@@ -0,0 +1,547 @@
+//! Task implementations
+//!
+//! Each task type reads its options from the task's `config` JSON; missing
+//! fields take the defaults below.
+
+use anyhow::{anyhow, bail, Context, Result};
+use bollard::image::PruneImagesOptions;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+use super::{Outcome, TaskRunner, TaskType};
+use crate::backup::engine::{BackupStatus, BackupTarget};
+use crate::backup::verify::{self, VerificationKind};
+use crate::scheduler::run_scheduled_backup;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackupOptions {
+    #[serde(default = "default_target")]
+    target: String,
+    #[serde(default = "default_true")]
+    include_databases: bool,
+    #[serde(default = "default_true")]
+    include_volumes: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DrillOptions {
+    #[serde(default = "default_target")]
+    target: String,
+    /// Percent of pack data `restic check` reads
+    #[serde(default = "default_read_data_percent")]
+    read_data_percent: u8,
+    /// Files restored from the newest snapshot
+    #[serde(default = "default_sample_files")]
+    sample_files: usize,
+    /// Larger files are only restored when nothing smaller exists
+    #[serde(default = "default_max_file_mb")]
+    max_file_mb: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConsolidationOptions {
+    /// Bitcoin Core wallet holding the UTXOs
+    #[serde(default = "default_wallet")]
+    wallet: String,
+    /// Only consolidate once at least this many spendable UTXOs exist
+    #[serde(default = "default_min_utxos")]
+    min_utxos: usize,
+    /// Most inputs swept in one transaction, smallest first
+    #[serde(default = "default_max_inputs")]
+    max_inputs: usize,
+    /// Fee rate in sat/vB
+    #[serde(default = "default_fee_rate")]
+    fee_rate: f64,
+    #[serde(default = "default_min_confirmations")]
+    min_confirmations: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyOptions {
+    /// Most recent message blocks to re-check against the node
+    #[serde(default = "default_sample_size")]
+    sample_size: i64,
+    /// Fail when the indexer is further behind the node's tip
+    #[serde(default = "default_max_lag")]
+    max_lag_blocks: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CleanupOptions {
+    /// Remove dangling Docker images
+    #[serde(default = "default_true")]
+    prune_images: bool,
+    /// Age after which leftover backup temp dirs are removed
+    #[serde(default = "default_temp_max_age")]
+    temp_max_age_hours: u64,
+    /// Task run history to keep
+    #[serde(default = "default_history_days")]
+    history_days: i32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_target() -> String {
+    "local".to_string()
+}
+
+fn default_wallet() -> String {
+    "anchor_wallet".to_string()
+}
+
+fn default_min_utxos() -> usize {
+    50
+}
+
+fn default_max_inputs() -> usize {
+    500
+}
+
+fn default_fee_rate() -> f64 {
+    2.0
+}
+
+fn default_min_confirmations() -> u32 {
+    6
+}
+
+fn default_sample_size() -> i64 {
+    100
+}
+
+fn default_max_lag() -> i64 {
+    6
+}
+
+fn default_temp_max_age() -> u64 {
+    24
+}
+
+fn default_history_days() -> i32 {
+    30
+}
+
+fn default_read_data_percent() -> u8 {
+    10
+}
+
+fn default_sample_files() -> usize {
+    20
+}
+
+fn default_max_file_mb() -> u64 {
+    64
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(config: &serde_json::Value) -> Result<T> {
+    let config = if config.is_null() {
+        serde_json::json!({})
+    } else {
+        config.clone()
+    };
+    serde_json::from_value(config).map_err(|e| anyhow!("invalid task config: {}", e))
+}
+
+/// Check a task's options before saving them
+pub fn validate_config(task_type: TaskType, config: &serde_json::Value) -> Result<()> {
+    match task_type {
+        TaskType::Backup => {
+            let options: BackupOptions = parse(config)?;
+            if !matches!(options.target.as_str(), "local" | "s3" | "smb") {
+                bail!("target must be local, s3, or smb");
+            }
+        }
+        TaskType::UtxoConsolidation => {
+            let options: ConsolidationOptions = parse(config)?;
+            if options.min_utxos < 2 || options.max_inputs < 2 {
+                bail!("min_utxos and max_inputs must be at least 2");
+            }
+            if !(options.fee_rate > 0.0 && options.fee_rate <= 1000.0) {
+                bail!("fee_rate must be between 0 and 1000 sat/vB");
+            }
+        }
+        TaskType::IndexerVerify => {
+            let options: VerifyOptions = parse(config)?;
+            if !(1..=10_000).contains(&options.sample_size) || options.max_lag_blocks < 0 {
+                bail!("sample_size must be 1-10000 and max_lag_blocks non-negative");
+            }
+        }
+        TaskType::DiskCleanup => {
+            let options: CleanupOptions = parse(config)?;
+            if options.history_days < 1 {
+                bail!("history_days must be at least 1");
+            }
+        }
+        TaskType::RestoreDrill => {
+            let options: DrillOptions = parse(config)?;
+            if !matches!(options.target.as_str(), "local" | "s3" | "smb") {
+                bail!("target must be local, s3, or smb");
+            }
+            if options.read_data_percent > 100 {
+                bail!("read_data_percent must be 0-100");
+            }
+            if !(1..=1000).contains(&options.sample_files) || options.max_file_mb < 1 {
+                bail!("sample_files must be 1-1000 and max_file_mb at least 1");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(super) async fn execute(
+    runner: &TaskRunner,
+    task_type: TaskType,
+    config: &serde_json::Value,
+) -> Result<Outcome> {
+    match task_type {
+        TaskType::Backup => backup(runner, parse(config)?).await,
+        TaskType::UtxoConsolidation => consolidate_utxos(runner, parse(config)?).await,
+        TaskType::IndexerVerify => verify_indexer(runner, parse(config)?).await,
+        TaskType::DiskCleanup => disk_cleanup(runner, parse(config)?).await,
+        TaskType::RestoreDrill => restore_drill(runner, parse(config)?).await,
+    }
+}
+
+async fn backup(runner: &TaskRunner, options: BackupOptions) -> Result<Outcome> {
+    let state = &runner.backup;
+    if state.current_job.read().await.is_some() {
+        return Ok(Outcome::Skipped(
+            "Another backup is in progress".to_string(),
+        ));
+    }
+
+    let target = match options.target.as_str() {
+        "s3" => BackupTarget::S3,
+        "smb" => BackupTarget::Smb,
+        _ => BackupTarget::Local,
+    };
+    let job = run_scheduled_backup(
+        &state.config,
+        target,
+        options.include_databases,
+        options.include_volumes,
+        Uuid::new_v4().to_string(),
+        state.job_history.clone(),
+        state.current_job.clone(),
+        state.verifications.clone(),
+    )
+    .await;
+
+    if job.status == BackupStatus::Failed {
+        bail!(
+            "{}",
+            job.error_message
+                .unwrap_or_else(|| "backup failed".to_string())
+        );
+    }
+    Ok(Outcome::Success(format!(
+        "Backup {} to {}: {} files, {} bytes",
+        job.id,
+        options.target,
+        job.files_count.unwrap_or(0),
+        job.size_bytes.unwrap_or(0)
+    )))
+}
+
+/// Verify the newest snapshot with a larger sample than post-backup checks
+async fn restore_drill(runner: &TaskRunner, options: DrillOptions) -> Result<Outcome> {
+    let state = &runner.backup;
+    if state.current_job.read().await.is_some() {
+        return Ok(Outcome::Skipped("A backup is in progress".to_string()));
+    }
+
+    let target = match options.target.as_str() {
+        "s3" => BackupTarget::S3,
+        "smb" => BackupTarget::Smb,
+        _ => BackupTarget::Local,
+    };
+    let report = state
+        .verifications
+        .run(
+            &state.engine,
+            &state.config.backup_dir,
+            target,
+            None,
+            VerificationKind::Drill,
+            verify::VerifyOptions {
+                read_data_percent: options.read_data_percent,
+                sample_files: options.sample_files,
+                max_file_bytes: options.max_file_mb << 20,
+            },
+        )
+        .await;
+
+    if !report.success {
+        bail!("{}", report.errors.join("; "));
+    }
+    Ok(Outcome::Success(format!(
+        "Snapshot {} on {}: check passed reading {}% of data, {}/{} files restored",
+        report.snapshot_id.as_deref().unwrap_or("?"),
+        options.target,
+        report.read_data_percent,
+        report.files_verified,
+        report.files_sampled
+    )))
+}
+
+/// A UTXO as listed by the wallet service
+#[derive(Debug, Deserialize)]
+struct WalletUtxo {
+    txid: String,
+    vout: u32,
+    amount: f64,
+    confirmations: u32,
+}
+
+/// Sweep small UTXOs into a fresh wallet address
+///
+/// Only UTXOs the wallet service reports as unlocked are spent, so outputs
+/// holding Anchor assets (domains, tokens) stay where they are. Locks are
+/// synced with the app backends first; if that fails the run aborts.
+async fn consolidate_utxos(runner: &TaskRunner, options: ConsolidationOptions) -> Result<Outcome> {
+    let wallet_url = &runner.config.wallet_url;
+    runner
+        .http
+        .post(format!("{}/wallet/utxos/sync-locks", wallet_url))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("failed to sync UTXO locks")?;
+
+    let mut utxos: Vec<WalletUtxo> = runner
+        .http
+        .get(format!("{}/wallet/utxos/unlocked", wallet_url))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .context("failed to list unlocked UTXOs")?
+        .json()
+        .await
+        .context("invalid UTXO list from wallet")?;
+    utxos.retain(|u| u.confirmations >= options.min_confirmations);
+
+    if utxos.len() < options.min_utxos {
+        return Ok(Outcome::Skipped(format!(
+            "{} spendable UTXOs, below the threshold of {}",
+            utxos.len(),
+            options.min_utxos
+        )));
+    }
+
+    utxos.sort_by(|a, b| a.amount.total_cmp(&b.amount));
+    utxos.truncate(options.max_inputs);
+    let total: f64 = utxos.iter().map(|u| u.amount).sum();
+    let inputs: Vec<serde_json::Value> = utxos
+        .iter()
+        .map(|u| serde_json::json!({ "txid": u.txid, "vout": u.vout }))
+        .collect();
+
+    let wallet_path = format!("/wallet/{}", options.wallet);
+    let address = rpc(runner, &wallet_path, "getnewaddress", serde_json::json!([]))
+        .await?
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("getnewaddress returned no address"))?;
+    let result = rpc(
+        runner,
+        &wallet_path,
+        "sendall",
+        serde_json::json!([[address], null, "unset", options.fee_rate, { "inputs": inputs }]),
+    )
+    .await?;
+
+    let txid = result
+        .get("txid")
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| anyhow!("sendall did not broadcast: {}", result))?;
+    Ok(Outcome::Success(format!(
+        "Consolidated {} UTXOs ({:.8} BTC) into {} in {}",
+        utxos.len(),
+        total,
+        address,
+        txid
+    )))
+}
+
+/// Compare the indexer's blocks with the node's active chain
+async fn verify_indexer(runner: &TaskRunner, options: VerifyOptions) -> Result<Outcome> {
+    let (indexed_height, indexed_hash): (i32, Option<Vec<u8>>) =
+        sqlx::query_as("SELECT last_block_height, last_block_hash FROM indexer_state WHERE id = 1")
+            .fetch_one(&runner.pool)
+            .await
+            .context("failed to read indexer state")?;
+    let tip = rpc(runner, "", "getblockcount", serde_json::json!([]))
+        .await?
+        .as_i64()
+        .ok_or_else(|| anyhow!("getblockcount returned no height"))?;
+
+    let mut problems = Vec::new();
+    let lag = tip - indexed_height as i64;
+    if lag > options.max_lag_blocks {
+        problems.push(format!(
+            "indexer is {} blocks behind the node (at {}, tip {})",
+            lag, indexed_height, tip
+        ));
+    }
+
+    if let Some(hash) = indexed_hash.filter(|_| indexed_height as i64 <= tip) {
+        if !block_matches(runner, indexed_height, &hash).await? {
+            problems.push(format!(
+                "indexed tip {} is not on the node's active chain",
+                indexed_height
+            ));
+        }
+    }
+
+    let ahead: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE block_height > $1")
+        .bind(indexed_height)
+        .fetch_one(&runner.pool)
+        .await?;
+    if ahead > 0 {
+        problems.push(format!(
+            "{} messages are above the indexed height {}",
+            ahead, indexed_height
+        ));
+    }
+
+    let blocks: Vec<(i32, Vec<u8>)> = sqlx::query_as(
+        "SELECT DISTINCT block_height, block_hash FROM messages \
+         WHERE block_height IS NOT NULL AND block_hash IS NOT NULL \
+         ORDER BY block_height DESC LIMIT $1",
+    )
+    .bind(options.sample_size)
+    .fetch_all(&runner.pool)
+    .await?;
+
+    let mut stale = Vec::new();
+    for (height, hash) in &blocks {
+        if *height as i64 > tip || !block_matches(runner, *height, hash).await? {
+            stale.push(height.to_string());
+        }
+    }
+    if !stale.is_empty() {
+        problems.push(format!(
+            "messages at heights {} are from blocks no longer in the active chain",
+            stale.join(", ")
+        ));
+    }
+
+    if !problems.is_empty() {
+        bail!("{}", problems.join("; "));
+    }
+    Ok(Outcome::Success(format!(
+        "Indexed to {} (node tip {}); {} message blocks match the node",
+        indexed_height,
+        tip,
+        blocks.len()
+    )))
+}
+
+/// Whether the node's block at `height` has this hash (stored in internal byte order)
+async fn block_matches(runner: &TaskRunner, height: i32, hash: &[u8]) -> Result<bool> {
+    let node_hash = rpc(runner, "", "getblockhash", serde_json::json!([height])).await?;
+    let mut reversed = hash.to_vec();
+    reversed.reverse();
+    Ok(node_hash.as_str() == Some(hex::encode(reversed).as_str()))
+}
+
+async fn disk_cleanup(runner: &TaskRunner, options: CleanupOptions) -> Result<Outcome> {
+    let mut report = Vec::new();
+
+    // Temp dirs of interrupted backups; never while a backup may be using one
+    if runner.backup.current_job.read().await.is_some() {
+        report.push("skipped backup temp dirs (backup in progress)".to_string());
+    } else {
+        let max_age = Duration::from_secs(options.temp_max_age_hours * 3600);
+        let mut removed = 0;
+        let backup_dir = &runner.backup.config.backup_dir;
+        if let Ok(mut entries) = tokio::fs::read_dir(backup_dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                if !entry.file_name().to_string_lossy().starts_with("temp-") {
+                    continue;
+                }
+                let old = entry
+                    .metadata()
+                    .await
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|t| SystemTime::now().duration_since(t).ok())
+                    .is_some_and(|age| age >= max_age);
+                if old && tokio::fs::remove_dir_all(entry.path()).await.is_ok() {
+                    removed += 1;
+                }
+            }
+        }
+        report.push(format!("removed {} stale backup temp dirs", removed));
+    }
+
+    if options.prune_images {
+        let mut filters = HashMap::new();
+        filters.insert("dangling", vec!["true"]);
+        let pruned = runner
+            .docker
+            .prune_images(Some(PruneImagesOptions { filters }))
+            .await
+            .context("failed to prune images")?;
+        report.push(format!(
+            "pruned {} dangling images ({} MB)",
+            pruned.images_deleted.map(|i| i.len()).unwrap_or(0),
+            pruned.space_reclaimed.unwrap_or(0) / 1_000_000
+        ));
+    }
+
+    let deleted = sqlx::query(
+        "DELETE FROM scheduled_task_runs \
+         WHERE finished_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(options.history_days)
+    .execute(&runner.pool)
+    .await?;
+    report.push(format!(
+        "deleted {} task runs older than {} days",
+        deleted.rows_affected(),
+        options.history_days
+    ));
+
+    Ok(Outcome::Success(report.join(", ")))
+}
+
+/// Call Bitcoin Core; `path` selects a wallet endpoint or is empty
+async fn rpc(
+    runner: &TaskRunner,
+    path: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let config = &runner.config;
+    let response: serde_json::Value = runner
+        .http
+        .post(format!("{}{}", config.bitcoin_rpc_url, path))
+        .basic_auth(&config.bitcoin_rpc_user, Some(&config.bitcoin_rpc_password))
+        .json(&serde_json::json!({
+            "jsonrpc": "1.0",
+            "id": "dashboard-tasks",
+            "method": method,
+            "params": params
+        }))
+        .send()
+        .await
+        .with_context(|| format!("failed to call {}", method))?
+        .json()
+        .await
+        .with_context(|| format!("invalid {} response", method))?;
+
+    if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+        bail!("{} failed: {}", method, error);
+    }
+    Ok(response["result"].clone())
+}
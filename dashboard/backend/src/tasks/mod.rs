@@ -0,0 +1,454 @@
+//! Scheduled maintenance tasks
+//!
+//! Tasks live in the `scheduled_tasks` table: each has a type, a cron
+//! expression, and type-specific JSON options. Enabled tasks are registered
+//! with the cron scheduler at startup and again whenever they are edited.
+//! Every run, scheduled or manual, is recorded in `scheduled_task_runs`, and
+//! the task row keeps the last status and error for quick display.
+
+mod jobs;
+
+use anyhow::{anyhow, Result};
+use bollard::Docker;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tokio_cron_scheduler::{Job, JobScheduler};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::handlers::backup::BackupState;
+use crate::scheduler::{next_run_time, normalize_cron};
+
+pub use jobs::validate_config;
+
+/// Columns selected for a [`ScheduledTask`]
+const TASK_COLUMNS: &str = "id, name, task_type, cron_expression, enabled, config, \
+     last_run_at, last_status, last_error, created_at, updated_at";
+
+/// What a task does when it fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskType {
+    /// Restic backup of databases and volumes
+    Backup,
+    /// Sweep small unlocked wallet UTXOs into one output
+    UtxoConsolidation,
+    /// Check the indexer's blocks against Bitcoin Core
+    IndexerVerify,
+    /// Remove stale backup temp dirs, dangling images, and old run history
+    DiskCleanup,
+    /// Check the backup repository and restore a sample of the newest snapshot
+    RestoreDrill,
+}
+
+impl TaskType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskType::Backup => "backup",
+            TaskType::UtxoConsolidation => "utxo_consolidation",
+            TaskType::IndexerVerify => "indexer_verify",
+            TaskType::DiskCleanup => "disk_cleanup",
+            TaskType::RestoreDrill => "restore_drill",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "backup" => Some(TaskType::Backup),
+            "utxo_consolidation" => Some(TaskType::UtxoConsolidation),
+            "indexer_verify" => Some(TaskType::IndexerVerify),
+            "disk_cleanup" => Some(TaskType::DiskCleanup),
+            "restore_drill" => Some(TaskType::RestoreDrill),
+            _ => None,
+        }
+    }
+}
+
+/// A configured task
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ScheduledTask {
+    pub id: i32,
+    pub name: String,
+    pub task_type: String,
+    pub cron_expression: String,
+    pub enabled: bool,
+    /// Task type specific options
+    pub config: serde_json::Value,
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// running, success, failed, skipped, or interrupted
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    /// Next scheduled run, if enabled
+    pub next_run_at: Option<DateTime<Utc>>,
+    /// Whether a run is in progress right now
+    pub running: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ScheduledTask {
+    fn from_row(row: &PgRow) -> Self {
+        let cron_expression: String = row.get("cron_expression");
+        let enabled: bool = row.get("enabled");
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            task_type: row.get("task_type"),
+            next_run_at: if enabled {
+                next_run_time(&cron_expression)
+            } else {
+                None
+            },
+            cron_expression,
+            enabled,
+            config: row.get("config"),
+            last_run_at: row.get("last_run_at"),
+            last_status: row.get("last_status"),
+            last_error: row.get("last_error"),
+            running: false,
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+}
+
+/// One execution of a task
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TaskRun {
+    pub id: i32,
+    pub task_id: i32,
+    /// schedule or manual
+    pub trigger: String,
+    /// running, success, failed, skipped, or interrupted
+    pub status: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl TaskRun {
+    pub fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            task_id: row.get("task_id"),
+            trigger: row.get("trigger"),
+            status: row.get("status"),
+            output: row.get("output"),
+            error: row.get("error"),
+            started_at: row.get("started_at"),
+            finished_at: row.get("finished_at"),
+        }
+    }
+}
+
+/// How a job finished, when it did not fail
+pub(crate) enum Outcome {
+    Success(String),
+    /// Nothing to do, or blocked by other work; not an error
+    Skipped(String),
+}
+
+/// Executes tasks and records their runs
+pub struct TaskRunner {
+    pub pool: PgPool,
+    docker: Docker,
+    http: reqwest::Client,
+    config: Config,
+    backup: Arc<BackupState>,
+    running: Mutex<HashSet<i32>>,
+}
+
+impl TaskRunner {
+    pub fn new(
+        pool: PgPool,
+        docker: Docker,
+        http: reqwest::Client,
+        config: Config,
+        backup: Arc<BackupState>,
+    ) -> Self {
+        Self {
+            pool,
+            docker,
+            http,
+            config,
+            backup,
+            running: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn is_running(&self, task_id: i32) -> bool {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(&task_id)
+    }
+
+    /// Load one task
+    pub async fn get_task(&self, task_id: i32) -> Result<Option<ScheduledTask>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM scheduled_tasks WHERE id = $1",
+            TASK_COLUMNS
+        ))
+        .bind(task_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| {
+            let mut task = ScheduledTask::from_row(&row);
+            task.running = self.is_running(task.id);
+            task
+        }))
+    }
+
+    /// Load every task, ordered by id
+    pub async fn list_tasks(&self) -> Result<Vec<ScheduledTask>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM scheduled_tasks ORDER BY id",
+            TASK_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let mut task = ScheduledTask::from_row(row);
+                task.running = self.is_running(task.id);
+                task
+            })
+            .collect())
+    }
+
+    /// Start a run in the background
+    ///
+    /// Returns the run id, or `None` if the task is already running.
+    pub async fn trigger(
+        self: &Arc<Self>,
+        task: &ScheduledTask,
+        trigger: &str,
+    ) -> Result<Option<i32>> {
+        let task_type = TaskType::parse(&task.task_type)
+            .ok_or_else(|| anyhow!("unknown task type '{}'", task.task_type))?;
+
+        if !self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(task.id)
+        {
+            return Ok(None);
+        }
+
+        let run_id = match self.start_run(task.id, trigger).await {
+            Ok(run_id) => run_id,
+            Err(e) => {
+                self.finish(task.id);
+                return Err(e);
+            }
+        };
+
+        let runner = self.clone();
+        let (task_id, name, config) = (task.id, task.name.clone(), task.config.clone());
+        tokio::spawn(async move {
+            info!(
+                "Task '{}' ({}) started, run {}",
+                name,
+                task_type.as_str(),
+                run_id
+            );
+            let result = jobs::execute(&runner, task_type, &config).await;
+            let (status, output, error) = match result {
+                Ok(Outcome::Success(output)) => ("success", Some(output), None),
+                Ok(Outcome::Skipped(output)) => ("skipped", Some(output), None),
+                Err(e) => ("failed", None, Some(format!("{:#}", e))),
+            };
+            match &error {
+                Some(e) => warn!("Task '{}' failed: {}", name, e),
+                None => info!("Task '{}' finished: {}", name, status),
+            }
+            if let Err(e) = runner
+                .record_result(task_id, run_id, status, output, error)
+                .await
+            {
+                error!("Failed to record result of task '{}': {}", name, e);
+            }
+            runner.finish(task_id);
+        });
+
+        Ok(Some(run_id))
+    }
+
+    fn finish(&self, task_id: i32) {
+        self.running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&task_id);
+    }
+
+    async fn start_run(&self, task_id: i32, trigger: &str) -> Result<i32> {
+        let run_id: i32 = sqlx::query_scalar(
+            "INSERT INTO scheduled_task_runs (task_id, trigger) VALUES ($1, $2) RETURNING id",
+        )
+        .bind(task_id)
+        .bind(trigger)
+        .fetch_one(&self.pool)
+        .await?;
+
+        sqlx::query(
+            "UPDATE scheduled_tasks SET last_run_at = NOW(), last_status = 'running' WHERE id = $1",
+        )
+        .bind(task_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(run_id)
+    }
+
+    async fn record_result(
+        &self,
+        task_id: i32,
+        run_id: i32,
+        status: &str,
+        output: Option<String>,
+        error: Option<String>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE scheduled_task_runs SET status = $1, output = $2, error = $3, finished_at = NOW() WHERE id = $4",
+        )
+        .bind(status)
+        .bind(&output)
+        .bind(&error)
+        .bind(run_id)
+        .execute(&self.pool)
+        .await?;
+
+        // A skipped run keeps the previous error visible
+        sqlx::query(
+            "UPDATE scheduled_tasks SET last_status = $1, \
+             last_error = CASE WHEN $1 = 'skipped' THEN last_error ELSE $2 END \
+             WHERE id = $3",
+        )
+        .bind(status)
+        .bind(&error)
+        .bind(task_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark runs left over from a previous process as interrupted
+    async fn recover_interrupted(&self) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE scheduled_task_runs SET status = 'interrupted', finished_at = NOW() WHERE status = 'running'",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "UPDATE scheduled_tasks SET last_status = 'interrupted' WHERE last_status = 'running'",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            warn!(
+                "Marked {} unfinished task runs as interrupted",
+                result.rows_affected()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Registers enabled tasks with the cron scheduler
+pub struct TaskScheduler {
+    scheduler: JobScheduler,
+    jobs: RwLock<HashMap<i32, Uuid>>,
+    /// Unavailable without a database
+    pub runner: Option<Arc<TaskRunner>>,
+}
+
+impl TaskScheduler {
+    pub async fn new(runner: Option<Arc<TaskRunner>>) -> Result<Self> {
+        Ok(Self {
+            scheduler: JobScheduler::new().await?,
+            jobs: RwLock::new(HashMap::new()),
+            runner,
+        })
+    }
+
+    /// Schedule every enabled task and start the scheduler
+    pub async fn start(&self) -> Result<()> {
+        let runner = self
+            .runner
+            .as_ref()
+            .ok_or_else(|| anyhow!("database not available"))?;
+
+        runner.recover_interrupted().await?;
+        for task in runner.list_tasks().await? {
+            if let Err(e) = self.schedule(&task).await {
+                warn!("Task '{}' not scheduled: {}", task.name, e);
+            }
+        }
+
+        self.scheduler.start().await?;
+        info!("Task scheduler started");
+        Ok(())
+    }
+
+    /// (Re)register a task after it was created or edited
+    pub async fn schedule(&self, task: &ScheduledTask) -> Result<()> {
+        self.unschedule(task.id).await;
+        if !task.enabled {
+            return Ok(());
+        }
+        let runner = self
+            .runner
+            .clone()
+            .ok_or_else(|| anyhow!("database not available"))?;
+
+        let cron_expr = normalize_cron(&task.cron_expression)?;
+        let task_id = task.id;
+        let job = Job::new_async(cron_expr.as_str(), move |_uuid, _lock| {
+            let runner = runner.clone();
+            Box::pin(async move {
+                // Reload so the run uses the stored options
+                match runner.get_task(task_id).await {
+                    Ok(Some(task)) if task.enabled => match runner.trigger(&task, "schedule").await
+                    {
+                        Ok(Some(_)) => {}
+                        Ok(None) => info!(
+                            "Skipping task '{}' - previous run still in progress",
+                            task.name
+                        ),
+                        Err(e) => error!("Failed to start task '{}': {}", task.name, e),
+                    },
+                    Ok(_) => {}
+                    Err(e) => error!("Failed to load task {}: {}", task_id, e),
+                }
+            })
+        })?;
+
+        let uuid = self.scheduler.add(job).await?;
+        self.jobs.write().await.insert(task.id, uuid);
+        info!("Task '{}' scheduled with cron: {}", task.name, cron_expr);
+        Ok(())
+    }
+
+    /// Remove a task's cron job, if any
+    pub async fn unschedule(&self, task_id: i32) {
+        if let Some(uuid) = self.jobs.write().await.remove(&task_id) {
+            if let Err(e) = self.scheduler.remove(&uuid).await {
+                warn!("Failed to remove job for task {}: {}", task_id, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Which wallet operations the dashboard proxies
+//!
+//! Every `/wallet/*` proxy route is an operation named by its path below
+//! `/wallet/` (`balance`, `mine`, `backup/mnemonic`, `utxos/lock`, ...).
+//! `WALLET_ALLOWED_OPERATIONS` limits the dashboard to the listed operations
+//! and `WALLET_DENIED_OPERATIONS` blocks operations on top of that; both are
+//! comma-separated, and a trailing `*` matches a prefix (`backup/*`). On
+//! mainnet (`BITCOIN_NETWORK=mainnet`) the deny list defaults to mining and
+//! mnemonic export.
+//!
+//! The policy is enforced before the request reaches the wallet service, and
+//! every call, allowed or not, is written to `wallet_audit_log` along with
+//! the response status and the client address. The client address is the
+//! peer's unless `WALLET_TRUST_PROXY` is set, in which case the address
+//! appended to `X-Forwarded-For` by the proxy in front is used.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::AppState;
+
+/// Operations denied on mainnet unless `WALLET_DENIED_OPERATIONS` is set
+const MAINNET_DENIED: &[&str] = &["mine", "backup/mnemonic"];
+
+/// Allow and deny lists of wallet operations
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WalletPolicy {
+    /// Bitcoin network the wallet runs on
+    pub network: String,
+    /// Only these operations are proxied; all of them when unset
+    pub allowed: Option<Vec<String>>,
+    /// Operations never proxied
+    pub denied: Vec<String>,
+    /// Audit the `X-Forwarded-For` client instead of the peer
+    pub trust_proxy: bool,
+}
+
+impl WalletPolicy {
+    /// Read the policy from the environment
+    pub fn from_env() -> Self {
+        let network = std::env::var("BITCOIN_NETWORK").unwrap_or_else(|_| "regtest".to_string());
+        let allowed = std::env::var("WALLET_ALLOWED_OPERATIONS")
+            .ok()
+            .map(|list| parse_list(&list));
+        let denied = match std::env::var("WALLET_DENIED_OPERATIONS") {
+            Ok(list) => parse_list(&list),
+            Err(_) if matches!(network.as_str(), "mainnet" | "bitcoin") => {
+                MAINNET_DENIED.iter().map(|op| op.to_string()).collect()
+            }
+            Err(_) => Vec::new(),
+        };
+        let trust_proxy = std::env::var("WALLET_TRUST_PROXY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(false);
+        Self {
+            network,
+            allowed,
+            denied,
+            trust_proxy,
+        }
+    }
+
+    /// Whether the dashboard may proxy `operation`
+    pub fn permits(&self, operation: &str) -> bool {
+        let allowed = self.allowed.as_ref().is_none_or(|allowed| {
+            allowed
+                .iter()
+                .any(|pattern| pattern_matches(pattern, operation))
+        });
+        allowed
+            && !self
+                .denied
+                .iter()
+                .any(|pattern| pattern_matches(pattern, operation))
+    }
+}
+
+fn parse_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|op| op.trim().trim_matches('/').to_string())
+        .filter(|op| !op.is_empty())
+        .collect()
+}
+
+fn pattern_matches(pattern: &str, operation: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => operation.starts_with(prefix),
+        None => pattern == operation,
+    }
+}
+
+/// Middleware on the wallet proxy routes: refuse operations the policy
+/// doesn't permit and audit every call
+pub async fn enforce(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path(), |path| path.as_str());
+    let operation = path.trim_start_matches("/wallet/").to_string();
+    let method = request.method().to_string();
+    let client = client_address(&state.wallet_policy, &request, peer).to_string();
+
+    let allowed = state.wallet_policy.permits(&operation);
+    let response = if allowed {
+        next.run(request).await
+    } else {
+        warn!("Refused wallet operation {} from {}", operation, client);
+        (
+            StatusCode::FORBIDDEN,
+            format!(
+                "Wallet operation '{}' is disabled on this dashboard",
+                operation
+            ),
+        )
+            .into_response()
+    };
+
+    let status = response.status().as_u16();
+    info!(
+        target: "wallet_audit",
+        operation = %operation,
+        method = %method,
+        allowed,
+        status,
+        client = %client,
+        "Wallet operation"
+    );
+    if let Some(pool) = state.db_pool.clone() {
+        tokio::spawn(async move {
+            if let Err(e) = record(&pool, &operation, &method, allowed, status, &client).await {
+                warn!("Failed to write wallet audit log: {}", e);
+            }
+        });
+    }
+    response
+}
+
+/// Address to audit: the peer, or the proxy-reported client
+fn client_address(policy: &WalletPolicy, request: &Request, peer: SocketAddr) -> IpAddr {
+    if policy.trust_proxy {
+        let forwarded = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(forwarded_ip);
+        if let Some(ip) = forwarded {
+            return ip;
+        }
+    }
+    peer.ip()
+}
+
+/// The last `X-Forwarded-For` entry, the one added by the trusted proxy
+fn forwarded_ip(header: &str) -> Option<IpAddr> {
+    header.rsplit(',').next()?.trim().parse().ok()
+}
+
+async fn record(
+    pool: &PgPool,
+    operation: &str,
+    method: &str,
+    allowed: bool,
+    status: u16,
+    client: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO wallet_audit_log (operation, method, allowed, status, client) \
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(operation)
+    .bind(method)
+    .bind(allowed)
+    .bind(status as i32)
+    .bind(client)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -256,7 +256,7 @@ export default function WalletPage() {
         </div>
 
         {balance && (
-          <div className="grid grid-cols-3 gap-6 pt-6 border-t border-border">
+          <div className="grid grid-cols-2 md:grid-cols-5 gap-6 pt-6 border-t border-border">
             <BalanceItem
               label={t('wallet.confirmed')}
               value={formatBtc(balance.confirmed)}
@@ -275,6 +275,18 @@ export default function WalletPage() {
               color="muted"
               t={t}
             />
+            <BalanceItem
+              label={t('wallet.locked')}
+              value={formatBtc(balance.locked || 0)}
+              color="muted"
+              t={t}
+            />
+            <BalanceItem
+              label={t('wallet.spendable')}
+              value={formatBtc(balance.spendable ?? balance.confirmed)}
+              color="success"
+              t={t}
+            />
           </div>
         )}
       </Section>
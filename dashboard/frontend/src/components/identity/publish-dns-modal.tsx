@@ -2,7 +2,13 @@
 
 import { useState, useEffect } from 'react';
 import { Globe, X, AlertCircle, RefreshCw, Check, Copy, ExternalLink, Loader2 } from 'lucide-react';
-import { Identity, publishIdentityToDns, fetchAssetsDomains, DomainAsset } from '@/lib/api';
+import {
+  Identity,
+  publishIdentityToDns,
+  fetchAssetsDomains,
+  DomainAsset,
+  apiError,
+} from '@/lib/api';
 
 interface PublishDnsModalProps {
   isOpen: boolean;
@@ -97,8 +103,7 @@ export function PublishDnsModal({ isOpen, onClose, identity, onSuccess }: Publis
       );
 
       if (!domainsResponse.ok) {
-        const errorText = await domainsResponse.text();
-        throw new Error(errorText || 'Failed to publish identity to domain');
+        throw await apiError(domainsResponse, 'Failed to publish identity to domain');
       }
 
       const domainsResult = await domainsResponse.json();
@@ -2,6 +2,24 @@ const API_URL = process.env.NEXT_PUBLIC_API_URL || 'http://localhost:8010';
 const TESTNET_URL = process.env.NEXT_PUBLIC_TESTNET_URL || 'http://localhost:8002';
 const WALLET_URL = process.env.NEXT_PUBLIC_WALLET_URL || 'http://localhost:8001';
 
+/** Error from an API call; `code` is the machine-readable code, e.g. `INSUFFICIENT_FUNDS` */
+export class ApiError extends Error {
+  constructor(message: string, public code?: string) {
+    super(message);
+  }
+}
+
+/** Build an ApiError from a failed response's `{ code, message }` body */
+export async function apiError(res: Response, fallback: string): Promise<ApiError> {
+  const text = await res.text();
+  try {
+    const body = JSON.parse(text);
+    return new ApiError(body.message || body.error || fallback, body.code);
+  } catch {
+    return new ApiError(text || fallback);
+  }
+}
+
 // Types
 export interface Container {
   id: string;
@@ -61,6 +79,8 @@ export interface WalletBalance {
   confirmed: number;
   unconfirmed: number;
   immature?: number;
+  locked?: number;
+  spendable?: number;
   total: number;
 }
 
@@ -357,6 +377,65 @@ export async function resetNodeSettings(): Promise<NodeSettingsResponse> {
   return res.json();
 }
 
+// Node storage mode (pruned/archival)
+export interface NodeStorageMode {
+  /** Prune target in MiB; 0 keeps every block */
+  prune_mb: number;
+  txindex: boolean;
+}
+
+export type NodeModeSwitchPhase =
+  | 'stopping_dependents'
+  | 'reconfiguring'
+  | 'restarting'
+  | 'syncing'
+  | 'building_txindex'
+  | 'resuming_dependents'
+  | 'completed'
+  | 'failed';
+
+export interface NodeModeSwitch {
+  id: string;
+  from: NodeStorageMode;
+  to: NodeStorageMode;
+  requires_reindex: boolean;
+  phase: NodeModeSwitchPhase;
+  paused_containers: string[];
+  progress: {
+    blocks: number;
+    headers: number;
+    verification_progress: number;
+    txindex_height: number | null;
+  } | null;
+  started_at: string;
+  completed_at: string | null;
+  error: string | null;
+}
+
+export interface NodeModeStatus {
+  mode: NodeStorageMode | null;
+  switch: NodeModeSwitch | null;
+}
+
+export async function fetchNodeMode(): Promise<NodeModeStatus> {
+  const res = await fetch(`${API_URL}/node/mode`);
+  if (!res.ok) throw new Error('Failed to fetch node mode');
+  return res.json();
+}
+
+export async function switchNodeMode(mode: NodeStorageMode): Promise<NodeModeSwitch> {
+  const res = await fetch(`${API_URL}/node/mode`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(mode),
+  });
+  if (!res.ok) {
+    const error = await res.text();
+    throw new Error(error || 'Failed to switch node mode');
+  }
+  return res.json();
+}
+
 // Testnet Types
 
 export interface TestnetConfig {
@@ -1083,6 +1162,101 @@ export async function resetInstallation(
   return res.json();
 }
 
+// Preset migration
+export type MigrationStageKind =
+  | 'remove_services'
+  | 'start_infrastructure'
+  | 'build_indexes'
+  | 'start_explorers'
+  | 'start_apps';
+
+export interface MigrationPlan {
+  from: InstallationPreset;
+  to: InstallationPreset;
+  added: string[];
+  removed: string[];
+  kept: string[];
+  stages: { kind: MigrationStageKind; services: string[] }[];
+  wait_timeout_secs: number;
+  created_at: string;
+}
+
+// Progress is reported by the installation stream once this returns
+export async function migrateInstallation(
+  preset: InstallationPreset,
+  services?: string[],
+  waitTimeoutSecs?: number
+): Promise<MigrationPlan> {
+  const res = await fetch(`${API_URL}/installation/migrate`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ preset, services, wait_timeout_secs: waitTimeoutSecs }),
+  });
+  if (!res.ok) {
+    const error = await res.text();
+    throw new Error(error || 'Failed to migrate installation');
+  }
+  return res.json();
+}
+
+// Installation preflight
+export type PreflightCheckStatus = 'pass' | 'warn' | 'fail';
+
+export interface HardwareProfile {
+  cpus: number | null;
+  memory_bytes: number | null;
+  disk_total_bytes: number | null;
+  disk_available_bytes: number | null;
+  docker_root_dir: string | null;
+  architecture: string | null;
+  operating_system: string | null;
+  kernel_version: string | null;
+  virtualization: 'docker_desktop' | 'wsl' | 'vm' | null;
+}
+
+export interface PreflightCheck {
+  id: string;
+  status: PreflightCheckStatus;
+  detail: string;
+}
+
+export interface PreflightRecommendation {
+  preset: InstallationPreset;
+  node_mode: 'archival' | 'pruned';
+  electrum_server: 'electrs' | 'fulcrum' | null;
+  services: string[];
+  reasons: string[];
+}
+
+export interface PreflightReport {
+  id: number | null;
+  hardware: HardwareProfile;
+  checks: PreflightCheck[];
+  recommendation: PreflightRecommendation;
+  warnings: string[];
+  created_at: string;
+}
+
+export async function runPreflight(): Promise<PreflightReport> {
+  const res = await fetch(`${API_URL}/installation/preflight`, { method: 'POST' });
+  if (!res.ok) throw new Error('Failed to run preflight checks');
+  return res.json();
+}
+
+export async function fetchPreflight(): Promise<PreflightReport | null> {
+  const res = await fetch(`${API_URL}/installation/preflight`);
+  if (res.status === 404) return null;
+  if (!res.ok) throw new Error('Failed to fetch preflight report');
+  return res.json();
+}
+
+export async function fetchPreflightHistory(): Promise<PreflightReport[]> {
+  const res = await fetch(`${API_URL}/installation/preflight/history`);
+  if (!res.ok) throw new Error('Failed to fetch preflight history');
+  const data = await res.json();
+  return data.reports;
+}
+
 // User Profile
 export interface UserProfile {
   name: string;
@@ -1201,6 +1375,208 @@ export async function clearReadNotifications(): Promise<NotificationActionRespon
   return res.json();
 }
 
+// Notification Rules
+export type NotificationRuleType =
+  | 'thread_reply'
+  | 'domain_registration'
+  | 'token_received'
+  | 'disk_usage'
+  | 'indexer_lag';
+
+export interface NotificationRule {
+  id: number;
+  name: string;
+  rule_type: NotificationRuleType;
+  enabled: boolean;
+  config: Record<string, unknown>;
+  severity: string;
+  webhook_url: string | null;
+  email_to: string | null;
+  last_checked_at: string | null;
+  last_triggered_at: string | null;
+  last_error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface NotificationRulesResponse {
+  rules: NotificationRule[];
+  email_enabled: boolean;
+}
+
+export interface NotificationRuleInput {
+  name?: string;
+  rule_type?: NotificationRuleType;
+  enabled?: boolean;
+  config?: Record<string, unknown>;
+  severity?: string;
+  webhook_url?: string;
+  email_to?: string;
+}
+
+export interface TestNotificationRuleResponse {
+  success: boolean;
+  message: string;
+  errors: string[];
+}
+
+export async function fetchNotificationRules(): Promise<NotificationRulesResponse> {
+  const res = await fetch(`${API_URL}/notifications/rules`);
+  if (!res.ok) throw new Error('Failed to fetch notification rules');
+  return res.json();
+}
+
+export async function createNotificationRule(rule: NotificationRuleInput): Promise<NotificationRule> {
+  const res = await fetch(`${API_URL}/notifications/rules`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(rule),
+  });
+  if (!res.ok) throw new Error(await res.text() || 'Failed to create notification rule');
+  return res.json();
+}
+
+export async function updateNotificationRule(
+  id: number,
+  rule: NotificationRuleInput
+): Promise<NotificationRule> {
+  const res = await fetch(`${API_URL}/notifications/rules/${id}`, {
+    method: 'PUT',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(rule),
+  });
+  if (!res.ok) throw new Error(await res.text() || 'Failed to update notification rule');
+  return res.json();
+}
+
+export async function deleteNotificationRule(id: number): Promise<NotificationActionResponse> {
+  const res = await fetch(`${API_URL}/notifications/rules/${id}`, {
+    method: 'DELETE',
+  });
+  if (!res.ok) throw new Error('Failed to delete notification rule');
+  return res.json();
+}
+
+export async function testNotificationRule(id: number): Promise<TestNotificationRuleResponse> {
+  const res = await fetch(`${API_URL}/notifications/rules/${id}/test`, {
+    method: 'POST',
+  });
+  if (!res.ok) throw new Error('Failed to test notification rule');
+  return res.json();
+}
+
+// Notification Channels
+export type NotificationChannelType = 'smtp' | 'telegram' | 'ntfy' | 'webhook';
+
+export interface NotificationChannel {
+  id: number;
+  name: string;
+  channel_type: NotificationChannelType;
+  enabled: boolean;
+  config: Record<string, unknown>;
+  min_severity: string;
+  notification_types: string[];
+  last_delivered_at: string | null;
+  last_error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface NotificationChannelInput {
+  name?: string;
+  channel_type?: NotificationChannelType;
+  enabled?: boolean;
+  config?: Record<string, unknown>;
+  min_severity?: string;
+  notification_types?: string[];
+}
+
+export interface TestNotificationChannelResponse {
+  success: boolean;
+  message: string;
+}
+
+export async function fetchNotificationChannels(): Promise<{ channels: NotificationChannel[] }> {
+  const res = await fetch(`${API_URL}/notifications/channels`);
+  if (!res.ok) throw new Error('Failed to fetch notification channels');
+  return res.json();
+}
+
+export async function createNotificationChannel(
+  channel: NotificationChannelInput
+): Promise<NotificationChannel> {
+  const res = await fetch(`${API_URL}/notifications/channels`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(channel),
+  });
+  if (!res.ok) throw new Error(await res.text() || 'Failed to create notification channel');
+  return res.json();
+}
+
+export async function updateNotificationChannel(
+  id: number,
+  channel: NotificationChannelInput
+): Promise<NotificationChannel> {
+  const res = await fetch(`${API_URL}/notifications/channels/${id}`, {
+    method: 'PUT',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(channel),
+  });
+  if (!res.ok) throw new Error(await res.text() || 'Failed to update notification channel');
+  return res.json();
+}
+
+export async function deleteNotificationChannel(id: number): Promise<NotificationActionResponse> {
+  const res = await fetch(`${API_URL}/notifications/channels/${id}`, {
+    method: 'DELETE',
+  });
+  if (!res.ok) throw new Error('Failed to delete notification channel');
+  return res.json();
+}
+
+export async function testNotificationChannel(
+  id: number
+): Promise<TestNotificationChannelResponse> {
+  const res = await fetch(`${API_URL}/notifications/channels/${id}/test`, {
+    method: 'POST',
+  });
+  if (!res.ok) throw new Error('Failed to test notification channel');
+  return res.json();
+}
+
+// Activity Feed
+export interface FeedItem {
+  id: number;
+  app: string;
+  kind: number;
+  kind_name: string;
+  address: string;
+  txid: string;
+  vout: number;
+  block_height: number | null;
+  created_at: string | null;
+  summary: string | null;
+}
+
+export interface FeedResponse {
+  items: FeedItem[];
+  next_cursor: number | null;
+}
+
+export async function fetchFeed(
+  addresses: string[],
+  options: { kinds?: string[]; cursor?: number; limit?: number } = {}
+): Promise<FeedResponse> {
+  const params = new URLSearchParams({ address: addresses.join(',') });
+  if (options.kinds?.length) params.set('kinds', options.kinds.join(','));
+  if (options.cursor !== undefined) params.set('cursor', String(options.cursor));
+  if (options.limit !== undefined) params.set('limit', String(options.limit));
+  const res = await fetch(`${API_URL}/feed?${params}`);
+  if (!res.ok) throw new Error('Failed to fetch activity feed');
+  return res.json();
+}
+
 // ============================================================================
 // Wallet Lock and Asset Management
 // ============================================================================
@@ -1392,6 +1768,32 @@ export interface WalletInfoResponse {
   has_mnemonic: boolean;
   addresses_used: number;
   bdk_enabled: boolean;
+  electrum_server: string | null;
+  electrum_servers: ElectrumServerHealth[];
+}
+
+export interface ElectrumServerHealth {
+  url: string;
+  onion: boolean;
+  healthy: boolean;
+  latency_ms: number | null;
+  height: number | null;
+  last_checked: string | null;
+  last_error: string | null;
+}
+
+export interface WalletSyncStatus {
+  syncing: boolean;
+  mode: 'full_scan' | 'incremental' | null;
+  scanned_scripts: number;
+  total_scripts: number | null;
+  progress_percent: number;
+  scanned_height: number;
+  chain_height: number | null;
+  last_sync_at: string | null;
+  last_full_scan_at: string | null;
+  last_duration_ms: number | null;
+  last_error: string | null;
 }
 
 export interface DescriptorsResponse {
@@ -1477,6 +1879,12 @@ export async function syncBdkWallet(): Promise<{ success: boolean; message: stri
   return res.json();
 }
 
+export async function fetchWalletSyncStatus(): Promise<WalletSyncStatus> {
+  const res = await fetch(`${API_URL}/wallet/sync-status`);
+  if (!res.ok) throw new Error('Failed to fetch wallet sync status');
+  return res.json();
+}
+
 export async function exportBackup(password: string): Promise<ExportBackupResponse> {
   const res = await fetch(`${API_URL}/wallet/backup/export`, {
     method: 'POST',
@@ -1885,8 +2293,7 @@ export async function createIdentity(request: CreateIdentityRequest): Promise<Id
     body: JSON.stringify(request),
   });
   if (!res.ok) {
-    const error = await res.text();
-    throw new Error(error || 'Failed to create identity');
+    throw await apiError(res, 'Failed to create identity');
   }
   return res.json();
 }
@@ -8,19 +8,26 @@
 -- 4 = witness_data
 
 -- Messages table: stores ANCHOR messages from various carriers
+-- A transaction can carry several messages (e.g. two OP_RETURNs, or an
+-- OP_RETURN plus an inscription); payload_index numbers them in detection
+-- order. vout is the output the message binds to, which witness carriers
+-- share with other payloads.
 CREATE TABLE messages (
     id SERIAL PRIMARY KEY,
     txid BYTEA NOT NULL,
     vout INTEGER NOT NULL,
+    payload_index SMALLINT NOT NULL DEFAULT 0,
     block_hash BYTEA,
     block_height INTEGER,
     kind SMALLINT NOT NULL,
+    -- Kind is not in the kind registry; the body is stored raw
+    kind_unknown BOOLEAN NOT NULL DEFAULT FALSE,
     body BYTEA NOT NULL,
     carrier SMALLINT NOT NULL DEFAULT 0,
     inscription_id TEXT,
     content_type TEXT,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
-    UNIQUE(txid, vout)
+    UNIQUE(txid, payload_index)
 );
 
 -- Anchors table: stores references to parent messages
@@ -51,8 +58,10 @@ INSERT INTO indexer_state (id, last_block_height) VALUES (1, 0);
 
 -- Indexes for efficient querying
 CREATE INDEX idx_messages_txid ON messages(txid);
+CREATE INDEX idx_messages_txid_vout ON messages(txid, vout);
 CREATE INDEX idx_messages_block_height ON messages(block_height);
 CREATE INDEX idx_messages_kind ON messages(kind);
+CREATE INDEX idx_messages_kind_unknown ON messages(kind) WHERE kind_unknown;
 CREATE INDEX idx_messages_carrier ON messages(carrier);
 CREATE INDEX idx_messages_created_at ON messages(created_at DESC);
 CREATE INDEX idx_messages_inscription_id ON messages(inscription_id) WHERE inscription_id IS NOT NULL;
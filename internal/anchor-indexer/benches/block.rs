@@ -0,0 +1,147 @@
+//! Block processing benchmarks
+//!
+//! Times message detection over whole synthetic blocks, the part of
+//! indexing a block that doesn't wait on the database or the node, plus
+//! language detection of Text bodies. Blocks hold `BLOCK_TXS` transactions
+//! of which a varying share carry ANCHOR messages, spread over the carriers.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use anchor_core::carrier::{
+    Carrier, CarrierOutput, CarrierSelector, InscriptionCarrier, OpReturnCarrier, StampsCarrier,
+    WitnessCarrier,
+};
+use anchor_core::{AnchorKind, Anchors, ParsedAnchorMessage};
+use anchor_indexer::detect::detect_block;
+use anchor_indexer::language::detect_language;
+use bitcoin::block::{Header, Version as BlockVersion};
+use bitcoin::hashes::Hash;
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, Block, BlockHash, CompactTarget, OutPoint,
+    ScriptBuf, Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Transactions per block, about a full mainnet block
+const BLOCK_TXS: usize = 3_000;
+
+/// Percent of transactions carrying ANCHOR messages
+const ANCHOR_SHARES: [usize; 3] = [0, 5, 50];
+
+const TEXT: &str = "Bitcoin is a peer-to-peer electronic cash system that allows \
+                    online payments to be sent directly from one party to another";
+
+/// An ordinary payment: one input with a signature, two P2WPKH outputs
+fn payment(n: usize) -> Transaction {
+    let mut txid = [0u8; 32];
+    txid[..8].copy_from_slice(&(n as u64).to_le_bytes());
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array(txid), 0),
+            witness: Witness::from_slice(&[vec![0x30; 72], vec![0x02; 33]]),
+            ..Default::default()
+        }],
+        output: (0..2)
+            .map(|i| TxOut {
+                value: Amount::from_sat(10_000 + i),
+                script_pubkey: ScriptBuf::from_bytes([&[0x00, 0x14][..], &[i as u8; 20]].concat()),
+            })
+            .collect(),
+    }
+}
+
+/// An ANCHOR transaction, cycling through the carriers
+fn anchor(n: usize) -> Transaction {
+    let carriers: [&dyn Carrier; 4] = [
+        &OpReturnCarrier::new(),
+        &StampsCarrier::new(),
+        &InscriptionCarrier::new(),
+        &WitnessCarrier::new(),
+    ];
+    let message = ParsedAnchorMessage {
+        kind: AnchorKind::Text,
+        anchors: Anchors::new(),
+        body: format!("{} #{}", TEXT, n).into_bytes(),
+    };
+
+    let mut tx = payment(n);
+    match carriers[n % carriers.len()].encode(&message).unwrap() {
+        CarrierOutput::OpReturn(script) => tx.output.push(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: script,
+        }),
+        CarrierOutput::Stamps(scripts) => {
+            tx.output.extend(scripts.into_iter().map(|script| TxOut {
+                value: Amount::from_sat(546),
+                script_pubkey: script,
+            }))
+        }
+        CarrierOutput::Inscription { reveal_script, .. } => {
+            tx.input[0].witness =
+                Witness::from_slice(&[vec![0; 64], reveal_script.to_bytes(), vec![0xc0; 33]]);
+        }
+        CarrierOutput::WitnessData { scripts, .. } => {
+            tx.input[0].witness = Witness::from_slice(&[scripts[0].to_bytes(), vec![0xc0; 33]]);
+        }
+        CarrierOutput::Annex(_) => unreachable!(),
+    }
+    tx
+}
+
+fn block(anchor_percent: usize) -> Block {
+    let txdata = (0..BLOCK_TXS)
+        .map(|n| {
+            if n % 100 < anchor_percent {
+                anchor(n)
+            } else {
+                payment(n)
+            }
+        })
+        .collect();
+    Block {
+        header: Header {
+            version: BlockVersion::TWO,
+            prev_blockhash: BlockHash::all_zeros(),
+            merkle_root: TxMerkleNode::all_zeros(),
+            time: 0,
+            bits: CompactTarget::from_consensus(0x207fffff),
+            nonce: 0,
+        },
+        txdata,
+    }
+}
+
+fn detection(c: &mut Criterion) {
+    let selector = CarrierSelector::new();
+    let mut group = c.benchmark_group("block");
+    group.throughput(Throughput::Elements(BLOCK_TXS as u64));
+    for share in ANCHOR_SHARES {
+        let block = block(share);
+        let expected = BLOCK_TXS * share / 100;
+        assert_eq!(detect_block(&selector, &block).len(), expected);
+
+        group.bench_with_input(BenchmarkId::new("detect", share), &block, |b, block| {
+            b.iter(|| detect_block(&selector, black_box(block)))
+        });
+    }
+    group.finish();
+}
+
+fn language(c: &mut Criterion) {
+    c.bench_function("language/text", |b| {
+        b.iter(|| detect_language(AnchorKind::Text, black_box(TEXT.as_bytes())))
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(3))
+        .sample_size(20);
+    targets = detection, language
+}
+criterion_main!(benches);
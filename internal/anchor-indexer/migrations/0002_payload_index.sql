@@ -0,0 +1,65 @@
+-- Migration: Key messages by (txid, payload_index) instead of (txid, vout)
+-- A transaction can carry several ANCHOR messages, and witness carriers
+-- report vout 0 even when an OP_RETURN at vout 0 carries another payload.
+
+-- Add payload_index column if it doesn't exist, numbering existing rows
+-- within each transaction by output
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns 
+        WHERE table_name = 'messages' AND column_name = 'payload_index'
+    ) THEN
+        ALTER TABLE messages ADD COLUMN payload_index SMALLINT NOT NULL DEFAULT 0;
+
+        UPDATE messages m
+        SET payload_index = numbered.payload_index
+        FROM (
+            SELECT id, (ROW_NUMBER() OVER (PARTITION BY txid ORDER BY vout, id) - 1)::SMALLINT AS payload_index
+            FROM messages
+        ) numbered
+        WHERE m.id = numbered.id;
+
+        RAISE NOTICE 'Added payload_index column to messages table';
+    ELSE
+        RAISE NOTICE 'payload_index column already exists';
+    END IF;
+END $$;
+
+-- Replace the (txid, vout) unique constraint with (txid, payload_index)
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'messages_txid_vout_key'
+    ) THEN
+        ALTER TABLE messages DROP CONSTRAINT messages_txid_vout_key;
+        RAISE NOTICE 'Dropped messages_txid_vout_key constraint';
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'messages_txid_payload_index_key'
+    ) THEN
+        ALTER TABLE messages ADD CONSTRAINT messages_txid_payload_index_key UNIQUE (txid, payload_index);
+        RAISE NOTICE 'Added messages_txid_payload_index_key constraint';
+    ELSE
+        RAISE NOTICE 'messages_txid_payload_index_key constraint already exists';
+    END IF;
+END $$;
+
+-- Keep (txid, vout) lookups fast
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_indexes 
+        WHERE indexname = 'idx_messages_txid_vout'
+    ) THEN
+        CREATE INDEX idx_messages_txid_vout ON messages(txid, vout);
+        RAISE NOTICE 'Created idx_messages_txid_vout index';
+    ELSE
+        RAISE NOTICE 'idx_messages_txid_vout index already exists';
+    END IF;
+END $$;
+
+COMMENT ON COLUMN messages.payload_index IS 'Position of the message among the ANCHOR payloads of its transaction';
@@ -0,0 +1,23 @@
+-- Migration: Identity rotations (kind 6)
+-- Each row hands an identity over from old_pubkey to new_pubkey. Only
+-- rotations with a valid signature by the old key are stored, and only the
+-- first rotation away from a key counts, so the rows for one identity_root
+-- form a single chain of keys.
+
+CREATE TABLE IF NOT EXISTS identity_rotations (
+    id SERIAL PRIMARY KEY,
+    message_id INTEGER NOT NULL UNIQUE REFERENCES messages(id) ON DELETE CASCADE,
+    identity_root BYTEA NOT NULL,
+    old_pubkey BYTEA NOT NULL,
+    new_pubkey BYTEA NOT NULL,
+    new_address TEXT,
+    block_height INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- A key rotates away at most once and joins at most one identity
+CREATE UNIQUE INDEX IF NOT EXISTS idx_identity_rotations_old_pubkey ON identity_rotations(old_pubkey);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_identity_rotations_new_pubkey ON identity_rotations(new_pubkey);
+CREATE INDEX IF NOT EXISTS idx_identity_rotations_root ON identity_rotations(identity_root);
+
+COMMENT ON TABLE identity_rotations IS 'Accepted identity key rotations, chained by identity_root (the first key of the identity)';
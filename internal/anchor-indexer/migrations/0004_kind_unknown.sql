@@ -0,0 +1,24 @@
+-- Migration: Flag messages whose kind is not in the kind registry
+-- Such messages are indexed like any other (raw body, anchors) so new
+-- application kinds are visible before the indexer learns about them;
+-- kind_unknown records that the indexer could not interpret the kind.
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns 
+        WHERE table_name = 'messages' AND column_name = 'kind_unknown'
+    ) THEN
+        ALTER TABLE messages ADD COLUMN kind_unknown BOOLEAN NOT NULL DEFAULT FALSE;
+
+        -- Keep in sync with anchor_specs::REGISTERED_KINDS
+        UPDATE messages
+        SET kind_unknown = kind NOT IN (0, 1, 2, 3, 4, 5, 6, 10, 11, 20, 30, 31, 32, 33, 40, 41, 42, 43, 44);
+
+        RAISE NOTICE 'Added kind_unknown column to messages table';
+    ELSE
+        RAISE NOTICE 'kind_unknown column already exists';
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_messages_kind_unknown ON messages(kind) WHERE kind_unknown;
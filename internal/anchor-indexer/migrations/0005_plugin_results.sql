@@ -0,0 +1,26 @@
+-- Migration: Results of indexer WASM plugins
+-- Plugins in PLUGIN_DIR can tag messages and attach extra fields; rows are
+-- namespaced by plugin name (the module's file stem) and removed with
+-- their message on reorg.
+
+CREATE TABLE IF NOT EXISTS message_plugin_tags (
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    plugin TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (message_id, plugin, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_plugin_tags_tag ON message_plugin_tags(plugin, tag);
+
+CREATE TABLE IF NOT EXISTS message_plugin_fields (
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    plugin TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (message_id, plugin, key)
+);
+
+COMMENT ON TABLE message_plugin_tags IS 'Tags attached to messages by indexer WASM plugins';
+COMMENT ON TABLE message_plugin_fields IS 'Extra fields stored on messages by indexer WASM plugins';
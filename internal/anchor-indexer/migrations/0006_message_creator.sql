@@ -0,0 +1,9 @@
+-- Migration: Message creator address
+-- The address spending a message transaction's first input, so activity can
+-- be listed per address across every app. NULL for coinbase transactions,
+-- non-standard scripts, and messages indexed before this column existed.
+
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS creator_address TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_messages_creator_address
+    ON messages(creator_address, id DESC) WHERE creator_address IS NOT NULL;
@@ -0,0 +1,9 @@
+-- Migration: Message language
+-- ISO 639-3 code of a Text message's language, detected by the indexer when
+-- DETECT_LANGUAGE is set. NULL for other kinds, texts too short or mixed to
+-- tell, and messages indexed without detection.
+
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS lang TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_messages_lang
+    ON messages(lang, created_at DESC) WHERE lang IS NOT NULL;
@@ -0,0 +1,15 @@
+-- Migration: ANCHOR activity filters
+-- One compact filter per indexed block over its ANCHOR transactions (txids,
+-- output scripts and creator scripts, see anchor_core::anchor_filter), for
+-- wallets rescanning their history after a restore. filter_header chains the
+-- filters BIP-157 style and is zero-based at the first block indexed with
+-- filters. Blocks indexed before this table existed have no row; lowering
+-- indexer_state.last_block_height reindexes them and fills it in.
+
+CREATE TABLE IF NOT EXISTS anchor_filters (
+    block_height INTEGER PRIMARY KEY,
+    block_hash BYTEA NOT NULL,
+    filter BYTEA NOT NULL,
+    filter_header BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,294 @@
+//! Cache of raw transactions and blocks fetched from Bitcoin Core
+//!
+//! Recently used entries stay in memory, bounded by entry count and evicted
+//! least recently used first. With `CACHE_DIR` set they're also written to
+//! disk, consensus-encoded, so re-processing blocks after a reorg or a
+//! restart, and repeated creator lookups of the same parent transactions,
+//! don't go back to the node. The disk cache is trimmed to
+//! `CACHE_MAX_DISK_MB`, least recently written files first.
+//!
+//! Entries are keyed by txid and block hash, so they never go stale.
+
+use anyhow::{Context, Result};
+use bitcoin::consensus::encode::{deserialize, serialize, Decodable, Encodable};
+use bitcoin::{Block, BlockHash, Transaction, Txid};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+/// Share of the disk budget kept after a trim, so trims don't run on every write
+const TRIM_TARGET_PERCENT: u64 = 90;
+
+/// Cache limits
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Directory of the on-disk cache; memory only when unset
+    pub dir: Option<PathBuf>,
+    /// Transactions kept in memory
+    pub max_transactions: usize,
+    /// Blocks kept in memory
+    pub max_blocks: usize,
+    /// Size limit of the on-disk cache, in bytes
+    pub max_disk_bytes: u64,
+}
+
+/// Least-recently-used map with a fixed entry count
+struct Lru<K, V> {
+    entries: HashMap<K, (V, u64)>,
+    /// Entries by last use
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        *used = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Consensus-encoded entries under `<dir>/<kind>/<key>`
+struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Bytes written since the last trim
+    written: AtomicU64,
+}
+
+impl DiskCache {
+    fn open(dir: PathBuf, max_bytes: u64) -> Result<Self> {
+        for kind in ["tx", "block"] {
+            fs::create_dir_all(dir.join(kind))
+                .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        }
+        let cache = Self {
+            dir,
+            max_bytes,
+            written: AtomicU64::new(0),
+        };
+        cache.trim();
+        Ok(cache)
+    }
+
+    fn read<T: Decodable>(&self, kind: &str, key: &str) -> Option<T> {
+        let path = self.dir.join(kind).join(key);
+        let bytes = fs::read(&path).ok()?;
+        match deserialize(&bytes) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                warn!("Dropping corrupt cache entry {}: {}", path.display(), e);
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    fn write<T: Encodable>(&self, kind: &str, key: &str, value: &T) {
+        let path = self.dir.join(kind).join(key);
+        if path.exists() {
+            return;
+        }
+        // Write then rename, so readers never see a partial entry
+        let bytes = serialize(value);
+        let tmp = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp, &bytes).and_then(|_| fs::rename(&tmp, &path)) {
+            warn!("Failed to write cache entry {}: {}", path.display(), e);
+            let _ = fs::remove_file(&tmp);
+            return;
+        }
+
+        let written = self
+            .written
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed)
+            + bytes.len() as u64;
+        if written > self.max_bytes * (100 - TRIM_TARGET_PERCENT) / 100 {
+            self.written.store(0, Ordering::Relaxed);
+            self.trim();
+        }
+    }
+
+    /// Remove the oldest entries until the cache fits its budget
+    fn trim(&self) {
+        let mut files: Vec<(SystemTime, u64, PathBuf)> = ["tx", "block"]
+            .iter()
+            .filter_map(|kind| fs::read_dir(self.dir.join(kind)).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect();
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+
+        files.sort();
+        let target = self.max_bytes * TRIM_TARGET_PERCENT / 100;
+        let mut removed = 0;
+        for (_, len, path) in files {
+            if total <= target {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+                removed += 1;
+            }
+        }
+        debug!(
+            "Trimmed {} entries from {}, {} bytes left",
+            removed,
+            self.dir.display(),
+            total
+        );
+    }
+}
+
+/// Raw transaction and block cache shared by the indexing loop
+pub struct ChainCache {
+    transactions: Mutex<Lru<Txid, Arc<Transaction>>>,
+    blocks: Mutex<Lru<BlockHash, Arc<Block>>>,
+    disk: Option<DiskCache>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ChainCache {
+    /// Create the cache, opening (and trimming) the on-disk cache if configured
+    pub fn new(config: &CacheConfig) -> Result<Self> {
+        let disk = match &config.dir {
+            Some(dir) => Some(DiskCache::open(dir.clone(), config.max_disk_bytes)?),
+            None => None,
+        };
+        Ok(Self {
+            transactions: Mutex::new(Lru::new(config.max_transactions)),
+            blocks: Mutex::new(Lru::new(config.max_blocks)),
+            disk,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    /// Directory of the on-disk cache, if any
+    pub fn dir(&self) -> Option<&Path> {
+        self.disk.as_ref().map(|disk| disk.dir.as_path())
+    }
+
+    /// A cached transaction, from memory or disk
+    pub fn transaction(&self, txid: &Txid) -> Option<Arc<Transaction>> {
+        let cached = lookup(&self.transactions, self.disk.as_ref(), "tx", txid);
+        self.count(cached.is_some());
+        cached
+    }
+
+    /// Cache a transaction fetched from the node or found in a block
+    pub fn insert_transaction(&self, tx: Transaction) -> Arc<Transaction> {
+        let txid = tx.compute_txid();
+        store(&self.transactions, self.disk.as_ref(), "tx", txid, tx)
+    }
+
+    /// A cached block, from memory or disk
+    pub fn block(&self, hash: &BlockHash) -> Option<Arc<Block>> {
+        let cached = lookup(&self.blocks, self.disk.as_ref(), "block", hash);
+        self.count(cached.is_some());
+        cached
+    }
+
+    /// Cache a block fetched from the node
+    pub fn insert_block(&self, block: Block) -> Arc<Block> {
+        let hash = block.block_hash();
+        store(&self.blocks, self.disk.as_ref(), "block", hash, block)
+    }
+
+    /// Lookups answered from the cache, and those that went to the node
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Look an entry up in memory, then on disk; disk hits move back into memory
+fn lookup<K, V>(
+    memory: &Mutex<Lru<K, Arc<V>>>,
+    disk: Option<&DiskCache>,
+    kind: &str,
+    key: &K,
+) -> Option<Arc<V>>
+where
+    K: Hash + Eq + Clone + ToString,
+    V: Decodable,
+{
+    if let Some(value) = memory.lock().unwrap_or_else(|e| e.into_inner()).get(key) {
+        return Some(value);
+    }
+    let value = Arc::new(disk?.read::<V>(kind, &key.to_string())?);
+    memory
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key.clone(), value.clone());
+    Some(value)
+}
+
+fn store<K, V>(
+    memory: &Mutex<Lru<K, Arc<V>>>,
+    disk: Option<&DiskCache>,
+    kind: &str,
+    key: K,
+    value: V,
+) -> Arc<V>
+where
+    K: Hash + Eq + Clone + ToString,
+    V: Encodable,
+{
+    if let Some(disk) = disk {
+        disk.write(kind, &key.to_string(), &value);
+    }
+    let value = Arc::new(value);
+    memory
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, value.clone());
+    value
+}
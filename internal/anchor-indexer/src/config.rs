@@ -3,6 +3,10 @@
 use anyhow::{Context, Result};
 use std::env;
 
+use crate::cache::CacheConfig;
+use crate::plugins::PluginConfig;
+use crate::proxy::Socks5Proxy;
+
 /// Indexer configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -18,6 +22,18 @@ pub struct Config {
     pub poll_interval_secs: u64,
     /// Number of confirmations before considering a block final
     pub confirmations: u32,
+    /// SOCKS5 proxy for Bitcoin Core RPC
+    pub bitcoin_rpc_proxy: Option<Socks5Proxy>,
+    /// Bitcoin Core RPC requests in flight at once
+    pub rpc_max_concurrency: usize,
+    /// Calls per batched RPC request
+    pub rpc_batch_size: usize,
+    /// Raw transaction and block cache
+    pub cache: CacheConfig,
+    /// WASM message plugins (enabled when PLUGIN_DIR is set)
+    pub plugins: Option<PluginConfig>,
+    /// Detect and store the language of Text messages
+    pub detect_language: bool,
 }
 
 impl Config {
@@ -38,6 +54,49 @@ impl Config {
                 .unwrap_or_else(|_| "1".to_string())
                 .parse()
                 .unwrap_or(1),
+            bitcoin_rpc_proxy: Socks5Proxy::from_env("BITCOIN_RPC_PROXY")?,
+            rpc_max_concurrency: env::var("BITCOIN_RPC_MAX_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(anchor_rpc::DEFAULT_MAX_CONCURRENCY),
+            rpc_batch_size: env::var("BITCOIN_RPC_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(anchor_rpc::DEFAULT_BATCH_SIZE),
+            cache: CacheConfig {
+                dir: env::var("CACHE_DIR").ok().map(Into::into),
+                max_transactions: env::var("CACHE_MAX_TRANSACTIONS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(50_000),
+                max_blocks: env::var("CACHE_MAX_BLOCKS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(32),
+                max_disk_bytes: env::var("CACHE_MAX_DISK_MB")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(2048)
+                    * 1024
+                    * 1024,
+            },
+            plugins: env::var("PLUGIN_DIR").ok().map(|dir| PluginConfig {
+                dir: dir.into(),
+                fuel: env::var("PLUGIN_FUEL")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(10_000_000),
+                max_memory_bytes: env::var("PLUGIN_MAX_MEMORY_MB")
+                    .ok()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(16)
+                    * 1024
+                    * 1024,
+            }),
+            detect_language: env::var("DETECT_LANGUAGE")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
         })
     }
 }
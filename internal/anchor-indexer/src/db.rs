@@ -8,6 +8,21 @@ use tracing::debug;
 
 use anchor_core::carrier::CarrierType;
 use anchor_core::{Anchor, ParsedAnchorMessage, TXID_PREFIX_SIZE};
+use anchor_specs::identity::IdentityRotationSpec;
+
+/// A confirmed message as stored, for export
+pub struct StoredMessage {
+    pub txid: Vec<u8>,
+    pub payload_index: i16,
+    pub vout: i32,
+    pub block_hash: Vec<u8>,
+    pub block_height: i32,
+    pub kind: i16,
+    pub carrier: i16,
+    pub body: Vec<u8>,
+    pub creator_address: Option<String>,
+    pub lang: Option<String>,
+}
 
 /// Database connection pool wrapper
 #[derive(Clone)]
@@ -22,6 +37,11 @@ impl Database {
         Ok(Self { pool })
     }
 
+    /// Close the pool once the queries in progress have finished
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
     /// Get the last indexed block height
     pub async fn get_last_block_height(&self) -> Result<i32> {
         let row: (i32,) =
@@ -45,39 +65,94 @@ impl Database {
         Ok(())
     }
 
+    /// Header of the ANCHOR filter stored for `block_height`, if any
+    pub async fn get_filter_header(&self, block_height: i32) -> Result<Option<Vec<u8>>> {
+        let header =
+            sqlx::query_scalar("SELECT filter_header FROM anchor_filters WHERE block_height = $1")
+                .bind(block_height)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(header)
+    }
+
+    /// Store a block's ANCHOR filter, replacing one left by an earlier run
+    pub async fn upsert_anchor_filter(
+        &self,
+        block_height: i32,
+        block_hash: &[u8],
+        filter: &[u8],
+        filter_header: &[u8],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO anchor_filters (block_height, block_hash, filter, filter_header)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (block_height) DO UPDATE
+                SET block_hash = EXCLUDED.block_hash,
+                    filter = EXCLUDED.filter,
+                    filter_header = EXCLUDED.filter_header,
+                    created_at = NOW()
+            "#,
+        )
+        .bind(block_height)
+        .bind(block_hash)
+        .bind(filter)
+        .bind(filter_header)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Insert a new ANCHOR message with carrier type
+    ///
+    /// `payload_index` is the message's position among the payloads of its
+    /// transaction and, with the txid, identifies it.
+    #[allow(clippy::too_many_arguments)]
     pub async fn insert_message_with_carrier(
         &self,
         txid: &Txid,
+        payload_index: u16,
         vout: u32,
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
         message: &ParsedAnchorMessage,
         carrier: CarrierType,
+        creator_address: Option<&str>,
+        lang: Option<&str>,
     ) -> Result<i32> {
         let txid_bytes = txid.to_byte_array().to_vec();
-        let kind = u8::from(message.kind) as i16;
+        let kind_byte = u8::from(message.kind);
+        let kind = kind_byte as i16;
+        let kind_unknown = !anchor_specs::is_registered_kind(kind_byte);
         let carrier_id = carrier as i16;
 
         // Insert the message with carrier
         let row: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO messages (txid, vout, block_hash, block_height, kind, body, carrier)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            ON CONFLICT (txid, vout) DO UPDATE SET
+            INSERT INTO messages (txid, payload_index, vout, block_hash, block_height, kind, kind_unknown, body, carrier, creator_address, lang)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (txid, payload_index) DO UPDATE SET
                 block_hash = EXCLUDED.block_hash,
                 block_height = EXCLUDED.block_height,
-                carrier = EXCLUDED.carrier
+                carrier = EXCLUDED.carrier,
+                creator_address = COALESCE(EXCLUDED.creator_address, messages.creator_address),
+                lang = COALESCE(EXCLUDED.lang, messages.lang)
             RETURNING id
             "#,
         )
         .bind(&txid_bytes)
+        .bind(payload_index as i16)
         .bind(vout as i32)
         .bind(block_hash)
         .bind(block_height)
         .bind(kind)
+        .bind(kind_unknown)
         .bind(&message.body)
         .bind(carrier_id)
+        .bind(creator_address)
+        .bind(lang)
         .fetch_one(&self.pool)
         .await?;
 
@@ -122,6 +197,49 @@ impl Database {
         Ok(())
     }
 
+    /// Attach a plugin tag to a message
+    pub async fn insert_plugin_tag(&self, message_id: i32, plugin: &str, tag: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO message_plugin_tags (message_id, plugin, tag)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (message_id, plugin, tag) DO NOTHING
+            "#,
+        )
+        .bind(message_id)
+        .bind(plugin)
+        .bind(tag)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Store a plugin field on a message, replacing an earlier value
+    pub async fn upsert_plugin_field(
+        &self,
+        message_id: i32,
+        plugin: &str,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO message_plugin_fields (message_id, plugin, key, value)
+            VALUES ($1, $2, $3, $4::jsonb)
+            ON CONFLICT (message_id, plugin, key) DO UPDATE SET value = EXCLUDED.value
+            "#,
+        )
+        .bind(message_id)
+        .bind(plugin)
+        .bind(key)
+        .bind(value.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Resolve anchors by finding matching txids
     pub async fn resolve_anchors(&self) -> Result<u64> {
         // Find anchors that haven't been resolved yet
@@ -138,11 +256,13 @@ impl Database {
         let mut resolved_count = 0u64;
 
         for (anchor_id, prefix, _vout) in unresolved {
-            // Find messages matching this prefix
+            // Find transactions matching this prefix, resolving to the first
+            // payload when a transaction carries several messages
             let matches: Vec<(Vec<u8>, i32)> = sqlx::query_as(
                 r#"
-                SELECT txid, id FROM messages
+                SELECT DISTINCT ON (txid) txid, id FROM messages
                 WHERE substring(txid from 1 for $1) = $2
+                ORDER BY txid, payload_index
                 "#,
             )
             .bind(TXID_PREFIX_SIZE as i32)
@@ -185,18 +305,143 @@ impl Database {
     }
 
     /// Check if a message already exists
-    pub async fn message_exists(&self, txid: &Txid, vout: u32) -> Result<bool> {
+    /// Record an identity rotation, chaining it onto the old key's identity
+    ///
+    /// Returns false if the rotation was rejected: the old key already
+    /// rotated away, or the new key already belongs to an identity.
+    pub async fn insert_identity_rotation(
+        &self,
+        message_id: i32,
+        rotation: &IdentityRotationSpec,
+        block_height: Option<i32>,
+    ) -> Result<bool> {
+        let old_pubkey = &rotation.old_pubkey[..];
+        let new_pubkey = &rotation.new_pubkey[..];
+
+        // Rotating back to a key already in some chain would create a cycle
+        let (new_key_taken,): (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM identity_rotations
+                WHERE old_pubkey = $1 OR identity_root = $1
+            )
+            "#,
+        )
+        .bind(new_pubkey)
+        .fetch_one(&self.pool)
+        .await?;
+        if new_key_taken {
+            return Ok(false);
+        }
+
+        let root: Option<(Vec<u8>,)> =
+            sqlx::query_as("SELECT identity_root FROM identity_rotations WHERE new_pubkey = $1")
+                .bind(old_pubkey)
+                .fetch_optional(&self.pool)
+                .await?;
+        let identity_root = root
+            .map(|(root,)| root)
+            .unwrap_or_else(|| old_pubkey.to_vec());
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO identity_rotations
+                (message_id, identity_root, old_pubkey, new_pubkey, new_address, block_height)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(message_id)
+        .bind(&identity_root)
+        .bind(old_pubkey)
+        .bind(new_pubkey)
+        .bind(&rotation.new_address)
+        .bind(block_height)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn message_exists(&self, txid: &Txid, payload_index: u16) -> Result<bool> {
         let txid_bytes = txid.to_byte_array().to_vec();
         let row: (i64,) =
-            sqlx::query_as("SELECT COUNT(*) FROM messages WHERE txid = $1 AND vout = $2")
+            sqlx::query_as("SELECT COUNT(*) FROM messages WHERE txid = $1 AND payload_index = $2")
                 .bind(&txid_bytes)
-                .bind(vout as i32)
+                .bind(payload_index as i16)
                 .fetch_one(&self.pool)
                 .await?;
 
         Ok(row.0 > 0)
     }
 
+    /// Confirmed messages between two heights (inclusive) in chain order,
+    /// limited to `kinds` unless it is empty
+    pub async fn confirmed_messages(
+        &self,
+        kinds: &[i16],
+        from_height: i32,
+        to_height: i32,
+    ) -> Result<Vec<StoredMessage>> {
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(
+            Vec<u8>,
+            i16,
+            i32,
+            Vec<u8>,
+            i32,
+            i16,
+            i16,
+            Vec<u8>,
+            Option<String>,
+            Option<String>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT txid, payload_index, vout, block_hash, block_height, kind, carrier, body,
+                   creator_address, lang
+            FROM messages
+            WHERE block_height BETWEEN $1 AND $2
+              AND block_hash IS NOT NULL
+              AND (cardinality($3::smallint[]) = 0 OR kind = ANY($3))
+            ORDER BY block_height, txid, payload_index
+            "#,
+        )
+        .bind(from_height)
+        .bind(to_height)
+        .bind(kinds)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    txid,
+                    payload_index,
+                    vout,
+                    block_hash,
+                    block_height,
+                    kind,
+                    carrier,
+                    body,
+                    creator_address,
+                    lang,
+                )| StoredMessage {
+                    txid,
+                    payload_index,
+                    vout,
+                    block_hash,
+                    block_height,
+                    kind,
+                    carrier,
+                    body,
+                    creator_address,
+                    lang,
+                },
+            )
+            .collect())
+    }
+
     /// Handle a blockchain reorganization
     pub async fn handle_reorg(&self, from_height: i32) -> Result<u64> {
         // Delete messages from the reorged blocks
@@ -205,6 +450,12 @@ impl Database {
             .execute(&self.pool)
             .await?;
 
+        // And their filters, so later ones chain from the new tip
+        sqlx::query("DELETE FROM anchor_filters WHERE block_height >= $1")
+            .bind(from_height)
+            .execute(&self.pool)
+            .await?;
+
         // Update indexer state
         sqlx::query("UPDATE indexer_state SET last_block_height = $1 - 1 WHERE id = 1")
             .bind(from_height)
@@ -0,0 +1,45 @@
+//! Message detection
+//!
+//! The CPU-bound part of indexing a block: decoding the ANCHOR messages each
+//! transaction carries. It touches neither the database nor the node, so
+//! `benches/block.rs` can time it on synthetic blocks.
+
+use anchor_core::carrier::{CarrierSelector, CarrierType};
+use anchor_core::{parse_transaction, ParsedAnchorMessage};
+use bitcoin::{Block, Transaction};
+
+/// A detected message: vout, carrier and the parsed message
+pub type DetectedMessage = (u32, CarrierType, ParsedAnchorMessage);
+
+/// ANCHOR messages carried by a transaction
+pub fn detect_messages(selector: &CarrierSelector, tx: &Transaction) -> Vec<DetectedMessage> {
+    // Try multi-carrier detection first
+    let detected = selector.detect(tx);
+
+    // Fall back to legacy OP_RETURN parsing if no messages detected
+    if detected.is_empty() {
+        // Use legacy parser for backwards compatibility
+        parse_transaction(tx)
+            .into_iter()
+            .map(|(vout, msg)| (vout, CarrierType::OpReturn, msg))
+            .collect()
+    } else {
+        detected
+            .into_iter()
+            .map(|d| (d.vout, d.carrier_type, d.message))
+            .collect()
+    }
+}
+
+/// Transactions of a block that carry ANCHOR messages, with their messages
+pub fn detect_block<'a>(
+    selector: &CarrierSelector,
+    block: &'a Block,
+) -> Vec<(&'a Transaction, Vec<DetectedMessage>)> {
+    block
+        .txdata
+        .iter()
+        .map(|tx| (tx, detect_messages(selector, tx)))
+        .filter(|(_, messages)| !messages.is_empty())
+        .collect()
+}
@@ -0,0 +1,527 @@
+//! Message exchange between deployments
+//!
+//! ```text
+//! anchor-indexer export [--kind K[,K...]] [--height-range FROM-TO] [--output FILE]
+//! anchor-indexer import [--input FILE] [--no-chain-check]
+//! ```
+//!
+//! `export` writes confirmed messages as JSON Lines: an [`ExportHeader`],
+//! then one [`ExportedMessage`] per message. Each record carries its raw
+//! transaction and a merkle proof of it in its block (the serialized
+//! `MerkleBlock` that `gettxoutproof` returns), so an importing explorer
+//! trusts nothing but the proof: `import` checks the proof against the block
+//! header, decodes the transaction again and keeps the message only if the
+//! payload, vout and carrier it finds match the record. Unless
+//! `--no-chain-check` is given, each block hash is also confirmed against
+//! the local node, so explorers indexing different height ranges can share
+//! their messages without trusting each other.
+//!
+//! The creator address and language are informational and imported as
+//! given. Anchors are parsed from the transaction, not the record.
+
+use anyhow::{bail, Context, Result};
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
+use bitcoin::hashes::Hash;
+use bitcoin::{Block, BlockHash, MerkleBlock, Network, Transaction, Txid};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use anchor_core::carrier::CarrierSelector;
+use anchor_core::{AnchorKind, ParsedAnchorMessage};
+use anchor_indexer::detect::detect_messages;
+use anchor_rpc::RpcClient;
+
+use crate::config::Config;
+use crate::db::{Database, StoredMessage};
+use crate::indexer::{connect, index_identity_rotation, parse_network};
+
+/// Identifies an export file
+const FORMAT: &str = "anchor-messages";
+
+/// Version of the record layout
+const FORMAT_VERSION: u32 = 1;
+
+/// Heights read from the database per query
+const EXPORT_CHUNK_BLOCKS: i32 = 500;
+
+/// Blocks fetched from the node at once
+const FETCH_BLOCKS: usize = 16;
+
+/// Records verified and inserted per batch
+const IMPORT_BATCH: usize = 500;
+
+/// First line of an export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportHeader {
+    pub format: String,
+    pub version: u32,
+    /// Chain as Bitcoin Core names it: main, test, signet or regtest
+    pub network: String,
+    /// Exported kinds; empty for all
+    pub kinds: Vec<u8>,
+    pub from_height: i32,
+    pub to_height: i32,
+}
+
+/// A message with the proof of where it was mined
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedMessage {
+    pub txid: String,
+    /// Position among the payloads of the transaction
+    pub payload_index: u16,
+    pub vout: u32,
+    pub kind: u8,
+    /// Carrier id (0 = OP_RETURN, 1 = inscription, ...)
+    pub carrier: u8,
+    /// Message body, hex
+    pub body: String,
+    pub block_hash: String,
+    pub block_height: i32,
+    pub creator_address: Option<String>,
+    pub lang: Option<String>,
+    /// Raw transaction, hex
+    pub tx: String,
+    /// Serialized `MerkleBlock` proving the transaction is in the block, hex
+    pub proof: String,
+}
+
+/// Arguments of `export`
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExportArgs {
+    pub kinds: Vec<u8>,
+    /// Inclusive; defaults to everything indexed
+    pub heights: Option<(i32, i32)>,
+    /// Defaults to stdout
+    pub output: Option<PathBuf>,
+}
+
+impl ExportArgs {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut kinds = Vec::new();
+        let mut heights = None;
+        let mut output = None;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", arg))
+            };
+            match arg.as_str() {
+                "--kind" => {
+                    for kind in value()?.split(',') {
+                        kinds.push(
+                            kind.trim()
+                                .parse()
+                                .map_err(|_| format!("invalid kind '{}'", kind))?,
+                        );
+                    }
+                }
+                "--height-range" => heights = Some(parse_height_range(&value()?)?),
+                "--output" | "-o" => output = Some(PathBuf::from(value()?)),
+                other => return Err(format!("unknown option {}", other)),
+            }
+        }
+
+        Ok(Self {
+            kinds,
+            heights,
+            output,
+        })
+    }
+}
+
+/// Arguments of `import`
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportArgs {
+    /// Defaults to stdin
+    pub input: Option<PathBuf>,
+    /// Confirm each block hash against the local node
+    pub chain_check: bool,
+}
+
+impl ImportArgs {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut input = None;
+        let mut chain_check = true;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--input" | "-i" => {
+                    input = Some(PathBuf::from(
+                        iter.next()
+                            .ok_or_else(|| format!("{} needs a value", arg))?,
+                    ))
+                }
+                "--no-chain-check" => chain_check = false,
+                other => return Err(format!("unknown option {}", other)),
+            }
+        }
+
+        Ok(Self { input, chain_check })
+    }
+}
+
+/// `FROM-TO`, or a single height
+fn parse_height_range(value: &str) -> Result<(i32, i32), String> {
+    let invalid = || format!("invalid height range '{}'", value);
+    let (from, to) = value.split_once('-').unwrap_or((value, value));
+    let from: i32 = from.trim().parse().map_err(|_| invalid())?;
+    let to: i32 = to.trim().parse().map_err(|_| invalid())?;
+    if from < 0 || to < from {
+        return Err(invalid());
+    }
+    Ok((from, to))
+}
+
+/// Write the confirmed messages in the requested range
+pub async fn export(config: &Config, args: &ExportArgs) -> Result<()> {
+    let db = Database::connect(&config.database_url).await?;
+    let rpc = node(config)?;
+    let network = parse_network(&rpc.get_blockchain_info().await?.chain)?;
+
+    let last_height = db.get_last_block_height().await?;
+    let (from_height, to_height) = match args.heights {
+        Some((from, to)) => (from, to.min(last_height)),
+        None => (0, last_height),
+    };
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("Failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let header = ExportHeader {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        network: network.to_core_arg().to_string(),
+        kinds: args.kinds.clone(),
+        from_height,
+        to_height,
+    };
+    writeln!(out, "{}", serde_json::to_string(&header)?)?;
+
+    let kinds: Vec<i16> = args.kinds.iter().map(|&kind| kind as i16).collect();
+    let mut exported = 0;
+    let mut start = from_height;
+    while start <= to_height {
+        let end = (start + EXPORT_CHUNK_BLOCKS - 1).min(to_height);
+        let messages = db.confirmed_messages(&kinds, start, end).await?;
+        start = end + 1;
+
+        // Messages come in chain order, so a block's messages are adjacent
+        let mut by_block: Vec<(BlockHash, Vec<StoredMessage>)> = Vec::new();
+        for message in messages {
+            let hash = BlockHash::from_slice(&message.block_hash)?;
+            match by_block.last_mut() {
+                Some((last, group)) if *last == hash => group.push(message),
+                _ => by_block.push((hash, vec![message])),
+            }
+        }
+
+        for chunk in by_block.chunks(FETCH_BLOCKS) {
+            let hashes: Vec<BlockHash> = chunk.iter().map(|(hash, _)| *hash).collect();
+            let blocks = rpc.get_blocks(&hashes).await?;
+            for ((hash, messages), block) in chunk.iter().zip(blocks) {
+                let block = block.with_context(|| format!("Failed to fetch block {}", hash))?;
+                for message in messages {
+                    let record = export_record(&block, message)?;
+                    writeln!(out, "{}", serde_json::to_string(&record)?)?;
+                    exported += 1;
+                }
+            }
+        }
+    }
+    out.flush()?;
+
+    info!(
+        "Exported {} messages from heights {}-{}",
+        exported, from_height, to_height
+    );
+    Ok(())
+}
+
+/// Record of a stored message, with its transaction and proof from `block`
+fn export_record(block: &Block, message: &StoredMessage) -> Result<ExportedMessage> {
+    let txid = Txid::from_slice(&message.txid)?;
+    let tx = block
+        .txdata
+        .iter()
+        .find(|tx| tx.compute_txid() == txid)
+        .with_context(|| format!("Transaction {} not in block {}", txid, block.block_hash()))?;
+    let proof = MerkleBlock::from_block_with_predicate(block, |t| *t == txid);
+
+    Ok(ExportedMessage {
+        txid: txid.to_string(),
+        payload_index: message.payload_index as u16,
+        vout: message.vout as u32,
+        kind: message.kind as u8,
+        carrier: message.carrier as u8,
+        body: hex::encode(&message.body),
+        block_hash: block.block_hash().to_string(),
+        block_height: message.block_height,
+        creator_address: message.creator_address.clone(),
+        lang: message.lang.clone(),
+        tx: serialize_hex(tx),
+        proof: serialize_hex(&proof),
+    })
+}
+
+/// A record whose proof and payload checked out
+struct VerifiedMessage {
+    line: usize,
+    txid: Txid,
+    payload_index: u16,
+    vout: u32,
+    block_hash: BlockHash,
+    block_height: i32,
+    carrier: anchor_core::carrier::CarrierType,
+    message: ParsedAnchorMessage,
+    creator_address: Option<String>,
+    lang: Option<String>,
+}
+
+/// Check a record's proof and decode its message from the transaction
+fn verify_record(
+    selector: &CarrierSelector,
+    record: ExportedMessage,
+    line: usize,
+) -> Result<VerifiedMessage> {
+    let tx: Transaction = deserialize_hex(&record.tx).context("invalid transaction")?;
+    let txid = tx.compute_txid();
+    if txid.to_string() != record.txid {
+        bail!("transaction is {}, not {}", txid, record.txid);
+    }
+
+    let proof: MerkleBlock = deserialize_hex(&record.proof).context("invalid proof")?;
+    let block_hash = proof.header.block_hash();
+    if block_hash.to_string() != record.block_hash {
+        bail!(
+            "proof is for block {}, not {}",
+            block_hash,
+            record.block_hash
+        );
+    }
+    proof
+        .header
+        .validate_pow(proof.header.target())
+        .context("block header fails its proof of work")?;
+    let mut matches = Vec::new();
+    proof
+        .extract_matches(&mut matches, &mut Vec::new())
+        .context("merkle proof does not match the block header")?;
+    if !matches.contains(&txid) {
+        bail!("merkle proof does not include {}", txid);
+    }
+
+    let detected = detect_messages(selector, &tx);
+    let Some((vout, carrier, message)) = detected.into_iter().nth(record.payload_index as usize)
+    else {
+        bail!("transaction has no payload {}", record.payload_index);
+    };
+    if vout != record.vout
+        || carrier as u8 != record.carrier
+        || u8::from(message.kind) != record.kind
+        || hex::encode(&message.body) != record.body
+    {
+        bail!(
+            "payload {} decodes to kind {} in vout {} via {}, not what the record says",
+            record.payload_index,
+            u8::from(message.kind),
+            vout,
+            carrier
+        );
+    }
+
+    Ok(VerifiedMessage {
+        line,
+        txid,
+        payload_index: record.payload_index,
+        vout,
+        block_hash,
+        block_height: record.block_height,
+        carrier,
+        message,
+        creator_address: record.creator_address,
+        lang: record.lang,
+    })
+}
+
+/// Verify and store the messages of an export
+pub async fn import(config: &Config, args: &ImportArgs) -> Result<()> {
+    let reader: Box<dyn BufRead> = match &args.input {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+        )),
+        None => Box::new(BufReader::new(io::stdin().lock())),
+    };
+    let mut lines = reader.lines();
+
+    let header: ExportHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?).context("Invalid export header")?,
+        None => bail!("Empty export"),
+    };
+    if header.format != FORMAT || header.version != FORMAT_VERSION {
+        bail!(
+            "Unsupported export format {} version {}",
+            header.format,
+            header.version
+        );
+    }
+
+    let rpc = if args.chain_check {
+        let rpc = node(config)?;
+        let network = parse_network(&rpc.get_blockchain_info().await?.chain)?;
+        if Network::from_core_arg(&header.network).ok() != Some(network) {
+            bail!(
+                "Export is from {}, the local node is on {}",
+                header.network,
+                network.to_core_arg()
+            );
+        }
+        Some(rpc)
+    } else {
+        warn!("Chain check disabled: block hashes are trusted as exported");
+        None
+    };
+
+    let db = Database::connect(&config.database_url).await?;
+    let selector = CarrierSelector::new();
+    info!(
+        "Importing {} messages of heights {}-{}",
+        header.network, header.from_height, header.to_height
+    );
+
+    let mut summary = ImportSummary::default();
+    let mut batch = Vec::with_capacity(IMPORT_BATCH);
+    // Line numbers are 1-based and the header is line 1
+    for (index, line) in lines.enumerate() {
+        let line_number = index + 2;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let verified = serde_json::from_str(&line)
+            .context("invalid record")
+            .and_then(|record| verify_record(&selector, record, line_number));
+        match verified {
+            Ok(message) => batch.push(message),
+            Err(e) => {
+                warn!("Line {}: rejected: {:#}", line_number, e);
+                summary.rejected += 1;
+            }
+        }
+        if batch.len() == IMPORT_BATCH {
+            store_batch(&db, rpc.as_ref(), std::mem::take(&mut batch), &mut summary).await?;
+        }
+    }
+    store_batch(&db, rpc.as_ref(), batch, &mut summary).await?;
+
+    let resolved = db.resolve_anchors().await?;
+    info!(
+        "Imported {} messages ({} already indexed, {} rejected), resolved {} anchors",
+        summary.imported, summary.existing, summary.rejected, resolved
+    );
+    Ok(())
+}
+
+#[derive(Default)]
+struct ImportSummary {
+    imported: u64,
+    existing: u64,
+    rejected: u64,
+}
+
+/// Confirm a batch's blocks against the node, then insert its new messages
+async fn store_batch(
+    db: &Database,
+    rpc: Option<&RpcClient>,
+    batch: Vec<VerifiedMessage>,
+    summary: &mut ImportSummary,
+) -> Result<()> {
+    let chain: HashMap<i32, BlockHash> = match rpc {
+        Some(rpc) => {
+            let heights: Vec<i32> = batch
+                .iter()
+                .map(|message| message.block_height)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            let hashes = rpc
+                .get_block_hashes(heights.iter().map(|&height| height as u64))
+                .await?;
+            heights
+                .into_iter()
+                .zip(hashes)
+                .filter_map(|(height, hash)| Some((height, hash.ok()?)))
+                .collect()
+        }
+        None => HashMap::new(),
+    };
+
+    for message in batch {
+        if rpc.is_some() && chain.get(&message.block_height) != Some(&message.block_hash) {
+            warn!(
+                "Line {}: rejected: block {} is not at height {} on the local chain",
+                message.line, message.block_hash, message.block_height
+            );
+            summary.rejected += 1;
+            continue;
+        }
+        if db
+            .message_exists(&message.txid, message.payload_index)
+            .await?
+        {
+            summary.existing += 1;
+            continue;
+        }
+
+        let message_id = db
+            .insert_message_with_carrier(
+                &message.txid,
+                message.payload_index,
+                message.vout,
+                Some(message.block_hash.as_byte_array()),
+                Some(message.block_height),
+                &message.message,
+                message.carrier,
+                message.creator_address.as_deref(),
+                message.lang.as_deref(),
+            )
+            .await?;
+        if message.message.kind == AnchorKind::Identity {
+            index_identity_rotation(
+                db,
+                &message.txid,
+                message_id,
+                &message.message,
+                Some(message.block_height),
+            )
+            .await?;
+        }
+        summary.imported += 1;
+    }
+    Ok(())
+}
+
+/// RPC client for the configured node
+fn node(config: &Config) -> Result<RpcClient> {
+    connect(
+        &config.bitcoin_rpc_url,
+        &config.bitcoin_rpc_user,
+        &config.bitcoin_rpc_password,
+        config.bitcoin_rpc_proxy.as_ref(),
+        config.rpc_max_concurrency,
+        config.rpc_batch_size,
+    )
+}
@@ -1,48 +1,92 @@
 //! Main indexer logic
 
 use anyhow::{Context, Result};
-use bitcoin::consensus::encode::deserialize;
 use bitcoin::hashes::Hash;
-use bitcoin::{Block, Transaction};
-use bitcoincore_rpc::{Auth, Client, RpcApi};
+use bitcoin::{Address, Block, Network, ScriptBuf, Transaction, Txid};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
+use tokio::sync::watch;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
-use anchor_core::carrier::{CarrierSelector, CarrierType};
-use anchor_core::parse_transaction;
+use anchor_core::carrier::CarrierSelector;
+use anchor_core::{anchor_filter, AnchorKind, FilterHeader};
+use anchor_indexer::detect::{detect_block, DetectedMessage};
+use anchor_indexer::language::detect_language;
+use anchor_rpc::RpcClient;
+use anchor_specs::identity::IdentityRotationSpec;
+use anchor_specs::KindSpec;
 
+use crate::cache::ChainCache;
 use crate::config::Config;
 use crate::db::Database;
+use crate::plugins::{PluginAction, PluginAnchor, PluginContext, PluginHost, PluginPayload};
+use crate::proxy::{rpc_config, Socks5Proxy};
+
+/// Blocks fetched ahead of the one being indexed
+const PREFETCH_BLOCKS: usize = 16;
 
 /// The main indexer service
 pub struct Indexer {
-    config: Config,
-    rpc: Client,
+    /// Bitcoin RPC client, swapped on reconnect
+    rpc: RwLock<RpcClient>,
+    /// SOCKS5 proxy used for every RPC connection, including reconnects
+    rpc_proxy: Option<Socks5Proxy>,
+    /// RPC requests in flight at once
+    rpc_max_concurrency: usize,
+    /// Calls per batched RPC request
+    rpc_batch_size: usize,
+    /// Network of the connected node, for creator addresses
+    network: RwLock<Network>,
+    /// Raw transactions and blocks already fetched from the node
+    cache: ChainCache,
+    /// Polling interval in seconds (reloadable)
+    poll_interval_secs: AtomicU64,
+    /// Confirmations before a block is indexed (reloadable)
+    confirmations: AtomicU32,
     db: Database,
     carrier_selector: CarrierSelector,
+    /// WASM message plugins, if PLUGIN_DIR is set
+    plugins: Option<PluginHost>,
+    /// Store the language of Text messages
+    detect_language: bool,
 }
 
 impl Indexer {
     /// Create a new indexer instance
     pub async fn new(config: Config) -> Result<Self> {
         // Connect to Bitcoin Core
-        let rpc = Client::new(
+        let rpc = connect(
             &config.bitcoin_rpc_url,
-            Auth::UserPass(
-                config.bitcoin_rpc_user.clone(),
-                config.bitcoin_rpc_password.clone(),
-            ),
-        )
-        .context("Failed to connect to Bitcoin RPC")?;
+            &config.bitcoin_rpc_user,
+            &config.bitcoin_rpc_password,
+            config.bitcoin_rpc_proxy.as_ref(),
+            config.rpc_max_concurrency,
+            config.rpc_batch_size,
+        )?;
+        if let Some(proxy) = &config.bitcoin_rpc_proxy {
+            info!("Bitcoin RPC via SOCKS5 proxy {}", proxy.addr);
+        }
 
         // Verify connection
-        let blockchain_info = rpc.get_blockchain_info()?;
+        let blockchain_info = rpc
+            .get_blockchain_info()
+            .await
+            .context("Failed to connect to Bitcoin RPC")?;
         info!(
             "Connected to Bitcoin node: chain={}, blocks={}",
             blockchain_info.chain, blockchain_info.blocks
         );
 
+        let network = parse_network(&blockchain_info.chain)?;
+
+        let cache = ChainCache::new(&config.cache)?;
+        if let Some(dir) = cache.dir() {
+            info!("Caching raw transactions and blocks in {}", dir.display());
+        }
+
         // Connect to database
         let db = Database::connect(&config.database_url).await?;
         info!("Connected to database");
@@ -54,20 +98,90 @@ impl Indexer {
             carrier_selector.carriers().len()
         );
 
+        if config.detect_language {
+            info!("Language detection of Text messages enabled");
+        }
+
+        let plugins = config.plugins.map(PluginHost::new);
+        if let Some(plugins) = &plugins {
+            if let Err(e) = plugins.refresh() {
+                warn!("Failed to load plugins: {:#}", e);
+            }
+        }
+
         Ok(Self {
-            config,
-            rpc,
+            rpc: RwLock::new(rpc),
+            rpc_proxy: config.bitcoin_rpc_proxy,
+            rpc_max_concurrency: config.rpc_max_concurrency,
+            rpc_batch_size: config.rpc_batch_size,
+            network: RwLock::new(network),
+            cache,
+            poll_interval_secs: AtomicU64::new(config.poll_interval_secs),
+            confirmations: AtomicU32::new(config.confirmations),
             db,
             carrier_selector,
+            plugins,
+            detect_language: config.detect_language,
         })
     }
 
-    /// Run the indexer loop
-    pub async fn run(&self) -> Result<()> {
+    fn rpc(&self) -> RpcClient {
+        self.rpc.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Point the indexer at a different Bitcoin Core RPC endpoint
+    ///
+    /// The new endpoint is verified before the client is swapped; a block being
+    /// indexed finishes on the old client.
+    pub async fn reconnect(&self, url: &str, user: &str, password: &str) -> Result<()> {
+        let rpc = connect(
+            url,
+            user,
+            password,
+            self.rpc_proxy.as_ref(),
+            self.rpc_max_concurrency,
+            self.rpc_batch_size,
+        )?;
+        let blockchain_info = rpc
+            .get_blockchain_info()
+            .await
+            .context("New Bitcoin RPC endpoint is not reachable")?;
+        let network = parse_network(&blockchain_info.chain)?;
+        *self.rpc.write().unwrap_or_else(|e| e.into_inner()) = rpc;
+        *self.network.write().unwrap_or_else(|e| e.into_inner()) = network;
+        info!(
+            "Reconnected to Bitcoin node: chain={}, blocks={}",
+            blockchain_info.chain, blockchain_info.blocks
+        );
+        Ok(())
+    }
+
+    /// Update the polling interval and confirmation depth
+    pub fn set_timing(&self, poll_interval_secs: u64, confirmations: u32) {
+        self.poll_interval_secs
+            .store(poll_interval_secs, Ordering::Relaxed);
+        self.confirmations.store(confirmations, Ordering::Relaxed);
+    }
+
+    /// Pick up added, changed, or removed plugin modules
+    pub fn refresh_plugins(&self) {
+        if let Some(plugins) = &self.plugins {
+            if let Err(e) = plugins.refresh() {
+                warn!("Plugin reload failed: {:#}", e);
+            }
+        }
+    }
+
+    /// Run the indexer loop until `shutdown` turns true
+    ///
+    /// A block being indexed is finished first, so the stored height always
+    /// matches the last fully indexed block, then the in-flight plugin
+    /// webhooks are awaited and the database pool closed.
+    pub async fn run(&self, mut shutdown: watch::Receiver<bool>) -> Result<()> {
         info!("Starting indexer loop");
 
-        loop {
-            match self.index_new_blocks().await {
+        while !*shutdown.borrow() {
+            match self.index_new_blocks(&shutdown).await {
                 Ok(indexed) => {
                     if indexed > 0 {
                         info!("Indexed {} new blocks", indexed);
@@ -89,17 +203,30 @@ impl Indexer {
             }
 
             // Wait before next poll
-            sleep(Duration::from_secs(self.config.poll_interval_secs)).await;
+            tokio::select! {
+                _ = sleep(Duration::from_secs(
+                    self.poll_interval_secs.load(Ordering::Relaxed),
+                )) => {}
+                _ = shutdown.changed() => {}
+            }
+        }
+
+        info!("Indexer loop stopped");
+        if let Some(plugins) = &self.plugins {
+            plugins.flush_webhooks().await;
         }
+        self.db.close().await;
+        Ok(())
     }
 
-    /// Index any new blocks since last indexed height
-    async fn index_new_blocks(&self) -> Result<u32> {
+    /// Index any new blocks since last indexed height, stopping after the
+    /// current block once `shutdown` turns true
+    async fn index_new_blocks(&self, shutdown: &watch::Receiver<bool>) -> Result<u32> {
         let last_height = self.db.get_last_block_height().await?;
-        let current_height = self.rpc.get_block_count()? as i32;
+        let current_height = self.rpc().get_block_count().await? as i32;
 
         // Calculate safe height (accounting for confirmations)
-        let safe_height = current_height - self.config.confirmations as i32;
+        let safe_height = current_height - self.confirmations.load(Ordering::Relaxed) as i32;
 
         if safe_height <= last_height {
             debug!(
@@ -110,98 +237,185 @@ impl Indexer {
         }
 
         let mut indexed = 0;
-
-        for height in (last_height + 1)..=safe_height {
-            match self.index_block(height).await {
-                Ok(messages) => {
-                    if messages > 0 {
-                        info!("Block {}: indexed {} ANCHOR messages", height, messages);
-                    } else {
-                        debug!("Block {}: no ANCHOR messages", height);
-                    }
-                    indexed += 1;
+        let mut next_height = last_height + 1;
+
+        while next_height <= safe_height {
+            let window = (safe_height - next_height + 1).min(PREFETCH_BLOCKS as i32);
+            let heights: Vec<i32> = (next_height..next_height + window).collect();
+            next_height += window;
+
+            for (height, block) in heights.iter().zip(self.fetch_blocks(&heights).await?) {
+                let height = *height;
+                if *shutdown.borrow() {
+                    info!("Shutting down before block {}", height);
+                    return Ok(indexed);
                 }
-                Err(e) => {
-                    error!("Failed to index block {}: {}", height, e);
-                    // Check for reorg
-                    if e.to_string().contains("Block not found") {
-                        warn!("Possible reorg detected at height {}", height);
-                        self.db.handle_reorg(height).await?;
-                        return Ok(indexed);
+                let result = match block {
+                    Ok(block) => self.index_block(height, &block).await,
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(messages) => {
+                        if messages > 0 {
+                            info!("Block {}: indexed {} ANCHOR messages", height, messages);
+                        } else {
+                            debug!("Block {}: no ANCHOR messages", height);
+                        }
+                        indexed += 1;
+                    }
+                    Err(e) => {
+                        error!("Failed to index block {}: {}", height, e);
+                        // Check for reorg
+                        if e.to_string().contains("Block not found") {
+                            warn!("Possible reorg detected at height {}", height);
+                            self.db.handle_reorg(height).await?;
+                            return Ok(indexed);
+                        }
+                        return Err(e);
                     }
-                    return Err(e);
                 }
             }
         }
 
+        let (hits, misses) = self.cache.stats();
+        debug!("Chain cache: {} hits, {} misses", hits, misses);
+
         Ok(indexed)
     }
 
+    /// Fetch the blocks at `heights`: their hashes in one batch, then the
+    /// blocks that aren't cached yet concurrently
+    ///
+    /// A block that can't be fetched comes back as its error, so the blocks
+    /// before it are still indexed.
+    async fn fetch_blocks(&self, heights: &[i32]) -> Result<Vec<Result<Arc<Block>>>> {
+        let rpc = self.rpc();
+        let hashes = rpc
+            .get_block_hashes(heights.iter().map(|height| *height as u64))
+            .await?;
+
+        // Stop at the first missing hash; later blocks can't be indexed anyway
+        let found: Vec<_> = hashes
+            .iter()
+            .map_while(|hash| hash.as_ref().ok().copied())
+            .collect();
+        let cached: Vec<Option<Arc<Block>>> =
+            found.iter().map(|hash| self.cache.block(hash)).collect();
+        let missing: Vec<_> = found
+            .iter()
+            .zip(&cached)
+            .filter(|(_, block)| block.is_none())
+            .map(|(hash, _)| *hash)
+            .collect();
+        let mut fetched = rpc.get_blocks(&missing).await?.into_iter();
+
+        let mut blocks: Vec<Result<Arc<Block>>> = Vec::with_capacity(heights.len());
+        for block in cached {
+            blocks.push(match block {
+                Some(block) => Ok(block),
+                None => match fetched.next() {
+                    Some(Ok(block)) => Ok(self.cache.insert_block(block)),
+                    Some(Err(e)) => Err(e.into()),
+                    None => Err(anyhow::anyhow!("Missing block in RPC response")),
+                },
+            });
+        }
+        if let Some(Err(e)) = hashes.into_iter().nth(found.len()) {
+            blocks.push(Err(e.into()));
+        }
+        Ok(blocks)
+    }
+
     /// Index a single block
-    async fn index_block(&self, height: i32) -> Result<u32> {
-        // Get block hash
-        let block_hash = self.rpc.get_block_hash(height as u64)?;
-        let block_hash_bytes = block_hash.to_byte_array().to_vec();
-
-        // Get raw block
-        let block_hex = self.rpc.call::<String>(
-            "getblock",
-            &[
-                serde_json::json!(block_hash.to_string()),
-                serde_json::json!(0), // Raw hex format
-            ],
-        )?;
+    async fn index_block(&self, height: i32, block: &Block) -> Result<u32> {
+        let block_hash_bytes = block.block_hash().to_byte_array().to_vec();
+
+        let detected = detect_block(&self.carrier_selector, block);
 
-        let block_bytes = hex::decode(&block_hex)?;
-        let block: Block = deserialize(&block_bytes)?;
+        // Later ANCHOR transactions often spend these, so keep them at hand
+        // for creator lookups
+        for (tx, _) in &detected {
+            self.cache.insert_transaction((*tx).clone());
+        }
+        let creators = self
+            .creator_scripts(detected.iter().map(|(tx, _)| *tx))
+            .await;
+        let network = *self.network.read().unwrap_or_else(|e| e.into_inner());
 
         let mut message_count = 0;
 
         // Process each transaction
-        for tx in &block.txdata {
+        for (tx, messages) in &detected {
+            let creator_address = creators
+                .get(&tx.compute_txid())
+                .and_then(|script| Address::from_script(script, network).ok())
+                .map(|address| address.to_string());
             let count = self
-                .index_transaction(tx, Some(&block_hash_bytes), Some(height))
+                .index_transaction(
+                    tx,
+                    messages,
+                    creator_address.as_deref(),
+                    Some(&block_hash_bytes),
+                    Some(height),
+                )
                 .await?;
             message_count += count;
         }
 
+        self.store_filter(height, block, &detected, &creators)
+            .await?;
+
         // Update last indexed block
         self.db.update_last_block(&block_hash_bytes, height).await?;
 
         Ok(message_count)
     }
 
-    /// Index a single transaction
+    /// Build the block's ANCHOR activity filter and chain it onto the one
+    /// of the previous block
+    async fn store_filter(
+        &self,
+        height: i32,
+        block: &Block,
+        detected: &[(&Transaction, Vec<DetectedMessage>)],
+        creators: &HashMap<Txid, ScriptBuf>,
+    ) -> Result<()> {
+        let filter = anchor_filter(
+            block,
+            detected.iter().map(|(tx, _)| {
+                let creator = creators.get(&tx.compute_txid());
+                (*tx, creator.map(|script| script.as_script()))
+            }),
+        );
+        let previous = self
+            .db
+            .get_filter_header(height - 1)
+            .await?
+            .and_then(|header| FilterHeader::from_slice(&header).ok())
+            .unwrap_or_else(FilterHeader::all_zeros);
+        let header = filter.filter_header(&previous);
+
+        self.db
+            .upsert_anchor_filter(
+                height,
+                block.block_hash().as_byte_array(),
+                &filter.content,
+                header.as_byte_array(),
+            )
+            .await
+    }
+
+    /// Index the messages detected in a single transaction
     async fn index_transaction(
         &self,
         tx: &Transaction,
+        messages: &[DetectedMessage],
+        creator_address: Option<&str>,
         block_hash: Option<&[u8]>,
         block_height: Option<i32>,
     ) -> Result<u32> {
         let txid = tx.compute_txid();
 
-        // Try multi-carrier detection first
-        let detected = self.carrier_selector.detect(tx);
-
-        // Fall back to legacy OP_RETURN parsing if no messages detected
-        let messages: Vec<(u32, CarrierType, anchor_core::ParsedAnchorMessage)> =
-            if detected.is_empty() {
-                // Use legacy parser for backwards compatibility
-                parse_transaction(tx)
-                    .into_iter()
-                    .map(|(vout, msg)| (vout, CarrierType::OpReturn, msg))
-                    .collect()
-            } else {
-                detected
-                    .into_iter()
-                    .map(|d| (d.vout, d.carrier_type, d.message))
-                    .collect()
-            };
-
-        if messages.is_empty() {
-            return Ok(0);
-        }
-
         debug!(
             "Found {} ANCHOR messages in tx {} (carriers: {:?})",
             messages.len(),
@@ -209,25 +423,211 @@ impl Indexer {
             messages.iter().map(|(_, c, _)| c).collect::<Vec<_>>()
         );
 
-        for (vout, carrier_type, message) in &messages {
+        // Messages are keyed by their position in the transaction, since
+        // several payloads can share a vout (e.g. OP_RETURN + inscription)
+        for (payload_index, (vout, carrier_type, message)) in messages.iter().enumerate() {
+            let payload_index = payload_index as u16;
+
             // Check if already indexed
-            if self.db.message_exists(&txid, *vout).await? {
-                debug!("Message {}:{} already indexed, skipping", txid, vout);
+            if self.db.message_exists(&txid, payload_index).await? {
+                debug!(
+                    "Message {} payload {} already indexed, skipping",
+                    txid, payload_index
+                );
                 continue;
             }
 
-            self.db
+            let message_id = self
+                .db
                 .insert_message_with_carrier(
                     &txid,
+                    payload_index,
                     *vout,
                     block_hash,
                     block_height,
                     message,
                     *carrier_type,
+                    creator_address,
+                    self.detect_language
+                        .then(|| detect_language(message.kind, &message.body))
+                        .flatten(),
                 )
                 .await?;
+
+            if message.kind == AnchorKind::Identity {
+                index_identity_rotation(&self.db, &txid, message_id, message, block_height).await?;
+            }
+
+            if let Some(plugins) = self.plugins.as_ref().filter(|p| !p.is_empty()) {
+                let context = PluginContext {
+                    message_id,
+                    txid: txid.to_string(),
+                    vout: *vout,
+                    payload_index,
+                    block_height,
+                    carrier: carrier_type.to_string(),
+                    kind_unknown: !anchor_specs::is_registered_kind(u8::from(message.kind)),
+                };
+                self.apply_plugins(plugins, message, &context).await?;
+            }
         }
 
         Ok(messages.len() as u32)
     }
+
+    /// Scripts that funded each transaction's first input, by txid
+    ///
+    /// Previous transactions come from the cache, or from the node in
+    /// batches, so the node must run with `txindex=1`; lookup failures leave
+    /// the creator unknown rather than stall indexing.
+    async fn creator_scripts<'a>(
+        &self,
+        txs: impl Iterator<Item = &'a Transaction>,
+    ) -> HashMap<Txid, ScriptBuf> {
+        let prevouts: Vec<(Txid, bitcoin::OutPoint)> = txs
+            .filter(|tx| !tx.is_coinbase())
+            .filter_map(|tx| Some((tx.compute_txid(), tx.input.first()?.previous_output)))
+            .collect();
+        if prevouts.is_empty() {
+            return HashMap::new();
+        }
+
+        let mut prev_txs: HashMap<Txid, Arc<Transaction>> = HashMap::new();
+        let mut missing = Vec::new();
+        for (_, prevout) in &prevouts {
+            match self.cache.transaction(&prevout.txid) {
+                Some(prev_tx) => {
+                    prev_txs.insert(prevout.txid, prev_tx);
+                }
+                None if !missing.contains(&prevout.txid) => missing.push(prevout.txid),
+                None => {}
+            }
+        }
+
+        if !missing.is_empty() {
+            match self.rpc().get_raw_transactions(&missing).await {
+                Ok(fetched) => {
+                    for (txid, prev_tx) in missing.iter().zip(fetched) {
+                        match prev_tx {
+                            Ok(prev_tx) => {
+                                prev_txs.insert(*txid, self.cache.insert_transaction(prev_tx));
+                            }
+                            Err(e) => debug!("No previous transaction {}: {}", txid, e),
+                        }
+                    }
+                }
+                Err(e) => debug!("No creators for {} transactions: {}", missing.len(), e),
+            }
+        }
+
+        prevouts
+            .into_iter()
+            .filter_map(|(txid, prevout)| {
+                let output = prev_txs
+                    .get(&prevout.txid)?
+                    .output
+                    .get(prevout.vout as usize)?;
+                Some((txid, output.script_pubkey.clone()))
+            })
+            .collect()
+    }
+
+    /// Run the plugins on a freshly indexed message and apply their actions
+    async fn apply_plugins(
+        &self,
+        plugins: &PluginHost,
+        message: &anchor_core::ParsedAnchorMessage,
+        context: &PluginContext,
+    ) -> Result<()> {
+        let payload = PluginPayload {
+            kind: u8::from(message.kind),
+            anchors: message
+                .anchors
+                .iter()
+                .map(|anchor| PluginAnchor {
+                    txid_prefix: hex::encode(anchor.txid_prefix),
+                    vout: anchor.vout,
+                })
+                .collect(),
+            body_hex: hex::encode(&message.body),
+        };
+
+        for (plugin, action) in plugins.run(&payload, context) {
+            match action {
+                PluginAction::Tag { tag } => {
+                    self.db
+                        .insert_plugin_tag(context.message_id, &plugin, &tag)
+                        .await?
+                }
+                PluginAction::Store { key, value } => {
+                    self.db
+                        .upsert_plugin_field(context.message_id, &plugin, &key, &value)
+                        .await?
+                }
+                PluginAction::Webhook { url, body } => {
+                    plugins.send_webhook(&plugin, context, &url, body)
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Chain a kind 6 message onto its identity if it is a valid rotation
+pub(crate) async fn index_identity_rotation(
+    db: &Database,
+    txid: &bitcoin::Txid,
+    message_id: i32,
+    message: &anchor_core::ParsedAnchorMessage,
+    block_height: Option<i32>,
+) -> Result<()> {
+    let rotation = match IdentityRotationSpec::from_bytes(&message.body)
+        .and_then(|rotation| rotation.validated())
+    {
+        Ok(rotation) => rotation,
+        Err(e) => {
+            debug!("Ignoring invalid identity rotation in {}: {}", txid, e);
+            return Ok(());
+        }
+    };
+
+    if db
+        .insert_identity_rotation(message_id, &rotation, block_height)
+        .await?
+    {
+        info!(
+            "Identity key {} rotated to {} in {}",
+            hex::encode(rotation.old_pubkey),
+            hex::encode(rotation.new_pubkey),
+            txid
+        );
+    } else {
+        debug!(
+            "Identity rotation in {} conflicts with an earlier one",
+            txid
+        );
+    }
+
+    Ok(())
+}
+
+/// Build the pooled RPC client for an endpoint
+pub(crate) fn connect(
+    url: &str,
+    user: &str,
+    password: &str,
+    proxy: Option<&Socks5Proxy>,
+    max_concurrency: usize,
+    batch_size: usize,
+) -> Result<RpcClient> {
+    let mut config = rpc_config(url, user, password, proxy);
+    config.max_concurrency = max_concurrency;
+    config.batch_size = batch_size;
+    RpcClient::new(config).context("Failed to create Bitcoin RPC client")
+}
+
+/// Network for a `getblockchaininfo` chain name
+pub(crate) fn parse_network(chain: &str) -> Result<Network> {
+    Network::from_core_arg(chain).with_context(|| format!("Unknown chain '{}'", chain))
 }
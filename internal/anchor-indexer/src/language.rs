@@ -0,0 +1,26 @@
+//! Language detection of Text messages
+//!
+//! With `DETECT_LANGUAGE` set, the body of each Text message is run through
+//! whatlang and the ISO 639-3 code of its language (`eng`, `spa`, ...) is
+//! stored in `messages.lang`, so explorers can filter feeds by language.
+//! Short or mixed texts that can't be told apart reliably are left NULL.
+
+use anchor_core::AnchorKind;
+use whatlang::detect;
+
+/// Shortest body, in characters, worth running detection on
+const MIN_CHARS: usize = 12;
+
+/// Language of a message, for Text messages whose language is clear
+pub fn detect_language(kind: AnchorKind, body: &[u8]) -> Option<&'static str> {
+    if kind != AnchorKind::Text {
+        return None;
+    }
+    let text = std::str::from_utf8(body).ok()?.trim();
+    if text.chars().count() < MIN_CHARS {
+        return None;
+    }
+    detect(text)
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang().code())
+}
@@ -0,0 +1,8 @@
+//! ANCHOR Protocol Indexer library
+//!
+//! The service itself lives in the `anchor-indexer` binary. This target
+//! exposes the parts of block processing that need no database or node, so
+//! they can be benchmarked on their own (`cargo bench -p anchor-indexer`).
+
+pub mod detect;
+pub mod language;
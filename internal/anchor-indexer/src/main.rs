@@ -1,23 +1,75 @@
 //! ANCHOR Protocol Indexer
 //!
 //! Scans the Bitcoin blockchain and indexes ANCHOR messages.
+//!
+//! Run without arguments to index; `export` and `import` exchange indexed
+//! messages with other deployments (see [`exchange`]).
 
+mod cache;
 mod config;
 mod db;
+mod exchange;
 mod indexer;
+mod plugins;
+mod proxy;
+mod reload;
+mod shutdown;
 
 use anyhow::Result;
+use std::sync::Arc;
 use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload as log_reload, EnvFilter};
 
 use crate::config::Config;
+use crate::exchange::{ExportArgs, ImportArgs};
 use crate::indexer::Indexer;
+use crate::reload::ConfigReloader;
+
+const USAGE: &str = "\
+Usage: anchor-indexer [COMMAND]
+
+Without a command, index the chain until SIGTERM.
+
+Commands:
+  export [--kind K[,K...]] [--height-range FROM-TO] [--output FILE]
+      Write confirmed messages with their block proofs (default: stdout)
+  import [--input FILE] [--no-chain-check]
+      Verify and store messages from an export (default: stdin)
+";
+
+fn usage(error: &str) -> ! {
+    eprintln!("anchor-indexer: {}\n\n{}", error, USAGE);
+    std::process::exit(2);
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {}
+        Some("export") => {
+            let args = ExportArgs::parse(&args[1..]).unwrap_or_else(|e| usage(&e));
+            let config = command_config()?;
+            return exchange::export(&config, &args).await;
+        }
+        Some("import") => {
+            let args = ImportArgs::parse(&args[1..]).unwrap_or_else(|e| usage(&e));
+            let config = command_config()?;
+            return exchange::import(&config, &args).await;
+        }
+        Some("-h" | "--help") => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+        Some(other) => usage(&format!("unknown command {}", other)),
+    }
+
+    // Initialize logging (the filter can be swapped on config reload)
+    let (log_filter, log_handle) = log_reload::Layer::new(EnvFilter::from_default_env());
+    tracing_subscriber::registry()
+        .with(log_filter)
+        .with(fmt::layer())
         .init();
 
     info!("Starting ANCHOR Indexer");
@@ -27,8 +79,26 @@ async fn main() -> Result<()> {
     let config = Config::from_env()?;
 
     // Create and run indexer
-    let indexer = Indexer::new(config).await?;
-    indexer.run().await?;
+    let indexer = Arc::new(Indexer::new(config.clone()).await?);
 
+    // Apply the settings file and watch it for changes and SIGHUP
+    reload::start_watcher(ConfigReloader::new(config, log_handle), indexer.clone()).await;
+
+    // Runs until SIGTERM, finishing the block in progress
+    indexer.run(shutdown::listen()).await?;
+
+    info!("Indexer stopped");
     Ok(())
 }
+
+/// Logging and configuration for a one-off command; logs go to stderr so
+/// `export` can write to stdout
+fn command_config() -> Result<Config> {
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .init();
+
+    dotenvy::dotenv().ok();
+    Config::from_env()
+}
@@ -0,0 +1,382 @@
+//! WASM message plugins
+//!
+//! Operators drop WebAssembly modules (`*.wasm`, or `*.wat` text) into
+//! `PLUGIN_DIR`; each one sees every indexed message and returns actions the
+//! indexer applies: tag the message, store extra fields, or call a webhook.
+//! The directory is rescanned on the config watcher's schedule and on SIGHUP,
+//! so plugins can be added, replaced, or removed without a restart.
+//!
+//! ## ABI
+//!
+//! A plugin exports its `memory` and two functions:
+//!
+//! - `alloc(len: i32) -> i32` returns a buffer for the input
+//! - `on_message(ptr: i32, len: i32) -> i64` receives the input JSON and
+//!   returns `(out_ptr << 32) | out_len` pointing at the output JSON, or 0
+//!   for no actions
+//!
+//! Input: `{"payload": {"kind", "anchors", "body_hex"}, "context": {...}}`.
+//! Output: `{"actions": [{"type": "tag", "tag": ".."},
+//! {"type": "store", "key": "..", "value": <json>},
+//! {"type": "webhook", "url": "..", "body": <json>}]}`.
+//!
+//! ## Sandbox
+//!
+//! Plugins get no imports (a module that imports anything fails to load),
+//! run in a fresh instance per message, are limited by fuel and memory,
+//! and a trap or bad output only skips that plugin for that message.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+use wasmi::{Config as WasmConfig, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Largest output a plugin may return
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Largest stored field value
+const MAX_FIELD_BYTES: usize = 16 * 1024;
+
+/// Timeout for webhook actions
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Plugin host limits
+#[derive(Debug, Clone)]
+pub struct PluginConfig {
+    pub dir: PathBuf,
+    /// Fuel (roughly, instructions) per plugin call
+    pub fuel: u64,
+    /// Linear memory limit per plugin instance, in bytes
+    pub max_memory_bytes: usize,
+}
+
+/// Message handed to plugins
+#[derive(Debug, Serialize)]
+pub struct PluginPayload {
+    pub kind: u8,
+    pub anchors: Vec<PluginAnchor>,
+    pub body_hex: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginAnchor {
+    pub txid_prefix: String,
+    pub vout: u8,
+}
+
+/// Where the message was found
+#[derive(Debug, Serialize)]
+pub struct PluginContext {
+    pub message_id: i32,
+    pub txid: String,
+    pub vout: u32,
+    pub payload_index: u16,
+    pub block_height: Option<i32>,
+    pub carrier: String,
+    pub kind_unknown: bool,
+}
+
+#[derive(Serialize)]
+struct PluginInput<'a> {
+    payload: &'a PluginPayload,
+    context: &'a PluginContext,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    actions: Vec<PluginAction>,
+}
+
+/// An action requested by a plugin
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginAction {
+    Tag {
+        tag: String,
+    },
+    Store {
+        key: String,
+        value: serde_json::Value,
+    },
+    Webhook {
+        url: String,
+        #[serde(default)]
+        body: serde_json::Value,
+    },
+}
+
+impl PluginAction {
+    fn validate(&self) -> Result<()> {
+        match self {
+            PluginAction::Tag { tag } if tag.is_empty() || tag.len() > 64 => {
+                bail!("tag must be 1-64 bytes")
+            }
+            PluginAction::Store { key, value } => {
+                if key.is_empty() || key.len() > 64 {
+                    bail!("field key must be 1-64 bytes");
+                }
+                if value.to_string().len() > MAX_FIELD_BYTES {
+                    bail!("field '{}' exceeds {} bytes", key, MAX_FIELD_BYTES);
+                }
+                Ok(())
+            }
+            PluginAction::Webhook { url, .. }
+                if !(url.starts_with("http://") || url.starts_with("https://")) =>
+            {
+                bail!("webhook url must be http(s)")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+struct Plugin {
+    name: String,
+    module: Module,
+}
+
+/// Loads plugins from a directory and runs them against messages
+pub struct PluginHost {
+    config: PluginConfig,
+    engine: Engine,
+    plugins: RwLock<Vec<Plugin>>,
+    /// Modification time of every loaded file, to spot changes
+    loaded: Mutex<HashMap<PathBuf, SystemTime>>,
+    http: reqwest::Client,
+    /// Webhook calls still in flight, awaited on shutdown
+    webhooks: Mutex<JoinSet<()>>,
+}
+
+impl PluginHost {
+    pub fn new(config: PluginConfig) -> Self {
+        let mut wasm_config = WasmConfig::default();
+        wasm_config.consume_fuel(true);
+        Self {
+            config,
+            engine: Engine::new(&wasm_config),
+            plugins: RwLock::new(Vec::new()),
+            loaded: Mutex::new(HashMap::new()),
+            http: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            webhooks: Mutex::new(JoinSet::new()),
+        }
+    }
+
+    /// Whether any plugin is loaded
+    pub fn is_empty(&self) -> bool {
+        self.plugins
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+
+    /// Reload the plugin set if files were added, changed, or removed
+    ///
+    /// A module that fails to compile is skipped; the rest still load.
+    pub fn refresh(&self) -> Result<()> {
+        let files = self.scan()?;
+        let mut loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+        if *loaded == files {
+            return Ok(());
+        }
+
+        let mut plugins = Vec::with_capacity(files.len());
+        let mut paths: Vec<&PathBuf> = files.keys().collect();
+        paths.sort();
+        for path in paths {
+            match self.compile(path) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => warn!("Skipping plugin {:?}: {:#}", path, e),
+            }
+        }
+
+        info!(
+            "Loaded {} plugins from {:?}: {:?}",
+            plugins.len(),
+            self.config.dir,
+            plugins.iter().map(|p| p.name.as_str()).collect::<Vec<_>>()
+        );
+        *self.plugins.write().unwrap_or_else(|e| e.into_inner()) = plugins;
+        *loaded = files;
+        Ok(())
+    }
+
+    /// Run every plugin on a message and collect their actions
+    pub fn run(
+        &self,
+        payload: &PluginPayload,
+        context: &PluginContext,
+    ) -> Vec<(String, PluginAction)> {
+        let plugins = self.plugins.read().unwrap_or_else(|e| e.into_inner());
+        if plugins.is_empty() {
+            return Vec::new();
+        }
+        let input = match serde_json::to_vec(&PluginInput { payload, context }) {
+            Ok(input) => input,
+            Err(e) => {
+                warn!("Failed to encode plugin input: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut actions = Vec::new();
+        for plugin in plugins.iter() {
+            match self.call(plugin, &input) {
+                Ok(output) => {
+                    for action in output {
+                        match action.validate() {
+                            Ok(()) => actions.push((plugin.name.clone(), action)),
+                            Err(e) => warn!(
+                                "Plugin {} returned an invalid action for {}: {}",
+                                plugin.name, context.txid, e
+                            ),
+                        }
+                    }
+                }
+                Err(e) => warn!(
+                    "Plugin {} failed on {}:{}: {:#}",
+                    plugin.name, context.txid, context.payload_index, e
+                ),
+            }
+        }
+        actions
+    }
+
+    /// POST a webhook action in the background
+    pub fn send_webhook(
+        &self,
+        plugin: &str,
+        context: &PluginContext,
+        url: &str,
+        body: serde_json::Value,
+    ) {
+        let request = self.http.post(url).json(&serde_json::json!({
+            "plugin": plugin,
+            "txid": context.txid,
+            "payload_index": context.payload_index,
+            "block_height": context.block_height,
+            "body": body,
+        }));
+        let (plugin, url) = (plugin.to_string(), url.to_string());
+        let mut webhooks = self.webhooks.lock().unwrap_or_else(|e| e.into_inner());
+        // Reap the calls that are done so the set stays small
+        while webhooks.try_join_next().is_some() {}
+        webhooks.spawn(async move {
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => {}
+                Err(e) => warn!("Plugin {} webhook to {} failed: {}", plugin, url, e),
+            }
+        });
+    }
+
+    /// Wait for the webhook calls still in flight (each is bounded by the
+    /// webhook timeout)
+    pub async fn flush_webhooks(&self) {
+        let mut webhooks =
+            std::mem::take(&mut *self.webhooks.lock().unwrap_or_else(|e| e.into_inner()));
+        if !webhooks.is_empty() {
+            info!("Waiting for {} plugin webhooks", webhooks.len());
+        }
+        while webhooks.join_next().await.is_some() {}
+    }
+
+    fn scan(&self) -> Result<HashMap<PathBuf, SystemTime>> {
+        let entries = match std::fs::read_dir(&self.config.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {:?}", self.config.dir))
+            }
+        };
+
+        let mut files = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_plugin = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("wasm" | "wat")
+            );
+            if !is_plugin {
+                continue;
+            }
+            if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                files.insert(path, modified);
+            }
+        }
+        Ok(files)
+    }
+
+    fn compile(&self, path: &Path) -> Result<Plugin> {
+        let bytes = std::fs::read(path)?;
+        let module = Module::new(&self.engine, bytes).map_err(|e| anyhow!("{}", e))?;
+        if module.imports().len() > 0 {
+            bail!("plugins may not import host functions");
+        }
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("plugin")
+            .to_string();
+        Ok(Plugin { name, module })
+    }
+
+    fn call(&self, plugin: &Plugin, input: &[u8]) -> Result<Vec<PluginAction>> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.config.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(self.config.fuel)
+            .map_err(|e| anyhow!("{}", e))?;
+
+        let instance = Linker::new(&self.engine)
+            .instantiate_and_start(&mut store, &plugin.module)
+            .map_err(|e| anyhow!("instantiation failed: {}", e))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .context("missing `memory` export")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| anyhow!("missing `alloc` export: {}", e))?;
+        let on_message = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "on_message")
+            .map_err(|e| anyhow!("missing `on_message` export: {}", e))?;
+
+        let len = i32::try_from(input.len()).context("input too large")?;
+        let ptr = alloc
+            .call(&mut store, len)
+            .map_err(|e| anyhow!("alloc trapped: {}", e))?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| anyhow!("alloc returned a bad buffer: {}", e))?;
+
+        let packed = on_message
+            .call(&mut store, (ptr, len))
+            .map_err(|e| anyhow!("on_message trapped: {}", e))? as u64;
+        if packed == 0 {
+            return Ok(Vec::new());
+        }
+
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        if out_len > MAX_OUTPUT_BYTES {
+            bail!("output exceeds {} bytes", MAX_OUTPUT_BYTES);
+        }
+        let mut output = vec![0u8; out_len];
+        memory
+            .read(&store, out_ptr, &mut output)
+            .map_err(|e| anyhow!("output out of bounds: {}", e))?;
+        let output: PluginOutput =
+            serde_json::from_slice(&output).context("output is not valid JSON")?;
+        Ok(output.actions)
+    }
+}
@@ -0,0 +1,82 @@
+//! SOCKS5 proxy for the Bitcoin Core RPC connection
+//!
+//! `BITCOIN_RPC_PROXY` takes `socks5://[user:pass@]host:port` (a bare
+//! `host:port` is accepted too). The RPC transport resolves the node's
+//! hostname locally, so an `.onion` RPC endpoint is not reachable through it.
+
+use anchor_rpc::RpcConfig;
+use anyhow::{Context, Result};
+use std::env;
+
+/// A SOCKS5 proxy endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Socks5Proxy {
+    /// `host:port` of the proxy
+    pub addr: String,
+    /// Username and password, if the proxy requires them
+    pub auth: Option<(String, String)>,
+}
+
+impl Socks5Proxy {
+    /// Parse a `socks5://`, `socks5h://` or bare `host:port` proxy address
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let rest = match value.split_once("://") {
+            Some(("socks5" | "socks5h", rest)) => rest,
+            Some(_) => {
+                anyhow::bail!("Unsupported proxy '{}': expected socks5://", value)
+            }
+            None => value,
+        };
+        let rest = rest.trim_end_matches('/');
+
+        let (auth, addr) = match rest.rsplit_once('@') {
+            Some((credentials, addr)) => {
+                let (user, password) = credentials
+                    .split_once(':')
+                    .context("Proxy credentials must be user:password")?;
+                (Some((user.to_string(), password.to_string())), addr)
+            }
+            None => (None, rest),
+        };
+
+        let (host, port) = addr
+            .rsplit_once(':')
+            .with_context(|| format!("Proxy '{}' must include a port", value))?;
+        if host.is_empty() {
+            anyhow::bail!("Proxy '{}' has no host", value);
+        }
+        port.parse::<u16>()
+            .with_context(|| format!("Invalid proxy port in '{}'", value))?;
+
+        Ok(Self {
+            addr: addr.to_string(),
+            auth,
+        })
+    }
+
+    /// Read an optional proxy from an env var; unset or empty means direct
+    pub fn from_env(name: &str) -> Result<Option<Self>> {
+        match env::var(name) {
+            Ok(value) if !value.trim().is_empty() => Self::parse(&value)
+                .with_context(|| format!("Invalid {}", name))
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Proxy URL; hostnames are resolved locally
+    pub fn url(&self) -> String {
+        match &self.auth {
+            Some((user, password)) => format!("socks5://{}:{}@{}", user, password, self.addr),
+            None => format!("socks5://{}", self.addr),
+        }
+    }
+}
+
+/// Bitcoin Core RPC client settings, connecting through `proxy` when set
+pub fn rpc_config(url: &str, user: &str, password: &str, proxy: Option<&Socks5Proxy>) -> RpcConfig {
+    let mut config = RpcConfig::new(url, user, password);
+    config.proxy = proxy.map(Socks5Proxy::url);
+    config
+}
@@ -0,0 +1,214 @@
+//! Runtime configuration reload
+//!
+//! A JSON settings file (`CONFIG_FILE`, default `indexer.json`) overlays the
+//! environment configuration for the Bitcoin RPC endpoint, polling interval,
+//! confirmation depth, and log filter. It is re-read when it changes on disk
+//! and on SIGHUP, without restarting the indexer loop. An invalid file or
+//! unreachable RPC endpoint leaves the running settings as is. The same
+//! watcher rescans the plugin directory (see [`crate::plugins`]).
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::config::Config;
+use crate::indexer::Indexer;
+
+/// Handle used to swap the active log filter
+pub type LogHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Settings file contents; every field is optional and falls back to the env
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub bitcoin_rpc_url: Option<String>,
+    pub bitcoin_rpc_user: Option<String>,
+    pub bitcoin_rpc_password: Option<String>,
+    pub poll_interval_secs: Option<u64>,
+    pub confirmations: Option<u32>,
+    /// Log filter directive, e.g. `info` or `anchor_indexer=debug`
+    pub log_level: Option<String>,
+}
+
+/// Settings derived from the env and the file
+#[derive(Debug, Clone, PartialEq)]
+struct Applied {
+    rpc: (String, String, String),
+    poll_interval_secs: u64,
+    confirmations: u32,
+    log_level: Option<String>,
+}
+
+/// Applies the settings file to a running indexer
+pub struct ConfigReloader {
+    path: PathBuf,
+    base: Config,
+    log_handle: LogHandle,
+    /// Held across the reconnect so reloads apply one at a time
+    applied: tokio::sync::Mutex<Applied>,
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl ConfigReloader {
+    pub fn new(base: Config, log_handle: LogHandle) -> Self {
+        let path = std::env::var("CONFIG_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("indexer.json"));
+        let applied = Applied {
+            rpc: (
+                base.bitcoin_rpc_url.clone(),
+                base.bitcoin_rpc_user.clone(),
+                base.bitcoin_rpc_password.clone(),
+            ),
+            poll_interval_secs: base.poll_interval_secs,
+            confirmations: base.confirmations,
+            log_level: None,
+        };
+        Self {
+            path,
+            base,
+            log_handle,
+            applied: tokio::sync::Mutex::new(applied),
+            last_modified: Mutex::new(None),
+        }
+    }
+
+    /// Re-read the settings file and apply any changes to the indexer
+    pub async fn reload(&self, indexer: &Indexer) -> Result<Vec<String>> {
+        let file = self.read_file()?;
+        let filter = match &file.log_level {
+            Some(level) => EnvFilter::try_new(level)
+                .with_context(|| format!("Invalid log_level '{}'", level))?,
+            None => EnvFilter::from_default_env(),
+        };
+
+        let new = Applied {
+            rpc: (
+                file.bitcoin_rpc_url
+                    .unwrap_or_else(|| self.base.bitcoin_rpc_url.clone()),
+                file.bitcoin_rpc_user
+                    .unwrap_or_else(|| self.base.bitcoin_rpc_user.clone()),
+                file.bitcoin_rpc_password
+                    .unwrap_or_else(|| self.base.bitcoin_rpc_password.clone()),
+            ),
+            poll_interval_secs: file
+                .poll_interval_secs
+                .unwrap_or(self.base.poll_interval_secs),
+            confirmations: file.confirmations.unwrap_or(self.base.confirmations),
+            log_level: file.log_level,
+        };
+
+        let mut applied = self.applied.lock().await;
+        let mut changed = Vec::new();
+        if applied.rpc != new.rpc {
+            indexer
+                .reconnect(&new.rpc.0, &new.rpc.1, &new.rpc.2)
+                .await?;
+            changed.push("bitcoin_rpc".to_string());
+        }
+        if applied.poll_interval_secs != new.poll_interval_secs {
+            changed.push("poll_interval_secs".to_string());
+        }
+        if applied.confirmations != new.confirmations {
+            changed.push("confirmations".to_string());
+        }
+        indexer.set_timing(new.poll_interval_secs, new.confirmations);
+        if applied.log_level != new.log_level {
+            self.log_handle
+                .reload(filter)
+                .context("Failed to swap log filter")?;
+            changed.push("log_level".to_string());
+        }
+        *applied = new;
+
+        if changed.is_empty() {
+            info!("Configuration reloaded from {:?}: no changes", self.path);
+        } else {
+            info!("Configuration reloaded from {:?}: {:?}", self.path, changed);
+        }
+        Ok(changed)
+    }
+
+    /// Read the settings file; a missing file means no overrides
+    fn read_file(&self) -> Result<ConfigFile> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid config file {:?}", self.path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ConfigFile::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", self.path)),
+        }
+    }
+
+    /// Whether the file changed on disk since the last check
+    fn file_changed(&self) -> bool {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok();
+        let mut last = self.last_modified.lock().unwrap_or_else(|e| e.into_inner());
+        let changed = modified != *last;
+        *last = modified;
+        changed
+    }
+}
+
+/// Apply the settings file now, then reload when it changes or on SIGHUP
+pub async fn start_watcher(reloader: ConfigReloader, indexer: Arc<Indexer>) {
+    reloader.file_changed();
+    if let Err(e) = reloader.reload(&indexer).await {
+        warn!("Ignoring settings file: {:#}", e);
+    }
+
+    let interval = std::env::var("CONFIG_WATCH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+    info!(
+        "Watching {:?} for configuration changes every {}s",
+        reloader.path, interval
+    );
+
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(signal) => Some(signal),
+            Err(e) => {
+                warn!("SIGHUP reload unavailable: {}", e);
+                None
+            }
+        };
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+
+        loop {
+            #[cfg(unix)]
+            let triggered = tokio::select! {
+                _ = ticker.tick() => reloader.file_changed(),
+                Some(_) = async {
+                    match hangup.as_mut() {
+                        Some(signal) => signal.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    info!("SIGHUP received, reloading configuration");
+                    true
+                }
+            };
+            #[cfg(not(unix))]
+            let triggered = {
+                ticker.tick().await;
+                reloader.file_changed()
+            };
+
+            if triggered {
+                if let Err(e) = reloader.reload(&indexer).await {
+                    error!("Configuration reload failed: {:#}", e);
+                }
+            }
+            indexer.refresh_plugins();
+        }
+    });
+}
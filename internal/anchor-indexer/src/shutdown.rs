@@ -0,0 +1,47 @@
+//! Shutdown on SIGTERM
+//!
+//! Container restarts send SIGTERM (Ctrl-C when run by hand). The indexer
+//! finishes the block it is on before stopping, so a restart never leaves a
+//! block half indexed behind the stored height.
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// A flag that turns true once the process is asked to stop
+pub fn listen() -> watch::Receiver<bool> {
+    let (stop, stopping) = watch::channel(false);
+    tokio::spawn(async move {
+        signal().await;
+        let _ = stop.send(true);
+    });
+    stopping
+}
+
+/// Wait for SIGTERM or Ctrl-C
+async fn signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(signal) => Some(signal),
+                Err(e) => {
+                    warn!("SIGTERM handler unavailable: {}", e);
+                    None
+                }
+            };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => info!("Ctrl-C received, shutting down"),
+            Some(_) = async {
+                match terminate.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => info!("SIGTERM received, shutting down"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Ctrl-C received, shutting down");
+    }
+}
@@ -0,0 +1,497 @@
+//! # ANCHOR RPC
+//!
+//! Pooled, batched JSON-RPC client for Bitcoin Core, shared by the indexer
+//! and the wallet service.
+//!
+//! - HTTP connections are kept alive and reused, so concurrent calls don't
+//!   queue behind one socket
+//! - [`RpcClient::batch`] sends many calls of one method as JSON-RPC batch
+//!   arrays of up to `batch_size` calls each
+//! - At most `max_concurrency` HTTP requests are in flight per client, which
+//!   keeps large batches within the node's `rpcworkqueue`
+//!
+//! Bitcoin Core answers a failed call with a non-2xx status and a JSON error
+//! body; those and per-call errors inside a batch both surface as
+//! [`RpcError::Rpc`].
+
+use bitcoin::consensus::encode::deserialize;
+use bitcoin::{Block, BlockHash, Transaction, Txid};
+use futures::future::join_all;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// Default HTTP requests in flight per client
+///
+/// Bitcoin Core serves 4 RPC threads by default (`rpcthreads`).
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Default calls per batch request
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// `RPC_INVALID_ADDRESS_OR_KEY`, returned for unknown blocks and transactions
+const RPC_NOT_FOUND: i64 = -5;
+
+/// Client settings
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    pub url: String,
+    pub user: String,
+    pub password: String,
+    /// Proxy URL, e.g. `socks5h://tor:9050`
+    pub proxy: Option<String>,
+    /// HTTP requests in flight at once
+    pub max_concurrency: usize,
+    /// Calls per batch request
+    pub batch_size: usize,
+    /// Timeout of a single HTTP request, batches included
+    pub timeout: Duration,
+}
+
+impl RpcConfig {
+    /// Settings with the default limits and no proxy
+    pub fn new(url: &str, user: &str, password: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            user: user.to_string(),
+            password: password.to_string(),
+            proxy: None,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            batch_size: DEFAULT_BATCH_SIZE,
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+/// RPC errors
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    #[error("RPC transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    #[error("RPC request failed with HTTP {0}")]
+    Status(u16),
+
+    #[error("Invalid RPC response: {0}")]
+    InvalidResponse(String),
+}
+
+impl RpcError {
+    /// Bitcoin Core error code, for errors returned by the node
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Self::Rpc { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Whether the node doesn't know the requested block or transaction
+    pub fn is_not_found(&self) -> bool {
+        self.code() == Some(RPC_NOT_FOUND)
+    }
+}
+
+/// Chain and height reported by `getblockchaininfo`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockchainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    #[serde(default)]
+    pub pruned: bool,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: &'a [Value],
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    result: Value,
+    #[serde(default)]
+    error: Option<ErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    fn into_result(self) -> Result<Value, RpcError> {
+        match self.error {
+            Some(error) => Err(RpcError::Rpc {
+                code: error.code,
+                message: error.message,
+            }),
+            None => Ok(self.result),
+        }
+    }
+}
+
+struct Inner {
+    http: reqwest::Client,
+    url: String,
+    user: String,
+    password: String,
+    permits: Semaphore,
+    batch_size: usize,
+    next_id: AtomicU64,
+}
+
+/// Bitcoin Core JSON-RPC client; clones share the connection pool and limits
+#[derive(Clone)]
+pub struct RpcClient {
+    inner: Arc<Inner>,
+}
+
+impl RpcClient {
+    /// Build a client; no connection is made until the first call
+    pub fn new(config: RpcConfig) -> Result<Self, RpcError> {
+        let max_concurrency = config.max_concurrency.max(1);
+        let mut builder = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .pool_max_idle_per_host(max_concurrency)
+            .tcp_keepalive(Duration::from_secs(60));
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                http: builder.build()?,
+                url: config.url,
+                user: config.user,
+                password: config.password,
+                permits: Semaphore::new(max_concurrency),
+                batch_size: config.batch_size.max(1),
+                next_id: AtomicU64::new(1),
+            }),
+        })
+    }
+
+    /// Endpoint URL
+    pub fn url(&self) -> &str {
+        &self.inner.url
+    }
+
+    /// Make a single call
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[Value],
+    ) -> Result<T, RpcError> {
+        let id = self.reserve_ids(1);
+        let body = serde_json::to_value(Request {
+            jsonrpc: "1.0",
+            id,
+            method,
+            params,
+        })
+        .map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+
+        let response: Response = serde_json::from_value(self.post(&body).await?)
+            .map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+        parse(response.into_result()?)
+    }
+
+    /// Call `method` once per parameter list, in batches of `batch_size`
+    ///
+    /// Results come back in the order of `params`. The outer error means a
+    /// whole batch failed; the inner ones are the node's per-call errors.
+    pub async fn batch<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Vec<Value>>,
+    ) -> Result<Vec<Result<T, RpcError>>, RpcError> {
+        self.batch_chunked(method, params, self.inner.batch_size)
+            .await
+    }
+
+    async fn batch_chunked<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Vec<Value>>,
+        chunk_size: usize,
+    ) -> Result<Vec<Result<T, RpcError>>, RpcError> {
+        if params.is_empty() {
+            return Ok(Vec::new());
+        }
+        let first_id = self.reserve_ids(params.len());
+
+        let chunks = params.chunks(chunk_size).enumerate().map(|(index, chunk)| {
+            let chunk_first_id = first_id + (index * chunk_size) as u64;
+            self.send_batch(method, chunk_first_id, chunk)
+        });
+
+        let mut results = Vec::with_capacity(params.len());
+        for chunk in join_all(chunks).await {
+            results.extend(chunk?.into_iter().map(|result| result.and_then(parse)));
+        }
+        Ok(results)
+    }
+
+    async fn send_batch(
+        &self,
+        method: &str,
+        first_id: u64,
+        params: &[Vec<Value>],
+    ) -> Result<Vec<Result<Value, RpcError>>, RpcError> {
+        let requests = params
+            .iter()
+            .enumerate()
+            .map(|(offset, params)| {
+                serde_json::to_value(Request {
+                    jsonrpc: "1.0",
+                    id: first_id + offset as u64,
+                    method,
+                    params,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+
+        let body = self.post(&Value::Array(requests)).await?;
+        match_batch(body, first_id, params.len())
+    }
+
+    /// POST a request body, waiting for a free slot first
+    async fn post(&self, body: &Value) -> Result<Value, RpcError> {
+        let _permit = self
+            .inner
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+
+        let response = self
+            .inner
+            .http
+            .post(&self.inner.url)
+            .basic_auth(&self.inner.user, Some(&self.inner.password))
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let bytes = response.bytes().await?;
+
+        // Core sends JSON error bodies with 404 and 500 responses
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => Ok(value),
+            Err(_) if !status.is_success() => Err(RpcError::Status(status.as_u16())),
+            Err(e) => Err(RpcError::InvalidResponse(e.to_string())),
+        }
+    }
+
+    fn reserve_ids(&self, count: usize) -> u64 {
+        self.inner
+            .next_id
+            .fetch_add(count as u64, Ordering::Relaxed)
+    }
+
+    /// `getblockchaininfo`
+    pub async fn get_blockchain_info(&self) -> Result<BlockchainInfo, RpcError> {
+        self.call("getblockchaininfo", &[]).await
+    }
+
+    /// `getblockcount`
+    pub async fn get_block_count(&self) -> Result<u64, RpcError> {
+        self.call("getblockcount", &[]).await
+    }
+
+    /// Block hashes at `heights`, in one batch
+    pub async fn get_block_hashes(
+        &self,
+        heights: impl IntoIterator<Item = u64>,
+    ) -> Result<Vec<Result<BlockHash, RpcError>>, RpcError> {
+        let params = heights
+            .into_iter()
+            .map(|height| vec![Value::from(height)])
+            .collect();
+        self.batch("getblockhash", params).await
+    }
+
+    /// Raw blocks, fetched concurrently
+    ///
+    /// Blocks can be several megabytes, so each one is its own request
+    /// rather than part of a batch.
+    pub async fn get_blocks(
+        &self,
+        hashes: &[BlockHash],
+    ) -> Result<Vec<Result<Block, RpcError>>, RpcError> {
+        let params = hashes
+            .iter()
+            .map(|hash| vec![Value::from(hash.to_string()), Value::from(0)])
+            .collect();
+        let blocks: Vec<Result<String, RpcError>> =
+            self.batch_chunked("getblock", params, 1).await?;
+        Ok(blocks
+            .into_iter()
+            .map(|hex| hex.and_then(|hex| decode_hex(&hex)))
+            .collect())
+    }
+
+    /// Transactions by txid, in batches; needs `txindex=1` for
+    /// transactions outside the mempool and the wallet
+    pub async fn get_raw_transactions(
+        &self,
+        txids: &[Txid],
+    ) -> Result<Vec<Result<Transaction, RpcError>>, RpcError> {
+        let params = txids
+            .iter()
+            .map(|txid| vec![Value::from(txid.to_string())])
+            .collect();
+        let transactions: Vec<Result<String, RpcError>> =
+            self.batch("getrawtransaction", params).await?;
+        Ok(transactions
+            .into_iter()
+            .map(|hex| hex.and_then(|hex| decode_hex(&hex)))
+            .collect())
+    }
+
+    /// Decoded transactions as Bitcoin Core returns them with
+    /// `verbose=true`, which includes output values and addresses
+    pub async fn get_raw_transactions_verbose(
+        &self,
+        txids: &[Txid],
+    ) -> Result<Vec<Result<Value, RpcError>>, RpcError> {
+        let params = txids
+            .iter()
+            .map(|txid| vec![Value::from(txid.to_string()), Value::from(true)])
+            .collect();
+        self.batch("getrawtransaction", params).await
+    }
+}
+
+fn parse<T: DeserializeOwned>(value: Value) -> Result<T, RpcError> {
+    serde_json::from_value(value).map_err(|e| RpcError::InvalidResponse(e.to_string()))
+}
+
+fn decode_hex<T: bitcoin::consensus::Decodable>(hex: &str) -> Result<T, RpcError> {
+    let bytes = hex::decode(hex).map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+    deserialize(&bytes).map_err(|e| RpcError::InvalidResponse(e.to_string()))
+}
+
+/// Put a batch response back in request order
+///
+/// Ids run from `first_id` to `first_id + len`; the node may answer in any
+/// order, and a request without an answer gets an error of its own.
+fn match_batch(
+    body: Value,
+    first_id: u64,
+    len: usize,
+) -> Result<Vec<Result<Value, RpcError>>, RpcError> {
+    let responses = match body {
+        Value::Array(responses) => responses,
+        // A rejected batch comes back as a single error object
+        other => {
+            let response: Response = serde_json::from_value(other)
+                .map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+            response.into_result()?;
+            return Err(RpcError::InvalidResponse(
+                "expected an array of responses".to_string(),
+            ));
+        }
+    };
+
+    let mut slots: Vec<Option<Result<Value, RpcError>>> = (0..len).map(|_| None).collect();
+    for response in responses {
+        let response: Response = serde_json::from_value(response)
+            .map_err(|e| RpcError::InvalidResponse(e.to_string()))?;
+        let slot = response
+            .id
+            .as_u64()
+            .and_then(|id| id.checked_sub(first_id))
+            .and_then(|offset| slots.get_mut(offset as usize));
+        if let Some(slot) = slot {
+            *slot = Some(response.into_result());
+        }
+    }
+
+    Ok(slots
+        .into_iter()
+        .map(|slot| {
+            slot.unwrap_or_else(|| {
+                Err(RpcError::InvalidResponse(
+                    "no response for request".to_string(),
+                ))
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_match_batch_reorders_by_id() {
+        let body = json!([
+            {"id": 12, "result": "c", "error": null},
+            {"id": 10, "result": "a", "error": null},
+            {"id": 11, "result": "b", "error": null},
+        ]);
+        let results: Vec<String> = match_batch(body, 10, 3)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.unwrap().as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(results, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_match_batch_per_call_errors() {
+        let body = json!([
+            {"id": 1, "result": null, "error": {"code": -5, "message": "No such mempool or blockchain transaction"}},
+            {"id": 2, "result": 7, "error": null},
+            {"id": 99, "result": 8, "error": null},
+        ]);
+        let results = match_batch(body, 1, 3).unwrap();
+        assert!(results[0].as_ref().unwrap_err().is_not_found());
+        assert_eq!(results[1].as_ref().unwrap(), &json!(7));
+        assert!(matches!(results[2], Err(RpcError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_match_batch_rejected_whole() {
+        let body = json!({"id": null, "result": null, "error": {"code": -32700, "message": "Parse error"}});
+        let error = match_batch(body, 1, 2).unwrap_err();
+        assert_eq!(error.code(), Some(-32700));
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        let genesis_coinbase = "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000";
+        let tx: Transaction = decode_hex(genesis_coinbase).unwrap();
+        assert_eq!(
+            tx.compute_txid().to_string(),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        assert!(decode_hex::<Transaction>("00zz").is_err());
+    }
+
+    #[test]
+    fn test_client_builds_with_proxy() {
+        let mut config = RpcConfig::new("http://127.0.0.1:18443", "anchor", "anchor");
+        config.proxy = Some("socks5h://127.0.0.1:9050".to_string());
+        let client = RpcClient::new(config).unwrap();
+        assert_eq!(client.url(), "http://127.0.0.1:18443");
+        assert_eq!(client.reserve_ids(3), 1);
+        assert_eq!(client.reserve_ids(1), 4);
+    }
+}
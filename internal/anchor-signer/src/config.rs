@@ -0,0 +1,61 @@
+//! Configuration for the signing daemon
+
+use anyhow::{Context, Result};
+use bdk_wallet::bitcoin::Network;
+use std::env;
+use std::fs;
+
+/// Signer configuration
+#[derive(Clone)]
+pub struct Config {
+    /// HTTP server port
+    pub port: u16,
+    /// Bearer token every request except `/health` must carry
+    pub token: String,
+    /// BIP39 mnemonic the keys derive from
+    pub mnemonic: String,
+    /// Bitcoin network
+    pub network: Network,
+}
+
+impl Config {
+    /// Load configuration from environment variables
+    ///
+    /// The mnemonic comes from `SIGNER_MNEMONIC_FILE` (e.g. a Docker secret) or
+    /// `SIGNER_MNEMONIC`.
+    pub fn from_env() -> Result<Self> {
+        let token = env::var("SIGNER_TOKEN").unwrap_or_default();
+        if token.len() < 16 {
+            anyhow::bail!("SIGNER_TOKEN must be set to at least 16 characters");
+        }
+
+        let mnemonic = match env::var("SIGNER_MNEMONIC_FILE") {
+            Ok(path) => fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read SIGNER_MNEMONIC_FILE {}", path))?,
+            Err(_) => env::var("SIGNER_MNEMONIC")
+                .context("SIGNER_MNEMONIC or SIGNER_MNEMONIC_FILE is required")?,
+        };
+
+        Ok(Self {
+            port: env::var("PORT")
+                .unwrap_or_else(|_| "8010".to_string())
+                .parse()
+                .context("Invalid PORT")?,
+            token,
+            mnemonic: mnemonic.trim().to_string(),
+            network: parse_network(
+                &env::var("BITCOIN_NETWORK").unwrap_or_else(|_| "regtest".to_string()),
+            )?,
+        })
+    }
+}
+
+fn parse_network(name: &str) -> Result<Network> {
+    Ok(match name {
+        "mainnet" | "bitcoin" => Network::Bitcoin,
+        "testnet" => Network::Testnet,
+        "signet" => Network::Signet,
+        "regtest" => Network::Regtest,
+        other => anyhow::bail!("Invalid BITCOIN_NETWORK '{}'", other),
+    })
+}
@@ -0,0 +1,156 @@
+//! ANCHOR Signer
+//!
+//! Holds the wallet keys for a wallet service running the `remote-signer`
+//! profile, so the network-facing service never sees private keys. The API is
+//! deliberately small: export the account xpub and sign PSBTs. Every route
+//! except `/health` requires `Authorization: Bearer <SIGNER_TOKEN>`.
+
+mod config;
+mod signer;
+
+use anchor_api_error::{ApiError, ErrorCode};
+use anyhow::Result;
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tower_http::trace::TraceLayer;
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::config::Config;
+use crate::signer::{AccountInfo, SignedPsbt, Signer};
+
+/// Shared state
+struct AppState {
+    signer: Signer,
+    /// SHA-256 of the bearer token
+    token_digest: [u8; 32],
+}
+
+#[derive(Debug, Deserialize)]
+struct SignPsbtRequest {
+    /// Base64 PSBT
+    psbt: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "anchor_signer=info,tower_http=info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let config = Config::from_env()?;
+    let signer = Signer::from_mnemonic(&config.mnemonic, config.network)?;
+    info!(
+        "Signer ready for {} account {} ({})",
+        config.network,
+        signer.account().derivation_path,
+        signer.account().fingerprint
+    );
+
+    let state = Arc::new(AppState {
+        signer,
+        token_digest: Sha256::digest(config.token.as_bytes()).into(),
+    });
+
+    let api = Router::new()
+        .route("/xpub", get(get_xpub))
+        .route("/sign-psbt", post(sign_psbt))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token));
+
+    let app = Router::new()
+        .route("/health", get(health))
+        .merge(api)
+        .with_state(state)
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
+        .layer(TraceLayer::new_for_http());
+
+    let addr = format!("0.0.0.0:{}", config.port);
+    info!("Listening on {}", addr);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+async fn health() -> &'static str {
+    "OK"
+}
+
+/// Account xpub and public descriptors
+async fn get_xpub(State(state): State<Arc<AppState>>) -> Json<AccountInfo> {
+    Json(state.signer.account().clone())
+}
+
+/// Add this signer's signatures to a PSBT
+async fn sign_psbt(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SignPsbtRequest>,
+) -> Result<Json<SignedPsbt>, ApiError> {
+    let signed = state
+        .signer
+        .sign_psbt(&req.psbt)
+        .map_err(|e| ApiError::bad_request(format!("{:#}", e)))?;
+    info!("Signed {} PSBT input(s)", signed.signed_inputs);
+    Ok(Json(signed))
+}
+
+/// Reject requests without the bearer token
+async fn require_token(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if !token_matches(&state.token_digest, presented) {
+        warn!("Rejected {} {}: bad token", req.method(), req.uri().path());
+        return Err(ApiError::new(
+            ErrorCode::Unauthorized,
+            "Missing or invalid signer token",
+        ));
+    }
+    Ok(next.run(req).await)
+}
+
+/// Compare digests so the comparison time does not depend on the token
+fn token_matches(expected: &[u8; 32], presented: &str) -> bool {
+    let digest: [u8; 32] = Sha256::digest(presented.as_bytes()).into();
+    expected
+        .iter()
+        .zip(digest.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_matches() {
+        let expected: [u8; 32] = Sha256::digest(b"0123456789abcdef").into();
+        assert!(token_matches(&expected, "0123456789abcdef"));
+        assert!(!token_matches(&expected, "0123456789abcdeg"));
+        assert!(!token_matches(&expected, ""));
+    }
+}
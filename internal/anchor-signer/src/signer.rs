@@ -0,0 +1,246 @@
+//! Keys and PSBT signing
+//!
+//! The keys derive from a BIP39 mnemonic as a BIP84 (native SegWit) account,
+//! the same layout as the wallet service's BDK wallet. Only the account xpub
+//! and descriptors ever leave this process.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bdk_wallet::{
+    bitcoin::{
+        bip32::{DerivationPath, Xpriv, Xpub},
+        secp256k1::Secp256k1,
+        Network, Psbt,
+    },
+    keys::{
+        bip39::{Language, Mnemonic},
+        DerivableKey, ExtendedKey,
+    },
+    template::Bip84,
+    KeychainKind, SignOptions, Wallet,
+};
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Public half of the signing account
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountInfo {
+    /// Master key fingerprint
+    pub fingerprint: String,
+    /// Account derivation path
+    pub derivation_path: String,
+    /// Account xpub with its key origin, e.g. `[73c5da0a/84'/0'/0']xpub...`
+    pub xpub: String,
+    /// Receive chain descriptor, without checksum
+    pub receive_descriptor: String,
+    /// Change chain descriptor, without checksum
+    pub change_descriptor: String,
+}
+
+/// Outcome of signing a PSBT
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedPsbt {
+    /// Base64 PSBT with this signer's partial signatures added
+    pub psbt: String,
+    /// Inputs this signer added a signature to
+    pub signed_inputs: usize,
+}
+
+/// Holder of the wallet keys
+pub struct Signer {
+    /// BDK wallet used only for its signers; it never syncs
+    wallet: Mutex<Wallet>,
+    account: AccountInfo,
+}
+
+impl Signer {
+    /// Derive the BIP84 account from a mnemonic
+    pub fn from_mnemonic(words: &str, network: Network) -> Result<Self> {
+        let mnemonic = Mnemonic::parse_in(Language::English, words)
+            .map_err(|e| anyhow::anyhow!("Invalid mnemonic: {:?}", e))?;
+        let xkey: ExtendedKey = mnemonic
+            .into_extended_key()
+            .map_err(|e| anyhow::anyhow!("Failed to create extended key: {:?}", e))?;
+        let xprv = xkey
+            .into_xprv(network)
+            .context("Failed to get xprv from extended key")?;
+
+        let wallet = Wallet::create(
+            Bip84(xprv, KeychainKind::External),
+            Bip84(xprv, KeychainKind::Internal),
+        )
+        .network(network)
+        .create_wallet_no_persist()
+        .context("Failed to create signing wallet")?;
+
+        let account = account_info(&wallet, &xprv, network)?;
+        Ok(Self {
+            wallet: Mutex::new(wallet),
+            account,
+        })
+    }
+
+    pub fn account(&self) -> &AccountInfo {
+        &self.account
+    }
+
+    /// Add signatures for every input derived from this account
+    ///
+    /// Inputs need their BIP32 derivations and witness UTXOs, as Bitcoin Core
+    /// includes in `walletcreatefundedpsbt`. Finalizing is left to the caller.
+    pub fn sign_psbt(&self, psbt: &str) -> Result<SignedPsbt> {
+        let mut psbt = Psbt::from_str(psbt).context("Invalid PSBT")?;
+        let before: usize = psbt.inputs.iter().map(|i| i.partial_sigs.len()).sum();
+
+        let wallet = self
+            .wallet
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock wallet: {}", e))?;
+        wallet
+            .sign(
+                &mut psbt,
+                SignOptions {
+                    trust_witness_utxo: true,
+                    try_finalize: false,
+                    ..Default::default()
+                },
+            )
+            .context("Failed to sign PSBT")?;
+
+        let after: usize = psbt.inputs.iter().map(|i| i.partial_sigs.len()).sum();
+        let signed_inputs = after.saturating_sub(before);
+        if signed_inputs == 0 {
+            anyhow::bail!("No inputs of the PSBT belong to this signer");
+        }
+
+        Ok(SignedPsbt {
+            psbt: BASE64.encode(psbt.serialize()),
+            signed_inputs,
+        })
+    }
+}
+
+/// BIP84 account path for the network: m/84'/0'/0' on mainnet, m/84'/1'/0' elsewhere
+fn account_path(network: Network) -> DerivationPath {
+    let coin = if network == Network::Bitcoin { 0 } else { 1 };
+    DerivationPath::from_str(&format!("m/84'/{}'/0'", coin)).expect("valid derivation path")
+}
+
+fn account_info(wallet: &Wallet, xprv: &Xpriv, network: Network) -> Result<AccountInfo> {
+    let secp = Secp256k1::new();
+    let path = account_path(network);
+    let xpub = Xpub::from_priv(&secp, &xprv.derive_priv(&secp, &path)?);
+    let fingerprint = xprv.fingerprint(&secp);
+    let descriptor = |keychain| {
+        let descriptor = wallet.public_descriptor(keychain).to_string();
+        descriptor
+            .split('#')
+            .next()
+            .unwrap_or(&descriptor)
+            .to_string()
+    };
+
+    Ok(AccountInfo {
+        fingerprint: fingerprint.to_string(),
+        derivation_path: format!("m/{}", path),
+        xpub: format!("[{}/{}]{}", fingerprint, path, xpub),
+        receive_descriptor: descriptor(KeychainKind::External),
+        change_descriptor: descriptor(KeychainKind::Internal),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::bitcoin::{
+        absolute::LockTime, bip32::Fingerprint, transaction::Version, Amount, OutPoint, Sequence,
+        Transaction, TxIn, TxOut, Witness,
+    };
+    use std::collections::BTreeMap;
+
+    // BIP84 test vector
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const ACCOUNT_XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+    #[test]
+    fn test_account_info_matches_bip84_vector() {
+        let signer = Signer::from_mnemonic(MNEMONIC, Network::Bitcoin).unwrap();
+        let account = signer.account();
+        assert_eq!(account.fingerprint, "73c5da0a");
+        assert_eq!(account.derivation_path, "m/84'/0'/0'");
+        assert_eq!(
+            account.xpub,
+            format!("[73c5da0a/84'/0'/0']{}", ACCOUNT_XPUB)
+        );
+        assert!(account
+            .receive_descriptor
+            .starts_with("wpkh([73c5da0a/84'/0'/0']"));
+        assert!(account.receive_descriptor.ends_with("/0/*)"));
+        assert!(account.change_descriptor.ends_with("/1/*)"));
+    }
+
+    #[test]
+    fn test_sign_psbt() {
+        let signer = Signer::from_mnemonic(MNEMONIC, Network::Bitcoin).unwrap();
+        let wallet = signer.wallet.lock().unwrap();
+        let address = wallet.peek_address(KeychainKind::External, 0).address;
+        drop(wallet);
+        assert_eq!(
+            address.to_string(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+
+        // A PSBT spending an output paid to the first receive address
+        let secp = Secp256k1::new();
+        let mnemonic = Mnemonic::parse_in(Language::English, MNEMONIC).unwrap();
+        let xkey: ExtendedKey = mnemonic.into_extended_key().unwrap();
+        let xprv = xkey.into_xprv(Network::Bitcoin).unwrap();
+        let path = DerivationPath::from_str("m/84'/0'/0'/0/0").unwrap();
+        let pubkey = xprv
+            .derive_priv(&secp, &path)
+            .unwrap()
+            .private_key
+            .public_key(&secp);
+
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Default::default(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(90_000),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: Amount::from_sat(100_000),
+            script_pubkey: address.script_pubkey(),
+        });
+        psbt.inputs[0].bip32_derivation =
+            BTreeMap::from([(pubkey, (Fingerprint::from_str("73c5da0a").unwrap(), path))]);
+
+        let signed = signer.sign_psbt(&BASE64.encode(psbt.serialize())).unwrap();
+        assert_eq!(signed.signed_inputs, 1);
+        let signed = Psbt::from_str(&signed.psbt).unwrap();
+        assert_eq!(signed.inputs[0].partial_sigs.len(), 1);
+    }
+
+    #[test]
+    fn test_sign_psbt_rejects_foreign_inputs() {
+        let signer = Signer::from_mnemonic(MNEMONIC, Network::Bitcoin).unwrap();
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![],
+        };
+        let psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        assert!(signer.sign_psbt(&BASE64.encode(psbt.serialize())).is_err());
+    }
+}
@@ -30,9 +30,9 @@ pub struct TestnetConfig {
     pub enable_oracle_attestation: bool, // Kind 31 (Attestation)
     pub enable_oracle_dispute: bool,     // Kind 32 (Dispute)
     pub enable_oracle_event: bool,       // Event Requests (via API)
-    pub enable_prediction: bool,         // Kind 40 (LotteryCreate)
-    pub enable_prediction_ticket: bool,  // Kind 41 (LotteryTicket)
-    pub enable_prediction_draw: bool,    // Kind 42 (LotteryDraw)
+    pub enable_prediction: bool,         // Kind 40 (MarketCreate)
+    pub enable_prediction_ticket: bool,  // Kind 41 (PlaceBet)
+    pub enable_prediction_draw: bool,    // Kind 42 (MarketResolve)
 
     // Carrier weights (0-100, will be normalized)
     pub weight_op_return: u8,
@@ -210,10 +210,10 @@ pub enum MessageType {
     OracleAttestation, // Attestation (Kind 31)
     OracleDispute,     // Dispute (Kind 32)
     OracleEvent,       // Event Request (via API)
-    // Prediction/Lottery types
-    Prediction,       // Kind 40 (LotteryCreate)
-    PredictionTicket, // Kind 41 (LotteryTicket)
-    PredictionDraw,   // Kind 42 (LotteryDraw)
+    // Prediction market types
+    Prediction,       // Kind 40 (MarketCreate)
+    PredictionTicket, // Kind 41 (PlaceBet)
+    PredictionDraw,   // Kind 42 (MarketResolve)
 }
 
 impl MessageType {
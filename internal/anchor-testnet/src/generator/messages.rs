@@ -83,7 +83,7 @@ pub struct MessageGenerator {
     attestation_history: Vec<TrackedAttestation>,
     /// History of created markets for bet/resolve operations
     market_history: Vec<TrackedMarket>,
-    /// Current simulated block height for lottery draws
+    /// Current simulated block height for market resolution
     current_block: u32,
     rng: rand::rngs::ThreadRng,
     config: SharedConfig,
@@ -1320,7 +1320,6 @@ impl MessageGenerator {
         })
     }
 
-    /// Create a lottery message (Kind 40 - LotteryCreate)
     /// Create a prediction market (Kind 40 - MarketCreate)
     /// Format expected by indexer:
     /// [market_id 32] [question_len 2 BE] [question var] [desc_len 2 BE] [desc var]
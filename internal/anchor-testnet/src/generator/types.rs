@@ -77,7 +77,7 @@ pub struct BalanceResponse {
 }
 
 /// Request for creating a message
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CreateMessageRequest {
     pub kind: u8,
     pub body: String,
@@ -76,11 +76,23 @@ impl WalletClient {
         &self,
         request: &CreateMessageRequest,
     ) -> Result<CreateMessageResponse> {
+        // Let the wallet pick a carrier the kind supports instead of
+        // rolling an incompatible one
+        let mut request = request.clone();
+        if let (Some(support), Some(carrier)) = (
+            anchor_specs::carrier_support(request.kind),
+            request.carrier.and_then(anchor_specs::CarrierType::from_u8),
+        ) {
+            if !support.allows(carrier) {
+                request.carrier = None;
+            }
+        }
+
         let url = format!("{}/wallet/create-message", self.wallet_url);
         let response = self
             .client
             .post(&url)
-            .json(request)
+            .json(&request)
             .send()
             .await?
             .error_for_status()
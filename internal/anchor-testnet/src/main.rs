@@ -20,6 +20,7 @@ use crate::websocket::{
     broadcast_log, broadcast_stats, create_log_buffer, create_ws_broadcast, ws_handler, LogEntry,
     LogLevel, WsState,
 };
+use anchor_api_security::SecurityConfig;
 use anyhow::Result;
 use axum::{
     routing::{get, post},
@@ -30,7 +31,6 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 use tokio::time::sleep;
-use tower_http::cors::{Any, CorsLayer};
 use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 
@@ -298,11 +298,6 @@ async fn main() -> Result<()> {
 
 /// Start the API server with all routes
 async fn start_api_server(app_state: AppState, ws_state: WsState, port: u16) {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
     // Config routes
     let config_routes = Router::new()
         .route("/health", get(health_handler))
@@ -322,7 +317,7 @@ async fn start_api_server(app_state: AppState, ws_state: WsState, port: u16) {
     let app = Router::new()
         .merge(config_routes)
         .merge(ws_routes)
-        .layer(cors);
+        .layer(SecurityConfig::from_env().layer());
 
     let addr = format!("0.0.0.0:{}", port);
     info!("🌐 Starting API server on {}", addr);
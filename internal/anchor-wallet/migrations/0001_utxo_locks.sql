@@ -0,0 +1,12 @@
+-- Migration: shared UTXO lock store
+-- Lock state of each wallet as one document, for wallet service replicas
+-- running with LOCK_STORE_URL. state is the lock manager's JSON, sealed by
+-- the vault when WALLET_DATA_ENCRYPTION is on. Writers update a row only at
+-- the version they read (optimistic concurrency) and bump it.
+
+CREATE TABLE IF NOT EXISTS wallet_utxo_locks (
+    wallet_name TEXT PRIMARY KEY,
+    state TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
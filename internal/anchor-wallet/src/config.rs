@@ -5,6 +5,8 @@ use bdk_wallet::bitcoin::Network;
 use std::env;
 use std::path::PathBuf;
 
+use crate::proxy::Socks5Proxy;
+
 /// Wallet service configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -24,16 +26,126 @@ pub struct Config {
     pub domains_url: String,
     /// Anchor Tokens backend URL
     pub tokens_url: String,
+    /// Anchor Predictions backend URL
+    pub predictions_url: String,
+    /// Anchor Oracles backend URL
+    pub oracles_url: String,
+    /// Anchor Proofs backend URL
+    pub proofs_url: String,
+    /// Anchor Canvas backend URL
+    pub canvas_url: String,
+    /// How long an aggregated portfolio is served from cache
+    pub portfolio_cache_secs: u64,
     /// Whether to auto-lock ownership UTXOs
     pub auto_lock_enabled: bool,
-    /// Electrum server URL for BDK
-    pub electrum_url: String,
+    /// Electrum servers for BDK, in preference order for the first pick
+    pub electrum_urls: Vec<String>,
+    /// Seconds between Electrum server health checks
+    pub electrum_health_secs: u64,
     /// Enable BDK wallet (key management)
     pub bdk_enabled: bool,
     /// BDK wallet password (for mnemonic encryption)
     pub bdk_password: Option<String>,
+    /// Seconds between background BDK syncs (0 disables)
+    pub bdk_sync_secs: u64,
+    /// Seconds between full scans among the background syncs
+    pub bdk_full_scan_secs: u64,
+    /// Unused BDK addresses in a row before new requests reuse unused ones
+    pub bdk_gap_limit: u32,
+    /// Encrypt the wallet's state files at rest
+    pub data_encryption: bool,
+    /// Passphrase that unlocks the encrypted state files at startup
+    pub data_passphrase: Option<String>,
+    /// Idle seconds before the encrypted state files are relocked (0 never)
+    pub relock_secs: u64,
+    /// Let reveals pay for zero-fee commits via package relay (Core 28+)
+    pub zero_fee_commit: bool,
+    /// Postgres URL of a lock store shared by wallet service replicas
+    pub lock_store_url: Option<String>,
     /// Bitcoin network
     pub network: String,
+    /// Descriptors tracked by the watch-only profile (`WALLET_PROFILE=watch-only`)
+    pub watch_only: Option<WatchOnly>,
+    /// Signing daemon of the remote-signer profile (`WALLET_PROFILE=remote-signer`)
+    pub signer: Option<SignerConfig>,
+    /// SOCKS5 proxy for Bitcoin Core RPC
+    pub bitcoin_rpc_proxy: Option<Socks5Proxy>,
+    /// SOCKS5 proxy for the Electrum servers
+    pub electrum_proxy: Option<Socks5Proxy>,
+    /// SOCKS5 proxy for `.onion` Electrum servers when `electrum_proxy` is unset
+    pub electrum_onion_proxy: Option<Socks5Proxy>,
+    /// SOCKS5 proxy for the app backends
+    pub backends_proxy: Option<Socks5Proxy>,
+    /// SOCKS5 proxy for payjoin endpoints
+    pub payjoin_proxy: Option<Socks5Proxy>,
+}
+
+/// Public descriptors for a wallet that holds no private keys
+///
+/// Transactions are funded from these descriptors and returned as PSBTs for
+/// an external signer instead of being signed and broadcast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchOnly {
+    /// Receive chain descriptor, without checksum
+    pub receive: String,
+    /// Change chain descriptor, without checksum
+    pub change: String,
+    /// Unix time to rescan from when the wallet is created (0 = genesis)
+    pub birthday: u64,
+}
+
+impl WatchOnly {
+    /// Parse `WALLET_XPUB`: an account xpub (with optional `[fingerprint/path]`
+    /// origin), used as `wpkh(xpub/0/*)`, or a receive descriptor ending in `/0/*`
+    pub fn parse(value: &str, birthday: u64) -> Result<Self> {
+        let value = value.trim();
+        let value = value.split('#').next().unwrap_or(value);
+        if value.contains("prv") {
+            anyhow::bail!("WALLET_XPUB must be a public key; private keys are never loaded in the watch-only profile");
+        }
+
+        let (receive, change) = if value.contains('(') {
+            let at = value
+                .rfind("/0/*")
+                .context("WALLET_XPUB descriptor must derive receive addresses with /0/*")?;
+            let change = format!("{}/1/*{}", &value[..at], &value[at + 4..]);
+            (value.to_string(), change)
+        } else if value.is_empty() {
+            anyhow::bail!("WALLET_XPUB is required in the watch-only profile");
+        } else {
+            (
+                format!("wpkh({}/0/*)", value),
+                format!("wpkh({}/1/*)", value),
+            )
+        };
+
+        Ok(Self {
+            receive,
+            change,
+            birthday,
+        })
+    }
+}
+
+/// Connection to an `anchor-signer` daemon
+#[derive(Clone)]
+pub struct SignerConfig {
+    /// Signer base URL
+    pub url: String,
+    /// Bearer token shared with the signer
+    pub token: String,
+    /// Unix time to rescan from when the watch-only wallet is created
+    pub birthday: u64,
+}
+
+impl std::fmt::Debug for SignerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignerConfig")
+            .field("url", &self.url)
+            .field("token", &"<redacted>")
+            .field("birthday", &self.birthday)
+            .finish()
+    }
 }
 
 impl Config {
@@ -49,6 +161,46 @@ impl Config {
 
         let network = env::var("BITCOIN_NETWORK").unwrap_or_else(|_| "regtest".to_string());
 
+        let birthday = || -> Result<u64> {
+            Ok(env::var("WALLET_BIRTHDAY")
+                .ok()
+                .map(|v| v.parse().context("Invalid WALLET_BIRTHDAY"))
+                .transpose()?
+                .unwrap_or(0))
+        };
+        let (watch_only, signer) = match env::var("WALLET_PROFILE").as_deref() {
+            Ok("watch-only") => (
+                Some(WatchOnly::parse(
+                    &env::var("WALLET_XPUB").unwrap_or_default(),
+                    birthday()?,
+                )?),
+                None,
+            ),
+            // The descriptors come from WALLET_XPUB or, when unset, from the
+            // signer's /xpub at startup
+            Ok("remote-signer") => {
+                let birthday = birthday()?;
+                let signer = SignerConfig {
+                    url: env::var("SIGNER_URL")
+                        .context("SIGNER_URL is required in the remote-signer profile")?,
+                    token: env::var("SIGNER_TOKEN")
+                        .context("SIGNER_TOKEN is required in the remote-signer profile")?,
+                    birthday,
+                };
+                let watch_only = env::var("WALLET_XPUB")
+                    .ok()
+                    .filter(|v| !v.trim().is_empty())
+                    .map(|xpub| WatchOnly::parse(&xpub, birthday))
+                    .transpose()?;
+                (watch_only, Some(signer))
+            }
+            Ok("hot") | Err(_) => (None, None),
+            Ok(other) => anyhow::bail!(
+                "Invalid WALLET_PROFILE '{}': expected 'hot', 'watch-only' or 'remote-signer'",
+                other
+            ),
+        };
+
         // Default Electrum URL based on network
         // In Docker, use the service name; on host, use localhost
         let default_electrum = match network.as_str() {
@@ -73,17 +225,71 @@ impl Config {
                 .unwrap_or_else(|_| "http://localhost:3400".to_string()),
             tokens_url: env::var("ANCHOR_TOKENS_URL")
                 .unwrap_or_else(|_| "http://localhost:3500".to_string()),
+            predictions_url: env::var("ANCHOR_PREDICTIONS_URL")
+                .unwrap_or_else(|_| "http://localhost:3801".to_string()),
+            oracles_url: env::var("ANCHOR_ORACLES_URL")
+                .unwrap_or_else(|_| "http://localhost:3701".to_string()),
+            proofs_url: env::var("ANCHOR_PROOFS_URL")
+                .unwrap_or_else(|_| "http://localhost:3501".to_string()),
+            canvas_url: env::var("ANCHOR_CANVAS_URL")
+                .unwrap_or_else(|_| "http://localhost:3201".to_string()),
+            portfolio_cache_secs: env::var("PORTFOLIO_CACHE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
             auto_lock_enabled: env::var("AUTO_LOCK_OWNERSHIP_UTXOS")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
                 .unwrap_or(true),
-            electrum_url: env::var("ELECTRUM_URL").unwrap_or_else(|_| default_electrum.to_string()),
+            electrum_urls: env::var("ELECTRUM_URLS")
+                .or_else(|_| env::var("ELECTRUM_URL"))
+                .map(|v| parse_server_list(&v))
+                .ok()
+                .filter(|urls| !urls.is_empty())
+                .unwrap_or_else(|| vec![default_electrum.to_string()]),
+            electrum_health_secs: env::var("ELECTRUM_HEALTH_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
             bdk_enabled: env::var("BDK_ENABLED")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
                 .unwrap_or(true),
             bdk_password: env::var("BDK_PASSWORD").ok(),
+            bdk_sync_secs: env::var("BDK_SYNC_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+            bdk_full_scan_secs: env::var("BDK_FULL_SCAN_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3600),
+            bdk_gap_limit: env::var("BDK_GAP_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(crate::wallet::addresses::DEFAULT_GAP_LIMIT),
+            data_encryption: env::var("WALLET_DATA_ENCRYPTION")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            data_passphrase: env::var("WALLET_DATA_PASSPHRASE").ok(),
+            relock_secs: env::var("WALLET_RELOCK_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(900),
+            zero_fee_commit: env::var("ZERO_FEE_COMMIT")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            lock_store_url: env::var("LOCK_STORE_URL").ok().filter(|v| !v.is_empty()),
             network,
+            watch_only,
+            signer,
+            bitcoin_rpc_proxy: Socks5Proxy::from_env("BITCOIN_RPC_PROXY")?,
+            electrum_proxy: Socks5Proxy::from_env("ELECTRUM_PROXY")?,
+            electrum_onion_proxy: Socks5Proxy::from_env("ELECTRUM_ONION_PROXY")?,
+            backends_proxy: Socks5Proxy::from_env("BACKENDS_PROXY")?,
+            payjoin_proxy: Socks5Proxy::from_env("PAYJOIN_PROXY")?,
         })
     }
 
@@ -97,3 +303,66 @@ impl Config {
         }
     }
 }
+
+/// Split a comma- or whitespace-separated list of Electrum server URLs
+fn parse_server_list(value: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for url in value.split(|c: char| c == ',' || c.is_whitespace()) {
+        if !url.is_empty() && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_only_from_xpub() {
+        let watch = WatchOnly::parse("[d34db33f/84h/1h/0h]tpubD6NzVbkrYhZ4X", 0).unwrap();
+        assert_eq!(
+            watch.receive,
+            "wpkh([d34db33f/84h/1h/0h]tpubD6NzVbkrYhZ4X/0/*)"
+        );
+        assert_eq!(
+            watch.change,
+            "wpkh([d34db33f/84h/1h/0h]tpubD6NzVbkrYhZ4X/1/*)"
+        );
+    }
+
+    #[test]
+    fn test_watch_only_from_descriptor() {
+        let watch =
+            WatchOnly::parse("tr([d34db33f/86h/1h/0h]tpubD6NzVbkrYhZ4X/0/*)#abcd1234", 7).unwrap();
+        assert_eq!(
+            watch.receive,
+            "tr([d34db33f/86h/1h/0h]tpubD6NzVbkrYhZ4X/0/*)"
+        );
+        assert_eq!(
+            watch.change,
+            "tr([d34db33f/86h/1h/0h]tpubD6NzVbkrYhZ4X/1/*)"
+        );
+        assert_eq!(watch.birthday, 7);
+    }
+
+    #[test]
+    fn test_watch_only_rejects_private_keys() {
+        assert!(WatchOnly::parse("tprv8ZgxMBicQKsPd", 0).is_err());
+        assert!(WatchOnly::parse("wpkh(xprv9s21ZrQH143K/0/*)", 0).is_err());
+        assert!(WatchOnly::parse("wpkh(tpubD6NzVbkrYhZ4X/*)", 0).is_err());
+        assert!(WatchOnly::parse("", 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_server_list() {
+        assert_eq!(
+            parse_server_list(
+                "tcp://core-electrs:50001, ssl://abc.onion:50002,,tcp://core-electrs:50001"
+            ),
+            vec!["tcp://core-electrs:50001", "ssl://abc.onion:50002"]
+        );
+        assert!(parse_server_list(" ").is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//! Escrows for peer-to-peer trades
+//!
+//! An escrow holds a buyer's payment in a 2-of-3 multisig of the buyer,
+//! seller and an arbiter. The happy path is buyer and seller signing the
+//! release to the seller; in a dispute the arbiter sides with one of them
+//! and signs either the release or the refund to the buyer. Every state
+//! change is recorded on chain with an Escrow message (kind 50).
+//!
+//! The store keeps the escrows this wallet takes part in: the keys, payout
+//! addresses and funding outpoint needed to build the settlement, and the
+//! settlement waiting for signatures. Like templates it is persisted to a
+//! JSON file sealed by the vault and loaded on startup or unlock.
+
+use anchor_specs::escrow::EscrowAction;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::vault::Vault;
+
+/// Where an escrow stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    /// Address derived, nothing paid in yet
+    Created,
+    /// Payment locked in the escrow output
+    Funded,
+    /// A party disputed the trade; the arbiter decides
+    Disputed,
+    /// Paid out to the seller
+    Released,
+    /// Paid back to the buyer
+    Refunded,
+}
+
+impl EscrowStatus {
+    /// The last action recorded on chain, `None` before funding
+    pub fn last_action(self) -> Option<EscrowAction> {
+        match self {
+            EscrowStatus::Created => None,
+            EscrowStatus::Funded => Some(EscrowAction::Fund),
+            EscrowStatus::Disputed => Some(EscrowAction::Dispute),
+            EscrowStatus::Released => Some(EscrowAction::Release),
+            EscrowStatus::Refunded => Some(EscrowAction::Refund),
+        }
+    }
+}
+
+/// A release or refund waiting for two signatures
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EscrowSettlement {
+    /// `release` or `refund`
+    pub action: String,
+    pub txid: String,
+    /// Unsigned PSBT (base64) to pass between the signers
+    pub psbt: String,
+}
+
+/// An escrow this wallet takes part in
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Escrow {
+    pub id: String,
+    /// Compressed public keys (hex)
+    pub buyer_pubkey: String,
+    pub seller_pubkey: String,
+    pub arbiter_pubkey: String,
+    /// Refund payout address
+    pub buyer_address: String,
+    /// Release payout address
+    pub seller_address: String,
+    pub amount_sats: u64,
+    pub terms: String,
+    /// P2WSH escrow address
+    pub address: String,
+    /// 2-of-3 multisig witness script (hex)
+    pub witness_script: String,
+    pub status: EscrowStatus,
+    pub funding_txid: Option<String>,
+    pub funding_vout: Option<u32>,
+    pub settlement: Option<EscrowSettlement>,
+    /// Settlement transaction, once broadcast
+    pub closing_txid: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Persisted escrow state
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct EscrowState {
+    escrows: Vec<Escrow>,
+}
+
+/// Store of escrows
+pub struct EscrowStore {
+    /// Path to the escrows file
+    state_path: PathBuf,
+    /// Encryption of the escrows file
+    vault: Arc<Vault>,
+    /// In-memory state protected by RwLock
+    state: Arc<RwLock<EscrowState>>,
+}
+
+impl EscrowStore {
+    /// Create a new EscrowStore with the given data directory
+    ///
+    /// If the vault is locked the state loads on `reload`, after unlocking.
+    pub fn new(data_dir: PathBuf, vault: Arc<Vault>) -> Result<Self> {
+        let state_path = data_dir.join("escrows.json");
+        fs::create_dir_all(&data_dir).context("Failed to create data directory")?;
+
+        let store = Self {
+            state_path,
+            vault,
+            state: Arc::new(RwLock::new(EscrowState::default())),
+        };
+        store.load();
+        Ok(store)
+    }
+
+    /// Load existing state
+    fn load(&self) {
+        let state = match self.vault.read(&self.state_path) {
+            Ok(Some(content)) => match serde_json::from_str::<EscrowState>(&content) {
+                Ok(state) => {
+                    info!("Loaded {} escrows", state.escrows.len());
+                    state
+                }
+                Err(e) => {
+                    warn!("Failed to parse escrows, starting fresh: {}", e);
+                    EscrowState::default()
+                }
+            },
+            Ok(None) => {
+                debug!("No escrows file, starting fresh");
+                EscrowState::default()
+            }
+            Err(e) if self.vault.is_locked() => {
+                info!("Escrows are encrypted, loading them on unlock: {}", e);
+                EscrowState::default()
+            }
+            Err(e) => {
+                warn!("Failed to read escrows, starting fresh: {}", e);
+                EscrowState::default()
+            }
+        };
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    /// Re-read the state from disk, e.g. after unlocking the vault
+    pub fn reload(&self) -> Result<()> {
+        self.load();
+        self.save()
+    }
+
+    /// Save the current state to disk
+    fn save(&self) -> Result<()> {
+        let state = self
+            .state
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let content = serde_json::to_string_pretty(&*state)?;
+        self.vault
+            .write(&self.state_path, &content)
+            .context("Failed to write escrows")?;
+        Ok(())
+    }
+
+    /// All escrows, newest first
+    pub fn list(&self) -> Vec<Escrow> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let mut escrows = state.escrows.clone();
+        escrows.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        escrows
+    }
+
+    /// An escrow by id
+    pub fn get(&self, id: &str) -> Option<Escrow> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.escrows.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// Record a new escrow, before it is funded
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        &self,
+        buyer_pubkey: String,
+        seller_pubkey: String,
+        arbiter_pubkey: String,
+        buyer_address: String,
+        seller_address: String,
+        amount_sats: u64,
+        terms: String,
+        address: String,
+        witness_script: String,
+    ) -> Result<Escrow> {
+        let now = Utc::now();
+        let escrow = Escrow {
+            id: Uuid::new_v4().to_string(),
+            buyer_pubkey,
+            seller_pubkey,
+            arbiter_pubkey,
+            buyer_address,
+            seller_address,
+            amount_sats,
+            terms,
+            address,
+            witness_script,
+            status: EscrowStatus::Created,
+            funding_txid: None,
+            funding_vout: None,
+            settlement: None,
+            closing_txid: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.state
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?
+            .escrows
+            .push(escrow.clone());
+        self.save()?;
+        info!("Created escrow {} at {}", escrow.id, escrow.address);
+        Ok(escrow)
+    }
+
+    /// Change an escrow in place and persist it
+    ///
+    /// Returns the updated escrow, or `None` if there is no such escrow.
+    pub fn update(&self, id: &str, change: impl FnOnce(&mut Escrow)) -> Result<Option<Escrow>> {
+        let mut state = self
+            .state
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let Some(escrow) = state.escrows.iter_mut().find(|e| e.id == id) else {
+            return Ok(None);
+        };
+        change(escrow);
+        escrow.updated_at = Utc::now();
+        let escrow = escrow.clone();
+
+        drop(state);
+        self.save()?;
+        Ok(Some(escrow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store(dir: &TempDir) -> EscrowStore {
+        let vault = Arc::new(Vault::new(dir.path(), false, None));
+        EscrowStore::new(dir.path().to_path_buf(), vault).unwrap()
+    }
+
+    fn create(escrows: &EscrowStore) -> Escrow {
+        escrows
+            .create(
+                "02aa".to_string(),
+                "02bb".to_string(),
+                "02cc".to_string(),
+                "bcrt1qbuyer".to_string(),
+                "bcrt1qseller".to_string(),
+                50_000,
+                "One used bicycle".to_string(),
+                "bcrt1qescrow".to_string(),
+                "52ae".to_string(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_create_update_persist() {
+        let dir = TempDir::new().unwrap();
+        let escrows = store(&dir);
+        let escrow = create(&escrows);
+        assert_eq!(escrow.status, EscrowStatus::Created);
+
+        let funded = escrows
+            .update(&escrow.id, |e| {
+                e.status = EscrowStatus::Funded;
+                e.funding_txid = Some("ab".repeat(32));
+                e.funding_vout = Some(1);
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(funded.status, EscrowStatus::Funded);
+        assert!(escrows.update("missing", |_| {}).unwrap().is_none());
+
+        let reloaded = store(&dir).get(&escrow.id).unwrap();
+        assert_eq!(reloaded.status, EscrowStatus::Funded);
+        assert_eq!(reloaded.funding_vout, Some(1));
+        assert_eq!(reloaded.terms, "One used bicycle");
+    }
+
+    #[test]
+    fn test_list_newest_first() {
+        let dir = TempDir::new().unwrap();
+        let escrows = store(&dir);
+        let first = create(&escrows);
+        let second = create(&escrows);
+        escrows
+            .update(&first.id, |e| e.created_at -= chrono::Duration::minutes(1))
+            .unwrap();
+        let ids: Vec<String> = escrows.list().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![second.id, first.id]);
+    }
+
+    #[test]
+    fn test_status_actions() {
+        assert_eq!(EscrowStatus::Created.last_action(), None);
+        assert!(EscrowAction::Fund.can_follow(EscrowStatus::Created.last_action()));
+        assert!(EscrowAction::Release.can_follow(EscrowStatus::Disputed.last_action()));
+        assert!(!EscrowAction::Refund.can_follow(EscrowStatus::Released.last_action()));
+        assert_eq!(
+            serde_json::to_value(EscrowStatus::Disputed).unwrap(),
+            "disputed"
+        );
+    }
+}
@@ -1,11 +1,26 @@
 //! Asset aggregation handlers (domains, tokens)
+//!
+//! Assets are found by classifying the wallet's UTXOs against the app
+//! backends: a UTXO may be the ownership output of domains and may carry
+//! token balances. Classifications are cached per outpoint, so a scan only
+//! asks the backends about UTXOs that appeared since the last one and drops
+//! the ones that were spent. `force=true` throws the cache away first.
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tracing::{info, warn};
-use utoipa::ToSchema;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+use utoipa::{IntoParams, ToSchema};
 
+use crate::wallet::Utxo;
 use crate::AppState;
 
 /// Domain asset
@@ -28,9 +43,26 @@ pub struct TokenAsset {
     pub max_supply: Option<String>,
     pub total_minted: Option<String>,
     pub holder_count: Option<i32>,
+    /// Balance summed over the wallet's UTXOs, in base units
+    pub balance: String,
+    pub utxo_count: usize,
     pub is_locked: bool,
 }
 
+/// How the last scan went
+#[derive(Serialize, ToSchema)]
+pub struct AssetScanSummary {
+    /// UTXOs classified by this scan
+    pub scanned: usize,
+    /// Cached UTXOs dropped because they were spent
+    pub pruned: usize,
+    /// UTXOs served from the cache
+    pub cached: usize,
+    /// False if a backend failed; unclassified UTXOs are retried next scan
+    pub complete: bool,
+    pub last_scan: Option<DateTime<Utc>>,
+}
+
 /// Aggregated asset overview
 #[derive(Serialize, ToSchema)]
 pub struct AssetsOverview {
@@ -38,159 +70,329 @@ pub struct AssetsOverview {
     pub tokens: Vec<TokenAsset>,
     pub total_domains: usize,
     pub total_token_types: usize,
+    pub scan: AssetScanSummary,
 }
 
-/// Domains API response structure
-#[derive(Deserialize)]
-struct DomainsApiResponse {
-    data: Vec<DomainData>,
-    total: i64,
+/// Asset query parameters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AssetsQuery {
+    /// Classify every UTXO again instead of using the cache
+    #[serde(default)]
+    pub force: bool,
 }
 
-#[derive(Deserialize)]
+/// Domain as returned by `/domains/by-owner`
+#[derive(Debug, Clone, Deserialize)]
 struct DomainData {
     name: String,
     txid: String,
+    owner_txid: String,
+    owner_vout: u32,
     record_count: Option<i64>,
     block_height: Option<i32>,
     created_at: Option<String>,
 }
 
-/// Tokens API response structure
-#[derive(Deserialize)]
-struct TokensApiResponse {
-    data: Vec<TokenData>,
-    total: i64,
+/// Token UTXO as returned by `/address/{address}/utxos`
+#[derive(Debug, Clone, Deserialize)]
+struct TokenUtxoData {
+    ticker: String,
+    txid: String,
+    vout: u32,
+    amount: String,
+    decimals: i16,
 }
 
-#[derive(Deserialize)]
+/// Token as returned by `/tokens/{ticker}`
+#[derive(Debug, Clone, Deserialize)]
 struct TokenData {
-    ticker: String,
     name: Option<String>,
-    decimals: Option<i16>,
     max_supply: Option<String>,
+    #[serde(alias = "minted_supply")]
     total_minted: Option<String>,
     holder_count: Option<i32>,
-    deploy_txid: Option<String>,
 }
 
-/// Get all assets owned by the wallet
-/// On regtest, this fetches ALL indexed domains and tokens since everything belongs to the user
-#[utoipa::path(
-    get,
-    path = "/wallet/assets",
-    tag = "Assets",
-    responses(
-        (status = 200, description = "All assets owned by the wallet", body = AssetsOverview),
-        (status = 500, description = "Internal server error")
-    )
-)]
-pub async fn get_assets(
-    State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    info!("Fetching all wallet assets...");
+/// What one wallet UTXO holds
+#[derive(Debug, Clone, Default)]
+struct UtxoAssets {
+    domains: Vec<DomainData>,
+    tokens: Vec<TokenUtxoData>,
+}
 
-    let locked_set = state.lock_manager.get_locked_set();
+#[derive(Default)]
+struct ScanState {
+    /// Classification of every wallet UTXO seen, keyed by (txid, vout)
+    utxos: HashMap<(String, u32), UtxoAssets>,
+    /// Token details by ticker, fetched once per ticker
+    tokens: HashMap<String, TokenData>,
+    last_scan: Option<DateTime<Utc>>,
+}
 
-    let mut domains: Vec<DomainAsset> = Vec::new();
-    let mut tokens: Vec<TokenAsset> = Vec::new();
-
-    // Fetch ALL domains from Anchor Domains backend
-    // On regtest, all domains belong to the wallet
-    let domains_url = format!("{}/domains?per_page=1000", state.config.domains_url);
-
-    match reqwest::get(&domains_url).await {
-        Ok(resp) if resp.status().is_success() => match resp.json::<DomainsApiResponse>().await {
-            Ok(data) => {
-                info!(
-                    "Fetched {} domains from backend (total: {})",
-                    data.data.len(),
-                    data.total
-                );
-                for domain in data.data {
-                    let is_locked = locked_set.contains(&(domain.txid.clone(), 0));
-
-                    domains.push(DomainAsset {
-                        name: domain.name,
-                        txid: domain.txid,
-                        record_count: domain.record_count.unwrap_or(0),
-                        block_height: domain.block_height,
-                        created_at: domain.created_at,
-                        is_locked,
-                    });
-                }
+/// Per-UTXO asset classifications kept between scans
+#[derive(Default)]
+pub struct AssetScanner {
+    state: Mutex<ScanState>,
+}
+
+impl AssetScanner {
+    /// Drop spent UTXOs (or everything, if `force`) and return the UTXOs
+    /// that still need classifying, with the number pruned
+    fn prepare(&self, utxos: &[Utxo], force: bool) -> (Vec<Utxo>, usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if force {
+            *state = ScanState::default();
+        }
+
+        let current: HashSet<(String, u32)> =
+            utxos.iter().map(|u| (u.txid.clone(), u.vout)).collect();
+        let before = state.utxos.len();
+        state.utxos.retain(|outpoint, _| current.contains(outpoint));
+        let pruned = before - state.utxos.len();
+
+        let pending = utxos
+            .iter()
+            .filter(|u| !state.utxos.contains_key(&(u.txid.clone(), u.vout)))
+            .cloned()
+            .collect();
+        (pending, pruned)
+    }
+
+    fn known_tickers(&self) -> HashSet<String> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tokens.keys().cloned().collect()
+    }
+
+    /// Store the classifications of a scan
+    fn record(&self, utxos: Vec<((String, u32), UtxoAssets)>, tokens: Vec<(String, TokenData)>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.utxos.extend(utxos);
+        state.tokens.extend(tokens);
+        state.last_scan = Some(Utc::now());
+    }
+
+    /// Domains and tokens across the cached UTXOs
+    fn aggregate(
+        &self,
+        locked_set: &HashSet<(String, u32)>,
+    ) -> (Vec<DomainAsset>, Vec<TokenAsset>, Option<DateTime<Utc>>) {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut domains = Vec::new();
+        let mut tokens: BTreeMap<&str, (i16, u128, usize, bool)> = BTreeMap::new();
+        for (outpoint, assets) in &state.utxos {
+            let is_locked = locked_set.contains(outpoint);
+            for domain in &assets.domains {
+                domains.push(DomainAsset {
+                    name: domain.name.clone(),
+                    txid: domain.txid.clone(),
+                    record_count: domain.record_count.unwrap_or(0),
+                    block_height: domain.block_height,
+                    created_at: domain.created_at.clone(),
+                    is_locked,
+                });
             }
-            Err(e) => {
-                warn!("Failed to parse domains response: {}", e);
+            for token in &assets.tokens {
+                let entry = tokens
+                    .entry(&token.ticker)
+                    .or_insert((token.decimals, 0, 0, false));
+                entry.1 += token.amount.parse::<u128>().unwrap_or(0);
+                entry.2 += 1;
+                entry.3 |= is_locked;
             }
-        },
-        Ok(resp) => {
-            warn!("Domains backend returned status {}", resp.status());
-        }
-        Err(e) => {
-            warn!("Failed to query domains backend: {}", e);
         }
+        domains.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let tokens = tokens
+            .into_iter()
+            .map(|(ticker, (decimals, balance, utxo_count, is_locked))| {
+                let details = state.tokens.get(ticker);
+                TokenAsset {
+                    ticker: ticker.to_string(),
+                    name: details.and_then(|t| t.name.clone()),
+                    decimals,
+                    max_supply: details.and_then(|t| t.max_supply.clone()),
+                    total_minted: details.and_then(|t| t.total_minted.clone()),
+                    holder_count: details.and_then(|t| t.holder_count),
+                    balance: balance.to_string(),
+                    utxo_count,
+                    is_locked,
+                }
+            })
+            .collect();
+
+        (domains, tokens, state.last_scan)
     }
+}
+
+/// Bring the cache up to date with the wallet's UTXOs and aggregate it
+async fn scan_assets(state: &AppState, force: bool) -> Result<AssetsOverview, ApiError> {
+    let utxos = state.wallet.list_utxos().map_err(|e| {
+        error!("Failed to list wallet UTXOs: {}", e);
+        ApiError::internal(e.to_string())
+    })?;
+    let (pending, pruned) = state.asset_scanner.prepare(&utxos, force);
+    let cached = utxos.len() - pending.len();
 
-    // Fetch ALL tokens from Anchor Tokens backend
-    // On regtest, all tokens belong to the wallet
-    let tokens_url = format!("{}/tokens?per_page=1000", state.config.tokens_url);
-
-    match reqwest::get(&tokens_url).await {
-        Ok(resp) if resp.status().is_success() => {
-            match resp.json::<TokensApiResponse>().await {
-                Ok(data) => {
-                    info!(
-                        "Fetched {} tokens from backend (total: {})",
-                        data.data.len(),
-                        data.total
-                    );
-                    for token in data.data {
-                        // Check if the deploy txid is locked
-                        let is_locked = token
-                            .deploy_txid
-                            .as_ref()
-                            .map(|txid| locked_set.contains(&(txid.clone(), 0)))
-                            .unwrap_or(false);
-
-                        tokens.push(TokenAsset {
-                            ticker: token.ticker,
-                            name: token.name,
-                            decimals: token.decimals.unwrap_or(0),
-                            max_supply: token.max_supply,
-                            total_minted: token.total_minted,
-                            holder_count: token.holder_count,
-                            is_locked,
-                        });
+    let mut complete = true;
+    if !pending.is_empty() {
+        let (domains, tokens) = tokio::join!(
+            fetch_domains(state, &pending),
+            fetch_token_utxos(state, &pending)
+        );
+        match (domains, tokens) {
+            (Ok(domains), Ok(token_utxos)) => {
+                let mut classified: HashMap<(String, u32), UtxoAssets> = pending
+                    .iter()
+                    .map(|u| ((u.txid.clone(), u.vout), UtxoAssets::default()))
+                    .collect();
+                for domain in domains {
+                    let outpoint = (domain.owner_txid.clone(), domain.owner_vout);
+                    if let Some(assets) = classified.get_mut(&outpoint) {
+                        assets.domains.push(domain);
                     }
                 }
-                Err(e) => {
-                    warn!("Failed to parse tokens response: {}", e);
+                for token in token_utxos {
+                    let outpoint = (token.txid.clone(), token.vout);
+                    if let Some(assets) = classified.get_mut(&outpoint) {
+                        assets.tokens.push(token);
+                    }
                 }
+
+                let known = state.asset_scanner.known_tickers();
+                let tickers: BTreeSet<String> = classified
+                    .values()
+                    .flat_map(|a| &a.tokens)
+                    .map(|t| t.ticker.clone())
+                    .filter(|ticker| !known.contains(ticker))
+                    .collect();
+                let details = fetch_token_details(state, tickers).await;
+
+                state
+                    .asset_scanner
+                    .record(classified.into_iter().collect(), details);
+            }
+            (domains, tokens) => {
+                for e in [domains.err(), tokens.err()].into_iter().flatten() {
+                    warn!("Asset scan incomplete: {}", e);
+                }
+                complete = false;
             }
-        }
-        Ok(resp) => {
-            warn!("Tokens backend returned status {}", resp.status());
-        }
-        Err(e) => {
-            warn!("Failed to query tokens backend: {}", e);
         }
     }
 
-    let total_domains = domains.len();
-    let total_token_types = tokens.len();
+    let scanned = if complete { pending.len() } else { 0 };
 
+    let locked_set = state.lock_manager.get_locked_set();
+    let (domains, tokens, last_scan) = state.asset_scanner.aggregate(&locked_set);
     info!(
-        "Found {} domains and {} token types",
-        total_domains, total_token_types
+        "Asset scan: {} classified, {} pruned, {} cached; {} domains and {} token types",
+        scanned,
+        pruned,
+        cached,
+        domains.len(),
+        tokens.len()
     );
 
-    Ok(Json(AssetsOverview {
+    Ok(AssetsOverview {
+        total_domains: domains.len(),
+        total_token_types: tokens.len(),
         domains,
         tokens,
-        total_domains,
-        total_token_types,
-    }))
+        scan: AssetScanSummary {
+            scanned,
+            pruned,
+            cached,
+            complete,
+            last_scan,
+        },
+    })
+}
+
+/// Domains whose ownership output is among the UTXOs
+async fn fetch_domains(state: &AppState, utxos: &[Utxo]) -> anyhow::Result<Vec<DomainData>> {
+    let txids: BTreeSet<&str> = utxos.iter().map(|u| u.txid.as_str()).collect();
+    Ok(state
+        .http
+        .post(format!("{}/domains/by-owner", state.config.domains_url))
+        .json(&serde_json::json!({ "txids": txids }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Token UTXOs held by the addresses of the UTXOs
+async fn fetch_token_utxos(state: &AppState, utxos: &[Utxo]) -> anyhow::Result<Vec<TokenUtxoData>> {
+    let addresses: BTreeSet<&str> = utxos.iter().filter_map(|u| u.address.as_deref()).collect();
+    let mut requests = JoinSet::new();
+    for address in addresses {
+        let request = state.http.get(format!(
+            "{}/address/{}/utxos",
+            state.config.tokens_url, address
+        ));
+        requests.spawn(async move {
+            request
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Vec<TokenUtxoData>>()
+                .await
+        });
+    }
+
+    let mut token_utxos = Vec::new();
+    while let Some(result) = requests.join_next().await {
+        token_utxos.extend(result??);
+    }
+    Ok(token_utxos)
+}
+
+/// Details of newly seen tokens; a ticker that can't be fetched is retried
+/// when it next shows up
+async fn fetch_token_details(
+    state: &AppState,
+    tickers: BTreeSet<String>,
+) -> Vec<(String, TokenData)> {
+    let mut details = Vec::new();
+    for ticker in tickers {
+        let url = format!("{}/tokens/{}", state.config.tokens_url, ticker);
+        let result = async {
+            state
+                .http
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<TokenData>()
+                .await
+        }
+        .await;
+        match result {
+            Ok(token) => details.push((ticker, token)),
+            Err(e) => warn!("Failed to fetch token {}: {}", ticker, e),
+        }
+    }
+    details
+}
+
+/// Get all assets owned by the wallet
+#[utoipa::path(
+    get,
+    path = "/wallet/assets",
+    tag = "Assets",
+    params(AssetsQuery),
+    responses(
+        (status = 200, description = "All assets owned by the wallet", body = AssetsOverview),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_assets(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AssetsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(scan_assets(&state, query.force).await?))
 }
 
 /// Get domains owned by the wallet
@@ -198,6 +400,7 @@ pub async fn get_assets(
     get,
     path = "/wallet/assets/domains",
     tag = "Assets",
+    params(AssetsQuery),
     responses(
         (status = 200, description = "Domains owned by the wallet", body = Vec<DomainAsset>),
         (status = 500, description = "Internal server error")
@@ -205,42 +408,9 @@ pub async fn get_assets(
 )]
 pub async fn get_assets_domains(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let locked_set = state.lock_manager.get_locked_set();
-    let mut domains: Vec<DomainAsset> = Vec::new();
-
-    // Fetch ALL domains from backend
-    let domains_url = format!("{}/domains?per_page=1000", state.config.domains_url);
-
-    match reqwest::get(&domains_url).await {
-        Ok(resp) if resp.status().is_success() => match resp.json::<DomainsApiResponse>().await {
-            Ok(data) => {
-                for domain in data.data {
-                    let is_locked = locked_set.contains(&(domain.txid.clone(), 0));
-
-                    domains.push(DomainAsset {
-                        name: domain.name,
-                        txid: domain.txid,
-                        record_count: domain.record_count.unwrap_or(0),
-                        block_height: domain.block_height,
-                        created_at: domain.created_at,
-                        is_locked,
-                    });
-                }
-            }
-            Err(e) => {
-                warn!("Failed to parse domains response: {}", e);
-            }
-        },
-        Ok(resp) => {
-            warn!("Domains backend returned status {}", resp.status());
-        }
-        Err(e) => {
-            warn!("Failed to query domains backend: {}", e);
-        }
-    }
-
-    Ok(Json(domains))
+    Query(query): Query<AssetsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(scan_assets(&state, query.force).await?.domains))
 }
 
 /// Get tokens owned by the wallet
@@ -248,6 +418,7 @@ pub async fn get_assets_domains(
     get,
     path = "/wallet/assets/tokens",
     tag = "Assets",
+    params(AssetsQuery),
     responses(
         (status = 200, description = "Tokens owned by the wallet", body = Vec<TokenAsset>),
         (status = 500, description = "Internal server error")
@@ -255,45 +426,7 @@ pub async fn get_assets_domains(
 )]
 pub async fn get_assets_tokens(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let locked_set = state.lock_manager.get_locked_set();
-    let mut tokens: Vec<TokenAsset> = Vec::new();
-
-    // Fetch ALL tokens from backend
-    let tokens_url = format!("{}/tokens?per_page=1000", state.config.tokens_url);
-
-    match reqwest::get(&tokens_url).await {
-        Ok(resp) if resp.status().is_success() => match resp.json::<TokensApiResponse>().await {
-            Ok(data) => {
-                for token in data.data {
-                    let is_locked = token
-                        .deploy_txid
-                        .as_ref()
-                        .map(|txid| locked_set.contains(&(txid.clone(), 0)))
-                        .unwrap_or(false);
-
-                    tokens.push(TokenAsset {
-                        ticker: token.ticker,
-                        name: token.name,
-                        decimals: token.decimals.unwrap_or(0),
-                        max_supply: token.max_supply,
-                        total_minted: token.total_minted,
-                        holder_count: token.holder_count,
-                        is_locked,
-                    });
-                }
-            }
-            Err(e) => {
-                warn!("Failed to parse tokens response: {}", e);
-            }
-        },
-        Ok(resp) => {
-            warn!("Tokens backend returned status {}", resp.status());
-        }
-        Err(e) => {
-            warn!("Failed to query tokens backend: {}", e);
-        }
-    }
-
-    Ok(Json(tokens))
+    Query(query): Query<AssetsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(scan_assets(&state, query.force).await?.tokens))
 }
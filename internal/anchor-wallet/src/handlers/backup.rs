@@ -2,13 +2,21 @@
 //!
 //! Endpoints for wallet backup, mnemonic display, and recovery.
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{error, info};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::migration::{MigrationNotification, WalletMigrator};
+use crate::wallet::addresses::{AddressReport, ManagedAddress};
+use crate::wallet::bdk_service::SyncStatus;
+use crate::wallet::electrum_pool::ServerHealth;
 use crate::AppState;
 
 /// Mnemonic response (seed phrase)
@@ -45,6 +53,10 @@ pub struct WalletInfoResponse {
     pub addresses_used: u32,
     /// BDK wallet enabled
     pub bdk_enabled: bool,
+    /// Electrum server the wallet currently syncs from
+    pub electrum_server: Option<String>,
+    /// Health of every configured Electrum server
+    pub electrum_servers: Vec<ServerHealth>,
 }
 
 /// Descriptors response
@@ -91,7 +103,7 @@ pub struct VerifyMnemonicResponse {
 )]
 pub async fn get_mnemonic(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let bdk_wallet = match &state.bdk_wallet {
         Some(w) => w,
         None => {
@@ -137,7 +149,7 @@ pub async fn get_mnemonic(
 )]
 pub async fn get_wallet_info(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let bdk_wallet = match &state.bdk_wallet {
         Some(w) => w,
         None => {
@@ -151,6 +163,8 @@ pub async fn get_wallet_info(
                 has_mnemonic: false,
                 addresses_used: 0,
                 bdk_enabled: false,
+                electrum_server: None,
+                electrum_servers: Vec::new(),
             }));
         }
     };
@@ -166,10 +180,12 @@ pub async fn get_wallet_info(
             has_mnemonic: info.has_mnemonic,
             addresses_used: info.addresses_used,
             bdk_enabled: true,
+            electrum_server: bdk_wallet.electrum().active_url(),
+            electrum_servers: bdk_wallet.electrum().servers(),
         })),
         Err(e) => {
             error!("Failed to get wallet info: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -186,13 +202,13 @@ pub async fn get_wallet_info(
 )]
 pub async fn get_descriptors(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let bdk_wallet = match &state.bdk_wallet {
         Some(w) => w,
         None => {
-            return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
-                "BDK wallet not enabled".to_string(),
+            return Err(ApiError::new(
+                ErrorCode::Unavailable,
+                "BDK wallet not enabled",
             ));
         }
     };
@@ -205,7 +221,7 @@ pub async fn get_descriptors(
         })),
         Err(e) => {
             error!("Failed to get descriptors: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -224,7 +240,7 @@ pub async fn get_descriptors(
 pub async fn verify_mnemonic(
     State(state): State<Arc<AppState>>,
     Json(req): Json<VerifyMnemonicRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     use bdk_wallet::keys::bip39::{Language, Mnemonic};
 
     // Try to parse the mnemonic
@@ -271,13 +287,13 @@ pub async fn verify_mnemonic(
 )]
 pub async fn sync_bdk_wallet(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let bdk_wallet = match &state.bdk_wallet {
         Some(w) => w,
         None => {
-            return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
-                "BDK wallet not enabled".to_string(),
+            return Err(ApiError::new(
+                ErrorCode::Unavailable,
+                "BDK wallet not enabled",
             ));
         }
     };
@@ -292,11 +308,33 @@ pub async fn sync_bdk_wallet(
         }
         Err(e) => {
             error!("Failed to sync BDK wallet: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
 
+/// Get the progress of the current or last BDK wallet sync
+#[utoipa::path(
+    get,
+    path = "/wallet/sync-status",
+    tag = "Backup",
+    responses(
+        (status = 200, description = "Sync status", body = SyncStatus),
+        (status = 503, description = "BDK wallet not available")
+    )
+)]
+pub async fn get_sync_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    match &state.bdk_wallet {
+        Some(w) => Ok(Json(w.sync_status())),
+        None => Err(ApiError::new(
+            ErrorCode::Unavailable,
+            "BDK wallet not enabled",
+        )),
+    }
+}
+
 /// Get BDK wallet balance
 #[utoipa::path(
     get,
@@ -309,22 +347,106 @@ pub async fn sync_bdk_wallet(
 )]
 pub async fn get_bdk_balance(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let bdk_wallet = match &state.bdk_wallet {
         Some(w) => w,
         None => {
-            return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
-                "BDK wallet not enabled".to_string(),
+            return Err(ApiError::new(
+                ErrorCode::Unavailable,
+                "BDK wallet not enabled",
             ));
         }
     };
 
-    match bdk_wallet.get_balance() {
+    match bdk_wallet.get_balance(&state.lock_manager.get_locked_set()) {
         Ok(balance) => Ok(Json(balance)),
         Err(e) => {
             error!("Failed to get BDK balance: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// BDK address request parameters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct BdkAddressQuery {
+    /// `receive` (default) or `change`
+    pub keychain: Option<String>,
+}
+
+/// Get a fresh BDK wallet address, within the gap limit
+#[utoipa::path(
+    get,
+    path = "/wallet/bdk/address",
+    tag = "Backup",
+    params(BdkAddressQuery),
+    responses(
+        (status = 200, description = "Fresh address", body = ManagedAddress),
+        (status = 400, description = "Unknown keychain"),
+        (status = 503, description = "BDK wallet not available")
+    )
+)]
+pub async fn get_bdk_address(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BdkAddressQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let bdk_wallet = match &state.bdk_wallet {
+        Some(w) => w,
+        None => {
+            return Err(ApiError::new(
+                ErrorCode::Unavailable,
+                "BDK wallet not enabled",
+            ));
+        }
+    };
+
+    let result = match query.keychain.as_deref().unwrap_or("receive") {
+        "receive" => bdk_wallet.next_receive_address(),
+        "change" => bdk_wallet.next_change_address(),
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "Unknown keychain '{}', expected receive or change",
+                other
+            )));
+        }
+    };
+    match result {
+        Ok(address) => Ok(Json(address)),
+        Err(e) => {
+            error!("Failed to get BDK address: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Get the BDK wallet's gap limit state and address usage
+#[utoipa::path(
+    get,
+    path = "/wallet/bdk/addresses",
+    tag = "Backup",
+    responses(
+        (status = 200, description = "Gap limit state and revealed addresses", body = AddressReport),
+        (status = 503, description = "BDK wallet not available")
+    )
+)]
+pub async fn get_bdk_addresses(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let bdk_wallet = match &state.bdk_wallet {
+        Some(w) => w,
+        None => {
+            return Err(ApiError::new(
+                ErrorCode::Unavailable,
+                "BDK wallet not enabled",
+            ));
+        }
+    };
+
+    match bdk_wallet.address_report() {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            error!("Failed to get BDK addresses: {}", e);
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -424,7 +546,7 @@ pub struct VerifyBackupResponse {
 pub async fn export_backup(
     State(state): State<Arc<AppState>>,
     Json(req): Json<ExportBackupRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     use aes_gcm::{
         aead::{Aead, KeyInit},
         Aes256Gcm, Nonce,
@@ -459,7 +581,7 @@ pub async fn export_backup(
     // Get wallet info
     let wallet_info = bdk_wallet
         .get_wallet_info()
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
 
     // Get locked UTXOs
     let locked_utxos: Vec<LockedUtxoBackup> = state
@@ -487,6 +609,11 @@ pub async fn export_backup(
                     Some(asset_type.clone()),
                     Some(asset_id.clone()),
                 ),
+                crate::locked::LockReason::Scheduled { txid } => (
+                    "scheduled".to_string(),
+                    Some("scheduled_message".to_string()),
+                    Some(txid.clone()),
+                ),
             };
             LockedUtxoBackup {
                 txid: u.txid,
@@ -506,12 +633,7 @@ pub async fn export_backup(
     let mut key = [0u8; 32];
     Argon2::default()
         .hash_password_into(req.password.as_bytes(), &salt, &mut key)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Key derivation failed: {:?}", e),
-            )
-        })?;
+        .map_err(|e| ApiError::internal(format!("Key derivation failed: {:?}", e)))?;
 
     // Generate nonce
     let mut nonce_bytes = [0u8; 12];
@@ -519,21 +641,12 @@ pub async fn export_backup(
     let nonce = Nonce::from_slice(&nonce_bytes);
 
     // Encrypt mnemonic
-    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Cipher creation failed: {:?}", e),
-        )
-    })?;
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| ApiError::internal(format!("Cipher creation failed: {:?}", e)))?;
 
     let ciphertext = cipher
         .encrypt(nonce, mnemonic_words.as_bytes())
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Encryption failed: {:?}", e),
-            )
-        })?;
+        .map_err(|e| ApiError::internal(format!("Encryption failed: {:?}", e)))?;
 
     // Calculate checksum (hash of mnemonic + descriptors + locked UTXOs)
     let mut hasher = Sha256::new();
@@ -583,7 +696,7 @@ pub async fn export_backup(
 )]
 pub async fn verify_backup(
     Json(req): Json<VerifyBackupRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     use aes_gcm::{
         aead::{Aead, KeyInit},
         Aes256Gcm, Nonce,
@@ -750,12 +863,12 @@ pub async fn verify_backup(
 )]
 pub async fn get_migration_status(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let migrator = WalletMigrator::new(state.config.data_dir.clone());
 
     let status = migrator
         .load_status()
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| ApiError::internal(e.to_string()))?;
 
     let notification = MigrationNotification::from_status(&status);
 
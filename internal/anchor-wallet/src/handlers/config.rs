@@ -0,0 +1,42 @@
+//! Runtime configuration endpoints
+
+use anchor_api_error::ApiError;
+use axum::{extract::State, response::IntoResponse, Json};
+use std::sync::Arc;
+use tracing::error;
+
+use crate::reload::{ReloadResponse, RuntimeSettings};
+use crate::AppState;
+
+/// Get the runtime settings currently in effect
+#[utoipa::path(
+    get,
+    path = "/config",
+    tag = "System",
+    responses(
+        (status = 200, description = "Current runtime settings", body = RuntimeSettings)
+    )
+)]
+pub async fn get_config(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.reloader.settings())
+}
+
+/// Re-read the settings file and apply it without restarting
+#[utoipa::path(
+    post,
+    path = "/config/reload",
+    tag = "System",
+    responses(
+        (status = 200, description = "Configuration reloaded", body = ReloadResponse),
+        (status = 400, description = "Invalid settings file or unreachable RPC endpoint")
+    )
+)]
+pub async fn reload_config(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let response = state.reloader.reload(&state.wallet).map_err(|e| {
+        error!("Configuration reload failed: {:#}", e);
+        ApiError::bad_request(format!("{:#}", e))
+    })?;
+    Ok(Json(response))
+}
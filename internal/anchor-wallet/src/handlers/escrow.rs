@@ -0,0 +1,547 @@
+//! Escrow handlers for peer-to-peer trades
+//!
+//! 2-of-3 multisig escrows between a buyer, a seller and an arbiter; see
+//! `crate::escrow`. Funding and disputes are signed by this wallet. Release
+//! and refund come back as PSBTs for two of the parties to sign, and are
+//! broadcast through `/wallet/escrow/{id}/broadcast`.
+
+use anchor_api_error::{ApiError, ErrorCode};
+use anchor_specs::escrow::{escrow_script, EscrowAction, EscrowSpec};
+use anchor_specs::KindSpec;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bitcoin::consensus::encode::deserialize_hex;
+use bitcoin::{Address, OutPoint, PublicKey, ScriptBuf, Transaction, Txid};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info};
+use utoipa::ToSchema;
+
+use crate::escrow::{Escrow, EscrowSettlement, EscrowStatus};
+use crate::wallet::{settlement_psbt, CoinControl};
+use crate::AppState;
+
+/// Request body for creating an escrow
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateEscrowRequest {
+    /// Compressed public keys (hex)
+    pub buyer_pubkey: String,
+    pub seller_pubkey: String,
+    pub arbiter_pubkey: String,
+    /// Where a refund is paid
+    pub buyer_address: String,
+    /// Where a release is paid
+    pub seller_address: String,
+    /// Amount to lock in the escrow, in satoshis
+    pub amount_sats: u64,
+    /// What is being traded
+    #[serde(default)]
+    pub terms: String,
+}
+
+/// Request body for escrow transactions
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct EscrowFeeRequest {
+    /// Fee rate in sat/vbyte (default: the configured default fee rate)
+    pub fee_rate: Option<u64>,
+}
+
+/// Request body for disputing an escrow
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DisputeEscrowRequest {
+    /// Why the trade is disputed
+    #[serde(default)]
+    pub reason: String,
+    /// Fee rate in sat/vbyte (default: the configured default fee rate)
+    pub fee_rate: Option<u64>,
+}
+
+/// Request body for broadcasting a settlement
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BroadcastEscrowRequest {
+    /// Signed copies of the settlement PSBT (base64); their signatures are
+    /// combined, so each signer may send their own
+    pub psbts: Vec<String>,
+}
+
+/// An escrow transaction that was broadcast
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EscrowTxResponse {
+    pub escrow: Escrow,
+    pub txid: String,
+    pub hex: String,
+}
+
+/// List escrows
+#[utoipa::path(
+    get,
+    path = "/wallet/escrow",
+    tag = "Escrow",
+    responses(
+        (status = 200, description = "Escrows, newest first", body = Vec<Escrow>)
+    )
+)]
+pub async fn list_escrows(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.escrows.list())
+}
+
+/// Get an escrow
+#[utoipa::path(
+    get,
+    path = "/wallet/escrow/{id}",
+    tag = "Escrow",
+    params(
+        ("id" = String, Path, description = "Escrow id")
+    ),
+    responses(
+        (status = 200, description = "Escrow", body = Escrow),
+        (status = 404, description = "No escrow with this id")
+    )
+)]
+pub async fn get_escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    find_escrow(&state, &id).map(Json)
+}
+
+/// Create a 2-of-3 escrow between a buyer, a seller and an arbiter
+#[utoipa::path(
+    post,
+    path = "/wallet/escrow",
+    tag = "Escrow",
+    request_body = CreateEscrowRequest,
+    responses(
+        (status = 201, description = "Escrow created, waiting for funding", body = Escrow),
+        (status = 400, description = "Invalid keys, addresses or amount")
+    )
+)]
+pub async fn create_escrow(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateEscrowRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let buyer = parse_pubkey("buyer_pubkey", &req.buyer_pubkey)?;
+    let seller = parse_pubkey("seller_pubkey", &req.seller_pubkey)?;
+    let arbiter = parse_pubkey("arbiter_pubkey", &req.arbiter_pubkey)?;
+    parse_address(&state, "buyer_address", &req.buyer_address)?;
+    parse_address(&state, "seller_address", &req.seller_address)?;
+    EscrowSpec::fund(
+        &buyer,
+        &seller,
+        &arbiter,
+        req.amount_sats,
+        req.terms.clone(),
+    )
+    .validate()
+    .map_err(|e| ApiError::bad_request(format!("Invalid escrow: {}", e)))?;
+
+    let script = escrow_script(&buyer, &seller, &arbiter);
+    let address = Address::p2wsh(&script, state.config.get_network());
+    let escrow = state
+        .escrows
+        .create(
+            buyer.to_string(),
+            seller.to_string(),
+            arbiter.to_string(),
+            req.buyer_address,
+            req.seller_address,
+            req.amount_sats,
+            req.terms,
+            address.to_string(),
+            script.to_hex_string(),
+        )
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?;
+    Ok((StatusCode::CREATED, Json(escrow)))
+}
+
+/// Fund an escrow from this wallet
+///
+/// Pays the amount into the escrow address with the Fund message.
+#[utoipa::path(
+    post,
+    path = "/wallet/escrow/{id}/fund",
+    tag = "Escrow",
+    params(
+        ("id" = String, Path, description = "Escrow id")
+    ),
+    request_body = EscrowFeeRequest,
+    responses(
+        (status = 200, description = "Funding transaction broadcast", body = EscrowTxResponse),
+        (status = 400, description = "Invalid fee rate or watch-only wallet"),
+        (status = 404, description = "No escrow with this id"),
+        (status = 409, description = "Escrow already funded")
+    )
+)]
+pub async fn fund_escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<EscrowFeeRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let escrow = find_escrow(&state, &id)?;
+    check_transition(&escrow, EscrowAction::Fund)?;
+    require_signing_wallet(&state)?;
+    let fee_rate = fee_rate(&state, req.fee_rate)?;
+
+    let spec = fund_spec(&escrow)?;
+    let address = parse_address(&state, "address", &escrow.address)?;
+    let coins = CoinControl::locked(state.lock_manager.get_locked_set());
+    let (created, vout) = state
+        .wallet
+        .fund_escrow(&spec, &address, escrow.amount_sats, fee_rate, Some(&coins))
+        .map_err(|e| {
+            error!("Failed to fund escrow {}: {:#}", id, e);
+            ApiError::internal(format!("{:#}", e))
+        })?;
+
+    let escrow = update(&state, &id, |e| {
+        e.status = EscrowStatus::Funded;
+        e.funding_txid = Some(created.txid.clone());
+        e.funding_vout = Some(vout);
+    })?;
+    Ok(Json(EscrowTxResponse {
+        escrow,
+        txid: created.txid,
+        hex: created.hex,
+    }))
+}
+
+/// Dispute an escrow
+///
+/// Records the Dispute message, anchored to the escrow output, so the
+/// arbiter takes over.
+#[utoipa::path(
+    post,
+    path = "/wallet/escrow/{id}/dispute",
+    tag = "Escrow",
+    params(
+        ("id" = String, Path, description = "Escrow id")
+    ),
+    request_body = DisputeEscrowRequest,
+    responses(
+        (status = 200, description = "Dispute broadcast", body = EscrowTxResponse),
+        (status = 400, description = "Invalid reason or fee rate, or watch-only wallet"),
+        (status = 404, description = "No escrow with this id"),
+        (status = 409, description = "Escrow isn't funded or is already disputed or settled")
+    )
+)]
+pub async fn dispute_escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<DisputeEscrowRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let escrow = find_escrow(&state, &id)?;
+    check_transition(&escrow, EscrowAction::Dispute)?;
+    require_signing_wallet(&state)?;
+    let fee_rate = fee_rate(&state, req.fee_rate)?;
+
+    let spec = EscrowSpec::dispute(req.reason);
+    spec.validate()
+        .map_err(|e| ApiError::bad_request(format!("Invalid dispute: {}", e)))?;
+    let funding = funding_outpoint(&escrow)?;
+    let anchor_vout = u8::try_from(funding.vout)
+        .map_err(|_| ApiError::bad_request("Escrow output index above 255"))?;
+    let coins = CoinControl::locked(state.lock_manager.get_locked_set());
+    let created = state
+        .wallet
+        .create_anchor_transaction_with_locks(
+            EscrowSpec::KIND_ID,
+            spec.to_bytes(),
+            Some(funding.txid.to_string()),
+            Some(anchor_vout),
+            Vec::new(),
+            Some(0),
+            fee_rate,
+            Some(&coins),
+        )
+        .map_err(|e| {
+            error!("Failed to dispute escrow {}: {:#}", id, e);
+            ApiError::internal(format!("{:#}", e))
+        })?;
+
+    let escrow = update(&state, &id, |e| {
+        e.status = EscrowStatus::Disputed;
+        e.settlement = None;
+    })?;
+    info!("Disputed escrow {} in {}", id, created.txid);
+    Ok(Json(EscrowTxResponse {
+        escrow,
+        txid: created.txid,
+        hex: created.hex,
+    }))
+}
+
+/// Build the release of an escrow to the seller
+///
+/// Returns the escrow with the unsigned settlement PSBT. Buyer and seller,
+/// or the arbiter and either of them, sign it.
+#[utoipa::path(
+    post,
+    path = "/wallet/escrow/{id}/release",
+    tag = "Escrow",
+    params(
+        ("id" = String, Path, description = "Escrow id")
+    ),
+    request_body = EscrowFeeRequest,
+    responses(
+        (status = 200, description = "Escrow with the settlement to sign", body = Escrow),
+        (status = 400, description = "Invalid fee rate, or the amount can't cover the fee"),
+        (status = 404, description = "No escrow with this id"),
+        (status = 409, description = "Escrow isn't funded or is already settled")
+    )
+)]
+pub async fn release_escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<EscrowFeeRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    settle(&state, &id, EscrowAction::Release, req.fee_rate).map(Json)
+}
+
+/// Build the refund of an escrow to the buyer
+///
+/// Returns the escrow with the unsigned settlement PSBT. Buyer and seller,
+/// or the arbiter and either of them, sign it.
+#[utoipa::path(
+    post,
+    path = "/wallet/escrow/{id}/refund",
+    tag = "Escrow",
+    params(
+        ("id" = String, Path, description = "Escrow id")
+    ),
+    request_body = EscrowFeeRequest,
+    responses(
+        (status = 200, description = "Escrow with the settlement to sign", body = Escrow),
+        (status = 400, description = "Invalid fee rate, or the amount can't cover the fee"),
+        (status = 404, description = "No escrow with this id"),
+        (status = 409, description = "Escrow isn't funded or is already settled")
+    )
+)]
+pub async fn refund_escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<EscrowFeeRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    settle(&state, &id, EscrowAction::Refund, req.fee_rate).map(Json)
+}
+
+/// Broadcast a signed settlement
+#[utoipa::path(
+    post,
+    path = "/wallet/escrow/{id}/broadcast",
+    tag = "Escrow",
+    params(
+        ("id" = String, Path, description = "Escrow id")
+    ),
+    request_body = BroadcastEscrowRequest,
+    responses(
+        (status = 200, description = "Settlement broadcast", body = EscrowTxResponse),
+        (status = 400, description = "Not enough signatures, or not the pending settlement"),
+        (status = 404, description = "No escrow with this id"),
+        (status = 409, description = "No settlement waiting for signatures")
+    )
+)]
+pub async fn broadcast_escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<BroadcastEscrowRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let escrow = find_escrow(&state, &id)?;
+    let settlement = escrow.settlement.clone().ok_or_else(|| {
+        ApiError::new(
+            ErrorCode::Conflict,
+            "Escrow has no settlement; build a release or refund first",
+        )
+    })?;
+    let status = match settlement.action.as_str() {
+        "release" => EscrowStatus::Released,
+        _ => EscrowStatus::Refunded,
+    };
+
+    let psbt = match req.psbts.as_slice() {
+        [] => {
+            return Err(ApiError::bad_request(
+                "At least one signed PSBT is required",
+            ))
+        }
+        [psbt] => psbt.clone(),
+        psbts => state
+            .wallet
+            .combine_psbts(psbts)
+            .map_err(|e| ApiError::bad_request(format!("Failed to combine PSBTs: {:#}", e)))?,
+    };
+    let hex = state
+        .wallet
+        .finalize_psbt(&psbt)
+        .map_err(|e| ApiError::bad_request(format!("{:#}", e)))?;
+    let tx: Transaction = deserialize_hex(&hex)
+        .map_err(|e| ApiError::internal(format!("Invalid finalized transaction: {}", e)))?;
+    if tx.compute_txid().to_string() != settlement.txid {
+        return Err(ApiError::bad_request(
+            "PSBT is not the escrow's pending settlement",
+        ));
+    }
+
+    let txid = state.wallet.broadcast(&hex).map_err(|e| {
+        error!("Failed to broadcast settlement of escrow {}: {:#}", id, e);
+        ApiError::internal(format!("{:#}", e))
+    })?;
+    let escrow = update(&state, &id, |e| {
+        e.status = status;
+        e.settlement = None;
+        e.closing_txid = Some(txid.clone());
+    })?;
+    info!("Settled escrow {} in {}", id, txid);
+    Ok(Json(EscrowTxResponse { escrow, txid, hex }))
+}
+
+/// Build a settlement PSBT and store it on the escrow
+fn settle(
+    state: &AppState,
+    id: &str,
+    action: EscrowAction,
+    fee_rate: Option<u64>,
+) -> Result<Escrow, ApiError> {
+    let escrow = find_escrow(state, id)?;
+    check_transition(&escrow, action)?;
+    let fee_rate = self::fee_rate(state, fee_rate)?;
+    let funding = funding_outpoint(&escrow)?;
+    let unspent = state
+        .wallet
+        .escrow_unspent(&funding.txid.to_string(), funding.vout)
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?;
+    if !unspent {
+        return Err(ApiError::new(
+            ErrorCode::Conflict,
+            "Escrow output is already spent",
+        ));
+    }
+
+    let (spec, payee, name) = match action {
+        EscrowAction::Release => (EscrowSpec::release(), &escrow.seller_address, "release"),
+        _ => (EscrowSpec::refund(), &escrow.buyer_address, "refund"),
+    };
+    let payee = parse_address(state, "payout address", payee)?;
+    let script = ScriptBuf::from_hex(&escrow.witness_script)
+        .map_err(|e| ApiError::internal(format!("Invalid witness script: {}", e)))?;
+    let unsigned = settlement_psbt(
+        &spec,
+        &script,
+        funding,
+        escrow.amount_sats,
+        &payee,
+        fee_rate,
+    )
+    .map_err(|e| ApiError::bad_request(format!("{:#}", e)))?;
+
+    info!("Built {} of escrow {} as {}", name, id, unsigned.tx.txid);
+    update(state, id, |e| {
+        e.settlement = Some(EscrowSettlement {
+            action: name.to_string(),
+            txid: unsigned.tx.txid,
+            psbt: unsigned.psbt,
+        });
+    })
+}
+
+fn find_escrow(state: &AppState, id: &str) -> Result<Escrow, ApiError> {
+    state
+        .escrows
+        .get(id)
+        .ok_or_else(|| ApiError::not_found(format!("No escrow with id '{}'", id)))
+}
+
+fn update(
+    state: &AppState,
+    id: &str,
+    change: impl FnOnce(&mut Escrow),
+) -> Result<Escrow, ApiError> {
+    state
+        .escrows
+        .update(id, change)
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?
+        .ok_or_else(|| ApiError::not_found(format!("No escrow with id '{}'", id)))
+}
+
+/// Reject an action the escrow's state doesn't allow
+fn check_transition(escrow: &Escrow, action: EscrowAction) -> Result<(), ApiError> {
+    if action.can_follow(escrow.status.last_action()) {
+        return Ok(());
+    }
+    Err(ApiError::new(
+        ErrorCode::Conflict,
+        format!("Can't {:?} an escrow that is {:?}", action, escrow.status).to_lowercase(),
+    ))
+}
+
+fn require_signing_wallet(state: &AppState) -> Result<(), ApiError> {
+    if state.wallet.is_watch_only() {
+        return Err(ApiError::bad_request(
+            "Escrow funding and disputes need a wallet that can sign",
+        ));
+    }
+    Ok(())
+}
+
+/// Requested fee rate, or the default, within the configured maximum
+fn fee_rate(state: &AppState, requested: Option<u64>) -> Result<u64, ApiError> {
+    let settings = state.reloader.settings();
+    let fee_rate = requested.unwrap_or(settings.default_fee_rate);
+    if fee_rate == 0 {
+        return Err(ApiError::bad_request("Fee rate must be at least 1 sat/vB"));
+    }
+    if let Some(max_fee_rate) = settings.max_fee_rate.filter(|max| fee_rate > *max) {
+        return Err(ApiError::bad_request(format!(
+            "Fee rate {} sat/vB exceeds the configured maximum of {}",
+            fee_rate, max_fee_rate
+        )));
+    }
+    Ok(fee_rate)
+}
+
+/// The Fund message of an escrow
+fn fund_spec(escrow: &Escrow) -> Result<EscrowSpec, ApiError> {
+    let key = |name: &str, hex: &str| {
+        PublicKey::from_str(hex)
+            .map_err(|e| ApiError::internal(format!("Invalid {} key: {}", name, e)))
+    };
+    Ok(EscrowSpec::fund(
+        &key("buyer", &escrow.buyer_pubkey)?,
+        &key("seller", &escrow.seller_pubkey)?,
+        &key("arbiter", &escrow.arbiter_pubkey)?,
+        escrow.amount_sats,
+        escrow.terms.clone(),
+    ))
+}
+
+fn funding_outpoint(escrow: &Escrow) -> Result<OutPoint, ApiError> {
+    let (Some(txid), Some(vout)) = (&escrow.funding_txid, escrow.funding_vout) else {
+        return Err(ApiError::new(ErrorCode::Conflict, "Escrow isn't funded"));
+    };
+    let txid = Txid::from_str(txid)
+        .map_err(|e| ApiError::internal(format!("Invalid funding txid: {}", e)))?;
+    Ok(OutPoint { txid, vout })
+}
+
+fn parse_pubkey(field: &str, hex: &str) -> Result<PublicKey, ApiError> {
+    let key = PublicKey::from_str(hex)
+        .map_err(|e| ApiError::bad_request(format!("Invalid {}: {}", field, e)))?;
+    if !key.compressed {
+        return Err(ApiError::bad_request(format!(
+            "{} must be a compressed key",
+            field
+        )));
+    }
+    Ok(key)
+}
+
+fn parse_address(state: &AppState, field: &str, address: &str) -> Result<Address, ApiError> {
+    Address::from_str(address)
+        .map_err(|e| ApiError::bad_request(format!("Invalid {}: {}", field, e)))?
+        .require_network(state.config.get_network())
+        .map_err(|e| ApiError::bad_request(format!("Invalid {}: {}", field, e)))
+}
@@ -0,0 +1,188 @@
+//! Fee advisory
+//!
+//! Recommends a fee rate per carrier from the node's fee estimates, the
+//! mempool and how long the wallet's recent ANCHOR transactions took to
+//! confirm, so clients can default to something better than a static
+//! sat/vB.
+
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::wallet::fees::{CarrierAdvice, FeeAdvisory, FeeBucket};
+use crate::AppState;
+
+/// Default confirmation target, in blocks
+const DEFAULT_TARGET_BLOCKS: u16 = 6;
+
+/// Fee advisory query parameters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FeeAdvisoryQuery {
+    /// Confirmation target in blocks (default: 6, max: 1008)
+    pub target_blocks: Option<u16>,
+}
+
+/// Mempool transactions paying at least a fee rate
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeeBucketResponse {
+    /// Lower bound of the bucket, in sat/vB
+    pub min_fee_rate: f64,
+    pub tx_count: usize,
+    pub vsize: u64,
+}
+
+/// Mempool state
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MempoolSummary {
+    pub tx_count: usize,
+    pub vsize: u64,
+    pub total_fee_sats: u64,
+    /// Lowest fee rate the node accepts, in sat/vB
+    pub min_fee_rate: f64,
+    /// Transactions per fee rate bucket, highest first
+    pub histogram: Vec<FeeBucketResponse>,
+}
+
+/// Node fee estimate for a confirmation target
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeeEstimateResponse {
+    pub target_blocks: u16,
+    /// Estimated fee rate in sat/vB; null when the node lacks the data
+    pub fee_rate: Option<f64>,
+}
+
+/// Fee rates paid and blocks waited by the wallet's recent transactions
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecentConfirmationsResponse {
+    pub samples: usize,
+    pub median_fee_rate: f64,
+    pub median_blocks: u32,
+}
+
+/// Recommendation for one carrier
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CarrierFeeAdvice {
+    pub carrier: String,
+    /// Transactions a message needs (commit and reveal for witness carriers)
+    pub transactions: u32,
+    /// Recommended fee rate, in sat/vB
+    pub fee_rate: u64,
+    /// Blocks until confirmation expected at that rate
+    pub predicted_blocks: u32,
+    /// Whether slow recent transactions raised the rate above the base rate
+    pub adjusted: bool,
+    pub recent: Option<RecentConfirmationsResponse>,
+}
+
+/// Fee advisory response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeeAdvisoryResponse {
+    pub block_height: u64,
+    pub target_blocks: u16,
+    /// Where the base rate comes from: `estimatesmartfee`, `mempool` or `default`
+    pub basis: String,
+    /// Base fee rate for the target, in sat/vB
+    pub fee_rate: f64,
+    /// Mempool congestion: `low`, `medium` or `high`
+    pub congestion: String,
+    pub mempool: MempoolSummary,
+    pub estimates: Vec<FeeEstimateResponse>,
+    pub carriers: Vec<CarrierFeeAdvice>,
+}
+
+/// Recommend fee rates per carrier for a confirmation target
+#[utoipa::path(
+    get,
+    path = "/wallet/fee-advisory",
+    tag = "Wallet",
+    params(FeeAdvisoryQuery),
+    responses(
+        (status = 200, description = "Fee recommendation per carrier", body = FeeAdvisoryResponse),
+        (status = 400, description = "Invalid target"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_fee_advisory(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FeeAdvisoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let target_blocks = query.target_blocks.unwrap_or(DEFAULT_TARGET_BLOCKS);
+    if !(1..=1008).contains(&target_blocks) {
+        return Err(ApiError::bad_request(
+            "target_blocks must be between 1 and 1008",
+        ));
+    }
+
+    let settings = state.reloader.settings();
+    let mut advisory = state
+        .wallet
+        .fee_advisory(target_blocks, settings.default_fee_rate)
+        .map_err(|e| {
+            error!("Failed to build fee advisory: {}", e);
+            ApiError::internal(e.to_string())
+        })?;
+    // Never recommend what create-message would refuse
+    if let Some(max_fee_rate) = settings.max_fee_rate {
+        for carrier in &mut advisory.carriers {
+            carrier.fee_rate = carrier.fee_rate.min(max_fee_rate);
+        }
+    }
+
+    Ok(Json(advisory_response(advisory)))
+}
+
+fn advisory_response(advisory: FeeAdvisory) -> FeeAdvisoryResponse {
+    FeeAdvisoryResponse {
+        block_height: advisory.block_height,
+        target_blocks: advisory.target_blocks,
+        basis: advisory.basis.to_string(),
+        fee_rate: advisory.fee_rate,
+        congestion: advisory.congestion.to_string(),
+        mempool: MempoolSummary {
+            tx_count: advisory.mempool_tx_count,
+            vsize: advisory.mempool_vsize,
+            total_fee_sats: advisory.mempool_total_fee_sats,
+            min_fee_rate: advisory.mempool_min_fee_rate,
+            histogram: advisory.histogram.into_iter().map(bucket).collect(),
+        },
+        estimates: advisory
+            .estimates
+            .into_iter()
+            .map(|(target_blocks, fee_rate)| FeeEstimateResponse {
+                target_blocks,
+                fee_rate,
+            })
+            .collect(),
+        carriers: advisory.carriers.into_iter().map(carrier).collect(),
+    }
+}
+
+fn bucket(bucket: FeeBucket) -> FeeBucketResponse {
+    FeeBucketResponse {
+        min_fee_rate: bucket.min_fee_rate,
+        tx_count: bucket.tx_count,
+        vsize: bucket.vsize,
+    }
+}
+
+fn carrier(advice: CarrierAdvice) -> CarrierFeeAdvice {
+    CarrierFeeAdvice {
+        carrier: advice.carrier,
+        transactions: advice.transactions,
+        fee_rate: advice.fee_rate,
+        predicted_blocks: advice.predicted_blocks,
+        adjusted: advice.adjusted,
+        recent: advice.recent.map(|recent| RecentConfirmationsResponse {
+            samples: recent.samples,
+            median_fee_rate: recent.median_fee_rate,
+            median_blocks: recent.median_blocks,
+        }),
+    }
+}
@@ -0,0 +1,255 @@
+//! Transaction history with decoded ANCHOR context
+//!
+//! Merges the wallet's transactions with the ANCHOR messages they carry. Token
+//! messages reference tokens by id, so their tickers are looked up on the
+//! tokens backend; if it can't be reached the raw amounts are shown and the
+//! failure is reported in `errors`.
+
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tracing::{error, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use super::SourceError;
+use crate::proxy::http_client_builder;
+use crate::wallet::history::{describe_token, token_id, WalletTransaction};
+use crate::AppState;
+
+/// Transactions searched when filtering by kind
+const HISTORY_WINDOW: usize = 1000;
+
+/// Per-request timeout for app backend queries
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// History query parameters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct HistoryQuery {
+    /// Page number, starting at 1
+    pub page: Option<usize>,
+    /// Entries per page (default: 20, max: 100)
+    pub per_page: Option<usize>,
+    /// Only transactions carrying an ANCHOR message of this kind
+    pub kind: Option<u8>,
+}
+
+/// An ANCHOR message in a history entry
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HistoryMessage {
+    pub vout: u32,
+    pub carrier: String,
+    pub kind: u8,
+    pub kind_name: String,
+    /// Text bodies as text, binary bodies as hex, both truncated
+    pub body_preview: String,
+    /// What the message did, e.g. "registered alice.btc" or "minted 100 TOKEN"
+    pub description: Option<String>,
+    /// App whose indexer tracks the message (domains, tokens, ...)
+    pub app: Option<String>,
+    /// The app's identifier for the affected item: domain name, token ticker, ...
+    pub reference: Option<String>,
+}
+
+/// A wallet transaction with its ANCHOR context
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HistoryEntry {
+    pub txid: String,
+    /// `send`, `receive`, `self`, `generate`, `immature` or `orphan`
+    pub category: String,
+    /// Net effect on the wallet in BTC, excluding the fee
+    pub amount: f64,
+    /// Fee in BTC, for transactions the wallet paid for
+    pub fee: Option<f64>,
+    pub confirmations: i32,
+    pub block_height: Option<u32>,
+    pub time: u64,
+    pub messages: Vec<HistoryMessage>,
+}
+
+/// A page of transaction history
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HistoryResponse {
+    pub entries: Vec<HistoryEntry>,
+    pub page: usize,
+    pub per_page: usize,
+    /// Matching transactions across all pages
+    pub total: usize,
+    /// App backends that could not be queried
+    pub errors: Vec<SourceError>,
+}
+
+/// Token as returned by the tokens backend
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenData {
+    ticker: String,
+    decimals: i16,
+}
+
+/// Get the wallet's transaction history with decoded ANCHOR messages
+#[utoipa::path(
+    get,
+    path = "/wallet/history",
+    tag = "Wallet",
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Transaction history, newest first", body = HistoryResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * per_page;
+
+    let txids = state
+        .wallet
+        .list_history_txids(HISTORY_WINDOW)
+        .map_err(|e| {
+            error!("Failed to list transactions: {}", e);
+            ApiError::internal(e.to_string())
+        })?;
+
+    // Without a filter only the requested page needs decoding
+    let (transactions, total) = match query.kind {
+        None => {
+            let transactions = txids
+                .iter()
+                .skip(offset)
+                .take(per_page)
+                .map(|txid| state.wallet.get_history_entry(txid))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map_err(|e| ApiError::internal(e.to_string()))?;
+            (transactions, txids.len())
+        }
+        Some(kind) => {
+            let mut matching = Vec::new();
+            for txid in &txids {
+                let tx = state
+                    .wallet
+                    .get_history_entry(txid)
+                    .map_err(|e| ApiError::internal(e.to_string()))?;
+                if tx.messages.iter().any(|m| m.kind == kind) {
+                    matching.push(tx);
+                }
+            }
+            let total = matching.len();
+            let page = matching.into_iter().skip(offset).take(per_page).collect();
+            (page, total)
+        }
+    };
+
+    let mut errors = Vec::new();
+    let tokens = match fetch_tokens(&state, &transactions).await {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            warn!("History: tokens backend failed: {}", e);
+            errors.push(SourceError {
+                source: "tokens".to_string(),
+                error: e.to_string(),
+            });
+            HashMap::new()
+        }
+    };
+
+    Ok(Json(HistoryResponse {
+        entries: transactions
+            .into_iter()
+            .map(|tx| history_entry(tx, &tokens))
+            .collect(),
+        page,
+        per_page,
+        total,
+        errors,
+    }))
+}
+
+fn history_entry(tx: WalletTransaction, tokens: &HashMap<u64, TokenData>) -> HistoryEntry {
+    HistoryEntry {
+        txid: tx.txid,
+        category: tx.category,
+        amount: tx.amount,
+        fee: tx.fee,
+        confirmations: tx.confirmations,
+        block_height: tx.block_height,
+        time: tx.time,
+        messages: tx
+            .messages
+            .into_iter()
+            .map(|message| {
+                let mut description = message.description;
+                let mut reference = message.reference;
+                if let Some(operation) = &message.token {
+                    let token = token_id(operation).and_then(|id| tokens.get(&id));
+                    if let Some(token) = token {
+                        description = Some(describe_token(
+                            operation,
+                            Some((&token.ticker, token.decimals as u8)),
+                        ));
+                        reference = Some(token.ticker.clone());
+                    }
+                }
+                HistoryMessage {
+                    vout: message.vout,
+                    carrier: message.carrier,
+                    kind: message.kind,
+                    kind_name: message.kind_name,
+                    body_preview: message.body_preview,
+                    description,
+                    app: message.app.map(str::to_string),
+                    reference,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Look up the tokens referenced by token messages on the page
+async fn fetch_tokens(
+    state: &AppState,
+    transactions: &[WalletTransaction],
+) -> anyhow::Result<HashMap<u64, TokenData>> {
+    let ids: BTreeSet<u64> = transactions
+        .iter()
+        .flat_map(|tx| &tx.messages)
+        .filter_map(|m| m.token.as_ref().and_then(token_id))
+        .collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let client = http_client_builder(state.config.backends_proxy.as_ref())?
+        .timeout(BACKEND_TIMEOUT)
+        .build()?;
+    let mut requests = JoinSet::new();
+    for id in ids {
+        let request = client.get(format!("{}/tokens/by-id/{}", state.config.tokens_url, id));
+        requests.spawn(async move {
+            let response = request.send().await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let token = response.error_for_status()?.json::<TokenData>().await?;
+            Ok::<_, reqwest::Error>(Some((id, token)))
+        });
+    }
+
+    let mut tokens = HashMap::new();
+    while let Some(result) = requests.join_next().await {
+        if let Some((id, token)) = result?? {
+            tokens.insert(id, token);
+        }
+    }
+    Ok(tokens)
+}
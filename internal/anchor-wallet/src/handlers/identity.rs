@@ -2,6 +2,7 @@
 //!
 //! These handlers manage decentralized identities (Nostr, Pubky, etc.)
 
+use anchor_api_error::{ApiError, ErrorCode};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -226,14 +227,14 @@ fn identity_to_response(identity: &Identity) -> IdentityResponse {
     }
 }
 
-fn parse_identity_type(s: &str) -> Result<IdentityType, (StatusCode, String)> {
+fn parse_identity_type(s: &str) -> Result<IdentityType, ApiError> {
     match s.to_lowercase().as_str() {
         "nostr" => Ok(IdentityType::Nostr),
         "pubky" => Ok(IdentityType::Pubky),
-        _ => Err((
-            StatusCode::BAD_REQUEST,
-            format!("Invalid identity type: {}", s),
-        )),
+        _ => Err(ApiError::bad_request(format!(
+            "Invalid identity type: {}",
+            s
+        ))),
     }
 }
 
@@ -277,10 +278,10 @@ pub async fn list_identities(State(state): State<Arc<AppState>>) -> impl IntoRes
 pub async fn get_identity(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.identity_manager.get(&id) {
         Some(identity) => Ok(Json(identity_to_response(&identity))),
-        None => Err((StatusCode::NOT_FOUND, "Identity not found".to_string())),
+        None => Err(ApiError::not_found("Identity not found")),
     }
 }
 
@@ -298,21 +299,17 @@ pub async fn get_identity(
 pub async fn create_identity(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateIdentityRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let identity_type = parse_identity_type(&req.identity_type)?;
 
     // Validate public key
     if req.public_key.len() != 64 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Public key must be 64 hex characters (32 bytes)".to_string(),
+        return Err(ApiError::bad_request(
+            "Public key must be 64 hex characters (32 bytes)",
         ));
     }
     if hex::decode(&req.public_key).is_err() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Invalid hex in public key".to_string(),
-        ));
+        return Err(ApiError::bad_request("Invalid hex in public key"));
     }
 
     // Build metadata
@@ -356,7 +353,7 @@ pub async fn create_identity(
 
     match state.identity_manager.create(identity) {
         Ok(created) => Ok((StatusCode::CREATED, Json(identity_to_response(&created)))),
-        Err(e) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+        Err(e) => Err(ApiError::bad_request(e.to_string())),
     }
 }
 
@@ -378,12 +375,12 @@ pub async fn update_identity(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
     Json(req): Json<UpdateIdentityRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Get current identity to determine type
     let current = state
         .identity_manager
         .get(&id)
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Identity not found".to_string()))?;
+        .ok_or_else(|| ApiError::not_found("Identity not found"))?;
 
     let new_metadata = req.metadata.map(|m| match current.identity_type {
         IdentityType::Nostr => {
@@ -429,7 +426,7 @@ pub async fn update_identity(
 
     match state.identity_manager.update(&id, req.label, new_metadata) {
         Ok(updated) => Ok(Json(identity_to_response(&updated))),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(ApiError::internal(e.to_string())),
     }
 }
 
@@ -449,11 +446,11 @@ pub async fn update_identity(
 pub async fn delete_identity(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.identity_manager.delete(&id) {
         Ok(true) => Ok(StatusCode::NO_CONTENT),
-        Ok(false) => Err((StatusCode::NOT_FOUND, "Identity not found".to_string())),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Ok(false) => Err(ApiError::not_found("Identity not found")),
+        Err(e) => Err(ApiError::internal(e.to_string())),
     }
 }
 
@@ -473,10 +470,10 @@ pub async fn delete_identity(
 pub async fn set_identity_primary(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.identity_manager.set_primary(&id) {
         Ok(updated) => Ok(Json(identity_to_response(&updated))),
-        Err(e) => Err((StatusCode::NOT_FOUND, e.to_string())),
+        Err(e) => Err(ApiError::not_found(e.to_string())),
     }
 }
 
@@ -498,12 +495,12 @@ pub async fn set_identity_dns(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
     Json(req): Json<SetDnsPublishedRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Get identity to build record name
     let identity = state
         .identity_manager
         .get(&id)
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Identity not found".to_string()))?;
+        .ok_or_else(|| ApiError::not_found("Identity not found"))?;
 
     let dns_prefix = identity.identity_type.dns_prefix();
     let record_name = if let Some(ref subdomain) = req.subdomain {
@@ -521,7 +518,7 @@ pub async fn set_identity_dns(
 
     match state.identity_manager.set_dns_published(&id, Some(info)) {
         Ok(updated) => Ok(Json(identity_to_response(&updated))),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => Err(ApiError::internal(e.to_string())),
     }
 }
 
@@ -541,10 +538,10 @@ pub async fn set_identity_dns(
 pub async fn remove_identity_dns(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.identity_manager.set_dns_published(&id, None) {
         Ok(updated) => Ok(Json(identity_to_response(&updated))),
-        Err(e) => Err((StatusCode::NOT_FOUND, e.to_string())),
+        Err(e) => Err(ApiError::not_found(e.to_string())),
     }
 }
 
@@ -589,7 +586,7 @@ pub async fn get_identity_defaults() -> impl IntoResponse {
 )]
 pub async fn generate_keypair(
     Json(req): Json<GenerateKeypairRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let identity_type = parse_identity_type(&req.identity_type)?;
 
     match identity_type {
@@ -652,11 +649,11 @@ pub async fn generate_keypair(
 pub async fn export_private_key(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-) -> Result<Json<ExportKeyResponse>, (StatusCode, String)> {
+) -> Result<Json<ExportKeyResponse>, ApiError> {
     let identity = state
         .identity_manager
         .get(&id)
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Identity not found".to_string()))?;
+        .ok_or_else(|| ApiError::not_found("Identity not found"))?;
 
     // Get the private key (currently stored as hex)
     let private_key_hex = identity.private_key_encrypted.clone();
@@ -711,18 +708,19 @@ pub async fn sign_message(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
     Json(_req): Json<SignMessageRequest>,
-) -> Result<Json<SignMessageResponse>, (StatusCode, String)> {
+) -> Result<Json<SignMessageResponse>, ApiError> {
     let _identity = state
         .identity_manager
         .get(&id)
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Identity not found".to_string()))?;
+        .ok_or_else(|| ApiError::not_found("Identity not found"))?;
 
     // TODO: Decrypt private key and sign message
     // For now, return error
-    Err((
-        StatusCode::NOT_IMPLEMENTED,
-        "Signing requires private key decryption (not yet implemented)".to_string(),
-    ))
+    Err(ApiError::new(
+        ErrorCode::Internal,
+        "Signing requires private key decryption (not yet implemented)",
+    )
+    .with_status(501))
 }
 
 /// Sync identities from DNS records
@@ -740,37 +738,27 @@ pub async fn sign_message(
 )]
 pub async fn sync_identities_from_dns(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let domains_api_url = std::env::var("DOMAINS_API_URL")
         .unwrap_or_else(|_| "http://anchor-app-domains-backend:3401".to_string());
 
-    let client = reqwest::Client::new();
+    let client = &state.http;
 
     // Step 1: Get all domains from anchor-domains
     let domains_response = client
         .get(format!("{}/domains", domains_api_url))
         .send()
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to fetch domains: {}", e),
-            )
-        })?;
+        .map_err(|e| ApiError::internal(format!("Failed to fetch domains: {}", e)))?;
 
     if !domains_response.status().is_success() {
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to fetch domains".to_string(),
-        ));
+        return Err(ApiError::internal("Failed to fetch domains"));
     }
 
-    let domains: DomainsListResponse = domains_response.json().await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to parse domains: {}", e),
-        )
-    })?;
+    let domains: DomainsListResponse = domains_response
+        .json()
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to parse domains: {}", e)))?;
 
     let mut synced_count = 0;
     let mut checked_domains = 0;
@@ -910,38 +898,20 @@ struct PublishedIdentityInfo {
 )]
 pub async fn verify_signature(
     Json(req): Json<VerifySignatureRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Parse inputs
-    let message = hex::decode(&req.message).map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            "Invalid hex in message".to_string(),
-        )
-    })?;
-    let signature = hex::decode(&req.signature).map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            "Invalid hex in signature".to_string(),
-        )
-    })?;
-    let public_key = hex::decode(&req.public_key).map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            "Invalid hex in public key".to_string(),
-        )
-    })?;
+    let message =
+        hex::decode(&req.message).map_err(|_| ApiError::bad_request("Invalid hex in message"))?;
+    let signature = hex::decode(&req.signature)
+        .map_err(|_| ApiError::bad_request("Invalid hex in signature"))?;
+    let public_key = hex::decode(&req.public_key)
+        .map_err(|_| ApiError::bad_request("Invalid hex in public key"))?;
 
     if signature.len() != 64 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Signature must be 64 bytes".to_string(),
-        ));
+        return Err(ApiError::bad_request("Signature must be 64 bytes"));
     }
     if public_key.len() != 32 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Public key must be 32 bytes".to_string(),
-        ));
+        return Err(ApiError::bad_request("Public key must be 32 bytes"));
     }
 
     // Try Ed25519 first (Pubky)
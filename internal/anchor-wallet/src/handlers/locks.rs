@@ -1,9 +1,10 @@
 //! UTXO lock management handlers
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::ApiError;
+use axum::{extract::State, response::IntoResponse, Json};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
 use tracing::{error, info, warn};
 use utoipa::ToSchema;
@@ -136,7 +137,7 @@ pub struct LockedAssetsQuery {
 )]
 pub async fn list_locked_utxos(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let locked = state.lock_manager.list_locked();
     let response: Vec<LockedUtxoResponse> = locked
         .into_iter()
@@ -161,6 +162,11 @@ pub async fn list_locked_utxos(
                     Some(asset_type.clone()),
                     Some(asset_id.clone()),
                 ),
+                LockReason::Scheduled { txid } => (
+                    "scheduled".to_string(),
+                    Some("scheduled_message".to_string()),
+                    Some(txid.clone()),
+                ),
             };
             LockedUtxoResponse {
                 txid: u.txid,
@@ -191,7 +197,7 @@ pub async fn list_locked_utxos(
 pub async fn lock_utxos(
     State(state): State<Arc<AppState>>,
     Json(req): Json<LockRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let mut locked_count = 0;
 
     for utxo in req.utxos {
@@ -203,7 +209,7 @@ pub async fn lock_utxos(
             Ok(false) => {} // Already locked
             Err(e) => {
                 error!("Failed to lock UTXO {}:{}: {}", utxo.txid, utxo.vout, e);
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+                return Err(ApiError::internal(e.to_string()));
             }
         }
     }
@@ -231,7 +237,7 @@ pub async fn lock_utxos(
 pub async fn unlock_utxos(
     State(state): State<Arc<AppState>>,
     Json(req): Json<UnlockRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     let mut unlocked_count = 0;
 
     for utxo in req.utxos {
@@ -240,7 +246,7 @@ pub async fn unlock_utxos(
             Ok(false) => {} // Was not locked
             Err(e) => {
                 error!("Failed to unlock UTXO {}:{}: {}", utxo.txid, utxo.vout, e);
-                return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+                return Err(ApiError::internal(e.to_string()));
             }
         }
     }
@@ -263,9 +269,7 @@ pub async fn unlock_utxos(
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn sync_locks(
-    State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+pub async fn sync_locks(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, ApiError> {
     info!("Starting lock sync with app backends...");
 
     // Get current wallet UTXOs
@@ -273,7 +277,7 @@ pub async fn sync_locks(
         Ok(utxos) => utxos,
         Err(e) => {
             error!("Failed to list wallet UTXOs: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -294,7 +298,7 @@ pub async fn sync_locks(
         let params = format!("owner_txids={}", utxo_txids.join(","));
         let full_url = format!("{}?{}", domains_url, params);
 
-        match reqwest::get(&full_url).await {
+        match state.http.get(&full_url).send().await {
             Ok(resp) if resp.status().is_success() => {
                 if let Ok(data) = resp.json::<serde_json::Value>().await {
                     if let Some(domains) = data.get("data").and_then(|d| d.as_array()) {
@@ -328,12 +332,15 @@ pub async fn sync_locks(
         }
     }
 
-    // Query Anchor Tokens backend for user's token UTXOs
+    // Query Anchor Tokens backend for user's token UTXOs, per address holding wallet UTXOs
     let tokens_url = format!("{}/wallet/utxos", state.config.tokens_url);
-    // For each wallet address, query token UTXOs
-    if let Ok(addr) = state.wallet.get_new_address() {
+    let utxo_addresses: BTreeSet<&str> = wallet_utxos
+        .iter()
+        .filter_map(|u| u.address.as_deref())
+        .collect();
+    for addr in utxo_addresses {
         let full_url = format!("{}?address={}", tokens_url, addr);
-        match reqwest::get(&full_url).await {
+        match state.http.get(&full_url).send().await {
             Ok(resp) if resp.status().is_success() => {
                 if let Ok(data) = resp.json::<serde_json::Value>().await {
                     if let Some(utxos) = data.as_array() {
@@ -374,7 +381,7 @@ pub async fn sync_locks(
         Ok(count) => count,
         Err(e) => {
             error!("Failed to bulk lock: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -383,7 +390,7 @@ pub async fn sync_locks(
         Ok(count) => count,
         Err(e) => {
             error!("Failed to prune stale locks: {}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+            return Err(ApiError::internal(e.to_string()));
         }
     };
 
@@ -418,7 +425,7 @@ pub async fn sync_locks(
 )]
 pub async fn get_lock_settings(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     Ok(Json(LockSettingsResponse {
         auto_lock_enabled: state.lock_manager.is_auto_lock_enabled(),
         total_locked: state.lock_manager.list_locked().len(),
@@ -440,7 +447,7 @@ pub async fn get_lock_settings(
 pub async fn set_auto_lock(
     State(state): State<Arc<AppState>>,
     Json(req): Json<SetAutoLockRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.lock_manager.set_auto_lock(req.enabled) {
         Ok(()) => {
             info!("Auto-lock set to: {}", req.enabled);
@@ -452,7 +459,7 @@ pub async fn set_auto_lock(
         }
         Err(e) => {
             error!("Failed to set auto-lock: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -473,7 +480,7 @@ pub async fn set_auto_lock(
 pub async fn get_locked_assets(
     State(state): State<Arc<AppState>>,
     axum::extract::Query(query): axum::extract::Query<LockedAssetsQuery>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     // Get all locked UTXOs
     let locked = state.lock_manager.list_locked();
 
@@ -525,6 +532,11 @@ pub async fn get_locked_assets(
                 manual_sats += amount_sats;
                 (asset_type.clone(), Some(asset_id.clone()))
             }
+            LockReason::Scheduled { txid } => {
+                manual_count += 1;
+                manual_sats += amount_sats;
+                ("scheduled".to_string(), Some(txid.clone()))
+            }
         };
 
         // Apply filter
@@ -549,7 +561,7 @@ pub async fn get_locked_assets(
     }
 
     // Sort by locked_at descending (newest first)
-    items.sort_by(|a, b| b.locked_at.cmp(&a.locked_at));
+    items.sort_by_key(|item| std::cmp::Reverse(item.locked_at));
 
     let summary = LockedAssetsSummary {
         domains: CategorySummary {
@@ -1,14 +1,29 @@
 //! ANCHOR message creation handler
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::{ApiError, ErrorCode};
+use anchor_core::carrier::{CarrierError, CarrierType};
+use anchor_specs::{carrier_support, CarrierSupport};
+use axum::{extract::State, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info, warn};
 use utoipa::ToSchema;
 
 use crate::locked::LockReason;
+use crate::proxy::http_client_builder;
+use crate::templates::{ChangePolicy, FeePriority, TransactionTemplate};
+use crate::wallet::payjoin::{request_proposal, PayjoinUri};
+use crate::wallet::{
+    CoinControl, CreatedTransaction, MessageSpec, RemoteSigner, UnsignedTransaction,
+};
 use crate::AppState;
 
+/// How long a payjoin receiver has to answer before the original is broadcast
+const PAYJOIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Anchor reference for additional message references
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct AnchorRef {
@@ -30,11 +45,18 @@ pub struct OutputSpec {
 /// Request body for creating an ANCHOR message
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateMessageRequest {
-    /// Message kind (0=generic, 1=text, etc.)
+    /// Message kind (0=generic, 1=text, etc.); set from `spec` when given
     #[serde(default = "default_kind")]
     pub kind: u8,
-    /// Message body (text for kind=1, or hex-encoded binary)
+    /// Message body (text for kind=1, or hex-encoded binary); omit when
+    /// `spec` is given
+    #[serde(default)]
     pub body: String,
+    /// Structured payload for a spec'd kind, keyed by kind name: `text`,
+    /// `state`, `geo_marker`, `dns`, `proof` or `token`. It is validated and
+    /// encoded server-side and sets `kind` and the body.
+    #[schema(value_type = Option<Object>)]
+    pub spec: Option<MessageSpec>,
     /// Whether body is hex-encoded (default: false, treated as UTF-8 text)
     #[serde(default)]
     pub body_is_hex: bool,
@@ -46,11 +68,11 @@ pub struct CreateMessageRequest {
     #[serde(default)]
     pub additional_anchors: Vec<AnchorRef>,
     /// Carrier type (0=op_return, 1=inscription, 2=stamps, 3=annex, 4=witness)
-    /// Default: 0 (OP_RETURN)
+    /// Must be supported by the kind. Default: the configured default carrier
+    /// if the kind supports it, else the kind's recommended carrier
     pub carrier: Option<u8>,
-    /// Fee rate in sat/vbyte (default: 1)
-    #[serde(default = "default_fee_rate")]
-    pub fee_rate: u64,
+    /// Fee rate in sat/vbyte (default: the configured default fee rate)
+    pub fee_rate: Option<u64>,
     /// Required inputs - UTXOs that MUST be spent as inputs (for token transfers)
     #[serde(default)]
     pub required_inputs: Vec<AnchorRef>,
@@ -73,10 +95,21 @@ pub struct CreateMessageRequest {
     pub lock_for_token: bool,
     /// Token ticker for token operations (used with lock_for_token)
     pub token_ticker: Option<String>,
-}
-
-fn default_fee_rate() -> u64 {
-    50 // 50 sat/vbyte - higher for regtest compatibility
+    /// BIP-21 URI with a `pj=` endpoint: pay this recipient with a payjoin
+    /// (BIP-78), falling back to a normal transaction if it fails.
+    /// OP_RETURN carrier only.
+    pub payjoin: Option<String>,
+    /// Coin control: fund the transaction only from these UTXOs ("txid:vout")
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Coin control: UTXOs ("txid:vout") that must not be spent
+    #[serde(default)]
+    pub exclude_inputs: Vec<String>,
+    /// Send change to this address instead of a fresh wallet address
+    pub change_address: Option<String>,
+    /// Name of a transaction template supplying the carrier, fee rate and
+    /// change address this request leaves unset
+    pub template: Option<String>,
 }
 
 fn default_kind() -> u8 {
@@ -91,32 +124,131 @@ pub struct CreateMessageResponse {
     pub hex: String,
     pub carrier: u8,
     pub carrier_name: String,
+    /// Unsigned PSBT (base64) from a watch-only wallet; nothing was broadcast
+    /// and `txid` is the id the transaction will have once signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub psbt: Option<String>,
+    /// Whether a requested payjoin went through; `false` means the original
+    /// transaction was broadcast instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payjoin: Option<bool>,
 }
 
 /// Create and broadcast an ANCHOR message
+///
+/// A watch-only wallet funds the message as an OP_RETURN transaction and
+/// returns it as a PSBT instead; sign it and submit it to `/wallet/broadcast`.
+/// In the remote-signer profile the signing daemon signs the PSBT and the
+/// message is broadcast as usual.
 #[utoipa::path(
     post,
     path = "/wallet/create-message",
     tag = "ANCHOR",
     request_body = CreateMessageRequest,
     responses(
-        (status = 200, description = "Message created and broadcast", body = CreateMessageResponse),
+        (status = 200, description = "Message created and broadcast, or funded as a PSBT by a watch-only wallet", body = CreateMessageResponse),
         (status = 400, description = "Invalid request"),
+        (status = 422, description = "Carrier not supported by the kind (UNSUPPORTED_CARRIER) or too small for the payload"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn create_message(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<CreateMessageRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    // Parse body
-    let body = if req.body_is_hex {
+    Json(mut req): Json<CreateMessageRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Parse body, encoding a structured spec server-side
+    let body = if let Some(spec) = &req.spec {
+        if !req.body.is_empty() {
+            return Err(ApiError::bad_request(
+                "Provide either body or spec, not both",
+            ));
+        }
+        let body = spec
+            .encode()
+            .map_err(|e| ApiError::bad_request(format!("Invalid spec: {}", e)))?;
+        req.kind = spec.kind();
+        if req.domain_name.is_none() {
+            req.domain_name = spec.domain_name().map(str::to_string);
+        }
+        if req.token_ticker.is_none() {
+            req.token_ticker = spec.token_ticker().map(str::to_string);
+        }
+        body
+    } else if req.body_is_hex {
         hex::decode(&req.body)
-            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid hex body: {}", e)))?
+            .map_err(|e| ApiError::bad_request(format!("Invalid hex body: {}", e)))?
     } else {
         req.body.as_bytes().to_vec()
     };
 
+    let template = match &req.template {
+        Some(name) => Some(
+            state
+                .templates
+                .get(name)
+                .ok_or_else(|| ApiError::bad_request(format!("Unknown template '{}'", name)))?,
+        ),
+        None => None,
+    };
+
+    // Apply the template, then runtime defaults, for carrier and fee rate
+    let settings = state.reloader.settings();
+    let watch_only = state.wallet.is_watch_only();
+    let default_carrier = template
+        .as_ref()
+        .and_then(|t| t.carrier)
+        .or(settings.default_carrier);
+    let carrier = select_carrier(req.kind, req.carrier, default_carrier, watch_only)?;
+    let fee_rate = match (req.fee_rate, &template) {
+        (Some(fee_rate), _) => fee_rate,
+        (None, Some(template)) => {
+            template_fee_rate(&state, template, carrier, settings.default_fee_rate)
+        }
+        (None, None) => settings.default_fee_rate,
+    };
+    if let Some(max_fee_rate) = settings.max_fee_rate.filter(|max| fee_rate > *max) {
+        return Err(ApiError::bad_request(format!(
+            "Fee rate {} sat/vB exceeds the configured maximum of {}",
+            fee_rate, max_fee_rate
+        )));
+    }
+    if let Some(ChangePolicy::Address { address }) = template.as_ref().map(|t| &t.change) {
+        if req.change_address.is_none() {
+            req.change_address = Some(address.clone());
+        }
+    }
+    if let Some(carrier) = carrier.filter(|c| watch_only && *c != 0) {
+        return Err(ApiError::bad_request(format!(
+            "Carrier {} is not available to a watch-only wallet; use OP_RETURN (0)",
+            carrier
+        )));
+    }
+
+    let payjoin = match &req.payjoin {
+        Some(uri) => {
+            if watch_only {
+                return Err(ApiError::bad_request(
+                    "Payjoin is not available to a watch-only wallet",
+                ));
+            }
+            if carrier.is_some_and(|c| c != 0) {
+                return Err(ApiError::bad_request(
+                    "Payjoin requires the OP_RETURN carrier (0)",
+                ));
+            }
+            if req.unlock_for_dns || req.lock_for_dns || req.lock_for_token {
+                return Err(ApiError::bad_request(
+                    "Payjoin cannot be combined with UTXO locks",
+                ));
+            }
+            Some(
+                PayjoinUri::parse(uri, &state.config.network)
+                    .map_err(|e| ApiError::bad_request(format!("{:#}", e)))?,
+            )
+        }
+        None => None,
+    };
+
     // Convert additional anchors
     let additional_anchors: Vec<(String, u8)> = req
         .additional_anchors
@@ -131,12 +263,15 @@ pub async fn create_message(
         .map(|a| (a.txid.clone(), a.vout as u32))
         .collect();
 
-    // Convert custom outputs
-    let custom_outputs: Vec<(String, u64)> = req
+    // Convert custom outputs; a payjoin recipient is paid alongside them
+    let mut custom_outputs: Vec<(String, u64)> = req
         .outputs
         .into_iter()
         .map(|o| (o.address, o.value))
         .collect();
+    if let Some(uri) = &payjoin {
+        custom_outputs.push((uri.address.clone(), uri.amount));
+    }
 
     // Track DNS unlock info for lock transfer after successful TX
     let dns_unlock_info: Option<(String, String, u32)> = if req.unlock_for_dns {
@@ -164,39 +299,87 @@ pub async fn create_message(
         req.kind,
         body.len(),
         req.parent_txid,
-        req.carrier,
-        req.fee_rate,
+        carrier,
+        fee_rate,
         required_inputs.len(),
         custom_outputs.len(),
         req.unlock_for_dns
     );
 
     // Get locked set but exclude DNS UTXOs if unlocking for DNS
-    let locked_set = if req.unlock_for_dns {
+    let mut locked_set = state.lock_manager.get_locked_set();
+    if req.unlock_for_dns {
         // For DNS updates, exclude the domain UTXO from the locked set
         // so it can be spent as a required input
-        let mut set = state.lock_manager.get_locked_set();
         for input in &required_inputs {
-            set.remove(&(input.0.clone(), input.1));
+            locked_set.remove(&(input.0.clone(), input.1));
+        }
+    }
+    let coins = coin_control(
+        &state,
+        locked_set,
+        &req.inputs,
+        &req.exclude_inputs,
+        req.change_address,
+    )?;
+
+    let created = if watch_only {
+        let funded = state.wallet.create_anchor_psbt(
+            req.kind,
+            body,
+            req.parent_txid,
+            req.parent_vout,
+            additional_anchors,
+            fee_rate,
+            required_inputs,
+            custom_outputs,
+            Some(&coins),
+        );
+        match (funded, &state.signer) {
+            (Ok(unsigned), Some(signer)) => sign_remotely(&state, signer, unsigned)
+                .await
+                .map(|tx| (tx, None, None)),
+            (funded, _) => funded.map(|unsigned| (unsigned.tx, Some(unsigned.psbt), None)),
+        }
+    } else if let Some(uri) = &payjoin {
+        let outputs = custom_outputs.clone();
+        let funded = state.wallet.create_anchor_psbt(
+            req.kind,
+            body,
+            req.parent_txid,
+            req.parent_vout,
+            additional_anchors,
+            fee_rate,
+            required_inputs,
+            custom_outputs,
+            Some(&coins),
+        );
+        match funded {
+            Ok(unsigned) => send_payjoin(&state, uri, &unsigned.psbt, &outputs, fee_rate)
+                .await
+                .map(|(tx, joined)| (tx, None, Some(joined))),
+            Err(e) => Err(e),
         }
-        Some(set)
     } else {
-        Some(state.lock_manager.get_locked_set())
+        state
+            .wallet
+            .create_anchor_transaction_advanced_with_locks(
+                req.kind,
+                body,
+                req.parent_txid,
+                req.parent_vout,
+                additional_anchors,
+                carrier,
+                fee_rate,
+                required_inputs,
+                custom_outputs,
+                Some(&coins),
+            )
+            .map(|tx| (tx, None, None))
     };
 
-    match state.wallet.create_anchor_transaction_advanced_with_locks(
-        req.kind,
-        body,
-        req.parent_txid,
-        req.parent_vout,
-        additional_anchors,
-        req.carrier,
-        req.fee_rate,
-        required_inputs,
-        custom_outputs,
-        locked_set.as_ref(),
-    ) {
-        Ok(result) => {
+    match created {
+        Ok((result, psbt, payjoin)) => {
             info!(
                 "Created transaction: {} with carrier {}",
                 result.txid, result.carrier_name
@@ -303,11 +486,226 @@ pub async fn create_message(
                 hex: result.hex,
                 carrier: result.carrier,
                 carrier_name: result.carrier_name,
+                psbt,
+                payjoin,
             }))
         }
         Err(e) => {
-            error!("Failed to create message: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            error!("Failed to create message: {:#}", e);
+            Err(create_error(&e))
+        }
+    }
+}
+
+/// Coin selection constraints from the lock manager and the request
+fn coin_control(
+    state: &AppState,
+    locked: HashSet<(String, u32)>,
+    inputs: &[String],
+    exclude_inputs: &[String],
+    change_address: Option<String>,
+) -> Result<CoinControl, ApiError> {
+    let inputs = parse_outpoints("inputs", inputs)?;
+    let excluded = parse_outpoints("exclude_inputs", exclude_inputs)?;
+    if let Some((txid, vout)) = inputs.iter().find(|o| excluded.contains(o)) {
+        return Err(ApiError::bad_request(format!(
+            "{}:{} is both in inputs and exclude_inputs",
+            txid, vout
+        )));
+    }
+    // Locks protect assets; selecting a locked UTXO has to go through unlocking it
+    if let Some((txid, vout)) = inputs.iter().find(|o| locked.contains(o)) {
+        return Err(ApiError::bad_request(format!(
+            "{}:{} is locked; unlock it before selecting it as an input",
+            txid, vout
+        )));
+    }
+    if let Some(address) = &change_address {
+        bitcoin::Address::from_str(address)
+            .map_err(|e| ApiError::bad_request(format!("Invalid change_address: {}", e)))?
+            .require_network(state.config.get_network())
+            .map_err(|e| ApiError::bad_request(format!("Invalid change_address: {}", e)))?;
+    }
+
+    let mut coins = CoinControl::locked(locked);
+    coins.locked.extend(excluded);
+    if !inputs.is_empty() {
+        coins.only = Some(inputs.into_iter().collect());
+    }
+    coins.change_address = change_address;
+    Ok(coins)
+}
+
+/// Parse `txid:vout` outpoints from a coin control field
+fn parse_outpoints(field: &str, values: &[String]) -> Result<HashSet<(String, u32)>, ApiError> {
+    values
+        .iter()
+        .map(|value| {
+            let invalid = || {
+                ApiError::bad_request(format!(
+                    "Invalid {} entry '{}': expected txid:vout",
+                    field, value
+                ))
+            };
+            let (txid, vout) = value.split_once(':').ok_or_else(invalid)?;
+            let txid = bitcoin::Txid::from_str(txid).map_err(|_| invalid())?;
+            let vout = vout.parse::<u32>().map_err(|_| invalid())?;
+            Ok((txid.to_string(), vout))
+        })
+        .collect()
+}
+
+/// Sign a funded PSBT with the remote signer, then finalize and broadcast it
+async fn sign_remotely(
+    state: &AppState,
+    signer: &RemoteSigner,
+    unsigned: UnsignedTransaction,
+) -> anyhow::Result<CreatedTransaction> {
+    let signed = signer.sign_psbt(&unsigned.psbt).await?;
+    let hex = state.wallet.finalize_psbt(&signed)?;
+    let txid = state.wallet.broadcast(&hex)?;
+    Ok(CreatedTransaction {
+        txid,
+        hex,
+        ..unsigned.tx
+    })
+}
+
+/// Sign the funded message and offer it to the payjoin receiver
+///
+/// Returns the broadcast transaction and whether it is the payjoin; when the
+/// negotiation fails the signed original is broadcast instead.
+async fn send_payjoin(
+    state: &AppState,
+    uri: &PayjoinUri,
+    unsigned_psbt: &str,
+    outputs: &[(String, u64)],
+    fee_rate: u64,
+) -> anyhow::Result<(CreatedTransaction, bool)> {
+    let original = state
+        .wallet
+        .create_payjoin_original(unsigned_psbt, uri, outputs, fee_rate)?;
+
+    let client = http_client_builder(state.config.payjoin_proxy.as_ref())
+        .and_then(|b| Ok(b.timeout(PAYJOIN_TIMEOUT).build()?));
+    let proposal = match client {
+        Ok(client) => request_proposal(&client, uri, &original).await,
+        Err(e) => Err(e),
+    };
+
+    match proposal.and_then(|p| state.wallet.complete_payjoin(&original, p)) {
+        Ok(tx) => Ok((tx, true)),
+        Err(e) => {
+            warn!(
+                "Payjoin with {} failed, broadcasting original {}: {:#}",
+                uri.endpoint, original.tx.txid, e
+            );
+            state
+                .wallet
+                .broadcast_payjoin_original(&original)
+                .map(|tx| (tx, false))
+        }
+    }
+}
+
+/// Pick the carrier for a message kind
+///
+/// A requested carrier must be one the kind's spec supports. Without one, the
+/// configured default (OP_RETURN for a watch-only wallet) is used when the
+/// kind supports it, else the kind's recommended carrier. Kinds without a spec
+/// accept any carrier.
+/// Fee rate of a template's priority for the selected carrier
+///
+/// Priorities are resolved with the fee advisory; if it fails the
+/// configured default applies.
+fn template_fee_rate(
+    state: &AppState,
+    template: &TransactionTemplate,
+    carrier: Option<u8>,
+    default_fee_rate: u64,
+) -> u64 {
+    let Some(target_blocks) = template.fee.target_blocks() else {
+        // Only fixed rates have no target
+        return match template.fee {
+            FeePriority::Fixed { fee_rate } => fee_rate,
+            _ => default_fee_rate,
+        };
+    };
+    let carrier = carrier
+        .and_then(CarrierType::from_u8)
+        .unwrap_or(CarrierType::OpReturn)
+        .to_string();
+    match state.wallet.fee_advisory(target_blocks, default_fee_rate) {
+        Ok(advisory) => advisory
+            .carriers
+            .iter()
+            .find(|advice| advice.carrier == carrier)
+            .map_or(advisory.fee_rate.ceil() as u64, |advice| advice.fee_rate),
+        Err(e) => {
+            warn!(
+                "Fee advisory failed for template '{}', using the default fee rate: {:#}",
+                template.name, e
+            );
+            default_fee_rate
         }
     }
 }
+
+fn select_carrier(
+    kind: u8,
+    requested: Option<u8>,
+    default: Option<u8>,
+    watch_only: bool,
+) -> Result<Option<u8>, ApiError> {
+    let Some(support) = carrier_support(kind) else {
+        return Ok(requested.or(default));
+    };
+
+    if let Some(carrier) = requested {
+        return match CarrierType::from_u8(carrier) {
+            Some(carrier_type) if !support.allows(carrier_type) => {
+                Err(unsupported_carrier(kind, carrier_type, &support))
+            }
+            _ => Ok(Some(carrier)),
+        };
+    }
+
+    let preferred = if watch_only { Some(0) } else { default };
+    let carrier = preferred
+        .and_then(CarrierType::from_u8)
+        .filter(|c| support.allows(*c))
+        .unwrap_or(support.recommended);
+    Ok(Some(carrier as u8))
+}
+
+fn unsupported_carrier(kind: u8, carrier: CarrierType, support: &CarrierSupport) -> ApiError {
+    let names: Vec<String> = support.supported.iter().map(|c| c.to_string()).collect();
+    ApiError::new(
+        ErrorCode::UnsupportedCarrier,
+        format!(
+            "Kind {} cannot use carrier {} ({}); supported: {}",
+            kind,
+            carrier as u8,
+            carrier,
+            names.join(", ")
+        ),
+    )
+    .with_details(serde_json::json!({
+        "kind": kind,
+        "carrier": carrier as u8,
+        "supported": support.supported.iter().map(|c| *c as u8).collect::<Vec<_>>(),
+        "recommended": support.recommended as u8,
+    }))
+}
+
+/// Map a message creation failure to its API error code
+fn create_error(e: &anyhow::Error) -> ApiError {
+    let carrier = e
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<CarrierError>());
+    if let Some(CarrierError::PayloadTooLarge { size, limit }) = carrier {
+        return ApiError::new(ErrorCode::CarrierTooSmall, format!("{:#}", e))
+            .with_details(serde_json::json!({ "size": size, "limit": limit }));
+    }
+    ApiError::from_message(ErrorCode::Internal, format!("{:#}", e))
+}
@@ -2,29 +2,56 @@
 //!
 //! This module is organized into submodules by functionality:
 //! - `health` - System health endpoints
+//! - `config` - Runtime configuration and reload
 //! - `wallet` - Basic wallet operations (balance, address, UTXOs)
+//! - `history` - Transaction history with decoded ANCHOR messages
+//! - `fees` - Fee advisory per carrier
 //! - `message` - ANCHOR message creation
-//! - `transaction` - Transaction operations (broadcast, mine, rawtx)
+//! - `schedule` - Timelocked messages broadcast later
+//! - `templates` - Named create-message presets
+//! - `escrow` - 2-of-3 escrows for peer-to-peer trades
+//! - `transaction` - Transaction operations (broadcast, package broadcast, mine, rawtx)
+//! - `regtest` - Chain controls for integration tests (invalidate, mock time, reorg)
 //! - `locks` - UTXO lock management
 //! - `assets` - Asset aggregation and browsing
+//! - `portfolio` - Cross-app portfolio for an address set
 //! - `backup` - Wallet backup, mnemonic, and recovery
 //! - `identity` - Decentralized identity management (Nostr, Pubky)
+//! - `vault` - Unlocking and locking the encrypted wallet data
 
 mod assets;
 mod backup;
+mod config;
+mod escrow;
+mod fees;
 mod health;
+mod history;
 mod identity;
 mod locks;
 mod message;
+mod portfolio;
+mod regtest;
+mod schedule;
+mod templates;
 mod transaction;
+mod vault;
 mod wallet;
 
 // Re-export all handlers
 pub use assets::*;
 pub use backup::*;
+pub use config::*;
+pub use escrow::*;
+pub use fees::*;
 pub use health::*;
+pub use history::*;
 pub use identity::*;
 pub use locks::*;
 pub use message::*;
+pub use portfolio::*;
+pub use regtest::*;
+pub use schedule::*;
+pub use templates::*;
 pub use transaction::*;
+pub use vault::*;
 pub use wallet::*;
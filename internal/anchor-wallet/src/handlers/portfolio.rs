@@ -0,0 +1,410 @@
+//! Portfolio aggregation across all app backends
+//!
+//! Collects, for a set of addresses (the wallet's own by default), the domains,
+//! token balances, prediction positions, oracle registrations, proofs, and
+//! pixels they hold. Backends are queried concurrently; a failing backend is
+//! reported in `errors` instead of failing the whole request.
+
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tracing::{error, info, warn};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::proxy::http_client_builder;
+use crate::AppState;
+
+/// Per-request timeout for app backend queries
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Max items requested from paginated backends
+const MAX_ITEMS: i32 = 500;
+
+/// Portfolio query parameters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PortfolioQuery {
+    /// Comma-separated addresses (defaults to every wallet address)
+    pub addresses: Option<String>,
+    /// Bypass the cache
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Token balance summed across the address set
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TokenHolding {
+    pub ticker: String,
+    pub decimals: i16,
+    pub balance: String,
+    pub utxo_count: i32,
+}
+
+/// Item counts per asset type
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct PortfolioSummary {
+    pub domains: usize,
+    pub token_types: usize,
+    pub positions: usize,
+    pub oracles: usize,
+    pub proofs: i64,
+    pub pixels: i64,
+}
+
+/// A backend that could not be queried
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SourceError {
+    pub source: String,
+    pub error: String,
+}
+
+/// Consolidated view of everything an address set holds
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Portfolio {
+    pub addresses: Vec<String>,
+    pub summary: PortfolioSummary,
+    #[schema(value_type = Vec<Object>)]
+    pub domains: Vec<Value>,
+    pub tokens: Vec<TokenHolding>,
+    #[schema(value_type = Vec<Object>)]
+    pub positions: Vec<Value>,
+    #[schema(value_type = Vec<Object>)]
+    pub oracles: Vec<Value>,
+    #[schema(value_type = Vec<Object>)]
+    pub proofs: Vec<Value>,
+    #[schema(value_type = Vec<Object>)]
+    pub pixels: Vec<Value>,
+    pub errors: Vec<SourceError>,
+    pub generated_at: String,
+    /// Whether this response was served from the cache
+    pub cached: bool,
+}
+
+/// Complete portfolios keyed by address set, reused for `ttl`
+pub struct PortfolioCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Portfolio)>>,
+}
+
+impl PortfolioCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Portfolio> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(key)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, portfolio)| portfolio.clone())
+    }
+
+    fn insert(&self, key: String, portfolio: Portfolio) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+            entries.insert(key, (Instant::now(), portfolio));
+        }
+    }
+}
+
+/// Token balance as returned by the tokens backend
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenBalanceData {
+    ticker: String,
+    decimals: i16,
+    balance: String,
+    utxo_count: i32,
+}
+
+/// Get the portfolio of the wallet (or an explicit address set)
+#[utoipa::path(
+    get,
+    path = "/wallet/portfolio",
+    tag = "Assets",
+    params(PortfolioQuery),
+    responses(
+        (status = 200, description = "Assets held across all apps", body = Portfolio),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_portfolio(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PortfolioQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let explicit = query.addresses.is_some();
+    let mut addresses: Vec<String> = match query.addresses {
+        Some(list) => list
+            .split(',')
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty())
+            .collect(),
+        None => state.wallet.list_received_addresses().map_err(|e| {
+            error!("Failed to list addresses: {}", e);
+            ApiError::internal(e.to_string())
+        })?,
+    };
+    addresses.sort();
+    addresses.dedup();
+
+    let cache_key = addresses.join(",");
+    if !query.refresh {
+        if let Some(mut portfolio) = state.portfolio_cache.get(&cache_key) {
+            portfolio.cached = true;
+            return Ok(Json(portfolio));
+        }
+    }
+
+    // Domains are owned by UTXOs; predictions are keyed by pubkey
+    let address_set: HashSet<&String> = addresses.iter().collect();
+    let owner_txids: Vec<String> = state
+        .wallet
+        .list_utxos()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|u| {
+            !explicit
+                || u.address
+                    .as_ref()
+                    .is_some_and(|address| address_set.contains(address))
+        })
+        .map(|u| u.txid)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let pubkeys = state.wallet.address_pubkeys(&addresses).unwrap_or_default();
+
+    let client = http_client_builder(state.config.backends_proxy.as_ref())
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .timeout(BACKEND_TIMEOUT)
+        .build()
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+    let config = &state.config;
+
+    let (domains, tokens, positions, oracles, proofs, pixels) = tokio::join!(
+        fetch_domains(&client, &config.domains_url, &owner_txids),
+        fetch_tokens(&client, &config.tokens_url, &addresses),
+        fetch_positions(&client, &config.predictions_url, &pubkeys),
+        fetch_oracles(&client, &config.oracles_url, &addresses),
+        fetch_proofs(&client, &config.proofs_url, &addresses),
+        fetch_pixels(&client, &config.canvas_url, &addresses),
+    );
+
+    let mut errors = Vec::new();
+    let domains = or_report("domains", domains, &mut errors);
+    let tokens = or_report("tokens", tokens, &mut errors);
+    let positions = or_report("predictions", positions, &mut errors);
+    let oracles = or_report("oracles", oracles, &mut errors);
+    let (proofs, total_proofs) = or_report("proofs", proofs, &mut errors);
+    let (pixels, total_pixels) = or_report("canvas", pixels, &mut errors);
+
+    let portfolio = Portfolio {
+        summary: PortfolioSummary {
+            domains: domains.len(),
+            token_types: tokens.len(),
+            positions: positions.len(),
+            oracles: oracles.len(),
+            proofs: total_proofs,
+            pixels: total_pixels,
+        },
+        addresses,
+        domains,
+        tokens,
+        positions,
+        oracles,
+        proofs,
+        pixels,
+        errors,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        cached: false,
+    };
+
+    info!(
+        "Portfolio for {} addresses: {:?} ({} backend errors)",
+        portfolio.addresses.len(),
+        portfolio.summary,
+        portfolio.errors.len()
+    );
+
+    // Only complete views are cached so a backend outage is not remembered
+    if portfolio.errors.is_empty() {
+        state.portfolio_cache.insert(cache_key, portfolio.clone());
+    }
+
+    Ok(Json(portfolio))
+}
+
+/// Unwrap a backend result, recording the failure and falling back to empty
+fn or_report<T: Default>(
+    source: &str,
+    result: anyhow::Result<T>,
+    errors: &mut Vec<SourceError>,
+) -> T {
+    result.unwrap_or_else(|e| {
+        warn!("Portfolio: {} backend failed: {}", source, e);
+        errors.push(SourceError {
+            source: source.to_string(),
+            error: e.to_string(),
+        });
+        T::default()
+    })
+}
+
+async fn fetch_domains(
+    client: &reqwest::Client,
+    base_url: &str,
+    owner_txids: &[String],
+) -> anyhow::Result<Vec<Value>> {
+    if owner_txids.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(client
+        .post(format!("{}/domains/by-owner", base_url))
+        .json(&serde_json::json!({ "txids": owner_txids }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn fetch_tokens(
+    client: &reqwest::Client,
+    base_url: &str,
+    addresses: &[String],
+) -> anyhow::Result<Vec<TokenHolding>> {
+    let mut requests = JoinSet::new();
+    for address in addresses {
+        let request = client.get(format!("{}/address/{}/balances", base_url, address));
+        requests.spawn(async move {
+            request
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Vec<TokenBalanceData>>()
+                .await
+        });
+    }
+
+    let mut totals: BTreeMap<String, (i16, u128, i32)> = BTreeMap::new();
+    while let Some(result) = requests.join_next().await {
+        for balance in result?? {
+            let entry = totals
+                .entry(balance.ticker)
+                .or_insert((balance.decimals, 0, 0));
+            entry.1 += balance.balance.parse::<u128>().unwrap_or(0);
+            entry.2 += balance.utxo_count;
+        }
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|(ticker, (decimals, balance, utxo_count))| TokenHolding {
+            ticker,
+            decimals,
+            balance: balance.to_string(),
+            utxo_count,
+        })
+        .collect())
+}
+
+async fn fetch_positions(
+    client: &reqwest::Client,
+    base_url: &str,
+    pubkeys: &[String],
+) -> anyhow::Result<Vec<Value>> {
+    let mut requests = JoinSet::new();
+    for pubkey in pubkeys {
+        let request = client.get(format!(
+            "{}/api/my/positions?pubkey={}&limit={}",
+            base_url, pubkey, MAX_ITEMS
+        ));
+        requests.spawn(async move {
+            request
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Vec<Value>>()
+                .await
+        });
+    }
+
+    let mut positions = Vec::new();
+    while let Some(result) = requests.join_next().await {
+        positions.extend(result??);
+    }
+    Ok(positions)
+}
+
+async fn fetch_oracles(
+    client: &reqwest::Client,
+    base_url: &str,
+    addresses: &[String],
+) -> anyhow::Result<Vec<Value>> {
+    if addresses.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(client
+        .post(format!("{}/api/oracles/by-addresses", base_url))
+        .json(&serde_json::json!({ "addresses": addresses }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn fetch_proofs(
+    client: &reqwest::Client,
+    base_url: &str,
+    addresses: &[String],
+) -> anyhow::Result<(Vec<Value>, i64)> {
+    if addresses.is_empty() {
+        return Ok((vec![], 0));
+    }
+    let res: Value = client
+        .post(format!("{}/api/proofs/by-addresses", base_url))
+        .json(&serde_json::json!({ "addresses": addresses, "per_page": MAX_ITEMS }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok((
+        serde_json::from_value(res["proofs"].clone()).unwrap_or_default(),
+        res["total_proofs"].as_i64().unwrap_or(0),
+    ))
+}
+
+async fn fetch_pixels(
+    client: &reqwest::Client,
+    base_url: &str,
+    addresses: &[String],
+) -> anyhow::Result<(Vec<Value>, i64)> {
+    if addresses.is_empty() {
+        return Ok((vec![], 0));
+    }
+    let res: Value = client
+        .post(format!("{}/pixels/by-addresses", base_url))
+        .json(&serde_json::json!({ "addresses": addresses, "per_page": MAX_ITEMS }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok((
+        serde_json::from_value(res["pixels"].clone()).unwrap_or_default(),
+        res["total_pixels"].as_i64().unwrap_or(0),
+    ))
+}
@@ -0,0 +1,174 @@
+//! Regtest chain controls: invalidate blocks, mock time, reorgs
+
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::{extract::State, response::IntoResponse, Json};
+use bitcoin::{BlockHash, Network};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::AppState;
+
+/// Request body for invalidating a block; one of the two is required
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InvalidateBlockRequest {
+    /// Hash of the block to invalidate
+    pub block_hash: Option<String>,
+    /// Height of the active block to invalidate
+    pub height: Option<u64>,
+}
+
+/// Response for an invalidated block
+#[derive(Serialize, ToSchema)]
+pub struct InvalidateBlockResponse {
+    pub invalidated: String,
+    /// Height of the tip after the rollback
+    pub tip_height: u64,
+}
+
+/// Request body for setting the node's clock
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetMocktimeRequest {
+    /// Unix timestamp the node uses as its time; 0 returns to the system clock
+    pub timestamp: u64,
+}
+
+/// Response for a set mock time
+#[derive(Serialize, ToSchema)]
+pub struct SetMocktimeResponse {
+    pub timestamp: u64,
+}
+
+/// Request body for a reorg
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReorgRequest {
+    /// Blocks to replace, from the tip down
+    pub depth: u64,
+    /// Blocks mined on the new branch (default: depth + 1)
+    pub blocks: Option<u32>,
+}
+
+/// Response for a reorg
+#[derive(Serialize, ToSchema)]
+pub struct ReorgResponse {
+    /// Height of the first replaced block
+    pub fork_height: u64,
+    /// Blocks that left the active chain, lowest first
+    pub disconnected: Vec<String>,
+    /// Blocks mined on the new branch, lowest first
+    pub connected: Vec<String>,
+}
+
+/// Refuse chain controls unless the wallet is configured for regtest
+fn require_regtest(state: &AppState) -> Result<(), ApiError> {
+    if state.config.get_network() != Network::Regtest {
+        return Err(ApiError::new(
+            ErrorCode::Forbidden,
+            "Chain controls are only available on regtest",
+        ));
+    }
+    Ok(())
+}
+
+/// Invalidate a block (regtest only)
+#[utoipa::path(
+    post,
+    path = "/regtest/invalidate-block",
+    tag = "Regtest",
+    request_body = InvalidateBlockRequest,
+    responses(
+        (status = 200, description = "Block invalidated", body = InvalidateBlockResponse),
+        (status = 400, description = "Neither block_hash nor height given"),
+        (status = 403, description = "Not a regtest wallet"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn invalidate_block(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<InvalidateBlockRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_regtest(&state)?;
+    let hash = match (&req.block_hash, req.height) {
+        (Some(hash), _) => BlockHash::from_str(hash)
+            .map_err(|e| ApiError::bad_request(format!("Invalid block hash: {}", e)))?,
+        (None, Some(height)) => state
+            .wallet
+            .block_hash_at(height)
+            .map_err(|e| ApiError::not_found(e.to_string()))?,
+        (None, None) => return Err(ApiError::bad_request("block_hash or height is required")),
+    };
+
+    match state.wallet.invalidate_block(&hash) {
+        Ok(tip_height) => Ok(Json(InvalidateBlockResponse {
+            invalidated: hash.to_string(),
+            tip_height,
+        })),
+        Err(e) => {
+            error!("Failed to invalidate block: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Set the node's mock time (regtest only)
+#[utoipa::path(
+    post,
+    path = "/regtest/set-mocktime",
+    tag = "Regtest",
+    request_body = SetMocktimeRequest,
+    responses(
+        (status = 200, description = "Mock time set", body = SetMocktimeResponse),
+        (status = 403, description = "Not a regtest wallet"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn set_mocktime(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SetMocktimeRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_regtest(&state)?;
+    match state.wallet.set_mocktime(req.timestamp) {
+        Ok(()) => Ok(Json(SetMocktimeResponse {
+            timestamp: req.timestamp,
+        })),
+        Err(e) => {
+            error!("Failed to set mock time: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Replace the last blocks with a new branch (regtest only)
+#[utoipa::path(
+    post,
+    path = "/regtest/reorg",
+    tag = "Regtest",
+    request_body = ReorgRequest,
+    responses(
+        (status = 200, description = "Chain reorganized", body = ReorgResponse),
+        (status = 403, description = "Not a regtest wallet"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn reorg(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ReorgRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_regtest(&state)?;
+    let blocks = req
+        .blocks
+        .unwrap_or_else(|| u32::try_from(req.depth + 1).unwrap_or(u32::MAX));
+    match state.wallet.reorg(req.depth, blocks) {
+        Ok(reorg) => Ok(Json(ReorgResponse {
+            fork_height: reorg.fork_height,
+            disconnected: reorg.disconnected,
+            connected: reorg.connected,
+        })),
+        Err(e) => {
+            error!("Failed to reorg: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Scheduled (timelocked) message handlers
+
+use anchor_api_error::ApiError;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use bitcoin::absolute::LockTime;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::message::AnchorRef;
+use crate::scheduler::{self, ScheduleStatus, ScheduledMessage};
+use crate::AppState;
+
+/// Request body for scheduling an ANCHOR message
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScheduleMessageRequest {
+    /// Message kind (0=generic, 1=text, etc.)
+    #[serde(default = "default_kind")]
+    pub kind: u8,
+    /// Message body (text for kind=1, or hex-encoded binary)
+    pub body: String,
+    /// Whether body is hex-encoded (default: false, treated as UTF-8 text)
+    #[serde(default)]
+    pub body_is_hex: bool,
+    /// Parent transaction ID (for replies)
+    pub parent_txid: Option<String>,
+    /// Parent output index (for replies)
+    pub parent_vout: Option<u8>,
+    /// Additional anchor references [(txid, vout), ...]
+    #[serde(default)]
+    pub additional_anchors: Vec<AnchorRef>,
+    /// Fee rate in sat/vbyte (default: the configured default fee rate)
+    pub fee_rate: Option<u64>,
+    /// Broadcast once the chain reaches this block height
+    pub block_height: Option<u32>,
+    /// Broadcast once the median time past reaches this Unix time
+    pub time: Option<u32>,
+}
+
+fn default_kind() -> u8 {
+    1 // Text
+}
+
+/// Sign an ANCHOR message now and broadcast it at a block height or time
+///
+/// The message is funded as an OP_RETURN transaction with an nLockTime, so
+/// it cannot be mined early even if the signed hex leaks. Its inputs are
+/// locked until it is broadcast or cancelled. Give exactly one of
+/// `block_height` or `time`.
+#[utoipa::path(
+    post,
+    path = "/wallet/schedule-message",
+    tag = "ANCHOR",
+    request_body = ScheduleMessageRequest,
+    responses(
+        (status = 200, description = "Message signed and scheduled", body = ScheduledMessage),
+        (status = 400, description = "Invalid request"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn schedule_message(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ScheduleMessageRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.wallet.is_watch_only() {
+        return Err(ApiError::bad_request(
+            "A watch-only wallet cannot sign scheduled messages",
+        ));
+    }
+
+    let lock_time = match (req.block_height, req.time) {
+        (Some(height), None) => LockTime::from_height(height)
+            .map_err(|e| ApiError::bad_request(format!("Invalid block_height: {}", e)))?,
+        (None, Some(time)) => LockTime::from_time(time)
+            .map_err(|e| ApiError::bad_request(format!("Invalid time: {}", e)))?,
+        _ => {
+            return Err(ApiError::bad_request(
+                "Give exactly one of block_height or time",
+            ))
+        }
+    };
+
+    let body = if req.body_is_hex {
+        hex::decode(&req.body)
+            .map_err(|e| ApiError::bad_request(format!("Invalid hex body: {}", e)))?
+    } else {
+        req.body.as_bytes().to_vec()
+    };
+
+    let settings = state.reloader.settings();
+    let fee_rate = req.fee_rate.unwrap_or(settings.default_fee_rate);
+    if let Some(max_fee_rate) = settings.max_fee_rate.filter(|max| fee_rate > *max) {
+        return Err(ApiError::bad_request(format!(
+            "Fee rate {} sat/vB exceeds the configured maximum of {}",
+            fee_rate, max_fee_rate
+        )));
+    }
+
+    let additional_anchors: Vec<(String, u8)> = req
+        .additional_anchors
+        .into_iter()
+        .map(|a| (a.txid, a.vout))
+        .collect();
+    let coins = crate::wallet::CoinControl::locked(state.lock_manager.get_locked_set());
+
+    let created = state
+        .wallet
+        .create_timelocked_message(
+            req.kind,
+            body,
+            req.parent_txid,
+            req.parent_vout,
+            additional_anchors,
+            fee_rate,
+            lock_time,
+            Some(&coins),
+        )
+        .map_err(|e| ApiError::internal(format!("{:#}", e)))?;
+
+    let reason = scheduler::lock_reason(&created.tx.txid);
+    let locks = created
+        .inputs
+        .iter()
+        .map(|(txid, vout)| (txid.clone(), *vout, reason.clone()))
+        .collect();
+    if let Err(e) = state.lock_manager.bulk_lock(locks) {
+        warn!(
+            "Failed to lock inputs of scheduled message {}: {}",
+            created.tx.txid, e
+        );
+    }
+
+    let scheduled = state
+        .scheduler
+        .add(&created, lock_time)
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    Ok(Json(scheduled))
+}
+
+/// List scheduled messages, newest first
+#[utoipa::path(
+    get,
+    path = "/wallet/scheduled",
+    tag = "ANCHOR",
+    responses(
+        (status = 200, description = "Scheduled messages", body = Vec<ScheduledMessage>)
+    )
+)]
+pub async fn list_scheduled_messages(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.scheduler.list())
+}
+
+/// Cancel a scheduled message before it is broadcast
+///
+/// Releases its inputs. Anyone holding the signed hex can still broadcast it
+/// after its lock time; spend one of the inputs to revoke it for good.
+#[utoipa::path(
+    delete,
+    path = "/wallet/scheduled/{txid}",
+    tag = "ANCHOR",
+    params(
+        ("txid" = String, Path, description = "Transaction ID of the scheduled message")
+    ),
+    responses(
+        (status = 200, description = "Message cancelled", body = ScheduledMessage),
+        (status = 404, description = "No pending message with this txid")
+    )
+)]
+pub async fn cancel_scheduled_message(
+    State(state): State<Arc<AppState>>,
+    Path(txid): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let message = state
+        .scheduler
+        .finish(&txid, ScheduleStatus::Cancelled)
+        .map_err(|e| ApiError::internal(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("No pending scheduled message with this txid"))?;
+
+    let release_state = state.clone();
+    let released = message.clone();
+    tokio::task::spawn_blocking(move || scheduler::release(&release_state, &released))
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    info!("Cancelled scheduled message {}", txid);
+    Ok(Json(message))
+}
@@ -0,0 +1,205 @@
+//! Transaction template management
+//!
+//! Named create-message presets; see `crate::templates`.
+
+use anchor_api_error::{ApiError, ErrorCode};
+use anchor_core::carrier::CarrierType;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::templates::{validate_name, ChangePolicy, FeePriority, TransactionTemplate};
+use crate::AppState;
+
+/// Settings of a template
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TemplateRequest {
+    pub description: Option<String>,
+    /// Preferred carrier (0=op_return, 1=inscription, 2=stamps, 3=annex, 4=witness)
+    pub carrier: Option<u8>,
+    /// `priority`: `economy`, `normal`, `fast`, or `fixed` with a `fee_rate`
+    #[serde(flatten)]
+    pub fee: FeePriority,
+    /// `change`: `fresh`, or `address` with an `address`
+    #[serde(flatten)]
+    pub change: ChangePolicy,
+}
+
+/// Request body for creating a template
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTemplateRequest {
+    /// Lower-case letters, digits and dashes
+    pub name: String,
+    #[serde(flatten)]
+    pub template: TemplateRequest,
+}
+
+/// List transaction templates
+#[utoipa::path(
+    get,
+    path = "/wallet/templates",
+    tag = "Templates",
+    responses(
+        (status = 200, description = "Templates, by name", body = Vec<TransactionTemplate>)
+    )
+)]
+pub async fn list_templates(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.templates.list())
+}
+
+/// Get a transaction template
+#[utoipa::path(
+    get,
+    path = "/wallet/templates/{name}",
+    tag = "Templates",
+    params(
+        ("name" = String, Path, description = "Template name")
+    ),
+    responses(
+        (status = 200, description = "Template", body = TransactionTemplate),
+        (status = 404, description = "No template with this name")
+    )
+)]
+pub async fn get_template(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    state
+        .templates
+        .get(&name)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("No template named '{}'", name)))
+}
+
+/// Create a transaction template
+#[utoipa::path(
+    post,
+    path = "/wallet/templates",
+    tag = "Templates",
+    request_body = CreateTemplateRequest,
+    responses(
+        (status = 201, description = "Template created", body = TransactionTemplate),
+        (status = 400, description = "Invalid template"),
+        (status = 409, description = "A template with this name exists")
+    )
+)]
+pub async fn create_template(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateTemplateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.templates.get(&req.name).is_some() {
+        return Err(ApiError::new(
+            ErrorCode::Conflict,
+            format!("A template named '{}' already exists", req.name),
+        ));
+    }
+    let template = save_template(&state, &req.name, req.template)?;
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+/// Replace a transaction template
+#[utoipa::path(
+    put,
+    path = "/wallet/templates/{name}",
+    tag = "Templates",
+    params(
+        ("name" = String, Path, description = "Template name")
+    ),
+    request_body = TemplateRequest,
+    responses(
+        (status = 200, description = "Template updated", body = TransactionTemplate),
+        (status = 400, description = "Invalid template"),
+        (status = 404, description = "No template with this name")
+    )
+)]
+pub async fn update_template(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(req): Json<TemplateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if state.templates.get(&name).is_none() {
+        return Err(ApiError::not_found(format!("No template named '{}'", name)));
+    }
+    save_template(&state, &name, req).map(Json)
+}
+
+/// Delete a transaction template
+#[utoipa::path(
+    delete,
+    path = "/wallet/templates/{name}",
+    tag = "Templates",
+    params(
+        ("name" = String, Path, description = "Template name")
+    ),
+    responses(
+        (status = 204, description = "Template deleted"),
+        (status = 404, description = "No template with this name")
+    )
+)]
+pub async fn delete_template(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    match state.templates.delete(&name) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::not_found(format!("No template named '{}'", name))),
+        Err(e) => Err(ApiError::internal(e.to_string())),
+    }
+}
+
+fn save_template(
+    state: &AppState,
+    name: &str,
+    req: TemplateRequest,
+) -> Result<TransactionTemplate, ApiError> {
+    validate_name(name).map_err(|e| ApiError::bad_request(e.to_string()))?;
+    if let Some(carrier) = req.carrier {
+        if CarrierType::from_u8(carrier).is_none() {
+            return Err(ApiError::bad_request(format!(
+                "Unknown carrier {}",
+                carrier
+            )));
+        }
+    }
+    if let FeePriority::Fixed { fee_rate } = req.fee {
+        if fee_rate == 0 {
+            return Err(ApiError::bad_request(
+                "Fixed fee rate must be at least 1 sat/vB",
+            ));
+        }
+        if let Some(max_fee_rate) = state
+            .reloader
+            .settings()
+            .max_fee_rate
+            .filter(|max| fee_rate > *max)
+        {
+            return Err(ApiError::bad_request(format!(
+                "Fee rate {} sat/vB exceeds the configured maximum of {}",
+                fee_rate, max_fee_rate
+            )));
+        }
+    }
+    if let ChangePolicy::Address { address } = &req.change {
+        bitcoin::Address::from_str(address)
+            .map_err(|e| ApiError::bad_request(format!("Invalid change address: {}", e)))?
+            .require_network(state.config.get_network())
+            .map_err(|e| ApiError::bad_request(format!("Invalid change address: {}", e)))?;
+    }
+
+    state
+        .templates
+        .put(name, req.description, req.carrier, req.fee, req.change)
+        .map(|(template, _)| template)
+        .map_err(|e| {
+            error!("Failed to save template '{}': {:#}", name, e);
+            ApiError::internal(e.to_string())
+        })
+}
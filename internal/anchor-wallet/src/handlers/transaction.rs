@@ -1,6 +1,7 @@
-//! Transaction operations: broadcast, mine, get raw tx
+//! Transaction operations: broadcast, package broadcast, mine, get raw tx
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::ApiError;
+use axum::{extract::State, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::{error, info};
@@ -12,7 +13,9 @@ use crate::AppState;
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct BroadcastRequest {
     /// Raw transaction hex
-    pub hex: String,
+    pub hex: Option<String>,
+    /// Signed PSBT (base64), finalized before broadcast
+    pub psbt: Option<String>,
 }
 
 /// Response for broadcast transaction
@@ -22,6 +25,25 @@ pub struct BroadcastResponse {
     pub txid: String,
 }
 
+/// Request body for broadcasting a parent and its child
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BroadcastPackageRequest {
+    /// Raw parent transaction hex, e.g. a commit
+    pub parent: String,
+    /// Raw child transaction hex spending the parent, e.g. a reveal or CPFP
+    pub child: String,
+}
+
+/// Response for a broadcast package
+#[derive(Serialize, ToSchema)]
+pub struct BroadcastPackageResponse {
+    pub parent_txid: String,
+    pub child_txid: String,
+    /// Submitted with `submitpackage`; false when the node has no package
+    /// relay and the two were sent one after the other
+    pub packaged: bool,
+}
+
 /// Request body for mining blocks (regtest only)
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct MineRequest {
@@ -53,7 +75,7 @@ pub struct RawTxResponse {
     pub fee_sats: Option<u64>,
 }
 
-/// Broadcast a raw transaction
+/// Broadcast a raw transaction or a signed PSBT
 #[utoipa::path(
     post,
     path = "/wallet/broadcast",
@@ -61,18 +83,77 @@ pub struct RawTxResponse {
     request_body = BroadcastRequest,
     responses(
         (status = 200, description = "Transaction broadcast", body = BroadcastResponse),
+        (status = 400, description = "Neither or both of hex and psbt given, or PSBT not fully signed"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn broadcast(
     State(state): State<Arc<AppState>>,
     Json(req): Json<BroadcastRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    match state.wallet.broadcast(&req.hex) {
+) -> Result<impl IntoResponse, ApiError> {
+    let hex = match (req.hex, req.psbt) {
+        (Some(hex), None) => hex,
+        (None, Some(psbt)) => state
+            .wallet
+            .finalize_psbt(&psbt)
+            .map_err(|e| ApiError::bad_request(e.to_string()))?,
+        _ => return Err(ApiError::bad_request("Provide exactly one of hex or psbt")),
+    };
+
+    match state.wallet.broadcast(&hex) {
         Ok(txid) => Ok(Json(serde_json::json!({ "txid": txid }))),
         Err(e) => {
             error!("Failed to broadcast: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Broadcast a parent and child together
+///
+/// Uses package relay (`submitpackage`, Bitcoin Core 28+) so a parent below
+/// the mempool minimum is carried by its child's fee. Nodes without it get
+/// the parent, then the child.
+#[utoipa::path(
+    post,
+    path = "/wallet/broadcast-package",
+    tag = "Transactions",
+    request_body = BroadcastPackageRequest,
+    responses(
+        (status = 200, description = "Package broadcast", body = BroadcastPackageResponse),
+        (status = 400, description = "Invalid hex, or the child does not spend the parent"),
+        (status = 500, description = "Rejected by the node")
+    )
+)]
+pub async fn broadcast_package(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BroadcastPackageRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let parent = bitcoin::consensus::encode::deserialize_hex::<bitcoin::Transaction>(&req.parent)
+        .map_err(|e| ApiError::bad_request(format!("Invalid parent hex: {}", e)))?;
+    let child = bitcoin::consensus::encode::deserialize_hex::<bitcoin::Transaction>(&req.child)
+        .map_err(|e| ApiError::bad_request(format!("Invalid child hex: {}", e)))?;
+    let parent_txid = parent.compute_txid();
+    if !child
+        .input
+        .iter()
+        .any(|input| input.previous_output.txid == parent_txid)
+    {
+        return Err(ApiError::bad_request(format!(
+            "Child does not spend parent {}",
+            parent_txid
+        )));
+    }
+
+    match state.wallet.broadcast_package(&req.parent, &req.child) {
+        Ok(sent) => Ok(Json(BroadcastPackageResponse {
+            parent_txid: sent.parent_txid,
+            child_txid: sent.child_txid,
+            packaged: sent.packaged,
+        })),
+        Err(e) => {
+            error!("Failed to broadcast package: {}", e);
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -91,7 +172,7 @@ pub async fn broadcast(
 pub async fn mine_blocks(
     State(state): State<Arc<AppState>>,
     Json(req): Json<MineRequest>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.wallet.mine_blocks(req.count) {
         Ok(hashes) => {
             info!("Mined {} blocks", hashes.len());
@@ -99,7 +180,7 @@ pub async fn mine_blocks(
         }
         Err(e) => {
             error!("Failed to mine: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -108,7 +189,7 @@ pub async fn mine_blocks(
 #[utoipa::path(
     get,
     path = "/wallet/rawtx/{txid}",
-    tag = "Transaction",
+    tag = "Transactions",
     params(
         ("txid" = String, Path, description = "Transaction ID")
     ),
@@ -121,8 +202,8 @@ pub async fn mine_blocks(
 pub async fn get_raw_tx(
     State(state): State<Arc<AppState>>,
     axum::extract::Path(txid): axum::extract::Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    match state.wallet.get_raw_transaction(&txid) {
+) -> Result<impl IntoResponse, ApiError> {
+    match state.wallet.get_raw_transaction(&txid).await {
         Ok((hex, decoded, fee_sats)) => Ok(Json(RawTxResponse {
             txid,
             hex,
@@ -131,10 +212,13 @@ pub async fn get_raw_tx(
         })),
         Err(e) => {
             error!("Failed to get raw tx {}: {}", txid, e);
-            if e.to_string().contains("not found") {
-                Err((StatusCode::NOT_FOUND, "Transaction not found".to_string()))
+            let not_found = e
+                .downcast_ref::<anchor_rpc::RpcError>()
+                .is_some_and(anchor_rpc::RpcError::is_not_found);
+            if not_found || e.to_string().contains("not found") {
+                Err(ApiError::not_found("Transaction not found"))
             } else {
-                Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+                Err(ApiError::internal(e.to_string()))
             }
         }
     }
@@ -0,0 +1,85 @@
+//! Unlocking and locking the encrypted wallet data
+
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::{extract::State, response::IntoResponse, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+use utoipa::ToSchema;
+
+use crate::vault::{self, VaultError, VaultStatus};
+use crate::AppState;
+
+/// Unlock request
+#[derive(Deserialize, ToSchema)]
+pub struct UnlockWalletRequest {
+    /// Data passphrase; the first unlock sets it
+    pub passphrase: String,
+}
+
+/// Unlock the encrypted wallet data
+#[utoipa::path(
+    post,
+    path = "/wallet/unlock",
+    tag = "Wallet",
+    request_body = UnlockWalletRequest,
+    responses(
+        (status = 200, description = "Wallet data unlocked", body = VaultStatus),
+        (status = 400, description = "Data encryption is not enabled"),
+        (status = 401, description = "Wrong passphrase"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn unlock_wallet(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<UnlockWalletRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let result = tokio::task::spawn_blocking(move || {
+        state.vault.unlock(&req.passphrase)?;
+        vault::reload_stores(&state)?;
+        Ok::<_, anyhow::Error>(state.vault.status())
+    })
+    .await
+    .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    match result {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => match e.downcast_ref::<VaultError>() {
+            Some(VaultError::WrongPassphrase) => {
+                Err(ApiError::new(ErrorCode::Unauthorized, e.to_string()))
+            }
+            Some(VaultError::Disabled) => Err(ApiError::bad_request(e.to_string())),
+            _ => {
+                error!("Failed to unlock wallet data: {:#}", e);
+                Err(ApiError::internal(format!("{:#}", e)))
+            }
+        },
+    }
+}
+
+/// Lock the encrypted wallet data
+#[utoipa::path(
+    post,
+    path = "/wallet/lock",
+    tag = "Wallet",
+    responses(
+        (status = 200, description = "Wallet data locked", body = VaultStatus)
+    )
+)]
+pub async fn lock_wallet(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    state.vault.lock();
+    Json(state.vault.status())
+}
+
+/// Get the encryption status of the wallet data
+#[utoipa::path(
+    get,
+    path = "/wallet/encryption",
+    tag = "Wallet",
+    responses(
+        (status = 200, description = "Encryption status", body = VaultStatus)
+    )
+)]
+pub async fn get_encryption_status(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.vault.status())
+}
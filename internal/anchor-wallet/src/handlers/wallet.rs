@@ -1,11 +1,13 @@
 //! Basic wallet operations: balance, address, UTXOs
 
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use anchor_api_error::ApiError;
+use axum::{extract::State, response::IntoResponse, Json};
 use serde::Serialize;
 use std::sync::Arc;
 use tracing::error;
 use utoipa::ToSchema;
 
+use crate::wallet::addresses::ReusedAddress;
 use crate::AppState;
 
 /// Response for new address
@@ -27,12 +29,15 @@ pub struct AddressResponse {
 )]
 pub async fn get_balance(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    match state.wallet.get_balance() {
+) -> Result<impl IntoResponse, ApiError> {
+    match state
+        .wallet
+        .get_balance(&state.lock_manager.get_locked_set())
+    {
         Ok(balance) => Ok(Json(balance)),
         Err(e) => {
             error!("Failed to get balance: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -49,12 +54,12 @@ pub async fn get_balance(
 )]
 pub async fn get_new_address(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.wallet.get_new_address() {
         Ok(address) => Ok(Json(serde_json::json!({ "address": address }))),
         Err(e) => {
             error!("Failed to get new address: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -69,14 +74,12 @@ pub async fn get_new_address(
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn list_utxos(
-    State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+pub async fn list_utxos(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, ApiError> {
     match state.wallet.list_utxos() {
         Ok(utxos) => Ok(Json(utxos)),
         Err(e) => {
             error!("Failed to list UTXOs: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -93,7 +96,7 @@ pub async fn list_utxos(
 )]
 pub async fn list_utxos_unlocked(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.wallet.list_utxos() {
         Ok(utxos) => {
             let locked_set = state.lock_manager.get_locked_set();
@@ -105,7 +108,7 @@ pub async fn list_utxos_unlocked(
         }
         Err(e) => {
             error!("Failed to list UTXOs: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -129,12 +132,41 @@ pub struct AddressesResponse {
 )]
 pub async fn list_addresses(
     State(state): State<Arc<AppState>>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     match state.wallet.list_received_addresses() {
         Ok(addresses) => Ok(Json(AddressesResponse { addresses })),
         Err(e) => {
             error!("Failed to list addresses: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Response for reused addresses
+#[derive(Serialize, ToSchema)]
+pub struct ReusedAddressesResponse {
+    /// Addresses that received funds in more than one transaction
+    pub addresses: Vec<ReusedAddress>,
+}
+
+/// List addresses that received funds more than once
+#[utoipa::path(
+    get,
+    path = "/wallet/addresses/reused",
+    tag = "Wallet",
+    responses(
+        (status = 200, description = "Addresses paid to by more than one transaction", body = ReusedAddressesResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_reused_addresses(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    match state.wallet.list_reused_addresses() {
+        Ok(addresses) => Ok(Json(ReusedAddressesResponse { addresses })),
+        Err(e) => {
+            error!("Failed to list reused addresses: {}", e);
+            Err(ApiError::internal(e.to_string()))
         }
     }
 }
@@ -3,7 +3,8 @@
 //! This module manages decentralized identities (Nostr, Pubky, etc.) that can be
 //! linked to oracles and published to DNS via Selfie Records.
 //!
-//! Identities are persisted to a JSON file and loaded on startup.
+//! Identities are persisted to a JSON file, sealed by the vault when data
+//! encryption is enabled, and loaded on startup or unlock.
 //! Private keys are encrypted using the wallet's encryption key.
 
 use anyhow::{Context, Result};
@@ -15,6 +16,8 @@ use std::sync::{Arc, RwLock};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use crate::vault::Vault;
+
 /// Type of identity (cryptographic curve/protocol)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -223,13 +226,17 @@ pub const PUBKY_HOMESERVERS: &[PubkyHomeserver] = &[PubkyHomeserver {
 pub struct IdentityManager {
     /// Path to the identity state file
     state_path: PathBuf,
+    /// Encryption of the state file
+    vault: Arc<Vault>,
     /// In-memory state protected by RwLock
     state: Arc<RwLock<IdentityState>>,
 }
 
 impl IdentityManager {
     /// Create a new IdentityManager with the given data directory
-    pub fn new(data_dir: PathBuf) -> Result<Self> {
+    ///
+    /// If the vault is locked the state loads on `reload`, after unlocking.
+    pub fn new(data_dir: PathBuf, vault: Arc<Vault>) -> Result<Self> {
         let state_path = data_dir.join("identities.json");
 
         // Ensure data directory exists
@@ -237,49 +244,63 @@ impl IdentityManager {
             fs::create_dir_all(parent).context("Failed to create data directory")?;
         }
 
-        // Load existing state or create default
-        let state = if state_path.exists() {
-            match fs::read_to_string(&state_path) {
-                Ok(content) => match serde_json::from_str::<IdentityState>(&content) {
-                    Ok(state) => {
-                        info!("Loaded {} identities from disk", state.identities.len());
-                        state
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse identity state, starting fresh: {}", e);
-                        IdentityState {
-                            version: 1,
-                            ..Default::default()
-                        }
-                    }
-                },
-                Err(e) => {
-                    warn!("Failed to read identity state file, starting fresh: {}", e);
-                    IdentityState {
-                        version: 1,
-                        ..Default::default()
-                    }
-                }
-            }
-        } else {
-            debug!("No existing identity state file, starting fresh");
-            IdentityState {
-                version: 1,
-                ..Default::default()
-            }
-        };
-
         let manager = Self {
             state_path,
-            state: Arc::new(RwLock::new(state)),
+            vault,
+            state: Arc::new(RwLock::new(IdentityState {
+                version: 1,
+                ..Default::default()
+            })),
         };
+        manager.load();
 
         // Save initial state to ensure file exists
-        manager.save()?;
+        if !manager.vault.is_locked() {
+            manager.save()?;
+        }
 
         Ok(manager)
     }
 
+    /// Load existing state or start fresh
+    fn load(&self) {
+        let fresh = || IdentityState {
+            version: 1,
+            ..Default::default()
+        };
+        let state = match self.vault.read(&self.state_path) {
+            Ok(Some(content)) => match serde_json::from_str::<IdentityState>(&content) {
+                Ok(state) => {
+                    info!("Loaded {} identities from disk", state.identities.len());
+                    state
+                }
+                Err(e) => {
+                    warn!("Failed to parse identity state, starting fresh: {}", e);
+                    fresh()
+                }
+            },
+            Ok(None) => {
+                debug!("No existing identity state file, starting fresh");
+                fresh()
+            }
+            Err(e) if self.vault.is_locked() => {
+                info!("Identity state is encrypted, loading it on unlock: {}", e);
+                fresh()
+            }
+            Err(e) => {
+                warn!("Failed to read identity state file, starting fresh: {}", e);
+                fresh()
+            }
+        };
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    /// Re-read the state from disk, e.g. after unlocking the vault
+    pub fn reload(&self) -> Result<()> {
+        self.load();
+        self.save()
+    }
+
     /// Save the current state to disk
     fn save(&self) -> Result<()> {
         let state = self
@@ -287,7 +308,9 @@ impl IdentityManager {
             .read()
             .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
         let content = serde_json::to_string_pretty(&*state)?;
-        fs::write(&self.state_path, content).context("Failed to write identity state")?;
+        self.vault
+            .write(&self.state_path, &content)
+            .context("Failed to write identity state")?;
         debug!(
             "Saved identity state with {} identities",
             state.identities.len()
@@ -557,11 +580,20 @@ impl IdentityManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
     use tempfile::TempDir;
 
+    fn plaintext_vault(data_dir: &Path) -> Arc<Vault> {
+        Arc::new(Vault::new(data_dir, false, None))
+    }
+
     fn create_test_manager() -> (IdentityManager, TempDir) {
         let temp_dir = TempDir::new().unwrap();
-        let manager = IdentityManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let manager = IdentityManager::new(
+            temp_dir.path().to_path_buf(),
+            plaintext_vault(temp_dir.path()),
+        )
+        .unwrap();
         (manager, temp_dir)
     }
 
@@ -671,7 +703,7 @@ mod tests {
 
         // Create manager and add identity
         let id = {
-            let manager = IdentityManager::new(path.clone()).unwrap();
+            let manager = IdentityManager::new(path.clone(), plaintext_vault(&path)).unwrap();
             let identity = Identity::new(
                 IdentityType::Nostr,
                 "Persistent".to_string(),
@@ -684,7 +716,7 @@ mod tests {
 
         // Create new manager and verify identity exists
         {
-            let manager = IdentityManager::new(path).unwrap();
+            let manager = IdentityManager::new(path.clone(), plaintext_vault(&path)).unwrap();
             let loaded = manager.get(&id);
             assert!(loaded.is_some());
             assert_eq!(loaded.unwrap().label, "Persistent");
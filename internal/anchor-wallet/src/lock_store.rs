@@ -0,0 +1,163 @@
+//! Storage backends for UTXO locks
+//!
+//! By default locks live in `locked_utxos.json` in the data dir, which only
+//! works for a single wallet service. With `LOCK_STORE_URL` pointing at
+//! Postgres, replicas share one `wallet_utxo_locks` row per wallet instead.
+//!
+//! Every write names the version it was based on and only lands if the
+//! stored version hasn't moved on (optimistic concurrency); the lock manager
+//! re-reads and retries when another replica wrote first. The state is
+//! sealed by the vault in either backend.
+
+use anyhow::{Context, Result};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::vault::Vault;
+
+/// Serialized lock state and the version it was stored at
+#[derive(Debug, Clone)]
+pub struct StoredLocks {
+    pub content: String,
+    pub version: i64,
+}
+
+/// Where the lock manager keeps its state
+pub trait LockStore: Send + Sync {
+    /// The stored state; `None` if nothing was stored yet
+    fn read(&self) -> Result<Option<StoredLocks>>;
+
+    /// Store `content` over `version` (0 when nothing was stored yet)
+    ///
+    /// Returns the new version, or `None` if another writer changed the state
+    /// since `version` was read.
+    fn write(&self, content: &str, version: i64) -> Result<Option<i64>>;
+
+    /// Whether other wallet service replicas write to this store too
+    fn is_shared(&self) -> bool;
+}
+
+/// Lock state in a file in the data dir, for a single wallet service
+pub struct FileLockStore {
+    path: PathBuf,
+    vault: Arc<Vault>,
+}
+
+impl FileLockStore {
+    pub fn new(data_dir: &Path, vault: Arc<Vault>) -> Result<Self> {
+        fs::create_dir_all(data_dir).context("Failed to create data directory")?;
+        Ok(Self {
+            path: data_dir.join("locked_utxos.json"),
+            vault,
+        })
+    }
+}
+
+impl LockStore for FileLockStore {
+    fn read(&self) -> Result<Option<StoredLocks>> {
+        Ok(self.vault.read(&self.path)?.map(|content| StoredLocks {
+            content,
+            version: 0,
+        }))
+    }
+
+    fn write(&self, content: &str, _version: i64) -> Result<Option<i64>> {
+        // Only this process writes the file
+        self.vault
+            .write(&self.path, content)
+            .context("Failed to write lock state")?;
+        Ok(Some(0))
+    }
+
+    fn is_shared(&self) -> bool {
+        false
+    }
+}
+
+/// Lock state in Postgres, shared by every replica of a wallet
+///
+/// The lock manager's API is synchronous, so queries block the calling
+/// thread; this needs the multi-threaded Tokio runtime.
+pub struct PgLockStore {
+    pool: PgPool,
+    wallet_name: String,
+    vault: Arc<Vault>,
+}
+
+impl PgLockStore {
+    /// Connect to the database holding `wallet_utxo_locks`
+    pub async fn connect(url: &str, wallet_name: &str, vault: Arc<Vault>) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect(url)
+            .await
+            .context("Failed to connect to lock store")?;
+        info!("Connected to lock store for wallet {}", wallet_name);
+        Ok(Self {
+            pool,
+            wallet_name: wallet_name.to_string(),
+            vault,
+        })
+    }
+}
+
+impl LockStore for PgLockStore {
+    fn read(&self) -> Result<Option<StoredLocks>> {
+        let row: Option<(String, i64)> = block_on(
+            sqlx::query_as("SELECT state, version FROM wallet_utxo_locks WHERE wallet_name = $1")
+                .bind(&self.wallet_name)
+                .fetch_optional(&self.pool),
+        )
+        .context("Failed to read lock store")?;
+        row.map(|(sealed, version)| {
+            Ok(StoredLocks {
+                content: self.vault.open_str(&sealed)?,
+                version,
+            })
+        })
+        .transpose()
+    }
+
+    fn write(&self, content: &str, version: i64) -> Result<Option<i64>> {
+        let sealed = self.vault.seal_str(content)?;
+        let result = if version == 0 {
+            block_on(
+                sqlx::query(
+                    "INSERT INTO wallet_utxo_locks (wallet_name, state, version) \
+                     VALUES ($1, $2, 1) ON CONFLICT (wallet_name) DO NOTHING",
+                )
+                .bind(&self.wallet_name)
+                .bind(&sealed)
+                .execute(&self.pool),
+            )
+        } else {
+            block_on(
+                sqlx::query(
+                    "UPDATE wallet_utxo_locks \
+                     SET state = $2, version = version + 1, updated_at = NOW() \
+                     WHERE wallet_name = $1 AND version = $3",
+                )
+                .bind(&self.wallet_name)
+                .bind(&sealed)
+                .bind(version)
+                .execute(&self.pool),
+            )
+        }
+        .context("Failed to write lock store")?;
+        Ok((result.rows_affected() == 1).then_some(version + 1))
+    }
+
+    fn is_shared(&self) -> bool {
+        true
+    }
+}
+
+/// Run a query to completion from synchronous code inside the runtime
+fn block_on<F: Future>(future: F) -> F::Output {
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::block_in_place(|| handle.block_on(future))
+}
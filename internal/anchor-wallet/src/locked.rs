@@ -4,19 +4,28 @@
 //! UTXOs can be locked for various reasons:
 //! - Domain ownership (DNS domains)
 //! - Token ownership
+//! - Inputs held for a scheduled message
 //! - Manual locks by user
 //!
-//! Locked UTXOs are persisted to a JSON file and loaded on startup.
+//! Locked UTXOs are persisted through a [`LockStore`]: a JSON file in the
+//! data dir, or a Postgres row shared by wallet service replicas. The state
+//! is sealed by the vault when data encryption is enabled, and loaded on
+//! startup or unlock.
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::fs;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 use tracing::{debug, info, warn};
 
+use crate::lock_store::{FileLockStore, LockStore};
+use crate::vault::Vault;
+
+/// Writes attempted before giving up on a lock state other replicas keep changing
+const MAX_WRITE_ATTEMPTS: usize = 5;
+
 /// Reason why a UTXO is locked
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -32,6 +41,8 @@ pub enum LockReason {
         asset_type: String,
         asset_id: String,
     },
+    /// Spent by a signed message waiting for its lock time
+    Scheduled { txid: String },
 }
 
 impl LockReason {
@@ -47,6 +58,7 @@ impl LockReason {
             } => {
                 format!("Asset: {} ({})", asset_id, asset_type)
             }
+            LockReason::Scheduled { txid } => format!("Scheduled message: {}", txid),
         }
     }
 
@@ -100,123 +112,197 @@ struct LockState {
     auto_lock_enabled: bool,
     /// Last sync timestamp
     last_sync: Option<DateTime<Utc>>,
+    /// Store version this state was read at
+    #[serde(skip)]
+    version: i64,
 }
 
 /// Manager for UTXO locks
 ///
 /// This manager handles locking and unlocking UTXOs to prevent them from being
-/// spent accidentally. Locks are persisted to disk and loaded on startup.
+/// spent accidentally. Locks are persisted to the lock store and loaded on
+/// startup. With a shared store every read starts from the stored state and
+/// every write is retried on top of changes made by other replicas.
 pub struct LockManager {
-    /// Path to the lock state file
-    state_path: PathBuf,
+    /// Where the lock state is persisted
+    store: Box<dyn LockStore>,
+    /// Encryption of the lock state
+    vault: Arc<Vault>,
     /// In-memory lock state protected by RwLock
     state: Arc<RwLock<LockState>>,
 }
 
 impl LockManager {
     /// Create a new LockManager with the given data directory
-    pub fn new(data_dir: PathBuf) -> Result<Self> {
-        let state_path = data_dir.join("locked_utxos.json");
+    ///
+    /// If the vault is locked the state loads on `reload`, after unlocking.
+    pub fn new(data_dir: PathBuf, vault: Arc<Vault>) -> Result<Self> {
+        let store = FileLockStore::new(&data_dir, vault.clone())?;
+        Self::with_store(Box::new(store), vault)
+    }
+
+    /// Create a LockManager on top of the given lock store
+    pub fn with_store(store: Box<dyn LockStore>, vault: Arc<Vault>) -> Result<Self> {
+        let manager = Self {
+            store,
+            vault,
+            state: Arc::new(RwLock::new(LockState::default())),
+        };
+        manager.load();
 
-        // Ensure data directory exists
-        if let Some(parent) = state_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create data directory")?;
+        // Save initial state to ensure the store has it
+        if !manager.vault.is_locked() {
+            manager.save()?;
         }
 
-        // Load existing state or create default
-        let state = if state_path.exists() {
-            match fs::read_to_string(&state_path) {
-                Ok(content) => match serde_json::from_str::<LockState>(&content) {
-                    Ok(state) => {
-                        info!("Loaded {} locked UTXOs from disk", state.locked_utxos.len());
-                        state
-                    }
-                    Err(e) => {
-                        warn!("Failed to parse lock state, starting fresh: {}", e);
-                        LockState::default()
-                    }
-                },
+        Ok(manager)
+    }
+
+    /// Load existing state or start fresh
+    fn load(&self) {
+        let state = match self.store.read() {
+            Ok(Some(stored)) => match serde_json::from_str::<LockState>(&stored.content) {
+                Ok(mut state) => {
+                    info!("Loaded {} locked UTXOs", state.locked_utxos.len());
+                    state.version = stored.version;
+                    state
+                }
                 Err(e) => {
-                    warn!("Failed to read lock state file, starting fresh: {}", e);
-                    LockState::default()
+                    warn!("Failed to parse lock state, starting fresh: {}", e);
+                    LockState {
+                        version: stored.version,
+                        ..LockState::default()
+                    }
                 }
+            },
+            Ok(None) => {
+                debug!("No existing lock state, starting fresh");
+                LockState::default()
+            }
+            Err(e) if self.vault.is_locked() => {
+                info!("Lock state is encrypted, loading it on unlock: {}", e);
+                LockState::default()
+            }
+            Err(e) => {
+                warn!("Failed to read lock state, starting fresh: {}", e);
+                LockState::default()
             }
-        } else {
-            debug!("No existing lock state file, starting fresh");
-            LockState::default()
         };
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
 
-        let manager = Self {
-            state_path,
-            state: Arc::new(RwLock::new(state)),
-        };
+    /// Re-read the state from the store, e.g. after unlocking the vault
+    pub fn reload(&self) -> Result<()> {
+        self.load();
+        self.save()
+    }
 
-        // Save initial state to ensure file exists
-        manager.save()?;
+    /// Write the current state back to the store
+    fn save(&self) -> Result<()> {
+        self.update(|_| ((), true))
+    }
 
-        Ok(manager)
+    /// The stored state, for a shared store where other replicas write too
+    fn fetch(&self) -> Result<LockState> {
+        let Some(stored) = self.store.read()? else {
+            return Ok(LockState::default());
+        };
+        let mut state: LockState =
+            serde_json::from_str(&stored.content).context("Failed to parse lock state")?;
+        state.version = stored.version;
+        Ok(state)
     }
 
-    /// Save the current state to disk
-    fn save(&self) -> Result<()> {
-        let state = self
-            .state
-            .read()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        let content = serde_json::to_string_pretty(&*state)?;
-        fs::write(&self.state_path, content).context("Failed to write lock state")?;
-        debug!("Saved lock state with {} UTXOs", state.locked_utxos.len());
-        Ok(())
+    /// The lock state to answer a query from
+    ///
+    /// Refreshed from a shared store first; if that fails the cached state
+    /// is used.
+    fn read_state(&self) -> RwLockReadGuard<'_, LockState> {
+        if self.store.is_shared() {
+            match self.fetch() {
+                Ok(fresh) => *self.state.write().unwrap_or_else(|e| e.into_inner()) = fresh,
+                Err(e) => warn!("Failed to refresh lock state, using cached: {:#}", e),
+            }
+        }
+        self.state.read().unwrap_or_else(|e| e.into_inner())
     }
 
-    /// Lock a UTXO with the given reason
+    /// Apply `mutate` to the latest state and persist it if it changed
     ///
-    /// Returns Ok(true) if the UTXO was newly locked, Ok(false) if already locked
-    pub fn lock(&self, txid: String, vout: u32, reason: LockReason) -> Result<bool> {
+    /// `mutate` returns its result and whether it changed the state. With a
+    /// shared store it runs on a fresh copy, and again after a conflicting
+    /// write by another replica.
+    fn update<T>(&self, mut mutate: impl FnMut(&mut LockState) -> (T, bool)) -> Result<T> {
         let mut state = self
             .state
             .write()
             .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-
-        // Check if already locked
-        if state
-            .locked_utxos
-            .iter()
-            .any(|u| u.txid == txid && u.vout == vout)
-        {
-            debug!("UTXO {}:{} is already locked", txid, vout);
-            return Ok(false);
+        for _ in 0..MAX_WRITE_ATTEMPTS {
+            if self.store.is_shared() {
+                *state = self.fetch()?;
+            }
+            let (result, changed) = mutate(&mut state);
+            if !changed {
+                return Ok(result);
+            }
+            let content = serde_json::to_string_pretty(&*state)?;
+            match self.store.write(&content, state.version)? {
+                Some(version) => {
+                    state.version = version;
+                    debug!("Saved lock state with {} UTXOs", state.locked_utxos.len());
+                    return Ok(result);
+                }
+                None => debug!("Lock state changed by another replica, retrying"),
+            }
         }
+        anyhow::bail!(
+            "Lock state kept changing, gave up after {} attempts",
+            MAX_WRITE_ATTEMPTS
+        )
+    }
 
-        // Add new lock
-        let locked_utxo = LockedUtxo::new(txid.clone(), vout, reason.clone());
-        state.locked_utxos.push(locked_utxo);
+    /// Lock a UTXO with the given reason
+    ///
+    /// Returns Ok(true) if the UTXO was newly locked, Ok(false) if already locked
+    pub fn lock(&self, txid: String, vout: u32, reason: LockReason) -> Result<bool> {
+        let locked = self.update(|state| {
+            // Check if already locked
+            if state
+                .locked_utxos
+                .iter()
+                .any(|u| u.txid == txid && u.vout == vout)
+            {
+                return (false, false);
+            }
 
-        drop(state);
-        self.save()?;
+            // Add new lock
+            let locked_utxo = LockedUtxo::new(txid.clone(), vout, reason.clone());
+            state.locked_utxos.push(locked_utxo);
+            (true, true)
+        })?;
 
-        info!("Locked UTXO {}:{} - {}", txid, vout, reason.description());
-        Ok(true)
+        if locked {
+            info!("Locked UTXO {}:{} - {}", txid, vout, reason.description());
+        } else {
+            debug!("UTXO {}:{} is already locked", txid, vout);
+        }
+        Ok(locked)
     }
 
     /// Unlock a specific UTXO
     ///
     /// Returns Ok(true) if the UTXO was unlocked, Ok(false) if it wasn't locked
     pub fn unlock(&self, txid: &str, vout: u32) -> Result<bool> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-
-        let initial_len = state.locked_utxos.len();
-        state
-            .locked_utxos
-            .retain(|u| !(u.txid == txid && u.vout == vout));
-        let removed = state.locked_utxos.len() < initial_len;
+        let removed = self.update(|state| {
+            let initial_len = state.locked_utxos.len();
+            state
+                .locked_utxos
+                .retain(|u| !(u.txid == txid && u.vout == vout));
+            let removed = state.locked_utxos.len() < initial_len;
+            (removed, removed)
+        })?;
 
         if removed {
-            drop(state);
-            self.save()?;
             info!("Unlocked UTXO {}:{}", txid, vout);
         }
 
@@ -232,22 +318,18 @@ impl LockManager {
         vout: u32,
         expected_reason: &LockReason,
     ) -> Result<bool> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-
-        let initial_len = state.locked_utxos.len();
-        state.locked_utxos.retain(|u| {
-            !(u.txid == txid
-                && u.vout == vout
-                && std::mem::discriminant(&u.reason) == std::mem::discriminant(expected_reason))
-        });
-        let removed = state.locked_utxos.len() < initial_len;
+        let removed = self.update(|state| {
+            let initial_len = state.locked_utxos.len();
+            state.locked_utxos.retain(|u| {
+                !(u.txid == txid
+                    && u.vout == vout
+                    && std::mem::discriminant(&u.reason) == std::mem::discriminant(expected_reason))
+            });
+            let removed = state.locked_utxos.len() < initial_len;
+            (removed, removed)
+        })?;
 
         if removed {
-            drop(state);
-            self.save()?;
             info!("Unlocked UTXO {}:{} (reason matched)", txid, vout);
         }
 
@@ -258,38 +340,35 @@ impl LockManager {
     ///
     /// Returns the number of newly locked UTXOs
     pub fn bulk_lock(&self, utxos: Vec<(String, u32, LockReason)>) -> Result<usize> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-
-        let mut newly_locked = 0;
+        let newly_locked = self.update(|state| {
+            let mut newly_locked = 0;
+
+            for (txid, vout, reason) in &utxos {
+                // Check if already locked
+                if state
+                    .locked_utxos
+                    .iter()
+                    .any(|u| &u.txid == txid && u.vout == *vout)
+                {
+                    continue;
+                }
 
-        for (txid, vout, reason) in utxos {
-            // Check if already locked
-            if state
-                .locked_utxos
-                .iter()
-                .any(|u| u.txid == txid && u.vout == vout)
-            {
-                continue;
+                // Add new lock
+                let locked_utxo = LockedUtxo::new(txid.clone(), *vout, reason.clone());
+                state.locked_utxos.push(locked_utxo);
+                newly_locked += 1;
+                debug!(
+                    "Bulk locked UTXO {}:{} - {}",
+                    txid,
+                    vout,
+                    reason.description()
+                );
             }
 
-            // Add new lock
-            let locked_utxo = LockedUtxo::new(txid.clone(), vout, reason.clone());
-            state.locked_utxos.push(locked_utxo);
-            newly_locked += 1;
-            debug!(
-                "Bulk locked UTXO {}:{} - {}",
-                txid,
-                vout,
-                reason.description()
-            );
-        }
+            (newly_locked, newly_locked > 0)
+        })?;
 
         if newly_locked > 0 {
-            drop(state);
-            self.save()?;
             info!("Bulk locked {} new UTXOs", newly_locked);
         }
 
@@ -300,20 +379,16 @@ impl LockManager {
     ///
     /// Returns the number of stale locks removed
     pub fn prune_stale_locks(&self, current_utxos: &HashSet<(String, u32)>) -> Result<usize> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-
-        let initial_len = state.locked_utxos.len();
-        state
-            .locked_utxos
-            .retain(|u| current_utxos.contains(&(u.txid.clone(), u.vout)));
-        let removed = initial_len - state.locked_utxos.len();
+        let removed = self.update(|state| {
+            let initial_len = state.locked_utxos.len();
+            state
+                .locked_utxos
+                .retain(|u| current_utxos.contains(&(u.txid.clone(), u.vout)));
+            let removed = initial_len - state.locked_utxos.len();
+            (removed, removed > 0)
+        })?;
 
         if removed > 0 {
-            drop(state);
-            self.save()?;
             info!("Pruned {} stale locks", removed);
         }
 
@@ -322,19 +397,19 @@ impl LockManager {
 
     /// Get a set of all locked (txid, vout) pairs for efficient lookup
     pub fn get_locked_set(&self) -> HashSet<(String, u32)> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state.locked_utxos.iter().map(|u| u.key()).collect()
     }
 
     /// List all locked UTXOs
     pub fn list_locked(&self) -> Vec<LockedUtxo> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state.locked_utxos.clone()
     }
 
     /// Check if a specific UTXO is locked
     pub fn is_locked(&self, txid: &str, vout: u32) -> bool {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state
             .locked_utxos
             .iter()
@@ -343,7 +418,7 @@ impl LockManager {
 
     /// Get the lock reason for a specific UTXO
     pub fn get_lock_reason(&self, txid: &str, vout: u32) -> Option<LockReason> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state
             .locked_utxos
             .iter()
@@ -353,44 +428,37 @@ impl LockManager {
 
     /// Check if auto-lock is enabled
     pub fn is_auto_lock_enabled(&self) -> bool {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state.auto_lock_enabled
     }
 
     /// Set auto-lock enabled/disabled
     pub fn set_auto_lock(&self, enabled: bool) -> Result<()> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        state.auto_lock_enabled = enabled;
-        drop(state);
-        self.save()?;
+        self.update(|state| {
+            state.auto_lock_enabled = enabled;
+            ((), true)
+        })?;
         info!("Auto-lock set to: {}", enabled);
         Ok(())
     }
 
     /// Get the last sync timestamp
     pub fn get_last_sync(&self) -> Option<DateTime<Utc>> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state.last_sync
     }
 
     /// Update the last sync timestamp
     pub fn update_last_sync(&self) -> Result<()> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        state.last_sync = Some(Utc::now());
-        drop(state);
-        self.save()?;
-        Ok(())
+        self.update(|state| {
+            state.last_sync = Some(Utc::now());
+            ((), true)
+        })
     }
 
     /// Get all locked domain UTXOs
     pub fn get_domain_locks(&self) -> Vec<LockedUtxo> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state
             .locked_utxos
             .iter()
@@ -401,7 +469,7 @@ impl LockManager {
 
     /// Get all locked token UTXOs
     pub fn get_token_locks(&self) -> Vec<LockedUtxo> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state
             .locked_utxos
             .iter()
@@ -412,7 +480,7 @@ impl LockManager {
 
     /// Find a domain lock by domain name
     pub fn find_domain_lock(&self, domain_name: &str) -> Option<LockedUtxo> {
-        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let state = self.read_state();
         state
             .locked_utxos
             .iter()
@@ -431,19 +499,32 @@ impl LockManager {
         new_txid: String,
         new_vout: u32,
     ) -> Result<bool> {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let transferred = self.update(|state| {
+            // Find and remove the old lock
+            let Some(old_lock_idx) = state.locked_utxos.iter().position(|u| {
+                u.txid == old_txid
+                    && u.vout == old_vout
+                    && matches!(&u.reason, LockReason::Domain { name } if name == domain_name)
+            }) else {
+                return (false, false);
+            };
+
+            // Remove old lock
+            state.locked_utxos.remove(old_lock_idx);
 
-        // Find and remove the old lock
-        let old_lock_idx = state.locked_utxos.iter().position(|u| {
-            u.txid == old_txid
-                && u.vout == old_vout
-                && matches!(&u.reason, LockReason::Domain { name } if name == domain_name)
-        });
+            // Add new lock
+            let new_lock = LockedUtxo::new(
+                new_txid.clone(),
+                new_vout,
+                LockReason::Domain {
+                    name: domain_name.to_string(),
+                },
+            );
+            state.locked_utxos.push(new_lock);
+            (true, true)
+        })?;
 
-        if old_lock_idx.is_none() {
+        if !transferred {
             warn!(
                 "No existing domain lock found for {} at {}:{}",
                 domain_name, old_txid, old_vout
@@ -451,22 +532,6 @@ impl LockManager {
             return Ok(false);
         }
 
-        // Remove old lock
-        state.locked_utxos.remove(old_lock_idx.unwrap());
-
-        // Add new lock
-        let new_lock = LockedUtxo::new(
-            new_txid.clone(),
-            new_vout,
-            LockReason::Domain {
-                name: domain_name.to_string(),
-            },
-        );
-        state.locked_utxos.push(new_lock);
-
-        drop(state);
-        self.save()?;
-
         info!(
             "Transferred domain lock for {} from {}:{} to {}:{}",
             domain_name, old_txid, old_vout, new_txid, new_vout
@@ -479,11 +544,59 @@ impl LockManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lock_store::StoredLocks;
+    use std::fs;
+    use std::path::Path;
     use tempfile::TempDir;
 
+    fn plaintext_vault(data_dir: &Path) -> Arc<Vault> {
+        Arc::new(Vault::new(data_dir, false, None))
+    }
+
+    /// A shared store in memory, standing in for Postgres
+    #[derive(Clone, Default)]
+    struct SharedStore {
+        stored: Arc<std::sync::Mutex<Option<StoredLocks>>>,
+        /// Written by "another replica" just before our next write
+        interloper: Arc<std::sync::Mutex<Option<String>>>,
+    }
+
+    impl LockStore for SharedStore {
+        fn read(&self) -> Result<Option<StoredLocks>> {
+            Ok(self.stored.lock().unwrap().clone())
+        }
+
+        fn write(&self, content: &str, version: i64) -> Result<Option<i64>> {
+            let mut stored = self.stored.lock().unwrap();
+            if let Some(other) = self.interloper.lock().unwrap().take() {
+                let other_version = stored.as_ref().map_or(0, |s| s.version) + 1;
+                *stored = Some(StoredLocks {
+                    content: other,
+                    version: other_version,
+                });
+            }
+            if stored.as_ref().map_or(0, |s| s.version) != version {
+                return Ok(None);
+            }
+            *stored = Some(StoredLocks {
+                content: content.to_string(),
+                version: version + 1,
+            });
+            Ok(Some(version + 1))
+        }
+
+        fn is_shared(&self) -> bool {
+            true
+        }
+    }
+
     fn create_test_manager() -> (LockManager, TempDir) {
         let temp_dir = TempDir::new().unwrap();
-        let manager = LockManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let manager = LockManager::new(
+            temp_dir.path().to_path_buf(),
+            plaintext_vault(temp_dir.path()),
+        )
+        .unwrap();
         (manager, temp_dir)
     }
 
@@ -612,7 +725,7 @@ mod tests {
 
         // Create manager and lock something
         {
-            let manager = LockManager::new(path.clone()).unwrap();
+            let manager = LockManager::new(path.clone(), plaintext_vault(&path)).unwrap();
             manager
                 .lock(
                     "persistent_tx".to_string(),
@@ -626,10 +739,68 @@ mod tests {
 
         // Create new manager from same path - should load lock
         {
-            let manager = LockManager::new(path).unwrap();
+            let manager = LockManager::new(path.clone(), plaintext_vault(&path)).unwrap();
             assert!(manager.is_locked("persistent_tx", 0));
             let lock = manager.find_domain_lock("test.btc");
             assert!(lock.is_some());
         }
     }
+
+    #[test]
+    fn test_encrypted_persistence() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+
+        let vault = Arc::new(Vault::new(&path, true, None));
+        vault.unlock("passphrase").unwrap();
+        let manager = LockManager::new(path.clone(), vault).unwrap();
+        manager
+            .lock("sealed_tx".to_string(), 1, LockReason::Manual)
+            .unwrap();
+        let on_disk = fs::read_to_string(path.join("locked_utxos.json")).unwrap();
+        assert!(!on_disk.contains("sealed_tx"));
+
+        // A locked vault loads nothing until unlocked and reloaded
+        let vault = Arc::new(Vault::new(&path, true, None));
+        let manager = LockManager::new(path.clone(), vault.clone()).unwrap();
+        assert!(!manager.is_locked("sealed_tx", 1));
+        // Writes fail rather than overwrite the sealed state
+        assert!(manager
+            .lock("other_tx".to_string(), 0, LockReason::Manual)
+            .is_err());
+
+        vault.unlock("passphrase").unwrap();
+        manager.reload().unwrap();
+        assert!(manager.is_locked("sealed_tx", 1));
+        assert!(!manager.is_locked("other_tx", 0));
+    }
+
+    #[test]
+    fn test_shared_store_replicas() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = plaintext_vault(temp_dir.path());
+        let store = SharedStore::default();
+        let a = LockManager::with_store(Box::new(store.clone()), vault.clone()).unwrap();
+        let b = LockManager::with_store(Box::new(store.clone()), vault).unwrap();
+
+        assert!(a.lock("tx1".to_string(), 0, LockReason::Manual).unwrap());
+        assert!(b.is_locked("tx1", 0));
+        assert!(!b.lock("tx1".to_string(), 0, LockReason::Manual).unwrap());
+        assert!(b.lock("tx2".to_string(), 1, LockReason::Manual).unwrap());
+        assert_eq!(a.get_locked_set().len(), 2);
+
+        // A write racing another replica is redone on top of its state
+        let mut other = LockState {
+            locked_utxos: a.list_locked(),
+            ..LockState::default()
+        };
+        other
+            .locked_utxos
+            .push(LockedUtxo::new("tx3".to_string(), 2, LockReason::Manual));
+        *store.interloper.lock().unwrap() = Some(serde_json::to_string(&other).unwrap());
+        assert!(a.unlock("tx1", 0).unwrap());
+        assert!(b.is_locked("tx3", 2));
+        assert!(b.is_locked("tx2", 1));
+        assert!(!b.is_locked("tx1", 0));
+    }
 }
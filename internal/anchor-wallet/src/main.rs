@@ -3,29 +3,46 @@
 //! HTTP API for creating and broadcasting ANCHOR transactions.
 
 mod config;
+mod escrow;
 mod handlers;
 mod identity;
+mod lock_store;
 mod locked;
 mod migration;
+mod proxy;
+mod reload;
+mod scheduler;
+mod shutdown;
+mod templates;
+mod vault;
 mod wallet;
 
-use anyhow::Result;
+use anchor_api_security::SecurityConfig;
+use anyhow::{Context, Result};
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::{info, warn};
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload as log_reload, EnvFilter};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::config::Config;
+use crate::escrow::EscrowStore;
 use crate::identity::IdentityManager;
+use crate::lock_store::PgLockStore;
 use crate::locked::LockManager;
-use crate::wallet::{BdkWalletService, WalletService};
+use crate::reload::ConfigReloader;
+use crate::scheduler::Scheduler;
+use crate::shutdown::Shutdown;
+use crate::templates::TemplateStore;
+use crate::vault::Vault;
+use crate::wallet::{BdkWalletService, ElectrumPool, WalletService};
 
 /// Application state shared across handlers
 pub struct AppState {
@@ -33,7 +50,22 @@ pub struct AppState {
     pub bdk_wallet: Option<BdkWalletService>,
     pub lock_manager: LockManager,
     pub identity_manager: IdentityManager,
+    pub scheduler: Scheduler,
+    pub templates: TemplateStore,
+    pub escrows: EscrowStore,
+    /// Encryption of the state files at rest
+    pub vault: Arc<Vault>,
     pub config: Config,
+    pub portfolio_cache: handlers::PortfolioCache,
+    /// Per-UTXO asset classifications behind `/wallet/assets`
+    pub asset_scanner: handlers::AssetScanner,
+    pub reloader: ConfigReloader,
+    /// HTTP client for the app backends, proxied when `BACKENDS_PROXY` is set
+    pub http: reqwest::Client,
+    /// Signing daemon of the remote-signer profile
+    pub signer: Option<wallet::RemoteSigner>,
+    /// Stops the background workers on SIGTERM
+    pub shutdown: Shutdown,
 }
 
 #[derive(OpenApi)]
@@ -47,13 +79,43 @@ pub struct AppState {
     ),
     paths(
         handlers::health,
+        handlers::get_config,
+        handlers::reload_config,
         handlers::get_balance,
         handlers::get_new_address,
+        handlers::list_addresses,
+        handlers::list_reused_addresses,
+        handlers::get_history,
+        handlers::get_fee_advisory,
+        handlers::unlock_wallet,
+        handlers::lock_wallet,
+        handlers::get_encryption_status,
         handlers::list_utxos,
         handlers::list_utxos_unlocked,
         handlers::create_message,
+        handlers::schedule_message,
+        handlers::list_scheduled_messages,
+        handlers::cancel_scheduled_message,
+        handlers::list_templates,
+        handlers::get_template,
+        handlers::create_template,
+        handlers::update_template,
+        handlers::delete_template,
+        handlers::list_escrows,
+        handlers::get_escrow,
+        handlers::create_escrow,
+        handlers::fund_escrow,
+        handlers::dispute_escrow,
+        handlers::release_escrow,
+        handlers::refund_escrow,
+        handlers::broadcast_escrow,
         handlers::broadcast,
+        handlers::broadcast_package,
+        handlers::get_raw_tx,
         handlers::mine_blocks,
+        handlers::invalidate_block,
+        handlers::set_mocktime,
+        handlers::reorg,
         handlers::list_locked_utxos,
         handlers::lock_utxos,
         handlers::unlock_utxos,
@@ -64,26 +126,72 @@ pub struct AppState {
         handlers::get_assets,
         handlers::get_assets_domains,
         handlers::get_assets_tokens,
+        handlers::get_portfolio,
+        handlers::list_identities,
+        handlers::get_identity,
+        handlers::get_identity_defaults,
+        handlers::create_identity,
+        handlers::generate_keypair,
+        handlers::update_identity,
+        handlers::delete_identity,
+        handlers::set_identity_primary,
+        handlers::set_identity_dns,
+        handlers::remove_identity_dns,
+        handlers::sync_identities_from_dns,
+        handlers::sign_message,
+        handlers::verify_signature,
+        handlers::export_private_key,
         handlers::get_mnemonic,
         handlers::get_wallet_info,
         handlers::get_descriptors,
         handlers::verify_mnemonic,
         handlers::sync_bdk_wallet,
+        handlers::get_sync_status,
         handlers::get_bdk_balance,
+        handlers::get_bdk_address,
+        handlers::get_bdk_addresses,
         handlers::export_backup,
         handlers::verify_backup,
         handlers::get_migration_status,
     ),
     components(schemas(
+        anchor_api_error::ErrorBody,
+        anchor_api_error::ErrorCode,
         handlers::HealthResponse,
+        reload::RuntimeSettings,
+        reload::ReloadResponse,
         handlers::CreateMessageRequest,
         handlers::CreateMessageResponse,
         handlers::AnchorRef,
+        handlers::ScheduleMessageRequest,
+        scheduler::ScheduledMessage,
+        scheduler::ScheduleStatus,
+        templates::TransactionTemplate,
+        templates::FeePriority,
+        templates::ChangePolicy,
+        handlers::TemplateRequest,
+        handlers::CreateTemplateRequest,
+        escrow::Escrow,
+        escrow::EscrowStatus,
+        escrow::EscrowSettlement,
+        handlers::CreateEscrowRequest,
+        handlers::EscrowFeeRequest,
+        handlers::DisputeEscrowRequest,
+        handlers::BroadcastEscrowRequest,
+        handlers::EscrowTxResponse,
         handlers::AddressResponse,
         handlers::BroadcastRequest,
         handlers::BroadcastResponse,
+        handlers::BroadcastPackageRequest,
+        handlers::BroadcastPackageResponse,
         handlers::MineRequest,
         handlers::MineResponse,
+        handlers::InvalidateBlockRequest,
+        handlers::InvalidateBlockResponse,
+        handlers::SetMocktimeRequest,
+        handlers::SetMocktimeResponse,
+        handlers::ReorgRequest,
+        handlers::ReorgResponse,
         handlers::LockRequest,
         handlers::UnlockRequest,
         handlers::LockResponse,
@@ -98,8 +206,31 @@ pub struct AppState {
         handlers::AssetsOverview,
         handlers::DomainAsset,
         handlers::TokenAsset,
+        handlers::AssetScanSummary,
+        handlers::Portfolio,
+        handlers::PortfolioSummary,
+        handlers::TokenHolding,
+        handlers::SourceError,
+        handlers::HistoryResponse,
+        handlers::HistoryEntry,
+        handlers::HistoryMessage,
+        handlers::FeeAdvisoryResponse,
+        handlers::MempoolSummary,
+        handlers::FeeBucketResponse,
+        handlers::FeeEstimateResponse,
+        handlers::CarrierFeeAdvice,
+        handlers::RecentConfirmationsResponse,
         handlers::MnemonicResponse,
         handlers::WalletInfoResponse,
+        wallet::electrum_pool::ServerHealth,
+        wallet::bdk_service::SyncStatus,
+        wallet::addresses::ManagedAddress,
+        wallet::addresses::KeychainGap,
+        wallet::addresses::AddressReport,
+        wallet::addresses::ReusedAddress,
+        handlers::ReusedAddressesResponse,
+        handlers::UnlockWalletRequest,
+        vault::VaultStatus,
         handlers::DescriptorsResponse,
         handlers::VerifyMnemonicRequest,
         handlers::VerifyMnemonicResponse,
@@ -114,10 +245,14 @@ pub struct AppState {
         (name = "System", description = "System health endpoints"),
         (name = "Wallet", description = "Wallet operations"),
         (name = "ANCHOR", description = "ANCHOR message creation"),
+        (name = "Templates", description = "Named presets for message creation"),
+        (name = "Escrow", description = "2-of-3 multisig escrows for peer-to-peer trades"),
         (name = "Transactions", description = "Transaction operations"),
         (name = "Mining", description = "Block mining (regtest only)"),
+        (name = "Regtest", description = "Chain controls for integration tests (regtest only)"),
         (name = "Locks", description = "UTXO lock management"),
         (name = "Assets", description = "Asset aggregation and browsing"),
+        (name = "Identities", description = "Nostr and Pubky identities, signing, and DNS publishing"),
         (name = "Backup", description = "Wallet backup, mnemonic, and recovery"),
     )
 )]
@@ -125,35 +260,98 @@ struct ApiDoc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if anchor_api_error::dump_openapi::<ApiDoc>()? {
+        return Ok(());
+    }
+
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+    // Initialize logging (the filter can be swapped on config reload)
+    let (log_filter, log_handle) = log_reload::Layer::new(EnvFilter::from_default_env());
+    tracing_subscriber::registry()
+        .with(log_filter)
+        .with(fmt::layer())
         .init();
 
     info!("Starting ANCHOR Wallet Service");
 
     // Load configuration
     dotenvy::dotenv().ok();
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
+
+    // The remote-signer profile watches the signer's account
+    let signer = config
+        .signer
+        .as_ref()
+        .map(wallet::RemoteSigner::new)
+        .transpose()?;
+    if let Some(signer) = &signer {
+        info!("Remote-signer profile: signing via {}", signer.url());
+        if config.watch_only.is_none() {
+            let account = signer
+                .account()
+                .await
+                .context("Failed to fetch the account xpub from the signer")?;
+            info!(
+                "Watching signer account {} ({})",
+                account.derivation_path, account.fingerprint
+            );
+            let birthday = config.signer.as_ref().map_or(0, |s| s.birthday);
+            config.watch_only = Some(config::WatchOnly::parse(&account.xpub, birthday)?);
+        }
+    }
 
     // Create wallet service (Bitcoin Core RPC)
     let wallet = WalletService::new(&config)?;
     info!("Bitcoin Core wallet service initialized");
 
-    // Check and perform migration if needed
+    // Encryption of the state files; without a passphrase at startup the
+    // stores load on POST /wallet/unlock
+    let vault = Arc::new(Vault::new(
+        &config.data_dir,
+        config.data_encryption,
+        (config.relock_secs > 0).then(|| std::time::Duration::from_secs(config.relock_secs)),
+    ));
+    if let Some(passphrase) = config
+        .data_passphrase
+        .as_deref()
+        .filter(|_| vault.is_enabled())
+    {
+        vault
+            .unlock(passphrase)
+            .context("Failed to unlock wallet data")?;
+    } else if vault.is_enabled() {
+        info!("Wallet data is encrypted and locked until POST /wallet/unlock");
+    }
+
+    // Check and perform migration if needed (the watch-only profile never moves to BDK keys)
     let migrator = migration::WalletMigrator::new(config.data_dir.clone());
-    if let Ok(true) = migrator.check_and_migrate(&wallet, &config.wallet_name) {
-        info!("Wallet migration completed");
+    if config.watch_only.is_none() {
+        if let Ok(true) = migrator.check_and_migrate(&wallet, &config.wallet_name) {
+            info!("Wallet migration completed");
+        }
     }
 
-    // Create BDK wallet if enabled
-    let bdk_wallet = if config.bdk_enabled {
-        match BdkWalletService::new(
-            config.data_dir.join("bdk"),
-            &config.electrum_url,
-            config.get_network(),
-            config.bdk_password.clone(),
-        ) {
+    // Create BDK wallet if enabled; it generates and stores a mnemonic, so
+    // the watch-only profile runs on Bitcoin Core alone
+    let bdk_wallet = if config.watch_only.is_some() {
+        info!("Watch-only profile: BDK wallet disabled");
+        None
+    } else if config.bdk_enabled {
+        match ElectrumPool::new(
+            config.electrum_urls.clone(),
+            config.electrum_proxy.clone(),
+            config.electrum_onion_proxy.clone(),
+        )
+        .and_then(|electrum| {
+            BdkWalletService::new(
+                config.data_dir.join("bdk"),
+                Arc::new(electrum),
+                config.get_network(),
+                config.bdk_password.clone(),
+                vault.clone(),
+            )
+            .map(|bdk| bdk.with_gap_limit(config.bdk_gap_limit))
+        }) {
             Ok(bdk) => {
                 info!("BDK wallet service initialized");
                 // Sync wallet on startup
@@ -176,29 +374,96 @@ async fn main() -> Result<()> {
     };
 
     // Create lock manager
-    let lock_manager = LockManager::new(config.data_dir.clone())?;
+    let lock_manager = match &config.lock_store_url {
+        Some(url) => {
+            let store = PgLockStore::connect(url, &config.wallet_name, vault.clone()).await?;
+            LockManager::with_store(Box::new(store), vault.clone())?
+        }
+        None => LockManager::new(config.data_dir.clone(), vault.clone())?,
+    };
     info!("Lock manager initialized");
 
     // Create identity manager
-    let identity_manager = IdentityManager::new(config.data_dir.clone())?;
+    let identity_manager = IdentityManager::new(config.data_dir.clone(), vault.clone())?;
     info!("Identity manager initialized");
 
+    // Create message scheduler
+    let scheduler = Scheduler::new(config.data_dir.clone(), vault.clone())?;
+    info!("Message scheduler initialized");
+
+    // Create transaction template store
+    let templates = TemplateStore::new(config.data_dir.clone(), vault.clone())?;
+    info!("Transaction templates initialized");
+
+    // Create escrow store
+    let escrows = EscrowStore::new(config.data_dir.clone(), vault.clone())?;
+    info!("Escrow store initialized");
+
+    let http = proxy::http_client_builder(config.backends_proxy.as_ref())?
+        .build()
+        .context("Failed to build HTTP client")?;
+    if let Some(proxy) = &config.backends_proxy {
+        info!("App backends via SOCKS5 proxy {}", proxy.addr);
+    }
+
     // Create application state
     let state = Arc::new(AppState {
         wallet,
         bdk_wallet,
         lock_manager,
         identity_manager,
+        scheduler,
+        templates,
+        escrows,
+        vault: vault.clone(),
         config: config.clone(),
+        portfolio_cache: handlers::PortfolioCache::new(std::time::Duration::from_secs(
+            config.portfolio_cache_secs,
+        )),
+        asset_scanner: handlers::AssetScanner::default(),
+        reloader: ConfigReloader::new(config.clone(), log_handle),
+        http,
+        signer,
+        shutdown: Shutdown::default(),
     });
 
+    // Apply the settings file, then watch it for changes and SIGHUP
+    if let Err(e) = state.reloader.reload(&state.wallet) {
+        warn!("Ignoring settings file: {:#}", e);
+    }
+    reload::start_watcher(state.clone());
+
+    // Drop the data key once idle
+    vault::start_auto_relock(state.clone());
+
+    // Broadcast scheduled messages as their lock times pass
+    scheduler::start(state.clone());
+
+    // Keep the BDK wallet on a healthy Electrum server
+    if state.bdk_wallet.is_some() {
+        wallet::electrum_pool::start_health_checks(state.clone());
+        wallet::bdk_service::start_background_sync(state.clone());
+    }
+
     // Build router
+    let app_state = state.clone();
     let app = Router::new()
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .route("/health", get(handlers::health))
+        .route("/config", get(handlers::get_config))
+        .route("/config/reload", post(handlers::reload_config))
+        .route("/wallet/unlock", post(handlers::unlock_wallet))
+        .route("/wallet/lock", post(handlers::lock_wallet))
+        .route("/wallet/encryption", get(handlers::get_encryption_status))
         .route("/wallet/balance", get(handlers::get_balance))
         .route("/wallet/address", get(handlers::get_new_address))
         .route("/wallet/addresses", get(handlers::list_addresses))
+        .route(
+            "/wallet/addresses/reused",
+            get(handlers::list_reused_addresses),
+        )
+        .route("/wallet/history", get(handlers::get_history))
+        .route("/wallet/fee-advisory", get(handlers::get_fee_advisory))
         .route("/wallet/utxos", get(handlers::list_utxos))
         .route("/wallet/utxos/unlocked", get(handlers::list_utxos_unlocked))
         .route("/wallet/utxos/locked", get(handlers::list_locked_utxos))
@@ -211,12 +476,14 @@ async fn main() -> Result<()> {
         .route("/wallet/assets", get(handlers::get_assets))
         .route("/wallet/assets/domains", get(handlers::get_assets_domains))
         .route("/wallet/assets/tokens", get(handlers::get_assets_tokens))
+        .route("/wallet/portfolio", get(handlers::get_portfolio))
         // Backup endpoints
         .route("/wallet/backup/mnemonic", get(handlers::get_mnemonic))
         .route("/wallet/backup/info", get(handlers::get_wallet_info))
         .route("/wallet/backup/descriptors", get(handlers::get_descriptors))
         .route("/wallet/backup/verify", post(handlers::verify_mnemonic))
         .route("/wallet/backup/sync", post(handlers::sync_bdk_wallet))
+        .route("/wallet/sync-status", get(handlers::get_sync_status))
         .route("/wallet/backup/export", post(handlers::export_backup))
         .route(
             "/wallet/backup/verify-backup",
@@ -227,10 +494,51 @@ async fn main() -> Result<()> {
             get(handlers::get_migration_status),
         )
         .route("/wallet/bdk/balance", get(handlers::get_bdk_balance))
+        .route("/wallet/bdk/address", get(handlers::get_bdk_address))
+        .route("/wallet/bdk/addresses", get(handlers::get_bdk_addresses))
         .route("/wallet/create-message", post(handlers::create_message))
+        .route("/wallet/schedule-message", post(handlers::schedule_message))
+        .route("/wallet/scheduled", get(handlers::list_scheduled_messages))
+        .route(
+            "/wallet/templates",
+            get(handlers::list_templates).post(handlers::create_template),
+        )
+        .route(
+            "/wallet/templates/:name",
+            get(handlers::get_template)
+                .put(handlers::update_template)
+                .delete(handlers::delete_template),
+        )
+        .route(
+            "/wallet/escrow",
+            get(handlers::list_escrows).post(handlers::create_escrow),
+        )
+        .route("/wallet/escrow/:id", get(handlers::get_escrow))
+        .route("/wallet/escrow/:id/fund", post(handlers::fund_escrow))
+        .route("/wallet/escrow/:id/dispute", post(handlers::dispute_escrow))
+        .route("/wallet/escrow/:id/release", post(handlers::release_escrow))
+        .route("/wallet/escrow/:id/refund", post(handlers::refund_escrow))
+        .route(
+            "/wallet/escrow/:id/broadcast",
+            post(handlers::broadcast_escrow),
+        )
+        .route(
+            "/wallet/scheduled/:txid",
+            axum::routing::delete(handlers::cancel_scheduled_message),
+        )
         .route("/wallet/broadcast", post(handlers::broadcast))
+        .route(
+            "/wallet/broadcast-package",
+            post(handlers::broadcast_package),
+        )
         .route("/wallet/mine", post(handlers::mine_blocks))
         .route("/wallet/rawtx/:txid", get(handlers::get_raw_tx))
+        .route(
+            "/regtest/invalidate-block",
+            post(handlers::invalidate_block),
+        )
+        .route("/regtest/set-mocktime", post(handlers::set_mocktime))
+        .route("/regtest/reorg", post(handlers::reorg))
         // Identity endpoints
         .route("/wallet/identities", get(handlers::list_identities))
         .route("/wallet/identities", post(handlers::create_identity))
@@ -276,14 +584,14 @@ async fn main() -> Result<()> {
             "/wallet/identities/sync-dns",
             post(handlers::sync_identities_from_dns),
         )
-        .with_state(state)
+        .with_state(app_state)
+        .layer(middleware::from_fn_with_state(
+            vault,
+            vault::require_unlocked,
+        ))
+        .layer(middleware::map_response(anchor_api_error::normalize_errors))
         .layer(TraceLayer::new_for_http())
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        );
+        .layer(SecurityConfig::from_env().layer());
 
     info!(
         "Swagger UI available at http://localhost:{}/swagger-ui/",
@@ -295,7 +603,21 @@ async fn main() -> Result<()> {
     info!("Listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    // On SIGTERM, stop accepting connections and finish the requests in flight
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown::signal())
+        .await?;
+
+    // Then let the background workers finish and save what isn't written
+    // on every change
+    state.shutdown.trigger();
+    state.shutdown.wait_for_workers().await;
+    if let Some(bdk) = &state.bdk_wallet {
+        if let Err(e) = bdk.save_state() {
+            warn!("Failed to save BDK wallet state: {:#}", e);
+        }
+    }
+    info!("Wallet service stopped");
 
     Ok(())
 }
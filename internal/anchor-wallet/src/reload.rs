@@ -0,0 +1,266 @@
+//! Runtime configuration reload
+//!
+//! A JSON settings file (`CONFIG_FILE`, default `<data_dir>/config.json`)
+//! overlays the environment configuration for the settings that can change
+//! without a restart: fee defaults, carrier preference, the Bitcoin RPC
+//! endpoint, and the log filter. The file is re-read when it changes on disk,
+//! on SIGHUP, and on `POST /config/reload`. A reload is all-or-nothing: an
+//! invalid file or unreachable RPC endpoint leaves the running settings as is.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use utoipa::ToSchema;
+
+use crate::config::Config;
+use crate::wallet::WalletService;
+
+/// Handle used to swap the active log filter
+pub type LogHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Fee rate used when neither the request nor the file sets one
+const DEFAULT_FEE_RATE: u64 = 50; // sat/vbyte - higher for regtest compatibility
+
+/// Settings file contents; every field is optional and falls back to the env
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub bitcoin_rpc_url: Option<String>,
+    pub bitcoin_rpc_user: Option<String>,
+    pub bitcoin_rpc_password: Option<String>,
+    /// Fee rate used when a request does not specify one (sat/vbyte)
+    pub default_fee_rate: Option<u64>,
+    /// Requests above this fee rate are rejected (sat/vbyte)
+    pub max_fee_rate: Option<u64>,
+    /// Carrier used when a request does not specify one
+    pub default_carrier: Option<u8>,
+    /// Log filter directive, e.g. `info` or `anchor_wallet=debug`
+    pub log_level: Option<String>,
+}
+
+/// Settings currently in effect
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct RuntimeSettings {
+    pub bitcoin_rpc_url: String,
+    pub default_fee_rate: u64,
+    pub max_fee_rate: Option<u64>,
+    pub default_carrier: Option<u8>,
+    pub log_level: String,
+}
+
+/// Result of a reload
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReloadResponse {
+    /// Names of the settings that changed
+    pub changed: Vec<String>,
+    pub settings: RuntimeSettings,
+}
+
+/// Owns the effective settings and applies reloads
+pub struct ConfigReloader {
+    path: PathBuf,
+    base: Config,
+    log_handle: LogHandle,
+    settings: RwLock<RuntimeSettings>,
+    /// RPC credentials currently in use (url, user, password)
+    rpc: Mutex<(String, String, String)>,
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl ConfigReloader {
+    pub fn new(base: Config, log_handle: LogHandle) -> Self {
+        let path = std::env::var("CONFIG_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| base.data_dir.join("config.json"));
+        let settings = RuntimeSettings {
+            bitcoin_rpc_url: base.bitcoin_rpc_url.clone(),
+            default_fee_rate: DEFAULT_FEE_RATE,
+            max_fee_rate: None,
+            default_carrier: None,
+            log_level: env_log_level(),
+        };
+        let rpc = (
+            base.bitcoin_rpc_url.clone(),
+            base.bitcoin_rpc_user.clone(),
+            base.bitcoin_rpc_password.clone(),
+        );
+        Self {
+            path,
+            base,
+            log_handle,
+            settings: RwLock::new(settings),
+            rpc: Mutex::new(rpc),
+            last_modified: Mutex::new(None),
+        }
+    }
+
+    /// Settings currently in effect
+    pub fn settings(&self) -> RuntimeSettings {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Re-read the settings file and apply any changes
+    pub fn reload(&self, wallet: &WalletService) -> Result<ReloadResponse> {
+        let (file, modified) = self.read_file()?;
+
+        let filter = match &file.log_level {
+            Some(level) => EnvFilter::try_new(level)
+                .with_context(|| format!("Invalid log_level '{}'", level))?,
+            None => EnvFilter::from_default_env(),
+        };
+
+        let rpc = (
+            file.bitcoin_rpc_url
+                .clone()
+                .unwrap_or_else(|| self.base.bitcoin_rpc_url.clone()),
+            file.bitcoin_rpc_user
+                .clone()
+                .unwrap_or_else(|| self.base.bitcoin_rpc_user.clone()),
+            file.bitcoin_rpc_password
+                .clone()
+                .unwrap_or_else(|| self.base.bitcoin_rpc_password.clone()),
+        );
+        let mut current_rpc = self.rpc.lock().unwrap_or_else(|e| e.into_inner());
+        let rpc_changed = *current_rpc != rpc;
+        if rpc_changed {
+            wallet.reconnect(&rpc.0, &rpc.1, &rpc.2)?;
+            *current_rpc = rpc;
+        }
+
+        let new = RuntimeSettings {
+            bitcoin_rpc_url: current_rpc.0.clone(),
+            default_fee_rate: file.default_fee_rate.unwrap_or(DEFAULT_FEE_RATE),
+            max_fee_rate: file.max_fee_rate,
+            default_carrier: file.default_carrier,
+            log_level: file.log_level.clone().unwrap_or_else(env_log_level),
+        };
+        drop(current_rpc);
+
+        let mut settings = self.settings.write().unwrap_or_else(|e| e.into_inner());
+        let mut changed = Vec::new();
+        if rpc_changed {
+            changed.push("bitcoin_rpc".to_string());
+        }
+        if settings.default_fee_rate != new.default_fee_rate {
+            changed.push("default_fee_rate".to_string());
+        }
+        if settings.max_fee_rate != new.max_fee_rate {
+            changed.push("max_fee_rate".to_string());
+        }
+        if settings.default_carrier != new.default_carrier {
+            changed.push("default_carrier".to_string());
+        }
+        if settings.log_level != new.log_level {
+            self.log_handle
+                .reload(filter)
+                .context("Failed to swap log filter")?;
+            changed.push("log_level".to_string());
+        }
+        *settings = new.clone();
+        drop(settings);
+
+        *self.last_modified.lock().unwrap_or_else(|e| e.into_inner()) = modified;
+        if changed.is_empty() {
+            info!("Configuration reloaded from {:?}: no changes", self.path);
+        } else {
+            info!("Configuration reloaded from {:?}: {:?}", self.path, changed);
+        }
+
+        Ok(ReloadResponse {
+            changed,
+            settings: new,
+        })
+    }
+
+    /// Read the settings file; a missing file means no overrides
+    fn read_file(&self) -> Result<(ConfigFile, Option<SystemTime>)> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok((ConfigFile::default(), None))
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", self.path)),
+        };
+        let file = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid config file {:?}", self.path))?;
+        Ok((file, self.modified()))
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    /// Whether the file changed on disk since the last reload attempt
+    fn file_changed(&self) -> bool {
+        let mut last = self.last_modified.lock().unwrap_or_else(|e| e.into_inner());
+        let modified = self.modified();
+        let changed = modified != *last;
+        *last = modified;
+        changed
+    }
+}
+
+fn env_log_level() -> String {
+    std::env::var("RUST_LOG").unwrap_or_default()
+}
+
+/// Reload when the settings file changes or on SIGHUP
+pub fn start_watcher(state: Arc<crate::AppState>) {
+    let interval = std::env::var("CONFIG_WATCH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+    info!(
+        "Watching {:?} for configuration changes every {}s",
+        state.reloader.path, interval
+    );
+
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(signal) => Some(signal),
+            Err(e) => {
+                warn!("SIGHUP reload unavailable: {}", e);
+                None
+            }
+        };
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+
+        loop {
+            #[cfg(unix)]
+            let triggered = tokio::select! {
+                _ = ticker.tick() => state.reloader.file_changed(),
+                Some(_) = async {
+                    match hangup.as_mut() {
+                        Some(signal) => signal.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    info!("SIGHUP received, reloading configuration");
+                    true
+                }
+            };
+            #[cfg(not(unix))]
+            let triggered = {
+                ticker.tick().await;
+                state.reloader.file_changed()
+            };
+
+            if triggered {
+                if let Err(e) = state.reloader.reload(&state.wallet) {
+                    error!("Configuration reload failed: {:#}", e);
+                }
+            }
+        }
+    });
+}
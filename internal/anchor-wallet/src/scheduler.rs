@@ -0,0 +1,369 @@
+//! Message scheduler for Anchor Wallet
+//!
+//! Scheduled messages are signed when they are scheduled, with an nLockTime
+//! at the target block height or time, and broadcast by a background task
+//! once that lock time has passed. Their inputs are locked with the lock
+//! manager so nothing else spends them in the meantime.
+//!
+//! Cancelling only forgets the transaction: the signed hex was returned to
+//! the caller, so a dead-man switch that must never fire has to be revoked
+//! by spending one of its inputs.
+//!
+//! Scheduled messages are persisted to a JSON file, sealed by the vault when
+//! data encryption is enabled, and loaded on startup or unlock.
+
+use anyhow::{Context, Result};
+use bitcoin::absolute::LockTime;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::locked::LockReason;
+use crate::vault::Vault;
+use crate::wallet::TimelockedTransaction;
+
+/// Where a scheduled message is in its life
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ScheduleStatus {
+    /// Waiting for its lock time
+    Pending,
+    /// Accepted by the node
+    Broadcast { broadcast_at: DateTime<Utc> },
+    /// Rejected for a reason other than not being final yet
+    Failed { error: String },
+    /// Cancelled before broadcast
+    Cancelled,
+}
+
+/// A signed message waiting for its lock time
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScheduledMessage {
+    /// Transaction ID
+    pub txid: String,
+    /// Output carrying the ANCHOR message
+    pub vout: u32,
+    /// Signed transaction (hex)
+    pub hex: String,
+    /// nLockTime: a block height below 500,000,000, otherwise a Unix time
+    pub lock_time: u32,
+    /// Inputs held for the transaction ("txid", vout)
+    pub inputs: Vec<(String, u32)>,
+    /// When the message was scheduled
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub status: ScheduleStatus,
+}
+
+impl ScheduledMessage {
+    /// Whether the transaction can be mined in the block after the given tip
+    ///
+    /// Height locks need the next block to be at least the target height;
+    /// time locks are compared against the median time past (BIP-113).
+    pub fn is_final(&self, tip_height: u32, median_time: u32) -> bool {
+        match LockTime::from_consensus(self.lock_time) {
+            LockTime::Blocks(height) => tip_height + 1 >= height.to_consensus_u32(),
+            LockTime::Seconds(time) => median_time >= time.to_consensus_u32(),
+        }
+    }
+}
+
+/// Persisted scheduler state
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct ScheduleState {
+    messages: Vec<ScheduledMessage>,
+}
+
+/// Store of scheduled messages
+pub struct Scheduler {
+    /// Path to the schedule file
+    state_path: PathBuf,
+    /// Encryption of the schedule file
+    vault: Arc<Vault>,
+    /// In-memory state protected by RwLock
+    state: Arc<RwLock<ScheduleState>>,
+}
+
+impl Scheduler {
+    /// Create a new Scheduler with the given data directory
+    ///
+    /// If the vault is locked the state loads on `reload`, after unlocking.
+    pub fn new(data_dir: PathBuf, vault: Arc<Vault>) -> Result<Self> {
+        let state_path = data_dir.join("scheduled_messages.json");
+        fs::create_dir_all(&data_dir).context("Failed to create data directory")?;
+
+        let scheduler = Self {
+            state_path,
+            vault,
+            state: Arc::new(RwLock::new(ScheduleState::default())),
+        };
+        scheduler.load();
+        Ok(scheduler)
+    }
+
+    /// Load existing state or start fresh
+    fn load(&self) {
+        let state = match self.vault.read(&self.state_path) {
+            Ok(Some(content)) => match serde_json::from_str::<ScheduleState>(&content) {
+                Ok(state) => {
+                    info!(
+                        "Loaded {} scheduled messages from disk",
+                        state.messages.len()
+                    );
+                    state
+                }
+                Err(e) => {
+                    warn!("Failed to parse schedule, starting fresh: {}", e);
+                    ScheduleState::default()
+                }
+            },
+            Ok(None) => {
+                debug!("No existing schedule file, starting fresh");
+                ScheduleState::default()
+            }
+            Err(e) if self.vault.is_locked() => {
+                info!("Schedule is encrypted, loading it on unlock: {}", e);
+                ScheduleState::default()
+            }
+            Err(e) => {
+                warn!("Failed to read schedule, starting fresh: {}", e);
+                ScheduleState::default()
+            }
+        };
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    /// Re-read the state from disk, e.g. after unlocking the vault
+    pub fn reload(&self) -> Result<()> {
+        self.load();
+        self.save()
+    }
+
+    /// Save the current state to disk
+    fn save(&self) -> Result<()> {
+        let state = self
+            .state
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let content = serde_json::to_string_pretty(&*state)?;
+        self.vault
+            .write(&self.state_path, &content)
+            .context("Failed to write schedule")?;
+        Ok(())
+    }
+
+    /// Add a signed timelocked transaction
+    pub fn add(
+        &self,
+        created: &TimelockedTransaction,
+        lock_time: LockTime,
+    ) -> Result<ScheduledMessage> {
+        let message = ScheduledMessage {
+            txid: created.tx.txid.clone(),
+            vout: created.tx.anchor_vout,
+            hex: created.tx.hex.clone(),
+            lock_time: lock_time.to_consensus_u32(),
+            inputs: created.inputs.clone(),
+            created_at: Utc::now(),
+            status: ScheduleStatus::Pending,
+        };
+        self.state
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?
+            .messages
+            .push(message.clone());
+        self.save()?;
+
+        info!(
+            "Scheduled message {} for lock time {}",
+            message.txid, message.lock_time
+        );
+        Ok(message)
+    }
+
+    /// List all scheduled messages, newest first
+    pub fn list(&self) -> Vec<ScheduledMessage> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.messages.iter().rev().cloned().collect()
+    }
+
+    /// Messages still waiting for their lock time
+    pub fn pending(&self) -> Vec<ScheduledMessage> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state
+            .messages
+            .iter()
+            .filter(|m| m.status == ScheduleStatus::Pending)
+            .cloned()
+            .collect()
+    }
+
+    /// Move a pending message to a new status
+    ///
+    /// Returns the message if it was pending.
+    pub fn finish(&self, txid: &str, status: ScheduleStatus) -> Result<Option<ScheduledMessage>> {
+        let mut state = self
+            .state
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let Some(message) = state
+            .messages
+            .iter_mut()
+            .find(|m| m.txid == txid && m.status == ScheduleStatus::Pending)
+        else {
+            return Ok(None);
+        };
+        message.status = status;
+        let message = message.clone();
+
+        drop(state);
+        self.save()?;
+        Ok(Some(message))
+    }
+}
+
+/// Lock reason for the inputs of a scheduled message
+pub fn lock_reason(txid: &str) -> LockReason {
+    LockReason::Scheduled {
+        txid: txid.to_string(),
+    }
+}
+
+/// Start the background task that broadcasts messages once they are final
+pub fn start(state: Arc<crate::AppState>) {
+    let interval = std::env::var("SCHEDULER_POLL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    let mut stopping = state.shutdown.subscribe();
+
+    let worker = tokio::spawn({
+        let state = state.clone();
+        async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            loop {
+                // On shutdown, broadcast what is already due one last time
+                let stop = tokio::select! {
+                    _ = ticker.tick() => false,
+                    _ = stopping.wait_for(|stop| *stop) => true,
+                };
+                let state = state.clone();
+                let result = tokio::task::spawn_blocking(move || broadcast_due(&state)).await;
+                match result {
+                    Ok(Err(e)) => warn!("Scheduler tick failed: {:#}", e),
+                    Err(e) => warn!("Scheduler task panicked: {}", e),
+                    Ok(Ok(())) => {}
+                }
+                if stop {
+                    break;
+                }
+            }
+        }
+    });
+    state.shutdown.track(worker);
+}
+
+/// Broadcast every pending message whose lock time has passed
+fn broadcast_due(state: &crate::AppState) -> Result<()> {
+    let pending = state.scheduler.pending();
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let (height, median_time) = state.wallet.chain_tip()?;
+
+    for message in pending.iter().filter(|m| m.is_final(height, median_time)) {
+        let status = match state.wallet.broadcast(&message.hex) {
+            Ok(_) => {
+                info!("Broadcast scheduled message {}", message.txid);
+                ScheduleStatus::Broadcast {
+                    broadcast_at: Utc::now(),
+                }
+            }
+            Err(e) if format!("{:#}", e).contains("non-final") => {
+                // The node's view lags ours; try again next tick
+                debug!("Scheduled message {} is not final yet", message.txid);
+                continue;
+            }
+            Err(e) => {
+                warn!("Scheduled message {} was rejected: {:#}", message.txid, e);
+                ScheduleStatus::Failed {
+                    error: format!("{:#}", e),
+                }
+            }
+        };
+        if let Some(message) = state.scheduler.finish(&message.txid, status)? {
+            release(state, &message);
+        }
+    }
+    Ok(())
+}
+
+/// Drop the locks held for a message that is no longer pending
+pub fn release(state: &crate::AppState, message: &ScheduledMessage) {
+    for (txid, vout) in &message.inputs {
+        if let Err(e) =
+            state
+                .lock_manager
+                .unlock_if_reason(txid, *vout, &lock_reason(&message.txid))
+        {
+            warn!("Failed to unlock {}:{}: {}", txid, vout, e);
+        }
+    }
+    if let Err(e) = state.wallet.release_inputs(&message.inputs) {
+        debug!(
+            "Inputs of {} were not locked in the node: {}",
+            message.txid, e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(lock_time: u32) -> ScheduledMessage {
+        ScheduledMessage {
+            txid: "00".repeat(32),
+            vout: 0,
+            hex: String::new(),
+            lock_time,
+            inputs: vec![],
+            created_at: Utc::now(),
+            status: ScheduleStatus::Pending,
+        }
+    }
+
+    #[test]
+    fn test_height_lock_is_final() {
+        let scheduled = message(100);
+        assert!(!scheduled.is_final(98, 0));
+        assert!(scheduled.is_final(99, 0));
+        assert!(scheduled.is_final(150, 0));
+    }
+
+    #[test]
+    fn test_time_lock_uses_median_time() {
+        let scheduled = message(1_700_000_000);
+        assert!(!scheduled.is_final(u32::MAX - 1, 1_699_999_999));
+        assert!(scheduled.is_final(0, 1_700_000_000));
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let json = serde_json::to_value(message(100)).unwrap();
+        assert_eq!(json["status"], "pending");
+
+        let mut failed = message(100);
+        failed.status = ScheduleStatus::Failed {
+            error: "bad-txns-inputs-missingorspent".to_string(),
+        };
+        let json = serde_json::to_value(failed).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["error"], "bad-txns-inputs-missingorspent");
+    }
+}
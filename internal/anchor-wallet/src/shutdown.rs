@@ -0,0 +1,101 @@
+//! Graceful shutdown on SIGTERM
+//!
+//! Container restarts send SIGTERM (Ctrl-C when run by hand). The HTTP
+//! server stops accepting connections and finishes the requests in flight,
+//! while the background workers finish their current pass: the scheduler
+//! broadcasts the messages already due, and a BDK sync in progress completes.
+//! The BDK wallet state is then saved. Locks, identities, scheduled messages
+//! and templates are written on every change, so they need no flush.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// How long background workers get to finish their current pass
+const WORKER_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Stop signal for the background workers, and their tasks
+pub struct Shutdown {
+    stop: watch::Sender<bool>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            stop: watch::channel(false).0,
+            workers: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Shutdown {
+    /// A flag that turns true once shutdown starts
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.stop.subscribe()
+    }
+
+    /// Wait for this worker before the process exits
+    pub fn track(&self, worker: JoinHandle<()>) {
+        self.workers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(worker);
+    }
+
+    /// Tell the workers to stop after their current pass
+    pub fn trigger(&self) {
+        self.stop.send_replace(true);
+    }
+
+    /// Wait for the tracked workers, up to `WORKER_TIMEOUT`
+    pub async fn wait_for_workers(&self) {
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap_or_else(|e| e.into_inner()));
+        if workers.is_empty() {
+            return;
+        }
+        info!("Waiting for {} background workers", workers.len());
+        let all = async {
+            for worker in workers {
+                let _ = worker.await;
+            }
+        };
+        if tokio::time::timeout(WORKER_TIMEOUT, all).await.is_err() {
+            warn!(
+                "Background workers still busy after {}s, exiting anyway",
+                WORKER_TIMEOUT.as_secs()
+            );
+        }
+    }
+}
+
+/// Wait for SIGTERM or Ctrl-C
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(signal) => Some(signal),
+                Err(e) => {
+                    warn!("SIGTERM handler unavailable: {}", e);
+                    None
+                }
+            };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => info!("Ctrl-C received, shutting down"),
+            Some(_) = async {
+                match terminate.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => info!("SIGTERM received, shutting down"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Ctrl-C received, shutting down");
+    }
+}
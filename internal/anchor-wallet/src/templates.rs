@@ -0,0 +1,390 @@
+//! Transaction templates for Anchor Wallet
+//!
+//! A template is a named preset for `create-message`: a preferred carrier,
+//! a fee priority and a change policy. Requests reference it by name and
+//! anything they set explicitly wins over the template. Priorities other
+//! than a fixed rate are resolved through the fee advisory when the message
+//! is created, so a template stays sensible as the mempool changes.
+//!
+//! Templates are persisted to a JSON file, sealed by the vault when data
+//! encryption is enabled, and loaded on startup or unlock. A wallet without
+//! a template file starts with the built-in presets; they can be edited or
+//! deleted like any other template.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+
+use crate::vault::Vault;
+
+/// Longest template name
+const MAX_NAME_LEN: usize = 64;
+
+/// How fast a message should confirm
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "priority", rename_all = "snake_case")]
+pub enum FeePriority {
+    /// Within about a day (144 blocks)
+    Economy,
+    /// Within about an hour (6 blocks)
+    Normal,
+    /// In the next block
+    Fast,
+    /// A fixed rate in sat/vB
+    Fixed { fee_rate: u64 },
+}
+
+impl FeePriority {
+    /// Confirmation target of the priority, `None` for a fixed rate
+    pub fn target_blocks(&self) -> Option<u16> {
+        match self {
+            FeePriority::Economy => Some(144),
+            FeePriority::Normal => Some(6),
+            FeePriority::Fast => Some(1),
+            FeePriority::Fixed { .. } => None,
+        }
+    }
+}
+
+/// Where change goes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ChangePolicy {
+    /// A fresh wallet address for every transaction
+    Fresh,
+    /// Always the same address, e.g. a cold wallet
+    Address { address: String },
+}
+
+/// A named create-message preset
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransactionTemplate {
+    /// Lower-case letters, digits and dashes, e.g. `cheap-text-post`
+    pub name: String,
+    pub description: Option<String>,
+    /// Preferred carrier (0=op_return, 1=inscription, 2=stamps, 3=annex,
+    /// 4=witness); skipped for kinds that don't support it
+    pub carrier: Option<u8>,
+    #[serde(flatten)]
+    pub fee: FeePriority,
+    #[serde(flatten)]
+    pub change: ChangePolicy,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Persisted template state
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct TemplateState {
+    templates: Vec<TransactionTemplate>,
+}
+
+/// Store of transaction templates
+pub struct TemplateStore {
+    /// Path to the templates file
+    state_path: PathBuf,
+    /// Encryption of the templates file
+    vault: Arc<Vault>,
+    /// In-memory state protected by RwLock
+    state: Arc<RwLock<TemplateState>>,
+}
+
+impl TemplateStore {
+    /// Create a new TemplateStore with the given data directory
+    ///
+    /// If the vault is locked the state loads on `reload`, after unlocking.
+    pub fn new(data_dir: PathBuf, vault: Arc<Vault>) -> Result<Self> {
+        let state_path = data_dir.join("templates.json");
+        fs::create_dir_all(&data_dir).context("Failed to create data directory")?;
+
+        let store = Self {
+            state_path,
+            vault,
+            state: Arc::new(RwLock::new(TemplateState::default())),
+        };
+        store.load();
+        Ok(store)
+    }
+
+    /// Load existing state, or the built-in presets for a new wallet
+    fn load(&self) {
+        let state = match self.vault.read(&self.state_path) {
+            Ok(Some(content)) => match serde_json::from_str::<TemplateState>(&content) {
+                Ok(state) => {
+                    info!("Loaded {} transaction templates", state.templates.len());
+                    state
+                }
+                Err(e) => {
+                    warn!("Failed to parse templates, starting fresh: {}", e);
+                    TemplateState::default()
+                }
+            },
+            Ok(None) => {
+                debug!("No templates file, starting with the built-in presets");
+                TemplateState {
+                    templates: presets(),
+                }
+            }
+            Err(e) if self.vault.is_locked() => {
+                info!("Templates are encrypted, loading them on unlock: {}", e);
+                TemplateState::default()
+            }
+            Err(e) => {
+                warn!("Failed to read templates, starting fresh: {}", e);
+                TemplateState::default()
+            }
+        };
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    /// Re-read the state from disk, e.g. after unlocking the vault
+    pub fn reload(&self) -> Result<()> {
+        self.load();
+        self.save()
+    }
+
+    /// Save the current state to disk
+    fn save(&self) -> Result<()> {
+        let state = self
+            .state
+            .read()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let content = serde_json::to_string_pretty(&*state)?;
+        self.vault
+            .write(&self.state_path, &content)
+            .context("Failed to write templates")?;
+        Ok(())
+    }
+
+    /// All templates, by name
+    pub fn list(&self) -> Vec<TransactionTemplate> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let mut templates = state.templates.clone();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+
+    /// A template by name
+    pub fn get(&self, name: &str) -> Option<TransactionTemplate> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.templates.iter().find(|t| t.name == name).cloned()
+    }
+
+    /// Create or replace a template
+    ///
+    /// Returns the stored template and whether it replaced an existing one.
+    pub fn put(
+        &self,
+        name: &str,
+        description: Option<String>,
+        carrier: Option<u8>,
+        fee: FeePriority,
+        change: ChangePolicy,
+    ) -> Result<(TransactionTemplate, bool)> {
+        validate_name(name)?;
+        if let FeePriority::Fixed { fee_rate: 0 } = fee {
+            bail!("Fixed fee rate must be at least 1 sat/vB");
+        }
+
+        let mut state = self
+            .state
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let now = Utc::now();
+        let existing = state.templates.iter_mut().find(|t| t.name == name);
+        let replaced = existing.is_some();
+        let template = match existing {
+            Some(template) => {
+                template.description = description;
+                template.carrier = carrier;
+                template.fee = fee;
+                template.change = change;
+                template.updated_at = now;
+                template.clone()
+            }
+            None => {
+                let template = TransactionTemplate {
+                    name: name.to_string(),
+                    description,
+                    carrier,
+                    fee,
+                    change,
+                    created_at: now,
+                    updated_at: now,
+                };
+                state.templates.push(template.clone());
+                template
+            }
+        };
+
+        drop(state);
+        self.save()?;
+        info!("Saved transaction template '{}'", name);
+        Ok((template, replaced))
+    }
+
+    /// Delete a template; returns whether it existed
+    pub fn delete(&self, name: &str) -> Result<bool> {
+        let mut state = self
+            .state
+            .write()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let before = state.templates.len();
+        state.templates.retain(|t| t.name != name);
+        if state.templates.len() == before {
+            return Ok(false);
+        }
+
+        drop(state);
+        self.save()?;
+        info!("Deleted transaction template '{}'", name);
+        Ok(true)
+    }
+}
+
+/// Check a template name
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        bail!("Template name must be 1 to {} characters", MAX_NAME_LEN);
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        bail!("Template name may only contain lower-case letters, digits and dashes");
+    }
+    Ok(())
+}
+
+/// Templates a new wallet starts with
+fn presets() -> Vec<TransactionTemplate> {
+    let now = Utc::now();
+    let preset =
+        |name: &str, description: &str, carrier: u8, fee: FeePriority| TransactionTemplate {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            carrier: Some(carrier),
+            fee,
+            change: ChangePolicy::Fresh,
+            created_at: now,
+            updated_at: now,
+        };
+    vec![
+        preset(
+            "cheap-text-post",
+            "Small OP_RETURN post that can wait for a quiet mempool",
+            0,
+            FeePriority::Economy,
+        ),
+        preset(
+            "permanent-stamp",
+            "Unprunable Stamps message, confirmed within the hour",
+            2,
+            FeePriority::Normal,
+        ),
+        preset(
+            "domain-ops",
+            "Anchor Domains registrations and updates, confirmed quickly",
+            0,
+            FeePriority::Fast,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store(dir: &TempDir) -> TemplateStore {
+        let vault = Arc::new(Vault::new(dir.path(), false, None));
+        TemplateStore::new(dir.path().to_path_buf(), vault).unwrap()
+    }
+
+    #[test]
+    fn test_new_wallet_gets_presets() {
+        let dir = TempDir::new().unwrap();
+        let names: Vec<String> = store(&dir).list().into_iter().map(|t| t.name).collect();
+        assert_eq!(
+            names,
+            vec!["cheap-text-post", "domain-ops", "permanent-stamp"]
+        );
+    }
+
+    #[test]
+    fn test_put_delete_persist() {
+        let dir = TempDir::new().unwrap();
+        let templates = store(&dir);
+        let change = ChangePolicy::Address {
+            address: "bcrt1qexample".to_string(),
+        };
+        let (_, replaced) = templates
+            .put(
+                "cold",
+                None,
+                Some(0),
+                FeePriority::Fixed { fee_rate: 3 },
+                change.clone(),
+            )
+            .unwrap();
+        assert!(!replaced);
+        let (_, replaced) = templates
+            .put("cold", None, None, FeePriority::Fast, change.clone())
+            .unwrap();
+        assert!(replaced);
+        assert!(templates.delete("domain-ops").unwrap());
+        assert!(!templates.delete("domain-ops").unwrap());
+
+        // Deleted presets stay deleted once the file exists
+        let reloaded = store(&dir);
+        assert!(reloaded.get("domain-ops").is_none());
+        let cold = reloaded.get("cold").unwrap();
+        assert_eq!(cold.fee, FeePriority::Fast);
+        assert_eq!(cold.change, change);
+        assert_eq!(cold.carrier, None);
+    }
+
+    #[test]
+    fn test_validation() {
+        let dir = TempDir::new().unwrap();
+        let templates = store(&dir);
+        assert!(validate_name("Cheap Post").is_err());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("cheap-post-2").is_ok());
+        assert!(templates
+            .put(
+                "free",
+                None,
+                None,
+                FeePriority::Fixed { fee_rate: 0 },
+                ChangePolicy::Fresh
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let json = serde_json::to_value(&presets()[0]).unwrap();
+        assert_eq!(json["priority"], "economy");
+        assert_eq!(json["change"], "fresh");
+
+        let template: TransactionTemplate = serde_json::from_value(serde_json::json!({
+            "name": "fixed",
+            "description": null,
+            "carrier": 4,
+            "priority": "fixed",
+            "fee_rate": 12,
+            "change": "address",
+            "address": "bcrt1qexample",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert_eq!(template.fee, FeePriority::Fixed { fee_rate: 12 });
+    }
+}
@@ -0,0 +1,502 @@
+//! Encryption of wallet data at rest
+//!
+//! With `WALLET_DATA_ENCRYPTION=true`, the wallet's state files (BDK wallet
+//! state, UTXO locks, identities and scheduled messages) are sealed with
+//! AES-256-GCM under a key derived from a passphrase with Argon2id. The
+//! passphrase is set by the first `POST /wallet/unlock` (or
+//! `WALLET_DATA_PASSPHRASE` at startup) and checked against `vault.json` from
+//! then on.
+//!
+//! While locked, `/wallet/*` endpoints other than unlock, lock and the
+//! encryption status are refused, and sealed files can be neither read nor
+//! written. The key is dropped after `WALLET_RELOCK_SECS` without requests.
+//! Plaintext files left from before encryption was enabled are read as-is and
+//! sealed on their next write.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use anchor_api_error::{ApiError, ErrorCode};
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use zeroize::Zeroizing;
+
+/// Known plaintext sealed into `vault.json` to check passphrases
+const CHECK_PLAINTEXT: &[u8] = b"anchor-wallet-vault";
+
+/// Endpoints under `/wallet/` that work while locked
+const UNLOCKED_PATHS: &[&str] = &["/wallet/unlock", "/wallet/lock", "/wallet/encryption"];
+
+/// Errors callers need to tell apart
+#[derive(Debug, thiserror::Error)]
+pub enum VaultError {
+    #[error("Wallet data is locked")]
+    Locked,
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+    #[error("Data encryption is not enabled")]
+    Disabled,
+}
+
+/// A file sealed with the vault key
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SealedFile {
+    /// Format version
+    sealed: u32,
+    /// Nonce for AES-GCM (base64)
+    nonce: String,
+    /// Encrypted contents (base64)
+    ciphertext: String,
+}
+
+/// Key derivation parameters and passphrase check
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    /// Version of the file format
+    version: u32,
+    /// Salt for key derivation (base64)
+    salt: String,
+    /// `CHECK_PLAINTEXT` sealed with the key
+    check: SealedFile,
+    /// Creation timestamp
+    created_at: String,
+}
+
+struct UnlockedKey {
+    key: Zeroizing<[u8; 32]>,
+    last_used: Instant,
+}
+
+/// Encryption status
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VaultStatus {
+    /// Whether wallet data is encrypted at rest
+    pub enabled: bool,
+    /// Whether a passphrase has been set
+    pub initialized: bool,
+    /// Whether the key is unavailable
+    pub locked: bool,
+    /// Idle seconds before the key is dropped (0 never)
+    pub relock_secs: u64,
+    /// Seconds left before the key is dropped, while unlocked
+    pub relocks_in_secs: Option<u64>,
+}
+
+/// Passphrase-protected key for the wallet's state files
+pub struct Vault {
+    /// Path to `vault.json`
+    vault_path: PathBuf,
+    enabled: bool,
+    /// Idle time after which the key is dropped
+    relock_after: Option<Duration>,
+    key: Mutex<Option<UnlockedKey>>,
+}
+
+impl Vault {
+    /// Create a vault for `data_dir`; it starts locked
+    pub fn new(data_dir: &Path, enabled: bool, relock_after: Option<Duration>) -> Self {
+        Self {
+            vault_path: data_dir.join("vault.json"),
+            enabled,
+            relock_after,
+            key: Mutex::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether sealed files are unreadable and writes are refused
+    pub fn is_locked(&self) -> bool {
+        self.enabled && self.key().is_none()
+    }
+
+    /// Derive the key from `passphrase`, setting it if no passphrase is set yet
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        if !self.enabled {
+            return Err(VaultError::Disabled.into());
+        }
+
+        let key = if self.vault_path.exists() {
+            let content = fs::read_to_string(&self.vault_path)?;
+            let file: VaultFile = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid {}", self.vault_path.display()))?;
+            if file.version != 1 {
+                anyhow::bail!("Unsupported vault file version: {}", file.version);
+            }
+            let key = derive_key(passphrase, &BASE64.decode(&file.salt)?)?;
+            match open(&key, &file.check) {
+                Ok(check) if check == CHECK_PLAINTEXT => key,
+                _ => return Err(VaultError::WrongPassphrase.into()),
+            }
+        } else {
+            if passphrase.is_empty() {
+                anyhow::bail!("Passphrase must not be empty");
+            }
+            let mut salt = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut salt);
+            let key = derive_key(passphrase, &salt)?;
+            let file = VaultFile {
+                version: 1,
+                salt: BASE64.encode(salt),
+                check: seal(&key, CHECK_PLAINTEXT)?,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            };
+            if let Some(parent) = self.vault_path.parent() {
+                fs::create_dir_all(parent).context("Failed to create data directory")?;
+            }
+            fs::write(&self.vault_path, serde_json::to_string_pretty(&file)?)?;
+            info!("Set the wallet data passphrase");
+            key
+        };
+
+        *self.key.lock().unwrap_or_else(|e| e.into_inner()) = Some(UnlockedKey {
+            key,
+            last_used: Instant::now(),
+        });
+        info!("Wallet data unlocked");
+        Ok(())
+    }
+
+    /// Drop the key
+    pub fn lock(&self) {
+        if self
+            .key
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .is_some()
+        {
+            info!("Wallet data locked");
+        }
+    }
+
+    /// Restart the idle timer
+    pub fn touch(&self) {
+        if let Some(unlocked) = self.key.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            unlocked.last_used = Instant::now();
+        }
+    }
+
+    /// Drop the key if it has been idle too long; true if it was dropped
+    pub fn relock_if_idle(&self) -> bool {
+        let Some(relock_after) = self.relock_after else {
+            return false;
+        };
+        let mut key = self.key.lock().unwrap_or_else(|e| e.into_inner());
+        if key
+            .as_ref()
+            .is_some_and(|unlocked| unlocked.last_used.elapsed() >= relock_after)
+        {
+            *key = None;
+            info!("Wallet data locked after {}s idle", relock_after.as_secs());
+            return true;
+        }
+        false
+    }
+
+    pub fn status(&self) -> VaultStatus {
+        let relocks_in_secs = self.relock_after.and_then(|relock_after| {
+            let key = self.key.lock().unwrap_or_else(|e| e.into_inner());
+            key.as_ref().map(|unlocked| {
+                relock_after
+                    .saturating_sub(unlocked.last_used.elapsed())
+                    .as_secs()
+            })
+        });
+        VaultStatus {
+            enabled: self.enabled,
+            initialized: self.vault_path.exists(),
+            locked: self.is_locked(),
+            relock_secs: self.relock_after.map_or(0, |d| d.as_secs()),
+            relocks_in_secs: relocks_in_secs.filter(|_| self.enabled),
+        }
+    }
+
+    /// Read a state file; `None` if it doesn't exist
+    pub fn read(&self, path: &Path) -> Result<Option<String>> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let Ok(sealed) = serde_json::from_str::<SealedFile>(&content) else {
+            return Ok(Some(content));
+        };
+        let key = self.key().ok_or(VaultError::Locked)?;
+        let plaintext =
+            open(&key, &sealed).with_context(|| format!("Failed to decrypt {}", path.display()))?;
+        Ok(Some(String::from_utf8(plaintext)?))
+    }
+
+    /// Write a state file, sealed if encryption is enabled
+    pub fn write(&self, path: &Path, contents: &str) -> Result<()> {
+        if !self.enabled {
+            if is_sealed(path) {
+                anyhow::bail!(
+                    "{} is encrypted; set WALLET_DATA_ENCRYPTION=true to use it",
+                    path.display()
+                );
+            }
+            fs::write(path, contents)?;
+            return Ok(());
+        }
+        let key = self.key().ok_or(VaultError::Locked)?;
+        let sealed = seal(&key, contents.as_bytes())?;
+        fs::write(path, serde_json::to_string(&sealed)?)?;
+        Ok(())
+    }
+
+    /// Seal state kept outside the data dir, e.g. in a shared lock store
+    pub fn seal_str(&self, contents: &str) -> Result<String> {
+        if !self.enabled {
+            return Ok(contents.to_string());
+        }
+        let key = self.key().ok_or(VaultError::Locked)?;
+        Ok(serde_json::to_string(&seal(&key, contents.as_bytes())?)?)
+    }
+
+    /// Open state written by [`seal_str`](Self::seal_str); plaintext passes through
+    pub fn open_str(&self, content: &str) -> Result<String> {
+        let Ok(sealed) = serde_json::from_str::<SealedFile>(content) else {
+            return Ok(content.to_string());
+        };
+        let key = self.key().ok_or(VaultError::Locked)?;
+        Ok(String::from_utf8(open(&key, &sealed)?)?)
+    }
+
+    /// The key, unless locked or idle too long
+    fn key(&self) -> Option<Zeroizing<[u8; 32]>> {
+        self.relock_if_idle();
+        self.key
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|unlocked| unlocked.key.clone())
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| anyhow::anyhow!("Failed to derive key: {:?}", e))?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], plaintext: &[u8]) -> Result<SealedFile> {
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| anyhow::anyhow!("Failed to create cipher: {:?}", e))?;
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt: {:?}", e))?;
+    Ok(SealedFile {
+        sealed: 1,
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn open(key: &[u8; 32], file: &SealedFile) -> Result<Vec<u8>> {
+    if file.sealed != 1 {
+        anyhow::bail!("Unsupported sealed file version: {}", file.sealed);
+    }
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| anyhow::anyhow!("Failed to create cipher: {:?}", e))?;
+    let nonce = BASE64.decode(&file.nonce)?;
+    if nonce.len() != 12 {
+        anyhow::bail!("Invalid nonce length: {}", nonce.len());
+    }
+    cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            BASE64.decode(&file.ciphertext)?.as_ref(),
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt (wrong key or corrupted file)"))
+}
+
+fn is_sealed(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .ok()
+        .is_some_and(|content| serde_json::from_str::<SealedFile>(&content).is_ok())
+}
+
+/// Refuse `/wallet/*` requests while locked, and keep the key alive otherwise
+pub async fn require_unlocked(
+    State(vault): State<Arc<Vault>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path();
+    if vault.is_enabled() && path.starts_with("/wallet/") && !UNLOCKED_PATHS.contains(&path) {
+        if vault.is_locked() {
+            return ApiError::new(
+                ErrorCode::Forbidden,
+                "Wallet data is locked; unlock it with POST /wallet/unlock",
+            )
+            .into_response();
+        }
+        vault.touch();
+    }
+    next.run(req).await
+}
+
+/// Drop the key once it has been idle for `WALLET_RELOCK_SECS`, even without
+/// requests to notice
+pub fn start_auto_relock(state: Arc<crate::AppState>) {
+    if !state.vault.is_enabled() || state.config.relock_secs == 0 {
+        return;
+    }
+    info!(
+        "Relocking wallet data after {}s idle",
+        state.config.relock_secs
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(10));
+        loop {
+            ticker.tick().await;
+            state.vault.relock_if_idle();
+        }
+    });
+}
+
+/// Re-read every store after unlocking, sealing files that were plaintext
+pub fn reload_stores(state: &crate::AppState) -> Result<()> {
+    state.lock_manager.reload()?;
+    state.identity_manager.reload()?;
+    state.scheduler.reload()?;
+    state.templates.reload()?;
+    state.escrows.reload()?;
+    if let Some(bdk) = &state.bdk_wallet {
+        if let Err(e) = bdk.restore_state() {
+            warn!("Failed to restore BDK wallet state: {:#}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn encrypted_vault(dir: &TempDir, relock_after: Option<Duration>) -> Vault {
+        Vault::new(dir.path(), true, relock_after)
+    }
+
+    #[test]
+    fn test_write_read_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let vault = encrypted_vault(&dir, None);
+        vault.unlock("correct horse").unwrap();
+
+        let path = dir.path().join("state.json");
+        vault.write(&path, r#"{"descriptor":"wpkh(...)"}"#).unwrap();
+
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("descriptor"));
+        assert_eq!(
+            vault.read(&path).unwrap().as_deref(),
+            Some(r#"{"descriptor":"wpkh(...)"}"#)
+        );
+    }
+
+    #[test]
+    fn test_locked_refuses_sealed_files() {
+        let dir = TempDir::new().unwrap();
+        let vault = encrypted_vault(&dir, None);
+        assert!(vault.is_locked());
+        let path = dir.path().join("state.json");
+        assert!(vault.write(&path, "{}").is_err());
+
+        vault.unlock("pass").unwrap();
+        vault.write(&path, "{}").unwrap();
+        vault.lock();
+
+        let err = vault.read(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VaultError>(),
+            Some(VaultError::Locked)
+        ));
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let dir = TempDir::new().unwrap();
+        encrypted_vault(&dir, None).unlock("first").unwrap();
+
+        let vault = encrypted_vault(&dir, None);
+        let err = vault.unlock("second").unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VaultError>(),
+            Some(VaultError::WrongPassphrase)
+        ));
+        assert!(vault.is_locked());
+        vault.unlock("first").unwrap();
+        assert!(!vault.is_locked());
+    }
+
+    #[test]
+    fn test_plaintext_files_are_read_and_sealed_on_write() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        fs::write(&path, r#"{"locked_utxos":[]}"#).unwrap();
+
+        let vault = encrypted_vault(&dir, None);
+        vault.unlock("pass").unwrap();
+        let content = vault.read(&path).unwrap().unwrap();
+        assert_eq!(content, r#"{"locked_utxos":[]}"#);
+
+        vault.write(&path, &content).unwrap();
+        assert!(is_sealed(&path));
+    }
+
+    #[test]
+    fn test_disabled_vault_is_plaintext_and_keeps_sealed_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+
+        let disabled = Vault::new(dir.path(), false, None);
+        assert!(!disabled.is_locked());
+        disabled.write(&path, "{}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        let vault = encrypted_vault(&dir, None);
+        vault.unlock("pass").unwrap();
+        vault.write(&path, "{}").unwrap();
+        assert!(disabled.write(&path, "{}").is_err());
+    }
+
+    #[test]
+    fn test_relock_after_idle() {
+        let dir = TempDir::new().unwrap();
+        let vault = encrypted_vault(&dir, Some(Duration::ZERO));
+        vault.unlock("pass").unwrap();
+        assert!(vault.is_locked());
+
+        let vault = encrypted_vault(&dir, Some(Duration::from_secs(3600)));
+        vault.unlock("pass").unwrap();
+        assert!(!vault.relock_if_idle());
+        assert!(!vault.is_locked());
+    }
+}
@@ -0,0 +1,267 @@
+//! Address management for the BDK wallet
+//!
+//! Every operation gets a fresh receive or change address, as long as that
+//! keeps the run of unused addresses within the gap limit. Recovery scans stop
+//! after `gap_limit` unused addresses in a row, so past the limit the manager
+//! rotates through the unused addresses it already handed out instead of
+//! deriving new ones. Addresses that received funds more than once are flagged
+//! as reused.
+
+use anyhow::Result;
+use bdk_wallet::{KeychainKind, Wallet};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::bdk_service::BdkWalletService;
+
+/// BIP-44 address gap limit
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// A wallet address and how it has been used
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ManagedAddress {
+    pub address: String,
+    /// `receive` or `change`
+    pub keychain: String,
+    /// Derivation index within the keychain
+    pub index: u32,
+    /// Transactions that paid to the address
+    pub tx_count: usize,
+    /// Whether the address has received funds
+    pub used: bool,
+    /// Whether the address received funds in more than one transaction
+    pub reused: bool,
+}
+
+/// An address that received funds in more than one transaction
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReusedAddress {
+    pub address: String,
+    /// Transactions that paid to the address
+    pub tx_count: usize,
+}
+
+/// Gap limit state of a keychain
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct KeychainGap {
+    /// Highest derivation index handed out
+    pub last_revealed: Option<u32>,
+    /// Highest derivation index that received funds
+    pub last_used: Option<u32>,
+    /// Unused addresses after the last used one
+    pub unused_gap: u32,
+    /// Whether new requests reuse unused addresses instead of deriving new ones
+    pub at_limit: bool,
+}
+
+/// Address usage of the BDK wallet
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AddressReport {
+    pub gap_limit: u32,
+    pub receive: KeychainGap,
+    pub change: KeychainGap,
+    /// Every revealed address, receive keychain first
+    pub addresses: Vec<ManagedAddress>,
+    /// Addresses that received funds more than once
+    pub reused: Vec<ReusedAddress>,
+}
+
+/// Where the next address for a keychain comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handout {
+    /// Derive the next address
+    Reveal,
+    /// Hand out the already revealed, unused address at this index
+    Rotate(u32),
+}
+
+impl BdkWalletService {
+    /// A fresh receiving address
+    pub fn next_receive_address(&self) -> Result<ManagedAddress> {
+        self.next_address(KeychainKind::External)
+    }
+
+    /// A fresh change address
+    pub fn next_change_address(&self) -> Result<ManagedAddress> {
+        self.next_address(KeychainKind::Internal)
+    }
+
+    fn next_address(&self, keychain: KeychainKind) -> Result<ManagedAddress> {
+        let mut wallet = self.lock_wallet()?;
+        let index = wallet.spk_index();
+        let last_revealed = index.last_revealed_index(keychain);
+        let last_used = index.last_used_index(keychain);
+
+        let mut cursors = self
+            .address_cursors
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let cursor = &mut cursors[keychain as usize];
+        let info = match handout(last_revealed, last_used, self.gap_limit, *cursor) {
+            Handout::Reveal => wallet.reveal_next_address(keychain),
+            Handout::Rotate(index) => {
+                *cursor = cursor.wrapping_add(1);
+                warn!(
+                    "{} keychain at the gap limit of {}, reusing unused address {}",
+                    keychain_name(keychain),
+                    self.gap_limit,
+                    index
+                );
+                wallet.peek_address(keychain, index)
+            }
+        };
+        drop(cursors);
+        let tx_counts = tx_counts(&wallet);
+        drop(wallet);
+        self.save_state()?;
+
+        Ok(managed_address(
+            keychain,
+            info.index,
+            info.address.to_string(),
+            &tx_counts,
+        ))
+    }
+
+    /// Gap limit state and usage of every revealed address
+    pub fn address_report(&self) -> Result<AddressReport> {
+        let wallet = self.lock_wallet()?;
+        let tx_counts = tx_counts(&wallet);
+
+        let mut addresses = Vec::new();
+        for keychain in [KeychainKind::External, KeychainKind::Internal] {
+            for (index, _) in wallet.spk_index().revealed_keychain_spks(keychain) {
+                let address = wallet.peek_address(keychain, index).address.to_string();
+                addresses.push(managed_address(keychain, index, address, &tx_counts));
+            }
+        }
+        let reused = addresses
+            .iter()
+            .filter(|a| a.reused)
+            .map(|a| ReusedAddress {
+                address: a.address.clone(),
+                tx_count: a.tx_count,
+            })
+            .collect();
+
+        Ok(AddressReport {
+            gap_limit: self.gap_limit,
+            receive: self.keychain_gap(&wallet, KeychainKind::External),
+            change: self.keychain_gap(&wallet, KeychainKind::Internal),
+            addresses,
+            reused,
+        })
+    }
+
+    fn keychain_gap(&self, wallet: &Wallet, keychain: KeychainKind) -> KeychainGap {
+        let index = wallet.spk_index();
+        let last_revealed = index.last_revealed_index(keychain);
+        let last_used = index.last_used_index(keychain);
+        let unused_gap = unused_gap(last_revealed, last_used);
+        KeychainGap {
+            last_revealed,
+            last_used,
+            unused_gap,
+            at_limit: unused_gap >= self.gap_limit,
+        }
+    }
+}
+
+fn keychain_name(keychain: KeychainKind) -> &'static str {
+    match keychain {
+        KeychainKind::External => "receive",
+        KeychainKind::Internal => "change",
+    }
+}
+
+fn managed_address(
+    keychain: KeychainKind,
+    index: u32,
+    address: String,
+    tx_counts: &BTreeMap<(KeychainKind, u32), usize>,
+) -> ManagedAddress {
+    let tx_count = tx_counts.get(&(keychain, index)).copied().unwrap_or(0);
+    ManagedAddress {
+        address,
+        keychain: keychain_name(keychain).to_string(),
+        index,
+        tx_count,
+        used: tx_count > 0,
+        reused: tx_count > 1,
+    }
+}
+
+/// Distinct transactions paying to each derivation index
+fn tx_counts(wallet: &Wallet) -> BTreeMap<(KeychainKind, u32), usize> {
+    let mut txids: BTreeMap<(KeychainKind, u32), BTreeSet<_>> = BTreeMap::new();
+    for output in wallet.list_output() {
+        txids
+            .entry((output.keychain, output.derivation_index))
+            .or_default()
+            .insert(output.outpoint.txid);
+    }
+    txids
+        .into_iter()
+        .map(|(key, txids)| (key, txids.len()))
+        .collect()
+}
+
+/// Unused addresses revealed after the last used one
+fn unused_gap(last_revealed: Option<u32>, last_used: Option<u32>) -> u32 {
+    match (last_revealed, last_used) {
+        (None, _) => 0,
+        (Some(revealed), None) => revealed + 1,
+        (Some(revealed), Some(used)) => revealed.saturating_sub(used),
+    }
+}
+
+/// Pick the next address: a new one while the unused gap is below the limit,
+/// otherwise the unused addresses after the last used one in turn
+fn handout(
+    last_revealed: Option<u32>,
+    last_used: Option<u32>,
+    gap_limit: u32,
+    cursor: u32,
+) -> Handout {
+    let gap = unused_gap(last_revealed, last_used);
+    if gap < gap_limit {
+        return Handout::Reveal;
+    }
+    let first = last_used.map_or(0, |used| used + 1);
+    Handout::Rotate(first + cursor % gap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_gap() {
+        assert_eq!(unused_gap(None, None), 0);
+        assert_eq!(unused_gap(Some(0), None), 1);
+        assert_eq!(unused_gap(Some(4), None), 5);
+        assert_eq!(unused_gap(Some(4), Some(4)), 0);
+        assert_eq!(unused_gap(Some(9), Some(4)), 5);
+    }
+
+    #[test]
+    fn test_handout_reveals_below_limit() {
+        assert_eq!(handout(None, None, 20, 0), Handout::Reveal);
+        assert_eq!(handout(Some(18), None, 20, 0), Handout::Reveal);
+        assert_eq!(handout(Some(30), Some(11), 20, 0), Handout::Reveal);
+    }
+
+    #[test]
+    fn test_handout_rotates_at_limit() {
+        // Indices 0-19 revealed, none used
+        assert_eq!(handout(Some(19), None, 20, 0), Handout::Rotate(0));
+        assert_eq!(handout(Some(19), None, 20, 21), Handout::Rotate(1));
+
+        // Indices 6-8 unused after index 5 was used
+        assert_eq!(handout(Some(8), Some(5), 3, 0), Handout::Rotate(6));
+        assert_eq!(handout(Some(8), Some(5), 3, 2), Handout::Rotate(8));
+        assert_eq!(handout(Some(8), Some(5), 3, 3), Handout::Rotate(6));
+    }
+}
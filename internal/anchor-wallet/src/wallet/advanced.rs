@@ -1,21 +1,20 @@
 //! Advanced transaction creation with required inputs and custom outputs
 
 use anyhow::{Context, Result};
-use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
 use bitcoin::taproot::{LeafVersion, TaprootBuilder};
 use bitcoin::transaction::Version;
 use bitcoin::{
     absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
     Witness,
 };
-use std::collections::HashSet;
 use std::str::FromStr;
 use tracing::{debug, info, warn};
 
 use anchor_core::{AnchorKind, AnchorMessageBuilder};
 
 use super::service::WalletService;
-use super::types::CreatedTransaction;
+use super::types::{CoinControl, CreatedTransaction};
 use super::utils::extract_op_return_data;
 
 impl WalletService {
@@ -62,7 +61,7 @@ impl WalletService {
         fee_rate: u64,
         required_inputs: Vec<(String, u32)>,
         custom_outputs: Vec<(String, u64)>,
-        locked_set: Option<&HashSet<(String, u32)>>,
+        coins: Option<&CoinControl>,
     ) -> Result<CreatedTransaction> {
         // Ensure wallet is loaded before proceeding
         if !self.ensure_wallet_loaded() {
@@ -79,7 +78,7 @@ impl WalletService {
                 additional_anchors,
                 carrier,
                 fee_rate,
-                locked_set,
+                coins,
             );
         }
 
@@ -129,7 +128,8 @@ impl WalletService {
             match carrier_impl.encode(&message) {
                 Ok(output) => {
                     match output {
-                        CarrierOutput::WitnessData { chunks: _, script } => {
+                        CarrierOutput::WitnessData { chunks: _, scripts } => {
+                            let script = single_witness_script(scripts)?;
                             debug!("Creating advanced WitnessData transaction with {} required inputs and {} outputs", 
                                    required_inputs.len(), custom_outputs.len());
                             self.create_and_broadcast_advanced_witness_tx(
@@ -137,7 +137,7 @@ impl WalletService {
                                 fee_rate,
                                 required_inputs,
                                 custom_outputs,
-                                locked_set,
+                                coins,
                             )
                         }
                         CarrierOutput::OpReturn(script) => {
@@ -148,21 +148,22 @@ impl WalletService {
                                 fee_rate,
                                 required_inputs,
                                 custom_outputs,
+                                coins,
                             )
                         }
                         _ => {
                             // For other carriers, fall back to WitnessData with advanced options
                             let fallback_carrier =
                                 selector.get_carrier(CarrierType::WitnessData).unwrap();
-                            if let Ok(CarrierOutput::WitnessData { chunks: _, script }) =
+                            if let Ok(CarrierOutput::WitnessData { chunks: _, scripts }) =
                                 fallback_carrier.encode(&message)
                             {
                                 self.create_and_broadcast_advanced_witness_tx(
-                                    script,
+                                    single_witness_script(scripts)?,
                                     fee_rate,
                                     required_inputs,
                                     custom_outputs,
-                                    locked_set,
+                                    coins,
                                 )
                             } else {
                                 anyhow::bail!("Failed to encode message for advanced transaction");
@@ -170,9 +171,7 @@ impl WalletService {
                         }
                     }
                 }
-                Err(e) => {
-                    anyhow::bail!("Carrier encode failed: {}", e);
-                }
+                Err(e) => Err(anyhow::Error::new(e).context("Carrier encode failed")),
             }
         } else {
             anyhow::bail!("Carrier not available");
@@ -187,9 +186,9 @@ impl WalletService {
         fee_rate: u64,
         required_inputs: Vec<(String, u32)>,
         custom_outputs: Vec<(String, u64)>,
+        coins: Option<&CoinControl>,
     ) -> Result<CreatedTransaction> {
-        let change_address = self.rpc.get_new_address(None, None)?;
-        let change_address = change_address.assume_checked();
+        let change_address = self.change_address(coins)?;
         let fee_rate_btc_kb = fee_rate as f64 * 0.00001;
 
         // Build inputs array
@@ -214,27 +213,29 @@ impl WalletService {
 
         use bitcoincore_rpc::RpcApi;
 
-        let raw_tx: String = self.rpc.call(
+        let raw_tx: String = self.rpc().call(
             "createrawtransaction",
             &[serde_json::json!(inputs), serde_json::json!(outputs_map)],
         )?;
 
         // Fund the transaction (will add additional inputs if needed and change output)
-        let funded: serde_json::Value = self.rpc.call(
-            "fundrawtransaction",
-            &[
-                serde_json::json!(raw_tx),
-                serde_json::json!({
-                    "changeAddress": change_address.to_string(),
-                    "feeRate": fee_rate_btc_kb,
-                }),
-            ],
-        )?;
+        let funded: serde_json::Value = self.with_coin_control(coins, &required_inputs, || {
+            Ok(self.rpc().call(
+                "fundrawtransaction",
+                &[
+                    serde_json::json!(raw_tx),
+                    serde_json::json!({
+                        "changeAddress": change_address.to_string(),
+                        "feeRate": fee_rate_btc_kb,
+                    }),
+                ],
+            )?)
+        })?;
 
         let funded_hex = funded["hex"].as_str().context("No hex in funded tx")?;
 
         // Sign the transaction
-        let signed: serde_json::Value = self.rpc.call(
+        let signed: serde_json::Value = self.rpc().call(
             "signrawtransactionwithwallet",
             &[serde_json::json!(funded_hex)],
         )?;
@@ -248,12 +249,12 @@ impl WalletService {
 
         // Broadcast
         let txid: String = self
-            .rpc
+            .rpc()
             .call("sendrawtransaction", &[serde_json::json!(signed_hex)])?;
 
         // Find the OP_RETURN output index
         let decoded: serde_json::Value = self
-            .rpc
+            .rpc()
             .call("decoderawtransaction", &[serde_json::json!(signed_hex)])?;
         let vouts = decoded["vout"].as_array().context("No vouts")?;
         let anchor_vout = vouts
@@ -278,7 +279,7 @@ impl WalletService {
         fee_rate: u64,
         required_inputs: Vec<(String, u32)>,
         custom_outputs: Vec<(String, u64)>,
-        locked_set: Option<&HashSet<(String, u32)>>,
+        coins: Option<&CoinControl>,
     ) -> Result<CreatedTransaction> {
         // Acquire the transaction creation mutex to prevent race conditions
         let _tx_guard = self
@@ -324,7 +325,7 @@ impl WalletService {
 
         // Step 1: Create commit transaction
         let required = commit_amount + commit_fee + 1000;
-        let utxos = self.list_unspent_unlocked(Some(1), locked_set)?;
+        let utxos = self.list_unspent_unlocked(Some(1), coins)?;
         if utxos.is_empty() {
             anyhow::bail!("No UTXOs available for advanced witness tx (all may be locked)");
         }
@@ -363,8 +364,7 @@ impl WalletService {
 
         use bitcoincore_rpc::RpcApi;
 
-        let change_address = self.rpc.get_new_address(None, None)?;
-        let change_script = change_address.assume_checked().script_pubkey();
+        let change_script = self.change_address(coins)?.script_pubkey();
 
         let change_value = total_input - commit_amount - commit_fee;
         let commit_outputs = vec![
@@ -388,7 +388,7 @@ impl WalletService {
         let commit_hex = serialize_hex(&commit_tx);
 
         // Sign commit transaction
-        let signed_commit: serde_json::Value = self.rpc.call(
+        let signed_commit: serde_json::Value = self.rpc().call(
             "signrawtransactionwithwallet",
             &[serde_json::json!(commit_hex)],
         )?;
@@ -401,17 +401,13 @@ impl WalletService {
             .as_str()
             .context("No hex in signed commit")?;
 
-        // Broadcast commit
-        let commit_txid: String = self.rpc.call(
-            "sendrawtransaction",
-            &[serde_json::json!(signed_commit_hex)],
-        )?;
-        info!("Broadcast advanced witness commit tx: {}", commit_txid);
-
-        let commit_txid_parsed = Txid::from_str(&commit_txid)?;
+        // The commit goes out together with the reveal
+        let commit_txid_parsed = deserialize_hex::<Transaction>(signed_commit_hex)
+            .context("Invalid signed commit")?
+            .compute_txid();
 
         // Step 2: Create reveal transaction with token inputs and custom outputs
-        let token_change_address = self.rpc.get_new_address(None, None)?;
+        let token_change_address = self.rpc().get_raw_change_address(None)?;
         let token_change_script = token_change_address.assume_checked().script_pubkey();
 
         // Build reveal inputs: first the commit output, then the token UTXOs
@@ -460,8 +456,7 @@ impl WalletService {
         }
 
         // Add BTC change output
-        let btc_change_address = self.rpc.get_new_address(None, None)?;
-        let btc_change_script = btc_change_address.assume_checked().script_pubkey();
+        let btc_change_script = self.change_address(coins)?.script_pubkey();
         let btc_change_value = commit_amount - reveal_fee - total_output_value;
         if btc_change_value > 546 {
             reveal_outputs.push(TxOut {
@@ -492,7 +487,7 @@ impl WalletService {
         let reveal_hex = serialize_hex(&reveal_tx);
 
         // Sign the remaining inputs (token UTXOs) with the wallet
-        let signed_reveal: serde_json::Value = self.rpc.call(
+        let signed_reveal: serde_json::Value = self.rpc().call(
             "signrawtransactionwithwallet",
             &[serde_json::json!(reveal_hex)],
         )?;
@@ -513,15 +508,13 @@ impl WalletService {
             .as_str()
             .context("No hex in signed reveal")?;
 
-        // Broadcast reveal transaction
-        let reveal_txid: String = self.rpc.call(
-            "sendrawtransaction",
-            &[serde_json::json!(signed_reveal_hex)],
-        )?;
+        // Broadcast commit and reveal
+        let sent = self.broadcast_package(signed_commit_hex, signed_reveal_hex)?;
+        let reveal_txid = sent.child_txid;
 
         info!(
-            "Broadcast advanced witness reveal tx: {} (commit: {})",
-            reveal_txid, commit_txid
+            "Broadcast advanced witness reveal tx: {} (commit: {}, package: {})",
+            reveal_txid, sent.parent_txid, sent.packaged
         );
 
         Ok(CreatedTransaction {
@@ -533,3 +526,17 @@ impl WalletService {
         })
     }
 }
+
+/// The data script of a single-part WitnessData payload
+///
+/// Advanced transactions reveal through one input alongside the required
+/// inputs, so multi-part payloads are rejected.
+fn single_witness_script(mut scripts: Vec<ScriptBuf>) -> Result<ScriptBuf> {
+    match scripts.len() {
+        1 => Ok(scripts.remove(0)),
+        n => anyhow::bail!(
+            "Payload needs {} witness parts; advanced transactions support a single part",
+            n
+        ),
+    }
+}
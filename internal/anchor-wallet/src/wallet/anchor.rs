@@ -2,20 +2,16 @@
 
 use anyhow::{Context, Result};
 use bitcoin::Txid;
-use std::collections::HashSet;
 use std::str::FromStr;
 use tracing::debug;
 
 use anchor_core::{AnchorKind, AnchorMessageBuilder};
 
 use super::service::WalletService;
-use super::types::CreatedTransaction;
+use super::types::{CoinControl, CreatedTransaction};
 
 impl WalletService {
     /// Create and broadcast an ANCHOR message transaction
-    ///
-    /// # Arguments
-    /// * `locked_set` - Optional set of locked UTXOs to exclude from coin selection
     #[allow(clippy::too_many_arguments)]
     pub fn create_anchor_transaction(
         &self,
@@ -42,7 +38,7 @@ impl WalletService {
     /// Create and broadcast an ANCHOR message transaction with lock awareness
     ///
     /// # Arguments
-    /// * `locked_set` - Optional set of locked UTXOs to exclude from coin selection
+    /// * `coins` - Optional coin control: locked or excluded UTXOs, allowed inputs, change address
     #[allow(clippy::too_many_arguments)]
     pub fn create_anchor_transaction_with_locks(
         &self,
@@ -53,7 +49,7 @@ impl WalletService {
         additional_anchors: Vec<(String, u8)>,
         carrier: Option<u8>,
         fee_rate: u64,
-        locked_set: Option<&HashSet<(String, u32)>>,
+        coins: Option<&CoinControl>,
     ) -> Result<CreatedTransaction> {
         // Ensure wallet is loaded before proceeding
         if !self.ensure_wallet_loaded() {
@@ -108,7 +104,7 @@ impl WalletService {
                     CarrierOutput::OpReturn(script) => {
                         debug!("Created ANCHOR OP_RETURN script: {} bytes", script.len());
                         super::carriers::op_return::create_and_broadcast_tx_with_script(
-                            self, script, 0, fee_rate,
+                            self, script, 0, fee_rate, coins,
                         )
                     }
                     CarrierOutput::Stamps(scripts) => {
@@ -117,7 +113,7 @@ impl WalletService {
                             scripts.len()
                         );
                         super::carriers::stamps::create_and_broadcast_stamps_tx(
-                            self, scripts, fee_rate, locked_set,
+                            self, scripts, fee_rate, coins,
                         )
                     }
                     CarrierOutput::Inscription {
@@ -129,7 +125,7 @@ impl WalletService {
                             self,
                             reveal_script,
                             fee_rate,
-                            locked_set,
+                            coins,
                         )
                     }
                     CarrierOutput::Annex(annex_data) => {
@@ -138,16 +134,17 @@ impl WalletService {
                             annex_data.len()
                         );
                         super::carriers::annex::create_and_broadcast_annex_tx(
-                            self, annex_data, fee_rate, locked_set,
+                            self, annex_data, fee_rate, coins,
                         )
                     }
-                    CarrierOutput::WitnessData { chunks: _, script } => {
+                    CarrierOutput::WitnessData { chunks: _, scripts } => {
                         debug!(
-                            "Creating WitnessData transaction with script {} bytes",
-                            script.len()
+                            "Creating WitnessData transaction with {} script(s), {} bytes",
+                            scripts.len(),
+                            scripts.iter().map(|s| s.len()).sum::<usize>()
                         );
                         super::carriers::witness::create_and_broadcast_witness_data_tx(
-                            self, script, fee_rate, locked_set,
+                            self, scripts, fee_rate, coins,
                         )
                     }
                 },
@@ -159,6 +156,7 @@ impl WalletService {
                         anchor_script,
                         0,
                         fee_rate,
+                        coins,
                     )
                 }
             }
@@ -170,6 +168,7 @@ impl WalletService {
                 anchor_script,
                 0,
                 fee_rate,
+                coins,
             )
         }
     }
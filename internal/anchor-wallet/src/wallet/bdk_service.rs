@@ -6,12 +6,10 @@
 //! Features:
 //! - Mnemonic generation and storage
 //! - Address derivation (BIP84 Native SegWit)
-//! - Electrum-based UTXO tracking
+//! - Electrum-based UTXO tracking, with periodic background syncs
 //! - Encrypted mnemonic storage
 
 use anyhow::{Context, Result};
-use bdk_electrum::electrum_client::{self, ElectrumApi};
-use bdk_electrum::BdkElectrumClient;
 use bdk_wallet::{
     bitcoin::Network,
     keys::{
@@ -19,14 +17,19 @@ use bdk_wallet::{
         DerivableKey, ExtendedKey,
     },
     template::Bip84,
-    KeychainKind, Wallet,
+    KeychainKind, Update, Wallet,
 };
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use tracing::{info, warn};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
+use super::addresses::DEFAULT_GAP_LIMIT;
+use super::electrum_pool::ElectrumPool;
 use super::types::{Balance, Utxo};
+use crate::vault::Vault;
 
 /// Encrypted mnemonic storage format
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -77,12 +80,58 @@ pub struct WalletInfo {
     pub addresses_used: u32,
 }
 
+/// Kind of wallet sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncMode {
+    /// Scan every keychain up to the stop gap
+    FullScan,
+    /// Check only revealed scripts
+    Incremental,
+}
+
+impl SyncMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::FullScan => "full_scan",
+            Self::Incremental => "incremental",
+        }
+    }
+}
+
+/// Progress of the current or last wallet sync
+#[derive(Debug, Clone, Default, serde::Serialize, utoipa::ToSchema)]
+pub struct SyncStatus {
+    /// Whether a sync is running
+    pub syncing: bool,
+    /// Kind of the current or last sync: `full_scan` or `incremental`
+    pub mode: Option<String>,
+    /// Scripts checked so far
+    pub scanned_scripts: usize,
+    /// Scripts the sync will check; unknown for full scans, which stop at
+    /// the gap limit
+    pub total_scripts: Option<usize>,
+    /// Progress of the current or last sync, 0-100
+    pub progress_percent: f64,
+    /// Block height the wallet has synced to
+    pub scanned_height: u32,
+    /// Chain tip of the active Electrum server
+    pub chain_height: Option<u32>,
+    /// When the last successful sync finished (RFC 3339)
+    pub last_sync_at: Option<String>,
+    /// When the last successful full scan finished (RFC 3339)
+    pub last_full_scan_at: Option<String>,
+    /// Duration of the last sync
+    pub last_duration_ms: Option<u64>,
+    /// Error from the last sync, if it failed
+    pub last_error: Option<String>,
+}
+
 /// BDK-based wallet service with full key management
 pub struct BdkWalletService {
     /// The BDK wallet instance
     wallet: Arc<Mutex<Wallet>>,
-    /// Electrum client for blockchain queries
-    electrum_client: Arc<BdkElectrumClient<electrum_client::Client>>,
+    /// Electrum servers for blockchain queries
+    electrum: Arc<ElectrumPool>,
     /// Cached mnemonic (only available if we created the wallet)
     mnemonic: Option<Mnemonic>,
     /// Network
@@ -91,6 +140,20 @@ pub struct BdkWalletService {
     data_dir: PathBuf,
     /// Current chain tip height
     chain_tip: Arc<Mutex<u32>>,
+    /// Held for the duration of a sync
+    sync_guard: Mutex<()>,
+    /// Progress of the current or last sync
+    sync_status: Arc<Mutex<SyncStatus>>,
+    /// When the last full scan finished
+    last_full_scan: Mutex<Option<Instant>>,
+    /// Unused addresses in a row before new requests reuse unused ones, and
+    /// the stop gap of full scans
+    pub(super) gap_limit: u32,
+    /// Rotation position among unused addresses per keychain, for requests
+    /// past the gap limit
+    pub(super) address_cursors: Mutex<[u32; 2]>,
+    /// Encryption of the wallet state file
+    vault: Arc<Vault>,
 }
 
 impl BdkWalletService {
@@ -100,22 +163,24 @@ impl BdkWalletService {
     /// If it exists, loads from the persistent store.
     pub fn new(
         data_dir: PathBuf,
-        electrum_url: &str,
+        electrum: Arc<ElectrumPool>,
         network: Network,
         password: Option<String>,
+        vault: Arc<Vault>,
     ) -> Result<Self> {
         info!("Initializing BDK wallet service...");
 
         // Ensure data directory exists
         fs::create_dir_all(&data_dir).context("Failed to create data directory")?;
 
-        // Initialize Electrum client
-        let electrum_client = Self::create_electrum_client(electrum_url)?;
-        let chain_tip = electrum_client.block_headers_subscribe()?.height as u32;
+        // Pick an Electrum server
+        if electrum.active_url().is_none() {
+            electrum.connect()?;
+        }
+        let chain_tip = electrum.active_height().unwrap_or_default();
 
         // Check if wallet exists
         let mnemonic_path = data_dir.join("mnemonic.enc");
-        let state_path = data_dir.join("wallet_state.json");
 
         let (wallet, mnemonic) = if mnemonic_path.exists() {
             // Load existing wallet from mnemonic
@@ -129,32 +194,7 @@ impl BdkWalletService {
                 Self::load_encrypted_mnemonic(&mnemonic_path, password.as_ref().unwrap())?;
             let wallet = Self::create_wallet_from_mnemonic(&mnemonic, network)?;
 
-            // Load state if available
-            if state_path.exists() {
-                if let Ok(content) = fs::read_to_string(&state_path) {
-                    if let Ok(state) = serde_json::from_str::<WalletState>(&content) {
-                        // Reveal addresses up to the stored indices
-                        let mut w = wallet;
-                        for _ in 0..state.last_external_index {
-                            w.reveal_next_address(KeychainKind::External);
-                        }
-                        for _ in 0..state.last_internal_index {
-                            w.reveal_next_address(KeychainKind::Internal);
-                        }
-                        info!(
-                            "Restored wallet state: {} external, {} internal addresses",
-                            state.last_external_index, state.last_internal_index
-                        );
-                        (w, Some(mnemonic))
-                    } else {
-                        (wallet, Some(mnemonic))
-                    }
-                } else {
-                    (wallet, Some(mnemonic))
-                }
-            } else {
-                (wallet, Some(mnemonic))
-            }
+            (wallet, Some(mnemonic))
         } else {
             // Create new wallet with fresh mnemonic
             info!("Creating new BDK wallet");
@@ -173,77 +213,27 @@ impl BdkWalletService {
             (wallet, Some(mnemonic))
         };
 
-        let bdk_electrum = BdkElectrumClient::new(electrum_client);
-
         let service = Self {
             wallet: Arc::new(Mutex::new(wallet)),
-            electrum_client: Arc::new(bdk_electrum),
+            electrum,
             mnemonic,
             network,
             data_dir,
             chain_tip: Arc::new(Mutex::new(chain_tip)),
+            sync_guard: Mutex::new(()),
+            sync_status: Arc::new(Mutex::new(SyncStatus::default())),
+            last_full_scan: Mutex::new(None),
+            gap_limit: DEFAULT_GAP_LIMIT,
+            address_cursors: Mutex::new([0; 2]),
+            vault,
         };
-
-        Ok(service)
-    }
-
-    /// Create Electrum client with retries and timeout
-    fn create_electrum_client(url: &str) -> Result<electrum_client::Client> {
-        info!("Connecting to Electrum server: {}", url);
-
-        // Retry connection up to 12 times (2 minutes total)
-        let max_retries = 12;
-        let retry_delay = std::time::Duration::from_secs(10);
-        let connection_timeout = std::time::Duration::from_secs(5);
-
-        for attempt in 1..=max_retries {
-            // Use a channel to implement connection timeout
-            let url_clone = url.to_string();
-            let (tx, rx) = std::sync::mpsc::channel();
-
-            std::thread::spawn(move || {
-                let result = electrum_client::Client::new(&url_clone).map(|c| {
-                    // Also test the connection
-                    c.block_headers_subscribe().map(|h| (c, h))
-                });
-                let _ = tx.send(result);
-            });
-
-            match rx.recv_timeout(connection_timeout) {
-                Ok(Ok(Ok((client, header)))) => {
-                    info!("Connected to Electrum, chain tip height: {}", header.height);
-                    return Ok(client);
-                }
-                Ok(Ok(Err(e))) => {
-                    warn!(
-                        "Electrum connection test failed (attempt {}/{}): {}",
-                        attempt, max_retries, e
-                    );
-                }
-                Ok(Err(e)) => {
-                    warn!(
-                        "Failed to connect to Electrum (attempt {}/{}): {}",
-                        attempt, max_retries, e
-                    );
-                }
-                Err(_) => {
-                    warn!(
-                        "Electrum connection timed out (attempt {}/{})",
-                        attempt, max_retries
-                    );
-                }
-            }
-
-            if attempt < max_retries {
-                info!("Retrying Electrum connection in 10s...");
-                std::thread::sleep(retry_delay);
+        if !service.vault.is_locked() {
+            if let Err(e) = service.restore_state() {
+                warn!("Failed to restore BDK wallet state: {:#}", e);
             }
         }
 
-        anyhow::bail!(
-            "Failed to connect to Electrum server after {} attempts",
-            max_retries
-        )
+        Ok(service)
     }
 
     /// Create a new wallet from mnemonic
@@ -272,8 +262,51 @@ impl BdkWalletService {
         Ok(wallet)
     }
 
-    /// Save wallet state to file
-    fn save_state(&self) -> Result<()> {
+    /// Set the address gap limit (default 20)
+    pub fn with_gap_limit(mut self, gap_limit: u32) -> Self {
+        self.gap_limit = gap_limit.max(1);
+        self
+    }
+
+    /// Reveal addresses up to the indices in the state file
+    ///
+    /// Only ever reveals more addresses, so indices handed out while the
+    /// vault was locked are kept.
+    pub fn restore_state(&self) -> Result<()> {
+        let state_path = self.data_dir.join("wallet_state.json");
+        let Some(content) = self.vault.read(&state_path)? else {
+            return Ok(());
+        };
+        let state: WalletState = match serde_json::from_str(&content) {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Ignoring unreadable BDK wallet state: {}", e);
+                return Ok(());
+            }
+        };
+
+        let mut wallet = self.lock_wallet()?;
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::External, state.last_external_index)
+            .count();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::Internal, state.last_internal_index)
+            .count();
+        info!(
+            "Restored wallet state: {} external, {} internal addresses",
+            state.last_external_index, state.last_internal_index
+        );
+        drop(wallet);
+        self.save_state()
+    }
+
+    /// Save wallet state to file; skipped while the vault is locked, as
+    /// `restore_state` merges the indices on unlock
+    pub(crate) fn save_state(&self) -> Result<()> {
+        if self.vault.is_locked() {
+            debug!("Wallet data locked, not saving BDK wallet state");
+            return Ok(());
+        }
         let wallet = self
             .wallet
             .lock()
@@ -287,7 +320,7 @@ impl BdkWalletService {
 
         let state_path = self.data_dir.join("wallet_state.json");
         let content = serde_json::to_string_pretty(&state)?;
-        fs::write(&state_path, content)?;
+        self.vault.write(&state_path, &content)?;
 
         Ok(())
     }
@@ -393,21 +426,160 @@ impl BdkWalletService {
         Ok(mnemonic)
     }
 
-    /// Sync wallet with Electrum server
+    /// Sync wallet with Electrum server, scanning each keychain up to the gap limit
+    ///
+    /// Finds funds on addresses the wallet has not revealed yet, e.g. after a
+    /// restore; `sync_incremental` is cheaper for routine updates.
     pub fn sync(&self) -> Result<()> {
-        info!("Syncing wallet with Electrum...");
+        self.run_sync(SyncMode::FullScan)
+    }
 
-        let mut wallet = self
-            .wallet
+    /// Sync only the scripts the wallet has already revealed
+    pub fn sync_incremental(&self) -> Result<()> {
+        self.run_sync(SyncMode::Incremental)
+    }
+
+    /// Incremental sync unless another sync is running; a full scan instead
+    /// once `full_scan_every` has passed since the last one
+    ///
+    /// Returns `Ok(false)` when skipped.
+    pub fn sync_in_background(&self, full_scan_every: Duration) -> Result<bool> {
+        let Ok(guard) = self.sync_guard.try_lock() else {
+            return Ok(false);
+        };
+        let full_scan_due = self
+            .last_full_scan
             .lock()
-            .map_err(|e| anyhow::anyhow!("Failed to lock wallet: {}", e))?;
+            .map_err(|e| anyhow::anyhow!("Failed to lock sync status: {}", e))?
+            .is_none_or(|at| at.elapsed() >= full_scan_every);
+        let mode = if full_scan_due {
+            SyncMode::FullScan
+        } else {
+            SyncMode::Incremental
+        };
+        self.sync_locked(mode, guard)?;
+        Ok(true)
+    }
+
+    /// Progress of the current or last sync
+    pub fn sync_status(&self) -> SyncStatus {
+        let mut status = self
+            .sync_status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        status.scanned_height = self.get_chain_tip();
+        status.chain_height = self.electrum.active_height();
+        status
+    }
 
-        // Full scan
-        let request = wallet.start_full_scan();
-        let update = self.electrum_client.full_scan(request, 10, 10, false)?;
+    fn run_sync(&self, mode: SyncMode) -> Result<()> {
+        let guard = self
+            .sync_guard
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock sync: {}", e))?;
+        self.sync_locked(mode, guard)
+    }
+
+    fn sync_locked(&self, mode: SyncMode, _guard: MutexGuard<'_, ()>) -> Result<()> {
+        info!("Syncing wallet with Electrum ({})...", mode.as_str());
+        let started = Instant::now();
+        self.update_sync_status(|status| {
+            status.syncing = true;
+            status.mode = Some(mode.as_str().to_string());
+            status.scanned_scripts = 0;
+            status.total_scripts = None;
+            status.progress_percent = 0.0;
+        });
+
+        let result = self.fetch_and_apply(mode);
+
+        let finished_at = chrono::Utc::now().to_rfc3339();
+        self.update_sync_status(|status| {
+            status.syncing = false;
+            status.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+            match &result {
+                Ok(()) => {
+                    status.progress_percent = 100.0;
+                    status.last_sync_at = Some(finished_at.clone());
+                    if mode == SyncMode::FullScan {
+                        status.last_full_scan_at = Some(finished_at);
+                    }
+                    status.last_error = None;
+                }
+                Err(e) => status.last_error = Some(format!("{:#}", e)),
+            }
+        });
+        if result.is_ok() && mode == SyncMode::FullScan {
+            *self
+                .last_full_scan
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        }
+        result?;
+
+        info!("Wallet sync complete");
+        Ok(())
+    }
+
+    /// Fetch an update for `mode` and apply it; the wallet is only locked
+    /// while building the request and applying the result
+    fn fetch_and_apply(&self, mode: SyncMode) -> Result<()> {
+        let update: Update = match mode {
+            SyncMode::FullScan => {
+                let request = || -> Result<_> {
+                    let status = self.sync_status.clone();
+                    Ok(self
+                        .lock_wallet()?
+                        .start_full_scan()
+                        .inspect(move |_, _, _| {
+                            let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+                            status.scanned_scripts += 1;
+                        }))
+                };
+                match self.electrum.client()?.full_scan(
+                    request()?,
+                    self.gap_limit as usize,
+                    10,
+                    false,
+                ) {
+                    Ok(update) => update.into(),
+                    Err(e) => {
+                        // Retry once on the next best server
+                        let client = self.electrum.failover(&e.into())?;
+                        client
+                            .full_scan(request()?, self.gap_limit as usize, 10, false)?
+                            .into()
+                    }
+                }
+            }
+            SyncMode::Incremental => {
+                let request = || -> Result<_> {
+                    let status = self.sync_status.clone();
+                    Ok(self.lock_wallet()?.start_sync_with_revealed_spks().inspect(
+                        move |_, progress| {
+                            let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+                            status.scanned_scripts = progress.consumed();
+                            status.total_scripts = Some(progress.total());
+                            if progress.total() > 0 {
+                                status.progress_percent =
+                                    progress.consumed() as f64 * 100.0 / progress.total() as f64;
+                            }
+                        },
+                    ))
+                };
+                match self.electrum.client()?.sync(request()?, 10, false) {
+                    Ok(update) => update.into(),
+                    Err(e) => {
+                        let client = self.electrum.failover(&e.into())?;
+                        client.sync(request()?, 10, false)?.into()
+                    }
+                }
+            }
+        };
 
         // Update chain tip
-        if let Some(tip) = update.chain_update.as_ref() {
+        if let Some(tip) = update.chain.as_ref() {
             let mut chain_tip = self
                 .chain_tip
                 .lock()
@@ -415,48 +587,50 @@ impl BdkWalletService {
             *chain_tip = tip.height();
         }
 
-        wallet.apply_update(update)?;
+        self.lock_wallet()?.apply_update(update)?;
 
         // Save state after sync
-        drop(wallet);
-        self.save_state()?;
+        self.save_state()
+    }
 
-        info!("Wallet sync complete");
-        Ok(())
+    pub(super) fn lock_wallet(&self) -> Result<MutexGuard<'_, Wallet>> {
+        self.wallet
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Failed to lock wallet: {}", e))
     }
 
-    /// Get wallet balance
-    pub fn get_balance(&self) -> Result<Balance> {
+    fn update_sync_status(&self, update: impl FnOnce(&mut SyncStatus)) {
+        update(&mut self.sync_status.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Get wallet balance; `locked_set` UTXOs count as locked, not spendable
+    pub fn get_balance(&self, locked_set: &HashSet<(String, u32)>) -> Result<Balance> {
         let wallet = self
             .wallet
             .lock()
             .map_err(|e| anyhow::anyhow!("Failed to lock wallet: {}", e))?;
 
         let balance = wallet.balance();
-
-        Ok(Balance {
-            confirmed: balance.confirmed.to_sat() as f64 / 100_000_000.0,
-            unconfirmed: (balance.untrusted_pending.to_sat() + balance.trusted_pending.to_sat())
-                as f64
-                / 100_000_000.0,
-            total: balance.total().to_sat() as f64 / 100_000_000.0,
-        })
+        let locked = wallet
+            .list_unspent()
+            .filter(|utxo| utxo.chain_position.is_confirmed())
+            .filter(|utxo| {
+                locked_set.contains(&(utxo.outpoint.txid.to_string(), utxo.outpoint.vout))
+            })
+            .map(|utxo| utxo.txout.value.to_sat())
+            .sum();
+
+        Ok(Balance::from_sats(
+            balance.confirmed.to_sat() + balance.trusted_pending.to_sat(),
+            balance.untrusted_pending.to_sat(),
+            balance.immature.to_sat(),
+            locked,
+        ))
     }
 
-    /// Get a new receiving address
+    /// Get a new receiving address, within the gap limit
     pub fn get_new_address(&self) -> Result<String> {
-        let mut wallet = self
-            .wallet
-            .lock()
-            .map_err(|e| anyhow::anyhow!("Failed to lock wallet: {}", e))?;
-
-        let address_info = wallet.reveal_next_address(KeychainKind::External);
-
-        // Save state after revealing new address
-        drop(wallet);
-        self.save_state()?;
-
-        Ok(address_info.address.to_string())
+        Ok(self.next_receive_address()?.address)
     }
 
     /// Peek at the next address without revealing it
@@ -616,6 +790,11 @@ impl BdkWalletService {
         *self.chain_tip.lock().unwrap_or_else(|e| e.into_inner())
     }
 
+    /// Electrum servers used for syncing
+    pub fn electrum(&self) -> &ElectrumPool {
+        &self.electrum
+    }
+
     /// Check if wallet file exists
     pub fn wallet_exists(data_dir: &std::path::Path) -> bool {
         data_dir.join("mnemonic.enc").exists()
@@ -624,10 +803,11 @@ impl BdkWalletService {
     /// Restore wallet from mnemonic
     pub fn restore_from_mnemonic(
         data_dir: PathBuf,
-        electrum_url: &str,
+        electrum: Arc<ElectrumPool>,
         network: Network,
         mnemonic_words: &str,
         password: &str,
+        vault: Arc<Vault>,
     ) -> Result<Self> {
         info!("Restoring wallet from mnemonic...");
 
@@ -643,10 +823,57 @@ impl BdkWalletService {
         Self::save_encrypted_mnemonic(&mnemonic_path, &mnemonic, password, network)?;
 
         // Create the wallet
-        Self::new(data_dir, electrum_url, network, Some(password.to_string()))
+        Self::new(
+            data_dir,
+            electrum,
+            network,
+            Some(password.to_string()),
+            vault,
+        )
     }
 }
 
+/// Sync the BDK wallet every `BDK_SYNC_INTERVAL_SECS` (0 disables)
+///
+/// Syncs are incremental, with a full scan every `BDK_FULL_SCAN_INTERVAL_SECS`
+/// to pick up funds sent to addresses the wallet has not revealed.
+pub fn start_background_sync(state: Arc<crate::AppState>) {
+    let interval = state.config.bdk_sync_secs;
+    if interval == 0 {
+        info!("BDK background sync disabled");
+        return;
+    }
+    let full_scan_every = Duration::from_secs(state.config.bdk_full_scan_secs);
+    info!("Syncing BDK wallet every {}s", interval);
+
+    let mut stopping = state.shutdown.subscribe();
+    let worker = tokio::spawn({
+        let state = state.clone();
+        async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            ticker.tick().await;
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = stopping.wait_for(|stop| *stop) => break,
+                }
+                let state = state.clone();
+                let result = tokio::task::spawn_blocking(move || match &state.bdk_wallet {
+                    Some(bdk) => bdk.sync_in_background(full_scan_every),
+                    None => Ok(false),
+                })
+                .await;
+                match result {
+                    Ok(Err(e)) => warn!("Background wallet sync failed: {:#}", e),
+                    Err(e) => warn!("Background wallet sync panicked: {}", e),
+                    Ok(Ok(_)) => {}
+                }
+            }
+        }
+    });
+    state.shutdown.track(worker);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
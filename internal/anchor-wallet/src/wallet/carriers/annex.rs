@@ -1,23 +1,21 @@
 //! Taproot Annex transaction builder
 
 use anyhow::{Context, Result};
-use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
 use bitcoin::key::{TapTweak, UntweakedKeypair};
 use bitcoin::secp256k1::{Secp256k1, SecretKey};
 use bitcoin::sighash::{Annex, Prevouts, SighashCache, TapSighashType};
 use bitcoin::taproot::TaprootBuilder;
 use bitcoin::transaction::Version;
 use bitcoin::{
-    absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
-    Witness, XOnlyPublicKey,
+    absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    XOnlyPublicKey,
 };
 use bitcoincore_rpc::RpcApi;
-use std::collections::HashSet;
-use std::str::FromStr;
 use tracing::{debug, info};
 
 use crate::wallet::service::WalletService;
-use crate::wallet::types::CreatedTransaction;
+use crate::wallet::types::{CoinControl, CreatedTransaction};
 
 /// Create and broadcast a Taproot Annex transaction
 /// The annex is the last element in the witness stack, prefixed with 0x50
@@ -26,7 +24,7 @@ pub fn create_and_broadcast_annex_tx(
     wallet: &WalletService,
     annex_data: Vec<u8>,
     fee_rate: u64,
-    locked_set: Option<&HashSet<(String, u32)>>,
+    coins: Option<&CoinControl>,
 ) -> Result<CreatedTransaction> {
     // Acquire the transaction creation mutex to prevent race conditions
     let _tx_guard = wallet
@@ -41,7 +39,7 @@ pub fn create_and_broadcast_annex_tx(
     let secret_bytes: [u8; 32] = {
         let mut bytes = [0u8; 32];
         // Use wallet's first address as entropy source
-        let addr = wallet.rpc.get_new_address(None, None)?;
+        let addr = wallet.rpc().get_new_address(None, None)?;
         let addr_bytes = addr.assume_checked().to_string().into_bytes();
         for (i, b) in addr_bytes.iter().take(32).enumerate() {
             bytes[i] = *b;
@@ -85,7 +83,7 @@ pub fn create_and_broadcast_annex_tx(
     // Step 1: Create commit transaction that funds the Taproot address
     // Commit amount must cover reveal fee + dust output
     let commit_amount = reveal_fee + 546; // reveal fee + dust limit
-    let utxos = wallet.list_unspent_unlocked(Some(1), locked_set)?;
+    let utxos = wallet.list_unspent_unlocked(Some(1), coins)?;
     if utxos.is_empty() {
         anyhow::bail!("No UTXOs available for Annex commit (all may be locked)");
     }
@@ -124,8 +122,7 @@ pub fn create_and_broadcast_annex_tx(
         })
         .collect();
 
-    let change_address = wallet.rpc.get_new_address(None, None)?;
-    let change_script = change_address.assume_checked().script_pubkey();
+    let change_script = wallet.change_address(coins)?.script_pubkey();
 
     let change_value = total_input - commit_amount - commit_fee;
     let commit_outputs = vec![
@@ -149,7 +146,7 @@ pub fn create_and_broadcast_annex_tx(
     let commit_hex = serialize_hex(&commit_tx);
 
     // Sign commit transaction
-    let signed_commit: serde_json::Value = wallet.rpc.call(
+    let signed_commit: serde_json::Value = wallet.rpc().call(
         "signrawtransactionwithwallet",
         &[serde_json::json!(commit_hex)],
     )?;
@@ -162,18 +159,13 @@ pub fn create_and_broadcast_annex_tx(
         .as_str()
         .context("No hex in signed commit")?;
 
-    // Broadcast commit
-    let commit_txid: String = wallet.rpc.call(
-        "sendrawtransaction",
-        &[serde_json::json!(signed_commit_hex)],
-    )?;
-    info!("Broadcast annex commit tx: {}", commit_txid);
-
-    let commit_txid_parsed = Txid::from_str(&commit_txid)?;
+    // The commit goes out together with the reveal
+    let commit_txid_parsed = deserialize_hex::<Transaction>(signed_commit_hex)
+        .context("Invalid signed commit")?
+        .compute_txid();
 
     // Step 2: Create the reveal transaction with annex in witness
-    let reveal_change_address = wallet.rpc.get_new_address(None, None)?;
-    let reveal_change_script = reveal_change_address.assume_checked().script_pubkey();
+    let reveal_change_script = wallet.change_address(coins)?.script_pubkey();
 
     let reveal_input = TxIn {
         previous_output: OutPoint {
@@ -245,18 +237,19 @@ pub fn create_and_broadcast_annex_tx(
 
     let reveal_hex = serialize_hex(&reveal_tx);
 
-    // Broadcast reveal transaction
-    // Note: Standard nodes may reject this, but libre relay nodes should accept it
-    let reveal_txid: String = wallet
-        .rpc
-        .call("sendrawtransaction", &[serde_json::json!(reveal_hex)])
+    // Broadcast commit and reveal
+    // Note: Standard nodes may reject the reveal, but libre relay nodes should
+    // accept it. The commit pays its own fee so it never depends on the reveal.
+    let sent = wallet
+        .broadcast_package(signed_commit_hex, &reveal_hex)
         .map_err(|e| {
             anyhow::anyhow!("Failed to broadcast annex tx (may need libre relay): {}", e)
         })?;
+    let reveal_txid = sent.child_txid;
 
     info!(
-        "Broadcast annex reveal tx: {} (commit: {})",
-        reveal_txid, commit_txid
+        "Broadcast annex reveal tx: {} (commit: {}, package: {})",
+        reveal_txid, sent.parent_txid, sent.packaged
     );
 
     Ok(CreatedTransaction {
@@ -1,22 +1,21 @@
 //! Inscription (Taproot commit+reveal) transaction builder
 
 use anyhow::{Context, Result};
-use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
 use bitcoin::key::UntweakedKeypair;
 use bitcoin::secp256k1::{Secp256k1, SecretKey};
 use bitcoin::taproot::{LeafVersion, TaprootBuilder, TaprootSpendInfo};
 use bitcoin::transaction::Version;
 use bitcoin::{
-    absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
-    Witness, XOnlyPublicKey,
+    absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    XOnlyPublicKey,
 };
 use bitcoincore_rpc::RpcApi;
-use std::collections::HashSet;
-use std::str::FromStr;
 use tracing::{debug, info};
 
+use crate::wallet::package::TRUC_VERSION;
 use crate::wallet::service::WalletService;
-use crate::wallet::types::CreatedTransaction;
+use crate::wallet::types::{CoinControl, CreatedTransaction};
 
 /// Create and broadcast an Inscription transaction using commit+reveal pattern
 /// This creates a Taproot script-path spend that reveals the inscription in the witness
@@ -24,7 +23,7 @@ pub fn create_and_broadcast_inscription_tx(
     wallet: &WalletService,
     reveal_script: ScriptBuf,
     fee_rate: u64,
-    locked_set: Option<&HashSet<(String, u32)>>,
+    coins: Option<&CoinControl>,
 ) -> Result<CreatedTransaction> {
     // Acquire the transaction creation mutex to prevent race conditions
     // This serializes all two-stage transactions to avoid UTXO conflicts
@@ -80,6 +79,19 @@ pub fn create_and_broadcast_inscription_tx(
     let reveal_fee = std::cmp::max(15000, reveal_vbytes as u64 * fee_rate);
     let commit_fee = std::cmp::max(12000, 150 * fee_rate); // Commit tx is ~150 vbytes
 
+    // With package relay the reveal can pay the commit's fee as well
+    let zero_fee_commit = wallet.zero_fee_commit(reveal_vbytes as u64);
+    let (reveal_fee, commit_fee) = if zero_fee_commit {
+        (reveal_fee + commit_fee, 0)
+    } else {
+        (reveal_fee, commit_fee)
+    };
+    let version = if zero_fee_commit {
+        TRUC_VERSION
+    } else {
+        Version::TWO
+    };
+
     debug!(
         "Inscription fees: reveal_script={} bytes, reveal_vbytes={}, reveal_fee={} sats",
         script_size, reveal_vbytes, reveal_fee
@@ -88,7 +100,7 @@ pub fn create_and_broadcast_inscription_tx(
     // Step 1: Create the commit transaction that funds the Taproot address
     // Commit amount must cover reveal fee + dust output
     let commit_amount = reveal_fee + 546; // reveal fee + dust limit
-    let utxos = wallet.list_unspent_unlocked(Some(1), locked_set)?;
+    let utxos = wallet.list_unspent_unlocked(Some(1), coins)?;
     if utxos.is_empty() {
         anyhow::bail!("No UTXOs available for Inscription commit (all may be locked)");
     }
@@ -128,8 +140,7 @@ pub fn create_and_broadcast_inscription_tx(
         .collect();
 
     // Get change address
-    let change_address = wallet.rpc.get_new_address(None, None)?;
-    let change_script = change_address.assume_checked().script_pubkey();
+    let change_script = wallet.change_address(coins)?.script_pubkey();
 
     // Build commit outputs: Taproot commit output + change
     let change_value = total_input - commit_amount - commit_fee;
@@ -145,7 +156,7 @@ pub fn create_and_broadcast_inscription_tx(
     ];
 
     let commit_tx = Transaction {
-        version: Version::TWO,
+        version,
         lock_time: LockTime::ZERO,
         input: commit_inputs,
         output: commit_outputs,
@@ -154,7 +165,7 @@ pub fn create_and_broadcast_inscription_tx(
     let commit_hex = serialize_hex(&commit_tx);
 
     // Sign commit transaction
-    let signed_commit: serde_json::Value = wallet.rpc.call(
+    let signed_commit: serde_json::Value = wallet.rpc().call(
         "signrawtransactionwithwallet",
         &[serde_json::json!(commit_hex)],
     )?;
@@ -167,22 +178,16 @@ pub fn create_and_broadcast_inscription_tx(
         .as_str()
         .context("No hex in signed commit")?;
 
-    // Broadcast commit
-    let commit_txid: String = wallet.rpc.call(
-        "sendrawtransaction",
-        &[serde_json::json!(signed_commit_hex)],
-    )?;
-    info!("Broadcast inscription commit tx: {}", commit_txid);
-
-    // Parse commit txid
-    let commit_txid_parsed = Txid::from_str(&commit_txid)?;
+    // The commit goes out together with the reveal
+    let commit_txid_parsed = deserialize_hex::<Transaction>(signed_commit_hex)
+        .context("Invalid signed commit")?
+        .compute_txid();
 
     // Step 2: Create the reveal transaction that spends the commit output
     // This reveals the inscription in the witness
 
     // Get another change address for reveal tx
-    let reveal_change_address = wallet.rpc.get_new_address(None, None)?;
-    let reveal_change_script = reveal_change_address.assume_checked().script_pubkey();
+    let reveal_change_script = wallet.change_address(coins)?.script_pubkey();
 
     let reveal_input = TxIn {
         previous_output: OutPoint {
@@ -207,7 +212,7 @@ pub fn create_and_broadcast_inscription_tx(
     };
 
     let mut reveal_tx = Transaction {
-        version: Version::TWO,
+        version,
         lock_time: LockTime::ZERO,
         input: vec![reveal_input],
         output: vec![reveal_output],
@@ -230,14 +235,13 @@ pub fn create_and_broadcast_inscription_tx(
 
     let reveal_hex = serialize_hex(&reveal_tx);
 
-    // Broadcast reveal transaction (no signing needed for script-path with no sig check)
-    let reveal_txid: String = wallet
-        .rpc
-        .call("sendrawtransaction", &[serde_json::json!(reveal_hex)])?;
+    // Broadcast commit and reveal (no signing needed for script-path with no sig check)
+    let sent = wallet.broadcast_package(signed_commit_hex, &reveal_hex)?;
+    let reveal_txid = sent.child_txid;
 
     info!(
-        "Broadcast inscription reveal tx: {} (commit: {})",
-        reveal_txid, commit_txid
+        "Broadcast inscription reveal tx: {} (commit: {}, package: {})",
+        reveal_txid, sent.parent_txid, sent.packaged
     );
 
     Ok(CreatedTransaction {
@@ -6,7 +6,7 @@ use bitcoincore_rpc::RpcApi;
 use tracing::debug;
 
 use crate::wallet::service::WalletService;
-use crate::wallet::types::CreatedTransaction;
+use crate::wallet::types::{CoinControl, CreatedTransaction};
 use crate::wallet::utils::{carrier_name, extract_op_return_data};
 
 /// Create and broadcast a transaction with the given OP_RETURN script
@@ -15,10 +15,10 @@ pub fn create_and_broadcast_tx_with_script(
     op_return_script: ScriptBuf,
     carrier_type: u8,
     fee_rate: u64, // sat/vbyte
+    coins: Option<&CoinControl>,
 ) -> Result<CreatedTransaction> {
     // Get a change address
-    let change_address = wallet.rpc.get_new_address(None, None)?;
-    let change_address = change_address.assume_checked();
+    let change_address = wallet.change_address(coins)?;
 
     // Convert sat/vbyte to BTC/kB for fundrawtransaction
     // 1 sat/vbyte = 0.00001 BTC/kB (1 sat = 0.00000001 BTC, 1 vbyte = 1/1000 kB)
@@ -26,7 +26,7 @@ pub fn create_and_broadcast_tx_with_script(
 
     // Create raw transaction with OP_RETURN output
     // We need to use the RPC call directly for complex output handling
-    let raw_tx: String = wallet.rpc.call(
+    let raw_tx: String = wallet.rpc().call(
         "createrawtransaction",
         &[
             serde_json::json!([]),
@@ -36,22 +36,24 @@ pub fn create_and_broadcast_tx_with_script(
         ],
     )?;
 
-    // Fund the transaction
-    let funded: serde_json::Value = wallet.rpc.call(
-        "fundrawtransaction",
-        &[
-            serde_json::json!(raw_tx),
-            serde_json::json!({
-                "changeAddress": change_address.to_string(),
-                "feeRate": fee_rate_btc_kb
-            }),
-        ],
-    )?;
+    // Fund the transaction from the UTXOs coin control allows
+    let funded: serde_json::Value = wallet.with_coin_control(coins, &[], || {
+        Ok(wallet.rpc().call(
+            "fundrawtransaction",
+            &[
+                serde_json::json!(raw_tx),
+                serde_json::json!({
+                    "changeAddress": change_address.to_string(),
+                    "feeRate": fee_rate_btc_kb
+                }),
+            ],
+        )?)
+    })?;
 
     let funded_hex = funded["hex"].as_str().context("No hex in funded tx")?;
 
     // Sign the transaction
-    let signed: serde_json::Value = wallet.rpc.call(
+    let signed: serde_json::Value = wallet.rpc().call(
         "signrawtransactionwithwallet",
         &[serde_json::json!(funded_hex)],
     )?;
@@ -64,14 +66,14 @@ pub fn create_and_broadcast_tx_with_script(
 
     // Broadcast the transaction
     let txid: String = wallet
-        .rpc
+        .rpc()
         .call("sendrawtransaction", &[serde_json::json!(signed_hex)])?;
 
     debug!("Broadcast transaction: {}", txid);
 
     // Find the OP_RETURN output index
     let decoded: serde_json::Value = wallet
-        .rpc
+        .rpc()
         .call("decoderawtransaction", &[serde_json::json!(signed_hex)])?;
 
     let mut anchor_vout = 0u32;
@@ -7,11 +7,10 @@ use bitcoin::{
     absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
 };
 use bitcoincore_rpc::RpcApi;
-use std::collections::HashSet;
 use tracing::{debug, info};
 
 use crate::wallet::service::WalletService;
-use crate::wallet::types::CreatedTransaction;
+use crate::wallet::types::{CoinControl, CreatedTransaction};
 
 /// Create and broadcast a Stamps transaction with bare multisig outputs
 /// Builds the transaction manually since Bitcoin Core RPC doesn't support custom scriptPubKey
@@ -19,10 +18,10 @@ pub fn create_and_broadcast_stamps_tx(
     wallet: &WalletService,
     scripts: Vec<ScriptBuf>,
     fee_rate: u64,
-    locked_set: Option<&HashSet<(String, u32)>>,
+    coins: Option<&CoinControl>,
 ) -> Result<CreatedTransaction> {
     // Get UTXOs (excluding locked ones if provided)
-    let utxos = wallet.list_unspent_unlocked(Some(0), locked_set)?;
+    let utxos = wallet.list_unspent_unlocked(Some(0), coins)?;
     if utxos.is_empty() {
         anyhow::bail!("No UTXOs available for Stamps transaction");
     }
@@ -59,8 +58,7 @@ pub fn create_and_broadcast_stamps_tx(
     }
 
     // Get change address
-    let change_address = wallet.rpc.get_new_address(None, None)?;
-    let change_address = change_address.assume_checked();
+    let change_address = wallet.change_address(coins)?;
 
     // Build inputs
     let inputs: Vec<TxIn> = selected_utxos
@@ -109,7 +107,7 @@ pub fn create_and_broadcast_stamps_tx(
     );
 
     // Sign using wallet
-    let signed: serde_json::Value = wallet.rpc.call(
+    let signed: serde_json::Value = wallet.rpc().call(
         "signrawtransactionwithwallet",
         &[serde_json::json!(unsigned_hex)],
     )?;
@@ -124,7 +122,7 @@ pub fn create_and_broadcast_stamps_tx(
 
     // Broadcast
     let txid: String = wallet
-        .rpc
+        .rpc()
         .call("sendrawtransaction", &[serde_json::json!(signed_hex)])?;
 
     info!(
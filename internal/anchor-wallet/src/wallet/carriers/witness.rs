@@ -1,30 +1,32 @@
 //! Witness Data (Taproot commit+reveal) transaction builder
 
 use anyhow::{Context, Result};
-use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
 use bitcoin::key::UntweakedKeypair;
 use bitcoin::secp256k1::{Secp256k1, SecretKey};
 use bitcoin::taproot::{LeafVersion, TaprootBuilder, TaprootSpendInfo};
 use bitcoin::transaction::Version;
 use bitcoin::{
-    absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid,
-    Witness, XOnlyPublicKey,
+    absolute::LockTime, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    XOnlyPublicKey,
 };
 use bitcoincore_rpc::RpcApi;
-use std::collections::HashSet;
-use std::str::FromStr;
 use tracing::{debug, info};
 
+use crate::wallet::package::TRUC_VERSION;
 use crate::wallet::service::WalletService;
-use crate::wallet::types::CreatedTransaction;
+use crate::wallet::types::{CoinControl, CreatedTransaction};
 
 /// Create and broadcast a WitnessData transaction using commit+reveal pattern
 /// Similar to inscriptions but uses a simpler data script (data drops + OP_TRUE)
+///
+/// Multi-part payloads get one commit output per data script and a reveal
+/// transaction spending all of them.
 pub fn create_and_broadcast_witness_data_tx(
     wallet: &WalletService,
-    data_script: ScriptBuf,
+    data_scripts: Vec<ScriptBuf>,
     fee_rate: u64,
-    locked_set: Option<&HashSet<(String, u32)>>,
+    coins: Option<&CoinControl>,
 ) -> Result<CreatedTransaction> {
     // Acquire the transaction creation mutex to prevent race conditions
     let _tx_guard = wallet
@@ -48,39 +50,70 @@ pub fn create_and_broadcast_witness_data_tx(
         })
     };
 
-    // Build the Taproot tree with the data script as a leaf
-    let taproot_builder = TaprootBuilder::new()
-        .add_leaf(0, data_script.clone())
-        .map_err(|e| anyhow::anyhow!("Failed to build Taproot tree: {:?}", e))?;
-
-    let taproot_info: TaprootSpendInfo = taproot_builder
-        .finalize(&secp, internal_key)
-        .map_err(|e| anyhow::anyhow!("Failed to finalize Taproot: {:?}", e))?;
+    // Build a Taproot tree per data script, each with the script as its only leaf
+    let mut parts: Vec<(ScriptBuf, TaprootSpendInfo)> = Vec::new();
+    for data_script in data_scripts {
+        let taproot_builder = TaprootBuilder::new()
+            .add_leaf(0, data_script.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to build Taproot tree: {:?}", e))?;
+
+        let taproot_info: TaprootSpendInfo = taproot_builder
+            .finalize(&secp, internal_key)
+            .map_err(|e| anyhow::anyhow!("Failed to finalize Taproot: {:?}", e))?;
+        parts.push((data_script, taproot_info));
+    }
+    if parts.is_empty() {
+        anyhow::bail!("No WitnessData scripts to reveal");
+    }
 
-    let output_key = taproot_info.output_key();
-    let commit_script = ScriptBuf::new_p2tr_tweaked(output_key);
+    let commit_scripts: Vec<ScriptBuf> = parts
+        .iter()
+        .map(|(_, info)| ScriptBuf::new_p2tr_tweaked(info.output_key()))
+        .collect();
 
     debug!(
-        "WitnessData commit script: {}",
-        hex::encode(commit_script.as_bytes())
+        "WitnessData commit scripts: {:?}",
+        commit_scripts
+            .iter()
+            .map(|s| hex::encode(s.as_bytes()))
+            .collect::<Vec<_>>()
     );
 
     // Calculate dynamic fee based on data script size and fee_rate
-    // Reveal tx: ~100 base vbytes + witness data (gets 75% discount)
-    let script_size = data_script.len();
-    let reveal_vbytes = 100 + script_size.div_ceil(4); // witness weight / 4
+    // Reveal tx: ~100 base vbytes + witness data (gets 75% discount), plus
+    // ~58 vbytes per extra input (outpoint, control block)
+    let script_size: usize = parts.iter().map(|(s, _)| s.len()).sum();
+    let reveal_vbytes = 100 + (parts.len() - 1) * 58 + script_size.div_ceil(4); // witness weight / 4
     let reveal_fee = std::cmp::max(15000, reveal_vbytes as u64 * fee_rate);
-    let commit_fee = std::cmp::max(12000, 150 * fee_rate); // Commit tx is ~150 vbytes
+    let commit_fee = std::cmp::max(12000, (150 + (parts.len() as u64 - 1) * 43) * fee_rate); // Commit tx is ~150 vbytes
+
+    // With package relay the reveal can pay the commit's fee as well
+    let zero_fee_commit = wallet.zero_fee_commit(reveal_vbytes as u64);
+    let (reveal_fee, commit_fee) = if zero_fee_commit {
+        (reveal_fee + commit_fee, 0)
+    } else {
+        (reveal_fee, commit_fee)
+    };
+    let version = if zero_fee_commit {
+        TRUC_VERSION
+    } else {
+        Version::TWO
+    };
 
     debug!(
-        "WitnessData fees: data_script={} bytes, reveal_vbytes={}, reveal_fee={} sats",
-        script_size, reveal_vbytes, reveal_fee
+        "WitnessData fees: data_scripts={} ({} bytes), reveal_vbytes={}, reveal_fee={} sats",
+        parts.len(),
+        script_size,
+        reveal_vbytes,
+        reveal_fee
     );
 
     // Step 1: Create the commit transaction
-    // Commit amount must cover reveal fee + dust output
-    let commit_amount = reveal_fee + 546; // reveal fee + dust limit
-    let utxos = wallet.list_unspent_unlocked(Some(1), locked_set)?;
+    // The first commit output covers the reveal fee + dust output; the
+    // others only need to be above dust
+    let first_commit_amount = reveal_fee + 546; // reveal fee + dust limit
+    let commit_amount = first_commit_amount + (parts.len() as u64 - 1) * 546;
+    let utxos = wallet.list_unspent_unlocked(Some(1), coins)?;
     if utxos.is_empty() {
         anyhow::bail!("No UTXOs available for WitnessData commit (all may be locked)");
     }
@@ -117,23 +150,24 @@ pub fn create_and_broadcast_witness_data_tx(
         })
         .collect();
 
-    let change_address = wallet.rpc.get_new_address(None, None)?;
-    let change_script = change_address.assume_checked().script_pubkey();
+    let change_script = wallet.change_address(coins)?.script_pubkey();
 
     let change_value = total_input - commit_amount - commit_fee;
-    let commit_outputs = vec![
-        TxOut {
-            value: Amount::from_sat(commit_amount),
-            script_pubkey: commit_script.clone(),
-        },
-        TxOut {
-            value: Amount::from_sat(change_value),
-            script_pubkey: change_script.clone(),
-        },
-    ];
+    let mut commit_outputs: Vec<TxOut> = commit_scripts
+        .iter()
+        .enumerate()
+        .map(|(i, script)| TxOut {
+            value: Amount::from_sat(if i == 0 { first_commit_amount } else { 546 }),
+            script_pubkey: script.clone(),
+        })
+        .collect();
+    commit_outputs.push(TxOut {
+        value: Amount::from_sat(change_value),
+        script_pubkey: change_script.clone(),
+    });
 
     let commit_tx = Transaction {
-        version: Version::TWO,
+        version,
         lock_time: LockTime::ZERO,
         input: commit_inputs,
         output: commit_outputs,
@@ -142,7 +176,7 @@ pub fn create_and_broadcast_witness_data_tx(
     let commit_hex = serialize_hex(&commit_tx);
 
     // Sign commit transaction
-    let signed_commit: serde_json::Value = wallet.rpc.call(
+    let signed_commit: serde_json::Value = wallet.rpc().call(
         "signrawtransactionwithwallet",
         &[serde_json::json!(commit_hex)],
     )?;
@@ -155,28 +189,25 @@ pub fn create_and_broadcast_witness_data_tx(
         .as_str()
         .context("No hex in signed commit")?;
 
-    // Broadcast commit
-    let commit_txid: String = wallet.rpc.call(
-        "sendrawtransaction",
-        &[serde_json::json!(signed_commit_hex)],
-    )?;
-    info!("Broadcast witness data commit tx: {}", commit_txid);
-
-    let commit_txid_parsed = Txid::from_str(&commit_txid)?;
+    // The commit goes out together with the reveal
+    let commit_txid_parsed = deserialize_hex::<Transaction>(signed_commit_hex)
+        .context("Invalid signed commit")?
+        .compute_txid();
 
     // Step 2: Create the reveal transaction
-    let reveal_change_address = wallet.rpc.get_new_address(None, None)?;
-    let reveal_change_script = reveal_change_address.assume_checked().script_pubkey();
-
-    let reveal_input = TxIn {
-        previous_output: OutPoint {
-            txid: commit_txid_parsed,
-            vout: 0,
-        },
-        script_sig: ScriptBuf::new(),
-        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
-        witness: Witness::new(),
-    };
+    let reveal_change_script = wallet.change_address(coins)?.script_pubkey();
+
+    let reveal_inputs: Vec<TxIn> = (0..parts.len())
+        .map(|vout| TxIn {
+            previous_output: OutPoint {
+                txid: commit_txid_parsed,
+                vout: vout as u32,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        })
+        .collect();
 
     // Reveal output value = commit_amount - reveal_fee, ensuring at least dust limit
     let reveal_output_value = if commit_amount > reveal_fee + 546 {
@@ -190,35 +221,35 @@ pub fn create_and_broadcast_witness_data_tx(
     };
 
     let mut reveal_tx = Transaction {
-        version: Version::TWO,
+        version,
         lock_time: LockTime::ZERO,
-        input: vec![reveal_input],
+        input: reveal_inputs,
         output: vec![reveal_output],
     };
 
-    // Build the witness for script-path spend
-    let control_block = taproot_info
-        .control_block(&(data_script.clone(), LeafVersion::TapScript))
-        .context("Failed to build control block")?;
-
-    // Witness: [script] [control block]
-    // The data script drops all data and returns true, no signature needed
-    let mut witness = Witness::new();
-    witness.push(data_script.as_bytes());
-    witness.push(control_block.serialize());
-
-    reveal_tx.input[0].witness = witness;
+    for (input, (data_script, taproot_info)) in reveal_tx.input.iter_mut().zip(&parts) {
+        // Build the witness for script-path spend
+        let control_block = taproot_info
+            .control_block(&(data_script.clone(), LeafVersion::TapScript))
+            .context("Failed to build control block")?;
+
+        // Witness: [script] [control block]
+        // The data script drops all data and returns true, no signature needed
+        let mut witness = Witness::new();
+        witness.push(data_script.as_bytes());
+        witness.push(control_block.serialize());
+        input.witness = witness;
+    }
 
     let reveal_hex = serialize_hex(&reveal_tx);
 
-    // Broadcast reveal transaction
-    let reveal_txid: String = wallet
-        .rpc
-        .call("sendrawtransaction", &[serde_json::json!(reveal_hex)])?;
+    // Broadcast commit and reveal
+    let sent = wallet.broadcast_package(signed_commit_hex, &reveal_hex)?;
+    let reveal_txid = sent.child_txid;
 
     info!(
-        "Broadcast witness data reveal tx: {} (commit: {})",
-        reveal_txid, commit_txid
+        "Broadcast witness data reveal tx: {} (commit: {}, package: {})",
+        reveal_txid, sent.parent_txid, sent.packaged
     );
 
     Ok(CreatedTransaction {
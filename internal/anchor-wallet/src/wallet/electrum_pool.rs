@@ -0,0 +1,418 @@
+//! Electrum server pool with health checks and failover
+//!
+//! The BDK wallet can be given several Electrum servers (`ELECTRUM_URLS`).
+//! Each health check connects to every server, asks for its chain tip, and
+//! times the round trip. The active server is kept while it stays healthy and
+//! within `MAX_TIP_LAG` blocks of the best tip seen; otherwise the pool moves
+//! to the fastest server that is. A sync that fails against the active server
+//! triggers an immediate check and one retry on whichever server wins it.
+//!
+//! `.onion` servers go through `ELECTRUM_PROXY`, or `ELECTRUM_ONION_PROXY`
+//! when only onion servers should use Tor.
+
+use anyhow::{Context, Result};
+use bdk_electrum::electrum_client::{self, ElectrumApi};
+use bdk_electrum::BdkElectrumClient;
+use serde::Serialize;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::proxy::Socks5Proxy;
+
+/// Blocks a server may trail the best tip before it is passed over
+const MAX_TIP_LAG: u32 = 2;
+
+/// Connect-and-query timeout for clearnet servers
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connect-and-query timeout for onion servers, which take longer to reach
+const ONION_PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Health of one Electrum server as of its last check
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServerHealth {
+    /// Server URL
+    pub url: String,
+    /// Whether the server is a Tor onion service
+    pub onion: bool,
+    /// Whether the last check succeeded
+    pub healthy: bool,
+    /// Connect plus tip query time of the last check
+    pub latency_ms: Option<u64>,
+    /// Chain tip height the server reported
+    pub height: Option<u32>,
+    /// When the server was last checked (RFC 3339)
+    pub last_checked: Option<String>,
+    /// Error from the last check, if it failed
+    pub last_error: Option<String>,
+}
+
+impl ServerHealth {
+    fn unchecked(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            onion: is_onion(url),
+            healthy: false,
+            latency_ms: None,
+            height: None,
+            last_checked: None,
+            last_error: None,
+        }
+    }
+}
+
+type Client = BdkElectrumClient<electrum_client::Client>;
+
+/// The server currently used for wallet syncs
+struct Active {
+    index: usize,
+    client: Arc<Client>,
+}
+
+/// Electrum servers the BDK wallet can sync from
+pub struct ElectrumPool {
+    servers: Vec<String>,
+    proxy: Option<Socks5Proxy>,
+    onion_proxy: Option<Socks5Proxy>,
+    health: Mutex<Vec<ServerHealth>>,
+    active: Mutex<Option<Active>>,
+}
+
+impl ElectrumPool {
+    /// Create a pool; no connections are made until `connect`
+    pub fn new(
+        servers: Vec<String>,
+        proxy: Option<Socks5Proxy>,
+        onion_proxy: Option<Socks5Proxy>,
+    ) -> Result<Self> {
+        anyhow::ensure!(!servers.is_empty(), "No Electrum servers configured");
+        for url in &servers {
+            if is_onion(url) && proxy.is_none() && onion_proxy.is_none() {
+                anyhow::bail!(
+                    "Electrum server {} is an onion service; set ELECTRUM_PROXY or ELECTRUM_ONION_PROXY",
+                    url
+                );
+            }
+        }
+        Ok(Self {
+            health: Mutex::new(
+                servers
+                    .iter()
+                    .map(|url| ServerHealth::unchecked(url))
+                    .collect(),
+            ),
+            servers,
+            proxy,
+            onion_proxy,
+            active: Mutex::new(None),
+        })
+    }
+
+    /// Check every server until one is usable, retrying for up to two minutes
+    pub fn connect(&self) -> Result<()> {
+        let max_retries = 12;
+        let retry_delay = Duration::from_secs(10);
+
+        for attempt in 1..=max_retries {
+            if self.check_health().is_some() {
+                return Ok(());
+            }
+            warn!(
+                "No Electrum server reachable (attempt {}/{})",
+                attempt, max_retries
+            );
+            if attempt < max_retries {
+                info!("Retrying Electrum connection in 10s...");
+                std::thread::sleep(retry_delay);
+            }
+        }
+
+        anyhow::bail!(
+            "Failed to connect to any of {} Electrum server(s) after {} attempts",
+            self.servers.len(),
+            max_retries
+        )
+    }
+
+    /// Probe all servers in parallel and switch the active server if needed
+    ///
+    /// Returns the active server's URL, or `None` when none is usable.
+    pub fn check_health(&self) -> Option<String> {
+        let started = Instant::now();
+        let probes: Vec<_> = self
+            .servers
+            .iter()
+            .map(|url| {
+                let (tx, rx) = mpsc::channel();
+                let url = url.clone();
+                let proxy = self.proxy_for(&url).cloned();
+                std::thread::spawn(move || {
+                    let _ = tx.send(probe(&url, proxy.as_ref()));
+                });
+                rx
+            })
+            .collect();
+
+        let results: Vec<_> = probes
+            .into_iter()
+            .zip(&self.servers)
+            .map(|(rx, url)| {
+                let timeout = if is_onion(url) {
+                    ONION_PROBE_TIMEOUT
+                } else {
+                    PROBE_TIMEOUT
+                };
+                rx.recv_timeout(timeout.saturating_sub(started.elapsed()))
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out")))
+            })
+            .collect();
+
+        let checked_at = chrono::Utc::now().to_rfc3339();
+        let mut clients = Vec::with_capacity(results.len());
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        for (index, result) in results.into_iter().enumerate() {
+            let entry = &mut health[index];
+            entry.last_checked = Some(checked_at.clone());
+            match result {
+                Ok((client, height, latency)) => {
+                    entry.healthy = true;
+                    entry.height = Some(height);
+                    entry.latency_ms = Some(latency.as_millis() as u64);
+                    entry.last_error = None;
+                    clients.push(Some(client));
+                }
+                Err(e) => {
+                    if entry.healthy {
+                        warn!("Electrum server {} is unhealthy: {:#}", entry.url, e);
+                    }
+                    entry.healthy = false;
+                    entry.latency_ms = None;
+                    entry.last_error = Some(format!("{:#}", e));
+                    clients.push(None);
+                }
+            }
+        }
+
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let current = active.as_ref().map(|a| a.index);
+        let chosen = choose(current, &health)?;
+        if current != Some(chosen) {
+            let client = clients[chosen].take()?;
+            info!(
+                "Using Electrum server {} ({}ms, height {})",
+                health[chosen].url,
+                health[chosen].latency_ms.unwrap_or_default(),
+                health[chosen].height.unwrap_or_default()
+            );
+            *active = Some(Active {
+                index: chosen,
+                client: Arc::new(BdkElectrumClient::new(client)),
+            });
+        }
+        Some(self.servers[chosen].clone())
+    }
+
+    /// The active server's client
+    pub fn client(&self) -> Result<Arc<Client>> {
+        self.active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|a| a.client.clone())
+            .context("No Electrum server available")
+    }
+
+    /// Mark the active server as failed and move to the best remaining one
+    pub fn failover(&self, error: &anyhow::Error) -> Result<Arc<Client>> {
+        let failed = self.active.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(active) = failed {
+            warn!(
+                "Electrum server {} failed: {:#}",
+                self.servers[active.index], error
+            );
+            let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+            health[active.index].healthy = false;
+            health[active.index].last_error = Some(format!("{:#}", error));
+        }
+        self.check_health();
+        self.client()
+    }
+
+    /// URL of the active server
+    pub fn active_url(&self) -> Option<String> {
+        self.active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|a| self.servers[a.index].clone())
+    }
+
+    /// Chain tip the active server reported at its last check
+    pub fn active_height(&self) -> Option<u32> {
+        let index = self
+            .active
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()?
+            .index;
+        self.health.lock().unwrap_or_else(|e| e.into_inner())[index].height
+    }
+
+    /// Health of every configured server
+    pub fn servers(&self) -> Vec<ServerHealth> {
+        self.health
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn proxy_for(&self, url: &str) -> Option<&Socks5Proxy> {
+        if is_onion(url) {
+            self.proxy.as_ref().or(self.onion_proxy.as_ref())
+        } else {
+            self.proxy.as_ref()
+        }
+    }
+}
+
+/// Re-check the BDK wallet's Electrum servers every `ELECTRUM_HEALTH_INTERVAL_SECS`
+pub fn start_health_checks(state: Arc<crate::AppState>) {
+    let interval = state.config.electrum_health_secs.max(1);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let state = state.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                state
+                    .bdk_wallet
+                    .as_ref()
+                    .and_then(|bdk| bdk.electrum().check_health())
+            })
+            .await;
+            match result {
+                Ok(None) => warn!("No healthy Electrum server"),
+                Err(e) => warn!("Electrum health check panicked: {}", e),
+                Ok(Some(_)) => {}
+            }
+        }
+    });
+}
+
+/// Connect to a server and fetch its tip, timing both
+fn probe(
+    url: &str,
+    proxy: Option<&Socks5Proxy>,
+) -> Result<(electrum_client::Client, u32, Duration)> {
+    let mut config = electrum_client::ConfigBuilder::new();
+    if let Some(proxy) = proxy {
+        config = config.socks5(Some(proxy.electrum()));
+    }
+    let started = Instant::now();
+    let client = electrum_client::Client::from_config(url, config.build())?;
+    let header = client.block_headers_subscribe()?;
+    Ok((client, header.height as u32, started.elapsed()))
+}
+
+/// Whether a server URL points at a Tor onion service
+pub fn is_onion(url: &str) -> bool {
+    let host = url.split("://").last().unwrap_or(url);
+    let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+    host.ends_with(".onion")
+}
+
+/// Pick the server to use given the last health check
+///
+/// The current server is kept while it is healthy and near the best tip, so
+/// small latency swings don't cause flapping; otherwise the fastest healthy
+/// server near the best tip wins.
+fn choose(current: Option<usize>, health: &[ServerHealth]) -> Option<usize> {
+    let best_height = health
+        .iter()
+        .filter(|h| h.healthy)
+        .filter_map(|h| h.height)
+        .max()?;
+    let eligible = |h: &ServerHealth| {
+        h.healthy
+            && h.height
+                .is_some_and(|height| height + MAX_TIP_LAG >= best_height)
+    };
+
+    if let Some(index) = current {
+        if health.get(index).is_some_and(eligible) {
+            return Some(index);
+        }
+    }
+    health
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| eligible(h))
+        .min_by_key(|(_, h)| h.latency_ms.unwrap_or(u64::MAX))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(healthy: bool, latency_ms: u64, height: u32) -> ServerHealth {
+        ServerHealth {
+            healthy,
+            latency_ms: Some(latency_ms),
+            height: Some(height),
+            ..ServerHealth::unchecked("tcp://example:50001")
+        }
+    }
+
+    #[test]
+    fn test_is_onion() {
+        assert!(is_onion("tcp://abcdefghijklmnop.onion:50001"));
+        assert!(is_onion("ssl://abcdefghijklmnop.onion:50002"));
+        assert!(!is_onion("ssl://electrum.blockstream.info:50002"));
+        assert!(!is_onion("core-electrs:50001"));
+    }
+
+    #[test]
+    fn test_choose_lowest_latency() {
+        let health = vec![
+            server(true, 120, 800_000),
+            server(true, 40, 800_000),
+            server(false, 5, 800_000),
+        ];
+        assert_eq!(choose(None, &health), Some(1));
+    }
+
+    #[test]
+    fn test_choose_keeps_healthy_current() {
+        let health = vec![server(true, 120, 800_000), server(true, 40, 800_000)];
+        assert_eq!(choose(Some(0), &health), Some(0));
+    }
+
+    #[test]
+    fn test_choose_fails_over_from_unhealthy_current() {
+        let health = vec![server(false, 10, 800_000), server(true, 200, 800_000)];
+        assert_eq!(choose(Some(0), &health), Some(1));
+    }
+
+    #[test]
+    fn test_choose_skips_lagging_servers() {
+        let health = vec![server(true, 10, 799_990), server(true, 90, 800_000)];
+        assert_eq!(choose(Some(0), &health), Some(1));
+        assert_eq!(choose(None, &health), Some(1));
+
+        // A block or two behind is normal propagation delay
+        let health = vec![server(true, 10, 799_998), server(true, 90, 800_000)];
+        assert_eq!(choose(None, &health), Some(0));
+    }
+
+    #[test]
+    fn test_choose_none_healthy() {
+        let health = vec![server(false, 10, 800_000)];
+        assert_eq!(choose(None, &health), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Escrow transactions for peer-to-peer trades
+//!
+//! The payment sits in a P2WSH output locked by a 2-of-3 multisig of the
+//! buyer, seller and arbiter keys (see `anchor_specs::escrow`). Funding is an
+//! ordinary wallet transaction carrying the Escrow Fund message. Release and
+//! refund spend the escrow output, so the wallet can't sign them alone: they
+//! are built as PSBTs with the witness script filled in, and any two parties
+//! sign before the result is broadcast. Their fee comes out of the escrowed
+//! amount.
+
+use anyhow::{Context, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    Address, Amount, OutPoint, Psbt, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Weight,
+    Witness,
+};
+use bitcoincore_rpc::RpcApi;
+use std::str::FromStr;
+use tracing::info;
+
+use anchor_core::carrier::{CarrierOutput, CarrierSelector, CarrierType};
+use anchor_core::{AnchorKind, AnchorMessageBuilder, ParsedAnchorMessage};
+use anchor_specs::escrow::EscrowSpec;
+use anchor_specs::KindSpec;
+
+use super::service::WalletService;
+use super::types::{CoinControl, CreatedTransaction, UnsignedTransaction};
+
+/// Smallest payout worth creating, in satoshis
+const DUST_SATS: u64 = 546;
+
+/// Witness of a 2-of-3 multisig spend: item count, the empty dummy, two
+/// signatures and the witness script
+const MULTISIG_WITNESS_WEIGHT: u64 = 1 + 1 + 2 * (1 + 73) + 1 + 105;
+
+impl WalletService {
+    /// Pay `amount` sats into an escrow address with its Fund message
+    ///
+    /// Returns the transaction and the index of the escrow output.
+    pub fn fund_escrow(
+        &self,
+        spec: &EscrowSpec,
+        address: &Address,
+        amount: u64,
+        fee_rate: u64,
+        coins: Option<&CoinControl>,
+    ) -> Result<(CreatedTransaction, u32)> {
+        let created = self.create_anchor_transaction_advanced_with_locks(
+            EscrowSpec::KIND_ID,
+            spec.to_bytes(),
+            None,
+            None,
+            Vec::new(),
+            Some(CarrierType::OpReturn as u8),
+            fee_rate,
+            Vec::new(),
+            vec![(address.to_string(), amount)],
+            coins,
+        )?;
+
+        // Funding places change anywhere, so look the escrow output up
+        let tx: Transaction =
+            deserialize_hex(&created.hex).context("Invalid funding transaction")?;
+        let script = address.script_pubkey();
+        let vout = tx
+            .output
+            .iter()
+            .position(|o| o.script_pubkey == script)
+            .context("Funding transaction has no escrow output")? as u32;
+
+        info!(
+            "Funded escrow {} with {} sats in {}:{}",
+            address, amount, created.txid, vout
+        );
+        Ok((created, vout))
+    }
+
+    /// Whether the escrow output is still unspent, counting the mempool
+    pub fn escrow_unspent(&self, txid: &str, vout: u32) -> Result<bool> {
+        let txid = Txid::from_str(txid).context("Invalid funding txid")?;
+        Ok(self.rpc().get_tx_out(&txid, vout, Some(true))?.is_some())
+    }
+}
+
+/// Build the unsigned transaction that spends an escrow output to `payee`
+/// with a Release or Refund message anchored to the escrow output
+pub fn settlement_psbt(
+    spec: &EscrowSpec,
+    witness_script: &ScriptBuf,
+    funding: OutPoint,
+    amount: u64,
+    payee: &Address,
+    fee_rate: u64,
+) -> Result<UnsignedTransaction> {
+    let anchor_vout = u8::try_from(funding.vout).context("Escrow output index above 255")?;
+    let message = AnchorMessageBuilder::new()
+        .kind(AnchorKind::from(EscrowSpec::KIND_ID))
+        .reply_to(&funding.txid, anchor_vout)
+        .body(spec.to_bytes());
+    let message = ParsedAnchorMessage {
+        kind: AnchorKind::from(EscrowSpec::KIND_ID),
+        anchors: message.get_anchors(),
+        body: message.get_body(),
+    };
+    let carrier = CarrierSelector::new();
+    let op_return = match carrier
+        .get_carrier(CarrierType::OpReturn)
+        .context("OP_RETURN carrier not available")?
+        .encode(&message)
+        .map_err(|e| anyhow::Error::new(e).context("Carrier encode failed"))?
+    {
+        CarrierOutput::OpReturn(script) => script,
+        _ => anyhow::bail!("OP_RETURN carrier returned an unexpected output"),
+    };
+
+    let mut tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: funding,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(amount),
+                script_pubkey: payee.script_pubkey(),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: op_return,
+            },
+        ],
+    };
+
+    // Segwit marker and flag, then the witness the signers will add
+    let weight = tx.weight() + Weight::from_wu(2 + MULTISIG_WITNESS_WEIGHT);
+    let fee = weight.to_vbytes_ceil() * fee_rate;
+    let payout = amount.saturating_sub(fee);
+    if payout < DUST_SATS {
+        anyhow::bail!(
+            "Escrow of {} sats can't pay a {} sat fee and a payout above dust",
+            amount,
+            fee
+        );
+    }
+    tx.output[0].value = Amount::from_sat(payout);
+
+    let mut psbt = Psbt::from_unsigned_tx(tx.clone()).context("Failed to create PSBT")?;
+    psbt.inputs[0].witness_utxo = Some(TxOut {
+        value: Amount::from_sat(amount),
+        script_pubkey: ScriptBuf::new_p2wsh(&witness_script.wscript_hash()),
+    });
+    psbt.inputs[0].witness_script = Some(witness_script.clone());
+
+    Ok(UnsignedTransaction {
+        tx: CreatedTransaction {
+            txid: tx.compute_txid().to_string(),
+            hex: serialize_hex(&tx),
+            anchor_vout: 1,
+            carrier: CarrierType::OpReturn as u8,
+            carrier_name: "op_return".to_string(),
+        },
+        psbt: psbt.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_specs::escrow::escrow_script;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::{Network, PublicKey};
+
+    fn key(byte: u8) -> PublicKey {
+        let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+        PublicKey::new(secret.public_key(&Secp256k1::new()))
+    }
+
+    fn funding() -> OutPoint {
+        OutPoint {
+            txid: Txid::from_str(&"ab".repeat(32)).unwrap(),
+            vout: 1,
+        }
+    }
+
+    #[test]
+    fn test_settlement_psbt() {
+        let script = escrow_script(&key(1), &key(2), &key(3));
+        let payee = Address::p2wpkh(&key(2).try_into().unwrap(), Network::Regtest);
+        let unsigned = settlement_psbt(
+            &EscrowSpec::release(),
+            &script,
+            funding(),
+            100_000,
+            &payee,
+            2,
+        )
+        .unwrap();
+
+        let psbt = Psbt::from_str(&unsigned.psbt).unwrap();
+        let tx = &psbt.unsigned_tx;
+        assert_eq!(tx.compute_txid().to_string(), unsigned.tx.txid);
+        assert_eq!(tx.input[0].previous_output, funding());
+        assert_eq!(psbt.inputs[0].witness_script.as_ref(), Some(&script));
+        assert_eq!(tx.output[0].script_pubkey, payee.script_pubkey());
+        assert!(tx.output[1].script_pubkey.is_op_return());
+
+        // Roughly 180 vbytes at 2 sat/vB
+        let fee = 100_000 - tx.output[0].value.to_sat();
+        assert!((300..500).contains(&fee), "fee {}", fee);
+    }
+
+    #[test]
+    fn test_settlement_needs_room_for_fee() {
+        let script = escrow_script(&key(1), &key(2), &key(3));
+        let payee = Address::p2wpkh(&key(1).try_into().unwrap(), Network::Regtest);
+        assert!(
+            settlement_psbt(&EscrowSpec::refund(), &script, funding(), 1_000, &payee, 5).is_err()
+        );
+    }
+}
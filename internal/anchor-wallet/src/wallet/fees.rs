@@ -0,0 +1,506 @@
+//! Fee advisory from node estimates, the mempool and recent ANCHOR sends
+//!
+//! Three sources are combined into a fee rate per carrier:
+//!
+//! - `estimatesmartfee` for a few confirmation targets, which needs fee
+//!   history the node may not have (fresh nodes, regtest)
+//! - the mempool, bucketed by fee rate; a target of N blocks is reached by
+//!   outbidding everything past the first N blocks' worth of vbytes
+//! - the wallet's recent confirmed ANCHOR transactions: the fee rate they
+//!   paid and how many blocks they waited, per carrier
+//!
+//! The node estimate is preferred, then the mempool, then the configured
+//! default. A carrier whose recent transactions paid at least that rate and
+//! still waited longer than the target is bumped above what they paid.
+
+use anchor_core::carrier::CarrierType;
+use anyhow::Result;
+use bitcoincore_rpc::json::{EstimateMode, GetTransactionResultDetailCategory as Category};
+use bitcoincore_rpc::RpcApi;
+use std::collections::HashSet;
+
+use super::history::decode_messages;
+use super::service::WalletService;
+
+/// Confirmation targets asked of `estimatesmartfee`
+pub const ESTIMATE_TARGETS: [u16; 6] = [1, 2, 3, 6, 12, 144];
+
+/// Virtual size of a full block
+pub const BLOCK_VSIZE: u64 = 1_000_000;
+
+/// Lower bounds of the mempool histogram buckets, in sat/vB
+const BUCKETS: [f64; 17] = [
+    1.0, 2.0, 3.0, 5.0, 8.0, 10.0, 15.0, 20.0, 30.0, 50.0, 75.0, 100.0, 150.0, 200.0, 300.0, 500.0,
+    1000.0,
+];
+
+/// Wallet transactions searched for confirmation samples
+const SAMPLE_WINDOW: usize = 200;
+
+/// Blocks walked back when measuring how long a transaction waited
+const MAX_WAIT_BLOCKS: u32 = 144;
+
+/// Bump over the fee rate of slow recent transactions, in percent
+const SLOW_BUMP_PERCENT: f64 = 25.0;
+
+/// Mempool transactions with at least a fee rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeBucket {
+    /// Lower bound of the bucket, in sat/vB
+    pub min_fee_rate: f64,
+    pub tx_count: usize,
+    pub vsize: u64,
+}
+
+/// Fee rate and size of the transactions in the mempool
+#[derive(Debug, Clone, Default)]
+pub struct MempoolSnapshot {
+    /// `(fee rate in sat/vB, vsize)` per transaction, highest rate first
+    pub transactions: Vec<(f64, u64)>,
+    pub total_fee_sats: u64,
+    /// Lowest fee rate the node accepts, in sat/vB
+    pub min_fee_rate: f64,
+}
+
+/// A recent confirmed ANCHOR transaction sent by the wallet
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmationSample {
+    pub carrier: String,
+    /// Fee rate paid, in sat/vB
+    pub fee_rate: f64,
+    /// Blocks mined between first seeing the transaction and its confirmation, inclusive
+    pub blocks: u32,
+}
+
+/// What recent transactions of a carrier paid and waited
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentConfirmations {
+    pub samples: usize,
+    pub median_fee_rate: f64,
+    pub median_blocks: u32,
+}
+
+/// Recommendation for one carrier
+#[derive(Debug, Clone)]
+pub struct CarrierAdvice {
+    pub carrier: String,
+    /// Transactions a message needs (commit and reveal for witness carriers)
+    pub transactions: u32,
+    /// Recommended fee rate, in sat/vB
+    pub fee_rate: u64,
+    /// Blocks until confirmation expected at that rate
+    pub predicted_blocks: u32,
+    /// Whether slow recent transactions raised the rate
+    pub adjusted: bool,
+    pub recent: Option<RecentConfirmations>,
+}
+
+/// Fee advisory for a confirmation target
+#[derive(Debug, Clone)]
+pub struct FeeAdvisory {
+    pub block_height: u64,
+    pub target_blocks: u16,
+    /// `estimatesmartfee`, `mempool` or `default`
+    pub basis: &'static str,
+    /// Base fee rate for the target before per-carrier adjustments, in sat/vB
+    pub fee_rate: f64,
+    /// `low`, `medium` or `high`
+    pub congestion: &'static str,
+    pub mempool_tx_count: usize,
+    pub mempool_vsize: u64,
+    pub mempool_total_fee_sats: u64,
+    pub mempool_min_fee_rate: f64,
+    pub histogram: Vec<FeeBucket>,
+    /// Node estimates per target, in sat/vB, when the node has one
+    pub estimates: Vec<(u16, Option<f64>)>,
+    pub carriers: Vec<CarrierAdvice>,
+}
+
+impl WalletService {
+    /// Fee advisory for confirmation within `target_blocks`, falling back to
+    /// `default_fee_rate` when neither the node nor the mempool has an answer
+    pub fn fee_advisory(&self, target_blocks: u16, default_fee_rate: u64) -> Result<FeeAdvisory> {
+        let rpc = self.base_rpc();
+        let block_height = rpc.get_block_count()?;
+        let mempool = self.mempool_snapshot()?;
+        let estimates: Vec<(u16, Option<f64>)> = ESTIMATE_TARGETS
+            .iter()
+            .map(|&target| {
+                let rate = rpc
+                    .estimate_smart_fee(target, Some(EstimateMode::Economical))
+                    .ok()
+                    .and_then(|result| result.fee_rate)
+                    .map(|rate| rate.to_sat() as f64 / 1000.0);
+                (target, rate)
+            })
+            .collect();
+        // Without wallet history the advisory still stands on the node's data
+        let samples = self.confirmation_samples().unwrap_or_default();
+
+        Ok(advise(
+            block_height,
+            target_blocks,
+            &mempool,
+            &estimates,
+            &samples,
+            default_fee_rate as f64,
+        ))
+    }
+
+    /// Fee rates and sizes of the node's mempool
+    pub fn mempool_snapshot(&self) -> Result<MempoolSnapshot> {
+        let rpc = self.base_rpc();
+        let info = rpc.get_mempool_info()?;
+        let entries = rpc.get_raw_mempool_verbose()?;
+
+        let mut total_fee_sats = 0;
+        let mut transactions: Vec<(f64, u64)> = entries
+            .values()
+            .filter(|entry| entry.vsize > 0)
+            .map(|entry| {
+                let fee = entry.fees.base.to_sat();
+                total_fee_sats += fee;
+                (fee as f64 / entry.vsize as f64, entry.vsize)
+            })
+            .collect();
+        transactions.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        Ok(MempoolSnapshot {
+            transactions,
+            total_fee_sats,
+            min_fee_rate: info.mempool_min_fee.to_sat() as f64 / 1000.0,
+        })
+    }
+
+    /// Confirmed ANCHOR transactions recently sent by the wallet
+    pub fn confirmation_samples(&self) -> Result<Vec<ConfirmationSample>> {
+        self.with_wallet_check(|| {
+            let entries =
+                self.rpc()
+                    .list_transactions(None, Some(SAMPLE_WINDOW), None, Some(true))?;
+            let mut seen = HashSet::new();
+            let mut samples = Vec::new();
+
+            for entry in entries.iter().rev() {
+                if entry.detail.category != Category::Send
+                    || entry.info.confirmations <= 0
+                    || !seen.insert(entry.info.txid)
+                {
+                    continue;
+                }
+                let result = self.rpc().get_transaction(&entry.info.txid, Some(true))?;
+                let (Some(fee), Some(block_hash), Ok(tx)) =
+                    (result.fee, result.info.blockhash, result.transaction())
+                else {
+                    continue;
+                };
+                let Some(message) = decode_messages(&tx).into_iter().next() else {
+                    continue;
+                };
+
+                samples.push(ConfirmationSample {
+                    carrier: message.carrier,
+                    fee_rate: fee.to_sat().unsigned_abs() as f64 / tx.vsize() as f64,
+                    blocks: self.blocks_waited(block_hash, result.info.timereceived)?,
+                });
+            }
+            Ok(samples)
+        })
+    }
+
+    /// Blocks from the first one mined after `received` up to `block_hash`
+    fn blocks_waited(&self, block_hash: bitcoin::BlockHash, received: u64) -> Result<u32> {
+        let rpc = self.base_rpc();
+        let mut header = rpc.get_block_header(&block_hash)?;
+        let mut blocks = 1;
+        while blocks < MAX_WAIT_BLOCKS {
+            let previous = rpc.get_block_header(&header.prev_blockhash)?;
+            if (previous.time as u64) <= received {
+                break;
+            }
+            header = previous;
+            blocks += 1;
+        }
+        Ok(blocks)
+    }
+}
+
+/// Combine the sources into an advisory
+pub fn advise(
+    block_height: u64,
+    target_blocks: u16,
+    mempool: &MempoolSnapshot,
+    estimates: &[(u16, Option<f64>)],
+    samples: &[ConfirmationSample],
+    default_fee_rate: f64,
+) -> FeeAdvisory {
+    let floor = mempool.min_fee_rate.max(1.0);
+    let estimate = estimates
+        .iter()
+        .filter(|(target, _)| *target <= target_blocks)
+        .filter_map(|(_, rate)| *rate)
+        .reduce(f64::min);
+    let (basis, fee_rate) = match (estimate, mempool_fee_rate(mempool, target_blocks)) {
+        (Some(rate), _) => ("estimatesmartfee", rate),
+        (None, Some(rate)) => ("mempool", rate),
+        (None, None) if mempool.transactions.is_empty() => ("default", default_fee_rate),
+        // Everything in the mempool confirms within the target
+        (None, None) => ("mempool", floor),
+    };
+    let fee_rate = fee_rate.max(floor);
+
+    let carriers = CarrierType::active_carriers()
+        .iter()
+        .map(|carrier| {
+            let name = carrier.to_string();
+            let recent = recent_confirmations(samples, &name);
+            let slow = recent.as_ref().filter(|recent| {
+                recent.median_blocks > target_blocks as u32 && recent.median_fee_rate >= fee_rate
+            });
+            let rate = match slow {
+                Some(recent) => recent.median_fee_rate * (1.0 + SLOW_BUMP_PERCENT / 100.0),
+                None => fee_rate,
+            };
+            let rate = rate.ceil() as u64;
+            CarrierAdvice {
+                carrier: name,
+                transactions: match carrier {
+                    CarrierType::OpReturn | CarrierType::Stamps => 1,
+                    _ => 2,
+                },
+                fee_rate: rate,
+                predicted_blocks: predicted_blocks(mempool, estimates, rate as f64),
+                adjusted: slow.is_some(),
+                recent,
+            }
+        })
+        .collect();
+
+    let mempool_vsize: u64 = mempool.transactions.iter().map(|(_, vsize)| vsize).sum();
+    FeeAdvisory {
+        block_height,
+        target_blocks,
+        basis,
+        fee_rate,
+        congestion: congestion(mempool_vsize),
+        mempool_tx_count: mempool.transactions.len(),
+        mempool_vsize,
+        mempool_total_fee_sats: mempool.total_fee_sats,
+        mempool_min_fee_rate: mempool.min_fee_rate,
+        histogram: histogram(&mempool.transactions),
+        estimates: estimates.to_vec(),
+        carriers,
+    }
+}
+
+/// Mempool transactions per fee rate bucket, highest bucket first; empty
+/// buckets are left out
+pub fn histogram(transactions: &[(f64, u64)]) -> Vec<FeeBucket> {
+    let mut buckets: Vec<FeeBucket> = BUCKETS
+        .iter()
+        .rev()
+        .map(|&min_fee_rate| FeeBucket {
+            min_fee_rate,
+            tx_count: 0,
+            vsize: 0,
+        })
+        .collect();
+    let lowest = buckets.len() - 1;
+    for &(rate, vsize) in transactions {
+        let index = buckets
+            .iter()
+            .position(|bucket| rate >= bucket.min_fee_rate)
+            .unwrap_or(lowest);
+        buckets[index].tx_count += 1;
+        buckets[index].vsize += vsize;
+    }
+    buckets.retain(|bucket| bucket.tx_count > 0);
+    buckets
+}
+
+/// Fee rate that outbids everything beyond the first `target_blocks` blocks
+/// of the mempool, or `None` when the whole mempool fits in them
+pub fn mempool_fee_rate(mempool: &MempoolSnapshot, target_blocks: u16) -> Option<f64> {
+    let capacity = target_blocks as u64 * BLOCK_VSIZE;
+    let mut cumulative = 0;
+    for &(rate, vsize) in &mempool.transactions {
+        cumulative += vsize;
+        if cumulative > capacity {
+            return Some(rate);
+        }
+    }
+    None
+}
+
+/// Blocks until a transaction paying `fee_rate` is expected to confirm
+///
+/// The node's estimates answer first: the smallest target whose estimate
+/// the rate meets. Otherwise the rate's position in the mempool decides.
+pub fn predicted_blocks(
+    mempool: &MempoolSnapshot,
+    estimates: &[(u16, Option<f64>)],
+    fee_rate: f64,
+) -> u32 {
+    let known: Vec<(u16, f64)> = estimates
+        .iter()
+        .filter_map(|&(target, rate)| rate.map(|rate| (target, rate)))
+        .collect();
+    if !known.is_empty() {
+        if let Some((target, _)) = known.iter().find(|(_, rate)| fee_rate >= *rate) {
+            return *target as u32;
+        }
+    }
+
+    let ahead: u64 = mempool
+        .transactions
+        .iter()
+        .take_while(|(rate, _)| *rate >= fee_rate)
+        .map(|(_, vsize)| vsize)
+        .sum();
+    (ahead / BLOCK_VSIZE) as u32 + 1
+}
+
+/// Congestion level from the mempool size, in blocks' worth of vbytes
+pub fn congestion(mempool_vsize: u64) -> &'static str {
+    match mempool_vsize / BLOCK_VSIZE {
+        0 => "low",
+        1..=3 => "medium",
+        _ => "high",
+    }
+}
+
+/// Medians over a carrier's samples
+fn recent_confirmations(
+    samples: &[ConfirmationSample],
+    carrier: &str,
+) -> Option<RecentConfirmations> {
+    let mut fee_rates: Vec<f64> = Vec::new();
+    let mut blocks: Vec<u32> = Vec::new();
+    for sample in samples.iter().filter(|sample| sample.carrier == carrier) {
+        fee_rates.push(sample.fee_rate);
+        blocks.push(sample.blocks);
+    }
+    if fee_rates.is_empty() {
+        return None;
+    }
+    fee_rates.sort_by(f64::total_cmp);
+    blocks.sort_unstable();
+    Some(RecentConfirmations {
+        samples: fee_rates.len(),
+        median_fee_rate: fee_rates[fee_rates.len() / 2],
+        median_blocks: blocks[blocks.len() / 2],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mempool(transactions: Vec<(f64, u64)>) -> MempoolSnapshot {
+        MempoolSnapshot {
+            transactions,
+            total_fee_sats: 0,
+            min_fee_rate: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let buckets = histogram(&[(0.5, 100), (1.5, 200), (12.0, 300), (11.0, 50), (2000.0, 1)]);
+        assert_eq!(
+            buckets,
+            vec![
+                FeeBucket {
+                    min_fee_rate: 1000.0,
+                    tx_count: 1,
+                    vsize: 1
+                },
+                FeeBucket {
+                    min_fee_rate: 10.0,
+                    tx_count: 2,
+                    vsize: 350
+                },
+                FeeBucket {
+                    min_fee_rate: 1.0,
+                    tx_count: 2,
+                    vsize: 300
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mempool_fee_rate() {
+        // Two and a half blocks: 50 sat/vB, then 20, then 5
+        let pool = mempool(vec![
+            (50.0, BLOCK_VSIZE),
+            (20.0, BLOCK_VSIZE),
+            (5.0, BLOCK_VSIZE / 2),
+        ]);
+        assert_eq!(mempool_fee_rate(&pool, 1), Some(20.0));
+        assert_eq!(mempool_fee_rate(&pool, 2), Some(5.0));
+        assert_eq!(mempool_fee_rate(&pool, 3), None);
+
+        assert_eq!(predicted_blocks(&pool, &[], 60.0), 1);
+        assert_eq!(predicted_blocks(&pool, &[], 20.0), 3);
+        assert_eq!(predicted_blocks(&pool, &[], 1.0), 3);
+        assert_eq!(congestion(0), "low");
+        assert_eq!(congestion(2 * BLOCK_VSIZE + 1), "medium");
+        assert_eq!(congestion(10 * BLOCK_VSIZE), "high");
+    }
+
+    #[test]
+    fn test_advise_prefers_node_estimates() {
+        let pool = mempool(vec![(50.0, 2 * BLOCK_VSIZE)]);
+        let estimates = vec![(1, Some(30.0)), (3, Some(12.0)), (6, None)];
+
+        let advisory = advise(100, 3, &pool, &estimates, &[], 2.0);
+        assert_eq!(advisory.basis, "estimatesmartfee");
+        assert_eq!(advisory.fee_rate, 12.0);
+        let op_return = &advisory.carriers[0];
+        assert_eq!(op_return.carrier, "op_return");
+        assert_eq!(op_return.transactions, 1);
+        assert_eq!(op_return.fee_rate, 12);
+        assert_eq!(op_return.predicted_blocks, 3);
+
+        let advisory = advise(100, 1, &pool, &[], &[], 2.0);
+        assert_eq!(advisory.basis, "mempool");
+        assert_eq!(advisory.fee_rate, 50.0);
+
+        let advisory = advise(100, 3, &mempool(vec![]), &[], &[], 2.0);
+        assert_eq!(advisory.basis, "default");
+        assert_eq!(advisory.fee_rate, 2.0);
+    }
+
+    #[test]
+    fn test_advise_bumps_slow_carriers() {
+        let samples = vec![
+            ConfirmationSample {
+                carrier: "inscription".to_string(),
+                fee_rate: 10.0,
+                blocks: 8,
+            },
+            ConfirmationSample {
+                carrier: "op_return".to_string(),
+                fee_rate: 10.0,
+                blocks: 1,
+            },
+        ];
+        let estimates = vec![(3, Some(8.0))];
+        let advisory = advise(100, 3, &mempool(vec![]), &estimates, &samples, 2.0);
+
+        let carrier = |name: &str| {
+            advisory
+                .carriers
+                .iter()
+                .find(|advice| advice.carrier == name)
+                .unwrap()
+        };
+        assert_eq!(carrier("op_return").fee_rate, 8);
+        assert!(!carrier("op_return").adjusted);
+        assert_eq!(carrier("inscription").fee_rate, 13);
+        assert!(carrier("inscription").adjusted);
+        assert_eq!(carrier("inscription").transactions, 2);
+        assert_eq!(carrier("inscription").recent.as_ref().unwrap().samples, 1);
+    }
+}
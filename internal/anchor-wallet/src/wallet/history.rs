@@ -0,0 +1,414 @@
+//! Wallet transaction history with decoded ANCHOR messages
+//!
+//! Transactions come from Bitcoin Core's wallet; each one's raw bytes are
+//! run through the anchor-core carrier detector, and known kinds are parsed
+//! with their anchor-specs spec into a short description such as
+//! "registered alice.btc". Token messages only carry a numeric token id, so
+//! their description is finished by the caller once the ticker is known.
+
+use anchor_core::carrier::CarrierSelector;
+use anchor_core::AnchorKind;
+use anchor_specs::dns::{DnsOperation, DnsSpec};
+use anchor_specs::escrow::{EscrowOperation, EscrowSpec};
+use anchor_specs::identity::IdentityRotationSpec;
+use anchor_specs::mirror::MirrorSpec;
+use anchor_specs::prelude::*;
+use anchor_specs::proof::{ProofOperation, ProofSpec};
+use anchor_specs::state::StateSpec;
+use anchor_specs::text::TextSpec;
+use anchor_specs::token::{TokenOperation, TokenSpec};
+use anyhow::Result;
+use bitcoin::{Transaction, Txid};
+use bitcoincore_rpc::json::GetTransactionResultDetailCategory as Category;
+use bitcoincore_rpc::RpcApi;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use super::service::WalletService;
+
+/// Characters of text bodies kept in a preview
+const PREVIEW_CHARS: usize = 80;
+
+/// Bytes of binary bodies kept (as hex) in a preview
+const PREVIEW_BYTES: usize = 32;
+
+/// A wallet transaction and the ANCHOR messages it carries
+#[derive(Debug, Clone)]
+pub struct WalletTransaction {
+    pub txid: String,
+    /// `send`, `receive`, `self`, `generate`, `immature` or `orphan`
+    pub category: String,
+    /// Net effect on the wallet in BTC, excluding the fee
+    pub amount: f64,
+    /// Fee in BTC, for transactions the wallet paid for
+    pub fee: Option<f64>,
+    pub confirmations: i32,
+    pub block_height: Option<u32>,
+    /// Unix time the transaction was first seen or mined
+    pub time: u64,
+    pub messages: Vec<DecodedMessage>,
+}
+
+/// An ANCHOR message found in a wallet transaction
+#[derive(Debug, Clone)]
+pub struct DecodedMessage {
+    pub vout: u32,
+    pub carrier: String,
+    pub kind: u8,
+    pub kind_name: String,
+    /// Text bodies as text, binary bodies as hex, both truncated
+    pub body_preview: String,
+    /// What the message did, e.g. "registered alice.btc"
+    pub description: Option<String>,
+    /// App whose indexer tracks the message
+    pub app: Option<&'static str>,
+    /// The app's identifier for the affected item (domain name, token id, ...)
+    pub reference: Option<String>,
+    /// Token operation awaiting the token's ticker
+    pub token: Option<TokenOperation>,
+}
+
+impl WalletService {
+    /// Most recent distinct transaction ids in the wallet, newest first
+    pub fn list_history_txids(&self, limit: usize) -> Result<Vec<String>> {
+        self.with_wallet_check(|| {
+            let entries = self
+                .rpc()
+                .list_transactions(None, Some(limit), None, Some(true))?;
+
+            let mut seen = HashSet::new();
+            Ok(entries
+                .iter()
+                .rev()
+                .map(|entry| entry.info.txid.to_string())
+                .filter(|txid| seen.insert(txid.clone()))
+                .collect())
+        })
+    }
+
+    /// A wallet transaction with its ANCHOR messages decoded
+    pub fn get_history_entry(&self, txid: &str) -> Result<WalletTransaction> {
+        let txid = Txid::from_str(txid)?;
+        self.with_wallet_check(|| {
+            let result = self.rpc().get_transaction(&txid, Some(true))?;
+            let categories: Vec<&Category> = result.details.iter().map(|d| &d.category).collect();
+
+            Ok(WalletTransaction {
+                txid: txid.to_string(),
+                category: category(&categories).to_string(),
+                amount: result.amount.to_btc(),
+                fee: result.fee.map(|fee| fee.to_btc().abs()),
+                confirmations: result.info.confirmations,
+                block_height: result.info.blockheight,
+                time: result.info.time,
+                messages: result
+                    .transaction()
+                    .map(|tx| decode_messages(&tx))
+                    .unwrap_or_default(),
+            })
+        })
+    }
+}
+
+/// Summarise the per-output categories Core reports for one transaction
+fn category(categories: &[&Category]) -> &'static str {
+    let has = |c: Category| categories.iter().any(|x| **x == c);
+    if has(Category::Generate) {
+        "generate"
+    } else if has(Category::Immature) {
+        "immature"
+    } else if has(Category::Orphan) {
+        "orphan"
+    } else if has(Category::Send) && has(Category::Receive) {
+        "self"
+    } else if has(Category::Receive) {
+        "receive"
+    } else {
+        "send"
+    }
+}
+
+/// Decode every ANCHOR message a transaction carries
+pub fn decode_messages(tx: &Transaction) -> Vec<DecodedMessage> {
+    CarrierSelector::new()
+        .detect(tx)
+        .into_iter()
+        .map(|detected| {
+            let message = detected.message;
+            let kind = u8::from(message.kind);
+            let mut decoded = DecodedMessage {
+                vout: detected.vout,
+                carrier: detected.carrier_type.to_string(),
+                kind,
+                kind_name: kind_name(kind).to_string(),
+                body_preview: preview(kind, &message.body),
+                description: None,
+                app: None,
+                reference: None,
+                token: None,
+            };
+            describe(&mut decoded, &message.body);
+            decoded
+        })
+        .collect()
+}
+
+/// Lower-case name of a kind, from the kind registry
+pub fn kind_name(kind: u8) -> &'static str {
+    match kind {
+        5 => "geomarker",
+        10 => "dns",
+        11 => "proof",
+        20 => "token",
+        50 => "escrow",
+        _ => match AnchorKind::from(kind) {
+            AnchorKind::Generic => "generic",
+            AnchorKind::Text => "text",
+            AnchorKind::State => "state",
+            AnchorKind::Vote => "vote",
+            AnchorKind::Image => "image",
+            AnchorKind::Identity => "identity",
+            AnchorKind::Mirror => "mirror",
+            AnchorKind::Oracle => "oracle",
+            AnchorKind::OracleAttestation => "oracle_attestation",
+            AnchorKind::OracleDispute => "oracle_dispute",
+            AnchorKind::OracleSlash => "oracle_slash",
+            AnchorKind::MarketCreate => "market_create",
+            AnchorKind::PlaceBet => "place_bet",
+            AnchorKind::MarketResolve => "market_resolve",
+            AnchorKind::ClaimWinnings => "claim_winnings",
+            AnchorKind::MarketOrder => "market_order",
+            AnchorKind::LotteryCreate => "lottery_create",
+            AnchorKind::LotteryTicket => "lottery_ticket",
+            AnchorKind::LotteryDraw => "lottery_draw",
+            AnchorKind::LotteryClaim => "lottery_claim",
+            AnchorKind::Custom(_) => "custom",
+        },
+    }
+}
+
+fn preview(kind: u8, body: &[u8]) -> String {
+    if kind == TextSpec::KIND_ID {
+        if let Ok(text) = std::str::from_utf8(body) {
+            let mut preview: String = text.chars().take(PREVIEW_CHARS).collect();
+            if text.chars().count() > PREVIEW_CHARS {
+                preview.push('…');
+            }
+            return preview;
+        }
+    }
+    let mut preview = hex::encode(&body[..body.len().min(PREVIEW_BYTES)]);
+    if body.len() > PREVIEW_BYTES {
+        preview.push('…');
+    }
+    preview
+}
+
+/// Fill in the description, app and reference for the kinds we can parse
+fn describe(message: &mut DecodedMessage, body: &[u8]) {
+    let (description, app, reference) = match message.kind {
+        TextSpec::KIND_ID => ("posted a message".to_string(), "threads", None),
+        DnsSpec::KIND_ID => {
+            let Ok(spec) = DnsSpec::from_bytes(body) else {
+                return;
+            };
+            let action = match spec.operation {
+                DnsOperation::Register => "registered",
+                DnsOperation::Update => "updated",
+                DnsOperation::Transfer => "transferred",
+            };
+            (
+                format!("{} {}", action, spec.name),
+                "domains",
+                Some(spec.name),
+            )
+        }
+        TokenSpec::KIND_ID => {
+            let Ok(spec) = TokenSpec::from_bytes(body) else {
+                return;
+            };
+            let reference = match &spec.operation {
+                TokenOperation::Deploy { ticker, .. } => Some(ticker.clone()),
+                operation => token_id(operation).map(|id| id.to_string()),
+            };
+            let description = describe_token(&spec.operation, None);
+            message.token = Some(spec.operation);
+            (description, "tokens", reference)
+        }
+        StateSpec::KIND_ID => {
+            let Ok(spec) = StateSpec::from_bytes(body) else {
+                return;
+            };
+            let description = match spec.pixels.len() {
+                1 => "painted 1 pixel".to_string(),
+                n => format!("painted {} pixels", n),
+            };
+            (description, "canvas", None)
+        }
+        ProofSpec::KIND_ID => {
+            let Ok(spec) = ProofSpec::from_bytes(body) else {
+                return;
+            };
+            let description = match (spec.operation, spec.entries.len()) {
+                (ProofOperation::Revoke, _) => "revoked a proof".to_string(),
+                (ProofOperation::Witness, _) => "witnessed a proof".to_string(),
+                (_, 1) => "stamped a proof".to_string(),
+                (_, n) => format!("stamped {} proofs", n),
+            };
+            (description, "proofs", None)
+        }
+        IdentityRotationSpec::KIND_ID => ("rotated an identity key".to_string(), "identity", None),
+        MirrorSpec::KIND_ID => {
+            let Ok(spec) = MirrorSpec::from_bytes(body) else {
+                return;
+            };
+            (
+                format!("reposted a {} message", kind_name(spec.kind)),
+                "threads",
+                None,
+            )
+        }
+        EscrowSpec::KIND_ID => {
+            let Ok(spec) = EscrowSpec::from_bytes(body) else {
+                return;
+            };
+            let description = match spec.operation {
+                EscrowOperation::Fund { amount, .. } => {
+                    format!("funded an escrow of {} sats", amount)
+                }
+                EscrowOperation::Release => "released an escrow".to_string(),
+                EscrowOperation::Refund => "refunded an escrow".to_string(),
+                EscrowOperation::Dispute { .. } => "disputed an escrow".to_string(),
+            };
+            (description, "escrow", None)
+        }
+        30..=33 => (kind_name(message.kind).replace('_', " "), "oracles", None),
+        40..=44 => (
+            kind_name(message.kind).replace('_', " "),
+            "predictions",
+            None,
+        ),
+        _ => return,
+    };
+    message.description = Some(description);
+    message.app = Some(app);
+    message.reference = reference;
+}
+
+/// Token id a token operation refers to (none for deploys)
+pub fn token_id(operation: &TokenOperation) -> Option<u64> {
+    match operation {
+        TokenOperation::Deploy { .. } => None,
+        TokenOperation::Mint { token_id, .. }
+        | TokenOperation::Transfer { token_id, .. }
+        | TokenOperation::Burn { token_id, .. }
+        | TokenOperation::Split { token_id, .. }
+        | TokenOperation::Approve { token_id, .. } => Some(*token_id),
+    }
+}
+
+/// Describe a token operation, with amounts in whole units once the token's
+/// ticker and decimals are known
+pub fn describe_token(operation: &TokenOperation, token: Option<(&str, u8)>) -> String {
+    let amount = |raw: u128| match token {
+        Some((ticker, decimals)) => format!("{} {}", format_units(raw, decimals), ticker),
+        None => format!(
+            "{} of token #{}",
+            raw,
+            token_id(operation).unwrap_or_default()
+        ),
+    };
+    match operation {
+        TokenOperation::Deploy { ticker, .. } => format!("deployed {}", ticker),
+        TokenOperation::Mint { amount: raw, .. } => format!("minted {}", amount(*raw)),
+        TokenOperation::Transfer { allocations, .. } => format!(
+            "transferred {}",
+            amount(allocations.iter().map(|a| a.amount).sum())
+        ),
+        TokenOperation::Burn { amount: raw, .. } => format!("burned {}", amount(*raw)),
+        TokenOperation::Split { allocations, .. } => format!(
+            "split {}",
+            amount(allocations.iter().map(|a| a.amount).sum())
+        ),
+        TokenOperation::Approve { amount: raw, .. } => format!("approved {}", amount(*raw)),
+    }
+}
+
+/// Format a raw token amount with `decimals` decimal places, trimming zeros
+pub fn format_units(raw: u128, decimals: u8) -> String {
+    if decimals == 0 {
+        return raw.to_string();
+    }
+    let scale = 10u128.pow(decimals as u32);
+    let fraction = format!("{:0width$}", raw % scale, width = decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (raw / scale).to_string()
+    } else {
+        format!("{}.{}", raw / scale, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_specs::token::TokenAllocation;
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(100, 0), "100");
+        assert_eq!(format_units(150_000_000, 8), "1.5");
+        assert_eq!(format_units(100_000_000, 8), "1");
+        assert_eq!(format_units(5, 3), "0.005");
+    }
+
+    #[test]
+    fn test_describe_token() {
+        let mint = TokenOperation::Mint {
+            token_id: 7,
+            amount: 10_000,
+            output_index: 1,
+        };
+        assert_eq!(
+            describe_token(&mint, Some(("TOKEN", 2))),
+            "minted 100 TOKEN"
+        );
+        assert_eq!(describe_token(&mint, None), "minted 10000 of token #7");
+
+        let transfer = TokenOperation::Transfer {
+            token_id: 7,
+            allocations: vec![TokenAllocation::new(1, 30), TokenAllocation::new(2, 70)],
+        };
+        assert_eq!(
+            describe_token(&transfer, Some(("TOKEN", 0))),
+            "transferred 100 TOKEN"
+        );
+    }
+
+    #[test]
+    fn test_describe_domain_registration() {
+        let body = DnsSpec::register("alice.btc", vec![]).to_bytes();
+        let mut message = DecodedMessage {
+            vout: 0,
+            carrier: "op_return".to_string(),
+            kind: DnsSpec::KIND_ID,
+            kind_name: kind_name(DnsSpec::KIND_ID).to_string(),
+            body_preview: preview(DnsSpec::KIND_ID, &body),
+            description: None,
+            app: None,
+            reference: None,
+            token: None,
+        };
+        describe(&mut message, &body);
+        assert_eq!(message.description.as_deref(), Some("registered alice.btc"));
+        assert_eq!(message.app, Some("domains"));
+        assert_eq!(message.reference.as_deref(), Some("alice.btc"));
+    }
+
+    #[test]
+    fn test_preview_truncates() {
+        let text = "x".repeat(PREVIEW_CHARS + 5);
+        assert!(preview(TextSpec::KIND_ID, text.as_bytes()).ends_with('…'));
+        assert_eq!(preview(0, &[0xab, 0xcd]), "abcd");
+    }
+}
@@ -4,16 +4,38 @@
 //! - `types` - Data structures (Utxo, Balance, CreatedTransaction)
 //! - `service` - WalletService core implementation (Bitcoin Core RPC)
 //! - `bdk_service` - BDK-based wallet with full key management
+//! - `addresses` - Gap-limit aware address handout and reuse detection
+//! - `electrum_pool` - Electrum server health checks and failover
+//! - `history` - Transaction history with decoded ANCHOR messages
+//! - `fees` - Fee advisory from node estimates, the mempool and recent sends
 //! - `anchor` - ANCHOR transaction creation
 //! - `advanced` - Advanced transaction with required inputs/outputs
+//! - `package` - Commit+reveal broadcast through package relay
+//! - `psbt` - Unsigned transactions for the watch-only profile
+//! - `escrow` - 2-of-3 escrow funding and settlement PSBTs
+//! - `regtest` - Chain controls for integration tests (regtest only)
+//! - `payjoin` - Payjoin (BIP-78) sending
+//! - `signer` - Client for the remote signing daemon
+//! - `timelock` - Signed messages held back by nLockTime
 //! - `specs` - Type-safe spec-based transaction creation
 //! - `carriers/` - Carrier-specific transaction builders
 
+pub mod addresses;
 mod advanced;
 mod anchor;
 pub mod bdk_service;
+pub mod electrum_pool;
+mod escrow;
+pub mod fees;
+pub mod history;
+mod package;
+pub mod payjoin;
+mod psbt;
+mod regtest;
 mod service;
+pub mod signer;
 mod specs;
+mod timelock;
 mod types;
 mod utils;
 
@@ -21,9 +43,13 @@ pub mod carriers;
 
 // Re-export public types
 pub use bdk_service::BdkWalletService;
+pub use electrum_pool::ElectrumPool;
+pub use escrow::settlement_psbt;
 pub use service::WalletService;
+pub use signer::RemoteSigner;
+pub use timelock::TimelockedTransaction;
 // Types are re-exported for external use
 #[allow(unused_imports)]
-pub use specs::AnchorRef;
+pub use specs::{AnchorRef, MessageSpec};
 #[allow(unused_imports)]
-pub use types::{Balance, CreatedTransaction, Utxo};
+pub use types::{Balance, CoinControl, CreatedTransaction, UnsignedTransaction, Utxo};
@@ -0,0 +1,161 @@
+//! Package relay for commit+reveal pairs
+//!
+//! Bitcoin Core 28 accepts a parent and its only child together through
+//! `submitpackage` (one-parent-one-child), judging the parent by the feerate
+//! of the pair. The reveal can then pay for its commit: with
+//! `ZERO_FEE_COMMIT` the commit carries no fee at all and both transactions
+//! are TRUC (version 3), which Core requires of zero-fee parents. TRUC
+//! allows one unconfirmed child, so the commit's change stays unspendable
+//! until it confirms. Older nodes get the pair one after the other.
+
+use anyhow::{Context, Result};
+use bitcoin::consensus::encode::deserialize_hex;
+use bitcoin::transaction::Version;
+use bitcoin::Transaction;
+use bitcoincore_rpc::RpcApi;
+use tracing::{debug, info};
+
+use super::service::WalletService;
+
+/// First Bitcoin Core version that relays one-parent-one-child packages
+const PACKAGE_RELAY_VERSION: usize = 280000;
+
+/// Largest TRUC child Bitcoin Core relays, in vbytes
+pub(crate) const TRUC_CHILD_MAX_VSIZE: u64 = 1000;
+
+/// Transaction version of TRUC (topologically restricted) transactions
+pub(crate) const TRUC_VERSION: Version = Version(3);
+
+/// A parent and child that reached the node's mempool
+#[derive(Debug, Clone)]
+pub struct PackageBroadcast {
+    pub parent_txid: String,
+    pub child_txid: String,
+    /// Submitted as a package rather than one after the other
+    pub packaged: bool,
+}
+
+impl WalletService {
+    /// Whether the node relays one-parent-one-child packages
+    pub fn supports_package_relay(&self) -> bool {
+        match self.base_rpc().get_network_info() {
+            Ok(info) => info.version >= PACKAGE_RELAY_VERSION,
+            Err(e) => {
+                debug!("getnetworkinfo failed, assuming no package relay: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Whether a commit+reveal pair should leave the whole fee to the reveal
+    ///
+    /// Needs `ZERO_FEE_COMMIT`, a node with package relay and a reveal small
+    /// enough to be a TRUC child.
+    pub(crate) fn zero_fee_commit(&self, reveal_vbytes: u64) -> bool {
+        if !self.zero_fee_commit || reveal_vbytes >= TRUC_CHILD_MAX_VSIZE {
+            return false;
+        }
+        let supported = self.supports_package_relay();
+        if !supported {
+            debug!("Node has no package relay, the commit pays its own fee");
+        }
+        supported
+    }
+
+    /// Broadcast a parent and the child spending it
+    ///
+    /// Uses `submitpackage` when the node supports package relay, so a
+    /// low-fee parent is carried by its child; otherwise sends the parent,
+    /// then the child.
+    pub fn broadcast_package(&self, parent_hex: &str, child_hex: &str) -> Result<PackageBroadcast> {
+        let parent: Transaction = deserialize_hex(parent_hex).context("Invalid parent hex")?;
+        let child: Transaction = deserialize_hex(child_hex).context("Invalid child hex")?;
+        let parent_txid = parent.compute_txid();
+        if !child
+            .input
+            .iter()
+            .any(|input| input.previous_output.txid == parent_txid)
+        {
+            anyhow::bail!("Child does not spend parent {}", parent_txid);
+        }
+
+        if self.supports_package_relay() {
+            let result: serde_json::Value = self.base_rpc().call(
+                "submitpackage",
+                &[serde_json::json!([parent_hex, child_hex])],
+            )?;
+            check_package_result(&result)?;
+            let sent = PackageBroadcast {
+                parent_txid: parent_txid.to_string(),
+                child_txid: child.compute_txid().to_string(),
+                packaged: true,
+            };
+            info!(
+                "Submitted package {} + {}",
+                sent.parent_txid, sent.child_txid
+            );
+            return Ok(sent);
+        }
+
+        let parent_txid: String = self
+            .base_rpc()
+            .call("sendrawtransaction", &[serde_json::json!(parent_hex)])?;
+        let child_txid: String = self
+            .base_rpc()
+            .call("sendrawtransaction", &[serde_json::json!(child_hex)])
+            .with_context(|| format!("Parent {} broadcast, child rejected", parent_txid))?;
+        Ok(PackageBroadcast {
+            parent_txid,
+            child_txid,
+            packaged: false,
+        })
+    }
+}
+
+/// Check a `submitpackage` result, collecting the per-transaction errors
+fn check_package_result(result: &serde_json::Value) -> Result<()> {
+    let message = result["package_msg"].as_str().unwrap_or("no package_msg");
+    if message == "success" {
+        return Ok(());
+    }
+    let errors: Vec<&str> = result["tx-results"]
+        .as_object()
+        .map(|results| {
+            results
+                .values()
+                .filter_map(|tx| tx["error"].as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+    if errors.is_empty() {
+        anyhow::bail!("Package rejected: {}", message);
+    }
+    anyhow::bail!("Package rejected: {} ({})", message, errors.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_package_result() {
+        let accepted = serde_json::json!({
+            "package_msg": "success",
+            "tx-results": { "aa": { "txid": "bb", "vsize": 150 } },
+            "replaced-transactions": []
+        });
+        assert!(check_package_result(&accepted).is_ok());
+
+        let rejected = serde_json::json!({
+            "package_msg": "transaction failed",
+            "tx-results": {
+                "aa": { "txid": "bb", "error": "min relay fee not met, 0 < 150" },
+                "cc": { "txid": "dd", "error": "bad-txns-inputs-missingorspent" }
+            }
+        });
+        let error = check_package_result(&rejected).unwrap_err().to_string();
+        assert!(error.contains("transaction failed"));
+        assert!(error.contains("min relay fee not met"));
+        assert!(check_package_result(&serde_json::json!({})).is_err());
+    }
+}
@@ -0,0 +1,586 @@
+//! Payjoin (BIP-78) sending
+//!
+//! A message that pays a recipient with a `pj=` endpoint in their BIP-21 URI
+//! is funded and signed as usual, but instead of being broadcast the signed
+//! PSBT is sent to the receiver, who may add inputs of their own. The
+//! proposal is checked so the receiver cannot alter the ANCHOR payload, our
+//! inputs or outputs, or take more than the agreed fee contribution, then
+//! signed and broadcast. Any failure along the way broadcasts the original
+//! transaction instead, so the payment always goes out.
+//!
+//! Only the OP_RETURN carrier is supported: the witness carriers commit to
+//! their outputs in an earlier transaction the receiver cannot join.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
+use bitcoin::{Address, Amount, Denomination, Psbt, ScriptBuf, Transaction, TxOut};
+use bitcoincore_rpc::RpcApi;
+use reqwest::Url;
+use std::collections::HashSet;
+use std::str::FromStr;
+use tracing::{info, warn};
+
+use super::service::WalletService;
+use super::types::CreatedTransaction;
+
+/// Virtual size of the P2WPKH input a receiver is expected to add
+const RECEIVER_INPUT_VBYTES: u64 = 68;
+
+/// Recipient of a payjoin payment, from a BIP-21 URI
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayjoinUri {
+    pub address: String,
+    /// Payment amount in satoshis
+    pub amount: u64,
+    /// Receiver's payjoin endpoint (`pj=`)
+    pub endpoint: Url,
+}
+
+impl PayjoinUri {
+    /// Parse `bitcoin:<address>?amount=<btc>&pj=<url>`
+    ///
+    /// The endpoint must be HTTPS or a Tor hidden service; plain HTTP is also
+    /// accepted on regtest.
+    pub fn parse(uri: &str, network: &str) -> Result<Self> {
+        let url = Url::parse(uri.trim()).context("Invalid payjoin URI")?;
+        if url.scheme() != "bitcoin" {
+            anyhow::bail!("Payjoin URI must start with bitcoin:");
+        }
+        let address = url.path().to_string();
+        if address.is_empty() {
+            anyhow::bail!("Payjoin URI has no address");
+        }
+
+        let mut amount = None;
+        let mut endpoint = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "amount" => {
+                    amount = Some(
+                        Amount::from_str_in(&value, Denomination::Bitcoin)
+                            .context("Invalid amount in payjoin URI")?
+                            .to_sat(),
+                    )
+                }
+                "pj" => endpoint = Some(Url::parse(&value).context("Invalid pj endpoint")?),
+                _ => {}
+            }
+        }
+        let amount = amount
+            .filter(|a| *a > 0)
+            .context("Payjoin URI must include an amount")?;
+        let endpoint = endpoint.context("URI has no pj= payjoin endpoint")?;
+
+        let onion = endpoint
+            .host_str()
+            .is_some_and(|host| host.ends_with(".onion"));
+        match endpoint.scheme() {
+            "https" => {}
+            "http" if onion || network == "regtest" => {}
+            scheme => anyhow::bail!(
+                "Payjoin endpoint must use https or a .onion address, not {}",
+                scheme
+            ),
+        }
+
+        Ok(Self {
+            address,
+            amount,
+            endpoint,
+        })
+    }
+}
+
+/// Signed transaction offered to the receiver, and broadcast if the payjoin fails
+#[derive(Debug, Clone)]
+pub struct PayjoinOriginal {
+    /// Finalized PSBT
+    pub psbt: Psbt,
+    pub tx: CreatedTransaction,
+    /// Output the receiver may take its fee contribution from (our change)
+    pub change_index: Option<usize>,
+    /// Recipient's output script
+    pub payee: ScriptBuf,
+    /// Requested fee rate in sat/vbyte
+    pub fee_rate: u64,
+    /// Most our change may be reduced by to pay for the receiver's input
+    pub max_additional_fee: u64,
+}
+
+impl WalletService {
+    /// Sign a funded message PSBT as the original transaction of a payjoin
+    ///
+    /// `unsigned_psbt` must pay `uri` alongside `custom_outputs`; any other non
+    /// OP_RETURN output is our change.
+    pub fn create_payjoin_original(
+        &self,
+        unsigned_psbt: &str,
+        uri: &PayjoinUri,
+        custom_outputs: &[(String, u64)],
+        fee_rate: u64,
+    ) -> Result<PayjoinOriginal> {
+        let processed: serde_json::Value = self.rpc().call(
+            "walletprocesspsbt",
+            &[
+                serde_json::json!(unsigned_psbt),
+                serde_json::json!(true),
+                serde_json::json!("ALL"),
+                serde_json::json!(false),
+            ],
+        )?;
+        if processed["complete"] != true {
+            anyhow::bail!("Wallet could not sign the original payjoin transaction");
+        }
+        let signed = processed["psbt"]
+            .as_str()
+            .context("No psbt in processed transaction")?;
+        let hex = self.finalize_psbt(signed)?;
+        let psbt = decode_psbt(signed)?;
+
+        let payee = script_for(&uri.address)?;
+        let mut ours: HashSet<ScriptBuf> = HashSet::from([payee.clone()]);
+        for (address, _) in custom_outputs {
+            ours.insert(script_for(address)?);
+        }
+        let change_index = psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .position(|o| !o.script_pubkey.is_op_return() && !ours.contains(&o.script_pubkey));
+
+        let tx: Transaction = deserialize_hex(&hex).context("Invalid finalized transaction")?;
+        Ok(PayjoinOriginal {
+            tx: created(&tx),
+            psbt,
+            change_index,
+            payee,
+            fee_rate,
+            max_additional_fee: fee_rate * RECEIVER_INPUT_VBYTES,
+        })
+    }
+
+    /// Check, sign and broadcast the receiver's payjoin proposal
+    pub fn complete_payjoin(
+        &self,
+        original: &PayjoinOriginal,
+        mut proposal: Psbt,
+    ) -> Result<CreatedTransaction> {
+        check_proposal(original, &proposal)?;
+
+        // Receivers strip our inputs; restore the UTXO data the signer needs
+        let sender = &original.psbt;
+        for (txin, input) in proposal
+            .unsigned_tx
+            .input
+            .iter()
+            .zip(proposal.inputs.iter_mut())
+        {
+            if let Some(k) = sender
+                .unsigned_tx
+                .input
+                .iter()
+                .position(|o| o.previous_output == txin.previous_output)
+            {
+                input.witness_utxo = sender.inputs[k].witness_utxo.clone();
+                input.non_witness_utxo = sender.inputs[k].non_witness_utxo.clone();
+                input.final_script_sig = None;
+                input.final_script_witness = None;
+                input.partial_sigs.clear();
+            }
+        }
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(proposal.serialize());
+        let processed: serde_json::Value = self.rpc().call(
+            "walletprocesspsbt",
+            &[
+                serde_json::json!(encoded),
+                serde_json::json!(true),
+                serde_json::json!("ALL"),
+                serde_json::json!(false),
+            ],
+        )?;
+        let signed = processed["psbt"]
+            .as_str()
+            .context("No psbt in processed proposal")?;
+        let hex = self.finalize_psbt(signed)?;
+        let tx: Transaction = deserialize_hex(&hex).context("Invalid payjoin transaction")?;
+
+        self.broadcast(&hex)
+            .context("Failed to broadcast payjoin")?;
+        info!(
+            "Broadcast payjoin {} ({} inputs) in place of {}",
+            tx.compute_txid(),
+            tx.input.len(),
+            original.tx.txid
+        );
+        Ok(created(&tx))
+    }
+
+    /// Broadcast the original transaction after a failed payjoin
+    ///
+    /// If that fails too, the inputs locked while funding are released so
+    /// they can be spent again.
+    pub fn broadcast_payjoin_original(
+        &self,
+        original: &PayjoinOriginal,
+    ) -> Result<CreatedTransaction> {
+        match self.broadcast(&original.tx.hex) {
+            Ok(_) => Ok(original.tx.clone()),
+            Err(e) => {
+                let outpoints: Vec<serde_json::Value> = original
+                    .psbt
+                    .unsigned_tx
+                    .input
+                    .iter()
+                    .map(|i| {
+                        serde_json::json!({
+                            "txid": i.previous_output.txid.to_string(),
+                            "vout": i.previous_output.vout,
+                        })
+                    })
+                    .collect();
+                if let Err(unlock) = self
+                    .rpc()
+                    .call::<bool>("lockunspent", &[true.into(), outpoints.into()])
+                {
+                    warn!("Failed to release payjoin inputs: {}", unlock);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Send the original PSBT to the receiver and return its proposal
+pub async fn request_proposal(
+    client: &reqwest::Client,
+    uri: &PayjoinUri,
+    original: &PayjoinOriginal,
+) -> Result<Psbt> {
+    let mut url = uri.endpoint.clone();
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("v", "1")
+            .append_pair("disableoutputsubstitution", "true")
+            .append_pair("minfeerate", &original.fee_rate.to_string());
+        if let Some(index) = original.change_index {
+            query
+                .append_pair("additionalfeeoutputindex", &index.to_string())
+                .append_pair(
+                    "maxadditionalfeecontribution",
+                    &original.max_additional_fee.to_string(),
+                );
+        }
+    }
+
+    // Key origins would tell the receiver about the rest of our wallet
+    let mut psbt = original.psbt.clone();
+    psbt.xpub.clear();
+    for output in &mut psbt.outputs {
+        output.bip32_derivation.clear();
+        output.tap_key_origins.clear();
+    }
+    let body = base64::engine::general_purpose::STANDARD.encode(psbt.serialize());
+
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "text/plain")
+        .body(body)
+        .send()
+        .await
+        .context("Payjoin endpoint unreachable")?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .context("Failed to read payjoin response")?;
+    if !status.is_success() {
+        anyhow::bail!("Payjoin endpoint returned {}: {}", status, text.trim());
+    }
+    decode_psbt(text.trim()).context("Invalid payjoin proposal")
+}
+
+/// Sender checks from BIP-78 against a receiver's proposal
+pub fn check_proposal(original: &PayjoinOriginal, proposal: &Psbt) -> Result<()> {
+    let sender = &original.psbt;
+    let (original_tx, tx) = (&sender.unsigned_tx, &proposal.unsigned_tx);
+    if tx.version != original_tx.version || tx.lock_time != original_tx.lock_time {
+        anyhow::bail!("Proposal changed the transaction version or locktime");
+    }
+    if tx.input.len() != proposal.inputs.len() || tx.output.len() != proposal.outputs.len() {
+        anyhow::bail!("Proposal PSBT is malformed");
+    }
+
+    // Inputs: all of ours, unchanged, plus finalized receiver inputs
+    let mut seen = 0;
+    let mut receiver_inputs = 0;
+    for (txin, input) in tx.input.iter().zip(&proposal.inputs) {
+        match original_tx
+            .input
+            .iter()
+            .find(|o| o.previous_output == txin.previous_output)
+        {
+            Some(ours) => {
+                if ours.sequence != txin.sequence {
+                    anyhow::bail!("Proposal changed the sequence of our input");
+                }
+                seen += 1;
+            }
+            None => {
+                if input.final_script_sig.is_none() && input.final_script_witness.is_none() {
+                    anyhow::bail!("Receiver input {} is not finalized", txin.previous_output);
+                }
+                let prevout = spent_output(proposal, txin, input)?;
+                let sender_script = sender
+                    .inputs
+                    .first()
+                    .and_then(|i| i.witness_utxo.as_ref())
+                    .map(|o| &o.script_pubkey);
+                if let Some(script) = sender_script {
+                    if script.is_p2wpkh() != prevout.script_pubkey.is_p2wpkh()
+                        || script.is_p2tr() != prevout.script_pubkey.is_p2tr()
+                    {
+                        anyhow::bail!("Receiver input script type differs from ours");
+                    }
+                }
+                receiver_inputs += 1;
+            }
+        }
+    }
+    if seen != original_tx.input.len() {
+        anyhow::bail!("Proposal dropped one of our inputs");
+    }
+    if receiver_inputs == 0 {
+        anyhow::bail!("Receiver did not contribute an input");
+    }
+
+    // Outputs: every original output present; only our change may shrink
+    let mut used = vec![false; tx.output.len()];
+    for (index, output) in original_tx.output.iter().enumerate() {
+        let found = tx
+            .output
+            .iter()
+            .enumerate()
+            .position(|(i, o)| !used[i] && o.script_pubkey == output.script_pubkey)
+            .with_context(|| format!("Proposal dropped output {}", index))?;
+        used[found] = true;
+        let value = tx.output[found].value;
+
+        if Some(index) == original.change_index {
+            let reduced = output.value.to_sat().saturating_sub(value.to_sat());
+            if value > output.value || reduced > original.max_additional_fee {
+                anyhow::bail!(
+                    "Proposal takes {} sat from our change, more than the {} sat offered",
+                    reduced,
+                    original.max_additional_fee
+                );
+            }
+        } else if output.script_pubkey == original.payee {
+            if value < output.value {
+                anyhow::bail!("Proposal reduced the payment");
+            }
+        } else if value != output.value {
+            anyhow::bail!("Proposal changed output {}", index);
+        }
+    }
+
+    // The receiver pays for its own input beyond our contribution
+    if psbt_fee(proposal, sender)? < psbt_fee(sender, sender)? {
+        anyhow::bail!("Proposal pays a lower fee than the original");
+    }
+    Ok(())
+}
+
+/// Total fee of a PSBT; our inputs' values come from `sender`
+fn psbt_fee(psbt: &Psbt, sender: &Psbt) -> Result<u64> {
+    let mut inputs = 0u64;
+    for (txin, input) in psbt.unsigned_tx.input.iter().zip(&psbt.inputs) {
+        let value = match sender
+            .unsigned_tx
+            .input
+            .iter()
+            .position(|o| o.previous_output == txin.previous_output)
+        {
+            Some(k) => spent_output(sender, txin, &sender.inputs[k])?.value,
+            None => spent_output(psbt, txin, input)?.value,
+        };
+        inputs += value.to_sat();
+    }
+    let outputs: u64 = psbt
+        .unsigned_tx
+        .output
+        .iter()
+        .map(|o| o.value.to_sat())
+        .sum();
+    inputs.checked_sub(outputs).context("Outputs exceed inputs")
+}
+
+/// Output spent by a PSBT input, from its UTXO fields
+fn spent_output(psbt: &Psbt, txin: &bitcoin::TxIn, input: &bitcoin::psbt::Input) -> Result<TxOut> {
+    if let Some(out) = &input.witness_utxo {
+        return Ok(out.clone());
+    }
+    input
+        .non_witness_utxo
+        .as_ref()
+        .and_then(|prev| prev.output.get(txin.previous_output.vout as usize))
+        .cloned()
+        .with_context(|| {
+            format!(
+                "Input {} of {} has no UTXO data",
+                txin.previous_output,
+                psbt.unsigned_tx.compute_txid()
+            )
+        })
+}
+
+fn decode_psbt(encoded: &str) -> Result<Psbt> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .context("Invalid PSBT encoding")?;
+    Psbt::deserialize(&bytes).context("Invalid PSBT")
+}
+
+fn script_for(address: &str) -> Result<ScriptBuf> {
+    Ok(Address::from_str(address)
+        .with_context(|| format!("Invalid address {}", address))?
+        .assume_checked()
+        .script_pubkey())
+}
+
+fn created(tx: &Transaction) -> CreatedTransaction {
+    CreatedTransaction {
+        txid: tx.compute_txid().to_string(),
+        hex: serialize_hex(tx),
+        anchor_vout: tx
+            .output
+            .iter()
+            .position(|o| o.script_pubkey.is_op_return())
+            .unwrap_or(0) as u32,
+        carrier: 0,
+        carrier_name: "op_return".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash;
+    use bitcoin::transaction::Version;
+    use bitcoin::{OutPoint, Sequence, TxIn, Txid, WPubkeyHash, Witness};
+
+    fn p2wpkh(byte: u8) -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([byte; 20]))
+    }
+
+    fn txin(byte: u8) -> TxIn {
+        TxIn {
+            previous_output: OutPoint::new(Txid::from_byte_array([byte; 32]), 0),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }
+    }
+
+    fn out(value: u64, script: ScriptBuf) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value),
+            script_pubkey: script,
+        }
+    }
+
+    /// 100_000 sat input paying 40_000 to the payee, 1_000 fee
+    fn original() -> PayjoinOriginal {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![txin(1)],
+            output: vec![
+                out(0, ScriptBuf::new_op_return([0xa1, 0x1c])),
+                out(40_000, p2wpkh(2)),
+                out(59_000, p2wpkh(3)),
+            ],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx.clone()).unwrap();
+        psbt.inputs[0].witness_utxo = Some(out(100_000, p2wpkh(1)));
+        PayjoinOriginal {
+            psbt,
+            tx: created(&tx),
+            change_index: Some(2),
+            payee: p2wpkh(2),
+            fee_rate: 10,
+            max_additional_fee: 680,
+        }
+    }
+
+    /// Receiver adds a 50_000 sat input and takes `from_change` for its fee
+    fn proposal(original: &PayjoinOriginal, from_change: u64) -> Psbt {
+        let mut tx = original.psbt.unsigned_tx.clone();
+        tx.input.push(txin(9));
+        tx.output[1].value = Amount::from_sat(90_000);
+        tx.output[2].value = Amount::from_sat(59_000 - from_change);
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[1].witness_utxo = Some(out(50_000, p2wpkh(9)));
+        psbt.inputs[1].final_script_witness = Some(Witness::from_slice(&[vec![1u8; 72]]));
+        psbt
+    }
+
+    #[test]
+    fn test_accepts_valid_proposal() {
+        let original = original();
+        assert!(check_proposal(&original, &proposal(&original, 680)).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_changed_payload() {
+        let original = original();
+        let mut proposal = proposal(&original, 0);
+        proposal.unsigned_tx.output[0].script_pubkey = ScriptBuf::new_op_return([0xff]);
+        assert!(check_proposal(&original, &proposal).is_err());
+    }
+
+    #[test]
+    fn test_rejects_excess_fee_contribution() {
+        let original = original();
+        assert!(check_proposal(&original, &proposal(&original, 681)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_dropped_input() {
+        let original = original();
+        let mut proposal = proposal(&original, 0);
+        proposal.unsigned_tx.input.remove(0);
+        proposal.inputs.remove(0);
+        assert!(check_proposal(&original, &proposal).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unfinalized_receiver_input() {
+        let original = original();
+        let mut proposal = proposal(&original, 0);
+        proposal.inputs[1].final_script_witness = None;
+        assert!(check_proposal(&original, &proposal).is_err());
+    }
+
+    #[test]
+    fn test_parse_payjoin_uri() {
+        let uri = PayjoinUri::parse(
+            "bitcoin:bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080?amount=0.0004&pj=https%3A%2F%2Fexample.com%2Fpj%3Fid%3D1",
+            "mainnet",
+        )
+        .unwrap();
+        assert_eq!(uri.amount, 40_000);
+        assert_eq!(uri.endpoint.as_str(), "https://example.com/pj?id=1");
+
+        let onion = "bitcoin:addr?amount=1&pj=http://abc.onion/pj";
+        assert!(PayjoinUri::parse(onion, "mainnet").is_ok());
+        let plain = "bitcoin:addr?amount=1&pj=http://example.com/pj";
+        assert!(PayjoinUri::parse(plain, "mainnet").is_err());
+        assert!(PayjoinUri::parse(plain, "regtest").is_ok());
+        assert!(PayjoinUri::parse("bitcoin:addr?pj=https://example.com", "mainnet").is_err());
+        assert!(PayjoinUri::parse("bitcoin:addr?amount=1", "mainnet").is_err());
+    }
+}
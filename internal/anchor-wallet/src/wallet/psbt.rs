@@ -0,0 +1,187 @@
+//! Unsigned transactions for the watch-only profile
+//!
+//! Without private keys the service funds the message from the watch-only
+//! descriptors and returns a PSBT. The operator signs it offline and submits
+//! the result through `/wallet/broadcast`. Only the OP_RETURN carrier is
+//! built this way: the witness carriers need a commit transaction broadcast
+//! before their reveal can be funded.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::{Psbt, Txid};
+use bitcoincore_rpc::RpcApi;
+use std::str::FromStr;
+use tracing::info;
+
+use anchor_core::carrier::{CarrierOutput, CarrierSelector, CarrierType};
+use anchor_core::{AnchorKind, AnchorMessageBuilder};
+
+use super::service::WalletService;
+use super::types::{CoinControl, CreatedTransaction, UnsignedTransaction};
+use super::utils::extract_op_return_data;
+
+impl WalletService {
+    /// Fund an ANCHOR message as an OP_RETURN transaction and return it unsigned
+    ///
+    /// UTXOs ruled out by `coins` are kept out of coin selection, and the selected
+    /// inputs stay locked in Bitcoin Core until the transaction is broadcast
+    /// or the node restarts, so pending PSBTs never share inputs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_anchor_psbt(
+        &self,
+        kind: u8,
+        body: Vec<u8>,
+        parent_txid: Option<String>,
+        parent_vout: Option<u8>,
+        additional_anchors: Vec<(String, u8)>,
+        fee_rate: u64,
+        required_inputs: Vec<(String, u32)>,
+        custom_outputs: Vec<(String, u64)>,
+        coins: Option<&CoinControl>,
+    ) -> Result<UnsignedTransaction> {
+        self.fund_anchor_psbt(
+            kind,
+            body,
+            parent_txid,
+            parent_vout,
+            additional_anchors,
+            fee_rate,
+            required_inputs,
+            custom_outputs,
+            coins,
+            LockTime::ZERO,
+        )
+    }
+
+    /// Internal: Fund an OP_RETURN message PSBT with the given nLockTime
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn fund_anchor_psbt(
+        &self,
+        kind: u8,
+        body: Vec<u8>,
+        parent_txid: Option<String>,
+        parent_vout: Option<u8>,
+        additional_anchors: Vec<(String, u8)>,
+        fee_rate: u64,
+        required_inputs: Vec<(String, u32)>,
+        custom_outputs: Vec<(String, u64)>,
+        coins: Option<&CoinControl>,
+        lock_time: LockTime,
+    ) -> Result<UnsignedTransaction> {
+        if !self.ensure_wallet_loaded() {
+            anyhow::bail!("Wallet is not available and could not be recovered");
+        }
+
+        let mut builder = AnchorMessageBuilder::new().kind(AnchorKind::from(kind));
+        if let (Some(txid_str), Some(vout)) = (parent_txid, parent_vout) {
+            let txid = Txid::from_str(&txid_str).context("Invalid parent txid")?;
+            builder = builder.reply_to(&txid, vout);
+        }
+        for (txid_str, vout) in additional_anchors {
+            let txid = Txid::from_str(&txid_str).context("Invalid anchor txid")?;
+            builder = builder.add_anchor(&txid, vout);
+        }
+        builder = builder.body(body);
+
+        let message = anchor_core::ParsedAnchorMessage {
+            kind: AnchorKind::from(kind),
+            anchors: builder.get_anchors(),
+            body: builder.get_body(),
+        };
+        let selector = CarrierSelector::new();
+        let carrier = selector
+            .get_carrier(CarrierType::OpReturn)
+            .context("OP_RETURN carrier not available")?;
+        let script = match carrier
+            .encode(&message)
+            .map_err(|e| anyhow::Error::new(e).context("Carrier encode failed"))?
+        {
+            CarrierOutput::OpReturn(script) => script,
+            _ => anyhow::bail!("OP_RETURN carrier returned an unexpected output"),
+        };
+
+        let inputs: Vec<serde_json::Value> = required_inputs
+            .iter()
+            .map(|(txid, vout)| serde_json::json!({ "txid": txid, "vout": vout }))
+            .collect();
+        let mut outputs: Vec<serde_json::Value> = custom_outputs
+            .iter()
+            .map(|(addr, value)| serde_json::json!({ addr: *value as f64 / 100_000_000.0 }))
+            .collect();
+        outputs.push(serde_json::json!({ "data": hex::encode(extract_op_return_data(&script)) }));
+
+        let mut options = serde_json::json!({ "fee_rate": fee_rate, "lockUnspents": true });
+        if let Some(address) = coins.and_then(|c| c.change_address.as_ref()) {
+            options["changeAddress"] = serde_json::json!(address);
+        }
+        let funded: serde_json::Value = self.with_coin_control(coins, &required_inputs, || {
+            Ok(self.rpc().call(
+                "walletcreatefundedpsbt",
+                &[
+                    serde_json::json!(inputs),
+                    serde_json::json!(outputs),
+                    serde_json::json!(lock_time.to_consensus_u32()),
+                    options,
+                    serde_json::json!(true),
+                ],
+            )?)
+        })?;
+
+        let psbt_base64 = funded["psbt"]
+            .as_str()
+            .context("No psbt in funded transaction")?;
+        let psbt_bytes = base64::engine::general_purpose::STANDARD
+            .decode(psbt_base64)
+            .context("Invalid PSBT encoding")?;
+        let psbt = Psbt::deserialize(&psbt_bytes).context("Invalid PSBT")?;
+        let tx = &psbt.unsigned_tx;
+        let anchor_vout = tx
+            .output
+            .iter()
+            .position(|o| o.script_pubkey.is_op_return())
+            .unwrap_or(0) as u32;
+        let txid = tx.compute_txid().to_string();
+
+        info!(
+            "Created unsigned PSBT {} ({} inputs, fee {} BTC)",
+            txid,
+            tx.input.len(),
+            funded["fee"]
+        );
+
+        Ok(UnsignedTransaction {
+            tx: CreatedTransaction {
+                txid,
+                hex: serialize_hex(tx),
+                anchor_vout,
+                carrier: 0,
+                carrier_name: "op_return".to_string(),
+            },
+            psbt: psbt_base64.to_string(),
+        })
+    }
+
+    /// Merge the signatures of several copies of one PSBT
+    pub fn combine_psbts(&self, psbts: &[String]) -> Result<String> {
+        let combined: String = self
+            .rpc()
+            .call("combinepsbt", &[serde_json::json!(psbts)])?;
+        Ok(combined)
+    }
+
+    /// Finalize a signed PSBT and return the network-serialized transaction
+    pub fn finalize_psbt(&self, psbt: &str) -> Result<String> {
+        let finalized: serde_json::Value = self
+            .rpc()
+            .call("finalizepsbt", &[serde_json::json!(psbt)])?;
+        if finalized["complete"] != true {
+            anyhow::bail!("PSBT is not fully signed");
+        }
+        Ok(finalized["hex"]
+            .as_str()
+            .context("No hex in finalized PSBT")?
+            .to_string())
+    }
+}
@@ -0,0 +1,93 @@
+//! Regtest chain controls for integration tests
+//!
+//! Invalidating blocks, setting the node's mock time and replacing the last
+//! blocks with a longer branch let app test suites exercise reorg handling
+//! and time-dependent logic (lock times, expiries). Every call checks that
+//! the node itself is on regtest, whatever the wallet was configured with.
+
+use anyhow::{Context, Result};
+use bitcoin::{BlockHash, Network};
+use bitcoincore_rpc::RpcApi;
+use tracing::info;
+
+use super::service::WalletService;
+
+/// Blocks replaced by a reorg from the tip down
+#[derive(Debug, Clone)]
+pub struct Reorg {
+    /// Height of the first replaced block
+    pub fork_height: u64,
+    /// Hashes of the blocks that left the active chain, lowest first
+    pub disconnected: Vec<String>,
+    /// Hashes of the blocks mined on the new branch, lowest first
+    pub connected: Vec<String>,
+}
+
+impl WalletService {
+    /// Fail unless the node runs on regtest
+    fn ensure_regtest(&self) -> Result<()> {
+        let chain = self.base_rpc().get_blockchain_info()?.chain;
+        if chain != Network::Regtest {
+            anyhow::bail!("Chain controls need a regtest node, not {}", chain);
+        }
+        Ok(())
+    }
+
+    /// Hash of the active block at `height`
+    pub fn block_hash_at(&self, height: u64) -> Result<BlockHash> {
+        self.base_rpc()
+            .get_block_hash(height)
+            .with_context(|| format!("No block at height {}", height))
+    }
+
+    /// Mark a block and its descendants invalid, rolling the chain back to
+    /// its parent
+    ///
+    /// Returns the height of the new tip.
+    pub fn invalidate_block(&self, hash: &BlockHash) -> Result<u64> {
+        self.ensure_regtest()?;
+        self.base_rpc().invalidate_block(hash)?;
+        let height = self.base_rpc().get_block_count()?;
+        info!("Invalidated block {}, tip now at {}", hash, height);
+        Ok(height)
+    }
+
+    /// Set the node's clock to `timestamp` (0 returns to the system clock)
+    pub fn set_mocktime(&self, timestamp: u64) -> Result<()> {
+        self.ensure_regtest()?;
+        self.base_rpc()
+            .call::<serde_json::Value>("setmocktime", &[timestamp.into()])?;
+        info!("Node mock time set to {}", timestamp);
+        Ok(())
+    }
+
+    /// Replace the last `depth` blocks with `blocks` new ones
+    ///
+    /// Transactions from the disconnected blocks go back to the mempool and
+    /// are mined again on the new branch, which is longer than the old one
+    /// when `blocks > depth`.
+    pub fn reorg(&self, depth: u64, blocks: u32) -> Result<Reorg> {
+        self.ensure_regtest()?;
+        let tip = self.base_rpc().get_block_count()?;
+        if depth == 0 || depth > tip {
+            anyhow::bail!("Reorg depth must be between 1 and {}", tip);
+        }
+        let fork_height = tip - depth + 1;
+        let disconnected = (fork_height..=tip)
+            .map(|height| self.block_hash_at(height).map(|hash| hash.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.base_rpc()
+            .invalidate_block(&self.block_hash_at(fork_height)?)?;
+        let connected = self.mine_blocks(blocks)?;
+        info!(
+            "Reorged {} blocks from height {}, mined {} on the new branch",
+            depth, fork_height, blocks
+        );
+        Ok(Reorg {
+            fork_height,
+            disconnected,
+            connected,
+        })
+    }
+}
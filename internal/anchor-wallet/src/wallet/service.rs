@@ -1,37 +1,53 @@
 //! WalletService core implementation
 
+use anchor_rpc::RpcClient;
 use anyhow::{Context, Result};
-use bitcoincore_rpc::{Auth, Client, RpcApi};
+use bitcoincore_rpc::{Client, RpcApi};
 use std::collections::HashSet;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, RwLock};
 use tracing::{info, warn};
 
-use super::types::{Balance, Utxo};
-use crate::config::Config;
+use super::addresses::ReusedAddress;
+use super::types::{Balance, CoinControl, Utxo};
+use crate::config::{Config, WatchOnly};
+use crate::proxy::{rpc_client, rpc_config, Socks5Proxy};
 
 /// The wallet service wrapping Bitcoin Core RPC
 pub struct WalletService {
-    pub(crate) rpc: Client,
-    pub(crate) base_rpc: Client,
+    /// Wallet-scoped RPC client, swapped on reconnect
+    rpc: RwLock<Arc<Client>>,
+    /// Node-level RPC client, swapped on reconnect
+    base_rpc: RwLock<Arc<Client>>,
+    /// Pooled, batched node-level client for bulk transaction lookups
+    batch_rpc: RwLock<RpcClient>,
     pub(crate) wallet_name: String,
     pub(crate) wallet_loaded: AtomicBool,
+    /// Descriptors of a wallet without private keys; transactions become PSBTs
+    pub(crate) watch_only: Option<WatchOnly>,
+    /// SOCKS5 proxy used for every RPC connection, including reconnects
+    rpc_proxy: Option<Socks5Proxy>,
     /// Mutex to serialize two-stage transaction creation (commit/reveal)
     /// This prevents race conditions where multiple transactions try to use the same UTXOs
     pub(crate) tx_creation_mutex: Mutex<()>,
+    /// Leave commit fees to the reveal when the node relays packages
+    pub(crate) zero_fee_commit: bool,
 }
 
 impl WalletService {
     /// Create a new wallet service
     pub fn new(config: &Config) -> Result<Self> {
-        let base_rpc = Client::new(
+        let rpc_proxy = config.bitcoin_rpc_proxy.as_ref();
+        let base_rpc = rpc_client(
             &config.bitcoin_rpc_url,
-            Auth::UserPass(
-                config.bitcoin_rpc_user.clone(),
-                config.bitcoin_rpc_password.clone(),
-            ),
-        )
-        .context("Failed to connect to Bitcoin RPC")?;
+            &config.bitcoin_rpc_user,
+            &config.bitcoin_rpc_password,
+            rpc_proxy,
+        )?;
+        if let Some(proxy) = rpc_proxy {
+            info!("Bitcoin RPC via SOCKS5 proxy {}", proxy.addr);
+        }
 
         // Verify connection
         let blockchain_info = base_rpc.get_blockchain_info()?;
@@ -45,12 +61,11 @@ impl WalletService {
 
         // First, check if wallet is already loaded by trying to get wallet info
         let wallet_url = format!("{}/wallet/{}", config.bitcoin_rpc_url, wallet_name);
-        let test_rpc = Client::new(
+        let test_rpc = rpc_client(
             &wallet_url,
-            Auth::UserPass(
-                config.bitcoin_rpc_user.clone(),
-                config.bitcoin_rpc_password.clone(),
-            ),
+            &config.bitcoin_rpc_user,
+            &config.bitcoin_rpc_password,
+            rpc_proxy,
         )?;
 
         match test_rpc.get_wallet_info() {
@@ -84,7 +99,12 @@ impl WalletService {
                         } else {
                             // Wallet doesn't exist, create it
                             info!("Creating new wallet: {}", wallet_name);
-                            base_rpc.create_wallet(&wallet_name, None, None, None, None)?;
+                            create_wallet(
+                                &base_rpc,
+                                &test_rpc,
+                                &wallet_name,
+                                config.watch_only.as_ref(),
+                            )?;
                         }
                     }
                 }
@@ -93,30 +113,108 @@ impl WalletService {
 
         // Create wallet-specific RPC client
         let wallet_url = format!("{}/wallet/{}", config.bitcoin_rpc_url, wallet_name);
-        let wallet_rpc = Client::new(
+        let wallet_rpc = rpc_client(
             &wallet_url,
-            Auth::UserPass(
-                config.bitcoin_rpc_user.clone(),
-                config.bitcoin_rpc_password.clone(),
-            ),
+            &config.bitcoin_rpc_user,
+            &config.bitcoin_rpc_password,
+            rpc_proxy,
         )?;
 
+        let batch_rpc = RpcClient::new(rpc_config(
+            &config.bitcoin_rpc_url,
+            &config.bitcoin_rpc_user,
+            &config.bitcoin_rpc_password,
+            rpc_proxy,
+        ))
+        .context("Failed to create batched Bitcoin RPC client")?;
+
+        if config.watch_only.is_some() {
+            let info: serde_json::Value = wallet_rpc.call("getwalletinfo", &[])?;
+            if info["private_keys_enabled"].as_bool() != Some(false) {
+                anyhow::bail!(
+                    "Wallet '{}' holds private keys; set a new WALLET_NAME for the watch-only profile",
+                    wallet_name
+                );
+            }
+            info!("Watch-only profile: transactions are returned as unsigned PSBTs");
+        }
+
         Ok(Self {
-            rpc: wallet_rpc,
-            base_rpc,
+            rpc: RwLock::new(Arc::new(wallet_rpc)),
+            base_rpc: RwLock::new(Arc::new(base_rpc)),
+            batch_rpc: RwLock::new(batch_rpc),
             wallet_name,
             wallet_loaded: AtomicBool::new(true),
+            watch_only: config.watch_only.clone(),
+            rpc_proxy: config.bitcoin_rpc_proxy.clone(),
             tx_creation_mutex: Mutex::new(()),
+            zero_fee_commit: config.zero_fee_commit,
         })
     }
 
+    /// Whether the wallet holds no private keys and only builds PSBTs
+    pub fn is_watch_only(&self) -> bool {
+        self.watch_only.is_some()
+    }
+
+    /// Wallet-scoped RPC client
+    pub(crate) fn rpc(&self) -> Arc<Client> {
+        self.rpc.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Node-level RPC client (wallet loading, chain queries)
+    pub(crate) fn base_rpc(&self) -> Arc<Client> {
+        self.base_rpc
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Pooled node-level client that batches calls
+    pub(crate) fn batch_rpc(&self) -> RpcClient {
+        self.batch_rpc
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Point the service at a different Bitcoin Core RPC endpoint
+    ///
+    /// The new endpoint is verified before the clients are swapped, so a bad
+    /// endpoint leaves the current connection in place.
+    pub fn reconnect(&self, url: &str, user: &str, password: &str) -> Result<()> {
+        let proxy = self.rpc_proxy.as_ref();
+        let base_rpc = rpc_client(url, user, password, proxy)?;
+        let blockchain_info = base_rpc
+            .get_blockchain_info()
+            .context("New Bitcoin RPC endpoint is not reachable")?;
+        let wallet_url = format!("{}/wallet/{}", url, self.wallet_name);
+        let wallet_rpc = rpc_client(&wallet_url, user, password, proxy)?;
+        let batch_rpc = RpcClient::new(rpc_config(url, user, password, proxy))
+            .context("Failed to create batched Bitcoin RPC client")?;
+
+        *self.base_rpc.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(base_rpc);
+        *self.rpc.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(wallet_rpc);
+        *self.batch_rpc.write().unwrap_or_else(|e| e.into_inner()) = batch_rpc;
+        self.wallet_loaded.store(false, Ordering::Relaxed);
+        info!(
+            "Reconnected to Bitcoin node: chain={}, blocks={}",
+            blockchain_info.chain, blockchain_info.blocks
+        );
+
+        if !self.ensure_wallet_loaded() {
+            warn!("Wallet {} not available on new endpoint", self.wallet_name);
+        }
+        Ok(())
+    }
+
     /// Ensure the wallet is loaded, attempting to reload if necessary
     /// Returns true if wallet is available, false otherwise
     pub(crate) fn ensure_wallet_loaded(&self) -> bool {
         // Quick check - if we think wallet is loaded, verify it
         if self.wallet_loaded.load(Ordering::Relaxed) {
             // Try a simple RPC call to verify
-            if self.rpc.get_wallet_info().is_ok() {
+            if self.rpc().get_wallet_info().is_ok() {
                 return true;
             }
             // Wallet is not responding, mark as not loaded
@@ -131,7 +229,7 @@ impl WalletService {
         info!("Attempting to reload wallet: {}", self.wallet_name);
 
         // First try to load it directly
-        match self.base_rpc.load_wallet(&self.wallet_name) {
+        match self.base_rpc().load_wallet(&self.wallet_name) {
             Ok(_) => {
                 info!("Successfully reloaded wallet: {}", self.wallet_name);
                 self.wallet_loaded.store(true, Ordering::Relaxed);
@@ -150,10 +248,12 @@ impl WalletService {
                         "Wallet not found, creating new wallet: {}",
                         self.wallet_name
                     );
-                    match self
-                        .base_rpc
-                        .create_wallet(&self.wallet_name, None, None, None, None)
-                    {
+                    match create_wallet(
+                        &self.base_rpc(),
+                        &self.rpc(),
+                        &self.wallet_name,
+                        self.watch_only.as_ref(),
+                    ) {
                         Ok(_) => {
                             info!("Created new wallet: {}", self.wallet_name);
                             self.wallet_loaded.store(true, Ordering::Relaxed);
@@ -174,10 +274,10 @@ impl WalletService {
             "Attempting unload/reload cycle for wallet: {}",
             self.wallet_name
         );
-        let _ = self.base_rpc.unload_wallet(Some(&self.wallet_name));
+        let _ = self.base_rpc().unload_wallet(Some(&self.wallet_name));
         std::thread::sleep(std::time::Duration::from_millis(500));
 
-        match self.base_rpc.load_wallet(&self.wallet_name) {
+        match self.base_rpc().load_wallet(&self.wallet_name) {
             Ok(_) => {
                 info!(
                     "Successfully reloaded wallet after unload: {}",
@@ -227,25 +327,33 @@ impl WalletService {
     }
 
     /// Get wallet balance
-    pub fn get_balance(&self) -> Result<Balance> {
+    ///
+    /// `locked_set` holds the asset-carrying UTXOs; their value is reported
+    /// as `locked` and left out of `spendable`.
+    pub fn get_balance(&self, locked_set: &HashSet<(String, u32)>) -> Result<Balance> {
         self.with_wallet_check(|| {
-            let balances = self.rpc.get_balances()?;
-
-            let confirmed = balances.mine.trusted.to_btc();
-            let unconfirmed = balances.mine.untrusted_pending.to_btc();
-
-            Ok(Balance {
-                confirmed,
-                unconfirmed,
-                total: confirmed + unconfirmed,
-            })
+            let balances = self.rpc().get_balances()?;
+            let locked = self
+                .rpc()
+                .list_unspent(None, None, None, None, None)?
+                .iter()
+                .filter(|u| locked_set.contains(&(u.txid.to_string(), u.vout)))
+                .map(|u| u.amount.to_sat())
+                .sum();
+
+            Ok(Balance::from_sats(
+                balances.mine.trusted.to_sat(),
+                balances.mine.untrusted_pending.to_sat(),
+                balances.mine.immature.to_sat(),
+                locked,
+            ))
         })
     }
 
     /// Get a new receiving address
     pub fn get_new_address(&self) -> Result<String> {
         self.with_wallet_check(|| {
-            let address = self.rpc.get_new_address(None, None)?;
+            let address = self.rpc().get_new_address(None, None)?;
             Ok(address.assume_checked().to_string())
         })
     }
@@ -263,14 +371,14 @@ impl WalletService {
             // include_empty: Some(true) (include addresses with 0 balance)
             // include_watchonly: None
             let received = self
-                .rpc
+                .rpc()
                 .list_received_by_address(None, Some(0), Some(true), None)?;
             for r in received {
                 all_addresses.insert(r.address.assume_checked().to_string());
             }
 
             // 2. Get addresses from current UTXOs (captures taproot and other address types)
-            if let Ok(utxos) = self.rpc.list_unspent(None, None, None, None, None) {
+            if let Ok(utxos) = self.rpc().list_unspent(None, None, None, None, None) {
                 for u in utxos {
                     if let Some(addr) = u.address {
                         all_addresses.insert(addr.assume_checked().to_string());
@@ -281,7 +389,7 @@ impl WalletService {
             // 3. Get addresses from list_transactions (past transactions)
             // This captures addresses used in spent outputs
             if let Ok(txs) = self
-                .rpc
+                .rpc()
                 .list_transactions(None, Some(1000), None, Some(true))
             {
                 for tx in txs {
@@ -295,10 +403,52 @@ impl WalletService {
         })
     }
 
+    /// Addresses that received funds in more than one transaction
+    ///
+    /// Bitcoin Core hands out a fresh address per request, so reuse comes from
+    /// senders paying to an address twice.
+    pub fn list_reused_addresses(&self) -> Result<Vec<ReusedAddress>> {
+        self.with_wallet_check(|| {
+            let received = self
+                .rpc()
+                .list_received_by_address(None, Some(0), Some(false), None)?;
+            Ok(received
+                .into_iter()
+                .filter(|r| r.txids.len() > 1)
+                .map(|r| ReusedAddress {
+                    address: r.address.assume_checked().to_string(),
+                    tx_count: r.txids.len(),
+                })
+                .collect())
+        })
+    }
+
+    /// Public keys (compressed hex) for wallet addresses; addresses the wallet
+    /// does not own or that carry no single key are skipped
+    pub fn address_pubkeys(&self, addresses: &[String]) -> Result<Vec<String>> {
+        use bitcoincore_rpc::bitcoin::Address;
+        use std::str::FromStr;
+
+        self.with_wallet_check(|| {
+            let mut pubkeys = Vec::new();
+            for address in addresses {
+                let Ok(parsed) = Address::from_str(address) else {
+                    continue;
+                };
+                if let Ok(info) = self.rpc().get_address_info(&parsed.assume_checked()) {
+                    if let Some(pubkey) = info.pubkey {
+                        pubkeys.push(pubkey.to_string());
+                    }
+                }
+            }
+            Ok(pubkeys)
+        })
+    }
+
     /// List unspent outputs
     pub fn list_utxos(&self) -> Result<Vec<Utxo>> {
         self.with_wallet_check(|| {
-            let utxos = self.rpc.list_unspent(None, None, None, None, None)?;
+            let utxos = self.rpc().list_unspent(None, None, None, None, None)?;
 
             Ok(utxos
                 .into_iter()
@@ -322,31 +472,95 @@ impl WalletService {
             .collect())
     }
 
-    /// Internal: List unspent from RPC, filtering out locked UTXOs
+    /// Internal: List unspent from RPC, filtering out UTXOs coin control rules out
     /// Returns RPC ListUnspent entries for transaction building
     pub(crate) fn list_unspent_unlocked(
         &self,
         min_conf: Option<usize>,
-        locked_set: Option<&HashSet<(String, u32)>>,
+        coins: Option<&CoinControl>,
     ) -> Result<Vec<bitcoincore_rpc::json::ListUnspentResultEntry>> {
-        let utxos = self.rpc.list_unspent(min_conf, None, None, None, None)?;
+        let utxos = self.rpc().list_unspent(min_conf, None, None, None, None)?;
 
-        if let Some(locked) = locked_set {
+        if let Some(coins) = coins {
             Ok(utxos
                 .into_iter()
-                .filter(|u| !locked.contains(&(u.txid.to_string(), u.vout)))
+                .filter(|u| coins.allows(&(u.txid.to_string(), u.vout)))
                 .collect())
         } else {
             Ok(utxos)
         }
     }
 
+    /// Internal: Address for change, the coin control override or a fresh
+    /// one from the change keychain
+    pub(crate) fn change_address(&self, coins: Option<&CoinControl>) -> Result<bitcoin::Address> {
+        match coins.and_then(|c| c.change_address.as_deref()) {
+            Some(address) => Ok(bitcoin::Address::from_str(address)
+                .with_context(|| format!("Invalid change address {}", address))?
+                .assume_checked()),
+            None => Ok(self.rpc().get_raw_change_address(None)?.assume_checked()),
+        }
+    }
+
+    /// Internal: Run `fund` while the UTXOs coin control rules out are locked in
+    /// Bitcoin Core, so Core's own coin selection skips them
+    ///
+    /// Serializes with the other transaction builders, since lockunspent is
+    /// wallet-wide.
+    pub(crate) fn with_coin_control<T>(
+        &self,
+        coins: Option<&CoinControl>,
+        required_inputs: &[(String, u32)],
+        fund: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let _tx_guard = self
+            .tx_creation_mutex
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Transaction mutex poisoned: {}", e))?;
+
+        let excluded = self.excluded_outpoints(coins, required_inputs)?;
+        if !excluded.is_empty() {
+            self.rpc()
+                .call::<bool>("lockunspent", &[false.into(), excluded.clone().into()])?;
+        }
+        let result = fund();
+        if !excluded.is_empty() {
+            if let Err(e) = self
+                .rpc()
+                .call::<bool>("lockunspent", &[true.into(), excluded.into()])
+            {
+                warn!("Failed to release temporarily locked UTXOs: {}", e);
+            }
+        }
+        result
+    }
+
+    /// Wallet UTXOs to hide from coin selection, as lockunspent outpoints
+    fn excluded_outpoints(
+        &self,
+        coins: Option<&CoinControl>,
+        required_inputs: &[(String, u32)],
+    ) -> Result<Vec<serde_json::Value>> {
+        let Some(coins) = coins else {
+            return Ok(Vec::new());
+        };
+        let required: HashSet<(String, u32)> = required_inputs.iter().cloned().collect();
+        Ok(self
+            .rpc()
+            .list_unspent(Some(0), None, None, None, None)?
+            .into_iter()
+            .map(|u| (u.txid.to_string(), u.vout))
+            .filter(|outpoint| !coins.allows(outpoint) && !required.contains(outpoint))
+            .map(|(txid, vout)| serde_json::json!({ "txid": txid, "vout": vout }))
+            .collect())
+    }
+
     /// Mine blocks (regtest only)
     pub fn mine_blocks(&self, count: u32) -> Result<Vec<String>> {
         self.with_wallet_check(|| {
-            let address = self.rpc.get_new_address(None, None)?;
+            let address = self.rpc().get_new_address(None, None)?;
             let hashes = self
-                .rpc
+                .rpc()
                 .generate_to_address(count as u64, &address.assume_checked())?;
             Ok(hashes.into_iter().map(|h| h.to_string()).collect())
         })
@@ -356,38 +570,48 @@ impl WalletService {
     pub fn broadcast(&self, tx_hex: &str) -> Result<String> {
         self.with_wallet_check(|| {
             let txid: String = self
-                .rpc
+                .rpc()
                 .call("sendrawtransaction", &[serde_json::json!(tx_hex)])?;
             Ok(txid)
         })
     }
 
     /// Get raw transaction by txid
-    pub fn get_raw_transaction(
+    ///
+    /// The hex and decoded forms come from one batch request, and the
+    /// previous transactions for the fee from a second one.
+    pub async fn get_raw_transaction(
         &self,
         txid: &str,
     ) -> Result<(String, serde_json::Value, Option<u64>)> {
-        self.with_wallet_check(|| {
-            // Get raw hex
-            let hex: String = self
-                .rpc
-                .call("getrawtransaction", &[serde_json::json!(txid)])?;
-
-            // Get decoded transaction
-            let decoded: serde_json::Value = self.rpc.call(
+        let mut results = self
+            .batch_rpc()
+            .batch::<serde_json::Value>(
                 "getrawtransaction",
-                &[serde_json::json!(txid), serde_json::json!(true)],
-            )?;
-
-            // Calculate fee by summing input values and subtracting output values
-            let fee = self.calculate_tx_fee(&decoded);
-
-            Ok((hex, decoded, fee))
-        })
+                vec![
+                    vec![serde_json::json!(txid)],
+                    vec![serde_json::json!(txid), serde_json::json!(true)],
+                ],
+            )
+            .await?
+            .into_iter();
+        let (Some(hex), Some(decoded)) = (results.next(), results.next()) else {
+            anyhow::bail!("Incomplete response for transaction {}", txid);
+        };
+        let hex = hex?
+            .as_str()
+            .context("getrawtransaction returned no hex")?
+            .to_string();
+        let decoded = decoded?;
+
+        // Calculate fee by summing input values and subtracting output values
+        let fee = self.calculate_tx_fee(&decoded).await;
+
+        Ok((hex, decoded, fee))
     }
 
     /// Calculate transaction fee by fetching input values
-    pub(crate) fn calculate_tx_fee(&self, decoded: &serde_json::Value) -> Option<u64> {
+    pub(crate) async fn calculate_tx_fee(&self, decoded: &serde_json::Value) -> Option<u64> {
         let vin = decoded.get("vin")?.as_array()?;
         let vout = decoded.get("vout")?.as_array()?;
 
@@ -397,31 +621,38 @@ impl WalletService {
             .filter_map(|out| out.get("value")?.as_f64())
             .sum();
 
-        // Sum input values by fetching each input's previous output
-        let mut input_total: f64 = 0.0;
-        for input in vin {
-            // Skip coinbase inputs
-            if input.get("coinbase").is_some() {
-                return None; // Coinbase txs have no fee
-            }
+        // Coinbase txs have no fee
+        if vin.iter().any(|input| input.get("coinbase").is_some()) {
+            return None;
+        }
+        let prevouts = vin
+            .iter()
+            .map(|input| {
+                let txid = bitcoin::Txid::from_str(input.get("txid")?.as_str()?).ok()?;
+                Some((txid, input.get("vout")?.as_u64()? as usize))
+            })
+            .collect::<Option<Vec<_>>>()?;
 
-            let prev_txid = input.get("txid")?.as_str()?;
-            let prev_vout = input.get("vout")?.as_u64()? as usize;
+        // Fetch all previous transactions in one batch
+        let prev_txids: Vec<bitcoin::Txid> = prevouts.iter().map(|(txid, _)| *txid).collect();
+        let prev_txs = self
+            .batch_rpc()
+            .get_raw_transactions_verbose(&prev_txids)
+            .await
+            .ok()?;
 
-            // Fetch previous transaction
-            if let Ok(prev_decoded) = self.rpc.call::<serde_json::Value>(
-                "getrawtransaction",
-                &[serde_json::json!(prev_txid), serde_json::json!(true)],
-            ) {
-                if let Some(prev_outputs) = prev_decoded.get("vout").and_then(|v| v.as_array()) {
-                    if let Some(prev_out) = prev_outputs.get(prev_vout) {
-                        if let Some(value) = prev_out.get("value").and_then(|v| v.as_f64()) {
-                            input_total += value;
-                        }
-                    }
-                }
-            }
-        }
+        let input_total: f64 = prevouts
+            .iter()
+            .zip(prev_txs)
+            .filter_map(|((_, prev_vout), prev_tx)| {
+                prev_tx
+                    .ok()?
+                    .get("vout")?
+                    .get(*prev_vout)?
+                    .get("value")?
+                    .as_f64()
+            })
+            .sum();
 
         // Fee in satoshis
         let fee_btc = input_total - output_total;
@@ -432,3 +663,48 @@ impl WalletService {
         }
     }
 }
+
+/// Create the Core wallet: a regular one, or for the watch-only profile a
+/// blank wallet without private keys that tracks the configured descriptors
+fn create_wallet(
+    base_rpc: &Client,
+    wallet_rpc: &Client,
+    wallet_name: &str,
+    watch_only: Option<&WatchOnly>,
+) -> Result<()> {
+    let Some(watch) = watch_only else {
+        base_rpc.create_wallet(wallet_name, None, None, None, None)?;
+        return Ok(());
+    };
+
+    base_rpc.create_wallet(wallet_name, Some(true), Some(true), None, None)?;
+    let mut requests = Vec::new();
+    for (descriptor, internal) in [(&watch.receive, false), (&watch.change, true)] {
+        let info: serde_json::Value =
+            base_rpc.call("getdescriptorinfo", &[serde_json::json!(descriptor)])?;
+        let checksum = info["checksum"]
+            .as_str()
+            .context("No checksum in descriptor info")?;
+        requests.push(serde_json::json!({
+            "desc": format!("{}#{}", descriptor, checksum),
+            "active": true,
+            "internal": internal,
+            "range": [0, 999],
+            "timestamp": watch.birthday,
+        }));
+    }
+
+    let results: Vec<serde_json::Value> =
+        wallet_rpc.call("importdescriptors", &[serde_json::json!(requests)])?;
+    if let Some(failed) = results.iter().find(|r| r["success"] != true) {
+        anyhow::bail!(
+            "Failed to import watch-only descriptor: {}",
+            failed["error"]
+        );
+    }
+    info!(
+        "Imported watch-only descriptors into {} (rescan from {})",
+        wallet_name, watch.birthday
+    );
+    Ok(())
+}
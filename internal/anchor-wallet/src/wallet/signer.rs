@@ -0,0 +1,95 @@
+//! Client for the `anchor-signer` daemon
+//!
+//! In the `remote-signer` profile the wallet service runs watch-only and hands
+//! the PSBTs it funds to a separate signing daemon, so the network-facing
+//! service never holds private keys.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::config::SignerConfig;
+
+/// Public half of the signer's account, as served by `GET /xpub`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignerAccount {
+    pub fingerprint: String,
+    pub derivation_path: String,
+    /// Account xpub with its key origin
+    pub xpub: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignPsbtResponse {
+    psbt: String,
+    signed_inputs: usize,
+}
+
+/// HTTP client for a remote signer
+#[derive(Clone)]
+pub struct RemoteSigner {
+    url: String,
+    token: String,
+    http: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(config: &SignerConfig) -> Result<Self> {
+        Ok(Self {
+            url: config.url.trim_end_matches('/').to_string(),
+            token: config.token.clone(),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .context("Failed to build signer HTTP client")?,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Fetch the account xpub the wallet watches
+    pub async fn account(&self) -> Result<SignerAccount> {
+        let response = self
+            .http
+            .get(format!("{}/xpub", self.url))
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .context("Signer unreachable")?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Signer returned {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+        response
+            .json()
+            .await
+            .context("Invalid signer xpub response")
+    }
+
+    /// Have the signer add its signatures to a base64 PSBT
+    pub async fn sign_psbt(&self, psbt: &str) -> Result<String> {
+        let response = self
+            .http
+            .post(format!("{}/sign-psbt", self.url))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({ "psbt": psbt }))
+            .send()
+            .await
+            .context("Signer unreachable")?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Signer refused the PSBT ({}): {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+        let signed: SignPsbtResponse = response.json().await.context("Invalid signer response")?;
+        tracing::debug!("Signer signed {} input(s)", signed.signed_inputs);
+        Ok(signed.psbt)
+    }
+}
@@ -4,17 +4,19 @@
 //! type-safe transaction creation with automatic validation and carrier selection.
 
 use anyhow::Result;
-use std::collections::HashSet;
 use tracing::debug;
 
 use anchor_specs::dns::DnsSpec;
+use anchor_specs::geomarker::GeoMarkerSpec;
 use anchor_specs::prelude::*;
 use anchor_specs::proof::ProofSpec;
+use anchor_specs::state::StateSpec;
 use anchor_specs::text::TextSpec;
-use anchor_specs::token::TokenSpec;
+use anchor_specs::token::{TokenOperation, TokenSpec};
+use serde::Deserialize;
 
 use super::service::WalletService;
-use super::types::CreatedTransaction;
+use super::types::{CoinControl, CreatedTransaction};
 
 /// Anchor reference for chained transactions
 #[derive(Debug, Clone)]
@@ -33,6 +35,70 @@ impl AnchorRef {
     }
 }
 
+/// A structured message payload for one of the spec'd kinds
+///
+/// Clients send the spec as JSON, keyed by kind name (e.g. `{"dns": {...}}`),
+/// and the server validates and encodes it, so no client encodes binary
+/// bodies by hand.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageSpec {
+    Text(TextSpec),
+    State(StateSpec),
+    GeoMarker(GeoMarkerSpec),
+    Dns(DnsSpec),
+    Proof(ProofSpec),
+    Token(TokenSpec),
+}
+
+impl MessageSpec {
+    /// Kind byte of the spec
+    pub fn kind(&self) -> u8 {
+        match self {
+            Self::Text(_) => TextSpec::KIND_ID,
+            Self::State(_) => StateSpec::KIND_ID,
+            Self::GeoMarker(_) => GeoMarkerSpec::KIND_ID,
+            Self::Dns(_) => DnsSpec::KIND_ID,
+            Self::Proof(_) => ProofSpec::KIND_ID,
+            Self::Token(_) => TokenSpec::KIND_ID,
+        }
+    }
+
+    /// Validate the spec and encode its message body
+    pub fn encode(&self) -> std::result::Result<Vec<u8>, SpecError> {
+        fn encode<S: KindSpec>(spec: &S) -> std::result::Result<Vec<u8>, SpecError> {
+            spec.validate()?;
+            Ok(spec.to_bytes())
+        }
+        match self {
+            Self::Text(spec) => encode(spec),
+            Self::State(spec) => encode(spec),
+            Self::GeoMarker(spec) => encode(spec),
+            Self::Dns(spec) => encode(spec),
+            Self::Proof(spec) => encode(spec),
+            Self::Token(spec) => encode(spec),
+        }
+    }
+
+    /// Domain a DNS spec registers or updates
+    pub fn domain_name(&self) -> Option<&str> {
+        match self {
+            Self::Dns(spec) => Some(&spec.name),
+            _ => None,
+        }
+    }
+
+    /// Ticker a token spec deploys
+    pub fn token_ticker(&self) -> Option<&str> {
+        match self {
+            Self::Token(TokenSpec {
+                operation: TokenOperation::Deploy { ticker, .. },
+            }) => Some(ticker),
+            _ => None,
+        }
+    }
+}
+
 impl WalletService {
     // ========================================================================
     // Generic Spec-based Transaction Creation
@@ -71,7 +137,7 @@ impl WalletService {
         anchors: Vec<AnchorRef>,
         carrier: Option<CarrierType>,
         fee_rate: u64,
-        locked_set: Option<&HashSet<(String, u32)>>,
+        coins: Option<&CoinControl>,
     ) -> Result<CreatedTransaction> {
         // Validate the spec
         spec.validate()
@@ -115,7 +181,7 @@ impl WalletService {
             additional_anchors,
             Some(selected_carrier as u8),
             fee_rate,
-            locked_set,
+            coins,
         )
     }
 
@@ -334,6 +400,23 @@ impl WalletService {
         let anchor = AnchorRef::new(proof_txid, proof_vout);
         self.create_spec_transaction(spec, vec![anchor], None, fee_rate)
     }
+
+    /// Co-attest someone else's proof as a witness
+    ///
+    /// Requires anchor to the original proof transaction.
+    pub fn witness_proof(
+        &self,
+        spec: ProofSpec,
+        proof_txid: impl Into<String>,
+        proof_vout: u8,
+        fee_rate: u64,
+    ) -> Result<CreatedTransaction> {
+        if !matches!(spec.operation, anchor_specs::proof::ProofOperation::Witness) {
+            anyhow::bail!("Expected Witness operation for witness_proof");
+        }
+        let anchor = AnchorRef::new(proof_txid, proof_vout);
+        self.create_spec_transaction(spec, vec![anchor], None, fee_rate)
+    }
 }
 
 #[cfg(test)]
@@ -359,4 +442,72 @@ mod tests {
         assert_eq!(anchor.txid, "abc123");
         assert_eq!(anchor.vout, 0);
     }
+
+    #[test]
+    fn test_message_spec_from_json() {
+        let spec: MessageSpec = serde_json::from_value(serde_json::json!({
+            "dns": {
+                "operation": "Register",
+                "name": "example.btc",
+                "records": [{ "record_type": "A", "ttl": 3600, "value": "93.184.216.34" }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(spec.kind(), DnsSpec::KIND_ID);
+        assert_eq!(spec.domain_name(), Some("example.btc"));
+
+        let body = spec.encode().unwrap();
+        let decoded = DnsSpec::from_bytes(&body).unwrap();
+        assert_eq!(decoded.name, "example.btc");
+        assert_eq!(decoded.records[0].value, "93.184.216.34");
+    }
+
+    #[test]
+    fn test_message_spec_token_deploy() {
+        let spec: MessageSpec = serde_json::from_value(serde_json::json!({
+            "token": {
+                "operation": {
+                    "Deploy": {
+                        "ticker": "TEST",
+                        "decimals": 8,
+                        "max_supply": 21000000,
+                        "mint_limit": null,
+                        "flags": 0
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(spec.kind(), TokenSpec::KIND_ID);
+        assert_eq!(spec.token_ticker(), Some("TEST"));
+        assert!(spec.encode().is_ok());
+    }
+
+    #[test]
+    fn test_message_spec_geo_marker_geometry() {
+        let spec: MessageSpec = serde_json::from_value(serde_json::json!({
+            "geo_marker": {
+                "category": 4,
+                "latitude": 48.855,
+                "longitude": 2.295,
+                "message": "Champ de Mars",
+                "geometry": {
+                    "shape": "polygon",
+                    "points": [[48.85, 2.29], [48.85, 2.30], [48.86, 2.30], [48.86, 2.29]]
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(spec.kind(), GeoMarkerSpec::KIND_ID);
+        assert!(spec.encode().unwrap().len() > anchor_specs::geomarker::HEADER_SIZE + 13);
+    }
+
+    #[test]
+    fn test_message_spec_rejects_invalid() {
+        let spec: MessageSpec = serde_json::from_value(serde_json::json!({
+            "geo_marker": { "category": 0, "latitude": 91.0, "longitude": 0.0, "message": "x" }
+        }))
+        .unwrap();
+        assert!(spec.encode().is_err());
+    }
 }
@@ -0,0 +1,114 @@
+//! Timelocked messages
+//!
+//! A scheduled message is funded and signed up front with an nLockTime, so
+//! the network rejects it until the target height or time. The scheduler
+//! holds on to the signed transaction and broadcasts it once it is final.
+//! Only the OP_RETURN carrier is built this way, since the witness carriers
+//! would need their commit transaction confirmed first.
+
+use anyhow::{Context, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::encode::deserialize_hex;
+use bitcoin::Transaction;
+use bitcoincore_rpc::RpcApi;
+use tracing::info;
+
+use super::service::WalletService;
+use super::types::{CoinControl, CreatedTransaction};
+
+/// Signed message transaction that is not final yet
+#[derive(Debug, Clone)]
+pub struct TimelockedTransaction {
+    pub tx: CreatedTransaction,
+    /// Inputs spent by the transaction, which must stay unspent until then
+    pub inputs: Vec<(String, u32)>,
+}
+
+impl WalletService {
+    /// Fund and sign an OP_RETURN message that can't be mined before `lock_time`
+    ///
+    /// Nothing is broadcast. The selected inputs stay locked in Bitcoin Core
+    /// until the node restarts; callers that hold the transaction longer
+    /// should lock them with the lock manager as well.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_timelocked_message(
+        &self,
+        kind: u8,
+        body: Vec<u8>,
+        parent_txid: Option<String>,
+        parent_vout: Option<u8>,
+        additional_anchors: Vec<(String, u8)>,
+        fee_rate: u64,
+        lock_time: LockTime,
+        coins: Option<&CoinControl>,
+    ) -> Result<TimelockedTransaction> {
+        let unsigned = self.fund_anchor_psbt(
+            kind,
+            body,
+            parent_txid,
+            parent_vout,
+            additional_anchors,
+            fee_rate,
+            Vec::new(),
+            Vec::new(),
+            coins,
+            lock_time,
+        )?;
+
+        let processed: serde_json::Value = self.rpc().call(
+            "walletprocesspsbt",
+            &[
+                serde_json::json!(unsigned.psbt),
+                serde_json::json!(true),
+                serde_json::json!("ALL"),
+                serde_json::json!(false),
+            ],
+        )?;
+        if processed["complete"] != true {
+            anyhow::bail!("Wallet could not sign the timelocked transaction");
+        }
+        let signed = processed["psbt"]
+            .as_str()
+            .context("No psbt in processed transaction")?;
+        let hex = self.finalize_psbt(signed)?;
+        let tx: Transaction = deserialize_hex(&hex).context("Invalid finalized transaction")?;
+
+        info!(
+            "Signed timelocked message {} (lock time {})",
+            tx.compute_txid(),
+            tx.lock_time
+        );
+
+        Ok(TimelockedTransaction {
+            inputs: tx
+                .input
+                .iter()
+                .map(|i| (i.previous_output.txid.to_string(), i.previous_output.vout))
+                .collect(),
+            tx: CreatedTransaction {
+                txid: tx.compute_txid().to_string(),
+                hex,
+                anchor_vout: unsigned.tx.anchor_vout,
+                carrier: 0,
+                carrier_name: "op_return".to_string(),
+            },
+        })
+    }
+
+    /// Tip height and median time past, which decide whether a lock time is met
+    pub fn chain_tip(&self) -> Result<(u32, u32)> {
+        let info = self.rpc().get_blockchain_info()?;
+        Ok((info.blocks as u32, info.median_time as u32))
+    }
+
+    /// Release inputs locked in Bitcoin Core while funding
+    pub fn release_inputs(&self, inputs: &[(String, u32)]) -> Result<()> {
+        let outpoints: Vec<serde_json::Value> = inputs
+            .iter()
+            .map(|(txid, vout)| serde_json::json!({ "txid": txid, "vout": vout }))
+            .collect();
+        self.rpc()
+            .call::<bool>("lockunspent", &[true.into(), outpoints.into()])?;
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! Wallet data types
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// UTXO information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,14 +15,38 @@ pub struct Utxo {
     pub address: Option<String>,
 }
 
-/// Wallet balance information
+/// Wallet balance information, in BTC
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Balance {
+    /// Confirmed, including own unconfirmed change
     pub confirmed: f64,
+    /// Unconfirmed incoming
     pub unconfirmed: f64,
+    /// Coinbase outputs that have not matured yet
+    pub immature: f64,
+    /// Confirmed UTXOs locked for assets (domains, tokens, ...)
+    pub locked: f64,
+    /// Confirmed and not locked: what new transactions can spend
+    pub spendable: f64,
+    /// Everything the wallet holds
     pub total: f64,
 }
 
+impl Balance {
+    /// Build a balance from satoshi amounts, deriving `spendable` and `total`
+    pub fn from_sats(confirmed: u64, unconfirmed: u64, immature: u64, locked: u64) -> Self {
+        let btc = |sats: u64| sats as f64 / 100_000_000.0;
+        Self {
+            confirmed: btc(confirmed),
+            unconfirmed: btc(unconfirmed),
+            immature: btc(immature),
+            locked: btc(locked),
+            spendable: btc(confirmed.saturating_sub(locked)),
+            total: btc(confirmed + unconfirmed + immature),
+        }
+    }
+}
+
 /// Created transaction result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatedTransaction {
@@ -31,3 +56,56 @@ pub struct CreatedTransaction {
     pub carrier: u8,
     pub carrier_name: String,
 }
+
+/// Funded transaction awaiting an external signature (watch-only profile)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    /// `txid` is the id the transaction will have once signed (segwit inputs)
+    pub tx: CreatedTransaction,
+    /// Base64 PSBT with BIP32 derivations for the signer
+    pub psbt: String,
+}
+
+/// Coin selection constraints for one transaction
+#[derive(Debug, Clone, Default)]
+pub struct CoinControl {
+    /// UTXOs that must not be selected (locked assets, excluded inputs)
+    pub locked: HashSet<(String, u32)>,
+    /// When set, coin selection draws only from these UTXOs
+    pub only: Option<HashSet<(String, u32)>>,
+    /// Change goes here instead of to a fresh wallet address
+    pub change_address: Option<String>,
+}
+
+impl CoinControl {
+    /// Constraints that only keep `locked` out of coin selection
+    pub fn locked(locked: HashSet<(String, u32)>) -> Self {
+        Self {
+            locked,
+            ..Self::default()
+        }
+    }
+
+    /// Whether coin selection may spend this UTXO
+    pub fn allows(&self, outpoint: &(String, u32)) -> bool {
+        !self.locked.contains(outpoint) && self.only.as_ref().is_none_or(|o| o.contains(outpoint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coin_control_allows() {
+        let outpoint = |vout| ("ab".repeat(32), vout);
+        let mut coins = CoinControl::locked(HashSet::from([outpoint(0)]));
+        assert!(!coins.allows(&outpoint(0)));
+        assert!(coins.allows(&outpoint(1)));
+
+        coins.only = Some(HashSet::from([outpoint(0), outpoint(2)]));
+        assert!(!coins.allows(&outpoint(0)));
+        assert!(!coins.allows(&outpoint(1)));
+        assert!(coins.allows(&outpoint(2)));
+    }
+}
@@ -0,0 +1,475 @@
+//! # ANCHOR API Errors
+//!
+//! A single error envelope shared by the wallet service and app backends, so
+//! clients can branch on a machine-readable [`ErrorCode`] instead of parsing
+//! message strings:
+//!
+//! ```json
+//! {
+//!   "code": "INSUFFICIENT_FUNDS",
+//!   "message": "Insufficient funds for inscription commit: need 12000 sats",
+//!   "retryable": false,
+//!   "details": { "carrier": 1 },
+//!   "error": "Insufficient funds for inscription commit: need 12000 sats"
+//! }
+//! ```
+//!
+//! `error` repeats `message` for clients written against the older
+//! `{"error": "..."}` bodies.
+//!
+//! With the `axum` feature, [`ApiError`] implements `IntoResponse` and
+//! [`normalize_errors`] rewrites any other non-2xx response into the envelope,
+//! so handlers that still return `(StatusCode, String)` are covered too.
+//!
+//! [`dump_openapi`] implements the `--dump-openapi` flag every backend binary
+//! accepts.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+/// Machine-readable error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// Malformed or invalid request
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    /// The request conflicts with existing state
+    Conflict,
+    RateLimited,
+    /// Unexpected server-side failure
+    Internal,
+    /// A service this one depends on returned an error
+    UpstreamError,
+    /// The service or one of its dependencies is temporarily unavailable
+    Unavailable,
+    Timeout,
+    /// The wallet cannot fund the transaction
+    InsufficientFunds,
+    /// The payload does not fit the selected carrier
+    CarrierTooSmall,
+    /// The message kind cannot be carried by the selected carrier
+    UnsupportedCarrier,
+    /// The domain name is already registered
+    DomainTaken,
+    /// The content hash already has a proof
+    HashAlreadyRegistered,
+    /// A code this client version does not know about
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    /// Code as it appears on the wire
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BadRequest => "BAD_REQUEST",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::NotFound => "NOT_FOUND",
+            Self::Conflict => "CONFLICT",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::Internal => "INTERNAL",
+            Self::UpstreamError => "UPSTREAM_ERROR",
+            Self::Unavailable => "UNAVAILABLE",
+            Self::Timeout => "TIMEOUT",
+            Self::InsufficientFunds => "INSUFFICIENT_FUNDS",
+            Self::CarrierTooSmall => "CARRIER_TOO_SMALL",
+            Self::UnsupportedCarrier => "UNSUPPORTED_CARRIER",
+            Self::DomainTaken => "DOMAIN_TAKEN",
+            Self::HashAlreadyRegistered => "HASH_ALREADY_REGISTERED",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// HTTP status normally used for this code
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::BadRequest => 400,
+            Self::Unauthorized => 401,
+            Self::Forbidden => 403,
+            Self::NotFound => 404,
+            Self::Conflict | Self::DomainTaken | Self::HashAlreadyRegistered => 409,
+            Self::InsufficientFunds | Self::CarrierTooSmall | Self::UnsupportedCarrier => 422,
+            Self::RateLimited => 429,
+            Self::Internal | Self::Unknown => 500,
+            Self::UpstreamError => 502,
+            Self::Unavailable => 503,
+            Self::Timeout => 504,
+        }
+    }
+
+    /// Whether repeating the same request later may succeed
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited | Self::UpstreamError | Self::Unavailable | Self::Timeout
+        )
+    }
+
+    /// Generic code for an HTTP status
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            409 => Self::Conflict,
+            429 => Self::RateLimited,
+            502 => Self::UpstreamError,
+            503 => Self::Unavailable,
+            504 => Self::Timeout,
+            500..=599 => Self::Internal,
+            _ => Self::BadRequest,
+        }
+    }
+
+    /// Recognize wallet and carrier failures from their messages
+    ///
+    /// Bitcoin Core and the carrier encoders report these as plain strings, so
+    /// this is the one place their wording is matched.
+    pub fn classify(message: &str) -> Option<Self> {
+        let message = message.to_lowercase();
+        if message.contains("insufficient funds") || message.contains("no utxos available") {
+            Some(Self::InsufficientFunds)
+        } else if message.contains("exceeds carrier limit") {
+            Some(Self::CarrierTooSmall)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+    /// Same as `message`, kept for older clients
+    #[serde(default)]
+    pub error: String,
+}
+
+impl ErrorBody {
+    /// Parse an envelope from a response body
+    ///
+    /// Also finds an envelope embedded in a longer message, as produced by
+    /// services that wrap an upstream error body in their own text.
+    pub fn parse(body: &str) -> Option<Self> {
+        if let Ok(parsed) = serde_json::from_str::<Self>(body) {
+            return Some(parsed);
+        }
+        let start = body.find('{')?;
+        let end = body.rfind('}')?;
+        serde_json::from_str(body.get(start..=end)?).ok()
+    }
+}
+
+/// An API error: code, message, optional details, and the HTTP status to send
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Option<Value>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status: code.status(),
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::BadRequest, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Conflict, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// Structured context for the client (amounts, limits, ids)
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Send a different HTTP status than the code's default
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Error for a failure whose cause is only known as a message
+    ///
+    /// Uses a specific code when the message is recognized and `fallback`
+    /// (with its default status) otherwise.
+    pub fn from_message(fallback: ErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let code = ErrorCode::classify(&message).unwrap_or(fallback);
+        Self::new(code, message)
+    }
+
+    /// Error for a failed call to another ANCHOR service
+    ///
+    /// An upstream envelope keeps its code so clients see the original cause
+    /// (e.g. `INSUFFICIENT_FUNDS` from the wallet behind an app backend).
+    pub fn from_upstream(status: u16, body: &str) -> Self {
+        if let Some(upstream) = ErrorBody::parse(body) {
+            let mut error = Self::new(upstream.code, upstream.message);
+            error.details = upstream.details;
+            if upstream.code == ErrorCode::Unknown {
+                error.status = 502;
+            }
+            return error;
+        }
+        let fallback = if status >= 500 {
+            ErrorCode::UpstreamError
+        } else {
+            ErrorCode::from_status(status)
+        };
+        Self::from_message(fallback, body)
+    }
+
+    /// Response body for this error
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            code: self.code,
+            message: self.message.clone(),
+            retryable: self.code.retryable(),
+            details: self.details.clone(),
+            error: self.message.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// `--dump-openapi [PATH]`: write the OpenAPI document of `D` to PATH (or
+/// stdout) for client generation
+///
+/// Returns `true` when the flag was given, in which case the binary should
+/// exit instead of starting the server.
+pub fn dump_openapi<D: utoipa::OpenApi>() -> std::io::Result<bool> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--dump-openapi");
+    if args.next().is_none() {
+        return Ok(false);
+    }
+    let json = D::openapi()
+        .to_pretty_json()
+        .map_err(std::io::Error::other)?;
+    match args.next() {
+        Some(path) => std::fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(true)
+}
+
+#[cfg(feature = "axum")]
+mod server {
+    use super::{ApiError, ErrorBody, ErrorCode};
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, StatusCode},
+        response::{IntoResponse, Response},
+        Json,
+    };
+
+    /// Largest error body read when normalizing a response
+    const MAX_ERROR_BODY: usize = 64 * 1024;
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            let status =
+                StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            (status, Json(self.body())).into_response()
+        }
+    }
+
+    /// Lets handlers that build `(StatusCode, String)` errors use `?` with `ApiError`
+    impl From<(StatusCode, String)> for ApiError {
+        fn from((status, message): (StatusCode, String)) -> Self {
+            let code = ErrorCode::classify(&message)
+                .unwrap_or_else(|| ErrorCode::from_status(status.as_u16()));
+            ApiError {
+                status: status.as_u16(),
+                code,
+                message,
+                details: None,
+            }
+        }
+    }
+
+    impl From<(StatusCode, &str)> for ApiError {
+        fn from((status, message): (StatusCode, &str)) -> Self {
+            (status, message.to_string()).into()
+        }
+    }
+
+    /// Rewrite non-2xx responses that are not already envelopes
+    ///
+    /// Use with `axum::middleware::map_response(normalize_errors)`. The status
+    /// is kept; the message is taken from a JSON `error`/`message` field or the
+    /// plain-text body.
+    pub async fn normalize_errors(response: Response) -> Response {
+        let status = response.status();
+        if !(status.is_client_error() || status.is_server_error()) {
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+        let bytes = match to_bytes(body, MAX_ERROR_BODY).await {
+            Ok(bytes) => bytes,
+            Err(_) => return Response::from_parts(parts, Body::empty()),
+        };
+        let text = String::from_utf8_lossy(&bytes);
+
+        let is_json = parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        if is_json && serde_json::from_str::<ErrorBody>(&text).is_ok() {
+            return Response::from_parts(parts, Body::from(bytes));
+        }
+
+        let message = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|json| {
+                ["error", "message"]
+                    .iter()
+                    .find_map(|key| json.get(key).and_then(|v| v.as_str()).map(str::to_string))
+            })
+            .unwrap_or_else(|| text.trim().to_string());
+        let message = if message.is_empty() {
+            status.canonical_reason().unwrap_or("Error").to_string()
+        } else {
+            message
+        };
+
+        let error = if ErrorBody::parse(&message).is_some() {
+            ApiError::from_upstream(status.as_u16(), &message)
+        } else {
+            ApiError::from((status, message))
+        };
+        error.into_response()
+    }
+}
+
+#[cfg(feature = "axum")]
+pub use server::normalize_errors;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_wire_format() {
+        let json = serde_json::to_string(&ErrorCode::InsufficientFunds).unwrap();
+        assert_eq!(json, "\"INSUFFICIENT_FUNDS\"");
+        assert_eq!(ErrorCode::CarrierTooSmall.as_str(), "CARRIER_TOO_SMALL");
+        assert_eq!(
+            ErrorCode::UnsupportedCarrier.as_str(),
+            "UNSUPPORTED_CARRIER"
+        );
+
+        let unknown: ErrorCode = serde_json::from_str("\"SOMETHING_NEW\"").unwrap();
+        assert_eq!(unknown, ErrorCode::Unknown);
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(ErrorCode::from_status(404), ErrorCode::NotFound);
+        assert_eq!(ErrorCode::from_status(422), ErrorCode::BadRequest);
+        assert_eq!(ErrorCode::from_status(503), ErrorCode::Unavailable);
+        assert_eq!(ErrorCode::from_status(500), ErrorCode::Internal);
+        assert_eq!(ErrorCode::DomainTaken.status(), 409);
+        assert!(ErrorCode::Unavailable.retryable());
+        assert!(!ErrorCode::InsufficientFunds.retryable());
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            ErrorCode::classify("Insufficient funds for inscription commit: need 12000 sats"),
+            Some(ErrorCode::InsufficientFunds)
+        );
+        assert_eq!(
+            ErrorCode::classify("payload too large: 120 bytes exceeds carrier limit of 80 bytes"),
+            Some(ErrorCode::CarrierTooSmall)
+        );
+        assert_eq!(ErrorCode::classify("Market not found"), None);
+    }
+
+    #[test]
+    fn test_upstream_code_is_preserved() {
+        let wallet = ApiError::new(ErrorCode::InsufficientFunds, "need 500 sats")
+            .with_details(serde_json::json!({ "need": 500 }));
+        let body = serde_json::to_string(&wallet.body()).unwrap();
+
+        let forwarded = ApiError::from_upstream(422, &format!("Wallet error: {}", body));
+        assert_eq!(forwarded.code, ErrorCode::InsufficientFunds);
+        assert_eq!(forwarded.message, "need 500 sats");
+        assert_eq!(forwarded.details, Some(serde_json::json!({ "need": 500 })));
+
+        let plain = ApiError::from_upstream(500, "connection reset");
+        assert_eq!(plain.code, ErrorCode::UpstreamError);
+        assert_eq!(plain.status, 502);
+    }
+
+    #[test]
+    fn test_body_keeps_legacy_error_field() {
+        let body = ApiError::not_found("Market not found").body();
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["code"], "NOT_FOUND");
+        assert_eq!(json["error"], "Market not found");
+        assert_eq!(json["retryable"], false);
+        assert!(json.get("details").is_none());
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn test_normalize_plain_text_error() {
+        use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+
+        let response = (StatusCode::SERVICE_UNAVAILABLE, "Wallet offline").into_response();
+        let response = normalize_errors(response).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body.code, ErrorCode::Unavailable);
+        assert_eq!(body.message, "Wallet offline");
+        assert!(body.retryable);
+    }
+}
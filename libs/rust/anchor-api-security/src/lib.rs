@@ -0,0 +1,401 @@
+//! # ANCHOR API Security
+//!
+//! The CORS policy and response security headers shared by the wallet
+//! service and app backends, configured from the environment:
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `CORS_ALLOWED_ORIGINS` | same host | Comma-separated origins, or `*` for any |
+//! | `CORS_ALLOWED_HEADERS` | | Request headers allowed on top of the defaults |
+//! | `HSTS_MAX_AGE` | off | `Strict-Transport-Security` max-age in seconds |
+//!
+//! By default a browser may call a service only from a page served by the
+//! same host (any port) or from localhost, which covers the bundled
+//! frontends without opening a public deployment to every site.
+//! `Content-Type`, `Authorization` and `X-API-Key` are always allowed so
+//! authenticated clients work cross-origin.
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .route("/health", get(health))
+//!     .layer(SecurityConfig::from_env().layer());
+//! ```
+
+use axum::{
+    extract::{Request, State},
+    http::{header, request::Parts, HeaderName, HeaderValue, Method},
+    middleware::{self, Next},
+    response::Response,
+    routing::Route,
+};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::{Layer, Service};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::warn;
+
+/// Path prefix of the Swagger UI, which gets a CSP that lets it run
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+/// CSP for API responses: nothing may load, nothing may frame them
+const API_CSP: &str = "default-src 'none'; frame-ancestors 'none'";
+
+/// CSP for the Swagger UI page and its assets
+const SWAGGER_CSP: &str = "default-src 'self'; script-src 'self' 'unsafe-inline'; \
+     style-src 'self' 'unsafe-inline'; img-src 'self' data:; connect-src 'self'; \
+     frame-ancestors 'none'";
+
+/// How long browsers may cache a preflight response
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// Which origins may call the service from a browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// Pages on the same host as the service, on any port, and localhost
+    SameHost,
+    /// Exactly these origins, e.g. `https://app.example.com`
+    List(Vec<HeaderValue>),
+    /// Any origin
+    Any,
+}
+
+impl AllowedOrigins {
+    /// Parse `CORS_ALLOWED_ORIGINS`; invalid entries are skipped with a warning
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() {
+            return Self::SameHost;
+        }
+        if value == "*" {
+            return Self::Any;
+        }
+        let origins = value
+            .split(',')
+            .map(|origin| origin.trim().trim_end_matches('/'))
+            .filter(|origin| !origin.is_empty())
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(origin) => Some(origin),
+                Err(_) => {
+                    warn!("Ignoring invalid CORS origin {:?}", origin);
+                    None
+                }
+            })
+            .collect();
+        Self::List(origins)
+    }
+
+    fn allow_origin(&self) -> AllowOrigin {
+        match self {
+            Self::SameHost => AllowOrigin::predicate(same_host),
+            Self::List(origins) => AllowOrigin::list(origins.clone()),
+            Self::Any => AllowOrigin::any(),
+        }
+    }
+}
+
+/// CORS and security header settings
+#[derive(Debug, Clone)]
+pub struct SecurityConfig {
+    pub allowed_origins: AllowedOrigins,
+    /// Request headers allowed in addition to the defaults
+    pub allowed_headers: Vec<HeaderName>,
+    /// `Strict-Transport-Security` max-age in seconds; `None` sends no HSTS
+    pub hsts_max_age: Option<u64>,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: AllowedOrigins::SameHost,
+            allowed_headers: Vec::new(),
+            hsts_max_age: None,
+        }
+    }
+}
+
+impl SecurityConfig {
+    /// Read the settings from the environment
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let allowed_headers = var("CORS_ALLOWED_HEADERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| match HeaderName::try_from(name) {
+                Ok(name) => Some(name),
+                Err(_) => {
+                    warn!("Ignoring invalid CORS header {:?}", name);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            allowed_origins: AllowedOrigins::parse(
+                &var("CORS_ALLOWED_ORIGINS").unwrap_or_default(),
+            ),
+            allowed_headers,
+            hsts_max_age: var("HSTS_MAX_AGE")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|&secs| secs > 0),
+        }
+    }
+
+    /// The CORS layer for this policy
+    pub fn cors(&self) -> CorsLayer {
+        let mut headers = vec![
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("x-api-key"),
+        ];
+        headers.extend(self.allowed_headers.iter().cloned());
+
+        CorsLayer::new()
+            .allow_origin(self.allowed_origins.allow_origin())
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ])
+            .allow_headers(headers)
+            .max_age(PREFLIGHT_MAX_AGE)
+    }
+
+    /// CORS plus the security headers, as one layer for `Router::layer`
+    pub fn layer(
+        &self,
+    ) -> impl Layer<
+        Route,
+        Service = impl Service<
+            Request,
+            Response = Response,
+            Error = Infallible,
+            Future = impl Send + 'static,
+        > + Clone
+                      + Send
+                      + 'static,
+    > + Clone
+           + Send
+           + 'static {
+        (
+            self.cors(),
+            middleware::from_fn_with_state(Arc::new(self.clone()), security_headers),
+        )
+    }
+}
+
+/// Middleware adding the standard security headers to each response
+///
+/// Headers a handler already set are left alone.
+pub async fn security_headers(
+    State(config): State<Arc<SecurityConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let csp = if request.uri().path().starts_with(SWAGGER_UI_PATH) {
+        SWAGGER_CSP
+    } else {
+        API_CSP
+    };
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    let mut set = |name: HeaderName, value: HeaderValue| {
+        headers.entry(name).or_insert(value);
+    };
+    set(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    set(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    set(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("no-referrer"),
+    );
+    set(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(csp),
+    );
+    if let Some(max_age) = config.hsts_max_age {
+        let value = format!("max-age={}; includeSubDomains", max_age);
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            set(header::STRICT_TRANSPORT_SECURITY, value);
+        }
+    }
+    response
+}
+
+/// Whether a request's origin is on the host it was sent to, or localhost
+fn same_host(origin: &HeaderValue, parts: &Parts) -> bool {
+    let Some(origin) = origin.to_str().ok().and_then(origin_host) else {
+        return false;
+    };
+    if matches!(origin, "localhost" | "127.0.0.1" | "[::1]") {
+        return true;
+    }
+    parts
+        .headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .is_some_and(|host| strip_port(host).eq_ignore_ascii_case(origin))
+}
+
+/// Host of an origin such as `http://example.com:3000`
+fn origin_host(origin: &str) -> Option<&str> {
+    let (_scheme, rest) = origin.split_once("://")?;
+    let host = strip_port(rest);
+    (!host.is_empty()).then_some(host)
+}
+
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return host.find(']').map_or(host, |end| &host[..=end]);
+    }
+    host.split(':').next().unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn vars(pairs: &[(&str, &str)]) -> SecurityConfig {
+        SecurityConfig::from_vars(|name| {
+            pairs
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    async fn send(config: &SecurityConfig, request: Request<Body>) -> Response {
+        let router: Router = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/swagger-ui/", get(|| async { "docs" }));
+        router.layer(config.layer()).oneshot(request).await.unwrap()
+    }
+
+    fn get_from(path: &str, host: &str, origin: &str) -> Request<Body> {
+        axum::http::Request::builder()
+            .uri(path)
+            .header(header::HOST, host)
+            .header(header::ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn allowed_origin(response: &Response) -> Option<&str> {
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|v| v.to_str().ok())
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let config = vars(&[]);
+        assert_eq!(config.allowed_origins, AllowedOrigins::SameHost);
+        assert_eq!(config.hsts_max_age, None);
+
+        let config = vars(&[
+            (
+                "CORS_ALLOWED_ORIGINS",
+                "https://app.example.com/, https://x.example.com",
+            ),
+            ("CORS_ALLOWED_HEADERS", "X-Request-Id, bad header"),
+            ("HSTS_MAX_AGE", "31536000"),
+        ]);
+        assert_eq!(
+            config.allowed_origins,
+            AllowedOrigins::List(vec![
+                HeaderValue::from_static("https://app.example.com"),
+                HeaderValue::from_static("https://x.example.com"),
+            ])
+        );
+        assert_eq!(
+            config.allowed_headers,
+            vec![HeaderName::from_static("x-request-id")]
+        );
+        assert_eq!(config.hsts_max_age, Some(31_536_000));
+
+        assert_eq!(
+            vars(&[("CORS_ALLOWED_ORIGINS", "*")]).allowed_origins,
+            AllowedOrigins::Any
+        );
+        assert_eq!(vars(&[("HSTS_MAX_AGE", "0")]).hsts_max_age, None);
+    }
+
+    #[test]
+    fn test_origin_host() {
+        assert_eq!(origin_host("http://localhost:3017"), Some("localhost"));
+        assert_eq!(origin_host("https://example.com"), Some("example.com"));
+        assert_eq!(origin_host("http://[::1]:8080"), Some("[::1]"));
+        assert_eq!(origin_host("null"), None);
+    }
+
+    #[tokio::test]
+    async fn test_same_host_cors() {
+        let config = SecurityConfig::default();
+
+        let response = send(
+            &config,
+            get_from("/health", "node.local:3401", "http://node.local:3017"),
+        )
+        .await;
+        assert_eq!(allowed_origin(&response), Some("http://node.local:3017"));
+
+        let response = send(
+            &config,
+            get_from("/health", "node.local:3401", "http://localhost:3017"),
+        )
+        .await;
+        assert_eq!(allowed_origin(&response), Some("http://localhost:3017"));
+
+        let response = send(
+            &config,
+            get_from("/health", "node.local:3401", "https://evil.example"),
+        )
+        .await;
+        assert_eq!(allowed_origin(&response), None);
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        let config = SecurityConfig {
+            hsts_max_age: Some(600),
+            ..SecurityConfig::default()
+        };
+
+        let response = send(
+            &config,
+            get_from("/health", "localhost", "http://localhost"),
+        )
+        .await;
+        let headers = response.headers();
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::CONTENT_SECURITY_POLICY], API_CSP);
+        assert_eq!(
+            headers[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=600; includeSubDomains"
+        );
+
+        let response = send(
+            &config,
+            get_from("/swagger-ui/", "localhost", "http://localhost"),
+        )
+        .await;
+        assert_eq!(
+            response.headers()[header::CONTENT_SECURITY_POLICY],
+            SWAGGER_CSP
+        );
+    }
+}
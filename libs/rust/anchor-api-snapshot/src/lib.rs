@@ -0,0 +1,298 @@
+//! # ANCHOR API Snapshots
+//!
+//! Snapshot-consistent reads for app backends.
+//!
+//! `POST /snapshots` opens a read-only REPEATABLE READ transaction on a
+//! dedicated connection and exports its MVCC snapshot. List endpoints called
+//! with `?snapshot=<token>` import that snapshot through [`begin`], so every
+//! page of an export sees the database as it was when the snapshot was
+//! taken, whatever the indexer writes in between.
+//!
+//! An open snapshot holds back vacuum, so snapshots expire after
+//! `SNAPSHOT_TTL_SECS` without use and only `MAX_SNAPSHOTS` may be open.
+//!
+//! ```ignore
+//! let snapshots = Arc::new(Snapshots::from_env(&database_url, HEIGHT_QUERY));
+//! snapshots.start();
+//!
+//! let app = Router::new()
+//!     .route("/messages", get(list_messages))
+//!     .merge(anchor_api_snapshot::routes(snapshots.clone()))
+//!     .with_state(state);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anchor_api_error::{ApiError, ErrorCode};
+use axum::{
+    extract::{Path, State},
+    routing::{delete, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, PgConnection, PgPool, Postgres, Transaction};
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+/// Snapshot to read a list endpoint from
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct SnapshotParams {
+    /// Token returned by `POST /snapshots`
+    pub snapshot: Option<String>,
+}
+
+/// An open read snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SnapshotResponse {
+    /// Pass as `?snapshot=` to list endpoints
+    pub snapshot: String,
+    /// Indexed height when the snapshot was taken
+    pub block_height: i32,
+    pub created_at: DateTime<Utc>,
+    /// Seconds of inactivity before the snapshot is closed
+    pub expires_in_secs: u64,
+}
+
+/// Exporting connection kept open for the life of a snapshot
+struct OpenSnapshot {
+    conn: PgConnection,
+    snapshot_id: String,
+    expires_at: Instant,
+}
+
+/// Registry of open snapshots
+pub struct Snapshots {
+    database_url: String,
+    height_query: &'static str,
+    ttl: Duration,
+    max_open: usize,
+    open: Mutex<HashMap<String, OpenSnapshot>>,
+}
+
+impl Snapshots {
+    /// Create the registry, reading limits from the environment
+    ///
+    /// `height_query` returns the app's indexed height as a single `INTEGER`,
+    /// recorded alongside each snapshot.
+    pub fn from_env(database_url: &str, height_query: &'static str) -> Self {
+        let ttl_secs = std::env::var("SNAPSHOT_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        let max_open = std::env::var("MAX_SNAPSHOTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(8);
+
+        Self {
+            database_url: database_url.to_string(),
+            height_query,
+            ttl: Duration::from_secs(ttl_secs),
+            max_open,
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Open and export a new snapshot
+    ///
+    /// Returns `None` when `MAX_SNAPSHOTS` are already open.
+    pub async fn create(&self) -> sqlx::Result<Option<SnapshotResponse>> {
+        self.close_expired().await;
+        if self.open.lock().unwrap().len() >= self.max_open {
+            return Ok(None);
+        }
+
+        let mut conn = PgConnection::connect(&self.database_url).await?;
+        sqlx::query("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut conn)
+            .await?;
+        let (snapshot_id,): (String,) = sqlx::query_as("SELECT pg_export_snapshot()")
+            .fetch_one(&mut conn)
+            .await?;
+        let (block_height,): (i32,) = sqlx::query_as(self.height_query)
+            .fetch_one(&mut conn)
+            .await?;
+
+        let mut bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+
+        let mut open = self.open.lock().unwrap();
+        if open.len() >= self.max_open {
+            return Ok(None);
+        }
+        open.insert(
+            token.clone(),
+            OpenSnapshot {
+                conn,
+                snapshot_id,
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+
+        Ok(Some(SnapshotResponse {
+            snapshot: token,
+            block_height,
+            created_at: Utc::now(),
+            expires_in_secs: self.ttl.as_secs(),
+        }))
+    }
+
+    /// Postgres snapshot id for a token, extending its lifetime
+    pub fn snapshot_id(&self, token: &str) -> Option<String> {
+        let mut open = self.open.lock().unwrap();
+        let snapshot = open.get_mut(token)?;
+        if snapshot.expires_at <= Instant::now() {
+            return None;
+        }
+        snapshot.expires_at = Instant::now() + self.ttl;
+        Some(snapshot.snapshot_id.clone())
+    }
+
+    /// Resolve a `?snapshot=` token to the Postgres snapshot it pins
+    pub fn resolve(&self, token: Option<&str>) -> Result<Option<String>, ApiError> {
+        match token {
+            Some(token) => self
+                .snapshot_id(token)
+                .map(Some)
+                .ok_or_else(|| ApiError::not_found("Snapshot not found or expired")),
+            None => Ok(None),
+        }
+    }
+
+    /// Close a snapshot before it expires
+    pub async fn release(&self, token: &str) -> bool {
+        let removed = self.open.lock().unwrap().remove(token);
+        match removed {
+            Some(snapshot) => {
+                close(snapshot).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Close every snapshot that has passed its expiry
+    async fn close_expired(&self) {
+        let now = Instant::now();
+        let expired: Vec<OpenSnapshot> = {
+            let mut open = self.open.lock().unwrap();
+            let tokens: Vec<String> = open
+                .iter()
+                .filter(|(_, s)| s.expires_at <= now)
+                .map(|(t, _)| t.clone())
+                .collect();
+            tokens.iter().filter_map(|t| open.remove(t)).collect()
+        };
+        for snapshot in expired {
+            close(snapshot).await;
+        }
+    }
+
+    /// Start the background task that closes expired snapshots
+    pub fn start(self: &Arc<Self>) {
+        info!(
+            "Read snapshots enabled (ttl {}s, max {})",
+            self.ttl.as_secs(),
+            self.max_open
+        );
+        let snapshots = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(30));
+            loop {
+                ticker.tick().await;
+                snapshots.close_expired().await;
+            }
+        });
+    }
+}
+
+/// End the exporting transaction and drop its connection
+async fn close(snapshot: OpenSnapshot) {
+    if let Err(e) = snapshot.conn.close().await {
+        warn!("Failed to close snapshot connection: {}", e);
+    }
+}
+
+/// Begin a read transaction, importing an exported snapshot if given
+pub async fn begin(
+    pool: &PgPool,
+    snapshot_id: Option<&str>,
+) -> sqlx::Result<Transaction<'static, Postgres>> {
+    let mut tx = pool.begin().await?;
+    if let Some(snapshot_id) = snapshot_id {
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!("SET TRANSACTION SNAPSHOT '{}'", snapshot_id))
+            .execute(&mut *tx)
+            .await?;
+    }
+    Ok(tx)
+}
+
+/// `POST /snapshots` and `DELETE /snapshots/:token`, for merging into an
+/// app's router
+pub fn routes<S>(snapshots: Arc<Snapshots>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/snapshots", post(create_snapshot))
+        .route("/snapshots/:token", delete(release_snapshot))
+        .with_state(snapshots)
+}
+
+/// Open a read snapshot for consistent pagination
+#[utoipa::path(
+    post,
+    path = "/snapshots",
+    tag = "Snapshots",
+    responses(
+        (status = 200, description = "Snapshot opened", body = SnapshotResponse),
+        (status = 429, description = "Too many open snapshots"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_snapshot(
+    State(snapshots): State<Arc<Snapshots>>,
+) -> Result<Json<SnapshotResponse>, ApiError> {
+    match snapshots.create().await {
+        Ok(Some(snapshot)) => Ok(Json(snapshot)),
+        Ok(None) => Err(ApiError::new(
+            ErrorCode::RateLimited,
+            "Too many open snapshots, release one or retry later",
+        )),
+        Err(e) => {
+            error!("Failed to open snapshot: {}", e);
+            Err(ApiError::internal(e.to_string()))
+        }
+    }
+}
+
+/// Release a read snapshot
+#[utoipa::path(
+    delete,
+    path = "/snapshots/{token}",
+    tag = "Snapshots",
+    params(
+        ("token" = String, Path, description = "Snapshot token")
+    ),
+    responses(
+        (status = 200, description = "Snapshot released"),
+        (status = 404, description = "Snapshot not found or expired")
+    )
+)]
+pub async fn release_snapshot(
+    State(snapshots): State<Arc<Snapshots>>,
+    Path(token): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if !snapshots.release(&token).await {
+        return Err(ApiError::not_found("Snapshot not found or expired"));
+    }
+    Ok(Json(serde_json::json!({ "released": token })))
+}
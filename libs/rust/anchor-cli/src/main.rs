@@ -0,0 +1,240 @@
+//! ANCHOR command-line tools
+//!
+//! ```text
+//! anchor-cli proof verify <FILE> <TXID> [--esplora URL]
+//!                                       [--rpc-url URL --rpc-user USER --rpc-password PASS]
+//! anchor-cli proof verify <FILE> --tx-hex HEX
+//! ```
+//!
+//! `proof verify` hashes a local file, fetches the transaction from a node or
+//! an Esplora server, decodes its carriers and checks that it stamps the file
+//! and is mined. With `--tx-hex` it checks a raw transaction fully offline,
+//! without the inclusion check. No ANCHOR backend is involved either way.
+
+mod source;
+
+use anyhow::{Context, Result};
+use bitcoin::consensus::encode::deserialize_hex;
+use bitcoin::{Transaction, Txid};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use source::TxSource;
+
+const USAGE: &str = "usage: anchor-cli proof verify <FILE> (<TXID> | --tx-hex HEX) \
+[--esplora URL] [--rpc-url URL] [--rpc-user USER] [--rpc-password PASS]";
+
+/// Where `proof verify` gets the transaction
+#[derive(Debug, PartialEq, Eq)]
+enum TxArg {
+    Txid(Txid),
+    Hex(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct VerifyArgs {
+    file: PathBuf,
+    tx: TxArg,
+    esplora: Option<String>,
+    rpc_url: String,
+    rpc_user: String,
+    rpc_password: String,
+}
+
+impl VerifyArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut tx_hex = None;
+        let mut esplora = std::env::var("ESPLORA_URL").ok();
+        let mut rpc_url = std::env::var("BITCOIN_RPC_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:18443".to_string());
+        let mut rpc_user =
+            std::env::var("BITCOIN_RPC_USER").unwrap_or_else(|_| "anchor".to_string());
+        let mut rpc_password =
+            std::env::var("BITCOIN_RPC_PASSWORD").unwrap_or_else(|_| "anchor".to_string());
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", arg))
+            };
+            match arg.as_str() {
+                "--tx-hex" => tx_hex = Some(value()?),
+                "--esplora" => esplora = Some(value()?),
+                "--rpc-url" => rpc_url = value()?,
+                "--rpc-user" => rpc_user = value()?,
+                "--rpc-password" => rpc_password = value()?,
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let tx = match (positional.len(), tx_hex) {
+            (1, Some(hex)) => TxArg::Hex(hex),
+            (2, None) => TxArg::Txid(
+                positional[1]
+                    .parse()
+                    .map_err(|_| format!("invalid txid '{}'", positional[1]))?,
+            ),
+            _ => return Err("expected a file and either a txid or --tx-hex".to_string()),
+        };
+
+        Ok(Self {
+            file: PathBuf::from(&positional[0]),
+            tx,
+            esplora,
+            rpc_url,
+            rpc_user,
+            rpc_password,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let rest = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["proof", "verify", ..] => &args[2..],
+        ["-h"] | ["--help"] => {
+            eprintln!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => return usage("unknown command"),
+    };
+    let args = match VerifyArgs::parse(rest) {
+        Ok(args) => args,
+        Err(e) => return usage(&e),
+    };
+
+    match verify(&args).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run `proof verify`, printing the outcome; returns whether the proof holds
+async fn verify(args: &VerifyArgs) -> Result<bool> {
+    let file = std::fs::read(&args.file)
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+
+    let (tx, inclusion): (Transaction, _) = match &args.tx {
+        TxArg::Hex(hex) => (
+            deserialize_hex(hex.trim()).context("Invalid transaction hex")?,
+            None,
+        ),
+        TxArg::Txid(txid) => {
+            let source = match &args.esplora {
+                Some(url) => TxSource::esplora(url),
+                None => TxSource::node(&args.rpc_url, &args.rpc_user, &args.rpc_password)?,
+            };
+            let fetched = source.fetch(txid).await?;
+            (fetched.tx, Some(fetched.inclusion))
+        }
+    };
+
+    let proof = match anchor_specs::proof::verify(&file, &tx) {
+        Ok(proof) => proof,
+        Err(e) => {
+            println!("NOT VERIFIED: {}", e);
+            return Ok(false);
+        }
+    };
+
+    println!("Transaction: {}", tx.compute_txid());
+    println!(
+        "Proof:       {:?} of {} {} ({:?}, index {})",
+        proof.operation,
+        proof.entry.algorithm.name(),
+        proof.entry.hash_hex(),
+        proof.carrier,
+        proof.vout
+    );
+    let metadata = &proof.entry.metadata;
+    if let Some(filename) = &metadata.filename {
+        println!("Filename:    {}", filename);
+    }
+    if let Some(description) = &metadata.description {
+        println!("Description: {}", description);
+    }
+
+    match inclusion {
+        None => {
+            println!("Inclusion:   not checked (offline)");
+            println!("VERIFIED (payload only)");
+            Ok(true)
+        }
+        Some(None) => {
+            println!("Inclusion:   unconfirmed");
+            println!("NOT VERIFIED: transaction is not mined yet");
+            Ok(false)
+        }
+        Some(Some(inclusion)) => {
+            println!(
+                "Inclusion:   block {}{}{}",
+                inclusion.block_hash,
+                inclusion
+                    .height
+                    .map(|h| format!(" at height {}", h))
+                    .unwrap_or_default(),
+                inclusion
+                    .confirmations
+                    .map(|c| format!(", {} confirmation(s)", c))
+                    .unwrap_or_default()
+            );
+            println!("VERIFIED");
+            Ok(true)
+        }
+    }
+}
+
+fn usage(error: &str) -> ExitCode {
+    eprintln!("error: {}", error);
+    eprintln!("{}", USAGE);
+    ExitCode::from(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    const TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+    #[test]
+    fn test_parse_txid() {
+        let parsed = VerifyArgs::parse(&args(&[
+            "deed.pdf",
+            TXID,
+            "--esplora",
+            "https://mempool.space/api",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.file, PathBuf::from("deed.pdf"));
+        assert_eq!(parsed.tx, TxArg::Txid(TXID.parse().unwrap()));
+        assert_eq!(parsed.esplora.as_deref(), Some("https://mempool.space/api"));
+    }
+
+    #[test]
+    fn test_parse_offline() {
+        let parsed = VerifyArgs::parse(&args(&["deed.pdf", "--tx-hex", "0200"])).unwrap();
+        assert_eq!(parsed.tx, TxArg::Hex("0200".to_string()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(VerifyArgs::parse(&args(&["deed.pdf"])).is_err());
+        assert!(VerifyArgs::parse(&args(&["deed.pdf", "nothex"])).is_err());
+        assert!(VerifyArgs::parse(&args(&["deed.pdf", TXID, "--tx-hex", "00"])).is_err());
+        assert!(VerifyArgs::parse(&args(&["deed.pdf", TXID, "--esplora"])).is_err());
+        assert!(VerifyArgs::parse(&args(&["deed.pdf", TXID, "--bogus"])).is_err());
+    }
+}
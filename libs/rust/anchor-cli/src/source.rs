@@ -0,0 +1,182 @@
+//! Where transactions come from: a Bitcoin Core node or an Esplora server
+//!
+//! Either way the transaction's merkle branch is checked against its block
+//! header locally, so a source can withhold a transaction but not forge one.
+
+use anchor_core::{verify_inclusion, InclusionProof};
+use anyhow::{bail, Context, Result};
+use bitcoin::block::Header;
+use bitcoin::consensus::encode::deserialize_hex;
+use bitcoin::merkle_tree::MerkleBlock;
+use bitcoin::{BlockHash, Transaction, TxMerkleNode, Txid};
+use bitcoincore_rpc::{Auth, Client, RpcApi};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// A transaction and, if it is mined, where
+pub struct Fetched {
+    pub tx: Transaction,
+    pub inclusion: Option<Inclusion>,
+}
+
+/// Verified inclusion of a transaction in a block
+pub struct Inclusion {
+    pub block_hash: BlockHash,
+    pub height: Option<u64>,
+    pub confirmations: Option<u64>,
+}
+
+pub enum TxSource {
+    Node(Client),
+    Esplora { url: String, http: reqwest::Client },
+}
+
+impl TxSource {
+    pub fn node(url: &str, user: &str, password: &str) -> Result<Self> {
+        let client = Client::new(url, Auth::UserPass(user.to_string(), password.to_string()))
+            .context("Failed to create RPC client")?;
+        Ok(Self::Node(client))
+    }
+
+    pub fn esplora(url: &str) -> Self {
+        Self::Esplora {
+            url: url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Fetch a transaction and check its inclusion proof
+    pub async fn fetch(&self, txid: &Txid) -> Result<Fetched> {
+        match self {
+            Self::Node(client) => fetch_node(client, txid),
+            Self::Esplora { url, http } => fetch_esplora(http, url, txid).await,
+        }
+    }
+}
+
+fn fetch_node(client: &Client, txid: &Txid) -> Result<Fetched> {
+    let info = client
+        .get_raw_transaction_info(txid, None)
+        .context("Node could not find the transaction (is txindex enabled?)")?;
+    let tx = info.transaction()?;
+    let Some(block_hash) = info.blockhash else {
+        return Ok(Fetched {
+            tx,
+            inclusion: None,
+        });
+    };
+
+    // Check the node's merkle proof ourselves rather than trusting verifytxoutproof
+    let proof_bytes = client.get_tx_out_proof(&[*txid], Some(&block_hash))?;
+    let merkle_block: MerkleBlock = bitcoin::consensus::deserialize(&proof_bytes)
+        .context("Node returned an invalid merkle proof")?;
+    let header = merkle_block.header;
+    if header.block_hash() != block_hash {
+        bail!("Merkle proof is for a different block");
+    }
+    header
+        .validate_pow(header.target())
+        .context("Block header has insufficient proof of work")?;
+    let mut matches = Vec::new();
+    let mut indexes = Vec::new();
+    let root = merkle_block
+        .txn
+        .extract_matches(&mut matches, &mut indexes)
+        .map_err(|e| anyhow::anyhow!("Invalid merkle proof: {:?}", e))?;
+    if root != header.merkle_root || !matches.contains(txid) {
+        bail!("Merkle proof does not commit to the transaction");
+    }
+
+    let header_info = client.get_block_header_info(&block_hash)?;
+    Ok(Fetched {
+        tx,
+        inclusion: Some(Inclusion {
+            block_hash,
+            height: Some(header_info.height as u64),
+            confirmations: Some(header_info.confirmations.max(0) as u64),
+        }),
+    })
+}
+
+#[derive(Deserialize)]
+struct EsploraStatus {
+    confirmed: bool,
+    block_hash: Option<BlockHash>,
+    block_height: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct EsploraMerkleProof {
+    merkle: Vec<String>,
+    pos: u32,
+}
+
+async fn fetch_esplora(http: &reqwest::Client, url: &str, txid: &Txid) -> Result<Fetched> {
+    let get = |path: String| async move {
+        let response = http
+            .get(format!("{}{}", url, path))
+            .send()
+            .await
+            .with_context(|| format!("Esplora unreachable at {}", url))?;
+        if !response.status().is_success() {
+            bail!("Esplora returned {} for {}", response.status(), path);
+        }
+        Ok(response)
+    };
+
+    let hex = get(format!("/tx/{}/hex", txid)).await?.text().await?;
+    let tx: Transaction =
+        deserialize_hex(hex.trim()).context("Esplora returned an invalid transaction")?;
+    if tx.compute_txid() != *txid {
+        bail!("Esplora returned a different transaction");
+    }
+
+    let status: EsploraStatus = get(format!("/tx/{}/status", txid)).await?.json().await?;
+    let (true, Some(block_hash)) = (status.confirmed, status.block_hash) else {
+        return Ok(Fetched {
+            tx,
+            inclusion: None,
+        });
+    };
+
+    let merkle: EsploraMerkleProof = get(format!("/tx/{}/merkle-proof", txid))
+        .await?
+        .json()
+        .await?;
+    let proof = InclusionProof {
+        txid: *txid,
+        block_hash,
+        index: merkle.pos,
+        branch: merkle
+            .merkle
+            .iter()
+            .map(|h| TxMerkleNode::from_str(h))
+            .collect::<Result<_, _>>()
+            .context("Esplora returned an invalid merkle branch")?,
+    };
+    let header_hex = get(format!("/block/{}/header", block_hash))
+        .await?
+        .text()
+        .await?;
+    let header: Header =
+        deserialize_hex(header_hex.trim()).context("Esplora returned an invalid block header")?;
+    verify_inclusion(&proof, &[header])?;
+
+    let tip: Option<u64> = get("/blocks/tip/height".to_string())
+        .await?
+        .text()
+        .await?
+        .trim()
+        .parse()
+        .ok();
+    Ok(Fetched {
+        tx,
+        inclusion: Some(Inclusion {
+            block_hash,
+            height: status.block_height,
+            confirmations: tip
+                .zip(status.block_height)
+                .map(|(tip, height)| tip.saturating_sub(height) + 1),
+        }),
+    })
+}
@@ -0,0 +1,279 @@
+//! Anchor Domains API (decentralized DNS)
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::{CreatedTx, Health, Page, PageParams};
+
+/// A registered domain with its records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Domain {
+    pub id: i32,
+    pub name: String,
+    pub txid: String,
+    pub vout: i32,
+    pub txid_prefix: String,
+    pub owner_txid: String,
+    /// Address holding the ownership UTXO now
+    #[serde(default)]
+    pub owner_address: Option<String>,
+    pub block_height: Option<i32>,
+    pub records: Vec<DnsRecord>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A DNS record as stored on-chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsRecord {
+    pub id: i32,
+    pub record_type: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub ttl: i32,
+    pub value: String,
+    #[serde(default)]
+    pub priority: Option<i32>,
+    #[serde(default)]
+    pub weight: Option<i32>,
+    #[serde(default)]
+    pub port: Option<i32>,
+    pub txid: String,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A domain in a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainSummary {
+    pub id: i32,
+    pub name: String,
+    pub txid: String,
+    pub txid_prefix: String,
+    /// Ownership UTXO
+    #[serde(default)]
+    pub owner_txid: String,
+    #[serde(default)]
+    pub owner_vout: i32,
+    pub record_count: i64,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Records a name resolves to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resolution {
+    pub name: String,
+    pub txid: String,
+    pub vout: i32,
+    pub txid_prefix: String,
+    pub records: Vec<DnsRecord>,
+}
+
+/// One registration or update of a domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub txid: String,
+    pub vout: i32,
+    pub operation: String,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainStats {
+    pub total_domains: i64,
+    pub total_records: i64,
+    pub total_transactions: i64,
+    pub last_block_height: Option<i32>,
+    pub last_update: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Availability {
+    pub name: String,
+    pub available: bool,
+}
+
+/// A record to publish; `ttl` defaults to 300 on the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsRecordInput {
+    pub record_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u16>,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+impl DnsRecordInput {
+    /// A record with only a type and value
+    pub fn new(record_type: &str, value: &str) -> Self {
+        Self {
+            record_type: record_type.to_string(),
+            ttl: None,
+            value: value.to_string(),
+            priority: None,
+            weight: None,
+            port: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RegisterRequest<'a> {
+    name: &'a str,
+    records: &'a [DnsRecordInput],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    carrier: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct UpdateRequest<'a> {
+    records: &'a [DnsRecordInput],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    carrier: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct ListQuery<'a> {
+    page: i32,
+    per_page: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owned_by: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ByOwnerRequest<'a> {
+    txids: &'a [String],
+}
+
+/// Client for the Anchor Domains backend
+#[derive(Debug, Clone)]
+pub struct DomainsClient {
+    http: Http,
+}
+
+impl DomainsClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/health").await
+    }
+
+    pub async fn stats(&self) -> Result<DomainStats> {
+        self.http.get("/stats").await
+    }
+
+    /// Resolve a name such as `satoshi.btc`
+    pub async fn resolve(&self, name: &str) -> Result<Resolution> {
+        self.http.get(&format!("/resolve/{}", segment(name))).await
+    }
+
+    /// Resolve the domain registered by a transaction, from its txid prefix
+    pub async fn resolve_by_txid(&self, prefix: &str) -> Result<Resolution> {
+        self.http
+            .get(&format!("/resolve/txid/{}", segment(prefix)))
+            .await
+    }
+
+    pub async fn list(
+        &self,
+        page: PageParams,
+        search: Option<&str>,
+    ) -> Result<Page<DomainSummary>> {
+        let query = ListQuery {
+            page: page.page,
+            per_page: page.per_page,
+            search,
+            owned_by: None,
+        };
+        self.http.get_query("/domains", &query).await
+    }
+
+    /// Domains currently owned by an address or hex output script
+    pub async fn owned_by(&self, owner: &str, page: PageParams) -> Result<Page<DomainSummary>> {
+        let query = ListQuery {
+            page: page.page,
+            per_page: page.per_page,
+            search: None,
+            owned_by: Some(owner),
+        };
+        self.http.get_query("/domains", &query).await
+    }
+
+    pub async fn domain(&self, name: &str) -> Result<Domain> {
+        self.http.get(&format!("/domains/{}", segment(name))).await
+    }
+
+    pub async fn history(&self, name: &str) -> Result<Vec<HistoryEntry>> {
+        self.http
+            .get(&format!("/domains/{}/history", segment(name)))
+            .await
+    }
+
+    /// Domains owned by the given ownership txids
+    pub async fn by_owner(&self, txids: &[String]) -> Result<Vec<DomainSummary>> {
+        self.http
+            .post("/domains/by-owner", &ByOwnerRequest { txids })
+            .await
+    }
+
+    pub async fn availability(&self, name: &str) -> Result<Availability> {
+        self.http
+            .get(&format!("/available/{}", segment(name)))
+            .await
+    }
+
+    /// Register a name; fails with `DOMAIN_TAKEN` if it is already registered
+    pub async fn register(
+        &self,
+        name: &str,
+        records: &[DnsRecordInput],
+        carrier: Option<u8>,
+    ) -> Result<CreatedTx> {
+        let request = RegisterRequest {
+            name,
+            records,
+            carrier,
+        };
+        self.http.post("/register", &request).await
+    }
+
+    /// Replace a domain's records; the wallet must hold the ownership UTXO
+    pub async fn update(
+        &self,
+        name: &str,
+        records: &[DnsRecordInput],
+        carrier: Option<u8>,
+    ) -> Result<CreatedTx> {
+        self.http
+            .post(
+                &format!("/update/{}", segment(name)),
+                &UpdateRequest { records, carrier },
+            )
+            .await
+    }
+}
@@ -0,0 +1,42 @@
+//! Error types for the client
+
+use anchor_api_error::{ApiError, ErrorCode};
+use thiserror::Error;
+
+/// Result type for client calls
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// Client error types
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// The request could not be sent or the response could not be read
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The service answered with an error envelope
+    #[error("{0}")]
+    Api(ApiError),
+
+    /// The response body did not match the expected type
+    #[error("Unexpected response from {url}: {message}")]
+    Decode { url: String, message: String },
+}
+
+impl ClientError {
+    /// Machine-readable code when the service returned an error
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Api(err) => Some(err.code),
+            _ => None,
+        }
+    }
+
+    /// Whether retrying the same call may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Api(err) => err.code.retryable(),
+            Self::Http(err) => err.is_timeout() || err.is_connect(),
+            Self::Decode { .. } => false,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Message explorer API (messages, threads, replies)
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::{Health, Page, PageParams};
+
+/// An indexed ANCHOR message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub id: i32,
+    pub txid: String,
+    pub vout: i32,
+    /// Position among the messages carried by the transaction
+    #[serde(default)]
+    pub payload_index: i16,
+    pub block_height: Option<i32>,
+    pub kind: i16,
+    pub kind_name: String,
+    pub carrier: i16,
+    pub carrier_name: String,
+    pub body_hex: String,
+    pub body_text: Option<String>,
+    pub anchors: Vec<MessageAnchor>,
+    pub reply_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A reference from a message to an earlier output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageAnchor {
+    pub index: i16,
+    pub txid_prefix: String,
+    pub vout: i16,
+    pub resolved_txid: Option<String>,
+    pub is_ambiguous: bool,
+    pub is_orphan: bool,
+}
+
+/// Breakdown of an arbitrary transaction from `/decode/{txid}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedTransaction {
+    pub txid: String,
+    pub indexed: bool,
+    pub payloads: Vec<DecodedPayload>,
+}
+
+/// One ANCHOR payload found by the decoder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedPayload {
+    pub carrier: i16,
+    pub carrier_name: String,
+    /// `output:N`, `input:N` or `transaction`
+    pub location: String,
+    pub kind: i16,
+    pub kind_name: String,
+    pub anchors: Vec<DecodedAnchor>,
+    pub body_size: usize,
+    pub body_preview_hex: String,
+    pub body_preview_text: Option<String>,
+    /// Kind-specific fields, for kinds with a known spec
+    pub fields: Option<serde_json::Value>,
+    pub fields_error: Option<String>,
+    pub roundtrip: bool,
+    pub conflicts: Vec<String>,
+}
+
+/// An anchor with the indexed transactions matching its prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedAnchor {
+    pub txid_prefix: String,
+    pub vout: i16,
+    pub candidates: Vec<String>,
+}
+
+/// Merkle proof that a message's transaction is in a block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageProof {
+    pub txid: String,
+    pub vout: i32,
+    pub block_hash: String,
+    pub block_height: Option<i32>,
+    /// Serialized block header, hex encoded
+    pub header_hex: String,
+    pub index: u32,
+    /// Sibling hashes from the transaction up to the merkle root
+    pub branch: Vec<String>,
+    pub explorer_url: String,
+}
+
+/// Indexer-wide message counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplorerStats {
+    pub total_messages: i64,
+    pub total_roots: i64,
+    pub total_replies: i64,
+    pub total_anchors: i64,
+    pub resolved_anchors: i64,
+    pub orphan_anchors: i64,
+    pub ambiguous_anchors: i64,
+    pub last_block_height: i32,
+    pub carriers: CarrierCounts,
+}
+
+/// Message counts per carrier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarrierCounts {
+    pub op_return: i64,
+    pub inscription: i64,
+    pub stamps: i64,
+    pub taproot_annex: i64,
+    pub witness_data: i64,
+}
+
+/// A root message with its reply tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thread {
+    pub root: Message,
+    pub replies: Vec<ThreadNode>,
+    pub total_messages: i64,
+}
+
+/// A reply and its own replies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadNode {
+    pub message: Message,
+    pub replies: Vec<ThreadNode>,
+}
+
+/// A root message with the size of its thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopularThread {
+    #[serde(flatten)]
+    pub message: Message,
+    pub total_thread_messages: i64,
+}
+
+#[derive(Serialize)]
+struct ListQuery {
+    page: i32,
+    per_page: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<i16>,
+}
+
+impl ListQuery {
+    fn new(page: PageParams, kind: Option<i16>) -> Self {
+        Self {
+            page: page.page,
+            per_page: page.per_page,
+            kind,
+        }
+    }
+}
+
+/// Client for the message explorer backend
+#[derive(Debug, Clone)]
+pub struct ExplorerClient {
+    http: Http,
+}
+
+impl ExplorerClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/health").await
+    }
+
+    pub async fn stats(&self) -> Result<ExplorerStats> {
+        self.http.get("/stats").await
+    }
+
+    /// List messages, newest first, optionally of one kind
+    pub async fn messages(&self, page: PageParams, kind: Option<i16>) -> Result<Page<Message>> {
+        self.http
+            .get_query("/messages", &ListQuery::new(page, kind))
+            .await
+    }
+
+    pub async fn message(&self, txid: &str, vout: u32) -> Result<Message> {
+        self.http
+            .get(&format!("/messages/{}/{}", segment(txid), vout))
+            .await
+    }
+
+    /// Merkle proof of the message's inclusion in its block
+    pub async fn message_proof(&self, txid: &str, vout: u32) -> Result<MessageProof> {
+        self.http
+            .get(&format!("/messages/{}/{}/proof", segment(txid), vout))
+            .await
+    }
+
+    /// Every message carried by a transaction, in payload order
+    pub async fn transaction_messages(&self, txid: &str) -> Result<Vec<Message>> {
+        self.http
+            .get(&format!("/tx/{}/messages", segment(txid)))
+            .await
+    }
+
+    /// Decode any transaction, indexed or not, through every carrier
+    pub async fn decode(&self, txid: &str) -> Result<DecodedTransaction> {
+        self.http.get(&format!("/decode/{}", segment(txid))).await
+    }
+
+    /// List thread roots (messages without a parent)
+    pub async fn roots(&self, page: PageParams) -> Result<Page<Message>> {
+        self.http
+            .get_query("/roots", &ListQuery::new(page, None))
+            .await
+    }
+
+    pub async fn thread(&self, txid: &str, vout: u32) -> Result<Thread> {
+        self.http
+            .get(&format!("/threads/{}/{}", segment(txid), vout))
+            .await
+    }
+
+    /// Direct replies to a message
+    pub async fn replies(&self, txid: &str, vout: u32) -> Result<Vec<Message>> {
+        self.http
+            .get(&format!("/replies/{}/{}", segment(txid), vout))
+            .await
+    }
+
+    /// Threads with the most messages
+    pub async fn popular(&self, limit: i32) -> Result<Vec<PopularThread>> {
+        self.http
+            .get_query("/popular", &[("per_page", limit)])
+            .await
+    }
+}
@@ -0,0 +1,177 @@
+//! Shared HTTP transport for the service clients
+
+use anchor_api_error::{ApiError, ErrorBody, ErrorCode};
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{ClientError, Result};
+
+/// Base URL plus a shared connection pool
+#[derive(Debug, Clone)]
+pub(crate) struct Http {
+    base_url: String,
+    client: Client,
+}
+
+impl Http {
+    pub(crate) fn new(base_url: &str, client: Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        }
+    }
+
+    pub(crate) fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.send(self.client.get(self.url(path))).await
+    }
+
+    pub(crate) async fn get_query<T, Q>(&self, path: &str, query: &Q) -> Result<T>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        self.send(self.client.get(self.url(path)).query(query))
+            .await
+    }
+
+    pub(crate) async fn post<T, B>(&self, path: &str, body: &B) -> Result<T>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        self.send(self.client.post(self.url(path)).json(body)).await
+    }
+
+    /// Send a request, turning non-2xx responses into [`ClientError::Api`]
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = request.send().await?;
+        let status = response.status();
+        let url = response.url().to_string();
+        let text = response.text().await?;
+
+        if !status.is_success() {
+            return Err(ClientError::Api(api_error(status.as_u16(), &text)));
+        }
+
+        // Endpoints that answer with no body decode as `()`
+        let text = if text.is_empty() { "null" } else { &text };
+        serde_json::from_str(text).map_err(|e| ClientError::Decode {
+            url,
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Error from a failed response, read from its envelope when it has one
+fn api_error(status: u16, body: &str) -> ApiError {
+    match ErrorBody::parse(body) {
+        Some(envelope) => {
+            let mut error = ApiError::new(envelope.code, envelope.message).with_status(status);
+            error.details = envelope.details;
+            error
+        }
+        None => {
+            ApiError::from_message(ErrorCode::from_status(status), body.trim()).with_status(status)
+        }
+    }
+}
+
+/// Percent-encode one path segment
+pub(crate) fn segment(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{Json, Router};
+
+    async fn serve(router: Router) -> Http {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        Http::new(&format!("http://{}/", addr), Client::new())
+    }
+
+    #[test]
+    fn test_segment() {
+        assert_eq!(segment("satoshi.btc"), "satoshi.btc");
+        assert_eq!(segment("a b/c?"), "a%20b%2Fc%3F");
+    }
+
+    #[test]
+    fn test_api_error_without_envelope() {
+        let err = api_error(409, "Domain already registered\n");
+        assert_eq!(err.status, 409);
+        assert_eq!(err.code, ErrorCode::Conflict);
+        assert_eq!(err.message, "Domain already registered");
+    }
+
+    #[tokio::test]
+    async fn test_send_decodes_success() {
+        let http =
+            serve(Router::new().route("/value", get(|| async { Json(vec![1, 2, 3]) }))).await;
+        let value: Vec<i32> = http.get("/value").await.unwrap();
+        assert_eq!(value, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_send_maps_error_envelope() {
+        let router = Router::new().route(
+            "/register",
+            get(|| async {
+                ApiError::new(ErrorCode::DomainTaken, "Domain is already registered")
+                    .with_details(serde_json::json!({ "name": "satoshi.btc" }))
+            }),
+        );
+        let http = serve(router).await;
+
+        let err = http
+            .get::<serde_json::Value>("/register")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(ErrorCode::DomainTaken));
+        assert!(!err.is_retryable());
+        match err {
+            ClientError::Api(api) => {
+                assert_eq!(api.status, 409);
+                assert_eq!(api.message, "Domain is already registered");
+                assert_eq!(api.details.unwrap()["name"], "satoshi.btc");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_maps_plain_error() {
+        let router = Router::new().route(
+            "/busy",
+            get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "Wallet is syncing") }),
+        );
+        let http = serve(router).await;
+
+        let err = http.get::<serde_json::Value>("/busy").await.unwrap_err();
+        assert_eq!(err.code(), Some(ErrorCode::Unavailable));
+        assert!(err.is_retryable());
+    }
+}
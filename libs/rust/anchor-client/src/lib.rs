@@ -0,0 +1,152 @@
+//! # ANCHOR Client
+//!
+//! Typed async clients for the ANCHOR wallet service and app backends, so Rust
+//! applications can use the stack without hand-written reqwest calls.
+//!
+//! ```rust,ignore
+//! use anchor_client::{AnchorClient, Endpoints, ErrorCode, PageParams};
+//!
+//! let anchor = AnchorClient::new(Endpoints::from_env());
+//!
+//! let domains = anchor.domains.list(PageParams::default(), Some("sat")).await?;
+//! let quorum = anchor.oracles.event_quorum(7).await?;
+//!
+//! match anchor.domains.register("satoshi.btc", &records, None).await {
+//!     Err(e) if e.code() == Some(ErrorCode::DomainTaken) => println!("taken"),
+//!     result => println!("{:?}", result?),
+//! }
+//! ```
+//!
+//! Failed calls return [`ClientError::Api`] carrying the service's
+//! [`ApiError`] envelope, including its machine-readable [`ErrorCode`].
+//!
+//! Code that only needs the wallet can take a [`WalletBackend`]; the
+//! `testing` feature adds an in-memory implementation for unit tests.
+
+mod domains;
+mod error;
+mod explorer;
+mod http;
+mod oracles;
+mod predictions;
+mod proofs;
+mod tokens;
+mod types;
+mod wallet;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub use anchor_api_error::{ApiError, ErrorCode};
+
+pub use domains::{
+    Availability, DnsRecord, DnsRecordInput, Domain, DomainStats, DomainSummary, DomainsClient,
+    HistoryEntry, Resolution,
+};
+pub use error::{ClientError, Result};
+pub use explorer::{
+    CarrierCounts, DecodedAnchor, DecodedPayload, DecodedTransaction, ExplorerClient,
+    ExplorerStats, Message, MessageAnchor, MessageProof, PopularThread, Thread, ThreadNode,
+};
+pub use oracles::{
+    Attestation, CategoryInfo, CreateDispute, CreateEvent, CreateSlash, CreatedEvent, Dispute,
+    EventQuorum, EventRequest, Oracle, OracleStats, OracleTx, OraclesClient, OutcomeSupport, Slash,
+};
+pub use predictions::{
+    ActionResponse, BetQuote, ClaimWinnings, CreateMarket, Market, MarketStats, Order,
+    OrderBookDepth, OrderBookLevel, OrderFill, PlaceBet, PlaceOrder, Position, PredictionsClient,
+    Winner,
+};
+pub use proofs::{
+    AddressProofs, Proof, ProofListItem, ProofStats, ProofsClient, Stamp, Validation,
+};
+pub use tokens::{
+    Allocation, DeployToken, Token, TokenAmount, TokenBalance, TokenHolder, TokenOperation,
+    TokenStats, TokenUtxo, TokensClient, TransferToken, WalletTokens,
+};
+pub use types::{CreatedTx, Health, Page, PageParams};
+pub use wallet::{
+    AnchorRef, Balance, CreateMessage, MessageTx, OutputSpec, Portfolio, PortfolioSummary, RawTx,
+    SourceError, TokenHolding, Utxo, WalletBackend, WalletClient,
+};
+
+use reqwest::Client;
+
+/// Base URLs of the services
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    pub explorer: String,
+    pub domains: String,
+    pub tokens: String,
+    pub proofs: String,
+    pub oracles: String,
+    pub predictions: String,
+    pub wallet: String,
+}
+
+impl Endpoints {
+    /// Ports published by the docker-compose stack on localhost
+    pub fn local() -> Self {
+        Self {
+            explorer: "http://localhost:3101".to_string(),
+            domains: "http://localhost:3401".to_string(),
+            tokens: "http://localhost:3601".to_string(),
+            proofs: "http://localhost:3501".to_string(),
+            oracles: "http://localhost:3701".to_string(),
+            predictions: "http://localhost:3801".to_string(),
+            wallet: "http://localhost:8001".to_string(),
+        }
+    }
+
+    /// `ANCHOR_<SERVICE>_URL` variables, falling back to [`Endpoints::local`]
+    pub fn from_env() -> Self {
+        let local = Self::local();
+        let var = |name: &str, default: String| std::env::var(name).unwrap_or(default);
+        Self {
+            explorer: var("ANCHOR_EXPLORER_URL", local.explorer),
+            domains: var("ANCHOR_DOMAINS_URL", local.domains),
+            tokens: var("ANCHOR_TOKENS_URL", local.tokens),
+            proofs: var("ANCHOR_PROOFS_URL", local.proofs),
+            oracles: var("ANCHOR_ORACLES_URL", local.oracles),
+            predictions: var("ANCHOR_PREDICTIONS_URL", local.predictions),
+            wallet: var("ANCHOR_WALLET_URL", local.wallet),
+        }
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::local()
+    }
+}
+
+/// All service clients over one connection pool
+#[derive(Debug, Clone)]
+pub struct AnchorClient {
+    pub explorer: ExplorerClient,
+    pub domains: DomainsClient,
+    pub tokens: TokensClient,
+    pub proofs: ProofsClient,
+    pub oracles: OraclesClient,
+    pub predictions: PredictionsClient,
+    pub wallet: WalletClient,
+}
+
+impl AnchorClient {
+    pub fn new(endpoints: Endpoints) -> Self {
+        Self::with_client(endpoints, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(endpoints: Endpoints, client: Client) -> Self {
+        Self {
+            explorer: ExplorerClient::with_client(&endpoints.explorer, client.clone()),
+            domains: DomainsClient::with_client(&endpoints.domains, client.clone()),
+            tokens: TokensClient::with_client(&endpoints.tokens, client.clone()),
+            proofs: ProofsClient::with_client(&endpoints.proofs, client.clone()),
+            oracles: OraclesClient::with_client(&endpoints.oracles, client.clone()),
+            predictions: PredictionsClient::with_client(&endpoints.predictions, client.clone()),
+            wallet: WalletClient::with_client(&endpoints.wallet, client),
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! Anchor Oracles API (attestations, k-of-n events, disputes)
+//!
+//! Public keys, event ids and outcomes are hex encoded. Timestamps are passed
+//! through as the strings the backend renders.
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::Health;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Oracle {
+    pub id: i32,
+    pub pubkey: String,
+    /// 0 = secp256k1 (Nostr), 1 = Ed25519 (Pubky)
+    pub key_type: i32,
+    pub key_type_name: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub categories: i32,
+    pub category_names: Vec<String>,
+    pub stake_sats: i64,
+    pub status: String,
+    pub registered_at: Option<i32>,
+    pub total_attestations: i32,
+    pub successful_attestations: i32,
+    pub disputed_attestations: i32,
+    pub reputation_score: f32,
+    pub created_at: String,
+    pub linked_identity_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attestation {
+    pub id: i32,
+    pub oracle_id: i32,
+    pub oracle_pubkey: Option<String>,
+    pub oracle_name: Option<String>,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub category: i32,
+    pub category_name: String,
+    pub event_id: String,
+    pub event_description: Option<String>,
+    pub outcome_data: String,
+    pub schnorr_signature: String,
+    pub status: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispute {
+    pub id: i32,
+    pub attestation_id: i32,
+    pub disputer_pubkey: String,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub reason: i32,
+    pub reason_name: String,
+    pub stake_sats: i64,
+    pub status: String,
+    pub resolution: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Slash {
+    pub id: i32,
+    pub dispute_id: Option<i32>,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub verdict: i32,
+    pub verdict_name: String,
+    pub amount_sats: i64,
+    pub status: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRequest {
+    pub id: i32,
+    pub event_id: String,
+    pub category: i32,
+    pub category_name: String,
+    pub description: String,
+    pub resolution_block: Option<i32>,
+    pub bounty_sats: i64,
+    pub status: String,
+    pub fulfilled_by: Option<i32>,
+    /// Agreeing attestations required (k)
+    pub threshold: i32,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeSupport {
+    pub outcome: String,
+    pub outcome_text: Option<String>,
+    pub count: i32,
+    pub oracles: Vec<String>,
+}
+
+/// Quorum status of a k-of-n event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventQuorum {
+    pub event_request_id: i32,
+    pub event_id: String,
+    pub threshold: i32,
+    /// Eligible oracle pubkeys (n); empty means any oracle may attest
+    pub eligible_oracles: Vec<String>,
+    pub attested_oracles: i32,
+    pub reached: bool,
+    pub conflicting: bool,
+    pub leading_outcome: Option<String>,
+    pub leading_outcome_text: Option<String>,
+    pub leading_count: i32,
+    pub quorum_height: Option<i32>,
+    pub outcomes: Vec<OutcomeSupport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleStats {
+    pub total_oracles: i64,
+    pub active_oracles: i64,
+    pub total_staked: i64,
+    pub avg_reputation: f64,
+    pub total_attestations: i64,
+    pub pending_events: i64,
+    pub active_disputes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryInfo {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub oracle_count: i64,
+    pub attestation_count: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateEvent {
+    pub category: i32,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution_block: Option<i32>,
+    pub bounty_sats: i64,
+    /// Defaults to 1 on the backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<i32>,
+    /// Restrict attestations to these oracle pubkeys
+    pub oracle_pubkeys: Vec<String>,
+}
+
+/// Event request as recorded by the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedEvent {
+    pub status: String,
+    pub id: i32,
+    pub event_id: String,
+    pub threshold: i32,
+    pub oracle_pubkeys: Vec<String>,
+}
+
+/// A bonded challenge against an attestation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateDispute {
+    pub attestation_id: i32,
+    pub disputer_pubkey: String,
+    /// Dispute reason (1-4)
+    pub reason: u8,
+    pub bond_sats: i64,
+    pub evidence: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<u64>,
+}
+
+/// Settlement of a dispute
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateSlash {
+    /// 1 = upheld (slash the oracle), 2 = rejected (forfeit the challenger bond)
+    pub verdict: u8,
+    /// Defaults to the full losing bond
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_sats: Option<i64>,
+    pub note: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<u64>,
+}
+
+/// Transaction built through the wallet service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleTx {
+    pub txid: String,
+    pub vout: u32,
+    pub hex: String,
+    /// Output holding the challenger bond (disputes only)
+    #[serde(default)]
+    pub bond_vout: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct ListQuery<'a> {
+    limit: i64,
+    offset: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'a str>,
+}
+
+impl<'a> ListQuery<'a> {
+    fn new(limit: i64, offset: i64, status: Option<&'a str>) -> Self {
+        Self {
+            limit,
+            offset,
+            status,
+        }
+    }
+}
+
+/// Client for the Anchor Oracles backend
+#[derive(Debug, Clone)]
+pub struct OraclesClient {
+    http: Http,
+}
+
+impl OraclesClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/health").await
+    }
+
+    pub async fn stats(&self) -> Result<OracleStats> {
+        self.http.get("/api/stats").await
+    }
+
+    /// Registered oracles; the backend caps `limit` at 100
+    pub async fn oracles(&self, limit: i64, offset: i64) -> Result<Vec<Oracle>> {
+        self.http
+            .get_query("/api/oracles", &ListQuery::new(limit, offset, None))
+            .await
+    }
+
+    pub async fn oracle(&self, pubkey: &str) -> Result<Oracle> {
+        self.http
+            .get(&format!("/api/oracles/{}", segment(pubkey)))
+            .await
+    }
+
+    /// Oracles registered from any of the given addresses
+    pub async fn by_addresses(&self, addresses: &[String]) -> Result<Vec<Oracle>> {
+        self.http
+            .post(
+                "/api/oracles/by-addresses",
+                &serde_json::json!({ "addresses": addresses }),
+            )
+            .await
+    }
+
+    pub async fn oracle_attestations(
+        &self,
+        pubkey: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Attestation>> {
+        self.http
+            .get_query(
+                &format!("/api/oracles/{}/attestations", segment(pubkey)),
+                &ListQuery::new(limit, offset, None),
+            )
+            .await
+    }
+
+    pub async fn attestations(&self, limit: i64, offset: i64) -> Result<Vec<Attestation>> {
+        self.http
+            .get_query("/api/attestations", &ListQuery::new(limit, offset, None))
+            .await
+    }
+
+    /// Event requests, optionally filtered by status (`pending`, `fulfilled`, ...)
+    pub async fn events(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<EventRequest>> {
+        self.http
+            .get_query("/api/events", &ListQuery::new(limit, offset, status))
+            .await
+    }
+
+    pub async fn event(&self, id: i32) -> Result<EventRequest> {
+        self.http.get(&format!("/api/events/{}", id)).await
+    }
+
+    pub async fn event_attestations(&self, id: i32) -> Result<Vec<Attestation>> {
+        self.http
+            .get(&format!("/api/events/{}/attestations", id))
+            .await
+    }
+
+    pub async fn event_quorum(&self, id: i32) -> Result<EventQuorum> {
+        self.http.get(&format!("/api/events/{}/quorum", id)).await
+    }
+
+    pub async fn request_event(&self, request: &CreateEvent) -> Result<CreatedEvent> {
+        self.http.post("/api/events/request", request).await
+    }
+
+    pub async fn disputes(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Dispute>> {
+        self.http
+            .get_query("/api/disputes", &ListQuery::new(limit, offset, status))
+            .await
+    }
+
+    pub async fn create_dispute(&self, request: &CreateDispute) -> Result<OracleTx> {
+        self.http.post("/api/disputes/create", request).await
+    }
+
+    pub async fn slash(&self, dispute_id: i32, request: &CreateSlash) -> Result<OracleTx> {
+        self.http
+            .post(&format!("/api/disputes/{}/slash", dispute_id), request)
+            .await
+    }
+
+    pub async fn slashes(&self, limit: i64, offset: i64) -> Result<Vec<Slash>> {
+        self.http
+            .get_query("/api/slashes", &ListQuery::new(limit, offset, None))
+            .await
+    }
+
+    pub async fn categories(&self) -> Result<Vec<CategoryInfo>> {
+        self.http.get("/api/categories").await
+    }
+}
@@ -0,0 +1,420 @@
+//! Anchor Predictions API (binary, categorical and scalar prediction markets)
+//!
+//! Market ids and pubkeys are hex encoded. Write endpoints answer with a
+//! `status`/`message` object whose remaining fields depend on the action, so
+//! they are returned as [`ActionResponse`].
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::Health;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Market {
+    pub id: i32,
+    pub market_id: String,
+    pub question: String,
+    pub description: Option<String>,
+    pub resolution_block: i32,
+    pub oracle_pubkey: String,
+    pub creator_pubkey: String,
+    pub status: String,
+    pub resolution: Option<i16>,
+    pub resolution_name: String,
+    /// `binary`, `categorical` or `scalar`
+    #[serde(default)]
+    pub market_type: String,
+    /// Outcome labels by index
+    #[serde(default)]
+    pub outcomes: Vec<String>,
+    pub yes_pool: i64,
+    pub no_pool: i64,
+    pub yes_price: f64,
+    pub no_price: f64,
+    /// AMM pool of every outcome, by index
+    #[serde(default)]
+    pub pools: Vec<i64>,
+    /// AMM price of every outcome, by index
+    #[serde(default)]
+    pub prices: Vec<f64>,
+    #[serde(default)]
+    pub scalar_lower: Option<i64>,
+    #[serde(default)]
+    pub scalar_upper: Option<i64>,
+    #[serde(default)]
+    pub scalar_unit: Option<String>,
+    /// Value a scalar market resolved at
+    #[serde(default)]
+    pub resolved_value: Option<i64>,
+    pub total_volume_sats: i64,
+    pub total_yes_sats: i64,
+    pub total_no_sats: i64,
+    pub position_count: i32,
+    /// Whether the market trades through the limit order book instead of the AMM
+    #[serde(default)]
+    pub order_book: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub id: i32,
+    pub market_id: String,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub user_pubkey: String,
+    pub outcome: i16,
+    pub outcome_name: String,
+    pub amount_sats: i64,
+    pub shares: i64,
+    pub avg_price: f32,
+    pub is_winner: bool,
+    pub payout_sats: i64,
+    pub claimed: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub total_markets: i32,
+    pub active_markets: i32,
+    pub resolved_markets: i32,
+    pub total_positions: i32,
+    pub total_volume_sats: i64,
+    pub total_payouts_sats: i64,
+    pub largest_market_sats: i64,
+}
+
+/// AMM price for a bet before it is placed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BetQuote {
+    pub outcome: i16,
+    pub outcome_name: String,
+    pub amount_sats: i64,
+    pub shares_out: i64,
+    pub avg_price: f64,
+    pub price_impact: f64,
+    pub new_yes_price: f64,
+    pub new_no_price: f64,
+    /// Price of every outcome after the bet, by index
+    #[serde(default)]
+    pub new_prices: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Winner {
+    pub position_id: i32,
+    pub user_pubkey: String,
+    pub outcome: i16,
+    pub outcome_name: String,
+    pub amount_sats: i64,
+    pub shares: i64,
+    pub payout_sats: i64,
+    pub claimed: bool,
+}
+
+/// Limit order in an order-book market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub id: i32,
+    pub market_id: String,
+    pub txid: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub user_pubkey: String,
+    pub outcome: i16,
+    pub outcome_name: String,
+    pub price_bps: i32,
+    pub shares: i64,
+    pub filled_shares: i64,
+    pub status: String,
+    pub expiry_block: i32,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderFill {
+    pub id: i32,
+    pub market_id: String,
+    pub maker_order_id: i32,
+    pub taker_order_id: i32,
+    pub shares: i64,
+    pub maker_price_bps: i32,
+    pub maker_cost_sats: i64,
+    pub taker_cost_sats: i64,
+    pub status: String,
+    pub settlement_txid: Option<String>,
+    pub settled_at_block: Option<i32>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookLevel {
+    pub price_bps: i64,
+    pub shares: i64,
+}
+
+/// Aggregated open bids on both sides of a market
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookDepth {
+    pub market_id: String,
+    pub yes_bids: Vec<OrderBookLevel>,
+    pub no_bids: Vec<OrderBookLevel>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateMarket {
+    pub question: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub resolution_block: i32,
+    pub oracle_pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_liquidity_sats: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_book: Option<bool>,
+    /// `binary` (default), `categorical` or `scalar`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_type: Option<String>,
+    /// Outcome labels of a categorical market
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcomes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scalar_lower: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scalar_upper: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scalar_unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaceBet {
+    /// 0 = NO/SHORT, 1 = YES/LONG, or a categorical outcome index
+    pub outcome: i16,
+    pub amount_sats: i64,
+    pub user_pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bet_address: Option<String>,
+    /// Slippage protection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_shares: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaceOrder {
+    /// 0 = NO, 1 = YES
+    pub outcome: i16,
+    pub price_bps: i64,
+    pub shares: i64,
+    pub user_pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry_block: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ticket_address: Option<String>,
+}
+
+/// Claim of a winning position, signed by the position owner
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaimWinnings {
+    pub position_id: i32,
+    pub payout_address: String,
+    pub user_pubkey: String,
+    pub signature: String,
+}
+
+/// Outcome of a write action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResponse {
+    pub status: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Action-specific fields (`market_id`, `txid`, `shares`, ...)
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct ListQuery<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pubkey: Option<&'a str>,
+    limit: i32,
+}
+
+impl<'a> ListQuery<'a> {
+    fn new(limit: i32) -> Self {
+        Self {
+            status: None,
+            pubkey: None,
+            limit,
+        }
+    }
+}
+
+/// Client for the Anchor Predictions backend
+#[derive(Debug, Clone)]
+pub struct PredictionsClient {
+    http: Http,
+}
+
+impl PredictionsClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/health").await
+    }
+
+    pub async fn stats(&self) -> Result<MarketStats> {
+        self.http.get("/api/stats").await
+    }
+
+    /// Markets, optionally filtered by status (`active`, `resolved`, ...)
+    pub async fn markets(&self, status: Option<&str>, limit: i32) -> Result<Vec<Market>> {
+        let query = ListQuery {
+            status,
+            ..ListQuery::new(limit)
+        };
+        self.http.get_query("/api/markets", &query).await
+    }
+
+    pub async fn market(&self, market_id: &str) -> Result<Market> {
+        self.http
+            .get(&format!("/api/markets/{}", segment(market_id)))
+            .await
+    }
+
+    pub async fn create_market(&self, request: &CreateMarket) -> Result<ActionResponse> {
+        self.http.post("/api/markets/create", request).await
+    }
+
+    pub async fn positions(&self, market_id: &str, limit: i32) -> Result<Vec<Position>> {
+        self.http
+            .get_query(
+                &format!("/api/markets/{}/positions", segment(market_id)),
+                &ListQuery::new(limit),
+            )
+            .await
+    }
+
+    pub async fn quote(&self, market_id: &str, bet: &PlaceBet) -> Result<BetQuote> {
+        self.http
+            .post(&format!("/api/markets/{}/quote", segment(market_id)), bet)
+            .await
+    }
+
+    pub async fn bet(&self, market_id: &str, bet: &PlaceBet) -> Result<ActionResponse> {
+        self.http
+            .post(&format!("/api/markets/{}/bet", segment(market_id)), bet)
+            .await
+    }
+
+    pub async fn resolution(&self, market_id: &str) -> Result<serde_json::Value> {
+        self.http
+            .get(&format!("/api/markets/{}/resolution", segment(market_id)))
+            .await
+    }
+
+    pub async fn winners(&self, market_id: &str) -> Result<Vec<Winner>> {
+        self.http
+            .get(&format!("/api/markets/{}/winners", segment(market_id)))
+            .await
+    }
+
+    pub async fn claim(&self, market_id: &str, claim: &ClaimWinnings) -> Result<ActionResponse> {
+        self.http
+            .post(&format!("/api/markets/{}/claim", segment(market_id)), claim)
+            .await
+    }
+
+    /// Orders of an order-book market, optionally filtered by status
+    pub async fn orders(
+        &self,
+        market_id: &str,
+        status: Option<&str>,
+        limit: i32,
+    ) -> Result<Vec<Order>> {
+        let query = ListQuery {
+            status,
+            ..ListQuery::new(limit)
+        };
+        self.http
+            .get_query(
+                &format!("/api/markets/{}/orders", segment(market_id)),
+                &query,
+            )
+            .await
+    }
+
+    pub async fn place_order(&self, market_id: &str, order: &PlaceOrder) -> Result<ActionResponse> {
+        self.http
+            .post(
+                &format!("/api/markets/{}/orders", segment(market_id)),
+                order,
+            )
+            .await
+    }
+
+    /// Cancel an order by spending its ticket output
+    pub async fn cancel_order(&self, order_id: i32, ticket_vout: u32) -> Result<ActionResponse> {
+        self.http
+            .post(
+                &format!("/api/orders/{}/cancel", order_id),
+                &serde_json::json!({ "ticket_vout": ticket_vout }),
+            )
+            .await
+    }
+
+    pub async fn orderbook(&self, market_id: &str) -> Result<OrderBookDepth> {
+        self.http
+            .get(&format!("/api/markets/{}/orderbook", segment(market_id)))
+            .await
+    }
+
+    pub async fn fills(&self, market_id: &str, limit: i32) -> Result<Vec<OrderFill>> {
+        self.http
+            .get_query(
+                &format!("/api/markets/{}/fills", segment(market_id)),
+                &ListQuery::new(limit),
+            )
+            .await
+    }
+
+    /// Positions held by one user pubkey
+    pub async fn my_positions(&self, pubkey: &str, limit: i32) -> Result<Vec<Position>> {
+        let query = ListQuery {
+            pubkey: Some(pubkey),
+            ..ListQuery::new(limit)
+        };
+        self.http.get_query("/api/my/positions", &query).await
+    }
+
+    pub async fn all_positions(&self, limit: i32) -> Result<Vec<Position>> {
+        self.http
+            .get_query("/api/positions", &ListQuery::new(limit))
+            .await
+    }
+
+    /// Resolved markets, most recent first
+    pub async fn history(&self, limit: i32) -> Result<Vec<Market>> {
+        self.http
+            .get_query("/api/history", &ListQuery::new(limit))
+            .await
+    }
+}
@@ -0,0 +1,237 @@
+//! Anchor Proofs API (proof of existence)
+//!
+//! Hashes are hex encoded; `hash_algo` is `"sha256"` or `"sha512"`.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::{CreatedTx, Health, Page, PageParams};
+
+/// Proof with full details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proof {
+    pub id: i32,
+    pub hash_algo: i16,
+    pub hash_algo_name: String,
+    pub file_hash: String,
+    pub filename: Option<String>,
+    pub mime_type: Option<String>,
+    pub file_size: Option<i64>,
+    pub description: Option<String>,
+    pub txid: String,
+    pub txid_prefix: String,
+    pub vout: i32,
+    pub block_height: Option<i32>,
+    pub is_revoked: bool,
+    pub revoked_txid: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Proof as listed, without description or revocation details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofListItem {
+    pub id: i32,
+    pub hash_algo: i16,
+    pub hash_algo_name: String,
+    pub file_hash: String,
+    pub filename: Option<String>,
+    pub mime_type: Option<String>,
+    pub file_size: Option<i64>,
+    pub txid: String,
+    pub txid_prefix: String,
+    pub block_height: Option<i32>,
+    pub is_revoked: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStats {
+    pub total_proofs: i64,
+    pub active_proofs: i64,
+    pub revoked_proofs: i64,
+    pub sha256_proofs: i64,
+    pub sha512_proofs: i64,
+    pub total_transactions: i64,
+    pub last_block_height: Option<i32>,
+    pub last_update: Option<DateTime<Utc>>,
+    pub total_file_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Validation {
+    pub is_valid: bool,
+    pub proof: Option<Proof>,
+}
+
+/// Proofs stamped by a set of addresses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressProofs {
+    pub proofs: Vec<ProofListItem>,
+    pub total_proofs: i64,
+    pub unique_transactions: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+/// A file hash to stamp, with optional metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stamp {
+    pub hash_algo: String,
+    pub file_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+}
+
+impl Stamp {
+    pub fn sha256(file_hash: impl Into<String>) -> Self {
+        Self {
+            hash_algo: "sha256".to_string(),
+            file_hash: file_hash.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ListQuery<'a> {
+    page: i32,
+    per_page: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<&'a str>,
+    include_revoked: bool,
+}
+
+#[derive(Serialize)]
+struct HashRequest<'a> {
+    hash_algo: &'a str,
+    file_hash: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    carrier: Option<u8>,
+}
+
+/// Client for the Anchor Proofs backend
+#[derive(Debug, Clone)]
+pub struct ProofsClient {
+    http: Http,
+}
+
+impl ProofsClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/api/health").await
+    }
+
+    pub async fn stats(&self) -> Result<ProofStats> {
+        self.http.get("/api/stats").await
+    }
+
+    pub async fn list(
+        &self,
+        page: PageParams,
+        search: Option<&str>,
+        include_revoked: bool,
+    ) -> Result<Page<ProofListItem>> {
+        let query = ListQuery {
+            page: page.page,
+            per_page: page.per_page,
+            search,
+            include_revoked,
+        };
+        self.http.get_query("/api/proofs", &query).await
+    }
+
+    /// Proofs stamped by the backend's own wallet
+    pub async fn my_proofs(&self, page: PageParams) -> Result<AddressProofs> {
+        self.http
+            .get_query(
+                "/api/proofs/my",
+                &[("page", page.page), ("per_page", page.per_page)],
+            )
+            .await
+    }
+
+    pub async fn by_addresses(&self, addresses: &[String], per_page: i32) -> Result<AddressProofs> {
+        self.http
+            .post(
+                "/api/proofs/by-addresses",
+                &serde_json::json!({ "addresses": addresses, "per_page": per_page }),
+            )
+            .await
+    }
+
+    /// Look up a proof by file hash, optionally restricted to one algorithm
+    pub async fn proof(&self, file_hash: &str, hash_algo: Option<&str>) -> Result<Proof> {
+        let path = format!("/api/proof/{}", segment(file_hash));
+        match hash_algo {
+            Some(algo) => self.http.get_query(&path, &[("algo", algo)]).await,
+            None => self.http.get(&path).await,
+        }
+    }
+
+    pub async fn proof_by_id(&self, id: i32) -> Result<Proof> {
+        self.http.get(&format!("/api/proof/id/{}", id)).await
+    }
+
+    /// Check whether a file hash has an active proof
+    pub async fn validate(&self, hash_algo: &str, file_hash: &str) -> Result<Validation> {
+        let request = HashRequest {
+            hash_algo,
+            file_hash,
+            carrier: None,
+        };
+        self.http.post("/api/validate", &request).await
+    }
+
+    pub async fn stamp(&self, stamp: &Stamp) -> Result<CreatedTx> {
+        self.http.post("/api/stamp", stamp).await
+    }
+
+    /// Stamp several hashes in one transaction
+    pub async fn stamp_batch(&self, entries: &[Stamp], carrier: Option<u8>) -> Result<CreatedTx> {
+        self.http
+            .post(
+                "/api/stamp/batch",
+                &serde_json::json!({ "entries": entries, "carrier": carrier }),
+            )
+            .await
+    }
+
+    pub async fn revoke(
+        &self,
+        hash_algo: &str,
+        file_hash: &str,
+        carrier: Option<u8>,
+    ) -> Result<CreatedTx> {
+        let request = HashRequest {
+            hash_algo,
+            file_hash,
+            carrier,
+        };
+        self.http.post("/api/revoke", &request).await
+    }
+}
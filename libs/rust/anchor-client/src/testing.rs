@@ -0,0 +1,383 @@
+//! In-memory wallet for unit tests
+//!
+//! [`FakeWallet`] implements [`WalletBackend`] without bitcoind or HTTP, so
+//! app backend handlers can be tested against a wallet whose state the test
+//! controls:
+//!
+//! ```rust,ignore
+//! use anchor_client::testing::FakeWallet;
+//!
+//! let wallet = Arc::new(FakeWallet::funded(100_000));
+//! let tx = wallet.create_message(&CreateMessage::text(1, "hi")).await?;
+//! assert_eq!(tx.txid, FakeWallet::txid(2));
+//! assert_eq!(wallet.confirmations(&tx.txid), Some(0));
+//! wallet.mine(1);
+//! assert_eq!(wallet.confirmations(&tx.txid), Some(1));
+//! ```
+//!
+//! Txids are the sequence number of the transaction in hex (the first one is
+//! [`FakeWallet::txid`]`(1)`), so tests can predict them. Transactions stay
+//! unconfirmed until [`FakeWallet::mine`] is called, unless auto-confirm is
+//! on. Every message costs [`FAKE_FEE_SATS`] from the largest UTXO, with the
+//! rest coming back as unconfirmed change.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+use anchor_api_error::{ApiError, ErrorCode};
+use async_trait::async_trait;
+
+use crate::error::{ClientError, Result};
+use crate::wallet::{Balance, CreateMessage, MessageTx, RawTx, Utxo, WalletBackend};
+
+/// Fee taken by every message and broadcast, in satoshis
+pub const FAKE_FEE_SATS: u64 = 1_000;
+
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+/// A transaction the fake wallet created or broadcast
+#[derive(Debug, Clone)]
+pub struct FakeTx {
+    pub txid: String,
+    pub hex: String,
+    /// The message it carries; `None` for raw broadcasts
+    pub message: Option<CreateMessage>,
+    /// Block height it confirmed at
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct FakeUtxo {
+    txid: String,
+    vout: u32,
+    sats: u64,
+    address: String,
+    height: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    height: u32,
+    transactions: Vec<FakeTx>,
+    utxos: Vec<FakeUtxo>,
+    addresses: Vec<String>,
+    auto_confirm: bool,
+    failures: VecDeque<ApiError>,
+}
+
+impl State {
+    fn next_txid(&self) -> String {
+        FakeWallet::txid(self.transactions.len() as u64 + 1)
+    }
+
+    fn confirmations(&self, height: Option<u32>) -> u32 {
+        height.map_or(0, |height| self.height.saturating_sub(height) + 1)
+    }
+
+    fn address(&mut self) -> String {
+        let address = format!("bcrt1qfake{:032x}", self.addresses.len() + 1);
+        self.addresses.push(address.clone());
+        address
+    }
+
+    /// Record a transaction paying `FAKE_FEE_SATS` from the largest UTXO
+    fn spend(&mut self, hex: String, message: Option<CreateMessage>) -> Result<String> {
+        let largest = self
+            .utxos
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, utxo)| utxo.sats)
+            .map(|(index, utxo)| (index, utxo.sats));
+        let (index, sats) = match largest {
+            Some((index, sats)) if sats >= FAKE_FEE_SATS => (index, sats),
+            _ => {
+                return Err(ClientError::Api(ApiError::new(
+                    ErrorCode::InsufficientFunds,
+                    format!("Insufficient funds: need {} sats", FAKE_FEE_SATS),
+                )))
+            }
+        };
+        self.utxos.remove(index);
+
+        let txid = self.next_txid();
+        let height = self.auto_confirm.then(|| {
+            self.height += 1;
+            self.height
+        });
+        if sats > FAKE_FEE_SATS {
+            let address = self.address();
+            self.utxos.push(FakeUtxo {
+                txid: txid.clone(),
+                vout: 1,
+                sats: sats - FAKE_FEE_SATS,
+                address,
+                height,
+            });
+        }
+        self.transactions.push(FakeTx {
+            txid: txid.clone(),
+            hex,
+            message,
+            height,
+        });
+        Ok(txid)
+    }
+}
+
+/// In-memory [`WalletBackend`] with deterministic txids
+#[derive(Debug, Default)]
+pub struct FakeWallet {
+    state: Mutex<State>,
+}
+
+impl FakeWallet {
+    /// A wallet holding one confirmed UTXO of `sats`
+    pub fn funded(sats: u64) -> Self {
+        let wallet = Self::default();
+        wallet.fund(sats);
+        wallet
+    }
+
+    /// Txid of the `n`th transaction (1-based) the fake records
+    pub fn txid(n: u64) -> String {
+        format!("{:064x}", n)
+    }
+
+    /// Receive a confirmed UTXO of `sats`; returns its txid
+    pub fn fund(&self, sats: u64) -> String {
+        let mut state = self.state();
+        state.height += 1;
+        let txid = state.next_txid();
+        let address = state.address();
+        let height = Some(state.height);
+        state.utxos.push(FakeUtxo {
+            txid: txid.clone(),
+            vout: 0,
+            sats,
+            address,
+            height,
+        });
+        state.transactions.push(FakeTx {
+            txid: txid.clone(),
+            hex: String::new(),
+            message: None,
+            height,
+        });
+        txid
+    }
+
+    /// Mine `blocks` blocks, confirming everything pending in the first
+    pub fn mine(&self, blocks: u32) {
+        if blocks == 0 {
+            return;
+        }
+        let mut state = self.state();
+        let height = state.height + 1;
+        for tx in state
+            .transactions
+            .iter_mut()
+            .filter(|tx| tx.height.is_none())
+        {
+            tx.height = Some(height);
+        }
+        for utxo in state.utxos.iter_mut().filter(|utxo| utxo.height.is_none()) {
+            utxo.height = Some(height);
+        }
+        state.height += blocks;
+    }
+
+    /// Confirm every new transaction in a block of its own right away
+    pub fn set_auto_confirm(&self, auto_confirm: bool) {
+        self.state().auto_confirm = auto_confirm;
+    }
+
+    /// Make the next call fail with `error`; queued errors are returned in
+    /// order, one per call
+    pub fn fail_next(&self, error: ApiError) {
+        self.state().failures.push_back(error);
+    }
+
+    /// Current block height
+    pub fn height(&self) -> u32 {
+        self.state().height
+    }
+
+    /// Confirmations of a transaction, or `None` if the wallet doesn't know it
+    pub fn confirmations(&self, txid: &str) -> Option<u32> {
+        let state = self.state();
+        state
+            .transactions
+            .iter()
+            .find(|tx| tx.txid == txid)
+            .map(|tx| state.confirmations(tx.height))
+    }
+
+    /// Every transaction recorded so far, oldest first
+    pub fn transactions(&self) -> Vec<FakeTx> {
+        self.state().transactions.clone()
+    }
+
+    /// Messages passed to `create_message`, oldest first
+    pub fn messages(&self) -> Vec<CreateMessage> {
+        self.state()
+            .transactions
+            .iter()
+            .filter_map(|tx| tx.message.clone())
+            .collect()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Take the next queued failure, if any
+    fn check(&self) -> Result<MutexGuard<'_, State>> {
+        let mut state = self.state();
+        match state.failures.pop_front() {
+            Some(error) => Err(ClientError::Api(error)),
+            None => Ok(state),
+        }
+    }
+}
+
+#[async_trait]
+impl WalletBackend for FakeWallet {
+    async fn balance(&self) -> Result<Balance> {
+        let state = self.check()?;
+        let (mut confirmed, mut unconfirmed) = (0, 0);
+        for utxo in &state.utxos {
+            match utxo.height {
+                Some(_) => confirmed += utxo.sats,
+                None => unconfirmed += utxo.sats,
+            }
+        }
+        let btc = |sats: u64| sats as f64 / SATS_PER_BTC;
+        Ok(Balance {
+            confirmed: btc(confirmed),
+            unconfirmed: btc(unconfirmed),
+            immature: 0.0,
+            locked: 0.0,
+            spendable: btc(confirmed),
+            total: btc(confirmed + unconfirmed),
+        })
+    }
+
+    async fn new_address(&self) -> Result<String> {
+        Ok(self.check()?.address())
+    }
+
+    async fn addresses(&self) -> Result<Vec<String>> {
+        Ok(self.check()?.addresses.clone())
+    }
+
+    async fn utxos(&self) -> Result<Vec<Utxo>> {
+        let state = self.check()?;
+        Ok(state
+            .utxos
+            .iter()
+            .map(|utxo| Utxo {
+                txid: utxo.txid.clone(),
+                vout: utxo.vout,
+                amount: utxo.sats as f64 / SATS_PER_BTC,
+                confirmations: state.confirmations(utxo.height),
+                address: Some(utxo.address.clone()),
+            })
+            .collect())
+    }
+
+    async fn create_message(&self, message: &CreateMessage) -> Result<MessageTx> {
+        let mut state = self.check()?;
+        let hex = if message.body_is_hex {
+            message.body.clone()
+        } else {
+            message.body.bytes().map(|b| format!("{:02x}", b)).collect()
+        };
+        let txid = state.spend(hex.clone(), Some(message.clone()))?;
+        Ok(MessageTx {
+            txid,
+            vout: 0,
+            hex,
+            carrier: message.carrier.unwrap_or(0),
+            carrier_name: "op_return".to_string(),
+            psbt: None,
+            payjoin: message.payjoin.as_ref().map(|_| false),
+        })
+    }
+
+    async fn broadcast(&self, hex: &str) -> Result<String> {
+        self.check()?.spend(hex.to_string(), None)
+    }
+
+    async fn raw_tx(&self, txid: &str) -> Result<RawTx> {
+        let state = self.check()?;
+        let tx = state
+            .transactions
+            .iter()
+            .find(|tx| tx.txid == txid)
+            .ok_or_else(|| {
+                ClientError::Api(ApiError::not_found(format!(
+                    "Transaction {} not found",
+                    txid
+                )))
+            })?;
+        Ok(RawTx {
+            txid: tx.txid.clone(),
+            hex: tx.hex.clone(),
+            decoded: None,
+            fee_sats: tx.message.as_ref().map(|_| FAKE_FEE_SATS),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_messages_confirm_when_mined() {
+        let wallet = FakeWallet::funded(100_000);
+        let tx = wallet
+            .create_message(&CreateMessage::text(1, "hi"))
+            .await
+            .unwrap();
+        assert_eq!(tx.txid, FakeWallet::txid(2));
+        assert_eq!(tx.hex, "6869");
+        assert_eq!(wallet.confirmations(&tx.txid), Some(0));
+
+        let balance = wallet.balance().await.unwrap();
+        assert_eq!(balance.confirmed, 0.0);
+        assert_eq!(balance.unconfirmed, 0.00099);
+
+        wallet.mine(2);
+        assert_eq!(wallet.confirmations(&tx.txid), Some(2));
+        assert_eq!(wallet.balance().await.unwrap().confirmed, 0.00099);
+        assert_eq!(wallet.messages()[0].body, "hi");
+    }
+
+    #[tokio::test]
+    async fn test_auto_confirm() {
+        let wallet = FakeWallet::funded(100_000);
+        wallet.set_auto_confirm(true);
+        let txid = wallet.broadcast("00").await.unwrap();
+        assert_eq!(wallet.confirmations(&txid), Some(1));
+        assert_eq!(wallet.height(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let wallet = FakeWallet::default();
+        let err = wallet
+            .create_message(&CreateMessage::text(1, "hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some(ErrorCode::InsufficientFunds));
+
+        wallet.fail_next(ApiError::new(ErrorCode::Unavailable, "down"));
+        let err = wallet.balance().await.unwrap_err();
+        assert_eq!(err.code(), Some(ErrorCode::Unavailable));
+        assert!(wallet.balance().await.is_ok());
+
+        let err = wallet.raw_tx(&FakeWallet::txid(9)).await.unwrap_err();
+        assert_eq!(err.code(), Some(ErrorCode::NotFound));
+    }
+}
@@ -0,0 +1,292 @@
+//! Anchor Tokens API (fungible tokens)
+//!
+//! Amounts are decimal strings in base units, as returned by the backend, so
+//! supplies above `u64::MAX` survive the round trip.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::{CreatedTx, Health, Page, PageParams};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Token {
+    pub id: i32,
+    pub ticker: String,
+    pub deploy_txid: String,
+    pub deploy_vout: i32,
+    pub decimals: i16,
+    pub max_supply: String,
+    pub mint_limit: Option<String>,
+    pub minted_supply: String,
+    pub burned_supply: String,
+    pub circulating_supply: String,
+    pub holder_count: i32,
+    pub tx_count: i32,
+    pub flags: i16,
+    pub is_open_mint: bool,
+    pub is_burnable: bool,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An unspent output carrying a token balance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUtxo {
+    pub id: i32,
+    pub token_id: i32,
+    pub ticker: String,
+    pub txid: String,
+    pub vout: i32,
+    pub amount: String,
+    pub decimals: i16,
+    pub owner_address: Option<String>,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub is_spent: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub token_id: i32,
+    pub ticker: String,
+    pub decimals: i16,
+    pub balance: String,
+    pub utxo_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenHolder {
+    pub address: String,
+    pub balance: String,
+    pub percentage: f64,
+    pub utxo_count: i32,
+    #[serde(default)]
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub vout: Option<i32>,
+}
+
+/// A deploy, mint, transfer or burn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenOperation {
+    pub id: i32,
+    pub token_id: i32,
+    pub ticker: String,
+    pub operation: String,
+    pub txid: String,
+    pub vout: i32,
+    pub amount: Option<String>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub block_height: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenStats {
+    pub total_tokens: i64,
+    pub total_holders: i64,
+    pub total_operations: i64,
+    pub last_block_height: Option<i32>,
+}
+
+/// Token balances and UTXOs held by the backend's wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletTokens {
+    pub balances: Vec<TokenBalance>,
+    pub utxos: Vec<TokenUtxo>,
+    pub total_utxos: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployToken {
+    pub ticker: String,
+    pub decimals: u8,
+    pub max_supply: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mint_limit: Option<String>,
+    pub open_mint: bool,
+    pub burnable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<f64>,
+}
+
+/// Mint or burn `amount` of `ticker`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenAmount {
+    pub ticker: String,
+    pub amount: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferToken {
+    pub ticker: String,
+    pub allocations: Vec<Allocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Allocation {
+    pub address: String,
+    pub amount: String,
+}
+
+#[derive(Serialize)]
+struct ListQuery<'a> {
+    page: i32,
+    per_page: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owned_by: Option<&'a str>,
+}
+
+impl<'a> ListQuery<'a> {
+    fn new(page: PageParams, search: Option<&'a str>) -> Self {
+        Self {
+            page: page.page,
+            per_page: page.per_page,
+            search,
+            owned_by: None,
+        }
+    }
+}
+
+/// Client for the Anchor Tokens backend
+#[derive(Debug, Clone)]
+pub struct TokensClient {
+    http: Http,
+}
+
+impl TokensClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/health").await
+    }
+
+    pub async fn stats(&self) -> Result<TokenStats> {
+        self.http.get("/stats").await
+    }
+
+    pub async fn list(&self, page: PageParams, search: Option<&str>) -> Result<Page<Token>> {
+        self.http
+            .get_query("/tokens", &ListQuery::new(page, search))
+            .await
+    }
+
+    /// Tokens currently held by an address or hex output script
+    pub async fn owned_by(&self, owner: &str, page: PageParams) -> Result<Page<Token>> {
+        let query = ListQuery {
+            owned_by: Some(owner),
+            ..ListQuery::new(page, None)
+        };
+        self.http.get_query("/tokens", &query).await
+    }
+
+    pub async fn token(&self, ticker: &str) -> Result<Token> {
+        self.http.get(&format!("/tokens/{}", segment(ticker))).await
+    }
+
+    pub async fn holders(&self, ticker: &str, page: PageParams) -> Result<Page<TokenHolder>> {
+        self.http
+            .get_query(
+                &format!("/tokens/{}/holders", segment(ticker)),
+                &ListQuery::new(page, None),
+            )
+            .await
+    }
+
+    pub async fn history(&self, ticker: &str, page: PageParams) -> Result<Page<TokenOperation>> {
+        self.http
+            .get_query(
+                &format!("/tokens/{}/history", segment(ticker)),
+                &ListQuery::new(page, None),
+            )
+            .await
+    }
+
+    pub async fn balances(&self, address: &str) -> Result<Vec<TokenBalance>> {
+        self.http
+            .get(&format!("/address/{}/balances", segment(address)))
+            .await
+    }
+
+    /// Unspent token outputs of an address, optionally for one ticker
+    pub async fn utxos(&self, address: &str, ticker: Option<&str>) -> Result<Vec<TokenUtxo>> {
+        let path = format!("/address/{}/utxos", segment(address));
+        match ticker {
+            Some(ticker) => self.http.get_query(&path, &[("ticker", ticker)]).await,
+            None => self.http.get(&path).await,
+        }
+    }
+
+    pub async fn address_history(
+        &self,
+        address: &str,
+        page: PageParams,
+    ) -> Result<Page<TokenOperation>> {
+        self.http
+            .get_query(
+                &format!("/address/{}/history", segment(address)),
+                &ListQuery::new(page, None),
+            )
+            .await
+    }
+
+    /// Token balances held by the backend's own wallet
+    pub async fn wallet_tokens(&self) -> Result<WalletTokens> {
+        self.http.get("/wallet/tokens").await
+    }
+
+    pub async fn deploy(&self, request: &DeployToken) -> Result<CreatedTx> {
+        self.http.post("/tx/deploy", request).await
+    }
+
+    pub async fn mint(&self, request: &TokenAmount) -> Result<CreatedTx> {
+        self.http.post("/tx/mint", request).await
+    }
+
+    pub async fn transfer(&self, request: &TransferToken) -> Result<CreatedTx> {
+        self.http.post("/tx/transfer", request).await
+    }
+
+    pub async fn burn(&self, request: &TokenAmount) -> Result<CreatedTx> {
+        self.http.post("/tx/burn", request).await
+    }
+}
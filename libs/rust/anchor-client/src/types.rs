@@ -0,0 +1,82 @@
+//! Types shared by several services
+
+use serde::{Deserialize, Serialize};
+
+/// One page of a paginated list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub total: i64,
+    pub page: i32,
+    #[serde(alias = "perPage")]
+    pub per_page: i32,
+    #[serde(alias = "totalPages")]
+    pub total_pages: i32,
+}
+
+/// Page selection for paginated endpoints
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PageParams {
+    pub page: i32,
+    pub per_page: i32,
+}
+
+impl Default for PageParams {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            per_page: 50,
+        }
+    }
+}
+
+/// Service health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Health {
+    pub status: String,
+    #[serde(default)]
+    pub service: String,
+}
+
+/// Transaction created through the wallet by an app backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedTx {
+    pub txid: String,
+    pub vout: i32,
+    pub hex: String,
+    pub carrier: i32,
+    #[serde(alias = "carrier_name")]
+    pub carrier_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_accepts_both_casings() {
+        let snake: Page<i32> = serde_json::from_str(
+            r#"{"data":[1],"total":1,"page":1,"per_page":50,"total_pages":1}"#,
+        )
+        .unwrap();
+        let camel: Page<i32> =
+            serde_json::from_str(r#"{"data":[1],"total":1,"page":1,"perPage":50,"totalPages":1}"#)
+                .unwrap();
+        assert_eq!(snake.per_page, camel.per_page);
+        assert_eq!(snake.total_pages, camel.total_pages);
+    }
+
+    #[test]
+    fn test_created_tx_accepts_both_casings() {
+        let camel: CreatedTx = serde_json::from_str(
+            r#"{"txid":"ab","vout":0,"hex":"00","carrier":0,"carrierName":"op_return"}"#,
+        )
+        .unwrap();
+        let snake: CreatedTx = serde_json::from_str(
+            r#"{"txid":"ab","vout":0,"hex":"00","carrier":0,"carrier_name":"op_return"}"#,
+        )
+        .unwrap();
+        assert_eq!(camel.carrier_name, snake.carrier_name);
+    }
+}
@@ -0,0 +1,422 @@
+//! Anchor Wallet service API
+//!
+//! Amounts in balances and UTXOs are BTC as reported by Bitcoin Core; output
+//! values in [`CreateMessage`] are satoshis.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Result;
+use crate::http::{segment, Http};
+use crate::types::Health;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    pub confirmed: f64,
+    pub unconfirmed: f64,
+    #[serde(default)]
+    pub immature: f64,
+    /// Confirmed value in UTXOs locked for assets
+    #[serde(default)]
+    pub locked: f64,
+    /// Confirmed value that is not locked
+    #[serde(default)]
+    pub spendable: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    pub amount: f64,
+    pub confirmations: u32,
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+/// An existing anchor or input, by outpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnchorRef {
+    pub txid: String,
+    pub vout: u8,
+}
+
+/// An extra transaction output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputSpec {
+    pub address: String,
+    /// Value in satoshis
+    pub value: u64,
+}
+
+/// An Anchor message for the wallet to build, sign and broadcast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMessage {
+    pub kind: u8,
+    pub body: String,
+    /// `body` is hex encoded binary rather than text
+    pub body_is_hex: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_txid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_vout: Option<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub additional_anchors: Vec<AnchorRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<u8>,
+    /// sat/vbyte
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<u64>,
+    /// Outputs that must be spent, e.g. an ownership UTXO
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_inputs: Vec<AnchorRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<OutputSpec>,
+    /// BIP-21 URI with a `pj=` endpoint to pay with a payjoin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payjoin: Option<String>,
+    /// Coin control: fund only from these `txid:vout` UTXOs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// Coin control: never spend these `txid:vout` UTXOs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_inputs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_address: Option<String>,
+}
+
+impl CreateMessage {
+    /// A text message of the given kind
+    pub fn text(kind: u8, body: impl Into<String>) -> Self {
+        Self {
+            kind,
+            body: body.into(),
+            body_is_hex: false,
+            parent_txid: None,
+            parent_vout: None,
+            additional_anchors: Vec::new(),
+            carrier: None,
+            fee_rate: None,
+            required_inputs: Vec::new(),
+            outputs: Vec::new(),
+            payjoin: None,
+            inputs: Vec::new(),
+            exclude_inputs: Vec::new(),
+            change_address: None,
+        }
+    }
+
+    /// A binary message of the given kind
+    pub fn binary(kind: u8, body: &[u8]) -> Self {
+        let body: String = body.iter().map(|b| format!("{:02x}", b)).collect();
+        Self {
+            body_is_hex: true,
+            ..Self::text(kind, body)
+        }
+    }
+
+    /// Reply to (or act on) an existing message
+    pub fn parent(mut self, txid: impl Into<String>, vout: u8) -> Self {
+        self.parent_txid = Some(txid.into());
+        self.parent_vout = Some(vout);
+        self
+    }
+
+    pub fn anchor(mut self, txid: impl Into<String>, vout: u8) -> Self {
+        self.additional_anchors.push(AnchorRef {
+            txid: txid.into(),
+            vout,
+        });
+        self
+    }
+
+    pub fn carrier(mut self, carrier: u8) -> Self {
+        self.carrier = Some(carrier);
+        self
+    }
+
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+
+    pub fn required_input(mut self, txid: impl Into<String>, vout: u8) -> Self {
+        self.required_inputs.push(AnchorRef {
+            txid: txid.into(),
+            vout,
+        });
+        self
+    }
+
+    pub fn output(mut self, address: impl Into<String>, value: u64) -> Self {
+        self.outputs.push(OutputSpec {
+            address: address.into(),
+            value,
+        });
+        self
+    }
+
+    /// Fund the message only from this UTXO (and any others given)
+    pub fn input(mut self, txid: impl AsRef<str>, vout: u32) -> Self {
+        self.inputs.push(format!("{}:{}", txid.as_ref(), vout));
+        self
+    }
+
+    /// Never spend this UTXO in the message
+    pub fn exclude_input(mut self, txid: impl AsRef<str>, vout: u32) -> Self {
+        self.exclude_inputs
+            .push(format!("{}:{}", txid.as_ref(), vout));
+        self
+    }
+
+    pub fn change_address(mut self, address: impl Into<String>) -> Self {
+        self.change_address = Some(address.into());
+        self
+    }
+
+    /// Pay the recipient of a BIP-21 URI, as a payjoin where possible
+    pub fn payjoin(mut self, uri: impl Into<String>) -> Self {
+        self.payjoin = Some(uri.into());
+        self
+    }
+}
+
+/// Message transaction as broadcast by the wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageTx {
+    pub txid: String,
+    pub vout: u32,
+    pub hex: String,
+    pub carrier: u8,
+    pub carrier_name: String,
+    /// Unsigned PSBT from a watch-only wallet; nothing was broadcast
+    #[serde(default)]
+    pub psbt: Option<String>,
+    /// Whether a requested payjoin went through or fell back to the original
+    #[serde(default)]
+    pub payjoin: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawTx {
+    pub txid: String,
+    pub hex: String,
+    #[serde(default)]
+    pub decoded: Option<Value>,
+    #[serde(default)]
+    pub fee_sats: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolding {
+    pub ticker: String,
+    pub decimals: i16,
+    pub balance: String,
+    pub utxo_count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSummary {
+    pub domains: usize,
+    pub token_types: usize,
+    pub positions: usize,
+    pub oracles: usize,
+    pub proofs: i64,
+    pub pixels: i64,
+}
+
+/// An app backend the portfolio could not reach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceError {
+    pub source: String,
+    pub error: String,
+}
+
+/// Assets held by a set of addresses across all app backends
+///
+/// Items other than tokens are passed through in each backend's own shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub addresses: Vec<String>,
+    pub summary: PortfolioSummary,
+    pub domains: Vec<Value>,
+    pub tokens: Vec<TokenHolding>,
+    pub positions: Vec<Value>,
+    pub oracles: Vec<Value>,
+    pub proofs: Vec<Value>,
+    pub pixels: Vec<Value>,
+    pub errors: Vec<SourceError>,
+    pub generated_at: String,
+    #[serde(default)]
+    pub cached: bool,
+}
+
+#[derive(Deserialize)]
+struct AddressResponse {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct AddressesResponse {
+    addresses: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BroadcastResponse {
+    txid: String,
+}
+
+#[derive(Serialize)]
+struct PortfolioQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    addresses: Option<String>,
+    refresh: bool,
+}
+
+/// Client for the Anchor Wallet service
+#[derive(Debug, Clone)]
+pub struct WalletClient {
+    http: Http,
+}
+
+impl WalletClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_client(base_url, Client::new())
+    }
+
+    /// Use an existing reqwest client (shared pool, proxy, timeouts)
+    pub fn with_client(base_url: &str, client: Client) -> Self {
+        Self {
+            http: Http::new(base_url, client),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.http.base_url()
+    }
+
+    pub async fn health(&self) -> Result<Health> {
+        self.http.get("/health").await
+    }
+
+    pub async fn balance(&self) -> Result<Balance> {
+        self.http.get("/wallet/balance").await
+    }
+
+    /// Derive a fresh receive address
+    pub async fn new_address(&self) -> Result<String> {
+        let response: AddressResponse = self.http.get("/wallet/address").await?;
+        Ok(response.address)
+    }
+
+    /// Every address the wallet has handed out
+    pub async fn addresses(&self) -> Result<Vec<String>> {
+        let response: AddressesResponse = self.http.get("/wallet/addresses").await?;
+        Ok(response.addresses)
+    }
+
+    pub async fn utxos(&self) -> Result<Vec<Utxo>> {
+        self.http.get("/wallet/utxos").await
+    }
+
+    /// Build, sign and broadcast an Anchor message
+    ///
+    /// A watch-only wallet returns the funded transaction as
+    /// [`MessageTx::psbt`] instead; sign it and pass it to [`Self::broadcast_psbt`].
+    pub async fn create_message(&self, message: &CreateMessage) -> Result<MessageTx> {
+        self.http.post("/wallet/create-message", message).await
+    }
+
+    /// Broadcast a signed transaction and return its txid
+    pub async fn broadcast(&self, hex: &str) -> Result<String> {
+        let response: BroadcastResponse = self
+            .http
+            .post("/wallet/broadcast", &serde_json::json!({ "hex": hex }))
+            .await?;
+        Ok(response.txid)
+    }
+
+    /// Finalize and broadcast a PSBT signed outside the wallet service
+    pub async fn broadcast_psbt(&self, psbt: &str) -> Result<String> {
+        let response: BroadcastResponse = self
+            .http
+            .post("/wallet/broadcast", &serde_json::json!({ "psbt": psbt }))
+            .await?;
+        Ok(response.txid)
+    }
+
+    pub async fn raw_tx(&self, txid: &str) -> Result<RawTx> {
+        self.http
+            .get(&format!("/wallet/rawtx/{}", segment(txid)))
+            .await
+    }
+
+    /// Assets held by `addresses`, or by the wallet itself when empty
+    pub async fn portfolio(&self, addresses: &[String], refresh: bool) -> Result<Portfolio> {
+        let query = PortfolioQuery {
+            addresses: (!addresses.is_empty()).then(|| addresses.join(",")),
+            refresh,
+        };
+        self.http.get_query("/wallet/portfolio", &query).await
+    }
+}
+
+/// Wallet operations app backends depend on
+///
+/// Implemented by [`WalletClient`] over HTTP; with the `testing` feature,
+/// `testing::FakeWallet` implements it in memory for unit tests. Hold it as
+/// `Arc<dyn WalletBackend>` to swap one for the other.
+#[async_trait]
+pub trait WalletBackend: Send + Sync {
+    async fn balance(&self) -> Result<Balance>;
+
+    /// Derive a fresh receive address
+    async fn new_address(&self) -> Result<String>;
+
+    /// Every address the wallet has handed out
+    async fn addresses(&self) -> Result<Vec<String>>;
+
+    async fn utxos(&self) -> Result<Vec<Utxo>>;
+
+    /// Build, sign and broadcast an Anchor message
+    async fn create_message(&self, message: &CreateMessage) -> Result<MessageTx>;
+
+    /// Broadcast a signed transaction and return its txid
+    async fn broadcast(&self, hex: &str) -> Result<String>;
+
+    async fn raw_tx(&self, txid: &str) -> Result<RawTx>;
+}
+
+#[async_trait]
+impl WalletBackend for WalletClient {
+    async fn balance(&self) -> Result<Balance> {
+        WalletClient::balance(self).await
+    }
+
+    async fn new_address(&self) -> Result<String> {
+        WalletClient::new_address(self).await
+    }
+
+    async fn addresses(&self) -> Result<Vec<String>> {
+        WalletClient::addresses(self).await
+    }
+
+    async fn utxos(&self) -> Result<Vec<Utxo>> {
+        WalletClient::utxos(self).await
+    }
+
+    async fn create_message(&self, message: &CreateMessage) -> Result<MessageTx> {
+        WalletClient::create_message(self, message).await
+    }
+
+    async fn broadcast(&self, hex: &str) -> Result<String> {
+        WalletClient::broadcast(self, hex).await
+    }
+
+    async fn raw_tx(&self, txid: &str) -> Result<RawTx> {
+        WalletClient::raw_tx(self, txid).await
+    }
+}
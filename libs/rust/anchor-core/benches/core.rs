@@ -0,0 +1,162 @@
+//! Payload and carrier benchmarks
+//!
+//! Times payload parsing and encoding, and each carrier's encode, decode
+//! and detection, at several body sizes. Run with `cargo bench -p
+//! anchor-core`; `scripts/bench.sh` compares the results to the committed
+//! baseline.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use anchor_core::carrier::{
+    AnnexCarrier, Carrier, CarrierInput, CarrierOutput, CarrierSelector, CarrierType,
+    InscriptionCarrier, OpReturnCarrier, StampsCarrier, WitnessCarrier,
+};
+use anchor_core::ParsedAnchorMessage;
+use anchor_core::{encode_anchor_payload, parse_anchor_payload, Anchor, AnchorKind};
+use bitcoin::hashes::Hash;
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, ScriptBuf, Transaction, TxIn, TxOut, Txid,
+    Witness,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Body sizes in bytes: a short post, a long post, the Stamps limit and a
+/// large OP_RETURN
+const BODY_SIZES: [usize; 4] = [64, 1_000, 7_000, 60_000];
+
+fn message(size: usize) -> ParsedAnchorMessage {
+    ParsedAnchorMessage {
+        kind: AnchorKind::Text,
+        anchors: vec![Anchor::from_txid(&Txid::from_byte_array([7; 32]), 0)].into(),
+        body: (0..size).map(|i| b'a' + (i % 26) as u8).collect(),
+    }
+}
+
+/// A transaction carrying a carrier's output where the indexer looks for it
+fn carrier_tx(output: CarrierOutput) -> Transaction {
+    let spend = |items: Vec<Vec<u8>>| TxIn {
+        witness: Witness::from_slice(&items),
+        ..Default::default()
+    };
+    let control = vec![0xc0; 33];
+    let (input, output) = match output {
+        CarrierOutput::OpReturn(script) => (vec![], vec![script]),
+        CarrierOutput::Stamps(scripts) => (vec![], scripts),
+        CarrierOutput::Inscription { reveal_script, .. } => (
+            vec![spend(vec![vec![0; 64], reveal_script.to_bytes(), control])],
+            vec![],
+        ),
+        CarrierOutput::Annex(annex) => (vec![spend(vec![vec![0; 64], annex])], vec![]),
+        CarrierOutput::WitnessData { scripts, .. } => (
+            scripts
+                .iter()
+                .map(|script| spend(vec![script.to_bytes(), control.clone()]))
+                .collect(),
+            vec![],
+        ),
+    };
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input,
+        output: output
+            .into_iter()
+            .map(|script_pubkey: ScriptBuf| TxOut {
+                value: Amount::from_sat(546),
+                script_pubkey,
+            })
+            .collect(),
+    }
+}
+
+/// Witness stack of the first input for payloads carried by a single
+/// input, `None` for those decoded from the whole transaction (outputs and
+/// multi-part witness data)
+fn witness_input(carrier_type: CarrierType, tx: &Transaction) -> Option<Vec<Vec<u8>>> {
+    let single_input = match carrier_type {
+        CarrierType::Inscription | CarrierType::TaprootAnnex => true,
+        CarrierType::WitnessData => tx.input.len() == 1,
+        _ => false,
+    };
+    single_input.then(|| tx.input[0].witness.iter().map(|w| w.to_vec()).collect())
+}
+
+fn decode(
+    carrier: &dyn Carrier,
+    tx: &Transaction,
+    witness: Option<&Vec<Vec<u8>>>,
+) -> ParsedAnchorMessage {
+    let input = match witness {
+        Some(witness) => CarrierInput::Witness(witness),
+        None => CarrierInput::Transaction { tx, vout: 0 },
+    };
+    carrier.decode(&input).unwrap()
+}
+
+fn payload(c: &mut Criterion) {
+    let mut group = c.benchmark_group("payload");
+    for size in BODY_SIZES {
+        let message = message(size);
+        let encoded = encode_anchor_payload(&message);
+        group.throughput(Throughput::Bytes(encoded.len() as u64));
+        group.bench_with_input(BenchmarkId::new("encode", size), &message, |b, message| {
+            b.iter(|| encode_anchor_payload(black_box(message)))
+        });
+        group.bench_with_input(BenchmarkId::new("parse", size), &encoded, |b, encoded| {
+            b.iter(|| parse_anchor_payload(black_box(encoded)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn carriers(c: &mut Criterion) {
+    let carriers: Vec<Box<dyn Carrier>> = vec![
+        Box::new(OpReturnCarrier::new()),
+        Box::new(StampsCarrier::new()),
+        Box::new(InscriptionCarrier::new()),
+        Box::new(AnnexCarrier::new()),
+        Box::new(WitnessCarrier::new()),
+    ];
+    let selector = CarrierSelector::new();
+
+    for carrier in &carriers {
+        let info = carrier.info();
+        let mut group = c.benchmark_group(format!("carrier/{}", info.carrier_type));
+        for size in BODY_SIZES {
+            let message = message(size);
+            if encode_anchor_payload(&message).len() > info.max_size {
+                continue;
+            }
+            let tx = carrier_tx(carrier.encode(&message).unwrap());
+            let witness = witness_input(info.carrier_type, &tx);
+            // Stamps decodes padding bytes along with multi-key bodies, so
+            // only the kind is compared
+            assert_eq!(
+                decode(carrier.as_ref(), &tx, witness.as_ref()).kind,
+                message.kind
+            );
+
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new("encode", size), &message, |b, message| {
+                b.iter(|| carrier.encode(black_box(message)).unwrap())
+            });
+            group.bench_with_input(BenchmarkId::new("decode", size), &tx, |b, tx| {
+                b.iter(|| decode(carrier.as_ref(), black_box(tx), witness.as_ref()))
+            });
+            group.bench_with_input(BenchmarkId::new("detect", size), &tx, |b, tx| {
+                b.iter(|| selector.detect(black_box(tx)))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(2));
+    targets = payload, carriers
+}
+criterion_main!(benches);
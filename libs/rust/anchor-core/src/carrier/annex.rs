@@ -221,7 +221,7 @@ impl Carrier for AnnexCarrier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AnchorKind;
+    use crate::{AnchorKind, Anchors};
 
     #[test]
     fn test_carrier_info() {
@@ -256,7 +256,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Hello from annex!".to_vec(),
         };
 
@@ -280,7 +280,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Annex roundtrip test".to_vec(),
         };
 
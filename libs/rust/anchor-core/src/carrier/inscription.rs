@@ -12,19 +12,30 @@
 //!
 //! # Format
 //!
+//! Envelopes follow the ordinals layout so explorers and `ord` index them as
+//! regular inscriptions. The body is the message body itself, served under a
+//! negotiated content type; the ANCHOR kind and anchors travel in the
+//! metadata field and the metaprotocol field marks the inscription as ANCHOR:
+//!
 //! ```text
 //! OP_FALSE
 //! OP_IF
-//!   OP_PUSH "anchor"              // Protocol ID
-//!   OP_PUSH 1                     // Content-type tag
-//!   OP_PUSH "application/anchor"  // MIME type
-//!   OP_PUSH 0                     // Body tag
-//!   OP_PUSH <payload_chunk_1>     // Data in 520-byte chunks
-//!   OP_PUSH <payload_chunk_2>
+//!   OP_PUSH "ord"                      // Protocol ID
+//!   OP_PUSH 1 OP_PUSH <content-type>   // e.g. "text/plain;charset=utf-8"
+//!   OP_PUSH 5 OP_PUSH <metadata>       // CBOR {"kind": u8, "anchors": [bytes]}
+//!   OP_PUSH 7 OP_PUSH "anchor"         // Metaprotocol
+//!   OP_PUSH 0                          // Body tag
+//!   OP_PUSH <body_chunk_1>             // Data in 520-byte chunks
+//!   OP_PUSH <body_chunk_2>
 //!   ...
 //! OP_ENDIF
 //! ```
+//!
+//! Parsing also accepts envelopes whose body is a complete ANCHOR payload,
+//! which covers the older `"anchor"` protocol envelopes and payloads
+//! inscribed with ord-compatible tooling.
 
+use bitcoin::opcodes::all::{OP_ENDIF, OP_IF, OP_PUSHBYTES_0, OP_PUSHNUM_1, OP_PUSHNUM_16};
 use bitcoin::script::{Builder, Instruction, PushBytesBuf};
 use bitcoin::ScriptBuf;
 
@@ -32,25 +43,88 @@ use super::{
     Carrier, CarrierError, CarrierInfo, CarrierInput, CarrierOutput, CarrierResult, CarrierStatus,
     CarrierType,
 };
-use crate::{encode_anchor_payload, parse_anchor_payload, AnchorKind, ParsedAnchorMessage};
+use crate::{
+    encode_anchor_payload, parse_anchor_payload, Anchor, AnchorKind, Anchors, ParsedAnchorMessage,
+};
 
 /// Inscription carrier implementation (Ordinals-style envelope)
 #[derive(Debug, Clone)]
 pub struct InscriptionCarrier {
     /// Maximum chunk size for push data
     chunk_size: usize,
+    /// Content type to use instead of the negotiated one
+    content_type: Option<String>,
+}
+
+/// Fields of a parsed inscription envelope
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InscriptionEnvelope {
+    /// Envelope protocol ID (`ord`, or `anchor` for older envelopes)
+    pub protocol: Vec<u8>,
+    /// Content type (tag 1)
+    pub content_type: Option<String>,
+    /// Raw CBOR metadata (tag 5)
+    pub metadata: Option<Vec<u8>>,
+    /// Metaprotocol (tag 7)
+    pub metaprotocol: Option<String>,
+    /// Content encoding (tag 9), e.g. `br`
+    pub content_encoding: Option<String>,
+    /// Inscription body
+    pub body: Vec<u8>,
+}
+
+impl InscriptionEnvelope {
+    /// Whether the metaprotocol field marks this as an ANCHOR inscription
+    pub fn is_anchor(&self) -> bool {
+        self.metaprotocol.as_deref() == Some(InscriptionCarrier::METAPROTOCOL)
+    }
+
+    /// Recover the ANCHOR message carried by this inscription
+    ///
+    /// ANCHOR inscriptions rebuild the message from the metadata and body;
+    /// any other inscription qualifies only if its body is a complete ANCHOR
+    /// payload. Encoded bodies (`content-encoding`) are not decompressed.
+    pub fn anchor_message(&self) -> Option<ParsedAnchorMessage> {
+        if self.content_encoding.is_some() {
+            return None;
+        }
+
+        if self.is_anchor() {
+            if let Some((kind, anchors)) = self.metadata.as_deref().and_then(decode_metadata) {
+                return Some(ParsedAnchorMessage {
+                    kind,
+                    anchors,
+                    body: self.body.clone(),
+                });
+            }
+        }
+
+        parse_anchor_payload(&self.body).ok()
+    }
 }
 
 impl InscriptionCarrier {
-    /// Protocol identifier for ANCHOR inscriptions
+    /// Protocol identifier of the original ANCHOR envelopes
     pub const PROTOCOL_ID: &'static [u8] = b"anchor";
 
-    /// Alternative protocol ID (Ordinals compatibility)
+    /// Ordinals protocol ID, used for new envelopes
     pub const ORD_PROTOCOL_ID: &'static [u8] = b"ord";
 
+    /// Metaprotocol identifying ANCHOR inscriptions
+    pub const METAPROTOCOL: &'static str = "anchor";
+
     /// Content-type tag
     pub const CONTENT_TYPE_TAG: u8 = 1;
 
+    /// Metadata tag (CBOR)
+    pub const METADATA_TAG: u8 = 5;
+
+    /// Metaprotocol tag
+    pub const METAPROTOCOL_TAG: u8 = 7;
+
+    /// Content-encoding tag
+    pub const CONTENT_ENCODING_TAG: u8 = 9;
+
     /// Body tag
     pub const BODY_TAG: u8 = 0;
 
@@ -64,12 +138,22 @@ impl InscriptionCarrier {
     pub fn new() -> Self {
         Self {
             chunk_size: Self::MAX_PUSH_SIZE,
+            content_type: None,
         }
     }
 
     /// Create with custom chunk size
     pub fn with_chunk_size(chunk_size: usize) -> Self {
-        Self { chunk_size }
+        Self {
+            chunk_size,
+            content_type: None,
+        }
+    }
+
+    /// Always use `content_type` instead of negotiating one from the body
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
     }
 
     /// Get content type for ANCHOR message kind
@@ -80,6 +164,8 @@ impl InscriptionCarrier {
             AnchorKind::State => "application/json",
             AnchorKind::Vote => "application/json",
             AnchorKind::Image => "image/png",
+            AnchorKind::Identity => "application/octet-stream",
+            AnchorKind::Mirror => "application/octet-stream",
             // Oracle types - use binary format
             AnchorKind::Oracle => "application/octet-stream",
             AnchorKind::OracleAttestation => "application/octet-stream",
@@ -90,48 +176,97 @@ impl InscriptionCarrier {
             AnchorKind::PlaceBet => "application/octet-stream",
             AnchorKind::MarketResolve => "application/octet-stream",
             AnchorKind::ClaimWinnings => "application/octet-stream",
+            AnchorKind::MarketOrder => "application/octet-stream",
+            // Lottery types - use binary format
+            AnchorKind::LotteryCreate => "application/octet-stream",
+            AnchorKind::LotteryTicket => "application/octet-stream",
+            AnchorKind::LotteryDraw => "application/octet-stream",
+            AnchorKind::LotteryClaim => "application/octet-stream",
             AnchorKind::Custom(_) => "application/octet-stream",
         }
     }
 
+    /// Pick the content type explorers should render the body as
+    ///
+    /// Starts from the kind's content type and checks it against the body:
+    /// text must be UTF-8, JSON kinds must look like JSON and images are
+    /// identified by their magic bytes. Anything else is served as
+    /// `application/octet-stream`.
+    pub fn negotiate_content_type(kind: AnchorKind, body: &[u8]) -> &'static str {
+        const BINARY: &str = "application/octet-stream";
+        match kind {
+            AnchorKind::Text => match std::str::from_utf8(body) {
+                Ok(_) => Self::content_type_for_kind(kind),
+                Err(_) => BINARY,
+            },
+            AnchorKind::State | AnchorKind::Vote => match std::str::from_utf8(body) {
+                Ok(text) if text.trim_start().starts_with(['{', '[']) => "application/json",
+                _ => BINARY,
+            },
+            AnchorKind::Image => {
+                if body.starts_with(b"\x89PNG\r\n\x1a\n") {
+                    "image/png"
+                } else if body.starts_with(&[0xFF, 0xD8, 0xFF]) {
+                    "image/jpeg"
+                } else if body.starts_with(b"GIF87a") || body.starts_with(b"GIF89a") {
+                    "image/gif"
+                } else if body.len() >= 12 && &body[0..4] == b"RIFF" && &body[8..12] == b"WEBP" {
+                    "image/webp"
+                } else if body.starts_with(b"<svg") {
+                    "image/svg+xml"
+                } else {
+                    BINARY
+                }
+            }
+            _ => Self::content_type_for_kind(kind),
+        }
+    }
+
+    /// Content type this carrier uses for `message`
+    fn content_type(&self, message: &ParsedAnchorMessage) -> String {
+        match &self.content_type {
+            Some(content_type) => content_type.clone(),
+            None => Self::negotiate_content_type(message.kind, &message.body).to_string(),
+        }
+    }
+
     /// Build the inscription envelope script
     pub fn build_envelope(&self, message: &ParsedAnchorMessage) -> CarrierResult<ScriptBuf> {
-        let payload = encode_anchor_payload(message);
-        let content_type = Self::content_type_for_kind(message.kind);
+        let content_type = self.content_type(message);
+        let metadata = encode_metadata(message);
 
         let mut builder = Builder::new()
-            .push_opcode(bitcoin::opcodes::all::OP_PUSHBYTES_0) // OP_FALSE
-            .push_opcode(bitcoin::opcodes::all::OP_IF);
+            .push_opcode(OP_PUSHBYTES_0) // OP_FALSE
+            .push_opcode(OP_IF);
+
+        builder = push_bytes(builder, Self::ORD_PROTOCOL_ID, "Protocol ID")?;
 
-        // Push protocol ID
-        let protocol_push = PushBytesBuf::try_from(Self::PROTOCOL_ID.to_vec())
-            .map_err(|e| CarrierError::Script(format!("Protocol ID: {}", e)))?;
-        builder = builder.push_slice(protocol_push.as_push_bytes());
+        builder = push_bytes(builder, &[Self::CONTENT_TYPE_TAG], "Content type tag")?;
+        builder = push_bytes(builder, content_type.as_bytes(), "Content type")?;
 
-        // Push content-type tag (1)
-        builder = builder.push_int(Self::CONTENT_TYPE_TAG as i64);
+        // Long fields repeat their tag for every chunk
+        for chunk in metadata.chunks(Self::MAX_PUSH_SIZE) {
+            builder = push_bytes(builder, &[Self::METADATA_TAG], "Metadata tag")?;
+            builder = push_bytes(builder, chunk, "Metadata")?;
+        }
 
-        // Push content type
-        let content_type_push = PushBytesBuf::try_from(content_type.as_bytes().to_vec())
-            .map_err(|e| CarrierError::Script(format!("Content type: {}", e)))?;
-        builder = builder.push_slice(content_type_push.as_push_bytes());
+        builder = push_bytes(builder, &[Self::METAPROTOCOL_TAG], "Metaprotocol tag")?;
+        builder = push_bytes(builder, Self::METAPROTOCOL.as_bytes(), "Metaprotocol")?;
 
         // Push body tag (0)
-        builder = builder.push_int(Self::BODY_TAG as i64);
+        builder = builder.push_opcode(OP_PUSHBYTES_0);
 
-        // Push payload in chunks
-        for chunk in payload.chunks(self.chunk_size) {
-            let chunk_push = PushBytesBuf::try_from(chunk.to_vec())
-                .map_err(|e| CarrierError::Script(format!("Payload chunk: {}", e)))?;
-            builder = builder.push_slice(chunk_push.as_push_bytes());
+        // Push body in chunks
+        for chunk in message.body.chunks(self.chunk_size) {
+            builder = push_bytes(builder, chunk, "Body chunk")?;
         }
 
-        builder = builder.push_opcode(bitcoin::opcodes::all::OP_ENDIF);
+        builder = builder.push_opcode(OP_ENDIF);
 
         // Add OP_PUSHNUM_1 (same as OP_TRUE) to make the script spendable
         // The envelope (OP_FALSE OP_IF ... OP_ENDIF) is a no-op that preserves the inscription data
         // OP_PUSHNUM_1 ensures the script succeeds with exactly one truthy value on the stack
-        builder = builder.push_opcode(bitcoin::opcodes::all::OP_PUSHNUM_1);
+        builder = builder.push_opcode(OP_PUSHNUM_1);
 
         Ok(builder.into_script())
     }
@@ -152,25 +287,24 @@ impl InscriptionCarrier {
 
     /// Try to parse an envelope from script bytes
     fn try_parse_script_envelope(&self, data: &[u8]) -> Option<ParsedAnchorMessage> {
+        Self::parse_inscription(data)?.anchor_message()
+    }
+
+    /// Parse the fields of an `ord` or `anchor` envelope in a tapscript
+    pub fn parse_inscription(data: &[u8]) -> Option<InscriptionEnvelope> {
         // Look for OP_FALSE OP_IF pattern
         if data.len() < 10 {
             return None;
         }
 
-        // Try to parse as a script and look for envelope
         let script = bitcoin::Script::from_bytes(data);
-        let mut instructions = script.instructions().peekable();
+        let mut instructions = script.instructions();
 
         // Look for OP_FALSE (0x00) or OP_0
         loop {
             match instructions.next() {
-                Some(Ok(Instruction::PushBytes(bytes))) if bytes.is_empty() => {
-                    // Found OP_FALSE / OP_0
-                    break;
-                }
-                Some(Ok(Instruction::Op(bitcoin::opcodes::all::OP_PUSHBYTES_0))) => {
-                    break;
-                }
+                Some(Ok(Instruction::PushBytes(bytes))) if bytes.is_empty() => break,
+                Some(Ok(Instruction::Op(OP_PUSHBYTES_0))) => break,
                 Some(Ok(_)) => continue, // Skip other instructions
                 _ => return None,
             }
@@ -178,51 +312,215 @@ impl InscriptionCarrier {
 
         // Next should be OP_IF
         match instructions.next() {
-            Some(Ok(Instruction::Op(bitcoin::opcodes::all::OP_IF))) => {}
+            Some(Ok(Instruction::Op(OP_IF))) => {}
             _ => return None,
         }
 
         // Next should be protocol ID
-        let protocol_id = match instructions.next() {
-            Some(Ok(Instruction::PushBytes(bytes))) => bytes.as_bytes(),
+        let protocol = match instructions.next() {
+            Some(Ok(Instruction::PushBytes(bytes))) => bytes.as_bytes().to_vec(),
             _ => return None,
         };
-
-        // Check if it's an ANCHOR inscription
-        if protocol_id != Self::PROTOCOL_ID && protocol_id != Self::ORD_PROTOCOL_ID {
+        if protocol != Self::PROTOCOL_ID && protocol != Self::ORD_PROTOCOL_ID {
             return None;
         }
 
-        // Parse tags and body
-        let mut body_data = Vec::new();
+        let mut envelope = InscriptionEnvelope {
+            protocol,
+            ..Default::default()
+        };
+        let mut metadata = Vec::new();
+        let mut content_type = Vec::new();
         let mut in_body = false;
 
-        while let Some(Ok(instruction)) = instructions.next() {
-            match instruction {
-                Instruction::Op(bitcoin::opcodes::all::OP_ENDIF) => break,
-                Instruction::PushBytes(bytes) => {
-                    // Empty push (OP_0) indicates body tag - start of body data
-                    if bytes.is_empty() {
-                        in_body = true;
-                    } else if in_body {
-                        body_data.extend_from_slice(bytes.as_bytes());
-                    }
+        loop {
+            let push = match instructions.next()? {
+                Ok(Instruction::Op(OP_ENDIF)) => break,
+                Ok(instruction) => push_value(&instruction)?,
+                Err(_) => return None,
+            };
+
+            if in_body {
+                envelope.body.extend_from_slice(&push);
+                continue;
+            }
+
+            // Empty tag (OP_0) starts the body
+            if push.is_empty() {
+                in_body = true;
+                continue;
+            }
+
+            let value = match instructions.next()? {
+                Ok(Instruction::Op(OP_ENDIF)) => break,
+                Ok(instruction) => push_value(&instruction)?,
+                Err(_) => return None,
+            };
+            match push.as_slice() {
+                [Self::CONTENT_TYPE_TAG] => content_type.extend_from_slice(&value),
+                [Self::METADATA_TAG] => metadata.extend_from_slice(&value),
+                [Self::METAPROTOCOL_TAG] => {
+                    envelope.metaprotocol = String::from_utf8(value).ok();
+                }
+                [Self::CONTENT_ENCODING_TAG] => {
+                    envelope.content_encoding = String::from_utf8(value).ok();
                 }
-                Instruction::Op(op) => {
-                    // Check for body tag (OP_0) as explicit opcode
-                    if op == bitcoin::opcodes::all::OP_PUSHBYTES_0 {
-                        in_body = true;
+                // Other fields (pointer, parent, delegate, ...) don't affect ANCHOR
+                _ => {}
+            }
+        }
+
+        if !content_type.is_empty() {
+            envelope.content_type = String::from_utf8(content_type).ok();
+        }
+        if !metadata.is_empty() {
+            envelope.metadata = Some(metadata);
+        }
+
+        Some(envelope)
+    }
+}
+
+/// Push `data` as a single data push
+fn push_bytes(builder: Builder, data: &[u8], what: &str) -> CarrierResult<Builder> {
+    let push = PushBytesBuf::try_from(data.to_vec())
+        .map_err(|e| CarrierError::Script(format!("{}: {}", what, e)))?;
+    Ok(builder.push_slice(push.as_push_bytes()))
+}
+
+/// Bytes pushed by a data push or small-number opcode
+fn push_value(instruction: &Instruction) -> Option<Vec<u8>> {
+    match instruction {
+        Instruction::PushBytes(bytes) => Some(bytes.as_bytes().to_vec()),
+        // Older ANCHOR envelopes wrote tags with OP_1..OP_16
+        Instruction::Op(op)
+            if op.to_u8() >= OP_PUSHNUM_1.to_u8() && op.to_u8() <= OP_PUSHNUM_16.to_u8() =>
+        {
+            Some(vec![op.to_u8() - OP_PUSHNUM_1.to_u8() + 1])
+        }
+        _ => None,
+    }
+}
+
+/// Encode the message kind and anchors as the CBOR map
+/// `{"kind": u8, "anchors": [bytes(9)]}`, each anchor being its txid prefix
+/// followed by the vout
+fn encode_metadata(message: &ParsedAnchorMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+    cbor_head(&mut out, 5, 2);
+    cbor_text(&mut out, "kind");
+    cbor_head(&mut out, 0, u8::from(message.kind) as u64);
+    cbor_text(&mut out, "anchors");
+    cbor_head(&mut out, 4, message.anchors.len() as u64);
+    for anchor in &message.anchors {
+        cbor_head(&mut out, 2, 9);
+        out.extend_from_slice(&anchor.txid_prefix);
+        out.push(anchor.vout);
+    }
+    out
+}
+
+/// Decode metadata written by [`encode_metadata`]
+fn decode_metadata(data: &[u8]) -> Option<(AnchorKind, Anchors)> {
+    let mut reader = CborReader { data, pos: 0 };
+    let (major, entries) = reader.head()?;
+    if major != 5 {
+        return None;
+    }
+
+    let mut kind = None;
+    let mut anchors = Anchors::new();
+    for _ in 0..entries {
+        match reader.text()? {
+            "kind" => {
+                let (major, value) = reader.head()?;
+                if major != 0 {
+                    return None;
+                }
+                kind = Some(AnchorKind::from(u8::try_from(value).ok()?));
+            }
+            "anchors" => {
+                let (major, count) = reader.head()?;
+                if major != 4 {
+                    return None;
+                }
+                for _ in 0..count {
+                    let bytes = reader.bytes()?;
+                    if bytes.len() != 9 {
+                        return None;
                     }
+                    anchors.push(Anchor {
+                        txid_prefix: bytes[..8].try_into().ok()?,
+                        vout: bytes[8],
+                    });
                 }
             }
+            _ => return None,
         }
+    }
 
-        if body_data.is_empty() {
-            return None;
+    Some((kind?, anchors))
+}
+
+fn cbor_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        out.push(major | value as u8);
+    } else if value <= u8::MAX as u64 {
+        out.extend_from_slice(&[major | 24, value as u8]);
+    } else if value <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(value as u16).to_be_bytes());
+    } else {
+        out.push(major | 26);
+        out.extend_from_slice(&(value as u32).to_be_bytes());
+    }
+}
+
+fn cbor_text(out: &mut Vec<u8>, text: &str) {
+    cbor_head(out, 3, text.len() as u64);
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Reader for the small CBOR subset used by the metadata
+struct CborReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    /// Major type and argument of the next item
+    fn head(&mut self) -> Option<(u8, u64)> {
+        let initial = *self.take(1)?.first()?;
+        let value = match initial & 0x1F {
+            n @ 0..=23 => n as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().ok()?) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().ok()?) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().ok()?),
+            _ => return None,
+        };
+        Some((initial >> 5, value))
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        match self.head()? {
+            (2, len) => self.take(usize::try_from(len).ok()?),
+            _ => None,
         }
+    }
 
-        // Try to parse as ANCHOR payload
-        parse_anchor_payload(&body_data).ok()
+    fn text(&mut self) -> Option<&'a str> {
+        match self.head()? {
+            (3, len) => std::str::from_utf8(self.take(usize::try_from(len).ok()?)?).ok(),
+            _ => None,
+        }
     }
 }
 
@@ -256,7 +554,7 @@ impl Carrier for InscriptionCarrier {
         }
 
         let reveal_script = self.build_envelope(message)?;
-        let content_type = Self::content_type_for_kind(message.kind).to_string();
+        let content_type = self.content_type(message);
 
         Ok(CarrierOutput::Inscription {
             reveal_script,
@@ -280,11 +578,13 @@ impl Carrier for InscriptionCarrier {
 
         // Envelope overhead:
         // - OP_FALSE (1) + OP_IF (1)
-        // - Protocol ID push (~8)
+        // - Protocol ID push (~4)
         // - Content-type tag + value (~30)
+        // - Metadata tag + value (~20 without anchors)
+        // - Metaprotocol tag + value (~9)
         // - Body tag (1)
         // - OP_ENDIF (1)
-        let envelope_overhead = 50;
+        let envelope_overhead = 70;
 
         // Chunk overhead: each 520-byte chunk needs push opcode
         let num_chunks = payload_size.div_ceil(self.chunk_size);
@@ -303,6 +603,7 @@ impl Carrier for InscriptionCarrier {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use smallvec::smallvec;
 
     #[test]
     fn test_carrier_info() {
@@ -337,7 +638,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Hello, ANCHOR inscription!".to_vec(),
         };
 
@@ -356,7 +657,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Test inscription".to_vec(),
         };
 
@@ -374,6 +675,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_envelope_roundtrip() {
+        let carrier = InscriptionCarrier::new();
+
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: smallvec![Anchor {
+                txid_prefix: [1, 2, 3, 4, 5, 6, 7, 8],
+                vout: 2,
+            }],
+            body: "x".repeat(1200).into_bytes(),
+        };
+
+        let script = carrier.build_envelope(&message).unwrap();
+        let envelope = InscriptionCarrier::parse_inscription(script.as_bytes()).unwrap();
+
+        assert_eq!(envelope.protocol, b"ord");
+        assert_eq!(
+            envelope.content_type.as_deref(),
+            Some("text/plain;charset=utf-8")
+        );
+        assert_eq!(envelope.metaprotocol.as_deref(), Some("anchor"));
+        assert_eq!(envelope.body, message.body);
+
+        let decoded = carrier
+            .decode(&CarrierInput::Bytes(script.as_bytes()))
+            .unwrap();
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn test_parse_ord_tooling_inscription() {
+        // An ord-style envelope whose body is a raw ANCHOR payload, with
+        // fields ANCHOR ignores
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: b"from ord".to_vec(),
+        };
+        let payload = encode_anchor_payload(&message);
+        let mut builder = Builder::new()
+            .push_opcode(OP_PUSHBYTES_0)
+            .push_opcode(OP_IF);
+        for push in [
+            b"ord".as_slice(),
+            &[1],
+            b"application/octet-stream",
+            &[2],
+            &[1],
+        ] {
+            builder = push_bytes(builder, push, "test").unwrap();
+        }
+        builder = builder.push_opcode(OP_PUSHBYTES_0);
+        builder = push_bytes(builder, &payload, "test").unwrap();
+        let script = builder.push_opcode(OP_ENDIF).into_script();
+
+        let carrier = InscriptionCarrier::new();
+        let decoded = carrier
+            .decode(&CarrierInput::Bytes(script.as_bytes()))
+            .unwrap();
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn test_parse_legacy_anchor_envelope() {
+        // Original layout: "anchor" protocol, OP_1 content-type tag and the
+        // full payload as body
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: b"legacy".to_vec(),
+        };
+        let payload = encode_anchor_payload(&message);
+        let mut builder = Builder::new()
+            .push_opcode(OP_PUSHBYTES_0)
+            .push_opcode(OP_IF);
+        builder = push_bytes(builder, b"anchor", "test").unwrap();
+        builder = builder.push_int(1);
+        builder = push_bytes(builder, b"text/plain;charset=utf-8", "test").unwrap();
+        builder = builder.push_int(0);
+        builder = push_bytes(builder, &payload, "test").unwrap();
+        let script = builder.push_opcode(OP_ENDIF).into_script();
+
+        let envelope = InscriptionCarrier::parse_inscription(script.as_bytes()).unwrap();
+        assert_eq!(
+            envelope.content_type.as_deref(),
+            Some("text/plain;charset=utf-8")
+        );
+        assert_eq!(envelope.anchor_message(), Some(message));
+    }
+
+    #[test]
+    fn test_non_anchor_inscription_is_ignored() {
+        let mut builder = Builder::new()
+            .push_opcode(OP_PUSHBYTES_0)
+            .push_opcode(OP_IF);
+        for push in [b"ord".as_slice(), &[1], b"text/plain"] {
+            builder = push_bytes(builder, push, "test").unwrap();
+        }
+        builder = builder.push_opcode(OP_PUSHBYTES_0);
+        builder = push_bytes(builder, b"just an inscription", "test").unwrap();
+        let script = builder.push_opcode(OP_ENDIF).into_script();
+
+        let carrier = InscriptionCarrier::new();
+        assert!(carrier
+            .decode(&CarrierInput::Bytes(script.as_bytes()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_negotiate_content_type() {
+        assert_eq!(
+            InscriptionCarrier::negotiate_content_type(
+                AnchorKind::Image,
+                &[0xFF, 0xD8, 0xFF, 0xE0]
+            ),
+            "image/jpeg"
+        );
+        assert_eq!(
+            InscriptionCarrier::negotiate_content_type(AnchorKind::Text, &[0xFF, 0xFE]),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            InscriptionCarrier::negotiate_content_type(AnchorKind::State, b"{\"a\":1}"),
+            "application/json"
+        );
+
+        let carrier = InscriptionCarrier::new().with_content_type("text/markdown");
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: b"# hi".to_vec(),
+        };
+        match carrier.encode(&message).unwrap() {
+            CarrierOutput::Inscription { content_type, .. } => {
+                assert_eq!(content_type, "text/markdown")
+            }
+            _ => panic!("Expected Inscription output"),
+        }
+    }
+
     #[test]
     fn test_fee_estimation_with_discount() {
         let carrier = InscriptionCarrier::new();
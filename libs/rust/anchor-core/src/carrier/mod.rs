@@ -31,6 +31,7 @@ mod inscription;
 mod op_return;
 mod selector;
 mod stamps;
+mod verify;
 mod witness;
 
 pub use annex::*;
@@ -39,6 +40,7 @@ pub use inscription::*;
 pub use op_return::*;
 pub use selector::*;
 pub use stamps::*;
+pub use verify::*;
 pub use witness::*;
 
 use bitcoin::script::ScriptBuf;
@@ -131,6 +133,25 @@ pub struct CarrierInfo {
     pub status: CarrierStatus,
 }
 
+/// Permanent UTXO set cost of a carrier's outputs
+///
+/// Counts the unprunable, unspendable outputs a message creates and the
+/// satoshis locked in them for good.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoCost {
+    /// Number of outputs added to the UTXO set
+    pub outputs: usize,
+    /// Total value locked in those outputs, in satoshis
+    pub value: u64,
+}
+
+impl UtxoCost {
+    /// Whether the carrier leaves nothing behind in the UTXO set
+    pub fn is_zero(&self) -> bool {
+        self.outputs == 0
+    }
+}
+
 /// Output from carrier encoding
 #[derive(Debug, Clone)]
 pub enum CarrierOutput {
@@ -152,8 +173,8 @@ pub enum CarrierOutput {
     WitnessData {
         /// Data chunks for witness stack
         chunks: Vec<Vec<u8>>,
-        /// Tapscript containing the data
-        script: ScriptBuf,
+        /// Tapscripts containing the data, one per reveal input
+        scripts: Vec<ScriptBuf>,
     },
 
     /// Annex data (prefixed with 0x50)
@@ -204,6 +225,16 @@ pub trait Carrier: Send + Sync {
     /// # Returns
     /// Estimated fee in satoshis
     fn estimate_fee(&self, payload_size: usize, fee_rate: f64) -> u64;
+
+    /// Value in satoshis to put on an output script created by this carrier
+    fn output_value(&self, _script: &Script) -> u64 {
+        0
+    }
+
+    /// Permanent UTXO set cost of a payload of the given size
+    fn utxo_cost(&self, _payload_size: usize) -> UtxoCost {
+        UtxoCost::default()
+    }
 }
 
 #[cfg(test)]
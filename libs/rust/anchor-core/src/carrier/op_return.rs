@@ -197,7 +197,7 @@ impl Carrier for OpReturnCarrier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AnchorKind;
+    use crate::{AnchorKind, Anchors};
 
     #[test]
     fn test_carrier_info() {
@@ -225,7 +225,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Hello, ANCHOR!".to_vec(),
         };
 
@@ -251,7 +251,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: vec![0u8; 100], // Too large for 80 byte legacy limit
         };
 
@@ -7,7 +7,7 @@ use bitcoin::Transaction;
 
 use super::{
     Carrier, CarrierError, CarrierInfo, CarrierInput, CarrierOutput, CarrierResult, CarrierStatus,
-    CarrierType,
+    CarrierType, UtxoCost,
 };
 use crate::{encode_anchor_payload, ParsedAnchorMessage};
 
@@ -131,6 +131,18 @@ impl CarrierSelector {
         }
     }
 
+    /// Replace the registered carrier of the same type with `carrier`
+    ///
+    /// Used to swap in a carrier with non-default settings, e.g. a
+    /// [`StampsCarrier`](super::StampsCarrier) with a custom output value.
+    pub fn with_carrier(mut self, carrier: impl Carrier + 'static) -> Self {
+        let carrier_type = carrier.info().carrier_type;
+        self.carriers
+            .retain(|c| c.info().carrier_type != carrier_type);
+        self.carriers.push(Arc::new(carrier));
+        self
+    }
+
     /// Get all registered carriers
     pub fn carriers(&self) -> &[Arc<dyn Carrier>] {
         &self.carriers
@@ -214,6 +226,19 @@ impl CarrierSelector {
         Ok(candidates[0].0)
     }
 
+    /// Permanent UTXO set cost of sending `message` with a carrier
+    ///
+    /// Returns `None` if the carrier is not registered.
+    pub fn utxo_cost(
+        &self,
+        carrier_type: CarrierType,
+        message: &ParsedAnchorMessage,
+    ) -> Option<UtxoCost> {
+        let size = encode_anchor_payload(message).len();
+        self.get_carrier(carrier_type)
+            .map(|carrier| carrier.utxo_cost(size))
+    }
+
     /// Encode a message using the best carrier
     pub fn encode(
         &self,
@@ -257,6 +282,7 @@ impl CarrierSelector {
         }
 
         // Check witness data for inscriptions, annex, and raw witness
+        let mut found_witness_data = false;
         for input in &tx.input {
             let witness_items: Vec<Vec<u8>> = input.witness.iter().map(|w| w.to_vec()).collect();
             if witness_items.is_empty() {
@@ -276,6 +302,7 @@ impl CarrierSelector {
                 }
 
                 if let Ok(message) = carrier.decode(&witness_input) {
+                    found_witness_data |= info.carrier_type == CarrierType::WitnessData;
                     results.push(DetectedMessage {
                         vout: 0, // Witness data binds to first sat typically
                         carrier_type: info.carrier_type,
@@ -285,6 +312,19 @@ impl CarrierSelector {
             }
         }
 
+        // Multi-part witness payloads span several inputs
+        if !found_witness_data {
+            if let Some(carrier) = self.get_carrier(CarrierType::WitnessData) {
+                if let Ok(message) = carrier.decode(&CarrierInput::Transaction { tx, vout: 0 }) {
+                    results.push(DetectedMessage {
+                        vout: 0,
+                        carrier_type: CarrierType::WitnessData,
+                        message,
+                    });
+                }
+            }
+        }
+
         results
     }
 }
@@ -298,7 +338,7 @@ impl Default for CarrierSelector {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AnchorKind;
+    use crate::{AnchorKind, Anchors};
 
     #[test]
     fn test_selector_creation() {
@@ -323,7 +363,7 @@ mod tests {
         let selector = CarrierSelector::new();
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Hello, ANCHOR!".to_vec(),
         };
 
@@ -339,7 +379,7 @@ mod tests {
         let selector = CarrierSelector::new();
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Permanent message".to_vec(),
         };
 
@@ -351,6 +391,29 @@ mod tests {
         assert!(!carrier.info().is_prunable);
     }
 
+    #[test]
+    fn test_utxo_cost() {
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: b"Permanent message".to_vec(),
+        };
+
+        let selector = CarrierSelector::new();
+        let op_return = selector.utxo_cost(CarrierType::OpReturn, &message).unwrap();
+        assert!(op_return.is_zero());
+
+        let stamps = selector.utxo_cost(CarrierType::Stamps, &message).unwrap();
+        assert!(stamps.outputs > 0);
+        assert!(stamps.value > 0);
+
+        let selector =
+            selector.with_carrier(crate::carrier::StampsCarrier::new().with_output_value(5_000));
+        assert_eq!(selector.carriers().len(), 5);
+        let custom = selector.utxo_cost(CarrierType::Stamps, &message).unwrap();
+        assert_eq!(custom.value, 5_000 * custom.outputs as u64);
+    }
+
     #[test]
     fn test_preferences_builder() {
         let prefs = CarrierPreferences::default()
@@ -22,13 +22,18 @@
 //! OP_N
 //! OP_CHECKMULTISIG
 //! ```
+//!
+//! Each output carries the smallest value Bitcoin Core relays for its script
+//! (about 786 sats for a 1-of-3) unless a higher value is configured with
+//! [`StampsCarrier::with_output_value`]. Every output stays in the UTXO set
+//! forever; [`Carrier::utxo_cost`] reports how many and how much.
 
 use bitcoin::script::Builder;
-use bitcoin::ScriptBuf;
+use bitcoin::{Script, ScriptBuf};
 
 use super::{
     Carrier, CarrierError, CarrierInfo, CarrierInput, CarrierOutput, CarrierResult, CarrierStatus,
-    CarrierType,
+    CarrierType, UtxoCost,
 };
 use crate::{encode_anchor_payload, is_anchor_payload, parse_anchor_payload, ParsedAnchorMessage};
 
@@ -37,6 +42,8 @@ use crate::{encode_anchor_payload, is_anchor_payload, parse_anchor_payload, Pars
 pub struct StampsCarrier {
     /// Maximum data keys per multisig script
     max_keys_per_script: usize,
+    /// Value for each multisig output; `None` uses the dust minimum
+    output_value: Option<u64>,
 }
 
 impl StampsCarrier {
@@ -64,6 +71,7 @@ impl StampsCarrier {
         Self {
             // Use 2 data keys + 1 burn key = 3 keys per script (1-of-3)
             max_keys_per_script: 2,
+            output_value: None,
         }
     }
 
@@ -71,9 +79,33 @@ impl StampsCarrier {
     pub fn with_keys_per_script(max_keys: usize) -> Self {
         Self {
             max_keys_per_script: max_keys.min(Self::MAX_MULTISIG_KEYS - 1),
+            output_value: None,
         }
     }
 
+    /// Lock `sats` in every multisig output instead of the dust minimum
+    ///
+    /// Values below an output's dust threshold are rejected when encoding.
+    pub fn with_output_value(mut self, sats: u64) -> Self {
+        self.output_value = Some(sats);
+        self
+    }
+
+    /// Split a payload into multisig output scripts
+    fn build_scripts(&self, payload: &[u8]) -> CarrierResult<Vec<ScriptBuf>> {
+        // Split payload into 31-byte chunks
+        let chunks: Vec<[u8; 33]> = payload
+            .chunks(Self::DATA_PER_CHUNK)
+            .map(Self::encode_chunk)
+            .collect();
+
+        // Group chunks into multisig scripts
+        chunks
+            .chunks(self.max_keys_per_script)
+            .map(|group| self.build_multisig_script(group))
+            .collect()
+    }
+
     /// Encode a data chunk as a fake public key
     ///
     /// Format: [prefix (0x02/0x03)] [31 bytes data]
@@ -209,17 +241,19 @@ impl Carrier for StampsCarrier {
             });
         }
 
-        // Split payload into 31-byte chunks
-        let chunks: Vec<[u8; 33]> = payload
-            .chunks(Self::DATA_PER_CHUNK)
-            .map(Self::encode_chunk)
-            .collect();
+        let scripts = self.build_scripts(&payload)?;
 
-        // Group chunks into multisig scripts
-        let scripts: Vec<ScriptBuf> = chunks
-            .chunks(self.max_keys_per_script)
-            .map(|group| self.build_multisig_script(group))
-            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(value) = self.output_value {
+            for script in &scripts {
+                let dust = script.minimal_non_dust().to_sat();
+                if value < dust {
+                    return Err(CarrierError::Custom(format!(
+                        "Stamps output value {} sats is below the dust threshold of {} sats",
+                        value, dust
+                    )));
+                }
+            }
+        }
 
         Ok(CarrierOutput::Stamps(scripts))
     }
@@ -287,12 +321,28 @@ impl Carrier for StampsCarrier {
         // No witness discount for bare multisig
         (total_size as f64 * fee_rate).ceil() as u64
     }
+
+    fn output_value(&self, script: &Script) -> u64 {
+        self.output_value
+            .unwrap_or_else(|| script.minimal_non_dust().to_sat())
+    }
+
+    fn utxo_cost(&self, payload_size: usize) -> UtxoCost {
+        // Script sizes only depend on the payload length
+        let scripts = self
+            .build_scripts(&vec![0; payload_size])
+            .unwrap_or_default();
+        UtxoCost {
+            outputs: scripts.len(),
+            value: scripts.iter().map(|s| self.output_value(s)).sum(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AnchorKind;
+    use crate::{AnchorKind, Anchors};
 
     #[test]
     fn test_carrier_info() {
@@ -331,7 +381,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Hello, Stamps!".to_vec(),
         };
 
@@ -353,7 +403,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Permanent ANCHOR message".to_vec(),
         };
 
@@ -376,6 +426,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_output_value_and_utxo_cost() {
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: vec![b'x'; 100],
+        };
+        let size = encode_anchor_payload(&message).len();
+
+        let carrier = StampsCarrier::new();
+        let CarrierOutput::Stamps(scripts) = carrier.encode(&message).unwrap() else {
+            panic!("Expected Stamps output");
+        };
+        let cost = carrier.utxo_cost(size);
+        assert_eq!(cost.outputs, scripts.len());
+        // Default values are each script's dust minimum
+        let dust: u64 = scripts.iter().map(|s| s.minimal_non_dust().to_sat()).sum();
+        assert_eq!(cost.value, dust);
+
+        let carrier = StampsCarrier::new().with_output_value(10_000);
+        assert_eq!(carrier.output_value(&scripts[0]), 10_000);
+        assert_eq!(carrier.utxo_cost(size).value, 10_000 * scripts.len() as u64);
+
+        let carrier = StampsCarrier::new().with_output_value(546);
+        assert!(carrier.encode(&message).is_err());
+    }
+
     #[test]
     fn test_fee_estimation_expensive() {
         let carrier = StampsCarrier::new();
@@ -0,0 +1,260 @@
+//! Carrier round-trip verification
+//!
+//! Decodes a transaction through every carrier, not just the first match,
+//! so callers can see every ANCHOR payload it carries and spot payloads that
+//! read differently depending on the carrier. Each decoded message is also
+//! re-encoded with the same carrier to check the on-chain bytes are exactly
+//! what an ANCHOR encoder would produce.
+
+use bitcoin::Transaction;
+
+use super::{CarrierInput, CarrierOutput, CarrierSelector, CarrierType};
+use crate::ParsedAnchorMessage;
+
+/// Where in a transaction a message was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageLocation {
+    /// Output script at this index (OP_RETURN, Stamps)
+    Output(u32),
+    /// Witness of the input at this index (Inscription, Annex, WitnessData)
+    Input(u32),
+    /// Spread over several inputs (multi-part WitnessData)
+    Transaction,
+}
+
+/// A message decoded by [`verify_carrier_roundtrip`]
+#[derive(Debug, Clone)]
+pub struct VerifiedAnchorMessage {
+    /// Carrier that decoded the message
+    pub carrier_type: CarrierType,
+    /// Where the message was found
+    pub location: MessageLocation,
+    /// The decoded message
+    pub message: ParsedAnchorMessage,
+    /// Re-encoding the message with the same carrier reproduces the data
+    /// found in the transaction
+    pub roundtrip: bool,
+    /// Other carriers that decoded a different message from the same location
+    pub conflicts: Vec<CarrierType>,
+}
+
+impl VerifiedAnchorMessage {
+    /// No other carrier reads this location differently
+    pub fn is_consistent(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Decode `tx` through every carrier and cross-check the results
+///
+/// Returns one entry per (carrier, location) that decoded, so a transaction
+/// with several payloads yields several entries.
+pub fn verify_carrier_roundtrip(tx: &Transaction) -> Vec<VerifiedAnchorMessage> {
+    CarrierSelector::new().verify_roundtrip(tx)
+}
+
+impl CarrierSelector {
+    /// Decode `tx` through every registered carrier and cross-check the results
+    ///
+    /// See [`verify_carrier_roundtrip`].
+    pub fn verify_roundtrip(&self, tx: &Transaction) -> Vec<VerifiedAnchorMessage> {
+        let mut found: Vec<(CarrierType, MessageLocation, ParsedAnchorMessage)> = Vec::new();
+
+        for carrier in self.carriers() {
+            let carrier_type = carrier.info().carrier_type;
+            match carrier_type {
+                CarrierType::OpReturn | CarrierType::Stamps => {
+                    for vout in 0..tx.output.len() as u32 {
+                        if let Ok(message) = carrier.decode(&CarrierInput::Transaction { tx, vout })
+                        {
+                            found.push((carrier_type, MessageLocation::Output(vout), message));
+                        }
+                    }
+                }
+                CarrierType::Inscription | CarrierType::TaprootAnnex | CarrierType::WitnessData => {
+                    for (index, input) in tx.input.iter().enumerate() {
+                        let witness: Vec<Vec<u8>> =
+                            input.witness.iter().map(|w| w.to_vec()).collect();
+                        if witness.is_empty() {
+                            continue;
+                        }
+                        if let Ok(message) = carrier.decode(&CarrierInput::Witness(&witness)) {
+                            found.push((
+                                carrier_type,
+                                MessageLocation::Input(index as u32),
+                                message,
+                            ));
+                        }
+                    }
+                }
+            }
+
+            if carrier_type == CarrierType::WitnessData {
+                if let Ok(message) = carrier.decode(&CarrierInput::Transaction { tx, vout: 0 }) {
+                    found.push((carrier_type, MessageLocation::Transaction, message));
+                }
+            }
+        }
+
+        found
+            .iter()
+            .map(|(carrier_type, location, message)| {
+                let conflicts = found
+                    .iter()
+                    .filter(|(other_type, other_location, other_message)| {
+                        other_type != carrier_type
+                            && other_location == location
+                            && other_message != message
+                    })
+                    .map(|(other_type, _, _)| *other_type)
+                    .collect();
+
+                VerifiedAnchorMessage {
+                    carrier_type: *carrier_type,
+                    location: *location,
+                    message: message.clone(),
+                    roundtrip: self.reencodes(tx, *carrier_type, *location, message),
+                    conflicts,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether re-encoding `message` reproduces the data at `location`
+    fn reencodes(
+        &self,
+        tx: &Transaction,
+        carrier_type: CarrierType,
+        location: MessageLocation,
+        message: &ParsedAnchorMessage,
+    ) -> bool {
+        let Some(output) = self
+            .get_carrier(carrier_type)
+            .and_then(|carrier| carrier.encode(message).ok())
+        else {
+            return false;
+        };
+
+        let encoded: Vec<Vec<u8>> = match output {
+            CarrierOutput::OpReturn(script) => vec![script.to_bytes()],
+            CarrierOutput::Stamps(scripts) => scripts.iter().map(|s| s.to_bytes()).collect(),
+            CarrierOutput::Inscription { reveal_script, .. } => vec![reveal_script.to_bytes()],
+            CarrierOutput::Annex(annex) => vec![annex],
+            CarrierOutput::WitnessData { scripts, .. } => {
+                scripts.iter().map(|s| s.to_bytes()).collect()
+            }
+        };
+
+        match location {
+            MessageLocation::Output(vout) => {
+                let start = vout as usize;
+                encoded.len() <= tx.output.len().saturating_sub(start)
+                    && encoded
+                        .iter()
+                        .zip(&tx.output[start..])
+                        .all(|(bytes, out)| out.script_pubkey.as_bytes() == bytes.as_slice())
+            }
+            MessageLocation::Input(index) => {
+                let witness = &tx.input[index as usize].witness;
+                encoded
+                    .iter()
+                    .all(|bytes| witness.iter().any(|item| item == bytes.as_slice()))
+            }
+            MessageLocation::Transaction => encoded.iter().all(|bytes| {
+                tx.input
+                    .iter()
+                    .any(|input| input.witness.iter().any(|item| item == bytes.as_slice()))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carrier::{Carrier, OpReturnCarrier, StampsCarrier};
+    use crate::{AnchorKind, Anchors};
+    use bitcoin::{absolute::LockTime, transaction::Version, Amount, ScriptBuf, TxOut};
+
+    fn message(body: &[u8]) -> ParsedAnchorMessage {
+        ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    fn tx(outputs: Vec<ScriptBuf>) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: outputs
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_op_return_roundtrip() {
+        let CarrierOutput::OpReturn(script) =
+            OpReturnCarrier::new().encode(&message(b"hi")).unwrap()
+        else {
+            panic!("Expected OP_RETURN output");
+        };
+
+        let verified = verify_carrier_roundtrip(&tx(vec![script]));
+        assert_eq!(verified.len(), 1);
+        assert_eq!(verified[0].carrier_type, CarrierType::OpReturn);
+        assert_eq!(verified[0].location, MessageLocation::Output(0));
+        assert_eq!(verified[0].message, message(b"hi"));
+        assert!(verified[0].roundtrip);
+        assert!(verified[0].is_consistent());
+    }
+
+    #[test]
+    fn test_multiple_payloads() {
+        let CarrierOutput::OpReturn(op_return) =
+            OpReturnCarrier::new().encode(&message(b"one")).unwrap()
+        else {
+            panic!("Expected OP_RETURN output");
+        };
+        let CarrierOutput::Stamps(stamps) = StampsCarrier::new().encode(&message(b"two")).unwrap()
+        else {
+            panic!("Expected Stamps output");
+        };
+
+        let mut outputs = vec![op_return];
+        outputs.extend(stamps);
+        let verified = verify_carrier_roundtrip(&tx(outputs));
+
+        assert_eq!(verified.len(), 2);
+        assert!(verified.iter().all(|m| m.is_consistent()));
+        assert!(verified
+            .iter()
+            .any(|m| m.carrier_type == CarrierType::Stamps
+                && m.location == MessageLocation::Output(1)));
+    }
+
+    #[test]
+    fn test_non_canonical_encoding() {
+        // Same payload pushed with OP_PUSHDATA1 instead of a direct push
+        let CarrierOutput::OpReturn(script) =
+            OpReturnCarrier::new().encode(&message(b"hi")).unwrap()
+        else {
+            panic!("Expected OP_RETURN output");
+        };
+        let payload = &script.as_bytes()[2..];
+        let mut bytes = vec![0x6a, 0x4c, payload.len() as u8];
+        bytes.extend_from_slice(payload);
+
+        let verified = verify_carrier_roundtrip(&tx(vec![ScriptBuf::from_bytes(bytes)]));
+        assert_eq!(verified.len(), 1);
+        assert_eq!(verified[0].message, message(b"hi"));
+        assert!(!verified[0].roundtrip);
+    }
+}
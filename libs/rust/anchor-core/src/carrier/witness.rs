@@ -27,9 +27,28 @@
 //!   [n-1]: <script>
 //!   [n]: <control_block>
 //! ```
+//!
+//! # Multi-part payloads
+//!
+//! Payloads larger than the part size are split across several tapscripts,
+//! one per reveal input of the same transaction. Each part script carries a
+//! 4-byte header after the marker with the part index and part count
+//! (both big-endian `u16`):
+//!
+//! ```text
+//!   <ANCHOR marker> OP_DROP
+//!   <index:2><total:2> OP_DROP
+//!   <data_chunk_1> OP_DROP
+//!   ...
+//!   OP_TRUE
+//! ```
+//!
+//! Decoding a transaction collects the parts from all of its inputs and
+//! concatenates them in index order. Payloads that fit in one part use the
+//! original single-script layout.
 
-use bitcoin::script::{Builder, PushBytesBuf};
-use bitcoin::ScriptBuf;
+use bitcoin::script::{Builder, Instruction, PushBytesBuf};
+use bitcoin::{ScriptBuf, Transaction};
 
 use super::{
     Carrier, CarrierError, CarrierInfo, CarrierInput, CarrierOutput, CarrierResult, CarrierStatus,
@@ -44,6 +63,19 @@ use crate::{encode_anchor_payload, is_anchor_payload, parse_anchor_payload, Pars
 pub struct WitnessCarrier {
     /// Maximum chunk size for push data
     chunk_size: usize,
+    /// Maximum payload bytes in one tapscript
+    part_size: usize,
+}
+
+/// One tapscript's share of a multi-part payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessPart {
+    /// Position of this part, starting at 0
+    pub index: u16,
+    /// Number of parts in the payload
+    pub total: u16,
+    /// Payload bytes carried by this part
+    pub data: Vec<u8>,
 }
 
 impl WitnessCarrier {
@@ -56,24 +88,81 @@ impl WitnessCarrier {
     /// Maximum witness data size (~4MB)
     pub const MAX_WITNESS_SIZE: usize = 4_000_000;
 
+    /// Default payload bytes per tapscript before splitting into parts
+    pub const DEFAULT_PART_SIZE: usize = 100_000;
+
+    /// Size of the index/total header in part scripts
+    pub const PART_HEADER_SIZE: usize = 4;
+
     /// Create a new witness carrier
     pub fn new() -> Self {
         Self {
             chunk_size: Self::MAX_PUSH_SIZE,
+            part_size: Self::DEFAULT_PART_SIZE,
         }
     }
 
     /// Create with custom chunk size
     pub fn with_chunk_size(chunk_size: usize) -> Self {
-        Self { chunk_size }
+        Self {
+            chunk_size,
+            part_size: Self::DEFAULT_PART_SIZE,
+        }
+    }
+
+    /// Split payloads larger than `part_size` bytes across reveal inputs
+    pub fn with_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    /// Number of tapscripts needed for a payload of the given size
+    pub fn part_count(&self, payload_size: usize) -> usize {
+        payload_size.div_ceil(self.part_size).max(1)
     }
 
     /// Build a Tapscript that contains ANCHOR data
     ///
     /// The script drops all data and returns true, making it always spendable.
     pub fn build_data_script(&self, message: &ParsedAnchorMessage) -> CarrierResult<ScriptBuf> {
+        self.build_script(None, &encode_anchor_payload(message))
+    }
+
+    /// Build one tapscript per reveal input for a message
+    ///
+    /// Returns a single script in the original layout when the payload fits
+    /// in one part, otherwise one headed part script per `part_size` bytes.
+    pub fn build_part_scripts(
+        &self,
+        message: &ParsedAnchorMessage,
+    ) -> CarrierResult<Vec<ScriptBuf>> {
         let payload = encode_anchor_payload(message);
+        let total = self.part_count(payload.len());
+        if total == 1 {
+            return Ok(vec![self.build_script(None, &payload)?]);
+        }
+
+        let total = u16::try_from(total).map_err(|_| {
+            CarrierError::Custom(format!("Payload needs {} parts, max {}", total, u16::MAX))
+        })?;
+        payload
+            .chunks(self.part_size)
+            .enumerate()
+            .map(|(index, data)| {
+                let mut header = [0u8; Self::PART_HEADER_SIZE];
+                header[..2].copy_from_slice(&(index as u16).to_be_bytes());
+                header[2..].copy_from_slice(&total.to_be_bytes());
+                self.build_script(Some(header), data)
+            })
+            .collect()
+    }
 
+    /// Build a data script, with a part header when `header` is set
+    fn build_script(
+        &self,
+        header: Option<[u8; Self::PART_HEADER_SIZE]>,
+        payload: &[u8],
+    ) -> CarrierResult<ScriptBuf> {
         let mut builder = Builder::new();
 
         // Push ANCHOR marker and drop it
@@ -83,6 +172,12 @@ impl WitnessCarrier {
             .push_slice(marker_push.as_push_bytes())
             .push_opcode(bitcoin::opcodes::all::OP_DROP);
 
+        if let Some(header) = header {
+            builder = builder
+                .push_slice(header)
+                .push_opcode(bitcoin::opcodes::all::OP_DROP);
+        }
+
         // Push payload in chunks and drop each
         for chunk in payload.chunks(self.chunk_size) {
             let chunk_push = PushBytesBuf::try_from(chunk.to_vec())
@@ -157,10 +252,71 @@ impl WitnessCarrier {
         Err(CarrierError::NotFound)
     }
 
+    /// Parse a part script of a multi-part payload
+    pub fn parse_part(data: &[u8]) -> Option<WitnessPart> {
+        let script = bitcoin::Script::from_bytes(data);
+        let mut pushes = script
+            .instructions()
+            .filter_map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes().to_vec()),
+                _ => None,
+            });
+
+        if pushes.next()? != Self::MARKER {
+            return None;
+        }
+        let header = pushes.next()?;
+        if header.len() != Self::PART_HEADER_SIZE {
+            return None;
+        }
+        let index = u16::from_be_bytes([header[0], header[1]]);
+        let total = u16::from_be_bytes([header[2], header[3]]);
+        if index >= total {
+            return None;
+        }
+
+        Some(WitnessPart {
+            index,
+            total,
+            data: pushes.flatten().collect(),
+        })
+    }
+
+    /// Reassemble a multi-part payload from all inputs of a transaction
+    pub fn reassemble(&self, tx: &Transaction) -> CarrierResult<ParsedAnchorMessage> {
+        let mut parts: Vec<WitnessPart> = tx
+            .input
+            .iter()
+            .filter_map(|input| input.witness.iter().find_map(Self::parse_part))
+            .collect();
+        if parts.is_empty() {
+            return Err(CarrierError::NotFound);
+        }
+
+        parts.sort_by_key(|part| part.index);
+        let total = parts[0].total;
+        let complete = parts.len() == total as usize
+            && parts
+                .iter()
+                .enumerate()
+                .all(|(i, part)| part.index as usize == i && part.total == total);
+        if !complete {
+            return Err(CarrierError::InvalidFormat(format!(
+                "Incomplete witness payload: {} of {} parts",
+                parts.len(),
+                total
+            )));
+        }
+
+        let payload: Vec<u8> = parts.into_iter().flat_map(|part| part.data).collect();
+        if !is_anchor_payload(&payload) {
+            return Err(CarrierError::NotAnchor);
+        }
+        parse_anchor_payload(&payload).map_err(CarrierError::from)
+    }
+
     /// Try to parse ANCHOR data from a script
     fn try_parse_script(&self, data: &[u8]) -> Option<ParsedAnchorMessage> {
-        use bitcoin::script::Instruction;
-
         let script = bitcoin::Script::from_bytes(data);
         let mut instructions = script.instructions();
 
@@ -232,15 +388,16 @@ impl Carrier for WitnessCarrier {
             });
         }
 
-        let script = self.build_data_script(message)?;
+        let scripts = self.build_part_scripts(message)?;
         let chunks = self.payload_to_chunks(message);
 
-        Ok(CarrierOutput::WitnessData { chunks, script })
+        Ok(CarrierOutput::WitnessData { chunks, scripts })
     }
 
     fn decode(&self, input: &CarrierInput) -> CarrierResult<ParsedAnchorMessage> {
         match input {
             CarrierInput::Witness(witness) => self.parse_witness(witness),
+            CarrierInput::Transaction { tx, .. } => self.reassemble(tx),
             CarrierInput::Bytes(data) => {
                 // Try as script
                 if let Some(msg) = self.try_parse_script(data) {
@@ -272,7 +429,14 @@ impl Carrier for WitnessCarrier {
         // Script overhead: MARKER OP_DROP + (chunk OP_DROP) * N + OP_TRUE
         let script_overhead = Self::MARKER.len() + 1 + (num_chunks * 2) + 1;
 
-        let total_witness = varint_overhead + Self::MARKER.len() + payload_size + script_overhead;
+        // Each extra part adds a reveal input (41 vB outside the witness),
+        // a control block and its own marker and header
+        let extra_parts = self.part_count(payload_size) - 1;
+        let part_overhead =
+            extra_parts * (41 * 4 + 33 + Self::MARKER.len() + Self::PART_HEADER_SIZE + 8);
+
+        let total_witness =
+            varint_overhead + Self::MARKER.len() + payload_size + script_overhead + part_overhead;
 
         // Witness discount: 1/4 weight
         let weight_units = total_witness;
@@ -285,7 +449,7 @@ impl Carrier for WitnessCarrier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::AnchorKind;
+    use crate::{AnchorKind, Anchors};
 
     #[test]
     fn test_carrier_info() {
@@ -305,7 +469,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Hello, witness!".to_vec(),
         };
 
@@ -329,7 +493,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Test message".to_vec(),
         };
 
@@ -348,16 +512,17 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Witness test".to_vec(),
         };
 
         let output = carrier.encode(&message).unwrap();
 
         match output {
-            CarrierOutput::WitnessData { chunks, script } => {
+            CarrierOutput::WitnessData { chunks, scripts } => {
                 assert!(!chunks.is_empty());
-                assert!(!script.is_empty());
+                assert_eq!(scripts.len(), 1);
+                assert!(!scripts[0].is_empty());
             }
             _ => panic!("Expected WitnessData output"),
         }
@@ -369,7 +534,7 @@ mod tests {
 
         let message = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![],
+            anchors: Anchors::new(),
             body: b"Raw payload test".to_vec(),
         };
 
@@ -382,6 +547,47 @@ mod tests {
         assert_eq!(decoded.body, message.body);
     }
 
+    #[test]
+    fn test_multi_part_roundtrip() {
+        use bitcoin::{absolute::LockTime, transaction::Version, TxIn, Witness};
+
+        let carrier = WitnessCarrier::new().with_part_size(1000);
+        let message = ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: vec![b'w'; 2500],
+        };
+
+        let CarrierOutput::WitnessData { scripts, .. } = carrier.encode(&message).unwrap() else {
+            panic!("Expected WitnessData output");
+        };
+        assert_eq!(scripts.len(), 3);
+
+        // A single part is not a message on its own
+        assert!(carrier
+            .decode(&CarrierInput::Bytes(scripts[0].as_bytes()))
+            .is_err());
+
+        // Reveal inputs in any order: script, control block
+        let input = |script: &ScriptBuf| TxIn {
+            witness: Witness::from_slice(&[script.as_bytes(), &[0xc0; 33]]),
+            ..Default::default()
+        };
+        let mut tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![input(&scripts[2]), input(&scripts[0]), input(&scripts[1])],
+            output: vec![],
+        };
+        let decoded = carrier
+            .decode(&CarrierInput::Transaction { tx: &tx, vout: 0 })
+            .unwrap();
+        assert_eq!(decoded, message);
+
+        tx.input.pop();
+        assert!(carrier.reassemble(&tx).is_err());
+    }
+
     #[test]
     fn test_fee_estimation_with_discount() {
         let carrier = WitnessCarrier::new();
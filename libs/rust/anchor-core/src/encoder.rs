@@ -3,7 +3,7 @@
 use bitcoin::script::{Builder, PushBytesBuf};
 use bitcoin::{ScriptBuf, Txid};
 
-use crate::{Anchor, AnchorKind, ParsedAnchorMessage, ANCHOR_MAGIC};
+use crate::{Anchor, AnchorKind, Anchors, ParsedAnchorMessage, ANCHOR_MAGIC};
 
 /// Encode an ANCHOR message to a raw payload
 pub fn encode_anchor_payload(message: &ParsedAnchorMessage) -> Vec<u8> {
@@ -47,7 +47,7 @@ pub fn create_anchor_script(message: &ParsedAnchorMessage) -> ScriptBuf {
 #[derive(Debug, Clone)]
 pub struct AnchorMessageBuilder {
     kind: AnchorKind,
-    anchors: Vec<Anchor>,
+    anchors: Anchors,
     body: Vec<u8>,
 }
 
@@ -56,7 +56,7 @@ impl AnchorMessageBuilder {
     pub fn new() -> Self {
         Self {
             kind: AnchorKind::Generic,
-            anchors: Vec::new(),
+            anchors: Anchors::new(),
             body: Vec::new(),
         }
     }
@@ -121,7 +121,7 @@ impl AnchorMessageBuilder {
     }
 
     /// Get the anchors (without consuming the builder)
-    pub fn get_anchors(&self) -> Vec<Anchor> {
+    pub fn get_anchors(&self) -> Anchors {
         self.anchors.clone()
     }
 
@@ -22,6 +22,14 @@ pub enum AnchorError {
         available_bytes: usize,
     },
 
+    /// Magic announces a major version this library cannot parse
+    #[error("unsupported protocol version {major}.{minor}")]
+    UnsupportedVersion { major: u8, minor: u8 },
+
+    /// Malformed TLV header area
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+
     /// Invalid anchor count
     #[error("invalid anchor count: {0}")]
     InvalidAnchorCount(u8),
@@ -0,0 +1,143 @@
+//! Compact filters of a block's ANCHOR activity
+//!
+//! An ANCHOR filter is a BIP-158 Golomb-coded set keyed by the block hash,
+//! like the basic block filter, but built from the block's ANCHOR
+//! transactions only. For each of them it holds:
+//!
+//! - the txid, in internal byte order
+//! - every output script other than OP_RETURN
+//! - the script spent by the first input (the creator), when known
+//! - the [`creator_commitment`] of the txid and that script
+//!
+//! A wallet restored from seed matches its scripts against the filters to
+//! find the few blocks holding its ANCHOR transactions, and the creator
+//! commitment tells it which transaction of a matched block it created
+//! without looking up the output that was spent.
+
+use bitcoin::bip158::BlockFilterWriter;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::{Block, Script, Transaction, Txid};
+
+pub use bitcoin::bip158::{BlockFilter, FilterHeader};
+
+/// Filter elements of one ANCHOR transaction
+pub fn anchor_filter_elements(tx: &Transaction, creator: Option<&Script>) -> Vec<Vec<u8>> {
+    let txid = tx.compute_txid();
+    let mut elements = vec![txid.to_byte_array().to_vec()];
+    elements.extend(
+        tx.output
+            .iter()
+            .filter(|output| !output.script_pubkey.is_op_return())
+            .map(|output| output.script_pubkey.to_bytes()),
+    );
+    if let Some(creator) = creator {
+        elements.push(creator.to_bytes());
+        elements.push(creator_commitment(&txid, creator).to_vec());
+    }
+    elements
+}
+
+/// Commitment to the script that created an ANCHOR transaction:
+/// `SHA256(txid || script)`
+pub fn creator_commitment(txid: &Txid, script: &Script) -> [u8; 32] {
+    let mut engine = sha256::Hash::engine();
+    engine.input(txid.as_byte_array());
+    engine.input(script.as_bytes());
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// ANCHOR filter of `block`, from its ANCHOR transactions and their
+/// creator scripts
+///
+/// A block without ANCHOR transactions gets an empty filter, which matches
+/// nothing.
+pub fn anchor_filter<'a>(
+    block: &Block,
+    txs: impl IntoIterator<Item = (&'a Transaction, Option<&'a Script>)>,
+) -> BlockFilter {
+    let mut content = Vec::new();
+    let mut writer = BlockFilterWriter::new(&mut content, block);
+    for (tx, creator) in txs {
+        for element in anchor_filter_elements(tx, creator) {
+            writer.add_element(&element);
+        }
+    }
+    writer.finish().expect("writing to a Vec never fails");
+    BlockFilter::new(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_anchor_script, AnchorKind, Anchors, ParsedAnchorMessage};
+    use bitcoin::block::{Header, Version as BlockVersion};
+    use bitcoin::{
+        absolute::LockTime, transaction::Version, Amount, BlockHash, CompactTarget, ScriptBuf,
+        TxMerkleNode, TxOut,
+    };
+
+    fn script(byte: u8) -> ScriptBuf {
+        ScriptBuf::from_bytes(vec![0x51, byte])
+    }
+
+    fn anchor_tx(change: ScriptBuf) -> Transaction {
+        let payload = create_anchor_script(&ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: b"hello".to_vec(),
+        });
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![Default::default()],
+            output: [payload, change]
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: Amount::from_sat(1_000),
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    fn block(txdata: Vec<Transaction>) -> Block {
+        Block {
+            header: Header {
+                version: BlockVersion::TWO,
+                prev_blockhash: BlockHash::all_zeros(),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 0,
+                bits: CompactTarget::from_consensus(0x207fffff),
+                nonce: 0,
+            },
+            txdata,
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_anchor_activity() {
+        let tx = anchor_tx(script(1));
+        let txid = tx.compute_txid();
+        let creator = script(2);
+        let block = block(vec![tx.clone()]);
+        let filter = anchor_filter(&block, [(&tx, Some(creator.as_script()))]);
+
+        let hash = block.block_hash();
+        let matches = |element: &[u8]| filter.match_any(&hash, std::iter::once(element)).unwrap();
+        assert!(matches(script(1).as_bytes()));
+        assert!(matches(creator.as_bytes()));
+        assert!(matches(txid.as_byte_array()));
+        assert!(matches(&creator_commitment(&txid, &creator)));
+        assert!(!matches(script(3).as_bytes()));
+        assert!(!matches(&creator_commitment(&txid, &script(1))));
+    }
+
+    #[test]
+    fn test_empty_filter_matches_nothing() {
+        let block = block(vec![anchor_tx(script(1))]);
+        let filter = anchor_filter(&block, []);
+        assert!(!filter
+            .match_any(&block.block_hash(), std::iter::once(script(1).as_bytes()))
+            .unwrap());
+    }
+}
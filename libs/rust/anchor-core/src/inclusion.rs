@@ -0,0 +1,222 @@
+//! Compact proofs that a transaction is included in a block
+//!
+//! An [`InclusionProof`] is the merkle branch from a transaction to its
+//! block's merkle root. Together with a chain of block headers it lets a
+//! light client check that an ANCHOR message was mined without a full node.
+
+use bitcoin::block::Header;
+use bitcoin::hashes::{Hash, HashEngine};
+use bitcoin::{Block, BlockHash, TxMerkleNode, Txid};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Merkle branch from a transaction to its block header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// Transaction being proven
+    pub txid: Txid,
+    /// Block containing the transaction
+    pub block_hash: BlockHash,
+    /// Position of the transaction in the block
+    pub index: u32,
+    /// Sibling hashes from the leaf up to (but not including) the root
+    pub branch: Vec<TxMerkleNode>,
+}
+
+/// Reasons an [`InclusionProof`] fails to verify
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InclusionError {
+    /// The branch does not hash to the header's merkle root
+    #[error("merkle branch does not match the block's merkle root")]
+    MerkleRootMismatch,
+
+    /// The proof's block is not in the header chain
+    #[error("block {0} is not in the header chain")]
+    BlockNotInChain(BlockHash),
+
+    /// A header does not point at the one before it
+    #[error("header {0} does not extend the previous header")]
+    BrokenChain(BlockHash),
+
+    /// A header's hash does not meet its own target
+    #[error("header {0} has insufficient proof of work")]
+    InvalidProofOfWork(BlockHash),
+}
+
+impl InclusionProof {
+    /// Build the proof for `txid` from a full block
+    ///
+    /// Returns `None` if the transaction is not in the block.
+    pub fn from_block(block: &Block, txid: &Txid) -> Option<Self> {
+        let mut level: Vec<TxMerkleNode> = block
+            .txdata
+            .iter()
+            .map(|tx| TxMerkleNode::from_raw_hash(tx.compute_txid().to_raw_hash()))
+            .collect();
+        let index = block
+            .txdata
+            .iter()
+            .position(|tx| tx.compute_txid() == *txid)?;
+
+        let mut branch = Vec::new();
+        let mut position = index;
+        while level.len() > 1 {
+            // Odd levels pair the last node with itself
+            let sibling = (position ^ 1).min(level.len() - 1);
+            branch.push(level[sibling]);
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            position /= 2;
+        }
+
+        Some(Self {
+            txid: *txid,
+            block_hash: block.block_hash(),
+            index: index as u32,
+            branch,
+        })
+    }
+
+    /// Merkle root implied by the branch
+    pub fn merkle_root(&self) -> TxMerkleNode {
+        let mut node = TxMerkleNode::from_raw_hash(self.txid.to_raw_hash());
+        let mut position = self.index;
+        for sibling in &self.branch {
+            node = if position & 1 == 1 {
+                hash_pair(sibling, &node)
+            } else {
+                hash_pair(&node, sibling)
+            };
+            position >>= 1;
+        }
+        node
+    }
+}
+
+/// Verify `proof` against a chain of consecutive block headers
+///
+/// The headers must be in height order, each extending the one before and
+/// each meeting its own proof-of-work target. Returns the number of
+/// confirmations the transaction has within the chain (1 if its block is the
+/// last header).
+pub fn verify_inclusion(
+    proof: &InclusionProof,
+    header_chain: &[Header],
+) -> Result<u32, InclusionError> {
+    for (i, header) in header_chain.iter().enumerate() {
+        let hash = header.block_hash();
+        if i > 0 && header.prev_blockhash != header_chain[i - 1].block_hash() {
+            return Err(InclusionError::BrokenChain(hash));
+        }
+        if header.validate_pow(header.target()).is_err() {
+            return Err(InclusionError::InvalidProofOfWork(hash));
+        }
+    }
+
+    let position = header_chain
+        .iter()
+        .position(|header| header.block_hash() == proof.block_hash)
+        .ok_or(InclusionError::BlockNotInChain(proof.block_hash))?;
+
+    if header_chain[position].merkle_root != proof.merkle_root() {
+        return Err(InclusionError::MerkleRootMismatch);
+    }
+
+    Ok((header_chain.len() - position) as u32)
+}
+
+fn hash_pair(left: &TxMerkleNode, right: &TxMerkleNode) -> TxMerkleNode {
+    let mut engine = TxMerkleNode::engine();
+    engine.input(left.as_byte_array());
+    engine.input(right.as_byte_array());
+    TxMerkleNode::from_engine(engine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::block::Version as BlockVersion;
+    use bitcoin::{
+        absolute::LockTime, transaction::Version, Amount, CompactTarget, ScriptBuf, Transaction,
+        TxOut,
+    };
+
+    fn tx(n: u64) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::from_sat(n),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    /// Mine a regtest-difficulty block on top of `prev`
+    fn block(prev: BlockHash, txs: usize) -> Block {
+        let mut block = Block {
+            header: Header {
+                version: BlockVersion::TWO,
+                prev_blockhash: prev,
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 0,
+                bits: CompactTarget::from_consensus(0x207fffff),
+                nonce: 0,
+            },
+            txdata: (0..txs as u64).map(tx).collect(),
+        };
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        while block.header.validate_pow(block.header.target()).is_err() {
+            block.header.nonce += 1;
+        }
+        block
+    }
+
+    #[test]
+    fn test_branch_matches_merkle_root() {
+        for txs in 1..=7 {
+            let block = block(BlockHash::all_zeros(), txs);
+            for tx in &block.txdata {
+                let proof = InclusionProof::from_block(&block, &tx.compute_txid()).unwrap();
+                assert_eq!(proof.merkle_root(), block.header.merkle_root);
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        let first = block(BlockHash::all_zeros(), 5);
+        let second = block(first.block_hash(), 1);
+        let txid = first.txdata[3].compute_txid();
+        let proof = InclusionProof::from_block(&first, &txid).unwrap();
+
+        assert_eq!(
+            verify_inclusion(&proof, &[first.header, second.header]),
+            Ok(2)
+        );
+        assert_eq!(
+            verify_inclusion(&proof, &[second.header]),
+            Err(InclusionError::BlockNotInChain(first.block_hash()))
+        );
+        assert_eq!(
+            verify_inclusion(&proof, &[second.header, first.header]),
+            Err(InclusionError::BrokenChain(first.block_hash()))
+        );
+
+        let mut wrong_index = proof.clone();
+        wrong_index.index = 2;
+        assert_eq!(
+            verify_inclusion(&wrong_index, &[first.header]),
+            Err(InclusionError::MerkleRootMismatch)
+        );
+    }
+
+    #[test]
+    fn test_missing_transaction() {
+        let block = block(BlockHash::all_zeros(), 2);
+        assert!(InclusionProof::from_block(&block, &tx(99).compute_txid()).is_none());
+    }
+}
@@ -9,6 +9,9 @@
 //!   Stamps, Taproot Annex, or Witness Data
 //! - **Message chaining**: Reference parent messages via compact 64-bit anchors
 //! - **Extensible kinds**: Support for text, images, state updates, votes, and more
+//! - **Version negotiation**: [`ProtocolVersion`] is read from the magic bytes, and
+//!   [`parse_versioned_payload`] accepts newer v1 minor revisions with a TLV
+//!   header area instead of rejecting them
 //!
 //! # Example
 //!
@@ -30,17 +33,23 @@
 pub mod carrier;
 mod encoder;
 mod error;
+mod filter;
+mod inclusion;
 mod parser;
 mod types;
+mod version;
 
 pub use encoder::*;
 pub use error::*;
+pub use filter::*;
+pub use inclusion::*;
 pub use parser::*;
 pub use types::*;
+pub use version::*;
 
 /// ANCHOR v1 magic bytes: 0xA11C0001
 /// - 0xA11C = "ANCH" in leetspeak
-/// - 0x0001 = version 1
+/// - 0x0001 = version 1.0 (minor revision 0x00, major version 0x01)
 pub const ANCHOR_MAGIC: [u8; 4] = [0xA1, 0x1C, 0x00, 0x01];
 
 /// Size of the txid prefix in bytes (64 bits)
@@ -61,6 +70,7 @@ mod tests {
     #[allow(unused_imports)]
     use bitcoin::hashes::Hash;
     use bitcoin::Txid;
+    use smallvec::smallvec;
     use std::str::FromStr;
 
     #[test]
@@ -112,7 +122,7 @@ mod tests {
     fn test_encode_decode_roundtrip() {
         let original = ParsedAnchorMessage {
             kind: AnchorKind::Text,
-            anchors: vec![Anchor {
+            anchors: smallvec![Anchor {
                 txid_prefix: [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00, 0x11],
                 vout: 1,
             }],
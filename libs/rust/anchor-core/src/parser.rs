@@ -5,7 +5,7 @@ use bitcoin::script::Instruction;
 use bitcoin::{Script, Transaction, Txid};
 
 use crate::{
-    Anchor, AnchorError, AnchorKind, ParsedAnchorMessage, ANCHOR_MAGIC, ANCHOR_SIZE,
+    Anchor, AnchorError, AnchorKind, Anchors, ParsedAnchorMessage, ANCHOR_MAGIC, ANCHOR_SIZE,
     MIN_PAYLOAD_SIZE, TXID_PREFIX_SIZE,
 };
 
@@ -47,7 +47,7 @@ pub fn parse_anchor_payload(data: &[u8]) -> Result<ParsedAnchorMessage, AnchorEr
     }
 
     // Parse anchors
-    let mut anchors = Vec::with_capacity(anchor_count);
+    let mut anchors = Anchors::with_capacity(anchor_count);
     for i in 0..anchor_count {
         let offset = header_size + i * ANCHOR_SIZE;
         let prefix_bytes = &data[offset..offset + TXID_PREFIX_SIZE];
@@ -189,6 +189,8 @@ mod tests {
         assert_eq!(msg.anchors.len(), 2);
         assert_eq!(msg.anchors[0].vout, 0);
         assert_eq!(msg.anchors[1].vout, 1);
+        // Up to INLINE_ANCHORS anchors need no heap allocation
+        assert!(!msg.anchors.spilled());
     }
 
     #[test]
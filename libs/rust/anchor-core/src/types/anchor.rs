@@ -2,9 +2,18 @@
 
 use bitcoin::Txid;
 use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 
 use super::serde_helpers::hex_array_8;
 
+/// Anchors a message holds without a heap allocation; most have 0–2
+pub const INLINE_ANCHORS: usize = 2;
+
+/// The anchors of one message, stored inline up to [`INLINE_ANCHORS`]
+///
+/// Derefs to `[Anchor]` and serializes as a sequence, like a `Vec`.
+pub type Anchors = SmallVec<[Anchor; INLINE_ANCHORS]>;
+
 /// A compact reference to a parent message
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Anchor {
@@ -16,6 +16,10 @@ pub enum AnchorKind {
     Vote = 3,
     /// Image (body is raw image bytes: PNG, JPEG, GIF, WebP)
     Image = 4,
+    /// Identity key rotation, signed by the old key
+    Identity = 6,
+    /// Copy of another message on a different carrier, anchored to it
+    Mirror = 7,
 
     // Oracle types (30-39)
     /// Oracle registration/update
@@ -27,7 +31,7 @@ pub enum AnchorKind {
     /// Slash oracle stake (after dispute resolution)
     OracleSlash = 33,
 
-    // Prediction Market types (40-49)
+    // Prediction Market types (40-44)
     /// Create a new prediction market
     MarketCreate = 40,
     /// Place a bet on a market outcome
@@ -36,6 +40,18 @@ pub enum AnchorKind {
     MarketResolve = 42,
     /// Claim winnings from resolved market
     ClaimWinnings = 43,
+    /// Limit order intent (place, cancel, or settle a fill)
+    MarketOrder = 44,
+
+    // Lottery types (45-48)
+    /// Create a lottery, committing to its draw secret
+    LotteryCreate = 45,
+    /// Buy lottery tickets
+    LotteryTicket = 46,
+    /// Reveal the draw secret
+    LotteryDraw = 47,
+    /// Claim a lottery prize or refund
+    LotteryClaim = 48,
 
     /// Custom type (value 5-255, excluding reserved ranges)
     Custom(u8),
@@ -49,6 +65,8 @@ impl From<u8> for AnchorKind {
             2 => AnchorKind::State,
             3 => AnchorKind::Vote,
             4 => AnchorKind::Image,
+            6 => AnchorKind::Identity,
+            7 => AnchorKind::Mirror,
             // Oracle types
             30 => AnchorKind::Oracle,
             31 => AnchorKind::OracleAttestation,
@@ -59,6 +77,12 @@ impl From<u8> for AnchorKind {
             41 => AnchorKind::PlaceBet,
             42 => AnchorKind::MarketResolve,
             43 => AnchorKind::ClaimWinnings,
+            44 => AnchorKind::MarketOrder,
+            // Lottery types
+            45 => AnchorKind::LotteryCreate,
+            46 => AnchorKind::LotteryTicket,
+            47 => AnchorKind::LotteryDraw,
+            48 => AnchorKind::LotteryClaim,
             n => AnchorKind::Custom(n),
         }
     }
@@ -72,6 +96,8 @@ impl From<AnchorKind> for u8 {
             AnchorKind::State => 2,
             AnchorKind::Vote => 3,
             AnchorKind::Image => 4,
+            AnchorKind::Identity => 6,
+            AnchorKind::Mirror => 7,
             // Oracle types
             AnchorKind::Oracle => 30,
             AnchorKind::OracleAttestation => 31,
@@ -82,6 +108,12 @@ impl From<AnchorKind> for u8 {
             AnchorKind::PlaceBet => 41,
             AnchorKind::MarketResolve => 42,
             AnchorKind::ClaimWinnings => 43,
+            AnchorKind::MarketOrder => 44,
+            // Lottery types
+            AnchorKind::LotteryCreate => 45,
+            AnchorKind::LotteryTicket => 46,
+            AnchorKind::LotteryDraw => 47,
+            AnchorKind::LotteryClaim => 48,
             AnchorKind::Custom(n) => n,
         }
     }
@@ -3,7 +3,7 @@
 use bitcoin::Txid;
 use serde::{Deserialize, Serialize};
 
-use super::anchor::Anchor;
+use super::anchor::{Anchor, Anchors};
 use super::kind::AnchorKind;
 use super::serde_helpers::{hex_array_8, hex_bytes, option_txid_hex, txid_hex};
 
@@ -13,7 +13,7 @@ pub struct ParsedAnchorMessage {
     /// Message type
     pub kind: AnchorKind,
     /// References to parent messages
-    pub anchors: Vec<Anchor>,
+    pub anchors: Anchors,
     /// Message body (opaque bytes)
     #[serde(with = "hex_bytes")]
     pub body: Vec<u8>,
@@ -24,7 +24,7 @@ impl ParsedAnchorMessage {
     pub fn new_root(kind: AnchorKind, body: Vec<u8>) -> Self {
         Self {
             kind,
-            anchors: Vec::new(),
+            anchors: Anchors::new(),
             body,
         }
     }
@@ -33,7 +33,7 @@ impl ParsedAnchorMessage {
     pub fn new_reply(kind: AnchorKind, parent_txid: &Txid, parent_vout: u8, body: Vec<u8>) -> Self {
         Self {
             kind,
-            anchors: vec![Anchor::from_txid(parent_txid, parent_vout)],
+            anchors: smallvec::smallvec![Anchor::from_txid(parent_txid, parent_vout)],
             body,
         }
     }
@@ -52,6 +52,17 @@ impl ParsedAnchorMessage {
     pub fn body_as_text(&self) -> Option<&str> {
         std::str::from_utf8(&self.body).ok()
     }
+
+    /// The body of a Text message; `None` for other kinds or invalid UTF-8
+    ///
+    /// Unlike [`body_as_text`](Self::body_as_text), this doesn't read binary
+    /// bodies of other kinds that happen to be valid UTF-8 as text.
+    pub fn as_text(&self) -> Option<&str> {
+        match self.kind {
+            AnchorKind::Text => self.body_as_text(),
+            _ => None,
+        }
+    }
 }
 
 /// A fully indexed ANCHOR message with blockchain context
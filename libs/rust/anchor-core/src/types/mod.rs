@@ -14,7 +14,7 @@ pub mod serde_helpers;
 mod thread;
 
 // Re-export all public types
-pub use anchor::Anchor;
+pub use anchor::{Anchor, Anchors, INLINE_ANCHORS};
 pub use kind::AnchorKind;
 pub use message::{IndexedAnchorMessage, ParsedAnchorMessage, ResolvedAnchor};
 pub use thread::{Thread, ThreadNode};
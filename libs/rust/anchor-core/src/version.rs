@@ -0,0 +1,377 @@
+//! Protocol version negotiation
+//!
+//! The last two magic bytes carry the protocol version: byte 3 is the major
+//! version and byte 2 the minor revision, so every existing message
+//! (`A1 1C 00 01`) is v1.0. Minor revisions of v1 (`A1 1C 01 01`, ...) add
+//! a length-prefixed TLV header area right after the magic, before the kind:
+//!
+//! ```text
+//! magic (4) | header_len (1) | TLVs (header_len bytes) | kind | anchor_count | anchors | body
+//! ```
+//!
+//! Each TLV is `type (1) | len (1) | value (len)`. Because the area is
+//! length-prefixed, a parser that does not know a field can skip it.
+//! Following the "it's OK to be odd" rule, odd types are optional and even
+//! types must be understood for the message to be interpreted correctly.
+//! Unknown minor versions are parsed with the same layout and flagged
+//! rather than rejected; unknown major versions are rejected.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parse_anchor_payload, Anchor, AnchorError, AnchorKind, ParsedAnchorMessage, ANCHOR_SIZE,
+    TXID_PREFIX_SIZE,
+};
+
+/// First two magic bytes, shared by every protocol version
+pub const ANCHOR_MAGIC_PREFIX: [u8; 2] = [0xA1, 0x1C];
+
+/// Maximum size of the TLV header area
+pub const MAX_HEADER_SIZE: usize = 255;
+
+/// An ANCHOR protocol version, as carried in the magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl ProtocolVersion {
+    /// The original format (`0xA11C0001`), without a header area
+    pub const V1: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+
+    /// v1 with the TLV header area
+    pub const V1_1: ProtocolVersion = ProtocolVersion { major: 1, minor: 1 };
+
+    /// Newest version this library understands fully
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V1_1;
+
+    /// Read the version from the first four payload bytes
+    pub fn from_magic(magic: &[u8]) -> Result<Self, AnchorError> {
+        if magic.len() < 4 || magic[0..2] != ANCHOR_MAGIC_PREFIX {
+            return Err(AnchorError::InvalidMagic);
+        }
+        Ok(ProtocolVersion {
+            major: magic[3],
+            minor: magic[2],
+        })
+    }
+
+    /// The four magic bytes announcing this version
+    pub fn magic(&self) -> [u8; 4] {
+        [
+            ANCHOR_MAGIC_PREFIX[0],
+            ANCHOR_MAGIC_PREFIX[1],
+            self.minor,
+            self.major,
+        ]
+    }
+
+    /// Whether payloads of this version carry the TLV header area
+    pub fn has_header(&self) -> bool {
+        *self != ProtocolVersion::V1
+    }
+
+    /// Whether this library knows every rule of this version
+    pub fn is_known(&self) -> bool {
+        self.major == ProtocolVersion::LATEST.major && self.minor <= ProtocolVersion::LATEST.minor
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}.{}", self.major, self.minor)
+    }
+}
+
+/// A field from the TLV header area
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderField {
+    pub field_type: u8,
+    #[serde(with = "crate::serde_helpers::hex_bytes")]
+    pub value: Vec<u8>,
+}
+
+impl HeaderField {
+    pub fn new(field_type: u8, value: Vec<u8>) -> Self {
+        Self { field_type, value }
+    }
+
+    /// Even types must be understood by the reader
+    pub fn is_required(&self) -> bool {
+        self.field_type.is_multiple_of(2)
+    }
+}
+
+/// A message parsed together with its version and header fields
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionedMessage {
+    pub version: ProtocolVersion,
+    /// Header fields in payload order (empty for v1.0)
+    pub header: Vec<HeaderField>,
+    pub message: ParsedAnchorMessage,
+}
+
+impl VersionedMessage {
+    /// A newer minor revision than this library knows; the message parsed,
+    /// but it may carry semantics that are not applied here
+    pub fn is_unknown_minor(&self) -> bool {
+        !self.version.is_known()
+    }
+
+    /// Required header fields whose type is not in `known_types`
+    pub fn unknown_required_fields<'a>(
+        &'a self,
+        known_types: &'a [u8],
+    ) -> impl Iterator<Item = &'a HeaderField> + 'a {
+        self.header
+            .iter()
+            .filter(move |f| f.is_required() && !known_types.contains(&f.field_type))
+    }
+
+    /// First header field of the given type
+    pub fn field(&self, field_type: u8) -> Option<&HeaderField> {
+        self.header.iter().find(|f| f.field_type == field_type)
+    }
+}
+
+/// Parse a payload of any supported major version
+///
+/// v1.0 payloads parse exactly as [`parse_anchor_payload`]. Payloads with a
+/// newer minor revision are accepted and flagged via
+/// [`VersionedMessage::is_unknown_minor`].
+pub fn parse_versioned_payload(data: &[u8]) -> Result<VersionedMessage, AnchorError> {
+    if data.len() < 4 {
+        return Err(AnchorError::PayloadTooShort);
+    }
+    let version = ProtocolVersion::from_magic(data)?;
+    if version.major != ProtocolVersion::LATEST.major {
+        return Err(AnchorError::UnsupportedVersion {
+            major: version.major,
+            minor: version.minor,
+        });
+    }
+
+    if !version.has_header() {
+        return Ok(VersionedMessage {
+            version,
+            header: Vec::new(),
+            message: parse_anchor_payload(data)?,
+        });
+    }
+
+    let header_len = *data.get(4).ok_or(AnchorError::PayloadTooShort)? as usize;
+    let header_end = 5 + header_len;
+    let area = data
+        .get(5..header_end)
+        .ok_or_else(|| AnchorError::InvalidHeader("header area exceeds payload".to_string()))?;
+    let header = parse_header(area)?;
+
+    let rest = &data[header_end..];
+    if rest.len() < 2 {
+        return Err(AnchorError::PayloadTooShort);
+    }
+    let kind = AnchorKind::from(rest[0]);
+    let anchor_count = rest[1] as usize;
+    let anchors_end = 2 + anchor_count * ANCHOR_SIZE;
+    if rest.len() < anchors_end {
+        return Err(AnchorError::TruncatedAnchors {
+            expected: anchor_count,
+            available_bytes: rest.len() - 2,
+        });
+    }
+
+    let anchors = rest[2..anchors_end]
+        .chunks_exact(ANCHOR_SIZE)
+        .map(|chunk| {
+            let mut txid_prefix = [0u8; 8];
+            txid_prefix.copy_from_slice(&chunk[..TXID_PREFIX_SIZE]);
+            Anchor {
+                txid_prefix,
+                vout: chunk[TXID_PREFIX_SIZE],
+            }
+        })
+        .collect();
+
+    Ok(VersionedMessage {
+        version,
+        header,
+        message: ParsedAnchorMessage {
+            kind,
+            anchors,
+            body: rest[anchors_end..].to_vec(),
+        },
+    })
+}
+
+/// Encode a message for a specific version
+///
+/// v1.0 output is identical to [`encode_anchor_payload`](crate::encode_anchor_payload)
+/// and cannot carry header fields.
+pub fn encode_versioned_payload(
+    version: ProtocolVersion,
+    header: &[HeaderField],
+    message: &ParsedAnchorMessage,
+) -> Result<Vec<u8>, AnchorError> {
+    if version.major != ProtocolVersion::LATEST.major {
+        return Err(AnchorError::UnsupportedVersion {
+            major: version.major,
+            minor: version.minor,
+        });
+    }
+    if !version.has_header() {
+        if !header.is_empty() {
+            return Err(AnchorError::InvalidHeader(
+                "v1.0 payloads cannot carry header fields".to_string(),
+            ));
+        }
+        return Ok(crate::encode_anchor_payload(message));
+    }
+
+    let mut area = Vec::new();
+    for field in header {
+        let len = u8::try_from(field.value.len()).map_err(|_| {
+            AnchorError::InvalidHeader(format!("field {} is too long", field.field_type))
+        })?;
+        area.push(field.field_type);
+        area.push(len);
+        area.extend_from_slice(&field.value);
+    }
+    if area.len() > MAX_HEADER_SIZE {
+        return Err(AnchorError::InvalidHeader(format!(
+            "header area is {} bytes (max {})",
+            area.len(),
+            MAX_HEADER_SIZE
+        )));
+    }
+
+    let mut payload = Vec::with_capacity(
+        4 + 1 + area.len() + 2 + message.anchors.len() * ANCHOR_SIZE + message.body.len(),
+    );
+    payload.extend_from_slice(&version.magic());
+    payload.push(area.len() as u8);
+    payload.extend_from_slice(&area);
+    payload.push(u8::from(message.kind));
+    payload.push(message.anchors.len() as u8);
+    for anchor in &message.anchors {
+        payload.extend_from_slice(&anchor.txid_prefix);
+        payload.push(anchor.vout);
+    }
+    payload.extend_from_slice(&message.body);
+    Ok(payload)
+}
+
+fn parse_header(mut area: &[u8]) -> Result<Vec<HeaderField>, AnchorError> {
+    let mut fields = Vec::new();
+    while !area.is_empty() {
+        if area.len() < 2 {
+            return Err(AnchorError::InvalidHeader("truncated field".to_string()));
+        }
+        let (field_type, len) = (area[0], area[1] as usize);
+        let value = area.get(2..2 + len).ok_or_else(|| {
+            AnchorError::InvalidHeader(format!("field {} exceeds header area", field_type))
+        })?;
+        fields.push(HeaderField::new(field_type, value.to_vec()));
+        area = &area[2 + len..];
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode_anchor_payload, ANCHOR_MAGIC};
+    use smallvec::smallvec;
+
+    fn reply() -> ParsedAnchorMessage {
+        ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: smallvec![Anchor {
+                txid_prefix: [1, 2, 3, 4, 5, 6, 7, 8],
+                vout: 2,
+            }],
+            body: b"hi".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_v1_magic_is_v1_0() {
+        let version = ProtocolVersion::from_magic(&ANCHOR_MAGIC).unwrap();
+        assert_eq!(version, ProtocolVersion::V1);
+        assert_eq!(version.magic(), ANCHOR_MAGIC);
+        assert_eq!(version.to_string(), "v1.0");
+
+        let payload = encode_anchor_payload(&reply());
+        let parsed = parse_versioned_payload(&payload).unwrap();
+        assert!(parsed.header.is_empty());
+        assert!(!parsed.is_unknown_minor());
+        assert_eq!(parsed.message, reply());
+        assert_eq!(
+            encode_versioned_payload(ProtocolVersion::V1, &[], &reply()).unwrap(),
+            payload
+        );
+    }
+
+    #[test]
+    fn test_header_roundtrip() {
+        let header = vec![HeaderField::new(1, vec![0xff]), HeaderField::new(4, vec![])];
+        let payload = encode_versioned_payload(ProtocolVersion::V1_1, &header, &reply()).unwrap();
+        assert_eq!(&payload[..6], &[0xA1, 0x1C, 0x01, 0x01, 5, 1]);
+
+        // Strict v1 parsers reject rather than misread the payload
+        assert_eq!(
+            parse_anchor_payload(&payload),
+            Err(AnchorError::InvalidMagic)
+        );
+
+        let parsed = parse_versioned_payload(&payload).unwrap();
+        assert_eq!(parsed.version, ProtocolVersion::V1_1);
+        assert_eq!(parsed.header, header);
+        assert_eq!(parsed.message, reply());
+        assert_eq!(parsed.field(1).unwrap().value, vec![0xff]);
+        let unknown: Vec<u8> = parsed
+            .unknown_required_fields(&[])
+            .map(|f| f.field_type)
+            .collect();
+        assert_eq!(unknown, vec![4]);
+    }
+
+    #[test]
+    fn test_unknown_minor_is_flagged() {
+        let version = ProtocolVersion { major: 1, minor: 7 };
+        let payload =
+            encode_versioned_payload(version, &[HeaderField::new(9, vec![1, 2])], &reply())
+                .unwrap();
+        let parsed = parse_versioned_payload(&payload).unwrap();
+        assert!(parsed.is_unknown_minor());
+        assert_eq!(parsed.message, reply());
+    }
+
+    #[test]
+    fn test_rejections() {
+        assert_eq!(
+            parse_versioned_payload(&[0xA1, 0x1C, 0x00, 0x02, 0x01, 0x00]),
+            Err(AnchorError::UnsupportedVersion { major: 2, minor: 0 })
+        );
+        assert_eq!(
+            parse_versioned_payload(&[0x00, 0x1C, 0x00, 0x01, 0x01, 0x00]),
+            Err(AnchorError::InvalidMagic)
+        );
+        // Header length runs past the payload
+        assert!(matches!(
+            parse_versioned_payload(&[0xA1, 0x1C, 0x01, 0x01, 9, 1, 0]),
+            Err(AnchorError::InvalidHeader(_))
+        ));
+        // Field length runs past the header area
+        assert!(matches!(
+            parse_versioned_payload(&[0xA1, 0x1C, 0x01, 0x01, 2, 1, 5, 1, 0]),
+            Err(AnchorError::InvalidHeader(_))
+        ));
+        assert!(encode_versioned_payload(
+            ProtocolVersion::V1,
+            &[HeaderField::new(1, vec![])],
+            &reply()
+        )
+        .is_err());
+    }
+}
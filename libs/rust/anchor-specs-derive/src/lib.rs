@@ -0,0 +1,462 @@
+//! Derive macros for ANCHOR kind specifications
+//!
+//! This crate provides `#[derive(KindSpec)]` and `#[derive(SpecRecord)]`,
+//! which generate the binary codec and basic validation for a spec struct
+//! from per-field attributes. Use them through the `anchor-specs` re-exports:
+//!
+//! ```rust,ignore
+//! use anchor_specs::prelude::*;
+//!
+//! #[derive(Debug, KindSpec)]
+//! #[kind(id = 3, name = "Vote", carriers(OpReturn, WitnessData), recommended = OpReturn)]
+//! struct VoteSpec {
+//!     #[spec(u8)]
+//!     choice: u8,
+//!     #[spec(string, max_len = 140)]
+//!     comment: String,
+//! }
+//! ```
+//!
+//! ## Struct Attributes (`KindSpec` only)
+//!
+//! | Attribute | Description |
+//! |-----------|-------------|
+//! | `id = N` | `KindSpec::KIND_ID` |
+//! | `name = "..."` | `KindSpec::KIND_NAME` |
+//! | `carriers(A, B, ...)` | `CarrierType` variants returned by `supported_carriers()` |
+//! | `recommended = A` | `recommended_carrier()` (defaults to the first carrier) |
+//! | `validate = path` | Extra `fn(&Self) -> Result<()>` run after field checks |
+//!
+//! ## Field Attributes
+//!
+//! | Attribute | Encoding |
+//! |-----------|----------|
+//! | `u8`, `u16_le`, `u32_le`, `u64_le` | Fixed-width integer |
+//! | `varint` | LEB128 varint |
+//! | `string` | u8 length-prefixed UTF-8 (`prefix = u16` for u16 LE) |
+//! | `bytes` | Remaining bytes (must be the last field) |
+//! | `repeated` | u8 count followed by `SpecRecord` items |
+//!
+//! Length-prefixed fields accept `max_len = N` and `non_empty` validation flags.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, LitInt, LitStr, Path};
+
+/// Derive `anchor_specs::KindSpec` from `#[kind(...)]` and `#[spec(...)]` attributes.
+#[proc_macro_derive(KindSpec, attributes(kind, spec))]
+pub fn derive_kind_spec(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_kind_spec(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `anchor_specs::codec::SpecRecord` from `#[spec(...)]` field attributes.
+#[proc_macro_derive(SpecRecord, attributes(spec))]
+pub fn derive_spec_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_spec_record(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// ============================================================================
+// Attribute Parsing
+// ============================================================================
+
+/// Wire encoding of a single field
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    U8,
+    U16Le,
+    U32Le,
+    U64Le,
+    Varint,
+    String,
+    Bytes,
+    Repeated,
+}
+
+/// A struct field together with its parsed `#[spec(...)]` attribute
+struct FieldSpec {
+    ident: Ident,
+    ty: syn::Type,
+    kind: FieldKind,
+    u16_prefix: bool,
+    max_len: Option<usize>,
+    non_empty: bool,
+}
+
+/// Parsed `#[kind(...)]` attribute
+struct KindAttrs {
+    id: LitInt,
+    name: LitStr,
+    carriers: Vec<Ident>,
+    recommended: Option<Ident>,
+    validate: Option<Path>,
+}
+
+fn parse_fields(input: &DeriveInput) -> syn::Result<Vec<FieldSpec>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "spec derives require a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "spec derives can only be used on structs",
+            ))
+        }
+    };
+
+    let mut specs = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.clone().unwrap();
+        let attr = field
+            .attrs
+            .iter()
+            .find(|a| a.path().is_ident("spec"))
+            .ok_or_else(|| {
+                syn::Error::new_spanned(field, "missing #[spec(...)] encoding attribute")
+            })?;
+
+        let mut kind = None;
+        let mut u16_prefix = false;
+        let mut max_len = None;
+        let mut non_empty = false;
+
+        attr.parse_nested_meta(|meta| {
+            let set_kind = |kind: &mut Option<FieldKind>, value| {
+                if kind.replace(value).is_some() {
+                    return Err(meta.error("field encoding specified more than once"));
+                }
+                Ok(())
+            };
+
+            if meta.path.is_ident("u8") {
+                set_kind(&mut kind, FieldKind::U8)
+            } else if meta.path.is_ident("u16_le") {
+                set_kind(&mut kind, FieldKind::U16Le)
+            } else if meta.path.is_ident("u32_le") {
+                set_kind(&mut kind, FieldKind::U32Le)
+            } else if meta.path.is_ident("u64_le") {
+                set_kind(&mut kind, FieldKind::U64Le)
+            } else if meta.path.is_ident("varint") {
+                set_kind(&mut kind, FieldKind::Varint)
+            } else if meta.path.is_ident("string") {
+                set_kind(&mut kind, FieldKind::String)
+            } else if meta.path.is_ident("bytes") {
+                set_kind(&mut kind, FieldKind::Bytes)
+            } else if meta.path.is_ident("repeated") {
+                set_kind(&mut kind, FieldKind::Repeated)
+            } else if meta.path.is_ident("prefix") {
+                let prefix: Ident = meta.value()?.parse()?;
+                match prefix.to_string().as_str() {
+                    "u8" => u16_prefix = false,
+                    "u16" => u16_prefix = true,
+                    _ => return Err(syn::Error::new_spanned(prefix, "prefix must be u8 or u16")),
+                }
+                Ok(())
+            } else if meta.path.is_ident("max_len") {
+                let lit: LitInt = meta.value()?.parse()?;
+                max_len = Some(lit.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("non_empty") {
+                non_empty = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown spec attribute"))
+            }
+        })?;
+
+        let kind = kind.ok_or_else(|| {
+            syn::Error::new_spanned(attr, "missing field encoding (e.g. u8, string, repeated)")
+        })?;
+
+        let length_bounded = matches!(
+            kind,
+            FieldKind::String | FieldKind::Bytes | FieldKind::Repeated
+        );
+        if !length_bounded && (max_len.is_some() || non_empty) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "max_len and non_empty only apply to string, bytes, and repeated fields",
+            ));
+        }
+        if u16_prefix && kind != FieldKind::String {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "prefix only applies to string fields",
+            ));
+        }
+
+        specs.push(FieldSpec {
+            ident,
+            ty: field.ty.clone(),
+            kind,
+            u16_prefix,
+            max_len,
+            non_empty,
+        });
+    }
+
+    if let Some(pos) = specs.iter().position(|f| f.kind == FieldKind::Bytes) {
+        if pos + 1 != specs.len() {
+            return Err(syn::Error::new_spanned(
+                &specs[pos].ident,
+                "bytes fields consume the rest of the payload and must come last",
+            ));
+        }
+    }
+
+    Ok(specs)
+}
+
+fn parse_kind_attrs(input: &DeriveInput) -> syn::Result<KindAttrs> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("kind"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "missing #[kind(id = ..., name = \"...\", carriers(...))] attribute",
+            )
+        })?;
+
+    let mut id = None;
+    let mut name = None;
+    let mut carriers = Vec::new();
+    let mut recommended = None;
+    let mut validate = None;
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("id") {
+            id = Some(meta.value()?.parse::<LitInt>()?);
+        } else if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?);
+        } else if meta.path.is_ident("carriers") {
+            meta.parse_nested_meta(|carrier| {
+                carriers.push(carrier.path.require_ident()?.clone());
+                Ok(())
+            })?;
+        } else if meta.path.is_ident("recommended") {
+            recommended = Some(meta.value()?.parse::<Ident>()?);
+        } else if meta.path.is_ident("validate") {
+            validate = Some(meta.value()?.parse::<Path>()?);
+        } else {
+            return Err(meta.error("unknown kind attribute"));
+        }
+        Ok(())
+    })?;
+
+    let missing =
+        |what: &str| syn::Error::new_spanned(attr, format!("missing `{}` in #[kind]", what));
+    let id = id.ok_or_else(|| missing("id"))?;
+    let name = name.ok_or_else(|| missing("name"))?;
+    if carriers.is_empty() {
+        return Err(missing("carriers(...)"));
+    }
+
+    Ok(KindAttrs {
+        id,
+        name,
+        carriers,
+        recommended,
+        validate,
+    })
+}
+
+// ============================================================================
+// Code Generation
+// ============================================================================
+
+/// Statements decoding every field from `reader` into local bindings
+fn gen_decode(fields: &[FieldSpec]) -> TokenStream2 {
+    let stmts = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        let name = ident.to_string();
+        let expr = match f.kind {
+            FieldKind::U8 => quote!(reader.read_u8()?),
+            FieldKind::U16Le => quote!(reader.read_u16_le()?),
+            FieldKind::U32Le => quote!(reader.read_u32_le()?),
+            FieldKind::U64Le => quote!(reader.read_u64_le()?),
+            FieldKind::Varint => quote! {
+                <#ty as ::core::convert::TryFrom<u128>>::try_from(reader.read_varint()?)
+                    .map_err(|_| ::anchor_specs::SpecError::InvalidFormat(
+                        ::std::format!("{} out of range", #name),
+                    ))?
+            },
+            FieldKind::String if f.u16_prefix => quote!(reader.read_string_u16()?),
+            FieldKind::String => quote!(reader.read_string_u8()?),
+            FieldKind::Bytes => quote!(reader.take_rest().to_vec()),
+            FieldKind::Repeated => quote!(reader.read_repeated()?),
+        };
+        quote!(let #ident: #ty = #expr;)
+    });
+    quote!(#(#stmts)*)
+}
+
+/// Statements appending every field of `self` to `out`
+fn gen_encode(fields: &[FieldSpec]) -> TokenStream2 {
+    let stmts = fields.iter().map(|f| {
+        let ident = &f.ident;
+        match f.kind {
+            FieldKind::U8 => quote!(out.push(self.#ident);),
+            FieldKind::U16Le | FieldKind::U32Le | FieldKind::U64Le => {
+                quote!(out.extend_from_slice(&self.#ident.to_le_bytes());)
+            }
+            FieldKind::Varint => quote! {
+                ::anchor_specs::codec::write_varint(out, ::core::convert::Into::<u128>::into(self.#ident));
+            },
+            FieldKind::String if f.u16_prefix => {
+                quote!(::anchor_specs::codec::write_string_u16(out, &self.#ident);)
+            }
+            FieldKind::String => quote!(::anchor_specs::codec::write_string_u8(out, &self.#ident);),
+            FieldKind::Bytes => quote!(out.extend_from_slice(&self.#ident);),
+            FieldKind::Repeated => quote!(::anchor_specs::codec::write_repeated(out, &self.#ident);),
+        }
+    });
+    quote!(#(#stmts)*)
+}
+
+/// Statements checking length bounds and nested record validity
+fn gen_validate(fields: &[FieldSpec]) -> TokenStream2 {
+    let stmts = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let name = ident.to_string();
+
+        let prefix_max = match f.kind {
+            FieldKind::String if f.u16_prefix => Some(u16::MAX as usize),
+            FieldKind::String | FieldKind::Repeated => Some(u8::MAX as usize),
+            _ => None,
+        };
+        let max = match (f.max_len, prefix_max) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        let len_check = max
+            .map(|max| quote!(::anchor_specs::codec::check_len(#name, self.#ident.len(), #max)?;));
+        let empty_check = f
+            .non_empty
+            .then(|| quote!(::anchor_specs::codec::check_non_empty(#name, self.#ident.len())?;));
+        let record_check = (f.kind == FieldKind::Repeated).then(|| {
+            quote! {
+                for record in &self.#ident {
+                    ::anchor_specs::codec::SpecRecord::validate(record)?;
+                }
+            }
+        });
+
+        quote!(#len_check #empty_check #record_check)
+    });
+    quote!(#(#stmts)*)
+}
+
+fn expand_kind_spec(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let attrs = parse_kind_attrs(input)?;
+    let fields = parse_fields(input)?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let field_idents = fields.iter().map(|f| &f.ident);
+    let decode = gen_decode(&fields);
+    let encode = gen_encode(&fields);
+    let validate = gen_validate(&fields);
+    let custom_validate = attrs.validate.as_ref().map(|path| quote!(#path(self)?;));
+
+    let KindAttrs {
+        id,
+        name,
+        carriers,
+        recommended,
+        ..
+    } = &attrs;
+    let recommended = recommended.as_ref().unwrap_or(&carriers[0]);
+
+    Ok(quote! {
+        impl #impl_generics ::anchor_specs::KindSpec for #ident #ty_generics #where_clause {
+            const KIND_ID: u8 = #id;
+            const KIND_NAME: &'static str = #name;
+
+            fn from_bytes(
+                body: &[u8],
+            ) -> ::core::result::Result<Self, ::anchor_specs::SpecError> {
+                let mut reader = ::anchor_specs::codec::Reader::new(body);
+                let reader = &mut reader;
+                #decode
+                Ok(Self { #(#field_idents),* })
+            }
+
+            fn to_bytes(&self) -> ::std::vec::Vec<u8> {
+                let mut buf = ::std::vec::Vec::new();
+                {
+                    let out = &mut buf;
+                    #encode
+                }
+                buf
+            }
+
+            fn validate(&self) -> ::core::result::Result<(), ::anchor_specs::SpecError> {
+                #validate
+                #custom_validate
+                Ok(())
+            }
+
+            fn supported_carriers() -> &'static [::anchor_specs::CarrierType] {
+                &[#(::anchor_specs::CarrierType::#carriers),*]
+            }
+
+            fn recommended_carrier() -> ::anchor_specs::CarrierType {
+                ::anchor_specs::CarrierType::#recommended
+            }
+        }
+    })
+}
+
+fn expand_spec_record(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = parse_fields(input)?;
+    if fields.iter().any(|f| f.kind == FieldKind::Bytes) {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "bytes fields are not allowed in records; use a length-prefixed string or repeated field",
+        ));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let field_idents = fields.iter().map(|f| &f.ident);
+    let decode = gen_decode(&fields);
+    let encode = gen_encode(&fields);
+    let validate = gen_validate(&fields);
+
+    Ok(quote! {
+        impl #impl_generics ::anchor_specs::codec::SpecRecord for #ident #ty_generics #where_clause {
+            fn decode(
+                reader: &mut ::anchor_specs::codec::Reader<'_>,
+            ) -> ::core::result::Result<Self, ::anchor_specs::SpecError> {
+                #decode
+                Ok(Self { #(#field_idents),* })
+            }
+
+            fn encode(&self, out: &mut ::std::vec::Vec<u8>) {
+                #encode
+            }
+
+            fn validate(&self) -> ::core::result::Result<(), ::anchor_specs::SpecError> {
+                #validate
+                Ok(())
+            }
+        }
+    })
+}
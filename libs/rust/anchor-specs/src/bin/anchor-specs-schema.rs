@@ -0,0 +1,85 @@
+//! Dump the JSON Schemas of the ANCHOR kinds
+//!
+//! ```text
+//! anchor-specs-schema            # all kinds as one JSON object, keyed by kind name
+//! anchor-specs-schema DNS        # a single kind, by name or kind number
+//! anchor-specs-schema --out DIR  # one <kind>.schema.json file per kind
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anchor_specs::kind_schemas;
+
+fn main() -> ExitCode {
+    let mut out: Option<PathBuf> = None;
+    let mut filter: Option<String> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => match args.next() {
+                Some(dir) => out = Some(dir.into()),
+                None => return usage("--out needs a directory"),
+            },
+            "-h" | "--help" => return usage(""),
+            _ if filter.is_none() => filter = Some(arg),
+            _ => return usage(&format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let schemas: Vec<_> = kind_schemas()
+        .into_iter()
+        .filter(|s| {
+            filter.as_deref().is_none_or(|f| {
+                s.name.eq_ignore_ascii_case(f) || f.parse::<u8>().is_ok_and(|k| k == s.kind)
+            })
+        })
+        .collect();
+    if schemas.is_empty() {
+        return usage(&format!("unknown kind '{}'", filter.unwrap_or_default()));
+    }
+
+    if let Some(dir) = out {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            eprintln!("Failed to create {}: {}", dir.display(), e);
+            return ExitCode::FAILURE;
+        }
+        for kind in &schemas {
+            let path = dir.join(format!("{}.schema.json", kind.name.to_lowercase()));
+            let json = serde_json::to_string_pretty(&kind.schema).expect("schema serializes");
+            if let Err(e) = std::fs::write(&path, json + "\n") {
+                eprintln!("Failed to write {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+            println!("{}", path.display());
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let json = match (filter, schemas.as_slice()) {
+        (Some(_), [kind]) => kind.schema.as_value().clone(),
+        _ => serde_json::Value::Object(
+            schemas
+                .iter()
+                .map(|k| (k.name.to_string(), k.schema.as_value().clone()))
+                .collect(),
+        ),
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json).expect("schema serializes")
+    );
+    ExitCode::SUCCESS
+}
+
+fn usage(error: &str) -> ExitCode {
+    if !error.is_empty() {
+        eprintln!("error: {}", error);
+    }
+    eprintln!("usage: anchor-specs-schema [KIND] [--out DIR]");
+    if error.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,306 @@
+//! Binary codec primitives shared by hand-written and derived specs
+//!
+//! `#[derive(KindSpec)]` and `#[derive(SpecRecord)]` expand to calls into this
+//! module, so the wire conventions live in one place:
+//!
+//! | Attribute | Encoding |
+//! |-----------|----------|
+//! | `u8` | 1 byte |
+//! | `u16_le` / `u32_le` / `u64_le` | Fixed-width little-endian integer |
+//! | `varint` | LEB128 varint (same as the Token kind) |
+//! | `string` | Length-prefixed UTF-8 (u8 prefix, or u16 LE with `prefix = u16`) |
+//! | `bytes` | All remaining bytes (last field only) |
+//! | `repeated` | u8 record count followed by each [`SpecRecord`] |
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use anchor_specs::prelude::*;
+//!
+//! #[derive(Debug, SpecRecord)]
+//! struct PollOption {
+//!     #[spec(string, max_len = 64, non_empty)]
+//!     label: String,
+//! }
+//!
+//! #[derive(Debug, KindSpec)]
+//! #[kind(id = 3, name = "Vote", carriers(OpReturn, WitnessData), recommended = OpReturn)]
+//! struct PollSpec {
+//!     #[spec(u16_le)]
+//!     duration_blocks: u16,
+//!     #[spec(string, non_empty)]
+//!     question: String,
+//!     #[spec(repeated, max_len = 16)]
+//!     options: Vec<PollOption>,
+//! }
+//! ```
+
+use crate::error::{Result, SpecError};
+use crate::kinds::token::{decode_varint, encode_varint};
+
+/// A record that can be embedded in a kind payload (e.g. a `repeated` field).
+pub trait SpecRecord: Sized {
+    /// Decode one record from the reader, advancing past it.
+    fn decode(reader: &mut Reader<'_>) -> Result<Self>;
+
+    /// Append the encoded record to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Validate the record contents.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Cursor over a payload body with bounds-checked reads.
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Create a reader positioned at the start of `bytes`
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Current offset into the payload
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bytes not yet consumed
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    /// Check if the whole payload has been consumed
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Consume exactly `len` bytes
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.remaining() < len {
+            return Err(SpecError::PayloadTooShort {
+                expected: self.pos + len,
+                actual: self.bytes.len(),
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    /// Consume all remaining bytes
+    pub fn take_rest(&mut self) -> &'a [u8] {
+        let slice = &self.bytes[self.pos..];
+        self.pos = self.bytes.len();
+        slice
+    }
+
+    /// Read a single byte
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read a little-endian u16
+    pub fn read_u16_le(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    /// Read a little-endian u32
+    pub fn read_u32_le(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Read a little-endian u64
+    pub fn read_u64_le(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Read a LEB128 varint
+    pub fn read_varint(&mut self) -> Result<u128> {
+        let (value, bytes_read) = decode_varint(&self.bytes[self.pos..])?;
+        self.pos += bytes_read;
+        Ok(value)
+    }
+
+    /// Read a UTF-8 string with a u8 length prefix
+    pub fn read_string_u8(&mut self) -> Result<String> {
+        let len = self.read_u8()? as usize;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+
+    /// Read a UTF-8 string with a little-endian u16 length prefix
+    pub fn read_string_u16(&mut self) -> Result<String> {
+        let len = self.read_u16_le()? as usize;
+        Ok(String::from_utf8(self.take(len)?.to_vec())?)
+    }
+
+    /// Read a u8 count followed by that many records
+    pub fn read_repeated<T: SpecRecord>(&mut self) -> Result<Vec<T>> {
+        let count = self.read_u8()? as usize;
+        let mut records = Vec::with_capacity(count);
+        for _ in 0..count {
+            records.push(T::decode(self)?);
+        }
+        Ok(records)
+    }
+}
+
+/// Append a LEB128 varint
+pub fn write_varint(out: &mut Vec<u8>, value: u128) {
+    out.extend_from_slice(&encode_varint(value));
+}
+
+/// Append a UTF-8 string with a u8 length prefix
+pub fn write_string_u8(out: &mut Vec<u8>, value: &str) {
+    out.push(value.len() as u8);
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Append a UTF-8 string with a little-endian u16 length prefix
+pub fn write_string_u16(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+/// Append a u8 count followed by each record
+pub fn write_repeated<T: SpecRecord>(out: &mut Vec<u8>, records: &[T]) {
+    out.push(records.len() as u8);
+    for record in records {
+        record.encode(out);
+    }
+}
+
+/// Check a length-bounded field, as generated for `max_len` and prefix limits
+pub fn check_len(field: &'static str, len: usize, max: usize) -> Result<()> {
+    if len > max {
+        return Err(SpecError::InvalidFormat(format!(
+            "{} too long: max {}, got {}",
+            field, max, len
+        )));
+    }
+    Ok(())
+}
+
+/// Check a field declared `non_empty`
+pub fn check_non_empty(field: &'static str, len: usize) -> Result<()> {
+    if len == 0 {
+        return Err(SpecError::InvalidFormat(format!(
+            "{} cannot be empty",
+            field
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq, SpecRecord)]
+    struct PollOption {
+        #[spec(u8)]
+        id: u8,
+        #[spec(string, max_len = 16, non_empty)]
+        label: String,
+    }
+
+    fn check_deadline(spec: &PollSpec) -> Result<()> {
+        if spec.deadline == 0 {
+            return Err(SpecError::InvalidFormat("deadline must be set".into()));
+        }
+        Ok(())
+    }
+
+    #[derive(Debug, Clone, PartialEq, KindSpec)]
+    #[kind(
+        id = 3,
+        name = "Vote",
+        carriers(OpReturn, WitnessData),
+        recommended = OpReturn,
+        validate = check_deadline
+    )]
+    struct PollSpec {
+        #[spec(u32_le)]
+        deadline: u32,
+        #[spec(varint)]
+        stake: u64,
+        #[spec(string, prefix = u16, non_empty)]
+        question: String,
+        #[spec(repeated, max_len = 4)]
+        options: Vec<PollOption>,
+        #[spec(bytes)]
+        extra: Vec<u8>,
+    }
+
+    fn sample() -> PollSpec {
+        PollSpec {
+            deadline: 880_000,
+            stake: 10_000,
+            question: "Best carrier?".to_string(),
+            options: vec![
+                PollOption {
+                    id: 1,
+                    label: "OP_RETURN".to_string(),
+                },
+                PollOption {
+                    id: 2,
+                    label: "Witness".to_string(),
+                },
+            ],
+            extra: vec![0xde, 0xad],
+        }
+    }
+
+    #[test]
+    fn test_derived_roundtrip() {
+        let spec = sample();
+        let bytes = spec.to_bytes();
+        assert_eq!(&bytes[..4], &880_000u32.to_le_bytes());
+        assert_eq!(PollSpec::from_bytes(&bytes).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_derived_metadata() {
+        assert_eq!(PollSpec::KIND_ID, 3);
+        assert_eq!(PollSpec::KIND_NAME, "Vote");
+        assert_eq!(PollSpec::recommended_carrier(), CarrierType::OpReturn);
+        assert!(PollSpec::is_carrier_supported(CarrierType::WitnessData));
+        assert!(!PollSpec::is_carrier_supported(CarrierType::Stamps));
+    }
+
+    #[test]
+    fn test_derived_validation() {
+        assert!(sample().validate().is_ok());
+
+        let mut spec = sample();
+        spec.question.clear();
+        assert!(spec.validate().is_err());
+
+        let mut spec = sample();
+        spec.options[0].label = "x".repeat(17);
+        assert!(spec.validate().is_err());
+
+        let mut spec = sample();
+        spec.options = vec![spec.options[0].clone(); 5];
+        assert!(spec.validate().is_err());
+
+        let mut spec = sample();
+        spec.deadline = 0;
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn test_derived_truncated_payload() {
+        let bytes = sample().to_bytes();
+        assert!(matches!(
+            PollSpec::from_bytes(&bytes[..3]),
+            Err(SpecError::PayloadTooShort { .. })
+        ));
+    }
+}
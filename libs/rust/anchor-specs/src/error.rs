@@ -35,6 +35,10 @@ pub enum SpecError {
         kind: &'static str,
     },
 
+    /// Payload version not supported by this kind
+    #[error("Unsupported {kind} payload version: {version}")]
+    UnsupportedVersion { kind: &'static str, version: u8 },
+
     // ========================================================================
     // DNS Errors
     // ========================================================================
@@ -100,6 +104,17 @@ pub enum SpecError {
     #[error("Invalid proof operation: {0}")]
     InvalidProofOperation(u8),
 
+    /// Transaction carries no proof of the given hash
+    #[error("No proof of {0} in the transaction")]
+    ProofNotFound(String),
+
+    // ========================================================================
+    // Identity Errors
+    // ========================================================================
+    /// Signature does not verify against the signing key
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
     // ========================================================================
     // Text/Generic Errors
     // ========================================================================
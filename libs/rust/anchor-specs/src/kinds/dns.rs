@@ -32,6 +32,11 @@
 //! │ (1 byte)  │ (2 bytes) │ (1 byte)  │ (data_len bytes)            │
 //! └───────────┴───────────┴───────────┴─────────────────────────────┘
 //! ```
+//!
+//! TLSA (8) data is `[usage][selector][matching_type][association...]` and
+//! SSHFP (9) data is `[algorithm][fp_type][fingerprint...]`; both are exposed
+//! in zone-file form (`3 1 1 <hex>`). URI (10) data is
+//! `[priority: u16][weight: u16][target...]`.
 
 use crate::error::{Result, SpecError};
 use crate::validation::{AnchorableSpec, KindSpec, OwnedSpec};
@@ -45,6 +50,9 @@ pub const SUPPORTED_TLDS: &[&str] = &[".btc", ".sat", ".anchor", ".anc", ".bit"]
 /// Maximum domain name length (including TLD)
 pub const MAX_DOMAIN_LENGTH: usize = 255;
 
+/// Maximum encoded record data length (the length prefix is one byte)
+pub const MAX_RECORD_DATA: usize = 255;
+
 /// DNS Operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -88,6 +96,12 @@ pub enum RecordType {
     NS = 6,
     /// Service record (u16×3 + UTF-8 target)
     SRV = 7,
+    /// TLS certificate association (usage, selector, matching type + data)
+    TLSA = 8,
+    /// SSH key fingerprint (algorithm, fingerprint type + fingerprint)
+    SSHFP = 9,
+    /// URI (u16 priority + u16 weight + UTF-8 target)
+    URI = 10,
 }
 
 impl TryFrom<u8> for RecordType {
@@ -102,6 +116,9 @@ impl TryFrom<u8> for RecordType {
             5 => Ok(RecordType::MX),
             6 => Ok(RecordType::NS),
             7 => Ok(RecordType::SRV),
+            8 => Ok(RecordType::TLSA),
+            9 => Ok(RecordType::SSHFP),
+            10 => Ok(RecordType::URI),
             _ => Err(SpecError::InvalidRecordType(value)),
         }
     }
@@ -118,6 +135,9 @@ impl RecordType {
             RecordType::MX => "MX",
             RecordType::NS => "NS",
             RecordType::SRV => "SRV",
+            RecordType::TLSA => "TLSA",
+            RecordType::SSHFP => "SSHFP",
+            RecordType::URI => "URI",
         }
     }
 }
@@ -225,6 +245,60 @@ impl DnsRecord {
         }
     }
 
+    /// Create a TLSA record
+    ///
+    /// The value is stored in zone-file form: `usage selector matching_type hex`.
+    pub fn tlsa(
+        usage: u8,
+        selector: u8,
+        matching_type: u8,
+        association: &[u8],
+        ttl: u16,
+    ) -> Result<Self> {
+        validate_tlsa(usage, selector, matching_type, association)?;
+        Ok(Self {
+            record_type: RecordType::TLSA,
+            ttl,
+            value: format!(
+                "{} {} {} {}",
+                usage,
+                selector,
+                matching_type,
+                hex::encode(association)
+            ),
+            priority: None,
+            weight: None,
+            port: None,
+        })
+    }
+
+    /// Create an SSHFP record
+    ///
+    /// The value is stored in zone-file form: `algorithm fp_type hex`.
+    pub fn sshfp(algorithm: u8, fp_type: u8, fingerprint: &[u8], ttl: u16) -> Result<Self> {
+        validate_sshfp(algorithm, fp_type, fingerprint)?;
+        Ok(Self {
+            record_type: RecordType::SSHFP,
+            ttl,
+            value: format!("{} {} {}", algorithm, fp_type, hex::encode(fingerprint)),
+            priority: None,
+            weight: None,
+            port: None,
+        })
+    }
+
+    /// Create a URI record
+    pub fn uri(target: &str, priority: u16, weight: u16, ttl: u16) -> Self {
+        Self {
+            record_type: RecordType::URI,
+            ttl,
+            value: target.to_string(),
+            priority: Some(priority),
+            weight: Some(weight),
+            port: None,
+        }
+    }
+
     /// Validate the record
     pub fn validate(&self) -> Result<()> {
         match self.record_type {
@@ -238,22 +312,50 @@ impl DnsRecord {
                     .parse::<Ipv6Addr>()
                     .map_err(|_| SpecError::InvalidIpv6(self.value.clone()))?;
             }
-            RecordType::MX => {
-                if self.priority.is_none() {
+            RecordType::MX if self.priority.is_none() => {
+                return Err(SpecError::InvalidDnsRecord(
+                    "MX record requires priority".to_string(),
+                ));
+            }
+            RecordType::SRV
+                if self.priority.is_none() || self.weight.is_none() || self.port.is_none() =>
+            {
+                return Err(SpecError::InvalidDnsRecord(
+                    "SRV record requires priority, weight, and port".to_string(),
+                ));
+            }
+            RecordType::TLSA => {
+                let (usage, selector, matching_type, association) = parse_tlsa(&self.value)?;
+                validate_tlsa(usage, selector, matching_type, &association)?;
+            }
+            RecordType::SSHFP => {
+                let (algorithm, fp_type, fingerprint) = parse_sshfp(&self.value)?;
+                validate_sshfp(algorithm, fp_type, &fingerprint)?;
+            }
+            RecordType::URI => {
+                if self.priority.is_none() || self.weight.is_none() {
                     return Err(SpecError::InvalidDnsRecord(
-                        "MX record requires priority".to_string(),
+                        "URI record requires priority and weight".to_string(),
                     ));
                 }
-            }
-            RecordType::SRV => {
-                if self.priority.is_none() || self.weight.is_none() || self.port.is_none() {
+                if self.value.is_empty() {
                     return Err(SpecError::InvalidDnsRecord(
-                        "SRV record requires priority, weight, and port".to_string(),
+                        "URI record target cannot be empty".to_string(),
                     ));
                 }
             }
             _ => {}
         }
+
+        let len = self.encode_data().len();
+        if len > MAX_RECORD_DATA {
+            return Err(SpecError::InvalidDnsRecord(format!(
+                "{} record data is {} bytes (max {})",
+                self.record_type.name(),
+                len,
+                MAX_RECORD_DATA
+            )));
+        }
         Ok(())
     }
 
@@ -289,6 +391,31 @@ impl DnsRecord {
                 data.extend_from_slice(self.value.as_bytes());
                 data
             }
+            RecordType::TLSA => {
+                if let Ok((usage, selector, matching_type, association)) = parse_tlsa(&self.value) {
+                    let mut data = vec![usage, selector, matching_type];
+                    data.extend_from_slice(&association);
+                    data
+                } else {
+                    Vec::new()
+                }
+            }
+            RecordType::SSHFP => {
+                if let Ok((algorithm, fp_type, fingerprint)) = parse_sshfp(&self.value) {
+                    let mut data = vec![algorithm, fp_type];
+                    data.extend_from_slice(&fingerprint);
+                    data
+                } else {
+                    Vec::new()
+                }
+            }
+            RecordType::URI => {
+                let mut data = Vec::new();
+                data.extend_from_slice(&self.priority.unwrap_or(0).to_be_bytes());
+                data.extend_from_slice(&self.weight.unwrap_or(0).to_be_bytes());
+                data.extend_from_slice(self.value.as_bytes());
+                data
+            }
         }
     }
 
@@ -397,6 +524,43 @@ impl DnsRecord {
                 let target = String::from_utf8(data[6..].to_vec())?;
                 (target, Some(priority), Some(weight), Some(port))
             }
+            RecordType::TLSA => {
+                if data.len() < 4 {
+                    return Err(SpecError::InvalidDnsRecord(
+                        "TLSA record too short".to_string(),
+                    ));
+                }
+                validate_tlsa(data[0], data[1], data[2], &data[3..])?;
+                let value = format!(
+                    "{} {} {} {}",
+                    data[0],
+                    data[1],
+                    data[2],
+                    hex::encode(&data[3..])
+                );
+                (value, None, None, None)
+            }
+            RecordType::SSHFP => {
+                if data.len() < 3 {
+                    return Err(SpecError::InvalidDnsRecord(
+                        "SSHFP record too short".to_string(),
+                    ));
+                }
+                validate_sshfp(data[0], data[1], &data[2..])?;
+                let value = format!("{} {} {}", data[0], data[1], hex::encode(&data[2..]));
+                (value, None, None, None)
+            }
+            RecordType::URI => {
+                if data.len() < 5 {
+                    return Err(SpecError::InvalidDnsRecord(
+                        "URI record too short".to_string(),
+                    ));
+                }
+                let priority = u16::from_be_bytes([data[0], data[1]]);
+                let weight = u16::from_be_bytes([data[2], data[3]]);
+                let target = String::from_utf8(data[4..].to_vec())?;
+                (target, Some(priority), Some(weight), None)
+            }
         };
 
         Ok(Self {
@@ -551,8 +715,10 @@ pub fn validate_domain_name(name: &str) -> Result<()> {
         SpecError::UnsupportedTld(tld_part)
     })?;
 
-    // Get the name part (without TLD)
+    // Get the name part (without TLD); a leading `*.` label registers a
+    // wildcard that answers for otherwise unregistered names beneath it
     let name_part = &name[..name.len() - tld.len()];
+    let name_part = name_part.strip_prefix("*.").unwrap_or(name_part);
 
     // Must be at least 1 character
     if name_part.is_empty() {
@@ -595,6 +761,117 @@ pub fn validate_domain_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Split a zone-file style value into its numeric fields and trailing hex data
+fn parse_presentation<const N: usize>(value: &str, record: &str) -> Result<([u8; N], Vec<u8>)> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != N + 1 {
+        return Err(SpecError::InvalidDnsRecord(format!(
+            "{} record must have {} fields, got {}",
+            record,
+            N + 1,
+            parts.len()
+        )));
+    }
+
+    let mut fields = [0u8; N];
+    for (field, part) in fields.iter_mut().zip(&parts) {
+        *field = part.parse().map_err(|_| {
+            SpecError::InvalidDnsRecord(format!("{} record field '{}' is not a u8", record, part))
+        })?;
+    }
+    let data = hex::decode(parts[N])
+        .map_err(|_| SpecError::InvalidDnsRecord(format!("{} record data must be hex", record)))?;
+
+    Ok((fields, data))
+}
+
+/// Parse a TLSA value: `usage selector matching_type hex`
+fn parse_tlsa(value: &str) -> Result<(u8, u8, u8, Vec<u8>)> {
+    let ([usage, selector, matching_type], association) = parse_presentation::<3>(value, "TLSA")?;
+    Ok((usage, selector, matching_type, association))
+}
+
+/// Parse an SSHFP value: `algorithm fp_type hex`
+fn parse_sshfp(value: &str) -> Result<(u8, u8, Vec<u8>)> {
+    let ([algorithm, fp_type], fingerprint) = parse_presentation::<2>(value, "SSHFP")?;
+    Ok((algorithm, fp_type, fingerprint))
+}
+
+/// Validate TLSA fields (RFC 6698)
+///
+/// Full certificates (matching type 0) rarely fit in a record, so DANE
+/// pinning on Anchor uses SHA-256 or SHA-512 digests.
+fn validate_tlsa(usage: u8, selector: u8, matching_type: u8, association: &[u8]) -> Result<()> {
+    if usage > 3 {
+        return Err(SpecError::InvalidDnsRecord(format!(
+            "TLSA usage must be 0-3, got {}",
+            usage
+        )));
+    }
+    if selector > 1 {
+        return Err(SpecError::InvalidDnsRecord(format!(
+            "TLSA selector must be 0-1, got {}",
+            selector
+        )));
+    }
+    let expected = match matching_type {
+        0 => None,
+        1 => Some(32),
+        2 => Some(64),
+        _ => {
+            return Err(SpecError::InvalidDnsRecord(format!(
+                "TLSA matching type must be 0-2, got {}",
+                matching_type
+            )))
+        }
+    };
+    if association.is_empty() {
+        return Err(SpecError::InvalidDnsRecord(
+            "TLSA association data cannot be empty".to_string(),
+        ));
+    }
+    if let Some(len) = expected {
+        if association.len() != len {
+            return Err(SpecError::InvalidDnsRecord(format!(
+                "TLSA matching type {} requires {} bytes, got {}",
+                matching_type,
+                len,
+                association.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validate SSHFP fields (RFC 4255, 6594, 7479, 8709)
+fn validate_sshfp(algorithm: u8, fp_type: u8, fingerprint: &[u8]) -> Result<()> {
+    if !matches!(algorithm, 1..=4 | 6) {
+        return Err(SpecError::InvalidDnsRecord(format!(
+            "Unknown SSHFP algorithm: {}",
+            algorithm
+        )));
+    }
+    let expected = match fp_type {
+        1 => 20,
+        2 => 32,
+        _ => {
+            return Err(SpecError::InvalidDnsRecord(format!(
+                "Unknown SSHFP fingerprint type: {}",
+                fp_type
+            )))
+        }
+    };
+    if fingerprint.len() != expected {
+        return Err(SpecError::InvalidDnsRecord(format!(
+            "SSHFP fingerprint type {} requires {} bytes, got {}",
+            fp_type,
+            expected,
+            fingerprint.len()
+        )));
+    }
+    Ok(())
+}
+
 /// Get the TLD from a domain name if it's supported
 pub fn get_tld(name: &str) -> Option<&'static str> {
     SUPPORTED_TLDS
@@ -634,6 +911,8 @@ mod tests {
         assert!(is_valid_domain_name("test.anchor"));
         assert!(is_valid_domain_name("test.anc"));
         assert!(is_valid_domain_name("test.bit"));
+        assert!(is_valid_domain_name("*.example.btc"));
+        assert!(is_valid_domain_name("*.sub.example.btc"));
     }
 
     #[test]
@@ -642,6 +921,8 @@ mod tests {
         assert!(!is_valid_domain_name(".btc")); // empty name
         assert!(!is_valid_domain_name("-test.btc")); // starts with hyphen
         assert!(!is_valid_domain_name("test-.sat")); // ends with hyphen
+        assert!(!is_valid_domain_name("*.btc")); // wildcard over a TLD
+        assert!(!is_valid_domain_name("a.*.example.btc")); // wildcard not leftmost
     }
 
     #[test]
@@ -682,4 +963,71 @@ mod tests {
         assert_eq!(parsed.records[1].record_type, RecordType::TXT);
         assert_eq!(parsed.records[2].record_type, RecordType::MX);
     }
+
+    #[test]
+    fn test_security_records_roundtrip() {
+        let spec = DnsSpec::register(
+            "secure.btc",
+            vec![
+                DnsRecord::tlsa(3, 1, 1, &[0xab; 32], 3600).unwrap(),
+                DnsRecord::sshfp(4, 2, &[0x12; 32], 3600).unwrap(),
+                DnsRecord::uri("https://secure.btc/", 10, 1, 300),
+            ],
+        );
+        spec.validate().unwrap();
+
+        let bytes = spec.to_bytes();
+        let parsed = DnsSpec::from_bytes(&bytes).unwrap();
+
+        assert_eq!(spec, parsed);
+        assert_eq!(
+            parsed.records[0].value,
+            format!("3 1 1 {}", "ab".repeat(32))
+        );
+        assert_eq!(parsed.records[2].priority, Some(10));
+        assert_eq!(parsed.records[2].weight, Some(1));
+    }
+
+    #[test]
+    fn test_tlsa_validation() {
+        assert!(DnsRecord::tlsa(4, 1, 1, &[0; 32], 300).is_err()); // bad usage
+        assert!(DnsRecord::tlsa(3, 2, 1, &[0; 32], 300).is_err()); // bad selector
+        assert!(DnsRecord::tlsa(3, 1, 3, &[0; 32], 300).is_err()); // bad matching type
+        assert!(DnsRecord::tlsa(3, 1, 1, &[0; 20], 300).is_err()); // wrong digest length
+        assert!(DnsRecord::tlsa(3, 1, 2, &[0; 64], 300).is_ok());
+
+        let mut record = DnsRecord::txt("3 1 1 zz", 300);
+        record.record_type = RecordType::TLSA;
+        assert!(record.validate().is_err());
+
+        // A full certificate does not fit in one record
+        let mut cert = DnsRecord::txt("", 300);
+        cert.record_type = RecordType::TLSA;
+        cert.value = format!("3 0 0 {}", "00".repeat(300));
+        assert!(cert.validate().is_err());
+    }
+
+    #[test]
+    fn test_sshfp_validation() {
+        assert!(DnsRecord::sshfp(5, 2, &[0; 32], 300).is_err()); // unknown algorithm
+        assert!(DnsRecord::sshfp(4, 3, &[0; 32], 300).is_err()); // unknown fp type
+        assert!(DnsRecord::sshfp(1, 1, &[0; 32], 300).is_err()); // SHA-1 is 20 bytes
+        assert!(DnsRecord::sshfp(1, 1, &[0; 20], 300).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_security_record_bytes() {
+        // TLSA with usage 9
+        let mut body = vec![0x01, 8];
+        body.extend_from_slice(b"test.btc");
+        body.extend_from_slice(&[8, 0x0e, 0x10, 35, 9, 1, 1]);
+        body.extend_from_slice(&[0; 32]);
+        assert!(DnsSpec::from_bytes(&body).is_err());
+
+        // URI without a target
+        let mut body = vec![0x01, 8];
+        body.extend_from_slice(b"test.btc");
+        body.extend_from_slice(&[10, 0x0e, 0x10, 4, 0, 10, 0, 1]);
+        assert!(DnsSpec::from_bytes(&body).is_err());
+    }
 }
@@ -0,0 +1,398 @@
+//! Kind 50: Escrow Specification
+//!
+//! Escrow messages record the life of a peer-to-peer trade whose payment is
+//! held in a 2-of-3 multisig output between a buyer, a seller and an
+//! arbiter. Any two of them can move the funds: buyer and seller together
+//! settle a trade that went well, and the arbiter sides with one of them
+//! when it didn't.
+//!
+//! ## Payload Format
+//!
+//! ```text
+//! Fund:    [0x01][buyer: 33][seller: 33][arbiter: 33][amount: varint][terms: UTF-8]
+//! Release: [0x02]
+//! Refund:  [0x03]
+//! Dispute: [0x04][reason: UTF-8]
+//! ```
+//!
+//! Keys are compressed secp256k1 public keys and the amount is in satoshis.
+//! Text fields run to the end of the payload and may be empty.
+//!
+//! ## Trade Flow
+//!
+//! | Action | Transaction | Anchors |
+//! |--------|-------------|---------|
+//! | Fund | Pays `amount` to the P2WSH of [`escrow_script`] | none |
+//! | Dispute | Any transaction | the escrow output |
+//! | Release | Spends the escrow output to the seller | the escrow output |
+//! | Refund | Spends the escrow output to the buyer | the escrow output |
+//!
+//! A trade is funded once, may be disputed, and ends with a release or a
+//! refund; [`EscrowAction::can_follow`] encodes the order. Release and refund
+//! only count when their transaction actually spends the escrow output.
+
+use crate::error::{Result, SpecError};
+use crate::kinds::token::{decode_varint, encode_varint};
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+use bitcoin::blockdata::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_2, OP_PUSHNUM_3};
+use bitcoin::script::Builder;
+use bitcoin::{PublicKey, ScriptBuf};
+use serde::{Deserialize, Serialize};
+
+/// Size of a compressed public key
+pub const KEY_SIZE: usize = 33;
+
+/// Size of a Fund payload before the amount
+const FUND_KEYS_END: usize = 1 + KEY_SIZE * 3;
+
+/// Longest terms or dispute reason
+pub const MAX_TEXT_LENGTH: usize = 1024;
+
+/// Step in the life of an escrowed trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum EscrowAction {
+    /// Payment locked in the multisig output
+    Fund = 1,
+    /// Payment released to the seller
+    Release = 2,
+    /// Payment returned to the buyer
+    Refund = 3,
+    /// A party asks the arbiter to step in
+    Dispute = 4,
+}
+
+impl EscrowAction {
+    /// Parse from byte value
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(EscrowAction::Fund),
+            2 => Some(EscrowAction::Release),
+            3 => Some(EscrowAction::Refund),
+            4 => Some(EscrowAction::Dispute),
+            _ => None,
+        }
+    }
+
+    /// Whether this action may follow `previous`, the trade's last action
+    /// (`None` before funding)
+    pub fn can_follow(self, previous: Option<EscrowAction>) -> bool {
+        use EscrowAction::*;
+        matches!(
+            (self, previous),
+            (Fund, None) | (Dispute, Some(Fund)) | (Release | Refund, Some(Fund | Dispute))
+        )
+    }
+
+    /// Whether the trade is over after this action
+    pub fn is_final(self) -> bool {
+        matches!(self, EscrowAction::Release | EscrowAction::Refund)
+    }
+}
+
+/// Escrow operation with its data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscrowOperation {
+    /// Open a trade, in the transaction that funds the escrow output
+    Fund {
+        /// Buyer key (33 bytes, compressed)
+        buyer: Vec<u8>,
+        /// Seller key (33 bytes, compressed)
+        seller: Vec<u8>,
+        /// Arbiter key (33 bytes, compressed)
+        arbiter: Vec<u8>,
+        /// Escrowed amount in satoshis
+        amount: u64,
+        /// What was agreed
+        terms: String,
+    },
+    /// Settle with the seller
+    Release,
+    /// Settle with the buyer
+    Refund,
+    /// Ask the arbiter to decide
+    Dispute { reason: String },
+}
+
+/// Escrow specification (Kind 50)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscrowSpec {
+    pub operation: EscrowOperation,
+}
+
+impl EscrowSpec {
+    /// Create a spec for an operation
+    pub fn new(operation: EscrowOperation) -> Self {
+        Self { operation }
+    }
+
+    /// Open a trade between compressed keys
+    pub fn fund(
+        buyer: &PublicKey,
+        seller: &PublicKey,
+        arbiter: &PublicKey,
+        amount: u64,
+        terms: impl Into<String>,
+    ) -> Self {
+        Self::new(EscrowOperation::Fund {
+            buyer: buyer.to_bytes(),
+            seller: seller.to_bytes(),
+            arbiter: arbiter.to_bytes(),
+            amount,
+            terms: terms.into(),
+        })
+    }
+
+    /// Settle with the seller
+    pub fn release() -> Self {
+        Self::new(EscrowOperation::Release)
+    }
+
+    /// Settle with the buyer
+    pub fn refund() -> Self {
+        Self::new(EscrowOperation::Refund)
+    }
+
+    /// Ask the arbiter to decide
+    pub fn dispute(reason: impl Into<String>) -> Self {
+        Self::new(EscrowOperation::Dispute {
+            reason: reason.into(),
+        })
+    }
+
+    /// The action the message records
+    pub fn action(&self) -> EscrowAction {
+        match &self.operation {
+            EscrowOperation::Fund { .. } => EscrowAction::Fund,
+            EscrowOperation::Release => EscrowAction::Release,
+            EscrowOperation::Refund => EscrowAction::Refund,
+            EscrowOperation::Dispute { .. } => EscrowAction::Dispute,
+        }
+    }
+
+    /// Witness script of the escrow output a Fund message opens
+    pub fn script(&self) -> Result<Option<ScriptBuf>> {
+        let EscrowOperation::Fund {
+            buyer,
+            seller,
+            arbiter,
+            ..
+        } = &self.operation
+        else {
+            return Ok(None);
+        };
+        Ok(Some(escrow_script(
+            &parse_key("buyer", buyer)?,
+            &parse_key("seller", seller)?,
+            &parse_key("arbiter", arbiter)?,
+        )))
+    }
+}
+
+/// 2-of-3 multisig witness script over the buyer, seller and arbiter keys,
+/// in that order
+pub fn escrow_script(buyer: &PublicKey, seller: &PublicKey, arbiter: &PublicKey) -> ScriptBuf {
+    Builder::new()
+        .push_opcode(OP_PUSHNUM_2)
+        .push_key(buyer)
+        .push_key(seller)
+        .push_key(arbiter)
+        .push_opcode(OP_PUSHNUM_3)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script()
+}
+
+fn parse_key(role: &str, key: &[u8]) -> Result<PublicKey> {
+    if key.len() != KEY_SIZE {
+        return Err(SpecError::InvalidFormat(format!(
+            "{} key must be {} bytes, got {}",
+            role,
+            KEY_SIZE,
+            key.len()
+        )));
+    }
+    PublicKey::from_slice(key)
+        .map_err(|e| SpecError::InvalidFormat(format!("Invalid {} key: {}", role, e)))
+}
+
+fn check_text(field: &str, text: &str) -> Result<()> {
+    if text.len() > MAX_TEXT_LENGTH {
+        return Err(SpecError::InvalidFormat(format!(
+            "{} must be at most {} bytes, got {}",
+            field,
+            MAX_TEXT_LENGTH,
+            text.len()
+        )));
+    }
+    Ok(())
+}
+
+impl KindSpec for EscrowSpec {
+    const KIND_ID: u8 = 50;
+    const KIND_NAME: &'static str = "Escrow";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        let Some(&action) = body.first() else {
+            return Err(SpecError::PayloadTooShort {
+                expected: 1,
+                actual: 0,
+            });
+        };
+        let action = EscrowAction::from_u8(action).ok_or(SpecError::InvalidOperation(action))?;
+        let rest = &body[1..];
+
+        match action {
+            EscrowAction::Fund => {
+                if body.len() < FUND_KEYS_END + 1 {
+                    return Err(SpecError::PayloadTooShort {
+                        expected: FUND_KEYS_END + 1,
+                        actual: body.len(),
+                    });
+                }
+                let (amount, read) = decode_varint(&body[FUND_KEYS_END..])?;
+                let amount = u64::try_from(amount)
+                    .map_err(|_| SpecError::InvalidFormat("Amount overflows u64".to_string()))?;
+                Ok(Self::new(EscrowOperation::Fund {
+                    buyer: rest[..KEY_SIZE].to_vec(),
+                    seller: rest[KEY_SIZE..KEY_SIZE * 2].to_vec(),
+                    arbiter: rest[KEY_SIZE * 2..KEY_SIZE * 3].to_vec(),
+                    amount,
+                    terms: String::from_utf8(body[FUND_KEYS_END + read..].to_vec())?,
+                }))
+            }
+            EscrowAction::Release | EscrowAction::Refund if !rest.is_empty() => Err(
+                SpecError::InvalidFormat(format!("{} trailing bytes after action", rest.len())),
+            ),
+            EscrowAction::Release => Ok(Self::release()),
+            EscrowAction::Refund => Ok(Self::refund()),
+            EscrowAction::Dispute => Ok(Self::dispute(String::from_utf8(rest.to_vec())?)),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = vec![self.action() as u8];
+        match &self.operation {
+            EscrowOperation::Fund {
+                buyer,
+                seller,
+                arbiter,
+                amount,
+                terms,
+            } => {
+                result.extend_from_slice(buyer);
+                result.extend_from_slice(seller);
+                result.extend_from_slice(arbiter);
+                result.extend(encode_varint(*amount as u128));
+                result.extend_from_slice(terms.as_bytes());
+            }
+            EscrowOperation::Release | EscrowOperation::Refund => {}
+            EscrowOperation::Dispute { reason } => result.extend_from_slice(reason.as_bytes()),
+        }
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        match &self.operation {
+            EscrowOperation::Fund {
+                buyer,
+                seller,
+                arbiter,
+                amount,
+                terms,
+            } => {
+                if *amount == 0 {
+                    return Err(SpecError::InvalidFormat(
+                        "Escrow amount cannot be zero".to_string(),
+                    ));
+                }
+                if buyer == seller || buyer == arbiter || seller == arbiter {
+                    return Err(SpecError::InvalidFormat(
+                        "Buyer, seller and arbiter need distinct keys".to_string(),
+                    ));
+                }
+                self.script()?;
+                check_text("Terms", terms)
+            }
+            EscrowOperation::Release | EscrowOperation::Refund => Ok(()),
+            EscrowOperation::Dispute { reason } => check_text("Reason", reason),
+        }
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        // Release and refund go out as PSBTs for the parties to sign, which
+        // only the OP_RETURN carrier can be built as
+        &[CarrierType::OpReturn]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+
+    fn key(byte: u8) -> PublicKey {
+        let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+        PublicKey::new(secret.public_key(&Secp256k1::new()))
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for spec in [
+            EscrowSpec::fund(&key(1), &key(2), &key(3), 150_000, "1 widget"),
+            EscrowSpec::release(),
+            EscrowSpec::refund(),
+            EscrowSpec::dispute("never shipped"),
+        ] {
+            assert!(spec.validate().is_ok());
+            let parsed = EscrowSpec::from_bytes(&spec.to_bytes()).unwrap();
+            assert_eq!(parsed, spec);
+        }
+    }
+
+    #[test]
+    fn test_fund_script() {
+        let spec = EscrowSpec::fund(&key(1), &key(2), &key(3), 1_000, "");
+        let script = spec.script().unwrap().unwrap();
+        // OP_2 <33> <33> <33> OP_3 OP_CHECKMULTISIG
+        assert_eq!(script.len(), 1 + 34 * 3 + 2);
+        assert_eq!(script, escrow_script(&key(1), &key(2), &key(3)));
+        assert!(EscrowSpec::release().script().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(EscrowSpec::fund(&key(1), &key(2), &key(3), 0, "")
+            .validate()
+            .is_err());
+        assert!(EscrowSpec::fund(&key(1), &key(1), &key(3), 1, "")
+            .validate()
+            .is_err());
+
+        let mut bytes = EscrowSpec::fund(&key(1), &key(2), &key(3), 1, "").to_bytes();
+        bytes[1] = 0x05; // not a key prefix
+        assert!(EscrowSpec::from_bytes(&bytes).unwrap().validate().is_err());
+
+        assert!(EscrowSpec::from_bytes(&[0x02, 0x00]).is_err());
+        assert!(matches!(
+            EscrowSpec::from_bytes(&[0x01; 20]),
+            Err(SpecError::PayloadTooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn test_trade_order() {
+        use EscrowAction::*;
+        assert!(Fund.can_follow(None));
+        assert!(Release.can_follow(Some(Fund)));
+        assert!(Refund.can_follow(Some(Dispute)));
+        assert!(!Release.can_follow(None));
+        assert!(!Dispute.can_follow(Some(Dispute)));
+        assert!(!Refund.can_follow(Some(Release)));
+        assert!(!Fund.can_follow(Some(Fund)));
+    }
+}
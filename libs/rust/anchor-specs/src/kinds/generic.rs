@@ -0,0 +1,280 @@
+//! Kind 0: Generic Specification
+//!
+//! A generic message is an opaque body with a hint of its content type, so
+//! explorers can show JSON or text payloads of applications that have no
+//! kind of their own. Common MIME types take a single hint byte; any other
+//! type is spelled out after the `CUSTOM_CONTENT_TYPE` hint.
+//!
+//! ## Payload Format
+//!
+//! ```text
+//! ┌───────────┬───────────────────────────────┬──────────┐
+//! │ Type hint │ Content type                  │ Body     │
+//! │ (1 byte)  │ (custom hint only: len + var) │ (var)    │
+//! └───────────┴───────────────────────────────┴──────────┘
+//! ```
+//!
+//! | Hint | Content type |
+//! |------|--------------|
+//! | 0x00 | none (opaque bytes) |
+//! | 0x01 | `text/plain` |
+//! | 0x02 | `application/json` |
+//! | 0x03 | `text/markdown` |
+//! | 0x04 | `image/png` |
+//! | 0x05 | `image/jpeg` |
+//! | 0x06 | `application/cbor` |
+//! | 0xFF | custom: `[len: u8][MIME type]` |
+//!
+//! Kind 0 bodies written before the envelope have no hint byte; readers
+//! should fall back to the raw body when a payload does not parse.
+
+use crate::error::{Result, SpecError};
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+use serde::{Deserialize, Serialize};
+
+/// Hint of a body without a content type
+pub const NO_CONTENT_TYPE: u8 = 0x00;
+
+/// Hint of a content type spelled out in the payload
+pub const CUSTOM_CONTENT_TYPE: u8 = 0xFF;
+
+/// Content types with a hint byte of their own
+pub const CONTENT_TYPE_HINTS: &[(u8, &str)] = &[
+    (0x01, "text/plain"),
+    (0x02, "application/json"),
+    (0x03, "text/markdown"),
+    (0x04, "image/png"),
+    (0x05, "image/jpeg"),
+    (0x06, "application/cbor"),
+];
+
+/// Maximum length of a custom content type
+pub const MAX_CONTENT_TYPE_LENGTH: usize = u8::MAX as usize;
+
+/// Generic specification (Kind 0)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenericSpec {
+    /// MIME type of the body, if known
+    pub content_type: Option<String>,
+    /// Opaque body
+    pub body: Vec<u8>,
+}
+
+impl GenericSpec {
+    /// Body of type `content_type`
+    pub fn new(content_type: impl Into<String>, body: Vec<u8>) -> Self {
+        Self {
+            content_type: Some(content_type.into()),
+            body,
+        }
+    }
+
+    /// Body without a content type
+    pub fn opaque(body: Vec<u8>) -> Self {
+        Self {
+            content_type: None,
+            body,
+        }
+    }
+
+    /// JSON body
+    pub fn json(value: &serde_json::Value) -> Self {
+        Self::new("application/json", value.to_string().into_bytes())
+    }
+
+    /// Content type without parameters, lowercased
+    pub fn mime_type(&self) -> Option<String> {
+        self.content_type.as_ref().map(|content_type| {
+            content_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+    }
+
+    /// Whether the body is UTF-8 text meant to be shown as is
+    pub fn is_text(&self) -> bool {
+        self.mime_type()
+            .is_some_and(|mime| mime.starts_with("text/") || mime == "application/json")
+    }
+
+    /// Hint byte of a known content type
+    fn hint(content_type: &str) -> Option<u8> {
+        CONTENT_TYPE_HINTS
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(content_type))
+            .map(|(hint, _)| *hint)
+    }
+}
+
+impl KindSpec for GenericSpec {
+    const KIND_ID: u8 = 0;
+    const KIND_NAME: &'static str = "Generic";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        let (&hint, rest) = body.split_first().ok_or(SpecError::PayloadTooShort {
+            expected: 1,
+            actual: 0,
+        })?;
+
+        let (content_type, body) = match hint {
+            NO_CONTENT_TYPE => (None, rest),
+            CUSTOM_CONTENT_TYPE => {
+                let (&len, rest) = rest.split_first().ok_or(SpecError::PayloadTooShort {
+                    expected: 2,
+                    actual: 1,
+                })?;
+                let len = len as usize;
+                if rest.len() < len {
+                    return Err(SpecError::PayloadTooShort {
+                        expected: 2 + len,
+                        actual: body.len(),
+                    });
+                }
+                let content_type = String::from_utf8(rest[..len].to_vec())?;
+                (Some(content_type), &rest[len..])
+            }
+            hint => {
+                let (_, known) = CONTENT_TYPE_HINTS
+                    .iter()
+                    .find(|(known, _)| *known == hint)
+                    .ok_or_else(|| {
+                        SpecError::InvalidFormat(format!("Unknown content type hint {:#04x}", hint))
+                    })?;
+                (Some(known.to_string()), rest)
+            }
+        };
+
+        Ok(Self {
+            content_type,
+            body: body.to_vec(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let type_len = self.content_type.as_ref().map_or(0, String::len);
+        let mut result = Vec::with_capacity(2 + type_len + self.body.len());
+        match self.content_type.as_deref() {
+            None => result.push(NO_CONTENT_TYPE),
+            Some(content_type) => match Self::hint(content_type) {
+                Some(hint) => result.push(hint),
+                None => {
+                    result.push(CUSTOM_CONTENT_TYPE);
+                    result.push(content_type.len() as u8);
+                    result.extend_from_slice(content_type.as_bytes());
+                }
+            },
+        }
+        result.extend_from_slice(&self.body);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.body.is_empty() {
+            return Err(SpecError::EmptyContent);
+        }
+
+        if let Some(content_type) = &self.content_type {
+            // Parameters may be separated by spaces, the type itself not
+            let valid = content_type.len() <= MAX_CONTENT_TYPE_LENGTH
+                && content_type
+                    .chars()
+                    .all(|c| c.is_ascii_graphic() || c == ' ')
+                && self.mime_type().is_some_and(|mime| {
+                    mime.chars().all(|c| c.is_ascii_graphic())
+                        && mime.split_once('/').is_some_and(|(kind, subtype)| {
+                            !kind.is_empty() && !subtype.is_empty() && !subtype.contains('/')
+                        })
+                });
+            if !valid {
+                return Err(SpecError::InvalidFormat(format!(
+                    "Invalid content type '{}'",
+                    content_type
+                )));
+            }
+        }
+
+        if self.is_text() {
+            let text = std::str::from_utf8(&self.body)
+                .map_err(|e| SpecError::InvalidFormat(format!("Text body is not UTF-8: {}", e)))?;
+            if self.mime_type().as_deref() == Some("application/json") {
+                serde_json::from_str::<serde_json::Value>(text)
+                    .map_err(|e| SpecError::InvalidFormat(format!("Invalid JSON body: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::Inscription,
+            CarrierType::Stamps,
+            CarrierType::TaprootAnnex,
+            CarrierType::WitnessData,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generic_roundtrip() {
+        let json = GenericSpec::json(&serde_json::json!({ "move": "e4" }));
+        let bytes = json.to_bytes();
+        assert_eq!(bytes[0], 0x02);
+        assert_eq!(GenericSpec::from_bytes(&bytes).unwrap(), json);
+        assert!(json.validate().is_ok());
+        assert!(json.is_text());
+
+        let custom = GenericSpec::new("application/x-chess-pgn", b"1. e4 e5".to_vec());
+        let bytes = custom.to_bytes();
+        assert_eq!(bytes[0], CUSTOM_CONTENT_TYPE);
+        assert_eq!(GenericSpec::from_bytes(&bytes).unwrap(), custom);
+        assert!(!custom.is_text());
+
+        let opaque = GenericSpec::opaque(vec![0xde, 0xad]);
+        assert_eq!(opaque.to_bytes(), vec![NO_CONTENT_TYPE, 0xde, 0xad]);
+        assert_eq!(GenericSpec::from_bytes(&opaque.to_bytes()).unwrap(), opaque);
+    }
+
+    #[test]
+    fn test_generic_validation() {
+        assert!(
+            GenericSpec::new("Text/Plain; charset=utf-8", b"hi".to_vec())
+                .validate()
+                .is_ok()
+        );
+        assert!(matches!(
+            GenericSpec::opaque(Vec::new()).validate(),
+            Err(SpecError::EmptyContent)
+        ));
+        assert!(GenericSpec::new("application/json", b"{".to_vec())
+            .validate()
+            .is_err());
+        assert!(GenericSpec::new("text/plain", vec![0xff])
+            .validate()
+            .is_err());
+        assert!(GenericSpec::new("json", b"{}".to_vec()).validate().is_err());
+        assert!(GenericSpec::new("text/ plain", b"hi".to_vec())
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_generic_rejects_malformed_payloads() {
+        assert!(GenericSpec::from_bytes(&[]).is_err());
+        assert!(GenericSpec::from_bytes(&[0x42, 1, 2]).is_err());
+        assert!(GenericSpec::from_bytes(&[CUSTOM_CONTENT_TYPE]).is_err());
+        assert!(GenericSpec::from_bytes(&[CUSTOM_CONTENT_TYPE, 4, b'a']).is_err());
+    }
+}
@@ -17,6 +17,25 @@
 //!
 //! Total header: 10 bytes + message
 //!
+//! ## Geometry
+//!
+//! A marker may describe a path or an area instead of a single point. The
+//! geometry follows the message as an optional trailing section, so parsers
+//! that predate it read the marker as a plain point:
+//!
+//! ```text
+//! ┌────────────┬────────────┬──────────────────────────────────────┐
+//! │   shape    │   count    │  count × (Δlat, Δlon) zigzag varints │
+//! │   (u8)     │   (u8)     │          in microdegrees             │
+//! └────────────┴────────────┴──────────────────────────────────────┘
+//! ```
+//!
+//! The first vertex is a delta from the marker's own coordinate and every
+//! following vertex a delta from the previous one, so nearby vertices take
+//! two or three bytes each. Polygon rings are closed implicitly (the first
+//! vertex is not repeated), wound counter-clockwise and must not
+//! self-intersect.
+//!
 //! ## Ownership Rule
 //!
 //! The first marker at any exact coordinate "owns" that location. Subsequent
@@ -47,6 +66,12 @@ pub const MAX_MESSAGE_LENGTH: usize = 255;
 /// Header size: category(1) + lat(4) + lon(4) + msg_len(1) = 10 bytes
 pub const HEADER_SIZE: usize = 10;
 
+/// Maximum number of vertices in a geometry
+pub const MAX_GEOMETRY_POINTS: usize = 255;
+
+/// Geometry coordinates are quantized to microdegrees (~11 cm)
+const MICRODEGREES: f64 = 1_000_000.0;
+
 /// Category definitions for GeoMarkers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -95,9 +120,327 @@ impl From<MarkerCategory> for u8 {
     }
 }
 
+/// Shape of a marker geometry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum GeometryShape {
+    /// Open polyline, e.g. a route or a trail
+    Path = 1,
+    /// Closed ring enclosing an area
+    Polygon = 2,
+}
+
+impl TryFrom<u8> for GeometryShape {
+    type Error = SpecError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(Self::Path),
+            2 => Ok(Self::Polygon),
+            n => Err(SpecError::InvalidFormat(format!(
+                "Unknown geometry shape {}",
+                n
+            ))),
+        }
+    }
+}
+
+/// A path or polygon attached to a marker
+///
+/// Vertices are `[latitude, longitude]` pairs in degrees.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub shape: GeometryShape,
+    pub points: Vec<[f64; 2]>,
+}
+
+impl Geometry {
+    /// Create an open path
+    pub fn path(points: Vec<[f64; 2]>) -> Self {
+        Self {
+            shape: GeometryShape::Path,
+            points,
+        }
+    }
+
+    /// Create a polygon, dropping a closing vertex that repeats the first
+    pub fn polygon(mut points: Vec<[f64; 2]>) -> Self {
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        Self {
+            shape: GeometryShape::Polygon,
+            points,
+        }
+    }
+
+    /// Bounding box as `(lat_min, lat_max, lng_min, lng_max)`
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        self.points.iter().fold(
+            (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+            |(lat_min, lat_max, lng_min, lng_max), [lat, lng]| {
+                (
+                    lat_min.min(*lat),
+                    lat_max.max(*lat),
+                    lng_min.min(*lng),
+                    lng_max.max(*lng),
+                )
+            },
+        )
+    }
+
+    /// Twice the signed area of a polygon ring, positive when counter-clockwise
+    ///
+    /// Longitude is treated as x and latitude as y.
+    pub fn signed_area2(&self) -> f64 {
+        self.edges()
+            .map(|([lat1, lng1], [lat2, lng2])| lng1 * lat2 - lng2 * lat1)
+            .sum()
+    }
+
+    /// Whether a point lies inside the polygon (always false for paths)
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        if self.shape != GeometryShape::Polygon {
+            return false;
+        }
+        let mut inside = false;
+        for ([lat1, lng1], [lat2, lng2]) in self.edges() {
+            if (lat1 > latitude) != (lat2 > latitude)
+                && longitude < lng1 + (latitude - lat1) / (lat2 - lat1) * (lng2 - lng1)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Whether the geometry touches the box `[lat_min, lat_max] × [lng_min, lng_max]`
+    pub fn intersects_bounds(
+        &self,
+        lat_min: f64,
+        lat_max: f64,
+        lng_min: f64,
+        lng_max: f64,
+    ) -> bool {
+        let in_box = |[lat, lng]: [f64; 2]| {
+            (lat_min..=lat_max).contains(&lat) && (lng_min..=lng_max).contains(&lng)
+        };
+        if self.points.iter().any(|p| in_box(*p)) {
+            return true;
+        }
+        // A box wholly inside the polygon has no vertex or edge crossing in it
+        if self.contains(lat_min, lng_min) {
+            return true;
+        }
+        let corners = [
+            [lat_min, lng_min],
+            [lat_min, lng_max],
+            [lat_max, lng_max],
+            [lat_max, lng_min],
+        ];
+        self.edges().any(|edge| {
+            (0..4).any(|i| segments_intersect(edge, (corners[i], corners[(i + 1) % 4])))
+        })
+    }
+
+    /// Segments of the geometry, including the closing edge of a polygon
+    fn edges(&self) -> impl Iterator<Item = ([f64; 2], [f64; 2])> + '_ {
+        let closing = match self.shape {
+            GeometryShape::Polygon if self.points.len() > 2 => self
+                .points
+                .last()
+                .copied()
+                .zip(self.points.first().copied()),
+            _ => None,
+        };
+        self.points.windows(2).map(|w| (w[0], w[1])).chain(closing)
+    }
+
+    /// Vertices in microdegrees
+    fn quantized(&self) -> Vec<(i64, i64)> {
+        self.points
+            .iter()
+            .map(|[lat, lng]| (to_micro(*lat), to_micro(*lng)))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        let min_points = match self.shape {
+            GeometryShape::Path => 2,
+            GeometryShape::Polygon => 3,
+        };
+        if self.points.len() < min_points {
+            return Err(SpecError::InvalidFormat(format!(
+                "A {:?} needs at least {} points, got {}",
+                self.shape,
+                min_points,
+                self.points.len()
+            )));
+        }
+        if self.points.len() > MAX_GEOMETRY_POINTS {
+            return Err(SpecError::InvalidFormat(format!(
+                "Geometry has {} points, at most {} allowed",
+                self.points.len(),
+                MAX_GEOMETRY_POINTS
+            )));
+        }
+        for [lat, lng] in &self.points {
+            if !(-90.0..=90.0).contains(lat) || !(-180.0..=180.0).contains(lng) {
+                return Err(SpecError::InvalidFormat(format!(
+                    "Geometry point ({}, {}) out of range",
+                    lat, lng
+                )));
+            }
+        }
+
+        let quantized = self.quantized();
+        if quantized.windows(2).any(|w| w[0] == w[1]) {
+            return Err(SpecError::InvalidFormat(
+                "Geometry repeats a point consecutively".to_string(),
+            ));
+        }
+        if self.shape == GeometryShape::Polygon {
+            if quantized.first() == quantized.last() {
+                return Err(SpecError::InvalidFormat(
+                    "Polygon rings are closed implicitly; drop the repeated first point"
+                        .to_string(),
+                ));
+            }
+            let area = self.signed_area2();
+            if area == 0.0 {
+                return Err(SpecError::InvalidFormat(
+                    "Polygon encloses no area".to_string(),
+                ));
+            }
+            if area < 0.0 {
+                return Err(SpecError::InvalidFormat(
+                    "Polygon must be wound counter-clockwise".to_string(),
+                ));
+            }
+            let edges: Vec<_> = self.edges().collect();
+            for i in 0..edges.len() {
+                // Skip the neighbours, which share a vertex with edge i
+                for j in i + 2..edges.len() {
+                    if i == 0 && j == edges.len() - 1 {
+                        continue;
+                    }
+                    if segments_intersect(edges[i], edges[j]) {
+                        return Err(SpecError::InvalidFormat(
+                            "Polygon edges must not cross".to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the geometry section, deltas starting from the marker coordinate
+    fn encode(&self, origin: (i64, i64), out: &mut Vec<u8>) {
+        out.push(self.shape as u8);
+        out.push(self.points.len().min(MAX_GEOMETRY_POINTS) as u8);
+        let mut previous = origin;
+        for point in self.quantized().into_iter().take(MAX_GEOMETRY_POINTS) {
+            write_varint(zigzag(point.0 - previous.0), out);
+            write_varint(zigzag(point.1 - previous.1), out);
+            previous = point;
+        }
+    }
+
+    fn decode(bytes: &[u8], origin: (i64, i64)) -> Result<Self> {
+        if bytes.len() < 2 {
+            return Err(SpecError::PayloadTooShort {
+                expected: 2,
+                actual: bytes.len(),
+            });
+        }
+        let shape = GeometryShape::try_from(bytes[0])?;
+        let count = bytes[1] as usize;
+        let mut cursor = &bytes[2..];
+        let mut previous = origin;
+        let mut points = Vec::with_capacity(count);
+        for _ in 0..count {
+            let lat = previous.0 + unzigzag(read_varint(&mut cursor)?);
+            let lng = previous.1 + unzigzag(read_varint(&mut cursor)?);
+            points.push([lat as f64 / MICRODEGREES, lng as f64 / MICRODEGREES]);
+            previous = (lat, lng);
+        }
+        Ok(Self { shape, points })
+    }
+
+    fn encoded_size(&self, origin: (i64, i64)) -> usize {
+        let mut out = Vec::new();
+        self.encode(origin, &mut out);
+        out.len()
+    }
+}
+
+fn to_micro(degrees: f64) -> i64 {
+    (degrees * MICRODEGREES).round() as i64
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+fn write_varint(mut n: u64, out: &mut Vec<u8>) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn read_varint(cursor: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = cursor.split_first().ok_or_else(|| {
+            SpecError::InvalidFormat("Truncated geometry coordinates".to_string())
+        })?;
+        *cursor = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(SpecError::InvalidFormat(
+        "Geometry varint too long".to_string(),
+    ))
+}
+
+/// Proper or touching intersection of two segments
+fn segments_intersect(a: ([f64; 2], [f64; 2]), b: ([f64; 2], [f64; 2])) -> bool {
+    fn orient(p: [f64; 2], q: [f64; 2], r: [f64; 2]) -> f64 {
+        (q[1] - p[1]) * (r[0] - p[0]) - (q[0] - p[0]) * (r[1] - p[1])
+    }
+    fn on_segment(p: [f64; 2], q: [f64; 2], r: [f64; 2]) -> bool {
+        r[0] >= p[0].min(q[0])
+            && r[0] <= p[0].max(q[0])
+            && r[1] >= p[1].min(q[1])
+            && r[1] <= p[1].max(q[1])
+    }
+    let (d1, d2) = (orient(b.0, b.1, a.0), orient(b.0, b.1, a.1));
+    let (d3, d4) = (orient(a.0, a.1, b.0), orient(a.0, a.1, b.1));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(b.0, b.1, a.0))
+        || (d2 == 0.0 && on_segment(b.0, b.1, a.1))
+        || (d3 == 0.0 && on_segment(a.0, a.1, b.0))
+        || (d4 == 0.0 && on_segment(a.0, a.1, b.1))
+}
+
 /// GeoMarker specification (Kind 5)
 ///
-/// Represents a geographic location with coordinates and a message.
+/// Represents a geographic location with coordinates and a message,
+/// optionally with a path or polygon anchored at that location.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GeoMarkerSpec {
     /// Marker category (0-255)
@@ -108,6 +451,9 @@ pub struct GeoMarkerSpec {
     pub longitude: f32,
     /// Description text (max 255 bytes)
     pub message: String,
+    /// Path or polygon; `None` for a point marker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<Geometry>,
 }
 
 impl GeoMarkerSpec {
@@ -118,9 +464,16 @@ impl GeoMarkerSpec {
             latitude,
             longitude,
             message: message.into(),
+            geometry: None,
         }
     }
 
+    /// Attach a path or polygon
+    pub fn with_geometry(mut self, geometry: Geometry) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
     /// Create with a typed category
     pub fn with_category(
         category: MarkerCategory,
@@ -138,7 +491,7 @@ impl GeoMarkerSpec {
 
     /// Check if the marker fits in OP_RETURN (legacy 80 bytes)
     pub fn fits_op_return_legacy(&self) -> bool {
-        self.message.len() <= MAX_OP_RETURN_MESSAGE
+        self.payload_size() <= HEADER_SIZE + MAX_OP_RETURN_MESSAGE
     }
 
     /// Check if the marker fits in OP_RETURN (extended 100KB)
@@ -154,7 +507,20 @@ impl GeoMarkerSpec {
 
     /// Calculate the payload size in bytes
     pub fn payload_size(&self) -> usize {
-        HEADER_SIZE + self.message.len().min(MAX_MESSAGE_LENGTH)
+        HEADER_SIZE
+            + self.message.len().min(MAX_MESSAGE_LENGTH)
+            + self
+                .geometry
+                .as_ref()
+                .map_or(0, |g| g.encoded_size(self.origin()))
+    }
+
+    /// The marker coordinate in microdegrees, where geometry deltas start
+    fn origin(&self) -> (i64, i64) {
+        (
+            to_micro(self.latitude as f64),
+            to_micro(self.longitude as f64),
+        )
     }
 }
 
@@ -198,12 +564,12 @@ impl KindSpec for GeoMarkerSpec {
             )));
         }
 
-        Ok(Self {
-            category,
-            latitude,
-            longitude,
-            message,
-        })
+        let mut spec = Self::new(category, latitude, longitude, message);
+        let rest = &body[HEADER_SIZE + msg_len..];
+        if !rest.is_empty() {
+            spec.geometry = Some(Geometry::decode(rest, spec.origin())?);
+        }
+        Ok(spec)
     }
 
     fn to_bytes(&self) -> Vec<u8> {
@@ -216,6 +582,9 @@ impl KindSpec for GeoMarkerSpec {
         payload.extend_from_slice(&self.longitude.to_be_bytes());
         payload.push(msg_len as u8);
         payload.extend_from_slice(&msg_bytes[..msg_len]);
+        if let Some(geometry) = &self.geometry {
+            geometry.encode(self.origin(), &mut payload);
+        }
 
         payload
     }
@@ -248,6 +617,10 @@ impl KindSpec for GeoMarkerSpec {
             });
         }
 
+        if let Some(geometry) = &self.geometry {
+            geometry.validate()?;
+        }
+
         Ok(())
     }
 
@@ -329,6 +702,123 @@ mod tests {
         assert!(max.fits_op_return());
     }
 
+    fn square() -> Geometry {
+        Geometry::polygon(vec![
+            [48.85, 2.29],
+            [48.85, 2.30],
+            [48.86, 2.30],
+            [48.86, 2.29],
+        ])
+    }
+
+    #[test]
+    fn test_geometry_roundtrip() {
+        let spec = GeoMarkerSpec::new(4, 48.855, 2.295, "Champ de Mars").with_geometry(square());
+        assert!(spec.validate().is_ok());
+
+        let bytes = spec.to_bytes();
+        assert_eq!(bytes.len(), spec.payload_size());
+        let parsed = GeoMarkerSpec::from_bytes(&bytes).unwrap();
+        let geometry = parsed.geometry.unwrap();
+        assert_eq!(geometry.shape, GeometryShape::Polygon);
+        for (a, b) in geometry.points.iter().zip(&square().points) {
+            assert!((a[0] - b[0]).abs() < 1e-6 && (a[1] - b[1]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_geometry_is_compact() {
+        let spec = GeoMarkerSpec::new(0, 48.855, 2.295, "Park").with_geometry(square());
+        // 4 vertices within a few hundred metres: at most 3 bytes per delta
+        assert!(spec.payload_size() <= HEADER_SIZE + 4 + 2 + 4 * 6);
+    }
+
+    #[test]
+    fn test_point_marker_unchanged() {
+        let spec = GeoMarkerSpec::new(1, 48.8566, 2.3522, "Eiffel Tower");
+        let bytes = spec.to_bytes();
+        assert_eq!(bytes.len(), HEADER_SIZE + 12);
+        assert!(GeoMarkerSpec::from_bytes(&bytes)
+            .unwrap()
+            .geometry
+            .is_none());
+    }
+
+    #[test]
+    fn test_polygon_closure_is_implicit() {
+        let mut closed = square().points;
+        closed.push(closed[0]);
+        assert_eq!(Geometry::polygon(closed.clone()), square());
+
+        let explicit = Geometry {
+            shape: GeometryShape::Polygon,
+            points: closed,
+        };
+        assert!(explicit.validate().is_err());
+    }
+
+    #[test]
+    fn test_polygon_winding() {
+        let mut clockwise = square().points;
+        clockwise.reverse();
+        assert!(square().signed_area2() > 0.0);
+        assert!(Geometry::polygon(clockwise).validate().is_err());
+    }
+
+    #[test]
+    fn test_polygon_rejects_self_intersection() {
+        let bowtie = Geometry::polygon(vec![[0.0, 0.0], [0.0, 2.0], [1.0, 0.0], [1.0, 1.0]]);
+        assert!(bowtie.validate().is_err());
+    }
+
+    #[test]
+    fn test_geometry_point_counts() {
+        assert!(Geometry::path(vec![[0.0, 0.0]]).validate().is_err());
+        assert!(Geometry::path(vec![[0.0, 0.0], [0.0, 0.0]])
+            .validate()
+            .is_err());
+        assert!(Geometry::path(vec![[0.0, 0.0], [0.5, 0.5]])
+            .validate()
+            .is_ok());
+        assert!(Geometry::polygon(vec![[0.0, 0.0], [0.0, 1.0]])
+            .validate()
+            .is_err());
+        assert!(Geometry::path(vec![[0.0, 0.0], [91.0, 0.0]])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_truncated_geometry_rejected() {
+        let spec = GeoMarkerSpec::new(0, 48.855, 2.295, "Park").with_geometry(square());
+        let bytes = spec.to_bytes();
+        assert!(GeoMarkerSpec::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_intersects_bounds() {
+        let area = square();
+        // Box inside the polygon
+        assert!(area.intersects_bounds(48.854, 48.856, 2.294, 2.296));
+        // Box containing a vertex
+        assert!(area.intersects_bounds(48.84, 48.851, 2.28, 2.291));
+        // Box crossed by an edge with no vertex inside
+        assert!(area.intersects_bounds(48.84, 48.87, 2.295, 2.296));
+        // Disjoint box
+        assert!(!area.intersects_bounds(48.87, 48.88, 2.29, 2.30));
+
+        let path = Geometry::path(vec![[0.0, 0.0], [1.0, 1.0]]);
+        assert!(path.intersects_bounds(0.4, 0.6, 0.4, 0.6));
+        assert!(!path.intersects_bounds(0.0, 0.2, 0.8, 1.0));
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(square().contains(48.855, 2.295));
+        assert!(!square().contains(48.87, 2.295));
+        assert!(!Geometry::path(square().points).contains(48.855, 2.295));
+    }
+
     #[test]
     fn test_supported_carriers() {
         assert!(GeoMarkerSpec::supported_carriers().contains(&CarrierType::OpReturn));
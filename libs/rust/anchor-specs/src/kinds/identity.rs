@@ -0,0 +1,292 @@
+//! Kind 6: Identity Rotation Specification
+//!
+//! An identity rotation lets a long-lived author move to a new key without
+//! losing the association with their history. The message is signed by the
+//! old key and names the new key (and optionally a new address); indexers
+//! chain rotations so every key in the chain resolves to the same identity.
+//!
+//! ## Payload Format
+//!
+//! ```text
+//! ┌───────────┬─────────┬─────────┬─────────────┬─────────┬───────────┐
+//! │ Operation │ Old key │ New key │ Address len │ Address │ Signature │
+//! │ (1 byte)  │ (32)    │ (32)    │ (1 byte)    │ (UTF-8) │ (64)      │
+//! └───────────┴─────────┴─────────┴─────────────┴─────────┴───────────┘
+//! ```
+//!
+//! Keys are x-only secp256k1 public keys. The signature is BIP-340 Schnorr
+//! by the old key over [`IdentityRotationSpec::signing_hash`], which commits
+//! to every field before the signature.
+//!
+//! ## Chaining
+//!
+//! Each key can rotate away once: the first valid rotation from a key wins
+//! and later ones from the same key are ignored. A rotation may anchor the
+//! previous rotation in the chain, but the chain is defined by the keys, not
+//! the anchors.
+
+use crate::error::{Result, SpecError};
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{schnorr, Keypair, Message, Secp256k1, XOnlyPublicKey};
+use serde::{Deserialize, Serialize};
+
+/// Size of an x-only public key
+pub const KEY_SIZE: usize = 32;
+
+/// Size of a Schnorr signature
+pub const SIGNATURE_SIZE: usize = 64;
+
+/// Minimum payload size (operation + keys + address length + signature)
+pub const ROTATION_MIN_SIZE: usize = 1 + KEY_SIZE * 2 + 1 + SIGNATURE_SIZE;
+
+/// Maximum length of the new address
+pub const MAX_ADDRESS_LENGTH: usize = 255;
+
+/// Domain separator for the signed hash
+const SIGNING_TAG: &[u8] = b"ANCHOR identity rotation";
+
+/// Identity operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum IdentityOperation {
+    /// Hand the identity over from the old key to the new key
+    Rotate = 1,
+}
+
+impl IdentityOperation {
+    /// Parse from byte value
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(IdentityOperation::Rotate),
+            _ => None,
+        }
+    }
+}
+
+/// Identity rotation specification (Kind 6)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityRotationSpec {
+    /// Operation type
+    pub operation: IdentityOperation,
+    /// Key being retired (x-only)
+    pub old_pubkey: [u8; 32],
+    /// Key taking over the identity (x-only)
+    pub new_pubkey: [u8; 32],
+    /// New address for the identity, if it changes
+    pub new_address: Option<String>,
+    /// Schnorr signature by the old key (64 bytes)
+    pub signature: Vec<u8>,
+}
+
+impl IdentityRotationSpec {
+    /// Create an unsigned rotation from `old_pubkey` to `new_pubkey`
+    pub fn new(
+        old_pubkey: XOnlyPublicKey,
+        new_pubkey: XOnlyPublicKey,
+        new_address: Option<String>,
+    ) -> Self {
+        Self {
+            operation: IdentityOperation::Rotate,
+            old_pubkey: old_pubkey.serialize(),
+            new_pubkey: new_pubkey.serialize(),
+            new_address,
+            signature: Vec::new(),
+        }
+    }
+
+    /// Create a rotation signed by `old_keypair`
+    pub fn signed(
+        old_keypair: &Keypair,
+        new_pubkey: XOnlyPublicKey,
+        new_address: Option<String>,
+    ) -> Self {
+        let mut spec = Self::new(old_keypair.x_only_public_key().0, new_pubkey, new_address);
+        let secp = Secp256k1::signing_only();
+        let message = Message::from_digest(spec.signing_hash());
+        spec.signature = secp
+            .sign_schnorr_no_aux_rand(&message, old_keypair)
+            .as_ref()
+            .to_vec();
+        spec
+    }
+
+    /// Hash signed by the old key
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut engine = sha256::Hash::engine();
+        engine.input(SIGNING_TAG);
+        engine.input(&self.unsigned_bytes());
+        sha256::Hash::from_engine(engine).to_byte_array()
+    }
+
+    /// Check the signature against the old key
+    pub fn verify_signature(&self) -> Result<()> {
+        let old_pubkey = XOnlyPublicKey::from_slice(&self.old_pubkey)
+            .map_err(|e| SpecError::InvalidFormat(format!("Invalid old key: {}", e)))?;
+        let signature = schnorr::Signature::from_slice(&self.signature)
+            .map_err(|e| SpecError::InvalidSignature(e.to_string()))?;
+
+        Secp256k1::verification_only()
+            .verify_schnorr(
+                &signature,
+                &Message::from_digest(self.signing_hash()),
+                &old_pubkey,
+            )
+            .map_err(|e| SpecError::InvalidSignature(e.to_string()))
+    }
+
+    /// Every field before the signature, as encoded on chain
+    fn unsigned_bytes(&self) -> Vec<u8> {
+        let address = self.new_address.as_deref().unwrap_or_default().as_bytes();
+        let mut result = Vec::with_capacity(ROTATION_MIN_SIZE + address.len());
+        result.push(self.operation as u8);
+        result.extend_from_slice(&self.old_pubkey);
+        result.extend_from_slice(&self.new_pubkey);
+        result.push(address.len() as u8);
+        result.extend_from_slice(address);
+        result
+    }
+}
+
+impl KindSpec for IdentityRotationSpec {
+    const KIND_ID: u8 = 6;
+    const KIND_NAME: &'static str = "Identity";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        if body.len() < ROTATION_MIN_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: ROTATION_MIN_SIZE,
+                actual: body.len(),
+            });
+        }
+
+        let operation =
+            IdentityOperation::from_u8(body[0]).ok_or(SpecError::InvalidOperation(body[0]))?;
+        let old_pubkey: [u8; 32] = body[1..33].try_into().unwrap();
+        let new_pubkey: [u8; 32] = body[33..65].try_into().unwrap();
+        let address_len = body[65] as usize;
+
+        let sig_start = 66 + address_len;
+        if body.len() < sig_start + SIGNATURE_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: sig_start + SIGNATURE_SIZE,
+                actual: body.len(),
+            });
+        }
+        if body.len() > sig_start + SIGNATURE_SIZE {
+            return Err(SpecError::InvalidFormat(format!(
+                "{} trailing bytes after signature",
+                body.len() - sig_start - SIGNATURE_SIZE
+            )));
+        }
+
+        let new_address = match address_len {
+            0 => None,
+            _ => Some(String::from_utf8(body[66..sig_start].to_vec())?),
+        };
+
+        Ok(Self {
+            operation,
+            old_pubkey,
+            new_pubkey,
+            new_address,
+            signature: body[sig_start..].to_vec(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = self.unsigned_bytes();
+        result.extend_from_slice(&self.signature);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.old_pubkey == self.new_pubkey {
+            return Err(SpecError::InvalidFormat(
+                "New key must differ from the old key".to_string(),
+            ));
+        }
+        XOnlyPublicKey::from_slice(&self.new_pubkey)
+            .map_err(|e| SpecError::InvalidFormat(format!("Invalid new key: {}", e)))?;
+        if let Some(address) = &self.new_address {
+            if address.is_empty() || address.len() > MAX_ADDRESS_LENGTH {
+                return Err(SpecError::InvalidFormat(format!(
+                    "Address must be 1-{} bytes, got {}",
+                    MAX_ADDRESS_LENGTH,
+                    address.len()
+                )));
+            }
+        }
+        self.verify_signature()
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::WitnessData,
+            CarrierType::Inscription,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(byte: u8) -> Keypair {
+        Keypair::from_seckey_slice(&Secp256k1::new(), &[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_signed_roundtrip() {
+        let new_key = keypair(2).x_only_public_key().0;
+        let spec = IdentityRotationSpec::signed(&keypair(1), new_key, Some("bcrt1qnew".into()));
+        assert!(spec.validate().is_ok());
+
+        let bytes = spec.to_bytes();
+        let parsed = IdentityRotationSpec::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, spec);
+        assert!(parsed.validate().is_ok());
+    }
+
+    #[test]
+    fn test_signature_must_match_old_key() {
+        let new_key = keypair(2).x_only_public_key().0;
+        let mut spec = IdentityRotationSpec::signed(&keypair(1), new_key, None);
+
+        // Claiming a different old key invalidates the signature
+        spec.old_pubkey = keypair(3).x_only_public_key().0.serialize();
+        assert!(matches!(
+            spec.validate(),
+            Err(SpecError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_signature_covers_address() {
+        let new_key = keypair(2).x_only_public_key().0;
+        let mut spec = IdentityRotationSpec::signed(&keypair(1), new_key, Some("a".into()));
+        spec.new_address = Some("b".into());
+        assert!(spec.verify_signature().is_err());
+    }
+
+    #[test]
+    fn test_rejects_self_rotation() {
+        let key = keypair(1);
+        let spec = IdentityRotationSpec::signed(&key, key.x_only_public_key().0, None);
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn test_truncated_payload() {
+        assert!(matches!(
+            IdentityRotationSpec::from_bytes(&[1; 10]),
+            Err(SpecError::PayloadTooShort { .. })
+        ));
+    }
+}
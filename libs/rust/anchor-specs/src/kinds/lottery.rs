@@ -0,0 +1,615 @@
+//! Kinds 45-48: Lottery Create, Ticket, Draw, and Claim Specifications
+//!
+//! A lottery sells numbered tickets until its sales close and then draws one
+//! winning ticket with randomness neither the operator nor a miner can pick
+//! alone:
+//!
+//! 1. [`LotteryCreateSpec`] commits to `SHA256(secret)` before any ticket is
+//!    sold, and names the draw height, a block mined after sales close.
+//! 2. [`LotteryTicketSpec`] buys tickets; the transaction must pay
+//!    `count * ticket_price` to the operator's pot script.
+//! 3. Once the draw block is mined, [`LotteryDrawSpec`] reveals the secret.
+//!    The draw seed is `SHA256(secret || draw block hash)` and the winning
+//!    ticket is the seed, read as a big-endian integer, modulo tickets sold.
+//! 4. [`LotteryClaimSpec`] claims the prize for the winning ticket, or a
+//!    refund for any ticket of a void lottery. A claim only counts when its
+//!    transaction spends an output of the ticket transaction, which only the
+//!    buyer can do.
+//!
+//! The operator learns the block hash only after committing, and a miner
+//! learns the secret only after the block is mined. An operator who doesn't
+//! like the outcome can withhold the reveal, so a lottery with no valid draw
+//! within the reveal window after the draw height is void and every ticket
+//! is refunded.
+//!
+//! Lottery IDs are create transaction IDs in display byte order. Tickets
+//! are numbered from 0 in chain order.
+//!
+//! ## Create Payload Format
+//!
+//! ```text
+//! ┌──────────┬─────────┬───────────┬─────────┬─────────┬────────────┬──────────┬─────────┐
+//! │ Ticket   │ Max     │ Sales end │ Draw    │ Fee     │ Commitment │ Operator │ Title   │
+//! │ price    │ tickets │ height    │ height  │ (bps)   │ (32)       │ key (33) │ (UTF-8) │
+//! │ (8, BE)  │ (4, BE) │ (4, BE)   │ (4, BE) │ (2, BE) │            │          │         │
+//! └──────────┴─────────┴───────────┴─────────┴─────────┴────────────┴──────────┴─────────┘
+//! ```
+//!
+//! ## Ticket, Draw, and Claim Payload Formats
+//!
+//! ```text
+//! Ticket: [lottery_id: 32][count: 2, BE][buyer key: 33]
+//! Draw:   [lottery_id: 32][secret: 32]
+//! Claim:  [lottery_id: 32][ticket_txid: 32]
+//! ```
+
+use crate::error::{Result, SpecError};
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{CompressedPublicKey, ScriptBuf};
+use serde::{Deserialize, Serialize};
+
+/// Size of a compressed public key
+pub const KEY_SIZE: usize = 33;
+
+/// Size of a Create payload before the title
+pub const CREATE_MIN_SIZE: usize = 8 + 4 + 4 + 4 + 2 + 32 + KEY_SIZE;
+
+/// Size of a Ticket payload
+pub const TICKET_SIZE: usize = 32 + 2 + KEY_SIZE;
+
+/// Size of a Draw payload
+pub const DRAW_SIZE: usize = 64;
+
+/// Size of a Claim payload
+pub const CLAIM_SIZE: usize = 64;
+
+/// Longest lottery title
+pub const MAX_TITLE_LENGTH: usize = 256;
+
+/// Basis points in a whole pot
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Default number of blocks after the draw height in which the secret must
+/// be revealed
+pub const DEFAULT_REVEAL_WINDOW_BLOCKS: u32 = 144;
+
+/// Commitment to a draw secret, published at creation
+pub fn commitment(secret: &[u8; 32]) -> [u8; 32] {
+    sha256::Hash::hash(secret).to_byte_array()
+}
+
+/// Draw seed from the revealed secret and the hash of the draw block
+/// (internal byte order, as in the block header)
+pub fn draw_seed(secret: &[u8; 32], block_hash: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(secret);
+    data[32..].copy_from_slice(block_hash);
+    sha256::Hash::hash(&data).to_byte_array()
+}
+
+/// Number of the winning ticket: the seed as a big-endian integer, modulo
+/// the tickets sold
+pub fn winning_ticket(seed: &[u8; 32], tickets_sold: u32) -> Option<u32> {
+    if tickets_sold == 0 {
+        return None;
+    }
+    let modulus = tickets_sold as u64;
+    let ticket = seed
+        .iter()
+        .fold(0u64, |acc, &byte| ((acc << 8) | byte as u64) % modulus);
+    Some(ticket as u32)
+}
+
+/// Check whether a draw confirmed at `height` reveals in time for a lottery
+/// drawn at `draw_height`: after the draw block and within `window` blocks
+pub fn reveal_window_open(draw_height: u32, height: u32, window: u32) -> bool {
+    height > draw_height && height - draw_height <= window
+}
+
+fn check_key(role: &str, key: &[u8]) -> Result<CompressedPublicKey> {
+    CompressedPublicKey::from_slice(key)
+        .map_err(|e| SpecError::InvalidFormat(format!("Invalid {} key: {}", role, e)))
+}
+
+fn check_exact_size(body: &[u8], size: usize) -> Result<()> {
+    if body.len() < size {
+        return Err(SpecError::PayloadTooShort {
+            expected: size,
+            actual: body.len(),
+        });
+    }
+    if body.len() > size {
+        return Err(SpecError::InvalidFormat(format!(
+            "{} trailing bytes after payload",
+            body.len() - size
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Create
+// ============================================================================
+
+/// Lottery create specification (Kind 45)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LotteryCreateSpec {
+    /// Price of one ticket in satoshis
+    pub ticket_price: u64,
+    /// Most tickets that can be sold, 0 for no limit
+    pub max_tickets: u32,
+    /// Last block in which tickets can be bought
+    pub sales_end_height: u32,
+    /// Block whose hash seeds the draw, after sales close
+    pub draw_height: u32,
+    /// Operator fee in basis points of the pot
+    pub fee_bps: u16,
+    /// `SHA256` of the operator's draw secret
+    pub commitment: [u8; 32],
+    /// Operator key (33 bytes, compressed); ticket payments go to its P2WPKH
+    pub operator: Vec<u8>,
+    /// Human-readable title
+    pub title: String,
+}
+
+impl LotteryCreateSpec {
+    /// Whether tickets confirmed at `height` are sold
+    pub fn sales_open(&self, height: u32) -> bool {
+        height <= self.sales_end_height
+    }
+
+    /// Satoshis paid for `tickets_sold` tickets
+    pub fn pot(&self, tickets_sold: u32) -> u64 {
+        self.ticket_price.saturating_mul(tickets_sold as u64)
+    }
+
+    /// Operator fee out of the pot of `tickets_sold` tickets
+    pub fn fee(&self, tickets_sold: u32) -> u64 {
+        (self.pot(tickets_sold) as u128 * self.fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// Prize of the winning ticket
+    pub fn prize(&self, tickets_sold: u32) -> u64 {
+        self.pot(tickets_sold) - self.fee(tickets_sold)
+    }
+
+    /// Script ticket transactions pay into
+    pub fn pot_script(&self) -> Result<ScriptBuf> {
+        let operator = check_key("operator", &self.operator)?;
+        Ok(ScriptBuf::new_p2wpkh(&operator.wpubkey_hash()))
+    }
+
+    /// Whether `secret` opens the commitment
+    pub fn verify_secret(&self, secret: &[u8; 32]) -> bool {
+        commitment(secret) == self.commitment
+    }
+}
+
+impl KindSpec for LotteryCreateSpec {
+    const KIND_ID: u8 = 45;
+    const KIND_NAME: &'static str = "LotteryCreate";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        if body.len() < CREATE_MIN_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: CREATE_MIN_SIZE,
+                actual: body.len(),
+            });
+        }
+
+        Ok(Self {
+            ticket_price: u64::from_be_bytes(body[0..8].try_into().unwrap()),
+            max_tickets: u32::from_be_bytes(body[8..12].try_into().unwrap()),
+            sales_end_height: u32::from_be_bytes(body[12..16].try_into().unwrap()),
+            draw_height: u32::from_be_bytes(body[16..20].try_into().unwrap()),
+            fee_bps: u16::from_be_bytes([body[20], body[21]]),
+            commitment: body[22..54].try_into().unwrap(),
+            operator: body[54..CREATE_MIN_SIZE].to_vec(),
+            title: String::from_utf8(body[CREATE_MIN_SIZE..].to_vec())?,
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(CREATE_MIN_SIZE + self.title.len());
+        result.extend_from_slice(&self.ticket_price.to_be_bytes());
+        result.extend_from_slice(&self.max_tickets.to_be_bytes());
+        result.extend_from_slice(&self.sales_end_height.to_be_bytes());
+        result.extend_from_slice(&self.draw_height.to_be_bytes());
+        result.extend_from_slice(&self.fee_bps.to_be_bytes());
+        result.extend_from_slice(&self.commitment);
+        result.extend_from_slice(&self.operator);
+        result.extend_from_slice(self.title.as_bytes());
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.ticket_price == 0 {
+            return Err(SpecError::InvalidAmount(
+                "Ticket price cannot be zero".to_string(),
+            ));
+        }
+        if self.draw_height <= self.sales_end_height {
+            return Err(SpecError::InvalidFormat(format!(
+                "Draw height {} must be after sales end {}",
+                self.draw_height, self.sales_end_height
+            )));
+        }
+        if self.fee_bps > BPS_DENOMINATOR {
+            return Err(SpecError::InvalidFormat(format!(
+                "Fee of {} bps exceeds the pot",
+                self.fee_bps
+            )));
+        }
+        if self.title.len() > MAX_TITLE_LENGTH {
+            return Err(SpecError::InvalidFormat(format!(
+                "Title must be at most {} bytes, got {}",
+                MAX_TITLE_LENGTH,
+                self.title.len()
+            )));
+        }
+        check_key("operator", &self.operator)?;
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::WitnessData,
+            CarrierType::Inscription,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+// ============================================================================
+// Ticket
+// ============================================================================
+
+/// Lottery ticket specification (Kind 46)
+///
+/// The transaction must pay at least `count * ticket_price` to the lottery's
+/// pot script and be confirmed while sales are open.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LotteryTicketSpec {
+    /// Lottery create transaction (display byte order)
+    pub lottery_id: [u8; 32],
+    /// Tickets bought
+    pub count: u16,
+    /// Buyer key (33 bytes, compressed)
+    pub buyer: Vec<u8>,
+}
+
+impl KindSpec for LotteryTicketSpec {
+    const KIND_ID: u8 = 46;
+    const KIND_NAME: &'static str = "LotteryTicket";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        check_exact_size(body, TICKET_SIZE)?;
+        Ok(Self {
+            lottery_id: body[0..32].try_into().unwrap(),
+            count: u16::from_be_bytes([body[32], body[33]]),
+            buyer: body[34..TICKET_SIZE].to_vec(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(TICKET_SIZE);
+        result.extend_from_slice(&self.lottery_id);
+        result.extend_from_slice(&self.count.to_be_bytes());
+        result.extend_from_slice(&self.buyer);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.count == 0 {
+            return Err(SpecError::InvalidAmount(
+                "Ticket count cannot be zero".to_string(),
+            ));
+        }
+        check_key("buyer", &self.buyer)?;
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        // The payment to the pot goes in the same transaction
+        &[CarrierType::OpReturn, CarrierType::WitnessData]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+// ============================================================================
+// Draw
+// ============================================================================
+
+/// Lottery draw specification (Kind 47)
+///
+/// Valid when the secret opens the lottery's commitment and the transaction
+/// confirms inside the reveal window; see [`reveal_window_open`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LotteryDrawSpec {
+    /// Lottery create transaction (display byte order)
+    pub lottery_id: [u8; 32],
+    /// Secret committed to at creation
+    pub secret: [u8; 32],
+}
+
+impl LotteryDrawSpec {
+    /// Draw seed, given the hash of the lottery's draw block
+    pub fn seed(&self, draw_block_hash: &[u8; 32]) -> [u8; 32] {
+        draw_seed(&self.secret, draw_block_hash)
+    }
+}
+
+impl KindSpec for LotteryDrawSpec {
+    const KIND_ID: u8 = 47;
+    const KIND_NAME: &'static str = "LotteryDraw";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        check_exact_size(body, DRAW_SIZE)?;
+        Ok(Self {
+            lottery_id: body[0..32].try_into().unwrap(),
+            secret: body[32..DRAW_SIZE].try_into().unwrap(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(DRAW_SIZE);
+        result.extend_from_slice(&self.lottery_id);
+        result.extend_from_slice(&self.secret);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::WitnessData,
+            CarrierType::Inscription,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+// ============================================================================
+// Claim
+// ============================================================================
+
+/// Lottery claim specification (Kind 48)
+///
+/// Claims the prize for the winning ticket of a drawn lottery, or the refund
+/// of a ticket of a void one. The transaction must spend an output of the
+/// ticket transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LotteryClaimSpec {
+    /// Lottery create transaction (display byte order)
+    pub lottery_id: [u8; 32],
+    /// Ticket transaction (display byte order)
+    pub ticket_txid: [u8; 32],
+}
+
+impl KindSpec for LotteryClaimSpec {
+    const KIND_ID: u8 = 48;
+    const KIND_NAME: &'static str = "LotteryClaim";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        check_exact_size(body, CLAIM_SIZE)?;
+        Ok(Self {
+            lottery_id: body[0..32].try_into().unwrap(),
+            ticket_txid: body[32..CLAIM_SIZE].try_into().unwrap(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(CLAIM_SIZE);
+        result.extend_from_slice(&self.lottery_id);
+        result.extend_from_slice(&self.ticket_txid);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.ticket_txid == self.lottery_id {
+            return Err(SpecError::InvalidFormat(
+                "A lottery's create transaction is not a ticket".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        // The claim spends the ticket output, so it can't be an inscription
+        // reveal paying elsewhere
+        &[CarrierType::OpReturn, CarrierType::WitnessData]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+
+    fn key(byte: u8) -> Vec<u8> {
+        let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+        secret.public_key(&Secp256k1::new()).serialize().to_vec()
+    }
+
+    fn create() -> LotteryCreateSpec {
+        LotteryCreateSpec {
+            ticket_price: 10_000,
+            max_tickets: 1_000,
+            sales_end_height: 900_000,
+            draw_height: 900_006,
+            fee_bps: 250,
+            commitment: commitment(&[7u8; 32]),
+            operator: key(1),
+            title: "Weekly draw".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let create = create();
+        assert!(create.validate().is_ok());
+        assert_eq!(
+            LotteryCreateSpec::from_bytes(&create.to_bytes()).unwrap(),
+            create
+        );
+
+        let ticket = LotteryTicketSpec {
+            lottery_id: [2u8; 32],
+            count: 3,
+            buyer: key(2),
+        };
+        assert!(ticket.validate().is_ok());
+        assert_eq!(ticket.to_bytes().len(), TICKET_SIZE);
+        assert_eq!(
+            LotteryTicketSpec::from_bytes(&ticket.to_bytes()).unwrap(),
+            ticket
+        );
+
+        let draw = LotteryDrawSpec {
+            lottery_id: [2u8; 32],
+            secret: [7u8; 32],
+        };
+        assert_eq!(LotteryDrawSpec::from_bytes(&draw.to_bytes()).unwrap(), draw);
+
+        let claim = LotteryClaimSpec {
+            lottery_id: [2u8; 32],
+            ticket_txid: [3u8; 32],
+        };
+        assert!(claim.validate().is_ok());
+        assert_eq!(
+            LotteryClaimSpec::from_bytes(&claim.to_bytes()).unwrap(),
+            claim
+        );
+    }
+
+    #[test]
+    fn test_create_validation() {
+        let mut spec = create();
+        spec.ticket_price = 0;
+        assert!(spec.validate().is_err());
+
+        let mut spec = create();
+        spec.draw_height = spec.sales_end_height;
+        assert!(spec.validate().is_err());
+
+        let mut spec = create();
+        spec.fee_bps = BPS_DENOMINATOR + 1;
+        assert!(spec.validate().is_err());
+
+        let mut spec = create();
+        spec.operator[0] = 0x05; // not a key prefix
+        assert!(spec.validate().is_err());
+
+        let mut spec = create();
+        spec.title = "x".repeat(MAX_TITLE_LENGTH + 1);
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_payloads() {
+        assert!(matches!(
+            LotteryCreateSpec::from_bytes(&[0u8; CREATE_MIN_SIZE - 1]),
+            Err(SpecError::PayloadTooShort { .. })
+        ));
+        assert!(LotteryTicketSpec::from_bytes(&[0u8; TICKET_SIZE + 1]).is_err());
+        assert!(LotteryDrawSpec::from_bytes(&[0u8; DRAW_SIZE - 1]).is_err());
+        assert!(LotteryClaimSpec::from_bytes(&[0u8; CLAIM_SIZE + 1]).is_err());
+
+        let ticket = LotteryTicketSpec {
+            lottery_id: [2u8; 32],
+            count: 0,
+            buyer: key(2),
+        };
+        assert!(ticket.validate().is_err());
+        let claim = LotteryClaimSpec {
+            lottery_id: [2u8; 32],
+            ticket_txid: [2u8; 32],
+        };
+        assert!(claim.validate().is_err());
+    }
+
+    #[test]
+    fn test_commit_reveal() {
+        let spec = create();
+        assert!(spec.verify_secret(&[7u8; 32]));
+        assert!(!spec.verify_secret(&[8u8; 32]));
+
+        // The seed depends on both the secret and the block
+        let draw = LotteryDrawSpec {
+            lottery_id: [2u8; 32],
+            secret: [7u8; 32],
+        };
+        let seed = draw.seed(&[1u8; 32]);
+        assert_eq!(seed, draw_seed(&[7u8; 32], &[1u8; 32]));
+        assert_ne!(seed, draw.seed(&[2u8; 32]));
+        assert_ne!(seed, draw_seed(&[8u8; 32], &[1u8; 32]));
+    }
+
+    #[test]
+    fn test_winning_ticket() {
+        assert_eq!(winning_ticket(&[0xff; 32], 0), None);
+        assert_eq!(winning_ticket(&[0xff; 32], 1), Some(0));
+
+        let mut seed = [0u8; 32];
+        seed[31] = 10;
+        assert_eq!(winning_ticket(&seed, 7), Some(3));
+        seed[30] = 1; // 266
+        assert_eq!(winning_ticket(&seed, 7), Some(0));
+
+        let ticket = winning_ticket(&draw_seed(&[7u8; 32], &[1u8; 32]), 1_000).unwrap();
+        assert!(ticket < 1_000);
+    }
+
+    #[test]
+    fn test_pot_and_windows() {
+        let spec = create();
+        assert_eq!(spec.pot(10), 100_000);
+        assert_eq!(spec.fee(10), 2_500);
+        assert_eq!(spec.prize(10), 97_500);
+        assert!(spec.pot_script().unwrap().is_p2wpkh());
+
+        assert!(spec.sales_open(spec.sales_end_height));
+        assert!(!spec.sales_open(spec.sales_end_height + 1));
+
+        let window = DEFAULT_REVEAL_WINDOW_BLOCKS;
+        assert!(!reveal_window_open(
+            spec.draw_height,
+            spec.draw_height,
+            window
+        ));
+        assert!(reveal_window_open(
+            spec.draw_height,
+            spec.draw_height + 1,
+            window
+        ));
+        assert!(reveal_window_open(
+            spec.draw_height,
+            spec.draw_height + window,
+            window
+        ));
+        assert!(!reveal_window_open(
+            spec.draw_height,
+            spec.draw_height + window + 1,
+            window
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! Kind 7: Mirror Specification
+//!
+//! A mirror republishes an existing message on another carrier, typically
+//! moving content from prunable witness data to Stamps so it survives
+//! pruning nodes. The body carries the original kind and body unchanged,
+//! and the first anchor points at the original message, so indexers can
+//! link the two as duplicates instead of treating the copy as new content.
+//!
+//! ## Payload Format
+//!
+//! ```text
+//! ┌───────────────┬───────────────────────┐
+//! │ Original kind │ Original body         │
+//! │ (1 byte)      │ (variable)            │
+//! └───────────────┴───────────────────────┘
+//! ```
+//!
+//! Application indexers only see kind 7, so a mirrored token transfer or
+//! DNS update is never applied twice. Mirrors of mirrors are invalid;
+//! mirror the original instead.
+
+use crate::error::{Result, SpecError};
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+use serde::{Deserialize, Serialize};
+
+/// Mirror specification (Kind 7)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorSpec {
+    /// Kind of the mirrored message
+    pub kind: u8,
+    /// Body of the mirrored message
+    pub body: Vec<u8>,
+}
+
+impl MirrorSpec {
+    /// Mirror a message of `kind` with `body`
+    pub fn new(kind: u8, body: Vec<u8>) -> Self {
+        Self { kind, body }
+    }
+}
+
+impl KindSpec for MirrorSpec {
+    const KIND_ID: u8 = 7;
+    const KIND_NAME: &'static str = "Mirror";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        let (&kind, rest) = body.split_first().ok_or(SpecError::PayloadTooShort {
+            expected: 1,
+            actual: 0,
+        })?;
+        Ok(Self {
+            kind,
+            body: rest.to_vec(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(1 + self.body.len());
+        result.push(self.kind);
+        result.extend_from_slice(&self.body);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.kind == Self::KIND_ID {
+            return Err(SpecError::InvalidFormat(
+                "A mirror cannot mirror another mirror".to_string(),
+            ));
+        }
+        if self.body.is_empty() {
+            return Err(SpecError::EmptyContent);
+        }
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::Inscription,
+            CarrierType::Stamps,
+            CarrierType::TaprootAnnex,
+            CarrierType::WitnessData,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::Stamps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_roundtrip() {
+        let spec = MirrorSpec::new(1, b"Hello, ANCHOR!".to_vec());
+        assert!(spec.validate().is_ok());
+
+        let bytes = spec.to_bytes();
+        assert_eq!(bytes[0], 1);
+        assert_eq!(MirrorSpec::from_bytes(&bytes).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_rejects_nested_and_empty() {
+        assert!(MirrorSpec::new(MirrorSpec::KIND_ID, vec![1])
+            .validate()
+            .is_err());
+        assert!(matches!(
+            MirrorSpec::new(1, Vec::new()).validate(),
+            Err(SpecError::EmptyContent)
+        ));
+        assert!(matches!(
+            MirrorSpec::from_bytes(&[]),
+            Err(SpecError::PayloadTooShort { .. })
+        ));
+    }
+}
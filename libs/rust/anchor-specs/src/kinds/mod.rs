@@ -6,14 +6,22 @@
 //!
 //! | Range | Category | Kinds |
 //! |-------|----------|-------|
-//! | 0-9 | Core | Generic, Text, State, Vote, Image |
+//! | 0-9 | Core | Generic, Text, State, Vote, Image, Identity, Mirror |
 //! | 10-19 | Infrastructure | DNS, Proof, GeoMarker |
 //! | 20-29 | Assets | Token |
 //! | 30-39 | Oracles | Oracle, OracleAttestation, OracleDispute, OracleSlash |
-//! | 40-49 | Predictions | MarketCreate, PlaceBet, MarketResolve, ClaimWinnings |
+//! | 40-44 | Predictions | MarketCreate, PlaceBet, MarketResolve, ClaimWinnings, MarketOrder |
+//! | 45-49 | Lotteries | LotteryCreate, LotteryTicket, LotteryDraw, LotteryClaim |
+//! | 50-59 | Trades | Escrow |
 
 pub mod dns;
+pub mod escrow;
+pub mod generic;
 pub mod geomarker;
+pub mod identity;
+pub mod lottery;
+pub mod mirror;
+pub mod oracle;
 pub mod proof;
 pub mod state;
 pub mod text;
@@ -21,10 +29,143 @@ pub mod token;
 
 // Re-export main types for convenience
 pub use dns::{DnsOperation, DnsRecord, DnsSpec, RecordType};
-pub use geomarker::{GeoMarkerSpec, MarkerCategory, HEADER_SIZE, MAX_MESSAGE_LENGTH};
-pub use proof::{HashAlgorithm, ProofEntry, ProofOperation, ProofSpec};
+pub use escrow::{escrow_script, EscrowAction, EscrowOperation, EscrowSpec};
+pub use generic::GenericSpec;
+pub use geomarker::{
+    GeoMarkerSpec, Geometry, GeometryShape, MarkerCategory, HEADER_SIZE, MAX_MESSAGE_LENGTH,
+};
+pub use identity::{IdentityOperation, IdentityRotationSpec};
+pub use lottery::{LotteryClaimSpec, LotteryCreateSpec, LotteryDrawSpec, LotteryTicketSpec};
+pub use mirror::MirrorSpec;
+pub use oracle::{
+    AggregateAttestation, DisputeReason, OracleAttestationSpec, OracleDisputeSpec, OracleSlashSpec,
+    OracleVote, OutcomeTally, QuorumStatus, SlashVerdict,
+};
+pub use proof::{
+    HashAlgorithm, ProofEntry, ProofOperation, ProofSpec, ProofWitness, VerifiedProof,
+};
 pub use state::{
-    PixelData, StateSpec, DEFAULT_CANVAS_HEIGHT, DEFAULT_CANVAS_WIDTH, MAX_PIXELS_PER_TX,
+    PixelData, RegionClaim, StateEntry, StateSpec, StateUpdate, DEFAULT_CANVAS_HEIGHT,
+    DEFAULT_CANVAS_WIDTH, MAX_PIXELS_PER_TX,
 };
 pub use text::TextSpec;
 pub use token::{TokenAllocation, TokenOperation, TokenSpec};
+
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+
+/// Kind bytes with an assigned meaning in the registry above
+///
+/// Messages with any other kind are still valid ANCHOR messages; indexers
+/// keep them as raw bodies so new applications show up before every
+/// reader knows their kind.
+pub const REGISTERED_KINDS: &[u8] = &[
+    0, 1, 2, 3, 4, 5, 6, 7, // Core
+    10, 11, // Infrastructure
+    20, // Assets
+    30, 31, 32, 33, // Oracles
+    40, 41, 42, 43, 44, // Predictions
+    45, 46, 47, 48, // Lotteries
+    50, // Trades
+];
+
+/// Whether `kind` is in the kind registry
+pub fn is_registered_kind(kind: u8) -> bool {
+    REGISTERED_KINDS.contains(&kind)
+}
+
+/// Carriers a kind can be embedded with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarrierSupport {
+    /// Carriers the kind's spec accepts
+    pub supported: &'static [CarrierType],
+    /// Carrier to use when none is requested
+    pub recommended: CarrierType,
+}
+
+impl CarrierSupport {
+    fn of<S: KindSpec>() -> Self {
+        Self {
+            supported: S::supported_carriers(),
+            recommended: S::recommended_carrier(),
+        }
+    }
+
+    /// Whether `carrier` is one of the supported carriers
+    pub fn allows(&self, carrier: CarrierType) -> bool {
+        self.supported.contains(&carrier)
+    }
+}
+
+/// Carrier support of a kind byte, for kinds with a spec in this crate
+///
+/// Kinds without a spec (votes, images, predictions) return
+/// `None` and may use any carrier.
+pub fn carrier_support(kind: u8) -> Option<CarrierSupport> {
+    Some(match kind {
+        GenericSpec::KIND_ID => CarrierSupport::of::<GenericSpec>(),
+        TextSpec::KIND_ID => CarrierSupport::of::<TextSpec>(),
+        StateSpec::KIND_ID => CarrierSupport::of::<StateSpec>(),
+        GeoMarkerSpec::KIND_ID => CarrierSupport::of::<GeoMarkerSpec>(),
+        IdentityRotationSpec::KIND_ID => CarrierSupport::of::<IdentityRotationSpec>(),
+        MirrorSpec::KIND_ID => CarrierSupport::of::<MirrorSpec>(),
+        DnsSpec::KIND_ID => CarrierSupport::of::<DnsSpec>(),
+        ProofSpec::KIND_ID => CarrierSupport::of::<ProofSpec>(),
+        TokenSpec::KIND_ID => CarrierSupport::of::<TokenSpec>(),
+        OracleAttestationSpec::KIND_ID => CarrierSupport::of::<OracleAttestationSpec>(),
+        OracleDisputeSpec::KIND_ID => CarrierSupport::of::<OracleDisputeSpec>(),
+        OracleSlashSpec::KIND_ID => CarrierSupport::of::<OracleSlashSpec>(),
+        LotteryCreateSpec::KIND_ID => CarrierSupport::of::<LotteryCreateSpec>(),
+        LotteryTicketSpec::KIND_ID => CarrierSupport::of::<LotteryTicketSpec>(),
+        LotteryDrawSpec::KIND_ID => CarrierSupport::of::<LotteryDrawSpec>(),
+        LotteryClaimSpec::KIND_ID => CarrierSupport::of::<LotteryClaimSpec>(),
+        EscrowSpec::KIND_ID => CarrierSupport::of::<EscrowSpec>(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_covers_specs() {
+        for kind in [
+            GenericSpec::KIND_ID,
+            TextSpec::KIND_ID,
+            StateSpec::KIND_ID,
+            IdentityRotationSpec::KIND_ID,
+            MirrorSpec::KIND_ID,
+            GeoMarkerSpec::KIND_ID,
+            DnsSpec::KIND_ID,
+            ProofSpec::KIND_ID,
+            TokenSpec::KIND_ID,
+            OracleAttestationSpec::KIND_ID,
+            OracleDisputeSpec::KIND_ID,
+            OracleSlashSpec::KIND_ID,
+            LotteryCreateSpec::KIND_ID,
+            LotteryTicketSpec::KIND_ID,
+            LotteryDrawSpec::KIND_ID,
+            LotteryClaimSpec::KIND_ID,
+            EscrowSpec::KIND_ID,
+        ] {
+            assert!(is_registered_kind(kind), "kind {} not registered", kind);
+        }
+        assert!(!is_registered_kind(8));
+        assert!(!is_registered_kind(200));
+    }
+
+    #[test]
+    fn test_carrier_support() {
+        let dns = carrier_support(DnsSpec::KIND_ID).unwrap();
+        assert!(!dns.allows(CarrierType::OpReturn));
+        assert!(dns.allows(dns.recommended));
+        assert_eq!(
+            carrier_support(TextSpec::KIND_ID).unwrap().recommended,
+            TextSpec::recommended_carrier()
+        );
+        assert!(carrier_support(GenericSpec::KIND_ID).is_some());
+        assert!(carrier_support(3).is_none());
+        assert!(carrier_support(42).is_none());
+    }
+}
@@ -0,0 +1,715 @@
+//! Kinds 31-33: Oracle Attestation, Dispute, and Slash Specifications
+//!
+//! Oracle attestations publish a signed outcome for an event. Events may
+//! require attestations from several oracles (k-of-n); [`AggregateAttestation`]
+//! collects the attestations referencing one event and computes quorum status.
+//!
+//! Attestations can be challenged with a bonded [`OracleDisputeSpec`] within
+//! the dispute window, and disputes are settled by an [`OracleSlashSpec`]
+//! that consumes the losing party's bond.
+//!
+//! ## Attestation Payload Format
+//!
+//! ```text
+//! ┌──────────┬──────────┬───────────────┬─────────────┬─────────┬───────────┐
+//! │ Category │ Event ID │ Attest. block │ Outcome len │ Outcome │ Signature │
+//! │ (1 byte) │ (32)     │ (8, BE)       │ (2, BE)     │ (var)   │ (64)      │
+//! └──────────┴──────────┴───────────────┴─────────────┴─────────┴───────────┘
+//! ```
+//!
+//! ## Dispute Payload Format
+//!
+//! ```text
+//! ┌──────────┬─────────────┬─────────────┬──────────┬──────────┬──────────┐
+//! │ Disputer │ Attestation │ Attestation │ Reason   │ Bond     │ Evidence │
+//! │ (32)     │ txid (32)   │ vout (2,BE) │ (1 byte) │ (8, BE)  │ (UTF-8)  │
+//! └──────────┴─────────────┴─────────────┴──────────┴──────────┴──────────┘
+//! ```
+//!
+//! ## Slash Payload Format
+//!
+//! ```text
+//! ┌──────────────┬──────────┬──────────┬─────────┐
+//! │ Dispute txid │ Verdict  │ Amount   │ Note    │
+//! │ (32)         │ (1 byte) │ (8, BE)  │ (UTF-8) │
+//! └──────────────┴──────────┴──────────┴─────────┘
+//! ```
+//!
+//! ## Quorum
+//!
+//! Each oracle counts once per event (its latest attestation wins). Quorum is
+//! reached when a single outcome is backed by at least `threshold` oracles.
+//! When an eligible oracle set is given, attestations from other oracles are
+//! ignored.
+//!
+//! The signature is a BIP-340 Schnorr signature by the oracle's x-only key
+//! over [`attestation_signing_hash`], which binds the outcome to its event.
+
+use crate::error::{Result, SpecError};
+use crate::validation::KindSpec;
+use anchor_core::carrier::CarrierType;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1, XOnlyPublicKey};
+use serde::{Deserialize, Serialize};
+
+/// Size of the fixed attestation header (category + event_id + block + outcome_len)
+pub const ATTESTATION_HEADER_SIZE: usize = 43;
+
+/// Size of a Schnorr signature
+pub const SIGNATURE_SIZE: usize = 64;
+
+/// Domain separation tag for attestation signatures
+const ATTESTATION_SIGNING_TAG: &[u8] = b"ANCHOR oracle attestation";
+
+/// Hash an oracle signs to attest `outcome` for `event_id`
+pub fn attestation_signing_hash(event_id: &[u8; 32], outcome: &[u8]) -> [u8; 32] {
+    let mut engine = sha256::Hash::engine();
+    engine.input(ATTESTATION_SIGNING_TAG);
+    engine.input(event_id);
+    engine.input(outcome);
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// Oracle attestation specification (Kind 31)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleAttestationSpec {
+    /// Oracle category bitmask of the event
+    pub category: u8,
+    /// Event identifier (32 bytes)
+    pub event_id: [u8; 32],
+    /// Block height at which the outcome was observed
+    pub attestation_block: u64,
+    /// Outcome data (opaque, often UTF-8)
+    pub outcome: Vec<u8>,
+    /// Schnorr signature over the event and outcome (64 bytes)
+    pub signature: Vec<u8>,
+}
+
+impl KindSpec for OracleAttestationSpec {
+    const KIND_ID: u8 = 31;
+    const KIND_NAME: &'static str = "OracleAttestation";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        if body.len() < ATTESTATION_HEADER_SIZE + SIGNATURE_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: ATTESTATION_HEADER_SIZE + SIGNATURE_SIZE,
+                actual: body.len(),
+            });
+        }
+
+        let category = body[0];
+        let event_id: [u8; 32] = body[1..33].try_into().unwrap();
+        let attestation_block = u64::from_be_bytes(body[33..41].try_into().unwrap());
+        let outcome_len = u16::from_be_bytes([body[41], body[42]]) as usize;
+
+        let sig_start = ATTESTATION_HEADER_SIZE + outcome_len;
+        if body.len() < sig_start + SIGNATURE_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: sig_start + SIGNATURE_SIZE,
+                actual: body.len(),
+            });
+        }
+
+        Ok(Self {
+            category,
+            event_id,
+            attestation_block,
+            outcome: body[ATTESTATION_HEADER_SIZE..sig_start].to_vec(),
+            signature: body[sig_start..sig_start + SIGNATURE_SIZE].to_vec(),
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result =
+            Vec::with_capacity(ATTESTATION_HEADER_SIZE + self.outcome.len() + self.signature.len());
+        result.push(self.category);
+        result.extend_from_slice(&self.event_id);
+        result.extend_from_slice(&self.attestation_block.to_be_bytes());
+        result.extend_from_slice(&(self.outcome.len() as u16).to_be_bytes());
+        result.extend_from_slice(&self.outcome);
+        result.extend_from_slice(&self.signature);
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.outcome.is_empty() {
+            return Err(SpecError::EmptyContent);
+        }
+        if self.outcome.len() > u16::MAX as usize {
+            return Err(SpecError::InvalidFormat(format!(
+                "Outcome too long: {} bytes (max {})",
+                self.outcome.len(),
+                u16::MAX
+            )));
+        }
+        if self.signature.len() != SIGNATURE_SIZE {
+            return Err(SpecError::InvalidFormat(format!(
+                "Signature must be {} bytes, got {}",
+                SIGNATURE_SIZE,
+                self.signature.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::WitnessData,
+            CarrierType::Inscription,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+// ============================================================================
+// Disputes and Slashing
+// ============================================================================
+
+/// Default number of blocks after an attestation during which it can be disputed
+pub const DEFAULT_DISPUTE_WINDOW_BLOCKS: u32 = 144;
+
+/// Minimum dispute payload size (disputer + txid + vout + reason + bond)
+pub const DISPUTE_MIN_SIZE: usize = 75;
+
+/// Minimum slash payload size (dispute txid + verdict + amount)
+pub const SLASH_MIN_SIZE: usize = 41;
+
+/// Check whether a dispute at `dispute_height` falls inside the window
+/// opened by an attestation confirmed at `attestation_height`.
+pub fn dispute_window_open(attestation_height: u32, dispute_height: u32, window: u32) -> bool {
+    dispute_height >= attestation_height && dispute_height - attestation_height <= window
+}
+
+/// Grounds for disputing an attestation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum DisputeReason {
+    /// The attested outcome is wrong
+    IncorrectOutcome = 1,
+    /// The attestation was published before the event resolved
+    Premature = 2,
+    /// The signature does not verify
+    InvalidSignature = 3,
+    /// The oracle is not registered for the event's category
+    NotAuthorized = 4,
+}
+
+impl TryFrom<u8> for DisputeReason {
+    type Error = SpecError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(DisputeReason::IncorrectOutcome),
+            2 => Ok(DisputeReason::Premature),
+            3 => Ok(DisputeReason::InvalidSignature),
+            4 => Ok(DisputeReason::NotAuthorized),
+            _ => Err(SpecError::InvalidFormat(format!(
+                "Invalid dispute reason: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Oracle dispute specification (Kind 32)
+///
+/// The dispute transaction must also carry a bond output of at least
+/// `bond_sats` owned by the challenger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleDisputeSpec {
+    /// Challenger public key
+    pub disputer_pubkey: [u8; 32],
+    /// Disputed attestation transaction (display byte order)
+    pub attestation_txid: [u8; 32],
+    /// Output index of the disputed attestation
+    pub attestation_vout: u16,
+    /// Grounds for the dispute
+    pub reason: DisputeReason,
+    /// Challenger bond in satoshis
+    pub bond_sats: u64,
+    /// Free-form evidence
+    pub evidence: String,
+}
+
+impl KindSpec for OracleDisputeSpec {
+    const KIND_ID: u8 = 32;
+    const KIND_NAME: &'static str = "OracleDispute";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        if body.len() < DISPUTE_MIN_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: DISPUTE_MIN_SIZE,
+                actual: body.len(),
+            });
+        }
+
+        Ok(Self {
+            disputer_pubkey: body[0..32].try_into().unwrap(),
+            attestation_txid: body[32..64].try_into().unwrap(),
+            attestation_vout: u16::from_be_bytes([body[64], body[65]]),
+            reason: DisputeReason::try_from(body[66])?,
+            bond_sats: u64::from_be_bytes(body[67..75].try_into().unwrap()),
+            evidence: String::from_utf8(body[DISPUTE_MIN_SIZE..].to_vec())?,
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(DISPUTE_MIN_SIZE + self.evidence.len());
+        result.extend_from_slice(&self.disputer_pubkey);
+        result.extend_from_slice(&self.attestation_txid);
+        result.extend_from_slice(&self.attestation_vout.to_be_bytes());
+        result.push(self.reason as u8);
+        result.extend_from_slice(&self.bond_sats.to_be_bytes());
+        result.extend_from_slice(self.evidence.as_bytes());
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.bond_sats == 0 {
+            return Err(SpecError::InvalidAmount(
+                "Dispute bond cannot be zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[
+            CarrierType::OpReturn,
+            CarrierType::WitnessData,
+            CarrierType::Inscription,
+        ]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+/// Outcome of a dispute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SlashVerdict {
+    /// Dispute upheld: the oracle bond is slashed
+    Upheld = 1,
+    /// Dispute rejected: the challenger bond is forfeited
+    Rejected = 2,
+}
+
+impl TryFrom<u8> for SlashVerdict {
+    type Error = SpecError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(SlashVerdict::Upheld),
+            2 => Ok(SlashVerdict::Rejected),
+            _ => Err(SpecError::InvalidFormat(format!(
+                "Invalid slash verdict: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Oracle slash specification (Kind 33)
+///
+/// A slash settles a dispute. It is only valid if the transaction spends the
+/// losing party's bond: the oracle's registration bond for [`SlashVerdict::Upheld`],
+/// or the challenger's dispute bond for [`SlashVerdict::Rejected`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleSlashSpec {
+    /// Dispute being settled (display byte order)
+    pub dispute_txid: [u8; 32],
+    /// Dispute outcome
+    pub verdict: SlashVerdict,
+    /// Amount slashed from the losing bond, in satoshis
+    pub amount_sats: u64,
+    /// Free-form resolution note
+    pub note: String,
+}
+
+impl KindSpec for OracleSlashSpec {
+    const KIND_ID: u8 = 33;
+    const KIND_NAME: &'static str = "OracleSlash";
+
+    fn from_bytes(body: &[u8]) -> Result<Self> {
+        if body.len() < SLASH_MIN_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: SLASH_MIN_SIZE,
+                actual: body.len(),
+            });
+        }
+
+        Ok(Self {
+            dispute_txid: body[0..32].try_into().unwrap(),
+            verdict: SlashVerdict::try_from(body[32])?,
+            amount_sats: u64::from_be_bytes(body[33..41].try_into().unwrap()),
+            note: String::from_utf8(body[SLASH_MIN_SIZE..].to_vec())?,
+        })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(SLASH_MIN_SIZE + self.note.len());
+        result.extend_from_slice(&self.dispute_txid);
+        result.push(self.verdict as u8);
+        result.extend_from_slice(&self.amount_sats.to_be_bytes());
+        result.extend_from_slice(self.note.as_bytes());
+        result
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.amount_sats == 0 {
+            return Err(SpecError::InvalidAmount(
+                "Slash amount cannot be zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn supported_carriers() -> &'static [CarrierType] {
+        &[CarrierType::OpReturn, CarrierType::WitnessData]
+    }
+
+    fn recommended_carrier() -> CarrierType {
+        CarrierType::OpReturn
+    }
+}
+
+// ============================================================================
+// Aggregate Attestations
+// ============================================================================
+
+/// A single oracle's signed outcome for an event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OracleVote {
+    /// Oracle public key (x-only, 32 bytes)
+    pub oracle_pubkey: [u8; 32],
+    /// Attested outcome
+    pub outcome: Vec<u8>,
+    /// Signature over the event and outcome
+    pub signature: Vec<u8>,
+}
+
+impl OracleVote {
+    /// Check the vote's signature against the oracle key for `event_id`
+    pub fn verify(&self, event_id: &[u8; 32]) -> Result<()> {
+        let pubkey = XOnlyPublicKey::from_slice(&self.oracle_pubkey)
+            .map_err(|e| SpecError::InvalidFormat(format!("Invalid oracle key: {}", e)))?;
+        let signature = schnorr::Signature::from_slice(&self.signature)
+            .map_err(|e| SpecError::InvalidSignature(e.to_string()))?;
+
+        Secp256k1::verification_only()
+            .verify_schnorr(
+                &signature,
+                &Message::from_digest(attestation_signing_hash(event_id, &self.outcome)),
+                &pubkey,
+            )
+            .map_err(|e| SpecError::InvalidSignature(e.to_string()))
+    }
+}
+
+/// Number of oracles backing one outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeTally {
+    pub outcome: Vec<u8>,
+    pub oracles: Vec<[u8; 32]>,
+}
+
+impl OutcomeTally {
+    /// Number of distinct oracles backing this outcome
+    pub fn count(&self) -> usize {
+        self.oracles.len()
+    }
+}
+
+/// Result of evaluating an aggregate against its threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuorumStatus {
+    /// Required number of agreeing oracles (k)
+    pub threshold: u8,
+    /// Size of the eligible oracle set (n), if the event restricts it
+    pub eligible_oracles: Option<usize>,
+    /// Distinct oracles that have attested
+    pub attested_oracles: usize,
+    /// Outcome backed by the most oracles (ties broken by first seen)
+    pub leading_outcome: Option<Vec<u8>>,
+    /// Oracles backing the leading outcome
+    pub leading_count: usize,
+    /// Whether the leading outcome meets the threshold
+    pub reached: bool,
+    /// Whether oracles disagree on the outcome
+    pub conflicting: bool,
+}
+
+/// Attestations from multiple oracles referencing the same event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateAttestation {
+    /// Event identifier shared by all votes
+    pub event_id: [u8; 32],
+    /// Required number of agreeing oracles (k)
+    pub threshold: u8,
+    /// Eligible oracle set (n); empty means any oracle may attest
+    pub eligible: Vec<[u8; 32]>,
+    /// One vote per oracle, in arrival order
+    pub votes: Vec<OracleVote>,
+}
+
+impl AggregateAttestation {
+    /// Create an empty aggregate open to any oracle
+    pub fn new(event_id: [u8; 32], threshold: u8) -> Self {
+        Self {
+            event_id,
+            threshold,
+            eligible: Vec::new(),
+            votes: Vec::new(),
+        }
+    }
+
+    /// Restrict the aggregate to an eligible oracle set
+    pub fn with_eligible(mut self, eligible: Vec<[u8; 32]>) -> Self {
+        self.eligible = eligible;
+        self
+    }
+
+    /// Check if an oracle may contribute to this aggregate
+    pub fn is_eligible(&self, oracle_pubkey: &[u8; 32]) -> bool {
+        self.eligible.is_empty() || self.eligible.contains(oracle_pubkey)
+    }
+
+    /// Add an oracle's vote, replacing any earlier vote from the same oracle.
+    ///
+    /// Returns `false` if the oracle is not in the eligible set.
+    pub fn add_vote(&mut self, vote: OracleVote) -> bool {
+        if !self.is_eligible(&vote.oracle_pubkey) {
+            return false;
+        }
+        self.votes.retain(|v| v.oracle_pubkey != vote.oracle_pubkey);
+        self.votes.push(vote);
+        true
+    }
+
+    /// Add a parsed attestation from an oracle.
+    ///
+    /// Attestations for a different event are rejected.
+    pub fn add_attestation(
+        &mut self,
+        oracle_pubkey: [u8; 32],
+        attestation: &OracleAttestationSpec,
+    ) -> Result<bool> {
+        if attestation.event_id != self.event_id {
+            return Err(SpecError::InvalidFormat(
+                "Attestation references a different event".to_string(),
+            ));
+        }
+        Ok(self.add_vote(OracleVote {
+            oracle_pubkey,
+            outcome: attestation.outcome.clone(),
+            signature: attestation.signature.clone(),
+        }))
+    }
+
+    /// Group votes by outcome, ordered by support (descending) then first vote
+    pub fn tally(&self) -> Vec<OutcomeTally> {
+        let mut tallies: Vec<OutcomeTally> = Vec::new();
+        for vote in &self.votes {
+            match tallies.iter_mut().find(|t| t.outcome == vote.outcome) {
+                Some(tally) => tally.oracles.push(vote.oracle_pubkey),
+                None => tallies.push(OutcomeTally {
+                    outcome: vote.outcome.clone(),
+                    oracles: vec![vote.oracle_pubkey],
+                }),
+            }
+        }
+        // Stable sort keeps first-seen order among equal counts
+        tallies.sort_by_key(|t| std::cmp::Reverse(t.count()));
+        tallies
+    }
+
+    /// Evaluate the aggregate against its threshold
+    pub fn quorum(&self) -> QuorumStatus {
+        let tallies = self.tally();
+        let leading = tallies.first();
+        let leading_count = leading.map(OutcomeTally::count).unwrap_or(0);
+
+        QuorumStatus {
+            threshold: self.threshold,
+            eligible_oracles: (!self.eligible.is_empty()).then_some(self.eligible.len()),
+            attested_oracles: self.votes.len(),
+            leading_outcome: leading.map(|t| t.outcome.clone()),
+            leading_count,
+            reached: self.threshold > 0 && leading_count >= self.threshold as usize,
+            conflicting: tallies.len() > 1,
+        }
+    }
+
+    /// Validate the aggregate parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.threshold == 0 {
+            return Err(SpecError::InvalidFormat(
+                "Threshold must be at least 1".to_string(),
+            ));
+        }
+        if !self.eligible.is_empty() && self.threshold as usize > self.eligible.len() {
+            return Err(SpecError::InvalidFormat(format!(
+                "Threshold {} exceeds eligible oracle count {}",
+                self.threshold,
+                self.eligible.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attestation(outcome: &str) -> OracleAttestationSpec {
+        OracleAttestationSpec {
+            category: 2,
+            event_id: [7u8; 32],
+            attestation_block: 840_000,
+            outcome: outcome.as_bytes().to_vec(),
+            signature: vec![0xab; SIGNATURE_SIZE],
+        }
+    }
+
+    #[test]
+    fn test_attestation_roundtrip() {
+        let spec = attestation("BTC>100k");
+        assert!(spec.validate().is_ok());
+        let bytes = spec.to_bytes();
+        assert_eq!(bytes.len(), ATTESTATION_HEADER_SIZE + 8 + SIGNATURE_SIZE);
+        assert_eq!(OracleAttestationSpec::from_bytes(&bytes).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_attestation_truncated() {
+        let bytes = attestation("yes").to_bytes();
+        assert!(OracleAttestationSpec::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_dispute_roundtrip() {
+        let spec = OracleDisputeSpec {
+            disputer_pubkey: [1u8; 32],
+            attestation_txid: [2u8; 32],
+            attestation_vout: 1,
+            reason: DisputeReason::IncorrectOutcome,
+            bond_sats: 25_000,
+            evidence: "Price feed mismatch".to_string(),
+        };
+        assert!(spec.validate().is_ok());
+        assert_eq!(
+            OracleDisputeSpec::from_bytes(&spec.to_bytes()).unwrap(),
+            spec
+        );
+
+        let mut bad_reason = spec.to_bytes();
+        bad_reason[66] = 9;
+        assert!(OracleDisputeSpec::from_bytes(&bad_reason).is_err());
+    }
+
+    #[test]
+    fn test_slash_roundtrip() {
+        let spec = OracleSlashSpec {
+            dispute_txid: [3u8; 32],
+            verdict: SlashVerdict::Upheld,
+            amount_sats: 50_000,
+            note: String::new(),
+        };
+        assert!(spec.validate().is_ok());
+        let bytes = spec.to_bytes();
+        assert_eq!(bytes.len(), SLASH_MIN_SIZE);
+        assert_eq!(OracleSlashSpec::from_bytes(&bytes).unwrap(), spec);
+        assert!(OracleSlashSpec::from_bytes(&bytes[..40]).is_err());
+    }
+
+    #[test]
+    fn test_dispute_window() {
+        assert!(dispute_window_open(100, 100, 144));
+        assert!(dispute_window_open(100, 244, 144));
+        assert!(!dispute_window_open(100, 245, 144));
+        assert!(!dispute_window_open(100, 99, 144));
+    }
+
+    #[test]
+    fn test_quorum_reached_with_conflict() {
+        let mut agg = AggregateAttestation::new([7u8; 32], 2);
+        agg.add_attestation([1u8; 32], &attestation("yes")).unwrap();
+        assert!(!agg.quorum().reached);
+
+        agg.add_attestation([2u8; 32], &attestation("no")).unwrap();
+        agg.add_attestation([3u8; 32], &attestation("yes")).unwrap();
+
+        let status = agg.quorum();
+        assert!(status.reached);
+        assert!(status.conflicting);
+        assert_eq!(status.leading_outcome.as_deref(), Some(&b"yes"[..]));
+        assert_eq!(status.leading_count, 2);
+        assert_eq!(status.attested_oracles, 3);
+    }
+
+    #[test]
+    fn test_latest_vote_per_oracle_wins() {
+        let mut agg = AggregateAttestation::new([7u8; 32], 2);
+        agg.add_attestation([1u8; 32], &attestation("yes")).unwrap();
+        agg.add_attestation([1u8; 32], &attestation("yes")).unwrap();
+        assert!(!agg.quorum().reached);
+        assert_eq!(agg.votes.len(), 1);
+
+        agg.add_attestation([1u8; 32], &attestation("no")).unwrap();
+        assert_eq!(agg.tally()[0].outcome, b"no".to_vec());
+    }
+
+    #[test]
+    fn test_eligible_set() {
+        let mut agg =
+            AggregateAttestation::new([7u8; 32], 2).with_eligible(vec![[1u8; 32], [2u8; 32]]);
+        assert!(agg.validate().is_ok());
+        assert!(!agg.add_attestation([9u8; 32], &attestation("yes")).unwrap());
+        assert!(agg.add_attestation([1u8; 32], &attestation("yes")).unwrap());
+        assert_eq!(agg.quorum().eligible_oracles, Some(2));
+
+        let mut wrong_event = attestation("yes");
+        wrong_event.event_id = [0u8; 32];
+        assert!(agg.add_attestation([2u8; 32], &wrong_event).is_err());
+
+        let too_high = AggregateAttestation::new([7u8; 32], 3).with_eligible(vec![[1u8; 32]]);
+        assert!(too_high.validate().is_err());
+    }
+
+    #[test]
+    fn test_vote_signature_binds_event_and_outcome() {
+        let secp = Secp256k1::new();
+        let keypair = bitcoin::secp256k1::Keypair::from_seckey_slice(&secp, &[5u8; 32]).unwrap();
+        let event_id = [7u8; 32];
+        let signature = secp.sign_schnorr_no_aux_rand(
+            &Message::from_digest(attestation_signing_hash(&event_id, b"yes")),
+            &keypair,
+        );
+        let vote = OracleVote {
+            oracle_pubkey: keypair.x_only_public_key().0.serialize(),
+            outcome: b"yes".to_vec(),
+            signature: signature.as_ref().to_vec(),
+        };
+        assert!(vote.verify(&event_id).is_ok());
+        assert!(vote.verify(&[8u8; 32]).is_err());
+
+        let forged = OracleVote {
+            outcome: b"no".to_vec(),
+            ..vote.clone()
+        };
+        assert!(forged.verify(&event_id).is_err());
+
+        let other_oracle = OracleVote {
+            oracle_pubkey: [1u8; 32],
+            ..vote
+        };
+        assert!(other_oracle.verify(&event_id).is_err());
+    }
+}
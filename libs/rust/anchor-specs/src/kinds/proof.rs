@@ -10,6 +10,7 @@
 //! | STAMP | 0x01 | Register a new proof |
 //! | REVOKE | 0x02 | Revoke an existing proof |
 //! | BATCH | 0x03 | Multiple proofs in one TX |
+//! | WITNESS | 0x04 | Third-party co-attestation of an existing proof |
 //!
 //! ## Payload Format
 //!
@@ -21,12 +22,42 @@
 //! │ (1 byte)  │ (1 byte)  │ (32/64 bytes)     │ (variable)          │
 //! └───────────┴───────────┴───────────────────┴─────────────────────┘
 //! ```
+//!
+//! ## Witness Attestations
+//!
+//! A WITNESS message anchors the original stamp and carries a BIP-340
+//! Schnorr signature by the witness over [`ProofSpec::witness_signing_hash`],
+//! which commits to the algorithm, the hash and the witness key. Independent
+//! witnesses add evidentiary weight to a proof beyond the stamper's own claim.
+//!
+//! ```text
+//! ┌───────────┬───────────┬───────────────┬─────────────┬───────────────┐
+//! │ Operation │ Algorithm │ Hash          │ Witness key │ Signature     │
+//! │ (1 byte)  │ (1 byte)  │ (32/64 bytes) │ (32 bytes)  │ (64 bytes)    │
+//! └───────────┴───────────┴───────────────┴─────────────┴───────────────┘
+//! ```
+//!
+//! ## Verification
+//!
+//! [`verify`] checks a file against a transaction without any backend: it
+//! hashes the file, decodes every carrier in the transaction and looks for a
+//! STAMP or BATCH entry with that hash. Whether the transaction is mined is
+//! a separate question, answered by `anchor_core::verify_inclusion`.
 
 use crate::error::{Result, SpecError};
 use crate::validation::KindSpec;
-use anchor_core::carrier::CarrierType;
+use anchor_core::carrier::{CarrierSelector, CarrierType};
+use bitcoin::hashes::{sha256, sha512, Hash, HashEngine};
+use bitcoin::secp256k1::{schnorr, Keypair, Message, Secp256k1, XOnlyPublicKey};
+use bitcoin::Transaction;
 use serde::{Deserialize, Serialize};
 
+/// Size of a witness key (x-only) plus its Schnorr signature
+const WITNESS_SIZE: usize = 32 + 64;
+
+/// Domain separator for the witness signing hash
+const WITNESS_SIGNING_TAG: &[u8] = b"ANCHOR proof witness";
+
 /// Proof operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -37,6 +68,8 @@ pub enum ProofOperation {
     Revoke = 0x02,
     /// Batch multiple proofs in single TX
     Batch = 0x03,
+    /// Co-attest an existing proof as a third-party witness
+    Witness = 0x04,
 }
 
 impl TryFrom<u8> for ProofOperation {
@@ -47,6 +80,7 @@ impl TryFrom<u8> for ProofOperation {
             0x01 => Ok(ProofOperation::Stamp),
             0x02 => Ok(ProofOperation::Revoke),
             0x03 => Ok(ProofOperation::Batch),
+            0x04 => Ok(ProofOperation::Witness),
             _ => Err(SpecError::InvalidProofOperation(value)),
         }
     }
@@ -334,11 +368,23 @@ impl ProofEntry {
     }
 }
 
+/// Witness key and signature of a WITNESS attestation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofWitness {
+    /// Witness public key (x-only)
+    pub pubkey: [u8; 32],
+    /// Schnorr signature by the witness key (64 bytes)
+    pub signature: Vec<u8>,
+}
+
 /// Proof specification (Kind 11)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProofSpec {
     pub operation: ProofOperation,
     pub entries: Vec<ProofEntry>,
+    /// Set for WITNESS attestations only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness: Option<ProofWitness>,
 }
 
 impl ProofSpec {
@@ -347,6 +393,7 @@ impl ProofSpec {
         Self {
             operation: ProofOperation::Stamp,
             entries: vec![entry],
+            witness: None,
         }
     }
 
@@ -355,6 +402,7 @@ impl ProofSpec {
         Self {
             operation: ProofOperation::Revoke,
             entries: vec![entry],
+            witness: None,
         }
     }
 
@@ -363,6 +411,7 @@ impl ProofSpec {
         Self {
             operation: ProofOperation::Batch,
             entries,
+            witness: None,
         }
     }
 
@@ -381,6 +430,130 @@ impl ProofSpec {
             algorithm, hex_hash, metadata,
         )?))
     }
+
+    /// Create a witness attestation of `hash`, signed by `keypair`
+    ///
+    /// The message must anchor the original stamp.
+    pub fn witness(keypair: &Keypair, algorithm: HashAlgorithm, hash: Vec<u8>) -> Result<Self> {
+        let entry = ProofEntry {
+            algorithm,
+            hash,
+            metadata: ProofMetadata::default(),
+        };
+        entry.validate()?;
+        let mut spec = Self {
+            operation: ProofOperation::Witness,
+            entries: vec![entry],
+            witness: Some(ProofWitness {
+                pubkey: keypair.x_only_public_key().0.serialize(),
+                signature: Vec::new(),
+            }),
+        };
+        let digest = spec.witness_signing_hash()?;
+        let signature = Secp256k1::signing_only()
+            .sign_schnorr_no_aux_rand(&Message::from_digest(digest), keypair);
+        if let Some(witness) = spec.witness.as_mut() {
+            witness.signature = signature.as_ref().to_vec();
+        }
+        Ok(spec)
+    }
+
+    /// Hash signed by the witness
+    pub fn witness_signing_hash(&self) -> Result<[u8; 32]> {
+        let (entry, witness) = self.witness_parts()?;
+        let mut engine = sha256::Hash::engine();
+        engine.input(WITNESS_SIGNING_TAG);
+        engine.input(&[entry.algorithm as u8]);
+        engine.input(&entry.hash);
+        engine.input(&witness.pubkey);
+        Ok(sha256::Hash::from_engine(engine).to_byte_array())
+    }
+
+    /// Check a witness attestation's signature against its witness key
+    pub fn verify_witness(&self) -> Result<()> {
+        let (_, witness) = self.witness_parts()?;
+        let pubkey = XOnlyPublicKey::from_slice(&witness.pubkey)
+            .map_err(|e| SpecError::InvalidFormat(format!("Invalid witness key: {}", e)))?;
+        let signature = schnorr::Signature::from_slice(&witness.signature)
+            .map_err(|e| SpecError::InvalidSignature(e.to_string()))?;
+
+        Secp256k1::verification_only()
+            .verify_schnorr(
+                &signature,
+                &Message::from_digest(self.witness_signing_hash()?),
+                &pubkey,
+            )
+            .map_err(|e| SpecError::InvalidSignature(e.to_string()))
+    }
+
+    fn witness_parts(&self) -> Result<(&ProofEntry, &ProofWitness)> {
+        match (self.operation, self.entries.first(), &self.witness) {
+            (ProofOperation::Witness, Some(entry), Some(witness)) => Ok((entry, witness)),
+            _ => Err(SpecError::InvalidFormat(
+                "Not a witness attestation".to_string(),
+            )),
+        }
+    }
+}
+
+/// A proof of a file found in a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedProof {
+    /// Output or input index the message was decoded from
+    pub vout: u32,
+    pub carrier: CarrierType,
+    pub operation: ProofOperation,
+    /// The matching entry, with the metadata the stamper recorded
+    pub entry: ProofEntry,
+}
+
+/// Check that `tx` stamps the contents of `file`
+///
+/// The file is hashed with every supported algorithm and matched against
+/// STAMP and BATCH entries on any carrier.
+pub fn verify(file: &[u8], tx: &Transaction) -> Result<VerifiedProof> {
+    let sha256 = sha256::Hash::hash(file).to_byte_array().to_vec();
+    let sha512 = sha512::Hash::hash(file).to_byte_array().to_vec();
+    find_proof(tx, |entry| match entry.algorithm {
+        HashAlgorithm::Sha256 => entry.hash == sha256,
+        HashAlgorithm::Sha512 => entry.hash == sha512,
+    })
+    .ok_or_else(|| SpecError::ProofNotFound(format!("sha256:{}", hex::encode(&sha256))))
+}
+
+/// Check that `tx` stamps a precomputed `hash`
+pub fn verify_hash(
+    algorithm: HashAlgorithm,
+    hash: &[u8],
+    tx: &Transaction,
+) -> Result<VerifiedProof> {
+    find_proof(tx, |entry| {
+        entry.algorithm == algorithm && entry.hash == hash
+    })
+    .ok_or_else(|| SpecError::ProofNotFound(hex::encode(hash)))
+}
+
+fn find_proof(tx: &Transaction, matches: impl Fn(&ProofEntry) -> bool) -> Option<VerifiedProof> {
+    CarrierSelector::new()
+        .detect(tx)
+        .into_iter()
+        .filter(|d| u8::from(d.message.kind) == ProofSpec::KIND_ID)
+        .find_map(|d| {
+            let spec = ProofSpec::from_bytes(&d.message.body).ok()?;
+            if !matches!(
+                spec.operation,
+                ProofOperation::Stamp | ProofOperation::Batch
+            ) {
+                return None;
+            }
+            let entry = spec.entries.into_iter().find(|e| matches(e))?;
+            Some(VerifiedProof {
+                vout: d.vout,
+                carrier: d.carrier_type,
+                operation: spec.operation,
+                entry,
+            })
+        })
 }
 
 impl KindSpec for ProofSpec {
@@ -433,7 +606,36 @@ impl KindSpec for ProofSpec {
                     });
                 }
 
-                Ok(ProofSpec { operation, entries })
+                Ok(ProofSpec {
+                    operation,
+                    entries,
+                    witness: None,
+                })
+            }
+            ProofOperation::Witness => {
+                let algorithm = HashAlgorithm::try_from(body[1])?;
+                let hash_size = algorithm.hash_size();
+                let expected = 2 + hash_size + WITNESS_SIZE;
+                if body.len() < expected {
+                    return Err(SpecError::PayloadTooShort {
+                        expected,
+                        actual: body.len(),
+                    });
+                }
+                let hash = body[2..2 + hash_size].to_vec();
+                let mut pubkey = [0u8; 32];
+                pubkey.copy_from_slice(&body[2 + hash_size..2 + hash_size + 32]);
+                let signature = body[2 + hash_size + 32..expected].to_vec();
+
+                Ok(ProofSpec {
+                    operation,
+                    entries: vec![ProofEntry {
+                        algorithm,
+                        hash,
+                        metadata: ProofMetadata::default(),
+                    }],
+                    witness: Some(ProofWitness { pubkey, signature }),
+                })
             }
             ProofOperation::Stamp | ProofOperation::Revoke => {
                 let mut offset = 1;
@@ -466,6 +668,7 @@ impl KindSpec for ProofSpec {
                         hash,
                         metadata,
                     }],
+                    witness: None,
                 })
             }
         }
@@ -485,6 +688,16 @@ impl KindSpec for ProofSpec {
                     result.extend_from_slice(&entry.metadata.to_bytes());
                 }
             }
+            ProofOperation::Witness => {
+                result.push(ProofOperation::Witness as u8);
+
+                if let (Some(entry), Some(witness)) = (self.entries.first(), &self.witness) {
+                    result.push(entry.algorithm as u8);
+                    result.extend_from_slice(&entry.hash);
+                    result.extend_from_slice(&witness.pubkey);
+                    result.extend_from_slice(&witness.signature);
+                }
+            }
             _ => {
                 result.push(self.operation as u8);
 
@@ -508,6 +721,19 @@ impl KindSpec for ProofSpec {
             entry.validate()?;
         }
 
+        if self.operation == ProofOperation::Witness {
+            if self.entries.len() != 1 {
+                return Err(SpecError::InvalidFormat(
+                    "A witness attestation covers exactly one proof".to_string(),
+                ));
+            }
+            self.verify_witness()?;
+        } else if self.witness.is_some() {
+            return Err(SpecError::InvalidFormat(
+                "Only witness attestations carry a witness signature".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -587,6 +813,108 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    fn witness_keypair() -> Keypair {
+        Keypair::from_seckey_slice(&Secp256k1::new(), &[7u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_witness_roundtrip() {
+        let spec =
+            ProofSpec::witness(&witness_keypair(), HashAlgorithm::Sha256, vec![9u8; 32]).unwrap();
+        assert!(spec.validate().is_ok());
+
+        let bytes = spec.to_bytes();
+        assert_eq!(bytes.len(), 2 + 32 + WITNESS_SIZE);
+        let parsed = ProofSpec::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, spec);
+        assert!(parsed.verify_witness().is_ok());
+    }
+
+    #[test]
+    fn test_witness_signature_binds_hash() {
+        let mut spec =
+            ProofSpec::witness(&witness_keypair(), HashAlgorithm::Sha256, vec![9u8; 32]).unwrap();
+        spec.entries[0].hash = vec![8u8; 32];
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    fn test_witness_truncated() {
+        let spec =
+            ProofSpec::witness(&witness_keypair(), HashAlgorithm::Sha512, vec![9u8; 64]).unwrap();
+        let bytes = spec.to_bytes();
+        assert!(ProofSpec::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_stamp_rejects_witness_signature() {
+        let mut spec = ProofSpec::stamp_sha256(vec![0u8; 32], ProofMetadata::default()).unwrap();
+        spec.witness = ProofSpec::witness(&witness_keypair(), HashAlgorithm::Sha256, vec![0u8; 32])
+            .unwrap()
+            .witness;
+        assert!(spec.validate().is_err());
+        assert!(spec.verify_witness().is_err());
+    }
+
+    fn proof_tx(spec: &ProofSpec) -> Transaction {
+        use anchor_core::{create_anchor_script, AnchorKind, ParsedAnchorMessage};
+        use bitcoin::{absolute::LockTime, transaction::Version, Amount, TxOut};
+
+        let message = ParsedAnchorMessage::new_root(AnchorKind::from(11u8), spec.to_bytes());
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![TxOut {
+                value: Amount::ZERO,
+                script_pubkey: create_anchor_script(&message),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_verify_file() {
+        let file = b"the quick brown fox";
+        let hash = sha256::Hash::hash(file).to_byte_array().to_vec();
+        let spec =
+            ProofSpec::stamp_sha256(hash, ProofMetadata::new().with_filename("fox.txt")).unwrap();
+        let tx = proof_tx(&spec);
+
+        let verified = verify(file, &tx).unwrap();
+        assert_eq!(verified.vout, 0);
+        assert_eq!(verified.carrier, CarrierType::OpReturn);
+        assert_eq!(verified.operation, ProofOperation::Stamp);
+        assert_eq!(verified.entry.metadata.filename.as_deref(), Some("fox.txt"));
+
+        assert!(matches!(
+            verify(b"another file", &tx),
+            Err(SpecError::ProofNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_batch_sha512() {
+        let file = b"batched";
+        let hash = sha512::Hash::hash(file).to_byte_array().to_vec();
+        let spec = ProofSpec::batch(vec![
+            ProofEntry::sha256(vec![1u8; 32], ProofMetadata::default()).unwrap(),
+            ProofEntry::sha512(hash.clone(), ProofMetadata::default()).unwrap(),
+        ]);
+        let tx = proof_tx(&spec);
+
+        assert_eq!(verify(file, &tx).unwrap().operation, ProofOperation::Batch);
+        assert!(verify_hash(HashAlgorithm::Sha512, &hash, &tx).is_ok());
+        assert!(verify_hash(HashAlgorithm::Sha256, &hash, &tx).is_err());
+    }
+
+    #[test]
+    fn test_verify_ignores_revocations() {
+        let file = b"revoked";
+        let hash = sha256::Hash::hash(file).to_byte_array().to_vec();
+        let spec = ProofSpec::revoke(ProofEntry::sha256(hash, ProofMetadata::default()).unwrap());
+        assert!(verify(file, &proof_tx(&spec)).is_err());
+    }
+
     #[test]
     fn test_from_hex() {
         let hex_hash = "a".repeat(64); // 32 bytes = 64 hex chars
@@ -12,6 +12,34 @@
 //! [x: u16][y: u16][r: u8][g: u8][b: u8] = 7 bytes
 //! ```
 //!
+//! ## Sub-kinds
+//!
+//! Payloads starting with `SUB_KIND_PREFIX` carry a sub-kind instead of
+//! pixels. The prefix would announce more than four billion pixels, so it
+//! never starts a valid pixel payload.
+//!
+//! ```text
+//! Region claim (SUB_KIND_REGION_CLAIM):
+//! [0xFF 0xFF 0xFF][0x01][x: u16][y: u16][width: u16][height: u16][duration_blocks: u32]
+//! ```
+//!
+//! A region claim asks a canvas running in governance mode for exclusive
+//! drawing rights on a rectangle for `duration_blocks` blocks.
+//!
+//! ```text
+//! Key-value update (SUB_KIND_KEY_VALUE):
+//! [0xFF 0xFF 0xFF][0x02][namespace: 32][sequence: u64][num_entries: u16][entry_1]...[entry_n]
+//!
+//! Each entry:
+//! [key_len: u8][key][value_len: u16][value]
+//! ```
+//!
+//! A key-value update writes entries into a namespace, identified by the
+//! SHA-256 hash of its name, so applications can keep replicated state on
+//! ANCHOR without a kind of their own. An empty value deletes the key.
+//! `sequence` orders the updates of a namespace: see [`crate::state_index`]
+//! for the reference rules indexers apply.
+//!
 //! ## Example
 //!
 //! ```rust,ignore
@@ -29,6 +57,7 @@
 use crate::error::SpecError;
 use crate::validation::KindSpec;
 use anchor_core::carrier::CarrierType;
+use bitcoin::hashes::{sha256, Hash};
 use serde::{Deserialize, Serialize};
 
 /// Default canvas width
@@ -42,6 +71,27 @@ pub const DEFAULT_CANVAS_HEIGHT: u32 = 4580;
 /// We use a conservative limit to leave room for protocol overhead
 pub const MAX_PIXELS_PER_TX: usize = 14000;
 
+/// First bytes of a sub-kind payload
+pub const SUB_KIND_PREFIX: [u8; 3] = [0xFF, 0xFF, 0xFF];
+
+/// Sub-kind of region claims
+pub const SUB_KIND_REGION_CLAIM: u8 = 0x01;
+
+/// Encoded size of a region claim
+pub const REGION_CLAIM_SIZE: usize = 16;
+
+/// Sub-kind of key-value updates
+pub const SUB_KIND_KEY_VALUE: u8 = 0x02;
+
+/// Size of the key-value update header (prefix + namespace + sequence + count)
+pub const KEY_VALUE_HEADER_SIZE: usize = 46;
+
+/// Maximum key length in bytes
+pub const MAX_STATE_KEY_LEN: usize = u8::MAX as usize;
+
+/// Maximum value length in bytes
+pub const MAX_STATE_VALUE_LEN: usize = u16::MAX as usize;
+
 /// Pixel data with coordinates and color
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PixelData {
@@ -141,6 +191,305 @@ impl StateSpec {
     }
 }
 
+/// Claim of exclusive drawing rights on a canvas region
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionClaim {
+    /// X coordinate of the top-left corner
+    pub x: u16,
+    /// Y coordinate of the top-left corner
+    pub y: u16,
+    /// Width in pixels
+    pub width: u16,
+    /// Height in pixels
+    pub height: u16,
+    /// Blocks the lease lasts, starting with the claim's block
+    pub duration_blocks: u32,
+}
+
+impl RegionClaim {
+    /// Create a new region claim
+    pub fn new(x: u16, y: u16, width: u16, height: u16, duration_blocks: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            duration_blocks,
+        }
+    }
+
+    /// Whether a State payload is a region claim rather than pixels
+    pub fn is_claim(bytes: &[u8]) -> bool {
+        bytes.len() > SUB_KIND_PREFIX.len()
+            && bytes[..SUB_KIND_PREFIX.len()] == SUB_KIND_PREFIX
+            && bytes[SUB_KIND_PREFIX.len()] == SUB_KIND_REGION_CLAIM
+    }
+
+    /// Encode the claim (16 bytes)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(REGION_CLAIM_SIZE);
+        bytes.extend_from_slice(&SUB_KIND_PREFIX);
+        bytes.push(SUB_KIND_REGION_CLAIM);
+        bytes.extend_from_slice(&self.x.to_be_bytes());
+        bytes.extend_from_slice(&self.y.to_be_bytes());
+        bytes.extend_from_slice(&self.width.to_be_bytes());
+        bytes.extend_from_slice(&self.height.to_be_bytes());
+        bytes.extend_from_slice(&self.duration_blocks.to_be_bytes());
+        bytes
+    }
+
+    /// Decode a claim
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SpecError> {
+        if !Self::is_claim(bytes) {
+            return Err(SpecError::InvalidFormat(
+                "Not a region claim payload".to_string(),
+            ));
+        }
+        if bytes.len() < REGION_CLAIM_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: REGION_CLAIM_SIZE,
+                actual: bytes.len(),
+            });
+        }
+        Ok(Self {
+            x: u16::from_be_bytes([bytes[4], bytes[5]]),
+            y: u16::from_be_bytes([bytes[6], bytes[7]]),
+            width: u16::from_be_bytes([bytes[8], bytes[9]]),
+            height: u16::from_be_bytes([bytes[10], bytes[11]]),
+            duration_blocks: u32::from_be_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+        })
+    }
+
+    /// Number of pixels in the region
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    /// Whether the region has a size and fits the canvas
+    pub fn validate(&self, width: u32, height: u32) -> Result<(), SpecError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(SpecError::InvalidFormat("Empty region".to_string()));
+        }
+        if self.duration_blocks == 0 {
+            return Err(SpecError::InvalidFormat("Zero lease duration".to_string()));
+        }
+        if self.x as u32 + self.width as u32 > width || self.y as u32 + self.height as u32 > height
+        {
+            return Err(SpecError::InvalidFormat(format!(
+                "Region {}x{} at ({}, {}) out of bounds for canvas {}x{}",
+                self.width, self.height, self.x, self.y, width, height
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether the region contains a pixel
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x
+            && y >= self.y
+            && (x as u32) < self.x as u32 + self.width as u32
+            && (y as u32) < self.y as u32 + self.height as u32
+    }
+
+    /// Whether two regions share at least one pixel
+    pub fn overlaps(&self, other: &RegionClaim) -> bool {
+        (self.x as u32) < other.x as u32 + other.width as u32
+            && (other.x as u32) < self.x as u32 + self.width as u32
+            && (self.y as u32) < other.y as u32 + other.height as u32
+            && (other.y as u32) < self.y as u32 + self.height as u32
+    }
+}
+
+/// One key written by a key-value update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateEntry {
+    /// Key within the namespace (1 to 255 bytes)
+    pub key: Vec<u8>,
+    /// New value; empty deletes the key
+    pub value: Vec<u8>,
+}
+
+impl StateEntry {
+    /// Set `key` to `value`
+    pub fn set(key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Delete `key`
+    pub fn delete(key: impl Into<Vec<u8>>) -> Self {
+        Self::set(key, Vec::new())
+    }
+
+    /// Whether the entry deletes its key
+    pub fn is_delete(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+/// Generic key-value update of a namespace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateUpdate {
+    /// SHA-256 hash of the namespace name
+    pub namespace: [u8; 32],
+    /// Position of the update in the namespace, starting at 1
+    pub sequence: u64,
+    /// Keys written, applied together
+    pub entries: Vec<StateEntry>,
+}
+
+impl StateUpdate {
+    /// Create an update of the namespace called `name`
+    pub fn new(name: &str, sequence: u64, entries: Vec<StateEntry>) -> Self {
+        Self {
+            namespace: Self::namespace_hash(name),
+            sequence,
+            entries,
+        }
+    }
+
+    /// Hash identifying the namespace called `name`
+    pub fn namespace_hash(name: &str) -> [u8; 32] {
+        sha256::Hash::hash(name.as_bytes()).to_byte_array()
+    }
+
+    /// Whether a State payload is a key-value update rather than pixels
+    pub fn is_update(bytes: &[u8]) -> bool {
+        bytes.len() > SUB_KIND_PREFIX.len()
+            && bytes[..SUB_KIND_PREFIX.len()] == SUB_KIND_PREFIX
+            && bytes[SUB_KIND_PREFIX.len()] == SUB_KIND_KEY_VALUE
+    }
+
+    /// Encode the update
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size: usize = self
+            .entries
+            .iter()
+            .map(|entry| 3 + entry.key.len() + entry.value.len())
+            .sum();
+        let mut bytes = Vec::with_capacity(KEY_VALUE_HEADER_SIZE + size);
+        bytes.extend_from_slice(&SUB_KIND_PREFIX);
+        bytes.push(SUB_KIND_KEY_VALUE);
+        bytes.extend_from_slice(&self.namespace);
+        bytes.extend_from_slice(&self.sequence.to_be_bytes());
+        bytes.extend_from_slice(&(self.entries.len() as u16).to_be_bytes());
+        for entry in &self.entries {
+            bytes.push(entry.key.len() as u8);
+            bytes.extend_from_slice(&entry.key);
+            bytes.extend_from_slice(&(entry.value.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&entry.value);
+        }
+        bytes
+    }
+
+    /// Decode an update
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SpecError> {
+        if !Self::is_update(bytes) {
+            return Err(SpecError::InvalidFormat(
+                "Not a key-value update payload".to_string(),
+            ));
+        }
+        if bytes.len() < KEY_VALUE_HEADER_SIZE {
+            return Err(SpecError::PayloadTooShort {
+                expected: KEY_VALUE_HEADER_SIZE,
+                actual: bytes.len(),
+            });
+        }
+
+        let mut namespace = [0u8; 32];
+        namespace.copy_from_slice(&bytes[4..36]);
+        let mut sequence = [0u8; 8];
+        sequence.copy_from_slice(&bytes[36..44]);
+        let count = u16::from_be_bytes([bytes[44], bytes[45]]) as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        let mut offset = KEY_VALUE_HEADER_SIZE;
+        for _ in 0..count {
+            let key_len = *bytes.get(offset).ok_or(SpecError::PayloadTooShort {
+                expected: offset + 1,
+                actual: bytes.len(),
+            })? as usize;
+            let value_at = offset + 1 + key_len;
+            if bytes.len() < value_at + 2 {
+                return Err(SpecError::PayloadTooShort {
+                    expected: value_at + 2,
+                    actual: bytes.len(),
+                });
+            }
+            let value_len = u16::from_be_bytes([bytes[value_at], bytes[value_at + 1]]) as usize;
+            let end = value_at + 2 + value_len;
+            if bytes.len() < end {
+                return Err(SpecError::PayloadTooShort {
+                    expected: end,
+                    actual: bytes.len(),
+                });
+            }
+            entries.push(StateEntry {
+                key: bytes[offset + 1..value_at].to_vec(),
+                value: bytes[value_at + 2..end].to_vec(),
+            });
+            offset = end;
+        }
+        if offset != bytes.len() {
+            return Err(SpecError::InvalidFormat(format!(
+                "{} trailing bytes after key-value entries",
+                bytes.len() - offset
+            )));
+        }
+
+        Ok(Self {
+            namespace,
+            sequence: u64::from_be_bytes(sequence),
+            entries,
+        })
+    }
+
+    /// Whether the update is well formed
+    pub fn validate(&self) -> Result<(), SpecError> {
+        if self.entries.is_empty() {
+            return Err(SpecError::EmptyContent);
+        }
+        if self.entries.len() > u16::MAX as usize {
+            return Err(SpecError::InvalidFormat(format!(
+                "Too many entries: {} (max {})",
+                self.entries.len(),
+                u16::MAX
+            )));
+        }
+        if self.sequence == 0 {
+            return Err(SpecError::InvalidFormat(
+                "Sequence numbers start at 1".to_string(),
+            ));
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.key.is_empty() || entry.key.len() > MAX_STATE_KEY_LEN {
+                return Err(SpecError::InvalidFormat(format!(
+                    "Key length {} out of range 1-{}",
+                    entry.key.len(),
+                    MAX_STATE_KEY_LEN
+                )));
+            }
+            if entry.value.len() > MAX_STATE_VALUE_LEN {
+                return Err(SpecError::InvalidFormat(format!(
+                    "Value length {} exceeds {}",
+                    entry.value.len(),
+                    MAX_STATE_VALUE_LEN
+                )));
+            }
+            // Entries apply together, so a key written twice is ambiguous
+            if self.entries[..i].iter().any(|other| other.key == entry.key) {
+                return Err(SpecError::InvalidFormat(format!(
+                    "Key {} written twice",
+                    hex::encode(&entry.key)
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Static array for supported carriers
 static STATE_CARRIERS: &[CarrierType] = &[
     CarrierType::OpReturn,
@@ -283,6 +632,93 @@ mod tests {
         assert!(spec.validate().is_err());
     }
 
+    #[test]
+    fn test_region_claim_encode_decode() {
+        let claim = RegionClaim::new(10, 20, 30, 40, 144);
+        let bytes = claim.to_bytes();
+        assert_eq!(bytes.len(), REGION_CLAIM_SIZE);
+        assert!(RegionClaim::is_claim(&bytes));
+        assert_eq!(RegionClaim::from_bytes(&bytes).unwrap(), claim);
+
+        // A claim never parses as pixels, and pixels never as a claim
+        assert!(StateSpec::from_bytes(&bytes).is_err());
+        let pixels = StateSpec::new(vec![PixelData::new(1, 2, 3, 4, 5)]).to_bytes();
+        assert!(!RegionClaim::is_claim(&pixels));
+        assert!(RegionClaim::from_bytes(&bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn test_region_claim_geometry() {
+        let claim = RegionClaim::new(10, 10, 5, 5, 1);
+        assert_eq!(claim.area(), 25);
+        assert!(claim.contains(10, 10));
+        assert!(claim.contains(14, 14));
+        assert!(!claim.contains(15, 10));
+        assert!(claim.overlaps(&RegionClaim::new(14, 14, 10, 10, 1)));
+        assert!(!claim.overlaps(&RegionClaim::new(15, 10, 10, 10, 1)));
+
+        assert!(claim.validate(100, 100).is_ok());
+        assert!(claim.validate(12, 100).is_err());
+        assert!(RegionClaim::new(0, 0, 0, 5, 1).validate(100, 100).is_err());
+        assert!(RegionClaim::new(0, 0, 5, 5, 0).validate(100, 100).is_err());
+    }
+
+    #[test]
+    fn test_key_value_update_encode_decode() {
+        let update = StateUpdate::new(
+            "chess/game-42",
+            3,
+            vec![
+                StateEntry::set("e2", "e4"),
+                StateEntry::delete("e7"),
+                StateEntry::set(vec![0u8; MAX_STATE_KEY_LEN], vec![1u8; 300]),
+            ],
+        );
+        assert!(update.validate().is_ok());
+
+        let bytes = update.to_bytes();
+        assert!(StateUpdate::is_update(&bytes));
+        assert!(!RegionClaim::is_claim(&bytes));
+        assert!(StateSpec::from_bytes(&bytes).is_err());
+        assert_eq!(StateUpdate::from_bytes(&bytes).unwrap(), update);
+        assert_eq!(
+            update.namespace,
+            StateUpdate::namespace_hash("chess/game-42")
+        );
+
+        // Truncated and padded payloads are rejected
+        assert!(StateUpdate::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(StateUpdate::from_bytes(&bytes[..KEY_VALUE_HEADER_SIZE - 1]).is_err());
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(StateUpdate::from_bytes(&padded).is_err());
+        assert!(StateUpdate::from_bytes(&RegionClaim::new(0, 0, 1, 1, 1).to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_key_value_update_validation() {
+        let set = |entries| StateUpdate::new("ns", 1, entries);
+        assert!(matches!(
+            set(Vec::new()).validate(),
+            Err(SpecError::EmptyContent)
+        ));
+        assert!(set(vec![StateEntry::set("", "x")]).validate().is_err());
+        assert!(set(vec![StateEntry::set(
+            "k",
+            vec![0u8; MAX_STATE_VALUE_LEN + 1]
+        )])
+        .validate()
+        .is_err());
+        assert!(
+            set(vec![StateEntry::set("k", "a"), StateEntry::delete("k")])
+                .validate()
+                .is_err()
+        );
+        assert!(StateUpdate::new("ns", 0, vec![StateEntry::set("k", "v")])
+            .validate()
+            .is_err());
+    }
+
     #[test]
     fn test_kind_id() {
         assert_eq!(StateSpec::KIND_ID, 2);
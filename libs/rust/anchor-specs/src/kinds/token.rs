@@ -1,7 +1,7 @@
 //! Kind 20: Token Specification
 //!
 //! The Token kind enables fungible token operations on Bitcoin using the ANCHOR protocol.
-//! Supports deployment, minting, transfers, burns, and splits.
+//! Supports deployment, minting, transfers, burns, splits, and allowances.
 //!
 //! ## Operations
 //!
@@ -12,11 +12,20 @@
 //! | TRANSFER | 0x03 | Transfer tokens |
 //! | BURN | 0x04 | Burn tokens |
 //! | SPLIT | 0x05 | Split tokens across outputs |
+//! | APPROVE | 0x06 | Allow a spender to transfer tokens |
 //!
 //! ## Payload Format
 //!
 //! Each operation has a different payload format. See the operation-specific
 //! documentation for details.
+//!
+//! ## Allowances
+//!
+//! APPROVE spends the owner's anchored token UTXOs and puts their balance on
+//! output `owner_index`. Whoever spends output `spender_index` may then move
+//! up to `amount` of that balance with a TRANSFER that anchors the owner's
+//! UTXO without spending it. The allowance is used up by that one transfer,
+//! and lapses when the owner spends the UTXO.
 
 use crate::error::{Result, SpecError};
 use crate::validation::{AnchorableSpec, KindSpec, OwnedSpec};
@@ -43,6 +52,8 @@ pub enum TokenOperationType {
     Burn = 0x04,
     /// Split tokens across outputs
     Split = 0x05,
+    /// Allow a spender to transfer tokens
+    Approve = 0x06,
 }
 
 impl TryFrom<u8> for TokenOperationType {
@@ -55,6 +66,7 @@ impl TryFrom<u8> for TokenOperationType {
             0x03 => Ok(TokenOperationType::Transfer),
             0x04 => Ok(TokenOperationType::Burn),
             0x05 => Ok(TokenOperationType::Split),
+            0x06 => Ok(TokenOperationType::Approve),
             _ => Err(SpecError::InvalidTokenOperation(value)),
         }
     }
@@ -159,6 +171,14 @@ pub enum TokenOperation {
         token_id: u64,
         allocations: Vec<TokenAllocation>,
     },
+    /// Allow the holder of `spender_index` to transfer up to `amount` of the
+    /// balance put on `owner_index`
+    Approve {
+        token_id: u64,
+        amount: u128,
+        spender_index: u8,
+        owner_index: u8,
+    },
 }
 
 impl TokenOperation {
@@ -170,6 +190,7 @@ impl TokenOperation {
             TokenOperation::Transfer { .. } => TokenOperationType::Transfer,
             TokenOperation::Burn { .. } => TokenOperationType::Burn,
             TokenOperation::Split { .. } => TokenOperationType::Split,
+            TokenOperation::Approve { .. } => TokenOperationType::Approve,
         }
     }
 
@@ -219,6 +240,16 @@ impl TokenOperation {
             allocations,
         }
     }
+
+    /// Create an approve operation
+    pub fn approve(token_id: u64, amount: u128, spender_index: u8, owner_index: u8) -> Self {
+        TokenOperation::Approve {
+            token_id,
+            amount,
+            spender_index,
+            owner_index,
+        }
+    }
 }
 
 /// Token specification (Kind 20)
@@ -265,6 +296,16 @@ impl TokenSpec {
     pub fn split(token_id: u64, allocations: Vec<TokenAllocation>) -> Self {
         Self::new(TokenOperation::split(token_id, allocations))
     }
+
+    /// Create an approve spec
+    pub fn approve(token_id: u64, amount: u128, spender_index: u8, owner_index: u8) -> Self {
+        Self::new(TokenOperation::approve(
+            token_id,
+            amount,
+            spender_index,
+            owner_index,
+        ))
+    }
 }
 
 impl KindSpec for TokenSpec {
@@ -286,6 +327,7 @@ impl KindSpec for TokenSpec {
             TokenOperationType::Transfer => parse_transfer(&body[1..])?,
             TokenOperationType::Burn => parse_burn(&body[1..])?,
             TokenOperationType::Split => parse_split(&body[1..])?,
+            TokenOperationType::Approve => parse_approve(&body[1..])?,
         };
 
         Ok(Self { operation })
@@ -353,6 +395,19 @@ impl KindSpec for TokenSpec {
                 }
                 result
             }
+            TokenOperation::Approve {
+                token_id,
+                amount,
+                spender_index,
+                owner_index,
+            } => {
+                let mut result = vec![TokenOperationType::Approve as u8];
+                result.extend_from_slice(&encode_varint(*token_id as u128));
+                result.extend_from_slice(&encode_varint(*amount));
+                result.push(*spender_index);
+                result.push(*owner_index);
+                result
+            }
         }
     }
 
@@ -403,6 +458,23 @@ impl KindSpec for TokenSpec {
                     ));
                 }
             }
+            TokenOperation::Approve {
+                amount,
+                spender_index,
+                owner_index,
+                ..
+            } => {
+                if *amount == 0 {
+                    return Err(SpecError::InvalidAmount(
+                        "Allowance cannot be zero".to_string(),
+                    ));
+                }
+                if spender_index == owner_index {
+                    return Err(SpecError::InvalidFormat(
+                        "Spender and owner outputs must differ".to_string(),
+                    ));
+                }
+            }
         }
         Ok(())
     }
@@ -424,7 +496,7 @@ impl KindSpec for TokenSpec {
 
 impl AnchorableSpec for TokenSpec {
     fn requires_anchor(&self) -> bool {
-        // Mint, transfer, burn, split all require anchor to token UTXO
+        // Mint, transfer, burn, split, approve all require anchor to token UTXO
         !matches!(self.operation, TokenOperation::Deploy { .. })
     }
 }
@@ -644,6 +716,38 @@ fn parse_split(bytes: &[u8]) -> Result<TokenOperation> {
     }
 }
 
+fn parse_approve(bytes: &[u8]) -> Result<TokenOperation> {
+    if bytes.len() < 4 {
+        return Err(SpecError::PayloadTooShort {
+            expected: 4,
+            actual: bytes.len(),
+        });
+    }
+
+    let mut offset = 0;
+
+    let (token_id, bytes_read) = decode_varint(bytes)?;
+    offset += bytes_read;
+
+    let (amount, bytes_read) = decode_varint(&bytes[offset..])?;
+    offset += bytes_read;
+
+    let [spender_index, owner_index] = bytes
+        .get(offset..offset + 2)
+        .and_then(|indexes| <[u8; 2]>::try_from(indexes).ok())
+        .ok_or_else(|| SpecError::PayloadTooShort {
+            expected: offset + 2,
+            actual: bytes.len(),
+        })?;
+
+    Ok(TokenOperation::Approve {
+        token_id: token_id as u64,
+        amount,
+        spender_index,
+        owner_index,
+    })
+}
+
 // ============================================================================
 // Validation Functions
 // ============================================================================
@@ -734,6 +838,20 @@ mod tests {
         assert_eq!(spec, parsed);
     }
 
+    #[test]
+    fn test_approve_roundtrip() {
+        let spec = TokenSpec::approve(42, 1_000_000, 1, 0);
+
+        let bytes = spec.to_bytes();
+        let parsed = TokenSpec::from_bytes(&bytes).unwrap();
+
+        assert_eq!(spec, parsed);
+        assert!(spec.validate().is_ok());
+        assert!(TokenSpec::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(TokenSpec::approve(42, 0, 1, 0).validate().is_err());
+        assert!(TokenSpec::approve(42, 5, 1, 1).validate().is_err());
+    }
+
     #[test]
     fn test_valid_ticker() {
         assert!(is_valid_ticker("BTC"));
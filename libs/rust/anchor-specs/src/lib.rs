@@ -13,21 +13,42 @@
 //! - `validate()` - Validate payload contents
 //! - `supported_carriers()` - List of supported carrier types
 //! - `recommended_carrier()` - Best carrier for this kind
+//! - `schema()` - JSON Schema of the structured form (see [`schema`])
+//!
+//! Kinds whose payload format evolves implement [`VersionedSpec`] on top of
+//! `KindSpec` to prefix a version byte and migrate older payloads; see
+//! [`versioning`] for details.
+//!
+//! [`ParsedMessageExt`] reads a parsed message's body as its spec in one
+//! call: `message.as_spec::<DnsSpec>()`, or `message.kind_spec()` for
+//! whatever kind it is (see [`message`]).
+//!
+//! State (kind 2) also carries generic namespaced key-value updates;
+//! [`state_index`] is the reference indexer for applications that build
+//! replicated state on them.
+//!
+//! Simple fixed-layout kinds can `#[derive(KindSpec)]` instead of writing the
+//! codec by hand; the supported field attributes are listed in [`codec`].
 //!
 //! ## Supported Kinds
 //!
 //! | Kind | ID | Description |
 //! |------|----|-------------|
+//! | Generic | 0 | Opaque body with a content type hint |
 //! | Text | 1 | UTF-8 text messages |
-//! | State | 2 | State updates |
+//! | State | 2 | Canvas pixels and key-value state updates |
 //! | Vote | 3 | Voting |
 //! | Image | 4 | Image data |
+//! | Identity | 6 | Identity key rotation |
+//! | Mirror | 7 | Copy of a message on another carrier |
 //! | DNS | 10 | Domain name registration |
 //! | Proof | 11 | Proof of existence |
 //! | GeoMarker | 12 | Geographic markers |
 //! | Token | 20 | Token operations |
 //! | Oracle | 30-33 | Oracle attestations |
-//! | Lottery | 40-43 | Lottery operations |
+//! | Predictions | 40-44 | Prediction market operations |
+//! | Lottery | 45-48 | Commit-reveal lotteries |
+//! | Escrow | 50 | Escrowed trade state |
 //!
 //! ## Example
 //!
@@ -52,30 +73,55 @@
 //! assert!(!DnsSpec::supported_carriers().contains(&CarrierType::OpReturn));
 //! ```
 
+// Lets derived impls refer to `::anchor_specs` from inside this crate
+extern crate self as anchor_specs;
+
+pub mod codec;
 mod error;
 pub mod kinds;
+pub mod message;
+pub mod schema;
+pub mod state_index;
 mod validation;
+pub mod versioning;
 
 pub use error::SpecError;
+pub use message::{AnySpec, ParsedMessageExt};
+pub use schema::{kind_schemas, KindSchema};
 pub use validation::{AnchorableSpec, KindSpec, OwnedSpec};
+pub use versioning::{SpecMigration, VersionedSpec};
+
+// Derive macros for the binary codec (see `codec` for field attributes)
+pub use anchor_specs_derive::{KindSpec, SpecRecord};
 
 // Re-export carrier types from anchor-core
 pub use anchor_core::carrier::CarrierType;
 
 /// Prelude module for convenient imports
 pub mod prelude {
+    pub use crate::codec::SpecRecord;
     pub use crate::error::SpecError;
+    pub use crate::message::{AnySpec, ParsedMessageExt};
     pub use crate::validation::{AnchorableSpec, KindSpec, OwnedSpec};
+    pub use crate::versioning::{SpecMigration, VersionedSpec};
     pub use anchor_core::carrier::CarrierType;
+    pub use anchor_specs_derive::{KindSpec, SpecRecord};
 }
 
 // Re-export all kinds at crate level for convenience
 pub use kinds::dns;
+pub use kinds::escrow;
+pub use kinds::generic;
 pub use kinds::geomarker;
+pub use kinds::identity;
+pub use kinds::lottery;
+pub use kinds::mirror;
+pub use kinds::oracle;
 pub use kinds::proof;
 pub use kinds::state;
 pub use kinds::text;
 pub use kinds::token;
+pub use kinds::{carrier_support, is_registered_kind, CarrierSupport, REGISTERED_KINDS};
 
 /// Protocol version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
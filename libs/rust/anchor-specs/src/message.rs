@@ -0,0 +1,207 @@
+//! Typed views of parsed messages
+//!
+//! [`ParsedMessageExt`] goes from a [`ParsedAnchorMessage`] straight to the
+//! spec of its kind:
+//!
+//! ```rust,ignore
+//! use anchor_specs::prelude::*;
+//! use anchor_specs::dns::DnsSpec;
+//!
+//! let message = anchor_core::parse_anchor_payload(&payload)?;
+//! let dns: DnsSpec = message.as_spec()?;
+//!
+//! // Or without knowing the kind up front
+//! match message.kind_spec()? {
+//!     Some(AnySpec::Dns(dns)) => println!("{}", dns.name),
+//!     Some(other) => println!("{}", other.kind_name()),
+//!     None => println!("kind {} has no spec", u8::from(message.kind)),
+//! }
+//! ```
+//!
+//! Parsing doesn't validate; call [`KindSpec::validated`] on the result when
+//! the contents must be checked too.
+
+use anchor_core::ParsedAnchorMessage;
+
+use crate::error::{Result, SpecError};
+use crate::kinds::{
+    DnsSpec, EscrowSpec, GenericSpec, GeoMarkerSpec, IdentityRotationSpec, LotteryClaimSpec,
+    LotteryCreateSpec, LotteryDrawSpec, LotteryTicketSpec, MirrorSpec, OracleAttestationSpec,
+    OracleDisputeSpec, OracleSlashSpec, ProofSpec, StateSpec, TextSpec, TokenSpec,
+};
+use crate::validation::KindSpec;
+
+/// The spec of any kind this crate defines
+#[derive(Debug, Clone)]
+pub enum AnySpec {
+    Generic(GenericSpec),
+    Text(TextSpec),
+    State(StateSpec),
+    GeoMarker(GeoMarkerSpec),
+    Identity(IdentityRotationSpec),
+    Mirror(MirrorSpec),
+    Dns(DnsSpec),
+    Proof(ProofSpec),
+    Token(TokenSpec),
+    OracleAttestation(OracleAttestationSpec),
+    OracleDispute(OracleDisputeSpec),
+    OracleSlash(OracleSlashSpec),
+    LotteryCreate(LotteryCreateSpec),
+    LotteryTicket(LotteryTicketSpec),
+    LotteryDraw(LotteryDrawSpec),
+    LotteryClaim(LotteryClaimSpec),
+    Escrow(EscrowSpec),
+}
+
+impl AnySpec {
+    /// Parse `body` as the spec of `kind`; `Ok(None)` for kinds without one
+    pub fn from_kind(kind: u8, body: &[u8]) -> Result<Option<Self>> {
+        Ok(Some(match kind {
+            GenericSpec::KIND_ID => Self::Generic(GenericSpec::from_bytes(body)?),
+            TextSpec::KIND_ID => Self::Text(TextSpec::from_bytes(body)?),
+            StateSpec::KIND_ID => Self::State(StateSpec::from_bytes(body)?),
+            GeoMarkerSpec::KIND_ID => Self::GeoMarker(GeoMarkerSpec::from_bytes(body)?),
+            IdentityRotationSpec::KIND_ID => {
+                Self::Identity(IdentityRotationSpec::from_bytes(body)?)
+            }
+            MirrorSpec::KIND_ID => Self::Mirror(MirrorSpec::from_bytes(body)?),
+            DnsSpec::KIND_ID => Self::Dns(DnsSpec::from_bytes(body)?),
+            ProofSpec::KIND_ID => Self::Proof(ProofSpec::from_bytes(body)?),
+            TokenSpec::KIND_ID => Self::Token(TokenSpec::from_bytes(body)?),
+            OracleAttestationSpec::KIND_ID => {
+                Self::OracleAttestation(OracleAttestationSpec::from_bytes(body)?)
+            }
+            OracleDisputeSpec::KIND_ID => Self::OracleDispute(OracleDisputeSpec::from_bytes(body)?),
+            OracleSlashSpec::KIND_ID => Self::OracleSlash(OracleSlashSpec::from_bytes(body)?),
+            LotteryCreateSpec::KIND_ID => Self::LotteryCreate(LotteryCreateSpec::from_bytes(body)?),
+            LotteryTicketSpec::KIND_ID => Self::LotteryTicket(LotteryTicketSpec::from_bytes(body)?),
+            LotteryDrawSpec::KIND_ID => Self::LotteryDraw(LotteryDrawSpec::from_bytes(body)?),
+            LotteryClaimSpec::KIND_ID => Self::LotteryClaim(LotteryClaimSpec::from_bytes(body)?),
+            EscrowSpec::KIND_ID => Self::Escrow(EscrowSpec::from_bytes(body)?),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Kind byte of the spec
+    pub fn kind(&self) -> u8 {
+        match self {
+            Self::Generic(_) => GenericSpec::KIND_ID,
+            Self::Text(_) => TextSpec::KIND_ID,
+            Self::State(_) => StateSpec::KIND_ID,
+            Self::GeoMarker(_) => GeoMarkerSpec::KIND_ID,
+            Self::Identity(_) => IdentityRotationSpec::KIND_ID,
+            Self::Mirror(_) => MirrorSpec::KIND_ID,
+            Self::Dns(_) => DnsSpec::KIND_ID,
+            Self::Proof(_) => ProofSpec::KIND_ID,
+            Self::Token(_) => TokenSpec::KIND_ID,
+            Self::OracleAttestation(_) => OracleAttestationSpec::KIND_ID,
+            Self::OracleDispute(_) => OracleDisputeSpec::KIND_ID,
+            Self::OracleSlash(_) => OracleSlashSpec::KIND_ID,
+            Self::LotteryCreate(_) => LotteryCreateSpec::KIND_ID,
+            Self::LotteryTicket(_) => LotteryTicketSpec::KIND_ID,
+            Self::LotteryDraw(_) => LotteryDrawSpec::KIND_ID,
+            Self::LotteryClaim(_) => LotteryClaimSpec::KIND_ID,
+            Self::Escrow(_) => EscrowSpec::KIND_ID,
+        }
+    }
+
+    /// Human-readable name of the kind
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Generic(_) => GenericSpec::KIND_NAME,
+            Self::Text(_) => TextSpec::KIND_NAME,
+            Self::State(_) => StateSpec::KIND_NAME,
+            Self::GeoMarker(_) => GeoMarkerSpec::KIND_NAME,
+            Self::Identity(_) => IdentityRotationSpec::KIND_NAME,
+            Self::Mirror(_) => MirrorSpec::KIND_NAME,
+            Self::Dns(_) => DnsSpec::KIND_NAME,
+            Self::Proof(_) => ProofSpec::KIND_NAME,
+            Self::Token(_) => TokenSpec::KIND_NAME,
+            Self::OracleAttestation(_) => OracleAttestationSpec::KIND_NAME,
+            Self::OracleDispute(_) => OracleDisputeSpec::KIND_NAME,
+            Self::OracleSlash(_) => OracleSlashSpec::KIND_NAME,
+            Self::LotteryCreate(_) => LotteryCreateSpec::KIND_NAME,
+            Self::LotteryTicket(_) => LotteryTicketSpec::KIND_NAME,
+            Self::LotteryDraw(_) => LotteryDrawSpec::KIND_NAME,
+            Self::LotteryClaim(_) => LotteryClaimSpec::KIND_NAME,
+            Self::Escrow(_) => EscrowSpec::KIND_NAME,
+        }
+    }
+}
+
+/// Spec views of a [`ParsedAnchorMessage`]
+///
+/// The text view, [`ParsedAnchorMessage::as_text`], lives in `anchor-core`
+/// itself since it needs no spec.
+pub trait ParsedMessageExt {
+    /// The body parsed as spec `T`, if the message is of `T`'s kind
+    fn as_spec<T: KindSpec>(&self) -> Result<T>;
+
+    /// The body parsed as the spec of the message's kind; `Ok(None)` for
+    /// kinds without a spec in this crate
+    fn kind_spec(&self) -> Result<Option<AnySpec>>;
+}
+
+impl ParsedMessageExt for ParsedAnchorMessage {
+    fn as_spec<T: KindSpec>(&self) -> Result<T> {
+        let kind = u8::from(self.kind);
+        if kind != T::KIND_ID {
+            return Err(SpecError::InvalidFormat(format!(
+                "expected a {} message (kind {}), got kind {}",
+                T::KIND_NAME,
+                T::KIND_ID,
+                kind
+            )));
+        }
+        T::from_bytes(&self.body)
+    }
+
+    fn kind_spec(&self) -> Result<Option<AnySpec>> {
+        AnySpec::from_kind(self.kind.into(), &self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kinds::{DnsOperation, DnsRecord};
+    use anchor_core::AnchorKind;
+
+    fn message(kind: u8, body: Vec<u8>) -> ParsedAnchorMessage {
+        ParsedAnchorMessage::new_root(AnchorKind::from(kind), body)
+    }
+
+    #[test]
+    fn test_as_spec() {
+        let dns = DnsSpec {
+            operation: DnsOperation::Register,
+            name: "example.btc".to_string(),
+            records: vec![DnsRecord::a("93.184.216.34", 3600).unwrap()],
+        };
+        let parsed = message(DnsSpec::KIND_ID, dns.to_bytes());
+        assert_eq!(parsed.as_spec::<DnsSpec>().unwrap().name, "example.btc");
+        assert!(parsed.as_spec::<TextSpec>().is_err());
+        assert_eq!(parsed.as_text(), None);
+
+        match parsed.kind_spec().unwrap() {
+            Some(AnySpec::Dns(spec)) => assert_eq!(spec.records.len(), 1),
+            other => panic!("expected a DNS spec, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_kind_spec() {
+        let text = message(TextSpec::KIND_ID, b"hello".to_vec());
+        assert_eq!(text.as_text(), Some("hello"));
+        let spec = text.kind_spec().unwrap().unwrap();
+        assert_eq!((spec.kind(), spec.kind_name()), (1, TextSpec::KIND_NAME));
+
+        // Kinds without a spec, and bodies that don't parse
+        assert!(message(3, b"raw".to_vec()).kind_spec().unwrap().is_none());
+        assert!(message(200, Vec::new()).kind_spec().unwrap().is_none());
+        assert!(message(DnsSpec::KIND_ID, vec![0xff]).kind_spec().is_err());
+        assert!(message(GenericSpec::KIND_ID, b"raw".to_vec())
+            .kind_spec()
+            .is_err());
+    }
+}
@@ -0,0 +1,952 @@
+//! JSON Schemas for the structured form of each kind
+//!
+//! Every spec's serde representation has a [`JsonSchema`] implementation, so
+//! clients in other languages can check their payload JSON before sending it
+//! to the wallet API. The schemas mirror the serde attributes on the spec
+//! types and are written by hand; keep them in sync when a spec changes.
+//!
+//! ```rust,ignore
+//! use anchor_specs::prelude::*;
+//! use anchor_specs::dns::DnsSpec;
+//!
+//! let schema = DnsSpec::schema();
+//! println!("{}", serde_json::to_string_pretty(&schema)?);
+//! ```
+//!
+//! The `anchor-specs-schema` binary dumps the schemas of every kind.
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use std::borrow::Cow;
+
+use crate::kinds::dns::{DnsOperation, DnsRecord, DnsSpec, RecordType, MAX_DOMAIN_LENGTH};
+use crate::kinds::escrow::{
+    EscrowOperation, EscrowSpec, KEY_SIZE, MAX_TEXT_LENGTH as MAX_ESCROW_TEXT,
+};
+use crate::kinds::generic::{GenericSpec, MAX_CONTENT_TYPE_LENGTH};
+use crate::kinds::geomarker::{GeoMarkerSpec, Geometry, MAX_GEOMETRY_POINTS};
+use crate::kinds::identity::{IdentityOperation, IdentityRotationSpec};
+use crate::kinds::lottery::{
+    LotteryClaimSpec, LotteryCreateSpec, LotteryDrawSpec, LotteryTicketSpec,
+    KEY_SIZE as LOTTERY_KEY_SIZE, MAX_TITLE_LENGTH,
+};
+use crate::kinds::mirror::MirrorSpec;
+use crate::kinds::oracle::{
+    DisputeReason, OracleAttestationSpec, OracleDisputeSpec, OracleSlashSpec, SlashVerdict,
+};
+use crate::kinds::proof::{
+    HashAlgorithm, ProofEntry, ProofMetadata, ProofOperation, ProofSpec, ProofWitness,
+};
+use crate::kinds::state::{PixelData, StateSpec, MAX_PIXELS_PER_TX};
+use crate::kinds::text::{TextSpec, MAX_TEXT_LENGTH};
+use crate::kinds::token::{
+    TokenAllocation, TokenOperation, TokenSpec, MAX_DECIMALS, MAX_TICKER_LENGTH,
+};
+use crate::validation::KindSpec;
+
+/// Schema of one kind, as listed by [`kind_schemas`]
+#[derive(Debug, Clone)]
+pub struct KindSchema {
+    pub kind: u8,
+    pub name: &'static str,
+    pub schema: Schema,
+}
+
+impl KindSchema {
+    fn of<S: KindSpec + JsonSchema>() -> Self {
+        Self {
+            kind: S::KIND_ID,
+            name: S::KIND_NAME,
+            schema: S::schema(),
+        }
+    }
+}
+
+/// Schemas of every kind with a spec, in kind order
+pub fn kind_schemas() -> Vec<KindSchema> {
+    let mut schemas = vec![
+        KindSchema::of::<GenericSpec>(),
+        KindSchema::of::<TextSpec>(),
+        KindSchema::of::<StateSpec>(),
+        KindSchema::of::<GeoMarkerSpec>(),
+        KindSchema::of::<IdentityRotationSpec>(),
+        KindSchema::of::<MirrorSpec>(),
+        KindSchema::of::<DnsSpec>(),
+        KindSchema::of::<ProofSpec>(),
+        KindSchema::of::<TokenSpec>(),
+        KindSchema::of::<OracleAttestationSpec>(),
+        KindSchema::of::<OracleDisputeSpec>(),
+        KindSchema::of::<OracleSlashSpec>(),
+        KindSchema::of::<LotteryCreateSpec>(),
+        KindSchema::of::<LotteryTicketSpec>(),
+        KindSchema::of::<LotteryDrawSpec>(),
+        KindSchema::of::<LotteryClaimSpec>(),
+        KindSchema::of::<EscrowSpec>(),
+    ];
+    schemas.sort_by_key(|s| s.kind);
+    schemas
+}
+
+/// Implement `JsonSchema` for a unit enum serialized by variant name
+macro_rules! unit_enum_schema {
+    ($ty:ty, $name:literal, $description:literal, [$($variant:literal),+ $(,)?]) => {
+        impl JsonSchema for $ty {
+            fn schema_name() -> Cow<'static, str> {
+                $name.into()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                json_schema!({
+                    "description": $description,
+                    "type": "string",
+                    "enum": [$($variant),+],
+                })
+            }
+        }
+    };
+}
+
+/// Bytes as serde writes a `Vec<u8>` or `[u8; N]`: an array of integers
+fn bytes(description: &str, len: Option<usize>) -> Schema {
+    let mut schema = json_schema!({
+        "description": description,
+        "type": "array",
+        "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+    });
+    if let Some(len) = len {
+        schema.insert("minItems".into(), len.into());
+        schema.insert("maxItems".into(), len.into());
+    }
+    schema
+}
+
+// ============================================================================
+// Generic, Text, State, GeoMarker, Identity, Mirror
+// ============================================================================
+
+impl JsonSchema for GenericSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "GenericSpec".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Opaque body with a content type hint (kind 0)",
+            "type": "object",
+            "properties": {
+                "content_type": {
+                    "description": "MIME type of the body",
+                    "type": ["string", "null"],
+                    "maxLength": MAX_CONTENT_TYPE_LENGTH,
+                },
+                "body": bytes("Opaque body", None),
+            },
+            "required": ["content_type", "body"],
+        })
+    }
+}
+
+impl JsonSchema for TextSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "TextSpec".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Text message (kind 1)",
+            "type": "object",
+            "properties": {
+                "text": { "type": "string", "maxLength": MAX_TEXT_LENGTH },
+            },
+            "required": ["text"],
+        })
+    }
+}
+
+impl JsonSchema for PixelData {
+    fn schema_name() -> Cow<'static, str> {
+        "PixelData".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let coord = generator.subschema_for::<u16>();
+        let channel = generator.subschema_for::<u8>();
+        json_schema!({
+            "description": "Pixel coordinates and RGB color",
+            "type": "object",
+            "properties": {
+                "x": coord,
+                "y": coord,
+                "r": channel,
+                "g": channel,
+                "b": channel,
+            },
+            "required": ["x", "y", "r", "g", "b"],
+        })
+    }
+}
+
+impl JsonSchema for StateSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "StateSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let pixel = generator.subschema_for::<PixelData>();
+        json_schema!({
+            "description": "Canvas pixel update (kind 2)",
+            "type": "object",
+            "properties": {
+                "pixels": {
+                    "type": "array",
+                    "items": pixel,
+                    "minItems": 1,
+                    "maxItems": MAX_PIXELS_PER_TX,
+                },
+            },
+            "required": ["pixels"],
+        })
+    }
+}
+
+impl JsonSchema for GeoMarkerSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "GeoMarkerSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let category = generator.subschema_for::<u8>();
+        let geometry = generator.subschema_for::<Geometry>();
+        json_schema!({
+            "description": "Geographic marker (kind 5)",
+            "type": "object",
+            "properties": {
+                "category": category,
+                "latitude": { "type": "number", "minimum": -90, "maximum": 90 },
+                "longitude": { "type": "number", "minimum": -180, "maximum": 180 },
+                "message": {
+                    "description": "At most 255 bytes of UTF-8",
+                    "type": "string",
+                },
+                "geometry": geometry,
+            },
+            "required": ["category", "latitude", "longitude", "message"],
+        })
+    }
+}
+
+impl JsonSchema for Geometry {
+    fn schema_name() -> Cow<'static, str> {
+        "Geometry".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Path or counter-clockwise polygon ring (closed implicitly)",
+            "type": "object",
+            "properties": {
+                "shape": { "type": "string", "enum": ["path", "polygon"] },
+                "points": {
+                    "description": "[latitude, longitude] vertices in degrees",
+                    "type": "array",
+                    "minItems": 2,
+                    "maxItems": MAX_GEOMETRY_POINTS,
+                    "items": {
+                        "type": "array",
+                        "prefixItems": [
+                            { "type": "number", "minimum": -90, "maximum": 90 },
+                            { "type": "number", "minimum": -180, "maximum": 180 },
+                        ],
+                        "minItems": 2,
+                        "maxItems": 2,
+                    },
+                },
+            },
+            "required": ["shape", "points"],
+        })
+    }
+}
+
+unit_enum_schema!(
+    IdentityOperation,
+    "IdentityOperation",
+    "Identity operation",
+    ["Rotate"]
+);
+
+impl JsonSchema for IdentityRotationSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "IdentityRotationSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let operation = generator.subschema_for::<IdentityOperation>();
+        json_schema!({
+            "description": "Identity key rotation (kind 6)",
+            "type": "object",
+            "properties": {
+                "operation": operation,
+                "old_pubkey": bytes("Key being retired (x-only)", Some(32)),
+                "new_pubkey": bytes("Key taking over the identity (x-only)", Some(32)),
+                "new_address": { "type": ["string", "null"] },
+                "signature": bytes("Schnorr signature by the old key", Some(64)),
+            },
+            "required": ["operation", "old_pubkey", "new_pubkey", "signature"],
+        })
+    }
+}
+
+impl JsonSchema for MirrorSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "MirrorSpec".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Copy of another message on a different carrier (kind 7)",
+            "type": "object",
+            "properties": {
+                "kind": { "type": "integer", "minimum": 0, "maximum": 255 },
+                "body": bytes("Body of the mirrored message", None),
+            },
+            "required": ["kind", "body"],
+        })
+    }
+}
+
+// ============================================================================
+// DNS
+// ============================================================================
+
+unit_enum_schema!(
+    DnsOperation,
+    "DnsOperation",
+    "DNS operation",
+    ["Register", "Update", "Transfer"]
+);
+
+unit_enum_schema!(
+    RecordType,
+    "RecordType",
+    "DNS record type",
+    ["A", "AAAA", "CNAME", "TXT", "MX", "NS", "SRV", "TLSA", "SSHFP", "URI"]
+);
+
+impl JsonSchema for DnsRecord {
+    fn schema_name() -> Cow<'static, str> {
+        "DnsRecord".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let record_type = generator.subschema_for::<RecordType>();
+        let ttl = generator.subschema_for::<u16>();
+        let optional = generator.subschema_for::<Option<u16>>();
+        json_schema!({
+            "description": "DNS record; priority is used by MX, SRV and URI, weight by SRV and URI, port by SRV",
+            "type": "object",
+            "properties": {
+                "record_type": record_type,
+                "ttl": ttl,
+                "value": { "type": "string" },
+                "priority": optional,
+                "weight": optional,
+                "port": optional,
+            },
+            "required": ["record_type", "ttl", "value"],
+        })
+    }
+}
+
+impl JsonSchema for DnsSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "DnsSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let operation = generator.subschema_for::<DnsOperation>();
+        let record = generator.subschema_for::<DnsRecord>();
+        json_schema!({
+            "description": "Domain registration, update or transfer (kind 10)",
+            "type": "object",
+            "properties": {
+                "operation": operation,
+                "name": {
+                    "description": "Domain name ending in a supported TLD, e.g. example.btc",
+                    "type": "string",
+                    "maxLength": MAX_DOMAIN_LENGTH,
+                },
+                "records": { "type": "array", "items": record },
+            },
+            "required": ["operation", "name", "records"],
+        })
+    }
+}
+
+// ============================================================================
+// Proof
+// ============================================================================
+
+unit_enum_schema!(
+    ProofOperation,
+    "ProofOperation",
+    "Proof operation",
+    ["Stamp", "Revoke", "Batch", "Witness"]
+);
+
+unit_enum_schema!(
+    HashAlgorithm,
+    "HashAlgorithm",
+    "Hash algorithm",
+    ["Sha256", "Sha512"]
+);
+
+impl JsonSchema for ProofMetadata {
+    fn schema_name() -> Cow<'static, str> {
+        "ProofMetadata".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let text = generator.subschema_for::<Option<String>>();
+        let size = generator.subschema_for::<Option<u64>>();
+        json_schema!({
+            "description": "Optional file metadata",
+            "type": "object",
+            "properties": {
+                "filename": text,
+                "mime_type": text,
+                "file_size": size,
+                "description": text,
+            },
+        })
+    }
+}
+
+impl JsonSchema for ProofEntry {
+    fn schema_name() -> Cow<'static, str> {
+        "ProofEntry".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let algorithm = generator.subschema_for::<HashAlgorithm>();
+        let metadata = generator.subschema_for::<ProofMetadata>();
+        json_schema!({
+            "description": "Hash of a document with its metadata",
+            "type": "object",
+            "properties": {
+                "algorithm": algorithm,
+                "hash": bytes("32 bytes for Sha256, 64 for Sha512", None),
+                "metadata": metadata,
+            },
+            "required": ["algorithm", "hash", "metadata"],
+        })
+    }
+}
+
+impl JsonSchema for ProofWitness {
+    fn schema_name() -> Cow<'static, str> {
+        "ProofWitness".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Witness key and signature of a Witness attestation",
+            "type": "object",
+            "properties": {
+                "pubkey": bytes("Witness key (x-only)", Some(32)),
+                "signature": bytes("Schnorr signature by the witness key", Some(64)),
+            },
+            "required": ["pubkey", "signature"],
+        })
+    }
+}
+
+impl JsonSchema for ProofSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "ProofSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let operation = generator.subschema_for::<ProofOperation>();
+        let entry = generator.subschema_for::<ProofEntry>();
+        let witness = generator.subschema_for::<ProofWitness>();
+        json_schema!({
+            "description": "Proof of existence (kind 11)",
+            "type": "object",
+            "properties": {
+                "operation": operation,
+                "entries": { "type": "array", "items": entry, "minItems": 1 },
+                "witness": witness,
+            },
+            "required": ["operation", "entries"],
+        })
+    }
+}
+
+// ============================================================================
+// Token
+// ============================================================================
+
+impl JsonSchema for TokenAllocation {
+    fn schema_name() -> Cow<'static, str> {
+        "TokenAllocation".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let output_index = generator.subschema_for::<u8>();
+        let amount = generator.subschema_for::<u128>();
+        json_schema!({
+            "description": "Amount assigned to a transaction output",
+            "type": "object",
+            "properties": {
+                "output_index": output_index,
+                "amount": amount,
+            },
+            "required": ["output_index", "amount"],
+        })
+    }
+}
+
+/// An externally tagged struct variant: `{ "<name>": { ...fields } }`
+fn variant(name: &str, fields: Schema) -> Schema {
+    json_schema!({
+        "type": "object",
+        "properties": { name: fields },
+        "required": [name],
+        "additionalProperties": false,
+    })
+}
+
+impl JsonSchema for TokenOperation {
+    fn schema_name() -> Cow<'static, str> {
+        "TokenOperation".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let token_id = generator.subschema_for::<u64>();
+        let amount = generator.subschema_for::<u128>();
+        let output_index = generator.subschema_for::<u8>();
+        let mint_limit = generator.subschema_for::<Option<u128>>();
+        let flags = generator.subschema_for::<u8>();
+        let allocations = generator.subschema_for::<Vec<TokenAllocation>>();
+
+        json_schema!({
+            "description": "Token operation",
+            "oneOf": [
+                variant("Deploy", json_schema!({
+                    "type": "object",
+                    "properties": {
+                        "ticker": { "type": "string", "minLength": 1, "maxLength": MAX_TICKER_LENGTH },
+                        "decimals": { "type": "integer", "minimum": 0, "maximum": MAX_DECIMALS },
+                        "max_supply": amount,
+                        "mint_limit": mint_limit,
+                        "flags": flags,
+                    },
+                    "required": ["ticker", "decimals", "max_supply", "flags"],
+                })),
+                variant("Mint", json_schema!({
+                    "type": "object",
+                    "properties": {
+                        "token_id": token_id,
+                        "amount": amount,
+                        "output_index": output_index,
+                    },
+                    "required": ["token_id", "amount", "output_index"],
+                })),
+                variant("Transfer", json_schema!({
+                    "type": "object",
+                    "properties": { "token_id": token_id, "allocations": allocations },
+                    "required": ["token_id", "allocations"],
+                })),
+                variant("Burn", json_schema!({
+                    "type": "object",
+                    "properties": { "token_id": token_id, "amount": amount },
+                    "required": ["token_id", "amount"],
+                })),
+                variant("Split", json_schema!({
+                    "type": "object",
+                    "properties": { "token_id": token_id, "allocations": allocations },
+                    "required": ["token_id", "allocations"],
+                })),
+                variant("Approve", json_schema!({
+                    "type": "object",
+                    "properties": {
+                        "token_id": token_id,
+                        "amount": amount,
+                        "spender_index": output_index,
+                        "owner_index": output_index,
+                    },
+                    "required": ["token_id", "amount", "spender_index", "owner_index"],
+                })),
+            ],
+        })
+    }
+}
+
+impl JsonSchema for TokenSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "TokenSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let operation = generator.subschema_for::<TokenOperation>();
+        json_schema!({
+            "description": "Token deploy, mint, transfer, burn, split or approve (kind 20)",
+            "type": "object",
+            "properties": { "operation": operation },
+            "required": ["operation"],
+        })
+    }
+}
+
+// ============================================================================
+// Oracles
+// ============================================================================
+
+unit_enum_schema!(
+    DisputeReason,
+    "DisputeReason",
+    "Grounds for disputing an attestation",
+    [
+        "IncorrectOutcome",
+        "Premature",
+        "InvalidSignature",
+        "NotAuthorized"
+    ]
+);
+
+unit_enum_schema!(
+    SlashVerdict,
+    "SlashVerdict",
+    "Outcome of a dispute",
+    ["Upheld", "Rejected"]
+);
+
+impl JsonSchema for OracleAttestationSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "OracleAttestationSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let category = generator.subschema_for::<u8>();
+        let block = generator.subschema_for::<u64>();
+        json_schema!({
+            "description": "Oracle attestation (kind 31)",
+            "type": "object",
+            "properties": {
+                "category": category,
+                "event_id": bytes("Event identifier", Some(32)),
+                "attestation_block": block,
+                "outcome": bytes("Outcome data, often UTF-8", None),
+                "signature": bytes("Schnorr signature over the outcome", Some(64)),
+            },
+            "required": ["category", "event_id", "attestation_block", "outcome", "signature"],
+        })
+    }
+}
+
+impl JsonSchema for OracleDisputeSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "OracleDisputeSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let vout = generator.subschema_for::<u16>();
+        let reason = generator.subschema_for::<DisputeReason>();
+        let sats = generator.subschema_for::<u64>();
+        json_schema!({
+            "description": "Dispute of an oracle attestation (kind 32)",
+            "type": "object",
+            "properties": {
+                "disputer_pubkey": bytes("Challenger public key", Some(32)),
+                "attestation_txid": bytes("Disputed attestation transaction, display byte order", Some(32)),
+                "attestation_vout": vout,
+                "reason": reason,
+                "bond_sats": sats,
+                "evidence": { "type": "string" },
+            },
+            "required": [
+                "disputer_pubkey",
+                "attestation_txid",
+                "attestation_vout",
+                "reason",
+                "bond_sats",
+                "evidence",
+            ],
+        })
+    }
+}
+
+impl JsonSchema for OracleSlashSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "OracleSlashSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let verdict = generator.subschema_for::<SlashVerdict>();
+        let sats = generator.subschema_for::<u64>();
+        json_schema!({
+            "description": "Settlement of an oracle dispute (kind 33)",
+            "type": "object",
+            "properties": {
+                "dispute_txid": bytes("Dispute being settled, display byte order", Some(32)),
+                "verdict": verdict,
+                "amount_sats": sats,
+                "note": { "type": "string" },
+            },
+            "required": ["dispute_txid", "verdict", "amount_sats", "note"],
+        })
+    }
+}
+
+// ============================================================================
+// Lotteries
+// ============================================================================
+
+impl JsonSchema for LotteryCreateSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "LotteryCreateSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let sats = generator.subschema_for::<u64>();
+        let count = generator.subschema_for::<u32>();
+        let height = generator.subschema_for::<u32>();
+        json_schema!({
+            "description": "Lottery with a committed draw secret (kind 45)",
+            "type": "object",
+            "properties": {
+                "ticket_price": sats,
+                "max_tickets": count,
+                "sales_end_height": height,
+                "draw_height": height,
+                "fee_bps": { "type": "integer", "minimum": 0, "maximum": 10000 },
+                "commitment": bytes("SHA256 of the draw secret", Some(32)),
+                "operator": bytes("Operator key, compressed", Some(LOTTERY_KEY_SIZE)),
+                "title": { "type": "string", "maxLength": MAX_TITLE_LENGTH },
+            },
+            "required": [
+                "ticket_price",
+                "max_tickets",
+                "sales_end_height",
+                "draw_height",
+                "fee_bps",
+                "commitment",
+                "operator",
+                "title",
+            ],
+        })
+    }
+}
+
+impl JsonSchema for LotteryTicketSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "LotteryTicketSpec".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Lottery ticket purchase (kind 46)",
+            "type": "object",
+            "properties": {
+                "lottery_id": bytes("Lottery create transaction, display byte order", Some(32)),
+                "count": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                "buyer": bytes("Buyer key, compressed", Some(LOTTERY_KEY_SIZE)),
+            },
+            "required": ["lottery_id", "count", "buyer"],
+        })
+    }
+}
+
+impl JsonSchema for LotteryDrawSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "LotteryDrawSpec".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Lottery draw secret reveal (kind 47)",
+            "type": "object",
+            "properties": {
+                "lottery_id": bytes("Lottery create transaction, display byte order", Some(32)),
+                "secret": bytes("Secret committed to at creation", Some(32)),
+            },
+            "required": ["lottery_id", "secret"],
+        })
+    }
+}
+
+impl JsonSchema for LotteryClaimSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "LotteryClaimSpec".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Lottery prize or refund claim (kind 48)",
+            "type": "object",
+            "properties": {
+                "lottery_id": bytes("Lottery create transaction, display byte order", Some(32)),
+                "ticket_txid": bytes("Ticket transaction, display byte order", Some(32)),
+            },
+            "required": ["lottery_id", "ticket_txid"],
+        })
+    }
+}
+
+// ============================================================================
+// Escrow
+// ============================================================================
+
+impl JsonSchema for EscrowOperation {
+    fn schema_name() -> Cow<'static, str> {
+        "EscrowOperation".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let amount = generator.subschema_for::<u64>();
+        let text = json_schema!({ "type": "string", "maxLength": MAX_ESCROW_TEXT });
+
+        json_schema!({
+            "description": "Escrow operation",
+            "oneOf": [
+                variant("Fund", json_schema!({
+                    "type": "object",
+                    "properties": {
+                        "buyer": bytes("Buyer key, compressed", Some(KEY_SIZE)),
+                        "seller": bytes("Seller key, compressed", Some(KEY_SIZE)),
+                        "arbiter": bytes("Arbiter key, compressed", Some(KEY_SIZE)),
+                        "amount": amount,
+                        "terms": text,
+                    },
+                    "required": ["buyer", "seller", "arbiter", "amount", "terms"],
+                })),
+                {
+                    "description": "Operations without data",
+                    "type": "string",
+                    "enum": ["Release", "Refund"],
+                },
+                variant("Dispute", json_schema!({
+                    "type": "object",
+                    "properties": { "reason": text },
+                    "required": ["reason"],
+                })),
+            ],
+        })
+    }
+}
+
+impl JsonSchema for EscrowSpec {
+    fn schema_name() -> Cow<'static, str> {
+        "EscrowSpec".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let operation = generator.subschema_for::<EscrowOperation>();
+        json_schema!({
+            "description": "Escrowed trade fund, release, refund or dispute (kind 50)",
+            "type": "object",
+            "properties": { "operation": operation },
+            "required": ["operation"],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kinds::dns::DnsRecord;
+    use crate::kinds::token::TokenOperation;
+
+    fn property_names(schema: &Schema) -> Vec<String> {
+        let mut names: Vec<String> = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Every serialized field appears in the schema
+    fn assert_covers<T: JsonSchema + serde::Serialize>(value: &T) {
+        let schema = schemars::schema_for!(T);
+        let json = serde_json::to_value(value).unwrap();
+        for key in json.as_object().unwrap().keys() {
+            assert!(
+                property_names(&schema).contains(key),
+                "{} schema lacks {}",
+                T::schema_name(),
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_kind_has_a_schema() {
+        let schemas = kind_schemas();
+        assert_eq!(schemas.len(), 17);
+        assert!(schemas.windows(2).all(|w| w[0].kind < w[1].kind));
+        for kind in &schemas {
+            assert_eq!(kind.schema.get("type"), Some(&"object".into()));
+            assert!(kind.schema.get("$schema").is_some());
+        }
+    }
+
+    #[test]
+    fn test_schemas_cover_serialized_fields() {
+        assert_covers(&TextSpec::new("hi"));
+        assert_covers(&StateSpec::new(vec![PixelData::new(1, 2, 3, 4, 5)]));
+        assert_covers(
+            &GeoMarkerSpec::new(0, 1.0, 2.0, "here")
+                .with_geometry(Geometry::path(vec![[1.0, 2.0], [1.5, 2.5]])),
+        );
+        assert_covers(&DnsSpec::register(
+            "example.btc",
+            vec![DnsRecord::a("93.184.216.34", 3600).unwrap()],
+        ));
+        let keypair = bitcoin::secp256k1::Keypair::from_seckey_slice(
+            &bitcoin::secp256k1::Secp256k1::new(),
+            &[7u8; 32],
+        )
+        .unwrap();
+        assert_covers(&ProofSpec::witness(&keypair, HashAlgorithm::Sha256, vec![0u8; 32]).unwrap());
+        assert_covers(&TokenSpec::new(TokenOperation::Burn {
+            token_id: 1,
+            amount: 5,
+        }));
+        assert_covers(&DnsRecord::srv("sip.example.btc", 10, 5, 5060, 3600));
+        assert_covers(&EscrowSpec::release());
+        assert_covers(&MirrorSpec::new(1, b"hi".to_vec()));
+        assert_covers(&GenericSpec::new("text/plain", b"hi".to_vec()));
+        assert_covers(&LotteryTicketSpec {
+            lottery_id: [1u8; 32],
+            count: 2,
+            buyer: vec![2u8; LOTTERY_KEY_SIZE],
+        });
+        assert_covers(&LotteryDrawSpec {
+            lottery_id: [1u8; 32],
+            secret: [3u8; 32],
+        });
+    }
+
+    #[test]
+    fn test_token_operation_variants() {
+        let schema = schemars::schema_for!(TokenOperation);
+        let variants = schema.get("oneOf").and_then(|v| v.as_array()).unwrap();
+        let names: Vec<&str> = variants
+            .iter()
+            .flat_map(|v| v["required"].as_array().unwrap())
+            .map(|n| n.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["Deploy", "Mint", "Transfer", "Burn", "Split", "Approve"]
+        );
+
+        let json = serde_json::to_value(TokenOperation::Burn {
+            token_id: 1,
+            amount: 5,
+        })
+        .unwrap();
+        assert!(json.get("Burn").is_some());
+    }
+}
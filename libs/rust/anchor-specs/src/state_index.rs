@@ -0,0 +1,244 @@
+//! Reference indexer for key-value State updates
+//!
+//! [`StateIndex`] applies [`StateUpdate`]s in chain order and keeps the
+//! resulting key-value state of every namespace, so applications can build
+//! replicated state machines on the State kind and agree on the state from
+//! the same messages.
+//!
+//! ## Rules
+//!
+//! - The update with sequence 1 creates a namespace and makes its writer the
+//!   namespace owner; later updates from any other writer are ignored.
+//! - Each update must carry the next sequence of its namespace. Replayed or
+//!   skipped sequences are ignored, so writers resubmit after a conflict.
+//! - All entries of an update apply together; an empty value deletes a key.
+//!
+//! What identifies a writer is up to the application: the script of the
+//! transaction's first input, an identity key, or nothing at all for
+//! namespaces anyone may write (pass an empty writer).
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use anchor_specs::state::{StateEntry, StateUpdate};
+//! use anchor_specs::state_index::{StateIndex, UpdateOutcome};
+//!
+//! let mut index = StateIndex::new();
+//! let update = StateUpdate::new("chess/game-42", 1, vec![StateEntry::set("e2", "e4")]);
+//! assert_eq!(index.apply(b"alice", &update)?, UpdateOutcome::Applied);
+//!
+//! let namespace = StateUpdate::namespace_hash("chess/game-42");
+//! assert_eq!(index.get(&namespace, b"e2"), Some(&b"e4"[..]));
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SpecError;
+use crate::kinds::state::StateUpdate;
+
+/// Result of applying a valid update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateOutcome {
+    /// The update changed the namespace
+    Applied,
+    /// The sequence was already applied
+    Stale { current: u64 },
+    /// Earlier sequences have not been applied yet
+    Gap { current: u64 },
+    /// The writer does not own the namespace
+    Unauthorized,
+}
+
+/// Current state of one namespace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceState {
+    /// Writer of the first update
+    pub owner: Vec<u8>,
+    /// Sequence of the last applied update
+    pub sequence: u64,
+    /// Current value of every key
+    pub entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+/// Key-value state of every namespace seen
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateIndex {
+    namespaces: BTreeMap<[u8; 32], NamespaceState>,
+}
+
+impl StateIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply an update sent by `writer`
+    ///
+    /// Invalid updates are rejected with an error and leave the index
+    /// unchanged, like updates with any outcome other than `Applied`.
+    pub fn apply(
+        &mut self,
+        writer: &[u8],
+        update: &StateUpdate,
+    ) -> Result<UpdateOutcome, SpecError> {
+        update.validate()?;
+
+        let current = self.sequence(&update.namespace);
+        if update.sequence <= current {
+            return Ok(UpdateOutcome::Stale { current });
+        }
+        if update.sequence > current + 1 {
+            return Ok(UpdateOutcome::Gap { current });
+        }
+
+        let namespace = self
+            .namespaces
+            .entry(update.namespace)
+            .or_insert_with(|| NamespaceState {
+                owner: writer.to_vec(),
+                ..NamespaceState::default()
+            });
+        if namespace.owner != writer {
+            return Ok(UpdateOutcome::Unauthorized);
+        }
+
+        namespace.sequence = update.sequence;
+        for entry in &update.entries {
+            if entry.is_delete() {
+                namespace.entries.remove(&entry.key);
+            } else {
+                namespace
+                    .entries
+                    .insert(entry.key.clone(), entry.value.clone());
+            }
+        }
+        Ok(UpdateOutcome::Applied)
+    }
+
+    /// Apply a State message body sent by `writer`
+    ///
+    /// Returns `None` for pixel and region claim payloads, which are not
+    /// key-value updates.
+    pub fn apply_payload(
+        &mut self,
+        writer: &[u8],
+        body: &[u8],
+    ) -> Result<Option<UpdateOutcome>, SpecError> {
+        if !StateUpdate::is_update(body) {
+            return Ok(None);
+        }
+        let update = StateUpdate::from_bytes(body)?;
+        self.apply(writer, &update).map(Some)
+    }
+
+    /// State of a namespace, if any update created it
+    pub fn namespace(&self, namespace: &[u8; 32]) -> Option<&NamespaceState> {
+        self.namespaces.get(namespace)
+    }
+
+    /// Sequence of the last update applied to a namespace, 0 if none
+    pub fn sequence(&self, namespace: &[u8; 32]) -> u64 {
+        self.namespace(namespace).map_or(0, |state| state.sequence)
+    }
+
+    /// Current value of a key
+    pub fn get(&self, namespace: &[u8; 32], key: &[u8]) -> Option<&[u8]> {
+        self.namespace(namespace)?
+            .entries
+            .get(key)
+            .map(Vec::as_slice)
+    }
+
+    /// Hashes of all namespaces, in byte order
+    pub fn namespaces(&self) -> impl Iterator<Item = &[u8; 32]> {
+        self.namespaces.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kinds::state::{PixelData, StateEntry, StateSpec};
+    use crate::validation::KindSpec;
+
+    fn update(sequence: u64, entries: Vec<StateEntry>) -> StateUpdate {
+        StateUpdate::new("counter", sequence, entries)
+    }
+
+    #[test]
+    fn test_applies_updates_in_sequence() {
+        let mut index = StateIndex::new();
+        let namespace = StateUpdate::namespace_hash("counter");
+
+        let first = update(
+            1,
+            vec![StateEntry::set("a", "1"), StateEntry::set("b", "2")],
+        );
+        assert_eq!(
+            index.apply(b"alice", &first).unwrap(),
+            UpdateOutcome::Applied
+        );
+        let second = update(2, vec![StateEntry::set("a", "3"), StateEntry::delete("b")]);
+        assert_eq!(
+            index.apply(b"alice", &second).unwrap(),
+            UpdateOutcome::Applied
+        );
+
+        assert_eq!(index.sequence(&namespace), 2);
+        assert_eq!(index.get(&namespace, b"a"), Some(&b"3"[..]));
+        assert_eq!(index.get(&namespace, b"b"), None);
+        assert_eq!(index.namespaces().count(), 1);
+    }
+
+    #[test]
+    fn test_ignores_out_of_order_and_foreign_updates() {
+        let mut index = StateIndex::new();
+        let namespace = StateUpdate::namespace_hash("counter");
+
+        // Nothing applies before the namespace exists except sequence 1
+        assert_eq!(
+            index
+                .apply(b"alice", &update(2, vec![StateEntry::set("a", "1")]))
+                .unwrap(),
+            UpdateOutcome::Gap { current: 0 }
+        );
+        index
+            .apply(b"alice", &update(1, vec![StateEntry::set("a", "1")]))
+            .unwrap();
+
+        assert_eq!(
+            index
+                .apply(b"alice", &update(1, vec![StateEntry::set("a", "2")]))
+                .unwrap(),
+            UpdateOutcome::Stale { current: 1 }
+        );
+        assert_eq!(
+            index
+                .apply(b"mallory", &update(2, vec![StateEntry::set("a", "2")]))
+                .unwrap(),
+            UpdateOutcome::Unauthorized
+        );
+        assert!(index.apply(b"alice", &update(2, Vec::new())).is_err());
+
+        assert_eq!(index.sequence(&namespace), 1);
+        assert_eq!(index.get(&namespace, b"a"), Some(&b"1"[..]));
+        assert_eq!(index.namespace(&namespace).unwrap().owner, b"alice");
+    }
+
+    #[test]
+    fn test_apply_payload_skips_pixels() {
+        let mut index = StateIndex::new();
+        let pixels = StateSpec::new(vec![PixelData::new(1, 2, 3, 4, 5)]).to_bytes();
+        assert_eq!(index.apply_payload(b"", &pixels).unwrap(), None);
+
+        let body = update(1, vec![StateEntry::set("a", "1")]).to_bytes();
+        assert_eq!(
+            index.apply_payload(b"", &body).unwrap(),
+            Some(UpdateOutcome::Applied)
+        );
+        assert!(index.apply_payload(b"", &body[..body.len() - 1]).is_err());
+    }
+}
@@ -76,6 +76,16 @@ pub trait KindSpec: Sized {
             })
         }
     }
+
+    /// JSON Schema of the spec's structured (serde) representation
+    ///
+    /// See [`crate::schema`] for the kinds that provide one.
+    fn schema() -> schemars::Schema
+    where
+        Self: schemars::JsonSchema,
+    {
+        schemars::schema_for!(Self)
+    }
 }
 
 /// Extension trait for specs that support anchoring (updates/transfers)
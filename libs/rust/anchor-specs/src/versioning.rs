@@ -0,0 +1,264 @@
+//! Payload versioning and migration helpers
+//!
+//! Kind payload formats evolve over time. To keep accepting older payloads
+//! after a new format ships, versioned kinds prefix their body with a single
+//! version byte:
+//!
+//! ```text
+//! ┌───────────┬─────────────────────────────┐
+//! │ Version   │ Kind payload (version N)    │
+//! │ (1 byte)  │ (variable)                  │
+//! └───────────┴─────────────────────────────┘
+//! ```
+//!
+//! The current format is decoded with [`KindSpec::from_bytes`]. Older formats
+//! are decoded by [`VersionedSpec::from_legacy_bytes`], which usually parses
+//! the legacy representation and converts it with [`SpecMigration`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use anchor_specs::versioning::{migrate_from, SpecMigration, VersionedSpec};
+//!
+//! impl SpecMigration<PollSpecV1> for PollSpec {
+//!     fn migrate(old: PollSpecV1) -> Result<Self> {
+//!         Ok(PollSpec { question: old.question, options: old.options, deadline: None })
+//!     }
+//! }
+//!
+//! impl VersionedSpec for PollSpec {
+//!     const CURRENT_VERSION: u8 = 2;
+//!     const MIN_SUPPORTED_VERSION: u8 = 1;
+//!
+//!     fn from_legacy_bytes(version: u8, body: &[u8]) -> Result<Self> {
+//!         match version {
+//!             1 => migrate_from::<PollSpecV1, Self>(body),
+//!             v => Err(Self::unsupported_version(v)),
+//!         }
+//!     }
+//! }
+//!
+//! // Indexers accept both v1 and v2 payloads transparently
+//! let spec = PollSpec::from_bytes_versioned(&payload)?;
+//! ```
+
+use crate::error::{Result, SpecError};
+use crate::validation::KindSpec;
+
+/// Split a versioned payload into its version byte and the remaining body.
+pub fn split_version(payload: &[u8]) -> Result<(u8, &[u8])> {
+    match payload.split_first() {
+        Some((version, body)) => Ok((*version, body)),
+        None => Err(SpecError::PayloadTooShort {
+            expected: 1,
+            actual: 0,
+        }),
+    }
+}
+
+/// Explicit conversion from an older payload representation.
+///
+/// Implemented by the current spec type once per legacy version it accepts.
+pub trait SpecMigration<Old>: Sized {
+    /// Convert a parsed legacy payload into the current representation.
+    fn migrate(old: Old) -> Result<Self>;
+}
+
+/// Parse a body with a legacy spec's codec and migrate it to the current spec.
+pub fn migrate_from<Old, New>(body: &[u8]) -> Result<New>
+where
+    Old: KindSpec,
+    New: SpecMigration<Old>,
+{
+    New::migrate(Old::from_bytes(body)?)
+}
+
+/// Extension trait for kinds whose payload carries a leading version byte.
+pub trait VersionedSpec: KindSpec {
+    /// Version written by [`VersionedSpec::to_bytes_versioned`]
+    const CURRENT_VERSION: u8;
+
+    /// Oldest version this spec can still decode
+    const MIN_SUPPORTED_VERSION: u8 = Self::CURRENT_VERSION;
+
+    /// Decode a body written in an older format.
+    ///
+    /// Called for versions in `MIN_SUPPORTED_VERSION..CURRENT_VERSION`. The
+    /// default rejects every legacy version.
+    fn from_legacy_bytes(version: u8, _body: &[u8]) -> Result<Self> {
+        Err(Self::unsupported_version(version))
+    }
+
+    /// Check whether a version byte can be decoded by this spec.
+    fn is_version_supported(version: u8) -> bool {
+        (Self::MIN_SUPPORTED_VERSION..=Self::CURRENT_VERSION).contains(&version)
+    }
+
+    /// Parse a version-prefixed payload, migrating older formats as needed.
+    fn from_bytes_versioned(payload: &[u8]) -> Result<Self> {
+        let (version, body) = split_version(payload)?;
+        if !Self::is_version_supported(version) {
+            return Err(Self::unsupported_version(version));
+        }
+        if version == Self::CURRENT_VERSION {
+            Self::from_bytes(body)
+        } else {
+            Self::from_legacy_bytes(version, body)
+        }
+    }
+
+    /// Encode the spec with the current version byte prepended.
+    fn to_bytes_versioned(&self) -> Vec<u8> {
+        let mut result = vec![Self::CURRENT_VERSION];
+        result.extend_from_slice(&self.to_bytes());
+        result
+    }
+
+    /// Build the error returned for an unknown or retired version.
+    fn unsupported_version(version: u8) -> SpecError {
+        SpecError::UnsupportedVersion {
+            kind: Self::KIND_NAME,
+            version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_core::carrier::CarrierType;
+
+    /// v1: a single UTF-8 label
+    #[derive(Debug, PartialEq)]
+    struct LabelV1 {
+        label: String,
+    }
+
+    impl KindSpec for LabelV1 {
+        const KIND_ID: u8 = 200;
+        const KIND_NAME: &'static str = "Label";
+
+        fn from_bytes(body: &[u8]) -> Result<Self> {
+            Ok(Self {
+                label: String::from_utf8(body.to_vec())?,
+            })
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            self.label.as_bytes().to_vec()
+        }
+
+        fn validate(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn supported_carriers() -> &'static [CarrierType] {
+            &[CarrierType::OpReturn]
+        }
+
+        fn recommended_carrier() -> CarrierType {
+            CarrierType::OpReturn
+        }
+    }
+
+    /// v2: a priority byte followed by the label
+    #[derive(Debug, PartialEq)]
+    struct Label {
+        priority: u8,
+        label: String,
+    }
+
+    impl KindSpec for Label {
+        const KIND_ID: u8 = 200;
+        const KIND_NAME: &'static str = "Label";
+
+        fn from_bytes(body: &[u8]) -> Result<Self> {
+            let (priority, rest) = split_version(body)?;
+            Ok(Self {
+                priority,
+                label: String::from_utf8(rest.to_vec())?,
+            })
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            let mut result = vec![self.priority];
+            result.extend_from_slice(self.label.as_bytes());
+            result
+        }
+
+        fn validate(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn supported_carriers() -> &'static [CarrierType] {
+            &[CarrierType::OpReturn]
+        }
+
+        fn recommended_carrier() -> CarrierType {
+            CarrierType::OpReturn
+        }
+    }
+
+    impl SpecMigration<LabelV1> for Label {
+        fn migrate(old: LabelV1) -> Result<Self> {
+            Ok(Self {
+                priority: 0,
+                label: old.label,
+            })
+        }
+    }
+
+    impl VersionedSpec for Label {
+        const CURRENT_VERSION: u8 = 2;
+        const MIN_SUPPORTED_VERSION: u8 = 1;
+
+        fn from_legacy_bytes(version: u8, body: &[u8]) -> Result<Self> {
+            match version {
+                1 => migrate_from::<LabelV1, Self>(body),
+                v => Err(Self::unsupported_version(v)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_versioned_roundtrip() {
+        let spec = Label {
+            priority: 7,
+            label: "hello".to_string(),
+        };
+        let bytes = spec.to_bytes_versioned();
+        assert_eq!(bytes[0], 2);
+        assert_eq!(Label::from_bytes_versioned(&bytes).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_legacy_payload_is_migrated() {
+        let mut payload = vec![1];
+        payload.extend_from_slice(b"legacy");
+
+        let spec = Label::from_bytes_versioned(&payload).unwrap();
+        assert_eq!(
+            spec,
+            Label {
+                priority: 0,
+                label: "legacy".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unsupported_versions_rejected() {
+        assert!(matches!(
+            Label::from_bytes_versioned(&[0, b'x']),
+            Err(SpecError::UnsupportedVersion { version: 0, .. })
+        ));
+        assert!(matches!(
+            Label::from_bytes_versioned(&[3, 0, b'x']),
+            Err(SpecError::UnsupportedVersion { version: 3, .. })
+        ));
+        assert!(matches!(
+            Label::from_bytes_versioned(&[]),
+            Err(SpecError::PayloadTooShort { .. })
+        ));
+    }
+}
@@ -0,0 +1,596 @@
+//! Broadcast scheduling
+//!
+//! Broadcasting several transactions back to back through the same node lets
+//! an observer link them by timing and origin. [`BroadcastScheduler`] waits a
+//! random delay between broadcasts and rotates them across endpoints, so
+//! consecutive transactions leave at unrelated times from different places.
+//!
+//! Endpoints implement [`BroadcastEndpoint`]:
+//!
+//! - [`NodeEndpoint`]: Bitcoin Core `sendrawtransaction`
+//! - [`EsploraEndpoint`]: Esplora `POST /tx` over plain HTTP, optionally
+//!   through a SOCKS5 proxy such as Tor. Each broadcast authenticates to the
+//!   proxy with fresh random credentials, which Tor's `IsolateSOCKSAuth`
+//!   (on by default) turns into a separate circuit.
+//!
+//! Plain HTTP means Esplora servers should be local or onion services;
+//! HTTPS URLs are rejected rather than sent in the clear.
+//!
+//! The scheduler blocks the calling thread while it waits; call it from
+//! `spawn_blocking` in async code.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::{Transaction, Txid};
+use bitcoincore_rpc::{Auth, Client, RpcApi};
+use rand::Rng;
+
+use crate::config::WalletConfig;
+use crate::error::{Result, WalletError};
+
+/// Shortest wait between broadcasts by default
+pub const DEFAULT_MIN_DELAY: Duration = Duration::from_secs(20);
+
+/// Longest wait between broadcasts by default
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(120);
+
+/// Longest Esplora response read, headers and error text included
+const MAX_RESPONSE_SIZE: u64 = 64 * 1024;
+
+/// Somewhere a signed transaction can be broadcast
+pub trait BroadcastEndpoint: Send {
+    /// Label for receipts and errors, e.g. `esplora(http://...)`
+    fn name(&self) -> String;
+
+    /// Broadcast `tx`, returning its txid once the endpoint accepted it
+    fn broadcast(&self, tx: &Transaction) -> Result<Txid>;
+}
+
+/// Bitcoin Core RPC endpoint
+pub struct NodeEndpoint {
+    url: String,
+    client: Client,
+}
+
+impl NodeEndpoint {
+    /// Connect to the node of a wallet configuration
+    pub fn new(config: &WalletConfig) -> Result<Self> {
+        let auth = Auth::UserPass(config.rpc_user.clone(), config.rpc_password.clone());
+        let client = Client::new(&config.rpc_url, auth)?;
+        Ok(Self {
+            url: config.rpc_url.clone(),
+            client,
+        })
+    }
+}
+
+impl BroadcastEndpoint for NodeEndpoint {
+    fn name(&self) -> String {
+        format!("node({})", self.url)
+    }
+
+    fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+        Ok(self.client.send_raw_transaction(tx)?)
+    }
+}
+
+/// Esplora HTTP endpoint, optionally behind a SOCKS5 proxy
+#[derive(Debug, Clone)]
+pub struct EsploraEndpoint {
+    url: String,
+    host: String,
+    port: u16,
+    path: String,
+    proxy: Option<SocketAddr>,
+    timeout: Duration,
+}
+
+impl EsploraEndpoint {
+    /// Esplora API base URL, e.g. `http://127.0.0.1:3002/api`
+    pub fn new(url: &str) -> Result<Self> {
+        let (host, port, path) = parse_http_url(url)?;
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            host,
+            port,
+            path,
+            proxy: None,
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// Connect through a SOCKS5 proxy, e.g. Tor at `127.0.0.1:9050`
+    ///
+    /// The host name is resolved by the proxy, so `.onion` URLs work.
+    pub fn with_proxy(mut self, proxy: SocketAddr) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set the connect and read timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn connect(&self) -> Result<TcpStream> {
+        http_connect(&self.host, self.port, self.proxy, self.timeout)
+            .map_err(|e| broadcast_error(&self.url, e))
+    }
+}
+
+impl BroadcastEndpoint for EsploraEndpoint {
+    fn name(&self) -> String {
+        match self.proxy {
+            Some(proxy) => format!("esplora({} via {})", self.url, proxy),
+            None => format!("esplora({})", self.url),
+        }
+    }
+
+    fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+        let body = serialize_hex(tx);
+        let request = format!(
+            "POST {}/tx HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            body.len(),
+            body
+        );
+
+        let mut stream = self.connect()?;
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| broadcast_error(&self.url, e))?;
+        let mut response = Vec::new();
+        stream
+            .take(MAX_RESPONSE_SIZE)
+            .read_to_end(&mut response)
+            .map_err(|e| broadcast_error(&self.url, e))?;
+
+        // Esplora answers with the txid, which we already know
+        let (status, message) = parse_http_response(&response)
+            .ok_or_else(|| broadcast_error(&self.url, "malformed HTTP response"))?;
+        if !(200..300).contains(&status) {
+            return Err(broadcast_error(
+                &self.url,
+                format!("HTTP {}: {}", status, message.trim()),
+            ));
+        }
+        Ok(tx.compute_txid())
+    }
+}
+
+/// Outcome of a scheduled broadcast
+#[derive(Debug, Clone)]
+pub struct BroadcastReceipt {
+    /// Txid of the broadcast transaction
+    pub txid: Txid,
+    /// Endpoint that accepted it
+    pub endpoint: String,
+    /// How long the scheduler waited before broadcasting
+    pub delay: Duration,
+    /// Endpoints tried first that failed, with their errors
+    pub failures: Vec<(String, String)>,
+}
+
+/// Spaces out broadcasts with random delays and rotates their endpoints
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use anchor_wallet_lib::{BroadcastScheduler, EsploraEndpoint, NodeEndpoint};
+///
+/// let mut scheduler = BroadcastScheduler::new()
+///     .with_endpoint(NodeEndpoint::new(wallet.config())?)
+///     .with_endpoint(EsploraEndpoint::new("http://esplora.onion/api")?
+///         .with_proxy("127.0.0.1:9050".parse()?));
+///
+/// for anchor_tx in &posts {
+///     let signed = wallet.sign_transaction(anchor_tx)?;
+///     let receipt = scheduler.broadcast(&signed)?;
+///     println!("{} via {} after {:?}", receipt.txid, receipt.endpoint, receipt.delay);
+/// }
+/// ```
+pub struct BroadcastScheduler {
+    endpoints: Vec<Box<dyn BroadcastEndpoint>>,
+    min_delay: Duration,
+    max_delay: Duration,
+    /// Endpoint for the next broadcast
+    next_endpoint: usize,
+    /// When the last broadcast went out
+    last_broadcast: Option<Instant>,
+}
+
+impl Default for BroadcastScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BroadcastScheduler {
+    /// A scheduler without endpoints, waiting between [`DEFAULT_MIN_DELAY`]
+    /// and [`DEFAULT_MAX_DELAY`]
+    pub fn new() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            min_delay: DEFAULT_MIN_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            next_endpoint: 0,
+            last_broadcast: None,
+        }
+    }
+
+    /// Add an endpoint to the rotation
+    ///
+    /// The rotation starts at a random endpoint, so the first broadcast of
+    /// a session doesn't always take the same route.
+    pub fn with_endpoint(mut self, endpoint: impl BroadcastEndpoint + 'static) -> Self {
+        self.endpoints.push(Box::new(endpoint));
+        self.next_endpoint = rand::thread_rng().gen_range(0..self.endpoints.len());
+        self
+    }
+
+    /// Wait between `min` and `max` (inclusive) between broadcasts
+    pub fn with_delay(mut self, min: Duration, max: Duration) -> Self {
+        self.min_delay = min.min(max);
+        self.max_delay = max.max(min);
+        self
+    }
+
+    /// Number of endpoints in the rotation
+    pub fn endpoint_count(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    /// Broadcast `tx` once its delay has passed
+    ///
+    /// The first broadcast goes out immediately; each later one waits a
+    /// random delay counted from the previous one. If the endpoint whose
+    /// turn it is fails, the others are tried in rotation order.
+    pub fn broadcast(&mut self, tx: &Transaction) -> Result<BroadcastReceipt> {
+        if self.endpoints.is_empty() {
+            return Err(WalletError::Config(
+                "Broadcast scheduler has no endpoints".to_string(),
+            ));
+        }
+
+        let delay = self.wait();
+        let mut failures = Vec::new();
+        for offset in 0..self.endpoints.len() {
+            let index = (self.next_endpoint + offset) % self.endpoints.len();
+            let endpoint = &self.endpoints[index];
+            match endpoint.broadcast(tx) {
+                Ok(txid) => {
+                    self.next_endpoint = (index + 1) % self.endpoints.len();
+                    self.last_broadcast = Some(Instant::now());
+                    return Ok(BroadcastReceipt {
+                        txid,
+                        endpoint: endpoint.name(),
+                        delay,
+                        failures,
+                    });
+                }
+                Err(e) => failures.push((endpoint.name(), e.to_string())),
+            }
+        }
+
+        Err(WalletError::Broadcast(
+            failures
+                .iter()
+                .map(|(name, error)| format!("{}: {}", name, error))
+                .collect::<Vec<_>>()
+                .join("; "),
+        ))
+    }
+
+    /// Broadcast transactions in order, each after its own delay
+    ///
+    /// Order is kept so children follow their parents; a failure doesn't
+    /// stop the rest.
+    pub fn broadcast_all(&mut self, txs: &[Transaction]) -> Vec<Result<BroadcastReceipt>> {
+        txs.iter().map(|tx| self.broadcast(tx)).collect()
+    }
+
+    /// Sleep until a random delay after the last broadcast has passed
+    fn wait(&self) -> Duration {
+        let Some(last) = self.last_broadcast else {
+            return Duration::ZERO;
+        };
+        let gap = random_delay(self.min_delay, self.max_delay);
+        let remaining = gap.saturating_sub(last.elapsed());
+        if !remaining.is_zero() {
+            std::thread::sleep(remaining);
+        }
+        remaining
+    }
+}
+
+/// Uniformly random duration between `min` and `max`, inclusive
+fn random_delay(min: Duration, max: Duration) -> Duration {
+    if max <= min {
+        return min;
+    }
+    let millis = rand::thread_rng().gen_range(min.as_millis()..=max.as_millis());
+    Duration::from_millis(millis as u64)
+}
+
+fn broadcast_error(url: &str, error: impl std::fmt::Display) -> WalletError {
+    WalletError::Broadcast(format!("{}: {}", url, error))
+}
+
+/// Open a connection to `host:port`, through a SOCKS5 proxy if given
+pub(crate) fn http_connect(
+    host: &str,
+    port: u16,
+    proxy: Option<SocketAddr>,
+    timeout: Duration,
+) -> std::io::Result<TcpStream> {
+    let stream = match proxy {
+        Some(proxy) => {
+            let mut stream = TcpStream::connect_timeout(&proxy, timeout)?;
+            stream.set_read_timeout(Some(timeout))?;
+            socks5_connect(&mut stream, host, port)?;
+            stream
+        }
+        None => {
+            let addr = (host, port)
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| std::io::Error::other("host did not resolve"))?;
+            TcpStream::connect_timeout(&addr, timeout)?
+        }
+    };
+    stream.set_read_timeout(Some(timeout))?;
+    Ok(stream)
+}
+
+/// Host, port and path of an `http://` URL
+pub(crate) fn parse_http_url(url: &str) -> Result<(String, u16, String)> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        WalletError::Config(format!(
+            "URL must be plain http:// (local or onion): {}",
+            url
+        ))
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], rest[index..].trim_end_matches('/')),
+        None => (rest, ""),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse()
+                .map_err(|_| WalletError::Config(format!("Invalid port in URL: {}", url)))?,
+        ),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(WalletError::Config(format!("Missing host in URL: {}", url)));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// Status code and body of an HTTP response
+pub(crate) fn parse_http_response(response: &[u8]) -> Option<(u16, String)> {
+    let text = String::from_utf8_lossy(response);
+    let (head, body) = text.split_once("\r\n\r\n")?;
+    let status = head.split_whitespace().nth(1)?.parse().ok()?;
+    Some((status, body.to_string()))
+}
+
+/// Open a SOCKS5 tunnel to `host:port`, letting the proxy resolve the name
+///
+/// Offers no authentication and username/password; the latter is answered
+/// with random credentials so Tor isolates each connection on its own
+/// circuit.
+fn socks5_connect(stream: &mut TcpStream, host: &str, port: u16) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+
+    stream.write_all(&[0x05, 0x02, 0x00, 0x02])?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply)?;
+    match reply {
+        [0x05, 0x00] => {}
+        [0x05, 0x02] => {
+            let mut rng = rand::thread_rng();
+            let user = format!("{:016x}", rng.gen::<u64>());
+            let pass = format!("{:016x}", rng.gen::<u64>());
+            let mut auth = vec![0x01, user.len() as u8];
+            auth.extend_from_slice(user.as_bytes());
+            auth.push(pass.len() as u8);
+            auth.extend_from_slice(pass.as_bytes());
+            stream.write_all(&auth)?;
+            stream.read_exact(&mut reply)?;
+            if reply[1] != 0x00 {
+                return Err(Error::other("SOCKS5 authentication failed"));
+            }
+        }
+        _ => {
+            return Err(Error::other(
+                "SOCKS5 proxy refused every authentication method",
+            ))
+        }
+    }
+
+    if host.len() > 255 {
+        return Err(Error::new(ErrorKind::InvalidInput, "host name too long"));
+    }
+    let mut request = vec![0x05, 0x01, 0x00, 0x03, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head)?;
+    if head[1] != 0x00 {
+        return Err(Error::other(format!(
+            "SOCKS5 connect failed with code {}",
+            head[1]
+        )));
+    }
+    // Skip the bound address
+    let address_len = match head[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len)?;
+            len[0] as usize
+        }
+        _ => return Err(Error::other("invalid SOCKS5 reply")),
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{absolute::LockTime, transaction::Version};
+    use std::sync::{Arc, Mutex};
+
+    struct MockEndpoint {
+        name: &'static str,
+        fail: bool,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl BroadcastEndpoint for MockEndpoint {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+            self.calls.lock().unwrap().push(self.name);
+            if self.fail {
+                return Err(WalletError::Broadcast("rejected".to_string()));
+            }
+            Ok(tx.compute_txid())
+        }
+    }
+
+    fn tx() -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    fn mock_scheduler(fail: &[bool], calls: &Arc<Mutex<Vec<&'static str>>>) -> BroadcastScheduler {
+        let names = ["a", "b", "c"];
+        let mut scheduler = BroadcastScheduler::new().with_delay(Duration::ZERO, Duration::ZERO);
+        for (name, &fail) in names.iter().zip(fail) {
+            scheduler = scheduler.with_endpoint(MockEndpoint {
+                name,
+                fail,
+                calls: calls.clone(),
+            });
+        }
+        scheduler
+    }
+
+    #[test]
+    fn test_rotates_endpoints() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut scheduler = mock_scheduler(&[false, false, false], &calls);
+        let receipts = scheduler.broadcast_all(&[tx(), tx(), tx(), tx()]);
+        assert!(receipts.iter().all(|r| r.is_ok()));
+
+        // Each endpoint in turn, from a random start
+        let calls = calls.lock().unwrap();
+        let start = ["a", "b", "c"].iter().position(|n| *n == calls[0]).unwrap();
+        let expected: Vec<&str> = (0..4).map(|i| ["a", "b", "c"][(start + i) % 3]).collect();
+        assert_eq!(*calls, expected);
+    }
+
+    #[test]
+    fn test_falls_back_on_failure() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut scheduler = mock_scheduler(&[true, false], &calls);
+        for _ in 0..3 {
+            let receipt = scheduler.broadcast(&tx()).unwrap();
+            assert_eq!(receipt.endpoint, "b");
+            assert!(receipt.failures.len() <= 1);
+        }
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut scheduler = mock_scheduler(&[true, true], &calls);
+        assert!(matches!(
+            scheduler.broadcast(&tx()),
+            Err(WalletError::Broadcast(_))
+        ));
+        assert!(BroadcastScheduler::new().broadcast(&tx()).is_err());
+    }
+
+    #[test]
+    fn test_spaces_broadcasts() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let delay = Duration::from_millis(30);
+        let mut scheduler = mock_scheduler(&[false], &calls).with_delay(delay, delay);
+
+        let first = scheduler.broadcast(&tx()).unwrap();
+        assert_eq!(first.delay, Duration::ZERO);
+        let started = Instant::now();
+        scheduler.broadcast(&tx()).unwrap();
+        assert!(started.elapsed() >= delay - Duration::from_millis(1));
+
+        for _ in 0..100 {
+            let delay = random_delay(Duration::from_secs(1), Duration::from_secs(2));
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        }
+    }
+
+    #[test]
+    fn test_esplora_post() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in ["200 OK", "400 Bad Request"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = vec![0u8; 4096];
+                let len = stream.read(&mut request).unwrap();
+                requests.push(String::from_utf8_lossy(&request[..len]).to_string());
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 8\r\n\r\nbad-txns", status);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+
+        let endpoint = EsploraEndpoint::new(&url).unwrap();
+        assert_eq!(endpoint.broadcast(&tx()).unwrap(), tx().compute_txid());
+        let error = endpoint.broadcast(&tx()).unwrap_err().to_string();
+        assert!(error.contains("HTTP 400: bad-txns"));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /api/tx HTTP/1.1\r\n"));
+        assert!(requests[0].ends_with(&serialize_hex(&tx())));
+    }
+
+    #[test]
+    fn test_parse_http() {
+        assert_eq!(
+            parse_http_url("http://127.0.0.1:3002/api/").unwrap(),
+            ("127.0.0.1".to_string(), 3002, "/api".to_string())
+        );
+        assert_eq!(
+            parse_http_url("http://explorer.onion").unwrap(),
+            ("explorer.onion".to_string(), 80, String::new())
+        );
+        assert!(parse_http_url("https://blockstream.info/api").is_err());
+
+        assert_eq!(
+            parse_http_response(b"HTTP/1.1 400 Bad Request\r\nA: b\r\n\r\nbad-txns"),
+            Some((400, "bad-txns".to_string()))
+        );
+    }
+}
@@ -48,6 +48,22 @@ pub enum WalletError {
     #[error("Serialization error: {0}")]
     Serialization(String),
 
+    /// P2P peer error (light mode)
+    #[error("Peer error: {0}")]
+    Peer(String),
+
+    /// ANCHOR filter index error (filter rescans)
+    #[error("Filter index error: {0}")]
+    FilterIndex(String),
+
+    /// Broadcast endpoint error
+    #[error("Broadcast error: {0}")]
+    Broadcast(String),
+
+    /// QR encoding error (UR / BBQr)
+    #[error("QR error: {0}")]
+    Qr(String),
+
     /// Hex decoding error
     #[error("Hex decoding error: {0}")]
     HexDecode(#[from] hex::FromHexError),
@@ -33,12 +33,39 @@
 //!
 //! - `async` - Enable async/await support with Tokio
 //!
+//! ## Light Mode
+//!
+//! [`LightClient`] finds the wallet's ANCHOR transactions through BIP-157/158
+//! compact block filters served by a P2P peer, without Bitcoin Core RPC.
+//!
+//! ## Filter Rescans
+//!
+//! [`AnchorFilterClient`] finds a restored wallet's ANCHOR transactions
+//! through the per-block ANCHOR activity filters an explorer API serves,
+//! fetching only the matching blocks from the node.
+//!
+//! ## Broadcast Scheduling
+//!
+//! [`BroadcastScheduler`] spaces out broadcasts with random delays and
+//! rotates them across node, Esplora and Tor endpoints, so posts sent
+//! together can't be linked by timing or origin.
+//!
+//! ## Airgapped Signing
+//!
+//! The UR (BC-UR v2 `crypto-psbt`) and BBQr helpers split a PSBT into
+//! animated QR frames for an offline signer and reassemble the signed PSBT
+//! from the frames it shows back.
+//!
 //! ## Re-exports
 //!
 //! This crate re-exports `anchor-core` types for convenience.
 
+mod broadcast;
 mod config;
 mod error;
+mod light;
+mod qr;
+mod rescan;
 mod transaction;
 mod types;
 mod wallet;
@@ -50,14 +77,31 @@ pub use anchor_core::{
 
 // Re-export carrier types
 pub use anchor_core::carrier::{
-    AnnexCarrier, Carrier, CarrierError, CarrierInfo, CarrierInput, CarrierOutput,
-    CarrierPreferences, CarrierResult, CarrierSelector, CarrierStatus, CarrierType,
-    InscriptionCarrier, OpReturnCarrier, StampsCarrier, WitnessCarrier,
+    verify_carrier_roundtrip, AnnexCarrier, Carrier, CarrierError, CarrierInfo, CarrierInput,
+    CarrierOutput, CarrierPreferences, CarrierResult, CarrierSelector, CarrierStatus, CarrierType,
+    InscriptionCarrier, MessageLocation, OpReturnCarrier, StampsCarrier, UtxoCost,
+    VerifiedAnchorMessage, WitnessCarrier,
 };
 
+// Re-export the specs built by `create_identity_rotation` and `repost`
+pub use anchor_specs::identity::IdentityRotationSpec;
+pub use anchor_specs::mirror::MirrorSpec;
+
+pub use broadcast::{
+    BroadcastEndpoint, BroadcastReceipt, BroadcastScheduler, EsploraEndpoint, NodeEndpoint,
+    DEFAULT_MAX_DELAY, DEFAULT_MIN_DELAY,
+};
 pub use config::WalletConfig;
 pub use error::{Result, WalletError};
-pub use transaction::{AnchorTransaction, CarrierData, TransactionBuilder, MAX_OP_RETURN_SIZE};
+pub use light::{LightClient, LightConfig, LightMessage, LightScan};
+pub use qr::{
+    encode_bbqr, encode_psbt_bbqr, BbqrDecoder, BbqrFileType, UrDecoder, UrEncoder, PSBT_UR_TYPE,
+};
+pub use rescan::{AnchorFilterClient, AnchorFilterScan};
+pub use transaction::{
+    AnchorTransaction, CarrierData, ChangeDecision, TransactionBuilder, DUST_LIMIT,
+    MAX_OP_RETURN_SIZE,
+};
 pub use types::{Balance, Utxo};
 pub use wallet::AnchorWallet;
 
@@ -0,0 +1,544 @@
+//! Light mode using BIP-157/158 compact block filters
+//!
+//! [`LightClient`] talks to a single filter-serving peer over the Bitcoin P2P
+//! protocol. It downloads headers and compact block filters, fetches only the
+//! blocks whose filter matches the wallet's scripts, and extracts the ANCHOR
+//! messages from the wallet's transactions. No Bitcoin Core RPC or Esplora
+//! server is involved.
+//!
+//! Headers are checked for linkage and proof of work and downloaded blocks
+//! against their merkle root, but filter contents are taken from the peer as
+//! served, so a dishonest peer can hide transactions from the wallet.
+//!
+//! The peer must advertise `NODE_COMPACT_FILTERS` (Bitcoin Core with
+//! `blockfilterindex=1` and `peerblockfilters=1`).
+
+use std::collections::HashSet;
+use std::io::{BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anchor_core::carrier::{verify_carrier_roundtrip, VerifiedAnchorMessage};
+use anchor_core::txid_to_prefix;
+use bitcoin::bip158::BlockFilter;
+use bitcoin::block::Header;
+use bitcoin::blockdata::constants::genesis_block;
+use bitcoin::consensus::{encode, Decodable};
+use bitcoin::hashes::Hash;
+use bitcoin::p2p::address::Address;
+use bitcoin::p2p::message::{NetworkMessage, RawNetworkMessage};
+use bitcoin::p2p::message_blockdata::{GetHeadersMessage, Inventory};
+use bitcoin::p2p::message_filter::GetCFilters;
+use bitcoin::p2p::message_network::VersionMessage;
+use bitcoin::p2p::ServiceFlags;
+use bitcoin::{Block, BlockHash, Network, OutPoint, ScriptBuf, Transaction, Txid};
+
+use crate::error::{Result, WalletError};
+
+/// Basic filter type from BIP-158
+const BASIC_FILTER: u8 = 0;
+
+/// Most filters a peer returns for one `getcfilters` request
+const MAX_FILTERS_PER_REQUEST: u32 = 1000;
+
+/// Most headers a peer returns for one `getheaders` request
+const MAX_HEADERS_PER_REQUEST: usize = 2000;
+
+/// Protocol version that introduced compact filter messages
+const FILTERS_PROTOCOL_VERSION: u32 = 70016;
+
+/// Configuration for [`LightClient`]
+#[derive(Debug, Clone)]
+pub struct LightConfig {
+    /// Filter-serving peer to connect to
+    pub peer: SocketAddr,
+
+    /// Bitcoin network
+    pub network: Network,
+
+    /// Height and hash of the block to scan after (e.g. the wallet's birthday)
+    pub checkpoint: (u32, BlockHash),
+
+    /// Scripts owned by the wallet
+    pub scripts: Vec<ScriptBuf>,
+
+    /// Timeout for each message from the peer
+    pub timeout: Duration,
+}
+
+impl LightConfig {
+    /// Scan `network` from genesis through `peer`
+    pub fn new(peer: SocketAddr, network: Network) -> Self {
+        Self {
+            peer,
+            network,
+            checkpoint: (0, genesis_block(network).block_hash()),
+            scripts: Vec::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Only scan blocks after this one
+    pub fn with_checkpoint(mut self, height: u32, block_hash: BlockHash) -> Self {
+        self.checkpoint = (height, block_hash);
+        self
+    }
+
+    /// Add a script owned by the wallet
+    pub fn with_script(mut self, script: ScriptBuf) -> Self {
+        self.scripts.push(script);
+        self
+    }
+
+    /// Set the per-message timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// An ANCHOR message found in one of the wallet's transactions
+#[derive(Debug, Clone)]
+pub struct LightMessage {
+    /// Transaction carrying the message
+    pub txid: Txid,
+    /// Block containing the transaction
+    pub block_hash: BlockHash,
+    /// Height of that block
+    pub height: u32,
+    /// The decoded message
+    pub message: VerifiedAnchorMessage,
+    /// Each anchor resolved to a wallet transaction found earlier in the scan,
+    /// in anchor order; `None` when the parent is not one of ours
+    pub parents: Vec<Option<Txid>>,
+}
+
+/// Result of [`LightClient::scan`]
+#[derive(Debug, Clone)]
+pub struct LightScan {
+    /// Height of the last scanned block
+    pub tip_height: u32,
+    /// Hash of the last scanned block
+    pub tip_hash: BlockHash,
+    /// Messages in the wallet's transactions, in chain order
+    pub messages: Vec<LightMessage>,
+    /// Wallet outputs still unspent at the tip
+    pub unspent: Vec<OutPoint>,
+}
+
+/// Compact block filter client for a single peer
+pub struct LightClient {
+    config: LightConfig,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl LightClient {
+    /// Connect to the configured peer and complete the version handshake
+    pub fn connect(config: LightConfig) -> Result<Self> {
+        let stream = TcpStream::connect_timeout(&config.peer, config.timeout)
+            .map_err(|e| WalletError::Peer(e.to_string()))?;
+        stream
+            .set_read_timeout(Some(config.timeout))
+            .map_err(|e| WalletError::Peer(e.to_string()))?;
+        let writer = stream
+            .try_clone()
+            .map_err(|e| WalletError::Peer(e.to_string()))?;
+
+        let mut client = Self {
+            config,
+            reader: BufReader::new(stream),
+            writer,
+        };
+        client.handshake()?;
+        Ok(client)
+    }
+
+    /// Scan every block after the checkpoint for the wallet's transactions
+    pub fn scan(&mut self) -> Result<LightScan> {
+        let hashes = self.sync_headers()?;
+        let (checkpoint_height, checkpoint_hash) = self.config.checkpoint;
+
+        let scripts: HashSet<ScriptBuf> = self.config.scripts.iter().cloned().collect();
+        let mut owned = HashSet::new();
+        let mut found = Vec::new();
+
+        for (chunk_index, chunk) in hashes.chunks(MAX_FILTERS_PER_REQUEST as usize).enumerate() {
+            let start_height = checkpoint_height + 1 + chunk_index as u32 * MAX_FILTERS_PER_REQUEST;
+            let matched = self.matching_blocks(start_height, chunk)?;
+
+            for (offset, block_hash) in matched {
+                let block = self.get_block(block_hash)?;
+                let height = start_height + offset as u32;
+                for tx in relevant_transactions(&block, &scripts, &mut owned) {
+                    found.push((tx, block_hash, height));
+                }
+            }
+        }
+
+        let (tip_height, tip_hash) = match hashes.last() {
+            Some(hash) => (checkpoint_height + hashes.len() as u32, *hash),
+            None => (checkpoint_height, checkpoint_hash),
+        };
+
+        Ok(LightScan {
+            tip_height,
+            tip_hash,
+            messages: collect_messages(&found),
+            unspent: owned.into_iter().collect(),
+        })
+    }
+
+    fn handshake(&mut self) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let local: SocketAddr = ([0, 0, 0, 0], 0).into();
+        let mut version = VersionMessage::new(
+            ServiceFlags::NONE,
+            timestamp,
+            Address::new(&self.config.peer, ServiceFlags::NONE),
+            Address::new(&local, ServiceFlags::NONE),
+            timestamp as u64,
+            format!("/anchor-wallet-lib:{}/", crate::VERSION),
+            0,
+        );
+        version.version = FILTERS_PROTOCOL_VERSION;
+        self.send(NetworkMessage::Version(version))?;
+
+        let services = self.wait_for(|message| match message {
+            NetworkMessage::Version(version) => Some(version.services),
+            _ => None,
+        })?;
+        if !services.has(ServiceFlags::COMPACT_FILTERS) {
+            return Err(WalletError::Peer(
+                "peer does not serve compact block filters".to_string(),
+            ));
+        }
+
+        self.send(NetworkMessage::Verack)?;
+        self.wait_for(|message| matches!(message, NetworkMessage::Verack).then_some(()))
+    }
+
+    /// Download and check headers after the checkpoint, returning their hashes
+    fn sync_headers(&mut self) -> Result<Vec<BlockHash>> {
+        let mut hashes = Vec::new();
+        let mut tip = self.config.checkpoint.1;
+
+        loop {
+            self.send(NetworkMessage::GetHeaders(GetHeadersMessage {
+                version: FILTERS_PROTOCOL_VERSION,
+                locator_hashes: vec![tip],
+                stop_hash: BlockHash::all_zeros(),
+            }))?;
+            let headers: Vec<Header> = self.wait_for(|message| match message {
+                NetworkMessage::Headers(headers) => Some(headers),
+                _ => None,
+            })?;
+
+            for header in &headers {
+                if header.prev_blockhash != tip {
+                    return Err(WalletError::Peer(format!(
+                        "header {} does not extend {}",
+                        header.block_hash(),
+                        tip
+                    )));
+                }
+                if header.validate_pow(header.target()).is_err() {
+                    return Err(WalletError::Peer(format!(
+                        "header {} has insufficient proof of work",
+                        header.block_hash()
+                    )));
+                }
+                tip = header.block_hash();
+                hashes.push(tip);
+            }
+
+            if headers.len() < MAX_HEADERS_PER_REQUEST {
+                return Ok(hashes);
+            }
+        }
+    }
+
+    /// Fetch filters for a run of blocks and return those matching our scripts
+    ///
+    /// The returned offsets are relative to `start_height`.
+    fn matching_blocks(
+        &mut self,
+        start_height: u32,
+        hashes: &[BlockHash],
+    ) -> Result<Vec<(usize, BlockHash)>> {
+        let Some(stop_hash) = hashes.last() else {
+            return Ok(Vec::new());
+        };
+        self.send(NetworkMessage::GetCFilters(GetCFilters {
+            filter_type: BASIC_FILTER,
+            start_height,
+            stop_hash: *stop_hash,
+        }))?;
+
+        let mut matched = Vec::new();
+        for (offset, expected) in hashes.iter().enumerate() {
+            let filter = self.wait_for(|message| match message {
+                NetworkMessage::CFilter(filter) => Some(filter),
+                _ => None,
+            })?;
+            if filter.block_hash != *expected {
+                return Err(WalletError::Peer(format!(
+                    "expected filter for {}, got {}",
+                    expected, filter.block_hash
+                )));
+            }
+
+            let matches = BlockFilter::new(&filter.filter)
+                .match_any(
+                    &filter.block_hash,
+                    self.config.scripts.iter().map(|s| s.as_bytes()),
+                )
+                .map_err(|e| WalletError::Peer(e.to_string()))?;
+            if matches {
+                matched.push((offset, filter.block_hash));
+            }
+        }
+
+        Ok(matched)
+    }
+
+    fn get_block(&mut self, block_hash: BlockHash) -> Result<Block> {
+        self.send(NetworkMessage::GetData(vec![Inventory::WitnessBlock(
+            block_hash,
+        )]))?;
+        let block = self.wait_for(|message| match message {
+            NetworkMessage::Block(block) if block.block_hash() == block_hash => Some(block),
+            _ => None,
+        })?;
+
+        if !block.check_merkle_root() {
+            return Err(WalletError::Peer(format!(
+                "block {} does not match its merkle root",
+                block_hash
+            )));
+        }
+        Ok(block)
+    }
+
+    fn send(&mut self, payload: NetworkMessage) -> Result<()> {
+        let message = RawNetworkMessage::new(self.config.network.magic(), payload);
+        self.writer
+            .write_all(&encode::serialize(&message))
+            .map_err(|e| WalletError::Peer(e.to_string()))
+    }
+
+    /// Read messages until `accept` returns a value, answering pings meanwhile
+    fn wait_for<T>(&mut self, mut accept: impl FnMut(NetworkMessage) -> Option<T>) -> Result<T> {
+        loop {
+            let message = RawNetworkMessage::consensus_decode(&mut self.reader)
+                .map_err(|e| WalletError::Peer(e.to_string()))?;
+            match message.payload() {
+                NetworkMessage::Ping(nonce) => self.send(NetworkMessage::Pong(*nonce))?,
+                _ => {
+                    if let Some(value) = accept(message.payload().clone()) {
+                        return Ok(value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Transactions in `block` that pay to `scripts` or spend an `owned` output
+///
+/// `owned` is updated as outputs are created and spent, so blocks must be
+/// passed in chain order.
+fn relevant_transactions(
+    block: &Block,
+    scripts: &HashSet<ScriptBuf>,
+    owned: &mut HashSet<OutPoint>,
+) -> Vec<Transaction> {
+    let mut relevant = Vec::new();
+
+    for tx in &block.txdata {
+        let txid = tx.compute_txid();
+        let mut is_ours = false;
+
+        for input in &tx.input {
+            if owned.remove(&input.previous_output) {
+                is_ours = true;
+            }
+        }
+        for (vout, output) in tx.output.iter().enumerate() {
+            if scripts.contains(&output.script_pubkey) {
+                owned.insert(OutPoint::new(txid, vout as u32));
+                is_ours = true;
+            }
+        }
+
+        if is_ours {
+            relevant.push(tx.clone());
+        }
+    }
+
+    relevant
+}
+
+/// Decode the ANCHOR messages in the wallet's transactions and link each
+/// anchor to an earlier wallet transaction with the same txid prefix
+pub(crate) fn collect_messages(found: &[(Transaction, BlockHash, u32)]) -> Vec<LightMessage> {
+    let mut seen: Vec<Txid> = Vec::new();
+    let mut messages = Vec::new();
+
+    for (tx, block_hash, height) in found {
+        let txid = tx.compute_txid();
+        for message in verify_carrier_roundtrip(tx) {
+            let parents = message
+                .message
+                .anchors
+                .iter()
+                .map(|anchor| {
+                    seen.iter()
+                        .find(|parent| txid_to_prefix(parent) == anchor.txid_prefix)
+                        .copied()
+                })
+                .collect();
+
+            messages.push(LightMessage {
+                txid,
+                block_hash: *block_hash,
+                height: *height,
+                message,
+                parents,
+            });
+        }
+        seen.push(txid);
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_core::{create_anchor_script, Anchor, AnchorKind, ParsedAnchorMessage};
+    use bitcoin::block::Version as BlockVersion;
+    use bitcoin::{
+        absolute::LockTime, transaction::Version, Amount, CompactTarget, TxIn, TxMerkleNode, TxOut,
+    };
+
+    fn script(byte: u8) -> ScriptBuf {
+        ScriptBuf::from_bytes(vec![0x51, byte])
+    }
+
+    fn tx(inputs: Vec<OutPoint>, outputs: Vec<ScriptBuf>) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .into_iter()
+                .map(|previous_output| TxIn {
+                    previous_output,
+                    ..Default::default()
+                })
+                .collect(),
+            output: outputs
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: Amount::from_sat(1_000),
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    fn block(txdata: Vec<Transaction>) -> Block {
+        let mut block = Block {
+            header: Header {
+                version: BlockVersion::TWO,
+                prev_blockhash: BlockHash::all_zeros(),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 0,
+                bits: CompactTarget::from_consensus(0x207fffff),
+                nonce: 0,
+            },
+            txdata,
+        };
+        block.header.merkle_root = block.compute_merkle_root().unwrap();
+        block
+    }
+
+    fn text(body: &str, anchors: Vec<Anchor>) -> ScriptBuf {
+        create_anchor_script(&ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: anchors.into(),
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    #[test]
+    fn test_filter_matches_wallet_script() {
+        let ours = script(1);
+        let block = block(vec![tx(vec![], vec![ours.clone()])]);
+        let filter = BlockFilter::new_script_filter(&block, |_| Ok(ScriptBuf::new())).unwrap();
+
+        let hash = block.block_hash();
+        assert!(filter
+            .match_any(&hash, std::iter::once(ours.as_bytes()))
+            .unwrap());
+        assert!(!filter
+            .match_any(&hash, std::iter::once(script(2).as_bytes()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_relevant_transactions_tracks_spends() {
+        let scripts: HashSet<ScriptBuf> = [script(1)].into_iter().collect();
+        let mut owned = HashSet::new();
+
+        let funding = tx(vec![], vec![script(1), script(2)]);
+        let funding_txid = funding.compute_txid();
+        let unrelated = tx(vec![], vec![script(3)]);
+        let first = block(vec![funding, unrelated]);
+        assert_eq!(relevant_transactions(&first, &scripts, &mut owned).len(), 1);
+        assert!(owned.contains(&OutPoint::new(funding_txid, 0)));
+
+        // Spending our output to someone else is still our transaction
+        let spend = tx(vec![OutPoint::new(funding_txid, 0)], vec![script(3)]);
+        let second = block(vec![spend]);
+        assert_eq!(
+            relevant_transactions(&second, &scripts, &mut owned).len(),
+            1
+        );
+        assert!(owned.is_empty());
+    }
+
+    #[test]
+    fn test_collect_messages_resolves_parents() {
+        let root = tx(vec![], vec![text("root", vec![]), script(1)]);
+        let root_txid = root.compute_txid();
+        let reply = tx(
+            vec![OutPoint::new(root_txid, 1)],
+            vec![
+                text(
+                    "reply",
+                    vec![
+                        Anchor::from_txid(&root_txid, 0),
+                        Anchor {
+                            txid_prefix: [0xff; 8],
+                            vout: 0,
+                        },
+                    ],
+                ),
+                script(1),
+            ],
+        );
+
+        let hash = BlockHash::all_zeros();
+        let messages = collect_messages(&[(root, hash, 1), (reply, hash, 2)]);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message.message.body, b"root");
+        assert!(messages[0].parents.is_empty());
+        assert_eq!(messages[1].height, 2);
+        assert_eq!(messages[1].parents, vec![Some(root_txid), None]);
+    }
+}
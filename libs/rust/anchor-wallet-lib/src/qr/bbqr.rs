@@ -0,0 +1,324 @@
+//! BBQr ("Better Bitcoin QR") animated codes
+//!
+//! Each frame starts with an 8 character header: `B$`, the encoding, the
+//! file type, then the part count and the zero-based part index as two
+//! base-36 digits each. Frames are Base32 encoded when produced here;
+//! hex and zlib-compressed frames from other tools (e.g. COLDCARD) are
+//! decoded as well.
+
+use std::io::Read;
+
+use bitcoin::consensus::encode;
+use bitcoin::psbt::Psbt;
+use bitcoin::Transaction;
+
+use crate::error::{Result, WalletError};
+
+/// Header length in characters
+const HEADER_LEN: usize = 8;
+
+/// Most parts a two-digit base-36 count allows
+const MAX_PARTS: usize = 1295;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// What a BBQr payload contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BbqrFileType {
+    Psbt,
+    Transaction,
+    Json,
+    Cbor,
+    Text,
+    Binary,
+}
+
+impl BbqrFileType {
+    fn code(&self) -> char {
+        match self {
+            BbqrFileType::Psbt => 'P',
+            BbqrFileType::Transaction => 'T',
+            BbqrFileType::Json => 'J',
+            BbqrFileType::Cbor => 'C',
+            BbqrFileType::Text => 'U',
+            BbqrFileType::Binary => 'B',
+        }
+    }
+
+    fn from_code(code: char) -> Result<Self> {
+        match code {
+            'P' => Ok(BbqrFileType::Psbt),
+            'T' => Ok(BbqrFileType::Transaction),
+            'J' => Ok(BbqrFileType::Json),
+            'C' => Ok(BbqrFileType::Cbor),
+            'U' => Ok(BbqrFileType::Text),
+            'B' => Ok(BbqrFileType::Binary),
+            _ => Err(WalletError::Qr(format!(
+                "unknown BBQr file type '{}'",
+                code
+            ))),
+        }
+    }
+}
+
+/// Split `data` into BBQr frames of at most `max_part_chars` characters
+pub fn encode_bbqr(
+    data: &[u8],
+    file_type: BbqrFileType,
+    max_part_chars: usize,
+) -> Result<Vec<String>> {
+    // Base32 frames must break on 8 character (5 byte) boundaries
+    let max_data_chars = max_part_chars.saturating_sub(HEADER_LEN) / 8 * 8;
+    if max_data_chars == 0 {
+        return Err(WalletError::Qr(format!(
+            "BBQr frames need at least {} characters",
+            HEADER_LEN + 8
+        )));
+    }
+
+    let encoded = base32_encode(data);
+    let count = encoded.len().div_ceil(max_data_chars).max(1);
+    if count > MAX_PARTS {
+        return Err(WalletError::Qr(format!(
+            "payload needs {} BBQr frames (max {})",
+            count, MAX_PARTS
+        )));
+    }
+    // Spread the data evenly rather than leaving a short last frame
+    let per_part = encoded.len().div_ceil(count).div_ceil(8) * 8;
+
+    Ok((0..count)
+        .map(|i| {
+            let start = (i * per_part).min(encoded.len());
+            let end = ((i + 1) * per_part).min(encoded.len());
+            format!(
+                "B$2{}{}{}{}",
+                file_type.code(),
+                base36(count),
+                base36(i),
+                &encoded[start..end]
+            )
+        })
+        .collect())
+}
+
+/// Encode a PSBT as BBQr frames
+pub fn encode_psbt_bbqr(psbt: &Psbt, max_part_chars: usize) -> Result<Vec<String>> {
+    encode_bbqr(&psbt.serialize(), BbqrFileType::Psbt, max_part_chars)
+}
+
+/// Collects scanned BBQr frames until every part has arrived
+#[derive(Debug, Clone, Default)]
+pub struct BbqrDecoder {
+    header: Option<(char, BbqrFileType)>,
+    parts: Vec<Option<String>>,
+}
+
+impl BbqrDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scanned frame; repeated frames are ignored
+    pub fn receive(&mut self, text: &str) -> Result<()> {
+        let text = text.trim();
+        if text.len() < HEADER_LEN || !text.starts_with("B$") || !text.is_ascii() {
+            return Err(WalletError::Qr("not a BBQr frame".to_string()));
+        }
+
+        let encoding = text.as_bytes()[2] as char;
+        if !matches!(encoding, 'H' | '2' | 'Z') {
+            return Err(WalletError::Qr(format!(
+                "unknown BBQr encoding '{}'",
+                encoding
+            )));
+        }
+        let file_type = BbqrFileType::from_code(text.as_bytes()[3] as char)?;
+        let count = parse_base36(&text[4..6])?;
+        let index = parse_base36(&text[6..8])?;
+        if count == 0 || index >= count {
+            return Err(WalletError::Qr("BBQr part index out of range".to_string()));
+        }
+
+        match self.header {
+            None => {
+                self.header = Some((encoding, file_type));
+                self.parts = vec![None; count];
+            }
+            Some(header) if header != (encoding, file_type) || self.parts.len() != count => {
+                return Err(WalletError::Qr(
+                    "frame belongs to a different BBQr payload".to_string(),
+                ))
+            }
+            Some(_) => {}
+        }
+
+        self.parts[index] = Some(text[HEADER_LEN..].to_string());
+        Ok(())
+    }
+
+    /// Whether every part has arrived
+    pub fn is_complete(&self) -> bool {
+        !self.parts.is_empty() && self.parts.iter().all(Option::is_some)
+    }
+
+    /// Fraction of parts received
+    pub fn progress(&self) -> f64 {
+        if self.parts.is_empty() {
+            return 0.0;
+        }
+        self.parts.iter().filter(|p| p.is_some()).count() as f64 / self.parts.len() as f64
+    }
+
+    /// File type announced by the frames
+    pub fn file_type(&self) -> Option<BbqrFileType> {
+        self.header.map(|(_, file_type)| file_type)
+    }
+
+    /// The decoded payload
+    pub fn data(&self) -> Result<Vec<u8>> {
+        let (encoding, _) = self
+            .header
+            .filter(|_| self.is_complete())
+            .ok_or_else(|| WalletError::Qr("BBQr payload is incomplete".to_string()))?;
+        let joined: String = self.parts.iter().flatten().map(String::as_str).collect();
+
+        match encoding {
+            'H' => Ok(hex::decode(joined)?),
+            '2' => base32_decode(&joined),
+            _ => {
+                // Raw deflate stream (zlib wbits -10)
+                let compressed = base32_decode(&joined)?;
+                let mut data = Vec::new();
+                flate2::read::DeflateDecoder::new(compressed.as_slice())
+                    .read_to_end(&mut data)
+                    .map_err(|e| WalletError::Qr(format!("BBQr decompression failed: {}", e)))?;
+                Ok(data)
+            }
+        }
+    }
+
+    /// The PSBT carried by a complete `P` payload
+    pub fn psbt(&self) -> Result<Psbt> {
+        self.expect_type(BbqrFileType::Psbt)?;
+        Psbt::deserialize(&self.data()?).map_err(|e| WalletError::Serialization(e.to_string()))
+    }
+
+    /// The transaction carried by a complete `T` payload
+    pub fn transaction(&self) -> Result<Transaction> {
+        self.expect_type(BbqrFileType::Transaction)?;
+        encode::deserialize(&self.data()?).map_err(|e| WalletError::Serialization(e.to_string()))
+    }
+
+    fn expect_type(&self, expected: BbqrFileType) -> Result<()> {
+        match self.file_type() {
+            Some(file_type) if file_type == expected => Ok(()),
+            other => Err(WalletError::Qr(format!(
+                "expected a {:?} payload, got {:?}",
+                expected, other
+            ))),
+        }
+    }
+}
+
+fn base36(n: usize) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let n = n.min(MAX_PARTS);
+    format!("{}{}", DIGITS[n / 36] as char, DIGITS[n % 36] as char)
+}
+
+fn parse_base36(text: &str) -> Result<usize> {
+    usize::from_str_radix(text, 36)
+        .map_err(|_| WalletError::Qr(format!("invalid BBQr number '{}'", text)))
+}
+
+/// RFC 4648 Base32 without padding
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    for chunk in data.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let bits = buf.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..chars {
+            let index = (bits >> (35 - i * 5)) & 0x1f;
+            out.push(BASE32_ALPHABET[index as usize] as char);
+        }
+    }
+    out
+}
+
+fn base32_decode(text: &str) -> Result<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut bits = 0u64;
+    let mut count = 0;
+    for c in text.bytes() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_uppercase())
+            .ok_or_else(|| WalletError::Qr(format!("invalid Base32 character '{}'", c as char)))?;
+        bits = (bits << 5) | value as u64;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_base32() {
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBOI").unwrap(), b"foobar");
+        assert_eq!(base32_decode("mzxw6ytboi======").unwrap(), b"foobar");
+        assert!(base32_decode("MZXW1").is_err());
+    }
+
+    #[test]
+    fn test_roundtrip_out_of_order() {
+        let data: Vec<u8> = (0..2000u32).map(|i| (i % 253) as u8).collect();
+        let frames = encode_bbqr(&data, BbqrFileType::Binary, 500).unwrap();
+        assert_eq!(frames.len(), 7);
+        assert!(frames.iter().all(|f| f.len() <= 500));
+        assert!(frames[0].starts_with("B$2B0700"));
+        assert!(frames[6].starts_with("B$2B0706"));
+
+        let mut decoder = BbqrDecoder::new();
+        for frame in frames.iter().rev() {
+            assert!(!decoder.is_complete());
+            decoder.receive(frame).unwrap();
+        }
+        decoder.receive(&frames[3]).unwrap();
+        assert_eq!(decoder.data().unwrap(), data);
+        assert!(decoder.psbt().is_err());
+    }
+
+    #[test]
+    fn test_decodes_hex_and_zlib() {
+        let mut decoder = BbqrDecoder::new();
+        decoder.receive("B$HU0100").unwrap();
+        assert!(decoder.receive("B$HU0200").is_err());
+
+        let mut decoder = BbqrDecoder::new();
+        decoder.receive("B$HU010048656C6C6F").unwrap();
+        assert_eq!(decoder.data().unwrap(), b"Hello");
+
+        let mut compressor =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        compressor.write_all(&[7u8; 300]).unwrap();
+        let compressed = compressor.finish().unwrap();
+        let mut decoder = BbqrDecoder::new();
+        decoder
+            .receive(&format!("B$ZB0100{}", base32_encode(&compressed)))
+            .unwrap();
+        assert_eq!(decoder.data().unwrap(), vec![7u8; 300]);
+    }
+}
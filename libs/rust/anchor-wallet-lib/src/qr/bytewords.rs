@@ -0,0 +1,97 @@
+//! Bytewords minimal encoding (BCR-2020-012)
+//!
+//! Each byte is written as the first and last letter of its word, and the
+//! CRC-32 of the data is appended so transcription errors are caught.
+
+use crate::error::{Result, WalletError};
+
+const WORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+/// Encode data and its checksum as minimal bytewords
+pub fn encode(data: &[u8]) -> String {
+    let checksum = crc32fast::hash(data).to_be_bytes();
+    let mut out = String::with_capacity((data.len() + 4) * 2);
+    for &byte in data.iter().chain(checksum.iter()) {
+        let word = WORDS[byte as usize].as_bytes();
+        out.push(word[0] as char);
+        out.push(word[3] as char);
+    }
+    out
+}
+
+/// Decode minimal bytewords, verifying the checksum
+pub fn decode(text: &str) -> Result<Vec<u8>> {
+    let text = text.to_ascii_lowercase();
+    if !text.len().is_multiple_of(2) || text.len() < 10 {
+        return Err(WalletError::Qr("invalid bytewords length".to_string()));
+    }
+
+    let mut bytes = Vec::with_capacity(text.len() / 2);
+    for pair in text.as_bytes().chunks(2) {
+        let byte = WORDS
+            .iter()
+            .position(|w| w.as_bytes()[0] == pair[0] && w.as_bytes()[3] == pair[1])
+            .ok_or_else(|| {
+                WalletError::Qr(format!(
+                    "invalid byteword '{}'",
+                    String::from_utf8_lossy(pair)
+                ))
+            })?;
+        bytes.push(byte as u8);
+    }
+
+    let (data, checksum) = bytes.split_at(bytes.len() - 4);
+    if crc32fast::hash(data).to_be_bytes() != checksum {
+        return Err(WalletError::Qr("bytewords checksum mismatch".to_string()));
+    }
+    Ok(data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_vector() {
+        assert_eq!(encode(&[0, 1, 2, 128, 255]), "aeadaolazmjendeoti");
+        assert_eq!(
+            decode("AEADAOLAZMJENDEOTI").unwrap(),
+            vec![0, 1, 2, 128, 255]
+        );
+    }
+
+    #[test]
+    fn test_checksum_and_words() {
+        assert!(decode("aeadaolazmjendeotf").is_err());
+        assert!(decode("aeadaolazmjendeoxx").is_err());
+
+        let minimal: std::collections::HashSet<(u8, u8)> = WORDS
+            .iter()
+            .map(|w| (w.as_bytes()[0], w.as_bytes()[3]))
+            .collect();
+        assert_eq!(minimal.len(), 256);
+    }
+}
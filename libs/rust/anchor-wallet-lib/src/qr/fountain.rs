@@ -0,0 +1,382 @@
+//! Fountain codes for multi-part URs (BCR-2020-005)
+//!
+//! A message is split into `seq_len` equal fragments. Parts `1..=seq_len`
+//! carry one fragment each; later parts XOR a pseudo-random subset chosen
+//! from the part's sequence number and the message checksum, so a scanner
+//! that misses frames can still finish from whatever parts it catches.
+
+use std::collections::{BTreeSet, HashMap};
+
+use bitcoin::hashes::{sha256, Hash};
+
+use crate::error::{Result, WalletError};
+
+/// Smallest fragment the encoder will choose
+const MIN_FRAGMENT_LEN: usize = 10;
+
+/// One fountain-coded part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    pub seq_num: u32,
+    pub seq_len: usize,
+    pub message_len: usize,
+    pub checksum: u32,
+    pub data: Vec<u8>,
+}
+
+/// Splits a message into an endless stream of parts
+#[derive(Debug, Clone)]
+pub struct FountainEncoder {
+    fragments: Vec<Vec<u8>>,
+    message_len: usize,
+    checksum: u32,
+    seq_num: u32,
+}
+
+impl FountainEncoder {
+    /// Fragment `message` into pieces of at most `max_fragment_len` bytes
+    pub fn new(message: &[u8], max_fragment_len: usize) -> Self {
+        let fragment_len = fragment_length(message.len(), max_fragment_len);
+        let mut padded = message.to_vec();
+        let seq_len = message.len().div_ceil(fragment_len).max(1);
+        padded.resize(seq_len * fragment_len, 0);
+
+        Self {
+            fragments: padded.chunks(fragment_len).map(<[u8]>::to_vec).collect(),
+            message_len: message.len(),
+            checksum: crc32fast::hash(message),
+            seq_num: 0,
+        }
+    }
+
+    /// Number of pure fragments
+    pub fn seq_len(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Produce the next part; after `seq_len` parts they are mixed
+    pub fn next_part(&mut self) -> Part {
+        self.seq_num = self.seq_num.wrapping_add(1);
+        let indexes = choose_fragments(self.seq_num, self.seq_len(), self.checksum);
+
+        let mut data = vec![0u8; self.fragments[0].len()];
+        for index in indexes {
+            xor_into(&mut data, &self.fragments[index]);
+        }
+
+        Part {
+            seq_num: self.seq_num,
+            seq_len: self.seq_len(),
+            message_len: self.message_len,
+            checksum: self.checksum,
+            data,
+        }
+    }
+}
+
+/// Reassembles a message from parts received in any order
+#[derive(Debug, Clone, Default)]
+pub struct FountainDecoder {
+    expected: Option<(usize, usize, u32, usize)>,
+    simple: HashMap<usize, Vec<u8>>,
+    mixed: Vec<(BTreeSet<usize>, Vec<u8>)>,
+    message: Option<Vec<u8>>,
+}
+
+impl FountainDecoder {
+    /// Add a part, returning an error if it belongs to another message
+    pub fn receive(&mut self, part: &Part) -> Result<()> {
+        if self.message.is_some() {
+            return Ok(());
+        }
+
+        let shape = (
+            part.seq_len,
+            part.message_len,
+            part.checksum,
+            part.data.len(),
+        );
+        if part.seq_len == 0 || part.data.is_empty() {
+            return Err(WalletError::Qr("empty fountain part".to_string()));
+        }
+        match self.expected {
+            None => self.expected = Some(shape),
+            Some(expected) if expected != shape => {
+                return Err(WalletError::Qr(
+                    "part belongs to a different message".to_string(),
+                ))
+            }
+            Some(_) => {}
+        }
+
+        let indexes: BTreeSet<usize> = choose_fragments(part.seq_num, part.seq_len, part.checksum)
+            .into_iter()
+            .collect();
+        self.add(indexes, part.data.clone());
+        self.finish()
+    }
+
+    /// Reduce a part by known fragments and queue or store it
+    fn add(&mut self, indexes: BTreeSet<usize>, data: Vec<u8>) {
+        let mut queue = vec![(indexes, data)];
+
+        while let Some((mut indexes, mut data)) = queue.pop() {
+            for (index, fragment) in &self.simple {
+                if indexes.len() > 1 && indexes.remove(index) {
+                    xor_into(&mut data, fragment);
+                }
+            }
+
+            match indexes.len() {
+                0 => {}
+                1 => {
+                    let index = *indexes.iter().next().unwrap();
+                    if self.simple.contains_key(&index) {
+                        continue;
+                    }
+                    self.simple.insert(index, data.clone());
+
+                    // A new fragment may unlock queued mixed parts
+                    let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.mixed)
+                        .into_iter()
+                        .partition(|(mixed, _)| mixed.contains(&index));
+                    self.mixed = waiting;
+                    queue.extend(ready);
+                }
+                _ => {
+                    if !self.mixed.iter().any(|(known, _)| *known == indexes) {
+                        self.mixed.push((indexes, data));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Join the fragments once all are known and check the checksum
+    fn finish(&mut self) -> Result<()> {
+        let Some((seq_len, message_len, checksum, _)) = self.expected else {
+            return Ok(());
+        };
+        if self.simple.len() < seq_len {
+            return Ok(());
+        }
+
+        let mut message: Vec<u8> = (0..seq_len)
+            .flat_map(|i| self.simple[&i].iter().copied())
+            .collect();
+        message.truncate(message_len);
+        if crc32fast::hash(&message) != checksum {
+            *self = Self::default();
+            return Err(WalletError::Qr(
+                "reassembled message failed its checksum".to_string(),
+            ));
+        }
+        self.message = Some(message);
+        Ok(())
+    }
+
+    /// Fraction of fragments recovered so far
+    pub fn progress(&self) -> f64 {
+        match self.expected {
+            _ if self.message.is_some() => 1.0,
+            Some((seq_len, ..)) => self.simple.len() as f64 / seq_len as f64,
+            None => 0.0,
+        }
+    }
+
+    /// The reassembled message, once complete
+    pub fn message(&self) -> Option<&[u8]> {
+        self.message.as_deref()
+    }
+}
+
+/// Fragment length that splits `message_len` into near-equal pieces
+fn fragment_length(message_len: usize, max_fragment_len: usize) -> usize {
+    let max_fragment_len = max_fragment_len.max(MIN_FRAGMENT_LEN);
+    let max_count = (message_len / MIN_FRAGMENT_LEN).max(1);
+    let mut fragment_len = message_len.max(1);
+    for count in 1..=max_count {
+        fragment_len = message_len.div_ceil(count).max(1);
+        if fragment_len <= max_fragment_len {
+            break;
+        }
+    }
+    fragment_len
+}
+
+/// Fragment indexes XORed into part `seq_num`
+fn choose_fragments(seq_num: u32, seq_len: usize, checksum: u32) -> Vec<usize> {
+    if seq_num as usize <= seq_len {
+        return vec![seq_num as usize - 1];
+    }
+
+    let mut seed = [0u8; 8];
+    seed[..4].copy_from_slice(&seq_num.to_be_bytes());
+    seed[4..].copy_from_slice(&checksum.to_be_bytes());
+    let mut rng = Xoshiro256::from_seed(&seed);
+
+    let probabilities: Vec<f64> = (1..=seq_len).map(|i| 1.0 / i as f64).collect();
+    let degree = AliasSampler::new(&probabilities).next(&mut rng) + 1;
+
+    let mut remaining: Vec<usize> = (0..seq_len).collect();
+    let mut shuffled = Vec::with_capacity(seq_len);
+    while !remaining.is_empty() {
+        let index = rng.next_int(0, remaining.len() as u64 - 1) as usize;
+        shuffled.push(remaining.remove(index));
+    }
+    shuffled.truncate(degree);
+    shuffled
+}
+
+fn xor_into(target: &mut [u8], other: &[u8]) {
+    for (a, b) in target.iter_mut().zip(other) {
+        *a ^= b;
+    }
+}
+
+/// xoshiro256** seeded from the SHA-256 of a seed
+pub(super) struct Xoshiro256 {
+    s: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub(super) fn from_seed(seed: &[u8]) -> Self {
+        let digest = sha256::Hash::hash(seed).to_byte_array();
+        let mut s = [0u64; 4];
+        for (i, word) in s.iter_mut().enumerate() {
+            *word = u64::from_be_bytes(digest[i * 8..i * 8 + 8].try_into().unwrap());
+        }
+        Self { s }
+    }
+
+    fn next(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next() as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    pub(super) fn next_int(&mut self, low: u64, high: u64) -> u64 {
+        (self.next_double() * (high - low + 1) as f64) as u64 + low
+    }
+}
+
+/// Walker's alias method, as used by the reference implementation
+struct AliasSampler {
+    probs: Vec<f64>,
+    aliases: Vec<usize>,
+}
+
+impl AliasSampler {
+    fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let sum: f64 = weights.iter().sum();
+        let mut p: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum).collect();
+
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+        for i in (0..n).rev() {
+            if p[i] < 1.0 {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+
+        let mut probs = vec![0.0; n];
+        let mut aliases = vec![0; n];
+        while !small.is_empty() && !large.is_empty() {
+            let a = small.pop().unwrap();
+            let g = large.pop().unwrap();
+            probs[a] = p[a];
+            aliases[a] = g;
+            p[g] += p[a] - 1.0;
+            if p[g] < 1.0 {
+                small.push(g);
+            } else {
+                large.push(g);
+            }
+        }
+        for i in large.into_iter().chain(small) {
+            probs[i] = 1.0;
+        }
+
+        Self { probs, aliases }
+    }
+
+    fn next(&self, rng: &mut Xoshiro256) -> usize {
+        let r1 = rng.next_double();
+        let r2 = rng.next_double();
+        let i = (self.probs.len() as f64 * r1) as usize;
+        if r2 < self.probs[i] {
+            i
+        } else {
+            self.aliases[i]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_vectors() {
+        let mut rng = Xoshiro256::from_seed(b"Wolf");
+        let numbers: Vec<u64> = (0..10).map(|_| rng.next() % 100).collect();
+        assert_eq!(numbers, vec![42, 81, 85, 8, 82, 84, 76, 73, 70, 88]);
+
+        let mut rng = Xoshiro256::from_seed(b"Wolf");
+        let mut remaining: Vec<u32> = (1..=10).collect();
+        let mut shuffled = Vec::new();
+        while !remaining.is_empty() {
+            let i = rng.next_int(0, remaining.len() as u64 - 1) as usize;
+            shuffled.push(remaining.remove(i));
+        }
+        assert_eq!(shuffled, vec![6, 4, 9, 3, 10, 5, 7, 8, 1, 2]);
+    }
+
+    #[test]
+    fn test_fragment_length() {
+        assert_eq!(fragment_length(12345, 1955), 1764);
+        assert_eq!(fragment_length(12345, 30000), 12345);
+        assert_eq!(fragment_length(259, 30), 29);
+    }
+
+    #[test]
+    fn test_decode_from_mixed_parts() {
+        let message: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut encoder = FountainEncoder::new(&message, 100);
+        assert_eq!(encoder.seq_len(), 10);
+
+        // Skip every pure part; the mixed ones alone must complete
+        let mut decoder = FountainDecoder::default();
+        for _ in 0..encoder.seq_len() {
+            encoder.next_part();
+        }
+        while decoder.message().is_none() {
+            let part = encoder.next_part();
+            decoder.receive(&part).unwrap();
+            assert!(part.seq_num < 200, "decoder did not converge");
+        }
+        assert_eq!(decoder.message().unwrap(), message.as_slice());
+    }
+
+    #[test]
+    fn test_rejects_foreign_part() {
+        let mut a = FountainEncoder::new(&[1u8; 100], 30);
+        let mut b = FountainEncoder::new(&[2u8; 100], 30);
+        let mut decoder = FountainDecoder::default();
+        decoder.receive(&a.next_part()).unwrap();
+        assert!(decoder.receive(&b.next_part()).is_err());
+    }
+}
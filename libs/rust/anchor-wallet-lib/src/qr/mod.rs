@@ -0,0 +1,17 @@
+//! Animated QR helpers for airgapped signing
+//!
+//! Unsigned PSBTs are shown to an offline signer as a sequence of QR codes
+//! and the signed result is scanned back, using either of the two formats
+//! hardware wallets understand:
+//!
+//! - UR (BC-UR v2, `ur:crypto-psbt/...`): fountain-coded frames, so the
+//!   scanner can finish from any sufficiently large subset.
+//! - BBQr (`B$...`): numbered frames, every part must be scanned.
+
+mod bbqr;
+mod bytewords;
+mod fountain;
+mod ur;
+
+pub use bbqr::{encode_bbqr, encode_psbt_bbqr, BbqrDecoder, BbqrFileType};
+pub use ur::{UrDecoder, UrEncoder, PSBT_UR_TYPE};
@@ -0,0 +1,418 @@
+//! Uniform Resources (BCR-2020-005)
+//!
+//! A UR is `ur:<type>/<bytewords>` for a single part, or
+//! `ur:<type>/<seq>-<len>/<bytewords>` for fountain-coded parts of an
+//! animated QR. PSBTs travel as `crypto-psbt`, a CBOR byte string.
+
+use bitcoin::psbt::Psbt;
+
+use super::bytewords;
+use super::fountain::{FountainDecoder, FountainEncoder, Part};
+use crate::error::{Result, WalletError};
+
+/// UR type for PSBTs understood by current airgapped signers
+pub const PSBT_UR_TYPE: &str = "crypto-psbt";
+
+/// Newer registry name for the same payload
+const PSBT_UR_TYPE_V2: &str = "psbt";
+
+/// Most fragments a decoder will accept for one message
+const MAX_SEQ_LEN: usize = 10_000;
+
+/// Produces the frames of an animated UR QR code
+#[derive(Debug, Clone)]
+pub struct UrEncoder {
+    ur_type: String,
+    cbor: Vec<u8>,
+    fountain: FountainEncoder,
+}
+
+impl UrEncoder {
+    /// Encode a CBOR payload, splitting it into fragments of at most
+    /// `max_fragment_len` bytes
+    pub fn new(ur_type: &str, cbor: &[u8], max_fragment_len: usize) -> Self {
+        Self {
+            ur_type: ur_type.to_string(),
+            cbor: cbor.to_vec(),
+            fountain: FountainEncoder::new(cbor, max_fragment_len),
+        }
+    }
+
+    /// Encode a PSBT as `crypto-psbt`
+    pub fn psbt(psbt: &Psbt, max_fragment_len: usize) -> Self {
+        Self::new(
+            PSBT_UR_TYPE,
+            &cbor_bytes(&psbt.serialize()),
+            max_fragment_len,
+        )
+    }
+
+    /// Whether the payload fits in one static QR code
+    pub fn is_single_part(&self) -> bool {
+        self.fountain.seq_len() == 1
+    }
+
+    /// Number of pure parts; show at least this many frames
+    pub fn seq_len(&self) -> usize {
+        self.fountain.seq_len()
+    }
+
+    /// The whole payload as a single-part UR
+    pub fn single_part(&self) -> String {
+        format!("ur:{}/{}", self.ur_type, bytewords::encode(&self.cbor))
+    }
+
+    /// The next frame
+    ///
+    /// Returns the single-part UR when the payload fits in one fragment.
+    /// Frames are lowercase; uppercase them for QR alphanumeric mode.
+    pub fn next_part(&mut self) -> String {
+        if self.is_single_part() {
+            return self.single_part();
+        }
+        let part = self.fountain.next_part();
+        format!(
+            "ur:{}/{}-{}/{}",
+            self.ur_type,
+            part.seq_num,
+            part.seq_len,
+            bytewords::encode(&encode_part(&part))
+        )
+    }
+}
+
+/// Collects scanned UR frames until the payload is complete
+#[derive(Debug, Clone, Default)]
+pub struct UrDecoder {
+    ur_type: Option<String>,
+    fountain: FountainDecoder,
+    single: Option<Vec<u8>>,
+}
+
+impl UrDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scanned frame
+    pub fn receive(&mut self, text: &str) -> Result<()> {
+        let text = text.trim().to_ascii_lowercase();
+        let rest = text
+            .strip_prefix("ur:")
+            .ok_or_else(|| WalletError::Qr("not a UR".to_string()))?;
+        let components: Vec<&str> = rest.split('/').collect();
+
+        let ur_type = components[0];
+        if ur_type.is_empty()
+            || !ur_type
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(WalletError::Qr(format!("invalid UR type '{}'", ur_type)));
+        }
+        match &self.ur_type {
+            Some(known) if known != ur_type => {
+                return Err(WalletError::Qr(format!(
+                    "expected ur:{}, got ur:{}",
+                    known, ur_type
+                )))
+            }
+            _ => self.ur_type = Some(ur_type.to_string()),
+        }
+
+        match components.len() {
+            2 => {
+                self.single = Some(bytewords::decode(components[1])?);
+                Ok(())
+            }
+            3 => {
+                let part = decode_part(&bytewords::decode(components[2])?)?;
+                let expected = format!("{}-{}", part.seq_num, part.seq_len);
+                if components[1] != expected {
+                    return Err(WalletError::Qr(
+                        "UR sequence does not match its part".to_string(),
+                    ));
+                }
+                self.fountain.receive(&part)
+            }
+            _ => Err(WalletError::Qr("malformed UR".to_string())),
+        }
+    }
+
+    /// Whether the payload has been fully received
+    pub fn is_complete(&self) -> bool {
+        self.cbor().is_some()
+    }
+
+    /// Estimated fraction received, for progress bars
+    pub fn progress(&self) -> f64 {
+        if self.single.is_some() {
+            1.0
+        } else {
+            self.fountain.progress()
+        }
+    }
+
+    /// UR type of the frames seen so far
+    pub fn ur_type(&self) -> Option<&str> {
+        self.ur_type.as_deref()
+    }
+
+    /// The CBOR payload, once complete
+    pub fn cbor(&self) -> Option<&[u8]> {
+        self.single.as_deref().or_else(|| self.fountain.message())
+    }
+
+    /// The PSBT carried by a complete `crypto-psbt` or `psbt` UR
+    pub fn psbt(&self) -> Result<Psbt> {
+        let cbor = self
+            .cbor()
+            .ok_or_else(|| WalletError::Qr("UR is incomplete".to_string()))?;
+        match self.ur_type() {
+            Some(PSBT_UR_TYPE) | Some(PSBT_UR_TYPE_V2) => {}
+            other => {
+                return Err(WalletError::Qr(format!(
+                    "expected a PSBT UR, got {}",
+                    other.unwrap_or("nothing")
+                )))
+            }
+        }
+
+        let mut reader = Cbor::new(cbor);
+        let bytes = reader.bytes()?;
+        reader.end()?;
+        Psbt::deserialize(bytes).map_err(|e| WalletError::Serialization(e.to_string()))
+    }
+}
+
+/// Wrap bytes as a CBOR byte string
+fn cbor_bytes(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 9);
+    cbor_head(&mut out, 2, data.len() as u64);
+    out.extend_from_slice(data);
+    out
+}
+
+/// CBOR major type and argument
+fn cbor_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+/// `[seq_num, seq_len, message_len, checksum, data]`
+fn encode_part(part: &Part) -> Vec<u8> {
+    let mut out = vec![0x85];
+    cbor_head(&mut out, 0, part.seq_num as u64);
+    cbor_head(&mut out, 0, part.seq_len as u64);
+    cbor_head(&mut out, 0, part.message_len as u64);
+    cbor_head(&mut out, 0, part.checksum as u64);
+    cbor_head(&mut out, 2, part.data.len() as u64);
+    out.extend_from_slice(&part.data);
+    out
+}
+
+fn decode_part(cbor: &[u8]) -> Result<Part> {
+    let mut reader = Cbor::new(cbor);
+    if reader.head(4)? != 5 {
+        return Err(WalletError::Qr("UR part must have 5 fields".to_string()));
+    }
+    let seq_num = u32::try_from(reader.head(0)?)
+        .map_err(|_| WalletError::Qr("UR sequence number too large".to_string()))?;
+    let seq_len = reader.head(0)? as usize;
+    let message_len = reader.head(0)? as usize;
+    let checksum = u32::try_from(reader.head(0)?)
+        .map_err(|_| WalletError::Qr("UR checksum too large".to_string()))?;
+    let data = reader.bytes()?.to_vec();
+    reader.end()?;
+
+    if seq_num == 0
+        || data.is_empty()
+        || seq_len > MAX_SEQ_LEN
+        || seq_len != message_len.div_ceil(data.len())
+    {
+        return Err(WalletError::Qr("inconsistent UR part".to_string()));
+    }
+    Ok(Part {
+        seq_num,
+        seq_len,
+        message_len,
+        checksum,
+        data,
+    })
+}
+
+/// Reader for the small CBOR subset URs use
+struct Cbor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cbor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| WalletError::Qr("truncated CBOR".to_string()))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Read a head of the given major type, returning its argument
+    fn head(&mut self, major: u8) -> Result<u64> {
+        let initial = self.take(1)?[0];
+        if initial >> 5 != major {
+            return Err(WalletError::Qr(format!(
+                "expected CBOR major type {}, got {}",
+                major,
+                initial >> 5
+            )));
+        }
+        let value = match initial & 0x1f {
+            n @ 0..=23 => n as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            _ => return Err(WalletError::Qr("unsupported CBOR length".to_string())),
+        };
+        Ok(value)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = usize::try_from(self.head(2)?)
+            .map_err(|_| WalletError::Qr("CBOR byte string too long".to_string()))?;
+        self.take(len)
+    }
+
+    fn end(&self) -> Result<()> {
+        if self.pos != self.data.len() {
+            return Err(WalletError::Qr("trailing CBOR data".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fountain::Xoshiro256;
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+
+    /// Reference test message: `make_message(len, "Wolf")`
+    fn wolf_message(len: usize) -> Vec<u8> {
+        let mut rng = Xoshiro256::from_seed(b"Wolf");
+        (0..len).map(|_| rng.next_int(0, 255) as u8).collect()
+    }
+
+    fn sample_psbt() -> Psbt {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: ScriptBuf::from_bytes([vec![0x6a, 60], vec![0x42; 60]].concat()),
+                },
+                TxOut {
+                    value: Amount::from_sat(50_000),
+                    script_pubkey: ScriptBuf::from_bytes(vec![0x51]),
+                },
+            ],
+        };
+        Psbt::from_unsigned_tx(tx).unwrap()
+    }
+
+    #[test]
+    fn test_single_part_vector() {
+        let encoder = UrEncoder::new("bytes", &cbor_bytes(&wolf_message(50)), 1000);
+        assert!(encoder.is_single_part());
+        assert_eq!(
+            encoder.single_part(),
+            "ur:bytes/hdeymejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtgwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsdwkbrkch"
+        );
+    }
+
+    #[test]
+    fn test_multi_part_vectors() {
+        let mut encoder = UrEncoder::new("bytes", &cbor_bytes(&wolf_message(256)), 30);
+        let parts: Vec<String> = (0..20).map(|_| encoder.next_part()).collect();
+        assert_eq!(
+            parts[0],
+            "ur:bytes/1-9/lpadascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtdkgslpgh"
+        );
+        assert_eq!(
+            parts[11],
+            "ur:bytes/12-9/lpbnascfadaxcywenbpljkhdcarllaluzmdmgstospeyiefmwejlwtpedamktksrvlcygmzemovovllarodtmtbnptrs"
+        );
+        assert_eq!(
+            parts[19],
+            "ur:bytes/20-9/lpbbascfadaxcywenbpljkhdcayapmrleeleaxpasfrtrdkncffwjyjzgyetdmlewtkpktgllepfrltataztksmhkbot"
+        );
+    }
+
+    #[test]
+    fn test_psbt_roundtrip() {
+        let psbt = sample_psbt();
+        let mut encoder = UrEncoder::psbt(&psbt, 40);
+        assert!(!encoder.is_single_part());
+
+        let mut decoder = UrDecoder::new();
+        // Drop every third frame, as a camera would
+        let mut frame = 0;
+        while !decoder.is_complete() {
+            let part = encoder.next_part().to_uppercase();
+            if frame % 3 != 2 {
+                decoder.receive(&part).unwrap();
+            }
+            frame += 1;
+            assert!(frame < 500);
+        }
+        assert_eq!(decoder.ur_type(), Some(PSBT_UR_TYPE));
+        assert_eq!(decoder.psbt().unwrap(), psbt);
+
+        let single = UrEncoder::psbt(&psbt, 10_000).single_part();
+        let mut decoder = UrDecoder::new();
+        decoder.receive(&single).unwrap();
+        assert_eq!(decoder.psbt().unwrap(), psbt);
+    }
+
+    #[test]
+    fn test_rejects_mismatched_frames() {
+        let mut decoder = UrDecoder::new();
+        assert!(decoder.receive("bytes/aeadaolazmjendeoti").is_err());
+        decoder.receive("ur:bytes/aeadaolazmjendeoti").unwrap();
+        assert!(decoder
+            .receive("ur:crypto-psbt/aeadaolazmjendeoti")
+            .is_err());
+        assert!(decoder.psbt().is_err());
+    }
+}
@@ -0,0 +1,455 @@
+//! Wallet rescans through the indexer's ANCHOR activity filters
+//!
+//! A wallet restored from seed doesn't know which blocks hold its ANCHOR
+//! transactions. The indexer builds a compact filter of every block's
+//! ANCHOR transactions (see [`anchor_core::anchor_filter`]) and the explorer
+//! API serves them at `GET /filters`. [`AnchorFilterClient`] downloads them,
+//! matches them against the wallet's scripts and fetches only the matching
+//! blocks, typically from the wallet's own node, instead of rescanning the
+//! whole chain.
+//!
+//! Filter headers are checked to chain across requests, so a server that
+//! reorgs mid-scan is caught, but filter contents are taken as served: a
+//! dishonest server can hide transactions from the wallet.
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anchor_core::{creator_commitment, BlockFilter, FilterHeader};
+use bitcoin::hashes::Hash;
+use bitcoin::{Block, BlockHash, ScriptBuf, Transaction};
+use serde::Deserialize;
+
+use crate::broadcast::{http_connect, parse_http_response, parse_http_url};
+use crate::error::{Result, WalletError};
+use crate::light::{collect_messages, LightMessage};
+
+/// Filters requested at once, the most the explorer API returns
+const FILTERS_PER_REQUEST: u32 = 1000;
+
+/// Largest response accepted for one request
+const MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// One page of `GET /filters`
+#[derive(Debug, Deserialize)]
+struct FiltersPage {
+    tip_height: Option<u32>,
+    previous_header: Option<String>,
+    filters: Vec<FilterEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FilterEntry {
+    block_height: u32,
+    block_hash: String,
+    filter: String,
+    header: String,
+}
+
+/// Result of [`AnchorFilterClient::scan`]
+#[derive(Debug, Clone)]
+pub struct AnchorFilterScan {
+    /// Highest block with a filter when the scan ended, `None` if the server
+    /// has no filters at all
+    pub tip_height: Option<u32>,
+    /// Blocks whose filter matched and that were fetched
+    pub matched_blocks: Vec<BlockHash>,
+    /// Height ranges (inclusive) the server had no filters for; the wallet's
+    /// transactions there were not searched
+    pub gaps: Vec<(u32, u32)>,
+    /// Messages in the wallet's ANCHOR transactions, in chain order
+    pub messages: Vec<LightMessage>,
+}
+
+/// Client for the ANCHOR activity filters of an explorer API
+#[derive(Debug, Clone)]
+pub struct AnchorFilterClient {
+    url: String,
+    host: String,
+    port: u16,
+    path: String,
+    proxy: Option<SocketAddr>,
+    timeout: Duration,
+    scripts: Vec<ScriptBuf>,
+    start_height: u32,
+}
+
+impl AnchorFilterClient {
+    /// Explorer API base URL, e.g. `http://127.0.0.1:3101`
+    pub fn new(url: &str) -> Result<Self> {
+        let (host, port, path) = parse_http_url(url)?;
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            host,
+            port,
+            path,
+            proxy: None,
+            timeout: Duration::from_secs(30),
+            scripts: Vec::new(),
+            start_height: 0,
+        })
+    }
+
+    /// Add a script owned by the wallet
+    pub fn with_script(mut self, script: ScriptBuf) -> Self {
+        self.scripts.push(script);
+        self
+    }
+
+    /// Only scan from this height (e.g. the wallet's birthday)
+    pub fn with_start_height(mut self, height: u32) -> Self {
+        self.start_height = height;
+        self
+    }
+
+    /// Connect through a SOCKS5 proxy, e.g. Tor at `127.0.0.1:9050`
+    pub fn with_proxy(mut self, proxy: SocketAddr) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set the connect and read timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Scan every filter from the start height for the wallet's scripts
+    ///
+    /// `get_block` fetches a matching block by hash, e.g. from Bitcoin Core
+    /// (see [`AnchorWallet::rescan_anchor_activity`]).
+    ///
+    /// [`AnchorWallet::rescan_anchor_activity`]: crate::AnchorWallet::rescan_anchor_activity
+    pub fn scan(
+        &self,
+        mut get_block: impl FnMut(&BlockHash) -> Result<Block>,
+    ) -> Result<AnchorFilterScan> {
+        if self.scripts.is_empty() {
+            return Err(WalletError::Config(
+                "No wallet scripts to scan for".to_string(),
+            ));
+        }
+
+        let mut scan = AnchorFilterScan {
+            tip_height: None,
+            matched_blocks: Vec::new(),
+            gaps: Vec::new(),
+            messages: Vec::new(),
+        };
+        let scripts: Vec<Vec<u8>> = self.scripts.iter().map(|s| s.to_bytes()).collect();
+        let mut found = Vec::new();
+        let mut from = self.start_height;
+
+        loop {
+            let page = self.fetch(from)?;
+            let Some(tip_height) = page.tip_height else {
+                break;
+            };
+            scan.tip_height = Some(tip_height);
+            if from > tip_height {
+                break;
+            }
+            let last = (from + FILTERS_PER_REQUEST - 1).min(tip_height);
+
+            let mut expected = from;
+            let mut previous = match &page.previous_header {
+                Some(header) => parse_header(header)?,
+                None => FilterHeader::all_zeros(),
+            };
+            for entry in &page.filters {
+                if entry.block_height < expected || entry.block_height > last {
+                    return Err(self.error(format!(
+                        "unexpected filter for height {}",
+                        entry.block_height
+                    )));
+                }
+                if entry.block_height > expected {
+                    // Headers restart from zero after blocks without filters
+                    scan.gaps.push((expected, entry.block_height - 1));
+                    previous = FilterHeader::all_zeros();
+                }
+
+                let block_hash = BlockHash::from_str(&entry.block_hash)
+                    .map_err(|e| self.error(format!("invalid block hash: {}", e)))?;
+                let filter = BlockFilter::new(&hex::decode(&entry.filter)?);
+                let header = parse_header(&entry.header)?;
+                if filter.filter_header(&previous) != header {
+                    return Err(self.error(format!(
+                        "filter header of block {} does not chain",
+                        entry.block_height
+                    )));
+                }
+                previous = header;
+                expected = entry.block_height + 1;
+
+                if !self.matches(&filter, &block_hash, &scripts)? {
+                    continue;
+                }
+                let block = get_block(&block_hash)?;
+                if block.block_hash() != block_hash {
+                    return Err(WalletError::Config(format!(
+                        "Block source returned {} for {}",
+                        block.block_hash(),
+                        block_hash
+                    )));
+                }
+                for tx in self.wallet_transactions(&block, &filter)? {
+                    found.push((tx, block_hash, entry.block_height));
+                }
+                scan.matched_blocks.push(block_hash);
+            }
+            if expected <= last {
+                scan.gaps.push((expected, last));
+            }
+
+            if last >= tip_height {
+                break;
+            }
+            from = last + 1;
+        }
+
+        scan.messages = collect_messages(&found);
+        Ok(scan)
+    }
+
+    /// Transactions of a matched block that pay one of our scripts or were
+    /// created by one, according to the filter's creator commitments
+    fn wallet_transactions(&self, block: &Block, filter: &BlockFilter) -> Result<Vec<Transaction>> {
+        let block_hash = block.block_hash();
+        let mut ours = Vec::new();
+        for tx in &block.txdata {
+            let pays_us = tx
+                .output
+                .iter()
+                .any(|output| self.scripts.contains(&output.script_pubkey));
+            let created_by_us = || {
+                let txid = tx.compute_txid();
+                let commitments: Vec<Vec<u8>> = self
+                    .scripts
+                    .iter()
+                    .map(|script| creator_commitment(&txid, script).to_vec())
+                    .collect();
+                self.matches(filter, &block_hash, &commitments)
+            };
+            if pays_us || created_by_us()? {
+                ours.push(tx.clone());
+            }
+        }
+        Ok(ours)
+    }
+
+    fn matches(
+        &self,
+        filter: &BlockFilter,
+        block_hash: &BlockHash,
+        query: &[Vec<u8>],
+    ) -> Result<bool> {
+        filter
+            .match_any(block_hash, query.iter().map(|element| element.as_slice()))
+            .map_err(|e| self.error(format!("invalid filter: {}", e)))
+    }
+
+    /// Fetch the page of filters starting at `from`
+    fn fetch(&self, from: u32) -> Result<FiltersPage> {
+        // HTTP/1.0 so the body is never chunked
+        let request = format!(
+            "GET {}/filters?from={}&count={} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\n\r\n",
+            self.path, from, FILTERS_PER_REQUEST, self.host
+        );
+
+        let mut stream = http_connect(&self.host, self.port, self.proxy, self.timeout)
+            .map_err(|e| self.error(e))?;
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| self.error(e))?;
+        let mut response = Vec::new();
+        stream
+            .take(MAX_RESPONSE_SIZE)
+            .read_to_end(&mut response)
+            .map_err(|e| self.error(e))?;
+
+        let (status, body) =
+            parse_http_response(&response).ok_or_else(|| self.error("malformed HTTP response"))?;
+        if status != 200 {
+            return Err(self.error(format!("HTTP {}: {}", status, body.trim())));
+        }
+        serde_json::from_str(&body).map_err(|e| self.error(format!("invalid response: {}", e)))
+    }
+
+    fn error(&self, error: impl std::fmt::Display) -> WalletError {
+        WalletError::FilterIndex(format!("{}: {}", self.url, error))
+    }
+}
+
+fn parse_header(header: &str) -> Result<FilterHeader> {
+    FilterHeader::from_str(header)
+        .map_err(|e| WalletError::FilterIndex(format!("invalid filter header: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_core::{
+        anchor_filter, create_anchor_script, AnchorKind, Anchors, ParsedAnchorMessage,
+    };
+    use bitcoin::block::{Header, Version as BlockVersion};
+    use bitcoin::{
+        absolute::LockTime, transaction::Version, Amount, CompactTarget, TxMerkleNode, TxOut,
+    };
+    use std::net::TcpListener;
+
+    fn script(byte: u8) -> ScriptBuf {
+        ScriptBuf::from_bytes(vec![0x51, byte])
+    }
+
+    fn anchor_tx(body: &str, change: ScriptBuf) -> Transaction {
+        let payload = create_anchor_script(&ParsedAnchorMessage {
+            kind: AnchorKind::Text,
+            anchors: Anchors::new(),
+            body: body.as_bytes().to_vec(),
+        });
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![Default::default()],
+            output: [payload, change]
+                .into_iter()
+                .map(|script_pubkey| TxOut {
+                    value: Amount::from_sat(1_000),
+                    script_pubkey,
+                })
+                .collect(),
+        }
+    }
+
+    fn block(time: u32, txdata: Vec<Transaction>) -> Block {
+        Block {
+            header: Header {
+                version: BlockVersion::TWO,
+                prev_blockhash: BlockHash::all_zeros(),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time,
+                bits: CompactTarget::from_consensus(0x207fffff),
+                nonce: 0,
+            },
+            txdata,
+        }
+    }
+
+    /// Serve one `GET /filters` response per request, then stop
+    fn serve(pages: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for page in pages {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = vec![0u8; 4096];
+                let _ = stream.read(&mut request).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                    page.len(),
+                    page
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    fn entry(height: u32, block: &Block, filter: &BlockFilter, header: &FilterHeader) -> String {
+        serde_json::json!({
+            "block_height": height,
+            "block_hash": block.block_hash().to_string(),
+            "filter": hex::encode(&filter.content),
+            "header": header.to_string(),
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_scan_finds_created_and_paid_transactions() {
+        // Block 10 pays our change script, block 11 holds a post we created
+        // from an address whose funding isn't in any filter, block 12 is
+        // someone else's and block 13 has no filter
+        let paid = anchor_tx("paid", script(1));
+        let created = anchor_tx("created", script(9));
+        let other = anchor_tx("other", script(8));
+        let blocks = [
+            block(10, vec![paid.clone()]),
+            block(11, vec![created.clone()]),
+            block(12, vec![other.clone()]),
+        ];
+        let filters = [
+            anchor_filter(&blocks[0], [(&paid, None)]),
+            anchor_filter(&blocks[1], [(&created, Some(script(2).as_script()))]),
+            anchor_filter(&blocks[2], [(&other, Some(script(7).as_script()))]),
+        ];
+        let mut header = FilterHeader::all_zeros();
+        let entries: Vec<String> = filters
+            .iter()
+            .zip(&blocks)
+            .enumerate()
+            .map(|(i, (filter, block))| {
+                header = filter.filter_header(&header);
+                entry(10 + i as u32, block, filter, &header)
+            })
+            .collect();
+        let page = format!(
+            r#"{{"tip_height": 13, "previous_header": null, "filters": [{}]}}"#,
+            entries.join(",")
+        );
+
+        let client = AnchorFilterClient::new(&serve(vec![page]))
+            .unwrap()
+            .with_start_height(10)
+            .with_script(script(1))
+            .with_script(script(2));
+        let mut fetched = Vec::new();
+        let scan = client
+            .scan(|hash| {
+                fetched.push(*hash);
+                Ok(blocks
+                    .iter()
+                    .find(|b| b.block_hash() == *hash)
+                    .unwrap()
+                    .clone())
+            })
+            .unwrap();
+
+        assert_eq!(
+            fetched,
+            vec![blocks[0].block_hash(), blocks[1].block_hash()]
+        );
+        assert_eq!(scan.matched_blocks, fetched);
+        assert_eq!(scan.tip_height, Some(13));
+        assert_eq!(scan.gaps, vec![(13, 13)]);
+        let bodies: Vec<&[u8]> = scan
+            .messages
+            .iter()
+            .map(|found| found.message.message.body.as_slice())
+            .collect();
+        assert_eq!(bodies, vec![b"paid".as_slice(), b"created".as_slice()]);
+        assert_eq!(scan.messages[1].height, 11);
+    }
+
+    #[test]
+    fn test_scan_rejects_broken_header_chain() {
+        let tx = anchor_tx("post", script(1));
+        let block = block(10, vec![tx.clone()]);
+        let filter = anchor_filter(&block, [(&tx, None)]);
+        let page = format!(
+            r#"{{"tip_height": 10, "previous_header": null, "filters": [{}]}}"#,
+            entry(10, &block, &filter, &FilterHeader::all_zeros())
+        );
+
+        let error = AnchorFilterClient::new(&serve(vec![page]))
+            .unwrap()
+            .with_start_height(10)
+            .with_script(script(1))
+            .scan(|_| unreachable!())
+            .unwrap_err();
+        assert!(error.to_string().contains("does not chain"));
+    }
+}
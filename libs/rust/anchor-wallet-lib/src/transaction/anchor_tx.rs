@@ -1,7 +1,7 @@
 //! ANCHOR transaction types
 
-use anchor_core::carrier::CarrierType;
-use anchor_core::{Anchor, AnchorKind};
+use anchor_core::carrier::{CarrierType, UtxoCost};
+use anchor_core::{AnchorKind, Anchors};
 use bitcoin::{ScriptBuf, Transaction, Txid};
 
 /// Represents an ANCHOR transaction
@@ -17,13 +17,42 @@ pub struct AnchorTransaction {
     pub kind: AnchorKind,
 
     /// Parent anchors (for replies)
-    pub anchors: Vec<Anchor>,
+    pub anchors: Anchors,
 
     /// The carrier type used
     pub carrier: CarrierType,
 
     /// Additional carrier-specific data (for inscription reveal, etc.)
     pub carrier_data: Option<CarrierData>,
+
+    /// Fee paid by the transaction in satoshis
+    pub fee: u64,
+
+    /// What was done with the value left over after outputs and fee
+    pub change: ChangeDecision,
+
+    /// Unprunable outputs this transaction adds to the UTXO set (Stamps)
+    pub utxo_cost: UtxoCost,
+}
+
+/// How the builder settled the leftover input value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeDecision {
+    /// A change output of `value` sats was added
+    Change { value: u64 },
+    /// The change would have been below the dust limit, so `amount` sats
+    /// were added to the fee instead
+    DustToFee { amount: u64 },
+    /// The inputs matched the outputs within the changeless tolerance, so no
+    /// change output was created and `excess` sats went to the fee
+    Changeless { excess: u64 },
+}
+
+impl ChangeDecision {
+    /// Whether the transaction has a change output
+    pub fn has_change(&self) -> bool {
+        matches!(self, ChangeDecision::Change { .. })
+    }
 }
 
 /// Additional data for specific carriers
@@ -38,10 +67,10 @@ pub enum CarrierData {
     Stamps { scripts: Vec<ScriptBuf> },
     /// Annex data for Taproot
     Annex { data: Vec<u8> },
-    /// Witness data chunks
+    /// Witness data chunks, with one tapscript per reveal input
     WitnessData {
         chunks: Vec<Vec<u8>>,
-        script: ScriptBuf,
+        scripts: Vec<ScriptBuf>,
     },
 }
 
@@ -56,6 +85,15 @@ impl AnchorTransaction {
         bitcoin::consensus::encode::serialize_hex(&self.transaction)
     }
 
+    /// Wrap the unsigned transaction in a PSBT for an external signer
+    ///
+    /// Input UTXO details are not known here; the caller (or the signer's
+    /// own wallet) must fill in `witness_utxo` before signing.
+    pub fn to_psbt(&self) -> crate::error::Result<bitcoin::psbt::Psbt> {
+        bitcoin::psbt::Psbt::from_unsigned_tx(self.transaction.clone())
+            .map_err(|e| crate::error::WalletError::TransactionBuild(e.to_string()))
+    }
+
     /// Get the OP_RETURN output index (always 0 for ANCHOR transactions)
     pub fn anchor_vout(&self) -> u32 {
         0
@@ -1,32 +1,64 @@
 //! Transaction builder for ANCHOR messages
 
-use anchor_core::carrier::{CarrierOutput, CarrierPreferences, CarrierSelector, CarrierType};
+use anchor_core::carrier::{
+    Carrier, CarrierOutput, CarrierPreferences, CarrierSelector, CarrierType, StampsCarrier,
+};
 use anchor_core::{
-    create_anchor_script, encode_anchor_payload, Anchor, AnchorKind, ParsedAnchorMessage,
+    create_anchor_script, encode_anchor_payload, Anchor, AnchorKind, Anchors, ParsedAnchorMessage,
 };
+use bitcoin::consensus::encode::{serialize, VarInt};
+use bitcoin::hashes::{sha256d, Hash};
 use bitcoin::{
-    absolute::LockTime, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence, Transaction,
-    TxIn, TxOut, Txid, Witness,
+    absolute::LockTime, relative, transaction::Version, Amount, OutPoint, ScriptBuf, Sequence,
+    Transaction, TxIn, TxOut, Txid, Witness,
 };
+use std::time::{Duration, Instant};
 
-use super::anchor_tx::{AnchorTransaction, CarrierData};
+use super::anchor_tx::{AnchorTransaction, CarrierData, ChangeDecision};
 use crate::error::{Result, WalletError};
 
 /// Maximum OP_RETURN payload size
 /// Bitcoin Core v30+ supports up to 100KB with datacarriersize=100000
 pub const MAX_OP_RETURN_SIZE: usize = 100000;
 
+/// Smallest change output the builder will create, in satoshis
+pub const DUST_LIMIT: u64 = 546;
+
+/// Estimated vsize of a P2WPKH input
+const INPUT_VSIZE: usize = 68;
+
+/// Version, locktime and input/output counts
+const TX_OVERHEAD_VSIZE: usize = 10;
+
+/// Upper bound on branches explored while looking for a changeless selection
+const MAX_SELECTION_TRIES: usize = 100_000;
+
+/// Longest anchor prefix pattern, in hex digits (the prefix is 8 bytes)
+const MAX_GRIND_PATTERN: usize = 16;
+
+/// Grinding attempts between checks of the time budget
+const GRIND_BATCH: u32 = 4096;
+
+/// Lowest nSequence tried while grinding (relative lock disable flag)
+const MIN_GRIND_SEQUENCE: u32 = 1 << 31;
+
 /// Builder for creating ANCHOR transactions
 #[derive(Debug)]
 pub struct TransactionBuilder {
     kind: AnchorKind,
     body: Vec<u8>,
-    anchors: Vec<Anchor>,
+    anchors: Anchors,
     inputs: Vec<(OutPoint, u64)>, // (outpoint, value in sats)
+    candidates: Vec<(OutPoint, u64)>,
+    changeless_tolerance: Option<u64>,
     change_script: Option<ScriptBuf>,
     fee_rate: f64,
     carrier: Option<CarrierType>,
     carrier_prefs: CarrierPreferences,
+    stamps_output_value: Option<u64>,
+    lock_time: LockTime,
+    relative_lock_time: Option<relative::LockTime>,
+    grind: Option<(Vec<u8>, Duration)>,
 }
 
 impl TransactionBuilder {
@@ -35,12 +67,18 @@ impl TransactionBuilder {
         Self {
             kind: AnchorKind::Text,
             body: Vec::new(),
-            anchors: Vec::new(),
+            anchors: Anchors::new(),
             inputs: Vec::new(),
+            candidates: Vec::new(),
+            changeless_tolerance: None,
             change_script: None,
             fee_rate: 1.0,
             carrier: None,
             carrier_prefs: CarrierPreferences::default(),
+            stamps_output_value: None,
+            lock_time: LockTime::ZERO,
+            relative_lock_time: None,
+            grind: None,
         }
     }
 
@@ -96,6 +134,26 @@ impl TransactionBuilder {
         Ok(self)
     }
 
+    /// Add a UTXO the builder may select from
+    ///
+    /// Candidates are only used when no inputs were added explicitly. The
+    /// builder first looks for a subset that pays the outputs and fee without
+    /// change, then falls back to spending the largest UTXOs first.
+    pub fn candidate(mut self, txid: Txid, vout: u32, value_sats: u64) -> Self {
+        self.candidates.push((OutPoint { txid, vout }, value_sats));
+        self
+    }
+
+    /// Leftover value, in satoshis, that may be given to the fee instead of
+    /// creating a change output
+    ///
+    /// Defaults to what the change output would cost to create and later
+    /// spend at the current fee rate.
+    pub fn changeless_tolerance(mut self, sats: u64) -> Self {
+        self.changeless_tolerance = Some(sats);
+        self
+    }
+
     /// Set the change script
     pub fn change_script(mut self, script: ScriptBuf) -> Self {
         self.change_script = Some(script);
@@ -120,6 +178,62 @@ impl TransactionBuilder {
         self
     }
 
+    /// Lock `sats` in each Stamps output instead of the dust minimum
+    pub fn stamps_output_value(mut self, sats: u64) -> Self {
+        self.stamps_output_value = Some(sats);
+        self
+    }
+
+    /// Don't let the transaction be mined before a block height or time
+    /// (nLockTime)
+    pub fn lock_time(mut self, lock_time: LockTime) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Don't let the transaction be mined until every input has aged by a
+    /// number of blocks or 512-second intervals (BIP-68 sequence locks, as
+    /// checked by OP_CHECKSEQUENCEVERIFY)
+    pub fn relative_lock_time(mut self, lock_time: relative::LockTime) -> Self {
+        self.relative_lock_time = Some(lock_time);
+        self
+    }
+
+    /// Grind the first input's nSequence until the anchor prefix of the txid
+    /// starts with `pattern`, giving up after `budget`
+    ///
+    /// `pattern` is up to 16 hex digits matched against the 8-byte prefix
+    /// replies anchor to (`txid_to_prefix`, internal byte order, i.e. the
+    /// displayed txid read backwards byte by byte). Each extra digit takes
+    /// about 16 times longer to find.
+    ///
+    /// Only sequences with the same meaning as the default are tried: RBF
+    /// signaled, nLockTime enforced, no relative lock. Candidates are tried
+    /// in a fixed order, so the same builder always grinds to the same
+    /// transaction. The txid survives signing only for segwit inputs.
+    ///
+    /// Replies anchor to the transaction that carries the message. For the
+    /// Inscription and WitnessData carriers that is the reveal transaction,
+    /// which this builder doesn't build, so `build` fails for them rather
+    /// than grinding the commit transaction.
+    pub fn grind_prefix(mut self, pattern: &str, budget: Duration) -> Result<Self> {
+        if pattern.is_empty() || pattern.len() > MAX_GRIND_PATTERN {
+            return Err(WalletError::TransactionBuild(format!(
+                "Prefix pattern must be 1 to {} hex digits",
+                MAX_GRIND_PATTERN
+            )));
+        }
+        let nibbles = pattern
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| {
+                WalletError::TransactionBuild(format!("Prefix pattern is not hex: {}", pattern))
+            })?;
+        self.grind = Some((nibbles, budget));
+        Ok(self)
+    }
+
     /// Require permanent storage (uses Stamps carrier)
     pub fn permanent(mut self) -> Self {
         self.carrier = Some(CarrierType::Stamps);
@@ -152,7 +266,7 @@ impl TransactionBuilder {
 
     /// Build the unsigned transaction
     pub fn build(self) -> Result<AnchorTransaction> {
-        if self.inputs.is_empty() {
+        if self.inputs.is_empty() && self.candidates.is_empty() {
             return Err(WalletError::NoUtxos);
         }
 
@@ -160,7 +274,10 @@ impl TransactionBuilder {
         let message = self.build_message();
 
         // Select carrier
-        let selector = CarrierSelector::new();
+        let mut selector = CarrierSelector::new();
+        if let Some(value) = self.stamps_output_value {
+            selector = selector.with_carrier(StampsCarrier::new().with_output_value(value));
+        }
         let carrier_type = if let Some(ct) = self.carrier {
             ct
         } else {
@@ -192,30 +309,18 @@ impl TransactionBuilder {
             .map_err(|e| WalletError::TransactionBuild(format!("Carrier encode error: {}", e)))?;
 
         // Build transaction based on carrier type
-        self.build_with_carrier(message, carrier_type, carrier_output)
+        self.build_with_carrier(message, carrier, carrier_output)
     }
 
     /// Build transaction with a specific carrier output
     fn build_with_carrier(
         self,
         message: ParsedAnchorMessage,
-        carrier_type: CarrierType,
+        carrier: &dyn Carrier,
         carrier_output: CarrierOutput,
     ) -> Result<AnchorTransaction> {
-        // Calculate total input value
-        let total_input: u64 = self.inputs.iter().map(|(_, v)| v).sum();
-
-        // Build inputs
-        let tx_inputs: Vec<TxIn> = self
-            .inputs
-            .iter()
-            .map(|(outpoint, _)| TxIn {
-                previous_output: *outpoint,
-                script_sig: ScriptBuf::new(),
-                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
-                witness: Witness::new(),
-            })
-            .collect();
+        let carrier_type = carrier.info().carrier_type;
+        let utxo_cost = carrier.utxo_cost(encode_anchor_payload(&message).len());
 
         // Build outputs based on carrier
         let (mut outputs, carrier_data) = match carrier_output {
@@ -230,7 +335,7 @@ impl TransactionBuilder {
                 let outputs: Vec<TxOut> = scripts
                     .iter()
                     .map(|s| TxOut {
-                        value: Amount::from_sat(546), // Dust limit for each output
+                        value: Amount::from_sat(carrier.output_value(s)),
                         script_pubkey: s.clone(),
                     })
                     .collect();
@@ -263,50 +368,76 @@ impl TransactionBuilder {
                 let data = CarrierData::Annex { data: annex_data };
                 (outputs, Some(data))
             }
-            CarrierOutput::WitnessData { chunks, script } => {
+            CarrierOutput::WitnessData { chunks, scripts } => {
                 let outputs = vec![TxOut {
                     value: Amount::ZERO,
                     script_pubkey: create_anchor_script(&message),
                 }];
-                let data = CarrierData::WitnessData { chunks, script };
+                let data = CarrierData::WitnessData { chunks, scripts };
                 (outputs, Some(data))
             }
         };
 
-        // Estimate transaction size for fee calculation
-        let estimated_vsize = 10 + (self.inputs.len() * 68) + (outputs.len() * 34);
-        let fee = (estimated_vsize as f64 * self.fee_rate).ceil() as u64;
+        let outputs_value: u64 = outputs.iter().map(|o| o.value.to_sat()).sum();
+        let outputs_vsize: usize = outputs.iter().map(|o| o.size()).sum();
 
-        // For Stamps, we need to account for the dust outputs
-        let stamps_dust: u64 = if carrier_type == CarrierType::Stamps {
-            outputs.iter().map(|o| o.value.to_sat()).sum()
+        let inputs = if self.inputs.is_empty() {
+            self.select_inputs(outputs_value, outputs_vsize)?
         } else {
-            0
+            self.inputs.clone()
         };
+        let total_input: u64 = inputs.iter().map(|(_, v)| v).sum();
+        let (fee, change) = self.settle(total_input, inputs.len(), outputs_value, outputs_vsize)?;
 
-        // Add change output if we have enough and a change script
-        if let Some(change_script) = self.change_script {
-            let change_value = total_input.saturating_sub(fee + stamps_dust);
-
-            if change_value < 546 {
-                return Err(WalletError::InsufficientFunds {
-                    needed: fee + stamps_dust + 546,
-                    available: total_input,
-                });
-            }
-
+        if let (ChangeDecision::Change { value }, Some(change_script)) =
+            (change, self.change_script.clone())
+        {
             outputs.push(TxOut {
-                value: Amount::from_sat(change_value),
+                value: Amount::from_sat(value),
                 script_pubkey: change_script,
             });
         }
 
-        let transaction = Transaction {
+        // Sequence locks imply RBF; otherwise the default sequence already
+        // leaves nLockTime enforced
+        let sequence = self
+            .relative_lock_time
+            .map(|lock| lock.to_sequence())
+            .unwrap_or(Sequence::ENABLE_RBF_NO_LOCKTIME);
+        let tx_inputs: Vec<TxIn> = inputs
+            .iter()
+            .map(|(outpoint, _)| TxIn {
+                previous_output: *outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness: Witness::new(),
+            })
+            .collect();
+
+        let mut transaction = Transaction {
             version: Version::TWO,
-            lock_time: LockTime::ZERO,
+            lock_time: self.lock_time,
             input: tx_inputs,
             output: outputs,
         };
+        if let Some((pattern, budget)) = &self.grind {
+            if matches!(
+                carrier_type,
+                CarrierType::Inscription | CarrierType::WitnessData
+            ) {
+                return Err(WalletError::TransactionBuild(format!(
+                    "Prefix grinding isn't supported for the {:?} carrier: \
+                     the message is anchored in the reveal transaction",
+                    carrier_type
+                )));
+            }
+            if self.relative_lock_time.is_some() {
+                return Err(WalletError::TransactionBuild(
+                    "Prefix grinding can't be combined with a relative lock time".to_string(),
+                ));
+            }
+            grind_sequence(&mut transaction, pattern, *budget)?;
+        }
 
         Ok(AnchorTransaction {
             transaction,
@@ -315,8 +446,276 @@ impl TransactionBuilder {
             anchors: self.anchors,
             carrier: carrier_type,
             carrier_data,
+            fee,
+            change,
+            utxo_cost,
+        })
+    }
+
+    /// Fee for a transaction with `inputs` inputs and the given outputs
+    fn fee_for(&self, inputs: usize, outputs_vsize: usize, with_change: bool) -> u64 {
+        let mut vsize = TX_OVERHEAD_VSIZE + inputs * INPUT_VSIZE + outputs_vsize;
+        if with_change {
+            vsize += self.change_output_vsize();
+        }
+        (vsize as f64 * self.fee_rate).ceil() as u64
+    }
+
+    fn change_output_vsize(&self) -> usize {
+        self.change_script
+            .as_ref()
+            .map(|script| {
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: script.clone(),
+                }
+                .size()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Leftover value that may go to the fee rather than to a change output
+    fn tolerance(&self) -> u64 {
+        self.changeless_tolerance.unwrap_or_else(|| {
+            ((self.change_output_vsize() + INPUT_VSIZE) as f64 * self.fee_rate).ceil() as u64
         })
     }
+
+    /// Work out the fee and what happens to the leftover value
+    fn settle(
+        &self,
+        total_input: u64,
+        inputs: usize,
+        outputs_value: u64,
+        outputs_vsize: usize,
+    ) -> Result<(u64, ChangeDecision)> {
+        let base_fee = self.fee_for(inputs, outputs_vsize, false);
+        let needed = outputs_value + base_fee;
+        if total_input < needed {
+            return Err(WalletError::InsufficientFunds {
+                needed,
+                available: total_input,
+            });
+        }
+        let excess = total_input - needed;
+
+        // Without a change script everything left over is paid as fee
+        if self.change_script.is_none() || excess <= self.tolerance() {
+            return Ok((base_fee + excess, ChangeDecision::Changeless { excess }));
+        }
+
+        let fee = self.fee_for(inputs, outputs_vsize, true);
+        let change_value = total_input.saturating_sub(outputs_value + fee);
+        if change_value < DUST_LIMIT {
+            return Ok((
+                base_fee + excess,
+                ChangeDecision::DustToFee { amount: excess },
+            ));
+        }
+
+        Ok((
+            fee,
+            ChangeDecision::Change {
+                value: change_value,
+            },
+        ))
+    }
+
+    /// Pick inputs from the candidates
+    ///
+    /// Prefers a changeless selection within the tolerance; otherwise takes
+    /// the largest candidates until the outputs and fee are covered.
+    fn select_inputs(
+        &self,
+        outputs_value: u64,
+        outputs_vsize: usize,
+    ) -> Result<Vec<(OutPoint, u64)>> {
+        let mut candidates = self.candidates.clone();
+        candidates.sort_by_key(|(_, value)| std::cmp::Reverse(*value));
+
+        if let Some(selected) = self.find_changeless(&candidates, outputs_value, outputs_vsize) {
+            return Ok(selected);
+        }
+
+        let mut selected = Vec::new();
+        let mut total = 0u64;
+        for candidate in &candidates {
+            selected.push(*candidate);
+            total += candidate.1;
+            if total >= outputs_value + self.fee_for(selected.len(), outputs_vsize, true) {
+                return Ok(selected);
+            }
+        }
+
+        // Not enough for a change output; settle() decides whether the
+        // whole set still covers the outputs and fee
+        if total >= outputs_value + self.fee_for(selected.len(), outputs_vsize, false) {
+            return Ok(selected);
+        }
+        Err(WalletError::InsufficientFunds {
+            needed: outputs_value + self.fee_for(selected.len(), outputs_vsize, false),
+            available: total,
+        })
+    }
+
+    /// Depth-first search for a subset whose excess over outputs and fee is
+    /// within the tolerance, keeping the one that wastes the least
+    fn find_changeless(
+        &self,
+        candidates: &[(OutPoint, u64)],
+        outputs_value: u64,
+        outputs_vsize: usize,
+    ) -> Option<Vec<(OutPoint, u64)>> {
+        self.change_script.as_ref()?;
+
+        let input_fee = (INPUT_VSIZE as f64 * self.fee_rate).ceil() as u64;
+        // Candidates worth less than the fee to spend them never help
+        let usable: Vec<(OutPoint, u64)> = candidates
+            .iter()
+            .filter(|(_, value)| *value > input_fee)
+            .copied()
+            .collect();
+        let effective: Vec<u64> = usable.iter().map(|(_, v)| v - input_fee).collect();
+        let mut remaining: Vec<u64> = vec![0; effective.len() + 1];
+        for i in (0..effective.len()).rev() {
+            remaining[i] = remaining[i + 1] + effective[i];
+        }
+
+        let target = outputs_value + self.fee_for(0, outputs_vsize, false);
+        let upper = target + self.tolerance();
+
+        let mut best: Option<(u64, Vec<usize>)> = None;
+        let mut current = Vec::new();
+        let mut tries = 0;
+        search(
+            &effective,
+            &remaining,
+            0,
+            0,
+            target,
+            upper,
+            &mut current,
+            &mut best,
+            &mut tries,
+        );
+
+        let (_, indices) = best?;
+        let selected: Vec<(OutPoint, u64)> = indices.iter().map(|&i| usable[i]).collect();
+        let total: u64 = selected.iter().map(|(_, v)| v).sum();
+        match self.settle(total, selected.len(), outputs_value, outputs_vsize) {
+            Ok((_, ChangeDecision::Changeless { .. })) => Some(selected),
+            _ => None,
+        }
+    }
+}
+
+/// Vary the first input's nSequence until the anchor prefix matches
+///
+/// Tries `0xFFFFFFFD` (the default) downwards to `0x80000000`: the disable
+/// flag keeps relative locks off, and everything below `0xFFFFFFFE` still
+/// signals RBF and enforces nLockTime.
+fn grind_sequence(transaction: &mut Transaction, pattern: &[u8], budget: Duration) -> Result<()> {
+    let first = transaction.input.first().ok_or(WalletError::NoUtxos)?;
+    // The sequence follows the version, input count, outpoint and scriptSig
+    let offset = 4
+        + serialize(&VarInt(transaction.input.len() as u64)).len()
+        + 36
+        + serialize(&first.script_sig).len();
+
+    // Witnesses are still empty, so this is the txid serialization
+    let mut bytes = serialize(&*transaction);
+    let started = Instant::now();
+    let mut sequence = Sequence::ENABLE_RBF_NO_LOCKTIME.0;
+    let mut tries = 0u32;
+    loop {
+        bytes[offset..offset + 4].copy_from_slice(&sequence.to_le_bytes());
+        let txid = Txid::from_raw_hash(sha256d::Hash::hash(&bytes));
+        if prefix_matches(&txid, pattern) {
+            transaction.input[0].sequence = Sequence(sequence);
+            return Ok(());
+        }
+
+        tries += 1;
+        if sequence == MIN_GRIND_SEQUENCE
+            || (tries.is_multiple_of(GRIND_BATCH) && started.elapsed() >= budget)
+        {
+            return Err(WalletError::TransactionBuild(format!(
+                "No txid with anchor prefix {} found within {:?}",
+                pattern
+                    .iter()
+                    .map(|n| format!("{:x}", n))
+                    .collect::<String>(),
+                budget
+            )));
+        }
+        sequence -= 1;
+    }
+}
+
+/// Whether the anchor prefix of `txid` starts with the `pattern` nibbles
+fn prefix_matches(txid: &Txid, pattern: &[u8]) -> bool {
+    let bytes = txid.as_byte_array();
+    pattern.iter().enumerate().all(|(i, &nibble)| {
+        let byte = bytes[i / 2];
+        let actual = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+        actual == nibble
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search(
+    effective: &[u64],
+    remaining: &[u64],
+    index: usize,
+    sum: u64,
+    target: u64,
+    upper: u64,
+    current: &mut Vec<usize>,
+    best: &mut Option<(u64, Vec<usize>)>,
+    tries: &mut usize,
+) {
+    *tries += 1;
+    if *tries > MAX_SELECTION_TRIES || sum > upper {
+        return;
+    }
+    if sum >= target {
+        let waste = sum - target;
+        if best.as_ref().is_none_or(|(w, _)| waste < *w) {
+            *best = Some((waste, current.clone()));
+        }
+        return;
+    }
+    if index == effective.len() || sum + remaining[index] < target {
+        return;
+    }
+
+    current.push(index);
+    search(
+        effective,
+        remaining,
+        index + 1,
+        sum + effective[index],
+        target,
+        upper,
+        current,
+        best,
+        tries,
+    );
+    current.pop();
+    if best.as_ref().is_some_and(|(waste, _)| *waste == 0) {
+        return;
+    }
+    search(
+        effective,
+        remaining,
+        index + 1,
+        sum,
+        target,
+        upper,
+        current,
+        best,
+        tries,
+    );
 }
 
 impl Default for TransactionBuilder {
@@ -328,6 +727,7 @@ impl Default for TransactionBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bitcoin::hashes::Hash;
 
     #[test]
     fn test_build_payload() {
@@ -349,4 +749,204 @@ mod tests {
         let result = builder.build_payload();
         assert!(matches!(result, Err(WalletError::MessageTooLarge { .. })));
     }
+
+    fn txid(n: u8) -> Txid {
+        format!("{:064x}", n).parse().unwrap()
+    }
+
+    fn p2wpkh() -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([7; 20]))
+    }
+
+    #[test]
+    fn test_change_output() {
+        let tx = TransactionBuilder::new()
+            .body_text("hi")
+            .input(txid(1), 0, 100_000)
+            .change_script(p2wpkh())
+            .build()
+            .unwrap();
+
+        let ChangeDecision::Change { value } = tx.change else {
+            panic!("expected change, got {:?}", tx.change);
+        };
+        assert_eq!(tx.transaction.output.len(), 2);
+        assert_eq!(tx.transaction.output[1].value.to_sat(), value);
+        assert_eq!(value + tx.fee, 100_000);
+    }
+
+    #[test]
+    fn test_dust_change_goes_to_fee() {
+        let tx = TransactionBuilder::new()
+            .body_text("hi")
+            .input(txid(1), 0, 600)
+            .change_script(p2wpkh())
+            .changeless_tolerance(0)
+            .build()
+            .unwrap();
+
+        assert!(matches!(tx.change, ChangeDecision::DustToFee { .. }));
+        assert_eq!(tx.transaction.output.len(), 1);
+        assert_eq!(tx.fee, 600);
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let result = TransactionBuilder::new()
+            .body_text("hi")
+            .input(txid(1), 0, 50)
+            .change_script(p2wpkh())
+            .build();
+
+        assert!(matches!(result, Err(WalletError::InsufficientFunds { .. })));
+    }
+
+    #[test]
+    fn test_selects_changeless_subset() {
+        // One OP_RETURN output with a two byte body plus one input costs
+        // well under 200 sats at 1 sat/vB
+        let tx = TransactionBuilder::new()
+            .body_text("hi")
+            .candidate(txid(1), 0, 50_000)
+            .candidate(txid(2), 0, 150)
+            .candidate(txid(3), 0, 10_000)
+            .change_script(p2wpkh())
+            .build()
+            .unwrap();
+
+        assert!(matches!(tx.change, ChangeDecision::Changeless { .. }));
+        assert_eq!(tx.transaction.input.len(), 1);
+        assert_eq!(tx.transaction.input[0].previous_output.txid, txid(2));
+        assert_eq!(tx.transaction.output.len(), 1);
+        assert_eq!(tx.fee, 150);
+    }
+
+    #[test]
+    fn test_falls_back_to_largest_first() {
+        let tx = TransactionBuilder::new()
+            .body_text("hi")
+            .candidate(txid(1), 0, 20_000)
+            .candidate(txid(2), 0, 50_000)
+            .change_script(p2wpkh())
+            .build()
+            .unwrap();
+
+        assert!(tx.change.has_change());
+        assert_eq!(tx.transaction.input.len(), 1);
+        assert_eq!(tx.transaction.input[0].previous_output.txid, txid(2));
+    }
+
+    #[test]
+    fn test_absolute_lock_time() {
+        let lock_time = LockTime::from_height(800_000).unwrap();
+        let tx = TransactionBuilder::new()
+            .body_text("later")
+            .input(txid(1), 0, 100_000)
+            .lock_time(lock_time)
+            .build()
+            .unwrap();
+
+        assert_eq!(tx.transaction.lock_time, lock_time);
+        assert!(tx.transaction.is_lock_time_enabled());
+    }
+
+    #[test]
+    fn test_relative_lock_time() {
+        let tx = TransactionBuilder::new()
+            .body_text("later")
+            .input(txid(1), 0, 50_000)
+            .input(txid(2), 1, 50_000)
+            .relative_lock_time(relative::LockTime::from_height(144))
+            .build()
+            .unwrap();
+
+        assert_eq!(tx.transaction.lock_time, LockTime::ZERO);
+        for input in &tx.transaction.input {
+            assert_eq!(
+                input.sequence.to_relative_lock_time(),
+                Some(relative::LockTime::from_height(144))
+            );
+        }
+    }
+
+    #[test]
+    fn test_stamps_utxo_cost() {
+        let tx = TransactionBuilder::new()
+            .body_text("permanent")
+            .permanent()
+            .stamps_output_value(1_000)
+            .input(txid(1), 0, 100_000)
+            .change_script(p2wpkh())
+            .build()
+            .unwrap();
+
+        let stamps = tx.transaction.output.len() - 1;
+        assert_eq!(tx.utxo_cost.outputs, stamps);
+        assert_eq!(tx.utxo_cost.value, 1_000 * stamps as u64);
+        assert!(tx.transaction.output[..stamps]
+            .iter()
+            .all(|o| o.value.to_sat() == 1_000));
+    }
+
+    #[test]
+    fn test_grind_prefix() {
+        let build = || {
+            TransactionBuilder::new()
+                .body_text("vanity")
+                .input(txid(1), 0, 50_000)
+                .change_script(p2wpkh())
+                .grind_prefix("A1c", Duration::from_secs(30))
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+
+        let tx = build();
+        let prefix = hex::encode(anchor_core::txid_to_prefix(&tx.txid()));
+        assert!(prefix.starts_with("a1c"));
+        // Same meaning as the default sequence, and the same result every time
+        let sequence = tx.transaction.input[0].sequence;
+        assert!(sequence.is_rbf() && sequence.enables_absolute_lock_time());
+        assert_eq!(sequence.to_relative_lock_time(), None);
+        assert_eq!(build().txid(), tx.txid());
+    }
+
+    #[test]
+    fn test_grind_prefix_errors() {
+        let builder = || TransactionBuilder::new().input(txid(1), 0, 50_000);
+        assert!(builder().grind_prefix("xyz", Duration::ZERO).is_err());
+        assert!(builder().grind_prefix("", Duration::ZERO).is_err());
+        assert!(builder()
+            .grind_prefix("00000000000000000", Duration::ZERO)
+            .is_err());
+
+        // Out of budget
+        assert!(builder()
+            .grind_prefix("0123456789abcdef", Duration::ZERO)
+            .unwrap()
+            .build()
+            .is_err());
+        assert!(builder()
+            .relative_lock_time(relative::LockTime::from_height(6))
+            .grind_prefix("a", Duration::from_secs(1))
+            .unwrap()
+            .build()
+            .is_err());
+
+        // The commit transaction isn't the one replies anchor to
+        for carrier in [CarrierType::Inscription, CarrierType::WitnessData] {
+            assert!(builder()
+                .body_text("vanity")
+                .carrier(carrier)
+                .build()
+                .is_ok());
+            assert!(builder()
+                .body_text("vanity")
+                .carrier(carrier)
+                .grind_prefix("a", Duration::from_secs(1))
+                .unwrap()
+                .build()
+                .is_err());
+        }
+    }
 }
@@ -3,5 +3,5 @@
 mod anchor_tx;
 mod builder;
 
-pub use anchor_tx::{AnchorTransaction, CarrierData};
-pub use builder::{TransactionBuilder, MAX_OP_RETURN_SIZE};
+pub use anchor_tx::{AnchorTransaction, CarrierData, ChangeDecision};
+pub use builder::{TransactionBuilder, DUST_LIMIT, MAX_OP_RETURN_SIZE};
@@ -17,13 +17,33 @@ pub struct Utxo {
     pub confirmations: u32,
 }
 
-/// Wallet balance
-#[derive(Debug, Clone, Default)]
+/// Wallet balance, broken down by what can actually be spent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Balance {
-    /// Confirmed balance in satoshis
+    /// Confirmed balance in satoshis, including own unconfirmed change
     pub confirmed: u64,
-    /// Unconfirmed balance in satoshis
+    /// Unconfirmed incoming balance in satoshis
     pub unconfirmed: u64,
+    /// Coinbase outputs that have not matured yet, in satoshis
+    pub immature: u64,
+    /// Confirmed balance held in locked UTXOs (e.g. ones carrying assets), in satoshis
+    pub locked: u64,
+    /// Confirmed balance available for new transactions, in satoshis
+    pub spendable: u64,
     /// Total balance in satoshis
     pub total: u64,
 }
+
+impl Balance {
+    /// Build a balance, deriving `spendable` and `total` from the parts
+    pub fn new(confirmed: u64, unconfirmed: u64, immature: u64, locked: u64) -> Self {
+        Self {
+            confirmed,
+            unconfirmed,
+            immature,
+            locked,
+            spendable: confirmed.saturating_sub(locked),
+            total: confirmed + unconfirmed + immature,
+        }
+    }
+}
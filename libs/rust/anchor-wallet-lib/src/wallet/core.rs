@@ -1,6 +1,6 @@
 //! Core wallet implementation
 
-use bitcoin::{Address, Network};
+use bitcoin::{Address, Network, Txid};
 use bitcoincore_rpc::{Auth, Client, RpcApi};
 
 use crate::config::WalletConfig;
@@ -50,17 +50,35 @@ impl AnchorWallet {
     }
 
     /// Get wallet balance
+    ///
+    /// `locked` counts outputs locked with `lockunspent`, which Bitcoin Core
+    /// keeps in its trusted balance but never spends.
     pub fn get_balance(&self) -> Result<Balance> {
         let balances = self.client.get_balances()?;
 
-        let confirmed = balances.mine.trusted.to_sat();
-        let unconfirmed = balances.mine.untrusted_pending.to_sat();
-
-        Ok(Balance {
-            confirmed,
-            unconfirmed,
-            total: confirmed + unconfirmed,
-        })
+        #[derive(serde::Deserialize)]
+        struct LockedOutPoint {
+            txid: Txid,
+            vout: u32,
+        }
+
+        let mut locked = 0;
+        let outpoints: Vec<LockedOutPoint> = self.client.call("listlockunspent", &[])?;
+        for outpoint in outpoints {
+            if let Some(txout) =
+                self.client
+                    .get_tx_out(&outpoint.txid, outpoint.vout, Some(false))?
+            {
+                locked += txout.value.to_sat();
+            }
+        }
+
+        Ok(Balance::new(
+            balances.mine.trusted.to_sat(),
+            balances.mine.untrusted_pending.to_sat(),
+            balances.mine.immature.to_sat(),
+            locked,
+        ))
     }
 
     /// Get a new receiving address
@@ -106,4 +124,14 @@ mod tests {
         let config = WalletConfig::new("http://localhost:18443", "user", "pass");
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_balance_breakdown() {
+        let balance = Balance::new(100_000, 20_000, 5_000_000_000, 546);
+        assert_eq!(balance.spendable, 99_454);
+        assert_eq!(balance.total, 5_000_120_000);
+
+        // Locks on unconfirmed outputs never make spendable negative
+        assert_eq!(Balance::new(300, 0, 0, 1_000).spendable, 0);
+    }
 }
@@ -1,7 +1,11 @@
 //! Message creation methods for the wallet
 
-use anchor_core::carrier::CarrierType;
-use anchor_core::AnchorKind;
+use anchor_core::carrier::{CarrierSelector, CarrierType};
+use anchor_core::{parse_transaction, AnchorKind};
+use anchor_specs::identity::IdentityRotationSpec;
+use anchor_specs::mirror::MirrorSpec;
+use anchor_specs::KindSpec;
+use bitcoin::secp256k1::{Keypair, XOnlyPublicKey};
 use bitcoin::Txid;
 
 use super::core::AnchorWallet;
@@ -58,46 +62,9 @@ impl AnchorWallet {
         anchors: &[(Txid, u8)],
         carrier: Option<CarrierType>,
     ) -> Result<Txid> {
-        // Get UTXOs
-        let utxos = self.list_utxos()?;
-        if utxos.is_empty() {
-            return Err(WalletError::NoUtxos);
-        }
-
-        // Get change address
-        let change_address = self.get_new_address()?;
-        let change_address = change_address.assume_checked();
-
-        // Build transaction
-        let mut builder = TransactionBuilder::new()
-            .kind(kind)
-            .body_bytes(body.to_vec())
-            .fee_rate(self.config.fee_rate)
-            .change_script(change_address.script_pubkey());
-
-        // Set carrier if specified
-        if let Some(ct) = carrier {
-            builder = builder.carrier(ct);
-        }
-
-        // Add anchors
-        for (txid, vout) in anchors {
-            builder = builder.anchor(*txid, *vout);
-        }
-
-        // Add inputs - for Stamps, we need more inputs due to dust outputs
-        let required_inputs = if carrier == Some(CarrierType::Stamps) {
-            2 // Stamps needs more funds
-        } else {
-            1
-        };
-
-        for utxo in utxos.iter().take(required_inputs.min(utxos.len())) {
-            builder = builder.input(utxo.txid, utxo.vout, utxo.amount);
-        }
-
-        // Build the transaction
-        let anchor_tx = builder.build()?;
+        let anchor_tx = self
+            .message_builder(kind, body, anchors, carrier)?
+            .build()?;
 
         // Sign and broadcast
         let txid = self.sign_and_broadcast(&anchor_tx)?;
@@ -132,6 +99,93 @@ impl AnchorWallet {
         )
     }
 
+    /// Hand the identity held by `old_keypair` over to `new_pubkey`
+    ///
+    /// Publishes an Identity rotation signed by the old key, so indexers can
+    /// attribute the new key's messages to the same author. Pass the previous
+    /// rotation in the chain as `previous` to anchor it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let txid = wallet.create_identity_rotation(
+    ///     &old_keypair,
+    ///     new_keypair.x_only_public_key().0,
+    ///     Some("bcrt1q..."),
+    ///     None,
+    /// )?;
+    /// ```
+    pub fn create_identity_rotation(
+        &self,
+        old_keypair: &Keypair,
+        new_pubkey: XOnlyPublicKey,
+        new_address: Option<&str>,
+        previous: Option<(Txid, u8)>,
+    ) -> Result<Txid> {
+        let spec =
+            IdentityRotationSpec::signed(old_keypair, new_pubkey, new_address.map(str::to_string))
+                .validated()
+                .map_err(|e| WalletError::TransactionBuild(e.to_string()))?;
+
+        let anchors: Vec<(Txid, u8)> = previous.into_iter().collect();
+        self.create_message(AnchorKind::Identity, &spec.to_bytes(), &anchors)
+    }
+
+    /// Repost the message at `txid:vout` on another carrier
+    ///
+    /// Decodes the original message from its transaction and publishes its
+    /// kind and body as a [`MirrorSpec`] anchored to it, so indexers link
+    /// the copy to the original as a duplicate. Reposting on
+    /// [`CarrierType::Stamps`] keeps content that was carried in prunable
+    /// witness data or OP_RETURN available for good.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mirror_txid = wallet.repost(&txid, 0, CarrierType::Stamps)?;
+    /// ```
+    pub fn repost(&self, txid: &Txid, vout: u8, new_carrier: CarrierType) -> Result<Txid> {
+        let tx = self.get_raw_transaction(txid)?;
+
+        // Same detection as the indexer, legacy OP_RETURN parsing included
+        let mut detected: Vec<(u32, CarrierType, _)> = CarrierSelector::new()
+            .detect(&tx)
+            .into_iter()
+            .map(|d| (d.vout, d.carrier_type, d.message))
+            .collect();
+        if detected.is_empty() {
+            detected = parse_transaction(&tx)
+                .into_iter()
+                .map(|(vout, message)| (vout, CarrierType::OpReturn, message))
+                .collect();
+        }
+        let (_, carrier, message) = detected
+            .into_iter()
+            .find(|(found, _, _)| *found == vout as u32)
+            .ok_or_else(|| {
+                WalletError::TransactionBuild(format!("No ANCHOR message at {}:{}", txid, vout))
+            })?;
+
+        if carrier == new_carrier {
+            return Err(WalletError::TransactionBuild(format!(
+                "{}:{} is already carried by {}",
+                txid, vout, new_carrier
+            )));
+        }
+        MirrorSpec::validate_carrier(new_carrier)
+            .map_err(|e| WalletError::TransactionBuild(e.to_string()))?;
+        let spec = MirrorSpec::new(u8::from(message.kind), message.body)
+            .validated()
+            .map_err(|e| WalletError::TransactionBuild(e.to_string()))?;
+
+        self.create_message_with_carrier(
+            AnchorKind::Mirror,
+            &spec.to_bytes(),
+            &[(*txid, vout)],
+            Some(new_carrier),
+        )
+    }
+
     /// Build an unsigned ANCHOR transaction
     ///
     /// Use this for custom signing flows (hardware wallets, etc.)
@@ -141,6 +195,35 @@ impl AnchorWallet {
         body: &[u8],
         anchors: &[(Txid, u8)],
     ) -> Result<AnchorTransaction> {
+        self.message_builder(kind, body, anchors, None)?.build()
+    }
+
+    /// Dry run of [`create_message_with_carrier`](Self::create_message_with_carrier)
+    ///
+    /// Returns the transaction that would be signed and broadcast, without
+    /// doing either. `fee` and `change` on the result report the fee and
+    /// whether leftover value became change, went to the fee as dust, or was
+    /// absorbed by a changeless selection. `utxo_cost` shows the outputs a
+    /// Stamps message would leave in the UTXO set for good.
+    pub fn simulate_message(
+        &self,
+        kind: AnchorKind,
+        body: &[u8],
+        anchors: &[(Txid, u8)],
+        carrier: Option<CarrierType>,
+    ) -> Result<AnchorTransaction> {
+        self.message_builder(kind, body, anchors, carrier)?.build()
+    }
+
+    /// Transaction builder for a message, with every wallet UTXO as a
+    /// selection candidate
+    fn message_builder(
+        &self,
+        kind: AnchorKind,
+        body: &[u8],
+        anchors: &[(Txid, u8)],
+        carrier: Option<CarrierType>,
+    ) -> Result<TransactionBuilder> {
         let utxos = self.list_utxos()?;
         if utxos.is_empty() {
             return Err(WalletError::NoUtxos);
@@ -155,13 +238,18 @@ impl AnchorWallet {
             .fee_rate(self.config.fee_rate)
             .change_script(change_address.script_pubkey());
 
+        if let Some(ct) = carrier {
+            builder = builder.carrier(ct);
+        }
+
         for (txid, vout) in anchors {
             builder = builder.anchor(*txid, *vout);
         }
 
-        let utxo = &utxos[0];
-        builder = builder.input(utxo.txid, utxo.vout, utxo.amount);
+        for utxo in &utxos {
+            builder = builder.candidate(utxo.txid, utxo.vout, utxo.amount);
+        }
 
-        builder.build()
+        Ok(builder)
     }
 }
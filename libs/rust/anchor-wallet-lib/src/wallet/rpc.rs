@@ -1,15 +1,39 @@
 //! RPC methods for the wallet
 
-use bitcoin::{Amount, Txid};
+use anchor_core::carrier::verify_carrier_roundtrip;
+use bitcoin::{Amount, Transaction, Txid};
 use bitcoincore_rpc::RpcApi;
 
 use super::core::AnchorWallet;
 use crate::error::{Result, WalletError};
+use crate::rescan::{AnchorFilterClient, AnchorFilterScan};
 use crate::transaction::AnchorTransaction;
 
 impl AnchorWallet {
     /// Sign and broadcast a transaction
     pub fn sign_and_broadcast(&self, anchor_tx: &AnchorTransaction) -> Result<Txid> {
+        let signed = self.sign_transaction(anchor_tx)?;
+        let txid = self.client.send_raw_transaction(&signed)?;
+        Ok(txid)
+    }
+
+    /// Sign a transaction with the node's wallet without broadcasting it,
+    /// e.g. to hand it to a [`BroadcastScheduler`](crate::BroadcastScheduler)
+    pub fn sign_transaction(&self, anchor_tx: &AnchorTransaction) -> Result<Transaction> {
+        // Refuse to broadcast anything indexers would read differently
+        let verified = verify_carrier_roundtrip(&anchor_tx.transaction);
+        if verified.is_empty() {
+            return Err(WalletError::TransactionBuild(
+                "Transaction carries no decodable ANCHOR message".to_string(),
+            ));
+        }
+        if let Some(conflict) = verified.iter().find(|m| !m.is_consistent()) {
+            return Err(WalletError::TransactionBuild(format!(
+                "{} payload at {:?} decodes differently via {:?}",
+                conflict.carrier_type, conflict.location, conflict.conflicts
+            )));
+        }
+
         let hex = anchor_tx.to_hex();
 
         // Sign the transaction
@@ -23,11 +47,9 @@ impl AnchorWallet {
             ));
         }
 
-        // Broadcast - signed.hex is the signed transaction bytes
-        let signed_hex = ::hex::encode(&signed.hex);
-        let txid = self.client.send_raw_transaction(signed_hex.as_str())?;
-
-        Ok(txid)
+        signed
+            .transaction()
+            .map_err(|e| WalletError::Serialization(e.to_string()))
     }
 
     /// Broadcast a raw transaction hex
@@ -59,6 +81,15 @@ impl AnchorWallet {
         Ok(tx)
     }
 
+    /// Find the wallet's ANCHOR history through an explorer's filters,
+    /// fetching only the matching blocks from the node
+    ///
+    /// Unlike `rescanblockchain`, blocks whose filter doesn't match the
+    /// client's scripts are never read.
+    pub fn rescan_anchor_activity(&self, filters: &AnchorFilterClient) -> Result<AnchorFilterScan> {
+        filters.scan(|hash| Ok(self.client.get_block(hash)?))
+    }
+
     /// Get blockchain info
     pub fn get_blockchain_info(&self) -> Result<bitcoincore_rpc::json::GetBlockchainInfoResult> {
         let info = self.client.get_blockchain_info()?;
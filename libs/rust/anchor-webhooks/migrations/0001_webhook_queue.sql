@@ -0,0 +1,37 @@
+-- Migration: Webhook Delivery Queue
+-- Creates an app's webhook delivery log, which doubles as the retry queue
+-- and the source for replays. Apps call it after creating their webhooks
+-- table, then add the columns that record what triggered a delivery:
+--
+--   SELECT anchor_webhook_deliveries('token_webhook_deliveries', 'token_webhooks');
+
+CREATE OR REPLACE FUNCTION anchor_webhook_deliveries(deliveries TEXT, webhooks TEXT)
+RETURNS VOID AS $$
+BEGIN
+    EXECUTE format($sql$
+        CREATE TABLE IF NOT EXISTS %1$I (
+            id BIGSERIAL PRIMARY KEY,
+            webhook_id INTEGER NOT NULL REFERENCES %2$I(id) ON DELETE CASCADE,
+            event VARCHAR(32) NOT NULL,
+            payload JSONB NOT NULL,
+            status VARCHAR(16) NOT NULL DEFAULT 'pending'
+                CHECK (status IN ('pending', 'delivered', 'failed')),
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+            response_status INTEGER,
+            last_error TEXT,
+            created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+            delivered_at TIMESTAMP WITH TIME ZONE
+        )
+    $sql$, deliveries, webhooks);
+
+    EXECUTE format(
+        'CREATE INDEX IF NOT EXISTS %I ON %I(next_attempt_at) WHERE status = ''pending''',
+        'idx_' || deliveries || '_due', deliveries
+    );
+    EXECUTE format(
+        'CREATE INDEX IF NOT EXISTS %I ON %I(webhook_id, id DESC)',
+        'idx_' || deliveries || '_webhook', deliveries
+    );
+END;
+$$ LANGUAGE plpgsql;
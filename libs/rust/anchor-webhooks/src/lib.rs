@@ -0,0 +1,214 @@
+//! # ANCHOR Webhooks
+//!
+//! Signed webhook delivery for app backends.
+//!
+//! An app queues a delivery in its deliveries table when something a webhook
+//! watches changes; [`start`] runs a background task that POSTs queued
+//! deliveries to the webhook's URL, retrying with backoff until the endpoint
+//! answers with a 2xx or the attempts run out.
+//!
+//! Deliveries are signed: `X-Anchor-Signature` is `sha256=` followed by the
+//! hex HMAC-SHA256 of `{X-Anchor-Timestamp}.{body}` under the webhook's
+//! secret. `X-Anchor-Delivery` stays the same across retries and replays,
+//! so receivers can drop duplicates.
+//!
+//! ```ignore
+//! let queue = WebhookQueue::new(pool, "token_webhooks", "token_webhook_deliveries");
+//! anchor_webhooks::start(queue);
+//! ```
+
+mod queue;
+
+pub use queue::{DueDelivery, WebhookDeliveryRow, WebhookQueue};
+
+use std::time::Duration;
+
+use bitcoin::hashes::{hmac, sha256, Hash, HashEngine};
+use rand::RngCore;
+use tracing::{debug, info, warn};
+
+/// Deliveries claimed per poll
+const BATCH_SIZE: i64 = 50;
+
+/// How long a claimed delivery is held before another poll may retry it
+const LEASE_SECS: i64 = 300;
+
+/// Longest wait between retries
+const MAX_RETRY_SECS: i64 = 3600;
+
+/// Random signing secret (32 bytes, hex)
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Check that a webhook URL is an absolute http(s) URL
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    match parsed.scheme() {
+        "http" | "https" if parsed.host_str().is_some() => Ok(()),
+        _ => Err("Webhook URL must be an http or https URL".to_string()),
+    }
+}
+
+/// Hex HMAC-SHA256 of `data` under `key`
+fn hmac_hex(key: &[u8], data: &[u8]) -> String {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(key);
+    engine.input(data);
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_string()
+}
+
+/// Value of the `X-Anchor-Signature` header
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(body);
+    format!("sha256={}", hmac_hex(secret.as_bytes(), &signed))
+}
+
+/// Wait before the next attempt after `attempts` failed ones
+fn retry_delay(attempts: i32) -> i64 {
+    30i64
+        .saturating_mul(1 << attempts.clamp(0, 20))
+        .min(MAX_RETRY_SECS)
+}
+
+/// Start the background task that sends queued deliveries
+pub fn start(queue: WebhookQueue) {
+    let poll_secs = std::env::var("WEBHOOK_POLL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(2);
+    let max_attempts: i32 = std::env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(8);
+
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Webhook delivery disabled: {}", e);
+            return;
+        }
+    };
+
+    info!("Starting webhook delivery (poll every {}s)", poll_secs);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(poll_secs));
+        loop {
+            ticker.tick().await;
+            match queue.claim_due(BATCH_SIZE, LEASE_SECS).await {
+                Ok(due) => {
+                    for delivery in due {
+                        deliver(&queue, &client, delivery, max_attempts).await;
+                    }
+                }
+                Err(e) => warn!("Failed to load webhook deliveries: {}", e),
+            }
+        }
+    });
+}
+
+/// POST one delivery and record the outcome
+async fn deliver(
+    queue: &WebhookQueue,
+    client: &reqwest::Client,
+    delivery: DueDelivery,
+    max_attempts: i32,
+) {
+    let body = serde_json::json!({
+        "id": delivery.id,
+        "event": delivery.event,
+        "createdAt": delivery.created_at,
+        "data": delivery.payload.0,
+    })
+    .to_string();
+    let timestamp = chrono::Utc::now().timestamp();
+
+    let result = client
+        .post(&delivery.url)
+        .header("Content-Type", "application/json")
+        .header("X-Anchor-Event", &delivery.event)
+        .header("X-Anchor-Delivery", delivery.id.to_string())
+        .header("X-Anchor-Timestamp", timestamp.to_string())
+        .header(
+            "X-Anchor-Signature",
+            signature(&delivery.secret, timestamp, body.as_bytes()),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    let (status, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            debug!("Delivered webhook event {}", delivery.id);
+            let status = response.status().as_u16() as i32;
+            if let Err(e) = queue.mark_delivered(delivery.id, status).await {
+                warn!("Failed to record webhook delivery {}: {}", delivery.id, e);
+            }
+            return;
+        }
+        Ok(response) => (
+            Some(response.status().as_u16() as i32),
+            format!("Endpoint answered {}", response.status()),
+        ),
+        Err(e) => (None, e.to_string()),
+    };
+
+    let attempts = delivery.attempts + 1;
+    let retry = (attempts < max_attempts).then(|| retry_delay(attempts));
+    if retry.is_none() {
+        warn!(
+            "Giving up on webhook delivery {} after {} attempts: {}",
+            delivery.id, attempts, error
+        );
+    }
+    if let Err(e) = queue
+        .mark_attempt_failed(delivery.id, status, &error, retry)
+        .await
+    {
+        warn!("Failed to record webhook attempt {}: {}", delivery.id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_matches_rfc4231() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            hmac_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp() {
+        let body = br#"{"id":1}"#;
+        let sig = signature("secret", 1_700_000_000, body);
+        assert!(sig.starts_with("sha256="));
+        assert_eq!(sig, signature("secret", 1_700_000_000, body));
+        assert_ne!(sig, signature("secret", 1_700_000_001, body));
+        assert_ne!(sig, signature("other", 1_700_000_000, body));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), 60);
+        assert_eq!(retry_delay(3), 240);
+        assert_eq!(retry_delay(30), MAX_RETRY_SECS);
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com/hooks/anchor").is_ok());
+        assert!(validate_url("http://localhost:8080").is_ok());
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("example.com").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Delivery queue tables
+//!
+//! Every app keeps its own webhooks table, with whatever the webhooks watch,
+//! and a deliveries table created by `anchor_webhook_deliveries()` (see
+//! `migrations/0001_webhook_queue.sql`). [`WebhookQueue`] runs the queue
+//! queries against one such pair.
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+
+/// Delivery from a webhook's log
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookDeliveryRow {
+    pub id: i64,
+    pub event: String,
+    pub payload: sqlx::types::Json<serde_json::Value>,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Delivery claimed for sending, with its webhook's endpoint
+#[derive(Debug, Clone, FromRow)]
+pub struct DueDelivery {
+    pub id: i64,
+    pub event: String,
+    pub payload: sqlx::types::Json<serde_json::Value>,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Queue queries over an app's webhooks and deliveries tables
+#[derive(Clone)]
+pub struct WebhookQueue {
+    pool: PgPool,
+    webhooks: &'static str,
+    deliveries: &'static str,
+}
+
+impl WebhookQueue {
+    /// Queue over `deliveries`, whose rows belong to webhooks in `webhooks`
+    pub fn new(pool: PgPool, webhooks: &'static str, deliveries: &'static str) -> Self {
+        Self {
+            pool,
+            webhooks,
+            deliveries,
+        }
+    }
+
+    /// A webhook's most recent deliveries
+    pub async fn deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+    ) -> sqlx::Result<Vec<WebhookDeliveryRow>> {
+        sqlx::query_as::<_, WebhookDeliveryRow>(&format!(
+            r#"
+            SELECT id, event, payload, status, attempts, response_status,
+                   last_error, created_at, delivered_at
+            FROM {}
+            WHERE webhook_id = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+            self.deliveries
+        ))
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Queue a webhook's deliveries again
+    ///
+    /// Replays one delivery, or every delivery created at or after `since`.
+    /// Returns how many were queued.
+    pub async fn replay(
+        &self,
+        webhook_id: i32,
+        delivery_id: Option<i64>,
+        since: Option<DateTime<Utc>>,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET status = 'pending', attempts = 0, next_attempt_at = NOW(), last_error = NULL
+            WHERE webhook_id = $1
+              AND ($2::bigint IS NULL OR id = $2)
+              AND ($3::timestamptz IS NULL OR created_at >= $3)
+            "#,
+            self.deliveries
+        ))
+        .bind(webhook_id)
+        .bind(delivery_id)
+        .bind(since)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Claim due deliveries for sending
+    ///
+    /// Claimed deliveries are pushed back by `lease_secs`, so another
+    /// instance doesn't send them too; the outcome is recorded before then.
+    pub async fn claim_due(&self, limit: i64, lease_secs: i64) -> sqlx::Result<Vec<DueDelivery>> {
+        sqlx::query_as::<_, DueDelivery>(&format!(
+            r#"
+            WITH due AS (
+                SELECT id FROM {deliveries}
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            ), claimed AS (
+                UPDATE {deliveries} d
+                SET next_attempt_at = NOW() + make_interval(secs => $2)
+                FROM due
+                WHERE d.id = due.id
+                RETURNING d.id, d.webhook_id, d.event, d.payload, d.attempts, d.created_at
+            )
+            SELECT c.id, c.event, c.payload, c.attempts, c.created_at, w.url, w.secret
+            FROM claimed c
+            JOIN {webhooks} w ON w.id = c.webhook_id
+            ORDER BY c.id
+            "#,
+            deliveries = self.deliveries,
+            webhooks = self.webhooks
+        ))
+        .bind(limit)
+        .bind(lease_secs as f64)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Record a delivery the endpoint accepted
+    pub async fn mark_delivered(&self, id: i64, response_status: i32) -> sqlx::Result<()> {
+        sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET status = 'delivered', attempts = attempts + 1, response_status = $2,
+                last_error = NULL, delivered_at = NOW()
+            WHERE id = $1
+            "#,
+            self.deliveries
+        ))
+        .bind(id)
+        .bind(response_status)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a failed attempt, retrying after `retry_secs` or giving up
+    pub async fn mark_attempt_failed(
+        &self,
+        id: i64,
+        response_status: Option<i32>,
+        error: &str,
+        retry_secs: Option<i64>,
+    ) -> sqlx::Result<()> {
+        sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET status = CASE WHEN $4::float8 IS NULL THEN 'failed' ELSE 'pending' END,
+                attempts = attempts + 1, response_status = $2, last_error = $3,
+                next_attempt_at = NOW() + make_interval(secs => COALESCE($4::float8, 0))
+            WHERE id = $1
+            "#,
+            self.deliveries
+        ))
+        .bind(id)
+        .bind(response_status)
+        .bind(error)
+        .bind(retry_secs.map(|s| s as f64))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
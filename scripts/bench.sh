@@ -0,0 +1,119 @@
+#!/bin/bash
+# =============================================================================
+# bench.sh - Run the Rust benchmarks and compare them to the baseline
+# =============================================================================
+# Usage:
+#   ./scripts/bench.sh          Fail if a benchmark got slower than the baseline
+#   ./scripts/bench.sh --save   Rewrite benches/baseline.json from this run
+#
+# The baseline holds the mean time of every criterion benchmark in
+# nanoseconds, keyed by benchmark id (e.g. "payload/parse/1000"). Timings
+# only compare on the machine that recorded them, so regenerate it with
+# --save on the machine that runs the gate before relying on the check.
+#
+# Environment:
+#   BENCH_THRESHOLD   Allowed slowdown in percent (default: 15)
+#   BENCH_PACKAGES    Packages to benchmark (default: anchor-core anchor-indexer)
+# =============================================================================
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(dirname "$SCRIPT_DIR")"
+BASELINE="$PROJECT_ROOT/benches/baseline.json"
+CRITERION_DIR="${CARGO_TARGET_DIR:-$PROJECT_ROOT/target}/criterion"
+THRESHOLD="${BENCH_THRESHOLD:-15}"
+PACKAGES="${BENCH_PACKAGES:-anchor-core anchor-indexer}"
+
+MODE=check
+if [ "$1" = "--save" ]; then
+    MODE=save
+fi
+
+if [ "$MODE" = check ] && [ ! -f "$BASELINE" ]; then
+    echo "❌ No baseline at $BASELINE; run with --save first"
+    exit 1
+fi
+
+cd "$PROJECT_ROOT"
+STARTED="$(date +%s)"
+
+echo "⏱️  Running benchmarks for: $PACKAGES"
+ARGS=""
+for package in $PACKAGES; do
+    ARGS="$ARGS -p $package"
+done
+# shellcheck disable=SC2086
+cargo bench $ARGS
+
+# Results written by this run, keyed by benchmark id
+collect_results() {
+    python3 - "$CRITERION_DIR" "$STARTED" <<'PY'
+import json, os, sys
+
+root, started = sys.argv[1], int(sys.argv[2])
+results = {}
+for directory, _, files in os.walk(root):
+    if os.path.basename(directory) != "new" or "estimates.json" not in files:
+        continue
+    estimates = os.path.join(directory, "estimates.json")
+    if os.path.getmtime(estimates) < started:
+        continue
+    with open(os.path.join(directory, "benchmark.json")) as f:
+        full_id = json.load(f)["full_id"]
+    with open(estimates) as f:
+        results[full_id] = round(json.load(f)["mean"]["point_estimate"], 1)
+print(json.dumps(results))
+PY
+}
+
+RESULTS="$(collect_results)"
+
+if [ "$MODE" = save ]; then
+    mkdir -p "$(dirname "$BASELINE")"
+    python3 - "$BASELINE" "$RESULTS" <<'PY'
+import json, platform, sys
+from datetime import datetime, timezone
+
+path, results = sys.argv[1], json.loads(sys.argv[2])
+baseline = {
+    "generated_at": datetime.now(timezone.utc).strftime("%Y-%m-%dT%H:%M:%SZ"),
+    "host": f"{platform.system()} {platform.machine()}",
+    "unit": "ns",
+    "benchmarks": dict(sorted(results.items())),
+}
+with open(path, "w") as f:
+    json.dump(baseline, f, indent=2)
+    f.write("\n")
+print(f"📝 Saved {len(results)} benchmarks to {path}")
+PY
+    exit 0
+fi
+
+python3 - "$BASELINE" "$RESULTS" "$THRESHOLD" <<'PY'
+import json, sys
+
+with open(sys.argv[1]) as f:
+    baseline = json.load(f)["benchmarks"]
+results, threshold = json.loads(sys.argv[2]), float(sys.argv[3])
+
+regressions = 0
+print(f"\n{'benchmark':<44} {'baseline':>12} {'current':>12} {'change':>8}")
+for name, before in baseline.items():
+    after = results.get(name)
+    if after is None:
+        print(f"{name:<44} {before:>12.1f} {'missing':>12}")
+        continue
+    change = (after - before) / before * 100
+    flag = ""
+    if change > threshold:
+        regressions += 1
+        flag = "  ❌"
+    print(f"{name:<44} {before:>12.1f} {after:>12.1f} {change:>+7.1f}%{flag}")
+for name in sorted(set(results) - set(baseline)):
+    print(f"{name:<44} {'new':>12} {results[name]:>12.1f}")
+
+if regressions:
+    print(f"\n❌ {regressions} benchmark(s) more than {threshold:g}% slower than the baseline")
+    sys.exit(1)
+print(f"\n✅ No benchmark more than {threshold:g}% slower than the baseline")
+PY
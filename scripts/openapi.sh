@@ -0,0 +1,63 @@
+#!/bin/bash
+# =============================================================================
+# openapi.sh - Write each backend's OpenAPI document for client generation
+# =============================================================================
+# Usage:
+#   ./scripts/openapi.sh            Write target/openapi/<service>.json
+#   ./scripts/openapi.sh --clients  Also generate TypeScript types next to
+#                                   each spec with openapi-typescript
+#
+# Every backend binary accepts --dump-openapi [PATH] and exits after writing
+# the spec, so this needs neither a database nor a running stack.
+#
+# Environment:
+#   OPENAPI_OUT   Output directory (default: target/openapi)
+# =============================================================================
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_ROOT="$(dirname "$SCRIPT_DIR")"
+TARGET_DIR="${CARGO_TARGET_DIR:-$PROJECT_ROOT/target}"
+OUT="${OPENAPI_OUT:-$TARGET_DIR/openapi}"
+
+# service name -> cargo package (the binary has the same name)
+SERVICES="
+wallet:anchor-wallet
+dashboard:dashboard-backend
+threads:threads-backend
+domains:anchor-domains-backend
+tokens:anchor-tokens-backend
+canvas:anchor-canvas-backend
+places:anchor-places-backend
+proofs:anchorproofs-backend
+oracles:anchor-oracles-backend
+predictions:anchor-predictions-backend
+lottery:anchor-lottery-backend
+"
+
+cd "$PROJECT_ROOT"
+mkdir -p "$OUT"
+
+ARGS=""
+for entry in $SERVICES; do
+    ARGS="$ARGS -p ${entry#*:}"
+done
+echo "🔨 Building backends"
+# shellcheck disable=SC2086
+cargo build $ARGS
+
+for entry in $SERVICES; do
+    service="${entry%%:*}"
+    package="${entry#*:}"
+    "$TARGET_DIR/debug/$package" --dump-openapi "$OUT/$service.json"
+    echo "📝 $OUT/$service.json"
+done
+
+if [ "$1" = "--clients" ]; then
+    for entry in $SERVICES; do
+        service="${entry%%:*}"
+        npx --yes openapi-typescript "$OUT/$service.json" -o "$OUT/$service.d.ts"
+    done
+fi
+
+echo "✅ OpenAPI documents written to $OUT"